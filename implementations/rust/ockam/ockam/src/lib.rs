@@ -87,7 +87,11 @@ pub use ockam_transport_tcp::{
     TcpConnectionOptions, TcpInletOptions, TcpListenerOptions, TcpOutletOptions, TcpTransport,
     TcpTransportExtension,
 };
-pub use relay_service::{RelayService, RelayServiceOptions};
+pub use relay_service::{
+    AliasConflictPolicy, RelayAuthorization, RelayLimits, RelayRegistration,
+    RelayRegistrationsRepository, RelayService, RelayServiceOptions, RelayServiceStatistics,
+    RelayStatistics,
+};
 pub use system::{SystemBuilder, SystemHandler, WorkerSystem};
 pub use unique::unique_with_prefix;
 
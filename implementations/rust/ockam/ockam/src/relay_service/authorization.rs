@@ -0,0 +1,210 @@
+use core::fmt::{Debug, Formatter};
+use core::str::from_utf8;
+use core::time::Duration;
+use ockam_core::compat::{sync::Arc, vec::Vec};
+use ockam_core::{LocalMessage, Result};
+use ockam_identity::utils::now;
+use ockam_identity::{
+    Identifier, IdentityAttributesRepository, IdentitySecureChannelLocalInfo, RevocationsRepository,
+};
+
+/// Default time during which a relay reuses the authorization of a registrant
+pub const DEFAULT_RELAY_AUTHORIZATION_CACHE_TTL: Duration = Duration::from_secs(10);
+
+/// Authorization of the registration requests received by a
+/// [`RelayService`](crate::RelayService), based on the attributes of the requester.
+///
+/// The attributes are the ones which were presented by the requester with its credential,
+/// so registration requests must be received through a secure channel.
+/// The authorization is checked again when a relay forwards messages, so that expired
+/// or changed attributes, and revoked credentials, take effect for the relays which are
+/// already registered. A relay reuses the authorization of a registrant for the duration of the
+/// authorization cache TTL before checking it again.
+///
+/// For example, the following authorization only accepts requests from identities having the
+/// `relay=allowed` attribute, and only for aliases listed in their `relay_aliases` attribute:
+///
+/// ```
+/// # use ockam::RelayAuthorization;
+/// # use ockam::identity::IdentityAttributesRepository;
+/// # use std::sync::Arc;
+/// # fn create(repository: Arc<dyn IdentityAttributesRepository>) -> RelayAuthorization {
+/// RelayAuthorization::new(repository)
+///     .with_required_attribute("relay", "allowed")
+///     .with_alias_attribute("relay_aliases")
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct RelayAuthorization {
+    cache_ttl: Duration,
+    identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
+    required_attributes: Vec<(Vec<u8>, Vec<u8>)>,
+    alias_attribute: Option<Vec<u8>>,
+    revocations_repository: Option<Arc<dyn RevocationsRepository>>,
+}
+
+impl RelayAuthorization {
+    /// Create an authorization which only requires the requester to be authenticated
+    /// with a secure channel and to have some attributes
+    pub fn new(identity_attributes_repository: Arc<dyn IdentityAttributesRepository>) -> Self {
+        Self {
+            cache_ttl: DEFAULT_RELAY_AUTHORIZATION_CACHE_TTL,
+            identity_attributes_repository,
+            required_attributes: vec![],
+            alias_attribute: None,
+            revocations_repository: None,
+        }
+    }
+
+    /// Require the requester to have an attribute with the given value
+    pub fn with_required_attribute(
+        mut self,
+        key: impl Into<Vec<u8>>,
+        value: impl Into<Vec<u8>>,
+    ) -> Self {
+        self.required_attributes.push((key.into(), value.into()));
+        self
+    }
+
+    /// Only accept static aliases matching the value of the given attribute.
+    ///
+    /// The attribute value is a comma-separated list of aliases. An alias ending with `*`
+    /// matches all the aliases starting with the same prefix.
+    /// Registrations with a random alias are not restricted by this attribute.
+    pub fn with_alias_attribute(mut self, key: impl Into<Vec<u8>>) -> Self {
+        self.alias_attribute = Some(key.into());
+        self
+    }
+
+    /// Reject the registrants whose attributes were attested before the credentials issued
+    /// to them were revoked
+    pub fn with_revocations_repository(
+        mut self,
+        revocations_repository: Arc<dyn RevocationsRepository>,
+    ) -> Self {
+        self.revocations_repository = Some(revocations_repository);
+        self
+    }
+
+    /// Set the time during which a relay reuses the authorization of a registrant.
+    /// A zero duration checks the authorization for every forwarded message
+    pub fn with_cache_ttl(mut self, cache_ttl: Duration) -> Self {
+        self.cache_ttl = cache_ttl;
+        self
+    }
+
+    /// Return the time during which a relay reuses the authorization of a registrant
+    pub(crate) fn cache_ttl(&self) -> Duration {
+        self.cache_ttl
+    }
+
+    /// Return true if the sender of the registration message can register a relay
+    /// with the given alias. `None` is used for registrations with a random alias
+    pub async fn is_authorized(
+        &self,
+        local_message: &LocalMessage,
+        alias: Option<&str>,
+    ) -> Result<bool> {
+        match IdentitySecureChannelLocalInfo::find_info(local_message) {
+            Ok(info) => {
+                self.is_identifier_authorized(&info.their_identity_id(), alias)
+                    .await
+            }
+            Err(_) => Ok(false),
+        }
+    }
+
+    /// Return true if the given identity can use a relay with the given alias, with its
+    /// current attributes. `None` is used for relays with a random alias
+    pub(crate) async fn is_identifier_authorized(
+        &self,
+        identifier: &Identifier,
+        alias: Option<&str>,
+    ) -> Result<bool> {
+        let entry = match self
+            .identity_attributes_repository
+            .get_attributes(identifier)
+            .await?
+        {
+            Some(entry) => entry,
+            None => return Ok(false),
+        };
+
+        let now = now()?;
+        if entry
+            .expires()
+            .map(|expires| expires <= now)
+            .unwrap_or(false)
+        {
+            return Ok(false);
+        }
+
+        if let (Some(repository), Some(issuer)) =
+            (&self.revocations_repository, entry.attested_by())
+        {
+            if let Some(revocation) = repository.get_revocation(&issuer, identifier).await? {
+                if entry.added() <= revocation.revoked_at {
+                    return Ok(false);
+                }
+            }
+        }
+
+        let attributes = entry.valid_attrs_at(now);
+        for (key, value) in self.required_attributes.iter() {
+            if attributes.get(key) != Some(value) {
+                return Ok(false);
+            }
+        }
+
+        match (&self.alias_attribute, alias) {
+            (Some(key), Some(alias)) => Ok(attributes
+                .get(key)
+                .map(|allowed| alias_matches(allowed, alias))
+                .unwrap_or(false)),
+            _ => Ok(true),
+        }
+    }
+}
+
+/// Return true if the alias is part of the comma-separated list of allowed aliases
+fn alias_matches(allowed: &[u8], alias: &str) -> bool {
+    let allowed = match from_utf8(allowed) {
+        Ok(allowed) => allowed,
+        Err(_) => return false,
+    };
+    allowed
+        .split(',')
+        .map(|a| a.trim())
+        .any(|pattern| match pattern.strip_suffix('*') {
+            Some(prefix) => alias.starts_with(prefix),
+            None => pattern == alias,
+        })
+}
+
+impl Debug for RelayAuthorization {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let attributes = format!("{:?}", self.required_attributes.iter().map(|x| &x.0));
+
+        f.debug_struct("Relay Authorization")
+            .field("Required attributes", &attributes)
+            .field("Alias attribute", &self.alias_attribute)
+            .field("Revocations", &self.revocations_repository.is_some())
+            .field("Cache TTL", &self.cache_ttl)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alias_matches() {
+        assert!(alias_matches(b"alice", "alice"));
+        assert!(alias_matches(b"bob, alice", "alice"));
+        assert!(alias_matches(b"team-*", "team-alice"));
+        assert!(!alias_matches(b"team-*", "alice"));
+        assert!(!alias_matches(b"alice", "alice2"));
+        assert!(!alias_matches(b"", "alice"));
+    }
+}
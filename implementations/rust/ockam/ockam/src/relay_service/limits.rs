@@ -0,0 +1,152 @@
+use ockam_identity::utils::now;
+
+/// Limits enforced by a [`RelayService`](crate::RelayService), so that a relay node
+/// can't be exhausted by one misbehaving client
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RelayLimits {
+    pub(super) max_relays: Option<usize>,
+    pub(super) max_relays_per_identity: Option<usize>,
+    pub(super) max_messages_per_second: Option<u64>,
+    pub(super) max_bytes_per_second: Option<u64>,
+}
+
+impl RelayLimits {
+    /// No limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maximum number of relays which can be active at the same time
+    pub fn with_max_relays(mut self, max_relays: usize) -> Self {
+        self.max_relays = Some(max_relays);
+        self
+    }
+
+    /// Maximum number of relay registrations by the same identity, including the registrations
+    /// as a standby or load-balanced registrant of an existing relay.
+    /// The registrations which are not received through a secure channel share the same limit
+    pub fn with_max_relays_per_identity(mut self, max_relays_per_identity: usize) -> Self {
+        self.max_relays_per_identity = Some(max_relays_per_identity);
+        self
+    }
+
+    /// Maximum number of messages forwarded by each relay every second.
+    /// Additional messages are dropped
+    pub fn with_max_messages_per_second(mut self, max_messages_per_second: u64) -> Self {
+        self.max_messages_per_second = Some(max_messages_per_second);
+        self
+    }
+
+    /// Maximum number of payload bytes forwarded by each relay every second.
+    /// Additional messages are dropped. A message bigger than this limit is forwarded when
+    /// no other bytes are pending, the next messages are then dropped until the excess bytes
+    /// are paid back by the following seconds
+    pub fn with_max_bytes_per_second(mut self, max_bytes_per_second: u64) -> Self {
+        self.max_bytes_per_second = Some(max_bytes_per_second);
+        self
+    }
+
+    /// Return a rate limiter for a new relay if a rate is limited
+    pub(super) fn rate_limiter(&self) -> Option<RateLimiter> {
+        if self.max_messages_per_second.is_none() && self.max_bytes_per_second.is_none() {
+            return None;
+        }
+        Some(RateLimiter {
+            max_messages: self.max_messages_per_second,
+            max_bytes: self.max_bytes_per_second,
+            window: 0,
+            messages: 0,
+            bytes: 0,
+        })
+    }
+}
+
+/// Count the messages and bytes forwarded by a relay during the current second
+#[derive(Debug)]
+pub(super) struct RateLimiter {
+    max_messages: Option<u64>,
+    max_bytes: Option<u64>,
+    window: u64,
+    messages: u64,
+    bytes: u64,
+}
+
+impl RateLimiter {
+    /// Return true if a message of the given size can be forwarded now
+    pub(super) fn allow(&mut self, size: usize) -> bool {
+        match now() {
+            Ok(now) => self.allow_at(now.0, size as u64),
+            // Without a clock the rate can't be measured
+            Err(_) => true,
+        }
+    }
+
+    fn allow_at(&mut self, second: u64, size: u64) -> bool {
+        if second != self.window {
+            // The bytes exceeding the limit are carried over to the next windows
+            let elapsed = second.saturating_sub(self.window);
+            self.bytes = match self.max_bytes {
+                Some(max_bytes) => self.bytes.saturating_sub(max_bytes.saturating_mul(elapsed)),
+                None => 0,
+            };
+            self.window = second;
+            self.messages = 0;
+        }
+        if let Some(max_messages) = self.max_messages {
+            if self.messages + 1 > max_messages {
+                return false;
+            }
+        }
+        if let Some(max_bytes) = self.max_bytes {
+            // An oversized message would never fit, it is only forwarded when nothing is pending
+            if self.bytes + size > max_bytes && self.bytes > 0 {
+                return false;
+            }
+        }
+        self.messages += 1;
+        self.bytes += size;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter() {
+        let mut limiter = RelayLimits::new()
+            .with_max_messages_per_second(2)
+            .with_max_bytes_per_second(100)
+            .rate_limiter()
+            .unwrap();
+
+        assert!(limiter.allow_at(1, 10));
+        assert!(limiter.allow_at(1, 10));
+        assert!(!limiter.allow_at(1, 10));
+        // A new second starts a new window
+        assert!(limiter.allow_at(2, 90));
+        assert!(!limiter.allow_at(2, 20));
+        assert!(RelayLimits::new().rate_limiter().is_none());
+    }
+
+    #[test]
+    fn test_rate_limiter_oversized_message() {
+        let mut limiter = RelayLimits::new()
+            .with_max_bytes_per_second(100)
+            .rate_limiter()
+            .unwrap();
+
+        // The message is forwarded even if it is bigger than the limit
+        assert!(limiter.allow_at(1, 250));
+        assert!(!limiter.allow_at(1, 1));
+        // The next windows pay back the excess bytes
+        assert!(!limiter.allow_at(2, 1));
+        assert!(limiter.allow_at(3, 50));
+        assert!(!limiter.allow_at(3, 1));
+        assert!(limiter.allow_at(4, 100));
+        // A big message is forwarded again once nothing is pending
+        assert!(!limiter.allow_at(4, 250));
+        assert!(limiter.allow_at(5, 250));
+    }
+}
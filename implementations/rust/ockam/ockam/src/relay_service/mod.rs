@@ -1,7 +1,15 @@
+mod authorization;
+mod limits;
 mod options;
+mod registrations_repository;
+mod registry;
 mod relay;
 #[allow(clippy::module_inception)]
 mod relay_service;
 
+pub use authorization::*;
+pub use limits::RelayLimits;
 pub use options::*;
+pub use registrations_repository::*;
+pub use registry::{AliasConflictPolicy, RelayServiceStatistics, RelayStatistics};
 pub use relay_service::*;
@@ -1,7 +1,15 @@
+use crate::relay_service::registry::RelayRegistry;
+use crate::relay_service::{
+    AliasConflictPolicy, RelayAuthorization, RelayLimits, RelayRegistrationsRepository,
+    RelayServiceStatistics,
+};
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::string::String;
 use ockam_core::compat::sync::Arc;
 use ockam_core::compat::vec::Vec;
 use ockam_core::flow_control::{FlowControlId, FlowControls};
 use ockam_core::{Address, AllowAll, IncomingAccessControl};
+use ockam_identity::Identifier;
 
 /// Trust Options for a Forwarding Service
 pub struct RelayServiceOptions {
@@ -9,6 +17,12 @@ pub struct RelayServiceOptions {
     pub(super) relays_incoming_access_control: Arc<dyn IncomingAccessControl>,
     pub(super) consumer_service: Vec<FlowControlId>,
     pub(super) consumer_relay: Vec<FlowControlId>,
+    pub(super) authorization: Option<RelayAuthorization>,
+    pub(super) limits: RelayLimits,
+    pub(super) alias_conflict_policy: AliasConflictPolicy,
+    pub(super) reserved_aliases: BTreeMap<String, Identifier>,
+    pub(super) registry: Arc<RelayRegistry>,
+    pub(super) registrations_repository: Option<Arc<dyn RelayRegistrationsRepository>>,
 }
 
 impl RelayServiceOptions {
@@ -19,6 +33,12 @@ impl RelayServiceOptions {
             relays_incoming_access_control: Arc::new(AllowAll),
             consumer_service: vec![],
             consumer_relay: vec![],
+            authorization: None,
+            limits: RelayLimits::new(),
+            alias_conflict_policy: AliasConflictPolicy::default(),
+            reserved_aliases: BTreeMap::new(),
+            registry: Arc::new(RelayRegistry::default()),
+            registrations_repository: None,
         }
     }
 
@@ -72,6 +92,53 @@ impl RelayServiceOptions {
         self
     }
 
+    /// Check each registration request against the attributes of the requester.
+    /// Requests which are not authorized are rejected and no relay is created.
+    /// A relay stops forwarding messages to a registrant once it is not authorized anymore
+    pub fn with_authorization(mut self, authorization: RelayAuthorization) -> Self {
+        self.authorization = Some(authorization);
+        self
+    }
+
+    /// Limit the number of relays and the rate of messages forwarded by each relay
+    pub fn with_limits(mut self, limits: RelayLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Set the policy applied when a registration is received for an alias which is
+    /// already used by another node. The default is to replace the previous registration
+    pub fn with_alias_conflict_policy(mut self, policy: AliasConflictPolicy) -> Self {
+        self.alias_conflict_policy = policy;
+        self
+    }
+
+    /// Reserve an alias: only the given identity can register it, through a secure channel
+    pub fn with_reserved_alias(mut self, alias: impl Into<String>, identifier: Identifier) -> Self {
+        self.reserved_aliases.insert(alias.into(), identifier);
+        self
+    }
+
+    /// Persist the registrations of the relays with a static alias. When the service starts,
+    /// the persisted relays are created again, so that the aliases remain reserved for their
+    /// registrants across restarts. The registrants must register again to receive messages:
+    /// a restored relay asks them to do so through their persisted routes, and a
+    /// [`RemoteRelay`](crate::remote::RemoteRelay) which can't be reached that way anymore
+    /// registers again when it reconnects
+    pub fn with_registrations_repository(
+        mut self,
+        repository: Arc<dyn RelayRegistrationsRepository>,
+    ) -> Self {
+        self.registrations_repository = Some(repository);
+        self
+    }
+
+    /// Return a handle to read the statistics of the relays created by the service
+    /// started with these options
+    pub fn statistics(&self) -> RelayServiceStatistics {
+        RelayServiceStatistics(self.registry.clone())
+    }
+
     pub(super) fn setup_flow_control_for_relay_service(
         &self,
         flow_controls: &FlowControls,
@@ -0,0 +1,40 @@
+use crate::relay_service::AliasConflictPolicy;
+use ockam_core::compat::{boxed::Box, string::String, vec::Vec};
+use ockam_core::{async_trait, Result, Route};
+use ockam_identity::Identifier;
+
+/// Registration of a node for a relay with a static alias, as persisted by a
+/// [`RelayService`](crate::RelayService) so that its relays can be restored after a restart
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RelayRegistration {
+    /// Alias of the relay
+    pub alias: String,
+    /// Return route of the registration message, leading to the registrant node
+    pub route: Route,
+    /// Identifier of the registrant, when it registered through a secure channel
+    pub identifier: Option<Identifier>,
+    /// Conflict policy which was applied when the registrant registered
+    pub policy: AliasConflictPolicy,
+}
+
+/// This trait supports the storage of the registrations received by a relay service:
+///
+///  - a relay is uniquely identified by its alias
+///  - a relay can have several registrants, depending on its conflict policy
+///  - the registrations are used to recreate the relays when the relay service restarts
+///
+#[async_trait]
+pub trait RelayRegistrationsRepository: Send + Sync + 'static {
+    /// Replace all the registrations of a relay
+    async fn store_registrations(
+        &self,
+        alias: &str,
+        registrations: &[RelayRegistration],
+    ) -> Result<()>;
+
+    /// Delete all the registrations of a relay
+    async fn delete_registrations(&self, alias: &str) -> Result<()>;
+
+    /// Get the registrations of all the relays
+    async fn get_registrations(&self) -> Result<Vec<RelayRegistration>>;
+}
@@ -0,0 +1,826 @@
+use crate::relay_service::{RelayLimits, RelayRegistration};
+use core::fmt::{Debug, Display, Formatter};
+use core::str::FromStr;
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::sync::{Arc, Mutex};
+use ockam_core::compat::{boxed::Box, string::String, vec::Vec};
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{async_trait, Address, Error, OutgoingAccessControl, RelayMessage, Result, Route};
+use ockam_identity::utils::now;
+use ockam_identity::Identifier;
+
+/// Policy applied by a [`RelayService`](crate::RelayService) when a registration is received
+/// for an alias which is already used by another registrant.
+///
+/// Registrants are identified by their identifier when they register through a secure
+/// channel, and by their route otherwise. A registrant can always register its own alias
+/// again, for example to send a heartbeat or after a reconnection. When the messages are
+/// load-balanced, the replicas of a service can share the same identity, so registrants
+/// are identified by their route as well.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AliasConflictPolicy {
+    /// Keep the current registrant and reject the new registration
+    Reject,
+    /// Forward the messages to the new registrant from now on
+    #[default]
+    Replace,
+    /// Keep the current registrant and use the new one as a standby, which receives the
+    /// messages when the current registrant can't be reached anymore
+    FailOver,
+    /// Keep all the registrants and distribute the messages round-robin across them.
+    /// A registrant which can't be reached anymore is removed until it registers again.
+    /// Registrants with the same identity but different routes are distinct replicas
+    LoadBalance,
+}
+
+impl Display for AliasConflictPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            AliasConflictPolicy::Reject => "reject",
+            AliasConflictPolicy::Replace => "replace",
+            AliasConflictPolicy::FailOver => "fail_over",
+            AliasConflictPolicy::LoadBalance => "load_balance",
+        })
+    }
+}
+
+impl FromStr for AliasConflictPolicy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "reject" => Ok(AliasConflictPolicy::Reject),
+            "replace" => Ok(AliasConflictPolicy::Replace),
+            "fail_over" => Ok(AliasConflictPolicy::FailOver),
+            "load_balance" => Ok(AliasConflictPolicy::LoadBalance),
+            _ => Err(Error::new(
+                Origin::Application,
+                Kind::Invalid,
+                format!("unknown alias conflict policy: {s}"),
+            )),
+        }
+    }
+}
+
+/// A node which registered a relay
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) struct Registrant {
+    /// Return route of the registration message
+    pub(super) route: Route,
+    pub(super) identifier: Option<Identifier>,
+    /// True if the registration was restored after a restart of the relay service
+    /// and the registrant didn't register again since then
+    pub(super) restored: bool,
+}
+
+impl Registrant {
+    pub(super) fn new(route: Route, identifier: Option<Identifier>) -> Self {
+        Self {
+            route,
+            identifier,
+            restored: false,
+        }
+    }
+
+    pub(super) fn restored(registration: RelayRegistration) -> Self {
+        Self {
+            route: registration.route,
+            identifier: registration.identifier,
+            restored: true,
+        }
+    }
+
+    fn is_same(&self, other: &Registrant) -> bool {
+        match (&self.identifier, &other.identifier) {
+            (Some(identifier), Some(other_identifier)) => identifier == other_identifier,
+            _ => self.route == other.route,
+        }
+    }
+
+    /// Return true if both registrants are the same replica of a load-balanced service
+    fn is_same_replica(&self, other: &Registrant) -> bool {
+        self.identifier == other.identifier && self.route == other.route
+    }
+
+    /// Route used to forward messages to the registrant node
+    fn forward_route(&self) -> Route {
+        let mut route = self.route.clone();
+        // Remove the last hop so that just route to the node itself is left
+        route.modify().pop_back();
+        route
+    }
+}
+
+/// Result of a registration for an existing alias
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Registration {
+    /// The alias is not used yet, a new relay must be created
+    New,
+    /// The registration was accepted for the existing relay
+    Accepted,
+    /// The registration was rejected
+    Rejected(&'static str),
+}
+
+/// Statistics of a relay created by a [`RelayService`](crate::RelayService)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RelayStatistics {
+    /// Alias of the relay
+    pub alias: String,
+    /// Time of the first registration, in seconds since the Unix epoch
+    pub registered_at: u64,
+    /// Time of the last registration or heartbeat, in seconds since the Unix epoch
+    pub last_heartbeat: u64,
+    /// Number of messages forwarded by the relay
+    pub messages_forwarded: u64,
+    /// Number of payload bytes forwarded by the relay
+    pub bytes_forwarded: u64,
+    /// Number of nodes registered for this alias, including the standby ones
+    pub consumers: usize,
+}
+
+/// Nodes registered for a relay, and the relay statistics
+#[derive(Debug)]
+struct RelayEntry {
+    /// The first registrant receives the forwarded messages, the other ones are standbys,
+    /// unless the messages are load-balanced
+    registrants: Vec<Registrant>,
+    load_balanced: bool,
+    // Index of the registrant receiving the next message when load-balanced
+    next_registrant: usize,
+    registered_at: u64,
+    last_heartbeat: u64,
+    messages_forwarded: u64,
+    bytes_forwarded: u64,
+}
+
+impl RelayEntry {
+    fn new(registrant: Registrant) -> Self {
+        let now = current_time();
+        Self {
+            registrants: vec![registrant],
+            load_balanced: false,
+            next_registrant: 0,
+            registered_at: now,
+            last_heartbeat: now,
+            messages_forwarded: 0,
+            bytes_forwarded: 0,
+        }
+    }
+}
+
+/// Relays which are currently active, with the nodes which registered them
+pub(super) struct RelayRegistry {
+    relays: Mutex<BTreeMap<Address, RelayEntry>>,
+    // Next hop towards a node whose registration is being rejected, so that the
+    // rejection can be sent back to it
+    rejected: Mutex<Option<Address>>,
+}
+
+impl Default for RelayRegistry {
+    fn default() -> Self {
+        Self {
+            relays: Mutex::new(BTreeMap::new()),
+            rejected: Mutex::new(None),
+        }
+    }
+}
+
+impl Debug for RelayRegistry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str("RelayRegistry")
+    }
+}
+
+impl RelayRegistry {
+    /// Return an error message if this registrant can't register a relay.
+    /// The registrants without an identifier are all counted as the same identity
+    pub(super) fn check_limits(
+        &self,
+        limits: &RelayLimits,
+        address: &Address,
+        registrant: &Registrant,
+    ) -> Option<&'static str> {
+        let relays = self.relays.lock().unwrap();
+        match relays.get(address) {
+            // Registering an existing relay again doesn't create a new registration
+            Some(entry) if entry.registrants.iter().any(|r| r.is_same(registrant)) => return None,
+            Some(_) => (),
+            None => {
+                if let Some(max_relays) = limits.max_relays {
+                    if relays.len() >= max_relays {
+                        return Some("the maximum number of relays is reached");
+                    }
+                }
+            }
+        }
+        if let Some(max) = limits.max_relays_per_identity {
+            let count = relays
+                .values()
+                .flat_map(|entry| entry.registrants.iter())
+                .filter(|r| r.identifier == registrant.identifier)
+                .count();
+            if count >= max {
+                return Some("the maximum number of relays for this identity is reached");
+            }
+        }
+        None
+    }
+
+    /// Register a node for an alias, according to the conflict policy
+    pub(super) fn register(
+        &self,
+        address: &Address,
+        registrant: Registrant,
+        policy: AliasConflictPolicy,
+    ) -> Registration {
+        let mut relays = self.relays.lock().unwrap();
+        let entry = match relays.get_mut(address) {
+            Some(entry) if !entry.registrants.is_empty() => entry,
+            _ => return Registration::New,
+        };
+        entry.last_heartbeat = current_time();
+        let registrants = &mut entry.registrants;
+
+        let existing = if policy == AliasConflictPolicy::LoadBalance {
+            // A replica reconnecting to a restarted relay service takes the place of one of
+            // the restored registrations of its identity
+            registrants
+                .iter()
+                .position(|r| r.is_same_replica(&registrant))
+                .or_else(|| {
+                    registrants
+                        .iter()
+                        .position(|r| r.restored && r.is_same(&registrant))
+                })
+        } else {
+            registrants.iter().position(|r| r.is_same(&registrant))
+        };
+        if let Some(index) = existing {
+            // The route may have changed after a reconnection
+            registrants[index] = registrant;
+            return Registration::Accepted;
+        }
+
+        match policy {
+            // Restored registrations may be stale, they don't prevent a new registrant
+            // from using the alias
+            AliasConflictPolicy::Reject if registrants.iter().all(|r| r.restored) => {
+                registrants.insert(0, registrant);
+                Registration::Accepted
+            }
+            AliasConflictPolicy::Reject => Registration::Rejected("the alias is already used"),
+            AliasConflictPolicy::Replace => {
+                *registrants = vec![registrant];
+                Registration::Accepted
+            }
+            AliasConflictPolicy::FailOver => {
+                registrants.push(registrant);
+                Registration::Accepted
+            }
+            AliasConflictPolicy::LoadBalance => {
+                registrants.push(registrant);
+                entry.load_balanced = true;
+                Registration::Accepted
+            }
+        }
+    }
+
+    pub(super) fn insert(&self, address: Address, registrant: Registrant) {
+        self.relays
+            .lock()
+            .unwrap()
+            .insert(address, RelayEntry::new(registrant));
+    }
+
+    pub(super) fn remove(&self, address: &Address) {
+        self.relays.lock().unwrap().remove(address);
+    }
+
+    /// Select the registrant receiving the next message sent to a relay.
+    /// Return its index, the route to its node and its identifier
+    pub(super) fn forward_route(
+        &self,
+        address: &Address,
+    ) -> Option<(usize, Route, Option<Identifier>)> {
+        let mut relays = self.relays.lock().unwrap();
+        let entry = relays.get_mut(address)?;
+        if entry.registrants.is_empty() {
+            return None;
+        }
+        let index = if entry.load_balanced {
+            let index = entry.next_registrant % entry.registrants.len();
+            entry.next_registrant = index + 1;
+            index
+        } else {
+            0
+        };
+        let registrant = &entry.registrants[index];
+        Some((
+            index,
+            registrant.forward_route(),
+            registrant.identifier.clone(),
+        ))
+    }
+
+    /// Remove a registrant of a relay, after it became unreachable.
+    /// Return true if another registrant can receive the messages instead
+    pub(super) fn fail_over(&self, address: &Address, index: usize) -> bool {
+        let mut relays = self.relays.lock().unwrap();
+        match relays.get_mut(address) {
+            Some(entry) if entry.registrants.len() > 1 && index < entry.registrants.len() => {
+                entry.registrants.remove(index);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Remove a registrant of a relay, after it lost the authorization to use it.
+    /// Return true if the relay still has some registrants
+    pub(super) fn remove_registrant(&self, address: &Address, index: usize) -> bool {
+        let mut relays = self.relays.lock().unwrap();
+        match relays.get_mut(address) {
+            Some(entry) => {
+                if index < entry.registrants.len() {
+                    entry.registrants.remove(index);
+                }
+                !entry.registrants.is_empty()
+            }
+            None => false,
+        }
+    }
+
+    /// Allow the rejection of a registration to be sent to the given next hop,
+    /// or disallow it again with `None`
+    pub(super) fn set_rejected(&self, next_hop: Option<Address>) {
+        *self.rejected.lock().unwrap() = next_hop;
+    }
+
+    /// Return the routes to the nodes which registered a relay, and true for the routes
+    /// of the registrations which were restored and didn't register again since then
+    pub(super) fn registrant_routes(&self, address: &Address) -> Vec<(Route, bool)> {
+        self.relays
+            .lock()
+            .unwrap()
+            .get(address)
+            .map(|entry| {
+                entry
+                    .registrants
+                    .iter()
+                    .map(|r| (r.route.clone(), r.restored))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Return the registrations of a relay, so that they can be persisted
+    pub(super) fn registrations(
+        &self,
+        address: &Address,
+        policy: AliasConflictPolicy,
+    ) -> Vec<RelayRegistration> {
+        self.relays
+            .lock()
+            .unwrap()
+            .get(address)
+            .map(|entry| {
+                entry
+                    .registrants
+                    .iter()
+                    .map(|r| RelayRegistration {
+                        alias: address.address().into(),
+                        route: r.route.clone(),
+                        identifier: r.identifier.clone(),
+                        policy,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Count a message forwarded by a relay
+    pub(super) fn record_forwarded(&self, address: &Address, bytes: usize) {
+        if let Some(entry) = self.relays.lock().unwrap().get_mut(address) {
+            entry.messages_forwarded += 1;
+            entry.bytes_forwarded += bytes as u64;
+        }
+    }
+
+    /// Return the statistics of all the active relays
+    pub(super) fn statistics(&self) -> Vec<RelayStatistics> {
+        self.relays
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(address, entry)| RelayStatistics {
+                alias: address.address().into(),
+                registered_at: entry.registered_at,
+                last_heartbeat: entry.last_heartbeat,
+                messages_forwarded: entry.messages_forwarded,
+                bytes_forwarded: entry.bytes_forwarded,
+                consumers: entry.registrants.len(),
+            })
+            .collect()
+    }
+
+    /// Return true if messages can be sent to this next hop because it leads to a registrant.
+    /// If `address` is set, only the registrants of that relay are considered
+    fn leads_to_registrant(&self, address: Option<&Address>, next_hop: &Address) -> bool {
+        if address.is_none() && self.rejected.lock().unwrap().as_ref() == Some(next_hop) {
+            return true;
+        }
+        let relays = self.relays.lock().unwrap();
+        let mut registrants = relays
+            .iter()
+            .filter(|(a, _)| address.map(|address| *a == address).unwrap_or(true))
+            .flat_map(|(_, entry)| entry.registrants.iter());
+        registrants.any(|r| match address {
+            // We are accessed with our node, no transport is involved
+            Some(_) if r.route.len() == 1 => true,
+            _ => r.route.next().ok() == Some(next_hop),
+        })
+    }
+}
+
+/// Read-only access to the statistics of the relays created by a
+/// [`RelayService`](crate::RelayService), see [`RelayServiceOptions::statistics`](crate::RelayServiceOptions::statistics)
+#[derive(Clone, Debug)]
+pub struct RelayServiceStatistics(pub(super) Arc<RelayRegistry>);
+
+impl RelayServiceStatistics {
+    /// Return the statistics of all the active relays
+    pub fn relays(&self) -> Vec<RelayStatistics> {
+        self.0.statistics()
+    }
+}
+
+/// Time in seconds since the Unix epoch, or 0 if there is no clock
+fn current_time() -> u64 {
+    now().map(|t| t.0).unwrap_or(0)
+}
+
+/// Allow the [`RelayService`](crate::RelayService) to send registration acknowledgements
+/// to the registrants, and rejections to the nodes whose registration is rejected
+#[derive(Debug)]
+pub(super) struct AllowRegistrants(pub(super) Arc<RelayRegistry>);
+
+#[async_trait]
+impl OutgoingAccessControl for AllowRegistrants {
+    async fn is_authorized(&self, relay_msg: &RelayMessage) -> Result<bool> {
+        Ok(self
+            .0
+            .leads_to_registrant(None, relay_msg.onward_route().next()?))
+    }
+}
+
+/// Allow a relay to forward messages to its registrants only
+#[derive(Debug)]
+pub(super) struct AllowRelayRegistrants {
+    pub(super) registry: Arc<RelayRegistry>,
+    pub(super) address: Address,
+}
+
+#[async_trait]
+impl OutgoingAccessControl for AllowRelayRegistrants {
+    async fn is_authorized(&self, relay_msg: &RelayMessage) -> Result<bool> {
+        Ok(self
+            .registry
+            .leads_to_registrant(Some(&self.address), relay_msg.onward_route().next()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_core::route;
+
+    #[test]
+    fn test_limits() {
+        let limits = RelayLimits::new()
+            .with_max_relays(3)
+            .with_max_relays_per_identity(1);
+        let alice = Identifier::try_from("I0123456789abcdef0123456789abcdef01234567").unwrap();
+        let bob = Identifier::try_from("I89abcdef0123456789abcdef0123456789abcdef").unwrap();
+        let registry = RelayRegistry::default();
+
+        registry.insert("r1".into(), registrant("tcp1", Some(&alice)));
+        assert!(limits_reached(&registry, &limits, "r2", Some(&alice)));
+        // The same relay can be registered again
+        assert!(!limits_reached(&registry, &limits, "r1", Some(&alice)));
+        assert!(!limits_reached(&registry, &limits, "r2", None));
+
+        registry.insert("r2".into(), registrant("tcp2", Some(&bob)));
+        registry.insert("r3".into(), registrant("tcp3", None));
+        assert!(limits_reached(&registry, &limits, "r4", None));
+
+        registry.remove(&"r3".into());
+        assert!(!limits_reached(&registry, &limits, "r4", None));
+    }
+
+    #[test]
+    fn test_limits_per_identity() {
+        let limits = RelayLimits::new().with_max_relays_per_identity(1);
+        let alice = Identifier::try_from("I0123456789abcdef0123456789abcdef01234567").unwrap();
+        let registry = RelayRegistry::default();
+        let alias: Address = "alias".into();
+
+        // The registrants without an identifier share the same limit
+        registry.insert("r1".into(), registrant("tcp1", None));
+        assert!(limits_reached(&registry, &limits, "r2", None));
+
+        // Standby registrants are counted too
+        registry.insert(alias.clone(), registrant("tcp2", Some(&alice)));
+        assert!(limits_reached(&registry, &limits, "r1", Some(&alice)));
+        assert!(limits_reached(&registry, &limits, "alias", None));
+        assert!(!limits_reached(&registry, &limits, "alias", Some(&alice)));
+    }
+
+    #[test]
+    fn test_conflict_policies() {
+        let alice = Identifier::try_from("I0123456789abcdef0123456789abcdef01234567").unwrap();
+        let registry = RelayRegistry::default();
+        let alias: Address = "alias".into();
+
+        assert_eq!(
+            registry.register(&alias, registrant("tcp1", Some(&alice)), Default::default()),
+            Registration::New
+        );
+        registry.insert(alias.clone(), registrant("tcp1", Some(&alice)));
+
+        // The same identity can register again with a new route
+        assert_eq!(
+            registry.register(
+                &alias,
+                registrant("tcp2", Some(&alice)),
+                AliasConflictPolicy::Reject
+            ),
+            Registration::Accepted
+        );
+        assert_eq!(forward_route(&registry, &alias), Some(route!["tcp2"]));
+
+        // Another registrant is rejected
+        assert!(matches!(
+            registry.register(
+                &alias,
+                registrant("tcp3", None),
+                AliasConflictPolicy::Reject
+            ),
+            Registration::Rejected(_)
+        ));
+
+        // Another registrant is used as a standby
+        assert_eq!(
+            registry.register(
+                &alias,
+                registrant("tcp3", None),
+                AliasConflictPolicy::FailOver
+            ),
+            Registration::Accepted
+        );
+        assert_eq!(forward_route(&registry, &alias), Some(route!["tcp2"]));
+        assert!(registry.fail_over(&alias, 0));
+        assert_eq!(forward_route(&registry, &alias), Some(route!["tcp3"]));
+        assert!(!registry.fail_over(&alias, 0));
+
+        // Another registrant replaces the current one
+        assert_eq!(
+            registry.register(
+                &alias,
+                registrant("tcp4", None),
+                AliasConflictPolicy::Replace
+            ),
+            Registration::Accepted
+        );
+        assert_eq!(forward_route(&registry, &alias), Some(route!["tcp4"]));
+        assert!(!registry.leads_to_registrant(Some(&alias), &"tcp3".into()));
+        assert!(registry.leads_to_registrant(None, &"tcp4".into()));
+    }
+
+    #[test]
+    fn test_statistics() {
+        let registry = RelayRegistry::default();
+        let alias: Address = "alias".into();
+        registry.insert(alias.clone(), registrant("tcp1", None));
+        registry.register(
+            &alias,
+            registrant("tcp2", None),
+            AliasConflictPolicy::FailOver,
+        );
+        registry.record_forwarded(&alias, 10);
+        registry.record_forwarded(&alias, 5);
+
+        let statistics = registry.statistics();
+        assert_eq!(statistics.len(), 1);
+        assert_eq!(statistics[0].alias, "alias");
+        assert_eq!(statistics[0].messages_forwarded, 2);
+        assert_eq!(statistics[0].bytes_forwarded, 15);
+        assert_eq!(statistics[0].consumers, 2);
+        assert!(statistics[0].last_heartbeat >= statistics[0].registered_at);
+    }
+
+    #[test]
+    fn test_load_balancing() {
+        let registry = RelayRegistry::default();
+        let alias: Address = "alias".into();
+        registry.insert(alias.clone(), registrant("tcp1", None));
+        for next_hop in ["tcp2", "tcp3"] {
+            registry.register(
+                &alias,
+                registrant(next_hop, None),
+                AliasConflictPolicy::LoadBalance,
+            );
+        }
+
+        let routes: Vec<Route> = (0..4)
+            .map(|_| forward_route(&registry, &alias).unwrap())
+            .collect();
+        assert_eq!(
+            routes,
+            vec![
+                route!["tcp1"],
+                route!["tcp2"],
+                route!["tcp3"],
+                route!["tcp1"]
+            ]
+        );
+
+        // An unreachable registrant is removed
+        assert!(registry.fail_over(&alias, 1));
+        assert_eq!(forward_route(&registry, &alias), Some(route!["tcp3"]));
+        assert_eq!(forward_route(&registry, &alias), Some(route!["tcp1"]));
+    }
+
+    #[test]
+    fn test_load_balancing_replicas() {
+        let alice = Identifier::try_from("I0123456789abcdef0123456789abcdef01234567").unwrap();
+        let registry = RelayRegistry::default();
+        let alias: Address = "alias".into();
+        registry.insert(alias.clone(), registrant("tcp1", Some(&alice)));
+
+        // Two replicas sharing the same identity both receive messages
+        assert_eq!(
+            registry.register(
+                &alias,
+                registrant("tcp2", Some(&alice)),
+                AliasConflictPolicy::LoadBalance,
+            ),
+            Registration::Accepted
+        );
+        let routes: Vec<Route> = (0..3)
+            .map(|_| forward_route(&registry, &alias).unwrap())
+            .collect();
+        assert_eq!(routes, vec![route!["tcp1"], route!["tcp2"], route!["tcp1"]]);
+
+        // A heartbeat of a replica doesn't add another registrant
+        registry.register(
+            &alias,
+            registrant("tcp2", Some(&alice)),
+            AliasConflictPolicy::LoadBalance,
+        );
+        assert_eq!(registry.statistics()[0].consumers, 2);
+
+        // A replica reconnecting after a restart replaces a restored registration
+        let restored_alias: Address = "restored".into();
+        registry.insert(
+            restored_alias.clone(),
+            Registrant::restored(RelayRegistration {
+                alias: "restored".into(),
+                route: route!["tcp1", "remote_relay"],
+                identifier: Some(alice.clone()),
+                policy: AliasConflictPolicy::LoadBalance,
+            }),
+        );
+        registry.register(
+            &restored_alias,
+            registrant("tcp3", Some(&alice)),
+            AliasConflictPolicy::LoadBalance,
+        );
+        assert_eq!(
+            registry.registrant_routes(&restored_alias),
+            vec![(route!["tcp3", "remote_relay"], false)]
+        );
+    }
+
+    #[test]
+    fn test_restored_registrations() {
+        let registry = RelayRegistry::default();
+        let alias: Address = "alias".into();
+        let registration = RelayRegistration {
+            alias: "alias".into(),
+            route: route!["tcp1", "remote_relay"],
+            identifier: None,
+            policy: AliasConflictPolicy::Reject,
+        };
+        registry.insert(alias.clone(), Registrant::restored(registration.clone()));
+        assert_eq!(
+            registry.registrations(&alias, AliasConflictPolicy::Reject),
+            vec![registration]
+        );
+
+        // A restored registration doesn't prevent another node from using the alias
+        assert_eq!(
+            registry.register(
+                &alias,
+                registrant("tcp2", None),
+                AliasConflictPolicy::Reject
+            ),
+            Registration::Accepted
+        );
+        assert_eq!(forward_route(&registry, &alias), Some(route!["tcp2"]));
+        // The restored registrant is asked to register again instead of being acknowledged
+        assert_eq!(
+            registry.registrant_routes(&alias),
+            vec![
+                (route!["tcp2", "remote_relay"], false),
+                (route!["tcp1", "remote_relay"], true)
+            ]
+        );
+
+        // But the new registrant does
+        assert!(matches!(
+            registry.register(
+                &alias,
+                registrant("tcp3", None),
+                AliasConflictPolicy::Reject
+            ),
+            Registration::Rejected(_)
+        ));
+    }
+
+    #[test]
+    fn test_remove_registrant() {
+        let alice = Identifier::try_from("I0123456789abcdef0123456789abcdef01234567").unwrap();
+        let registry = RelayRegistry::default();
+        let alias: Address = "alias".into();
+        registry.insert(alias.clone(), registrant("tcp1", Some(&alice)));
+        registry.register(
+            &alias,
+            registrant("tcp2", None),
+            AliasConflictPolicy::FailOver,
+        );
+        assert_eq!(
+            registry.forward_route(&alias),
+            Some((0, route!["tcp1"], Some(alice)))
+        );
+
+        assert!(registry.remove_registrant(&alias, 0));
+        assert_eq!(forward_route(&registry, &alias), Some(route!["tcp2"]));
+        assert!(!registry.remove_registrant(&alias, 0));
+        assert_eq!(forward_route(&registry, &alias), None);
+    }
+
+    #[test]
+    fn test_rejected_next_hop() {
+        let registry = RelayRegistry::default();
+        let next_hop: Address = "tcp1".into();
+        assert!(!registry.leads_to_registrant(None, &next_hop));
+
+        registry.set_rejected(Some(next_hop.clone()));
+        assert!(registry.leads_to_registrant(None, &next_hop));
+        // Relays can only reach their own registrants
+        assert!(!registry.leads_to_registrant(Some(&"alias".into()), &next_hop));
+
+        registry.set_rejected(None);
+        assert!(!registry.leads_to_registrant(None, &next_hop));
+    }
+
+    #[test]
+    fn test_policy_names() {
+        for policy in [
+            AliasConflictPolicy::Reject,
+            AliasConflictPolicy::Replace,
+            AliasConflictPolicy::FailOver,
+            AliasConflictPolicy::LoadBalance,
+        ] {
+            assert_eq!(
+                policy.to_string().parse::<AliasConflictPolicy>().unwrap(),
+                policy
+            );
+        }
+        assert!("unknown".parse::<AliasConflictPolicy>().is_err());
+    }
+
+    fn forward_route(registry: &RelayRegistry, address: &Address) -> Option<Route> {
+        registry.forward_route(address).map(|(_, route, _)| route)
+    }
+
+    fn registrant(next_hop: &str, identifier: Option<&Identifier>) -> Registrant {
+        Registrant::new(route![next_hop, "remote_relay"], identifier.cloned())
+    }
+
+    fn limits_reached(
+        registry: &RelayRegistry,
+        limits: &RelayLimits,
+        address: &str,
+        identifier: Option<&Identifier>,
+    ) -> bool {
+        let next_hop = match identifier {
+            Some(identifier) => identifier.to_string(),
+            None => "tcp".to_string(),
+        };
+        registry
+            .check_limits(limits, &address.into(), &registrant(&next_hop, identifier))
+            .is_some()
+    }
+}
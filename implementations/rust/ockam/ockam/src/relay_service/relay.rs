@@ -1,92 +1,89 @@
-use crate::Context;
+use crate::relay_service::limits::RateLimiter;
+use crate::relay_service::registry::{AllowRelayRegistrants, RelayRegistry};
+use crate::relay_service::relay_service::{audit_relay, REGISTER_AGAIN};
+use crate::{Context, OckamError, RelayAuthorization};
+use ockam_core::compat::collections::BTreeMap;
 use ockam_core::compat::sync::Arc;
-use ockam_core::compat::{boxed::Box, vec::Vec};
+use ockam_core::compat::{boxed::Box, string::String, vec::Vec};
 use ockam_core::{
-    Address, AllowAll, AllowOnwardAddress, Any, IncomingAccessControl, LocalMessage,
-    OutgoingAccessControl, Result, Route, Routed, TransportMessage, Worker,
+    Address, Any, Encodable, IncomingAccessControl, LocalMessage, Result, Route, Routed,
+    TransportMessage, Worker,
 };
+use ockam_identity::utils::{add_seconds, now};
+use ockam_identity::{Identifier, TimestampInSeconds};
 use ockam_node::WorkerBuilder;
-use tracing::info;
+use tracing::{info, warn};
 
 pub(super) struct Relay {
-    forward_route: Route,
     // this option will be `None` after this worker is initialized, because
     // while initializing, the worker will send the payload contained in this
-    // field to the `forward_route`, to indicate a successful connection
+    // field to the registrants, to indicate a successful connection
     payload: Option<Vec<u8>>,
+    // Routes to the nodes which registered this relay
+    registry: Arc<RelayRegistry>,
+    rate_limiter: Option<RateLimiter>,
+    // Checked for every forwarded message, `None` if the relay service has no authorization
+    authorization: Option<RelayAuthorization>,
+    // Registrants which were authorized recently, with the time until which their
+    // authorization is reused
+    authorized_until: BTreeMap<Identifier, TimestampInSeconds>,
+    // Static alias of the relay, `None` for a random alias
+    alias: Option<String>,
 }
 
 impl Relay {
+    #[allow(clippy::too_many_arguments)]
     pub(super) async fn create(
         ctx: &Context,
         address: Address,
-        forward_route: Route,
+        alias: Option<String>,
         registration_payload: Vec<u8>,
         incoming_access_control: Arc<dyn IncomingAccessControl>,
+        registry: Arc<RelayRegistry>,
+        rate_limiter: Option<RateLimiter>,
+        authorization: Option<RelayAuthorization>,
     ) -> Result<()> {
-        info!("Created new alias {} for {}", address, forward_route);
+        info!("Created new alias {}", address);
 
-        // Should be able to reach last and second last hops
-        let outgoing_access_control: Arc<dyn OutgoingAccessControl> = if forward_route.len() == 1 {
-            // We are accessed with our node, no transport is involved
-            Arc::new(AllowAll)
-        } else {
-            let next_hop = forward_route.next()?.clone();
-            Arc::new(AllowOnwardAddress(next_hop))
+        // Should be able to reach the nodes which registered this relay
+        let outgoing_access_control = AllowRelayRegistrants {
+            registry: registry.clone(),
+            address: address.clone(),
         };
 
         let relay = Self {
-            forward_route,
             payload: Some(registration_payload.clone()),
+            registry,
+            rate_limiter,
+            authorization,
+            authorized_until: BTreeMap::new(),
+            alias,
         };
 
         WorkerBuilder::new(relay)
             .with_address(address)
             .with_incoming_access_control_arc(incoming_access_control)
-            .with_outgoing_access_control_arc(outgoing_access_control)
+            .with_outgoing_access_control(outgoing_access_control)
             .start(ctx)
             .await?;
 
         Ok(())
     }
-}
-
-#[crate::worker]
-impl Worker for Relay {
-    type Context = Context;
-    type Message = Any;
-
-    async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
-        let payload = self
-            .payload
-            .take()
-            .expect("payload must be available on init");
-        let msg = TransportMessage::v1(self.forward_route.clone(), ctx.address(), payload);
 
-        ctx.forward(LocalMessage::new(msg, Vec::new())).await?;
-
-        // Remove the last hop so that just route to the node itself is left
-        self.forward_route.modify().pop_back();
-
-        Ok(())
-    }
-
-    async fn handle_message(
-        &mut self,
-        ctx: &mut Self::Context,
-        msg: Routed<Self::Message>,
+    /// Forward a message to one of the nodes which registered this relay
+    async fn forward(
+        &self,
+        ctx: &Context,
+        mut message: LocalMessage,
+        forward_route: Route,
     ) -> Result<()> {
-        let mut message = msg.into_local_message();
         let transport_message = message.transport_mut();
 
-        // Remove my address from the onward_route
-        transport_message.onward_route.step()?;
-
         // Prepend forward route
         transport_message
             .onward_route
             .modify()
-            .prepend_route(self.forward_route.clone());
+            .prepend_route(forward_route);
 
         let next_hop = transport_message.onward_route.next()?.clone();
         let prev_hop = transport_message.return_route.next()?.clone();
@@ -109,4 +106,138 @@ impl Worker for Relay {
 
         ctx.forward(message).await
     }
+
+    /// Return true if a registrant is still authorized to use this relay,
+    /// its attributes may have changed since its registration.
+    /// A successful authorization is reused until the authorization cache TTL elapses
+    async fn is_registrant_authorized(&mut self, identifier: Option<&Identifier>) -> Result<bool> {
+        let (authorization, identifier) = match (&self.authorization, identifier) {
+            (None, _) => return Ok(true),
+            (Some(authorization), Some(identifier)) => (authorization, identifier),
+            (Some(_), None) => return Ok(false),
+        };
+
+        let now = now()?;
+        if let Some(authorized_until) = self.authorized_until.get(identifier) {
+            if now < *authorized_until {
+                return Ok(true);
+            }
+        }
+
+        let authorized = authorization
+            .is_identifier_authorized(identifier, self.alias.as_deref())
+            .await?;
+        let cache_ttl = authorization.cache_ttl();
+        if authorized && !cache_ttl.is_zero() {
+            self.authorized_until
+                .insert(identifier.clone(), add_seconds(&now, cache_ttl.as_secs()));
+        } else {
+            self.authorized_until.remove(identifier);
+        }
+        Ok(authorized)
+    }
+}
+
+#[crate::worker]
+impl Worker for Relay {
+    type Context = Context;
+    type Message = Any;
+
+    async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        let payload = self
+            .payload
+            .take()
+            .expect("payload must be available on init");
+
+        // Acknowledge the registration which created this relay. A restored relay asks its
+        // restored registrants to register again, so that their routes are known to lead to
+        // them. The request only reaches the registrants whose persisted routes still work,
+        // the other ones register again when they reconnect,
+        // see `RemoteRelayOptions::with_reconnection`
+        let register_again = REGISTER_AGAIN.encode()?;
+        for (registration_route, restored) in self.registry.registrant_routes(&ctx.address()) {
+            let payload = if restored {
+                register_again.clone()
+            } else {
+                payload.clone()
+            };
+            let msg = TransportMessage::v1(registration_route.clone(), ctx.address(), payload);
+            if let Err(e) = ctx.forward(LocalMessage::new(msg, Vec::new())).await {
+                warn!(
+                    "the relay {} cannot notify its registrant at {registration_route}: {e}",
+                    ctx.address()
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        self.registry.remove(&ctx.address());
+        Ok(())
+    }
+
+    async fn handle_message(
+        &mut self,
+        ctx: &mut Self::Context,
+        msg: Routed<Self::Message>,
+    ) -> Result<()> {
+        if let Some(rate_limiter) = &mut self.rate_limiter {
+            if !rate_limiter.allow(msg.payload().len()) {
+                warn!(
+                    "the relay {} dropped a message: rate limit exceeded",
+                    ctx.address()
+                );
+                return Ok(());
+            }
+        }
+
+        let size = msg.payload().len();
+        let mut message = msg.into_local_message();
+
+        // Remove my address from the onward_route
+        message.transport_mut().onward_route.step()?;
+
+        loop {
+            let (index, forward_route, identifier) = self
+                .registry
+                .forward_route(&ctx.address())
+                .ok_or(OckamError::UnknownForwarderDestinationAddress)?;
+
+            if !self.is_registrant_authorized(identifier.as_ref()).await? {
+                warn!(
+                    "the registrant {forward_route} of the relay {} is not authorized anymore",
+                    ctx.address()
+                );
+                audit_relay(
+                    "relay_unauthorized",
+                    ctx.address().address(),
+                    identifier.as_ref(),
+                    Some("not authorized anymore"),
+                );
+                if self.registry.remove_registrant(&ctx.address(), index) {
+                    continue;
+                }
+                // Nobody can use this relay anymore, the message is dropped
+                ctx.stop_worker(ctx.address()).await?;
+                return Ok(());
+            }
+
+            match self.forward(ctx, message.clone(), forward_route).await {
+                Ok(()) => {
+                    self.registry.record_forwarded(&ctx.address(), size);
+                    return Ok(());
+                }
+                // Use another registrant, if there is one
+                Err(e) if self.registry.fail_over(&ctx.address(), index) => {
+                    warn!(
+                        "the relay {} fails over to another registrant: {e}",
+                        ctx.address()
+                    );
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
 }
@@ -1,10 +1,27 @@
+use crate::relay_service::registry::{AllowRegistrants, Registrant, Registration, RelayRegistry};
 use crate::relay_service::relay::Relay;
+use crate::relay_service::RelayRegistration;
 use crate::{Context, RelayServiceOptions};
 use core::str::from_utf8;
 use ockam_core::compat::boxed::Box;
-use ockam_core::{Address, Any, DenyAll, Result, Routed, Worker};
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::string::{String, ToString};
+use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
+use ockam_core::{
+    Address, Any, Encodable, LocalMessage, Result, Route, Routed, TransportMessage, Worker,
+};
+use ockam_identity::{Identifier, IdentitySecureChannelLocalInfo};
 use ockam_node::WorkerBuilder;
 
+/// Prefix of the payload sent back to a node whose registration was rejected,
+/// followed by the reason of the rejection
+pub(crate) const REGISTRATION_REJECTED: &str = "registration_rejected:";
+
+/// Payload sent by a restored relay to its registrants, asking them to register again
+/// so that the relay forwards messages to them without waiting for their next heartbeat
+pub(crate) const REGISTER_AGAIN: &str = "register_again";
+
 /// Alias worker to register remote workers under local names.
 ///
 /// To talk with this worker, you can use the
@@ -13,6 +30,7 @@ use ockam_node::WorkerBuilder;
 #[non_exhaustive]
 pub struct RelayService {
     options: RelayServiceOptions,
+    registry: Arc<RelayRegistry>,
 }
 
 impl RelayService {
@@ -27,18 +45,120 @@ impl RelayService {
         options.setup_flow_control_for_relay_service(ctx.flow_controls(), &address);
 
         let service_incoming_access_control = options.service_incoming_access_control.clone();
+        let registry = options.registry.clone();
 
-        let s = Self { options };
+        let s = Self {
+            options,
+            registry: registry.clone(),
+        };
 
+        // The service only sends acknowledgements to the nodes registering relays
         WorkerBuilder::new(s)
             .with_address(address)
             .with_incoming_access_control_arc(service_incoming_access_control)
-            .with_outgoing_access_control(DenyAll)
+            .with_outgoing_access_control(AllowRegistrants(registry))
             .start(ctx)
             .await?;
 
         Ok(())
     }
+
+    /// Create the relays which were persisted before the service was stopped.
+    /// Each restored relay asks its registrants to register again, and they keep their
+    /// aliases when they register again after reconnecting
+    async fn restore_relays(ctx: &Context, options: &RelayServiceOptions) -> Result<()> {
+        let repository = match &options.registrations_repository {
+            Some(repository) => repository,
+            None => return Ok(()),
+        };
+
+        let mut relays: BTreeMap<String, Vec<RelayRegistration>> = BTreeMap::new();
+        for registration in repository.get_registrations().await? {
+            relays
+                .entry(registration.alias.clone())
+                .or_default()
+                .push(registration);
+        }
+
+        for (alias, registrations) in relays {
+            let address = Address::from_string(alias.as_str());
+            for registration in registrations {
+                let policy = registration.policy;
+                let registrant = Registrant::restored(registration);
+                if options
+                    .registry
+                    .register(&address, registrant.clone(), policy)
+                    == Registration::New
+                {
+                    options.registry.insert(address.clone(), registrant);
+                }
+            }
+
+            info!("restoring the relay {address}");
+            options.setup_flow_control_for_relay(ctx.flow_controls(), &address);
+            let created = Relay::create(
+                ctx,
+                address.clone(),
+                Some(alias.clone()),
+                alias.encode()?,
+                options.relays_incoming_access_control.clone(),
+                options.registry.clone(),
+                options.limits.rate_limiter(),
+                options.authorization.clone(),
+            )
+            .await;
+            if let Err(e) = created {
+                warn!("the relay {address} cannot be restored: {e}");
+                options.registry.remove(&address);
+                repository.delete_registrations(&alias).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Persist the registrations of a relay with a static alias, if they changed
+    async fn store_registrations(
+        &self,
+        address: &Address,
+        previous: Vec<RelayRegistration>,
+    ) -> Result<()> {
+        if let Some(repository) = &self.options.registrations_repository {
+            let registrations = self
+                .registry
+                .registrations(address, self.options.alias_conflict_policy);
+            if registrations != previous {
+                repository
+                    .store_registrations(address.address(), &registrations)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Send the reason of a rejection back to the node which sent the registration,
+    /// so that it doesn't wait for an acknowledgement
+    async fn reject(
+        &self,
+        ctx: &Context,
+        forward_route: Route,
+        alias: &str,
+        identifier: Option<&Identifier>,
+        reason: &str,
+    ) -> Result<()> {
+        warn!("the registration of the relay {alias} from {forward_route} was rejected: {reason}");
+        audit_relay("relay_rejected", alias, identifier, Some(reason));
+
+        let payload = format!("{REGISTRATION_REJECTED}{reason}").encode()?;
+        let next_hop = forward_route.next().ok().cloned();
+        let msg = TransportMessage::v1(forward_route, ctx.address(), payload);
+        self.registry.set_rejected(next_hop);
+        let sent = ctx.forward(LocalMessage::new(msg, Vec::new())).await;
+        self.registry.set_rejected(None);
+        if let Err(e) = sent {
+            warn!("the rejection of the relay {alias} cannot be sent: {e}");
+        }
+        Ok(())
+    }
 }
 
 #[crate::worker]
@@ -46,38 +166,161 @@ impl Worker for RelayService {
     type Context = Context;
     type Message = Any;
 
+    async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        // The service is started first, so that it receives the registrations which the
+        // restored relays ask for
+        Self::restore_relays(ctx, &self.options).await
+    }
+
     async fn handle_message(
         &mut self,
         ctx: &mut Self::Context,
         msg: Routed<Self::Message>,
     ) -> Result<()> {
         let forward_route = msg.return_route();
-        let payload = msg.into_transport_message().payload;
-
-        let random_address = Address::random_tagged("Relay.service");
+        let local_message = msg.into_local_message();
+        let payload = local_message.transport().payload.clone();
 
         // TODO: assume that the first byte is length, ignore it.
         // We have to improve this actually parse the payload.
-        let address = match payload.get(1..) {
+        let alias = match payload.get(1..) {
             Some(address) => match from_utf8(address) {
-                Ok(v) if v != "register" => Address::from_string(v),
-                _ => random_address,
+                Ok(v) if v != "register" => Some(v),
+                _ => None,
             },
-            None => random_address,
+            None => None,
         };
 
-        self.options
-            .setup_flow_control_for_relay(ctx.flow_controls(), &address);
+        let identifier = IdentitySecureChannelLocalInfo::find_info(&local_message)
+            .ok()
+            .map(|info| info.their_identity_id());
 
-        Relay::create(
-            ctx,
-            address,
-            forward_route,
-            payload,
-            self.options.relays_incoming_access_control.clone(),
-        )
-        .await?;
+        if let Some(authorization) = &self.options.authorization {
+            if !authorization.is_authorized(&local_message, alias).await? {
+                return self
+                    .reject(
+                        ctx,
+                        forward_route,
+                        alias.unwrap_or("<random>"),
+                        identifier.as_ref(),
+                        "not authorized",
+                    )
+                    .await;
+            }
+        }
 
-        Ok(())
+        let address = match alias {
+            Some(alias) => Address::from_string(alias),
+            None => Address::random_tagged("Relay.service"),
+        };
+
+        if let Some(reserved_for) = alias.and_then(|a| self.options.reserved_aliases.get(a)) {
+            if identifier.as_ref() != Some(reserved_for) {
+                return self
+                    .reject(
+                        ctx,
+                        forward_route,
+                        address.address(),
+                        identifier.as_ref(),
+                        "reserved alias",
+                    )
+                    .await;
+            }
+        }
+
+        // Relays with a random alias can't be registered again after a restart
+        let persisted = alias.is_some();
+        let registrant = Registrant::new(forward_route.clone(), identifier.clone());
+        if let Some(reason) =
+            self.registry
+                .check_limits(&self.options.limits, &address, &registrant)
+        {
+            return self
+                .reject(
+                    ctx,
+                    forward_route,
+                    address.address(),
+                    identifier.as_ref(),
+                    reason,
+                )
+                .await;
+        }
+
+        let previous = self
+            .registry
+            .registrations(&address, self.options.alias_conflict_policy);
+        let result = match self.registry.register(
+            &address,
+            registrant.clone(),
+            self.options.alias_conflict_policy,
+        ) {
+            Registration::Rejected(reason) => {
+                self.reject(
+                    ctx,
+                    forward_route,
+                    address.address(),
+                    identifier.as_ref(),
+                    reason,
+                )
+                .await
+            }
+            Registration::Accepted => {
+                // Acknowledge the registration on behalf of the existing relay
+                let msg = TransportMessage::v1(forward_route, address.clone(), payload);
+                ctx.forward(LocalMessage::new(msg, Vec::new())).await
+            }
+            Registration::New => {
+                self.options
+                    .setup_flow_control_for_relay(ctx.flow_controls(), &address);
+
+                self.registry.insert(address.clone(), registrant);
+                let created = Relay::create(
+                    ctx,
+                    address.clone(),
+                    alias.map(|a| a.to_string()),
+                    payload,
+                    self.options.relays_incoming_access_control.clone(),
+                    self.registry.clone(),
+                    self.options.limits.rate_limiter(),
+                    self.options.authorization.clone(),
+                )
+                .await;
+                if created.is_err() {
+                    self.registry.remove(&address);
+                } else {
+                    audit_relay(
+                        "relay_registered",
+                        address.address(),
+                        identifier.as_ref(),
+                        None,
+                    );
+                }
+                created
+            }
+        };
+
+        if result.is_ok() && persisted {
+            if let Err(e) = self.store_registrations(&address, previous).await {
+                warn!("the registrations of the relay {address} cannot be persisted: {e}");
+            }
+        }
+        result
     }
 }
+
+/// Emit an audit event for the registration of a relay
+pub(super) fn audit_relay(
+    event: &str,
+    alias: &str,
+    identifier: Option<&Identifier>,
+    reason: Option<&str>,
+) {
+    info!(
+        target: "ockam::audit::relays",
+        event,
+        alias,
+        registrant = identifier.map(|i| i.to_string()),
+        reason,
+        "relay registration"
+    );
+}
@@ -1,7 +1,8 @@
 use crate::Message;
 use ockam_core::compat::string::String;
+use ockam_core::errcode::{Kind, Origin};
 use ockam_core::flow_control::FlowControlId;
-use ockam_core::{Address, Route};
+use ockam_core::{Address, Error, Result, Route};
 use serde::{Deserialize, Serialize};
 
 /// Information about a remotely forwarded worker.
@@ -45,3 +46,25 @@ impl RemoteRelayInfo {
         &self.flow_control_id
     }
 }
+
+/// Result of the first registration of a [`RemoteRelay`](super::RemoteRelay), sent to the
+/// context waiting for the relay to be created
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug, Message)]
+pub(super) enum RegistrationResult {
+    Registered(RemoteRelayInfo),
+    /// The registration was rejected by the relay service, with the reason of the rejection
+    Rejected(String),
+}
+
+impl RegistrationResult {
+    pub(super) fn into_info(self) -> Result<RemoteRelayInfo> {
+        match self {
+            RegistrationResult::Registered(info) => Ok(info),
+            RegistrationResult::Rejected(reason) => Err(Error::new(
+                Origin::Ockam,
+                Kind::Invalid,
+                format!("the relay registration was rejected: {reason}"),
+            )),
+        }
+    }
+}
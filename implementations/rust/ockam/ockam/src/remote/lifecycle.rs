@@ -1,6 +1,7 @@
-use crate::remote::{Addresses, RemoteRelay, RemoteRelayInfo, RemoteRelayOptions};
+use crate::remote::{
+    Addresses, RegistrationResult, RelayLiveness, RemoteRelay, RemoteRelayInfo, RemoteRelayOptions,
+};
 use crate::Context;
-use core::time::Duration;
 use ockam_core::compat::sync::Arc;
 use ockam_core::compat::{
     string::{String, ToString},
@@ -71,7 +72,7 @@ impl RemoteRelay {
         registration_payload: String,
         flow_control_id: Option<FlowControlId>,
         heartbeat: Option<DelayedEvent<Vec<u8>>>,
-        heartbeat_interval: Duration,
+        options: RemoteRelayOptions,
     ) -> Self {
         Self {
             addresses,
@@ -80,11 +81,23 @@ impl RemoteRelay {
             registration_payload,
             flow_control_id,
             heartbeat,
-            heartbeat_interval,
+            heartbeat_interval: options.heartbeat_interval,
+            reconnection: options.reconnection,
+            events_address: options.events_address,
+            disconnected: false,
+            reconnection_attempts: 0,
+            liveness: RelayLiveness::Lost,
+            liveness_callback: options.liveness_callback,
+            acknowledged: false,
+            missed_heartbeats: 0,
+            max_missed_heartbeats: options.max_missed_heartbeats,
         }
     }
 
     /// Create and start static RemoteRelay at predefined address with given Ockam Orchestrator route
+    ///
+    /// If the options contain a reconnection setting, the relay re-dials its route and registers
+    /// again under the same alias when the connection carrying its registration is lost.
     pub async fn create_static(
         ctx: &Context,
         hub_route: impl Into<Route>,
@@ -117,7 +130,7 @@ impl RemoteRelay {
             alias.into(),
             flow_control_id,
             Some(heartbeat),
-            Duration::from_secs(5),
+            options,
         );
 
         debug!("Starting static RemoteRelay at {}", &addresses.heartbeat);
@@ -131,9 +144,11 @@ impl RemoteRelay {
             .start(ctx)
             .await?;
 
-        let resp = child_ctx.receive::<RemoteRelayInfo>().await?.body();
-
-        Ok(resp)
+        child_ctx
+            .receive::<RegistrationResult>()
+            .await?
+            .body()
+            .into_info()
     }
 
     /// Create and start new ephemeral RemoteRelay at random address with given Ockam Hub route
@@ -165,7 +180,7 @@ impl RemoteRelay {
             "register".to_string(),
             flow_control_id,
             None,
-            Duration::from_secs(10),
+            options,
         );
 
         debug!(
@@ -178,9 +193,11 @@ impl RemoteRelay {
             .start(ctx)
             .await?;
 
-        let resp = callback_ctx.receive::<RemoteRelayInfo>().await?.body();
-
-        Ok(resp)
+        callback_ctx
+            .receive::<RegistrationResult>()
+            .await?
+            .body()
+            .into_info()
     }
 
     /// Create and start new static RemoteRelay without heart beats
@@ -216,7 +233,7 @@ impl RemoteRelay {
             alias.into(),
             flow_control_id,
             None,
-            Duration::from_secs(10),
+            options,
         );
 
         debug!(
@@ -229,8 +246,10 @@ impl RemoteRelay {
             .start(ctx)
             .await?;
 
-        let resp = callback_ctx.receive::<RemoteRelayInfo>().await?.body();
-
-        Ok(resp)
+        callback_ctx
+            .receive::<RegistrationResult>()
+            .await?
+            .body()
+            .into_info()
     }
 }
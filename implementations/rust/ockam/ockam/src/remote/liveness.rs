@@ -0,0 +1,88 @@
+use crate::remote::RemoteRelay;
+use core::fmt::{Debug, Formatter};
+use ockam_core::compat::sync::Arc;
+use tracing::{info, warn};
+
+/// Liveness state of a [`RemoteRelay`](super::RemoteRelay), as observed from the
+/// acknowledgements sent by the relay service
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RelayLiveness {
+    /// The relay is registered and its last registration was acknowledged
+    Registered,
+    /// Some heartbeats were not acknowledged, the relay may be stale
+    Degraded,
+    /// The relay is not registered anymore: too many heartbeats were not acknowledged
+    /// or the connection carrying the registration was lost
+    Lost,
+}
+
+/// Function called every time the liveness state of a relay changes
+#[derive(Clone)]
+pub struct LivenessCallback(Arc<dyn Fn(&str, RelayLiveness) + Send + Sync>);
+
+impl LivenessCallback {
+    /// Create a callback from a function receiving the alias of the relay and its new state
+    pub fn new(f: impl Fn(&str, RelayLiveness) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+
+    fn call(&self, alias: &str, liveness: RelayLiveness) {
+        (self.0)(alias, liveness)
+    }
+}
+
+impl Debug for LivenessCallback {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str("LivenessCallback")
+    }
+}
+
+impl RemoteRelay {
+    /// Change the liveness state and notify the callback if the state changed
+    pub(super) fn set_liveness(&mut self, liveness: RelayLiveness) {
+        if self.liveness == liveness {
+            return;
+        }
+        info!(
+            "RemoteRelay {} is now {:?}",
+            self.registration_payload, liveness
+        );
+        self.liveness = liveness;
+        if let Some(callback) = &self.liveness_callback {
+            callback.call(&self.registration_payload, liveness);
+        }
+    }
+
+    /// Called when a message coming from the relay service shows that the registration is alive
+    pub(super) fn on_alive(&mut self) {
+        self.acknowledged = true;
+        self.missed_heartbeats = 0;
+        if !self.disconnected {
+            self.set_liveness(RelayLiveness::Registered);
+        }
+    }
+
+    /// Called before sending a heartbeat: count the previous heartbeat as missed if it was
+    /// not acknowledged. Return true if too many heartbeats were missed.
+    ///
+    /// A heartbeat registers the same alias again. The relay service accepts the registration
+    /// of an alias by its current registrant and acknowledges it through the existing relay.
+    pub(super) fn on_heartbeat(&mut self) -> bool {
+        if self.acknowledged {
+            self.acknowledged = false;
+            return false;
+        }
+        self.missed_heartbeats += 1;
+        warn!(
+            "RemoteRelay {} missed {} heartbeat(s)",
+            self.registration_payload, self.missed_heartbeats
+        );
+        if self.missed_heartbeats >= self.max_missed_heartbeats {
+            self.set_liveness(RelayLiveness::Lost);
+            true
+        } else {
+            self.set_liveness(RelayLiveness::Degraded);
+            false
+        }
+    }
+}
@@ -4,17 +4,22 @@
 mod addresses;
 mod info;
 mod lifecycle;
+mod liveness;
 mod options;
+mod reconnect;
 mod worker;
 
 pub use info::*;
+pub use liveness::*;
 pub use options::*;
+pub use reconnect::*;
 
 use crate::remote::addresses::Addresses;
+use crate::remote::reconnect::Reconnection;
 use core::time::Duration;
 use ockam_core::compat::{string::String, vec::Vec};
 use ockam_core::flow_control::FlowControlId;
-use ockam_core::Route;
+use ockam_core::{Address, Route};
 use ockam_node::DelayedEvent;
 
 /// This Worker is responsible for registering on Ockam Orchestrator and forwarding messages to local Worker
@@ -28,4 +33,16 @@ pub struct RemoteRelay {
     // We only use Heartbeat for static RemoteRelay
     heartbeat: Option<DelayedEvent<Vec<u8>>>,
     heartbeat_interval: Duration,
+    // Only set for static RemoteRelays created with a reconnection option
+    reconnection: Option<Reconnection>,
+    events_address: Option<Address>,
+    // True when the connection carrying the registration was lost
+    disconnected: bool,
+    reconnection_attempts: u32,
+    liveness: RelayLiveness,
+    liveness_callback: Option<LivenessCallback>,
+    // True when a message was received from the relay service since the last heartbeat
+    acknowledged: bool,
+    missed_heartbeats: u32,
+    max_missed_heartbeats: u32,
 }
@@ -1,10 +1,18 @@
-use crate::remote::Addresses;
+use crate::remote::reconnect::Reconnection;
+use crate::remote::{Addresses, LivenessCallback, ReconnectPolicy, RelayLiveness, RouteDialer};
+use core::time::Duration;
 use ockam_core::compat::sync::Arc;
 use ockam_core::flow_control::{FlowControlId, FlowControlOutgoingAccessControl, FlowControls};
 use ockam_core::{Address, AllowAll, OutgoingAccessControl};
 
 /// Trust options for [`RemoteRelay`](super::RemoteRelay)
-pub struct RemoteRelayOptions {}
+pub struct RemoteRelayOptions {
+    pub(super) reconnection: Option<Reconnection>,
+    pub(super) events_address: Option<Address>,
+    pub(super) heartbeat_interval: Duration,
+    pub(super) max_missed_heartbeats: u32,
+    pub(super) liveness_callback: Option<LivenessCallback>,
+}
 
 impl RemoteRelayOptions {
     /// Usually [`FlowControlId`] should be shared with the Producer that was used to create this
@@ -15,7 +23,54 @@ impl RemoteRelayOptions {
     /// through the [`RemoteRelay`](super::RemoteRelay) through the same Secure Channel.
     #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
-        Self {}
+        Self {
+            reconnection: None,
+            events_address: None,
+            heartbeat_interval: Duration::from_secs(5),
+            max_missed_heartbeats: 3,
+            liveness_callback: None,
+        }
+    }
+
+    /// Interval between two registration messages sent by a static relay to keep its
+    /// registration alive. The default is 5 seconds
+    pub fn with_heartbeat_interval(mut self, heartbeat_interval: Duration) -> Self {
+        self.heartbeat_interval = heartbeat_interval;
+        self
+    }
+
+    /// Number of consecutive heartbeats which can stay unacknowledged before the relay is
+    /// considered as lost. The default is 3
+    pub fn with_max_missed_heartbeats(mut self, max_missed_heartbeats: u32) -> Self {
+        self.max_missed_heartbeats = max_missed_heartbeats.max(1);
+        self
+    }
+
+    /// Call the given function every time the [`RelayLiveness`] of the relay changes
+    pub fn with_liveness_callback(
+        mut self,
+        callback: impl Fn(&str, RelayLiveness) + Send + Sync + 'static,
+    ) -> Self {
+        self.liveness_callback = Some(LivenessCallback::new(callback));
+        self
+    }
+
+    /// Re-dial the route with the given [`RouteDialer`] and register again under the same alias
+    /// when the connection carrying the registration is lost.
+    /// This is only supported by static relays with heartbeats.
+    pub fn with_reconnection(mut self, dialer: impl RouteDialer, policy: ReconnectPolicy) -> Self {
+        self.reconnection = Some(Reconnection {
+            dialer: Arc::new(dialer),
+            policy,
+        });
+        self
+    }
+
+    /// Send a [`RemoteRelayEvent`](super::RemoteRelayEvent) to the given address
+    /// every time the relay is disconnected or reconnected
+    pub fn with_events_address(mut self, address: impl Into<Address>) -> Self {
+        self.events_address = Some(address.into());
+        self
     }
 
     pub(super) fn setup_flow_control(
@@ -44,6 +99,21 @@ impl RemoteRelayOptions {
         }
     }
 
+    /// Allow messages coming from a new connection, after a reconnection,
+    /// to be received and forwarded by the relay
+    pub(super) fn setup_flow_control_after_reconnection(
+        flow_controls: &FlowControls,
+        addresses: &Addresses,
+        next: &Address,
+    ) {
+        if let Some(flow_control_id) = flow_controls
+            .find_flow_control_with_producer_address(next)
+            .map(|x| x.flow_control_id().clone())
+        {
+            flow_controls.add_consumer(addresses.main_remote.clone(), &flow_control_id);
+        }
+    }
+
     pub(super) fn create_access_control(
         &self,
         flow_controls: &FlowControls,
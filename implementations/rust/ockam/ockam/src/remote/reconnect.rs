@@ -0,0 +1,350 @@
+use crate::remote::{RelayLiveness, RemoteRelay, RemoteRelayOptions};
+use crate::{Context, Message};
+use core::time::Duration;
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::rand::{thread_rng, Rng};
+use ockam_core::compat::string::String;
+use ockam_core::compat::sync::Arc;
+use ockam_core::{async_trait, route, Result, Route};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+/// Re-establish a route to the node hosting the relay service, for example by
+/// opening a new TCP connection, when the previous route stopped working.
+#[async_trait]
+pub trait RouteDialer: Send + Sync + 'static {
+    /// Return a new route to the node hosting the relay service
+    async fn dial(&self, ctx: &Context) -> Result<Route>;
+}
+
+/// Exponential backoff, with jitter, used by a [`RemoteRelay`](super::RemoteRelay)
+/// to re-dial its route when the connection carrying its registration is lost
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    initial_delay: Duration,
+    max_delay: Duration,
+    multiplier: u32,
+    jitter_percent: u8,
+    max_attempts: Option<u32>,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+            multiplier: 2,
+            jitter_percent: 20,
+            max_attempts: None,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Default policy: start at 1s, double the delay after each attempt, up to 60s,
+    /// with 20% of jitter, and never give up
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Delay before the first reconnection attempt
+    pub fn with_initial_delay(mut self, initial_delay: Duration) -> Self {
+        self.initial_delay = initial_delay;
+        self
+    }
+
+    /// Maximum delay between two reconnection attempts
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Factor applied to the delay after each failed attempt
+    pub fn with_multiplier(mut self, multiplier: u32) -> Self {
+        self.multiplier = multiplier.max(1);
+        self
+    }
+
+    /// Percentage of the delay which is randomly added or removed to avoid having
+    /// all relays reconnecting at the same time
+    pub fn with_jitter_percent(mut self, jitter_percent: u8) -> Self {
+        self.jitter_percent = jitter_percent.min(100);
+        self
+    }
+
+    /// Stop reconnecting after the given number of failed attempts
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = Some(max_attempts);
+        self
+    }
+
+    /// Return true if another attempt can be made after `attempts` failed attempts
+    pub fn can_retry(&self, attempts: u32) -> bool {
+        self.max_attempts.map(|max| attempts < max).unwrap_or(true)
+    }
+
+    /// Delay to wait before the attempt number `attempt` (starting at 1), without jitter
+    pub fn base_delay(&self, attempt: u32) -> Duration {
+        let mut delay = self.initial_delay;
+        for _ in 1..attempt {
+            delay = delay.saturating_mul(self.multiplier);
+            if delay >= self.max_delay {
+                return self.max_delay;
+            }
+        }
+        delay.min(self.max_delay)
+    }
+
+    /// Delay to wait before the attempt number `attempt` (starting at 1), with jitter
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let base = self.base_delay(attempt);
+        let jitter = base.as_millis() as u64 * self.jitter_percent as u64 / 100;
+        if jitter == 0 {
+            return base;
+        }
+        let offset = thread_rng().gen_range(0..=2 * jitter);
+        Duration::from_millis(base.as_millis() as u64 - jitter + offset)
+    }
+}
+
+/// Reconnection settings of a [`RemoteRelay`](super::RemoteRelay)
+#[derive(Clone)]
+pub(super) struct Reconnection {
+    pub(super) dialer: Arc<dyn RouteDialer>,
+    pub(super) policy: ReconnectPolicy,
+}
+
+/// Events sent by a [`RemoteRelay`](super::RemoteRelay) to its events address,
+/// when one is configured with [`RemoteRelayOptions::with_events_address`](super::RemoteRelayOptions::with_events_address)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Message)]
+pub enum RemoteRelayEvent {
+    /// The connection carrying the registration was lost
+    Disconnected {
+        /// Remote address of the relay
+        alias: String,
+    },
+    /// A reconnection attempt is scheduled
+    Reconnecting {
+        /// Remote address of the relay
+        alias: String,
+        /// Attempt number, starting at 1
+        attempt: u32,
+        /// Delay before the attempt, in milliseconds
+        delay_ms: u64,
+    },
+    /// The relay was registered again under the same alias
+    Reconnected {
+        /// Remote address of the relay
+        alias: String,
+        /// Number of attempts which were necessary
+        attempts: u32,
+    },
+    /// The maximum number of attempts was reached, the relay is not reconnected anymore
+    GaveUp {
+        /// Remote address of the relay
+        alias: String,
+        /// Number of failed attempts
+        attempts: u32,
+    },
+    /// The registration was rejected by the relay service, the relay is stopped
+    Rejected {
+        /// Remote address of the relay
+        alias: String,
+        /// Reason of the rejection
+        reason: String,
+    },
+}
+
+impl RemoteRelay {
+    /// Return true if this relay re-dials its route when the connection is lost
+    pub(super) fn can_reconnect(&self) -> bool {
+        self.reconnection.is_some() && self.heartbeat.is_some()
+    }
+
+    /// Send an event to the events address, if there is one
+    pub(super) async fn send_event(&self, ctx: &Context, event: RemoteRelayEvent) {
+        if let Some(events_address) = &self.events_address {
+            if let Err(e) = ctx
+                .send_from_address(
+                    events_address.clone(),
+                    event,
+                    self.addresses.main_remote.clone(),
+                )
+                .await
+            {
+                warn!("cannot send a RemoteRelay event to {events_address}: {e}");
+            }
+        }
+    }
+
+    /// Mark the relay as disconnected and schedule the first reconnection attempt
+    pub(super) async fn on_disconnected(&mut self, ctx: &Context) -> Result<()> {
+        if self.disconnected {
+            return self.schedule_reconnection(ctx).await;
+        }
+        warn!(
+            "RemoteRelay {} lost the connection to {}",
+            self.registration_payload, self.registration_route
+        );
+        self.disconnected = true;
+        self.reconnection_attempts = 0;
+        self.set_liveness(RelayLiveness::Lost);
+        self.send_event(
+            ctx,
+            RemoteRelayEvent::Disconnected {
+                alias: self.registration_payload.clone(),
+            },
+        )
+        .await;
+        self.schedule_reconnection(ctx).await
+    }
+
+    /// Schedule the next reconnection attempt, unless the maximum number of attempts is reached
+    async fn schedule_reconnection(&mut self, ctx: &Context) -> Result<()> {
+        let policy = match &self.reconnection {
+            Some(reconnection) => reconnection.policy.clone(),
+            None => return Ok(()),
+        };
+        if !policy.can_retry(self.reconnection_attempts) {
+            warn!(
+                "RemoteRelay {} gave up reconnecting after {} attempts",
+                self.registration_payload, self.reconnection_attempts
+            );
+            self.send_event(
+                ctx,
+                RemoteRelayEvent::GaveUp {
+                    alias: self.registration_payload.clone(),
+                    attempts: self.reconnection_attempts,
+                },
+            )
+            .await;
+            return Ok(());
+        }
+
+        self.reconnection_attempts += 1;
+        let delay = policy.delay(self.reconnection_attempts);
+        self.send_event(
+            ctx,
+            RemoteRelayEvent::Reconnecting {
+                alias: self.registration_payload.clone(),
+                attempt: self.reconnection_attempts,
+                delay_ms: delay.as_millis() as u64,
+            },
+        )
+        .await;
+        if let Some(heartbeat) = &mut self.heartbeat {
+            heartbeat.schedule(delay).await?;
+        }
+        Ok(())
+    }
+
+    /// Dial a new route and send the registration message again, with the same alias
+    pub(super) async fn reconnect(&mut self, ctx: &Context) -> Result<()> {
+        let dialer = match &self.reconnection {
+            Some(reconnection) => reconnection.dialer.clone(),
+            None => return Ok(()),
+        };
+        let service = self.registration_route.recipient()?;
+        let hub_route = match dialer.dial(ctx).await {
+            Ok(hub_route) => hub_route,
+            Err(e) => {
+                warn!(
+                    "RemoteRelay {} cannot dial a new route: {e}",
+                    self.registration_payload
+                );
+                return self.schedule_reconnection(ctx).await;
+            }
+        };
+        self.registration_route = route![hub_route, service];
+        RemoteRelayOptions::setup_flow_control_after_reconnection(
+            ctx.flow_controls(),
+            &self.addresses,
+            self.registration_route.next()?,
+        );
+
+        info!(
+            "RemoteRelay {} registering again with route {}",
+            self.registration_payload, self.registration_route
+        );
+        if let Err(e) = ctx
+            .send_from_address(
+                self.registration_route.clone(),
+                self.registration_payload.clone(),
+                self.addresses.main_remote.clone(),
+            )
+            .await
+        {
+            warn!(
+                "RemoteRelay {} cannot register again: {e}",
+                self.registration_payload
+            );
+            return self.schedule_reconnection(ctx).await;
+        }
+
+        // If the registration is not acknowledged before the next heartbeat, another
+        // attempt will be made
+        if let Some(heartbeat) = &mut self.heartbeat {
+            heartbeat.schedule(self.heartbeat_interval).await?;
+        }
+        Ok(())
+    }
+
+    /// Called when the relay service acknowledged a registration
+    pub(super) async fn on_registered(&mut self, ctx: &Context) {
+        if self.disconnected {
+            info!(
+                "RemoteRelay {} reconnected after {} attempts",
+                self.registration_payload, self.reconnection_attempts
+            );
+            self.disconnected = false;
+            self.send_event(
+                ctx,
+                RemoteRelayEvent::Reconnected {
+                    alias: self.registration_payload.clone(),
+                    attempts: self.reconnection_attempts,
+                },
+            )
+            .await;
+            self.reconnection_attempts = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_delays() {
+        let policy = ReconnectPolicy::new()
+            .with_initial_delay(Duration::from_millis(100))
+            .with_max_delay(Duration::from_secs(1))
+            .with_jitter_percent(0);
+
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(200));
+        assert_eq!(policy.delay(4), Duration::from_millis(800));
+        assert_eq!(policy.delay(5), Duration::from_secs(1));
+        assert_eq!(policy.delay(100), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_backoff_jitter() {
+        let policy = ReconnectPolicy::new()
+            .with_initial_delay(Duration::from_millis(1000))
+            .with_jitter_percent(10);
+        for _ in 0..100 {
+            let delay = policy.delay(1);
+            assert!(delay >= Duration::from_millis(900));
+            assert!(delay <= Duration::from_millis(1100));
+        }
+    }
+
+    #[test]
+    fn test_max_attempts() {
+        let policy = ReconnectPolicy::new().with_max_attempts(3);
+        assert!(policy.can_retry(2));
+        assert!(!policy.can_retry(3));
+        assert!(ReconnectPolicy::new().can_retry(u32::MAX));
+    }
+}
@@ -1,4 +1,7 @@
-use crate::remote::{RemoteRelay, RemoteRelayInfo};
+use crate::relay_service::{REGISTER_AGAIN, REGISTRATION_REJECTED};
+use crate::remote::{
+    RegistrationResult, RelayLiveness, RemoteRelay, RemoteRelayEvent, RemoteRelayInfo,
+};
 use crate::{Context, OckamError};
 use ockam_core::compat::{
     boxed::Box,
@@ -6,7 +9,7 @@ use ockam_core::compat::{
     vec::Vec,
 };
 use ockam_core::{Any, Decodable, Result, Routed, Worker};
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 #[crate::worker]
 impl Worker for RemoteRelay {
@@ -32,13 +35,32 @@ impl Worker for RemoteRelay {
         msg: Routed<Self::Message>,
     ) -> Result<()> {
         if msg.msg_addr() == self.addresses.heartbeat {
+            if self.disconnected {
+                // The connection was lost, try to establish a new one
+                return self.reconnect(ctx).await;
+            }
+
+            // Check that the previous registration was acknowledged
+            let lost = self.on_heartbeat();
+            if lost && self.can_reconnect() {
+                return self.on_disconnected(ctx).await;
+            }
+
             // Heartbeat message, send registration message
-            ctx.send_from_address(
-                self.registration_route.clone(),
-                self.registration_payload.clone(),
-                self.addresses.main_remote.clone(),
-            )
-            .await?;
+            let sent = ctx
+                .send_from_address(
+                    self.registration_route.clone(),
+                    self.registration_payload.clone(),
+                    self.addresses.main_remote.clone(),
+                )
+                .await;
+            if let Err(e) = sent {
+                self.set_liveness(RelayLiveness::Lost);
+                if self.can_reconnect() {
+                    return self.on_disconnected(ctx).await;
+                }
+                return Err(e);
+            }
 
             if let Some(heartbeat) = &mut self.heartbeat {
                 heartbeat.schedule(self.heartbeat_interval).await?;
@@ -61,11 +83,28 @@ impl Worker for RemoteRelay {
                         .map_err(|_| OckamError::InvalidHubResponse)?;
                     let payload =
                         String::from_utf8(payload).map_err(|_| OckamError::InvalidHubResponse)?;
+                    if let Some(reason) = payload.strip_prefix(REGISTRATION_REJECTED) {
+                        return self.on_rejected(ctx, reason).await;
+                    }
+                    if payload == REGISTER_AGAIN {
+                        // The relay service restarted, our registration must be renewed
+                        debug!("RemoteRelay is asked to register again");
+                        return ctx
+                            .send_from_address(
+                                self.registration_route.clone(),
+                                self.registration_payload.clone(),
+                                self.addresses.main_remote.clone(),
+                            )
+                            .await;
+                    }
                     // using ends_with() instead of == to allow for prefixes
                     if !payload.ends_with(&self.registration_payload) {
                         return Err(OckamError::InvalidHubResponse)?;
                     }
 
+                    self.on_registered(ctx).await;
+                    self.on_alive();
+
                     if !self.completion_msg_sent {
                         info!("RemoteRelay registered with route: {}", return_route);
                         let address = match return_route.recipient()?.to_string().strip_prefix("0#")
@@ -76,12 +115,12 @@ impl Worker for RemoteRelay {
 
                         ctx.send_from_address(
                             self.addresses.completion_callback.clone(),
-                            RemoteRelayInfo::new(
+                            RegistrationResult::Registered(RemoteRelayInfo::new(
                                 return_route,
                                 address,
                                 self.addresses.main_remote.clone(),
                                 self.flow_control_id.clone(),
-                            ),
+                            )),
                             self.addresses.main_remote.clone(),
                         )
                         .await?;
@@ -111,6 +150,7 @@ impl Worker for RemoteRelay {
 
                     // We received message from the other node, our registration is still alive, let's reset
                     // heartbeat timer
+                    self.on_alive();
                     if let Some(heartbeat) = &mut self.heartbeat {
                         heartbeat.schedule(self.heartbeat_interval).await?;
                     }
@@ -123,3 +163,35 @@ impl Worker for RemoteRelay {
         }
     }
 }
+
+impl RemoteRelay {
+    /// Stop the relay when its registration is rejected by the relay service, and report
+    /// the rejection to the context waiting for the relay to be created, if there is one
+    async fn on_rejected(&mut self, ctx: &Context, reason: &str) -> Result<()> {
+        warn!(
+            "RemoteRelay {} registration was rejected: {reason}",
+            self.registration_payload
+        );
+        self.set_liveness(RelayLiveness::Lost);
+        self.send_event(
+            ctx,
+            RemoteRelayEvent::Rejected {
+                alias: self.registration_payload.clone(),
+                reason: reason.to_string(),
+            },
+        )
+        .await;
+
+        if !self.completion_msg_sent {
+            ctx.send_from_address(
+                self.addresses.completion_callback.clone(),
+                RegistrationResult::Rejected(reason.to_string()),
+                self.addresses.main_remote.clone(),
+            )
+            .await?;
+            self.completion_msg_sent = true;
+        }
+
+        ctx.stop_worker(self.addresses.main_internal.clone()).await
+    }
+}
@@ -1,10 +1,19 @@
-use ockam::identity::{secure_channels, SecureChannelListenerOptions, SecureChannelOptions};
-use ockam::remote::{RemoteRelay, RemoteRelayOptions};
+use ockam::identity::utils::now;
+use ockam::identity::{
+    secure_channels, AttributesEntry, SecureChannelListenerOptions, SecureChannelOptions,
+};
+use ockam::remote::{
+    ReconnectPolicy, RelayLiveness, RemoteRelay, RemoteRelayEvent, RemoteRelayOptions, RouteDialer,
+};
 use ockam::workers::Echoer;
-use ockam::{RelayService, RelayServiceOptions};
-use ockam_core::{route, AllowAll, Result};
+use ockam::{
+    AliasConflictPolicy, RelayAuthorization, RelayRegistration, RelayRegistrationsRepository,
+    RelayService, RelayServiceOptions,
+};
+use ockam_core::{async_trait, route, Address, AllowAll, Result, Route};
 use ockam_node::{Context, MessageReceiveOptions};
-use ockam_transport_tcp::{TcpConnectionOptions, TcpListenerOptions, TcpTransport};
+use ockam_transport_tcp::{TcpConnectionOptions, TcpListener, TcpListenerOptions, TcpTransport};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 // Node creates a Relay service and a Remote Relay, Echoer is reached through the Relay. No flow control
@@ -253,3 +262,432 @@ async fn test4(ctx: &mut Context) -> Result<()> {
 
     ctx.stop().await
 }
+
+struct TcpDialer {
+    tcp: TcpTransport,
+    address: String,
+}
+
+#[async_trait]
+impl RouteDialer for TcpDialer {
+    async fn dial(&self, _ctx: &Context) -> Result<Route> {
+        let connection = self
+            .tcp
+            .connect(&self.address, TcpConnectionOptions::new())
+            .await?;
+        Ok(route![connection])
+    }
+}
+
+// Cloud: Hosts a static Relay service and listens on a tcp port
+// Server: Connects to a Cloud using tcp and creates a static Relay which reconnects when its
+//         connection is lost
+#[ockam_macros::test]
+async fn test5(ctx: &mut Context) -> Result<()> {
+    let tcp_listener_options = TcpListenerOptions::new();
+    let options = RelayServiceOptions::new()
+        .service_as_consumer(&tcp_listener_options.spawner_flow_control_id())
+        .relay_as_consumer(&tcp_listener_options.spawner_flow_control_id());
+    RelayService::create(ctx, "static_forwarding_service", options).await?;
+    let cloud_tcp = TcpTransport::create(ctx).await?;
+    let cloud_listener = cloud_tcp
+        .listen("127.0.0.1:0", tcp_listener_options)
+        .await?;
+
+    let mut events = ctx.new_detached("relay_events", AllowAll, AllowAll).await?;
+
+    let server_tcp = TcpTransport::create(ctx).await?;
+    let cloud_connection = server_tcp
+        .connect(cloud_listener.socket_string(), TcpConnectionOptions::new())
+        .await?;
+
+    let dialer = TcpDialer {
+        tcp: server_tcp.clone(),
+        address: cloud_listener.socket_string(),
+    };
+    let policy = ReconnectPolicy::new()
+        .with_initial_delay(Duration::from_millis(100))
+        .with_jitter_percent(0);
+    let options = RemoteRelayOptions::new()
+        .with_reconnection(dialer, policy)
+        .with_events_address("relay_events");
+    RemoteRelay::create_static(ctx, cloud_connection.clone(), "alias", options).await?;
+
+    // Lose the connection and the relay registered by the Cloud
+    server_tcp
+        .disconnect(cloud_connection.sender_address().clone())
+        .await?;
+    ctx.stop_worker("alias").await?;
+
+    let receive_options = || MessageReceiveOptions::new().with_timeout(Duration::from_secs(10));
+    let event = events
+        .receive_extended::<RemoteRelayEvent>(receive_options())
+        .await?
+        .body();
+    assert_eq!(
+        event,
+        RemoteRelayEvent::Disconnected {
+            alias: "alias".to_string()
+        }
+    );
+
+    let event = events
+        .receive_extended::<RemoteRelayEvent>(receive_options())
+        .await?
+        .body();
+    assert_eq!(
+        event,
+        RemoteRelayEvent::Reconnecting {
+            alias: "alias".to_string(),
+            attempt: 1,
+            delay_ms: 100
+        }
+    );
+
+    let event = events
+        .receive_extended::<RemoteRelayEvent>(receive_options())
+        .await?
+        .body();
+    assert_eq!(
+        event,
+        RemoteRelayEvent::Reconnected {
+            alias: "alias".to_string(),
+            attempts: 1
+        }
+    );
+
+    ctx.stop().await
+}
+
+// Node creates a static Relay with a liveness callback, the Relay stays registered while its
+// heartbeats are acknowledged, and is lost when the Relay service stops
+#[ockam_macros::test]
+async fn test6(ctx: &mut Context) -> Result<()> {
+    RelayService::create(ctx, "static_forwarding_service", RelayServiceOptions::new()).await?;
+
+    let states = Arc::new(Mutex::new(vec![]));
+    let states_clone = states.clone();
+    let options = RemoteRelayOptions::new()
+        .with_heartbeat_interval(Duration::from_millis(100))
+        .with_liveness_callback(move |alias, liveness| {
+            states_clone
+                .lock()
+                .unwrap()
+                .push((alias.to_string(), liveness))
+        });
+    RemoteRelay::create_static(ctx, route![], "alias", options).await?;
+    assert_eq!(
+        *states.lock().unwrap(),
+        vec![("alias".to_string(), RelayLiveness::Registered)]
+    );
+
+    // The heartbeats re-register the alias and are acknowledged by the existing Relay,
+    // so the Relay stays registered
+    ctx.sleep(Duration::from_millis(500)).await;
+    assert_eq!(
+        *states.lock().unwrap(),
+        vec![("alias".to_string(), RelayLiveness::Registered)]
+    );
+
+    ctx.stop_worker("static_forwarding_service").await?;
+    ctx.sleep(Duration::from_millis(500)).await;
+    assert_eq!(
+        states.lock().unwrap().last(),
+        Some(&("alias".to_string(), RelayLiveness::Lost))
+    );
+
+    ctx.stop().await
+}
+
+#[derive(Default)]
+struct InMemoryRegistrations {
+    registrations: Mutex<Vec<RelayRegistration>>,
+}
+
+#[async_trait]
+impl RelayRegistrationsRepository for InMemoryRegistrations {
+    async fn store_registrations(
+        &self,
+        alias: &str,
+        registrations: &[RelayRegistration],
+    ) -> Result<()> {
+        let mut stored = self.registrations.lock().unwrap();
+        stored.retain(|r| r.alias != alias);
+        stored.extend_from_slice(registrations);
+        Ok(())
+    }
+
+    async fn delete_registrations(&self, alias: &str) -> Result<()> {
+        self.registrations
+            .lock()
+            .unwrap()
+            .retain(|r| r.alias != alias);
+        Ok(())
+    }
+
+    async fn get_registrations(&self) -> Result<Vec<RelayRegistration>> {
+        Ok(self.registrations.lock().unwrap().clone())
+    }
+}
+
+// Node creates a Relay service persisting its registrations and a static Relay.
+// The Relay is available again when the Relay service restarts
+#[ockam_macros::test]
+async fn test8(ctx: &mut Context) -> Result<()> {
+    let repository = Arc::new(InMemoryRegistrations::default());
+    RelayService::create(
+        ctx,
+        "static_forwarding_service",
+        RelayServiceOptions::new().with_registrations_repository(repository.clone()),
+    )
+    .await?;
+
+    ctx.start_worker("echoer", Echoer).await?;
+
+    // Relays with a random alias are not persisted
+    RemoteRelay::create(ctx, route![], RemoteRelayOptions::new()).await?;
+    RemoteRelay::create_static(ctx, route![], "alias", RemoteRelayOptions::new()).await?;
+    let registrations = repository.get_registrations().await?;
+    assert_eq!(registrations.len(), 1);
+    assert_eq!(registrations[0].alias, "alias");
+
+    // Restart the Relay service
+    ctx.stop_worker("alias").await?;
+    ctx.stop_worker("static_forwarding_service").await?;
+    ctx.sleep(Duration::from_millis(200)).await;
+    RelayService::create(
+        ctx,
+        "static_forwarding_service",
+        RelayServiceOptions::new().with_registrations_repository(repository.clone()),
+    )
+    .await?;
+
+    let resp = ctx
+        .send_and_receive::<String>(route!["alias", "echoer"], "Hello".to_string())
+        .await?;
+    assert_eq!(resp, "Hello");
+
+    ctx.stop().await
+}
+
+// Cloud: Hosts a Relay service only accepting registrations from identities with the relay=allowed
+//        attribute, for aliases listed in their relay_aliases attribute
+// Server: Registers static Relays through a secure channel, a registration for another alias
+//         is rejected and the relay stops forwarding once the server loses its attribute
+#[ockam_macros::test]
+async fn test7(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+    let identities_creation = secure_channels.identities().identities_creation();
+    let cloud = identities_creation.create_identity().await?;
+    let server = identities_creation.create_identity().await?;
+
+    let attributes = [
+        (b"relay".to_vec(), b"allowed".to_vec()),
+        (b"relay_aliases".to_vec(), b"server-*".to_vec()),
+    ];
+    secure_channels
+        .identities()
+        .identity_attributes_repository()
+        .put_attributes(
+            &server,
+            AttributesEntry::new(attributes.into(), now()?, None, None),
+        )
+        .await?;
+
+    let cloud_listener_options = SecureChannelListenerOptions::new();
+    let authorization = RelayAuthorization::new(
+        secure_channels
+            .identities()
+            .identity_attributes_repository(),
+    )
+    .with_required_attribute("relay", "allowed")
+    .with_alias_attribute("relay_aliases");
+    let options = RelayServiceOptions::new()
+        .service_as_consumer(&cloud_listener_options.spawner_flow_control_id())
+        .relay_as_consumer(&cloud_listener_options.spawner_flow_control_id())
+        .with_authorization(authorization);
+    RelayService::create(ctx, "static_forwarding_service", options).await?;
+    secure_channels
+        .create_secure_channel_listener(ctx, &cloud, "cloud_listener", cloud_listener_options)
+        .await?;
+
+    let channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            &server,
+            route!["cloud_listener"],
+            SecureChannelOptions::new(),
+        )
+        .await?;
+
+    // The alias matches the relay_aliases attribute
+    let remote_info =
+        RemoteRelay::create_static(ctx, channel.clone(), "server-1", RemoteRelayOptions::new())
+            .await?;
+    assert_eq!(remote_info.remote_address(), "server-1");
+
+    // The alias does not match the relay_aliases attribute, the registration is rejected
+    let rejected =
+        RemoteRelay::create_static(ctx, channel.clone(), "other", RemoteRelayOptions::new()).await;
+    assert!(rejected.is_err());
+    assert!(!ctx
+        .list_workers()
+        .await?
+        .contains(&Address::from_string("other")));
+
+    // The server loses the relay attribute, the relay does not forward its messages anymore
+    secure_channels
+        .identities()
+        .identity_attributes_repository()
+        .put_attributes(
+            &server,
+            AttributesEntry::new(
+                [(b"relay".to_vec(), b"denied".to_vec())].into(),
+                now()?,
+                None,
+                None,
+            ),
+        )
+        .await?;
+    ctx.send(route![channel, "server-1", "echoer"], "Hello".to_string())
+        .await?;
+    ctx.sleep(Duration::from_millis(200)).await;
+    assert!(!ctx
+        .list_workers()
+        .await?
+        .contains(&Address::from_string("server-1")));
+
+    ctx.stop().await
+}
+
+// Cloud: Hosts a static Relay service persisting its registrations and listens on a tcp port
+// Server: Connects to the Cloud using tcp and creates a static Relay which reconnects when its
+//         connection is lost
+// The Cloud restarts, the restored Relay forwards messages again once the Server reconnected
+#[ockam_macros::test]
+async fn test9(ctx: &mut Context) -> Result<()> {
+    let repository = Arc::new(InMemoryRegistrations::default());
+    let cloud_tcp = TcpTransport::create(ctx).await?;
+    let cloud_listener = start_cloud(ctx, &cloud_tcp, repository.clone(), "127.0.0.1:0").await?;
+
+    let mut events = ctx.new_detached("relay_events", AllowAll, AllowAll).await?;
+    let mut server_ctx = ctx.new_detached("server_ctx", AllowAll, AllowAll).await?;
+
+    let tcp_options = TcpConnectionOptions::new();
+    ctx.flow_controls()
+        .add_consumer("server_ctx", &tcp_options.flow_control_id());
+    let server_tcp = TcpTransport::create(ctx).await?;
+    let cloud_connection = server_tcp
+        .connect(cloud_listener.socket_string(), tcp_options)
+        .await?;
+
+    let dialer = TcpDialer {
+        tcp: server_tcp.clone(),
+        address: cloud_listener.socket_string(),
+    };
+    let policy = ReconnectPolicy::new()
+        .with_initial_delay(Duration::from_millis(100))
+        .with_jitter_percent(0);
+    let options = RemoteRelayOptions::new()
+        .with_heartbeat_interval(Duration::from_millis(200))
+        .with_reconnection(dialer, policy)
+        .with_events_address("relay_events");
+    RemoteRelay::create_static(ctx, cloud_connection.clone(), "alias", options).await?;
+    assert_eq!(repository.get_registrations().await?.len(), 1);
+
+    // Restart the Cloud, the persisted route of the Server does not lead to it anymore
+    server_tcp
+        .disconnect(cloud_connection.sender_address().clone())
+        .await?;
+    ctx.stop_worker("alias").await?;
+    ctx.stop_worker("static_forwarding_service").await?;
+    cloud_tcp
+        .stop_listener(cloud_listener.processor_address())
+        .await?;
+    ctx.sleep(Duration::from_millis(100)).await;
+    start_cloud(
+        ctx,
+        &cloud_tcp,
+        repository.clone(),
+        &cloud_listener.socket_string(),
+    )
+    .await?;
+
+    loop {
+        let event = events
+            .receive_extended::<RemoteRelayEvent>(
+                MessageReceiveOptions::new().with_timeout(Duration::from_secs(10)),
+            )
+            .await?
+            .body();
+        if matches!(event, RemoteRelayEvent::Reconnected { .. }) {
+            break;
+        }
+    }
+
+    ctx.send(route!["alias", "server_ctx"], "Hello".to_string())
+        .await?;
+    let res = server_ctx
+        .receive_extended::<String>(
+            MessageReceiveOptions::new().with_timeout(Duration::from_secs(5)),
+        )
+        .await?;
+    assert_eq!(res.body(), "Hello");
+
+    ctx.stop().await
+}
+
+// Node creates a Relay service persisting its registrations and rejecting alias conflicts,
+// and a static Relay. When the Relay service restarts, the restored Relay asks its registrant
+// to register again, so that the alias can't be taken by another node
+#[ockam_macros::test]
+async fn test10(ctx: &mut Context) -> Result<()> {
+    let repository = Arc::new(InMemoryRegistrations::default());
+    let options = || {
+        RelayServiceOptions::new()
+            .with_registrations_repository(repository.clone())
+            .with_alias_conflict_policy(AliasConflictPolicy::Reject)
+    };
+    RelayService::create(ctx, "static_forwarding_service", options()).await?;
+
+    ctx.start_worker("echoer", Echoer).await?;
+
+    // No heartbeat is sent during the test, the registrant only registers again on request
+    let relay_options =
+        || RemoteRelayOptions::new().with_heartbeat_interval(Duration::from_secs(60));
+    RemoteRelay::create_static(ctx, route![], "alias", relay_options()).await?;
+
+    // Restart the Relay service
+    ctx.stop_worker("alias").await?;
+    ctx.stop_worker("static_forwarding_service").await?;
+    ctx.sleep(Duration::from_millis(200)).await;
+    RelayService::create(ctx, "static_forwarding_service", options()).await?;
+    ctx.sleep(Duration::from_millis(200)).await;
+
+    // The alias is still used by the first registrant
+    let rejected = RemoteRelay::create_static(ctx, route![], "alias", relay_options()).await;
+    assert!(rejected.is_err());
+
+    let resp = ctx
+        .send_and_receive::<String>(route!["alias", "echoer"], "Hello".to_string())
+        .await?;
+    assert_eq!(resp, "Hello");
+
+    ctx.stop().await
+}
+
+/// Start a static Relay service persisting its registrations, reached with tcp
+async fn start_cloud(
+    ctx: &Context,
+    cloud_tcp: &TcpTransport,
+    repository: Arc<InMemoryRegistrations>,
+    address: &str,
+) -> Result<TcpListener> {
+    let tcp_listener_options = TcpListenerOptions::new();
+    let options = RelayServiceOptions::new()
+        .service_as_consumer(&tcp_listener_options.spawner_flow_control_id())
+        .relay_as_consumer(&tcp_listener_options.spawner_flow_control_id())
+        .with_registrations_repository(repository);
+    RelayService::create(ctx, "static_forwarding_service", options).await?;
+    cloud_tcp.listen(address, tcp_listener_options).await
+}
@@ -7,7 +7,7 @@ use ockam_core::compat::fmt::Formatter;
 use ockam_core::compat::str;
 use ockam_core::compat::vec::vec;
 use ockam_core::Result;
-use ockam_core::{IncomingAccessControl, RelayMessage};
+use ockam_core::{IncomingAccessControl, OutgoingAccessControl, RelayMessage};
 use tracing as log;
 
 use crate::expr::str;
@@ -16,7 +16,14 @@ use crate::{eval, Env, Expr, Policy};
 use ockam_core::compat::format;
 use ockam_core::compat::string::ToString;
 use ockam_core::compat::sync::Arc;
-use ockam_identity::{Identifier, IdentityAttributesRepository, IdentitySecureChannelLocalInfo};
+use ockam_identity::utils::now;
+use ockam_identity::{
+    Identifier, IdentityAttributesRepository, IdentitySecureChannelLocalInfo, SecureChannelRegistry,
+};
+
+/// Name of the environment entry containing the current time, in seconds since the unix epoch.
+/// It is added to the environment when evaluating a policy, unless it is already present
+pub const CURRENT_TIME: &str = "current.time";
 
 /// This AccessControl uses a storage for authenticated attributes in order
 /// to verify if a policy expression is valid
@@ -82,7 +89,8 @@ impl AbacAccessControl {
             .get_attributes(&id)
             .await?
         {
-            for (key, value) in attrs.attrs() {
+            // Attributes with their own expiration time are ignored once they are expired
+            for (key, value) in attrs.valid_attrs()?.iter() {
                 let key = match from_utf8(key) {
                     Ok(key) => key,
                     Err(_) => {
@@ -131,8 +139,15 @@ impl AbacAccessControl {
         // add the identifier itself as a subject parameter
         environment.put("subject.identifier", str(id.to_string()));
 
+        // add the current time, for the policies restricting the access to some time ranges
+        if !environment.contains(CURRENT_TIME) {
+            if let Ok(now) = now() {
+                environment.put(CURRENT_TIME, Int(now.0 as i64));
+            }
+        }
+
         // Finally, evaluate the expression and return the result:
-        match eval(self.policy.expression(), &environment) {
+        let is_authorized = match eval(self.policy.expression(), &environment) {
             Ok(Expr::Bool(b)) => {
                 log::debug! {
                     policy        = %self.policy,
@@ -140,7 +155,7 @@ impl AbacAccessControl {
                     is_authorized = %b,
                     "policy evaluated"
                 }
-                Ok(b)
+                b
             }
             Ok(x) => {
                 log::warn! {
@@ -149,7 +164,7 @@ impl AbacAccessControl {
                     expr   = %x,
                     "evaluation did not yield a boolean result"
                 }
-                Ok(false)
+                false
             }
             Err(e) => {
                 log::warn! {
@@ -158,8 +173,23 @@ impl AbacAccessControl {
                     err    = %e,
                     "policy evaluation failed"
                 }
-                Ok(false)
+                false
             }
+        };
+        if !is_authorized {
+            self.audit_denial(Some(&id));
+        }
+        Ok(is_authorized)
+    }
+
+    /// Emit an audit event for a denied access
+    fn audit_denial(&self, id: Option<&Identifier>) {
+        log::info! {
+            target: "ockam::audit::policies",
+            event   = "policy_denied",
+            policy  = %self.policy,
+            subject = id.map(|id| id.to_string()),
+            "access denied by policy"
         }
     }
 }
@@ -176,9 +206,84 @@ impl IncomingAccessControl for AbacAccessControl {
                 policy = %self.policy,
                 "identity identifier not found; access denied"
             }
+            self.audit_denial(None);
             return Ok(false);
         };
 
         self.is_identity_authorized(id).await
     }
 }
+
+/// This AccessControl evaluates a policy expression against the authenticated attributes
+/// of the identity on the other side of the secure channel a message is sent through,
+/// so that a worker only sends messages to the identities allowed by the policy.
+///
+/// Messages which are not sent through a secure channel are denied.
+pub struct AbacOutgoingAccessControl {
+    access_control: AbacAccessControl,
+    secure_channel_registry: SecureChannelRegistry,
+}
+
+/// Debug implementation printing out the policy expression only
+impl Debug for AbacOutgoingAccessControl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.access_control.fmt(f)
+    }
+}
+
+impl AbacOutgoingAccessControl {
+    /// Create a new AccessControl using a specific policy for checking the attributes
+    /// of the recipients of the messages
+    pub fn new(
+        identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
+        secure_channel_registry: SecureChannelRegistry,
+        policy: Policy,
+        environment: Env,
+    ) -> Self {
+        Self {
+            access_control: AbacAccessControl::new(
+                identity_attributes_repository,
+                policy,
+                environment,
+            ),
+            secure_channel_registry,
+        }
+    }
+
+    /// Create an AccessControl which will verify that the recipient of
+    /// a message has an authenticated attribute with the correct name and value
+    pub fn create(
+        identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
+        secure_channel_registry: SecureChannelRegistry,
+        attribute_name: &str,
+        attribute_value: &str,
+    ) -> Self {
+        Self {
+            access_control: AbacAccessControl::create(
+                identity_attributes_repository,
+                attribute_name,
+                attribute_value,
+            ),
+            secure_channel_registry,
+        }
+    }
+}
+
+#[async_trait]
+impl OutgoingAccessControl for AbacOutgoingAccessControl {
+    /// Returns true if the recipient of the message is validated by the expression
+    async fn is_authorized(&self, msg: &RelayMessage) -> Result<bool> {
+        let id = if let Some(id) = self.secure_channel_registry.get_peer_identifier(msg) {
+            id
+        } else {
+            log::debug! {
+                policy = %self.access_control.policy,
+                "recipient identity identifier not found; access denied"
+            }
+            self.access_control.audit_denial(None);
+            return Ok(false);
+        };
+
+        self.access_control.is_identity_authorized(id).await
+    }
+}
@@ -3,6 +3,8 @@ use core::cmp::Ordering;
 use crate::env::Env;
 use crate::error::EvalError;
 use crate::expr::{unit, Expr};
+use crate::functions;
+use ockam_core::compat::format;
 use ockam_core::compat::string::ToString;
 use ockam_core::compat::vec::Vec;
 
@@ -22,7 +24,10 @@ pub fn eval(expr: &Expr, env: &Env) -> Result<Expr, EvalError> {
         Eq(usize),
         Gt(usize),
         Lt(usize),
+        Ge(usize),
+        Le(usize),
         Member,
+        Call(&'a str, usize),
         Seq(usize),
     }
 
@@ -114,6 +119,20 @@ pub fn eval(expr: &Expr, env: &Env) -> Result<Expr, EvalError> {
                             }
                             ctrl.push(Op::Gt(nargs))
                         }
+                        "<=" => {
+                            if nargs < 2 {
+                                let msg = "'<=' requires at least two arguments";
+                                return Err(EvalError::malformed(msg))
+                            }
+                            ctrl.push(Op::Le(nargs))
+                        }
+                        ">=" => {
+                            if nargs < 2 {
+                                let msg = "'>=' requires at least two arguments";
+                                return Err(EvalError::malformed(msg))
+                            }
+                            ctrl.push(Op::Ge(nargs))
+                        }
                         "=" => {
                             if nargs < 2 {
                                 let msg = "'=' requires at least two arguments";
@@ -149,7 +168,14 @@ pub fn eval(expr: &Expr, env: &Env) -> Result<Expr, EvalError> {
                             args.push(Expr::Bool(b));
                             continue
                         }
-                        _  => return Err(EvalError::Unknown(id.to_string()))
+                        _ => match functions::arity(id) {
+                            Some(n) if n == nargs => ctrl.push(Op::Call(id, nargs)),
+                            Some(n) => {
+                                let msg = format!("'{id}' requires {n} argument(s)");
+                                return Err(EvalError::malformed(msg))
+                            }
+                            None => return Err(EvalError::Unknown(id.to_string()))
+                        }
                     }
                     for x in xs[1 ..].iter().rev() {
                         ctrl.push(Op::Eval(x))
@@ -235,6 +261,12 @@ pub fn eval(expr: &Expr, env: &Env) -> Result<Expr, EvalError> {
             Op::Gt(n) => eval_predicate(n, &mut args, |x, y| {
                 x.compare(y).map(|o| o == Some(Ordering::Greater))
             })?,
+            Op::Le(n) => eval_predicate(n, &mut args, |x, y| {
+                x.compare(y).map(|o| matches!(o, Some(Ordering::Less | Ordering::Equal)))
+            })?,
+            Op::Ge(n) => eval_predicate(n, &mut args, |x, y| {
+                x.compare(y).map(|o| matches!(o, Some(Ordering::Greater | Ordering::Equal)))
+            })?,
             Op::Member => {
                 let s = pop(&mut args);
                 let y = pop(&mut args);
@@ -259,6 +291,10 @@ pub fn eval(expr: &Expr, env: &Env) -> Result<Expr, EvalError> {
                 let s = args.split_off(args.len() - n);
                 args.push(Expr::Seq(s))
             }
+            Op::Call(f, n) => {
+                let xs = args.split_off(args.len() - n);
+                args.push(functions::apply(f, xs)?)
+            }
         }
     }
 
@@ -266,6 +302,72 @@ pub fn eval(expr: &Expr, env: &Env) -> Result<Expr, EvalError> {
     Ok(pop(&mut args))
 }
 
+/// Check that an expression is well-formed without evaluating it.
+///
+/// Every list must start with a known operator which is applied to the
+/// right number of arguments. Identifiers are not resolved, which means
+/// that an expression can be validated before the environment it will be
+/// evaluated against is known.
+#[rustfmt::skip]
+pub fn validate(expr: &Expr) -> Result<(), EvalError> {
+    // Control stack.
+    let mut ctrl: Vec<&Expr> = Vec::new();
+    ctrl.push(expr);
+
+    while let Some(x) = ctrl.pop() {
+        match x {
+            Expr::Seq(xs)  => ctrl.extend(xs.iter()),
+            Expr::List(xs) => match &xs[..] {
+                [] => {}
+                [Expr::Ident(id), rest @ ..] => {
+                    let nargs = rest.len();
+                    match id.as_str() {
+                        "and" | "or" => {}
+                        "not" => if nargs != 1 {
+                            return Err(EvalError::malformed("'not' requires one argument"))
+                        }
+                        "if" => if nargs != 3 {
+                            return Err(EvalError::malformed("'if' requires three arguments"))
+                        }
+                        "<" | ">" | "<=" | ">=" | "=" | "!=" => if nargs < 2 {
+                            let msg = format!("'{id}' requires at least two arguments");
+                            return Err(EvalError::malformed(msg))
+                        }
+                        "member?" => if nargs != 2 {
+                            return Err(EvalError::malformed("'member?' requires two arguments"))
+                        }
+                        "exists?" => {
+                            for x in rest {
+                                if !x.is_ident() {
+                                    let msg = "'exists?' expects identifiers as arguments";
+                                    return Err(EvalError::InvalidType(x.clone(), msg))
+                                }
+                            }
+                            continue
+                        }
+                        _ => match functions::arity(id) {
+                            Some(n) if n == nargs => {}
+                            Some(n) => {
+                                let msg = format!("'{id}' requires {n} argument(s)");
+                                return Err(EvalError::malformed(msg))
+                            }
+                            None => return Err(EvalError::Unknown(id.to_string()))
+                        }
+                    }
+                    ctrl.extend(rest.iter())
+                }
+                [other, ..] => {
+                    let msg = "expected (op ...)";
+                    return Err(EvalError::InvalidType(other.clone(), msg))
+                }
+            },
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
 /// Pop off the topmost stack value.
 ///
 /// # Panics
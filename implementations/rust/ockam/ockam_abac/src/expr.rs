@@ -7,7 +7,7 @@ use core::str::FromStr;
 use core::cmp::Ordering;
 use core::fmt;
 use minicbor::{Decode, Encode};
-use ockam_core::compat::string::String;
+use ockam_core::compat::string::{String, ToString};
 use ockam_core::compat::vec::{vec, Vec};
 
 #[derive(Debug, Clone, Encode, Decode)]
@@ -73,6 +73,44 @@ impl Expr {
         matches!(self, Expr::Ident(_))
     }
 
+    /// Render this expression on several lines.
+    ///
+    /// Lists and sequences which do not fit in `width` characters are broken
+    /// up, with one argument per line, indented under their operator.
+    pub fn pretty(&self, width: usize) -> String {
+        let mut output = String::new();
+        self.write_pretty(&mut output, 0, width);
+        output
+    }
+
+    fn write_pretty(&self, output: &mut String, indent: usize, width: usize) {
+        let flat = self.to_string();
+        let (open, close, children) = match self {
+            Expr::List(xs) if !xs.is_empty() && indent + flat.len() > width => ("(", ")", xs),
+            Expr::Seq(xs) if !xs.is_empty() && indent + flat.len() > width => ("[", "]", xs),
+            _ => {
+                output.push_str(&flat);
+                return;
+            }
+        };
+        // The operator of a list stays on the first line and its arguments
+        // are indented under it. Sequence elements are simply aligned.
+        let children_indent = if open == "(" { indent + 2 } else { indent + 1 };
+        output.push_str(open);
+        let mut first = true;
+        for x in children {
+            if first {
+                x.write_pretty(output, indent + 1, width);
+                first = false;
+            } else {
+                output.push('\n');
+                output.push_str(&" ".repeat(children_indent));
+                x.write_pretty(output, children_indent, width);
+            }
+        }
+        output.push_str(close);
+    }
+
     /// Like `PartialEq` but errors if expressions are of different types.
     #[rustfmt::skip]
     pub fn equals(&self, other: &Expr) -> Result<bool, EvalError> {
@@ -345,7 +383,7 @@ impl FromStr for Expr {
 
 #[cfg(test)]
 mod tests {
-    use super::Expr;
+    use super::{int, str, Expr};
     use crate::{eval, parser::parse, Env};
     use core::cmp::Ordering;
     use ockam_core::compat::string::ToString;
@@ -559,6 +597,89 @@ mod tests {
         assert_eq!(Some(Ordering::Equal), x.compare(&z).unwrap());
     }
 
+    #[test]
+    fn pretty() {
+        let x = parse(
+            r#"(and (= subject.component "web") (member? subject.role ["admin" "operator"]))"#,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(x.pretty(100), x.to_string());
+        assert_eq!(
+            x.pretty(40),
+            r#"(and
+  (= subject.component "web")
+  (member?
+    subject.role
+    ["admin" "operator"]))"#
+        );
+        let y = parse(&x.pretty(20)).unwrap().unwrap();
+        assert!(x.equals(&y).unwrap());
+    }
+
+    #[test]
+    fn validate() {
+        let valid = [
+            r#"(= subject.component "web")"#,
+            r#"(and (exists? subject.role) (not (= subject.role "guest")))"#,
+            r#"(if (< subject.level 3) true false)"#,
+            r#"[(or) (member? "a" ["a" "b"])]"#,
+            r#"(>= (number subject.level) 3)"#,
+            r#"(time-in-range? current.time "09:00" "17:00")"#,
+            "true",
+        ];
+        for s in valid {
+            let x = parse(s).unwrap().unwrap();
+            assert!(crate::validate(&x).is_ok(), "{s} should be valid");
+        }
+
+        let invalid = [
+            r#"(== subject.component "web")"#,
+            r#"(and (not true false))"#,
+            r#"(if true false)"#,
+            r#"(= subject.component)"#,
+            r#"(member? "a")"#,
+            r#"(exists? "subject.role")"#,
+            r#"("and" true)"#,
+            r#"(<= 1)"#,
+            r#"(weekday)"#,
+            r#"(ip-in-range? target.host "10.0.0.0/8" "192.168.0.0/16")"#,
+        ];
+        for s in invalid {
+            let x = parse(s).unwrap().unwrap();
+            assert!(crate::validate(&x).is_err(), "{s} should be invalid");
+        }
+    }
+
+    #[test]
+    fn office_hours() {
+        let x = parse(
+            r#"(and (member? (weekday current.time) ["mon" "tue" "wed" "thu" "fri"])
+                    (time-in-range? current.time "09:00" "17:00")
+                    (ip-in-range? target.host ["10.0.0.0/8" "192.168.1.0/24"])
+                    (intersects? (split subject.roles ",") ["admin" "operator"])
+                    (<= 1 (number subject.level) 5))"#,
+        )
+        .unwrap()
+        .unwrap();
+        assert!(crate::validate(&x).is_ok());
+
+        let mut env = Env::new();
+        env.put("current.time", int(1_705_311_000)) // Monday 2024-01-15 09:30:00 UTC
+            .put("target.host", str("192.168.1.42"))
+            .put("subject.roles", str("dev,operator"))
+            .put("subject.level", str("3"));
+        assert!(eval(&x, &env).unwrap().is_true());
+
+        // on Saturday 2024-01-20 at the same time
+        env.put("current.time", int(1_705_743_000));
+        assert!(eval(&x, &env).unwrap().is_false());
+
+        env.put("current.time", int(1_705_311_000))
+            .put("target.host", str("172.16.0.1"));
+        assert!(eval(&x, &env).unwrap().is_false());
+    }
+
     #[derive(Debug, Clone)]
     struct S(String);
 
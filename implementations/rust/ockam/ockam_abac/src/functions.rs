@@ -0,0 +1,425 @@
+//! Built-in functions of the policy expression language.
+//!
+//! Contrary to operators like `and` or `if`, functions always evaluate all
+//! their arguments and have a fixed number of arguments:
+//!
+//!  - `(time-in-range? t "09:00" "17:00")`: the UTC time of day of the unix
+//!    timestamp `t` is in the range `[start, end)`. If `start` is after `end`
+//!    the range wraps around midnight
+//!  - `(date-in-range? t "2024-01-01" "2024-12-31")`: the UTC date of the unix
+//!    timestamp `t` is between the two dates, inclusive
+//!  - `(weekday t)`: the UTC day of the week of the unix timestamp `t`, as one
+//!    of `"mon"`, `"tue"`, `"wed"`, `"thu"`, `"fri"`, `"sat"`, `"sun"`
+//!  - `(ip-in-range? ip "10.0.0.0/8")`: the IP address is in the CIDR range, or
+//!    in one of the CIDR ranges when a sequence of ranges is given
+//!  - `(subset? [a b] [a b c])`: all the elements of the first sequence are
+//!    elements of the second sequence
+//!  - `(intersects? [a b] [b c])`: the two sequences have at least one element
+//!    in common
+//!  - `(split "a,b" ",")`: the sequence of the non-empty, trimmed parts of a string
+//!  - `(number "42")`: the integer or float value of a string
+use crate::error::EvalError;
+use crate::expr::Expr;
+use ockam_core::compat::string::ToString;
+use ockam_core::compat::vec::Vec;
+
+/// Names of the built-in functions with their number of arguments
+const FUNCTIONS: &[(&str, usize)] = &[
+    ("time-in-range?", 3),
+    ("date-in-range?", 3),
+    ("weekday", 1),
+    ("ip-in-range?", 2),
+    ("subset?", 2),
+    ("intersects?", 2),
+    ("split", 2),
+    ("number", 1),
+];
+
+/// Names of the days of the week, starting on monday
+const WEEKDAYS: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Return the number of arguments of a built-in function, or `None` if there
+/// is no function with this name
+pub(crate) fn arity(name: &str) -> Option<usize> {
+    FUNCTIONS
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, arity)| *arity)
+}
+
+/// Apply a built-in function to the values of its arguments.
+///
+/// The number of arguments must have been checked with [`arity`].
+pub(crate) fn apply(name: &str, args: Vec<Expr>) -> Result<Expr, EvalError> {
+    match (name, &args[..]) {
+        ("time-in-range?", [t, start, end]) => {
+            let t = timestamp(t, name)?.rem_euclid(SECONDS_PER_DAY);
+            let start = time_of_day(start)?;
+            let end = time_of_day(end)?;
+            let b = if start <= end {
+                start <= t && t < end
+            } else {
+                start <= t || t < end
+            };
+            Ok(Expr::Bool(b))
+        }
+        ("date-in-range?", [t, start, end]) => {
+            let day = timestamp(t, name)?.div_euclid(SECONDS_PER_DAY);
+            Ok(Expr::Bool(date(start)? <= day && day <= date(end)?))
+        }
+        ("weekday", [t]) => {
+            // the 1st of January 1970 was a thursday
+            let day = timestamp(t, name)?.div_euclid(SECONDS_PER_DAY);
+            let weekday = WEEKDAYS[(day + 3).rem_euclid(7) as usize];
+            Ok(Expr::Str(weekday.to_string()))
+        }
+        ("ip-in-range?", [ip, ranges]) => ip_in_range(ip, ranges),
+        ("subset?", [xs, ys]) => {
+            let ys = sequence(ys, name)?;
+            for x in sequence(xs, name)? {
+                if !contains(ys, x)? {
+                    return Ok(Expr::Bool(false));
+                }
+            }
+            Ok(Expr::Bool(true))
+        }
+        ("intersects?", [xs, ys]) => {
+            let ys = sequence(ys, name)?;
+            for x in sequence(xs, name)? {
+                if contains(ys, x)? {
+                    return Ok(Expr::Bool(true));
+                }
+            }
+            Ok(Expr::Bool(false))
+        }
+        ("split", [Expr::Str(s), Expr::Str(sep)]) => Ok(Expr::Seq(
+            s.split(sep.as_str())
+                .map(|x| x.trim())
+                .filter(|x| !x.is_empty())
+                .map(|x| Expr::Str(x.to_string()))
+                .collect(),
+        )),
+        ("split", [Expr::Str(_), other]) | ("split", [other, _]) => Err(EvalError::InvalidType(
+            other.clone(),
+            "'split' expects string arguments",
+        )),
+        ("number", [x @ (Expr::Int(_) | Expr::Float(_))]) => Ok(x.clone()),
+        ("number", [Expr::Str(s)]) => {
+            let s = s.trim();
+            if let Ok(i) = s.parse::<i64>() {
+                Ok(Expr::Int(i))
+            } else if let Ok(f) = s.parse::<f64>() {
+                Ok(Expr::Float(f))
+            } else {
+                Err(EvalError::InvalidType(
+                    Expr::Str(s.to_string()),
+                    "'number' expects a numeric string",
+                ))
+            }
+        }
+        ("number", [other]) => Err(EvalError::InvalidType(
+            other.clone(),
+            "'number' expects a string or a number",
+        )),
+        _ => Err(EvalError::Unknown(name.to_string())),
+    }
+}
+
+/// Return the number of seconds since the unix epoch of a timestamp argument
+fn timestamp(t: &Expr, name: &str) -> Result<i64, EvalError> {
+    match t {
+        Expr::Int(t) => Ok(*t),
+        other => Err(EvalError::InvalidType(
+            other.clone(),
+            match name {
+                "time-in-range?" => "'time-in-range?' expects a unix timestamp as first argument",
+                "date-in-range?" => "'date-in-range?' expects a unix timestamp as first argument",
+                _ => "'weekday' expects a unix timestamp",
+            },
+        )),
+    }
+}
+
+/// Return the number of seconds since midnight of a `HH:MM` or `HH:MM:SS` time
+fn time_of_day(t: &Expr) -> Result<i64, EvalError> {
+    let invalid = || EvalError::InvalidType(t.clone(), "expected a time formatted as HH:MM");
+    let s = match t {
+        Expr::Str(s) => s,
+        _ => return Err(invalid()),
+    };
+    let parts = s
+        .split(':')
+        .map(|p| p.parse::<i64>().map_err(|_| invalid()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let (h, m, s) = match parts[..] {
+        [h, m] => (h, m, 0),
+        [h, m, s] => (h, m, s),
+        _ => return Err(invalid()),
+    };
+    let valid = (0..60).contains(&m)
+        && (0..60).contains(&s)
+        && ((0..24).contains(&h) || (h, m, s) == (24, 0, 0));
+    if !valid {
+        return Err(invalid());
+    }
+    Ok(h * 3600 + m * 60 + s)
+}
+
+/// Return the number of days since the unix epoch of a `YYYY-MM-DD` date
+fn date(d: &Expr) -> Result<i64, EvalError> {
+    let invalid = || EvalError::InvalidType(d.clone(), "expected a date formatted as YYYY-MM-DD");
+    let s = match d {
+        Expr::Str(s) => s,
+        _ => return Err(invalid()),
+    };
+    let parts = s
+        .split('-')
+        .map(|p| p.parse::<i64>().map_err(|_| invalid()))
+        .collect::<Result<Vec<_>, _>>()?;
+    match parts[..] {
+        [y, m, d] if (1..=12).contains(&m) && (1..=31).contains(&d) => Ok(days_from_civil(y, m, d)),
+        _ => Err(invalid()),
+    }
+}
+
+/// Number of days since the unix epoch of a date of the proleptic gregorian calendar.
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#days_from_civil>.
+fn days_from_civil(y: i64, m: i64, d: i64) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * ((m + 9) % 12) + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn sequence<'a>(xs: &'a Expr, name: &str) -> Result<&'a [Expr], EvalError> {
+    match xs {
+        Expr::Seq(xs) => Ok(xs),
+        other => Err(EvalError::InvalidType(
+            other.clone(),
+            if name == "subset?" {
+                "'subset?' expects sequence arguments"
+            } else {
+                "'intersects?' expects sequence arguments"
+            },
+        )),
+    }
+}
+
+fn contains(xs: &[Expr], y: &Expr) -> Result<bool, EvalError> {
+    for x in xs {
+        if y.equals(x)? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+#[cfg(feature = "std")]
+fn ip_in_range(ip: &Expr, ranges: &Expr) -> Result<Expr, EvalError> {
+    use std::net::IpAddr;
+
+    /// Parse an IP address, or a CIDR range like `10.0.0.0/8`, returning
+    /// the address with its prefix length
+    fn parse(range: &Expr) -> Result<(IpAddr, u32), EvalError> {
+        let invalid =
+            || EvalError::InvalidType(range.clone(), "'ip-in-range?' expects CIDR ranges");
+        let s = match range {
+            Expr::Str(s) => s.trim(),
+            _ => return Err(invalid()),
+        };
+        let (addr, prefix) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s, None),
+        };
+        let addr: IpAddr = addr.parse().map_err(|_| invalid())?;
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(p) => p.parse::<u32>().map_err(|_| invalid())?,
+            None => max,
+        };
+        if prefix > max {
+            return Err(invalid());
+        }
+        Ok((addr, prefix))
+    }
+
+    fn matches(ip: &IpAddr, (network, prefix): (IpAddr, u32)) -> bool {
+        // IPv4-mapped IPv6 addresses are matched against the IPv4 ranges
+        let ip = match ip {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(*ip),
+            ip => *ip,
+        };
+        match (ip, network) {
+            (IpAddr::V4(ip), IpAddr::V4(network)) => {
+                let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+                u32::from(ip) & mask == u32::from(network) & mask
+            }
+            (IpAddr::V6(ip), IpAddr::V6(network)) => {
+                let mask = u128::MAX.checked_shl(128 - prefix).unwrap_or(0);
+                u128::from(ip) & mask == u128::from(network) & mask
+            }
+            _ => false,
+        }
+    }
+
+    let ip: IpAddr = match ip {
+        Expr::Str(s) => s.trim().parse().map_err(|_| {
+            EvalError::InvalidType(ip.clone(), "'ip-in-range?' expects an IP address")
+        })?,
+        other => {
+            let msg = "'ip-in-range?' expects an IP address as first argument";
+            return Err(EvalError::InvalidType(other.clone(), msg));
+        }
+    };
+    let b = match ranges {
+        Expr::Seq(ranges) => {
+            let mut b = false;
+            for range in ranges {
+                if matches(&ip, parse(range)?) {
+                    b = true;
+                    break;
+                }
+            }
+            b
+        }
+        range => matches(&ip, parse(range)?),
+    };
+    Ok(Expr::Bool(b))
+}
+
+#[cfg(not(feature = "std"))]
+fn ip_in_range(_ip: &Expr, _ranges: &Expr) -> Result<Expr, EvalError> {
+    Err(EvalError::malformed(
+        "'ip-in-range?' is only supported with the std feature",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::{int, seq, str};
+
+    /// Monday 2024-01-15 09:30:00 UTC
+    const MONDAY_MORNING: i64 = 1_705_311_000;
+
+    fn call(name: &str, args: Vec<Expr>) -> Expr {
+        assert_eq!(arity(name), Some(args.len()));
+        apply(name, args).unwrap()
+    }
+
+    #[test]
+    fn time_and_date_functions() {
+        let t = int(MONDAY_MORNING);
+        assert_eq!(call("weekday", vec![t.clone()]), str("mon"));
+        assert_eq!(call("weekday", vec![int(0)]), str("thu"));
+        assert_eq!(
+            call(
+                "time-in-range?",
+                vec![t.clone(), str("09:00"), str("17:00")]
+            ),
+            Expr::Bool(true)
+        );
+        assert_eq!(
+            call(
+                "time-in-range?",
+                vec![t.clone(), str("09:31"), str("17:00")]
+            ),
+            Expr::Bool(false)
+        );
+        assert_eq!(
+            call(
+                "time-in-range?",
+                vec![t.clone(), str("22:00"), str("10:00")]
+            ),
+            Expr::Bool(true)
+        );
+        assert_eq!(
+            call(
+                "date-in-range?",
+                vec![t.clone(), str("2024-01-15"), str("2024-01-15")]
+            ),
+            Expr::Bool(true)
+        );
+        assert_eq!(
+            call(
+                "date-in-range?",
+                vec![t.clone(), str("2024-01-16"), str("2024-12-31")]
+            ),
+            Expr::Bool(false)
+        );
+        assert!(apply("time-in-range?", vec![t.clone(), str("9h"), str("17:00")]).is_err());
+        assert!(apply(
+            "date-in-range?",
+            vec![t, str("2024-13-01"), str("2024-12-31")]
+        )
+        .is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn ip_functions() {
+        let ip = str("192.168.1.42");
+        assert_eq!(
+            call("ip-in-range?", vec![ip.clone(), str("192.168.1.0/24")]),
+            Expr::Bool(true)
+        );
+        assert_eq!(
+            call("ip-in-range?", vec![ip.clone(), str("192.168.2.0/24")]),
+            Expr::Bool(false)
+        );
+        assert_eq!(
+            call(
+                "ip-in-range?",
+                vec![ip.clone(), seq([str("10.0.0.0/8"), str("192.168.0.0/16")])]
+            ),
+            Expr::Bool(true)
+        );
+        assert_eq!(
+            call(
+                "ip-in-range?",
+                vec![str("::ffff:10.1.2.3"), str("10.0.0.0/8")]
+            ),
+            Expr::Bool(true)
+        );
+        assert_eq!(
+            call("ip-in-range?", vec![str("fd00::1"), str("fd00::/8")]),
+            Expr::Bool(true)
+        );
+        assert_eq!(
+            call("ip-in-range?", vec![ip.clone(), str("0.0.0.0/0")]),
+            Expr::Bool(true)
+        );
+        assert!(apply("ip-in-range?", vec![ip, str("192.168.1.0/33")]).is_err());
+    }
+
+    #[test]
+    fn set_functions() {
+        let xs = call("split", vec![str("web, db,"), str(",")]);
+        assert_eq!(xs, seq([str("web"), str("db")]));
+        let ys = seq([str("web"), str("db"), str("cache")]);
+        assert_eq!(
+            call("subset?", vec![xs.clone(), ys.clone()]),
+            Expr::Bool(true)
+        );
+        assert_eq!(
+            call("subset?", vec![ys.clone(), xs.clone()]),
+            Expr::Bool(false)
+        );
+        assert_eq!(
+            call("intersects?", vec![seq([str("cache")]), xs]),
+            Expr::Bool(false)
+        );
+        assert_eq!(
+            call("intersects?", vec![seq([str("cache")]), ys]),
+            Expr::Bool(true)
+        );
+        assert_eq!(call("number", vec![str("42")]), int(42));
+        assert_eq!(call("number", vec![str("1.5")]), Expr::Float(1.5));
+        assert!(apply("number", vec![str("forty-two")]).is_err());
+    }
+}
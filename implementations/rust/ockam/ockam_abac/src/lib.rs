@@ -10,6 +10,7 @@ extern crate core;
 mod env;
 mod error;
 mod eval;
+mod functions;
 mod policy;
 mod types;
 
@@ -20,10 +21,10 @@ pub mod attribute_access_control;
 pub mod expr;
 mod storage;
 
-pub use attribute_access_control::AbacAccessControl;
+pub use attribute_access_control::{AbacAccessControl, AbacOutgoingAccessControl, CURRENT_TIME};
 pub use env::Env;
 pub use error::{EvalError, ParseError};
-pub use eval::eval;
+pub use eval::{eval, validate};
 pub use expr::Expr;
 pub use policy::PolicyAccessControl;
 pub use storage::*;
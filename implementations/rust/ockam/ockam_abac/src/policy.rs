@@ -1,14 +1,15 @@
 use crate::types::{Action, Resource};
-use crate::{AbacAccessControl, PoliciesRepository};
+use crate::{AbacAccessControl, PoliciesRepository, Policy};
 use crate::{Env, Expr};
 use core::fmt;
 use core::fmt::{Debug, Formatter};
 use ockam_core::compat::boxed::Box;
 use ockam_core::compat::format;
+use ockam_core::compat::string::ToString;
 use ockam_core::compat::sync::Arc;
 use ockam_core::{async_trait, RelayMessage};
 use ockam_core::{IncomingAccessControl, Result};
-use ockam_identity::IdentityAttributesRepository;
+use ockam_identity::{Identifier, IdentityAttributesRepository, IdentitySecureChannelLocalInfo};
 use tracing as log;
 
 /// Evaluates a policy expression against an environment of attributes.
@@ -56,13 +57,45 @@ impl PolicyAccessControl {
             environment: env,
         }
     }
-}
 
-#[async_trait]
-impl IncomingAccessControl for PolicyAccessControl {
-    async fn is_authorized(&self, msg: &RelayMessage) -> Result<bool> {
-        // Load the policy expression for resource and action:
-        let policy = if let Some(policy) = self
+    /// Return true if the identity is authorized by the policy.
+    ///
+    /// The given environment is added to the initial environment, for example
+    /// with attributes which are only known when a connection is requested.
+    pub async fn is_identity_authorized(&self, id: Identifier, environment: Env) -> Result<bool> {
+        let policy = match self.get_policy().await? {
+            PolicyDecision::Constant(b) => {
+                if !b {
+                    self.audit_denial(Some(&id));
+                }
+                return Ok(b);
+            }
+            PolicyDecision::Evaluate(policy) => policy,
+        };
+
+        let mut env = self.environment.clone();
+        env.merge_right(environment);
+        AbacAccessControl::new(self.identity_attributes_repository.clone(), policy, env)
+            .is_identity_authorized(id)
+            .await
+    }
+
+    /// Emit an audit event for an access denied without evaluating a policy expression,
+    /// because there is no policy or because the policy is `false`
+    fn audit_denial(&self, id: Option<&Identifier>) {
+        log::info! {
+            target: "ockam::audit::policies",
+            event    = "policy_denied",
+            resource = %self.resource,
+            action   = %self.action,
+            subject  = id.map(|id| id.to_string()),
+            "access denied by policy"
+        }
+    }
+
+    /// Load the policy expression for resource and action
+    async fn get_policy(&self) -> Result<PolicyDecision> {
+        if let Some(policy) = self
             .policies
             .get_policy(&self.resource, &self.action)
             .await?
@@ -70,9 +103,9 @@ impl IncomingAccessControl for PolicyAccessControl {
             if let Expr::Bool(b) = policy.expression() {
                 // If the policy is a constant there is no need to populate
                 // the environment or look for message metadata.
-                return Ok(*b);
+                Ok(PolicyDecision::Constant(*b))
             } else {
-                policy
+                Ok(PolicyDecision::Evaluate(policy))
             }
         } else {
             // If no policy exists for this resource and action access is denied:
@@ -81,7 +114,31 @@ impl IncomingAccessControl for PolicyAccessControl {
                 action   = %self.action,
                 "no policy found; access denied"
             }
-            return Ok(false);
+            Ok(PolicyDecision::Constant(false))
+        }
+    }
+}
+
+/// Policy of a resource and action, unless the decision does not depend on the environment
+enum PolicyDecision {
+    Constant(bool),
+    Evaluate(Policy),
+}
+
+#[async_trait]
+impl IncomingAccessControl for PolicyAccessControl {
+    async fn is_authorized(&self, msg: &RelayMessage) -> Result<bool> {
+        let policy = match self.get_policy().await? {
+            PolicyDecision::Constant(b) => {
+                if !b {
+                    let id = IdentitySecureChannelLocalInfo::find_info(msg.local_message())
+                        .ok()
+                        .map(|info| info.their_identity_id());
+                    self.audit_denial(id.as_ref());
+                }
+                return Ok(b);
+            }
+            PolicyDecision::Evaluate(policy) => policy,
         };
 
         AbacAccessControl::new(
@@ -5,6 +5,7 @@ use ockam_core::async_trait;
 use ockam_core::compat::boxed::Box;
 use ockam_core::compat::vec::Vec;
 use ockam_core::Result;
+use ockam_identity::{Identifier, TimestampInSeconds};
 
 /// This repository stores policies.
 /// A policy is an expression which can be evaluated against an environment (a list of attribute
@@ -22,6 +23,37 @@ pub trait PoliciesRepository: Send + Sync + 'static {
 
     /// Return the list of all the policies associated to a given resource
     async fn get_policies_by_resource(&self, r: &Resource) -> Result<Vec<(Action, Policy)>>;
+
+    /// Set a policy for a given resource and action, recording the identity which made
+    /// the change in the history of the policy
+    async fn set_policy_by(
+        &self,
+        r: &Resource,
+        a: &Action,
+        c: &Policy,
+        changed_by: Option<&Identifier>,
+    ) -> Result<()>;
+
+    /// Delete the policy associated to a given resource and action, recording the identity
+    /// which made the change in the history of the policy
+    async fn delete_policy_by(
+        &self,
+        r: &Resource,
+        a: &Action,
+        changed_by: Option<&Identifier>,
+    ) -> Result<()>;
+
+    /// Return all the versions of the policy associated to a given resource and action,
+    /// from the oldest to the most recent one
+    async fn get_policy_versions(&self, r: &Resource, a: &Action) -> Result<Vec<PolicyVersion>>;
+
+    /// Return a specific version of the policy associated to a given resource and action
+    async fn get_policy_version(
+        &self,
+        r: &Resource,
+        a: &Action,
+        version: u64,
+    ) -> Result<Option<PolicyVersion>>;
 }
 
 #[derive(Debug, Decode, Encode, PartialEq, Eq)]
@@ -46,3 +78,55 @@ impl Display for Policy {
         self.expression.fmt(f)
     }
 }
+
+/// A version of the policy of a resource and action, created each time the policy is set or deleted
+#[derive(Clone, Debug, Decode, Encode, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct PolicyVersion {
+    #[n(1)] version: u64,
+    #[n(2)] expression: Option<Expr>,
+    #[n(3)] changed_by: Option<Identifier>,
+    #[n(4)] changed_at: TimestampInSeconds,
+}
+
+impl PolicyVersion {
+    pub fn new(
+        version: u64,
+        expression: Option<Expr>,
+        changed_by: Option<Identifier>,
+        changed_at: TimestampInSeconds,
+    ) -> Self {
+        Self {
+            version,
+            expression,
+            changed_by,
+            changed_at,
+        }
+    }
+
+    /// Version number, starting at 1 for each resource and action
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Expression of the policy, or `None` if the policy was deleted in this version
+    pub fn expression(&self) -> Option<&Expr> {
+        self.expression.as_ref()
+    }
+
+    /// Return true if the policy was deleted in this version
+    pub fn is_deletion(&self) -> bool {
+        self.expression.is_none()
+    }
+
+    /// Identity which made the change, when it is known
+    pub fn changed_by(&self) -> Option<&Identifier> {
+        self.changed_by.as_ref()
+    }
+
+    /// Time of the change
+    pub fn changed_at(&self) -> TimestampInSeconds {
+        self.changed_at
+    }
+}
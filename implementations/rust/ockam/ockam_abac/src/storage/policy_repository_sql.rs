@@ -1,12 +1,18 @@
+use core::str::FromStr;
+
+use sqlx::query::Query;
+use sqlx::sqlite::SqliteArguments;
 use sqlx::*;
 use tracing::debug;
 
 use ockam_core::async_trait;
 use ockam_core::compat::vec::Vec;
 use ockam_core::Result;
+use ockam_identity::utils::now;
+use ockam_identity::{Identifier, TimestampInSeconds};
 use ockam_node::database::{FromSqlxError, SqlxDatabase, SqlxType, ToSqlxType, ToVoid};
 
-use crate::{Action, Expr, PoliciesRepository, Policy, Resource};
+use crate::{Action, Expr, PoliciesRepository, Policy, PolicyVersion, Resource};
 
 #[derive(Clone)]
 pub struct PolicySqlxDatabase {
@@ -47,18 +53,11 @@ impl PoliciesRepository for PolicySqlxDatabase {
         action: &Action,
         policy: &Policy,
     ) -> Result<()> {
-        let query = query("INSERT OR REPLACE INTO policy VALUES (?, ?, ?)")
-            .bind(resource.to_sql())
-            .bind(action.to_sql())
-            .bind(minicbor::to_vec(policy.expression())?.to_sql());
-        query.execute(&*self.database.pool).await.void()
+        self.set_policy_by(resource, action, policy, None).await
     }
 
     async fn delete_policy(&self, resource: &Resource, action: &Action) -> Result<()> {
-        let query = query("DELETE FROM policy WHERE resource = ? and action = ?")
-            .bind(resource.to_sql())
-            .bind(action.to_sql());
-        query.execute(&*self.database.pool).await.void()
+        self.delete_policy_by(resource, action, None).await
     }
 
     async fn get_policies_by_resource(&self, resource: &Resource) -> Result<Vec<(Action, Policy)>> {
@@ -69,6 +68,104 @@ impl PoliciesRepository for PolicySqlxDatabase {
             .map(|r| r.policy().map(|e| (r.action(), e)))
             .collect::<Result<Vec<(Action, Policy)>>>()
     }
+
+    async fn set_policy_by(
+        &self,
+        resource: &Resource,
+        action: &Action,
+        policy: &Policy,
+        changed_by: Option<&Identifier>,
+    ) -> Result<()> {
+        let expression = minicbor::to_vec(policy.expression())?;
+        let mut transaction = self.database.begin().await.into_core()?;
+        let query1 = query("INSERT OR REPLACE INTO policy VALUES (?, ?, ?)")
+            .bind(resource.to_sql())
+            .bind(action.to_sql())
+            .bind(expression.to_sql());
+        query1.execute(&mut *transaction).await.void()?;
+
+        Self::insert_version_query(resource, action, Some(expression), changed_by)?
+            .execute(&mut *transaction)
+            .await
+            .void()?;
+        transaction.commit().await.void()
+    }
+
+    async fn delete_policy_by(
+        &self,
+        resource: &Resource,
+        action: &Action,
+        changed_by: Option<&Identifier>,
+    ) -> Result<()> {
+        let mut transaction = self.database.begin().await.into_core()?;
+        let query1 = query("DELETE FROM policy WHERE resource = ? and action = ?")
+            .bind(resource.to_sql())
+            .bind(action.to_sql());
+        let deleted = query1.execute(&mut *transaction).await.into_core()?;
+
+        // the deletion of a policy which does not exist is not a new version of that policy
+        if deleted.rows_affected() > 0 {
+            Self::insert_version_query(resource, action, None, changed_by)?
+                .execute(&mut *transaction)
+                .await
+                .void()?;
+        }
+        transaction.commit().await.void()
+    }
+
+    async fn get_policy_versions(
+        &self,
+        resource: &Resource,
+        action: &Action,
+    ) -> Result<Vec<PolicyVersion>> {
+        let query = query_as(
+            "SELECT version, expression, changed_by, changed_at FROM policy_version WHERE resource = $1 and action = $2 ORDER BY version",
+        )
+        .bind(resource.to_sql())
+        .bind(action.to_sql());
+        let rows: Vec<PolicyVersionRow> =
+            query.fetch_all(&*self.database.pool).await.into_core()?;
+        rows.iter().map(|r| r.policy_version()).collect()
+    }
+
+    async fn get_policy_version(
+        &self,
+        resource: &Resource,
+        action: &Action,
+        version: u64,
+    ) -> Result<Option<PolicyVersion>> {
+        let query = query_as(
+            "SELECT version, expression, changed_by, changed_at FROM policy_version WHERE resource = $1 and action = $2 and version = $3",
+        )
+        .bind(resource.to_sql())
+        .bind(action.to_sql())
+        .bind(version.to_sql());
+        let row: Option<PolicyVersionRow> = query
+            .fetch_optional(&*self.database.pool)
+            .await
+            .into_core()?;
+        row.map(|r| r.policy_version()).transpose()
+    }
+}
+
+impl PolicySqlxDatabase {
+    /// Add a new version to the history of a policy, numbered after the latest version
+    fn insert_version_query<'a>(
+        resource: &Resource,
+        action: &Action,
+        expression: Option<Vec<u8>>,
+        changed_by: Option<&Identifier>,
+    ) -> Result<Query<'a, Sqlite, SqliteArguments<'a>>> {
+        Ok(query(
+            "INSERT INTO policy_version (resource, action, version, expression, changed_by, changed_at) \
+             SELECT ?1, ?2, COALESCE(MAX(version), 0) + 1, ?3, ?4, ?5 FROM policy_version WHERE resource = ?1 and action = ?2",
+        )
+        .bind(resource.to_sql())
+        .bind(action.to_sql())
+        .bind(expression.map(|e| e.to_sql()))
+        .bind(changed_by.map(|i| i.to_sql()))
+        .bind(now()?.to_sql()))
+    }
 }
 
 // Database serialization / deserialization
@@ -93,6 +190,35 @@ pub(crate) struct PolicyRow {
     expression: Vec<u8>,
 }
 
+/// Low-level representation of a row in the policy versions table
+#[derive(FromRow)]
+pub(crate) struct PolicyVersionRow {
+    version: i64,
+    expression: Option<Vec<u8>>,
+    changed_by: Option<String>,
+    changed_at: i64,
+}
+
+impl PolicyVersionRow {
+    pub(crate) fn policy_version(&self) -> Result<PolicyVersion> {
+        let expression = match &self.expression {
+            Some(e) => Some(minicbor::decode::<Expr>(e.as_slice())?),
+            None => None,
+        };
+        let changed_by = self
+            .changed_by
+            .as_ref()
+            .map(|i| Identifier::from_str(i))
+            .transpose()?;
+        Ok(PolicyVersion::new(
+            self.version as u64,
+            expression,
+            changed_by,
+            TimestampInSeconds(self.changed_at as u64),
+        ))
+    }
+}
+
 impl PolicyRow {
     #[allow(dead_code)]
     pub(crate) fn resource(&self) -> Resource {
@@ -150,6 +276,52 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_policy_versions() -> Result<()> {
+        let repository = create_repository().await?;
+        let r = Resource::from("outlet");
+        let a = Action::from("handle_message");
+        let identifier = Identifier::from_str("I124ed0b2e5a2be82e267ead6b3279f683616b66d")?;
+
+        // each change of a policy creates a new version
+        let p1 = Policy::new(eq([ident("subject.component"), str("web")]));
+        let p2 = Policy::new(eq([ident("subject.component"), str("db")]));
+        repository.set_policy(&r, &a, &p1).await?;
+        repository
+            .set_policy_by(&r, &a, &p2, Some(&identifier))
+            .await?;
+        repository
+            .delete_policy_by(&r, &a, Some(&identifier))
+            .await?;
+
+        // deleting a policy which does not exist anymore does not create a new version
+        repository.delete_policy(&r, &a).await?;
+
+        let versions = repository.get_policy_versions(&r, &a).await?;
+        assert_eq!(
+            versions.iter().map(|v| v.version()).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(versions[0].expression(), Some(p1.expression()));
+        assert_eq!(versions[0].changed_by(), None);
+        assert_eq!(versions[1].expression(), Some(p2.expression()));
+        assert_eq!(versions[1].changed_by(), Some(&identifier));
+        assert!(versions[2].is_deletion());
+
+        // a specific version can be retrieved
+        let version = repository.get_policy_version(&r, &a, 1).await?.unwrap();
+        assert_eq!(version.expression(), Some(p1.expression()));
+        assert!(repository.get_policy_version(&r, &a, 4).await?.is_none());
+
+        // the versions of different actions are numbered independently
+        let other = Action::from("create");
+        repository.set_policy(&r, &other, &p1).await?;
+        let versions = repository.get_policy_versions(&r, &other).await?;
+        assert_eq!(versions.len(), 1);
+        assert_eq!(versions[0].version(), 1);
+        Ok(())
+    }
+
     /// HELPERS
     async fn create_repository() -> Result<Arc<dyn PoliciesRepository>> {
         Ok(Arc::new(PolicySqlxDatabase::create().await?))
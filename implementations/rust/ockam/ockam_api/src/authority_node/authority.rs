@@ -5,7 +5,7 @@ use tracing::info;
 use ockam::identity::{
     CredentialsIssuer, Identifier, Identities, IdentityAttributesRepository,
     IdentityAttributesSqlxDatabase, SecureChannelListenerOptions, SecureChannels,
-    TrustEveryonePolicy,
+    TracingCredentialsAuditSink, TrustEveryonePolicy,
 };
 use ockam_abac::expr::{and, eq, ident, str};
 use ockam_abac::{AbacAccessControl, Env, Policy};
@@ -71,6 +71,7 @@ impl Authority {
 
         let identities = Identities::create(database.clone())
             .with_identity_attributes_repository(identity_attributes_repository)
+            .with_credentials_audit_sink(Arc::new(TracingCredentialsAuditSink))
             .build();
 
         let secure_channels = SecureChannels::builder()
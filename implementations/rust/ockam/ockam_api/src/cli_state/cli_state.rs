@@ -139,7 +139,8 @@ impl CliState {
         root_path.join("database.sqlite3")
     }
 
-    pub(super) fn make_node_dir_path(root_path: &Path, node_name: &str) -> PathBuf {
+    /// Return the directory of a node in the state directory `root_path`
+    pub fn make_node_dir_path(root_path: &Path, node_name: &str) -> PathBuf {
         Self::make_nodes_dir_path(root_path).join(node_name)
     }
 
@@ -1,5 +1,6 @@
 use ockam::identity::models::{ChangeHistory, CredentialAndPurposeKey};
-use ockam::identity::{AttributesEntry, Identifier, Identity};
+use ockam::identity::utils::{add_seconds, now};
+use ockam::identity::{AttributesEntry, Identifier, Identity, DEFAULT_CREDENTIAL_REFRESH_MARGIN};
 
 use crate::cli_state::{CliState, CliStateError};
 
@@ -67,6 +68,45 @@ impl CliState {
             .get_credentials()
             .await?)
     }
+
+    /// Return the credential cached for a given subject by a given issuer
+    /// if that credential is not about to expire
+    pub async fn get_cached_credential(
+        &self,
+        issuer: &Identifier,
+        subject: &Identifier,
+    ) -> Result<Option<CredentialAndPurposeKey>> {
+        match self
+            .cached_credentials_repository()
+            .await?
+            .get_credential(issuer, subject)
+            .await?
+        {
+            Some((credential, expires_at))
+                if add_seconds(&now()?, DEFAULT_CREDENTIAL_REFRESH_MARGIN.as_secs())
+                    < expires_at =>
+            {
+                Ok(Some(credential))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Cache a credential issued to a given subject so that it can be reused until it expires.
+    /// Cached credentials are kept apart from the credentials stored by name
+    pub async fn cache_credential(
+        &self,
+        issuer: &Identifier,
+        subject: &Identifier,
+        credential: CredentialAndPurposeKey,
+    ) -> Result<()> {
+        let credential_data = credential.get_credential_data()?;
+        Ok(self
+            .cached_credentials_repository()
+            .await?
+            .put_credential(issuer, subject, credential_data.expires_at, &credential)
+            .await?)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -155,6 +195,42 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_cached_credential() -> Result<()> {
+        let cli = CliState::test().await?;
+        let identities = identities().await?;
+        let issuer = identities.identities_creation().create_identity().await?;
+        let subject = identities.identities_creation().create_identity().await?;
+        let credential = identities
+            .credentials()
+            .credentials_creation()
+            .issue_credential(
+                &issuer,
+                &subject,
+                AttributesBuilder::with_schema(CredentialSchemaIdentifier(1)).build(),
+                Duration::from_secs(3600),
+            )
+            .await?;
+
+        // no credential is cached yet
+        let result = cli.get_cached_credential(&issuer, &subject).await?;
+        assert_eq!(result, None);
+
+        // the credential is returned for its issuer and subject
+        cli.cache_credential(&issuer, &subject, credential.clone())
+            .await?;
+        let result = cli.get_cached_credential(&issuer, &subject).await?;
+        assert_eq!(result, Some(credential));
+
+        // but not for another subject
+        let result = cli.get_cached_credential(&issuer, &issuer).await?;
+        assert_eq!(result, None);
+
+        // and it is not listed with the named credentials
+        assert!(cli.get_credentials().await?.is_empty());
+        Ok(())
+    }
+
     /// HELPERS
     async fn create_credential(
         identities: Arc<Identities>,
@@ -45,6 +45,13 @@ pub enum CliStateError {
     #[diagnostic(code("OCK500"))]
     InvalidOperation(String),
 
+    #[error("The vault {0} is encrypted and no passphrase was provided to unlock it")]
+    #[diagnostic(
+        code("OCK401"),
+        help("Please set the OCKAM_VAULT_PASSPHRASE environment variable or run the command in an interactive terminal")
+    )]
+    VaultLocked(String),
+
     #[error("Invalid configuration version '{0}'")]
     #[diagnostic(
         code("OCK500"),
@@ -36,7 +36,10 @@ impl CliState {
 
         let vault = self.get_named_vault(vault_name).await?;
         let identities = self.make_identities(vault.vault().await?).await?;
-        let identity = identities.identities_creation().create_identity().await?;
+        let identity = identities
+            .identities_creation()
+            .create_identity_with_key_type(vault.signing_key_type())
+            .await?;
 
         self.store_named_identity(&identity, name, &vault.name())
             .await
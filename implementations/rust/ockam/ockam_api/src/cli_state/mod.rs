@@ -6,12 +6,14 @@ pub use identities::*;
 pub use nodes::*;
 pub use policies::*;
 pub use projects::*;
+pub use relays::*;
 pub use secure_channels::*;
 pub use spaces::*;
 pub use storage::*;
 pub use test_support::*;
 pub use trust_contexts::*;
 pub use users::*;
+pub use vault_passphrases::*;
 pub use vaults::*;
 
 #[allow(clippy::module_inception)]
@@ -23,6 +25,7 @@ pub mod identities;
 pub mod nodes;
 pub mod policies;
 pub mod projects;
+pub mod relays;
 pub mod repositories;
 pub mod secure_channels;
 pub mod spaces;
@@ -30,4 +33,5 @@ pub mod storage;
 pub mod test_support;
 pub mod trust_contexts;
 pub mod users;
+pub mod vault_passphrases;
 pub mod vaults;
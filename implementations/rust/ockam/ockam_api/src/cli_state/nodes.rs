@@ -12,9 +12,11 @@ use ockam_multiaddr::MultiAddr;
 use ockam_transport_tcp::TcpListener;
 
 use crate::cli_state::{random_name, Result};
-use crate::cli_state::{CliState, CliStateError};
+use crate::cli_state::{CliState, CliStateError, RelayRegistrationsSqlxDatabase};
 use crate::cloud::project::Project;
 use crate::config::lookup::InternetAddress;
+#[cfg(unix)]
+use crate::nodes::service::control_api::ControlApiPaths;
 use crate::NamedVault;
 
 /// The methods below support the creation and update of local nodes
@@ -116,6 +118,7 @@ impl CliState {
     /// Remove a node:
     ///
     ///  - remove it from the repository
+    ///  - remove the definitions of its relays
     ///  - remove the node log files
     pub async fn remove_node(&self, node_name: &str) -> Result<()> {
         // don't try to remove a node on a non-existent database
@@ -127,6 +130,15 @@ impl CliState {
         let repository = self.nodes_repository().await?;
         let node_exists = repository.get_node(node_name).await.is_ok();
         repository.delete_node(node_name).await?;
+        // remove the relays created by the node
+        self.relays_repository()
+            .await?
+            .delete_relays(node_name)
+            .await?;
+        // remove the registrations received by the relay service of the node
+        RelayRegistrationsSqlxDatabase::new(self.database())
+            .delete_node_registrations(node_name)
+            .await?;
         // set another node as the default node
         if node_exists {
             let other_nodes = repository.get_nodes().await?;
@@ -151,6 +163,10 @@ impl CliState {
             .set_no_node_pid(node_name)
             .await?;
 
+        // the management API of a stopped node can't be used anymore
+        #[cfg(unix)]
+        ControlApiPaths::new(&self.node_dir(node_name)).remove();
+
         if let Some(pid) = node.pid() {
             // avoid killing the current process, return successfully instead.
             // this is useful when we need to stop all the nodes, for example
@@ -1,6 +1,7 @@
-use crate::cli_state::CliState;
 use crate::cli_state::Result;
-use ockam_abac::{Action, Env, Policy, PolicyAccessControl, Resource};
+use crate::cli_state::{CliState, CliStateError};
+use ockam::identity::Identifier;
+use ockam_abac::{Action, Env, Policy, PolicyAccessControl, PolicyVersion, Resource};
 
 impl CliState {
     pub async fn get_policy(&self, resource: &Resource, action: &Action) -> Result<Option<Policy>> {
@@ -32,6 +33,95 @@ impl CliState {
             .await?)
     }
 
+    /// Set a policy, recording the identity which made the change in the history of the policy
+    pub async fn set_policy_by(
+        &self,
+        resource: &Resource,
+        action: &Action,
+        policy: &Policy,
+        changed_by: Option<&Identifier>,
+    ) -> Result<()> {
+        Ok(self
+            .policies_repository()
+            .await?
+            .set_policy_by(resource, action, policy, changed_by)
+            .await?)
+    }
+
+    /// Delete a policy, recording the identity which made the change in the history of the policy
+    pub async fn delete_policy_by(
+        &self,
+        resource: &Resource,
+        action: &Action,
+        changed_by: Option<&Identifier>,
+    ) -> Result<()> {
+        Ok(self
+            .policies_repository()
+            .await?
+            .delete_policy_by(resource, action, changed_by)
+            .await?)
+    }
+
+    /// Return all the versions of a policy, from the oldest to the most recent one
+    pub async fn get_policy_versions(
+        &self,
+        resource: &Resource,
+        action: &Action,
+    ) -> Result<Vec<PolicyVersion>> {
+        Ok(self
+            .policies_repository()
+            .await?
+            .get_policy_versions(resource, action)
+            .await?)
+    }
+
+    /// Restore a previous version of a policy. If the policy was deleted in that version
+    /// the current policy is deleted.
+    ///
+    /// The rollback is recorded as a new version of the policy, which is returned
+    pub async fn rollback_policy(
+        &self,
+        resource: &Resource,
+        action: &Action,
+        version: u64,
+        changed_by: Option<&Identifier>,
+    ) -> Result<PolicyVersion> {
+        let repository = self.policies_repository().await?;
+        let previous = repository
+            .get_policy_version(resource, action, version)
+            .await?
+            .ok_or_else(|| CliStateError::ResourceNotFound {
+                resource: "policy version".into(),
+                name: format!("{resource}/{action} v{version}"),
+            })?;
+        match previous.expression() {
+            Some(expression) => {
+                let policy = Policy::new(expression.clone());
+                repository
+                    .set_policy_by(resource, action, &policy, changed_by)
+                    .await?
+            }
+            None => {
+                if repository.get_policy(resource, action).await?.is_none() {
+                    return Err(CliStateError::InvalidOperation(format!(
+                        "the policy {resource}/{action} is already deleted"
+                    )));
+                }
+                repository
+                    .delete_policy_by(resource, action, changed_by)
+                    .await?
+            }
+        }
+        repository
+            .get_policy_versions(resource, action)
+            .await?
+            .pop()
+            .ok_or_else(|| CliStateError::ResourceNotFound {
+                resource: "policy version".into(),
+                name: format!("{resource}/{action}"),
+            })
+    }
+
     pub async fn get_policies_by_resource(
         &self,
         resource: &Resource,
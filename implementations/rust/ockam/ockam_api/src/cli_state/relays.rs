@@ -0,0 +1,115 @@
+use ockam::identity::Identifier;
+use ockam::RelayRegistrationsRepository;
+use ockam_core::compat::sync::Arc;
+use ockam_multiaddr::MultiAddr;
+
+use crate::cli_state::CliState;
+use crate::cli_state::Result;
+
+/// The methods below support the persistence of relay definitions so that
+/// a node can recreate its relays when it is restarted
+impl CliState {
+    /// Store the definition of a relay created by a node
+    pub async fn store_relay(&self, relay: &NamedRelay) -> Result<()> {
+        Ok(self.relays_repository().await?.store_relay(relay).await?)
+    }
+
+    /// Return the definition of a relay given its node and its remote address
+    pub async fn get_relay(
+        &self,
+        node_name: &str,
+        remote_address: &str,
+    ) -> Result<Option<NamedRelay>> {
+        Ok(self
+            .relays_repository()
+            .await?
+            .get_relay(node_name, remote_address)
+            .await?)
+    }
+
+    /// Return the definitions of all the relays created by a node
+    pub async fn get_node_relays(&self, node_name: &str) -> Result<Vec<NamedRelay>> {
+        Ok(self
+            .relays_repository()
+            .await?
+            .get_relays(node_name)
+            .await?)
+    }
+
+    /// Delete the definition of a relay so that it is not recreated when its node restarts
+    pub async fn delete_relay(&self, node_name: &str, remote_address: &str) -> Result<()> {
+        Ok(self
+            .relays_repository()
+            .await?
+            .delete_relay(node_name, remote_address)
+            .await?)
+    }
+
+    /// Return the repository used by the relay service of the current node to persist
+    /// its registrations, so that its relays are restored when the node restarts
+    pub async fn relay_service_registrations(
+        &self,
+    ) -> Result<Arc<dyn RelayRegistrationsRepository>> {
+        self.relay_registrations_repository().await
+    }
+}
+
+/// Definition of a relay created by a node.
+/// It contains all the parameters necessary to recreate the relay
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamedRelay {
+    node_name: String,
+    alias: String,
+    remote_address: String,
+    address: MultiAddr,
+    at_rust_node: bool,
+    authorized: Option<Identifier>,
+}
+
+impl NamedRelay {
+    pub fn new(
+        node_name: &str,
+        alias: &str,
+        remote_address: &str,
+        address: MultiAddr,
+        at_rust_node: bool,
+        authorized: Option<Identifier>,
+    ) -> Self {
+        Self {
+            node_name: node_name.to_string(),
+            alias: alias.to_string(),
+            remote_address: remote_address.to_string(),
+            address,
+            at_rust_node,
+            authorized,
+        }
+    }
+
+    /// Name of the node which created the relay
+    pub fn node_name(&self) -> String {
+        self.node_name.clone()
+    }
+
+    /// Alias used to register the relay
+    pub fn alias(&self) -> String {
+        self.alias.clone()
+    }
+
+    /// Address of the relay on the remote node
+    pub fn remote_address(&self) -> String {
+        self.remote_address.clone()
+    }
+
+    /// Route to the node where the relay is created
+    pub fn address(&self) -> MultiAddr {
+        self.address.clone()
+    }
+
+    pub fn at_rust_node(&self) -> bool {
+        self.at_rust_node
+    }
+
+    pub fn authorized(&self) -> Option<Identifier> {
+        self.authorized.clone()
+    }
+}
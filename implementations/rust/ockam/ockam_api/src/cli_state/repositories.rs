@@ -1,8 +1,9 @@
 use ockam::identity::storage::{PurposeKeysRepository, PurposeKeysSqlxDatabase};
 use ockam::identity::{
-    ChangeHistoryRepository, ChangeHistorySqlxDatabase, IdentityAttributesRepository,
-    IdentityAttributesSqlxDatabase,
+    CachedCredentialsRepository, CachedCredentialsSqlxDatabase, ChangeHistoryRepository,
+    ChangeHistorySqlxDatabase, IdentityAttributesRepository, IdentityAttributesSqlxDatabase,
 };
+use ockam::RelayRegistrationsRepository;
 use ockam_abac::{PoliciesRepository, PolicySqlxDatabase};
 use ockam_core::compat::sync::Arc;
 use ockam_vault::storage::{SecretsRepository, SecretsSqlxDatabase};
@@ -65,6 +66,18 @@ impl CliState {
         Ok(Arc::new(ProjectsSqlxDatabase::new(self.database())))
     }
 
+    pub(super) async fn relays_repository(&self) -> Result<Arc<dyn RelaysRepository>> {
+        Ok(Arc::new(RelaysSqlxDatabase::new(self.database())))
+    }
+
+    pub(super) async fn relay_registrations_repository(
+        &self,
+    ) -> Result<Arc<dyn RelayRegistrationsRepository>> {
+        Ok(Arc::new(RelayRegistrationsSqlxDatabase::new(
+            self.database(),
+        )))
+    }
+
     pub(super) async fn spaces_repository(&self) -> Result<Arc<dyn SpacesRepository>> {
         Ok(Arc::new(SpacesSqlxDatabase::new(self.database())))
     }
@@ -77,6 +90,14 @@ impl CliState {
         Ok(Arc::new(CredentialsSqlxDatabase::new(self.database())))
     }
 
+    pub(super) async fn cached_credentials_repository(
+        &self,
+    ) -> Result<Arc<dyn CachedCredentialsRepository>> {
+        Ok(Arc::new(CachedCredentialsSqlxDatabase::new(
+            self.database(),
+        )))
+    }
+
     pub(super) async fn trust_contexts_repository(
         &self,
     ) -> Result<Arc<dyn TrustContextsRepository>> {
@@ -1,6 +1,8 @@
 use std::sync::Arc;
 
-use ockam::identity::{Identities, IdentityAttributesRepository, SecureChannels};
+use ockam::identity::{
+    Identities, IdentityAttributesRepository, SecureChannels, TracingCredentialsAuditSink,
+};
 
 use crate::bootstrapped_identities_store::{
     BootstrapedIdentityAttributesStore, PreTrustedIdentities,
@@ -36,6 +38,7 @@ impl CliState {
         let identities = Identities::create(self.database())
             .with_identity_attributes_repository(identity_attributes_repository)
             .with_vault(vault)
+            .with_credentials_audit_sink(Arc::new(TracingCredentialsAuditSink))
             .build();
         Ok(SecureChannels::builder()
             .await?
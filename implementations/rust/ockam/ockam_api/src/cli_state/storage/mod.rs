@@ -8,6 +8,9 @@ pub use nodes_repository::*;
 pub use nodes_repository_sql::*;
 pub use projects_repository::*;
 pub use projects_repository_sql::*;
+pub use relay_registrations_repository_sql::*;
+pub use relays_repository::*;
+pub use relays_repository_sql::*;
 pub use spaces_repository::*;
 pub use spaces_repository_sql::*;
 pub use trust_contexts_repository::*;
@@ -27,6 +30,9 @@ mod nodes_repository;
 mod nodes_repository_sql;
 mod projects_repository;
 mod projects_repository_sql;
+mod relay_registrations_repository_sql;
+mod relays_repository;
+mod relays_repository_sql;
 mod spaces_repository;
 mod spaces_repository_sql;
 mod trust_contexts_repository;
@@ -0,0 +1,182 @@
+use std::str::FromStr;
+
+use sqlx::*;
+
+use ockam::identity::Identifier;
+use ockam::{
+    AliasConflictPolicy, FromSqlxError, RelayRegistration, RelayRegistrationsRepository,
+    SqlxDatabase, ToSqlxType, ToVoid,
+};
+use ockam_core::async_trait;
+use ockam_core::Result;
+
+/// Implementation of the `RelayRegistrationsRepository` trait, storing the registrations
+/// received by the relay service of the current node
+#[derive(Clone)]
+pub struct RelayRegistrationsSqlxDatabase {
+    database: SqlxDatabase,
+}
+
+impl RelayRegistrationsSqlxDatabase {
+    /// Create a new database
+    pub fn new(database: SqlxDatabase) -> Self {
+        debug!("create a repository for relay registrations");
+        Self { database }
+    }
+
+    /// Create a new in-memory database, passing the name of the node hosting the relay service
+    pub async fn create_with_node_name(node_name: &str) -> Result<Self> {
+        let mut database = SqlxDatabase::in_memory("relay registrations").await?;
+        database.node_name = Some(node_name.to_string());
+        Ok(Self::new(database))
+    }
+
+    /// Delete all the registrations received by a node
+    pub async fn delete_node_registrations(&self, node_name: &str) -> Result<()> {
+        let query =
+            query("DELETE FROM relay_registration WHERE node_name = ?").bind(node_name.to_sql());
+        query.execute(&*self.database.pool).await.void()
+    }
+}
+
+#[async_trait]
+impl RelayRegistrationsRepository for RelayRegistrationsSqlxDatabase {
+    async fn store_registrations(
+        &self,
+        alias: &str,
+        registrations: &[RelayRegistration],
+    ) -> Result<()> {
+        let node_name = self.database.node_name()?;
+        let mut transaction = self.database.begin().await.into_core()?;
+
+        let query = query("DELETE FROM relay_registration WHERE node_name = ? AND alias = ?")
+            .bind(node_name.to_sql())
+            .bind(alias.to_sql());
+        query.execute(&mut *transaction).await.void()?;
+
+        for registration in registrations {
+            let query = query("INSERT INTO relay_registration VALUES (?1, ?2, ?3, ?4, ?5)")
+                .bind(node_name.to_sql())
+                .bind(alias.to_sql())
+                .bind(minicbor::to_vec(&registration.route)?.to_sql())
+                .bind(registration.identifier.as_ref().map(|i| i.to_sql()))
+                .bind(registration.policy.to_string().to_sql());
+            query.execute(&mut *transaction).await.void()?;
+        }
+
+        transaction.commit().await.void()
+    }
+
+    async fn delete_registrations(&self, alias: &str) -> Result<()> {
+        let query = query("DELETE FROM relay_registration WHERE node_name = ? AND alias = ?")
+            .bind(self.database.node_name()?.to_sql())
+            .bind(alias.to_sql());
+        query.execute(&*self.database.pool).await.void()
+    }
+
+    async fn get_registrations(&self) -> Result<Vec<RelayRegistration>> {
+        let query = query_as("SELECT alias, route, identifier, policy FROM relay_registration WHERE node_name = ? ORDER BY rowid")
+            .bind(self.database.node_name()?.to_sql());
+        let rows: Vec<RelayRegistrationRow> =
+            query.fetch_all(&*self.database.pool).await.into_core()?;
+        rows.iter().map(|r| r.registration()).collect()
+    }
+}
+
+// Database serialization / deserialization
+
+#[derive(FromRow)]
+struct RelayRegistrationRow {
+    alias: String,
+    route: Vec<u8>,
+    identifier: Option<String>,
+    policy: String,
+}
+
+impl RelayRegistrationRow {
+    fn registration(&self) -> Result<RelayRegistration> {
+        let identifier = self
+            .identifier
+            .as_ref()
+            .map(|i| Identifier::from_str(i))
+            .transpose()?;
+        Ok(RelayRegistration {
+            alias: self.alias.clone(),
+            route: minicbor::decode(self.route.as_slice())?,
+            identifier,
+            policy: AliasConflictPolicy::from_str(&self.policy)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use ockam_core::route;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_repository() -> Result<()> {
+        let repository = create_repository().await?;
+
+        // store the registrations of 2 relays
+        let registration1 = RelayRegistration {
+            alias: "relay1".into(),
+            route: route!["tcp1", "remote_relay"],
+            identifier: None,
+            policy: AliasConflictPolicy::FailOver,
+        };
+        let registration2 = RelayRegistration {
+            alias: "relay1".into(),
+            route: route!["tcp2", "remote_relay"],
+            identifier: Some(Identifier::from_str(
+                "I124ed0b2e5a2be82e267ead6b3279f683616b66d",
+            )?),
+            policy: AliasConflictPolicy::FailOver,
+        };
+        let registration3 = RelayRegistration {
+            alias: "relay2".into(),
+            route: route!["tcp3", "remote_relay"],
+            identifier: None,
+            policy: AliasConflictPolicy::Replace,
+        };
+        repository
+            .store_registrations("relay1", &[registration1.clone(), registration2.clone()])
+            .await?;
+        repository
+            .store_registrations("relay2", &[registration3.clone()])
+            .await?;
+
+        let result = repository.get_registrations().await?;
+        assert_eq!(
+            result,
+            vec![
+                registration1.clone(),
+                registration2.clone(),
+                registration3.clone()
+            ]
+        );
+
+        // the registrations of a relay are replaced
+        repository
+            .store_registrations("relay1", &[registration2.clone()])
+            .await?;
+        let result = repository.get_registrations().await?;
+        assert_eq!(result, vec![registration3.clone(), registration2]);
+
+        // the registrations of a relay can be deleted
+        repository.delete_registrations("relay1").await?;
+        let result = repository.get_registrations().await?;
+        assert_eq!(result, vec![registration3]);
+        Ok(())
+    }
+
+    /// HELPERS
+    async fn create_repository() -> Result<Arc<dyn RelayRegistrationsRepository>> {
+        Ok(Arc::new(
+            RelayRegistrationsSqlxDatabase::create_with_node_name("node").await?,
+        ))
+    }
+}
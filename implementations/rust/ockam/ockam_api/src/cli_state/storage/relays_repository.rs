@@ -0,0 +1,28 @@
+use ockam_core::async_trait;
+use ockam_core::Result;
+
+use crate::cli_state::NamedRelay;
+
+/// This trait supports the storage of relay definitions:
+///
+///  - a relay is created by a given node
+///  - a relay is uniquely identified by its alias for a given node
+///  - the relay definition is used to recreate the relay when the node restarts
+///
+#[async_trait]
+pub trait RelaysRepository: Send + Sync + 'static {
+    /// Store or update the definition of a relay
+    async fn store_relay(&self, relay: &NamedRelay) -> Result<()>;
+
+    /// Get a relay given the name of its node and its remote address
+    async fn get_relay(&self, node_name: &str, remote_address: &str) -> Result<Option<NamedRelay>>;
+
+    /// Get all the relays created by a node
+    async fn get_relays(&self, node_name: &str) -> Result<Vec<NamedRelay>>;
+
+    /// Delete a relay given the name of its node and its remote address
+    async fn delete_relay(&self, node_name: &str, remote_address: &str) -> Result<()>;
+
+    /// Delete all the relays created by a node
+    async fn delete_relays(&self, node_name: &str) -> Result<()>;
+}
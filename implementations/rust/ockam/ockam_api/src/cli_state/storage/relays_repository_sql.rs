@@ -0,0 +1,189 @@
+use std::str::FromStr;
+
+use sqlx::*;
+
+use ockam::identity::Identifier;
+use ockam::{FromSqlxError, SqlxDatabase, ToSqlxType, ToVoid};
+use ockam_core::async_trait;
+use ockam_core::env::FromString;
+use ockam_core::Result;
+use ockam_multiaddr::MultiAddr;
+
+use crate::cli_state::{NamedRelay, RelaysRepository};
+
+#[derive(Clone)]
+pub struct RelaysSqlxDatabase {
+    database: SqlxDatabase,
+}
+
+impl RelaysSqlxDatabase {
+    /// Create a new database
+    pub fn new(database: SqlxDatabase) -> Self {
+        debug!("create a repository for relays");
+        Self { database }
+    }
+
+    /// Create a new in-memory database
+    pub async fn create() -> Result<Self> {
+        Ok(Self::new(SqlxDatabase::in_memory("relays").await?))
+    }
+}
+
+#[async_trait]
+impl RelaysRepository for RelaysSqlxDatabase {
+    async fn store_relay(&self, relay: &NamedRelay) -> Result<()> {
+        let query = query("INSERT OR REPLACE INTO relay VALUES (?1, ?2, ?3, ?4, ?5, ?6)")
+            .bind(relay.node_name().to_sql())
+            .bind(relay.alias().to_sql())
+            .bind(relay.remote_address().to_sql())
+            .bind(relay.address().to_string().to_sql())
+            .bind(relay.at_rust_node().to_sql())
+            .bind(relay.authorized().as_ref().map(|a| a.to_sql()));
+        query.execute(&*self.database.pool).await.void()
+    }
+
+    async fn get_relay(&self, node_name: &str, remote_address: &str) -> Result<Option<NamedRelay>> {
+        let query = query_as("SELECT node_name, alias, remote_address, address, at_rust_node, authorized FROM relay WHERE node_name = ? AND remote_address = ?")
+            .bind(node_name.to_sql())
+            .bind(remote_address.to_sql());
+        let row: Option<RelayRow> = query
+            .fetch_optional(&*self.database.pool)
+            .await
+            .into_core()?;
+        row.map(|r| r.named_relay()).transpose()
+    }
+
+    async fn get_relays(&self, node_name: &str) -> Result<Vec<NamedRelay>> {
+        let query = query_as("SELECT node_name, alias, remote_address, address, at_rust_node, authorized FROM relay WHERE node_name = ?")
+            .bind(node_name.to_sql());
+        let rows: Vec<RelayRow> = query.fetch_all(&*self.database.pool).await.into_core()?;
+        rows.iter().map(|r| r.named_relay()).collect()
+    }
+
+    async fn delete_relay(&self, node_name: &str, remote_address: &str) -> Result<()> {
+        let query = query("DELETE FROM relay WHERE node_name = ? AND remote_address = ?")
+            .bind(node_name.to_sql())
+            .bind(remote_address.to_sql());
+        query.execute(&*self.database.pool).await.void()
+    }
+
+    async fn delete_relays(&self, node_name: &str) -> Result<()> {
+        let query = query("DELETE FROM relay WHERE node_name = ?").bind(node_name.to_sql());
+        query.execute(&*self.database.pool).await.void()
+    }
+}
+
+// Database serialization / deserialization
+
+#[derive(FromRow)]
+struct RelayRow {
+    node_name: String,
+    alias: String,
+    remote_address: String,
+    address: String,
+    at_rust_node: bool,
+    authorized: Option<String>,
+}
+
+impl RelayRow {
+    fn named_relay(&self) -> Result<NamedRelay> {
+        let authorized = self
+            .authorized
+            .as_ref()
+            .map(|a| Identifier::from_str(a))
+            .transpose()?;
+        Ok(NamedRelay::new(
+            &self.node_name,
+            &self.alias,
+            &self.remote_address,
+            MultiAddr::from_string(&self.address)?,
+            self.at_rust_node,
+            authorized,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_repository() -> Result<()> {
+        let repository = create_repository().await?;
+
+        // store some relays for 2 nodes
+        let relay1 = NamedRelay::new(
+            "node1",
+            "relay1",
+            "forward_to_relay1",
+            MultiAddr::from_string("/project/default")?,
+            false,
+            None,
+        );
+        let relay2 = NamedRelay::new(
+            "node1",
+            "relay2",
+            "forward_to_relay2",
+            MultiAddr::from_string("/node/node2")?,
+            true,
+            Some(Identifier::from_str(
+                "I124ed0b2e5a2be82e267ead6b3279f683616b66d",
+            )?),
+        );
+        let relay3 = NamedRelay::new(
+            "node2",
+            "relay1",
+            "forward_to_relay1",
+            MultiAddr::from_string("/project/default")?,
+            false,
+            None,
+        );
+        repository.store_relay(&relay1).await?;
+        repository.store_relay(&relay2).await?;
+        repository.store_relay(&relay3).await?;
+
+        // get the relays of a node
+        let result = repository.get_relays("node1").await?;
+        assert_eq!(result, vec![relay1.clone(), relay2.clone()]);
+
+        // get a relay by remote address
+        let result = repository.get_relay("node1", "forward_to_relay2").await?;
+        assert_eq!(result, Some(relay2.clone()));
+
+        // a relay with the same alias replaces the previous definition
+        let updated = NamedRelay::new(
+            "node1",
+            "relay1",
+            "forward_to_relay1_updated",
+            MultiAddr::from_string("/project/default")?,
+            false,
+            None,
+        );
+        repository.store_relay(&updated).await?;
+        let result = repository.get_relays("node1").await?;
+        assert_eq!(result.len(), 2);
+        assert!(result.contains(&updated));
+
+        // a relay can be deleted
+        repository
+            .delete_relay("node1", "forward_to_relay2")
+            .await?;
+        let result = repository.get_relays("node1").await?;
+        assert_eq!(result, vec![updated]);
+
+        // all the relays of a node can be deleted
+        repository.delete_relays("node1").await?;
+        let result = repository.get_relays("node1").await?;
+        assert!(result.is_empty());
+        let result = repository.get_relays("node2").await?;
+        assert_eq!(result, vec![relay3]);
+        Ok(())
+    }
+
+    /// HELPERS
+    async fn create_repository() -> Result<Arc<dyn RelaysRepository>> {
+        Ok(Arc::new(RelaysSqlxDatabase::create().await?))
+    }
+}
@@ -12,6 +12,26 @@ pub trait VaultsRepository: Send + Sync + 'static {
     /// Store a new vault path with an associated name
     async fn store_vault(&self, name: &str, path: &Path, is_kms: bool) -> Result<NamedVault>;
 
+    /// Store a new vault path with an associated name, for a vault storing its signing keys
+    /// in a Google Cloud KMS key ring
+    async fn store_gcp_kms_vault(
+        &self,
+        name: &str,
+        path: &Path,
+        key_ring: &str,
+    ) -> Result<NamedVault>;
+
+    /// Store a new vault path with an associated name, for a vault storing its signing keys
+    /// in the secure hardware of the platform, or in the vault database if there is none.
+    /// If `fallback` is true, the keys are also stored in the vault database when the
+    /// secure hardware is present but can't be used
+    async fn store_platform_vault(
+        &self,
+        name: &str,
+        path: &Path,
+        fallback: bool,
+    ) -> Result<NamedVault>;
+
     /// Update a vault path
     async fn update_vault(&self, name: &str, path: &Path) -> Result<()>;
 
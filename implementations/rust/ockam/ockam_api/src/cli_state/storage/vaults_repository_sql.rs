@@ -28,14 +28,49 @@ impl VaultsSqlxDatabase {
 #[async_trait]
 impl VaultsRepository for VaultsSqlxDatabase {
     async fn store_vault(&self, name: &str, path: &Path, is_kms: bool) -> Result<NamedVault> {
-        let query = query("INSERT INTO vault VALUES (?1, ?2, ?3, ?4)")
+        let query =
+            query("INSERT INTO vault (name, path, is_default, is_kms) VALUES (?1, ?2, ?3, ?4)")
+                .bind(name.to_sql())
+                .bind(path.to_sql())
+                .bind(true.to_sql())
+                .bind(is_kms.to_sql());
+        query.execute(&*self.database.pool).await.void()?;
+
+        Ok(NamedVault::new(name, path.into(), is_kms))
+    }
+
+    async fn store_gcp_kms_vault(
+        &self,
+        name: &str,
+        path: &Path,
+        key_ring: &str,
+    ) -> Result<NamedVault> {
+        let query = query("INSERT INTO vault (name, path, is_default, is_kms, gcp_kms_key_ring) VALUES (?1, ?2, ?3, ?4, ?5)")
             .bind(name.to_sql())
             .bind(path.to_sql())
             .bind(true.to_sql())
-            .bind(is_kms.to_sql());
+            .bind(true.to_sql())
+            .bind(key_ring.to_sql());
         query.execute(&*self.database.pool).await.void()?;
 
-        Ok(NamedVault::new(name, path.into(), is_kms))
+        Ok(NamedVault::new_gcp_kms(name, path.into(), key_ring))
+    }
+
+    async fn store_platform_vault(
+        &self,
+        name: &str,
+        path: &Path,
+        fallback: bool,
+    ) -> Result<NamedVault> {
+        let query = query("INSERT INTO vault (name, path, is_default, is_kms, platform_fallback) VALUES (?1, ?2, ?3, ?4, ?5)")
+            .bind(name.to_sql())
+            .bind(path.to_sql())
+            .bind(true.to_sql())
+            .bind(false.to_sql())
+            .bind(fallback.to_sql());
+        query.execute(&*self.database.pool).await.void()?;
+
+        Ok(NamedVault::new_platform(name, path.into(), fallback))
     }
 
     async fn update_vault(&self, name: &str, path: &Path) -> Result<()> {
@@ -53,7 +88,8 @@ impl VaultsRepository for VaultsSqlxDatabase {
 
     async fn get_named_vault(&self, name: &str) -> Result<Option<NamedVault>> {
         let query =
-            query_as("SELECT name, path, is_kms FROM vault WHERE name = $1").bind(name.to_sql());
+            query_as("SELECT name, path, is_kms, gcp_kms_key_ring, platform_fallback FROM vault WHERE name = $1")
+                .bind(name.to_sql());
         let row: Option<VaultRow> = query
             .fetch_optional(&*self.database.pool)
             .await
@@ -63,7 +99,8 @@ impl VaultsRepository for VaultsSqlxDatabase {
 
     async fn get_named_vault_with_path(&self, path: &Path) -> Result<Option<NamedVault>> {
         let query =
-            query_as("SELECT name, path, is_kms FROM vault WHERE path = $1").bind(path.to_sql());
+            query_as("SELECT name, path, is_kms, gcp_kms_key_ring, platform_fallback FROM vault WHERE path = $1")
+                .bind(path.to_sql());
         let row: Option<VaultRow> = query
             .fetch_optional(&*self.database.pool)
             .await
@@ -72,7 +109,8 @@ impl VaultsRepository for VaultsSqlxDatabase {
     }
 
     async fn get_named_vaults(&self) -> Result<Vec<NamedVault>> {
-        let query = query_as("SELECT name, path, is_kms FROM vault");
+        let query =
+            query_as("SELECT name, path, is_kms, gcp_kms_key_ring, platform_fallback FROM vault");
         let rows: Vec<VaultRow> = query.fetch_all(&*self.database.pool).await.into_core()?;
         rows.iter().map(|r| r.named_vault()).collect()
     }
@@ -85,15 +123,18 @@ pub(crate) struct VaultRow {
     name: String,
     path: String,
     is_kms: bool,
+    gcp_kms_key_ring: Option<String>,
+    platform_fallback: Option<bool>,
 }
 
 impl VaultRow {
     pub(crate) fn named_vault(&self) -> Result<NamedVault> {
-        Ok(NamedVault::new(
-            &self.name,
-            PathBuf::from_str(self.path.as_str()).unwrap(),
-            self.is_kms,
-        ))
+        let path = PathBuf::from_str(self.path.as_str()).unwrap();
+        Ok(match (&self.gcp_kms_key_ring, self.platform_fallback) {
+            (Some(key_ring), _) => NamedVault::new_gcp_kms(&self.name, path, key_ring),
+            (None, Some(fallback)) => NamedVault::new_platform(&self.name, path, fallback),
+            (None, None) => NamedVault::new(&self.name, path, self.is_kms),
+        })
     }
 }
 
@@ -156,6 +197,37 @@ mod test {
             .await?;
         let expected = NamedVault::new("kms", Path::new("path").into(), true);
         assert_eq!(kms, expected);
+
+        // A Google Cloud KMS vault is stored with its key ring
+        let key_ring = "projects/p/locations/l/keyRings/k";
+        let gcp_kms = repository
+            .store_gcp_kms_vault("gcp-kms", Path::new("path2"), key_ring)
+            .await?;
+        assert_eq!(gcp_kms.gcp_kms_key_ring(), Some(key_ring.to_string()));
+        assert!(gcp_kms.is_kms());
+        let result = repository.get_named_vault("gcp-kms").await?;
+        assert_eq!(result, Some(gcp_kms));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_store_platform_vault() -> Result<()> {
+        let repository = create_repository().await?;
+
+        // A platform vault is stored with its fallback policy
+        let platform = repository
+            .store_platform_vault("platform", Path::new("path"), false)
+            .await?;
+        assert!(platform.is_platform());
+        assert!(!platform.is_kms());
+        let result = repository.get_named_vault("platform").await?;
+        assert_eq!(result, Some(platform));
+
+        let with_fallback = repository
+            .store_platform_vault("platform-fallback", Path::new("path2"), true)
+            .await?;
+        let result = repository.get_named_vault("platform-fallback").await?;
+        assert_eq!(result, Some(with_fallback));
         Ok(())
     }
 
@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+use std::io::BufRead;
+use std::sync::{Mutex, OnceLock};
+
+use ockam_core::env::get_env;
+use rand::RngCore;
+use tracing::debug;
+
+use crate::cli_state::{CliStateError, Result};
+
+/// Environment variable which can be used to provide the passphrase of encrypted vaults
+pub const OCKAM_VAULT_PASSPHRASE: &str = "OCKAM_VAULT_PASSPHRASE";
+
+/// Name of the service used to store vault passphrases in the OS keychain
+const KEYCHAIN_SERVICE: &str = "ockam-vault";
+
+/// Function used to interactively ask a user for the passphrase of a vault, given its name
+pub type VaultPassphrasePrompt = Box<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+static PASSPHRASE_PROMPT: OnceLock<VaultPassphrasePrompt> = OnceLock::new();
+
+/// Passphrases of the vaults which have already been unlocked by this process
+static UNLOCKED_VAULTS: Mutex<BTreeMap<String, String>> = Mutex::new(BTreeMap::new());
+
+/// Passphrase read once from the OCKAM_VAULT_PASSPHRASE environment variable.
+/// The variable is not inherited by the spawned nodes, which get the passphrase on their stdin
+static ENV_PASSPHRASE: OnceLock<Option<String>> = OnceLock::new();
+
+/// Set the function used to ask a user for the passphrase of a vault when that passphrase
+/// is neither provided by the environment, nor stored in the OS keychain
+pub fn set_vault_passphrase_prompt(prompt: VaultPassphrasePrompt) {
+    let _ = PASSPHRASE_PROMPT.set(prompt);
+}
+
+/// Return the passphrase of an encrypted vault, looking in order at:
+///
+///  - the passphrases of the vaults already unlocked by this process
+///  - the OCKAM_VAULT_PASSPHRASE environment variable
+///  - the OS keychain
+///  - the user, if a prompt has been set
+///
+pub(crate) fn get_vault_passphrase(vault_name: &str) -> Result<String> {
+    if let Some(passphrase) = UNLOCKED_VAULTS.lock().unwrap().get(vault_name) {
+        return Ok(passphrase.clone());
+    }
+    if let Some(passphrase) = get_env_passphrase() {
+        return Ok(passphrase);
+    }
+    if let Some(passphrase) = get_keychain_passphrase(vault_name) {
+        return Ok(passphrase);
+    }
+    PASSPHRASE_PROMPT
+        .get()
+        .and_then(|prompt| prompt(vault_name))
+        .ok_or_else(|| CliStateError::VaultLocked(vault_name.to_string()))
+}
+
+/// Remember the passphrase of a vault which has been successfully unlocked
+pub(crate) fn set_vault_passphrase(vault_name: &str, passphrase: &str) {
+    UNLOCKED_VAULTS
+        .lock()
+        .unwrap()
+        .insert(vault_name.to_string(), passphrase.to_string());
+}
+
+/// Read the passphrase of a vault from the first line of the given input, for example the
+/// stdin of a node process started by the command line, and unlock the vault with it
+pub fn read_vault_passphrase(vault_name: &str, mut input: impl BufRead) -> Result<()> {
+    let mut line = String::new();
+    input.read_line(&mut line).map_err(|e| {
+        CliStateError::InvalidOperation(format!(
+            "the passphrase of the vault {vault_name} can't be read: {e}"
+        ))
+    })?;
+    let passphrase = line.trim_end_matches(['\r', '\n']);
+    if passphrase.is_empty() {
+        return Err(CliStateError::VaultLocked(vault_name.to_string()));
+    }
+    set_vault_passphrase(vault_name, passphrase);
+    Ok(())
+}
+
+/// Return the passphrase set with the OCKAM_VAULT_PASSPHRASE environment variable.
+/// The variable is read only once. The environment of the process is not modified since
+/// other threads might read it concurrently
+fn get_env_passphrase() -> Option<String> {
+    ENV_PASSPHRASE
+        .get_or_init(|| get_env::<String>(OCKAM_VAULT_PASSPHRASE).ok().flatten())
+        .clone()
+}
+
+/// Create a random passphrase, suitable to be stored in the OS keychain
+pub(crate) fn random_passphrase() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Return the passphrase of a vault if it is stored in the OS keychain
+pub(crate) fn get_keychain_passphrase(vault_name: &str) -> Option<String> {
+    match keyring::Entry::new(KEYCHAIN_SERVICE, vault_name).and_then(|e| e.get_password()) {
+        Ok(passphrase) => Some(passphrase),
+        Err(keyring::Error::NoEntry) => None,
+        Err(e) => {
+            debug!("the passphrase of the vault {vault_name} can't be read from the keychain: {e}");
+            None
+        }
+    }
+}
+
+/// Store the passphrase of a vault in the OS keychain
+pub(crate) fn set_keychain_passphrase(vault_name: &str, passphrase: &str) -> Result<()> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, vault_name)
+        .and_then(|e| e.set_password(passphrase))
+        .map_err(|e| {
+            CliStateError::InvalidOperation(format!(
+                "the passphrase of the vault {vault_name} can't be stored in the keychain: {e}"
+            ))
+        })
+}
+
+/// Remove the passphrase of a vault from the OS keychain, if it is stored there
+pub(crate) fn delete_keychain_passphrase(vault_name: &str) {
+    if let Err(e) =
+        keyring::Entry::new(KEYCHAIN_SERVICE, vault_name).and_then(|e| e.delete_password())
+    {
+        debug!("the passphrase of the vault {vault_name} can't be deleted from the keychain: {e}");
+    }
+}
@@ -6,8 +6,16 @@ use std::sync::Arc;
 use ockam::identity::{Identities, Vault};
 use ockam_core::errcode::{Kind, Origin};
 use ockam_node::database::SqlxDatabase;
+use ockam_vault::storage::SecretsSqlxDatabase;
+use ockam_vault::SigningKeyType;
 use ockam_vault_aws::AwsSigningVault;
+use ockam_vault_gcp::GcpSigningVault;
+use ockam_vault_platform::{PlatformFallback, PlatformSigningVault};
 
+use crate::cli_state::vault_passphrases::{
+    delete_keychain_passphrase, get_keychain_passphrase, get_vault_passphrase, random_passphrase,
+    set_keychain_passphrase, set_vault_passphrase,
+};
 use crate::cli_state::{random_name, CliState, Result};
 use crate::CliStateError;
 
@@ -27,7 +35,8 @@ impl CliState {
         vault_name: &Option<String>,
         path: &Option<PathBuf>,
     ) -> Result<NamedVault> {
-        self.create_a_vault(vault_name, path, false).await
+        self.create_a_vault(vault_name, path, VaultKind::Local)
+            .await
     }
 
     /// Create a KMS vault with a given name
@@ -39,7 +48,97 @@ impl CliState {
         vault_name: &Option<String>,
         path: &Option<PathBuf>,
     ) -> Result<NamedVault> {
-        self.create_a_vault(vault_name, path, true).await
+        self.create_a_vault(vault_name, path, VaultKind::AwsKms)
+            .await
+    }
+
+    /// Create a vault with a given name, storing its signing keys in a Google Cloud KMS key ring
+    /// The key ring has the form `projects/{project}/locations/{location}/keyRings/{key_ring}`
+    /// If the path is not specified then:
+    ///   - if this is the first vault then secrets are persisted in the main database
+    ///   - if this is a new vault then secrets are persisted in $OCKAM_HOME/vault_name
+    pub async fn create_gcp_kms_vault(
+        &self,
+        vault_name: &Option<String>,
+        path: &Option<PathBuf>,
+        key_ring: &str,
+    ) -> Result<NamedVault> {
+        self.create_a_vault(vault_name, path, VaultKind::GcpKms(key_ring.to_string()))
+            .await
+    }
+
+    /// Create a vault with a given name, storing its signing keys in the secure hardware of
+    /// the platform (Secure Enclave or TPM), or in the vault database if there is none.
+    /// Secure hardware which is present but can't be used is an error when the vault is used,
+    /// unless `fallback` is true, in which case the keys are stored in the vault database
+    /// If the path is not specified then:
+    ///   - if this is the first vault then secrets are persisted in the main database
+    ///   - if this is a new vault then secrets are persisted in $OCKAM_HOME/vault_name
+    pub async fn create_platform_vault(
+        &self,
+        vault_name: &Option<String>,
+        path: &Option<PathBuf>,
+        fallback: bool,
+    ) -> Result<NamedVault> {
+        self.create_a_vault(vault_name, path, VaultKind::Platform { fallback })
+            .await
+    }
+
+    /// Encrypt the secrets of a vault with a passphrase.
+    /// The vault then needs to be unlocked with the same passphrase before being used
+    pub async fn encrypt_named_vault(&self, vault_name: &str, passphrase: &str) -> Result<()> {
+        let vault = self.get_named_vault(vault_name).await?;
+        if vault.is_kms() {
+            return Err(CliStateError::InvalidOperation(format!(
+                "the vault {vault_name} is a KMS vault, its keys can't be encrypted"
+            )));
+        }
+        SecretsSqlxDatabase::new(vault.database().await?)
+            .encrypt(passphrase)
+            .await?;
+        set_vault_passphrase(vault_name, passphrase);
+        Ok(())
+    }
+
+    /// Encrypt the secrets of a vault with a random passphrase stored in the OS keychain.
+    /// The vault is then unlocked transparently as long as the keychain is accessible
+    pub async fn encrypt_named_vault_with_keychain(&self, vault_name: &str) -> Result<()> {
+        let passphrase = random_passphrase();
+        set_keychain_passphrase(vault_name, &passphrase)?;
+        if let Err(e) = self.encrypt_named_vault(vault_name, &passphrase).await {
+            delete_keychain_passphrase(vault_name);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Change the passphrase of an encrypted vault.
+    /// If the passphrase is stored in the OS keychain, it is updated there as well
+    pub async fn change_named_vault_passphrase(
+        &self,
+        vault_name: &str,
+        old_passphrase: &str,
+        new_passphrase: &str,
+    ) -> Result<()> {
+        let vault = self.get_named_vault(vault_name).await?;
+        SecretsSqlxDatabase::new(vault.database().await?)
+            .change_passphrase(old_passphrase, new_passphrase)
+            .await?;
+        if get_keychain_passphrase(vault_name).is_some() {
+            set_keychain_passphrase(vault_name, new_passphrase)?;
+        }
+        set_vault_passphrase(vault_name, new_passphrase);
+        Ok(())
+    }
+
+    /// Return the passphrase of a vault if it is encrypted
+    pub async fn get_named_vault_passphrase(&self, vault_name: &str) -> Result<Option<String>> {
+        let vault = self.get_named_vault(vault_name).await?;
+        if vault.is_encrypted().await? {
+            Ok(Some(get_vault_passphrase(vault_name)?))
+        } else {
+            Ok(None)
+        }
     }
 
     /// Delete an existing vault
@@ -72,6 +171,7 @@ impl CliState {
 
             // if the vault is stored in a separate file
             // remove that file
+            delete_keychain_passphrase(vault_name);
             if vault.path != self.database_path() {
                 let _ = std::fs::remove_file(vault.path);
             } else {
@@ -137,7 +237,7 @@ impl CliState {
         if let Ok(Some(existing_vault)) = vaults_repository.get_named_vault(vault_name).await {
             return Ok(existing_vault);
         }
-        self.create_a_vault(&Some(vault_name.to_string()), &None, false)
+        self.create_a_vault(&Some(vault_name.to_string()), &None, VaultKind::Local)
             .await
     }
 
@@ -203,14 +303,14 @@ impl CliState {
 
 /// Private functions
 impl CliState {
-    /// Create a vault with the given name and indicate if it is going to be used as a KMS vault
+    /// Create a vault with the given name and indicate where its signing keys are stored
     /// If the vault with the same name already exists then an error is returned
     /// If there is already a file at the provided path, then an error is returned
     async fn create_a_vault(
         &self,
         vault_name: &Option<String>,
         path: &Option<PathBuf>,
-        is_kms: bool,
+        kind: VaultKind,
     ) -> Result<NamedVault> {
         let vaults_repository = self.vaults_repository().await?;
 
@@ -260,9 +360,29 @@ impl CliState {
         };
 
         // store the vault metadata
-        Ok(vaults_repository
-            .store_vault(&vault_name, &path, is_kms)
-            .await?)
+        let named_vault = match kind {
+            VaultKind::Local => {
+                vaults_repository
+                    .store_vault(&vault_name, &path, false)
+                    .await?
+            }
+            VaultKind::AwsKms => {
+                vaults_repository
+                    .store_vault(&vault_name, &path, true)
+                    .await?
+            }
+            VaultKind::GcpKms(key_ring) => {
+                vaults_repository
+                    .store_gcp_kms_vault(&vault_name, &path, &key_ring)
+                    .await?
+            }
+            VaultKind::Platform { fallback } => {
+                vaults_repository
+                    .store_platform_vault(&vault_name, &path, fallback)
+                    .await?
+            }
+        };
+        Ok(named_vault)
     }
 
     /// Return the vault name to use for a vault:
@@ -308,11 +428,25 @@ impl CliState {
     }
 }
 
+/// Storage of the signing keys of a new vault
+enum VaultKind {
+    Local,
+    AwsKms,
+    GcpKms(String),
+    Platform { fallback: bool },
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, serde::Serialize, serde::Deserialize)]
 pub struct NamedVault {
     name: String,
     path: PathBuf,
     is_kms: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    gcp_kms_key_ring: Option<String>,
+    /// Set for a vault storing its signing keys in the secure hardware of the platform:
+    /// true if the keys can be stored in the vault database when the hardware can't be used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    platform_fallback: Option<bool>,
 }
 
 impl NamedVault {
@@ -322,6 +456,24 @@ impl NamedVault {
             name: name.to_string(),
             path,
             is_kms,
+            gcp_kms_key_ring: None,
+            platform_fallback: None,
+        }
+    }
+
+    /// Create a new named vault storing its signing keys in a Google Cloud KMS key ring
+    pub fn new_gcp_kms(name: &str, path: PathBuf, key_ring: &str) -> Self {
+        Self {
+            gcp_kms_key_ring: Some(key_ring.to_string()),
+            ..Self::new(name, path, true)
+        }
+    }
+
+    /// Create a new named vault storing its signing keys in the secure hardware of the platform
+    pub fn new_platform(name: &str, path: PathBuf, fallback: bool) -> Self {
+        Self {
+            platform_fallback: Some(fallback),
+            ..Self::new(name, path, false)
         }
     }
 
@@ -345,18 +497,98 @@ impl NamedVault {
         self.is_kms
     }
 
+    /// Return the Google Cloud KMS key ring if this vault is a Google Cloud KMS vault
+    pub fn gcp_kms_key_ring(&self) -> Option<String> {
+        self.gcp_kms_key_ring.clone()
+    }
+
+    /// Return true if this vault stores its signing keys in the secure hardware of the platform
+    pub fn is_platform(&self) -> bool {
+        self.platform_fallback.is_some()
+    }
+
+    /// Return a description of the type of vault
+    pub fn vault_type(&self) -> &'static str {
+        match (self.is_kms, &self.gcp_kms_key_ring) {
+            (true, Some(_)) => "GCP KMS",
+            (true, None) => "AWS KMS",
+            (false, _) if self.is_platform() => "PLATFORM",
+            (false, _) => "OCKAM",
+        }
+    }
+
+    /// Return the type of signing key to use for identities stored in this vault
+    /// KMS and platform vaults only support NIST P-256 keys
+    pub fn signing_key_type(&self) -> SigningKeyType {
+        if self.is_kms || self.is_platform() {
+            SigningKeyType::ECDSASHA256CurveP256
+        } else {
+            SigningKeyType::EdDSACurve25519
+        }
+    }
+
     pub async fn vault(&self) -> Result<Vault> {
         if self.is_kms {
             let mut vault = Vault::create().await?;
-            let aws_vault = Arc::new(AwsSigningVault::create().await?);
-            vault.identity_vault = aws_vault.clone();
-            vault.credential_vault = aws_vault;
+            match &self.gcp_kms_key_ring {
+                Some(key_ring) => {
+                    let gcp_vault = Arc::new(GcpSigningVault::create(key_ring).await?);
+                    vault.identity_vault = gcp_vault.clone();
+                    vault.credential_vault = gcp_vault;
+                }
+                None => {
+                    let aws_vault = Arc::new(AwsSigningVault::create().await?);
+                    vault.identity_vault = aws_vault.clone();
+                    vault.credential_vault = aws_vault;
+                }
+            }
             Ok(vault)
         } else {
-            Ok(Vault::create_with_database(self.database().await?))
+            let secrets = SecretsSqlxDatabase::new(self.database().await?);
+            let secrets = if secrets.is_encrypted().await? {
+                self.unlock(secrets).await?
+            } else {
+                secrets
+            };
+            let mut vault = Vault::create_with_secrets_repository(Arc::new(secrets));
+
+            // the keys of a platform vault are only stored in the vault database
+            // when the secure hardware can't be used
+            if let Some(fallback) = self.platform_fallback {
+                let when = if fallback {
+                    PlatformFallback::Always
+                } else {
+                    PlatformFallback::NoHardware
+                };
+                let platform_vault = Arc::new(
+                    PlatformSigningVault::create_or_fallback(vault.identity_vault.clone(), when)
+                        .await?,
+                );
+                vault.identity_vault = platform_vault.clone();
+                vault.credential_vault = platform_vault;
+            }
+            Ok(vault)
         }
     }
 
+    /// Return true if the secrets of this vault are encrypted with a passphrase
+    pub async fn is_encrypted(&self) -> Result<bool> {
+        if self.is_kms {
+            return Ok(false);
+        }
+        Ok(SecretsSqlxDatabase::new(self.database().await?)
+            .is_encrypted()
+            .await?)
+    }
+
+    /// Unlock the secrets of an encrypted vault
+    async fn unlock(&self, secrets: SecretsSqlxDatabase) -> Result<SecretsSqlxDatabase> {
+        let passphrase = get_vault_passphrase(&self.name)?;
+        let secrets = secrets.unlock(&passphrase).await?;
+        set_vault_passphrase(&self.name, &passphrase);
+        Ok(secrets)
+    }
+
     async fn database(&self) -> Result<SqlxDatabase> {
         // FIXME: We should really have one instance of the SqlxDatabase per process
         Ok(SqlxDatabase::create(self.path.as_path()).await?)
@@ -366,14 +598,7 @@ impl NamedVault {
 impl Display for NamedVault {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "Name: {}", self.name)?;
-        writeln!(
-            f,
-            "Type: {}",
-            match self.is_kms {
-                true => "AWS KMS",
-                false => "OCKAM",
-            }
-        )?;
+        writeln!(f, "Type: {}", self.vault_type())?;
         Ok(())
     }
 }
@@ -527,6 +752,44 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_encrypt_vault() -> Result<()> {
+        let cli = CliState::test().await?;
+
+        // use a random vault name since unlocked vaults are remembered by the process
+        let vault_name = random_name();
+        let vault = cli
+            .create_named_vault(&Some(vault_name.clone()), &None)
+            .await?;
+        let key = vault
+            .vault()
+            .await?
+            .identity_vault
+            .generate_signing_secret_key(SigningKeyType::EdDSACurve25519)
+            .await?;
+
+        cli.encrypt_named_vault(&vault_name, "passphrase").await?;
+        assert!(vault.is_encrypted().await?);
+
+        // the existing keys can still be used once the vault is unlocked
+        let signing_vault = vault.vault().await?.identity_vault;
+        assert!(signing_vault.get_verifying_public_key(&key).await.is_ok());
+
+        // the passphrase can only be changed with the current passphrase
+        let result = cli
+            .change_named_vault_passphrase(&vault_name, "wrong passphrase", "new passphrase")
+            .await;
+        assert!(result.is_err());
+        cli.change_named_vault_passphrase(&vault_name, "passphrase", "new passphrase")
+            .await?;
+        assert_eq!(
+            cli.get_named_vault_passphrase(&vault_name).await?,
+            Some("new passphrase".to_string())
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_create_vault_with_no_user_path() -> Result<()> {
         let cli = CliState::test().await?;
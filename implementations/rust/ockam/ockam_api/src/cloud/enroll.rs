@@ -157,6 +157,37 @@ pub mod auth0 {
     }
 }
 
+pub mod saml {
+    use super::*;
+
+    /// Base64-encoded SAML response returned by an Identity Provider
+    /// after a successful authentication
+    #[derive(Encode, Decode, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+    #[cbor(transparent)]
+    #[serde(transparent)]
+    pub struct SamlAssertion(#[n(0)] pub String);
+
+    impl SamlAssertion {
+        pub fn new(assertion: impl Into<String>) -> Self {
+            Self(assertion.into())
+        }
+    }
+
+    #[derive(Encode, Decode, Debug)]
+    #[cfg_attr(test, derive(Clone))]
+    #[rustfmt::skip]
+    #[cbor(map)]
+    pub struct AuthenticateSamlAssertion {
+        #[n(1)] pub assertion: SamlAssertion,
+    }
+
+    impl AuthenticateSamlAssertion {
+        pub fn new(assertion: SamlAssertion) -> Self {
+            Self { assertion }
+        }
+    }
+}
+
 pub mod enrollment_token {
     use serde::Serialize;
 
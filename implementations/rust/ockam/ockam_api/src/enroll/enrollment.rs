@@ -1,4 +1,5 @@
 use crate::cloud::enroll::auth0::{AuthenticateOidcToken, OidcToken};
+use crate::cloud::enroll::saml::{AuthenticateSamlAssertion, SamlAssertion};
 use crate::cloud::HasSecureClient;
 use crate::nodes::service::default_address::DefaultAddress;
 use miette::IntoDiagnostic;
@@ -31,6 +32,12 @@ pub trait Enrollment {
         token: OidcToken,
     ) -> miette::Result<()>;
 
+    async fn enroll_with_saml_assertion(
+        &self,
+        ctx: &Context,
+        assertion: SamlAssertion,
+    ) -> miette::Result<()>;
+
     async fn present_token(&self, ctx: &Context, token: &OneTimeCode) -> miette::Result<()>;
 
     async fn issue_credential(&self, ctx: &Context) -> miette::Result<CredentialAndPurposeKey>;
@@ -58,6 +65,16 @@ impl<T: HasSecureClient + Send + Sync> Enrollment for T {
             .await
     }
 
+    async fn enroll_with_saml_assertion(
+        &self,
+        ctx: &Context,
+        assertion: SamlAssertion,
+    ) -> miette::Result<()> {
+        self.get_secure_client()
+            .enroll_with_saml_assertion(ctx, assertion)
+            .await
+    }
+
     async fn present_token(&self, ctx: &Context, token: &OneTimeCode) -> miette::Result<()> {
         self.get_secure_client().present_token(ctx, token).await
     }
@@ -105,6 +122,20 @@ impl Enrollment for SecureClient {
             .into_diagnostic()
     }
 
+    async fn enroll_with_saml_assertion(
+        &self,
+        ctx: &Context,
+        assertion: SamlAssertion,
+    ) -> miette::Result<()> {
+        let req = Request::post("v0/enroll").body(AuthenticateSamlAssertion::new(assertion));
+        trace!(target: TARGET, "executing saml flow");
+        self.tell(ctx, DefaultAddress::SAML_IDENTITY_PROVIDER, req)
+            .await
+            .into_diagnostic()?
+            .success()
+            .into_diagnostic()
+    }
+
     async fn present_token(&self, ctx: &Context, token: &OneTimeCode) -> miette::Result<()> {
         let req = Request::post("/").body(token);
         trace!(target: TARGET, "present a token");
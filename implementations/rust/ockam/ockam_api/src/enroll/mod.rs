@@ -3,3 +3,4 @@ pub mod ockam_oidc_provider;
 pub mod oidc_provider;
 pub mod oidc_service;
 pub mod okta_oidc_provider;
+pub mod saml_service;
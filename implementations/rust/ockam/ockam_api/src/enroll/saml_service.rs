@@ -0,0 +1,388 @@
+use std::io::{Read, Write};
+use std::str::FromStr;
+use std::time::Duration;
+
+use base64_url::base64::engine::general_purpose::STANDARD;
+use base64_url::base64::Engine;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use tiny_http::{Header, Method, Request, Response, Server};
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+use url::Url;
+
+use crate::cloud::enroll::saml::SamlAssertion;
+use crate::error::ApiError;
+use ockam_core::compat::rand::{thread_rng, RngCore};
+use ockam_core::Result;
+use ockam_node::callback::{new_callback, CallbackSender};
+
+/// Configuration of a SAML 2.0 Identity Provider used to enroll with a project
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SamlConfig {
+    /// URL of the Identity Provider single sign-on endpoint
+    pub sso_url: Url,
+    /// Entity id of this service provider, as registered in the Identity Provider
+    pub entity_id: String,
+    /// Local URL where the Identity Provider posts its response.
+    /// If its port is 0, a free port is picked when the local web server starts
+    pub acs_url: Url,
+}
+
+impl SamlConfig {
+    pub fn new(sso_url: Url, entity_id: impl Into<String>) -> Self {
+        Self {
+            sso_url,
+            entity_id: entity_id.into(),
+            acs_url: Url::parse("http://localhost:8000/saml/acs").unwrap(),
+        }
+    }
+
+    /// Use a specific local URL to receive the response of the Identity Provider
+    pub fn with_acs_url(mut self, acs_url: Url) -> Self {
+        self.acs_url = acs_url;
+        self
+    }
+}
+
+/// Local URL opened in the browser to start the single sign-on flow
+fn login_url(acs_url: &Url) -> Url {
+    let mut url = acs_url.clone();
+    url.set_path("/saml/login");
+    url
+}
+
+/// This service supports the SAML 2.0 web single sign-on profile:
+///
+///  - an AuthnRequest is sent to the Identity Provider with the HTTP-POST binding,
+///    via an auto-submitted form served by a local web server
+///  - the user authenticates in their browser
+///  - the Identity Provider posts a SAML response back to the local web server
+///
+/// The resulting assertion can then be presented to the SAML addon of a project authority
+/// in order to get enrolled
+///
+pub struct SamlService {
+    config: SamlConfig,
+    redirect_timeout: Duration,
+}
+
+impl SamlService {
+    /// Create a SAML service for a given Identity Provider
+    pub fn new(config: SamlConfig) -> Self {
+        Self {
+            config,
+            redirect_timeout: Duration::from_secs(120),
+        }
+    }
+
+    /// Authenticate the user with their browser and return the SAML assertion
+    /// sent back by the Identity Provider
+    pub async fn get_assertion(&self) -> Result<SamlAssertion> {
+        let relay_state = Self::create_relay_state();
+        let (assertion_receiver, assertion_sender) = new_callback();
+        let (acs_url, _server) = self
+            .wait_for_assertion(relay_state.clone(), assertion_sender)
+            .await?;
+
+        let login_url = login_url(&acs_url);
+        if open::that(login_url.as_str()).is_err() {
+            error!("Couldn't open the SAML login url automatically [url={login_url}]");
+        };
+
+        assertion_receiver
+            .receive_timeout(self.redirect_timeout)
+            .await
+    }
+
+    /// Start a local web server which:
+    ///
+    ///  - serves an auto-submitted form posting an AuthnRequest to the Identity Provider
+    ///  - receives the SAML response on the assertion consumer service endpoint
+    ///
+    /// Use the provided callback channel sender to return the assertion asynchronously.
+    /// Return the URL the server is actually listening on, and a handle on the server task
+    async fn wait_for_assertion(
+        &self,
+        relay_state: String,
+        assertion: CallbackSender<SamlAssertion>,
+    ) -> Result<(Url, JoinHandle<Result<()>>)> {
+        let mut acs_url = self.config.acs_url.clone();
+        let host_and_port = format!(
+            "{}:{}",
+            acs_url.host().unwrap(),
+            acs_url.port_or_known_default().unwrap()
+        );
+        let server =
+            Server::http(host_and_port).map_err(|_| ApiError::core("failed to set up server"))?;
+        if let Some(address) = server.server_addr().to_ip() {
+            acs_url
+                .set_port(Some(address.port()))
+                .map_err(|_| ApiError::core("cannot set the port of the SAML ACS url"))?;
+        }
+        info!("server is started at {acs_url} and waiting for a SAML response");
+
+        let login_form = self.login_form(&acs_url, &relay_state)?;
+        let redirect_timeout = self.redirect_timeout;
+        let server_url = acs_url.clone();
+        let handle = tokio::task::spawn_blocking(move || {
+            let result = Self::serve(
+                server,
+                server_url,
+                login_form,
+                relay_state,
+                assertion,
+                redirect_timeout,
+            );
+            if let Err(e) = &result {
+                error!("The SAML response could not be received: {e}");
+            }
+            result
+        });
+        Ok((acs_url, handle))
+    }
+
+    /// Serve the login form and wait for the Identity Provider to post a SAML response
+    fn serve(
+        server: Server,
+        acs_url: Url,
+        login_form: String,
+        relay_state: String,
+        assertion: CallbackSender<SamlAssertion>,
+        redirect_timeout: Duration,
+    ) -> Result<()> {
+        loop {
+            let mut request = match server.recv_timeout(redirect_timeout) {
+                Ok(Some(request)) => request,
+                Ok(None) => {
+                    return Err(ApiError::core(format!(
+                        "timeout while trying to receive a SAML response on {acs_url} (waited for {redirect_timeout:?})"
+                    )))
+                }
+                Err(e) => {
+                    return Err(ApiError::core(format!(
+                        "error while trying to receive a request on {acs_url}: {e}"
+                    )))
+                }
+            };
+            let method = request.method().clone();
+            let path = request
+                .url()
+                .split('?')
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            match (method, path.as_str()) {
+                (Method::Get, "/saml/login") => {
+                    Self::respond(request, Response::from_string(login_form.clone()), true)?;
+                }
+                (Method::Post, "/saml/acs") => {
+                    let saml_assertion = Self::read_assertion(&mut request, &relay_state);
+                    let message = match &saml_assertion {
+                        Ok(_) => "Authentication succeeded, you can now close this window",
+                        Err(_) => {
+                            "Authentication failed, please check the output of the ockam command"
+                        }
+                    };
+                    Self::respond(request, Response::from_string(message), false)?;
+                    return match saml_assertion {
+                        Ok(saml_assertion) => assertion.send(saml_assertion),
+                        Err(e) => Err(e),
+                    };
+                }
+                _ => {
+                    Self::respond(
+                        request,
+                        Response::from_string("Not found").with_status_code(404),
+                        false,
+                    )?;
+                }
+            }
+        }
+    }
+
+    /// Return an HTML page posting an AuthnRequest to the Identity Provider as soon as it is loaded
+    fn login_form(&self, acs_url: &Url, relay_state: &str) -> Result<String> {
+        let authn_request = STANDARD.encode(self.authn_request(acs_url)?);
+        Ok(format!(
+            r#"<!DOCTYPE html>
+<html>
+<body onload="document.forms[0].submit()">
+<form method="post" action="{}">
+<input type="hidden" name="SAMLRequest" value="{authn_request}"/>
+<input type="hidden" name="RelayState" value="{relay_state}"/>
+<noscript><input type="submit" value="Continue"/></noscript>
+</form>
+</body>
+</html>"#,
+            xml_escape(self.config.sso_url.as_str())
+        ))
+    }
+
+    /// Create a SAML AuthnRequest asking for the assertion to be posted to the local ACS url
+    fn authn_request(&self, acs_url: &Url) -> Result<String> {
+        let mut id = [0u8; 16];
+        thread_rng().fill_bytes(&mut id);
+        let issue_instant = OffsetDateTime::now_utc()
+            .format(&Rfc3339)
+            .map_err(|e| ApiError::core(e.to_string()))?;
+        Ok(format!(
+            r#"<samlp:AuthnRequest xmlns:samlp="urn:oasis:names:tc:SAML:2.0:protocol" xmlns:saml="urn:oasis:names:tc:SAML:2.0:assertion" ID="_{}" Version="2.0" IssueInstant="{issue_instant}" Destination="{}" AssertionConsumerServiceURL="{}" ProtocolBinding="urn:oasis:names:tc:SAML:2.0:bindings:HTTP-POST"><saml:Issuer>{}</saml:Issuer><samlp:NameIDPolicy Format="urn:oasis:names:tc:SAML:1.1:nameid-format:emailAddress" AllowCreate="true"/></samlp:AuthnRequest>"#,
+            hex::encode(id),
+            xml_escape(self.config.sso_url.as_str()),
+            xml_escape(acs_url.as_str()),
+            xml_escape(&self.config.entity_id),
+        ))
+    }
+
+    /// Extract the SAML response from a form posted by the Identity Provider
+    fn read_assertion(request: &mut Request, relay_state: &str) -> Result<SamlAssertion> {
+        let mut body = String::new();
+        request
+            .as_reader()
+            .read_to_string(&mut body)
+            .map_err(|e| ApiError::core(e.to_string()))?;
+        Self::parse_assertion(&body, relay_state)
+    }
+
+    /// Parse the url-encoded body posted by the Identity Provider and check
+    /// that the relay state is the one which was sent with the AuthnRequest
+    fn parse_assertion(body: &str, relay_state: &str) -> Result<SamlAssertion> {
+        let mut saml_response = None;
+        let mut received_relay_state = None;
+        for (name, value) in url::form_urlencoded::parse(body.as_bytes()) {
+            match name.as_ref() {
+                "SAMLResponse" => saml_response = Some(value.to_string()),
+                "RelayState" => received_relay_state = Some(value.to_string()),
+                _ => (),
+            }
+        }
+        if received_relay_state.as_deref() != Some(relay_state) {
+            return Err(ApiError::core(
+                "the relay state of the SAML response does not match the SAML request",
+            ));
+        }
+        let saml_response =
+            saml_response.ok_or_else(|| ApiError::core("the SAMLResponse parameter is missing"))?;
+        // check that the response is properly encoded before sending it to the authority
+        STANDARD
+            .decode(saml_response.as_bytes())
+            .map_err(|e| ApiError::core(format!("the SAML response is not valid base64: {e}")))?;
+        Ok(SamlAssertion::new(saml_response))
+    }
+
+    fn respond<R: Read>(request: Request, response: Response<R>, html: bool) -> Result<()> {
+        let content_type = if html { "text/html" } else { "text/plain" };
+        let response = response.with_header(
+            Header::from_str(&format!("Content-Type: {content_type}; charset=utf-8")).unwrap(),
+        );
+        let mut writer = request.into_writer();
+        response
+            .raw_print(&mut writer, tiny_http::HTTPVersion(1, 0), &[], true, None)
+            .and_then(|_| writer.flush())
+            .map_err(|e| ApiError::core(format!("error while sending a response: {e}")))
+    }
+
+    /// Generate a random value used to correlate the SAML response with the SAML request
+    fn create_relay_state() -> String {
+        let mut relay_state = [0u8; 16];
+        thread_rng().fill_bytes(&mut relay_state);
+        hex::encode(relay_state)
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service() -> SamlService {
+        SamlService::new(SamlConfig::new(
+            Url::parse("https://idp.example.com/sso/saml").unwrap(),
+            "ockam",
+        ))
+    }
+
+    #[test]
+    fn test_authn_request() -> Result<()> {
+        let service = service();
+        let request = service.authn_request(&service.config.acs_url)?;
+        assert!(request.contains(r#"Destination="https://idp.example.com/sso/saml""#));
+        assert!(request.contains(r#"AssertionConsumerServiceURL="http://localhost:8000/saml/acs""#));
+        assert!(request.contains("<saml:Issuer>ockam</saml:Issuer>"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_login_form() -> Result<()> {
+        let service = service();
+        let form = service.login_form(&service.config.acs_url, "1234")?;
+        assert!(form.contains(r#"action="https://idp.example.com/sso/saml""#));
+        assert!(form.contains(r#"name="RelayState" value="1234""#));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_assertion() {
+        let response = STANDARD.encode("<samlp:Response/>");
+        let body = url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("SAMLResponse", &response)
+            .append_pair("RelayState", "1234")
+            .finish();
+
+        let assertion = SamlService::parse_assertion(&body, "1234").unwrap();
+        assert_eq!(assertion, SamlAssertion::new(response));
+
+        // the relay state must match
+        assert!(SamlService::parse_assertion(&body, "5678").is_err());
+
+        // the response must be present
+        assert!(SamlService::parse_assertion("RelayState=1234", "1234").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_assertion() -> Result<()> {
+        let saml_service = SamlService::new(
+            SamlConfig::new(
+                Url::parse("https://idp.example.com/sso/saml").unwrap(),
+                "ockam",
+            )
+            .with_acs_url(Url::parse("http://localhost:0/saml/acs").unwrap()),
+        );
+        let (assertion_receiver, assertion_sender) = new_callback();
+        let (acs_url, server) = saml_service
+            .wait_for_assertion("1234".to_string(), assertion_sender)
+            .await?;
+        assert_ne!(acs_url.port(), Some(0));
+
+        let response = STANDARD.encode("<samlp:Response/>");
+        let client_response = response.clone();
+        let client_thread = tokio::spawn(async move {
+            let client = reqwest::ClientBuilder::new().build().unwrap();
+            client
+                .post(acs_url)
+                .form(&[
+                    ("SAMLResponse", client_response.as_str()),
+                    ("RelayState", "1234"),
+                ])
+                .send()
+                .await
+        });
+        let res = client_thread.await.unwrap();
+        assert!(res.is_ok());
+
+        let assertion = assertion_receiver
+            .receive_timeout(Duration::from_secs(1))
+            .await?;
+        assert_eq!(assertion, SamlAssertion::new(response));
+
+        // the server stops after receiving the response
+        server.await.unwrap()
+    }
+}
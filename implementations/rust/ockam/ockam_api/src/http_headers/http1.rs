@@ -0,0 +1,375 @@
+use ockam_core::Result;
+
+use crate::http_headers::{is_reserved_header, protocol_error};
+
+/// Largest request head, or chunk size line, which is buffered before being rewritten
+const MAX_HEAD_SIZE: usize = 64 * 1024;
+
+/// Largest number of header fields in a request head
+const MAX_HEADERS: usize = 128;
+
+const CRLF: &[u8] = b"\r\n";
+const END_OF_HEAD: &[u8] = b"\r\n\r\n";
+
+/// Where the rewriter is in the stream of requests
+enum State {
+    /// Reading the request line and the header fields of a request
+    Head,
+    /// Forwarding a body delimited by a `Content-Length` header
+    Body { remaining: u64 },
+    /// Reading the size line of a chunk
+    ChunkSize,
+    /// Forwarding the data of a chunk
+    ChunkData { remaining: u64 },
+    /// Reading the line ending the data of a chunk
+    ChunkEnd,
+    /// Reading the trailer fields following the last chunk
+    Trailers,
+    /// The connection was upgraded to another protocol, the data is forwarded unchanged
+    Tunnel,
+}
+
+/// Rewrites the stream of HTTP/1.1 requests sent by a client
+///
+/// The header fields starting with `X-Ockam-` are removed from every request
+/// and replaced with the injected ones. The bodies are forwarded unchanged, they
+/// are only parsed as far as needed to find where the next request starts.
+pub(crate) struct Http1RequestRewriter {
+    state: State,
+    buffer: Vec<u8>,
+}
+
+impl Http1RequestRewriter {
+    pub(crate) fn new() -> Self {
+        Self {
+            state: State::Head,
+            buffer: vec![],
+        }
+    }
+
+    /// Rewrite the next bytes of the stream. The bytes of an incomplete
+    /// request head are kept until the rest of the head is received.
+    pub(crate) fn rewrite(&mut self, data: &[u8], headers: &[(String, String)]) -> Result<Vec<u8>> {
+        let mut output = Vec::with_capacity(data.len());
+        let mut data = data;
+
+        while !data.is_empty() {
+            match self.state {
+                State::Tunnel => {
+                    output.extend_from_slice(data);
+                    data = &[];
+                }
+                State::Body { remaining } => {
+                    let remaining = forward(&mut output, &mut data, remaining);
+                    self.state = if remaining == 0 {
+                        State::Head
+                    } else {
+                        State::Body { remaining }
+                    };
+                }
+                State::ChunkData { remaining } => {
+                    let remaining = forward(&mut output, &mut data, remaining);
+                    self.state = if remaining == 0 {
+                        State::ChunkEnd
+                    } else {
+                        State::ChunkData { remaining }
+                    };
+                }
+                State::Head => {
+                    if let Some(head) = self.read_until(&mut data, END_OF_HEAD)? {
+                        let (head, state) = rewrite_head(&head, headers)?;
+                        output.extend_from_slice(&head);
+                        self.state = state;
+                    }
+                }
+                State::ChunkSize => {
+                    if let Some(line) = self.read_until(&mut data, CRLF)? {
+                        let size = parse_chunk_size(&line)?;
+                        output.extend_from_slice(&line);
+                        self.state = if size == 0 {
+                            State::Trailers
+                        } else {
+                            State::ChunkData { remaining: size }
+                        };
+                    }
+                }
+                State::ChunkEnd => {
+                    if let Some(line) = self.read_until(&mut data, CRLF)? {
+                        if line != CRLF {
+                            return Err(protocol_error("invalid end of chunk"));
+                        }
+                        output.extend_from_slice(&line);
+                        self.state = State::ChunkSize;
+                    }
+                }
+                State::Trailers => {
+                    if let Some(line) = self.read_until(&mut data, CRLF)? {
+                        if line == CRLF {
+                            output.extend_from_slice(CRLF);
+                            self.state = State::Head;
+                        } else if let Some(trailer) = rewrite_trailer(&line)? {
+                            output.extend(trailer);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Buffer the data until the terminator is found, and return the
+    /// buffered bytes, terminator included
+    fn read_until(&mut self, data: &mut &[u8], terminator: &[u8]) -> Result<Option<Vec<u8>>> {
+        // the terminator may start in the bytes previously buffered
+        let start = self.buffer.len().saturating_sub(terminator.len() - 1);
+        let previous_length = self.buffer.len();
+        self.buffer.extend_from_slice(data);
+
+        match find(&self.buffer[start..], terminator) {
+            Some(position) => {
+                let end = start + position + terminator.len();
+                *data = &data[end - previous_length..];
+                self.buffer.truncate(end);
+                Ok(Some(core::mem::take(&mut self.buffer)))
+            }
+            None => {
+                if self.buffer.len() > MAX_HEAD_SIZE {
+                    return Err(protocol_error("HTTP request head is too large"));
+                }
+                *data = &[];
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// Forward at most `remaining` bytes and return how many bytes remain to be forwarded
+fn forward(output: &mut Vec<u8>, data: &mut &[u8], remaining: u64) -> u64 {
+    let length = remaining.min(data.len() as u64) as usize;
+    output.extend_from_slice(&data[..length]);
+    *data = &data[length..];
+    remaining - length as u64
+}
+
+/// Rewrite a request head, and return the state for the data following it
+fn rewrite_head(head: &[u8], headers: &[(String, String)]) -> Result<(Vec<u8>, State)> {
+    // empty lines preceding a request are ignored
+    let mut head = head;
+    while head.starts_with(CRLF) {
+        head = &head[CRLF.len()..];
+    }
+    if head.is_empty() {
+        return Ok((vec![], State::Head));
+    }
+
+    let mut fields = [httparse::EMPTY_HEADER; MAX_HEADERS];
+    let mut request = httparse::Request::new(&mut fields);
+    match request.parse(head) {
+        Ok(httparse::Status::Complete(length)) if length == head.len() => (),
+        Ok(_) => return Err(protocol_error("invalid HTTP request head")),
+        Err(e) => return Err(protocol_error(&format!("invalid HTTP request head: {e}"))),
+    }
+    let method = request.method.unwrap_or_default();
+
+    // the head is written again from the parsed fields, with normalized line endings
+    let mut output = Vec::with_capacity(head.len());
+    output.extend_from_slice(method.as_bytes());
+    output.push(b' ');
+    output.extend_from_slice(request.path.unwrap_or_default().as_bytes());
+    output.extend_from_slice(match request.version {
+        Some(0) => b" HTTP/1.0",
+        _ => b" HTTP/1.1",
+    });
+    output.extend_from_slice(CRLF);
+
+    let mut content_length: Option<u64> = None;
+    let mut transfer_encoding = false;
+    let mut chunked = false;
+    let mut upgrade = false;
+
+    for field in request.headers.iter() {
+        if is_reserved_header(field.name.as_bytes()) {
+            continue;
+        }
+
+        let value = trim(field.value);
+        if field.name.eq_ignore_ascii_case("content-length") {
+            let length = parse_content_length(value)?;
+            if content_length.map(|l| l != length).unwrap_or(false) {
+                return Err(protocol_error("conflicting Content-Length headers"));
+            }
+            content_length = Some(length);
+        } else if field.name.eq_ignore_ascii_case("transfer-encoding") {
+            // the last transfer coding must be chunked for the body to be delimited
+            transfer_encoding = true;
+            chunked = value
+                .rsplit(|b| *b == b',')
+                .next()
+                .map(|coding| trim(coding).eq_ignore_ascii_case(b"chunked"))
+                .unwrap_or(false);
+        } else if field.name.eq_ignore_ascii_case("upgrade") {
+            upgrade = true;
+        }
+
+        write_header(&mut output, field.name.as_bytes(), field.value);
+    }
+
+    for (name, value) in headers {
+        write_header(&mut output, name.as_bytes(), value.as_bytes());
+    }
+    output.extend_from_slice(CRLF);
+
+    let state = if method == "CONNECT" || upgrade {
+        State::Tunnel
+    } else if transfer_encoding {
+        // a request with both headers could be framed differently by the target
+        if content_length.is_some() {
+            return Err(protocol_error(
+                "both Content-Length and Transfer-Encoding headers are present",
+            ));
+        }
+        if !chunked {
+            return Err(protocol_error("the request body is not chunked"));
+        }
+        State::ChunkSize
+    } else {
+        match content_length {
+            Some(0) | None => State::Head,
+            Some(remaining) => State::Body { remaining },
+        }
+    };
+
+    Ok((output, state))
+}
+
+/// Rewrite a trailer field line, or return None if the field is removed
+fn rewrite_trailer(line: &[u8]) -> Result<Option<Vec<u8>>> {
+    // a trailer line is parsed as a section made of a single field
+    let mut section = line.to_vec();
+    section.extend_from_slice(CRLF);
+    let mut fields = [httparse::EMPTY_HEADER; 1];
+    let field = match httparse::parse_headers(&section, &mut fields) {
+        Ok(httparse::Status::Complete((_, [field]))) => field,
+        _ => return Err(protocol_error("invalid HTTP trailer field")),
+    };
+    if is_reserved_header(field.name.as_bytes()) {
+        return Ok(None);
+    }
+
+    let mut output = vec![];
+    write_header(&mut output, field.name.as_bytes(), field.value);
+    Ok(Some(output))
+}
+
+fn write_header(output: &mut Vec<u8>, name: &[u8], value: &[u8]) {
+    output.extend_from_slice(name);
+    output.extend_from_slice(b": ");
+    output.extend_from_slice(value);
+    output.extend_from_slice(CRLF);
+}
+
+fn parse_content_length(value: &[u8]) -> Result<u64> {
+    if value.is_empty() || !value.iter().all(u8::is_ascii_digit) {
+        return Err(protocol_error("invalid Content-Length header"));
+    }
+    core::str::from_utf8(value)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| protocol_error("invalid Content-Length header"))
+}
+
+fn parse_chunk_size(line: &[u8]) -> Result<u64> {
+    // chunk extensions are forwarded but ignored
+    match httparse::parse_chunk_size(line) {
+        Ok(httparse::Status::Complete((length, size))) if length == line.len() => Ok(size),
+        _ => Err(protocol_error("invalid chunk size")),
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Remove the leading and trailing whitespaces
+fn trim(value: &[u8]) -> &[u8] {
+    let is_whitespace = |b: &u8| *b == b' ' || *b == b'\t';
+    let start = value
+        .iter()
+        .position(|b| !is_whitespace(b))
+        .unwrap_or(value.len());
+    let end = value
+        .iter()
+        .rposition(|b| !is_whitespace(b))
+        .map(|p| p + 1)
+        .unwrap_or(start);
+    &value[start..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers() -> Vec<(String, String)> {
+        vec![("x-ockam-identifier".to_string(), "I1234".to_string())]
+    }
+
+    /// Rewrite the stream split in payloads of the given size
+    fn rewrite(stream: &[u8], payload_size: usize) -> Result<Vec<u8>> {
+        let mut rewriter = Http1RequestRewriter::new();
+        let mut output = vec![];
+        for payload in stream.chunks(payload_size) {
+            output.extend(rewriter.rewrite(payload, &headers())?);
+        }
+        Ok(output)
+    }
+
+    #[test]
+    fn test_rewrite_requests() {
+        let stream = b"GET / HTTP/1.1\r\nHost: localhost\r\nX-Ockam-Identifier: I0000\r\n\r\n\
+            POST /items HTTP/1.1\r\nContent-Length: 22\r\n\r\nX-Ockam-Identifier: I0\
+            PUT /items HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n\
+            4\r\n\r\n\r\n\r\n0\r\nx-ockam-identifier: I0\r\n\r\n";
+        let expected = b"GET / HTTP/1.1\r\nHost: localhost\r\nx-ockam-identifier: I1234\r\n\r\n\
+            POST /items HTTP/1.1\r\nContent-Length: 22\r\nx-ockam-identifier: I1234\r\n\r\nX-Ockam-Identifier: I0\
+            PUT /items HTTP/1.1\r\nTransfer-Encoding: chunked\r\nx-ockam-identifier: I1234\r\n\r\n\
+            4\r\n\r\n\r\n\r\n0\r\n\r\n";
+
+        // the result does not depend on how the stream is split
+        for payload_size in [1, 2, 3, 7, 64, stream.len()] {
+            assert_eq!(rewrite(stream, payload_size).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_upgrade() {
+        let stream = b"GET /ws HTTP/1.1\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\r\n\
+            X-Ockam-Identifier: I0\r\n\r\n";
+        let expected = b"GET /ws HTTP/1.1\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+            x-ockam-identifier: I1234\r\n\r\nX-Ockam-Identifier: I0\r\n\r\n";
+        assert_eq!(rewrite(stream, 5).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_reject_ambiguous_requests() {
+        let requests: [&[u8]; 5] = [
+            b"GET / HTTP/1.1\r\nContent-Length: 1\r\nTransfer-Encoding: chunked\r\n\r\n",
+            b"GET / HTTP/1.1\r\nContent-Length: 1\r\nContent-Length: 2\r\n\r\n",
+            b"GET / HTTP/1.1\r\nTransfer-Encoding: gzip\r\n\r\n",
+            b"GET / HTTP/1.1\r\nHost: localhost\r\n X-Ockam-Identifier: I0\r\n\r\n",
+            b"GET / HTTP/1.1\r\nContent-Length: +1\r\n\r\n",
+        ];
+        for request in requests {
+            assert!(rewrite(request, request.len()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_normalize_line_endings() {
+        let stream = b"GET / HTTP/1.1\nHost: localhost\r\n\r\n";
+        let expected = b"GET / HTTP/1.1\r\nHost: localhost\r\nx-ockam-identifier: I1234\r\n\r\n";
+        assert_eq!(rewrite(stream, stream.len()).unwrap(), expected);
+    }
+}
@@ -0,0 +1,446 @@
+use ockam_core::Result;
+
+use crate::http_headers::{is_reserved_header, protocol_error};
+
+/// Connection preface sent by an HTTP/2 client before its first frame
+pub(crate) const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+const FRAME_HEADER_LENGTH: usize = 9;
+
+/// Largest frame payload which a peer must always accept
+const DEFAULT_MAX_FRAME_SIZE: usize = 16_384;
+
+/// Largest header block which is buffered before being rewritten
+const MAX_HEADER_BLOCK_SIZE: usize = 256 * 1024;
+
+const HEADERS: u8 = 0x1;
+const CONTINUATION: u8 = 0x9;
+
+const END_STREAM: u8 = 0x1;
+const END_HEADERS: u8 = 0x4;
+const PADDED: u8 = 0x8;
+const PRIORITY: u8 = 0x20;
+
+/// Length of the priority fields of a HEADERS frame
+const PRIORITY_LENGTH: usize = 5;
+
+/// Where the rewriter is in the stream of frames
+enum State {
+    /// Forwarding the connection preface
+    Preface { remaining: usize },
+    /// Reading the header of a frame
+    FrameHeader,
+    /// Reading the payload of a HEADERS or CONTINUATION frame
+    HeaderBlock {
+        frame_type: u8,
+        flags: u8,
+        stream_id: u32,
+        length: usize,
+    },
+    /// Forwarding the payload of any other frame
+    FramePayload { remaining: usize },
+}
+
+/// A header block which is split across several frames
+struct PendingHeaderBlock {
+    stream_id: u32,
+    end_stream: bool,
+    priority: Option<Vec<u8>>,
+    fragment: Vec<u8>,
+}
+
+/// Rewrites the stream of HTTP/2 frames sent by a client
+///
+/// Every header block is decoded, the header fields starting with `x-ockam-`
+/// are removed, and the injected fields are added to the blocks starting a
+/// request. The rewritten blocks are encoded again with the encoder of the
+/// connection, whose dynamic table is the one of the target's decoder, since
+/// all the header blocks sent to the target go through it, in order.
+/// All the other frames are forwarded unchanged.
+pub(crate) struct Http2RequestRewriter {
+    state: State,
+    buffer: Vec<u8>,
+    pending: Option<PendingHeaderBlock>,
+    decoder: hpack::Decoder<'static>,
+    encoder: hpack::Encoder<'static>,
+}
+
+impl Http2RequestRewriter {
+    pub(crate) fn new() -> Self {
+        Self {
+            state: State::Preface {
+                remaining: PREFACE.len(),
+            },
+            buffer: vec![],
+            pending: None,
+            decoder: hpack::Decoder::new(),
+            encoder: hpack::Encoder::new(),
+        }
+    }
+
+    /// Rewrite the next bytes of the stream. The frames carrying header
+    /// blocks are kept until they are complete.
+    pub(crate) fn rewrite(&mut self, data: &[u8], headers: &[(String, String)]) -> Result<Vec<u8>> {
+        let mut output = Vec::with_capacity(data.len());
+        let mut data = data;
+
+        while !data.is_empty() {
+            match self.state {
+                State::Preface { remaining } => {
+                    let length = remaining.min(data.len());
+                    output.extend_from_slice(&data[..length]);
+                    data = &data[length..];
+                    self.state = if remaining == length {
+                        State::FrameHeader
+                    } else {
+                        State::Preface {
+                            remaining: remaining - length,
+                        }
+                    };
+                }
+                State::FramePayload { remaining } => {
+                    let length = remaining.min(data.len());
+                    output.extend_from_slice(&data[..length]);
+                    data = &data[length..];
+                    self.state = if remaining == length {
+                        State::FrameHeader
+                    } else {
+                        State::FramePayload {
+                            remaining: remaining - length,
+                        }
+                    };
+                }
+                State::FrameHeader => {
+                    if let Some(header) = self.read(&mut data, FRAME_HEADER_LENGTH) {
+                        self.state = self.handle_frame_header(&header, &mut output)?;
+                    }
+                }
+                State::HeaderBlock {
+                    frame_type,
+                    flags,
+                    stream_id,
+                    length,
+                } => {
+                    if let Some(payload) = self.read(&mut data, length) {
+                        self.handle_header_block_frame(
+                            frame_type,
+                            flags,
+                            stream_id,
+                            &payload,
+                            headers,
+                            &mut output,
+                        )?;
+                        self.state = State::FrameHeader;
+                    }
+                }
+            }
+        }
+
+        // frames without payload are complete as soon as their header is read
+        if let State::HeaderBlock {
+            frame_type,
+            flags,
+            stream_id,
+            length: 0,
+        } = self.state
+        {
+            self.handle_header_block_frame(
+                frame_type,
+                flags,
+                stream_id,
+                &[],
+                headers,
+                &mut output,
+            )?;
+            self.state = State::FrameHeader;
+        }
+
+        Ok(output)
+    }
+
+    fn handle_frame_header(&mut self, header: &[u8], output: &mut Vec<u8>) -> Result<State> {
+        let length = u32::from_be_bytes([0, header[0], header[1], header[2]]) as usize;
+        let frame_type = header[3];
+        let flags = header[4];
+        let stream_id =
+            u32::from_be_bytes([header[5], header[6], header[7], header[8]]) & 0x7fff_ffff;
+
+        if self.pending.is_some() && frame_type != CONTINUATION {
+            return Err(protocol_error("expected a CONTINUATION frame"));
+        }
+
+        if frame_type == HEADERS || frame_type == CONTINUATION {
+            if length > MAX_HEADER_BLOCK_SIZE {
+                return Err(protocol_error("HTTP/2 header block is too large"));
+            }
+            Ok(State::HeaderBlock {
+                frame_type,
+                flags,
+                stream_id,
+                length,
+            })
+        } else {
+            output.extend_from_slice(header);
+            Ok(if length == 0 {
+                State::FrameHeader
+            } else {
+                State::FramePayload { remaining: length }
+            })
+        }
+    }
+
+    fn handle_header_block_frame(
+        &mut self,
+        frame_type: u8,
+        flags: u8,
+        stream_id: u32,
+        payload: &[u8],
+        headers: &[(String, String)],
+        output: &mut Vec<u8>,
+    ) -> Result<()> {
+        let mut pending = if frame_type == HEADERS {
+            let mut payload = payload;
+            if flags & PADDED != 0 {
+                let padding = *payload
+                    .first()
+                    .ok_or_else(|| protocol_error("invalid HTTP/2 padding"))?
+                    as usize;
+                if padding >= payload.len() {
+                    return Err(protocol_error("invalid HTTP/2 padding"));
+                }
+                payload = &payload[1..payload.len() - padding];
+            }
+
+            let priority = if flags & PRIORITY != 0 {
+                if payload.len() < PRIORITY_LENGTH {
+                    return Err(protocol_error("invalid HTTP/2 priority"));
+                }
+                let (priority, rest) = payload.split_at(PRIORITY_LENGTH);
+                payload = rest;
+                Some(priority.to_vec())
+            } else {
+                None
+            };
+
+            PendingHeaderBlock {
+                stream_id,
+                end_stream: flags & END_STREAM != 0,
+                priority,
+                fragment: payload.to_vec(),
+            }
+        } else {
+            let mut pending = self
+                .pending
+                .take()
+                .ok_or_else(|| protocol_error("unexpected CONTINUATION frame"))?;
+            if pending.stream_id != stream_id {
+                return Err(protocol_error("unexpected CONTINUATION frame"));
+            }
+            if pending.fragment.len() + payload.len() > MAX_HEADER_BLOCK_SIZE {
+                return Err(protocol_error("HTTP/2 header block is too large"));
+            }
+            pending.fragment.extend_from_slice(payload);
+            pending
+        };
+
+        if flags & END_HEADERS == 0 {
+            self.pending = Some(pending);
+            return Ok(());
+        }
+
+        let mut fields = self
+            .decoder
+            .decode(&pending.fragment)
+            .map_err(|e| protocol_error(&format!("invalid HTTP/2 header block: {e:?}")))?;
+        // trailers are sent in a header block without pseudo-header fields
+        let is_request = fields.iter().any(|(name, _)| name == b":method");
+        fields.retain(|(name, _)| !is_reserved_header(name));
+        if is_request {
+            fields.extend(
+                headers
+                    .iter()
+                    .map(|(name, value)| (name.as_bytes().to_vec(), value.as_bytes().to_vec())),
+            );
+        }
+
+        pending.fragment = self.encoder.encode(
+            fields
+                .iter()
+                .map(|(name, value)| (name.as_slice(), value.as_slice())),
+        );
+        write_header_block(&pending, output);
+        Ok(())
+    }
+
+    /// Buffer the data until `length` bytes are available, and return them
+    fn read(&mut self, data: &mut &[u8], length: usize) -> Option<Vec<u8>> {
+        let missing = length - self.buffer.len();
+        let available = missing.min(data.len());
+        self.buffer.extend_from_slice(&data[..available]);
+        *data = &data[available..];
+
+        if self.buffer.len() == length {
+            Some(core::mem::take(&mut self.buffer))
+        } else {
+            None
+        }
+    }
+}
+
+/// Write a header block as a HEADERS frame followed by as many
+/// CONTINUATION frames as needed
+fn write_header_block(block: &PendingHeaderBlock, output: &mut Vec<u8>) {
+    let priority = block.priority.as_deref().unwrap_or_default();
+    let first_length = block
+        .fragment
+        .len()
+        .min(DEFAULT_MAX_FRAME_SIZE - priority.len());
+    let (first, mut rest) = block.fragment.split_at(first_length);
+
+    let mut flags = 0;
+    if block.end_stream {
+        flags |= END_STREAM;
+    }
+    if !priority.is_empty() {
+        flags |= PRIORITY;
+    }
+    if rest.is_empty() {
+        flags |= END_HEADERS;
+    }
+    write_frame_header(
+        output,
+        priority.len() + first.len(),
+        HEADERS,
+        flags,
+        block.stream_id,
+    );
+    output.extend_from_slice(priority);
+    output.extend_from_slice(first);
+
+    while !rest.is_empty() {
+        let (fragment, next) = rest.split_at(rest.len().min(DEFAULT_MAX_FRAME_SIZE));
+        rest = next;
+        let flags = if rest.is_empty() { END_HEADERS } else { 0 };
+        write_frame_header(output, fragment.len(), CONTINUATION, flags, block.stream_id);
+        output.extend_from_slice(fragment);
+    }
+}
+
+fn write_frame_header(
+    output: &mut Vec<u8>,
+    length: usize,
+    frame_type: u8,
+    flags: u8,
+    stream_id: u32,
+) {
+    output.extend_from_slice(&(length as u32).to_be_bytes()[1..]);
+    output.push(frame_type);
+    output.push(flags);
+    output.extend_from_slice(&stream_id.to_be_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(frame_type: u8, flags: u8, stream_id: u32, payload: &[u8]) -> Vec<u8> {
+        let mut frame = vec![];
+        write_frame_header(&mut frame, payload.len(), frame_type, flags, stream_id);
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    fn fields(fields: &[(&str, &str)]) -> Vec<(Vec<u8>, Vec<u8>)> {
+        fields
+            .iter()
+            .map(|(name, value)| (name.as_bytes().to_vec(), value.as_bytes().to_vec()))
+            .collect()
+    }
+
+    fn encode(encoder: &mut hpack::Encoder, fields: &[(&str, &str)]) -> Vec<u8> {
+        encoder.encode(
+            fields
+                .iter()
+                .map(|(name, value)| (name.as_bytes(), value.as_bytes())),
+        )
+    }
+
+    /// Parse the frames and decode the header blocks written by the rewriter
+    fn parse(mut output: &[u8]) -> (Vec<Vec<(Vec<u8>, Vec<u8>)>>, Vec<u8>) {
+        assert!(output.starts_with(PREFACE));
+        output = &output[PREFACE.len()..];
+
+        let mut decoder = hpack::Decoder::new();
+        let mut blocks = vec![];
+        let mut block = vec![];
+        let mut data = vec![];
+        while !output.is_empty() {
+            let length = u32::from_be_bytes([0, output[0], output[1], output[2]]) as usize;
+            let (frame_type, flags) = (output[3], output[4]);
+            let payload = &output[FRAME_HEADER_LENGTH..FRAME_HEADER_LENGTH + length];
+            output = &output[FRAME_HEADER_LENGTH + length..];
+
+            match frame_type {
+                HEADERS | CONTINUATION => {
+                    let skip = if flags & PRIORITY != 0 { 5 } else { 0 };
+                    block.extend_from_slice(&payload[skip..]);
+                    if flags & END_HEADERS != 0 {
+                        blocks.push(decoder.decode(&block).unwrap());
+                        block.clear();
+                    }
+                }
+                _ => data.extend_from_slice(payload),
+            }
+        }
+        (blocks, data)
+    }
+
+    #[test]
+    fn test_rewrite_frames() {
+        // the client's blocks use its own dynamic table
+        let mut encoder = hpack::Encoder::new();
+        let request = encode(
+            &mut encoder,
+            &[
+                (":method", "GET"),
+                (":path", "/"),
+                ("x-ockam-identifier", "I0000"),
+            ],
+        );
+        let trailers = encode(&mut encoder, &[("x-ockam-identifier", "I0000")]);
+
+        let mut stream = PREFACE.to_vec();
+        // a request split in a padded HEADERS frame and a CONTINUATION frame
+        let (first, second) = request.split_at(10);
+        let mut padded = vec![3];
+        padded.extend_from_slice(first);
+        padded.extend_from_slice(&[0, 0, 0]);
+        stream.extend(frame(HEADERS, PADDED, 1, &padded));
+        stream.extend(frame(CONTINUATION, END_HEADERS, 1, second));
+        stream.extend(frame(0x0, 0, 1, b"body"));
+        stream.extend(frame(HEADERS, END_STREAM | END_HEADERS, 1, &trailers));
+
+        let headers = vec![("x-ockam-identifier".to_string(), "I1234".to_string())];
+        for payload_size in [1, 5, 9, 100, stream.len()] {
+            let mut rewriter = Http2RequestRewriter::new();
+            let mut output = vec![];
+            for payload in stream.chunks(payload_size) {
+                output.extend(rewriter.rewrite(payload, &headers).unwrap());
+            }
+
+            let (blocks, data) = parse(&output);
+            assert_eq!(
+                blocks,
+                vec![
+                    fields(&[
+                        (":method", "GET"),
+                        (":path", "/"),
+                        ("x-ockam-identifier", "I1234")
+                    ]),
+                    vec![]
+                ]
+            );
+            assert_eq!(data, b"body");
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use std::sync::Arc;
+
+use ockam::identity::{IdentityAttributesRepository, IdentitySecureChannelLocalInfo};
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{async_trait, LocalInfo, Result};
+use ockam_transport_tcp::{PortalInterceptor, PortalInterceptorFactory};
+
+use crate::http_headers::http1::Http1RequestRewriter;
+use crate::http_headers::http2::{Http2RequestRewriter, PREFACE};
+use crate::http_headers::{is_token, ATTRIBUTE_HEADER_PREFIX, IDENTIFIER_HEADER};
+
+/// Creates the interceptors injecting the identity headers in the HTTP
+/// requests sent by a TCP outlet
+pub struct HttpHeadersInterceptorFactory {
+    identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
+    attributes: Arc<Vec<String>>,
+}
+
+impl core::fmt::Debug for HttpHeadersInterceptorFactory {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("HttpHeadersInterceptorFactory")
+            .field("attributes", &self.attributes)
+            .finish()
+    }
+}
+
+impl HttpHeadersInterceptorFactory {
+    /// Create a factory for interceptors injecting the identifier of the peer
+    /// and the given attributes of its credential
+    pub fn new(
+        identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
+        attributes: Vec<String>,
+    ) -> Result<Self> {
+        if let Some(attribute) = attributes.iter().find(|a| !is_token(a.as_bytes())) {
+            return Err(ockam_core::Error::new(
+                Origin::Api,
+                Kind::Invalid,
+                format!("'{attribute}' can't be used in an HTTP header name"),
+            ));
+        }
+
+        Ok(Self {
+            identity_attributes_repository,
+            attributes: Arc::new(attributes),
+        })
+    }
+}
+
+impl PortalInterceptorFactory for HttpHeadersInterceptorFactory {
+    fn create(&self) -> Box<dyn PortalInterceptor> {
+        Box::new(HttpHeadersInterceptor {
+            identity_attributes_repository: self.identity_attributes_repository.clone(),
+            attributes: self.attributes.clone(),
+            headers: None,
+            protocol: Protocol::Unknown(vec![]),
+        })
+    }
+}
+
+/// HTTP version used by a connection
+enum Protocol {
+    /// Not enough data was received to know if the connection starts with the HTTP/2 preface
+    Unknown(Vec<u8>),
+    Http1(Http1RequestRewriter),
+    Http2(Box<Http2RequestRewriter>),
+}
+
+/// Injects the identity headers in the HTTP requests of a connection
+struct HttpHeadersInterceptor {
+    identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
+    attributes: Arc<Vec<String>>,
+    /// Headers injected in every request, computed when the first payload is received
+    headers: Option<Vec<(String, String)>>,
+    protocol: Protocol,
+}
+
+impl HttpHeadersInterceptor {
+    async fn identity_headers(&self, local_info: &[LocalInfo]) -> Result<Vec<(String, String)>> {
+        // the payloads which are not received from a secure channel get no identity headers
+        let identifier = match IdentitySecureChannelLocalInfo::find_info_from_list(local_info) {
+            Ok(info) => info.their_identity_id(),
+            Err(_) => return Ok(vec![]),
+        };
+
+        let mut headers = vec![(IDENTIFIER_HEADER.to_string(), identifier.to_string())];
+        if let Some(entry) = self
+            .identity_attributes_repository
+            .get_attributes(&identifier)
+            .await?
+        {
+            let attributes = entry.valid_attrs()?;
+            for name in self.attributes.iter() {
+                let value = match attributes.get(name.as_bytes()) {
+                    Some(value) => value,
+                    None => continue,
+                };
+                match String::from_utf8(value.clone()) {
+                    Ok(value) if is_header_value(&value) => headers.push((
+                        format!("{ATTRIBUTE_HEADER_PREFIX}{}", name.to_ascii_lowercase()),
+                        value,
+                    )),
+                    _ => warn!(
+                        %identifier,
+                        attribute = %name,
+                        "the attribute value can't be used in an HTTP header"
+                    ),
+                }
+            }
+        }
+
+        Ok(headers)
+    }
+}
+
+#[async_trait]
+impl PortalInterceptor for HttpHeadersInterceptor {
+    async fn intercept(&mut self, local_info: &[LocalInfo], payload: Vec<u8>) -> Result<Vec<u8>> {
+        if self.headers.is_none() {
+            self.headers = Some(self.identity_headers(local_info).await?);
+        }
+
+        let mut payload = payload;
+        if let Protocol::Unknown(buffer) = &mut self.protocol {
+            buffer.extend_from_slice(&payload);
+            let length = buffer.len().min(PREFACE.len());
+            let protocol = if buffer[..length] != PREFACE[..length] {
+                Protocol::Http1(Http1RequestRewriter::new())
+            } else if length == PREFACE.len() {
+                Protocol::Http2(Box::new(Http2RequestRewriter::new()))
+            } else {
+                return Ok(vec![]);
+            };
+            payload = core::mem::take(buffer);
+            self.protocol = protocol;
+        }
+
+        let headers = self.headers.as_deref().unwrap_or_default();
+        match &mut self.protocol {
+            Protocol::Http1(rewriter) => rewriter.rewrite(&payload, headers),
+            Protocol::Http2(rewriter) => rewriter.rewrite(&payload, headers),
+            Protocol::Unknown(_) => Ok(vec![]),
+        }
+    }
+}
+
+/// Return true if the value only contains visible characters and spaces
+fn is_header_value(value: &str) -> bool {
+    value
+        .bytes()
+        .all(|b| b == b' ' || b == b'\t' || b.is_ascii_graphic())
+}
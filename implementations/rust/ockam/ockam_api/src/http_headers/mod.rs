@@ -0,0 +1,50 @@
+//! This module injects the identity of the peer of a TCP outlet in the HTTP
+//! requests sent to its target, so that a web application can authorize each
+//! request without speaking Ockam.
+//!
+//! The headers are added on the outlet side, where the peer was authenticated by
+//! a secure channel, and any header with the same prefix sent by the client is
+//! removed, so they can't be forged:
+//!
+//!  - `x-ockam-identifier` contains the identifier of the peer
+//!  - `x-ockam-attribute-<name>` contains the value of each selected attribute
+//!    of the peer credential
+//!
+//! Both HTTP/1.1 and HTTP/2 with prior knowledge are supported. After an
+//! HTTP/1.1 connection is upgraded, for example to a WebSocket, its data is
+//! forwarded unchanged. The requests sent over TLS can't be modified, the TLS
+//! connection must then be terminated before the outlet.
+
+mod http1;
+mod http2;
+mod interceptor;
+
+pub use interceptor::HttpHeadersInterceptorFactory;
+
+use ockam_core::errcode::{Kind, Origin};
+
+/// Header carrying the identifier of the peer
+pub const IDENTIFIER_HEADER: &str = "x-ockam-identifier";
+
+/// Prefix of the headers carrying the attributes of the peer
+pub const ATTRIBUTE_HEADER_PREFIX: &str = "x-ockam-attribute-";
+
+/// Prefix of all the headers which are removed from the requests
+const RESERVED_HEADER_PREFIX: &[u8] = b"x-ockam-";
+
+fn is_reserved_header(name: &[u8]) -> bool {
+    name.len() >= RESERVED_HEADER_PREFIX.len()
+        && name[..RESERVED_HEADER_PREFIX.len()].eq_ignore_ascii_case(RESERVED_HEADER_PREFIX)
+}
+
+/// Return true if the value is a valid header name, or method, as defined in RFC 9110
+fn is_token(value: &[u8]) -> bool {
+    !value.is_empty()
+        && value
+            .iter()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(b))
+}
+
+fn protocol_error(message: &str) -> ockam_core::Error {
+    ockam_core::Error::new(Origin::Api, Kind::Protocol, message)
+}
@@ -103,6 +103,8 @@ mod test {
             inlet_controller,
             secure_channel_controller.into_trait(),
             listener_address,
+            None,
+            Default::default(),
         )
         .await?;
 
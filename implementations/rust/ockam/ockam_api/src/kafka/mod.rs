@@ -9,15 +9,22 @@ mod outlet_service;
 mod portal_listener;
 mod portal_worker;
 mod protocol_aware;
+mod sasl;
+mod schema_registry;
 mod secure_channel_map;
+mod topic_policy;
 
 pub(crate) use inlet_controller::KafkaInletController;
 use ockam_core::Address;
 pub(crate) use outlet_service::prefix_relay::PrefixRelayService;
 pub(crate) use outlet_service::OutletManagerService;
 pub(crate) use portal_listener::KafkaPortalListener;
+pub(crate) use sasl::KafkaSaslAuthenticator;
+pub use sasl::KafkaSaslMechanism;
+pub(crate) use schema_registry::KafkaSchemaRegistry;
 pub(crate) use secure_channel_map::ConsumerNodeAddr;
 pub(crate) use secure_channel_map::KafkaSecureChannelControllerImpl;
+pub use topic_policy::{KafkaTopicEncryption, KafkaTopicPolicy};
 
 pub const KAFKA_OUTLET_CONSUMERS: &str = "kafka_consumers";
 pub const KAFKA_OUTLET_INTERCEPTOR_ADDRESS: &str = "kafka_interceptor";
@@ -1,5 +1,6 @@
 use crate::kafka::kafka_outlet_address;
-use crate::nodes::models::portal::{CreateOutlet, OutletStatus};
+use crate::nodes::models::portal::{CreateOutlet, OutletStatus, OutletTls};
+use crate::nodes::models::services::KafkaSaslCredentials;
 use crate::nodes::NODEMANAGER_ADDR;
 use minicbor::Decoder;
 use ockam::compat::tokio::sync::Mutex;
@@ -10,7 +11,8 @@ use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{route, Error};
 use ockam_core::{Address, Result};
 use ockam_node::Context;
-use std::net::SocketAddr;
+use ockam_transport_tcp::resolve_peer;
+use std::net::{IpAddr, SocketAddr};
 
 type BrokerId = i32;
 
@@ -25,13 +27,21 @@ pub(crate) struct KafkaOutletController {
 #[derive(Debug)]
 struct KafkaOutletMapInner {
     broker_map: HashMap<BrokerId, SocketAddr>,
+    tls: Option<OutletTls>,
+    sasl: Option<KafkaSaslCredentials>,
 }
 
 impl KafkaOutletController {
-    pub(crate) fn new() -> KafkaOutletController {
+    /// The TLS settings and the SASL credentials, if any, are used by every broker outlet
+    pub(crate) fn new(
+        tls: Option<OutletTls>,
+        sasl: Option<KafkaSaslCredentials>,
+    ) -> KafkaOutletController {
         Self {
             inner: Arc::new(Mutex::new(KafkaOutletMapInner {
                 broker_map: HashMap::new(),
+                tls,
+                sasl,
             })),
         }
     }
@@ -43,17 +53,27 @@ impl KafkaOutletController {
         &self,
         context: &Context,
         broker_id: BrokerId,
-        socket_addr: SocketAddr,
+        host: &str,
+        port: i32,
     ) -> Result<Address> {
         let outlet_address = kafka_outlet_address(broker_id);
         let mut inner = self.inner.lock().await;
         if !inner.broker_map.contains_key(&broker_id) {
-            let socket_address = Self::request_outlet_creation(
-                context,
-                socket_addr,
-                kafka_outlet_address(broker_id),
-            )
-            .await?;
+            let socket_addr = resolve_peer(format!("{host}:{port}"))?;
+            // keep the host name of the broker to verify its TLS certificate
+            let hostname = match host.parse::<IpAddr>() {
+                Ok(_) => None,
+                Err(_) => Some(host.to_string()),
+            };
+            let create_outlet =
+                CreateOutlet::new(socket_addr, kafka_outlet_address(broker_id), None, false)
+                    .with_tls(inner.tls.clone())
+                    .with_kafka_sasl(inner.sasl.clone());
+            let create_outlet = match hostname {
+                Some(hostname) => create_outlet.with_hostname(hostname),
+                None => create_outlet,
+            };
+            let socket_address = Self::request_outlet_creation(context, create_outlet).await?;
             inner.broker_map.insert(broker_id, socket_address);
         }
         Ok(outlet_address)
@@ -61,20 +81,12 @@ impl KafkaOutletController {
 
     async fn request_outlet_creation(
         context: &Context,
-        socket_address: SocketAddr,
-        worker_address: Address,
+        create_outlet: CreateOutlet,
     ) -> Result<SocketAddr> {
         let buffer: Vec<u8> = context
             .send_and_receive(
                 route![NODEMANAGER_ADDR],
-                Request::post("/node/outlet")
-                    .body(CreateOutlet::new(
-                        socket_address,
-                        worker_address,
-                        None,
-                        false,
-                    ))
-                    .to_vec()?,
+                Request::post("/node/outlet").body(create_outlet).to_vec()?,
             )
             .await?;
 
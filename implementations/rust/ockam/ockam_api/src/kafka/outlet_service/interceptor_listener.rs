@@ -2,6 +2,8 @@ use crate::kafka::outlet_controller::KafkaOutletController;
 use crate::kafka::portal_worker::KafkaPortalWorker;
 use crate::kafka::protocol_aware::OutletInterceptorImpl;
 use crate::kafka::{KAFKA_OUTLET_BOOTSTRAP_ADDRESS, KAFKA_OUTLET_INTERCEPTOR_ADDRESS};
+use crate::nodes::models::portal::OutletTls;
+use crate::nodes::models::services::KafkaSaslCredentials;
 use ockam::identity::{SecureChannels, TRUST_CONTEXT_ID_UTF8};
 use ockam::{Any, Context, Result, Routed, Worker};
 use ockam_abac::AbacAccessControl;
@@ -28,6 +30,8 @@ impl OutletManagerService {
         secure_channels: Arc<SecureChannels>,
         trust_context_id: &str,
         default_secure_channel_listener_flow_control_id: FlowControlId,
+        tls: Option<OutletTls>,
+        sasl: Option<KafkaSaslCredentials>,
     ) -> Result<()> {
         let flow_controls = context.flow_controls();
 
@@ -46,7 +50,7 @@ impl OutletManagerService {
         flow_controls.add_consumer(KAFKA_OUTLET_BOOTSTRAP_ADDRESS, &flow_control_id);
 
         let worker = OutletManagerService {
-            outlet_controller: KafkaOutletController::new(),
+            outlet_controller: KafkaOutletController::new(tls, sasl),
             incoming_access_control: Arc::new(AbacAccessControl::create(
                 secure_channels
                     .identities()
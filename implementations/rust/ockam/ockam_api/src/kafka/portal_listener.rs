@@ -6,7 +6,9 @@ use tracing::trace;
 use crate::kafka::inlet_controller::KafkaInletController;
 use crate::kafka::portal_worker::KafkaPortalWorker;
 use crate::kafka::protocol_aware::TopicUuidMap;
+use crate::kafka::schema_registry::KafkaSchemaRegistry;
 use crate::kafka::secure_channel_map::KafkaSecureChannelController;
+use crate::kafka::topic_policy::KafkaTopicPolicy;
 
 ///First point of ingress of kafka connections, at the first message it spawns new stateful workers
 /// to take care of the connection.
@@ -14,6 +16,8 @@ pub(crate) struct KafkaPortalListener {
     inlet_controller: KafkaInletController,
     secure_channel_controller: Arc<dyn KafkaSecureChannelController>,
     uuid_to_name: TopicUuidMap,
+    schema_registry: Option<KafkaSchemaRegistry>,
+    topic_policy: KafkaTopicPolicy,
 }
 
 #[ockam::worker]
@@ -48,6 +52,8 @@ impl Worker for KafkaPortalListener {
             self.secure_channel_controller.clone(),
             self.uuid_to_name.clone(),
             self.inlet_controller.clone(),
+            self.schema_registry.clone(),
+            self.topic_policy.clone(),
             None,
             flow_control_id,
             route![inlet_responder_address],
@@ -79,6 +85,8 @@ impl KafkaPortalListener {
         inlet_controller: KafkaInletController,
         secure_channel_controller: Arc<dyn KafkaSecureChannelController>,
         listener_address: Address,
+        schema_registry: Option<KafkaSchemaRegistry>,
+        topic_policy: KafkaTopicPolicy,
     ) -> ockam_core::Result<()> {
         context
             .start_worker(
@@ -87,6 +95,8 @@ impl KafkaPortalListener {
                     inlet_controller,
                     secure_channel_controller,
                     uuid_to_name: Default::default(),
+                    schema_registry,
+                    topic_policy,
                 },
             )
             .await
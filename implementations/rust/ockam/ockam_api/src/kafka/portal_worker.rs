@@ -15,7 +15,9 @@ use ockam_transport_tcp::{PortalMessage, MAX_PAYLOAD_SIZE};
 use crate::kafka::inlet_controller::KafkaInletController;
 use crate::kafka::length_delimited::{length_encode, KafkaMessageDecoder};
 use crate::kafka::protocol_aware::{InletInterceptorImpl, KafkaMessageInterceptor, TopicUuidMap};
+use crate::kafka::schema_registry::KafkaSchemaRegistry;
 use crate::kafka::secure_channel_map::KafkaSecureChannelController;
+use crate::kafka::topic_policy::KafkaTopicPolicy;
 use crate::kafka::KAFKA_OUTLET_BOOTSTRAP_ADDRESS;
 
 ///by default kafka supports up to 1MB messages, 16MB is the maximum suggested
@@ -391,6 +393,8 @@ impl KafkaPortalWorker {
         secure_channel_controller: Arc<dyn KafkaSecureChannelController>,
         uuid_to_name: TopicUuidMap,
         inlet_map: KafkaInletController,
+        schema_registry: Option<KafkaSchemaRegistry>,
+        topic_policy: KafkaTopicPolicy,
         max_kafka_message_size: Option<u32>,
         flow_control_id: Option<FlowControlId>,
         inlet_responder_route: Route,
@@ -399,6 +403,8 @@ impl KafkaPortalWorker {
             secure_channel_controller,
             uuid_to_name,
             inlet_map,
+            schema_registry,
+            topic_policy,
         ));
 
         let requests_worker_address = Address::random_tagged("KafkaPortalWorker.requests");
@@ -754,6 +760,8 @@ mod test {
             secure_channel_controller,
             Default::default(),
             inlet_map,
+            None,
+            Default::default(),
             Some(TEST_MAX_KAFKA_MESSAGE_SIZE),
             None,
             route![context.address()],
@@ -817,6 +825,8 @@ mod test {
             Default::default(),
             inlet_map.clone(),
             None,
+            Default::default(),
+            None,
             None,
             route![context.address()],
         )
@@ -1,5 +1,7 @@
 use crate::kafka::portal_worker::InterceptError;
+use crate::kafka::schema_registry::KafkaSchemaRegistry;
 use crate::kafka::secure_channel_map::KafkaSecureChannelController;
+use crate::kafka::topic_policy::KafkaTopicPolicy;
 use crate::kafka::KafkaInletController;
 use bytes::BytesMut;
 use kafka_protocol::messages::ApiKey;
@@ -53,6 +55,8 @@ pub(crate) struct InletInterceptorImpl {
     uuid_to_name: TopicUuidMap,
     secure_channel_controller: Arc<dyn KafkaSecureChannelController>,
     inlet_map: KafkaInletController,
+    schema_registry: Option<KafkaSchemaRegistry>,
+    topic_policy: KafkaTopicPolicy,
 }
 
 #[async_trait]
@@ -80,7 +84,9 @@ impl KafkaMessageInterceptor for InletInterceptorImpl {
 ///Wraps the content within every record batch
 struct MessageWrapper {
     #[n(1)] consumer_decryptor_address: Address,
-    #[n(2)] content: Vec<u8>
+    #[n(2)] content: Vec<u8>,
+    /// Schema id of the records in the Confluent wire format, only the payload being encrypted
+    #[n(3)] schema_id: Option<i32>,
 }
 
 impl InletInterceptorImpl {
@@ -88,12 +94,16 @@ impl InletInterceptorImpl {
         secure_channel_controller: Arc<dyn KafkaSecureChannelController>,
         uuid_to_name: TopicUuidMap,
         inlet_map: KafkaInletController,
+        schema_registry: Option<KafkaSchemaRegistry>,
+        topic_policy: KafkaTopicPolicy,
     ) -> InletInterceptorImpl {
         Self {
             request_map: Arc::new(Mutex::new(Default::default())),
             uuid_to_name,
             secure_channel_controller,
             inlet_map,
+            schema_registry,
+            topic_policy,
         }
     }
 }
@@ -17,6 +17,8 @@ use tracing::warn;
 use crate::kafka::portal_worker::InterceptError;
 use crate::kafka::protocol_aware::utils::{decode_body, encode_request};
 use crate::kafka::protocol_aware::{InletInterceptorImpl, MessageWrapper, RequestInfo};
+use crate::kafka::schema_registry::split_wire_format;
+use crate::kafka::topic_policy::KafkaTopicEncryption;
 
 impl InletInterceptorImpl {
     ///Parse request and map request <=> response
@@ -128,6 +130,16 @@ impl InletInterceptorImpl {
                     })?
             };
 
+            match self.topic_policy.encryption(&topic_id) {
+                KafkaTopicEncryption::Encrypt => {}
+                // the records are read in plaintext, no relay is needed to decrypt them
+                KafkaTopicEncryption::Passthrough => continue,
+                KafkaTopicEncryption::Deny => {
+                    warn!("fetching the topic {topic_id} is denied! closing connection");
+                    return Err(InterceptError::Io(Error::from(ErrorKind::PermissionDenied)));
+                }
+            }
+
             let partitions: Vec<i32> = topic
                 .partitions
                 .iter()
@@ -162,6 +174,15 @@ impl InletInterceptorImpl {
         //for each we wrap the content and add the secure channel identifier of
         //the encrypted content
         for (topic_name, topic) in request.topic_data.iter_mut() {
+            match self.topic_policy.encryption(topic_name) {
+                KafkaTopicEncryption::Encrypt => {}
+                KafkaTopicEncryption::Passthrough => continue,
+                KafkaTopicEncryption::Deny => {
+                    warn!("producing to the topic {topic_name:?} is denied! closing connection");
+                    return Err(InterceptError::Io(Error::from(ErrorKind::PermissionDenied)));
+                }
+            }
+
             for data in &mut topic.partition_data {
                 if let Some(content) = data.records.take() {
                     let mut content = BytesMut::from(content.as_ref());
@@ -170,13 +191,23 @@ impl InletInterceptorImpl {
 
                     for record in records.iter_mut() {
                         if let Some(record_value) = record.value.take() {
+                            // with the Confluent wire format, only the payload is encrypted
+                            let (schema_id, payload) = match self
+                                .schema_registry
+                                .as_ref()
+                                .and_then(|_| split_wire_format(&record_value))
+                            {
+                                Some((schema_id, payload)) => (Some(schema_id), payload),
+                                None => (None, record_value.as_ref()),
+                            };
+
                             let encrypted_content = self
                                 .secure_channel_controller
                                 .encrypt_content_for(
                                     context,
                                     topic_name,
                                     data.index,
-                                    record_value.to_vec(),
+                                    payload.to_vec(),
                                 )
                                 .await
                                 .map_err(InterceptError::Ockam)?;
@@ -187,6 +218,7 @@ impl InletInterceptorImpl {
                                 consumer_decryptor_address: encrypted_content
                                     .consumer_decryptor_address,
                                 content: encrypted_content.content,
+                                schema_id,
                             };
 
                             let mut write_buffer = Vec::with_capacity(1024);
@@ -195,6 +227,14 @@ impl InletInterceptorImpl {
                                 InterceptError::Io(Error::from(ErrorKind::InvalidData))
                             })?;
 
+                            let write_buffer = match (&self.schema_registry, schema_id) {
+                                (Some(schema_registry), Some(schema_id)) => schema_registry
+                                    .encode_encrypted(topic_name, schema_id, write_buffer)
+                                    .await
+                                    .map_err(InterceptError::Ockam)?,
+                                _ => write_buffer,
+                            };
+
                             record.value = Some(write_buffer.into());
                         }
                     }
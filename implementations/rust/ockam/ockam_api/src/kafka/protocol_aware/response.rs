@@ -20,6 +20,8 @@ use crate::kafka::inlet_controller::KafkaInletController;
 use crate::kafka::portal_worker::InterceptError;
 use crate::kafka::protocol_aware::utils::{decode_body, encode_response, string_to_str_bytes};
 use crate::kafka::protocol_aware::{InletInterceptorImpl, MessageWrapper, RequestInfo};
+use crate::kafka::schema_registry::{join_wire_format, split_wire_format};
+use crate::kafka::topic_policy::KafkaTopicEncryption;
 
 impl InletInterceptorImpl {
     pub(crate) async fn intercept_response_impl(
@@ -222,6 +224,25 @@ impl InletInterceptorImpl {
         //we take every record batch content, unwrap and decode it
         //using the relative secure channel
         for response in response.responses.iter_mut() {
+            let topic_name = if request_info.request_api_version <= 12 {
+                response.topic.0.to_string()
+            } else {
+                let topic_id = response.topic_id.to_string();
+                self.uuid_to_name
+                    .lock()
+                    .unwrap()
+                    .get(&topic_id)
+                    .cloned()
+                    .ok_or_else(|| {
+                        warn!("missing map from uuid {topic_id} to name");
+                        InterceptError::Io(Error::from(ErrorKind::InvalidData))
+                    })?
+            };
+            // only the encrypted topics can be fetched through a relay
+            if self.topic_policy.encryption(&topic_name) != KafkaTopicEncryption::Encrypt {
+                continue;
+            }
+
             for partition in response.partitions.iter_mut() {
                 if let Some(content) = partition.records.take() {
                     let mut content = BytesMut::from(content.as_ref());
@@ -230,8 +251,20 @@ impl InletInterceptorImpl {
 
                     for record in records.iter_mut() {
                         if let Some(record_value) = record.value.take() {
+                            // records in the Confluent wire format only have an encrypted payload
+                            let encrypted_payload = match &self.schema_registry {
+                                Some(schema_registry) => schema_registry
+                                    .decode_encrypted(&record_value)
+                                    .await
+                                    .map_err(InterceptError::Ockam)?,
+                                None => {
+                                    split_wire_format(&record_value).map(|(_, payload)| payload)
+                                }
+                            }
+                            .unwrap_or(record_value.as_ref());
+
                             let message_wrapper: MessageWrapper =
-                                Decoder::new(record_value.as_ref()).decode().map_err(|_| {
+                                Decoder::new(encrypted_payload).decode().map_err(|_| {
                                     InterceptError::Io(Error::from(ErrorKind::InvalidData))
                                 })?;
 
@@ -245,6 +278,11 @@ impl InletInterceptorImpl {
                                 .await
                                 .map_err(InterceptError::Ockam)?;
 
+                            let decrypted_content = match message_wrapper.schema_id {
+                                Some(schema_id) => join_wire_format(schema_id, &decrypted_content),
+                                None => decrypted_content,
+                            };
+
                             record.value = Some(decrypted_content.into());
                         }
                     }
@@ -70,6 +70,8 @@ mod test {
             Arc::new(DummySecureChannelController {}),
             Default::default(),
             inlet_map,
+            None,
+            Default::default(),
         );
 
         let mut correlation_id = 0;
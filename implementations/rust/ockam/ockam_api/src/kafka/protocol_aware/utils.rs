@@ -54,7 +54,7 @@ pub(crate) fn encode_response<H: Encodable, T: Encodable>(
     Ok(buffer)
 }
 
-pub(super) fn string_to_str_bytes(ip_address: String) -> StrBytes {
+pub(crate) fn string_to_str_bytes(ip_address: String) -> StrBytes {
     //TryFrom is broken, ugly but effective
     unsafe { StrBytes::from_utf8_unchecked(bytes::Bytes::from(ip_address)) }
 }
@@ -0,0 +1,454 @@
+use std::fmt::{Debug, Formatter};
+use std::str::FromStr;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use bytes::{Buf, Bytes};
+use hmac::{Hmac, Mac};
+use kafka_protocol::messages::{
+    ApiKey, RequestHeader, ResponseHeader, SaslAuthenticateRequest, SaslAuthenticateResponse,
+    SaslHandshakeRequest, SaslHandshakeResponse,
+};
+use kafka_protocol::protocol::{Builder, Decodable, Encodable};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use sha2::{Digest, Sha256, Sha512};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{async_trait, Error, Result};
+use ockam_transport_tcp::TcpOutletConnectionInitializer;
+
+use crate::kafka::portal_worker::MAX_KAFKA_MESSAGE_SIZE;
+use crate::kafka::protocol_aware::utils::{encode_request, string_to_str_bytes};
+
+const SASL_HANDSHAKE_VERSION: i16 = 1;
+const SASL_AUTHENTICATE_VERSION: i16 = 1;
+const CLIENT_ID: &str = "ockam-kafka-outlet";
+
+/// SASL mechanisms supported to authenticate to the Kafka brokers
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KafkaSaslMechanism {
+    Plain,
+    ScramSha256,
+    ScramSha512,
+}
+
+impl KafkaSaslMechanism {
+    pub fn name(&self) -> &'static str {
+        match self {
+            KafkaSaslMechanism::Plain => "PLAIN",
+            KafkaSaslMechanism::ScramSha256 => "SCRAM-SHA-256",
+            KafkaSaslMechanism::ScramSha512 => "SCRAM-SHA-512",
+        }
+    }
+}
+
+impl FromStr for KafkaSaslMechanism {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_uppercase().as_str() {
+            "PLAIN" => Ok(KafkaSaslMechanism::Plain),
+            "SCRAM-SHA-256" => Ok(KafkaSaslMechanism::ScramSha256),
+            "SCRAM-SHA-512" => Ok(KafkaSaslMechanism::ScramSha512),
+            _ => Err(sasl_error(format!(
+                "unsupported SASL mechanism {s}, expected PLAIN, SCRAM-SHA-256 or SCRAM-SHA-512"
+            ))),
+        }
+    }
+}
+
+/// Authenticates every connection of a Kafka outlet to the brokers with the
+/// credentials of the outlet, instead of the credentials of the Kafka clients.
+///
+/// The clients then connect to the inlet without SASL, since they are already
+/// authenticated by their secure channel.
+pub(crate) struct KafkaSaslAuthenticator {
+    mechanism: KafkaSaslMechanism,
+    username: String,
+    password: String,
+}
+
+impl Debug for KafkaSaslAuthenticator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KafkaSaslAuthenticator")
+            .field("mechanism", &self.mechanism)
+            .field("username", &self.username)
+            .finish()
+    }
+}
+
+impl KafkaSaslAuthenticator {
+    pub(crate) fn new(mechanism: KafkaSaslMechanism, username: String, password: String) -> Self {
+        Self {
+            mechanism,
+            username,
+            password,
+        }
+    }
+}
+
+#[async_trait]
+impl TcpOutletConnectionInitializer for KafkaSaslAuthenticator {
+    async fn initialize(
+        &self,
+        reader: &mut (dyn AsyncRead + Send + Unpin),
+        writer: &mut (dyn AsyncWrite + Send + Unpin),
+    ) -> Result<()> {
+        let mut connection = KafkaConnection {
+            reader,
+            writer,
+            correlation_id: 0,
+        };
+
+        let handshake = SaslHandshakeRequest::builder()
+            .mechanism(string_to_str_bytes(self.mechanism.name().to_string()))
+            .unknown_tagged_fields(Default::default())
+            .build()
+            .map_err(build_error)?;
+        let response: SaslHandshakeResponse = connection
+            .send(ApiKey::SaslHandshakeKey, SASL_HANDSHAKE_VERSION, &handshake)
+            .await?;
+        if response.error_code != 0 {
+            let enabled: Vec<String> = response.mechanisms.iter().map(|m| m.to_string()).collect();
+            return Err(sasl_error(format!(
+                "the broker doesn't support the {} mechanism, enabled mechanisms: {}",
+                self.mechanism.name(),
+                enabled.join(", ")
+            )));
+        }
+
+        match self.mechanism {
+            KafkaSaslMechanism::Plain => {
+                let message = format!("\0{}\0{}", self.username, self.password);
+                connection.authenticate(message.into_bytes()).await?;
+            }
+            KafkaSaslMechanism::ScramSha256 | KafkaSaslMechanism::ScramSha512 => {
+                let mut scram = ScramClient::new(
+                    self.mechanism,
+                    &self.username,
+                    &self.password,
+                    rand::thread_rng()
+                        .sample_iter(&Alphanumeric)
+                        .take(24)
+                        .map(char::from)
+                        .collect(),
+                );
+                let server_first = connection
+                    .authenticate(scram.client_first().into_bytes())
+                    .await?;
+                let client_final = scram.client_final(&utf8(server_first)?)?;
+                let server_final = connection.authenticate(client_final.into_bytes()).await?;
+                scram.verify_server_final(&utf8(server_final)?)?;
+            }
+        }
+
+        debug!(
+            "authenticated to the Kafka broker as {} with {}",
+            self.username,
+            self.mechanism.name()
+        );
+        Ok(())
+    }
+}
+
+/// Connection to a broker, used to send requests before the portal starts forwarding data
+struct KafkaConnection<'a> {
+    reader: &'a mut (dyn AsyncRead + Send + Unpin),
+    writer: &'a mut (dyn AsyncWrite + Send + Unpin),
+    correlation_id: i32,
+}
+
+impl KafkaConnection<'_> {
+    /// Send a SaslAuthenticate request and return the authentication bytes of the response
+    async fn authenticate(&mut self, auth_bytes: Vec<u8>) -> Result<Bytes> {
+        let request = SaslAuthenticateRequest::builder()
+            .auth_bytes(Bytes::from(auth_bytes))
+            .unknown_tagged_fields(Default::default())
+            .build()
+            .map_err(build_error)?;
+        let response: SaslAuthenticateResponse = self
+            .send(
+                ApiKey::SaslAuthenticateKey,
+                SASL_AUTHENTICATE_VERSION,
+                &request,
+            )
+            .await?;
+
+        if response.error_code != 0 {
+            let message = response
+                .error_message
+                .as_ref()
+                .map(|m| m.to_string())
+                .unwrap_or_default();
+            return Err(sasl_error(format!(
+                "the broker rejected the SASL authentication (error code {}): {message}",
+                response.error_code
+            )));
+        }
+        Ok(response.auth_bytes)
+    }
+
+    async fn send<T: Encodable + Sync, R: Decodable + Send>(
+        &mut self,
+        api_key: ApiKey,
+        api_version: i16,
+        request: &T,
+    ) -> Result<R> {
+        self.correlation_id += 1;
+        let header = RequestHeader::builder()
+            .request_api_key(api_key as i16)
+            .request_api_version(api_version)
+            .correlation_id(self.correlation_id)
+            .client_id(Some(string_to_str_bytes(CLIENT_ID.to_string())))
+            .unknown_tagged_fields(Default::default())
+            .build()
+            .map_err(build_error)?;
+
+        let request = encode_request(&header, request, api_version, api_key)
+            .map_err(|_| sasl_error(format!("cannot encode the {api_key:?} request")))?;
+        self.writer
+            .write_all(&(request.len() as u32).to_be_bytes())
+            .await
+            .map_err(io_error)?;
+        self.writer.write_all(&request).await.map_err(io_error)?;
+        self.writer.flush().await.map_err(io_error)?;
+
+        let length = self.reader.read_u32().await.map_err(io_error)?;
+        if length > MAX_KAFKA_MESSAGE_SIZE {
+            return Err(sasl_error(format!(
+                "the {api_key:?} response is too large: {length} bytes"
+            )));
+        }
+        let mut response = vec![0u8; length as usize];
+        self.reader
+            .read_exact(&mut response)
+            .await
+            .map_err(io_error)?;
+
+        let mut response = Bytes::from(response);
+        let header =
+            ResponseHeader::decode(&mut response, api_key.response_header_version(api_version))
+                .map_err(|_| {
+                    sasl_error(format!("cannot decode the {api_key:?} response header"))
+                })?;
+        if header.correlation_id != self.correlation_id {
+            return Err(sasl_error(format!(
+                "unexpected correlation id {} for the {api_key:?} response",
+                header.correlation_id
+            )));
+        }
+
+        let body = R::decode(&mut response, api_version)
+            .map_err(|_| sasl_error(format!("cannot decode the {api_key:?} response")))?;
+        if response.has_remaining() {
+            warn!("ignoring the unexpected trailing bytes of the {api_key:?} response");
+        }
+        Ok(body)
+    }
+}
+
+/// Client side of a SCRAM authentication, as specified in RFC 5802
+struct ScramClient {
+    mechanism: KafkaSaslMechanism,
+    password: String,
+    nonce: String,
+    client_first_bare: String,
+    server_signature: Option<Vec<u8>>,
+}
+
+impl ScramClient {
+    fn new(mechanism: KafkaSaslMechanism, username: &str, password: &str, nonce: String) -> Self {
+        let username = username.replace('=', "=3D").replace(',', "=2C");
+        Self {
+            mechanism,
+            password: password.to_string(),
+            client_first_bare: format!("n={username},r={nonce}"),
+            nonce,
+            server_signature: None,
+        }
+    }
+
+    fn client_first(&self) -> String {
+        format!("n,,{}", self.client_first_bare)
+    }
+
+    fn client_final(&mut self, server_first: &str) -> Result<String> {
+        let mut nonce = None;
+        let mut salt = None;
+        let mut iterations = None;
+        for attribute in server_first.split(',') {
+            match attribute.split_once('=') {
+                Some(("r", value)) => nonce = Some(value),
+                Some(("s", value)) => salt = BASE64.decode(value).ok(),
+                Some(("i", value)) => iterations = value.parse::<u32>().ok(),
+                _ => {}
+            }
+        }
+        let (nonce, salt, iterations) = match (nonce, salt, iterations) {
+            (Some(nonce), Some(salt), Some(iterations)) => (nonce, salt, iterations),
+            _ => return Err(sasl_error("invalid SCRAM server first message")),
+        };
+        if !nonce.starts_with(&self.nonce) || nonce.len() == self.nonce.len() {
+            return Err(sasl_error("invalid SCRAM server nonce"));
+        }
+
+        let salted_password = self.salted_password(&salt, iterations);
+        let client_key = self.hmac(&salted_password, b"Client Key");
+        let stored_key = self.hash(&client_key);
+        let client_final_without_proof = format!("c=biws,r={nonce}");
+        let auth_message = format!(
+            "{},{server_first},{client_final_without_proof}",
+            self.client_first_bare
+        );
+        let client_signature = self.hmac(&stored_key, auth_message.as_bytes());
+        let proof: Vec<u8> = client_key
+            .iter()
+            .zip(client_signature.iter())
+            .map(|(k, s)| k ^ s)
+            .collect();
+
+        let server_key = self.hmac(&salted_password, b"Server Key");
+        self.server_signature = Some(self.hmac(&server_key, auth_message.as_bytes()));
+
+        Ok(format!(
+            "{client_final_without_proof},p={}",
+            BASE64.encode(proof)
+        ))
+    }
+
+    fn verify_server_final(&self, server_final: &str) -> Result<()> {
+        if let Some(error) = server_final.strip_prefix("e=") {
+            return Err(sasl_error(format!("SCRAM authentication failed: {error}")));
+        }
+        let signature = server_final
+            .split(',')
+            .find_map(|attribute| attribute.strip_prefix("v="))
+            .and_then(|signature| BASE64.decode(signature).ok());
+        match (signature, &self.server_signature) {
+            (Some(signature), Some(expected)) if &signature == expected => Ok(()),
+            _ => Err(sasl_error("invalid SCRAM server signature")),
+        }
+    }
+
+    fn salted_password(&self, salt: &[u8], iterations: u32) -> Vec<u8> {
+        let password = self.password.as_bytes();
+        match self.mechanism {
+            KafkaSaslMechanism::ScramSha512 => {
+                let mut output = [0u8; 64];
+                pbkdf2::pbkdf2_hmac::<Sha512>(password, salt, iterations, &mut output);
+                output.to_vec()
+            }
+            _ => {
+                let mut output = [0u8; 32];
+                pbkdf2::pbkdf2_hmac::<Sha256>(password, salt, iterations, &mut output);
+                output.to_vec()
+            }
+        }
+    }
+
+    fn hmac(&self, key: &[u8], data: &[u8]) -> Vec<u8> {
+        match self.mechanism {
+            KafkaSaslMechanism::ScramSha512 => {
+                let mut mac =
+                    Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts keys of any size");
+                mac.update(data);
+                mac.finalize().into_bytes().to_vec()
+            }
+            _ => {
+                let mut mac =
+                    Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+                mac.update(data);
+                mac.finalize().into_bytes().to_vec()
+            }
+        }
+    }
+
+    fn hash(&self, data: &[u8]) -> Vec<u8> {
+        match self.mechanism {
+            KafkaSaslMechanism::ScramSha512 => Sha512::digest(data).to_vec(),
+            _ => Sha256::digest(data).to_vec(),
+        }
+    }
+}
+
+fn utf8(bytes: Bytes) -> Result<String> {
+    String::from_utf8(bytes.to_vec()).map_err(|_| sasl_error("invalid SCRAM server message"))
+}
+
+fn build_error(e: impl std::fmt::Display) -> Error {
+    sasl_error(format!("cannot build a Kafka request: {e}"))
+}
+
+fn io_error(e: std::io::Error) -> Error {
+    Error::new(Origin::Transport, Kind::Io, e)
+}
+
+fn sasl_error(message: impl Into<String>) -> Error {
+    Error::new(Origin::Application, Kind::Invalid, message.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test vector of RFC 7677
+    #[test]
+    fn test_scram_sha_256() -> Result<()> {
+        let mut client = ScramClient::new(
+            KafkaSaslMechanism::ScramSha256,
+            "user",
+            "pencil",
+            "rOprNGfwEbeRWgbNEkqO".to_string(),
+        );
+        assert_eq!(client.client_first(), "n,,n=user,r=rOprNGfwEbeRWgbNEkqO");
+
+        let client_final = client.client_final(
+            "r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,s=W22ZaJ0SNY7soEsUEjb6gQ==,i=4096",
+        )?;
+        assert_eq!(
+            client_final,
+            "c=biws,r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,p=dHzbZapWIk4jUhN+Ute9ytag9zjfMHgsqmmiz7AndVQ="
+        );
+
+        client.verify_server_final("v=6rriTRBi23WpRR/wtup+mMhUZUn/dB5nLTJRsjl95G4=")?;
+        assert!(client
+            .verify_server_final("v=AAAATRBi23WpRR/wtup+mMhUZUn/dB5nLTJRsjl95G4=")
+            .is_err());
+        assert!(client.verify_server_final("e=invalid-proof").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_scram_server_nonce_must_extend_client_nonce() {
+        let mut client = ScramClient::new(
+            KafkaSaslMechanism::ScramSha512,
+            "user",
+            "pencil",
+            "abc".to_string(),
+        );
+        assert!(client
+            .client_final("r=xyz,s=QSXCR+Q6sek8bf92,i=4096")
+            .is_err());
+        assert!(client
+            .client_final("r=abc,s=QSXCR+Q6sek8bf92,i=4096")
+            .is_err());
+        assert!(client
+            .client_final("r=abcdef,s=QSXCR+Q6sek8bf92,i=4096")
+            .is_ok());
+    }
+
+    #[test]
+    fn test_mechanism_names() -> Result<()> {
+        for mechanism in [
+            KafkaSaslMechanism::Plain,
+            KafkaSaslMechanism::ScramSha256,
+            KafkaSaslMechanism::ScramSha512,
+        ] {
+            assert_eq!(KafkaSaslMechanism::from_str(mechanism.name())?, mechanism);
+        }
+        assert!(KafkaSaslMechanism::from_str("GSSAPI").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,263 @@
+use ockam_core::compat::collections::HashMap;
+use ockam_core::compat::sync::{Arc, Mutex};
+use ockam_core::Result;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::error::ApiError;
+use crate::nodes::models::services::KafkaSchemaRegistryOptions;
+
+/// First byte of the record values in the Confluent wire format
+const MAGIC_BYTE: u8 = 0;
+/// The magic byte is followed by the schema id, as a big-endian 32 bits integer
+const HEADER_LENGTH: usize = 5;
+
+const ENCRYPTED_SCHEMA_NAME: &str = "OckamEncrypted";
+const ENCRYPTED_SCHEMA_NAMESPACE: &str = "io.ockam";
+const SCHEMA_REGISTRY_CONTENT_TYPE: &str = "application/vnd.schemaregistry.v1+json";
+
+/// Handles the record values written in the Confluent wire format by the clients of a
+/// schema registry: a magic byte, the id of the schema, then the payload.
+///
+/// Only the payload is encrypted, the header stays in plaintext so that the records can still
+/// be routed and inspected by the schema registry tooling.
+///
+/// When the URL of the schema registry is known, an "encrypted" variant of each schema is
+/// registered, describing the encrypted payload as Avro bytes, and its id replaces the
+/// original schema id in the header. The original schema id is restored when the record
+/// is decrypted. The producer and the consumer must then both use the schema registry.
+#[derive(Clone, Debug)]
+pub(crate) struct KafkaSchemaRegistry {
+    client: Option<SchemaRegistryClient>,
+}
+
+impl KafkaSchemaRegistry {
+    pub(crate) fn new(options: &KafkaSchemaRegistryOptions) -> Self {
+        Self {
+            client: options.url.as_deref().map(SchemaRegistryClient::new),
+        }
+    }
+
+    /// Write the encrypted payload of a record which had the given schema id
+    pub(crate) async fn encode_encrypted(
+        &self,
+        topic: &str,
+        schema_id: i32,
+        encrypted_payload: Vec<u8>,
+    ) -> Result<Vec<u8>> {
+        match &self.client {
+            Some(client) => {
+                let encrypted_schema_id = client.encrypted_schema_id(topic, schema_id).await?;
+                Ok(join_wire_format(
+                    encrypted_schema_id,
+                    &encode_avro_bytes(&encrypted_payload),
+                ))
+            }
+            None => Ok(join_wire_format(schema_id, &encrypted_payload)),
+        }
+    }
+
+    /// Return the encrypted payload of a record in the Confluent wire format,
+    /// or None if the record is not in this format
+    pub(crate) async fn decode_encrypted<'a>(&self, value: &'a [u8]) -> Result<Option<&'a [u8]>> {
+        let Some((schema_id, payload)) = split_wire_format(value) else {
+            return Ok(None);
+        };
+        match &self.client {
+            Some(client) if client.is_encrypted_schema(schema_id).await? => {
+                decode_avro_bytes(payload).map(Some).ok_or_else(|| {
+                    ApiError::core("invalid encrypted record in the Confluent wire format")
+                })
+            }
+            _ => Ok(Some(payload)),
+        }
+    }
+}
+
+/// Return the schema id and the payload of a record value in the Confluent wire format
+pub(crate) fn split_wire_format(value: &[u8]) -> Option<(i32, &[u8])> {
+    if value.len() < HEADER_LENGTH || value[0] != MAGIC_BYTE {
+        return None;
+    }
+    let schema_id = i32::from_be_bytes(value[1..HEADER_LENGTH].try_into().ok()?);
+    Some((schema_id, &value[HEADER_LENGTH..]))
+}
+
+/// Write a record value in the Confluent wire format
+pub(crate) fn join_wire_format(schema_id: i32, payload: &[u8]) -> Vec<u8> {
+    let mut value = Vec::with_capacity(HEADER_LENGTH + payload.len());
+    value.push(MAGIC_BYTE);
+    value.extend_from_slice(&schema_id.to_be_bytes());
+    value.extend_from_slice(payload);
+    value
+}
+
+/// Encode bytes as an Avro record with a single `bytes` field: a zig-zag encoded length
+/// followed by the bytes
+fn encode_avro_bytes(bytes: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(bytes.len() + 10);
+    let mut length = (bytes.len() as u64) << 1;
+    loop {
+        let byte = (length & 0x7f) as u8;
+        length >>= 7;
+        if length == 0 {
+            encoded.push(byte);
+            break;
+        }
+        encoded.push(byte | 0x80);
+    }
+    encoded.extend_from_slice(bytes);
+    encoded
+}
+
+fn decode_avro_bytes(encoded: &[u8]) -> Option<&[u8]> {
+    let mut length: u64 = 0;
+    for (index, byte) in encoded.iter().enumerate().take(10) {
+        length |= ((byte & 0x7f) as u64) << (7 * index);
+        if byte & 0x80 == 0 {
+            // a negative length is invalid
+            if length & 1 == 1 {
+                return None;
+            }
+            let bytes = &encoded[index + 1..];
+            return (bytes.len() as u64 == length >> 1).then_some(bytes);
+        }
+    }
+    None
+}
+
+/// Client of the REST API of a Confluent compatible schema registry
+#[derive(Clone, Debug)]
+struct SchemaRegistryClient {
+    url: String,
+    http: reqwest::Client,
+    /// Ids of the encrypted variants, by topic and original schema id
+    encrypted_schema_ids: Arc<Mutex<HashMap<(String, i32), i32>>>,
+    /// Schema ids which are known to be, or not to be, encrypted variants
+    is_encrypted: Arc<Mutex<HashMap<i32, bool>>>,
+}
+
+#[derive(Deserialize)]
+struct SchemaId {
+    id: i32,
+}
+
+#[derive(Deserialize)]
+struct Schema {
+    schema: String,
+}
+
+#[derive(Deserialize)]
+struct AvroSchemaName {
+    name: Option<String>,
+    namespace: Option<String>,
+}
+
+impl SchemaRegistryClient {
+    fn new(url: &str) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            http: reqwest::Client::new(),
+            encrypted_schema_ids: Default::default(),
+            is_encrypted: Default::default(),
+        }
+    }
+
+    /// Register, if needed, the encrypted variant of a schema and return its id
+    async fn encrypted_schema_id(&self, topic: &str, schema_id: i32) -> Result<i32> {
+        let key = (topic.to_string(), schema_id);
+        if let Some(id) = self.encrypted_schema_ids.lock().unwrap().get(&key) {
+            return Ok(*id);
+        }
+
+        let schema = json!({
+            "type": "record",
+            "name": ENCRYPTED_SCHEMA_NAME,
+            "namespace": ENCRYPTED_SCHEMA_NAMESPACE,
+            "doc": format!("Ockam end-to-end encrypted record of the schema {schema_id}"),
+            "fields": [{ "name": "ciphertext", "type": "bytes" }]
+        });
+        // The variants are registered under their own subject, so that they are not
+        // checked for compatibility against the schemas of the topic
+        let subject = format!("{topic}-ockam-encrypted-value");
+        let response = self
+            .http
+            .post(format!("{}/subjects/{subject}/versions", self.url))
+            .header(reqwest::header::CONTENT_TYPE, SCHEMA_REGISTRY_CONTENT_TYPE)
+            .body(json!({ "schema": schema.to_string() }).to_string())
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| ApiError::core(format!("cannot register the schema {subject}: {e}")))?;
+        let SchemaId { id } = response
+            .json()
+            .await
+            .map_err(|e| ApiError::core(format!("invalid schema registry response: {e}")))?;
+
+        debug!("registered the encrypted variant {id} of the schema {schema_id} of {topic}");
+        self.encrypted_schema_ids.lock().unwrap().insert(key, id);
+        self.is_encrypted.lock().unwrap().insert(id, true);
+        Ok(id)
+    }
+
+    /// Return true if the schema with the given id is an encrypted variant
+    async fn is_encrypted_schema(&self, schema_id: i32) -> Result<bool> {
+        if let Some(is_encrypted) = self.is_encrypted.lock().unwrap().get(&schema_id) {
+            return Ok(*is_encrypted);
+        }
+
+        let response = self
+            .http
+            .get(format!("{}/schemas/ids/{schema_id}", self.url))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| ApiError::core(format!("cannot get the schema {schema_id}: {e}")))?;
+        let Schema { schema } = response
+            .json()
+            .await
+            .map_err(|e| ApiError::core(format!("invalid schema registry response: {e}")))?;
+        // Protobuf and JSON schemas can't be encrypted variants
+        let is_encrypted = serde_json::from_str::<AvroSchemaName>(&schema)
+            .map(|s| {
+                s.name.as_deref() == Some(ENCRYPTED_SCHEMA_NAME)
+                    && s.namespace.as_deref() == Some(ENCRYPTED_SCHEMA_NAMESPACE)
+            })
+            .unwrap_or(false);
+
+        self.is_encrypted
+            .lock()
+            .unwrap()
+            .insert(schema_id, is_encrypted);
+        Ok(is_encrypted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wire_format_round_trip() {
+        let value = join_wire_format(42, b"payload");
+        assert_eq!(&value[..5], &[0, 0, 0, 0, 42]);
+        assert_eq!(split_wire_format(&value), Some((42, b"payload".as_ref())));
+
+        // CBOR maps, used by the encrypted records, never start with the magic byte
+        assert_eq!(split_wire_format(&[0xa2, 0, 0, 0, 1, 2]), None);
+        assert_eq!(split_wire_format(&[0, 0, 1]), None);
+    }
+
+    #[test]
+    fn avro_bytes_round_trip() {
+        for length in [0, 1, 63, 64, 300, 70_000] {
+            let bytes = vec![7u8; length];
+            let encoded = encode_avro_bytes(&bytes);
+            assert_eq!(decode_avro_bytes(&encoded), Some(bytes.as_slice()));
+        }
+        // 3 bytes, zig-zag encoded as 6
+        assert_eq!(encode_avro_bytes(b"abc"), b"\x06abc");
+        assert_eq!(decode_avro_bytes(b"\x06ab"), None);
+        assert_eq!(decode_avro_bytes(b"\x05abc"), None);
+    }
+}
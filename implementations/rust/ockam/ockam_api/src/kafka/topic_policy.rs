@@ -0,0 +1,181 @@
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+
+use ockam_core::compat::string::{String, ToString};
+use ockam_core::compat::vec::Vec;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Error, Result};
+
+/// How the records of a topic are handled by a Kafka inlet
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KafkaTopicEncryption {
+    /// The records are encrypted end-to-end between the producers and the consumers
+    Encrypt,
+    /// The records are sent and received in plaintext, so that they can be read
+    /// by consumers which don't use Ockam
+    Passthrough,
+    /// The records can neither be produced nor consumed: the connection is closed
+    Deny,
+}
+
+impl KafkaTopicEncryption {
+    pub fn name(&self) -> &'static str {
+        match self {
+            KafkaTopicEncryption::Encrypt => "encrypt",
+            KafkaTopicEncryption::Passthrough => "passthrough",
+            KafkaTopicEncryption::Deny => "deny",
+        }
+    }
+}
+
+impl FromStr for KafkaTopicEncryption {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "encrypt" => Ok(KafkaTopicEncryption::Encrypt),
+            "passthrough" => Ok(KafkaTopicEncryption::Passthrough),
+            "deny" => Ok(KafkaTopicEncryption::Deny),
+            _ => Err(invalid_rule(format!(
+                "unknown topic encryption {s}, expected encrypt, passthrough or deny"
+            ))),
+        }
+    }
+}
+
+impl Display for KafkaTopicEncryption {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Encryption of the topics handled by a Kafka inlet.
+///
+/// Each rule associates a topic name pattern to a [`KafkaTopicEncryption`], and is written
+/// `<pattern>=<encryption>`, for example `public.*=passthrough`. A pattern is a topic name
+/// where `*` matches any sequence of characters. The first rule matching a topic is used,
+/// and the topics which don't match any rule are encrypted.
+///
+/// Each partition of an encrypted topic is encrypted with the keys of its own secure channel,
+/// so the records of different topics never share keys.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KafkaTopicPolicy {
+    rules: Vec<(String, KafkaTopicEncryption)>,
+}
+
+impl KafkaTopicPolicy {
+    /// Create a policy encrypting all the topics
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a rule, with a lower priority than the previous ones
+    pub fn with_rule(mut self, pattern: &str, encryption: KafkaTopicEncryption) -> Result<Self> {
+        let pattern = pattern.trim();
+        if pattern.is_empty() {
+            return Err(invalid_rule("the topic pattern can't be empty"));
+        }
+        self.rules.push((pattern.to_string(), encryption));
+        Ok(self)
+    }
+
+    /// Create a policy from rules written `<pattern>=<encryption>`
+    pub fn from_rules<S: AsRef<str>>(rules: &[S]) -> Result<Self> {
+        rules.iter().try_fold(Self::new(), |policy, rule| {
+            let rule = rule.as_ref();
+            let (pattern, encryption) = rule.rsplit_once('=').ok_or_else(|| {
+                invalid_rule(format!(
+                    "invalid topic rule {rule}, expected <pattern>=<encryption>"
+                ))
+            })?;
+            policy.with_rule(pattern, encryption.parse()?)
+        })
+    }
+
+    /// Return how the records of the given topic must be handled
+    pub fn encryption(&self, topic: &str) -> KafkaTopicEncryption {
+        self.rules
+            .iter()
+            .find(|(pattern, _)| matches_pattern(pattern, topic))
+            .map(|(_, encryption)| *encryption)
+            .unwrap_or(KafkaTopicEncryption::Encrypt)
+    }
+}
+
+/// Match a topic name against a pattern where `*` matches any sequence of characters
+fn matches_pattern(pattern: &str, topic: &str) -> bool {
+    let mut parts = pattern.split('*');
+    // split always returns at least one element
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = topic.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = parts.collect();
+    let Some(last) = parts.pop() else {
+        // no wildcard
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+fn invalid_rule(message: impl Into<String>) -> Error {
+    Error::new(Origin::Application, Kind::Invalid, message.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patterns() {
+        assert!(matches_pattern("orders", "orders"));
+        assert!(!matches_pattern("orders", "orders-v2"));
+        assert!(matches_pattern("public.*", "public.events"));
+        assert!(!matches_pattern("public.*", "private.events"));
+        assert!(matches_pattern("*-audit", "payments-audit"));
+        assert!(matches_pattern("*", "anything"));
+        assert!(matches_pattern("a*b*c", "aXXbYYc"));
+        assert!(!matches_pattern("a*b*c", "aXXcYYb"));
+        assert!(!matches_pattern("ab*ba", "aba"));
+    }
+
+    #[test]
+    fn test_first_matching_rule_wins() -> Result<()> {
+        let policy = KafkaTopicPolicy::from_rules(&[
+            "public.secrets=deny",
+            "public.*=passthrough",
+            "legacy-*=PASSTHROUGH",
+        ])?;
+
+        assert_eq!(
+            policy.encryption("public.secrets"),
+            KafkaTopicEncryption::Deny
+        );
+        assert_eq!(
+            policy.encryption("public.events"),
+            KafkaTopicEncryption::Passthrough
+        );
+        assert_eq!(
+            policy.encryption("legacy-orders"),
+            KafkaTopicEncryption::Passthrough
+        );
+        assert_eq!(policy.encryption("orders"), KafkaTopicEncryption::Encrypt);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        for rule in ["orders", "=deny", "orders=drop"] {
+            assert!(
+                KafkaTopicPolicy::from_rules(&[rule]).is_err(),
+                "{rule} should be invalid"
+            );
+        }
+    }
+}
@@ -30,6 +30,7 @@ pub mod echoer;
 pub mod enroll;
 pub mod error;
 pub mod hop;
+pub mod http_headers;
 pub mod kafka;
 pub mod minicbor_url;
 pub mod nodes;
@@ -0,0 +1,350 @@
+//! Audit of the security-relevant actions of a node.
+//!
+//! The Ockam crates log an event with an `ockam::audit::*` target for each of these actions:
+//!
+//!  - `secure_channel_established`, `secure_channel_rejected`
+//!  - `credential_issued`, `credential_issuance_failed`, `credential_verified`, `credential_rejected`
+//!  - `policy_denied`
+//!  - `relay_registered`, `relay_rejected`
+//!
+//! The [`AuditLayer`] turns those events into [`AuditEvent`]s, serialized as JSON objects, and
+//! sends them to the sinks configured with the `OCKAM_AUDIT_SINKS` environment variable:
+//!
+//! ```text
+//! OCKAM_AUDIT_SINKS=file:/var/log/ockam/audit.log,syslog:127.0.0.1:514,webhook:https://soc.example.com/events
+//! ```
+//!
+//! The events are sent from a dedicated thread so that a slow sink doesn't slow down the node.
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::net::UdpSocket;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::thread;
+
+use serde::Serialize;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::Layer;
+
+/// Prefix of the targets of the audit events
+pub const AUDIT_TARGET_PREFIX: &str = "ockam::audit";
+
+/// Security-relevant action performed by a node
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+pub struct AuditEvent {
+    /// Time of the action, in the RFC 3339 format
+    pub timestamp: String,
+    /// Name of the node performing the action
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node: Option<String>,
+    /// Kind of resource concerned by the action: `secure_channels`, `credentials`, etc.
+    pub category: String,
+    /// Name of the action, for example `secure_channel_established`
+    pub event: String,
+    /// Description of the action
+    pub message: String,
+    /// Other fields of the event: the identifier of the other party, the reason of a failure, etc.
+    #[serde(flatten)]
+    pub fields: BTreeMap<String, String>,
+}
+
+impl AuditEvent {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// Destination of the audit events
+pub trait AuditSink: Send + 'static {
+    /// Store an audit event
+    fn record(&mut self, event: &AuditEvent) -> io::Result<()>;
+}
+
+/// Configuration of an audit sink, parsed from `file:<path>`, `syslog`, `syslog:<host:port>`
+/// or `webhook:<url>`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AuditSinkConfig {
+    /// Append the events, one JSON object per line, to a file
+    File(PathBuf),
+    /// Send the events to a syslog server over UDP, or to the local syslog daemon
+    Syslog(Option<String>),
+    /// Post each event as a JSON object to a URL
+    Webhook(String),
+}
+
+impl FromStr for AuditSinkConfig {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some(("file", path)) if !path.is_empty() => Ok(Self::File(PathBuf::from(path))),
+            Some(("syslog", address)) if !address.is_empty() => {
+                Ok(Self::Syslog(Some(address.to_string())))
+            }
+            None if s == "syslog" => Ok(Self::Syslog(None)),
+            Some(("webhook", url)) if url.starts_with("http://") || url.starts_with("https://") => {
+                Ok(Self::Webhook(url.to_string()))
+            }
+            _ => Err(format!(
+                "invalid audit sink '{s}', expected 'file:<path>', 'syslog', 'syslog:<host:port>' or 'webhook:<url>'"
+            )),
+        }
+    }
+}
+
+impl AuditSinkConfig {
+    /// Parse a comma-separated list of sinks
+    pub fn parse_list(s: &str) -> Result<Vec<Self>, String> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(Self::from_str)
+            .collect()
+    }
+
+    /// Create the sink
+    pub fn create(&self) -> io::Result<Box<dyn AuditSink>> {
+        Ok(match self {
+            Self::File(path) => Box::new(FileAuditSink::open(path)?),
+            Self::Syslog(address) => Box::new(SyslogAuditSink::connect(address.as_deref())?),
+            Self::Webhook(url) => Box::new(WebhookAuditSink::new(url)),
+        })
+    }
+}
+
+/// Sink appending the events to a file, one JSON object per line
+pub struct FileAuditSink {
+    file: File,
+}
+
+impl FileAuditSink {
+    pub fn open(path: &PathBuf) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self { file })
+    }
+}
+
+impl AuditSink for FileAuditSink {
+    fn record(&mut self, event: &AuditEvent) -> io::Result<()> {
+        writeln!(self.file, "{}", event.to_json())?;
+        self.file.flush()
+    }
+}
+
+/// Sink sending the events to syslog, with the RFC 5424 format and the `authpriv` facility.
+/// The message of each syslog entry is the JSON object of the event
+pub struct SyslogAuditSink {
+    socket: SyslogSocket,
+}
+
+enum SyslogSocket {
+    Udp(UdpSocket),
+    #[cfg(unix)]
+    Local(std::os::unix::net::UnixDatagram),
+}
+
+/// `authpriv` facility (10) and `notice` severity (5)
+const SYSLOG_PRIORITY: u8 = 10 * 8 + 5;
+
+impl SyslogAuditSink {
+    /// Connect to a syslog server, or to the local syslog daemon if no address is given
+    pub fn connect(address: Option<&str>) -> io::Result<Self> {
+        let socket = match address {
+            Some(address) => {
+                let socket = UdpSocket::bind("0.0.0.0:0")?;
+                socket.connect(address)?;
+                SyslogSocket::Udp(socket)
+            }
+            #[cfg(unix)]
+            None => {
+                let socket = std::os::unix::net::UnixDatagram::unbound()?;
+                socket.connect("/dev/log")?;
+                SyslogSocket::Local(socket)
+            }
+            #[cfg(not(unix))]
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "the address of a syslog server is required on this platform",
+                ))
+            }
+        };
+        Ok(Self { socket })
+    }
+
+    fn format(event: &AuditEvent) -> String {
+        format!(
+            "<{SYSLOG_PRIORITY}>1 {} - ockam {} {} - {}",
+            event.timestamp,
+            std::process::id(),
+            event.event,
+            event.to_json()
+        )
+    }
+}
+
+impl AuditSink for SyslogAuditSink {
+    fn record(&mut self, event: &AuditEvent) -> io::Result<()> {
+        let message = Self::format(event);
+        match &self.socket {
+            SyslogSocket::Udp(socket) => socket.send(message.as_bytes())?,
+            #[cfg(unix)]
+            SyslogSocket::Local(socket) => socket.send(message.as_bytes())?,
+        };
+        Ok(())
+    }
+}
+
+/// Sink posting each event as a JSON object to a URL
+pub struct WebhookAuditSink {
+    /// The client is created on the thread sending the events since
+    /// a blocking client can't be created in an async context
+    client: Option<reqwest::blocking::Client>,
+    url: String,
+}
+
+impl WebhookAuditSink {
+    pub fn new(url: &str) -> Self {
+        Self {
+            client: None,
+            url: url.to_string(),
+        }
+    }
+}
+
+impl AuditSink for WebhookAuditSink {
+    fn record(&mut self, event: &AuditEvent) -> io::Result<()> {
+        self.client
+            .get_or_insert_with(reqwest::blocking::Client::new)
+            .post(&self.url)
+            .json(event)
+            .send()
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        Ok(())
+    }
+}
+
+/// Layer collecting the audit events and sending them to the audit sinks
+pub struct AuditLayer {
+    node_name: Option<String>,
+    sender: Mutex<Sender<AuditEvent>>,
+}
+
+impl AuditLayer {
+    /// Start a thread sending the audit events to the sinks
+    pub fn new(mut sinks: Vec<Box<dyn AuditSink>>, node_name: Option<&str>) -> Self {
+        let (sender, receiver) = channel::<AuditEvent>();
+        thread::spawn(move || {
+            for event in receiver {
+                for sink in sinks.iter_mut() {
+                    if let Err(e) = sink.record(&event) {
+                        warn!(%e, event = %event.event, "Failed to record an audit event");
+                    }
+                }
+            }
+        });
+        Self {
+            node_name: node_name.map(|n| n.to_string()),
+            sender: Mutex::new(sender),
+        }
+    }
+
+    fn audit_event(&self, event: &Event<'_>) -> Option<AuditEvent> {
+        let category = event
+            .metadata()
+            .target()
+            .strip_prefix(AUDIT_TARGET_PREFIX)?
+            .trim_start_matches("::");
+        let mut visitor = AuditEventVisitor::default();
+        event.record(&mut visitor);
+        Some(AuditEvent {
+            timestamp: OffsetDateTime::now_utc()
+                .format(&Rfc3339)
+                .unwrap_or_default(),
+            node: self.node_name.clone(),
+            category: category.to_string(),
+            event: visitor.event.unwrap_or_else(|| category.to_string()),
+            message: visitor.message,
+            fields: visitor.fields,
+        })
+    }
+}
+
+impl<S: Subscriber> Layer<S> for AuditLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if let Some(audit_event) = self.audit_event(event) {
+            if let Ok(sender) = self.sender.lock() {
+                let _ = sender.send(audit_event);
+            }
+        }
+    }
+}
+
+/// Collect the fields of an audit event
+#[derive(Default)]
+struct AuditEventVisitor {
+    event: Option<String>,
+    message: String,
+    fields: BTreeMap<String, String>,
+}
+
+impl Visit for AuditEventVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "event" => self.event = Some(value.to_string()),
+            "message" => self.message = value.to_string(),
+            name => {
+                self.fields.insert(name.to_string(), value.to_string());
+            }
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.record_str(field, &format!("{value:?}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_audit_sinks() {
+        assert_eq!(
+            AuditSinkConfig::parse_list("file:/tmp/audit.log, syslog,syslog:127.0.0.1:514,webhook:https://example.com/events").unwrap(),
+            vec![
+                AuditSinkConfig::File(PathBuf::from("/tmp/audit.log")),
+                AuditSinkConfig::Syslog(None),
+                AuditSinkConfig::Syslog(Some("127.0.0.1:514".to_string())),
+                AuditSinkConfig::Webhook("https://example.com/events".to_string()),
+            ]
+        );
+        assert!(AuditSinkConfig::parse_list("webhook:example.com").is_err());
+        assert!(AuditSinkConfig::parse_list("kafka:localhost:9092").is_err());
+    }
+
+    #[test]
+    fn test_audit_event_json() {
+        let event = AuditEvent {
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+            node: Some("n1".to_string()),
+            category: "relays".to_string(),
+            event: "relay_registered".to_string(),
+            message: "relay registration".to_string(),
+            fields: BTreeMap::from([("alias".to_string(), "default".to_string())]),
+        };
+        assert_eq!(
+            event.to_json(),
+            r#"{"timestamp":"2024-01-01T00:00:00Z","node":"n1","category":"relays","event":"relay_registered","message":"relay registration","alias":"default"}"#
+        );
+    }
+}
@@ -15,7 +15,26 @@ pub fn log_max_files() -> usize {
     get_env_with_default("OCKAM_LOG_MAX_FILES", default).unwrap_or(default) as usize
 }
 
+/// Name of the variable containing the format of the logs: `default`, `pretty` or `json`
+pub const OCKAM_LOG_FORMAT: &str = "OCKAM_LOG_FORMAT";
+
 pub fn log_format() -> LogFormat {
     let default = LogFormat::Default;
-    get_env_with_default("OCKAM_LOG_FORMAT", default.clone()).unwrap_or(default)
+    get_env_with_default(OCKAM_LOG_FORMAT, default.clone()).unwrap_or(default)
+}
+
+/// Name of the variable containing the URL of the OpenTelemetry collector receiving the traces,
+/// for example `http://localhost:4318`
+pub const OCKAM_OPENTELEMETRY_ENDPOINT: &str = "OCKAM_OPENTELEMETRY_ENDPOINT";
+
+pub fn opentelemetry_endpoint() -> Option<String> {
+    get_env(OCKAM_OPENTELEMETRY_ENDPOINT).unwrap_or_default()
+}
+
+/// Name of the variable containing the comma-separated list of the sinks receiving the audit
+/// events, for example `file:/var/log/ockam/audit.log,syslog:127.0.0.1:514`
+pub const OCKAM_AUDIT_SINKS: &str = "OCKAM_AUDIT_SINKS";
+
+pub fn audit_sinks() -> Option<String> {
+    get_env(OCKAM_AUDIT_SINKS).unwrap_or_default()
 }
@@ -1,42 +1,82 @@
-use crate::logs::env::{log_format, log_max_files};
+use crate::logs::audit::{AuditLayer, AuditSinkConfig, AUDIT_TARGET_PREFIX};
+use crate::logs::env::{audit_sinks, log_format, log_max_files, opentelemetry_endpoint};
 use ockam_core::env::FromString;
-use std::io::stdout;
+#[cfg(feature = "opentelemetry")]
+use opentelemetry::KeyValue;
+#[cfg(feature = "opentelemetry")]
+use opentelemetry_otlp::WithExportConfig;
+#[cfg(feature = "opentelemetry")]
+use opentelemetry_sdk::{trace, Resource};
+use std::io::{self, stdout, Write};
 use std::path::PathBuf;
 pub use tracing::level_filters::LevelFilter;
 pub use tracing_appender::non_blocking::WorkerGuard;
 use tracing_appender::rolling::{RollingFileAppender, Rotation};
-use tracing_subscriber::fmt::layer;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::fmt::{layer, MakeWriter};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
 
+pub mod audit;
 pub mod env;
 
 pub struct Logging;
 
 impl Logging {
+    /// Set up the logs, and the export of the traces, of a command or a node.
+    ///
+    /// When the logs are in the JSON format, the name of the node is added to every event,
+    /// along with the fields of the enclosing spans: the address of the worker handling a
+    /// message, the identifier of the other side of a secure channel, the key of a session, etc.
     pub fn setup(
         level: LevelFilter,
         color: bool,
         node_dir: Option<PathBuf>,
+        node_name: Option<&str>,
         crates: &[&str],
     ) -> Option<WorkerGuard> {
-        let filter = {
-            let builder = EnvFilter::builder();
-            builder.with_default_directive(level.into()).parse_lossy(
-                crates
-                    .iter()
-                    .map(|c| format!("{c}={level}"))
-                    .collect::<Vec<_>>()
-                    .join(","),
-            )
+        let log_format = log_format();
+        let json_node_name = match log_format {
+            LogFormat::Json => node_name,
+            _ => None,
         };
+        let filter = Self::filter(level, crates);
+        // Errors are logged once the subscriber is initialized
+        let mut errors = vec![];
+        // The spans are exported to an OpenTelemetry collector, if one is configured,
+        // even when the logs are turned off. The spans of the data paths are only
+        // exported with the debug level.
+        let opentelemetry = opentelemetry_endpoint().and_then(|endpoint| {
+            // Only the spans of the Ockam crates are exported, not the spans of the
+            // libraries used to export them
+            let filter = EnvFilter::builder()
+                .with_default_directive(LevelFilter::OFF.into())
+                .parse_lossy(Self::directives(level.max(LevelFilter::INFO), crates));
+            match Self::opentelemetry_layer(&endpoint, node_dir.as_ref()) {
+                Ok(layer) => Some(layer.with_filter(filter)),
+                Err(e) => {
+                    errors.push(format!("Failed to export the traces to {endpoint}: {e}"));
+                    None
+                }
+            }
+        });
+        // The audit events are sent to the audit sinks, if some are configured,
+        // whatever the level of the logs is
+        let audit = audit_sinks()
+            .and_then(|sinks| Self::audit_layer(&sinks, node_name, &mut errors))
+            .map(|l| {
+                l.with_filter(Targets::new().with_target(AUDIT_TARGET_PREFIX, LevelFilter::INFO))
+            });
         let subscriber = tracing_subscriber::registry()
-            .with(filter)
-            .with(tracing_error::ErrorLayer::default());
+            .with(tracing_error::ErrorLayer::default())
+            .with(opentelemetry)
+            .with(audit);
         let (appender, guard) = match node_dir {
             // If a node dir path is not provided, log to stdout.
             None => {
                 let (n, guard) = tracing_appender::non_blocking(stdout());
-                let appender = layer().with_ansi(color).with_writer(n);
+                let appender = layer()
+                    .with_ansi(color)
+                    .with_writer(NodeNameWriter::new(n, json_node_name));
                 (Box::new(appender), guard)
             }
             // If a log path is provided, log to a rolling file appender.
@@ -49,18 +89,170 @@ impl Logging {
                     .build(node_dir)
                     .expect("Failed to create rolling file appender");
                 let (n, guard) = tracing_appender::non_blocking(r);
-                let appender = layer().with_ansi(false).with_writer(n);
+                let appender = layer()
+                    .with_ansi(false)
+                    .with_writer(NodeNameWriter::new(n, json_node_name));
                 (Box::new(appender), guard)
             }
         };
-        let res = match log_format() {
-            LogFormat::Pretty => subscriber.with(appender.pretty()).try_init(),
-            LogFormat::Json => subscriber.with(appender.json()).try_init(),
-            LogFormat::Default => subscriber.with(appender).try_init(),
+        let res = match log_format {
+            LogFormat::Pretty => subscriber
+                .with(appender.pretty().with_filter(filter))
+                .try_init(),
+            LogFormat::Json => subscriber
+                .with(appender.json().with_filter(filter))
+                .try_init(),
+            LogFormat::Default => subscriber.with(appender.with_filter(filter)).try_init(),
         };
         res.expect("Failed to initialize tracing subscriber");
+        for e in errors {
+            error!("{e}");
+        }
         Some(guard)
     }
+
+    /// Export the spans which are still buffered to the OpenTelemetry collector.
+    /// This must be called before the process exits.
+    pub fn shutdown() {
+        #[cfg(feature = "opentelemetry")]
+        opentelemetry::global::shutdown_tracer_provider();
+    }
+
+    fn filter(level: LevelFilter, crates: &[&str]) -> EnvFilter {
+        let builder = EnvFilter::builder();
+        builder
+            .with_default_directive(level.into())
+            .parse_lossy(Self::directives(level, crates))
+    }
+
+    fn directives(level: LevelFilter, crates: &[&str]) -> String {
+        crates
+            .iter()
+            .map(|c| format!("{c}={level}"))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Create a layer sending the audit events to a comma-separated list of sinks
+    fn audit_layer(
+        sinks: &str,
+        node_name: Option<&str>,
+        errors: &mut Vec<String>,
+    ) -> Option<AuditLayer> {
+        let configs = match AuditSinkConfig::parse_list(sinks) {
+            Ok(configs) => configs,
+            Err(e) => {
+                errors.push(format!("Failed to configure the audit sinks: {e}"));
+                return None;
+            }
+        };
+        let mut sinks = vec![];
+        for config in configs {
+            match config.create() {
+                Ok(sink) => sinks.push(sink),
+                Err(e) => errors.push(format!("Failed to create the audit sink {config:?}: {e}")),
+            }
+        }
+        if sinks.is_empty() {
+            None
+        } else {
+            Some(AuditLayer::new(sinks, node_name))
+        }
+    }
+
+    /// Create a layer exporting the spans to an OpenTelemetry collector with the OTLP protocol.
+    /// The spans of a node are attributed to a service named after the node.
+    #[cfg(feature = "opentelemetry")]
+    fn opentelemetry_layer<S>(
+        endpoint: &str,
+        node_dir: Option<&PathBuf>,
+    ) -> Result<tracing_opentelemetry::OpenTelemetryLayer<S, trace::Tracer>, String>
+    where
+        S: tracing::Subscriber + for<'span> tracing_subscriber::registry::LookupSpan<'span>,
+    {
+        let service_name = match node_dir.and_then(|d| d.file_name()) {
+            Some(node_name) => format!("ockam-node-{}", node_name.to_string_lossy()),
+            None => "ockam".to_string(),
+        };
+        let tracer =
+            opentelemetry_otlp::new_pipeline()
+                .tracing()
+                .with_exporter(
+                    opentelemetry_otlp::new_exporter()
+                        .http()
+                        .with_endpoint(endpoint),
+                )
+                .with_trace_config(trace::config().with_resource(Resource::new(vec![
+                    KeyValue::new("service.name", service_name),
+                ])))
+                // The spans are exported from a dedicated thread, which doesn't
+                // require the process to run an async runtime
+                .install_simple();
+        let tracer = tracer.map_err(|e| e.to_string())?;
+        // The trace context is only propagated with the messages once the spans are exported
+        ockam_node::TracingContext::enable();
+        Ok(tracing_opentelemetry::layer().with_tracer(tracer))
+    }
+
+    #[cfg(not(feature = "opentelemetry"))]
+    fn opentelemetry_layer(
+        _endpoint: &str,
+        _node_dir: Option<&PathBuf>,
+    ) -> Result<tracing_subscriber::layer::Identity, String> {
+        Err("this binary was built without the opentelemetry feature".to_string())
+    }
+}
+
+/// Writer adding the name of the node to the events logged as JSON objects
+struct NodeNameWriter<W> {
+    inner: W,
+    /// Start of the JSON objects, with the node name field
+    prefix: Option<String>,
+}
+
+impl<W> NodeNameWriter<W> {
+    fn new(inner: W, node_name: Option<&str>) -> Self {
+        let prefix = node_name.map(|name| {
+            format!(
+                "{{\"node\":{},",
+                serde_json::Value::String(name.to_string())
+            )
+        });
+        Self { inner, prefix }
+    }
+}
+
+impl<'a, W: MakeWriter<'a>> MakeWriter<'a> for NodeNameWriter<W> {
+    type Writer = NodeNameWriter<W::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        NodeNameWriter {
+            inner: self.inner.make_writer(),
+            prefix: self.prefix.clone(),
+        }
+    }
+}
+
+impl<W: Write> Write for NodeNameWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Each event is written at once, so the field can be inserted at the start
+        // of the object. The line is written with a single call so that it is not
+        // interleaved with the events of other threads
+        match (&self.prefix, buf.strip_prefix(b"{")) {
+            (Some(prefix), Some(rest)) if rest.first() != Some(&b'}') => {
+                let mut line = Vec::with_capacity(prefix.len() + rest.len());
+                line.extend_from_slice(prefix.as_bytes());
+                line.extend_from_slice(rest);
+                self.inner.write_all(&line)?;
+                Ok(buf.len())
+            }
+            _ => self.inner.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
 }
 
 #[derive(Clone)]
@@ -89,3 +281,25 @@ impl std::fmt::Display for LogFormat {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_name_is_added_to_json_events() {
+        let mut writer = NodeNameWriter::new(Vec::new(), Some("n\"1"));
+        writer.write_all(b"{\"level\":\"INFO\"}\n").unwrap();
+        assert_eq!(
+            String::from_utf8(writer.inner).unwrap(),
+            "{\"node\":\"n\\\"1\",\"level\":\"INFO\"}\n"
+        );
+
+        let mut writer = NodeNameWriter::new(Vec::new(), None);
+        writer.write_all(b"{\"level\":\"INFO\"}\n").unwrap();
+        assert_eq!(
+            String::from_utf8(writer.inner).unwrap(),
+            "{\"level\":\"INFO\"}\n"
+        );
+    }
+}
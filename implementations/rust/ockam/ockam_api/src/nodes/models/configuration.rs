@@ -0,0 +1,55 @@
+use minicbor::{Decode, Encode};
+use std::fmt::{self, Display};
+
+/// Request body to reload the configuration of a node
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct ReloadConfiguration {
+    /// Content of the new configuration, as JSON.
+    /// The configuration file of the node is read again if it is not set
+    #[n(1)] pub configuration: Option<String>,
+}
+
+impl ReloadConfiguration {
+    pub fn new(configuration: Option<String>) -> Self {
+        Self { configuration }
+    }
+}
+
+/// Response body describing the changes made when a configuration is reloaded
+#[derive(Clone, Debug, Default, Decode, Encode, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct ConfigurationChanges {
+    /// Changes which were applied, for example `created the tcp outlet web`
+    #[n(1)] pub applied: Vec<String>,
+    /// Changes which could not be applied, with the reason of the failure
+    #[n(2)] pub failed: Vec<String>,
+    /// Changes which require a restart of the node to be applied
+    #[n(3)] pub skipped: Vec<String>,
+}
+
+impl ConfigurationChanges {
+    pub fn is_empty(&self) -> bool {
+        self.applied.is_empty() && self.failed.is_empty() && self.skipped.is_empty()
+    }
+}
+
+impl Display for ConfigurationChanges {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "The configuration is unchanged");
+        }
+        for change in &self.applied {
+            writeln!(f, "[applied] {change}")?;
+        }
+        for change in &self.failed {
+            writeln!(f, "[failed] {change}")?;
+        }
+        for change in &self.skipped {
+            writeln!(f, "[skipped] {change}")?;
+        }
+        Ok(())
+    }
+}
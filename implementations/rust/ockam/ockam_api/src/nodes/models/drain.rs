@@ -0,0 +1,68 @@
+use std::fmt::{self, Display};
+use std::time::Duration;
+
+use minicbor::{Decode, Encode};
+
+/// Default maximum time spent waiting for the portal connections of a draining node to be closed
+pub const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Request body to drain the connections of a node before stopping it
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct DrainNode {
+    /// Maximum time spent waiting for the open portal connections to be closed
+    #[n(1)] pub timeout: Duration,
+}
+
+impl DrainNode {
+    pub fn new(timeout: Duration) -> Self {
+        Self { timeout }
+    }
+}
+
+impl Default for DrainNode {
+    fn default() -> Self {
+        Self::new(DEFAULT_DRAIN_TIMEOUT)
+    }
+}
+
+/// Response body describing what was done while draining a node
+#[derive(Clone, Debug, Default, Decode, Encode, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct NodeDrained {
+    /// Secure channel listeners, TCP inlets and TCP outlets which stopped accepting connections
+    #[n(1)] pub stopped_listeners: Vec<String>,
+    /// Relays withdrawn from the nodes where they were registered
+    #[n(2)] pub withdrawn_relays: Vec<String>,
+    /// Number of portal connections which were still open when the timeout expired
+    #[n(3)] pub remaining_connections: u64,
+}
+
+impl NodeDrained {
+    /// Return true if all the portal connections were closed before the timeout
+    pub fn is_complete(&self) -> bool {
+        self.remaining_connections == 0
+    }
+}
+
+impl Display for NodeDrained {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for listener in &self.stopped_listeners {
+            writeln!(f, "[stopped] {listener}")?;
+        }
+        for relay in &self.withdrawn_relays {
+            writeln!(f, "[withdrawn] relay {relay}")?;
+        }
+        if self.is_complete() {
+            writeln!(f, "All the portal connections are closed")
+        } else {
+            writeln!(
+                f,
+                "{} portal connection(s) were still open when the drain timeout expired",
+                self.remaining_connections
+            )
+        }
+    }
+}
@@ -1,5 +1,12 @@
+use std::time::Duration;
+
 use minicbor::{Decode, Encode};
-use ockam_core::flow_control::FlowControlId;
+use serde::Serialize;
+
+use ockam_core::flow_control::{
+    AddressFlowControlInfo, ConsumerLimits, FlowControlId, FlowControlInfo,
+};
+use ockam_core::Address;
 use ockam_multiaddr::MultiAddr;
 
 #[derive(Debug, Clone, Decode, Encode)]
@@ -8,6 +15,10 @@ use ockam_multiaddr::MultiAddr;
 pub struct AddConsumer {
     #[n(1)] flow_control_id: FlowControlId,
     #[n(2)] address: MultiAddr,
+    /// The consumer is removed after receiving this number of messages
+    #[n(3)] max_messages: Option<u64>,
+    /// The consumer is removed after this duration
+    #[n(4)] duration: Option<Duration>,
 }
 
 impl AddConsumer {
@@ -15,12 +26,114 @@ impl AddConsumer {
         Self {
             flow_control_id,
             address,
+            max_messages: None,
+            duration: None,
         }
     }
+    pub fn with_max_messages(mut self, max_messages: Option<u64>) -> Self {
+        self.max_messages = max_messages;
+        self
+    }
+    pub fn with_duration(mut self, duration: Option<Duration>) -> Self {
+        self.duration = duration;
+        self
+    }
     pub fn flow_control_id(&self) -> &FlowControlId {
         &self.flow_control_id
     }
     pub fn address(&self) -> &MultiAddr {
         &self.address
     }
+    /// Return the limits of the consumer, if it is a temporary consumer
+    pub fn limits(&self) -> Option<ConsumerLimits> {
+        if self.max_messages.is_none() && self.duration.is_none() {
+            return None;
+        }
+        let mut limits = ConsumerLimits::default();
+        if let Some(max_messages) = self.max_messages {
+            limits = limits.with_max_messages(max_messages);
+        }
+        if let Some(duration) = self.duration {
+            limits = limits.with_duration(duration);
+        }
+        Some(limits)
+    }
+}
+
+/// Response body listing the Spawners, Producers and Consumers of the flow controls of a node
+#[derive(Debug, Clone, Default, Decode, Encode, Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct FlowControlList {
+    #[n(1)] pub flow_controls: Vec<FlowControlStatus>,
+}
+
+impl FlowControlList {
+    pub fn new(flow_controls: Vec<FlowControlStatus>) -> Self {
+        Self { flow_controls }
+    }
+}
+
+/// Spawners, Producers and Consumers of a flow control
+#[derive(Debug, Clone, Decode, Encode, Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct FlowControlStatus {
+    #[n(1)] pub flow_control_id: FlowControlId,
+    #[n(2)] pub spawners: Vec<String>,
+    #[n(3)] pub producers: Vec<String>,
+    #[n(4)] pub consumers: Vec<String>,
+}
+
+impl From<&FlowControlInfo> for FlowControlStatus {
+    fn from(info: &FlowControlInfo) -> Self {
+        Self {
+            flow_control_id: info.flow_control_id().clone(),
+            spawners: addresses(info.spawners()),
+            producers: addresses(info.producers()),
+            consumers: addresses(info.consumers()),
+        }
+    }
+}
+
+/// Flow controls in which a worker address takes part
+#[derive(Debug, Clone, Decode, Encode, Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct AddressFlowControlStatus {
+    #[n(1)] pub address: String,
+    /// Flow controls for which the address is a consumer
+    #[n(2)] pub consumer_of: Vec<FlowControlId>,
+    /// Flow control for which the address, or the producer it belongs to, is a producer
+    #[n(3)] pub producer_of: Option<FlowControlId>,
+    /// Flow control of the spawner which created the producer
+    #[n(4)] pub producer_spawner: Option<FlowControlId>,
+    /// Address of the producer, when the address is an additional address of that producer,
+    /// for example the encryptor of a secure channel for its decryptor
+    #[n(5)] pub producer_address: Option<String>,
+    /// Flow control for which the address is a spawner
+    #[n(6)] pub spawner_of: Option<FlowControlId>,
+}
+
+impl From<&AddressFlowControlInfo> for AddressFlowControlStatus {
+    fn from(info: &AddressFlowControlInfo) -> Self {
+        Self {
+            address: info.address().to_string(),
+            consumer_of: info.consumer_of().to_vec(),
+            producer_of: info
+                .producer()
+                .as_ref()
+                .map(|p| p.flow_control_id().clone()),
+            producer_spawner: info
+                .producer()
+                .as_ref()
+                .and_then(|p| p.spawner_flow_control_id().clone()),
+            producer_address: info.producer_address().as_ref().map(|a| a.to_string()),
+            spawner_of: info.spawner_of().clone(),
+        }
+    }
+}
+
+fn addresses(addresses: &[Address]) -> Vec<String> {
+    addresses.iter().map(|a| a.to_string()).collect()
 }
@@ -0,0 +1,87 @@
+use minicbor::{Decode, Encode};
+use std::fmt::{self, Display};
+
+/// Result of one of the checks performed to decide if a node is ready
+#[derive(Debug, Clone, Decode, Encode, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct ReadinessCheck {
+    #[n(1)] pub name: String,
+    #[n(2)] pub ok: bool,
+    #[n(3)] pub message: Option<String>,
+}
+
+impl ReadinessCheck {
+    pub fn ok(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ok: true,
+            message: None,
+        }
+    }
+
+    pub fn failed(name: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ok: false,
+            message: Some(message.into()),
+        }
+    }
+}
+
+/// Response body for the readiness of a node.
+/// The node is ready when all its checks are ok
+#[derive(Debug, Clone, Decode, Encode, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct NodeReadiness {
+    #[n(1)] pub checks: Vec<ReadinessCheck>,
+}
+
+impl NodeReadiness {
+    pub fn new(checks: Vec<ReadinessCheck>) -> Self {
+        Self { checks }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.checks.iter().all(|c| c.ok)
+    }
+}
+
+impl Display for NodeReadiness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = if self.is_ready() {
+            "ready"
+        } else {
+            "not ready"
+        };
+        writeln!(f, "{status}")?;
+        for check in &self.checks {
+            let result = if check.ok { "[ok]" } else { "[failed]" };
+            match &check.message {
+                Some(message) => writeln!(f, "{result} {}: {message}", check.name)?,
+                None => writeln!(f, "{result} {}", check.name)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_readiness() {
+        let readiness = NodeReadiness::new(vec![
+            ReadinessCheck::ok("enrollment"),
+            ReadinessCheck::failed("relay default", "the relay is not registered"),
+        ]);
+        assert!(!readiness.is_ready());
+        assert_eq!(
+            readiness.to_string(),
+            "not ready\n[ok] enrollment\n[failed] relay default: the relay is not registered\n"
+        );
+        assert!(NodeReadiness::new(vec![]).is_ready());
+    }
+}
@@ -3,12 +3,16 @@
 /// This module is only a type facade and should not have any logic of
 /// its own
 pub mod base;
+pub mod configuration;
 pub mod credentials;
+pub mod drain;
 pub mod flow_controls;
+pub mod health;
 pub mod policy;
 pub mod portal;
 pub mod relay;
 pub mod secure_channel;
 pub mod services;
 pub mod transport;
+pub mod udp_portal;
 pub mod workers;
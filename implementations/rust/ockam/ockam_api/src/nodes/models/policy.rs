@@ -1,5 +1,5 @@
 use minicbor::{Decode, Encode};
-use ockam_abac::{Action, Expr};
+use ockam_abac::{Action, Expr, PolicyVersion};
 
 #[derive(Debug, Decode, Encode)]
 #[rustfmt::skip]
@@ -39,3 +39,39 @@ impl Expression {
         &self.expr
     }
 }
+
+/// History of the changes of a policy, from the oldest to the most recent one
+#[derive(Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct PolicyVersionList {
+    #[n(1)] versions: Vec<PolicyVersion>,
+}
+
+impl PolicyVersionList {
+    pub fn new(versions: Vec<PolicyVersion>) -> Self {
+        PolicyVersionList { versions }
+    }
+
+    pub fn versions(&self) -> &Vec<PolicyVersion> {
+        &self.versions
+    }
+}
+
+/// Request body to restore a previous version of a policy
+#[derive(Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct RollbackPolicy {
+    #[n(1)] version: u64,
+}
+
+impl RollbackPolicy {
+    pub fn new(version: u64) -> Self {
+        RollbackPolicy { version }
+    }
+
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+}
@@ -1,16 +1,18 @@
 //! Inlets and outlet request/response types
 
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
-use std::time::Duration;
+use std::time::{Duration, UNIX_EPOCH};
 
 use minicbor::{Decode, Encode};
-use ockam::identity::Identifier;
+use ockam::identity::{Identifier, IdentitySecureChannelLocalInfo};
 use ockam::route;
 use ockam_core::{Address, Route};
 use ockam_multiaddr::MultiAddr;
+use ockam_transport_tcp::{TcpPortalConnectionInfo, TcpTlsClientOptions};
 use serde::{Deserialize, Serialize};
 
 use crate::error::ApiError;
+use crate::nodes::models::services::KafkaSaslCredentials;
 use crate::route_to_multiaddr;
 use crate::session::sessions::ConnectionStatus;
 
@@ -128,6 +130,18 @@ pub struct CreateOutlet {
     /// Allow the outlet to be reachable from the default secure channel, useful when we want to
     /// tighten the flow control
     #[n(4)] pub reachable_from_default_secure_channel: bool,
+    /// Inject the identifier of the peer, and the given attributes of its credential,
+    /// in the HTTP requests sent to the target
+    #[n(5)] pub http_headers: Option<Vec<String>>,
+    /// Host names, host name wildcards, IP addresses or CIDR ranges of the targets
+    /// the outlet is allowed to connect to
+    #[n(6)] pub allowlist: Option<Vec<String>>,
+    /// Host name of the target, resolved again for each connection
+    #[n(7)] pub hostname: Option<String>,
+    /// Originate TLS to the target
+    #[n(8)] pub tls: Option<OutletTls>,
+    /// Authenticate each connection to a Kafka broker with SASL
+    #[n(9)] pub kafka_sasl: Option<KafkaSaslCredentials>,
 }
 
 impl CreateOutlet {
@@ -142,8 +156,68 @@ impl CreateOutlet {
             worker_addr,
             alias: alias.into(),
             reachable_from_default_secure_channel,
+            http_headers: None,
+            allowlist: None,
+            hostname: None,
+            tls: None,
+            kafka_sasl: None,
         }
     }
+
+    pub fn with_http_headers(mut self, attributes: Vec<String>) -> Self {
+        self.http_headers = Some(attributes);
+        self
+    }
+
+    pub fn with_allowlist(mut self, entries: Vec<String>) -> Self {
+        self.allowlist = Some(entries);
+        self
+    }
+
+    pub fn with_hostname(mut self, hostname: String) -> Self {
+        self.hostname = Some(hostname);
+        self
+    }
+
+    pub fn with_tls(mut self, tls: Option<OutletTls>) -> Self {
+        self.tls = tls;
+        self
+    }
+
+    pub fn with_kafka_sasl(mut self, credentials: Option<KafkaSaslCredentials>) -> Self {
+        self.kafka_sasl = credentials;
+        self
+    }
+}
+
+/// TLS settings used by an outlet to connect to its target
+#[derive(Clone, Debug, Default, Decode, Encode, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct OutletTls {
+    /// Server name used to verify the target, instead of its host name
+    #[n(1)] pub server_name: Option<String>,
+    /// PEM file of the certificates used to verify the target, instead of the platform ones
+    #[n(2)] pub ca_certificates: Option<String>,
+    /// PEM files of the certificate chain and of the private key used to authenticate
+    /// to the target
+    #[n(3)] pub client_certificate: Option<(String, String)>,
+}
+
+impl OutletTls {
+    pub fn options(&self) -> TcpTlsClientOptions {
+        let mut options = TcpTlsClientOptions::new();
+        if let Some(server_name) = &self.server_name {
+            options = options.with_server_name(server_name);
+        }
+        if let Some(ca_certificates) = &self.ca_certificates {
+            options = options.with_ca_certificates(ca_certificates);
+        }
+        if let Some((certificate_chain, private_key)) = &self.client_certificate {
+            options = options.with_client_certificate(certificate_chain, private_key);
+        }
+        options
+    }
 }
 
 /// Response body when interacting with a portal endpoint
@@ -158,6 +232,8 @@ pub struct InletStatus {
     #[n(4)] pub payload: Option<String>,
     #[n(5)] pub outlet_route: String,
     #[n(6)] pub status: ConnectionStatus,
+    /// The connections currently going through the inlet
+    #[n(7)] pub connections: Option<Vec<PortalConnectionStatus>>,
 }
 
 impl InletStatus {
@@ -169,6 +245,7 @@ impl InletStatus {
             payload: Some(reason.into()),
             outlet_route: "".into(),
             status: ConnectionStatus::Down,
+            connections: None,
         }
     }
 
@@ -187,8 +264,14 @@ impl InletStatus {
             payload: payload.into(),
             outlet_route: outlet_route.into(),
             status,
+            connections: None,
         }
     }
+
+    pub fn with_connections(mut self, connections: Vec<PortalConnectionStatus>) -> Self {
+        self.connections = Some(connections);
+        self
+    }
 }
 
 /// Response body when interacting with a portal endpoint
@@ -201,6 +284,8 @@ pub struct OutletStatus {
     #[n(3)] pub alias: String,
     /// An optional status payload
     #[n(4)] pub payload: Option<String>,
+    /// The connections currently going through the outlet
+    #[n(5)] pub connections: Option<Vec<PortalConnectionStatus>>,
 }
 
 impl OutletStatus {
@@ -210,6 +295,7 @@ impl OutletStatus {
             worker_addr: "".into(),
             alias: "".into(),
             payload: Some(reason.into()),
+            connections: None,
         }
     }
 
@@ -224,9 +310,15 @@ impl OutletStatus {
             worker_addr,
             alias: alias.into(),
             payload: payload.into(),
+            connections: None,
         }
     }
 
+    pub fn with_connections(mut self, connections: Vec<PortalConnectionStatus>) -> Self {
+        self.connections = Some(connections);
+        self
+    }
+
     pub fn worker_address(&self) -> Result<MultiAddr, ockam_core::Error> {
         route_to_multiaddr(&route![self.worker_addr.to_string()])
             .ok_or_else(|| ApiError::core("Invalid Worker Address"))
@@ -241,6 +333,44 @@ impl OutletStatus {
     }
 }
 
+/// A TCP connection going through an inlet or an outlet
+#[derive(Clone, Debug, Decode, Encode, Serialize, Deserialize, PartialEq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct PortalConnectionStatus {
+    #[n(1)] pub worker_addr: String,
+    /// Address of the TCP client for an inlet, or of the target for an outlet
+    #[n(2)] pub peer_addr: String,
+    /// Identifier of the node at the other end of the portal, when it is reached
+    /// through a secure channel
+    #[n(3)] pub identifier: Option<Identifier>,
+    /// Start of the connection, in seconds since the Unix epoch
+    #[n(4)] pub started_at: u64,
+    /// Bytes written to the peer
+    #[n(5)] pub bytes_sent: u64,
+    /// Bytes read from the peer
+    #[n(6)] pub bytes_received: u64,
+}
+
+impl From<&TcpPortalConnectionInfo> for PortalConnectionStatus {
+    fn from(value: &TcpPortalConnectionInfo) -> Self {
+        Self {
+            worker_addr: value.address().to_string(),
+            peer_addr: value.peer().to_string(),
+            identifier: IdentitySecureChannelLocalInfo::find_info_from_list(value.local_info())
+                .ok()
+                .map(|info| info.their_identity_id()),
+            started_at: value
+                .started_at()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            bytes_sent: value.metrics().bytes_sent(),
+            bytes_received: value.metrics().bytes_received(),
+        }
+    }
+}
+
 /// Response body when returning a list of Inlets
 #[derive(Debug, Clone, Decode, Encode)]
 #[rustfmt::skip]
@@ -3,6 +3,7 @@ use minicbor::{Decode, Encode};
 use ockam::identity::Identifier;
 use ockam::remote::RemoteRelayInfo;
 use ockam::route;
+use ockam::RelayStatistics;
 use ockam_core::flow_control::FlowControlId;
 use ockam_multiaddr::MultiAddr;
 
@@ -103,3 +104,29 @@ impl From<RemoteRelayInfo> for RelayInfo {
         }
     }
 }
+
+/// Statistics of a relay created by the relay service of a node, for another node
+#[derive(Debug, Clone, Decode, Encode, serde::Serialize, serde::Deserialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct ServedRelayInfo {
+    #[n(1)] pub alias: String,
+    #[n(2)] pub registered_at: u64,
+    #[n(3)] pub last_heartbeat: u64,
+    #[n(4)] pub messages_forwarded: u64,
+    #[n(5)] pub bytes_forwarded: u64,
+    #[n(6)] pub consumers: u64,
+}
+
+impl From<RelayStatistics> for ServedRelayInfo {
+    fn from(statistics: RelayStatistics) -> Self {
+        Self {
+            alias: statistics.alias,
+            registered_at: statistics.registered_at,
+            last_heartbeat: statistics.last_heartbeat,
+            messages_forwarded: statistics.messages_forwarded,
+            bytes_forwarded: statistics.bytes_forwarded,
+            consumers: statistics.consumers as u64,
+        }
+    }
+}
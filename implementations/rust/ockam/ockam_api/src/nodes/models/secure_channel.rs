@@ -1,9 +1,10 @@
+use std::collections::BTreeMap;
 use std::time::Duration;
 
 use minicbor::{Decode, Encode};
 use serde::Serialize;
 
-use ockam::identity::{Identifier, SecureChannel, DEFAULT_TIMEOUT};
+use ockam::identity::{Identifier, SecureChannel, SecureChannelDetails, DEFAULT_TIMEOUT};
 use ockam_core::flow_control::FlowControlId;
 use ockam_core::{route, Address, Result};
 use ockam_multiaddr::MultiAddr;
@@ -214,6 +215,7 @@ pub struct ShowSecureChannelResponse {
     #[n(2)] pub route: Option<String>,
     #[n(3)] pub authorized_identifiers: Option<Vec<String>>,
     #[n(4)] pub flow_control_id: Option<FlowControlId>,
+    #[n(5)] pub details: Option<SecureChannelDetailsResponse>,
 }
 
 impl ShowSecureChannelResponse {
@@ -232,6 +234,67 @@ impl ShowSecureChannelResponse {
                 })
                 .unwrap_or(None),
             flow_control_id: info.map(|info| info.sc().flow_control_id().clone()),
+            details: None,
+        }
+    }
+
+    /// Add the details of the channel: identifiers, addresses, statistics and attributes
+    pub fn with_details(mut self, details: Option<SecureChannelDetails>) -> Self {
+        self.details = details.map(|d| SecureChannelDetailsResponse::new(&d));
+        self
+    }
+}
+
+/// Details of an established secure channel
+#[derive(Debug, Clone, Decode, Encode, Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SecureChannelDetailsResponse {
+    #[n(1)] pub is_initiator: bool,
+    #[n(2)] pub my_identifier: String,
+    #[n(3)] pub their_identifier: String,
+    #[n(4)] pub decryptor_address: String,
+    #[n(5)] pub their_decryptor_address: String,
+    #[n(6)] pub created_at: Option<u64>,
+    #[n(7)] pub last_activity: Option<u64>,
+    #[n(8)] pub messages_sent: Option<u64>,
+    #[n(9)] pub bytes_sent: Option<u64>,
+    #[n(10)] pub messages_received: Option<u64>,
+    #[n(11)] pub bytes_received: Option<u64>,
+    #[n(12)] pub their_attributes: BTreeMap<String, String>,
+}
+
+impl SecureChannelDetailsResponse {
+    pub fn new(details: &SecureChannelDetails) -> Self {
+        let entry = details.entry();
+        let stats = entry.stats();
+        Self {
+            is_initiator: entry.is_initiator(),
+            my_identifier: entry.my_id().to_string(),
+            their_identifier: entry.their_id().to_string(),
+            decryptor_address: entry.decryptor_messaging_address().to_string(),
+            their_decryptor_address: entry.their_decryptor_address().to_string(),
+            created_at: stats.map(|s| *s.created_at()),
+            last_activity: stats.map(|s| *s.last_activity()),
+            messages_sent: stats.map(|s| s.messages_sent()),
+            bytes_sent: stats.map(|s| s.bytes_sent()),
+            messages_received: stats.map(|s| s.messages_received()),
+            bytes_received: stats.map(|s| s.bytes_received()),
+            their_attributes: details
+                .their_attributes()
+                .map(|entry| {
+                    entry
+                        .attrs()
+                        .iter()
+                        .map(|(k, v)| {
+                            (
+                                String::from_utf8_lossy(k).to_string(),
+                                String::from_utf8_lossy(v).to_string(),
+                            )
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
         }
     }
 }
@@ -4,6 +4,10 @@ use ockam_core::Address;
 use ockam_multiaddr::MultiAddr;
 
 use serde::Serialize;
+use std::fmt::{Debug, Formatter};
+
+use crate::kafka::{KafkaSaslAuthenticator, KafkaSaslMechanism};
+use crate::nodes::models::portal::OutletTls;
 
 #[derive(Debug, Clone, Decode, Encode)]
 #[rustfmt::skip]
@@ -52,20 +56,52 @@ impl DeleteServiceRequest {
 #[cbor(map)]
 pub struct StartKafkaOutletRequest {
     #[n(1)] pub bootstrap_server_addr: SocketAddr,
+    #[n(2)] pub tls: Option<OutletTls>,
+    #[n(3)] pub sasl: Option<KafkaSaslCredentials>,
+    /// Host name of the bootstrap server, used to verify its TLS certificate
+    #[n(4)] pub bootstrap_server_hostname: Option<String>,
 }
 
 impl StartKafkaOutletRequest {
     pub fn new(bootstrap_server_addr: SocketAddr) -> Self {
         Self {
             bootstrap_server_addr,
+            tls: None,
+            sasl: None,
+            bootstrap_server_hostname: None,
         }
     }
 
+    pub fn with_tls(mut self, tls: Option<OutletTls>) -> Self {
+        self.tls = tls;
+        self
+    }
+
+    pub fn with_sasl(mut self, credentials: Option<KafkaSaslCredentials>) -> Self {
+        self.sasl = credentials;
+        self
+    }
+
+    pub fn with_bootstrap_server_hostname(mut self, hostname: Option<String>) -> Self {
+        self.bootstrap_server_hostname = hostname;
+        self
+    }
+
     pub fn bootstrap_server_addr(&self) -> &SocketAddr {
         &self.bootstrap_server_addr
     }
 }
 
+/// Encrypt only the payload of the records written in the Confluent wire format
+/// by the clients of a schema registry
+#[derive(Clone, Debug, Default, Decode, Encode, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct KafkaSchemaRegistryOptions {
+    /// URL of the schema registry where the encrypted variants of the schemas are registered
+    #[n(1)] pub url: Option<String>,
+}
+
 #[derive(Debug, Clone, Decode, Encode)]
 #[rustfmt::skip]
 #[cbor(map)]
@@ -73,6 +109,9 @@ pub struct StartKafkaConsumerRequest {
     #[n(1)] pub bootstrap_server_addr: SocketAddr,
     #[n(2)] brokers_port_range: (u16, u16),
     #[n(3)] project_route: String,
+    #[n(4)] schema_registry: Option<KafkaSchemaRegistryOptions>,
+    /// Rules `<topic pattern>=<encrypt|passthrough|deny>`, see [`crate::kafka::KafkaTopicPolicy`]
+    #[n(5)] topic_policy: Option<Vec<String>>,
 }
 
 impl StartKafkaConsumerRequest {
@@ -85,9 +124,21 @@ impl StartKafkaConsumerRequest {
             bootstrap_server_addr,
             brokers_port_range: brokers_port_range.into(),
             project_route: project_route.to_string(),
+            schema_registry: None,
+            topic_policy: None,
         }
     }
 
+    pub fn with_topic_policy(mut self, rules: Vec<String>) -> Self {
+        self.topic_policy = Some(rules);
+        self
+    }
+
+    pub fn with_schema_registry(mut self, options: Option<KafkaSchemaRegistryOptions>) -> Self {
+        self.schema_registry = options;
+        self
+    }
+
     pub fn bootstrap_server_addr(&self) -> SocketAddr {
         self.bootstrap_server_addr
     }
@@ -97,6 +148,12 @@ impl StartKafkaConsumerRequest {
     pub fn project_route(&self) -> &String {
         &self.project_route
     }
+    pub fn schema_registry(&self) -> Option<&KafkaSchemaRegistryOptions> {
+        self.schema_registry.as_ref()
+    }
+    pub fn topic_policy(&self) -> Option<&Vec<String>> {
+        self.topic_policy.as_ref()
+    }
 }
 
 #[derive(Debug, Clone, Decode, Encode)]
@@ -106,6 +163,9 @@ pub struct StartKafkaProducerRequest {
     #[n(1)] pub bootstrap_server_addr: SocketAddr,
     #[n(2)] brokers_port_range: (u16, u16),
     #[n(3)] project_route: String,
+    #[n(4)] schema_registry: Option<KafkaSchemaRegistryOptions>,
+    /// Rules `<topic pattern>=<encrypt|passthrough|deny>`, see [`crate::kafka::KafkaTopicPolicy`]
+    #[n(5)] topic_policy: Option<Vec<String>>,
 }
 
 impl StartKafkaProducerRequest {
@@ -118,9 +178,21 @@ impl StartKafkaProducerRequest {
             bootstrap_server_addr,
             brokers_port_range: brokers_port_range.into(),
             project_route: project_route.to_string(),
+            schema_registry: None,
+            topic_policy: None,
         }
     }
 
+    pub fn with_topic_policy(mut self, rules: Vec<String>) -> Self {
+        self.topic_policy = Some(rules);
+        self
+    }
+
+    pub fn with_schema_registry(mut self, options: Option<KafkaSchemaRegistryOptions>) -> Self {
+        self.schema_registry = options;
+        self
+    }
+
     pub fn bootstrap_server_addr(&self) -> SocketAddr {
         self.bootstrap_server_addr
     }
@@ -130,6 +202,12 @@ impl StartKafkaProducerRequest {
     pub fn project_route(&self) -> &String {
         &self.project_route
     }
+    pub fn schema_registry(&self) -> Option<&KafkaSchemaRegistryOptions> {
+        self.schema_registry.as_ref()
+    }
+    pub fn topic_policy(&self) -> Option<&Vec<String>> {
+        self.topic_policy.as_ref()
+    }
 }
 
 #[derive(Debug, Clone, Decode, Encode)]
@@ -140,6 +218,9 @@ pub struct StartKafkaDirectRequest {
     #[n(2)] bootstrap_server_addr: SocketAddr,
     #[n(3)] brokers_port_range: (u16, u16),
     #[n(4)] consumer_route: Option<String>,
+    #[n(5)] schema_registry: Option<KafkaSchemaRegistryOptions>,
+    /// Rules `<topic pattern>=<encrypt|passthrough|deny>`, see [`crate::kafka::KafkaTopicPolicy`]
+    #[n(6)] topic_policy: Option<Vec<String>>,
 }
 
 impl StartKafkaDirectRequest {
@@ -154,9 +235,21 @@ impl StartKafkaDirectRequest {
             bootstrap_server_addr,
             brokers_port_range: brokers_port_range.into(),
             consumer_route: consumer_route.map(|a| a.to_string()),
+            schema_registry: None,
+            topic_policy: None,
         }
     }
 
+    pub fn with_topic_policy(mut self, rules: Vec<String>) -> Self {
+        self.topic_policy = Some(rules);
+        self
+    }
+
+    pub fn with_schema_registry(mut self, options: Option<KafkaSchemaRegistryOptions>) -> Self {
+        self.schema_registry = options;
+        self
+    }
+
     pub fn bind_address(&self) -> SocketAddr {
         self.bind_address
     }
@@ -169,6 +262,12 @@ impl StartKafkaDirectRequest {
     pub fn consumer_route(&self) -> Option<String> {
         self.consumer_route.clone()
     }
+    pub fn schema_registry(&self) -> Option<&KafkaSchemaRegistryOptions> {
+        self.schema_registry.as_ref()
+    }
+    pub fn topic_policy(&self) -> Option<&Vec<String>> {
+        self.topic_policy.as_ref()
+    }
 }
 
 /// Request body when instructing a node to start an Identity service
@@ -391,3 +490,43 @@ impl ServiceList {
         Self { list }
     }
 }
+
+/// Credentials used by a Kafka outlet to authenticate to the brokers with SASL
+///
+/// The password is only sent to the node starting the outlet, it is never
+/// displayed, logged or stored
+#[derive(Clone, Decode, Encode, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct KafkaSaslCredentials {
+    #[n(1)] pub mechanism: String,
+    #[n(2)] pub username: String,
+    #[n(3)] password: String,
+}
+
+impl KafkaSaslCredentials {
+    pub fn new(mechanism: KafkaSaslMechanism, username: String, password: String) -> Self {
+        Self {
+            mechanism: mechanism.name().to_string(),
+            username,
+            password,
+        }
+    }
+
+    pub(crate) fn authenticator(&self) -> ockam_core::Result<KafkaSaslAuthenticator> {
+        Ok(KafkaSaslAuthenticator::new(
+            self.mechanism.parse()?,
+            self.username.clone(),
+            self.password.clone(),
+        ))
+    }
+}
+
+impl Debug for KafkaSaslCredentials {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KafkaSaslCredentials")
+            .field("mechanism", &self.mechanism)
+            .field("username", &self.username)
+            .finish_non_exhaustive()
+    }
+}
@@ -6,7 +6,9 @@ use ockam_core::flow_control::FlowControlId;
 use ockam_core::{Error, Result};
 use ockam_multiaddr::proto::Worker;
 use ockam_multiaddr::MultiAddr;
-use ockam_transport_tcp::{TcpConnection, TcpListener, TcpListenerInfo, TcpSenderInfo};
+use ockam_transport_tcp::{
+    TcpConnection, TcpConnectionMetrics, TcpListener, TcpListenerInfo, TcpSenderInfo,
+};
 use std::net::SocketAddrV4;
 
 /// Response body when interacting with a transport
@@ -26,6 +28,39 @@ pub struct TransportStatus {
     #[n(5)] pub processor_address: String,
     /// Corresponding flow control id
     #[n(6)] pub flow_control_id: FlowControlId,
+    /// Traffic counters of a connection
+    #[n(7)] pub metrics: Option<TransportMetrics>,
+}
+
+/// Traffic counters of a transport connection
+#[derive(Debug, Clone, Default, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct TransportMetrics {
+    #[n(1)] pub bytes_sent: u64,
+    #[n(2)] pub bytes_received: u64,
+    #[n(3)] pub messages_sent: u64,
+    #[n(4)] pub messages_received: u64,
+    #[n(5)] pub send_failures: u64,
+    #[n(6)] pub receive_failures: u64,
+    #[n(7)] pub failed_connect_attempts: u64,
+    /// Time taken to establish an outgoing connection, in milliseconds
+    #[n(8)] pub connect_latency_ms: Option<u64>,
+}
+
+impl From<&TcpConnectionMetrics> for TransportMetrics {
+    fn from(value: &TcpConnectionMetrics) -> Self {
+        Self {
+            bytes_sent: value.bytes_sent(),
+            bytes_received: value.bytes_received(),
+            messages_sent: value.messages_sent(),
+            messages_received: value.messages_received(),
+            send_failures: value.send_failures(),
+            receive_failures: value.receive_failures(),
+            failed_connect_attempts: value.failed_connect_attempts(),
+            connect_latency_ms: value.connect_latency().map(|l| l.as_millis() as u64),
+        }
+    }
 }
 
 impl TransportStatus {
@@ -60,6 +95,7 @@ impl From<ApiTransport> for TransportStatus {
             worker_addr: value.worker_address.clone(),
             processor_address: value.processor_address.clone(),
             flow_control_id: value.flow_control_id,
+            metrics: None,
         }
     }
 }
@@ -73,6 +109,7 @@ impl From<TcpSenderInfo> for TransportStatus {
             worker_addr: value.address().to_string(),
             processor_address: value.receiver_address().to_string(),
             flow_control_id: value.flow_control_id().clone(),
+            metrics: Some(value.metrics().into()),
         }
     }
 }
@@ -86,6 +123,7 @@ impl From<TcpListenerInfo> for TransportStatus {
             worker_addr: "<none>".into(),
             processor_address: value.address().to_string(),
             flow_control_id: value.flow_control_id().clone(),
+            metrics: None,
         }
     }
 }
@@ -99,6 +137,7 @@ impl From<TcpConnection> for TransportStatus {
             worker_addr: value.sender_address().to_string(),
             processor_address: value.receiver_address().to_string(),
             flow_control_id: value.flow_control_id().clone(),
+            metrics: Some(value.metrics().into()),
         }
     }
 }
@@ -112,6 +151,7 @@ impl From<TcpListener> for TransportStatus {
             worker_addr: "<none>".into(),
             processor_address: value.processor_address().to_string(),
             flow_control_id: value.flow_control_id().clone(),
+            metrics: None,
         }
     }
 }
@@ -0,0 +1,147 @@
+//! UDP inlets and outlets request/response types
+
+use std::net::SocketAddr;
+
+use minicbor::{Decode, Encode};
+use ockam::route;
+use ockam_core::Address;
+use ockam_multiaddr::MultiAddr;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ApiError;
+use crate::route_to_multiaddr;
+
+/// Request body to create a UDP inlet
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct CreateUdpInlet {
+    /// The address the inlet should receive datagrams at
+    #[n(1)] pub listen_addr: String,
+    /// The address of the outlet, for example the address of an outlet on another node,
+    /// or the address of a relay in a project
+    #[n(2)] pub outlet_addr: MultiAddr,
+    /// A human-friendly alias for this portal endpoint
+    #[n(3)] pub alias: Option<String>,
+}
+
+impl CreateUdpInlet {
+    pub fn new(
+        listen_addr: impl Into<String>,
+        outlet_addr: MultiAddr,
+        alias: impl Into<Option<String>>,
+    ) -> Self {
+        Self {
+            listen_addr: listen_addr.into(),
+            outlet_addr,
+            alias: alias.into(),
+        }
+    }
+}
+
+/// Request body to create a UDP outlet
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct CreateUdpOutlet {
+    /// The address the datagrams are sent to
+    #[n(1)] pub socket_addr: SocketAddr,
+    /// The address of the outlet worker
+    #[n(2)] pub worker_addr: Address,
+    /// A human-friendly alias for this portal endpoint
+    #[n(3)] pub alias: Option<String>,
+}
+
+impl CreateUdpOutlet {
+    pub fn new(
+        socket_addr: SocketAddr,
+        worker_addr: Address,
+        alias: impl Into<Option<String>>,
+    ) -> Self {
+        Self {
+            socket_addr,
+            worker_addr,
+            alias: alias.into(),
+        }
+    }
+}
+
+/// Response body when interacting with a UDP inlet
+#[derive(Clone, Debug, Decode, Encode, Serialize, Deserialize, PartialEq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct UdpInletStatus {
+    #[n(1)] pub bind_addr: String,
+    #[n(2)] pub worker_addr: String,
+    #[n(3)] pub alias: String,
+    #[n(4)] pub outlet_route: String,
+}
+
+impl UdpInletStatus {
+    pub fn new(
+        bind_addr: impl Into<String>,
+        worker_addr: impl Into<String>,
+        alias: impl Into<String>,
+        outlet_route: impl Into<String>,
+    ) -> Self {
+        Self {
+            bind_addr: bind_addr.into(),
+            worker_addr: worker_addr.into(),
+            alias: alias.into(),
+            outlet_route: outlet_route.into(),
+        }
+    }
+}
+
+/// Response body when interacting with a UDP outlet
+#[derive(Clone, Debug, Decode, Encode, Serialize, Deserialize, PartialEq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct UdpOutletStatus {
+    #[n(1)] pub socket_addr: SocketAddr,
+    #[n(2)] pub worker_addr: Address,
+    #[n(3)] pub alias: String,
+}
+
+impl UdpOutletStatus {
+    pub fn new(socket_addr: SocketAddr, worker_addr: Address, alias: impl Into<String>) -> Self {
+        Self {
+            socket_addr,
+            worker_addr,
+            alias: alias.into(),
+        }
+    }
+
+    pub fn worker_address(&self) -> Result<MultiAddr, ockam_core::Error> {
+        route_to_multiaddr(&route![self.worker_addr.to_string()])
+            .ok_or_else(|| ApiError::core("Invalid Worker Address"))
+    }
+}
+
+/// Response body when returning a list of UDP inlets
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct UdpInletList {
+    #[n(1)] pub list: Vec<UdpInletStatus>
+}
+
+impl UdpInletList {
+    pub fn new(list: Vec<UdpInletStatus>) -> Self {
+        Self { list }
+    }
+}
+
+/// Response body when returning a list of UDP outlets
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct UdpOutletList {
+    #[n(1)] pub list: Vec<UdpOutletStatus>
+}
+
+impl UdpOutletList {
+    pub fn new(list: Vec<UdpOutletStatus>) -> Self {
+        Self { list }
+    }
+}
@@ -1,15 +1,73 @@
 use minicbor::{Decode, Encode};
+use std::fmt::{self, Display};
+
+/// Kind of worker, as known by the node manager
+#[derive(Copy, Clone, Debug, Decode, Encode, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(index_only)]
+pub enum WorkerType {
+    /// TCP inlet
+    #[n(0)] Inlet,
+    /// TCP outlet
+    #[n(1)] Outlet,
+    /// Relay registered on a remote node
+    #[n(2)] Relay,
+    /// Secure channel encryptor or decryptor
+    #[n(3)] SecureChannel,
+    /// Secure channel listener
+    #[n(4)] SecureChannelListener,
+    /// Service started by the node manager
+    #[n(5)] Service,
+    /// Processor which is not known by the node manager
+    #[n(6)] Processor,
+    /// Any other worker
+    #[n(7)] Other,
+}
+
+impl Display for WorkerType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Inlet => "inlet",
+            Self::Outlet => "outlet",
+            Self::Relay => "relay",
+            Self::SecureChannel => "secure channel",
+            Self::SecureChannelListener => "secure channel listener",
+            Self::Service => "service",
+            Self::Processor => "processor",
+            Self::Other => "worker",
+        })
+    }
+}
 
 #[derive(Debug, Clone, Decode, Encode)]
 #[rustfmt::skip]
 #[cbor(map)]
 pub struct WorkerStatus {
     #[n(2)] pub addr: String,
+    #[n(3)] pub worker_type: Option<WorkerType>,
+    #[n(4)] pub messages_count: Option<u64>,
+    #[n(5)] pub mailbox_size: Option<u64>,
 }
 
 impl WorkerStatus {
     pub fn new(addr: impl Into<String>) -> Self {
-        Self { addr: addr.into() }
+        Self {
+            addr: addr.into(),
+            worker_type: None,
+            messages_count: None,
+            mailbox_size: None,
+        }
+    }
+
+    pub fn with_type(mut self, worker_type: WorkerType) -> Self {
+        self.worker_type = Some(worker_type);
+        self
+    }
+
+    pub fn with_counts(mut self, messages_count: u64, mailbox_size: u64) -> Self {
+        self.messages_count = Some(messages_count);
+        self.mailbox_size = Some(mailbox_size);
+        self
     }
 }
 
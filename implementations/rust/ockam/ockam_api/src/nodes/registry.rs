@@ -208,6 +208,8 @@ pub(crate) struct Registry {
     pub(crate) relays: RegistryOf<String, RemoteRelayInfo>,
     pub(crate) inlets: RegistryOf<Alias, InletInfo>,
     pub(crate) outlets: RegistryOf<Alias, OutletInfo>,
+    pub(crate) udp_inlets: RegistryOf<Alias, InletInfo>,
+    pub(crate) udp_outlets: RegistryOf<Alias, OutletInfo>,
 }
 
 pub(crate) struct RegistryOf<K, V> {
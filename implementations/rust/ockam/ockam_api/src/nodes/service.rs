@@ -4,6 +4,7 @@ use std::collections::BTreeMap;
 use std::error::Error as _;
 use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
 use std::time::Duration;
 
 use miette::IntoDiagnostic;
@@ -13,18 +14,24 @@ use ockam::identity::models::CredentialAndPurposeKey;
 use ockam::identity::TrustContext;
 use ockam::identity::{Credentials, CredentialsServer, Identities};
 use ockam::identity::{CredentialsServerModule, IdentityAttributesRepository};
-use ockam::identity::{Identifier, SecureChannels};
+use ockam::identity::{Identifier, IdentitySecureChannelLocalInfo, SecureChannels};
 use ockam::{
-    Address, Context, RelayService, RelayServiceOptions, Result, Routed, TcpTransport, Worker,
+    Address, Context, RelayService, RelayServiceOptions, RelayServiceStatistics, Result, Routed,
+    TcpTransport, Worker,
 };
 use ockam_abac::expr::{eq, ident, str};
-use ockam_abac::{Action, Env, Expr, Policy, Resource};
+use ockam_abac::{Action, Env, Expr, Policy, PolicyAccessControl, Resource};
 use ockam_core::api::{Method, RequestHeader, Response};
 use ockam_core::compat::{string::String, sync::Arc};
 use ockam_core::flow_control::FlowControlId;
 use ockam_core::AllowAll;
 use ockam_core::IncomingAccessControl;
 use ockam_multiaddr::MultiAddr;
+#[cfg(feature = "opentelemetry")]
+use ockam_node::TracingContext;
+use ockam_transport_udp::UdpTransport;
+use tokio::sync::OnceCell;
+use tracing::Instrument;
 
 use crate::bootstrapped_identities_store::PreTrustedIdentities;
 use crate::cli_state::CliState;
@@ -46,13 +53,20 @@ use super::registry::Registry;
 
 pub mod actions;
 pub(crate) mod background_node_client;
+pub mod configuration;
+#[cfg(unix)]
+pub mod control_api;
 pub(crate) mod credentials;
 pub mod default_address;
+pub mod drain;
 mod flow_controls;
+pub mod health;
 pub(crate) mod in_memory_node;
 pub mod kafka_services;
 pub mod message;
+pub mod metrics;
 mod node_services;
+mod outlet_access_control;
 pub(crate) mod policy;
 pub mod portals;
 mod projects;
@@ -60,6 +74,7 @@ pub mod relay;
 pub mod resources;
 mod secure_channel;
 mod transport;
+pub mod udp_portals;
 pub mod workers;
 
 const TARGET: &str = "ockam_api::nodemanager::service";
@@ -96,10 +111,16 @@ pub struct NodeManager {
     node_identifier: Identifier,
     api_transport_flow_control_id: FlowControlId,
     pub(crate) tcp_transport: TcpTransport,
+    // Created with the first UDP inlet or outlet of the node
+    pub(crate) udp_transport: OnceCell<UdpTransport>,
     pub(crate) secure_channels: Arc<SecureChannels>,
     trust_context: Option<TrustContext>,
     pub(crate) registry: Registry,
     pub(crate) medic_handle: MedicHandle,
+    // Statistics of the relays created by other nodes on this node
+    relay_service_statistics: Option<RelayServiceStatistics>,
+    // Set when the node starts draining its connections before being stopped
+    pub(crate) draining: AtomicBool,
 }
 
 impl NodeManager {
@@ -236,29 +257,8 @@ impl NodeManager {
         custom_default: Option<&Expr>,
     ) -> Result<Arc<dyn IncomingAccessControl>> {
         if let Some(tcid) = trust_context_id {
-            // Populate environment with known attributes:
-            let mut env = Env::new();
-            env.put("resource.id", str(resource.as_str()));
-            env.put("action.id", str(action.as_str()));
-            env.put("resource.trust_context_id", str(tcid));
-
-            // Check if a policy exists for (resource, action) and if not, then
-            // create or use a default entry:
-            if self.cli_state.get_policy(resource, action).await?.is_none() {
-                let fallback = match custom_default {
-                    Some(e) => e.clone(),
-                    None => eq([
-                        ident("resource.trust_context_id"),
-                        ident("subject.trust_context_id"),
-                    ]),
-                };
-                self.cli_state
-                    .set_policy(resource, action, &Policy::new(fallback))
-                    .await?;
-            }
             let policy_access_control = self
-                .cli_state
-                .make_policy_access_control(resource, action, env)
+                .policy_access_control(resource, action, tcid, custom_default, Env::new())
                 .await?;
             Ok(Arc::new(policy_access_control))
         } else {
@@ -270,6 +270,42 @@ impl NodeManager {
         }
     }
 
+    /// Create a policy access control for a resource and action, with an
+    /// environment extended with the given attributes
+    async fn policy_access_control(
+        &self,
+        resource: &Resource,
+        action: &Action,
+        trust_context_id: &str,
+        custom_default: Option<&Expr>,
+        attributes: Env,
+    ) -> Result<PolicyAccessControl> {
+        // Populate environment with known attributes:
+        let mut env = Env::new();
+        env.put("resource.id", str(resource.as_str()));
+        env.put("action.id", str(action.as_str()));
+        env.put("resource.trust_context_id", str(trust_context_id));
+        env.merge_right(attributes);
+
+        // Check if a policy exists for (resource, action) and if not, then
+        // create or use a default entry:
+        if self.cli_state.get_policy(resource, action).await?.is_none() {
+            let fallback = match custom_default {
+                Some(e) => e.clone(),
+                None => eq([
+                    ident("resource.trust_context_id"),
+                    ident("subject.trust_context_id"),
+                ]),
+            };
+            self.cli_state
+                .set_policy(resource, action, &Policy::new(fallback))
+                .await?;
+        }
+        self.cli_state
+            .make_policy_access_control(resource, action, env)
+            .await
+    }
+
     pub(crate) fn trust_context(&self) -> Result<&TrustContext> {
         self.trust_context
             .as_ref()
@@ -396,10 +432,13 @@ impl NodeManager {
             node_identifier,
             api_transport_flow_control_id: transport_options.api_transport_flow_control_id,
             tcp_transport,
+            udp_transport: OnceCell::new(),
             secure_channels,
             trust_context,
             registry: Default::default(),
             medic_handle,
+            relay_service_statistics: None,
+            draining: AtomicBool::new(false),
         };
 
         debug!("retrieve the node identifier");
@@ -411,7 +450,7 @@ impl NodeManager {
     }
 
     async fn initialize_default_services(
-        &self,
+        &mut self,
         ctx: &Context,
         api_flow_control_id: &FlowControlId,
     ) -> Result<()> {
@@ -421,14 +460,12 @@ impl NodeManager {
         self.start_uppercase_service(ctx, DefaultAddress::UPPERCASE_SERVICE.into())
             .await?;
 
-        RelayService::create(
-            ctx,
-            DefaultAddress::RELAY_SERVICE,
-            RelayServiceOptions::new()
-                .service_as_consumer(api_flow_control_id)
-                .relay_as_consumer(api_flow_control_id),
-        )
-        .await?;
+        let relay_service_options = RelayServiceOptions::new()
+            .service_as_consumer(api_flow_control_id)
+            .relay_as_consumer(api_flow_control_id)
+            .with_registrations_repository(self.cli_state.relay_service_registrations().await?);
+        self.relay_service_statistics = Some(relay_service_options.statistics());
+        RelayService::create(ctx, DefaultAddress::RELAY_SERVICE, relay_service_options).await?;
 
         self.create_secure_channel_listener(
             DefaultAddress::SECURE_CHANNEL_LISTENER.into(),
@@ -536,6 +573,7 @@ impl NodeManagerWorker {
         ctx: &mut Context,
         req: &RequestHeader,
         dec: &mut Decoder<'_>,
+        requester: Option<Identifier>,
     ) -> Result<Vec<u8>> {
         debug! {
             target: TARGET,
@@ -686,6 +724,9 @@ impl NodeManagerWorker {
                 encode_response(req, self.show_relay(req, remote_address).await)?
             }
             (Get, ["node", "forwarder"]) => encode_response(req, self.get_relays(req).await)?,
+            (Get, ["node", "relay_service", "relays"]) => {
+                encode_response(req, self.get_served_relays(req))?
+            }
             (Delete, ["node", "forwarder", remote_address]) => {
                 encode_response(req, self.delete_relay(ctx, req, remote_address).await)?
             }
@@ -714,18 +755,59 @@ impl NodeManagerWorker {
             }
             (Delete, ["node", "portal"]) => todo!(),
 
+            // ==*== UDP Inlets & Outlets ==*==
+            (Get, ["node", "udp", "inlet"]) => encode_response(req, self.get_udp_inlets().await)?,
+            (Get, ["node", "udp", "outlet"]) => encode_response(req, self.get_udp_outlets().await)?,
+            (Post, ["node", "udp", "inlet"]) => {
+                encode_response(req, self.create_udp_inlet(ctx, dec.decode()?).await)?
+            }
+            (Post, ["node", "udp", "outlet"]) => {
+                encode_response(req, self.create_udp_outlet(ctx, dec.decode()?).await)?
+            }
+            (Delete, ["node", "udp", "inlet", alias]) => {
+                encode_response(req, self.delete_udp_inlet(alias).await)?
+            }
+            (Delete, ["node", "udp", "outlet", alias]) => {
+                encode_response(req, self.delete_udp_outlet(alias).await)?
+            }
+
             // ==*== Flow Controls ==*==
             (Post, ["node", "flow_controls", "add_consumer"]) => {
                 encode_response(req, self.add_consumer(ctx, dec.decode()?).await)?
             }
+            (Get, ["node", "flow_controls"]) => encode_response(req, self.list_flow_controls(ctx))?,
+            (Get, ["node", "flow_controls", address]) => {
+                encode_response(req, self.get_address_flow_controls(ctx, address))?
+            }
+
+            // ==*== Configuration ==*==
+            (Post, ["node", "configuration"]) => {
+                encode_response(req, self.reload_configuration(ctx, dec.decode()?).await)?
+            }
+
+            // ==*== Drain ==*==
+            (Post, ["node", "drain"]) => {
+                encode_response(req, self.drain_node(ctx, dec.decode()?).await)?
+            }
+
+            // ==*== Metrics and health ==*==
+            (Get, ["node", "metrics"]) => encode_response(req, self.get_metrics(ctx).await)?,
+            (Get, ["node", "readiness"]) => {
+                encode_response(req, self.get_node_readiness(ctx).await)?
+            }
 
             // ==*== Workers ==*==
             (Get, ["node", "workers"]) => encode_response(req, self.list_workers(ctx).await)?,
+            (Get, ["node", "workers", address]) => {
+                encode_response(req, self.show_worker(ctx, address).await)?
+            }
 
             // ==*== Policies ==*==
-            (Post, ["policy", resource, action]) => {
-                encode_response(req, self.add_policy(resource, action, dec.decode()?).await)?
-            }
+            (Post, ["policy", resource, action]) => encode_response(
+                req,
+                self.add_policy(resource, action, dec.decode()?, requester)
+                    .await,
+            )?,
             (Get, ["policy", resource, action]) => {
                 encode_response(req, self.get_policy(resource, action).await)?
             }
@@ -733,8 +815,16 @@ impl NodeManagerWorker {
                 encode_response(req, self.list_policies(resource).await)?
             }
             (Delete, ["policy", resource, action]) => {
-                encode_response(req, self.delete_policy(resource, action).await)?
+                encode_response(req, self.delete_policy(resource, action, requester).await)?
             }
+            (Get, ["policy", resource, action, "versions"]) => {
+                encode_response(req, self.list_policy_versions(resource, action).await)?
+            }
+            (Post, ["policy", resource, action, "rollback"]) => encode_response(
+                req,
+                self.rollback_policy(resource, action, dec.decode()?, requester)
+                    .await,
+            )?,
 
             // ==*== Messages ==*==
             (Post, ["v0", "message"]) => {
@@ -771,7 +861,27 @@ impl Worker for NodeManagerWorker {
             }
         };
 
-        let r = match self.handle_request(ctx, &req, &mut dec).await {
+        let span = info_span!(
+            "handle_request",
+            method = ?req.method(),
+            path = %req.path()
+        );
+        #[cfg(feature = "opentelemetry")]
+        if let Some(tracing_context) = req.tracing_context().and_then(TracingContext::parse) {
+            tracing_context.set_as_parent_of(&span);
+        }
+
+        // Identity of the sender when the request is received via a secure channel,
+        // used to record who made a change, for example to a policy
+        let requester = IdentitySecureChannelLocalInfo::find_info(msg.local_message())
+            .ok()
+            .map(|info| info.their_identity_id());
+
+        let r = match self
+            .handle_request(ctx, &req, &mut dec, requester)
+            .instrument(span)
+            .await
+        {
             Ok(r) => r,
             Err(err) => {
                 error! {
@@ -0,0 +1,563 @@
+//! Declarative configuration of a node, which can be reloaded while the node is running.
+//!
+//! The configuration is a JSON file describing the policies, TCP outlets, TCP inlets and relays
+//! of the node:
+//!
+//! ```json
+//! {
+//!   "policies": { "my-service": { "handle_message": "(= subject.component \"web\")" } },
+//!   "tcp-outlets": { "web": { "to": "127.0.0.1:8080", "access-control": "(= subject.component \"web\")" } },
+//!   "tcp-inlets": { "db": { "from": "127.0.0.1:5432", "to": "/project/default/service/forward_to_db/secure/api/service/db" } },
+//!   "relays": { "web": { "at": "/project/default" } }
+//! }
+//! ```
+//!
+//! When the configuration is reloaded, with a `POST /node/configuration` request or a `SIGHUP`
+//! signal, it is compared with the configuration which was previously applied and only the
+//! differences are applied: the entries which are unchanged are left untouched, and so are the
+//! secure channels which they use.
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use ockam::identity::Identifier;
+use ockam_abac::{parse, validate, Action, Policy, Resource};
+use ockam_core::api::{Error, Response};
+use ockam_core::{route, Address, Result};
+use ockam_multiaddr::MultiAddr;
+use ockam_node::Context;
+
+use crate::error::ApiError;
+use crate::nodes::models::configuration::{ConfigurationChanges, ReloadConfiguration};
+use crate::nodes::service::actions;
+use crate::nodes::{InMemoryNode, NodeManagerWorker};
+
+/// Declarative configuration of the policies, portals and relays of a node
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct NodeConfiguration {
+    /// Name of the trust context of the node. The trust context is set when the node is created,
+    /// so a different trust context is reported as a change requiring a restart
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trust_context: Option<String>,
+    /// Policy expressions, by resource and action
+    #[serde(default)]
+    pub policies: BTreeMap<String, BTreeMap<String, String>>,
+    /// TCP outlets, by alias
+    #[serde(default)]
+    pub tcp_outlets: BTreeMap<String, OutletConfiguration>,
+    /// TCP inlets, by alias
+    #[serde(default)]
+    pub tcp_inlets: BTreeMap<String, InletConfiguration>,
+    /// Relays, by alias
+    #[serde(default)]
+    pub relays: BTreeMap<String, RelayConfiguration>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct OutletConfiguration {
+    /// Address of the outlet worker. The alias of the outlet is used by default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    /// Socket address of the target
+    pub to: String,
+    /// Policy expression controlling the access to the outlet
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_control: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct InletConfiguration {
+    /// Socket address of the inlet listener
+    pub from: String,
+    /// Route to the outlet
+    pub to: String,
+    /// Identifier of the node running the outlet
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authorized: Option<String>,
+    /// Policy expression controlling the access to the inlet
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_control: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct RelayConfiguration {
+    /// Route to the node where the relay is created
+    pub at: String,
+    /// Identifier of the node where the relay is created
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authorized: Option<String>,
+}
+
+impl NodeConfiguration {
+    /// Parse and validate a configuration, so that an invalid configuration
+    /// is rejected before any of its changes is applied
+    pub fn parse(configuration: &str) -> Result<Self> {
+        let configuration: Self = serde_json::from_str(configuration)
+            .map_err(|e| ApiError::core(format!("invalid node configuration: {e}")))?;
+        configuration.validate()?;
+        Ok(configuration)
+    }
+
+    /// Read a configuration file
+    pub fn read(path: &Path) -> Result<Self> {
+        let configuration = std::fs::read_to_string(path)
+            .map_err(|e| ApiError::core(format!("cannot read the configuration {path:?}: {e}")))?;
+        Self::parse(&configuration)
+    }
+
+    fn validate(&self) -> Result<()> {
+        for ((resource, action), expression) in self.all_policies() {
+            parse_policy(&expression)
+                .map_err(|e| ApiError::core(format!("invalid policy {resource}/{action}: {e}")))?;
+        }
+        for (alias, outlet) in &self.tcp_outlets {
+            SocketAddr::from_str(&outlet.to).map_err(|e| {
+                ApiError::core(format!("invalid target for the tcp outlet {alias}: {e}"))
+            })?;
+        }
+        for (alias, inlet) in &self.tcp_inlets {
+            MultiAddr::from_str(&inlet.to).map_err(|e| {
+                ApiError::core(format!("invalid route for the tcp inlet {alias}: {e}"))
+            })?;
+            parse_identifier(&inlet.authorized)?;
+        }
+        for (alias, relay) in &self.relays {
+            MultiAddr::from_str(&relay.at)
+                .map_err(|e| ApiError::core(format!("invalid route for the relay {alias}: {e}")))?;
+            parse_identifier(&relay.authorized)?;
+        }
+        Ok(())
+    }
+
+    /// Return the policies of the configuration, including the access control of the portals,
+    /// which is set on the resource named after the alias of each portal
+    fn all_policies(&self) -> BTreeMap<(String, String), String> {
+        let mut policies = BTreeMap::new();
+        for (resource, actions) in &self.policies {
+            for (action, expression) in actions {
+                policies.insert((resource.clone(), action.clone()), expression.clone());
+            }
+        }
+        let portals = self
+            .tcp_outlets
+            .iter()
+            .map(|(alias, o)| (alias, &o.access_control))
+            .chain(
+                self.tcp_inlets
+                    .iter()
+                    .map(|(alias, i)| (alias, &i.access_control)),
+            );
+        for (alias, access_control) in portals {
+            if let Some(expression) = access_control {
+                policies.insert(
+                    (alias.clone(), actions::HANDLE_MESSAGE.to_string()),
+                    expression.clone(),
+                );
+            }
+        }
+        policies
+    }
+}
+
+/// Keys of the entries which were added, changed or removed between two configurations
+#[derive(Debug, PartialEq, Eq)]
+struct Diff<K> {
+    added: BTreeSet<K>,
+    changed: BTreeSet<K>,
+    removed: BTreeSet<K>,
+}
+
+impl<K: Ord + Clone> Diff<K> {
+    fn new<V: PartialEq>(old: &BTreeMap<K, V>, new: &BTreeMap<K, V>) -> Self {
+        let mut diff = Diff {
+            added: BTreeSet::new(),
+            changed: BTreeSet::new(),
+            removed: BTreeSet::new(),
+        };
+        for (key, value) in new {
+            match old.get(key) {
+                None => {
+                    diff.added.insert(key.clone());
+                }
+                Some(old_value) if old_value != value => {
+                    diff.changed.insert(key.clone());
+                }
+                _ => (),
+            }
+        }
+        for key in old.keys() {
+            if !new.contains_key(key) {
+                diff.removed.insert(key.clone());
+            }
+        }
+        diff
+    }
+
+    /// Entries which must be deleted before the new configuration is applied
+    fn to_delete(&self) -> impl Iterator<Item = &K> {
+        self.removed.iter().chain(self.changed.iter())
+    }
+
+    /// Entries which must be created to apply the new configuration
+    fn to_create(&self) -> impl Iterator<Item = &K> {
+        self.added.iter().chain(self.changed.iter())
+    }
+}
+
+/// Configuration applied on a node, and the file it was read from
+#[derive(Default)]
+pub(crate) struct AppliedConfiguration {
+    path: Option<PathBuf>,
+    configuration: NodeConfiguration,
+    /// Remote addresses of the relays created from the configuration, by alias
+    relays: BTreeMap<String, String>,
+}
+
+impl NodeManagerWorker {
+    pub(super) async fn reload_configuration(
+        &self,
+        ctx: &Context,
+        request: ReloadConfiguration,
+    ) -> Result<Response<ConfigurationChanges>, Response<Error>> {
+        match self
+            .node_manager
+            .reload_configuration(ctx, request.configuration)
+            .await
+        {
+            Ok(changes) => Ok(Response::ok().body(changes)),
+            Err(e) => Err(Response::bad_request_no_request(&e.to_string())),
+        }
+    }
+}
+
+impl InMemoryNode {
+    /// Apply the configuration file of the node, and keep its path
+    /// so that it can be read again when the configuration is reloaded
+    pub async fn apply_configuration_file(
+        &self,
+        ctx: &Context,
+        path: &Path,
+    ) -> Result<ConfigurationChanges> {
+        let configuration = NodeConfiguration::read(path)?;
+        let changes = self.apply_configuration(ctx, configuration).await;
+        self.configuration.lock().unwrap().path = Some(path.to_path_buf());
+        Ok(changes)
+    }
+
+    /// Reload the configuration of the node, from the given content or from the
+    /// configuration file of the node, and apply the differences with the current configuration
+    pub async fn reload_configuration(
+        &self,
+        ctx: &Context,
+        configuration: Option<String>,
+    ) -> Result<ConfigurationChanges> {
+        let configuration = match configuration {
+            Some(configuration) => NodeConfiguration::parse(&configuration)?,
+            None => {
+                let path = self.configuration.lock().unwrap().path.clone();
+                match path {
+                    Some(path) => NodeConfiguration::read(&path)?,
+                    None => {
+                        return Err(ApiError::core(
+                            "the node was not started with a configuration file",
+                        ))
+                    }
+                }
+            }
+        };
+        Ok(self.apply_configuration(ctx, configuration).await)
+    }
+
+    /// Apply the differences between the new configuration and the current one.
+    /// An entry which cannot be created is not recorded as applied, so that its creation
+    /// is attempted again on the next reload
+    async fn apply_configuration(
+        &self,
+        ctx: &Context,
+        new: NodeConfiguration,
+    ) -> ConfigurationChanges {
+        let (old, mut relay_addresses) = {
+            let applied = self.configuration.lock().unwrap();
+            (applied.configuration.clone(), applied.relays.clone())
+        };
+        let mut applied = new.clone();
+        let mut changes = ConfigurationChanges::default();
+
+        if let Some(name) = &new.trust_context {
+            if !self.uses_trust_context(name).await {
+                changes.skipped.push(format!(
+                    "the trust context can only be changed to {name} by restarting the node"
+                ));
+            }
+        }
+
+        let relays = Diff::new(&old.relays, &new.relays);
+        let inlets = Diff::new(&old.tcp_inlets, &new.tcp_inlets);
+        let outlets = Diff::new(&old.tcp_outlets, &new.tcp_outlets);
+        let policies = Diff::new(&old.all_policies(), &new.all_policies());
+
+        // delete the entries which were removed or changed
+        for alias in relays.to_delete() {
+            let remote_address = match relay_addresses.remove(alias) {
+                Some(remote_address) => Some(remote_address),
+                None => self.persisted_relay_address(alias).await,
+            };
+            let result = match remote_address {
+                Some(remote_address) => self.delete_relay(ctx, &remote_address).await.map(|_| ()),
+                None => Err(ApiError::core("the relay was not found")),
+            };
+            record(&mut changes, format!("deleted the relay {alias}"), result);
+        }
+        for alias in inlets.to_delete() {
+            let result = self.delete_inlet(alias).await.map(|_| ());
+            record(
+                &mut changes,
+                format!("deleted the tcp inlet {alias}"),
+                result,
+            );
+        }
+        for alias in outlets.to_delete() {
+            let result = self.delete_outlet(alias).await.map(|_| ());
+            record(
+                &mut changes,
+                format!("deleted the tcp outlet {alias}"),
+                result,
+            );
+        }
+
+        // policies are set before the portals which they protect are created
+        let new_policies = new.all_policies();
+        for (resource, action) in policies.removed.iter() {
+            let result = self
+                .delete_policy(Resource::new(resource), Action::new(action))
+                .await;
+            record(
+                &mut changes,
+                format!("deleted the policy {resource}/{action}"),
+                result,
+            );
+        }
+        for (resource, action) in policies.to_create() {
+            let expression = &new_policies[&(resource.clone(), action.clone())];
+            let result = match parse_policy(expression) {
+                Ok(policy) => {
+                    self.set_policy(Resource::new(resource), Action::new(action), policy)
+                        .await
+                }
+                Err(e) => Err(e),
+            };
+            record(
+                &mut changes,
+                format!("set the policy {resource}/{action}"),
+                result,
+            );
+        }
+
+        // create the entries which were added or changed
+        for alias in outlets.to_create() {
+            let outlet = &new.tcp_outlets[alias];
+            let result = self.create_configured_outlet(ctx, alias, outlet).await;
+            if result.is_err() {
+                applied.tcp_outlets.remove(alias);
+            }
+            record(
+                &mut changes,
+                format!("created the tcp outlet {alias}"),
+                result,
+            );
+        }
+        for alias in inlets.to_create() {
+            let inlet = &new.tcp_inlets[alias];
+            let result = self.create_configured_inlet(ctx, alias, inlet).await;
+            if result.is_err() {
+                applied.tcp_inlets.remove(alias);
+            }
+            record(
+                &mut changes,
+                format!("created the tcp inlet {alias}"),
+                result,
+            );
+        }
+        for alias in relays.to_create() {
+            let relay = &new.relays[alias];
+            let result = self.create_configured_relay(ctx, alias, relay).await;
+            let result = match result {
+                Ok(remote_address) => {
+                    relay_addresses.insert(alias.clone(), remote_address);
+                    Ok(())
+                }
+                Err(e) => {
+                    applied.relays.remove(alias);
+                    Err(e)
+                }
+            };
+            record(&mut changes, format!("created the relay {alias}"), result);
+        }
+
+        let mut configuration = self.configuration.lock().unwrap();
+        configuration.configuration = applied;
+        configuration.relays = relay_addresses;
+        changes
+    }
+
+    async fn create_configured_outlet(
+        &self,
+        ctx: &Context,
+        alias: &str,
+        outlet: &OutletConfiguration,
+    ) -> Result<()> {
+        let socket_addr = SocketAddr::from_str(&outlet.to).map_err(ApiError::core)?;
+        let worker_addr = Address::from_string(outlet.from.as_deref().unwrap_or(alias));
+        self.create_outlet(
+            ctx,
+            socket_addr,
+            worker_addr,
+            Some(alias.to_string()),
+            true,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn create_configured_inlet(
+        &self,
+        ctx: &Context,
+        alias: &str,
+        inlet: &InletConfiguration,
+    ) -> Result<()> {
+        let outlet_addr = MultiAddr::from_str(&inlet.to).map_err(ApiError::core)?;
+        self.create_inlet(
+            ctx,
+            inlet.from.clone(),
+            Some(alias.to_string()),
+            route![],
+            route![],
+            outlet_addr,
+            None,
+            parse_identifier(&inlet.authorized)?,
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Create a relay, unless it was already recreated when the node restarted,
+    /// and return its remote address
+    async fn create_configured_relay(
+        &self,
+        ctx: &Context,
+        alias: &str,
+        relay: &RelayConfiguration,
+    ) -> Result<String> {
+        let address = MultiAddr::from_str(&relay.at).map_err(ApiError::core)?;
+        if let Some(remote_address) = self.persisted_relay_address(alias).await {
+            if self.registry.relays.contains_key(&remote_address).await {
+                return Ok(remote_address);
+            }
+        }
+        let relay = InMemoryNode::create_relay(
+            self,
+            ctx,
+            &address,
+            Some(alias.to_string()),
+            false,
+            parse_identifier(&relay.authorized)?,
+        )
+        .await?;
+        Ok(relay.remote_address().to_string())
+    }
+
+    /// Return true if the node uses the trust context with the given name
+    async fn uses_trust_context(&self, name: &str) -> bool {
+        match self.cli_state.get_trust_context(name).await {
+            Ok(trust_context) => self.trust_context_id() == Some(trust_context.trust_context_id()),
+            Err(_) => false,
+        }
+    }
+
+    /// Return the remote address of a relay persisted for this node
+    async fn persisted_relay_address(&self, alias: &str) -> Option<String> {
+        self.cli_state
+            .get_node_relays(&self.node_name())
+            .await
+            .ok()?
+            .into_iter()
+            .find(|r| r.alias() == alias)
+            .map(|r| r.remote_address())
+    }
+}
+
+fn parse_policy(expression: &str) -> Result<Policy> {
+    let expression = parse(expression)
+        .map_err(|e| ApiError::core(e.to_string()))?
+        .ok_or_else(|| ApiError::core("the policy expression is empty"))?;
+    validate(&expression).map_err(|e| ApiError::core(e.to_string()))?;
+    Ok(Policy::new(expression))
+}
+
+fn parse_identifier(identifier: &Option<String>) -> Result<Option<Identifier>> {
+    identifier.as_deref().map(Identifier::from_str).transpose()
+}
+
+fn record(changes: &mut ConfigurationChanges, change: String, result: Result<()>) {
+    match result {
+        Ok(()) => {
+            info!("node configuration: {change}");
+            changes.applied.push(change)
+        }
+        Err(e) => {
+            warn!("node configuration: failed to {change}: {e}");
+            changes.failed.push(format!("{change}: {e}"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_configuration() {
+        let configuration = NodeConfiguration::parse(
+            r#"{
+              "policies": { "my-service": { "handle_message": "(= subject.component \"web\")" } },
+              "tcp-outlets": { "web": { "to": "127.0.0.1:8080", "access-control": "(= subject.component \"web\")" } },
+              "relays": { "web": { "at": "/project/default" } }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(configuration.tcp_outlets["web"].to, "127.0.0.1:8080");
+        assert_eq!(configuration.all_policies().len(), 2);
+
+        assert!(
+            NodeConfiguration::parse(r#"{ "tcp-outlets": { "web": { "to": "web" } } }"#).is_err()
+        );
+        assert!(NodeConfiguration::parse(r#"{ "policies": { "r": { "a": "(= a" } } }"#).is_err());
+        assert!(NodeConfiguration::parse(r#"{ "tcp_outlets": {} }"#).is_err());
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = BTreeMap::from([("a", 1), ("b", 2), ("c", 3)]);
+        let new = BTreeMap::from([("b", 2), ("c", 4), ("d", 5)]);
+        let diff = Diff::new(&old, &new);
+        assert_eq!(diff.added, BTreeSet::from(["d"]));
+        assert_eq!(diff.changed, BTreeSet::from(["c"]));
+        assert_eq!(diff.removed, BTreeSet::from(["a"]));
+        assert_eq!(diff.to_delete().collect::<Vec<_>>(), vec![&"a", &"c"]);
+        assert_eq!(diff.to_create().collect::<Vec<_>>(), vec![&"d", &"c"]);
+    }
+}
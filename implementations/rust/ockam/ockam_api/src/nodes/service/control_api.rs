@@ -0,0 +1,292 @@
+//! Local HTTP management API for a running node.
+//!
+//! When a node is started in the foreground it listens on a unix socket located in its
+//! node directory (`<node dir>/api.sock`). Each HTTP request received on that socket is
+//! translated into a node manager request with the same method and path, for example:
+//!
+//!  - `POST /node/inlet` creates an inlet
+//!  - `GET /node/secure_channel` lists secure channels
+//!  - `GET /node/workers` returns the workers and their message counts
+//!
+//! Request and response bodies are the CBOR-encoded node manager models
+//! (`Content-Type: application/cbor`), so any language with a CBOR library can manage a node
+//! without shelling out to the `ockam` command.
+//!
+//! Requests must be authenticated with the token stored next to the socket in
+//! `<node dir>/api.token` (only readable by the user running the node):
+//!
+//! ```text
+//! Authorization: Bearer <token>
+//! ```
+//!
+//! Both files are removed when the node is stopped or deleted.
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use subtle::ConstantTimeEq;
+use tracing::{debug, warn};
+
+use ockam_core::api::{Cbor, Method, Request, Response, Status};
+use ockam_core::compat::rand::{thread_rng, RngCore};
+use ockam_core::{AsyncTryClone, Result};
+use ockam_node::Context;
+
+use crate::error::ApiError;
+use crate::nodes::{NodeManager, NODEMANAGER_ADDR};
+
+/// Name of the unix socket file, in the node directory
+pub const CONTROL_API_SOCKET: &str = "api.sock";
+
+/// Name of the file containing the authentication token, in the node directory
+pub const CONTROL_API_TOKEN: &str = "api.token";
+
+/// Location of the control API socket and token for a given node directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ControlApiPaths {
+    pub socket: PathBuf,
+    pub token: PathBuf,
+}
+
+impl ControlApiPaths {
+    pub fn new(node_dir: &Path) -> Self {
+        Self {
+            socket: node_dir.join(CONTROL_API_SOCKET),
+            token: node_dir.join(CONTROL_API_TOKEN),
+        }
+    }
+
+    /// Remove the socket and token files, if they exist
+    pub fn remove(&self) {
+        for path in [&self.socket, &self.token] {
+            if let Err(e) = std::fs::remove_file(path) {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    warn!("could not remove {}: {e:?}", path.display());
+                }
+            }
+        }
+    }
+}
+
+impl NodeManager {
+    /// Start serving the local HTTP management API on a unix socket in the node directory
+    pub async fn start_control_api(&self, ctx: &Context) -> Result<ControlApiPaths> {
+        let node_dir = self.cli_state.node_dir(&self.node_name);
+        std::fs::create_dir_all(&node_dir).map_err(ApiError::core)?;
+        let paths = ControlApiPaths::new(&node_dir);
+
+        let token = create_token();
+        write_token(&paths.token, &token)?;
+
+        // A socket file can be left behind if the node was not stopped gracefully
+        let _ = std::fs::remove_file(&paths.socket);
+        let server = tiny_http::Server::http_unix(&paths.socket).map_err(ApiError::core)?;
+        debug!("control api listening on {}", paths.socket.display());
+
+        let ctx = ctx.async_try_clone().await?;
+        let runtime = ctx.runtime().clone();
+        tokio::task::spawn_blocking(move || {
+            for mut request in server.incoming_requests() {
+                let response = if is_authorized(&request, &token) {
+                    let mut body = vec![];
+                    let read = request.as_reader().read_to_end(&mut body);
+                    match read {
+                        Ok(_) => {
+                            runtime.block_on(forward(&ctx, request.method(), request.url(), body))
+                        }
+                        Err(e) => text_response(400, &e.to_string()),
+                    }
+                } else {
+                    text_response(401, "missing or invalid bearer token")
+                };
+                if let Err(e) = request.respond(response) {
+                    warn!("could not send a control api response: {e:?}");
+                }
+            }
+        });
+        Ok(paths)
+    }
+}
+
+/// Send an HTTP request to the node manager and translate its response back to HTTP
+async fn forward(
+    ctx: &Context,
+    method: &tiny_http::Method,
+    path: &str,
+    body: Vec<u8>,
+) -> tiny_http::Response<std::io::Cursor<Vec<u8>>> {
+    let method = match to_api_method(method) {
+        Some(method) => method,
+        None => return text_response(405, &format!("unsupported method {method}")),
+    };
+    let request = Request::get(path).method(method);
+    let encoded = if body.is_empty() {
+        request.to_vec()
+    } else {
+        request.body(Cbor(&body)).to_vec()
+    };
+    let encoded = match encoded {
+        Ok(encoded) => encoded,
+        Err(e) => return text_response(400, &e.to_string()),
+    };
+
+    let bytes: Vec<u8> = match ctx.send_and_receive(NODEMANAGER_ADDR, encoded).await {
+        Ok(bytes) => bytes,
+        Err(e) => return text_response(502, &e.to_string()),
+    };
+    let (header, decoder) = match Response::parse_response_header(&bytes) {
+        Ok(parsed) => parsed,
+        Err(e) => return text_response(502, &e.to_string()),
+    };
+    let status = header.status().map(to_http_status).unwrap_or(500);
+    let body = bytes[decoder.position()..].to_vec();
+    cbor_response(status, body)
+}
+
+fn to_api_method(method: &tiny_http::Method) -> Option<Method> {
+    match method {
+        tiny_http::Method::Get => Some(Method::Get),
+        tiny_http::Method::Post => Some(Method::Post),
+        tiny_http::Method::Put => Some(Method::Put),
+        tiny_http::Method::Delete => Some(Method::Delete),
+        tiny_http::Method::Patch => Some(Method::Patch),
+        _ => None,
+    }
+}
+
+fn to_http_status(status: Status) -> u16 {
+    match status {
+        Status::Ok => 200,
+        Status::BadRequest => 400,
+        Status::Unauthorized => 401,
+        Status::Forbidden => 403,
+        Status::NotFound => 404,
+        Status::Conflict => 409,
+        Status::MethodNotAllowed => 405,
+        Status::InternalServerError => 500,
+        Status::NotImplemented => 501,
+    }
+}
+
+fn is_authorized(request: &tiny_http::Request, token: &str) -> bool {
+    request
+        .headers()
+        .iter()
+        .filter(|h| h.field.equiv("Authorization"))
+        .any(|h| is_valid_authorization(h.value.as_str(), token))
+}
+
+/// Compare the authorization header in constant time so that the token can't be guessed
+/// by timing the responses
+fn is_valid_authorization(authorization: &str, token: &str) -> bool {
+    let expected = format!("Bearer {token}");
+    authorization.as_bytes().ct_eq(expected.as_bytes()).into()
+}
+
+fn create_token() -> String {
+    let mut bytes = [0u8; 32];
+    thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+fn write_token(path: &Path, token: &str) -> Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    // The mode only applies to a new file: a token left by a previous run is removed,
+    // so that the new token is never readable by other users
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(ApiError::core(e)),
+        _ => (),
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)
+        .map_err(ApiError::core)?;
+    file.write_all(token.as_bytes()).map_err(ApiError::core)
+}
+
+fn text_response(status: u16, message: &str) -> tiny_http::Response<std::io::Cursor<Vec<u8>>> {
+    tiny_http::Response::from_string(message).with_status_code(status)
+}
+
+fn cbor_response(status: u16, body: Vec<u8>) -> tiny_http::Response<std::io::Cursor<Vec<u8>>> {
+    let content_type = tiny_http::Header::from_bytes("Content-Type", "application/cbor")
+        .expect("the content type header is valid");
+    tiny_http::Response::from_data(body)
+        .with_status_code(status)
+        .with_header(content_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_control_api_paths() {
+        let paths = ControlApiPaths::new(Path::new("/tmp/node"));
+        assert_eq!(paths.socket, PathBuf::from("/tmp/node/api.sock"));
+        assert_eq!(paths.token, PathBuf::from("/tmp/node/api.token"));
+    }
+
+    #[test]
+    fn test_status_mapping() {
+        assert_eq!(to_http_status(Status::Ok), 200);
+        assert_eq!(to_http_status(Status::NotFound), 404);
+        assert_eq!(to_http_status(Status::InternalServerError), 500);
+        assert!(to_api_method(&tiny_http::Method::Head).is_none());
+        assert!(matches!(
+            to_api_method(&tiny_http::Method::Post),
+            Some(Method::Post)
+        ));
+    }
+
+    #[test]
+    fn test_authorization() {
+        let token = create_token();
+        assert!(is_valid_authorization(&format!("Bearer {token}"), &token));
+        assert!(!is_valid_authorization(
+            &format!("Bearer {}", create_token()),
+            &token
+        ));
+        assert!(!is_valid_authorization(&token, &token));
+        assert!(!is_valid_authorization("", &token));
+    }
+
+    #[test]
+    fn test_remove_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = ControlApiPaths::new(dir.path());
+        write_token(&paths.token, &create_token()).unwrap();
+        paths.remove();
+        assert!(!paths.token.exists());
+
+        // removing missing files is not an error
+        paths.remove();
+    }
+
+    #[test]
+    fn test_token_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("api.token");
+        std::fs::write(&path, "previous").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        // an existing token file is replaced with a file only readable by its owner
+        let token = create_token();
+        write_token(&path, &token).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), token);
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn test_tokens_are_random() {
+        let token = create_token();
+        assert_eq!(token.len(), 64);
+        assert_ne!(token, create_token());
+    }
+}
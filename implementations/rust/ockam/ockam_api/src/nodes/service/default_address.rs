@@ -13,6 +13,7 @@ impl DefaultAddress {
     pub const ENROLLMENT_TOKEN_ISSUER: &'static str = "enrollment_token_issuer";
     pub const ENROLLMENT_TOKEN_ACCEPTOR: &'static str = "enrollment_token_acceptor";
     pub const OKTA_IDENTITY_PROVIDER: &'static str = "okta";
+    pub const SAML_IDENTITY_PROVIDER: &'static str = "saml";
     pub const KAFKA_OUTLET: &'static str = "kafka_outlet";
     pub const KAFKA_CONSUMER: &'static str = "kafka_consumer";
     pub const KAFKA_PRODUCER: &'static str = "kafka_producer";
@@ -33,6 +34,7 @@ impl DefaultAddress {
                 | Self::ENROLLMENT_TOKEN_ISSUER
                 | Self::ENROLLMENT_TOKEN_ACCEPTOR
                 | Self::OKTA_IDENTITY_PROVIDER
+                | Self::SAML_IDENTITY_PROVIDER
                 | Self::KAFKA_CONSUMER
                 | Self::KAFKA_PRODUCER
                 | Self::KAFKA_OUTLET
@@ -54,6 +56,7 @@ impl DefaultAddress {
             Self::ENROLLMENT_TOKEN_ISSUER,
             Self::ENROLLMENT_TOKEN_ACCEPTOR,
             Self::OKTA_IDENTITY_PROVIDER,
+            Self::SAML_IDENTITY_PROVIDER,
             Self::KAFKA_CONSUMER,
             Self::KAFKA_PRODUCER,
             Self::KAFKA_OUTLET,
@@ -97,6 +100,9 @@ mod test {
         assert!(DefaultAddress::is_valid(
             DefaultAddress::OKTA_IDENTITY_PROVIDER
         ));
+        assert!(DefaultAddress::is_valid(
+            DefaultAddress::SAML_IDENTITY_PROVIDER
+        ));
         assert!(DefaultAddress::is_valid(DefaultAddress::KAFKA_CONSUMER));
         assert!(DefaultAddress::is_valid(DefaultAddress::KAFKA_PRODUCER));
     }
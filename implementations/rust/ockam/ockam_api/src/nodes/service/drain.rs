@@ -0,0 +1,132 @@
+//! Drain the connections of a node before stopping it, for rolling upgrades without downtime.
+//!
+//! When a node is drained, with a `POST /node/drain` request:
+//!
+//!  - its readiness check fails, so that load balancers stop sending it new traffic
+//!  - its secure channel listeners, TCP inlets and TCP outlets stop accepting new connections.
+//!    The secure channels and portal connections which are already open are left untouched
+//!  - its relays are withdrawn from the nodes where they were registered, so that the relay
+//!    services route the messages to the other nodes registered with the same alias
+//!  - it waits for the open portal connections to be closed, at most for the drain timeout
+//!
+//! The relays, inlets and outlets are not deleted from the node state, so they are created again
+//! when the upgraded node is started.
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use tracing::{debug, info, warn};
+
+use ockam_core::api::{Error, Response};
+use ockam_core::Result;
+use ockam_node::tokio::time::{sleep, Instant};
+use ockam_node::Context;
+
+use crate::nodes::models::drain::{DrainNode, NodeDrained};
+use crate::nodes::{InMemoryNode, NodeManagerWorker};
+
+/// Interval between two checks of the number of open portal connections
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+impl NodeManagerWorker {
+    pub(super) async fn drain_node(
+        &self,
+        ctx: &Context,
+        request: DrainNode,
+    ) -> Result<Response<NodeDrained>, Response<Error>> {
+        match self.node_manager.drain(ctx, request.timeout).await {
+            Ok(drained) => Ok(Response::ok().body(drained)),
+            Err(e) => Err(Response::internal_error_no_request(&e.to_string())),
+        }
+    }
+}
+
+impl InMemoryNode {
+    /// Stop accepting new connections, withdraw the relays of the node and wait, at most for
+    /// `timeout`, until the open portal connections are closed
+    pub async fn drain(&self, ctx: &Context, timeout: Duration) -> Result<NodeDrained> {
+        if self.draining.swap(true, Ordering::SeqCst) {
+            info!("the node is already draining its connections");
+        } else {
+            info!("draining the connections of the node");
+        }
+
+        let mut drained = NodeDrained::default();
+        self.stop_listeners(ctx, &mut drained).await;
+        self.withdraw_relays(ctx, &mut drained).await;
+        drained.remaining_connections = self.wait_for_portal_connections(timeout).await;
+
+        if drained.is_complete() {
+            info!("all the portal connections of the node are closed");
+        } else {
+            warn!(
+                remaining_connections = drained.remaining_connections,
+                "the drain timeout expired before all the portal connections were closed"
+            );
+        }
+        Ok(drained)
+    }
+
+    /// Return true if the node is draining its connections
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::SeqCst)
+    }
+
+    async fn stop_listeners(&self, ctx: &Context, drained: &mut NodeDrained) {
+        for address in self.registry.secure_channel_listeners.keys().await {
+            match self.delete_secure_channel_listener(ctx, &address).await {
+                Ok(_) => drained
+                    .stopped_listeners
+                    .push(format!("secure channel listener {address}")),
+                Err(e) => warn!(%address, %e, "the secure channel listener could not be stopped"),
+            }
+        }
+
+        for (alias, inlet) in self.registry.inlets.entries().await {
+            // The medic would otherwise create the inlet again
+            self.remove_session(&format!("inlet-{alias}"));
+            self.registry.inlets.remove(&alias).await;
+            match self.tcp_transport.stop_inlet(inlet.worker_addr).await {
+                Ok(_) => drained.stopped_listeners.push(format!("tcp inlet {alias}")),
+                Err(e) => warn!(%alias, %e, "the tcp inlet could not be stopped"),
+            }
+        }
+
+        for (alias, outlet) in self.registry.outlets.entries().await {
+            self.registry.outlets.remove(&alias).await;
+            match self.tcp_transport.stop_outlet(outlet.worker_addr).await {
+                Ok(_) => drained
+                    .stopped_listeners
+                    .push(format!("tcp outlet {alias}")),
+                Err(e) => warn!(%alias, %e, "the tcp outlet could not be stopped"),
+            }
+        }
+    }
+
+    async fn withdraw_relays(&self, ctx: &Context, drained: &mut NodeDrained) {
+        for remote_address in self.registry.relays.keys().await {
+            self.remove_session(&format!("relay-{remote_address}"));
+            match self.delete_relay_impl(ctx, &remote_address).await {
+                Ok(_) => drained.withdrawn_relays.push(remote_address),
+                Err(e) => warn!(%remote_address, %e, "the relay could not be withdrawn"),
+            }
+        }
+    }
+
+    /// Wait until there are no more open portal connections, or until the timeout expires.
+    /// Return the number of portal connections which are still open
+    async fn wait_for_portal_connections(&self, timeout: Duration) -> u64 {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = self
+                .tcp_transport
+                .registry()
+                .get_all_portal_connections()
+                .len() as u64;
+            if remaining == 0 || Instant::now() >= deadline {
+                return remaining;
+            }
+            debug!(remaining, "waiting for the portal connections to be closed");
+            sleep(DRAIN_POLL_INTERVAL).await;
+        }
+    }
+}
@@ -1,11 +1,11 @@
 use ockam_core::api::{Error, Response};
-use ockam_core::flow_control::FlowControlId;
-use ockam_core::Result;
+use ockam_core::flow_control::{ConsumerLimits, FlowControlId};
+use ockam_core::{Address, Result};
 use ockam_multiaddr::MultiAddr;
 use ockam_node::Context;
 
 use crate::local_multiaddr_to_route;
-use crate::nodes::models::flow_controls::AddConsumer;
+use crate::nodes::models::flow_controls::{AddConsumer, AddressFlowControlStatus, FlowControlList};
 use crate::nodes::NodeManager;
 
 use super::NodeManagerWorker;
@@ -18,7 +18,12 @@ impl NodeManagerWorker {
     ) -> Result<Response, Response<Error>> {
         match self
             .node_manager
-            .add_consumer(ctx, consumer.address(), consumer.flow_control_id())
+            .add_consumer(
+                ctx,
+                consumer.address(),
+                consumer.flow_control_id(),
+                consumer.limits(),
+            )
             .await
         {
             Ok(None) => Ok(Response::ok()),
@@ -26,17 +31,35 @@ impl NodeManagerWorker {
             Err(e) => Err(Response::internal_error_no_request(&e.to_string())),
         }
     }
+
+    pub(super) fn list_flow_controls(
+        &self,
+        ctx: &Context,
+    ) -> Result<Response<FlowControlList>, Response<Error>> {
+        Ok(Response::ok().body(self.node_manager.list_flow_controls(ctx)))
+    }
+
+    pub(super) fn get_address_flow_controls(
+        &self,
+        ctx: &Context,
+        address: &str,
+    ) -> Result<Response<AddressFlowControlStatus>, Response<Error>> {
+        let address = Address::from_string(address);
+        Ok(Response::ok().body(self.node_manager.get_address_flow_controls(ctx, &address)))
+    }
 }
 
 impl NodeManager {
     /// Add a consumer address for a given flow control id
     /// The given multiaddress must correspond to a route with only one Address
-    /// otherwise a  AddConsumerError is returned
+    /// otherwise a  AddConsumerError is returned.
+    /// When limits are given, the consumer is removed once they are reached
     pub async fn add_consumer(
         &self,
         ctx: &Context,
         consumer: &MultiAddr,
         flow_control_id: &FlowControlId,
+        limits: Option<ConsumerLimits>,
     ) -> Result<Option<AddConsumerError>> {
         let mut route = local_multiaddr_to_route(consumer)?;
 
@@ -48,10 +71,37 @@ impl NodeManager {
             return Ok(Some(AddConsumerError::InvalidAddress(consumer.clone())));
         };
 
-        ctx.flow_controls().add_consumer(address, flow_control_id);
+        match limits {
+            Some(limits) => {
+                ctx.flow_controls()
+                    .add_consumer_with_limits(address, flow_control_id, limits)
+            }
+            None => ctx.flow_controls().add_consumer(address, flow_control_id),
+        }
 
         Ok(None)
     }
+
+    /// Return the spawners, producers and consumers of all the flow controls of the node
+    pub fn list_flow_controls(&self, ctx: &Context) -> FlowControlList {
+        FlowControlList::new(
+            ctx.flow_controls()
+                .get_flow_controls_info()
+                .iter()
+                .map(|info| info.into())
+                .collect(),
+        )
+    }
+
+    /// Return the flow controls in which a worker address takes part, to find out why
+    /// a message can't be sent from a worker to another
+    pub fn get_address_flow_controls(
+        &self,
+        ctx: &Context,
+        address: &Address,
+    ) -> AddressFlowControlStatus {
+        (&ctx.flow_controls().get_address_info(address)).into()
+    }
 }
 
 pub enum AddConsumerError {
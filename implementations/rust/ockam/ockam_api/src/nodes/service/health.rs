@@ -0,0 +1,128 @@
+//! Health and readiness of a running node.
+//!
+//! A node is healthy as soon as its node manager answers requests. It is ready when:
+//!
+//!  - its identity is enrolled, if the node uses a trust context with an authority
+//!  - a valid credential can be obtained from that authority
+//!  - all the relays configured for the node are registered and their connection is not down
+//!  - the node is not draining its connections before being stopped
+//!
+//! The readiness is returned by the `GET /node/readiness` request and, when a metrics listener is
+//! started, both probes are served over HTTP on `GET /healthz` and `GET /readyz`.
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use ockam::identity::utils::now;
+use ockam_core::api::{Error, Response};
+use ockam_core::Result;
+use ockam_node::Context;
+
+use crate::cli_state::EnrollmentStatus;
+use crate::nodes::models::health::{NodeReadiness, ReadinessCheck};
+use crate::nodes::{NodeManager, NodeManagerWorker};
+use crate::session::sessions::ConnectionStatus;
+
+/// Maximum time spent retrieving a credential when checking the readiness of a node
+const CREDENTIAL_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+impl NodeManagerWorker {
+    /// Return the result of the readiness checks of the node
+    pub async fn get_node_readiness(
+        &self,
+        ctx: &Context,
+    ) -> Result<Response<NodeReadiness>, Response<Error>> {
+        match self.node_manager.readiness(ctx).await {
+            Ok(readiness) => Ok(Response::ok().body(readiness)),
+            Err(e) => Err(Response::internal_error_no_request(&e.to_string())),
+        }
+    }
+}
+
+impl NodeManager {
+    /// Check if the node is enrolled, has a valid credential, has registered all its relays
+    /// and is not draining its connections
+    pub async fn readiness(&self, ctx: &Context) -> Result<NodeReadiness> {
+        let mut checks = vec![];
+        if self.draining.load(Ordering::SeqCst) {
+            checks.push(ReadinessCheck::failed(
+                "drain",
+                "the node is draining its connections",
+            ));
+        }
+        let has_authority = self
+            .trust_context
+            .as_ref()
+            .and_then(|tc| tc.authority_identifier())
+            .is_some();
+        if has_authority {
+            checks.push(self.check_enrollment().await?);
+            checks.push(self.check_credential(ctx).await);
+        }
+        checks.extend(self.check_relays().await?);
+        Ok(NodeReadiness::new(checks))
+    }
+
+    async fn check_enrollment(&self) -> Result<ReadinessCheck> {
+        let identifier = self.identifier();
+        let enrolled = self
+            .cli_state
+            .get_identity_enrollments(EnrollmentStatus::Enrolled)
+            .await?
+            .iter()
+            .any(|e| e.identifier() == identifier);
+        Ok(if enrolled {
+            ReadinessCheck::ok("enrollment")
+        } else {
+            ReadinessCheck::failed(
+                "enrollment",
+                format!("the identity {identifier} is not enrolled"),
+            )
+        })
+    }
+
+    async fn check_credential(&self, ctx: &Context) -> ReadinessCheck {
+        let credential = self
+            .get_credential(ctx, &self.identifier(), Some(CREDENTIAL_CHECK_TIMEOUT))
+            .await;
+        let credential = match credential {
+            Ok(Some(credential)) => credential,
+            Ok(None) => return ReadinessCheck::failed("credential", "no credential is available"),
+            Err(e) => {
+                return ReadinessCheck::failed(
+                    "credential",
+                    format!("the credential cannot be retrieved: {e}"),
+                )
+            }
+        };
+        match (credential.get_credential_data(), now()) {
+            (Ok(data), Ok(now)) if data.expires_at > now => ReadinessCheck::ok("credential"),
+            (Ok(_), Ok(_)) => ReadinessCheck::failed("credential", "the credential is expired"),
+            (Err(e), _) | (_, Err(e)) => ReadinessCheck::failed(
+                "credential",
+                format!("the credential cannot be checked: {e}"),
+            ),
+        }
+    }
+
+    async fn check_relays(&self) -> Result<Vec<ReadinessCheck>> {
+        let mut checks = vec![];
+        for relay in self.cli_state.get_node_relays(&self.node_name()).await? {
+            let name = format!("relay {}", relay.alias());
+            let remote_address = relay.remote_address();
+            if self.registry.relays.get(&remote_address).await.is_none() {
+                checks.push(ReadinessCheck::failed(name, "the relay is not registered"));
+                continue;
+            }
+            let status = self
+                .medic_handle
+                .status_of(&format!("relay-{remote_address}"));
+            checks.push(match status {
+                Some(ConnectionStatus::Down) => {
+                    ReadinessCheck::failed(name, "the connection of the relay is down")
+                }
+                _ => ReadinessCheck::ok(name),
+            });
+        }
+        Ok(checks)
+    }
+}
@@ -1,4 +1,5 @@
 use std::ops::Deref;
+use std::sync::Mutex;
 use std::time::Duration;
 
 use futures::executor;
@@ -14,6 +15,7 @@ use crate::cli_state::random_name;
 use crate::cli_state::CliState;
 use crate::cli_state::NamedTrustContext;
 use crate::cloud::ControllerClient;
+use crate::nodes::service::configuration::AppliedConfiguration;
 use crate::nodes::service::default_address::DefaultAddress;
 use crate::nodes::service::{
     NodeManagerGeneralOptions, NodeManagerTransportOptions, NodeManagerTrustOptions,
@@ -36,8 +38,10 @@ use crate::session::sessions::Session;
 ///
 pub struct InMemoryNode {
     pub(crate) node_manager: Arc<NodeManager>,
-    persistent: bool,
+    pub(crate) persistent: bool,
     timeout: Option<Duration>,
+    /// Declarative configuration applied on the node, if any
+    pub(crate) configuration: Mutex<AppliedConfiguration>,
 }
 
 /// This Deref instance makes it easy to access the NodeManager functions from an InMemoryNode
@@ -194,6 +198,7 @@ impl InMemoryNode {
             node_manager: Arc::new(node_manager),
             persistent,
             timeout: None,
+            configuration: Mutex::new(AppliedConfiguration::default()),
         })
     }
 
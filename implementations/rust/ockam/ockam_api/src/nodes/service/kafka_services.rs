@@ -5,19 +5,24 @@ use ockam_abac::expr::{eq, ident, str};
 use ockam_abac::Policy;
 use ockam_core::api::{Error, Response};
 use ockam_core::compat::net::SocketAddr;
+use ockam_core::compat::sync::Arc;
 use ockam_core::route;
 use ockam_multiaddr::MultiAddr;
+use ockam_transport_tcp::TcpOutletConnectionInitializer;
 
 use super::{actions, resources, NodeManagerWorker};
 use crate::error::ApiError;
 use crate::kafka::{
-    ConsumerNodeAddr, KafkaInletController, KafkaPortalListener, KafkaSecureChannelControllerImpl,
-    KAFKA_OUTLET_BOOTSTRAP_ADDRESS, KAFKA_OUTLET_INTERCEPTOR_ADDRESS,
+    ConsumerNodeAddr, KafkaInletController, KafkaPortalListener, KafkaSchemaRegistry,
+    KafkaSecureChannelControllerImpl, KafkaTopicPolicy, KAFKA_OUTLET_BOOTSTRAP_ADDRESS,
+    KAFKA_OUTLET_INTERCEPTOR_ADDRESS,
 };
 use crate::kafka::{OutletManagerService, PrefixRelayService};
+use crate::nodes::models::portal::OutletTls;
 use crate::nodes::models::services::{
-    DeleteServiceRequest, StartKafkaConsumerRequest, StartKafkaDirectRequest,
-    StartKafkaOutletRequest, StartKafkaProducerRequest, StartServiceRequest,
+    DeleteServiceRequest, KafkaSaslCredentials, KafkaSchemaRegistryOptions,
+    StartKafkaConsumerRequest, StartKafkaDirectRequest, StartKafkaOutletRequest,
+    StartKafkaProducerRequest, StartServiceRequest,
 };
 use crate::nodes::registry::{KafkaServiceInfo, KafkaServiceKind};
 use crate::nodes::service::default_address::DefaultAddress;
@@ -31,12 +36,16 @@ impl NodeManagerWorker {
         context: &Context,
         body: StartServiceRequest<StartKafkaOutletRequest>,
     ) -> Result<Response<()>, Response<Error>> {
+        let request = body.request().clone();
         match self
             .node_manager
             .start_kafka_outlet_service(
                 context,
                 Address::from_string(body.address()),
-                body.request().bootstrap_server_addr,
+                request.bootstrap_server_addr,
+                request.bootstrap_server_hostname,
+                request.tls,
+                request.sasl,
             )
             .await
         {
@@ -56,6 +65,14 @@ impl NodeManagerWorker {
                 Ok(multiaddr) => multiaddr,
                 Err(e) => return Err(Response::bad_request_no_request(&e.to_string())),
             };
+        let topic_policy = match request
+            .topic_policy()
+            .map(|rules| KafkaTopicPolicy::from_rules(rules))
+            .transpose()
+        {
+            Ok(topic_policy) => topic_policy.unwrap_or_default(),
+            Err(e) => return Err(Response::bad_request_no_request(&e.to_string())),
+        };
 
         match self
             .node_manager
@@ -67,6 +84,8 @@ impl NodeManagerWorker {
                 request.brokers_port_range(),
                 *request.bootstrap_server_addr(),
                 consumer_route,
+                request.schema_registry().cloned(),
+                topic_policy,
             )
             .await
         {
@@ -85,6 +104,14 @@ impl NodeManagerWorker {
             Ok(multiaddr) => multiaddr,
             Err(e) => return Err(Response::bad_request_no_request(&e.to_string())),
         };
+        let topic_policy = match request
+            .topic_policy()
+            .map(|rules| KafkaTopicPolicy::from_rules(rules))
+            .transpose()
+        {
+            Ok(topic_policy) => topic_policy.unwrap_or_default(),
+            Err(e) => return Err(Response::bad_request_no_request(&e.to_string())),
+        };
 
         match self
             .node_manager
@@ -96,6 +123,8 @@ impl NodeManagerWorker {
                 request.brokers_port_range(),
                 outlet_node_multiaddr,
                 KafkaServiceKind::Consumer,
+                request.schema_registry().cloned(),
+                topic_policy,
             )
             .await
         {
@@ -114,6 +143,14 @@ impl NodeManagerWorker {
             Ok(multiaddr) => multiaddr,
             Err(e) => return Err(Response::bad_request_no_request(&e.to_string())),
         };
+        let topic_policy = match request
+            .topic_policy()
+            .map(|rules| KafkaTopicPolicy::from_rules(rules))
+            .transpose()
+        {
+            Ok(topic_policy) => topic_policy.unwrap_or_default(),
+            Err(e) => return Err(Response::bad_request_no_request(&e.to_string())),
+        };
 
         match self
             .node_manager
@@ -125,6 +162,8 @@ impl NodeManagerWorker {
                 request.brokers_port_range(),
                 outlet_node_multiaddr,
                 KafkaServiceKind::Producer,
+                request.schema_registry().cloned(),
+                topic_policy,
             )
             .await
         {
@@ -171,6 +210,8 @@ impl InMemoryNode {
         brokers_port_range: (u16, u16),
         bootstrap_server_addr: SocketAddr,
         consumer_route: Option<MultiAddr>,
+        schema_registry: Option<KafkaSchemaRegistryOptions>,
+        topic_policy: KafkaTopicPolicy,
     ) -> Result<()> {
         let default_secure_channel_listener_flow_control_id = context
             .flow_controls()
@@ -185,6 +226,8 @@ impl InMemoryNode {
                 self.secure_channels.clone(),
                 self.trust_context()?.id(),
                 default_secure_channel_listener_flow_control_id,
+                None,
+                None,
             )
             .await?;
         }
@@ -196,6 +239,11 @@ impl InMemoryNode {
             Some(KAFKA_OUTLET_BOOTSTRAP_ADDRESS.to_string()),
             false,
             None,
+            None,
+            None,
+            None,
+            None,
+            None,
         )
         .await?;
 
@@ -243,6 +291,8 @@ impl InMemoryNode {
             inlet_controller,
             secure_channel_controller.into_trait(),
             local_interceptor_address.clone(),
+            schema_registry.as_ref().map(KafkaSchemaRegistry::new),
+            topic_policy,
         )
         .await?;
 
@@ -269,6 +319,8 @@ impl InMemoryNode {
         brokers_port_range: (u16, u16),
         outlet_node_multiaddr: MultiAddr,
         kind: KafkaServiceKind,
+        schema_registry: Option<KafkaSchemaRegistryOptions>,
+        topic_policy: KafkaTopicPolicy,
     ) -> Result<()> {
         debug!(
             "outlet_node_multiaddr: {}",
@@ -337,6 +389,8 @@ impl InMemoryNode {
             inlet_controller,
             secure_channel_controller.into_trait(),
             local_interceptor_address.clone(),
+            schema_registry.as_ref().map(KafkaSchemaRegistry::new),
+            topic_policy,
         )
         .await?;
 
@@ -357,7 +411,17 @@ impl NodeManager {
         context: &Context,
         service_address: Address,
         bootstrap_server_addr: SocketAddr,
+        bootstrap_server_hostname: Option<String>,
+        tls: Option<OutletTls>,
+        sasl: Option<KafkaSaslCredentials>,
     ) -> Result<()> {
+        // Every connection to the brokers is authenticated with the credentials of the outlet
+        let connection_initializer: Option<Arc<dyn TcpOutletConnectionInitializer>> = sasl
+            .as_ref()
+            .map(|c| c.authenticator())
+            .transpose()?
+            .map(|authenticator| Arc::new(authenticator) as _);
+
         let default_secure_channel_listener_flow_control_id = context
             .flow_controls()
             .get_flow_control_with_spawner(&DefaultAddress::SECURE_CHANNEL_LISTENER.into())
@@ -377,6 +441,8 @@ impl NodeManager {
                 self.secure_channels.clone(),
                 self.trust_context()?.id(),
                 default_secure_channel_listener_flow_control_id,
+                tls.clone(),
+                sasl,
             )
             .await?;
         }
@@ -389,6 +455,11 @@ impl NodeManager {
                 Some(KAFKA_OUTLET_BOOTSTRAP_ADDRESS.to_string()),
                 false,
                 None,
+                None,
+                bootstrap_server_hostname,
+                None,
+                tls.map(|tls| tls.options()),
+                connection_initializer,
             )
             .await
         {
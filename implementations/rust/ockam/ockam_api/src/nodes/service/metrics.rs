@@ -0,0 +1,381 @@
+//! Metrics of a running node, in the Prometheus text exposition format.
+//!
+//! The metrics are returned by the `GET /node/metrics` request and, when a metrics listener
+//! is started, served over HTTP on `GET /metrics` so that they can be scraped by Prometheus:
+//!
+//! ```text
+//! ockam node create n1 --metrics-listener 127.0.0.1:9090
+//! curl http://127.0.0.1:9090/metrics
+//! ```
+//!
+//! The same listener serves the `/healthz` and `/readyz` probes described in [`super::health`].
+//!
+//! The message and byte counts are the sums over the workers, channels and connections which
+//! are currently running, so they decrease when some of them are stopped and are all exposed
+//! as gauges. The numbers of workers and secure channels rejected because of the limits of the
+//! node are counted since the node started and are exposed as counters.
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::net::SocketAddr;
+
+use minicbor::Decode;
+use tracing::{debug, warn};
+
+use ockam_core::api::{Error, Request, Response};
+use ockam_core::{AsyncTryClone, Result};
+use ockam_node::Context;
+
+use crate::error::ApiError;
+use crate::nodes::models::base::NodeStatus;
+use crate::nodes::models::health::NodeReadiness;
+use crate::nodes::{NodeManager, NodeManagerWorker, NODEMANAGER_ADDR};
+
+/// Content type of the Prometheus text exposition format
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+impl NodeManagerWorker {
+    /// Return the metrics of the node in the Prometheus text format
+    pub async fn get_metrics(&self, ctx: &Context) -> Result<Response<String>, Response<Error>> {
+        match self.node_manager.metrics(ctx).await {
+            Ok(metrics) => Ok(Response::ok().body(metrics)),
+            Err(e) => Err(Response::internal_error_no_request(&e.to_string())),
+        }
+    }
+}
+
+impl NodeManager {
+    /// Return the metrics of the node in the Prometheus text format
+    pub async fn metrics(&self, ctx: &Context) -> Result<String> {
+        let mut metrics = PrometheusMetrics::default();
+
+        let usage = ctx.resource_usage().await?;
+        let mailbox_capacity = usage.limits.mailbox_capacity as u64;
+
+        let workers = self.list_workers(ctx).await?.list;
+        let mut workers_by_type: BTreeMap<String, (u64, u64)> = BTreeMap::new();
+        let mut full_mailboxes = 0;
+        for worker in workers {
+            if worker.mailbox_size.unwrap_or_default() >= mailbox_capacity {
+                full_mailboxes += 1;
+            }
+            let worker_type = worker
+                .worker_type
+                .map(|t| t.to_string().replace(' ', "_"))
+                .unwrap_or_else(|| "other".to_string());
+            let entry = workers_by_type.entry(worker_type).or_default();
+            entry.0 += 1;
+            entry.1 += worker.messages_count.unwrap_or_default();
+        }
+        metrics.gauge(
+            "ockam_workers",
+            "Number of workers and processors running on the node",
+            workers_by_type
+                .iter()
+                .map(|(t, (count, _))| (vec![("type", t.as_str())], *count)),
+        );
+        metrics.gauge(
+            "ockam_worker_messages",
+            "Number of messages routed to the running workers",
+            workers_by_type
+                .iter()
+                .map(|(t, (_, messages))| (vec![("type", t.as_str())], *messages)),
+        );
+
+        metrics.gauge(
+            "ockam_full_mailboxes",
+            "Number of workers with a full mailbox, which makes the senders of messages wait",
+            [(vec![], full_mailboxes)],
+        );
+
+        let registry = self.secure_channels.secure_channel_registry();
+        let limits = [
+            ("workers", usage.limits.max_workers),
+            ("mailbox_messages", Some(usage.limits.mailbox_capacity)),
+            ("secure_channels", registry.max_channels()),
+        ];
+        metrics.gauge(
+            "ockam_resource_limit",
+            "Limits on the resources used by the node, for the resources which are limited",
+            limits.iter().filter_map(|(resource, limit)| {
+                Some((vec![("resource", *resource)], (*limit)? as u64))
+            }),
+        );
+        metrics.counter(
+            "ockam_resource_limit_rejections_total",
+            "Number of workers and secure channels rejected because a limit was reached",
+            [
+                (vec![("resource", "workers")], usage.rejected_workers),
+                (
+                    vec![("resource", "secure_channels")],
+                    registry.rejected_channels(),
+                ),
+            ],
+        );
+
+        let channels = registry.get_channel_list();
+        let stats = channels.iter().filter_map(|c| c.stats());
+        let (mut messages_sent, mut bytes_sent, mut messages_received, mut bytes_received) =
+            (0, 0, 0, 0);
+        for s in stats {
+            messages_sent += s.messages_sent();
+            bytes_sent += s.bytes_sent();
+            messages_received += s.messages_received();
+            bytes_received += s.bytes_received();
+        }
+        metrics.gauge(
+            "ockam_secure_channels",
+            "Number of open secure channels",
+            [(vec![], channels.len() as u64)],
+        );
+        metrics.gauge(
+            "ockam_secure_channel_messages",
+            "Number of payload messages exchanged on the open secure channels",
+            [
+                (vec![("direction", "sent")], messages_sent),
+                (vec![("direction", "received")], messages_received),
+            ],
+        );
+        metrics.gauge(
+            "ockam_secure_channel_bytes",
+            "Number of payload bytes exchanged on the open secure channels",
+            [
+                (vec![("direction", "sent")], bytes_sent),
+                (vec![("direction", "received")], bytes_received),
+            ],
+        );
+
+        metrics.gauge(
+            "ockam_relays",
+            "Number of relays created by the node on other nodes",
+            [(vec![], self.registry.relays.keys().await.len() as u64)],
+        );
+
+        let tcp_registry = self.tcp_transport.registry();
+        let portals = tcp_registry.get_all_portal_connections();
+        let (bytes_sent, bytes_received) = portals.iter().fold((0, 0), |(s, r), p| {
+            (
+                s + p.metrics().bytes_sent(),
+                r + p.metrics().bytes_received(),
+            )
+        });
+        metrics.gauge(
+            "ockam_portal_connections",
+            "Number of open inlet and outlet connections",
+            [(vec![], portals.len() as u64)],
+        );
+        metrics.gauge(
+            "ockam_portal_bytes",
+            "Number of bytes exchanged on the open inlet and outlet connections",
+            [
+                (vec![("direction", "sent")], bytes_sent),
+                (vec![("direction", "received")], bytes_received),
+            ],
+        );
+
+        let connections = tcp_registry.get_all_sender_workers();
+        let mut by_mode: BTreeMap<String, (u64, u64, u64)> = BTreeMap::new();
+        for connection in connections {
+            let entry = by_mode.entry(connection.mode().to_string()).or_default();
+            entry.0 += 1;
+            entry.1 += connection.metrics().bytes_sent();
+            entry.2 += connection.metrics().bytes_received();
+        }
+        metrics.gauge(
+            "ockam_tcp_connections",
+            "Number of open TCP connections",
+            by_mode
+                .iter()
+                .map(|(mode, (count, _, _))| (vec![("mode", mode.as_str())], *count)),
+        );
+        metrics.gauge(
+            "ockam_tcp_bytes",
+            "Number of bytes exchanged on the open TCP connections",
+            by_mode.iter().flat_map(|(mode, (_, sent, received))| {
+                [
+                    (vec![("mode", mode.as_str()), ("direction", "sent")], *sent),
+                    (
+                        vec![("mode", mode.as_str()), ("direction", "received")],
+                        *received,
+                    ),
+                ]
+            }),
+        );
+
+        Ok(metrics.text)
+    }
+
+    /// Serve the metrics of the node over HTTP, on `GET /metrics`, and its health and readiness
+    /// probes on `GET /healthz` and `GET /readyz`.
+    /// Return the address of the listener, which is useful when the given port is 0
+    pub async fn start_metrics_listener(&self, ctx: &Context, address: &str) -> Result<SocketAddr> {
+        let server = tiny_http::Server::http(address).map_err(ApiError::core)?;
+        let address = server
+            .server_addr()
+            .to_ip()
+            .ok_or_else(|| ApiError::core("the metrics listener must be a TCP listener"))?;
+        debug!("metrics listener listening on {address}");
+
+        let ctx = ctx.async_try_clone().await?;
+        let runtime = ctx.runtime().clone();
+        tokio::task::spawn_blocking(move || {
+            for request in server.incoming_requests() {
+                let response = match (request.method(), request.url()) {
+                    (tiny_http::Method::Get, "/metrics") => {
+                        match runtime.block_on(fetch_metrics(&ctx)) {
+                            Ok(metrics) => {
+                                let content_type = tiny_http::Header::from_bytes(
+                                    "Content-Type",
+                                    PROMETHEUS_CONTENT_TYPE,
+                                )
+                                .expect("the content type header is valid");
+                                tiny_http::Response::from_string(metrics).with_header(content_type)
+                            }
+                            Err(e) => tiny_http::Response::from_string(e.to_string())
+                                .with_status_code(500),
+                        }
+                    }
+                    (tiny_http::Method::Get, "/healthz") => {
+                        match runtime.block_on(fetch_health(&ctx)) {
+                            Ok(()) => tiny_http::Response::from_string("ok"),
+                            Err(e) => tiny_http::Response::from_string(e.to_string())
+                                .with_status_code(503),
+                        }
+                    }
+                    (tiny_http::Method::Get, "/readyz") => {
+                        match runtime.block_on(fetch_readiness(&ctx)) {
+                            Ok(readiness) if readiness.is_ready() => {
+                                tiny_http::Response::from_string(readiness.to_string())
+                            }
+                            Ok(readiness) => {
+                                tiny_http::Response::from_string(readiness.to_string())
+                                    .with_status_code(503)
+                            }
+                            Err(e) => tiny_http::Response::from_string(e.to_string())
+                                .with_status_code(503),
+                        }
+                    }
+                    _ => tiny_http::Response::from_string("not found").with_status_code(404),
+                };
+                if let Err(e) = request.respond(response) {
+                    warn!("could not send a metrics response: {e:?}");
+                }
+            }
+        });
+        Ok(address)
+    }
+}
+
+/// Get the metrics from the node manager, so that they are collected
+/// with the same access to the node state as the other requests
+async fn fetch_metrics(ctx: &Context) -> Result<String> {
+    send_to_node_manager(ctx, Request::get("/node/metrics")).await
+}
+
+/// The node is healthy if its node manager can answer a status request
+async fn fetch_health(ctx: &Context) -> Result<()> {
+    send_to_node_manager::<NodeStatus>(ctx, Request::get("/node")).await?;
+    Ok(())
+}
+
+/// Get the result of the readiness checks from the node manager
+async fn fetch_readiness(ctx: &Context) -> Result<NodeReadiness> {
+    send_to_node_manager(ctx, Request::get("/node/readiness")).await
+}
+
+/// Send a request to the node manager and decode the body of its response
+async fn send_to_node_manager<T>(ctx: &Context, request: Request) -> Result<T>
+where
+    T: for<'b> Decode<'b, ()>,
+{
+    let bytes: Vec<u8> = ctx
+        .send_and_receive(NODEMANAGER_ADDR, request.to_vec()?)
+        .await?;
+    let (header, mut decoder) = Response::parse_response_header(&bytes)?;
+    if !header.is_ok() {
+        return Err(ApiError::core(header.parse_err_msg(decoder)));
+    }
+    Ok(decoder.decode::<T>()?)
+}
+
+/// Writer of metrics in the Prometheus text exposition format
+#[derive(Default)]
+struct PrometheusMetrics {
+    text: String,
+}
+
+impl PrometheusMetrics {
+    fn gauge<'a>(
+        &mut self,
+        name: &str,
+        help: &str,
+        samples: impl IntoIterator<Item = (Vec<(&'a str, &'a str)>, u64)>,
+    ) {
+        self.metric(name, help, "gauge", samples)
+    }
+
+    fn counter<'a>(
+        &mut self,
+        name: &str,
+        help: &str,
+        samples: impl IntoIterator<Item = (Vec<(&'a str, &'a str)>, u64)>,
+    ) {
+        self.metric(name, help, "counter", samples)
+    }
+
+    fn metric<'a>(
+        &mut self,
+        name: &str,
+        help: &str,
+        metric_type: &str,
+        samples: impl IntoIterator<Item = (Vec<(&'a str, &'a str)>, u64)>,
+    ) {
+        let _ = writeln!(self.text, "# HELP {name} {help}");
+        let _ = writeln!(self.text, "# TYPE {name} {metric_type}");
+        for (labels, value) in samples {
+            if labels.is_empty() {
+                let _ = writeln!(self.text, "{name} {value}");
+            } else {
+                let labels = labels
+                    .iter()
+                    .map(|(k, v)| format!("{k}=\"{}\"", escape_label_value(v)))
+                    .collect::<Vec<_>>()
+                    .join(",");
+                let _ = writeln!(self.text, "{name}{{{labels}}} {value}");
+            }
+        }
+    }
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prometheus_format() {
+        let mut metrics = PrometheusMetrics::default();
+        metrics.gauge("ockam_relays", "Number of relays", [(vec![], 2)]);
+        metrics.gauge(
+            "ockam_worker_messages",
+            "Number of messages",
+            [(vec![("type", "inlet")], 3), (vec![("type", "a\"b")], 4)],
+        );
+        assert_eq!(
+            metrics.text,
+            r#"# HELP ockam_relays Number of relays
+# TYPE ockam_relays gauge
+ockam_relays 2
+# HELP ockam_worker_messages Number of messages
+# TYPE ockam_worker_messages gauge
+ockam_worker_messages{type="inlet"} 3
+ockam_worker_messages{type="a\"b"} 4
+"#
+        );
+    }
+}
@@ -0,0 +1,54 @@
+use std::net::SocketAddr;
+
+use ockam::identity::IdentitySecureChannelLocalInfo;
+use ockam_abac::expr::str;
+use ockam_abac::{Env, PolicyAccessControl};
+use ockam_core::compat::sync::Arc;
+use ockam_core::{async_trait, LocalInfo, Result};
+use ockam_transport_tcp::TcpOutletConnectionAccessControl;
+
+/// Name of the attribute containing the IP address of an outlet target
+pub(crate) const TARGET_HOST: &str = "target.host";
+
+/// Name of the attribute containing the port of an outlet target
+pub(crate) const TARGET_PORT: &str = "target.port";
+
+/// Evaluate the policy of an outlet each time a new portal connection is requested.
+///
+/// The policy is evaluated with the attributes of the identity at the other end
+/// of the secure channel and with the host and port of the outlet target.
+#[derive(Debug)]
+pub(crate) struct OutletConnectionAccessControl {
+    policy_access_control: Arc<PolicyAccessControl>,
+}
+
+impl OutletConnectionAccessControl {
+    pub(crate) fn new(policy_access_control: Arc<PolicyAccessControl>) -> Self {
+        Self {
+            policy_access_control,
+        }
+    }
+}
+
+/// Return an environment containing the attributes of an outlet target
+pub(crate) fn target_env(target: SocketAddr) -> Env {
+    let mut env = Env::new();
+    env.put(TARGET_HOST, str(target.ip().to_string()));
+    env.put(TARGET_PORT, str(target.port().to_string()));
+    env
+}
+
+#[async_trait]
+impl TcpOutletConnectionAccessControl for OutletConnectionAccessControl {
+    async fn is_authorized(&self, local_info: &[LocalInfo], target: SocketAddr) -> Result<bool> {
+        // connections which are not requested via a secure channel have no attributes
+        let identifier = match IdentitySecureChannelLocalInfo::find_info_from_list(local_info) {
+            Ok(info) => info.their_identity_id(),
+            Err(_) => return Ok(false),
+        };
+
+        self.policy_access_control
+            .is_identity_authorized(identifier, target_env(target))
+            .await
+    }
+}
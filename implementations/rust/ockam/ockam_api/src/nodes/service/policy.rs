@@ -1,10 +1,11 @@
+use ockam::identity::Identifier;
 use ockam_abac::expr::{eq, ident, str};
-use ockam_abac::{Action, Policy, Resource};
+use ockam_abac::{validate, Action, Policy, PolicyVersion, Resource};
 use ockam_core::api::{Error, Request, Response};
 use ockam_core::{async_trait, Result};
 use ockam_node::Context;
 
-use crate::nodes::models::policy::{Expression, PolicyList};
+use crate::nodes::models::policy::{Expression, PolicyList, PolicyVersionList, RollbackPolicy};
 use crate::nodes::{BackgroundNodeClient, NodeManagerWorker};
 
 use super::NodeManager;
@@ -15,11 +16,17 @@ impl NodeManagerWorker {
         resource: &str,
         action: &str,
         policy: Policy,
+        requester: Option<Identifier>,
     ) -> Result<Response<()>, Response<Error>> {
+        if let Err(e) = validate(policy.expression()) {
+            return Err(Response::bad_request_no_request(&format!(
+                "invalid policy expression: {e}"
+            )));
+        }
         let resource = Resource::new(resource);
         let action = Action::new(action);
         self.node_manager
-            .set_policy(resource, action, policy)
+            .set_policy_by(resource, action, policy, requester)
             .await
             .map(|_| Response::ok())
             .map_err(|e| Response::internal_error_no_request(&e.to_string()))
@@ -65,14 +72,69 @@ impl NodeManagerWorker {
         &self,
         resource: &str,
         action: &str,
+        requester: Option<Identifier>,
     ) -> Result<Response<()>, Response<Error>> {
         let resource = Resource::new(resource);
         let action = Action::new(action);
-        match self.node_manager.delete_policy(resource, action).await {
+        match self
+            .node_manager
+            .delete_policy_by(resource, action, requester)
+            .await
+        {
             Ok(_) => Ok(Response::ok()),
             Err(e) => Err(Response::internal_error_no_request(&e.to_string())),
         }
     }
+
+    pub(super) async fn list_policy_versions(
+        &self,
+        resource: &str,
+        action: &str,
+    ) -> Result<Response<PolicyVersionList>, Response<Error>> {
+        let resource = Resource::new(resource);
+        let action = Action::new(action);
+        match self
+            .node_manager
+            .get_policy_versions(&resource, &action)
+            .await
+        {
+            Ok(versions) => Ok(Response::ok().body(PolicyVersionList::new(versions))),
+            Err(e) => Err(Response::internal_error_no_request(&e.to_string())),
+        }
+    }
+
+    pub(super) async fn rollback_policy(
+        &self,
+        resource: &str,
+        action: &str,
+        request: RollbackPolicy,
+        requester: Option<Identifier>,
+    ) -> Result<Response<PolicyVersion>, Response<Error>> {
+        let resource = Resource::new(resource);
+        let action = Action::new(action);
+        let version = request.version();
+        let exists = match self
+            .node_manager
+            .get_policy_versions(&resource, &action)
+            .await
+        {
+            Ok(versions) => versions.iter().any(|v| v.version() == version),
+            Err(e) => return Err(Response::internal_error_no_request(&e.to_string())),
+        };
+        if !exists {
+            return Err(Response::not_found_no_request(&format!(
+                "no version {version} found for the policy {resource}/{action}"
+            )));
+        }
+        match self
+            .node_manager
+            .rollback_policy(resource, action, version, requester)
+            .await
+        {
+            Ok(version) => Ok(Response::ok().body(version)),
+            Err(e) => Err(Response::bad_request_no_request(&e.to_string())),
+        }
+    }
 }
 
 impl NodeManager {
@@ -83,9 +145,22 @@ impl NodeManager {
         action: Action,
         policy: Policy,
     ) -> Result<()> {
+        self.set_policy_by(resource, action, policy, None).await
+    }
+
+    /// Set a policy on a resource accessed with a specific action, on behalf of an identity.
+    /// When no identity is given, the change is attributed to the identity of the node
+    pub async fn set_policy_by(
+        &self,
+        resource: Resource,
+        action: Action,
+        policy: Policy,
+        changed_by: Option<Identifier>,
+    ) -> Result<()> {
+        let changed_by = changed_by.unwrap_or_else(|| self.identifier());
         Ok(self
             .cli_state
-            .set_policy(&resource, &action, &policy)
+            .set_policy_by(&resource, &action, &policy, Some(&changed_by))
             .await?)
     }
 
@@ -102,7 +177,47 @@ impl NodeManager {
     }
 
     pub async fn delete_policy(&self, resource: Resource, action: Action) -> Result<()> {
-        Ok(self.cli_state.delete_policy(&resource, &action).await?)
+        self.delete_policy_by(resource, action, None).await
+    }
+
+    /// Delete a policy on behalf of an identity.
+    /// When no identity is given, the change is attributed to the identity of the node
+    pub async fn delete_policy_by(
+        &self,
+        resource: Resource,
+        action: Action,
+        changed_by: Option<Identifier>,
+    ) -> Result<()> {
+        let changed_by = changed_by.unwrap_or_else(|| self.identifier());
+        Ok(self
+            .cli_state
+            .delete_policy_by(&resource, &action, Some(&changed_by))
+            .await?)
+    }
+
+    /// Return the history of the changes of a policy
+    pub async fn get_policy_versions(
+        &self,
+        resource: &Resource,
+        action: &Action,
+    ) -> Result<Vec<PolicyVersion>> {
+        Ok(self.cli_state.get_policy_versions(resource, action).await?)
+    }
+
+    /// Restore a previous version of a policy, on behalf of an identity.
+    /// When no identity is given, the change is attributed to the identity of the node
+    pub async fn rollback_policy(
+        &self,
+        resource: Resource,
+        action: Action,
+        version: u64,
+        changed_by: Option<Identifier>,
+    ) -> Result<PolicyVersion> {
+        let changed_by = changed_by.unwrap_or_else(|| self.identifier());
+        Ok(self
+            .cli_state
+            .rollback_policy(&resource, &action, version, Some(&changed_by))
+            .await?)
     }
 }
 
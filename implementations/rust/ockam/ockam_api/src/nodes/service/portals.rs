@@ -13,15 +13,21 @@ use ockam_core::{async_trait, route, AsyncTryClone, IncomingAccessControl, Route
 use ockam_multiaddr::proto::Project;
 use ockam_multiaddr::{MultiAddr, Protocol};
 use ockam_node::Context;
-use ockam_transport_tcp::{TcpInletOptions, TcpOutletOptions};
+use ockam_transport_tcp::{
+    TcpInletOptions, TcpOutletAllowlist, TcpOutletConnectionInitializer, TcpOutletOptions,
+    TcpTlsClientOptions,
+};
 
 use crate::error::ApiError;
+use crate::http_headers::HttpHeadersInterceptorFactory;
 use crate::nodes::connection::Connection;
 use crate::nodes::models::portal::{
     CreateInlet, CreateOutlet, InletList, InletStatus, OutletList, OutletStatus,
+    PortalConnectionStatus,
 };
 use crate::nodes::registry::{InletInfo, OutletInfo};
 use crate::nodes::service::default_address::DefaultAddress;
+use crate::nodes::service::outlet_access_control::{target_env, OutletConnectionAccessControl};
 use crate::nodes::service::{actions, random_alias, resources};
 use crate::nodes::{BackgroundNodeClient, InMemoryNode, Policies};
 use crate::session::sessions::{
@@ -105,9 +111,27 @@ impl NodeManagerWorker {
             worker_addr,
             alias,
             reachable_from_default_secure_channel,
-            ..
+            http_headers,
+            allowlist,
+            hostname,
+            tls,
+            kafka_sasl,
         } = create_outlet;
 
+        let allowlist = match allowlist
+            .map(|entries| TcpOutletAllowlist::from_entries(&entries))
+            .transpose()
+        {
+            Ok(allowlist) => allowlist,
+            Err(e) => return Err(Response::bad_request_no_request(&e.to_string())),
+        };
+
+        let connection_initializer: Option<Arc<dyn TcpOutletConnectionInitializer>> =
+            match kafka_sasl.map(|c| c.authenticator()).transpose() {
+                Ok(authenticator) => authenticator.map(|a| Arc::new(a) as _),
+                Err(e) => return Err(Response::bad_request_no_request(&e.to_string())),
+            };
+
         match self
             .node_manager
             .create_outlet(
@@ -117,6 +141,11 @@ impl NodeManagerWorker {
                 alias,
                 reachable_from_default_secure_channel,
                 None,
+                http_headers,
+                hostname,
+                allowlist,
+                tls.map(|tls| tls.options()),
+                connection_initializer,
             )
             .await
         {
@@ -166,6 +195,7 @@ impl NodeManagerWorker {
 
 /// OUTLETS
 impl NodeManager {
+    #[allow(clippy::too_many_arguments)]
     pub async fn create_outlet(
         &self,
         ctx: &Context,
@@ -174,6 +204,11 @@ impl NodeManager {
         alias: Option<String>,
         reachable_from_default_secure_channel: bool,
         access_control: Option<Arc<dyn IncomingAccessControl>>,
+        http_headers: Option<Vec<String>>,
+        hostname: Option<String>,
+        allowlist: Option<TcpOutletAllowlist>,
+        tls: Option<TcpTlsClientOptions>,
+        connection_initializer: Option<Arc<dyn TcpOutletConnectionInitializer>>,
     ) -> Result<OutletStatus> {
         info!(
             "Handling request to create outlet portal at {:?} with worker {:?}",
@@ -196,25 +231,47 @@ impl NodeManager {
             ));
         }
 
-        let access_control = if let Some(access_control) = access_control {
-            access_control
-        } else {
-            self.access_control(
-                &resource,
-                &actions::HANDLE_MESSAGE,
-                self.trust_context_id().as_deref(),
-                None,
-            )
-            .await?
+        let options = match (access_control, self.trust_context_id()) {
+            (Some(access_control), _) => {
+                TcpOutletOptions::new().with_incoming_access_control(access_control)
+            }
+            (None, Some(trust_context_id)) => {
+                // The policy is evaluated when messages are received and, with
+                // the target attributes, each time a new connection is requested
+                let policy_access_control = Arc::new(
+                    self.policy_access_control(
+                        &resource,
+                        &actions::HANDLE_MESSAGE,
+                        &trust_context_id,
+                        None,
+                        target_env(socket_addr),
+                    )
+                    .await?,
+                );
+                TcpOutletOptions::new()
+                    .with_incoming_access_control(policy_access_control.clone())
+                    .with_connection_access_control(Arc::new(OutletConnectionAccessControl::new(
+                        policy_access_control,
+                    )))
+            }
+            (None, None) => TcpOutletOptions::new(),
         };
-
-        let options = TcpOutletOptions::new().with_incoming_access_control(access_control);
         let options = if self.trust_context_id().is_none() {
             options.as_consumer(&self.api_transport_flow_control_id)
         } else {
             options
         };
 
+        let options = match http_headers {
+            Some(attributes) => {
+                options.with_interceptor(Arc::new(HttpHeadersInterceptorFactory::new(
+                    self.identity_attributes_repository(),
+                    attributes,
+                )?))
+            }
+            None => options,
+        };
+
         let options = if reachable_from_default_secure_channel {
             // Accept messages from the default secure channel listener
             if let Some(flow_control_id) = ctx
@@ -229,10 +286,39 @@ impl NodeManager {
             options
         };
 
-        let res = self
-            .tcp_transport
-            .create_tcp_outlet(worker_addr.clone(), socket_addr, options)
-            .await;
+        let options = match allowlist {
+            Some(allowlist) => options.with_allowlist(allowlist),
+            None => options,
+        };
+
+        let options = match tls {
+            Some(tls) => options.with_tls(tls),
+            None => options,
+        };
+
+        let options = match connection_initializer {
+            Some(initializer) => options.with_connection_initializer(initializer),
+            None => options,
+        };
+
+        // Use the host name of the target when it is known, so that it can be
+        // resolved again for each connection
+        let res = match hostname {
+            Some(hostname) => {
+                self.tcp_transport
+                    .create_outlet(
+                        worker_addr.clone(),
+                        format!("{hostname}:{}", socket_addr.port()),
+                        options,
+                    )
+                    .await
+            }
+            None => {
+                self.tcp_transport
+                    .create_tcp_outlet(worker_addr.clone(), socket_addr, options)
+                    .await
+            }
+        };
 
         Ok(match res {
             Ok(_) => {
@@ -282,12 +368,16 @@ impl NodeManager {
         info!(%alias, "Handling request to show outlet portal");
         if let Some(outlet_to_show) = self.registry.outlets.get(alias).await {
             debug!(%alias, "Outlet not found in node registry");
-            Some(OutletStatus::new(
-                outlet_to_show.socket_addr,
-                outlet_to_show.worker_addr.clone(),
-                alias,
-                None,
-            ))
+            let connections = self.portal_connections(&outlet_to_show.worker_addr);
+            Some(
+                OutletStatus::new(
+                    outlet_to_show.socket_addr,
+                    outlet_to_show.worker_addr.clone(),
+                    alias,
+                    None,
+                )
+                .with_connections(connections),
+            )
         } else {
             error!(%alias, "Outlet not found in the node registry");
             None
@@ -295,6 +385,19 @@ impl NodeManager {
     }
 }
 
+/// PORTAL CONNECTIONS
+impl NodeManager {
+    /// Return the connections going through the inlet or outlet with the given address
+    fn portal_connections(&self, address: &Address) -> Vec<PortalConnectionStatus> {
+        self.tcp_transport
+            .registry()
+            .get_portal_connections(address)
+            .iter()
+            .map(PortalConnectionStatus::from)
+            .collect()
+    }
+}
+
 /// INLETS
 impl NodeManager {
     pub async fn create_inlet(
@@ -480,14 +583,18 @@ impl NodeManager {
                 .unwrap_or(ConnectionStatus::Down);
 
             debug!(%alias, "Inlet not found in node registry");
-            Some(InletStatus::new(
-                inlet_to_show.bind_addr.to_string(),
-                inlet_to_show.worker_addr.address(),
-                alias,
-                None,
-                inlet_to_show.outlet_route.to_string(),
-                status,
-            ))
+            let connections = self.portal_connections(&inlet_to_show.worker_addr);
+            Some(
+                InletStatus::new(
+                    inlet_to_show.bind_addr.to_string(),
+                    inlet_to_show.worker_addr.address(),
+                    alias,
+                    None,
+                    inlet_to_show.outlet_route.to_string(),
+                    status,
+                )
+                .with_connections(connections),
+            )
         } else {
             error!(%alias, "Inlet not found in the node registry");
             None
@@ -580,6 +687,7 @@ impl InMemoryNode {
                 self.node_manager.clone(),
                 connection_ctx,
                 connection,
+                inlet.alias.clone(),
                 Address::from_string(inlet.worker_addr.clone()),
                 listen_addr,
                 outlet_addr,
@@ -604,6 +712,7 @@ impl InMemoryNode {
         node_manager: Arc<NodeManager>,
         ctx: Arc<Context>,
         connection: Connection,
+        alias: String,
         inlet_address: Address,
         bind: String,
         addr: MultiAddr,
@@ -617,6 +726,7 @@ impl InMemoryNode {
         let node_manager = node_manager.clone();
 
         Box::new(move |previous_addr| {
+            let alias = alias.clone();
             let addr = addr.clone();
             let authorized = authorized.clone();
             let bind = bind.clone();
@@ -685,7 +795,14 @@ impl InMemoryNode {
                         .create_inlet(bind, normalized_route, options)
                         .await?
                         .1;
-                    *inlet_address_arc.lock().unwrap() = new_inlet_address;
+                    *inlet_address_arc.lock().unwrap() = new_inlet_address.clone();
+
+                    // Keep the registry pointing to the running inlet, to stop it and
+                    // to find its connections
+                    if let Some(mut inlet) = node_manager.registry.inlets.get(&alias).await {
+                        inlet.worker_addr = new_inlet_address;
+                        node_manager.registry.inlets.insert(alias, inlet).await;
+                    }
 
                     Ok(new_connection.transport_route())
                 };
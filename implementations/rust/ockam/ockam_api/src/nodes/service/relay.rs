@@ -15,9 +15,10 @@ use ockam_multiaddr::{MultiAddr, Protocol};
 use ockam_node::tokio::time::timeout;
 use ockam_node::Context;
 
+use crate::cli_state::NamedRelay;
 use crate::error::ApiError;
 use crate::nodes::connection::Connection;
-use crate::nodes::models::relay::{CreateRelay, RelayInfo};
+use crate::nodes::models::relay::{CreateRelay, RelayInfo, ServedRelayInfo};
 use crate::nodes::models::secure_channel::{
     CreateSecureChannelRequest, CreateSecureChannelResponse,
 };
@@ -63,11 +64,7 @@ impl NodeManagerWorker {
     ) -> Result<Response<Option<RelayInfo>>, Response<Error>> {
         debug!(%remote_address , "Handling DeleteRelay request");
 
-        match self
-            .node_manager
-            .delete_relay_impl(ctx, remote_address)
-            .await
-        {
+        match self.node_manager.delete_relay(ctx, remote_address).await {
             Ok(body) => Ok(Response::ok().with_headers(req).body(body)),
             Err(err) => match err.code().kind {
                 Kind::NotFound => Err(Response::not_found(
@@ -99,6 +96,16 @@ impl NodeManagerWorker {
             .with_headers(req)
             .body(self.node_manager.get_relays().await))
     }
+
+    pub fn get_served_relays(
+        &self,
+        req: &RequestHeader,
+    ) -> Result<Response<Vec<ServedRelayInfo>>, Response<Error>> {
+        debug!("Handling GetServedRelays request");
+        Ok(Response::ok()
+            .with_headers(req)
+            .body(self.node_manager.get_served_relays()))
+    }
 }
 
 impl NodeManager {
@@ -117,6 +124,21 @@ impl NodeManager {
         relays
     }
 
+    /// This function returns the statistics of the relays created by other nodes
+    /// with the relay service of this node
+    pub fn get_served_relays(&self) -> Vec<ServedRelayInfo> {
+        self.relay_service_statistics
+            .as_ref()
+            .map(|statistics| {
+                statistics
+                    .relays()
+                    .into_iter()
+                    .map(ServedRelayInfo::from)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     /// Create a new Relay
     /// The Connection encapsulates the list of workers required on the relay route.
     /// This route is monitored in the `InMemoryNode` and the workers are restarted if necessary
@@ -262,6 +284,21 @@ impl InMemoryNode {
             )
             .await?;
 
+        // Persist the relay definition so that it can be recreated when the node restarts
+        if self.persistent {
+            if let Some(alias) = &alias {
+                let named_relay = NamedRelay::new(
+                    &self.node_name(),
+                    alias,
+                    relay.remote_address(),
+                    address.clone(),
+                    at_rust_node,
+                    authorized.clone(),
+                );
+                self.cli_state.store_relay(&named_relay).await?;
+            }
+        }
+
         if !at_rust_node && !connection.transport_route().is_empty() {
             let ping_route = connection.transport_route().clone();
             let repl = Self::relay_replacer(
@@ -284,13 +321,40 @@ impl InMemoryNode {
         ctx: &Context,
         remote_address: &str,
     ) -> Result<Option<RelayInfo>, ockam::Error> {
-        if let Some(relay) = self.registry.relays.remove(remote_address).await {
+        if let Some(relay) = self.registry.relays.get(remote_address).await {
             let session_id = format!("relay-{}", relay.remote_address());
             self.remove_session(&session_id);
         }
+        self.cli_state
+            .delete_relay(&self.node_name(), remote_address)
+            .await?;
         self.delete_relay_impl(ctx, remote_address).await
     }
 
+    /// Recreate the relays which were persisted for this node before it was stopped.
+    ///
+    /// A relay which cannot be recreated is skipped and its definition is kept
+    /// so that it can be recreated on the next restart
+    pub async fn restore_relays(&self, ctx: &Context) -> Result<()> {
+        let relays = self.cli_state.get_node_relays(&self.node_name()).await?;
+        for relay in relays {
+            debug!(alias = %relay.alias(), address = %relay.address(), "recreating relay");
+            if let Err(err) = self
+                .create_relay(
+                    ctx,
+                    &relay.address(),
+                    Some(relay.alias()),
+                    relay.at_rust_node(),
+                    relay.authorized(),
+                )
+                .await
+            {
+                warn!(alias = %relay.alias(), address = %relay.address(), %err, "cannot recreate relay");
+            }
+        }
+        Ok(())
+    }
+
     /// Create a session replacer.
     ///
     /// This returns a function that accepts the previous ping address (e.g.
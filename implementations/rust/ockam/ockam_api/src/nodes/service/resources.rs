@@ -2,3 +2,5 @@ use ockam_abac::Resource;
 
 pub const INLET: Resource = Resource::assert_inline("tcp-inlet");
 pub const OUTLET: Resource = Resource::assert_inline("tcp-outlet");
+pub const UDP_INLET: Resource = Resource::assert_inline("udp-inlet");
+pub const UDP_OUTLET: Resource = Resource::assert_inline("udp-outlet");
@@ -91,15 +91,15 @@ impl NodeManagerWorker {
     ) -> Result<Response<ShowSecureChannelResponse>, Response<Error>> {
         let ShowSecureChannelRequest { channel: address } = show_secure_channel;
 
-        let response =
-            self.node_manager
-                .get_secure_channel(&address)
-                .await
-                .map(|secure_channel| {
-                    Response::ok().body(ShowSecureChannelResponse::new(Some(secure_channel)))
-                })?;
+        let secure_channel = self.node_manager.get_secure_channel(&address).await?;
+        let details = self
+            .node_manager
+            .secure_channels
+            .get_secure_channel_details(&address)
+            .await?;
 
-        Ok(response)
+        Ok(Response::ok()
+            .body(ShowSecureChannelResponse::new(Some(secure_channel)).with_details(details)))
     }
 }
 
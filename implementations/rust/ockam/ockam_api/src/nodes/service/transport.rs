@@ -3,7 +3,7 @@ use std::net::SocketAddr;
 use ockam::Result;
 use ockam_core::api::{Error, RequestHeader, Response};
 use ockam_node::Context;
-use ockam_transport_tcp::{TcpConnectionOptions, TcpListenerOptions};
+use ockam_transport_tcp::{TcpConnectionOptions, TcpListenerOptions, TcpProxy};
 
 use super::{NodeManager, NodeManagerWorker};
 use crate::nodes::models::transport::{
@@ -46,9 +46,13 @@ impl NodeManager {
     async fn create_tcp_connection(
         &self,
         address: String,
+        proxy: Option<TcpProxy>,
         ctx: &Context,
     ) -> Result<TransportStatus> {
-        let options = TcpConnectionOptions::new();
+        let mut options = TcpConnectionOptions::new();
+        if let Some(proxy) = proxy {
+            options = options.with_proxy(proxy);
+        }
 
         // Add all Hop workers as consumers for Demo purposes
         // Production nodes should not run any Hop workers
@@ -148,11 +152,20 @@ impl NodeManagerWorker {
         ctx: &Context,
         create: CreateTcpConnection,
     ) -> Result<Response<TransportStatus>, Response<Error>> {
-        let CreateTcpConnection { addr, .. } = create;
+        let CreateTcpConnection { addr, proxy } = create;
         info!("Handling request to create a new TCP connection: {addr}");
 
+        let proxy = match proxy.map(|proxy| proxy.parse::<TcpProxy>()).transpose() {
+            Ok(proxy) => proxy,
+            Err(e) => {
+                return Err(Response::bad_request_no_request(&format!(
+                    "Invalid proxy: {e}"
+                )))
+            }
+        };
+
         self.node_manager
-            .create_tcp_connection(addr.to_string(), ctx)
+            .create_tcp_connection(addr.to_string(), proxy, ctx)
             .await
             .map(|status| Response::ok().body(status))
             .map_err(|msg| {
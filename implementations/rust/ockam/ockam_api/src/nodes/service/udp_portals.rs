@@ -0,0 +1,352 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use ockam::{Address, Result};
+use ockam_abac::Resource;
+use ockam_core::api::{Error, Response};
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{AsyncTryClone, Route};
+use ockam_multiaddr::MultiAddr;
+use ockam_node::Context;
+use ockam_transport_udp::{UdpInletOptions, UdpOutletOptions, UdpTransport};
+
+use crate::nodes::models::udp_portal::{
+    CreateUdpInlet, CreateUdpOutlet, UdpInletList, UdpInletStatus, UdpOutletList, UdpOutletStatus,
+};
+use crate::nodes::registry::{InletInfo, OutletInfo};
+use crate::nodes::service::default_address::DefaultAddress;
+use crate::nodes::service::{actions, random_alias, resources};
+
+use super::{NodeManager, NodeManagerWorker};
+
+/// UDP INLETS
+impl NodeManagerWorker {
+    pub(super) async fn get_udp_inlets(&self) -> Result<Response<UdpInletList>, Response<Error>> {
+        Ok(Response::ok().body(self.node_manager.list_udp_inlets().await))
+    }
+
+    pub(super) async fn create_udp_inlet(
+        &self,
+        ctx: &Context,
+        create_inlet: CreateUdpInlet,
+    ) -> Result<Response<UdpInletStatus>, Response<Error>> {
+        let CreateUdpInlet {
+            listen_addr,
+            outlet_addr,
+            alias,
+        } = create_inlet;
+        match self
+            .node_manager
+            .create_udp_inlet(ctx, listen_addr, outlet_addr, alias)
+            .await
+        {
+            Ok(status) => Ok(Response::ok().body(status)),
+            Err(e) => Err(Response::bad_request_no_request(&format!("{e:?}"))),
+        }
+    }
+
+    pub(super) async fn delete_udp_inlet(
+        &self,
+        alias: &str,
+    ) -> Result<Response<UdpInletStatus>, Response<Error>> {
+        match self.node_manager.delete_udp_inlet(alias).await {
+            Ok(Some(status)) => Ok(Response::ok().body(status)),
+            Ok(None) => Err(Response::not_found_no_request(&format!(
+                "UDP inlet with alias {alias} not found"
+            ))),
+            Err(e) => Err(Response::bad_request_no_request(&format!("{e:?}"))),
+        }
+    }
+}
+
+/// UDP OUTLETS
+impl NodeManagerWorker {
+    pub(super) async fn get_udp_outlets(&self) -> Result<Response<UdpOutletList>, Response<Error>> {
+        Ok(Response::ok().body(self.node_manager.list_udp_outlets().await))
+    }
+
+    pub(super) async fn create_udp_outlet(
+        &self,
+        ctx: &Context,
+        create_outlet: CreateUdpOutlet,
+    ) -> Result<Response<UdpOutletStatus>, Response<Error>> {
+        let CreateUdpOutlet {
+            socket_addr,
+            worker_addr,
+            alias,
+        } = create_outlet;
+        match self
+            .node_manager
+            .create_udp_outlet(ctx, socket_addr, worker_addr, alias)
+            .await
+        {
+            Ok(status) => Ok(Response::ok().body(status)),
+            Err(e) => Err(Response::bad_request_no_request(&format!("{e:?}"))),
+        }
+    }
+
+    pub(super) async fn delete_udp_outlet(
+        &self,
+        alias: &str,
+    ) -> Result<Response<UdpOutletStatus>, Response<Error>> {
+        match self.node_manager.delete_udp_outlet(alias).await {
+            Ok(Some(status)) => Ok(Response::ok().body(status)),
+            Ok(None) => Err(Response::not_found_no_request(&format!(
+                "UDP outlet with alias {alias} not found"
+            ))),
+            Err(e) => Err(Response::bad_request_no_request(&format!("{e:?}"))),
+        }
+    }
+}
+
+/// UDP INLETS
+impl NodeManager {
+    /// Create a UDP inlet receiving datagrams on `listen_addr` and sending them to the
+    /// outlet at `outlet_addr`. The route to the outlet is resolved once, when the inlet
+    /// is created
+    pub async fn create_udp_inlet(
+        &self,
+        ctx: &Context,
+        listen_addr: String,
+        outlet_addr: MultiAddr,
+        alias: Option<String>,
+    ) -> Result<UdpInletStatus> {
+        info!("Handling request to create UDP inlet portal");
+        let resource = alias
+            .as_deref()
+            .map(Resource::new)
+            .unwrap_or(resources::UDP_INLET);
+        let alias = alias.unwrap_or_else(random_alias);
+
+        if self.registry.udp_inlets.contains_key(&alias).await {
+            let message = format!("A UDP inlet with alias '{alias}' already exists");
+            return Err(ockam_core::Error::new(
+                Origin::Node,
+                Kind::AlreadyExists,
+                message,
+            ));
+        }
+
+        let connection_ctx = Arc::new(ctx.async_try_clone().await?);
+        let connection = self
+            .make_connection(
+                connection_ctx,
+                &outlet_addr,
+                self.identifier(),
+                None,
+                None,
+                None,
+            )
+            .await?;
+        let outlet_route: Route = connection.route(self.tcp_transport()).await?;
+
+        let access_control = self
+            .access_control(
+                &resource,
+                &actions::HANDLE_MESSAGE,
+                self.trust_context_id().as_deref(),
+                None,
+            )
+            .await?;
+        let options = UdpInletOptions::new().with_incoming_access_control(access_control);
+
+        let (socket_addr, worker_addr) = match self
+            .udp_transport(ctx)
+            .await?
+            .create_inlet(&listen_addr, outlet_route.clone(), options)
+            .await
+        {
+            Ok(inlet) => inlet,
+            Err(e) => {
+                warn!(to = %outlet_addr, err = %e, "Failed to create UDP inlet");
+                let message = format!("Failed to create UDP inlet: {}", e);
+                return Err(ockam_core::Error::new(
+                    Origin::Node,
+                    Kind::Internal,
+                    message,
+                ));
+            }
+        };
+
+        // When using the port 0, the chosen port is in the returned socket address
+        let listen_addr = socket_addr.to_string();
+        self.registry
+            .udp_inlets
+            .insert(
+                alias.clone(),
+                InletInfo::new(&listen_addr, Some(&worker_addr), &outlet_route),
+            )
+            .await;
+
+        Ok(UdpInletStatus::new(
+            listen_addr,
+            worker_addr.to_string(),
+            alias,
+            outlet_route.to_string(),
+        ))
+    }
+
+    pub async fn delete_udp_inlet(&self, alias: &str) -> Result<Option<UdpInletStatus>> {
+        info!(%alias, "Handling request to delete UDP inlet portal");
+        let inlet = match self.registry.udp_inlets.remove(alias).await {
+            Some(inlet) => inlet,
+            None => {
+                warn!(%alias, "UDP inlet not found in the node registry");
+                return Ok(None);
+            }
+        };
+        if let Some(udp_transport) = self.udp_transport.get() {
+            udp_transport.stop_inlet(inlet.worker_addr.clone()).await?;
+        }
+        Ok(Some(UdpInletStatus::new(
+            inlet.bind_addr,
+            inlet.worker_addr.to_string(),
+            alias,
+            inlet.outlet_route.to_string(),
+        )))
+    }
+
+    pub async fn list_udp_inlets(&self) -> UdpInletList {
+        UdpInletList::new(
+            self.registry
+                .udp_inlets
+                .entries()
+                .await
+                .into_iter()
+                .map(|(alias, info)| {
+                    UdpInletStatus::new(
+                        info.bind_addr,
+                        info.worker_addr.to_string(),
+                        alias,
+                        info.outlet_route.to_string(),
+                    )
+                })
+                .collect(),
+        )
+    }
+}
+
+/// UDP OUTLETS
+impl NodeManager {
+    /// Create a UDP outlet at `worker_addr` sending the datagrams of each inlet session
+    /// to `socket_addr`
+    pub async fn create_udp_outlet(
+        &self,
+        ctx: &Context,
+        socket_addr: SocketAddr,
+        worker_addr: Address,
+        alias: Option<String>,
+    ) -> Result<UdpOutletStatus> {
+        info!(
+            "Handling request to create UDP outlet portal at {:?} with worker {:?}",
+            socket_addr, worker_addr
+        );
+        let resource = alias
+            .as_deref()
+            .map(Resource::new)
+            .unwrap_or(resources::UDP_OUTLET);
+        let alias = alias.unwrap_or_else(random_alias);
+
+        if self.registry.udp_outlets.contains_key(&alias).await {
+            let message = format!("A UDP outlet with alias '{alias}' already exists");
+            return Err(ockam_core::Error::new(
+                Origin::Node,
+                Kind::AlreadyExists,
+                message,
+            ));
+        }
+
+        let access_control = self
+            .access_control(
+                &resource,
+                &actions::HANDLE_MESSAGE,
+                self.trust_context_id().as_deref(),
+                None,
+            )
+            .await?;
+        let options = UdpOutletOptions::new().with_incoming_access_control(access_control);
+        let options = if self.trust_context_id().is_none() {
+            options.as_consumer(&self.api_transport_flow_control_id)
+        } else {
+            options
+        };
+
+        // Accept messages from the default secure channel listener
+        let options = match ctx
+            .flow_controls()
+            .get_flow_control_with_spawner(&DefaultAddress::SECURE_CHANNEL_LISTENER.into())
+        {
+            Some(flow_control_id) => options.as_consumer(&flow_control_id),
+            None => options,
+        };
+
+        if let Err(e) = self
+            .udp_transport(ctx)
+            .await?
+            .create_outlet(worker_addr.clone(), socket_addr.to_string(), options)
+            .await
+        {
+            warn!(at = %socket_addr, err = %e, "Failed to create UDP outlet");
+            let message = format!("Failed to create UDP outlet: {}", e);
+            return Err(ockam_core::Error::new(
+                Origin::Node,
+                Kind::Internal,
+                message,
+            ));
+        }
+
+        self.registry
+            .udp_outlets
+            .insert(
+                alias.clone(),
+                OutletInfo::new(&socket_addr, Some(&worker_addr)),
+            )
+            .await;
+
+        Ok(UdpOutletStatus::new(socket_addr, worker_addr, alias))
+    }
+
+    pub async fn delete_udp_outlet(&self, alias: &str) -> Result<Option<UdpOutletStatus>> {
+        info!(%alias, "Handling request to delete UDP outlet portal");
+        let outlet = match self.registry.udp_outlets.remove(alias).await {
+            Some(outlet) => outlet,
+            None => {
+                warn!(%alias, "UDP outlet not found in the node registry");
+                return Ok(None);
+            }
+        };
+        if let Some(udp_transport) = self.udp_transport.get() {
+            udp_transport
+                .stop_outlet(outlet.worker_addr.clone())
+                .await?;
+        }
+        Ok(Some(UdpOutletStatus::new(
+            outlet.socket_addr,
+            outlet.worker_addr,
+            alias,
+        )))
+    }
+
+    pub async fn list_udp_outlets(&self) -> UdpOutletList {
+        UdpOutletList::new(
+            self.registry
+                .udp_outlets
+                .entries()
+                .await
+                .into_iter()
+                .map(|(alias, info)| {
+                    UdpOutletStatus::new(info.socket_addr, info.worker_addr, alias)
+                })
+                .collect(),
+        )
+    }
+}
+
+impl NodeManager {
+    /// Return the UDP transport of the node, creating it if needed
+    async fn udp_transport(&self, ctx: &Context) -> Result<&UdpTransport> {
+        self.udp_transport
+            .get_or_try_init(|| UdpTransport::create(ctx))
+            .await
+    }
+}
@@ -1,8 +1,10 @@
-use crate::nodes::models::workers::{WorkerList, WorkerStatus};
-use crate::nodes::NodeManagerWorker;
+use std::collections::BTreeMap;
+
+use crate::nodes::models::workers::{WorkerList, WorkerStatus, WorkerType};
+use crate::nodes::{NodeManager, NodeManagerWorker};
 use ockam_core::api::{Error, Response};
-use ockam_core::Result;
-use ockam_node::Context;
+use ockam_core::{Address, Result};
+use ockam_node::{Context, WorkerInfo};
 
 impl NodeManagerWorker {
     /// Return the current list of workers
@@ -10,16 +12,99 @@ impl NodeManagerWorker {
         &self,
         ctx: &Context,
     ) -> Result<Response<WorkerList>, Response<Error>> {
-        let workers = match ctx.list_workers().await {
+        match self.node_manager.list_workers(ctx).await {
+            Ok(workers) => Ok(Response::ok().body(workers)),
             Err(e) => Err(Response::internal_error_no_request(&e.to_string())),
-            Ok(workers) => Ok(workers),
-        }?;
+        }
+    }
+
+    /// Return the details of the worker with the given address
+    pub async fn show_worker(
+        &self,
+        ctx: &Context,
+        address: &str,
+    ) -> Result<Response<WorkerStatus>, Response<Error>> {
+        let workers = match self.node_manager.list_workers(ctx).await {
+            Ok(workers) => workers,
+            Err(e) => return Err(Response::internal_error_no_request(&e.to_string())),
+        };
+        match workers.list.into_iter().find(|w| w.addr == address) {
+            Some(worker) => Ok(Response::ok().body(worker)),
+            None => Err(Response::not_found_no_request(&format!(
+                "Worker with address {address} not found"
+            ))),
+        }
+    }
+}
 
-        let list = workers
+impl NodeManager {
+    /// Return the list of workers running on this node, with their type and message counts
+    pub async fn list_workers(&self, ctx: &Context) -> Result<WorkerList> {
+        let types = self.worker_types().await;
+        let list = ctx
+            .list_workers_info()
+            .await?
             .into_iter()
-            .map(|addr| WorkerStatus::new(addr.address()))
+            .map(|info| Self::worker_status(&types, info))
             .collect();
+        Ok(WorkerList::new(list))
+    }
+
+    fn worker_status(types: &BTreeMap<Address, WorkerType>, info: WorkerInfo) -> WorkerStatus {
+        let worker_type = info
+            .addresses
+            .iter()
+            .find_map(|a| types.get(a).copied())
+            .unwrap_or(if info.processor {
+                WorkerType::Processor
+            } else {
+                WorkerType::Other
+            });
+        WorkerStatus::new(info.address.address())
+            .with_type(worker_type)
+            .with_counts(info.messages_count, info.mailbox_size as u64)
+    }
 
-        Ok(Response::ok().body(WorkerList::new(list)))
+    /// Map the addresses of the workers created by the node manager to their type
+    async fn worker_types(&self) -> BTreeMap<Address, WorkerType> {
+        let mut types = BTreeMap::new();
+        for inlet in self.registry.inlets.values().await {
+            types.insert(inlet.worker_addr, WorkerType::Inlet);
+        }
+        for outlet in self.registry.outlets.values().await {
+            types.insert(outlet.worker_addr, WorkerType::Outlet);
+        }
+        for relay in self.registry.relays.values().await {
+            types.insert(relay.worker_address().clone(), WorkerType::Relay);
+        }
+        for address in self.registry.secure_channel_listeners.keys().await {
+            types.insert(address, WorkerType::SecureChannelListener);
+        }
+        for channel in self
+            .secure_channels
+            .secure_channel_registry()
+            .get_channel_list()
+        {
+            types.insert(
+                channel.encryptor_messaging_address().clone(),
+                WorkerType::SecureChannel,
+            );
+            types.insert(
+                channel.decryptor_messaging_address().clone(),
+                WorkerType::SecureChannel,
+            );
+        }
+        let services = [
+            self.registry.authenticated_services.keys().await,
+            self.registry.uppercase_services.keys().await,
+            self.registry.echoer_services.keys().await,
+            self.registry.kafka_services.keys().await,
+            self.registry.hop_services.keys().await,
+            self.registry.credentials_services.keys().await,
+        ];
+        for address in services.into_iter().flatten() {
+            types.insert(address, WorkerType::Service);
+        }
+        types
     }
 }
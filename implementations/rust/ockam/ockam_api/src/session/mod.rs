@@ -1,6 +1,7 @@
 use minicbor::{Decode, Encode};
 use tokio::task::JoinHandle;
 use tracing as log;
+use tracing::Instrument;
 
 use ockam::{LocalMessage, Route, TransportMessage, Worker};
 use ockam_core::compat::sync::{Arc, Mutex};
@@ -10,8 +11,8 @@ use ockam_core::{
 use ockam_node::tokio::sync::mpsc;
 use ockam_node::tokio::task::JoinSet;
 use ockam_node::tokio::time::{sleep, timeout, Duration};
-use ockam_node::Context;
 use ockam_node::{tokio, WorkerBuilder};
+use ockam_node::{Context, LOG_CONTEXT_TARGET};
 
 use crate::nodes::service::default_address::DefaultAddress;
 use crate::session::sessions::{ConnectionStatus, Ping, Session};
@@ -129,10 +130,18 @@ impl Medic {
                                 session.set_status(ConnectionStatus::Degraded);
                                 log::info!(%key, "replacing session");
                                 let retry_delay = self.retry_delay;
-                                self.replacements.spawn(async move {
-                                    sleep(retry_delay).await;
-                                    (key, f.await)
-                                });
+                                let span = log::info_span!(
+                                    target: LOG_CONTEXT_TARGET,
+                                    "session",
+                                    session = %key
+                                );
+                                self.replacements.spawn(
+                                    async move {
+                                        sleep(retry_delay).await;
+                                        (key, f.await)
+                                    }
+                                    .instrument(span),
+                                );
                             }
                             ConnectionStatus::Degraded => {
                                 log::warn!(%key, "session is being replaced");
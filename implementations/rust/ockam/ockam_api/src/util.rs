@@ -7,13 +7,16 @@ use ockam_core::errcode::{Kind, Origin};
 use ockam_core::flow_control::FlowControlId;
 use ockam_core::{Address, Error, Result, Route, TransportType, LOCAL};
 use ockam_multiaddr::proto::{
-    DnsAddr, Ip4, Ip6, Node, Project, Secure, Service, Space, Tcp, Worker,
+    DnsAddr, Ip4, Ip6, Node, Project, Secure, Service, Space, Tcp, Unix, Worker,
 };
 use ockam_multiaddr::{Code, MultiAddr, Protocol};
 use ockam_transport_tcp::{TcpConnection, TcpConnectionOptions, TCP};
 
 use crate::error::ApiError;
 
+/// Transport type of the Unix domain socket transport, see `ockam_transport_uds::UDS`
+const UDS: TransportType = TransportType::new(5);
+
 /// Try to convert a multi-address to an Ockam route.
 pub fn local_multiaddr_to_route(ma: &MultiAddr) -> Result<Route> {
     let mut rb = Route::new();
@@ -205,6 +208,7 @@ pub async fn multiaddr_to_route(
 
 /// Resolve all the multiaddresses which represent transport addresses
 /// For example /tcp/127.0.0.1/port/4000 is transformed to the Address (TCP, "127.0.0.1:4000")
+/// and /unix/%2Ftmp%2Fnode.sock to the Address (UDS, "/tmp/node.sock")
 /// The creation of a TCP worker and the substitution of that transport address to a worker address
 /// is done later with `context.resolve_transport_route(route)`
 pub fn multiaddr_to_transport_route(ma: &MultiAddr) -> Option<Route> {
@@ -237,6 +241,10 @@ pub fn multiaddr_to_transport_route(ma: &MultiAddr) -> Option<Route> {
                     }
                 }
             }
+            Unix::CODE => {
+                let path = p.cast::<Unix>()?;
+                route = route.append(Address::new(UDS, &*path))
+            }
             Worker::CODE => {
                 let local = p.cast::<Worker>()?;
                 route = route.append(Address::new(LOCAL, &*local))
@@ -344,6 +352,10 @@ pub fn is_local_node(ma: &MultiAddr) -> miette::Result<bool> {
                     .map(|ip6| ip6.is_loopback())
                     .ok_or_else(|| miette!("Invalid \"ip6\" value"))?;
             }
+            // A "/unix" socket is always on the local host
+            Unix::CODE => {
+                at_rust_node = true;
+            }
             // A MultiAddr starting with "/service" could reference both local and remote nodes.
             _ => {
                 return Err(miette!("Invalid address, protocol not supported"));
@@ -365,6 +377,7 @@ pub fn local_worker(code: &Code) -> Result<bool> {
         | Ip4::CODE
         | Ip6::CODE
         | Tcp::CODE
+        | Unix::CODE
         | Secure::CODE => Ok(false),
         Worker::CODE | Service::CODE => Ok(true),
 
@@ -31,7 +31,9 @@ impl AppState {
             "ockam_command",
             "ockam_app_lib",
         ];
-        if let Some(guard) = Logging::setup(level, false, Some(node_dir), &ockam_crates) {
+        if let Some(guard) =
+            Logging::setup(level, false, Some(node_dir), Some(NODE_NAME), &ockam_crates)
+        {
             self.tracing_guard
                 .set(guard)
                 .expect("Failed to initialize logs");
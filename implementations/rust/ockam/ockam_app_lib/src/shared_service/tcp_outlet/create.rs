@@ -30,6 +30,9 @@ impl AppState {
                 Some(worker_addr.clone()),
                 true,
                 Some(self.create_invitations_access_control(worker_addr).await?),
+                None,
+                None,
+                None,
             )
             .await
         {
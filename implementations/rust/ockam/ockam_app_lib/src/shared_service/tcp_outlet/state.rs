@@ -58,6 +58,9 @@ impl AppState {
                     Some(tcp_outlet.alias.clone()),
                     true,
                     Some(access_control),
+                    None,
+                    None,
+                    None,
                 )
                 .await
                 .map_err(|e| {
@@ -120,6 +120,7 @@ impl TcpOutletStatusRow {
             socket_addr,
             worker_addr,
             payload: self.payload.clone(),
+            connections: None,
         })
     }
 }
@@ -182,7 +182,7 @@ async fn spawn_background_node(
     }
     args.push(cmd.node_name.to_string());
 
-    run_ockam(args).await
+    run_ockam(args, None).await
 }
 
 impl CreateCommand {
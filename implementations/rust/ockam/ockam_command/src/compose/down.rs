@@ -0,0 +1,91 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use colorful::Colorful;
+use miette::miette;
+
+use ockam_node::Context;
+
+use crate::compose::manifest::{default_manifest_path, Manifest};
+use crate::util::node_rpc;
+use crate::{color, docs, fmt_info, fmt_ok, fmt_warn, CommandGlobalOpts, OckamColor};
+
+const LONG_ABOUT: &str = include_str!("./static/down/long_about.txt");
+const PREVIEW_TAG: &str = include_str!("../static/preview_tag.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/down/after_long_help.txt");
+
+/// Stop and delete the nodes described in a manifest
+#[derive(Clone, Debug, Args)]
+#[command(
+    long_about = docs::about(LONG_ABOUT),
+    before_help = docs::before_help(PREVIEW_TAG),
+    after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct DownCommand {
+    /// Path to the manifest. The `ockam-compose.yml` file of the current directory is used by default
+    #[arg(long, short, value_name = "PATH")]
+    file: Option<PathBuf>,
+
+    /// Terminate the node processes immediately (uses SIGKILL instead of SIGTERM)
+    #[arg(long)]
+    force: bool,
+}
+
+impl DownCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(run_impl, (opts, self))
+    }
+}
+
+async fn run_impl(
+    _ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, DownCommand),
+) -> miette::Result<()> {
+    let path = match cmd.file {
+        Some(path) => path,
+        None => default_manifest_path()?,
+    };
+    let manifest = Manifest::read(&path)?;
+
+    let mut failed = vec![];
+    for node_name in manifest.shutdown_order()? {
+        if opts.state.get_node(&node_name).await.is_err() {
+            opts.terminal.write_line(&fmt_info!(
+                "The node {} does not exist",
+                color!(&node_name, OckamColor::PrimaryResource)
+            ))?;
+            continue;
+        }
+        match opts.state.delete_node(&node_name, cmd.force).await {
+            Ok(_) => {
+                opts.terminal.write_line(&fmt_ok!(
+                    "The node {} is stopped and deleted",
+                    color!(&node_name, OckamColor::PrimaryResource)
+                ))?;
+            }
+            Err(e) => {
+                opts.terminal.write_line(&fmt_warn!(
+                    "The node {} could not be deleted: {e}",
+                    color!(&node_name, OckamColor::PrimaryResource)
+                ))?;
+                failed.push(node_name);
+            }
+        }
+    }
+
+    if failed.is_empty() {
+        opts.terminal
+            .stdout()
+            .plain(fmt_ok!(
+                "All the nodes of {} are stopped",
+                color!(&manifest.name, OckamColor::PrimaryResource)
+            ))
+            .write_line()?;
+        Ok(())
+    } else {
+        Err(miette!(
+            "Some nodes could not be deleted: {}",
+            failed.join(", ")
+        ))
+    }
+}
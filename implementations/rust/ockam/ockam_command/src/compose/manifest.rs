@@ -0,0 +1,431 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use miette::{miette, Context as _, IntoDiagnostic};
+use serde::{Deserialize, Deserializer};
+
+use crate::run::{InletConfig, OutletConfig, RelayConfig};
+
+/// Names of the manifest files looked up in the current directory
+pub const DEFAULT_MANIFEST_FILE_NAMES: [&str; 2] = ["ockam-compose.yml", "ockam-compose.yaml"];
+
+/// The manifest describes a set of local nodes, which are started in the order of their
+/// dependencies and share the same trust context:
+/// ```yml
+/// name: demo
+/// enrollment-ticket: $OCKAM_ENROLLMENT_TICKET
+/// nodes:
+///   relay:
+///     tcp-listener-address: '127.0.0.1:4000'
+///
+///   db:
+///     depends-on: relay
+///     tcp-outlets:
+///       db:
+///         from: /service/outlet
+///         to: '127.0.0.1:5432'
+///     relays:
+///       db:
+///         at: /node/relay
+///
+///   app:
+///     depends-on: [relay, db]
+///     tcp-inlets:
+///       db:
+///         from: '127.0.0.1:15432'
+///         to: /node/relay/service/forward_to_db/secure/api/service/outlet
+/// ```
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Manifest {
+    /// Name of the set of nodes. It is used as the name of the trust context
+    /// created when the nodes are enrolled with an enrollment ticket
+    #[serde(default = "default_name")]
+    pub name: String,
+    /// Enrollment ticket used to enroll the nodes to a project, once for all the nodes
+    pub enrollment_ticket: Option<String>,
+    /// Name of an existing trust context, or path to a trust context JSON file, used by all the nodes
+    pub trust_context: Option<String>,
+    /// Nodes, by name
+    pub nodes: BTreeMap<String, ComposeNode>,
+}
+
+/// Defines the structure of a node in the manifest
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ComposeNode {
+    /// Nodes which must be started before this node
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub depends_on: Vec<String>,
+    /// Address of the TCP listener of the node
+    pub tcp_listener_address: Option<String>,
+    #[serde(default)]
+    pub tcp_outlets: BTreeMap<String, OutletConfig>,
+    #[serde(default)]
+    pub tcp_inlets: BTreeMap<String, InletConfig>,
+    #[serde(default)]
+    pub relays: BTreeMap<String, RelayConfig>,
+}
+
+/// A command run to bring up a node and its services
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ComposeStep {
+    /// Name of the node for which the command is run
+    pub node_name: String,
+    /// Arguments of the `ockam` command
+    pub args: Vec<String>,
+}
+
+impl Manifest {
+    /// Read a manifest file
+    pub fn read(path: &Path) -> miette::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .into_diagnostic()
+            .wrap_err_with(|| format!("Failed to read the manifest {}", path.display()))?;
+        Self::parse(&contents)
+    }
+
+    /// Parse and validate the contents of a manifest
+    pub fn parse(contents: &str) -> miette::Result<Self> {
+        let manifest: Self = serde_yaml::from_str(contents).into_diagnostic()?;
+        if manifest.enrollment_ticket.is_some() && manifest.trust_context.is_some() {
+            return Err(miette!(
+                "The manifest can't define both an enrollment ticket and a trust context"
+            ));
+        }
+        manifest.startup_order()?;
+        Ok(manifest)
+    }
+
+    /// Return the name of the trust context shared by all the nodes, if any
+    pub fn shared_trust_context(&self) -> Option<&str> {
+        match &self.enrollment_ticket {
+            Some(_) => Some(&self.name),
+            None => self.trust_context.as_deref(),
+        }
+    }
+
+    /// Return the names of the nodes, each node coming after all its dependencies.
+    /// Nodes without dependencies between them are sorted by name
+    pub fn startup_order(&self) -> miette::Result<Vec<String>> {
+        for (name, node) in &self.nodes {
+            if let Some(unknown) = node
+                .depends_on
+                .iter()
+                .find(|d| !self.nodes.contains_key(*d))
+            {
+                return Err(miette!(
+                    "The node {name} depends on the node {unknown}, which is not defined"
+                ));
+            }
+        }
+
+        let mut started: BTreeSet<&String> = BTreeSet::new();
+        let mut order = vec![];
+        while order.len() < self.nodes.len() {
+            let ready: Vec<&String> = self
+                .nodes
+                .iter()
+                .filter(|(name, node)| {
+                    !started.contains(name) && node.depends_on.iter().all(|d| started.contains(d))
+                })
+                .map(|(name, _)| name)
+                .collect();
+            if ready.is_empty() {
+                let remaining: Vec<&str> = self
+                    .nodes
+                    .keys()
+                    .filter(|name| !started.contains(name))
+                    .map(|name| name.as_str())
+                    .collect();
+                return Err(miette!(
+                    "Circular dependency detected between the nodes: {}",
+                    remaining.join(", ")
+                ));
+            }
+            for name in ready {
+                started.insert(name);
+                order.push(name.clone());
+            }
+        }
+        Ok(order)
+    }
+
+    /// Return the names of the nodes, each node coming before all its dependencies
+    pub fn shutdown_order(&self) -> miette::Result<Vec<String>> {
+        let mut order = self.startup_order()?;
+        order.reverse();
+        Ok(order)
+    }
+
+    /// Return the commands enrolling the nodes, when an enrollment ticket is used
+    pub fn enrollment_steps(&self) -> Vec<ComposeStep> {
+        match &self.enrollment_ticket {
+            Some(ticket) => vec![ComposeStep::new(
+                &self.name,
+                &[
+                    "project",
+                    "enroll",
+                    "--new-trust-context-name",
+                    &self.name,
+                    ticket,
+                ],
+            )],
+            None => vec![],
+        }
+    }
+
+    /// Return the commands creating a node, then its policies, outlets, inlets and relays
+    pub fn node_steps(&self, node_name: &str) -> miette::Result<Vec<ComposeStep>> {
+        let node = self
+            .nodes
+            .get(node_name)
+            .ok_or_else(|| miette!("The node {node_name} is not defined in the manifest"))?;
+
+        let mut steps = vec![];
+        let mut args = vec!["node", "create", node_name];
+        if let Some(address) = &node.tcp_listener_address {
+            args.extend(["--tcp-listener-address", address]);
+        }
+        if let Some(trust_context) = self.shared_trust_context() {
+            args.extend(["--trust-context", trust_context]);
+        }
+        steps.push(ComposeStep::new(node_name, &args));
+
+        let at = format!("/node/{node_name}");
+        for (alias, outlet) in &node.tcp_outlets {
+            if let Some(expression) = &outlet.access_control {
+                steps.push(ComposeStep::new(
+                    node_name,
+                    &[
+                        "policy",
+                        "create",
+                        "--at",
+                        &at,
+                        "--resource",
+                        "tcp-outlet",
+                        "--expression",
+                        expression,
+                    ],
+                ));
+            }
+            steps.push(ComposeStep::new(
+                node_name,
+                &[
+                    "tcp-outlet",
+                    "create",
+                    "--at",
+                    &at,
+                    "--from",
+                    &outlet.from,
+                    "--to",
+                    &outlet.to,
+                    "--alias",
+                    alias,
+                ],
+            ));
+        }
+        for (alias, inlet) in &node.tcp_inlets {
+            if let Some(expression) = &inlet.access_control {
+                steps.push(ComposeStep::new(
+                    node_name,
+                    &[
+                        "policy",
+                        "create",
+                        "--at",
+                        &at,
+                        "--resource",
+                        "tcp-inlet",
+                        "--expression",
+                        expression,
+                    ],
+                ));
+            }
+            steps.push(ComposeStep::new(
+                node_name,
+                &[
+                    "tcp-inlet",
+                    "create",
+                    "--at",
+                    &at,
+                    "--from",
+                    &inlet.from,
+                    "--to",
+                    &inlet.to,
+                    "--alias",
+                    alias,
+                ],
+            ));
+        }
+        for (alias, relay) in &node.relays {
+            steps.push(ComposeStep::new(
+                node_name,
+                &["relay", "create", alias, "--to", &at, "--at", &relay.at],
+            ));
+        }
+        Ok(steps)
+    }
+}
+
+impl ComposeStep {
+    fn new(node_name: &str, args: &[&str]) -> Self {
+        Self {
+            node_name: node_name.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+}
+
+/// Find the manifest file in the current directory
+pub fn default_manifest_path() -> miette::Result<std::path::PathBuf> {
+    let dir = std::env::current_dir()
+        .into_diagnostic()
+        .wrap_err("Failed to get current directory")?;
+    DEFAULT_MANIFEST_FILE_NAMES
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.exists())
+        .ok_or_else(|| {
+            miette!(
+                "No manifest found in the current directory.\n\
+                Try passing the path to the manifest with the --file flag."
+            )
+        })
+}
+
+fn default_name() -> String {
+    "compose".to_string()
+}
+
+/// Accept either a single node name or a list of node names
+fn one_or_many<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(name) => vec![name],
+        OneOrMany::Many(names) => names,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"
+        name: demo
+        enrollment-ticket: abcd
+        nodes:
+          app:
+            depends-on: [relay, db]
+            tcp-inlets:
+              db:
+                from: '127.0.0.1:15432'
+                to: /node/relay/service/forward_to_db/secure/api/service/outlet
+          db:
+            depends-on: relay
+            tcp-outlets:
+              db:
+                from: /service/outlet
+                to: '127.0.0.1:5432'
+                access_control: '(= subject.component "app")'
+            relays:
+              db:
+                at: /node/relay
+          relay:
+            tcp-listener-address: '127.0.0.1:4000'
+    "#;
+
+    #[test]
+    fn nodes_are_started_after_their_dependencies() {
+        let manifest = Manifest::parse(MANIFEST).unwrap();
+        assert_eq!(manifest.startup_order().unwrap(), ["relay", "db", "app"]);
+        assert_eq!(manifest.shutdown_order().unwrap(), ["app", "db", "relay"]);
+    }
+
+    #[test]
+    fn nodes_share_the_trust_context_of_the_enrollment() {
+        let manifest = Manifest::parse(MANIFEST).unwrap();
+        let enrollment = manifest.enrollment_steps();
+        assert_eq!(
+            enrollment[0].args,
+            [
+                "project",
+                "enroll",
+                "--new-trust-context-name",
+                "demo",
+                "abcd"
+            ]
+        );
+
+        let steps = manifest.node_steps("relay").unwrap();
+        assert_eq!(
+            steps[0].args,
+            [
+                "node",
+                "create",
+                "relay",
+                "--tcp-listener-address",
+                "127.0.0.1:4000",
+                "--trust-context",
+                "demo"
+            ]
+        );
+
+        let steps: Vec<String> = manifest
+            .node_steps("db")
+            .unwrap()
+            .iter()
+            .map(|s| s.args[..2].join(" "))
+            .collect();
+        assert_eq!(
+            steps,
+            [
+                "node create",
+                "policy create",
+                "tcp-outlet create",
+                "relay create"
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_manifests_are_rejected() {
+        let cases = [
+            r#"
+                nodes:
+                  node1:
+                    depends-on: node2
+                  node2:
+                    depends-on: [node3]
+                  node3:
+                    depends-on: node1
+            "#,
+            r#"
+                nodes:
+                  node1:
+                    depends-on: node2
+            "#,
+            r#"
+                enrollment-ticket: abcd
+                trust-context: tc
+                nodes:
+                  node1:
+            "#,
+            r#"
+                nodes:
+                  node1:
+                    unknown: value
+            "#,
+        ];
+        for manifest in cases {
+            assert!(Manifest::parse(manifest).is_err(), "{manifest}");
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use clap::{Args, Subcommand};
+
+pub(crate) use down::DownCommand;
+pub use manifest::{ComposeNode, ComposeStep, Manifest};
+pub(crate) use up::UpCommand;
+
+use crate::{docs, CommandGlobalOpts};
+
+mod down;
+mod manifest;
+mod up;
+
+const LONG_ABOUT: &str = include_str!("./static/long_about.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/after_long_help.txt");
+
+/// Start and stop a set of local nodes described in a manifest
+#[derive(Clone, Debug, Args)]
+#[command(
+    arg_required_else_help = true,
+    subcommand_required = true,
+    long_about = docs::about(LONG_ABOUT),
+    after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct ComposeCommand {
+    #[command(subcommand)]
+    subcommand: ComposeSubcommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum ComposeSubcommand {
+    Up(UpCommand),
+    Down(DownCommand),
+}
+
+impl ComposeCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        match self.subcommand {
+            ComposeSubcommand::Up(c) => c.run(opts),
+            ComposeSubcommand::Down(c) => c.run(opts),
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+use clap::Args;
+use colorful::Colorful;
+use miette::{Context as _, IntoDiagnostic};
+use tracing::debug;
+
+use ockam_node::Context;
+
+use crate::compose::manifest::{default_manifest_path, ComposeStep, Manifest};
+use crate::run::binary_path;
+use crate::util::node_rpc;
+use crate::{color, docs, fmt_info, fmt_ok, fmt_warn, CommandGlobalOpts, OckamColor};
+
+const LONG_ABOUT: &str = include_str!("./static/up/long_about.txt");
+const PREVIEW_TAG: &str = include_str!("../static/preview_tag.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/up/after_long_help.txt");
+
+/// Start the nodes described in a manifest
+#[derive(Clone, Debug, Args)]
+#[command(
+    long_about = docs::about(LONG_ABOUT),
+    before_help = docs::before_help(PREVIEW_TAG),
+    after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct UpCommand {
+    /// Path to the manifest. The `ockam-compose.yml` file of the current directory is used by default
+    #[arg(long, short, value_name = "PATH")]
+    file: Option<PathBuf>,
+}
+
+impl UpCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(run_impl, (opts, self))
+    }
+}
+
+async fn run_impl(
+    _ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, UpCommand),
+) -> miette::Result<()> {
+    let path = match cmd.file {
+        Some(path) => path,
+        None => default_manifest_path()?,
+    };
+    let manifest = Manifest::read(&path)?;
+
+    let running_nodes: BTreeSet<String> = opts
+        .state
+        .get_nodes()
+        .await?
+        .iter()
+        .filter(|node| node.is_running())
+        .map(|node| node.name())
+        .collect();
+    let nodes = manifest.startup_order()?;
+    if nodes.iter().all(|n| running_nodes.contains(n)) {
+        opts.terminal
+            .stdout()
+            .plain(fmt_info!("All the nodes are already running"))
+            .write_line()?;
+        return Ok(());
+    }
+
+    // Enrolling is idempotent, and done once for all the nodes
+    for step in manifest.enrollment_steps() {
+        run_step(&step)?;
+    }
+
+    // Nodes started by this command, and whether they existed before
+    let mut started: Vec<(String, bool)> = vec![];
+    for node_name in nodes {
+        if running_nodes.contains(&node_name) {
+            opts.terminal.write_line(&fmt_info!(
+                "The node {} is already running",
+                color!(&node_name, OckamColor::PrimaryResource)
+            ))?;
+            continue;
+        }
+        let existed = opts.state.get_node(&node_name).await.is_ok();
+        started.push((node_name.clone(), existed));
+
+        let result = manifest
+            .node_steps(&node_name)?
+            .iter()
+            .try_for_each(run_step);
+        if let Err(e) = result {
+            opts.terminal.write_line(&fmt_warn!(
+                "The node {} could not be started, stopping the nodes started so far",
+                color!(&node_name, OckamColor::PrimaryResource)
+            ))?;
+            rollback(&opts, started).await;
+            return Err(e);
+        }
+        opts.terminal.write_line(&fmt_ok!(
+            "The node {} is started",
+            color!(&node_name, OckamColor::PrimaryResource)
+        ))?;
+    }
+
+    opts.terminal
+        .stdout()
+        .plain(fmt_ok!(
+            "All the nodes of {} are running",
+            color!(&manifest.name, OckamColor::PrimaryResource)
+        ))
+        .write_line()?;
+    Ok(())
+}
+
+/// Run an `ockam` command in a child process
+fn run_step(step: &ComposeStep) -> miette::Result<()> {
+    // Only the command name is displayed, since the arguments can contain an enrollment ticket
+    let command = step
+        .args
+        .iter()
+        .take(2)
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(" ");
+    debug!(node = %step.node_name, "running ockam {command}");
+    duct::cmd(binary_path(), &step.args)
+        .run()
+        .into_diagnostic()
+        .wrap_err_with(|| format!("Failed to run `ockam {command}` for {}", step.node_name))?;
+    Ok(())
+}
+
+/// Stop the nodes which were started, in reverse order. The nodes created by the command are
+/// deleted, the nodes which existed before are only stopped
+async fn rollback(opts: &CommandGlobalOpts, started: Vec<(String, bool)>) {
+    for (node_name, existed) in started.into_iter().rev() {
+        let result = if existed {
+            opts.state.stop_node(&node_name, false).await
+        } else {
+            opts.state.delete_node(&node_name, false).await
+        };
+        if let Err(e) = result {
+            debug!(%node_name, %e, "the node could not be stopped");
+        }
+    }
+}
@@ -0,0 +1,496 @@
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
+
+use clap::Args;
+use colorful::Colorful;
+use miette::{miette, IntoDiagnostic};
+use serde::Serialize;
+use tokio::net::TcpStream;
+
+use ockam::{AsyncTryClone, Context};
+use ockam_api::nodes::InMemoryNode;
+use ockam_multiaddr::proto::{Project, Secure, Service};
+use ockam_multiaddr::{MultiAddr, Protocol};
+
+use crate::util::api::{CloudOpts, TrustContextOpts};
+use crate::util::duration::duration_parser;
+use crate::util::{clean_nodes_multiaddr, node_rpc};
+use crate::{docs, fmt_err, fmt_log, fmt_ok, fmt_warn, CommandGlobalOpts};
+
+const PREVIEW_TAG: &str = include_str!("./static/preview_tag.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/doctor/after_long_help.txt");
+
+const IDENTITY_SUGGESTION: &str =
+    "Check the identity name with `ockam identity list` or create one with `ockam identity create`";
+
+/// Diagnose a route, hop by hop, and report which stage fails
+///
+/// The following stages are checked in order:
+///  - the resolution of the node and project names contained in the route
+///  - the TCP reachability of the first hop
+///  - the retrieval of a credential, when a project or a trust context is used
+///  - the secure channel handshakes and the relays found along the route
+#[derive(Clone, Debug, Args)]
+#[command(
+    arg_required_else_help = true,
+    before_help = docs::before_help(PREVIEW_TAG),
+    after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct DoctorCommand {
+    /// The route to diagnose, for example /node/n1/service/forward_to_n2/secure/api
+    #[arg(value_name = "ROUTE")]
+    pub to: MultiAddr,
+
+    /// Timeout used for each stage
+    #[arg(long, value_name = "TIMEOUT", default_value = "10s", value_parser = duration_parser)]
+    pub timeout: Duration,
+
+    #[command(flatten)]
+    cloud_opts: CloudOpts,
+
+    #[command(flatten)]
+    trust_context_opts: TrustContextOpts,
+}
+
+impl DoctorCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(rpc, (opts, self));
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, DoctorCommand)) -> miette::Result<()> {
+    let mut report = DoctorReport::default();
+    diagnose(&ctx, &opts, &cmd, &mut report).await?;
+
+    let plain = report
+        .checks
+        .iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    opts.terminal
+        .stdout()
+        .plain(plain)
+        .json(serde_json::to_string(&report).into_diagnostic()?)
+        .write_line()?;
+
+    if let Some(failed) = report.first_failure() {
+        return Err(miette!(
+            "The route {} failed at the stage: {}",
+            cmd.to,
+            failed.stage
+        ));
+    }
+    Ok(())
+}
+
+/// Run all the checks in order, and stop at the first failure
+async fn diagnose(
+    ctx: &Context,
+    opts: &CommandGlobalOpts,
+    cmd: &DoctorCommand,
+    report: &mut DoctorReport,
+) -> miette::Result<()> {
+    // Resolve node names and project names
+    let (route, meta) = match clean_nodes_multiaddr(&cmd.to, &opts.state).await {
+        Ok(resolved) => resolved,
+        Err(e) => {
+            report.failed(
+                Stage::Resolution,
+                e,
+                "Check the node names with `ockam node list` and make sure that the nodes are started",
+            );
+            return Ok(());
+        }
+    };
+    let mut projects = VecDeque::new();
+    for project_name in meta.project.iter() {
+        match opts.state.get_project_by_name(project_name).await {
+            Ok(project) => projects.push_back(project),
+            Err(e) => {
+                report.failed(
+                    Stage::Resolution,
+                    e,
+                    "Check the project names with `ockam project list` or run `ockam project enroll`",
+                );
+                return Ok(());
+            }
+        }
+    }
+    report.ok(Stage::Resolution, format!("{} resolves to {route}", cmd.to));
+
+    // Check that the first hop can be reached with TCP
+    let first_hop = match (route.first(), projects.front()) {
+        (Some(p), Some(project)) if p.code() == Project::CODE => project
+            .access_route()
+            .ok()
+            .and_then(|r| r.to_socket_addr().ok()),
+        _ => route.to_socket_addr().ok(),
+    };
+    match first_hop {
+        Some(address) => {
+            match tokio::time::timeout(cmd.timeout, TcpStream::connect(&address)).await {
+                Ok(Ok(_)) => report.ok(Stage::TcpReachability, format!("{address} is reachable")),
+                Ok(Err(e)) => {
+                    report.failed(
+                        Stage::TcpReachability,
+                        format!("{address} is not reachable: {e}"),
+                        "Check that the node is running with `ockam node show` and that no firewall blocks this address",
+                    );
+                    return Ok(());
+                }
+                Err(_) => {
+                    report.failed(
+                        Stage::TcpReachability,
+                        format!("{address} could not be reached after {:?}", cmd.timeout),
+                        "Check that the host is up and that no firewall silently drops the connections",
+                    );
+                    return Ok(());
+                }
+            }
+        }
+        None => report.skipped(Stage::TcpReachability, "the route has no TCP address"),
+    }
+
+    // Retrieve a credential if the route uses a project or a trust context
+    let Some(identity_name) = report.check(
+        Stage::CredentialExchange,
+        opts.state
+            .get_identity_name_or_default(&cmd.cloud_opts.identity)
+            .await,
+        IDENTITY_SUGGESTION,
+    ) else {
+        return Ok(());
+    };
+    let Some(identifier) = report.check(
+        Stage::CredentialExchange,
+        opts.state.get_identifier_by_name(&identity_name).await,
+        IDENTITY_SUGGESTION,
+    ) else {
+        return Ok(());
+    };
+    let project_name = cmd
+        .trust_context_opts
+        .project_name
+        .clone()
+        .or_else(|| projects.front().map(|p| p.name()));
+    let Some(trust_context) = report.check(
+        Stage::CredentialExchange,
+        opts.state
+            .retrieve_trust_context(
+                &cmd.trust_context_opts.trust_context,
+                &project_name,
+                &None,
+                &None,
+            )
+            .await,
+        "Check the trust context with `ockam trust-context list` or the project with `ockam project list`",
+    ) else {
+        return Ok(());
+    };
+    let Some(node) = report.check(
+        Stage::CredentialExchange,
+        InMemoryNode::start_node(
+            ctx,
+            &opts.state,
+            &identity_name,
+            project_name,
+            trust_context,
+        )
+        .await,
+        Stage::CredentialExchange.suggestion(),
+    ) else {
+        return Ok(());
+    };
+    let credential = match node
+        .get_credential(ctx, &identifier, Some(cmd.timeout))
+        .await
+    {
+        Ok(Some(credential)) => {
+            report.ok(
+                Stage::CredentialExchange,
+                format!("a credential was issued to {identifier}"),
+            );
+            Some(credential)
+        }
+        Ok(None) => {
+            report.skipped(Stage::CredentialExchange, "no trust context is used");
+            None
+        }
+        Err(e) => {
+            report.failed(
+                Stage::CredentialExchange,
+                e,
+                "Make sure that your identity is a member of the project with `ockam project enroll`",
+            );
+            return Ok(());
+        }
+    };
+
+    // Establish the secure channels found along the route, one after the other
+    let mut prefix = MultiAddr::default();
+    let mut through_relay = false;
+    let mut checked = false;
+    for p in route.iter() {
+        prefix.push_back_value(&p).into_diagnostic()?;
+        let stage = match p.code() {
+            Service::CODE => {
+                through_relay = true;
+                continue;
+            }
+            Project::CODE => Stage::SecureChannel,
+            Secure::CODE if through_relay => Stage::RelayResolution,
+            Secure::CODE => Stage::SecureChannel,
+            _ => continue,
+        };
+        through_relay = false;
+        checked = true;
+        let connection_ctx = Arc::new(ctx.async_try_clone().await.into_diagnostic()?);
+        match node
+            .make_connection(
+                connection_ctx,
+                &prefix,
+                identifier.clone(),
+                None,
+                credential.clone(),
+                Some(cmd.timeout),
+            )
+            .await
+        {
+            Ok(_) => report.ok(stage, format!("{prefix} is reachable")),
+            Err(e) => {
+                report.failed(stage, e, stage.suggestion());
+                return Ok(());
+            }
+        }
+    }
+    if !checked {
+        report.skipped(Stage::SecureChannel, "the route has no secure channel");
+    }
+    Ok(())
+}
+
+/// Stages of a route diagnostic
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Stage {
+    Resolution,
+    TcpReachability,
+    CredentialExchange,
+    SecureChannel,
+    RelayResolution,
+}
+
+impl Stage {
+    fn suggestion(&self) -> &'static str {
+        match self {
+            Stage::SecureChannel => "Check that a secure channel listener is started on the destination node with `ockam secure-channel-listener list` and that your identity is authorized to use it",
+            Stage::RelayResolution => "Check that the relay exists with `ockam relay list` and that the node which created it is running",
+            _ => "Run the command again with `-vv` to get more details",
+        }
+    }
+}
+
+impl Display for Stage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Stage::Resolution => "route resolution",
+            Stage::TcpReachability => "TCP reachability",
+            Stage::CredentialExchange => "credential exchange",
+            Stage::SecureChannel => "secure channel handshake",
+            Stage::RelayResolution => "relay resolution",
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum CheckStatus {
+    Ok,
+    Skipped,
+    Failed,
+}
+
+/// Result of the diagnostic of a stage
+#[derive(Debug, Serialize)]
+struct Check {
+    stage: Stage,
+    status: CheckStatus,
+    details: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    suggestion: Option<String>,
+}
+
+impl Display for Check {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.status {
+            CheckStatus::Ok => write!(f, "{}", fmt_ok!("{}: {}", self.stage, self.details))?,
+            CheckStatus::Skipped => write!(
+                f,
+                "{}",
+                fmt_warn!("{}: skipped, {}", self.stage, self.details)
+            )?,
+            CheckStatus::Failed => write!(f, "{}", fmt_err!("{}: {}", self.stage, self.details))?,
+        }
+        if let Some(suggestion) = &self.suggestion {
+            write!(
+                f,
+                "\n{}",
+                fmt_log!("{}", suggestion.as_str().light_yellow())
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+struct DoctorReport {
+    checks: Vec<Check>,
+}
+
+impl DoctorReport {
+    fn ok(&mut self, stage: Stage, details: impl Display) {
+        self.push(stage, CheckStatus::Ok, details, None)
+    }
+
+    fn skipped(&mut self, stage: Stage, details: impl Display) {
+        self.push(stage, CheckStatus::Skipped, details, None)
+    }
+
+    fn failed(&mut self, stage: Stage, details: impl Display, suggestion: &str) {
+        self.push(stage, CheckStatus::Failed, details, Some(suggestion))
+    }
+
+    /// Return the value of a successful result, or record a failure for the given stage
+    fn check<T, E: Display>(
+        &mut self,
+        stage: Stage,
+        result: Result<T, E>,
+        suggestion: &str,
+    ) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(e) => {
+                self.failed(stage, e, suggestion);
+                None
+            }
+        }
+    }
+
+    fn push(
+        &mut self,
+        stage: Stage,
+        status: CheckStatus,
+        details: impl Display,
+        suggestion: Option<&str>,
+    ) {
+        self.checks.push(Check {
+            stage,
+            status,
+            details: details.to_string(),
+            suggestion: suggestion.map(|s| s.to_string()),
+        })
+    }
+
+    fn first_failure(&self) -> Option<&Check> {
+        self.checks.iter().find(|c| c.status == CheckStatus::Failed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_first_failure() {
+        let mut report = DoctorReport::default();
+        report.ok(
+            Stage::Resolution,
+            "/node/n1 resolves to /ip4/127.0.0.1/tcp/1234",
+        );
+        report.skipped(Stage::TcpReachability, "the route has no TCP address");
+        assert!(report.first_failure().is_none());
+
+        report.failed(
+            Stage::SecureChannel,
+            "timeout",
+            Stage::SecureChannel.suggestion(),
+        );
+        report.failed(
+            Stage::RelayResolution,
+            "not found",
+            Stage::RelayResolution.suggestion(),
+        );
+        let failure = report.first_failure().unwrap();
+        assert_eq!(failure.stage, Stage::SecureChannel);
+        assert_eq!(failure.details, "timeout");
+    }
+
+    #[test]
+    fn test_report_check() {
+        let mut report = DoctorReport::default();
+        let value = report.check(
+            Stage::CredentialExchange,
+            Ok::<_, String>("identity"),
+            IDENTITY_SUGGESTION,
+        );
+        assert_eq!(value, Some("identity"));
+        assert!(report.checks.is_empty());
+
+        // an error is reported as a failed stage instead of stopping the diagnostic
+        let value = report.check(
+            Stage::CredentialExchange,
+            Err::<(), _>("identity not found"),
+            IDENTITY_SUGGESTION,
+        );
+        assert_eq!(value, None);
+        let failure = report.first_failure().unwrap();
+        assert_eq!(failure.stage, Stage::CredentialExchange);
+        assert_eq!(failure.details, "identity not found");
+        assert_eq!(failure.suggestion.as_deref(), Some(IDENTITY_SUGGESTION));
+    }
+
+    #[test]
+    fn test_report_display() {
+        let mut report = DoctorReport::default();
+        report.ok(Stage::TcpReachability, "127.0.0.1:4000 is reachable");
+        report.failed(
+            Stage::RelayResolution,
+            "not found",
+            Stage::RelayResolution.suggestion(),
+        );
+
+        let ok = report.checks[0].to_string();
+        assert!(ok.contains("TCP reachability: 127.0.0.1:4000 is reachable"));
+        assert!(!ok.contains('\n'));
+
+        let failed = report.checks[1].to_string();
+        assert!(failed.contains("relay resolution: not found"));
+        assert!(failed.contains("ockam relay list"));
+    }
+
+    #[test]
+    fn test_report_json() {
+        let mut report = DoctorReport::default();
+        report.ok(Stage::Resolution, "resolved");
+        report.failed(Stage::CredentialExchange, "no credential", "enroll");
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "checks": [
+                    { "stage": "resolution", "status": "ok", "details": "resolved" },
+                    {
+                        "stage": "credential_exchange",
+                        "status": "failed",
+                        "details": "no credential",
+                        "suggestion": "enroll"
+                    }
+                ]
+            })
+        );
+    }
+}
@@ -50,6 +50,11 @@ pub trait OidcServiceExt {
 #[async_trait]
 impl OidcServiceExt for OidcService {
     async fn get_token_interactively(&self, opts: &CommandGlobalOpts) -> Result<OidcToken> {
+        if opts.global_args.non_interactive {
+            return Err(miette!(
+                "Enrolling requires an interaction with a browser, which is not possible in non-interactive mode"
+            ))?;
+        }
         let dc = self.device_code().await?;
 
         // On Linux, the clipboard is cleared when the record goes out of scope, so
@@ -169,6 +169,53 @@ impl miette::ReportHandler for ErrorReportHandler {
     }
 }
 
+/// Error report handler used in non-interactive mode.
+///
+/// Errors are written as a single JSON object, for example:
+/// `{"code":"OCK404","message":"Unable to find node named n1","help":"Please check the spelling and try again"}`
+/// The `code` field is stable and can be used by scripts to react to specific failures.
+pub struct JsonErrorReportHandler;
+
+impl JsonErrorReportHandler {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn to_json(error: &dyn Diagnostic) -> serde_json::Value {
+        let code = match error.code() {
+            Some(code) => code.to_string(),
+            None => "OCK500".to_string(),
+        };
+        let mut causes = vec![];
+        let mut source = error.source();
+        while let Some(cause) = source {
+            causes.push(cause.to_string());
+            source = cause.source();
+        }
+        serde_json::json!({
+            "code": code,
+            "message": error.to_string(),
+            "help": error.help().map(|h| h.to_string()),
+            "causes": causes,
+        })
+    }
+}
+
+impl Default for JsonErrorReportHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl miette::ReportHandler for JsonErrorReportHandler {
+    fn debug(&self, error: &dyn Diagnostic, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if f.alternate() {
+            return core::fmt::Debug::fmt(error, f);
+        }
+        write!(f, "{}", Self::to_json(error))
+    }
+}
+
 macro_rules! gen_from_impl {
     ($t:ty, $c:ident) => {
         impl From<$t> for Error {
@@ -196,3 +243,25 @@ gen_from_impl!(ockam_multiaddr::Error, SOFTWARE);
 gen_from_impl!(miette::ErrReport, SOFTWARE);
 gen_from_impl!(time::error::Parse, DATAERR);
 gen_from_impl!(dialoguer::Error, DATAERR);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_error_report() {
+        let error = Error::NotFound {
+            resource: "node".to_string(),
+            resource_name: "n1".to_string(),
+        };
+        let json = JsonErrorReportHandler::to_json(&error);
+        assert_eq!(json["code"], "OCK404");
+        assert_eq!(json["message"], "Unable to find node named n1");
+        assert_eq!(json["help"], "Please check the spelling and try again");
+
+        let error = Error::new(exitcode::SOFTWARE, miette!("failed"));
+        let json = JsonErrorReportHandler::to_json(&error);
+        assert_eq!(json["code"], "OCK500");
+        assert_eq!(json["message"], "failed");
+    }
+}
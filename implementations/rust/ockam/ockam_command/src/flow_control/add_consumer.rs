@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use clap::Args;
 
 use ockam::Context;
@@ -6,6 +8,7 @@ use ockam_core::flow_control::FlowControlId;
 use ockam_multiaddr::MultiAddr;
 
 use crate::node::NodeOpts;
+use crate::util::duration::duration_parser;
 use crate::util::{api, node_rpc};
 use crate::CommandGlobalOpts;
 
@@ -20,6 +23,14 @@ pub struct AddConsumerCommand {
 
     /// Address of the Consumer
     address: MultiAddr,
+
+    /// Remove the Consumer after it received this number of messages
+    #[arg(long, value_name = "COUNT")]
+    max_messages: Option<u64>,
+
+    /// Remove the Consumer after this duration, for example 30s or 5m
+    #[arg(long, value_name = "DURATION", value_parser = duration_parser)]
+    duration: Option<Duration>,
 }
 
 impl AddConsumerCommand {
@@ -41,8 +52,16 @@ async fn run_impl(
     cmd: AddConsumerCommand,
 ) -> miette::Result<()> {
     let node = BackgroundNodeClient::create(ctx, &opts.state, &cmd.node_opts.at_node).await?;
-    node.tell(ctx, api::add_consumer(cmd.flow_control_id, cmd.address))
-        .await?;
+    node.tell(
+        ctx,
+        api::add_consumer(
+            cmd.flow_control_id,
+            cmd.address,
+            cmd.max_messages,
+            cmd.duration,
+        ),
+    )
+    .await?;
 
     Ok(())
 }
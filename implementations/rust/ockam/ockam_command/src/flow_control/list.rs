@@ -0,0 +1,68 @@
+use clap::Args;
+use colorful::Colorful;
+use miette::IntoDiagnostic;
+
+use ockam::Context;
+use ockam_api::nodes::models::flow_controls::{FlowControlList, FlowControlStatus};
+use ockam_api::nodes::BackgroundNodeClient;
+
+use crate::node::NodeOpts;
+use crate::output::Output;
+use crate::terminal::OckamColor;
+use crate::util::{api, node_rpc};
+use crate::CommandGlobalOpts;
+
+/// List the spawners, producers and consumers of the flow controls of a node
+#[derive(Clone, Debug, Args)]
+pub struct ListCommand {
+    #[command(flatten)]
+    pub node_opts: NodeOpts,
+}
+
+impl ListCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        node_rpc(run_impl, (options, self))
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, ListCommand),
+) -> miette::Result<()> {
+    let node = BackgroundNodeClient::create(&ctx, &opts.state, &cmd.node_opts.at_node).await?;
+    let flow_controls: FlowControlList = node.ask(&ctx, api::list_flow_controls()).await?;
+
+    let plain = opts.terminal.build_list(
+        &flow_controls.flow_controls,
+        &format!("Flow controls on {}", node.node_name()),
+        &format!("No flow controls found on {}.", node.node_name()),
+    )?;
+    let json = serde_json::to_string_pretty(&flow_controls).into_diagnostic()?;
+    opts.terminal
+        .stdout()
+        .plain(plain)
+        .json(json)
+        .write_line()?;
+    Ok(())
+}
+
+impl Output for FlowControlStatus {
+    fn output(&self) -> crate::Result<String> {
+        let addresses = |addresses: &[String]| {
+            if addresses.is_empty() {
+                "-".to_string()
+            } else {
+                addresses.join(", ")
+            }
+        };
+        Ok(format!(
+            "Flow control {}\nSpawners: {}\nProducers: {}\nConsumers: {}",
+            self.flow_control_id
+                .to_string()
+                .color(OckamColor::PrimaryResource.color()),
+            addresses(&self.spawners),
+            addresses(&self.producers),
+            addresses(&self.consumers),
+        ))
+    }
+}
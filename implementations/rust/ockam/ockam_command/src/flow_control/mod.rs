@@ -2,8 +2,12 @@ use crate::CommandGlobalOpts;
 use clap::{Args, Subcommand};
 
 mod add_consumer;
+mod list;
+mod show;
 
 pub use add_consumer::AddConsumerCommand;
+pub use list::ListCommand;
+pub use show::ShowCommand;
 
 #[derive(Clone, Debug, Args)]
 #[command(arg_required_else_help = true, subcommand_required = true)]
@@ -16,12 +20,16 @@ pub struct FlowControlCommand {
 pub enum FlowControlSubcommand {
     #[command(display_order = 800)]
     AddConsumer(AddConsumerCommand),
+    List(ListCommand),
+    Show(ShowCommand),
 }
 
 impl FlowControlCommand {
     pub fn run(self, options: CommandGlobalOpts) {
         match self.subcommand {
             FlowControlSubcommand::AddConsumer(c) => c.run(options),
+            FlowControlSubcommand::List(c) => c.run(options),
+            FlowControlSubcommand::Show(c) => c.run(options),
         }
     }
 }
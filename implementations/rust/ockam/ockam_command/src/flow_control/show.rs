@@ -0,0 +1,83 @@
+use clap::Args;
+use colorful::Colorful;
+use miette::IntoDiagnostic;
+
+use ockam::Context;
+use ockam_api::nodes::models::flow_controls::AddressFlowControlStatus;
+use ockam_api::nodes::BackgroundNodeClient;
+
+use crate::node::NodeOpts;
+use crate::output::Output;
+use crate::terminal::OckamColor;
+use crate::util::{api, node_rpc};
+use crate::CommandGlobalOpts;
+
+/// Show the flow controls in which a worker takes part.
+/// This helps finding out why a message is denied by the flow control access controls
+#[derive(Clone, Debug, Args)]
+#[command(arg_required_else_help = true)]
+pub struct ShowCommand {
+    #[command(flatten)]
+    pub node_opts: NodeOpts,
+
+    /// Address of the worker
+    address: String,
+}
+
+impl ShowCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        node_rpc(run_impl, (options, self))
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, ShowCommand),
+) -> miette::Result<()> {
+    let node = BackgroundNodeClient::create(&ctx, &opts.state, &cmd.node_opts.at_node).await?;
+    let status: AddressFlowControlStatus = node
+        .ask(&ctx, api::get_address_flow_controls(&cmd.address))
+        .await?;
+
+    let json = serde_json::to_string_pretty(&status).into_diagnostic()?;
+    opts.terminal
+        .stdout()
+        .plain(status.output()?)
+        .json(json)
+        .write_line()?;
+    Ok(())
+}
+
+impl Output for AddressFlowControlStatus {
+    fn output(&self) -> crate::Result<String> {
+        let mut output = format!(
+            "Worker {}",
+            self.address
+                .as_str()
+                .color(OckamColor::PrimaryResource.color())
+        );
+        if self.consumer_of.is_empty() {
+            output.push_str("\nConsumer of: -");
+        } else {
+            let ids: Vec<String> = self.consumer_of.iter().map(|id| id.to_string()).collect();
+            output.push_str(&format!("\nConsumer of: {}", ids.join(", ")));
+        }
+        match &self.producer_of {
+            Some(id) => {
+                output.push_str(&format!("\nProducer of: {id}"));
+                if let Some(spawner) = &self.producer_spawner {
+                    output.push_str(&format!(", spawned by {spawner}"));
+                }
+                if let Some(address) = &self.producer_address {
+                    output.push_str(&format!(", as an additional address of {address}"));
+                }
+            }
+            None => output.push_str("\nProducer of: -"),
+        }
+        match &self.spawner_of {
+            Some(id) => output.push_str(&format!("\nSpawner of: {id}")),
+            None => output.push_str("\nSpawner of: -"),
+        }
+        Ok(output)
+    }
+}
@@ -78,7 +78,8 @@ impl ShowCommand {
                 let selected_names = opts.terminal.select_multiple(
                     "Select one or more identities that you want to show".to_string(),
                     identities_names,
-                );
+                    "identity name",
+                )?;
 
                 if selected_names.is_empty() {
                     opts.terminal
@@ -91,7 +92,7 @@ impl ShowCommand {
                 if opts.terminal.confirm_interactively(format!(
                     "Would you like to show these items : {:?}?",
                     selected_names
-                )) {
+                ))? {
                     Self::show_identity_list(&opts, selected_names).await?;
                 }
             }
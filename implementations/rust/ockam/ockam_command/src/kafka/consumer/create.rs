@@ -5,7 +5,7 @@ use clap::{command, Args};
 use ockam_api::port_range::PortRange;
 use ockam_multiaddr::MultiAddr;
 
-use crate::kafka::util::{make_brokers_port_range, rpc, ArgOpts};
+use crate::kafka::util::{make_brokers_port_range, rpc, ArgOpts, SchemaRegistryArgs};
 use crate::{
     kafka::{
         kafka_consumer_default_addr, kafka_default_consumer_server, kafka_default_project_route,
@@ -35,6 +35,13 @@ pub struct CreateCommand {
     /// The route to the project in ockam orchestrator, expected something like /project/<name>
     #[arg(long, default_value_t = kafka_default_project_route())]
     project_route: MultiAddr,
+    #[command(flatten)]
+    schema_registry: SchemaRegistryArgs,
+    /// How the records of the topics matching a pattern are handled, as
+    /// `<pattern>=<encrypt|passthrough|deny>`, where `*` matches any sequence of characters.
+    /// The first matching rule is used, and the other topics are encrypted
+    #[arg(long = "topic", value_name = "PATTERN=ENCRYPTION")]
+    topic_policy: Vec<String>,
 }
 
 impl CreateCommand {
@@ -49,6 +56,8 @@ impl CreateCommand {
                 .brokers_port_range
                 .unwrap_or_else(|| make_brokers_port_range(&self.bootstrap_server)),
             project_route: self.project_route,
+            schema_registry: self.schema_registry.options(),
+            topic_policy: self.topic_policy,
         };
         node_rpc(rpc, (opts, arg_opts));
     }
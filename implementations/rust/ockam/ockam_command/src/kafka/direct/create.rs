@@ -1,7 +1,7 @@
 use std::net::SocketAddr;
 
 use crate::kafka::direct::rpc::{start, ArgOpts};
-use crate::kafka::util::make_brokers_port_range;
+use crate::kafka::util::{make_brokers_port_range, SchemaRegistryArgs};
 use crate::{
     kafka::{
         kafka_default_consumer_server, kafka_default_outlet_server, kafka_direct_default_addr,
@@ -36,6 +36,13 @@ pub struct CreateCommand {
     /// The route to another kafka consumer node
     #[arg(long)]
     consumer_route: Option<MultiAddr>,
+    #[command(flatten)]
+    schema_registry: SchemaRegistryArgs,
+    /// How the records of the topics matching a pattern are handled, as
+    /// `<pattern>=<encrypt|passthrough|deny>`, where `*` matches any sequence of characters.
+    /// The first matching rule is used, and the other topics are encrypted
+    #[arg(long = "topic", value_name = "PATTERN=ENCRYPTION")]
+    topic_policy: Vec<String>,
 }
 
 impl CreateCommand {
@@ -51,6 +58,8 @@ impl CreateCommand {
                 .unwrap_or_else(|| make_brokers_port_range(&self.bootstrap_server)),
             consumer_route: self.consumer_route,
             bootstrap_server: self.bootstrap_server,
+            schema_registry: self.schema_registry.options(),
+            topic_policy: self.topic_policy,
         };
         node_rpc(start, (opts, arg_opts));
     }
@@ -4,7 +4,9 @@ use colorful::Colorful;
 use tokio::{sync::Mutex, try_join};
 
 use ockam::Context;
-use ockam_api::nodes::models::services::{StartKafkaDirectRequest, StartServiceRequest};
+use ockam_api::nodes::models::services::{
+    KafkaSchemaRegistryOptions, StartKafkaDirectRequest, StartServiceRequest,
+};
 use ockam_api::nodes::BackgroundNodeClient;
 use ockam_api::port_range::PortRange;
 use ockam_core::api::Request;
@@ -26,6 +28,8 @@ pub struct ArgOpts {
     pub brokers_port_range: PortRange,
     pub consumer_route: Option<MultiAddr>,
     pub bootstrap_server: SocketAddr,
+    pub schema_registry: Option<KafkaSchemaRegistryOptions>,
+    pub topic_policy: Vec<String>,
 }
 
 pub async fn start(ctx: Context, (opts, args): (CommandGlobalOpts, ArgOpts)) -> miette::Result<()> {
@@ -39,6 +43,8 @@ pub async fn start(ctx: Context, (opts, args): (CommandGlobalOpts, ArgOpts)) ->
         brokers_port_range,
         consumer_route,
         bootstrap_server,
+        schema_registry,
+        topic_policy,
     } = args;
 
     opts.terminal
@@ -61,7 +67,9 @@ pub async fn start(ctx: Context, (opts, args): (CommandGlobalOpts, ArgOpts)) ->
             bootstrap_server,
             brokers_port_range,
             consumer_route,
-        );
+        )
+        .with_schema_registry(schema_registry)
+        .with_topic_policy(topic_policy);
         let payload = StartServiceRequest::new(payload, &addr);
         let req = Request::post(endpoint).body(payload);
         start_service_impl(&ctx, &node, &kafka_entity, req).await?;
@@ -1,14 +1,19 @@
-use std::net::SocketAddr;
+use std::net::IpAddr;
+use std::path::PathBuf;
 
 use clap::{command, Args};
 use colorful::Colorful;
+use miette::{miette, IntoDiagnostic, WrapErr};
 use tokio::{sync::Mutex, try_join};
 
 use ockam::Context;
-use ockam_api::nodes::models::services::StartKafkaOutletRequest;
+use ockam_api::kafka::KafkaSaslMechanism;
+use ockam_api::nodes::models::portal::OutletTls;
 use ockam_api::nodes::models::services::StartServiceRequest;
+use ockam_api::nodes::models::services::{KafkaSaslCredentials, StartKafkaOutletRequest};
 use ockam_api::nodes::BackgroundNodeClient;
 use ockam_core::api::Request;
+use ockam_core::env::get_env;
 
 use crate::node::util::initialize_default_node;
 use crate::{
@@ -18,6 +23,7 @@ use crate::{
     service::start::start_service_impl,
     terminal::OckamColor,
     util::node_rpc,
+    util::parsers::{host_port_parser, socket_addr_parser},
     CommandGlobalOpts,
 };
 
@@ -30,8 +36,107 @@ pub struct CreateCommand {
     #[arg(long, default_value_t = kafka_default_outlet_addr())]
     addr: String,
     /// The address of the kafka bootstrap broker
-    #[arg(long, default_value_t = kafka_default_outlet_server())]
-    bootstrap_server: SocketAddr,
+    #[arg(long, default_value_t = kafka_default_outlet_server().to_string(), value_parser = host_port_parser)]
+    bootstrap_server: String,
+    #[command(flatten)]
+    tls: KafkaTlsArgs,
+    #[command(flatten)]
+    sasl: KafkaSaslArgs,
+}
+
+/// Connect to the brokers with TLS, optionally authenticating with a client certificate
+#[derive(Clone, Debug, Args)]
+struct KafkaTlsArgs {
+    /// Connect to the brokers with TLS
+    #[arg(long)]
+    tls: bool,
+    /// PEM file of the certificates used to verify the brokers,
+    /// instead of the platform trusted certificates. Implies --tls
+    #[arg(long, value_name = "PEM_FILE")]
+    tls_ca_certificates: Option<String>,
+    /// PEM file of the certificate chain used to authenticate to the brokers. Implies --tls
+    #[arg(long, value_name = "PEM_FILE", requires = "tls_client_private_key")]
+    tls_client_certificate: Option<String>,
+    /// PEM file of the private key used to authenticate to the brokers. Implies --tls
+    #[arg(long, value_name = "PEM_FILE", requires = "tls_client_certificate")]
+    tls_client_private_key: Option<String>,
+    /// Server name used to verify the brokers, instead of their host names. Implies --tls
+    #[arg(long)]
+    tls_server_name: Option<String>,
+}
+
+impl KafkaTlsArgs {
+    fn outlet_tls(&self) -> Option<OutletTls> {
+        let enabled = self.tls
+            || self.tls_ca_certificates.is_some()
+            || self.tls_client_certificate.is_some()
+            || self.tls_server_name.is_some();
+        enabled.then(|| OutletTls {
+            server_name: self.tls_server_name.clone(),
+            ca_certificates: self.tls_ca_certificates.clone(),
+            client_certificate: self
+                .tls_client_certificate
+                .clone()
+                .zip(self.tls_client_private_key.clone()),
+        })
+    }
+}
+
+/// Environment variable containing the SASL password of the outlet
+const SASL_PASSWORD_ENV: &str = "OCKAM_KAFKA_SASL_PASSWORD";
+
+/// Authenticate every connection to the brokers with SASL, using the credentials of the outlet
+///
+/// The password is read from the file given with --sasl-password-file, or from the
+/// OCKAM_KAFKA_SASL_PASSWORD environment variable, or else it is prompted, so that it
+/// never appears in the command line
+#[derive(Clone, Debug, Args)]
+struct KafkaSaslArgs {
+    /// SASL mechanism: PLAIN, SCRAM-SHA-256 or SCRAM-SHA-512
+    #[arg(long, requires = "sasl_username")]
+    sasl_mechanism: Option<KafkaSaslMechanism>,
+    /// SASL user name
+    #[arg(long, requires = "sasl_mechanism")]
+    sasl_username: Option<String>,
+    /// File containing the SASL password, instead of the OCKAM_KAFKA_SASL_PASSWORD
+    /// environment variable or a prompt
+    #[arg(long, value_name = "FILE", requires = "sasl_mechanism")]
+    sasl_password_file: Option<PathBuf>,
+}
+
+impl KafkaSaslArgs {
+    fn credentials(
+        &self,
+        opts: &CommandGlobalOpts,
+    ) -> miette::Result<Option<KafkaSaslCredentials>> {
+        let (mechanism, username) = match (&self.sasl_mechanism, &self.sasl_username) {
+            (Some(mechanism), Some(username)) => (*mechanism, username.clone()),
+            _ => return Ok(None),
+        };
+        Ok(Some(KafkaSaslCredentials::new(
+            mechanism,
+            username,
+            self.password(opts)?,
+        )))
+    }
+
+    fn password(&self, opts: &CommandGlobalOpts) -> miette::Result<String> {
+        if let Some(path) = &self.sasl_password_file {
+            let password = std::fs::read_to_string(path)
+                .into_diagnostic()
+                .wrap_err(format!("Failed to read the SASL password file {path:?}"))?;
+            return Ok(password.trim_end_matches(['\r', '\n']).to_string());
+        }
+        if let Some(password) = get_env::<String>(SASL_PASSWORD_ENV).into_diagnostic()? {
+            return Ok(password);
+        }
+        if !opts.terminal.can_ask_for_user_input() {
+            return Err(miette!(
+                "The SASL password must be given with --sasl-password-file or {SASL_PASSWORD_ENV}"
+            ));
+        }
+        Ok(opts.terminal.password("Enter the SASL password", None)?)
+    }
 }
 
 impl CreateCommand {
@@ -48,10 +153,23 @@ async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, CreateCommand)) -> m
         node_opts,
         addr,
         bootstrap_server,
+        tls,
+        sasl,
     } = cmd;
+    // Keep the host name of the bootstrap server to verify its TLS certificate
+    let bootstrap_server_hostname = bootstrap_server
+        .rsplit_once(':')
+        .map(|(host, _)| host.trim_start_matches('[').trim_end_matches(']'))
+        .filter(|host| host.parse::<IpAddr>().is_err())
+        .map(|host| host.to_string());
+    let bootstrap_server_addr = socket_addr_parser(&bootstrap_server)?;
+    let sasl = sasl.credentials(&opts)?;
     let is_finished = Mutex::new(false);
     let send_req = async {
-        let payload = StartKafkaOutletRequest::new(bootstrap_server);
+        let payload = StartKafkaOutletRequest::new(bootstrap_server_addr)
+            .with_bootstrap_server_hostname(bootstrap_server_hostname)
+            .with_tls(tls.outlet_tls())
+            .with_sasl(sasl);
         let payload = StartServiceRequest::new(payload, &addr);
         let req = Request::post("/node/services/kafka_outlet").body(payload);
         let node = BackgroundNodeClient::create(&ctx, &opts.state, &node_opts.at_node).await?;
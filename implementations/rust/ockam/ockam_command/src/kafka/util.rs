@@ -1,10 +1,13 @@
 use std::net::SocketAddr;
 
+use clap::Args;
 use colorful::Colorful;
 use tokio::{sync::Mutex, try_join};
 
 use ockam::Context;
-use ockam_api::nodes::models::services::{StartKafkaProducerRequest, StartServiceRequest};
+use ockam_api::nodes::models::services::{
+    KafkaSchemaRegistryOptions, StartKafkaProducerRequest, StartServiceRequest,
+};
 use ockam_api::nodes::BackgroundNodeClient;
 use ockam_api::port_range::PortRange;
 use ockam_core::api::Request;
@@ -25,6 +28,31 @@ pub struct ArgOpts {
     pub bootstrap_server: SocketAddr,
     pub brokers_port_range: PortRange,
     pub project_route: MultiAddr,
+    pub schema_registry: Option<KafkaSchemaRegistryOptions>,
+    pub topic_policy: Vec<String>,
+}
+
+/// Handle the records written by the clients of a schema registry
+#[derive(Clone, Debug, Args)]
+pub struct SchemaRegistryArgs {
+    /// Encrypt only the payload of the records in the Confluent wire format, used by the
+    /// clients of a schema registry, and keep their magic byte and schema id in plaintext
+    #[arg(long)]
+    confluent_wire_format: bool,
+    /// URL of the schema registry where the encrypted variants of the schemas are registered.
+    /// Implies --confluent-wire-format. It must be set on both the producers and the consumers
+    #[arg(long, value_name = "URL")]
+    schema_registry_url: Option<String>,
+}
+
+impl SchemaRegistryArgs {
+    pub fn options(&self) -> Option<KafkaSchemaRegistryOptions> {
+        (self.confluent_wire_format || self.schema_registry_url.is_some()).then(|| {
+            KafkaSchemaRegistryOptions {
+                url: self.schema_registry_url.clone(),
+            }
+        })
+    }
 }
 
 /// Return a range of 100 ports after the bootstrap server port
@@ -44,6 +72,8 @@ pub async fn rpc(ctx: Context, (opts, args): (CommandGlobalOpts, ArgOpts)) -> mi
         bootstrap_server,
         brokers_port_range,
         project_route,
+        schema_registry,
+        topic_policy,
     } = args;
 
     opts.terminal
@@ -61,7 +91,9 @@ pub async fn rpc(ctx: Context, (opts, args): (CommandGlobalOpts, ArgOpts)) -> mi
             bootstrap_server.to_owned(),
             brokers_port_range,
             project_route,
-        );
+        )
+        .with_schema_registry(schema_registry)
+        .with_topic_policy(topic_policy);
         let payload = StartServiceRequest::new(payload, &addr);
         let req = Request::post(endpoint).body(payload);
         start_service_impl(&ctx, &node, &kafka_entity, req).await?;
@@ -30,9 +30,10 @@ use authenticated::AuthenticatedCommand;
 use completion::CompletionCommand;
 use configuration::ConfigurationCommand;
 use credential::CredentialCommand;
+use doctor::DoctorCommand;
 use enroll::EnrollCommand;
 use environment::EnvironmentCommand;
-use error::{Error, Result};
+use error::{Error, JsonErrorReportHandler, Result};
 use identity::IdentityCommand;
 use kafka::consumer::KafkaConsumerCommand;
 use kafka::producer::KafkaProducerCommand;
@@ -41,8 +42,14 @@ use manpages::ManpagesCommand;
 use markdown::MarkdownCommand;
 use message::MessageCommand;
 use node::NodeCommand;
-use ockam_api::cli_state::CliState;
+use ockam::identity::OCKAM_MAX_SECURE_CHANNELS;
+use ockam_api::cli_state::{set_vault_passphrase_prompt, CliState};
+#[cfg(feature = "opentelemetry")]
+use ockam_api::logs::env::OCKAM_OPENTELEMETRY_ENDPOINT;
+use ockam_api::logs::env::{OCKAM_AUDIT_SINKS, OCKAM_LOG_FORMAT};
+use ockam_api::logs::Logging;
 use ockam_core::env::get_env_with_default;
+use ockam_node::{OCKAM_MAILBOX_CAPACITY, OCKAM_MAX_WORKERS};
 use policy::PolicyCommand;
 use project::ProjectCommand;
 use relay::RelayCommand;
@@ -58,6 +65,7 @@ use tcp::{
     outlet::TcpOutletCommand,
 };
 use trust_context::TrustContextCommand;
+use udp::{inlet::UdpInletCommand, outlet::UdpOutletCommand};
 use upgrade::check_if_an_upgrade_is_available;
 use util::{exitcode, exitcode::ExitCode};
 use vault::VaultCommand;
@@ -66,11 +74,12 @@ use worker::WorkerCommand;
 
 use crate::admin::AdminCommand;
 use crate::authority::AuthorityCommand;
+use crate::compose::ComposeCommand;
 use crate::flow_control::FlowControlCommand;
 use crate::kafka::direct::KafkaDirectCommand;
 use crate::kafka::outlet::KafkaOutletCommand;
 use crate::logs::setup_logging;
-use crate::node::NodeSubcommand;
+use crate::node::{CreateCommand, NodeSubcommand};
 use crate::output::OutputFormat;
 use crate::run::RunCommand;
 use crate::sidecar::SidecarCommand;
@@ -81,9 +90,11 @@ mod admin;
 mod authenticated;
 mod authority;
 mod completion;
+pub mod compose;
 mod configuration;
 mod credential;
 mod docs;
+mod doctor;
 pub mod enroll;
 mod environment;
 pub mod error;
@@ -116,6 +127,7 @@ mod subscription;
 pub mod tcp;
 mod terminal;
 mod trust_context;
+mod udp;
 mod upgrade;
 pub mod util;
 mod vault;
@@ -184,6 +196,11 @@ pub struct GlobalArgs {
     #[arg(hide = docs::hide(), global = true, long, default_value_t = no_input_default_value())]
     no_input: bool,
 
+    /// Never prompt for input and fail instead when some input is needed.
+    /// Errors are printed as JSON objects, with a stable error code, on stderr
+    #[arg(global = true, long, default_value_t = non_interactive_default_value())]
+    non_interactive: bool,
+
     /// Output format
     #[arg(
     hide = docs::hide(),
@@ -212,6 +229,10 @@ fn no_input_default_value() -> bool {
     get_env_with_default("NO_INPUT", false).unwrap_or(false)
 }
 
+fn non_interactive_default_value() -> bool {
+    get_env_with_default("NON_INTERACTIVE", false).unwrap_or(false)
+}
+
 impl Default for GlobalArgs {
     fn default() -> Self {
         Self {
@@ -220,6 +241,7 @@ impl Default for GlobalArgs {
             verbose: 0,
             no_color: no_color_default_value(),
             no_input: no_input_default_value(),
+            non_interactive: non_interactive_default_value(),
             output_format: OutputFormat::Plain,
             test_argument_parser: false,
         }
@@ -227,6 +249,11 @@ impl Default for GlobalArgs {
 }
 
 impl GlobalArgs {
+    /// Return true if the user can't be asked for any input
+    pub fn no_input(&self) -> bool {
+        self.no_input || self.non_interactive
+    }
+
     pub fn set_quiet(&self) -> Self {
         let mut clone = self.clone();
         clone.quiet = true;
@@ -259,6 +286,14 @@ impl CommandGlobalOpts {
                 exit(exitcode::SOFTWARE);
             }
         };
+        if terminal.can_ask_for_user_input() {
+            set_vault_passphrase_prompt(Box::new(|vault_name| {
+                dialoguer::Password::new()
+                    .with_prompt(format!("Enter the passphrase of the vault {vault_name}"))
+                    .interact()
+                    .ok()
+            }));
+        }
         Self {
             global_args,
             state,
@@ -280,7 +315,7 @@ impl CommandGlobalOpts {
         let terminal = Terminal::new(
             global_args.quiet,
             global_args.no_color,
-            global_args.no_input,
+            global_args.no_input(),
             global_args.output_format.clone(),
         );
         Self {
@@ -314,6 +349,9 @@ pub enum OckamSubcommand {
     TcpOutlet(TcpOutletCommand),
     TcpInlet(TcpInletCommand),
 
+    UdpOutlet(UdpOutletCommand),
+    UdpInlet(UdpInletCommand),
+
     KafkaOutlet(KafkaOutletCommand),
     KafkaConsumer(KafkaConsumerCommand),
     KafkaDirect(KafkaDirectCommand),
@@ -330,7 +368,9 @@ pub enum OckamSubcommand {
     Lease(LeaseCommand),
 
     Run(RunCommand),
+    Compose(ComposeCommand),
     Status(StatusCommand),
+    Doctor(DoctorCommand),
     Reset(ResetCommand),
     Authenticated(AuthenticatedCommand),
     Configuration(ConfigurationCommand),
@@ -370,16 +410,59 @@ impl OckamCommand {
         // Sets a hook using our own Error Report Handler
         // This allows us to customize how we
         // format the error messages and their content.
-        let _hook_result = miette::set_hook(Box::new(|_| {
-            Box::new(
-                GraphicalReportHandler::new()
-                    .with_cause_chain()
-                    .with_footer(Version::short().light_gray().to_string())
-                    .with_urls(false),
-            )
-        }));
+        // In non-interactive mode, errors are formatted as JSON so that they can be parsed by scripts.
+        let _hook_result = if self.global_args.non_interactive {
+            miette::set_hook(Box::new(|_| Box::new(JsonErrorReportHandler::new())))
+        } else {
+            miette::set_hook(Box::new(|_| {
+                Box::new(
+                    GraphicalReportHandler::new()
+                        .with_cause_chain()
+                        .with_footer(Version::short().light_gray().to_string())
+                        .with_urls(false),
+                )
+            }))
+        };
         let options = CommandGlobalOpts::new(self.global_args.clone());
 
+        // The traces of a node, and of the background process creating it, are exported
+        // to the collector of the node. The variable is inherited by the node process.
+        #[cfg(feature = "opentelemetry")]
+        if let Some(endpoint) = self
+            .node_create()
+            .and_then(|c| c.opentelemetry_endpoint.as_ref())
+        {
+            std::env::set_var(OCKAM_OPENTELEMETRY_ENDPOINT, endpoint);
+        }
+        // Same for the format of the logs
+        if let Some(log_format) = self.node_create().and_then(|c| c.log_format.as_ref()) {
+            std::env::set_var(OCKAM_LOG_FORMAT, log_format.to_string());
+        }
+        // And for the sinks of the audit events
+        if let Some(audit_sinks) = self
+            .node_create()
+            .map(|c| &c.audit_sinks)
+            .filter(|s| !s.is_empty())
+        {
+            std::env::set_var(OCKAM_AUDIT_SINKS, audit_sinks.join(","));
+        }
+        // And for the limits on the resources of the node
+        if let Some(c) = self.node_create() {
+            let limits = [
+                (OCKAM_MAX_WORKERS, c.max_workers.map(|m| m as u64)),
+                (OCKAM_MAILBOX_CAPACITY, c.mailbox_capacity),
+                (
+                    OCKAM_MAX_SECURE_CHANNELS,
+                    c.max_secure_channels.map(|m| m as u64),
+                ),
+            ];
+            for (name, limit) in limits {
+                if let Some(limit) = limit {
+                    std::env::set_var(name, limit.to_string());
+                }
+            }
+        }
+
         let _tracing_guard = if !options.global_args.quiet {
             let log_path = self.log_path(&options);
             let guard = setup_logging(
@@ -387,6 +470,7 @@ impl OckamCommand {
                 options.global_args.no_color,
                 options.terminal.is_tty(),
                 log_path,
+                self.node_create().map(|c| c.node_name.as_str()),
             );
             tracing::debug!("{}", Version::short());
             tracing::debug!("Parsed {:?}", &self);
@@ -415,7 +499,10 @@ impl OckamCommand {
                 .write_line(&format!("{}\n", colored_header));
         }
 
-        match self.subcommand {
+        // All the spans created by the command, including the spans of the nodes
+        // receiving its requests, are part of the same trace
+        let span = tracing::info_span!("command", name = %command_name());
+        span.in_scope(|| match self.subcommand {
             OckamSubcommand::Enroll(c) => c.run(options),
             OckamSubcommand::Space(c) => c.run(options),
             OckamSubcommand::Project(c) => c.run(options),
@@ -435,6 +522,8 @@ impl OckamCommand {
             OckamSubcommand::TcpConnection(c) => c.run(options),
             OckamSubcommand::TcpOutlet(c) => c.run(options),
             OckamSubcommand::TcpInlet(c) => c.run(options),
+            OckamSubcommand::UdpOutlet(c) => c.run(options),
+            OckamSubcommand::UdpInlet(c) => c.run(options),
 
             OckamSubcommand::KafkaConsumer(c) => c.run(options),
             OckamSubcommand::KafkaProducer(c) => c.run(options),
@@ -451,7 +540,9 @@ impl OckamCommand {
             OckamSubcommand::Lease(c) => c.run(options),
 
             OckamSubcommand::Run(c) => c.run(options),
+            OckamSubcommand::Compose(c) => c.run(options),
             OckamSubcommand::Status(c) => c.run(options),
+            OckamSubcommand::Doctor(c) => c.run(options),
             OckamSubcommand::Reset(c) => c.run(options),
             OckamSubcommand::Authenticated(c) => c.run(options),
             OckamSubcommand::Configuration(c) => c.run(options),
@@ -464,7 +555,18 @@ impl OckamCommand {
 
             OckamSubcommand::FlowControl(c) => c.run(options),
             OckamSubcommand::Sidecar(c) => c.run(options),
+        });
+        Logging::shutdown();
+    }
+
+    /// Return the `node create` command, if it is the command being run
+    fn node_create(&self) -> Option<&CreateCommand> {
+        if let OckamSubcommand::Node(c) = &self.subcommand {
+            if let NodeSubcommand::Create(c) = &c.subcommand {
+                return Some(c);
+            }
         }
+        None
     }
 
     fn log_path(&self, opts: &CommandGlobalOpts) -> Option<PathBuf> {
@@ -484,6 +586,17 @@ impl OckamCommand {
     }
 }
 
+/// Return the name of the command being run, for example `node create`.
+/// Only the subcommands are returned since the arguments may contain secrets.
+fn command_name() -> String {
+    std::env::args()
+        .skip(1)
+        .filter(|arg| !arg.starts_with('-'))
+        .take(2)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 /// Display and clear any known messages from parsing.
 pub(crate) fn display_parse_logs(opts: &CommandGlobalOpts) {
     if let Ok(mut logs) = PARSER_LOGS.lock() {
@@ -1,4 +1,4 @@
-use ockam_api::logs::env::log_level;
+use ockam_api::logs::env::{audit_sinks, log_level, opentelemetry_endpoint};
 use ockam_api::logs::{LevelFilter, Logging, WorkerGuard};
 use std::path::PathBuf;
 use std::str::FromStr;
@@ -8,6 +8,7 @@ pub fn setup_logging(
     no_color: bool,
     is_tty: bool,
     log_path: Option<PathBuf>,
+    node_name: Option<&str>,
 ) -> Option<WorkerGuard> {
     let level = {
         // Parse the the raw log level value (e.g. "info" or "-vvv").
@@ -24,7 +25,9 @@ pub fn setup_logging(
         };
         // If the parsed log level is not valid, default to info.
         let level = LevelFilter::from_str(&level_raw).unwrap_or(LevelFilter::INFO);
-        if level == LevelFilter::OFF {
+        // Spans and audit events are still exported when the logs are turned off
+        let exports_spans = cfg!(feature = "opentelemetry") && opentelemetry_endpoint().is_some();
+        if level == LevelFilter::OFF && !exports_spans && audit_sinks().is_none() {
             return None;
         }
         level
@@ -40,5 +43,5 @@ pub fn setup_logging(
         "ockam_api",
         "ockam_command",
     ];
-    Logging::setup(level, color, log_path, &ockam_crates)
+    Logging::setup(level, color, log_path, node_name, &ockam_crates)
 }
@@ -6,6 +6,7 @@ use miette::{miette, IntoDiagnostic};
 
 use ockam::identity::Identity;
 use ockam_api::cli_state::random_name;
+use ockam_api::logs::audit::AuditSinkConfig;
 
 use crate::node::create::background::background_mode;
 use crate::node::create::foreground::foreground_mode;
@@ -51,10 +52,26 @@ pub struct CreateCommand {
     )]
     pub tcp_listener_address: String,
 
+    /// Address of an HTTP listener serving the metrics of the node in the Prometheus
+    /// format on `/metrics`, and its health and readiness probes on `/healthz` and `/readyz`,
+    /// for example `127.0.0.1:9090`
+    #[arg(display_order = 900, long, value_name = "SOCKET_ADDRESS")]
+    pub metrics_listener: Option<String>,
+
+    /// JSON file declaring the policies, TCP outlets, TCP inlets and relays of the node.
+    /// The file is read again, and only its changes are applied, when the node receives
+    /// a SIGHUP signal or when `ockam node reload` is called
+    #[arg(display_order = 900, long, value_name = "PATH")]
+    pub configuration: Option<PathBuf>,
+
     /// `node create` started a child process to run this node in foreground.
     #[arg(long, hide = true)]
     pub child_process: bool,
 
+    /// The passphrase of the vault of the node is written on stdin by the parent process
+    #[arg(long, hide = true)]
+    pub vault_passphrase_stdin: bool,
+
     /// JSON config to setup a foreground node
     ///
     /// This argument is currently ignored on background nodes.  Node
@@ -83,6 +100,43 @@ pub struct CreateCommand {
 
     #[command(flatten)]
     pub trust_context_opts: TrustContextOpts,
+
+    /// Format of the logs of the node. With the `json` format, every event contains the
+    /// node name and, when available, the worker address, the peer identifier and the session.
+    /// The OCKAM_LOG_FORMAT environment variable is used by default
+    #[arg(long, value_name = "FORMAT", value_parser = ["default", "pretty", "json"])]
+    pub log_format: Option<String>,
+
+    /// URL of an OpenTelemetry collector receiving the traces of the node with the OTLP/HTTP
+    /// protocol, for example `http://localhost:4318`.
+    /// The OCKAM_OPENTELEMETRY_ENDPOINT environment variable is used by default
+    #[cfg(feature = "opentelemetry")]
+    #[arg(long, value_name = "URL")]
+    pub opentelemetry_endpoint: Option<String>,
+
+    /// Sink receiving the audit events of the node: secure channels established or rejected,
+    /// credentials verified or rejected, accesses denied by a policy, relays registered.
+    /// Can be `file:<path>`, `syslog`, `syslog:<host:port>` or `webhook:<url>`, and be repeated.
+    /// The OCKAM_AUDIT_SINKS environment variable is used by default
+    #[arg(long = "audit-sink", value_name = "SINK", value_parser = parse_audit_sink)]
+    pub audit_sinks: Vec<String>,
+
+    /// Maximum number of workers and processors running on the node. When it is reached,
+    /// new workers, for example the workers of new relays or portal connections, are rejected.
+    /// The OCKAM_MAX_WORKERS environment variable is used by default
+    #[arg(long, value_name = "COUNT")]
+    pub max_workers: Option<usize>,
+
+    /// Maximum number of messages queued in the mailbox of a worker. When a mailbox is full,
+    /// the senders wait until the worker processes its messages.
+    /// The OCKAM_MAILBOX_CAPACITY environment variable is used by default
+    #[arg(long, value_name = "COUNT", value_parser = clap::value_parser!(u64).range(1..))]
+    pub mailbox_capacity: Option<u64>,
+
+    /// Maximum number of secure channels of the node. When it is reached, new secure channels
+    /// are rejected. The OCKAM_MAX_SECURE_CHANNELS environment variable is used by default
+    #[arg(long, value_name = "COUNT")]
+    pub max_secure_channels: Option<usize>,
 }
 
 impl Default for CreateCommand {
@@ -92,8 +146,11 @@ impl Default for CreateCommand {
             node_name: random_name(),
             exit_on_eof: false,
             tcp_listener_address: node_manager_defaults.tcp_listener_address,
+            metrics_listener: None,
+            configuration: None,
             foreground: false,
             child_process: false,
+            vault_passphrase_stdin: false,
             launch_config: None,
             identity: None,
             authority_identity: None,
@@ -102,6 +159,13 @@ impl Default for CreateCommand {
             reload_from_trusted_identities_file: None,
             credential: None,
             trust_context_opts: node_manager_defaults.trust_context_opts,
+            log_format: None,
+            #[cfg(feature = "opentelemetry")]
+            opentelemetry_endpoint: None,
+            audit_sinks: vec![],
+            max_workers: None,
+            mailbox_capacity: None,
+            max_secure_channels: None,
         }
     }
 }
@@ -142,6 +206,10 @@ impl CreateCommand {
     }
 }
 
+fn parse_audit_sink(sink: &str) -> std::result::Result<String, String> {
+    AuditSinkConfig::from_str(sink).map(|_| sink.to_string())
+}
+
 pub fn parse_launch_config(config_or_path: &str) -> Result<Config> {
     match serde_json::from_str::<Config>(config_or_path) {
         Ok(c) => Ok(c),
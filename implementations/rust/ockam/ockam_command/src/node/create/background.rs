@@ -95,6 +95,8 @@ pub(crate) async fn spawn_background_node(
         &cmd.node_name,
         &cmd.identity,
         &cmd.tcp_listener_address,
+        cmd.metrics_listener.as_ref(),
+        cmd.configuration.as_ref(),
         cmd.trusted_identities.as_ref(),
         cmd.trusted_identities_file.as_ref(),
         cmd.reload_from_trusted_identities_file.as_ref(),
@@ -3,11 +3,14 @@ use std::sync::Arc;
 use colorful::Colorful;
 use miette::{miette, IntoDiagnostic};
 use minicbor::{Decoder, Encode};
+use tokio::sync::mpsc::Sender;
 use tokio::time::{sleep, Duration};
-use tracing::debug;
+use tracing::{debug, error, info};
 
 use ockam::{Address, AsyncTryClone, TcpListenerOptions};
 use ockam::{Context, TcpTransport};
+use ockam_api::cli_state::read_vault_passphrase;
+use ockam_api::nodes::models::configuration::ReloadConfiguration;
 use ockam_api::nodes::service::NodeManagerTrustOptions;
 use ockam_api::nodes::InMemoryNode;
 use ockam_api::{
@@ -47,6 +50,14 @@ pub(super) async fn foreground_mode(
         return Err(miette!("Node {} is already running", &node_name));
     };
 
+    if cmd.vault_passphrase_stdin {
+        let identity = opts
+            .state
+            .get_named_identity_or_default(&cmd.identity)
+            .await?;
+        read_vault_passphrase(&identity.vault_name(), std::io::stdin().lock())?;
+    }
+
     let tcp = TcpTransport::create(&ctx).await.into_diagnostic()?;
     let options = TcpListenerOptions::new();
     let listener = tcp
@@ -101,7 +112,8 @@ pub(super) async fn foreground_mode(
     )
     .await
     .into_diagnostic()?;
-    let node_manager_worker = NodeManagerWorker::new(Arc::new(node_man));
+    let node_man = Arc::new(node_man);
+    let node_manager_worker = NodeManagerWorker::new(node_man.clone());
 
     ctx.flow_controls()
         .add_consumer(NODEMANAGER_ADDR, listener.flow_control_id());
@@ -109,6 +121,42 @@ pub(super) async fn foreground_mode(
         .await
         .into_diagnostic()?;
 
+    // Recreate the relays which were created by this node before it was stopped
+    node_man.restore_relays(&ctx).await.into_diagnostic()?;
+
+    // Apply the declarative configuration of the node, which is reloaded on SIGHUP
+    let reload = match &cmd.configuration {
+        Some(path) => {
+            let path = std::fs::canonicalize(path).into_diagnostic()?;
+            let changes = node_man
+                .apply_configuration_file(&ctx, &path)
+                .await
+                .into_diagnostic()?;
+            debug!("applied the configuration {path:?} on the node {node_name}: {changes}");
+            Some(reload_on_signal(&ctx).await?)
+        }
+        None => None,
+    };
+
+    // Expose the node manager requests over a local, authenticated HTTP socket.
+    // The node can still be used without it, through the node manager worker
+    #[cfg(unix)]
+    match node_man.start_control_api(&ctx).await {
+        Ok(control_api) => debug!(
+            "node {node_name} control api available at {}",
+            control_api.socket.display()
+        ),
+        Err(e) => tracing::warn!("the control api of the node {node_name} can't be started: {e}"),
+    }
+
+    if let Some(metrics_listener) = &cmd.metrics_listener {
+        let address = node_man
+            .start_metrics_listener(&ctx, metrics_listener)
+            .await
+            .into_diagnostic()?;
+        debug!("node {node_name} metrics and probes available at http://{address}");
+    }
+
     if let Some(config) = &cmd.launch_config {
         if start_services(&ctx, config).await.is_err() {
             //TODO: Process should terminate on any error during its setup phase,
@@ -133,6 +181,7 @@ pub(super) async fn foreground_mode(
         opts.global_args.quiet,
         tx,
         &mut rx,
+        reload,
     )
     .await?;
 
@@ -146,6 +195,24 @@ pub(super) async fn foreground_mode(
     Ok(())
 }
 
+/// Return a channel used to reload the configuration of the node.
+/// The reload is requested from the node manager worker, so that it is not concurrent
+/// with a reload requested with `ockam node reload`
+async fn reload_on_signal(ctx: &Context) -> miette::Result<Sender<()>> {
+    let ctx = ctx.async_try_clone().await.into_diagnostic()?;
+    let (tx, mut rx) = tokio::sync::mpsc::channel(2);
+    tokio::spawn(async move {
+        while rx.recv().await.is_some() {
+            let req = Request::post("/node/configuration").body(ReloadConfiguration::new(None));
+            match send_req_to_node_manager(&ctx, req).await {
+                Ok(()) => info!("the configuration of the node was reloaded"),
+                Err(e) => error!("the configuration of the node could not be reloaded: {e:?}"),
+            }
+        }
+    });
+    Ok(tx)
+}
+
 pub fn load_pre_trusted_identities(cmd: &CreateCommand) -> Result<Option<PreTrustedIdentities>> {
     let command = cmd.clone();
     let pre_trusted_identities = match (
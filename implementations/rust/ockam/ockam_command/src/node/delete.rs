@@ -6,6 +6,7 @@ use ockam_node::Context;
 use crate::terminal::tui::DeleteCommandTui;
 use crate::terminal::PluralTerm;
 use crate::util::node_rpc;
+use crate::util::parallel::{run_concurrently, DEFAULT_CONCURRENCY};
 use crate::{docs, fmt_ok, CommandGlobalOpts, Terminal, TerminalStream};
 
 const LONG_ABOUT: &str = include_str!("./static/delete/long_about.txt");
@@ -116,4 +117,29 @@ impl DeleteCommandTui for DeleteTui {
             .write_line()?;
         Ok(())
     }
+
+    async fn delete_multiple(&self, items_names: Vec<String>) -> miette::Result<()> {
+        let force = self.cmd.force;
+        let results = run_concurrently(
+            &self.opts.terminal,
+            items_names,
+            DEFAULT_CONCURRENCY,
+            |node_name| {
+                let state = self.opts.state.clone();
+                async move { Ok(state.delete_node(&node_name, force).await?) }
+            },
+        )
+        .await;
+        let failed: Vec<_> = results
+            .failed
+            .iter()
+            .map(|(name, error)| serde_json::json!({ "name": name, "error": error }))
+            .collect();
+        self.terminal()
+            .stdout()
+            .plain(results.summary(Self::ITEM_NAME, "deleted").join("\n"))
+            .json(serde_json::json!({ "deleted": &results.succeeded, "failed": failed }))
+            .write_line()?;
+        results.into_result(Self::ITEM_NAME, "deleted")
+    }
 }
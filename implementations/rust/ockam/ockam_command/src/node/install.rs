@@ -0,0 +1,889 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use clap::{Args, ValueEnum};
+use colorful::Colorful;
+use miette::{miette, Context as _, IntoDiagnostic};
+
+use ockam_api::cli_state::{random_name, CliState};
+
+use crate::util::local_cmd;
+use crate::{docs, fmt_log, fmt_ok, CommandGlobalOpts};
+
+const LONG_ABOUT: &str = include_str!("./static/install/long_about.txt");
+const PREVIEW_TAG: &str = include_str!("../static/preview_tag.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/install/after_long_help.txt");
+
+/// Install a node as a system service
+#[derive(Clone, Debug, Args)]
+#[command(
+long_about = docs::about(LONG_ABOUT),
+before_help = docs::before_help(PREVIEW_TAG),
+after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct InstallCommand {
+    /// Name of the node.
+    #[arg(hide_default_value = true, default_value_t = random_name())]
+    node_name: String,
+
+    /// Register a system-wide service instead of a service for the current user.
+    /// This usually requires administrator privileges
+    #[arg(long)]
+    system: bool,
+
+    /// Name of the user running a system-wide service.
+    /// Defaults to the user who invoked `sudo`
+    #[arg(long, value_name = "USER_NAME", requires = "system")]
+    user_name: Option<String>,
+
+    /// TCP listener address
+    #[arg(long, short, id = "SOCKET_ADDRESS", default_value = "127.0.0.1:0")]
+    tcp_listener_address: String,
+
+    /// Name of the Identity that the node will use
+    #[arg(long = "identity", value_name = "IDENTITY_NAME")]
+    identity: Option<String>,
+
+    /// Environment variable to set for the node process, in the KEY=VALUE format.
+    /// Can be used several times
+    #[arg(long = "env", value_name = "KEY=VALUE", value_parser = parse_env_var)]
+    env: Vec<(String, String)>,
+
+    /// Restart policy of the service
+    #[arg(long, value_enum, default_value_t = RestartPolicy::OnFailure)]
+    restart: RestartPolicy,
+
+    /// Directory where the stdout and stderr logs of the service are written.
+    /// Defaults to the node directory of the user running the service
+    #[arg(long, value_name = "DIRECTORY")]
+    log_dir: Option<PathBuf>,
+
+    /// Only print the service definition, without registering it
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Run the node as a Windows service. Used by the Windows service control manager
+    #[arg(long, hide = true)]
+    windows_service_host: bool,
+}
+
+/// Restart policy of a node service
+#[derive(Clone, Copy, Debug, ValueEnum, PartialEq, Eq)]
+pub enum RestartPolicy {
+    /// Always restart the node when it stops
+    Always,
+    /// Only restart the node when it exits with an error
+    OnFailure,
+    /// Never restart the node
+    Never,
+}
+
+impl InstallCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        if self.windows_service_host {
+            local_cmd(run_windows_service_host(opts, self));
+        } else {
+            local_cmd(run_impl(opts, self));
+        }
+    }
+
+    /// Return the user running a system-wide service. When the command is run with `sudo`
+    /// $USER is `root`, so the original user is taken from $SUDO_USER instead.
+    /// A Windows service always runs as the LocalSystem account
+    fn service_user(&self, platform: Platform) -> miette::Result<Option<String>> {
+        if platform == Platform::WindowsService {
+            return match &self.user_name {
+                Some(_) => Err(miette!(
+                    "A Windows service runs as the LocalSystem account, the --user-name argument is not supported on Windows"
+                )),
+                None => Ok(None),
+            };
+        }
+        if !self.system {
+            return Ok(None);
+        }
+        if let Some(user_name) = &self.user_name {
+            return Ok(Some(user_name.clone()));
+        }
+        match std::env::var("SUDO_USER") {
+            Ok(user) if !user.is_empty() => Ok(Some(user)),
+            _ => Err(miette!(
+                "Cannot determine the user running the service, please use the --user-name argument"
+            )),
+        }
+    }
+
+    /// Return the state directory used by the service.
+    /// A system-wide service run by another user uses the state directory of that user,
+    /// unless $OCKAM_HOME is explicitly set
+    fn state_dir(&self, opts: &CommandGlobalOpts, user: Option<&str>) -> miette::Result<PathBuf> {
+        match user {
+            Some(user) if std::env::var_os("OCKAM_HOME").is_none() => {
+                Ok(user_home_dir(user)?.join(".ockam"))
+            }
+            _ => Ok(opts.state.dir()),
+        }
+    }
+
+    fn service_definition(
+        &self,
+        opts: &CommandGlobalOpts,
+        platform: Platform,
+    ) -> miette::Result<ServiceDefinition> {
+        let executable = std::env::current_exe()
+            .into_diagnostic()
+            .context("cannot find the path of the ockam executable")?;
+        let mut args = vec![
+            "node".to_string(),
+            "create".to_string(),
+            "--foreground".to_string(),
+            "--no-color".to_string(),
+            "--tcp-listener-address".to_string(),
+            self.tcp_listener_address.clone(),
+        ];
+        if let Some(identity) = &self.identity {
+            args.push("--identity".to_string());
+            args.push(identity.clone());
+        }
+        args.push(self.node_name.clone());
+
+        // The service must use the state directory of the user running it
+        let user = self.service_user(platform)?;
+        let state_dir = self.state_dir(opts, user.as_deref())?;
+        let mut environment = vec![("OCKAM_HOME".to_string(), state_dir.display().to_string())];
+        environment.extend(self.env.iter().cloned());
+
+        let log_dir = self
+            .log_dir
+            .clone()
+            .unwrap_or_else(|| CliState::make_node_dir_path(&state_dir, &self.node_name));
+
+        // The Windows service control manager starts this command, which runs the node
+        let mut host_args = vec![
+            "node".to_string(),
+            "install".to_string(),
+            self.node_name.clone(),
+            "--windows-service-host".to_string(),
+            "--tcp-listener-address".to_string(),
+            self.tcp_listener_address.clone(),
+        ];
+        if let Some(identity) = &self.identity {
+            host_args.push("--identity".to_string());
+            host_args.push(identity.clone());
+        }
+        if let Some(restart) = self.restart.to_possible_value() {
+            host_args.push("--restart".to_string());
+            host_args.push(restart.get_name().to_string());
+        }
+        host_args.push("--log-dir".to_string());
+        host_args.push(log_dir.display().to_string());
+
+        Ok(ServiceDefinition {
+            name: format!("ockam-node-{}", self.node_name),
+            node_name: self.node_name.clone(),
+            executable,
+            args,
+            host_args,
+            environment,
+            restart: self.restart,
+            stdout_log: log_dir.join("service-stdout.log"),
+            stderr_log: log_dir.join("service-stderr.log"),
+            log_dir,
+            // Windows services are always registered with the system service control manager
+            system: self.system || platform == Platform::WindowsService,
+            user,
+        })
+    }
+}
+
+fn run_impl(opts: CommandGlobalOpts, cmd: InstallCommand) -> miette::Result<()> {
+    let platform = Platform::current()?;
+    let service = cmd.service_definition(&opts, platform)?;
+    let contents = service.render(platform);
+
+    if cmd.dry_run {
+        opts.terminal
+            .stdout()
+            .plain(&contents)
+            .machine(&contents)
+            .json(serde_json::json!({ "service": service.name, "definition": contents }))
+            .write_line()?;
+        return Ok(());
+    }
+
+    create_log_dir(&service.log_dir, service.user.as_deref())?;
+
+    opts.terminal.write_line(&fmt_log!(
+        "Installing node {} as the {} service {}...",
+        cmd.node_name.clone().light_magenta(),
+        if service.system { "system" } else { "user" },
+        service.name.clone().light_magenta()
+    ))?;
+    let path = platform.install(&service, &contents)?;
+
+    let path_str = path.display().to_string();
+    opts.terminal
+        .stdout()
+        .plain(fmt_ok!(
+            "Node {} is now running as the service {}",
+            cmd.node_name.clone().light_magenta(),
+            service.name.clone().light_magenta()
+        ))
+        .machine(&service.name)
+        .json(serde_json::json!({ "service": service.name, "path": path_str }))
+        .write_line()?;
+    Ok(())
+}
+
+fn parse_env_var(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!(
+            "invalid environment variable '{s}', expected KEY=VALUE"
+        )),
+    }
+}
+
+/// Run the node for the Windows service control manager, see [`windows_service_host`]
+#[cfg(windows)]
+fn run_windows_service_host(opts: CommandGlobalOpts, cmd: InstallCommand) -> miette::Result<()> {
+    let service = cmd.service_definition(&opts, Platform::WindowsService)?;
+    windows_service_host::run(service)
+}
+
+#[cfg(not(windows))]
+fn run_windows_service_host(_opts: CommandGlobalOpts, _cmd: InstallCommand) -> miette::Result<()> {
+    Err(miette!(
+        "A node can only be run as a Windows service on Windows"
+    ))
+}
+
+/// Service managers supported by the install command
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Platform {
+    Systemd,
+    Launchd,
+    WindowsService,
+}
+
+impl Platform {
+    fn current() -> miette::Result<Self> {
+        match std::env::consts::OS {
+            "linux" => Ok(Platform::Systemd),
+            "macos" => Ok(Platform::Launchd),
+            "windows" => Ok(Platform::WindowsService),
+            os => Err(miette!(
+                "Installing a node as a service is not supported on {os}"
+            )),
+        }
+    }
+
+    /// Write the service definition and register it with the service manager.
+    /// Return the path of the service definition file
+    fn install(&self, service: &ServiceDefinition, contents: &str) -> miette::Result<PathBuf> {
+        match self {
+            Platform::Systemd => {
+                let dir = if service.system {
+                    PathBuf::from("/etc/systemd/system")
+                } else {
+                    home_dir()?.join(".config/systemd/user")
+                };
+                let path = dir.join(format!("{}.service", service.name));
+                write_file(&dir, &path, contents)?;
+                let user = if service.system { None } else { Some("--user") };
+                run_command("systemctl", user.into_iter().chain(["daemon-reload"]))?;
+                let unit = format!("{}.service", service.name);
+                run_command(
+                    "systemctl",
+                    user.into_iter().chain(["enable", "--now", unit.as_str()]),
+                )?;
+                Ok(path)
+            }
+            Platform::Launchd => {
+                let dir = if service.system {
+                    PathBuf::from("/Library/LaunchDaemons")
+                } else {
+                    home_dir()?.join("Library/LaunchAgents")
+                };
+                let path = dir.join(format!("{}.plist", service.launchd_label()));
+                write_file(&dir, &path, contents)?;
+                run_command("launchctl", ["load", "-w", &path.display().to_string()])?;
+                Ok(path)
+            }
+            Platform::WindowsService => {
+                for command in service.windows_commands() {
+                    run_command(&command[0], command[1..].iter().map(String::as_str))?;
+                }
+                Ok(PathBuf::from(service.windows_registry_key()))
+            }
+        }
+    }
+}
+
+/// All the parameters needed to generate a service running a foreground node
+#[derive(Clone, Debug)]
+struct ServiceDefinition {
+    name: String,
+    node_name: String,
+    executable: PathBuf,
+    args: Vec<String>,
+    /// Arguments of the command started by the Windows service control manager
+    host_args: Vec<String>,
+    environment: Vec<(String, String)>,
+    restart: RestartPolicy,
+    stdout_log: PathBuf,
+    stderr_log: PathBuf,
+    log_dir: PathBuf,
+    system: bool,
+    user: Option<String>,
+}
+
+impl ServiceDefinition {
+    fn render(&self, platform: Platform) -> String {
+        match platform {
+            Platform::Systemd => self.systemd_unit(),
+            Platform::Launchd => self.launchd_plist(),
+            Platform::WindowsService => self
+                .windows_commands()
+                .iter()
+                .map(|command| {
+                    command
+                        .iter()
+                        .map(|a| windows_quote(a))
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+
+    fn launchd_label(&self) -> String {
+        format!("io.ockam.node.{}", self.node_name)
+    }
+
+    fn command_line(&self) -> Vec<String> {
+        let mut command = vec![self.executable.display().to_string()];
+        command.extend(self.args.iter().cloned());
+        command
+    }
+
+    fn systemd_unit(&self) -> String {
+        let exec_start = self
+            .command_line()
+            .iter()
+            .map(|a| quote(a))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let restart = match self.restart {
+            RestartPolicy::Always => "always",
+            RestartPolicy::OnFailure => "on-failure",
+            RestartPolicy::Never => "no",
+        };
+        let mut unit = format!(
+            "[Unit]\nDescription=Ockam node {}\nAfter=network-online.target\nWants=network-online.target\n\n[Service]\nType=simple\n",
+            self.node_name
+        );
+        if let Some(user) = &self.user {
+            unit.push_str(&format!("User={user}\n"));
+        }
+        for (key, value) in &self.environment {
+            unit.push_str(&format!(
+                "Environment={}\n",
+                quote(&format!("{key}={value}"))
+            ));
+        }
+        // User units are only started by the user's service manager through default.target
+        let wanted_by = if self.system {
+            "multi-user.target"
+        } else {
+            "default.target"
+        };
+        unit.push_str(&format!(
+            "ExecStart={exec_start}\nRestart={restart}\nRestartSec=5\nStandardOutput=append:{}\nStandardError=append:{}\n\n[Install]\nWantedBy={wanted_by}\n",
+            self.stdout_log.display(),
+            self.stderr_log.display()
+        ));
+        unit
+    }
+
+    fn windows_registry_key(&self) -> String {
+        format!("HKLM\\SYSTEM\\CurrentControlSet\\Services\\{}", self.name)
+    }
+
+    /// Commands registering and starting a Windows service.
+    /// The service runs `ockam node install --windows-service-host`, which answers the service
+    /// control manager and runs `ockam node create --foreground` with its output appended
+    /// to the log files. The environment of the service is stored in its registry key
+    fn windows_commands(&self) -> Vec<Vec<String>> {
+        let bin_path = std::iter::once(self.executable.display().to_string())
+            .chain(self.host_args.iter().cloned())
+            .map(|a| windows_quote(&a))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mut commands = vec![
+            vec![
+                "sc.exe".to_string(),
+                "create".to_string(),
+                self.name.clone(),
+                "binPath=".to_string(),
+                bin_path,
+                "start=".to_string(),
+                "auto".to_string(),
+                "DisplayName=".to_string(),
+                format!("Ockam node {}", self.node_name),
+            ],
+            vec![
+                "sc.exe".to_string(),
+                "description".to_string(),
+                self.name.clone(),
+                format!("Runs the Ockam node {}", self.node_name),
+            ],
+        ];
+        if !self.environment.is_empty() {
+            let environment = self
+                .environment
+                .iter()
+                .map(|(key, value)| format!("{key}={value}"))
+                .collect::<Vec<_>>()
+                .join("\\0");
+            commands.push(vec![
+                "reg.exe".to_string(),
+                "add".to_string(),
+                self.windows_registry_key(),
+                "/v".to_string(),
+                "Environment".to_string(),
+                "/t".to_string(),
+                "REG_MULTI_SZ".to_string(),
+                "/d".to_string(),
+                environment,
+                "/f".to_string(),
+            ]);
+        }
+        // The failure actions are applied when the node exits with an error. The service host
+        // reports an error when the node exits successfully, if it must always be restarted
+        if self.restart != RestartPolicy::Never {
+            commands.push(vec![
+                "sc.exe".to_string(),
+                "failure".to_string(),
+                self.name.clone(),
+                "reset=".to_string(),
+                "86400".to_string(),
+                "actions=".to_string(),
+                "restart/5000/restart/5000/restart/5000".to_string(),
+            ]);
+            commands.push(vec![
+                "sc.exe".to_string(),
+                "failureflag".to_string(),
+                self.name.clone(),
+                "1".to_string(),
+            ]);
+        }
+        commands.push(vec![
+            "sc.exe".to_string(),
+            "start".to_string(),
+            self.name.clone(),
+        ]);
+        commands
+    }
+
+    fn launchd_plist(&self) -> String {
+        let arguments = self
+            .command_line()
+            .iter()
+            .map(|a| format!("        <string>{}</string>\n", xml_escape(a)))
+            .collect::<String>();
+        let environment = self
+            .environment
+            .iter()
+            .map(|(k, v)| {
+                format!(
+                    "        <key>{}</key>\n        <string>{}</string>\n",
+                    xml_escape(k),
+                    xml_escape(v)
+                )
+            })
+            .collect::<String>();
+        let keep_alive = match self.restart {
+            RestartPolicy::Always => "<true/>".to_string(),
+            RestartPolicy::OnFailure => {
+                "<dict>\n        <key>SuccessfulExit</key>\n        <false/>\n    </dict>"
+                    .to_string()
+            }
+            RestartPolicy::Never => "<false/>".to_string(),
+        };
+        let user = self
+            .user
+            .as_ref()
+            .map(|u| {
+                format!(
+                    "    <key>UserName</key>\n    <string>{}</string>\n",
+                    xml_escape(u)
+                )
+            })
+            .unwrap_or_default();
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+{arguments}    </array>
+    <key>EnvironmentVariables</key>
+    <dict>
+{environment}    </dict>
+{user}    <key>RunAtLoad</key>
+    <true/>
+    <key>KeepAlive</key>
+    {keep_alive}
+    <key>StandardOutPath</key>
+    <string>{}</string>
+    <key>StandardErrorPath</key>
+    <string>{}</string>
+</dict>
+</plist>
+"#,
+            xml_escape(&self.launchd_label()),
+            xml_escape(&self.stdout_log.display().to_string()),
+            xml_escape(&self.stderr_log.display().to_string()),
+        )
+    }
+}
+
+fn quote(s: &str) -> String {
+    if s.contains(char::is_whitespace) || s.contains('"') {
+        format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Quote an argument of a Windows command line
+fn windows_quote(s: &str) -> String {
+    if s.is_empty() || s.contains(char::is_whitespace) || s.contains('"') {
+        format!("\"{}\"", s.replace('"', "\\\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn home_dir() -> miette::Result<PathBuf> {
+    std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .ok_or_else(|| miette!("The $HOME environment variable is not set"))
+}
+
+/// Return the home directory of a user
+#[cfg(unix)]
+fn user_home_dir(user_name: &str) -> miette::Result<PathBuf> {
+    Ok(find_user(user_name)?.dir)
+}
+
+#[cfg(not(unix))]
+fn user_home_dir(user_name: &str) -> miette::Result<PathBuf> {
+    Err(miette!(
+        "Cannot find the home directory of the user {user_name}"
+    ))
+}
+
+#[cfg(unix)]
+fn find_user(user_name: &str) -> miette::Result<nix::unistd::User> {
+    nix::unistd::User::from_name(user_name)
+        .into_diagnostic()
+        .with_context(|| format!("cannot look up the user {user_name}"))?
+        .ok_or_else(|| miette!("The user {user_name} does not exist"))
+}
+
+/// Create the log directory of the service.
+/// The directories created for a service run by another user are owned by that user,
+/// so that the node can write its state next to its logs
+fn create_log_dir(dir: &Path, user_name: Option<&str>) -> miette::Result<()> {
+    let missing: Vec<PathBuf> = dir
+        .ancestors()
+        .take_while(|d| !d.exists())
+        .map(Path::to_path_buf)
+        .collect();
+    std::fs::create_dir_all(dir)
+        .into_diagnostic()
+        .with_context(|| format!("cannot create the directory {}", dir.display()))?;
+    #[cfg(unix)]
+    if let Some(user_name) = user_name {
+        let user = find_user(user_name)?;
+        for d in missing {
+            nix::unistd::chown(&d, Some(user.uid), Some(user.gid))
+                .into_diagnostic()
+                .with_context(|| format!("cannot change the owner of {}", d.display()))?;
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = (missing, user_name);
+    Ok(())
+}
+
+fn write_file(dir: &Path, path: &Path, contents: &str) -> miette::Result<()> {
+    std::fs::create_dir_all(dir)
+        .into_diagnostic()
+        .with_context(|| format!("cannot create the directory {}", dir.display()))?;
+    std::fs::write(path, contents)
+        .into_diagnostic()
+        .with_context(|| format!("cannot write the service definition {}", path.display()))
+}
+
+fn run_command<'a>(program: &str, args: impl IntoIterator<Item = &'a str>) -> miette::Result<()> {
+    let args: Vec<&str> = args.into_iter().collect();
+    let output = Command::new(program)
+        .args(&args)
+        .output()
+        .into_diagnostic()
+        .with_context(|| format!("cannot run {program}"))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(miette!(
+            "{program} {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Host of a node run as a Windows service.
+///
+/// The service control manager only starts processes which report their state to it, so the
+/// service runs this host, which starts `ockam node create --foreground` with its output
+/// appended to the log files, and stops the node when the service is stopped
+#[cfg(windows)]
+mod windows_service_host {
+    use super::{RestartPolicy, ServiceDefinition};
+    use miette::{Context as _, IntoDiagnostic};
+    use std::ffi::OsString;
+    use std::fs::{File, OpenOptions};
+    use std::io::Write;
+    use std::path::Path;
+    use std::process::{Child, Command, Stdio};
+    use std::sync::{mpsc, OnceLock};
+    use std::time::Duration;
+    use windows_service::service::{
+        ServiceControl, ServiceControlAccept, ServiceExitCode, ServiceState, ServiceStatus,
+        ServiceType,
+    };
+    use windows_service::service_control_handler::{self, ServiceControlHandlerResult};
+    use windows_service::{define_windows_service, service_dispatcher};
+
+    static SERVICE: OnceLock<ServiceDefinition> = OnceLock::new();
+
+    define_windows_service!(ffi_service_main, service_main);
+
+    pub(super) fn run(service: ServiceDefinition) -> miette::Result<()> {
+        let name = service.name.clone();
+        let _ = SERVICE.set(service);
+        service_dispatcher::start(name, ffi_service_main)
+            .into_diagnostic()
+            .context("cannot connect to the Windows service control manager")
+    }
+
+    fn service_main(_arguments: Vec<OsString>) {
+        if let Some(service) = SERVICE.get() {
+            if let Err(e) = run_node(service) {
+                if let Ok(mut log) = append_to(&service.stderr_log) {
+                    let _ = writeln!(log, "{e:?}");
+                }
+            }
+        }
+    }
+
+    /// Run the node until it exits or the service is stopped
+    fn run_node(service: &ServiceDefinition) -> miette::Result<()> {
+        let (stop_tx, stop_rx) = mpsc::channel();
+        let status_handle =
+            service_control_handler::register(&service.name, move |control| match control {
+                ServiceControl::Stop | ServiceControl::Shutdown => {
+                    let _ = stop_tx.send(());
+                    ServiceControlHandlerResult::NoError
+                }
+                ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+                _ => ServiceControlHandlerResult::NotImplemented,
+            })
+            .into_diagnostic()?;
+        let set_state = |current_state, exit_code| {
+            let controls_accepted = if current_state == ServiceState::Running {
+                ServiceControlAccept::STOP | ServiceControlAccept::SHUTDOWN
+            } else {
+                ServiceControlAccept::empty()
+            };
+            status_handle
+                .set_service_status(ServiceStatus {
+                    service_type: ServiceType::OWN_PROCESS,
+                    current_state,
+                    controls_accepted,
+                    exit_code,
+                    checkpoint: 0,
+                    wait_hint: Duration::default(),
+                    process_id: None,
+                })
+                .into_diagnostic()
+        };
+
+        let mut node = match start_node(service) {
+            Ok(node) => node,
+            Err(e) => {
+                set_state(ServiceState::Stopped, ServiceExitCode::ServiceSpecific(1))?;
+                return Err(e);
+            }
+        };
+        set_state(ServiceState::Running, ServiceExitCode::Win32(0))?;
+
+        let exit_code = loop {
+            if stop_rx.recv_timeout(Duration::from_secs(1)).is_ok() {
+                let _ = node.kill();
+                let _ = node.wait();
+                break ServiceExitCode::Win32(0);
+            }
+            if let Some(status) = node.try_wait().into_diagnostic()? {
+                // The failure actions of the service are only applied when it exits with an error
+                if status.success() && service.restart != RestartPolicy::Always {
+                    break ServiceExitCode::Win32(0);
+                }
+                let code = status.code().filter(|c| *c != 0).unwrap_or(1);
+                break ServiceExitCode::ServiceSpecific(code as u32);
+            }
+        };
+        set_state(ServiceState::Stopped, exit_code)
+    }
+
+    fn start_node(service: &ServiceDefinition) -> miette::Result<Child> {
+        Command::new(&service.executable)
+            .args(&service.args)
+            .envs(service.environment.iter().map(|(k, v)| (k, v)))
+            .stdin(Stdio::null())
+            .stdout(append_to(&service.stdout_log)?)
+            .stderr(append_to(&service.stderr_log)?)
+            .spawn()
+            .into_diagnostic()
+            .with_context(|| format!("cannot start the node {}", service.node_name))
+    }
+
+    fn append_to(path: &Path) -> miette::Result<File> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .into_diagnostic()
+            .with_context(|| format!("cannot open the log file {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(restart: RestartPolicy) -> ServiceDefinition {
+        ServiceDefinition {
+            name: "ockam-node-n1".to_string(),
+            node_name: "n1".to_string(),
+            executable: PathBuf::from("/usr/local/bin/ockam"),
+            args: vec![
+                "node".to_string(),
+                "create".to_string(),
+                "--foreground".to_string(),
+                "n1".to_string(),
+            ],
+            host_args: vec![
+                "node".to_string(),
+                "install".to_string(),
+                "n1".to_string(),
+                "--windows-service-host".to_string(),
+            ],
+            environment: vec![("OCKAM_HOME".to_string(), "/home/me/.ockam".to_string())],
+            restart,
+            stdout_log: PathBuf::from("/var/log/ockam/stdout.log"),
+            stderr_log: PathBuf::from("/var/log/ockam/stderr.log"),
+            log_dir: PathBuf::from("/var/log/ockam"),
+            system: true,
+            user: Some("me".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_systemd_unit() {
+        let unit = service(RestartPolicy::OnFailure).systemd_unit();
+        assert!(unit.contains("Description=Ockam node n1"));
+        assert!(unit.contains("User=me"));
+        assert!(unit.contains("Environment=OCKAM_HOME=/home/me/.ockam"));
+        assert!(unit.contains("ExecStart=/usr/local/bin/ockam node create --foreground n1"));
+        assert!(unit.contains("Restart=on-failure"));
+        assert!(unit.contains("StandardOutput=append:/var/log/ockam/stdout.log"));
+        assert!(unit.contains("StandardError=append:/var/log/ockam/stderr.log"));
+        assert!(unit.contains("WantedBy=multi-user.target"));
+
+        let unit = service(RestartPolicy::Never).systemd_unit();
+        assert!(unit.contains("Restart=no"));
+
+        let mut user_service = service(RestartPolicy::OnFailure);
+        user_service.system = false;
+        user_service.user = None;
+        let unit = user_service.systemd_unit();
+        assert!(unit.contains("WantedBy=default.target"));
+        assert!(!unit.contains("User="));
+    }
+
+    #[test]
+    fn test_launchd_plist() {
+        let plist = service(RestartPolicy::Always).launchd_plist();
+        assert!(plist.contains("<string>io.ockam.node.n1</string>"));
+        assert!(plist.contains("<string>/usr/local/bin/ockam</string>"));
+        assert!(plist.contains("<string>--foreground</string>"));
+        assert!(plist.contains("<key>OCKAM_HOME</key>"));
+        assert!(plist.contains("<key>KeepAlive</key>\n    <true/>"));
+        assert!(plist.contains("<string>/var/log/ockam/stderr.log</string>"));
+    }
+
+    #[test]
+    fn test_windows_commands() {
+        let mut service = service(RestartPolicy::OnFailure);
+        service.executable = PathBuf::from("C:\\Program Files\\Ockam\\ockam.exe");
+        service
+            .environment
+            .push(("OCKAM_LOG".to_string(), "debug".to_string()));
+        let commands = service.render(Platform::WindowsService);
+        assert!(commands.contains(
+            r#"sc.exe create ockam-node-n1 binPath= "\"C:\Program Files\Ockam\ockam.exe\" node install n1 --windows-service-host" start= auto"#
+        ));
+        assert!(commands.contains(
+            r#"reg.exe add HKLM\SYSTEM\CurrentControlSet\Services\ockam-node-n1 /v Environment /t REG_MULTI_SZ /d OCKAM_HOME=/home/me/.ockam\0OCKAM_LOG=debug /f"#
+        ));
+        assert!(commands.contains("sc.exe failure ockam-node-n1 reset= 86400"));
+        assert!(commands.contains("sc.exe failureflag ockam-node-n1 1"));
+        assert!(commands.ends_with("sc.exe start ockam-node-n1"));
+
+        service.restart = RestartPolicy::Never;
+        let commands = service.render(Platform::WindowsService);
+        assert!(!commands.contains("sc.exe failure"));
+    }
+
+    #[test]
+    fn test_parse_env_var() {
+        assert_eq!(
+            parse_env_var("KEY=a=b").unwrap(),
+            ("KEY".to_string(), "a=b".to_string())
+        );
+        assert!(parse_env_var("KEY").is_err());
+        assert!(parse_env_var("=value").is_err());
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("simple"), "simple");
+        assert_eq!(quote("with space"), "\"with space\"");
+    }
+}
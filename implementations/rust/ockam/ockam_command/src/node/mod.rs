@@ -5,8 +5,10 @@ pub use create::CreateCommand;
 pub use create::*;
 use default::DefaultCommand;
 use delete::DeleteCommand;
+use install::InstallCommand;
 use list::ListCommand;
 use logs::LogCommand;
+use reload::ReloadCommand;
 use show::ShowCommand;
 use start::StartCommand;
 use stop::StopCommand;
@@ -16,9 +18,11 @@ use crate::{docs, CommandGlobalOpts};
 mod create;
 mod default;
 mod delete;
+mod install;
 mod list;
 mod logs;
 mod models;
+mod reload;
 mod show;
 mod start;
 mod stop;
@@ -47,9 +51,13 @@ pub enum NodeSubcommand {
     #[command(display_order = 800)]
     Delete(DeleteCommand),
     #[command(display_order = 800)]
+    Install(InstallCommand),
+    #[command(display_order = 800)]
     List(ListCommand),
     #[command(display_order = 800)]
     Logs(LogCommand),
+    #[command(display_order = 800)]
+    Reload(ReloadCommand),
     Show(ShowCommand),
     #[command(display_order = 800)]
     Start(StartCommand),
@@ -64,11 +72,13 @@ impl NodeCommand {
         match self.subcommand {
             NodeSubcommand::Create(c) => c.run(options),
             NodeSubcommand::Delete(c) => c.run(options),
+            NodeSubcommand::Install(c) => c.run(options),
             NodeSubcommand::List(c) => c.run(options),
             NodeSubcommand::Show(c) => c.run(options),
             NodeSubcommand::Start(c) => c.run(options),
             NodeSubcommand::Stop(c) => c.run(options),
             NodeSubcommand::Logs(c) => c.run(options),
+            NodeSubcommand::Reload(c) => c.run(options),
             NodeSubcommand::Default(c) => c.run(options),
         }
     }
@@ -0,0 +1,77 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use miette::{miette, IntoDiagnostic};
+
+use ockam_api::nodes::models::configuration::{ConfigurationChanges, ReloadConfiguration};
+use ockam_api::nodes::BackgroundNodeClient;
+use ockam_core::api::Request;
+use ockam_node::Context;
+
+use crate::util::node_rpc;
+use crate::{docs, fmt_ok, fmt_warn, CommandGlobalOpts};
+
+const LONG_ABOUT: &str = include_str!("./static/reload/long_about.txt");
+const PREVIEW_TAG: &str = include_str!("../static/preview_tag.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/reload/after_long_help.txt");
+
+/// Reload the configuration of a running node
+#[derive(Clone, Debug, Args)]
+#[command(
+    long_about = docs::about(LONG_ABOUT),
+    before_help = docs::before_help(PREVIEW_TAG),
+    after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct ReloadCommand {
+    /// Name of the node.
+    node_name: Option<String>,
+
+    /// JSON file with the new configuration of the node.
+    /// The configuration file given when the node was created is read again by default
+    #[arg(long, value_name = "PATH")]
+    configuration: Option<PathBuf>,
+}
+
+impl ReloadCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(run_impl, (opts, self))
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, ReloadCommand),
+) -> miette::Result<()> {
+    let configuration = match &cmd.configuration {
+        Some(path) => Some(std::fs::read_to_string(path).into_diagnostic()?),
+        None => None,
+    };
+    let node = BackgroundNodeClient::create(&ctx, &opts.state, &cmd.node_name).await?;
+    let req = Request::post("/node/configuration").body(ReloadConfiguration::new(configuration));
+    let changes: ConfigurationChanges = node.ask(&ctx, req).await?;
+
+    let output = if changes.failed.is_empty() {
+        fmt_ok!(
+            "The configuration of the node {} was reloaded\n{changes}",
+            node.node_name()
+        )
+    } else {
+        fmt_warn!(
+            "The configuration of the node {} was partially reloaded\n{changes}",
+            node.node_name()
+        )
+    };
+    opts.terminal
+        .stdout()
+        .plain(output)
+        .machine(changes.to_string())
+        .write_line()?;
+
+    if changes.failed.is_empty() {
+        Ok(())
+    } else {
+        Err(miette!(
+            "Some changes of the configuration could not be applied"
+        ))
+    }
+}
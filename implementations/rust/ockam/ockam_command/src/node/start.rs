@@ -6,8 +6,10 @@ use ockam_node::Context;
 
 use crate::node::show::print_query_status;
 use crate::node::util::spawn_node;
+use crate::terminal::PluralTerm;
 use crate::util::node_rpc;
-use crate::{docs, fmt_err, fmt_info, fmt_log, fmt_ok, fmt_warn, CommandGlobalOpts, OckamColor};
+use crate::util::parallel::{run_concurrently, DEFAULT_CONCURRENCY};
+use crate::{docs, fmt_err, fmt_info, fmt_log, CommandGlobalOpts, OckamColor};
 
 const LONG_ABOUT: &str = include_str!("./static/start/long_about.txt");
 const PREVIEW_TAG: &str = include_str!("../static/preview_tag.txt");
@@ -22,7 +24,12 @@ after_long_help = docs::after_help(AFTER_LONG_HELP)
 )]
 pub struct StartCommand {
     /// Name of the node to be started
+    #[arg(group = "nodes")]
     node_name: Option<String>,
+
+    /// Start all the nodes which are not running
+    #[arg(long, short, group = "nodes")]
+    all: bool,
 }
 
 impl StartCommand {
@@ -35,6 +42,21 @@ async fn run_impl(
     ctx: Context,
     (opts, cmd): (CommandGlobalOpts, StartCommand),
 ) -> miette::Result<()> {
+    if cmd.all {
+        let inactive_nodes = get_inactive_nodes(&opts).await?;
+        if inactive_nodes.is_empty() {
+            opts.terminal
+                .stdout()
+                .plain(fmt_info!(
+                    "All the nodes are already started, nothing to do. Exiting gratefully"
+                ))
+                .write_line()?;
+        } else {
+            start_multiple_nodes(&opts, inactive_nodes).await?;
+        }
+        return Ok(());
+    }
+
     if cmd.node_name.is_some() || !opts.terminal.can_ask_for_user_input() {
         let node_name = opts.state.get_node_or_default(&cmd.node_name).await?.name();
         start_single_node(&node_name, opts, &ctx).await?;
@@ -55,9 +77,11 @@ async fn run_impl(
             start_single_node(&inactive_nodes[0], opts, &ctx).await?;
         }
         _ => {
-            let selected_nodes = opts
-                .terminal
-                .select_multiple("Select the nodes".to_string(), inactive_nodes);
+            let selected_nodes = opts.terminal.select_multiple(
+                "Select the nodes".to_string(),
+                inactive_nodes,
+                "node name",
+            )?;
             match selected_nodes.len() {
                 0 => {
                     opts.terminal
@@ -70,7 +94,7 @@ async fn run_impl(
                     if !opts.terminal.confirm_interactively(format!(
                         "You are about to start the given nodes:[ {} ]. Confirm?",
                         &selected_nodes.join(", ")
-                    )) {
+                    ))? {
                         opts.terminal
                             .stdout()
                             .plain(fmt_info!("No node selected, exiting gratefully!"))
@@ -78,13 +102,7 @@ async fn run_impl(
                         return Ok(());
                     }
 
-                    let formatted_starts_result =
-                        start_multiple_nodes(&ctx, &opts, &selected_nodes).await?;
-
-                    opts.terminal
-                        .stdout()
-                        .plain(formatted_starts_result.join("\n"))
-                        .write_line()?;
+                    start_multiple_nodes(&opts, selected_nodes).await?;
                 }
             }
         }
@@ -119,28 +137,31 @@ async fn start_single_node(
     Ok(())
 }
 
-/// Start multiples nodes and return a formatted result in the form as a list.
-/// Eventually append info on how to find error logs if there are.
+/// Start multiples nodes concurrently and display the result in the form of a list.
+/// Eventually append info on how to find error logs if there are, and return an error.
 async fn start_multiple_nodes(
-    ctx: &Context,
     opts: &CommandGlobalOpts,
-    node_selected: &[String],
-) -> miette::Result<Vec<String>> {
-    let mut node_error_flag: bool = false;
-    let mut node_starts_output: Vec<String> = vec![];
-    for node_name in node_selected {
-        match run_node(node_name, ctx, opts).await {
-            Ok(_) => node_starts_output.push(fmt_ok!("{node_name}")),
-            Err(_) => {
-                node_error_flag = true;
-                node_starts_output.push(fmt_warn!("{node_name}"))
-            }
-        }
-    }
-    if node_error_flag {
+    node_selected: Vec<String>,
+) -> miette::Result<()> {
+    let results = run_concurrently(
+        &opts.terminal,
+        node_selected,
+        DEFAULT_CONCURRENCY,
+        |node_name| {
+            let opts = opts.clone();
+            async move { restart_node_process(&node_name, &opts).await }
+        },
+    )
+    .await;
+    let mut node_starts_output = results.summary(PluralTerm::Node, "started");
+    if !results.is_success() {
         append_info_if_errors(&mut node_starts_output);
     };
-    Ok(node_starts_output)
+    opts.terminal
+        .stdout()
+        .plain(node_starts_output.join("\n"))
+        .write_line()?;
+    results.into_result(PluralTerm::Node, "started")
 }
 
 /// Run a single node. Return the BackgroundNode instance of the created node or error
@@ -149,6 +170,13 @@ async fn run_node(
     ctx: &Context,
     opts: &CommandGlobalOpts,
 ) -> miette::Result<BackgroundNodeClient> {
+    restart_node_process(node_name, opts).await?;
+    let node = BackgroundNodeClient::create_to_node(ctx, &opts.state, node_name).await?;
+    Ok(node)
+}
+
+/// Stop the process of a node, if it is still running, and spawn a new one
+async fn restart_node_process(node_name: &str, opts: &CommandGlobalOpts) -> miette::Result<()> {
     let node_info = opts.state.get_node(node_name).await?;
     opts.state.stop_node(node_name, false).await?;
     let node_address = node_info
@@ -162,6 +190,8 @@ async fn run_node(
         node_name,     // The selected node name
         &None,         // Use the default identity
         &node_address, // The selected node api address
+        None,          // No metrics listener
+        None,          // No configuration file
         None,          // No project information available
         None,          // No trusted identities
         None,          // "
@@ -172,9 +202,7 @@ async fn run_node(
         true,          // Restarted nodes will log to files
     )
     .await?;
-
-    let node = BackgroundNodeClient::create_to_node(ctx, &opts.state, node_name).await?;
-    Ok(node)
+    Ok(())
 }
 
 /// Get a list of the inactive_nodes
@@ -1,15 +1,26 @@
+use std::time::Duration;
+
+use crate::terminal::PluralTerm;
+use crate::util::duration::duration_parser;
 use crate::util::node_rpc;
+use crate::util::parallel::{run_concurrently, DEFAULT_CONCURRENCY};
 use crate::{color, docs, fmt_info, fmt_ok, fmt_warn, CommandGlobalOpts, OckamColor};
 
 use clap::Args;
 use colorful::Colorful;
 use miette::miette;
+use ockam_api::nodes::models::drain::{DrainNode, NodeDrained};
+use ockam_api::nodes::BackgroundNodeClient;
+use ockam_core::api::Request;
 use ockam_node::Context;
 
 const LONG_ABOUT: &str = include_str!("./static/stop/long_about.txt");
 const PREVIEW_TAG: &str = include_str!("../static/preview_tag.txt");
 const AFTER_LONG_HELP: &str = include_str!("./static/stop/after_long_help.txt");
 
+/// Time given to a node to answer a drain request, in addition to the drain timeout
+const DRAIN_REQUEST_MARGIN: Duration = Duration::from_secs(10);
+
 /// Stop a running node
 #[derive(Clone, Debug, Args)]
 #[command(
@@ -19,11 +30,25 @@ const AFTER_LONG_HELP: &str = include_str!("./static/stop/after_long_help.txt");
 )]
 pub struct StopCommand {
     /// Name of the node.
+    #[arg(group = "nodes")]
     node_name: Option<String>,
 
+    /// Stop all the running nodes
+    #[arg(long, short, group = "nodes")]
+    all: bool,
+
     /// Whether to use the SIGTERM or SIGKILL signal to stop the node
     #[arg(short, long)]
     force: bool,
+
+    /// Drain the node before stopping it: stop accepting new secure channels and portal
+    /// connections, withdraw its relays and wait for its open portal connections to be closed
+    #[arg(long)]
+    drain: bool,
+
+    /// Maximum time spent waiting for the open portal connections to be closed when draining
+    #[arg(long, value_name = "DURATION", default_value = "30s", value_parser = duration_parser, requires = "drain")]
+    drain_timeout: Duration,
 }
 
 impl StopCommand {
@@ -33,7 +58,7 @@ impl StopCommand {
 }
 
 async fn run_impl(
-    _ctx: Context,
+    ctx: Context,
     (opts, cmd): (CommandGlobalOpts, StopCommand),
 ) -> miette::Result<()> {
     let running_nodes = opts
@@ -52,6 +77,12 @@ async fn run_impl(
         return Ok(());
     }
 
+    let drain_timeout = cmd.drain.then_some(cmd.drain_timeout);
+    if cmd.all {
+        stop_multiple_nodes(&ctx, opts, running_nodes, cmd.force, drain_timeout).await?;
+        return Ok(());
+    }
+
     if cmd.node_name.is_some() || !opts.terminal.can_ask_for_user_input() {
         let node_name = opts.state.get_node_or_default(&cmd.node_name).await?.name();
         if !running_nodes.contains(&node_name) {
@@ -60,7 +91,7 @@ async fn run_impl(
                 node_name.light_magenta()
             ));
         }
-        stop_node(opts, &node_name, cmd.force).await?;
+        stop_node(&ctx, opts, &node_name, cmd.force, drain_timeout).await?;
         return Ok(());
     }
 
@@ -70,13 +101,14 @@ async fn run_impl(
         }
         1 => {
             let node_name = running_nodes[0].as_str();
-            stop_node(opts, node_name, cmd.force).await?;
+            stop_node(&ctx, opts, node_name, cmd.force, drain_timeout).await?;
         }
         _ => {
             let selected_item_names = opts.terminal.select_multiple(
                 "Select one or more nodes that you want to stop".to_string(),
                 running_nodes,
-            );
+                "node name",
+            )?;
             match selected_item_names.len() {
                 0 => {
                     opts.terminal
@@ -86,12 +118,11 @@ async fn run_impl(
                 }
                 1 => {
                     let node_name = selected_item_names[0].as_str();
-                    stop_node(opts, node_name, cmd.force).await?;
+                    stop_node(&ctx, opts, node_name, cmd.force, drain_timeout).await?;
                 }
                 _ => {
-                    for item_name in selected_item_names {
-                        stop_node(opts.clone(), &item_name, cmd.force).await?;
-                    }
+                    stop_multiple_nodes(&ctx, opts, selected_item_names, cmd.force, drain_timeout)
+                        .await?;
                 }
             }
         }
@@ -99,7 +130,16 @@ async fn run_impl(
     Ok(())
 }
 
-async fn stop_node(opts: CommandGlobalOpts, node_name: &str, force: bool) -> miette::Result<()> {
+async fn stop_node(
+    ctx: &Context,
+    opts: CommandGlobalOpts,
+    node_name: &str,
+    force: bool,
+    drain_timeout: Option<Duration>,
+) -> miette::Result<()> {
+    if let Some(timeout) = drain_timeout {
+        drain_node(ctx, &opts, node_name, timeout).await?;
+    }
     let res = opts.state.stop_node(node_name, force).await;
     let output = if res.is_ok() {
         fmt_ok!(
@@ -115,3 +155,63 @@ async fn stop_node(opts: CommandGlobalOpts, node_name: &str, force: bool) -> mie
     opts.terminal.stdout().plain(output).write_line()?;
     Ok(())
 }
+
+/// Stop several nodes concurrently and display a summary of the results
+async fn stop_multiple_nodes(
+    ctx: &Context,
+    opts: CommandGlobalOpts,
+    node_names: Vec<String>,
+    force: bool,
+    drain_timeout: Option<Duration>,
+) -> miette::Result<()> {
+    // The nodes are drained one after the other, so that they don't all stop accepting
+    // connections at the same time
+    if let Some(timeout) = drain_timeout {
+        for node_name in &node_names {
+            drain_node(ctx, &opts, node_name, timeout).await?;
+        }
+    }
+    let results = run_concurrently(
+        &opts.terminal,
+        node_names,
+        DEFAULT_CONCURRENCY,
+        |node_name| {
+            let state = opts.state.clone();
+            async move { Ok(state.stop_node(&node_name, force).await?) }
+        },
+    )
+    .await;
+    opts.terminal
+        .stdout()
+        .plain(results.summary(PluralTerm::Node, "stopped").join("\n"))
+        .write_line()?;
+    results.into_result(PluralTerm::Node, "stopped")
+}
+
+/// Drain the connections of a node before it is stopped
+async fn drain_node(
+    ctx: &Context,
+    opts: &CommandGlobalOpts,
+    node_name: &str,
+    timeout: Duration,
+) -> miette::Result<()> {
+    let node = BackgroundNodeClient::create_to_node(ctx, &opts.state, node_name).await?;
+    let req = Request::post("/node/drain").body(DrainNode::new(timeout));
+    // The node answers once its connections are closed, or when the drain timeout expires
+    let drained: NodeDrained = node
+        .ask_with_timeout(ctx, req, timeout + DRAIN_REQUEST_MARGIN)
+        .await?;
+    let output = if drained.is_complete() {
+        fmt_ok!(
+            "Node with name {} was drained\n{drained}",
+            color!(node_name, OckamColor::PrimaryResource)
+        )
+    } else {
+        fmt_warn!(
+            "Node with name {} was partially drained\n{drained}",
+            color!(node_name, OckamColor::PrimaryResource)
+        )
+    };
+    opts.terminal.stdout().plain(output).write_line()?;
+    Ok(())
+}
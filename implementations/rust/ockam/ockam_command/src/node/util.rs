@@ -1,4 +1,5 @@
 use std::env::current_exe;
+use std::io::Write;
 use std::path::PathBuf;
 use std::process::{Command, Stdio};
 
@@ -6,7 +7,7 @@ use miette::IntoDiagnostic;
 use miette::{miette, Context as _};
 use rand::random;
 
-use ockam_api::cli_state::NamedTrustContext;
+use ockam_api::cli_state::{NamedTrustContext, OCKAM_VAULT_PASSPHRASE};
 use ockam_api::nodes::BackgroundNodeClient;
 use ockam_core::env::get_env_with_default;
 use ockam_node::Context;
@@ -71,6 +72,8 @@ pub async fn spawn_node(
     name: &str,
     identity_name: &Option<String>,
     address: &str,
+    metrics_listener: Option<&String>,
+    configuration: Option<&PathBuf>,
     trusted_identities: Option<&String>,
     trusted_identities_file: Option<&PathBuf>,
     reload_from_trusted_identities_file: Option<&PathBuf>,
@@ -97,6 +100,16 @@ pub async fn spawn_node(
         args.push("--no-color".to_string());
     }
 
+    if let Some(metrics_listener) = metrics_listener {
+        args.push("--metrics-listener".to_string());
+        args.push(metrics_listener.to_string());
+    }
+
+    if let Some(configuration) = configuration {
+        args.push("--configuration".to_string());
+        args.push(configuration.to_string_lossy().to_string());
+    }
+
     if let Some(identity_name) = identity_name {
         args.push("--identity".to_string());
         args.push(identity_name.to_string());
@@ -141,13 +154,31 @@ pub async fn spawn_node(
         args.push(project_name.to_string());
     }
 
+    // The node process can't ask for the passphrase of an encrypted vault
+    // so the passphrase is resolved here and written on the stdin of the node process
+    let mut vault_passphrase = None;
+    if let Ok(identity) = opts
+        .state
+        .get_named_identity_or_default(identity_name)
+        .await
+    {
+        vault_passphrase = opts
+            .state
+            .get_named_vault_passphrase(&identity.vault_name())
+            .await?;
+    }
+    if vault_passphrase.is_some() {
+        args.push("--vault-passphrase-stdin".to_string());
+    }
+
     args.push(name.to_owned());
 
-    run_ockam(args).await
+    run_ockam(args, vault_passphrase).await
 }
 
-/// Run the ockam command line with specific arguments
-pub async fn run_ockam(args: Vec<String>) -> miette::Result<()> {
+/// Run the ockam command line with specific arguments.
+/// The input, if any, is written on the stdin of the process, which is then closed
+pub async fn run_ockam(args: Vec<String>, input: Option<String>) -> miette::Result<()> {
     // On systems with non-obvious path setups (or during
     // development) re-executing the current binary is a more
     // deterministic way of starting a node.
@@ -156,13 +187,23 @@ pub async fn run_ockam(args: Vec<String>) -> miette::Result<()> {
             .unwrap()
             .into()
     });
-    Command::new(ockam_exe)
+    let mut child = Command::new(ockam_exe)
         .args(args)
+        .env_remove(OCKAM_VAULT_PASSPHRASE)
         .stdout(Stdio::null())
         .stderr(Stdio::null())
-        .stdin(Stdio::null())
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
         .spawn()
         .into_diagnostic()
         .context("failed to spawn node")?;
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        writeln!(stdin, "{input}")
+            .into_diagnostic()
+            .context("failed to write to the node stdin")?;
+    }
     Ok(())
 }
@@ -16,10 +16,11 @@ use ockam::identity::{Credential, Identifier, Identity, TimestampInSeconds};
 use ockam_api::cli_state::vaults::NamedVault;
 use ockam_api::cloud::project::Project;
 use ockam_api::cloud::space::Space;
-use ockam_api::nodes::models::portal::{InletStatus, OutletStatus};
+use ockam_api::nodes::models::portal::{InletStatus, OutletStatus, PortalConnectionStatus};
 use ockam_api::nodes::models::secure_channel::{
     CreateSecureChannelResponse, ShowSecureChannelResponse,
 };
+use ockam_api::nodes::models::udp_portal::{UdpInletStatus, UdpOutletStatus};
 use ockam_api::route_to_multiaddr;
 use ockam_core::api::Reply;
 use ockam_core::{route, Route};
@@ -195,7 +196,7 @@ impl Output for ShowSecureChannelResponse {
     fn output(&self) -> Result<String> {
         let s = match &self.channel {
             Some(addr) => {
-                format!(
+                let s = format!(
                     "\n  Secure Channel:\n{} {}\n{} {}\n{} {}",
                     "  •         At: ".light_magenta(),
                     route_to_multiaddr(&route![addr.to_string()])
@@ -212,7 +213,34 @@ impl Output for ShowSecureChannelResponse {
                         .map(|id| id.clone().light_yellow().to_string())
                         .collect::<Vec<String>>()
                         .join("\n\t")
-                )
+                );
+                match &self.details {
+                    Some(details) => format!(
+                        "{s}\n{} {}\n{} {}\n{} {} messages, {} bytes\n{} {} messages, {} bytes\n{} {}",
+                        "  •       Peer: ".light_magenta(),
+                        details.their_identifier.clone().light_yellow(),
+                        "  •  Last used: ".light_magenta(),
+                        details
+                            .last_activity
+                            .map(|t| t.to_string())
+                            .unwrap_or("unknown".to_string())
+                            .light_yellow(),
+                        "  •       Sent: ".light_magenta(),
+                        details.messages_sent.unwrap_or_default(),
+                        details.bytes_sent.unwrap_or_default(),
+                        "  •   Received: ".light_magenta(),
+                        details.messages_received.unwrap_or_default(),
+                        details.bytes_received.unwrap_or_default(),
+                        "  • Attributes: ".light_magenta(),
+                        details
+                            .their_attributes
+                            .iter()
+                            .map(|(k, v)| format!("{k}={v}").light_yellow().to_string())
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    ),
+                    None => s,
+                }
             }
             None => format!("{}", "Channel not found".red()),
         };
@@ -256,6 +284,85 @@ From {} to {}"#,
     }
 }
 
+impl Output for UdpInletStatus {
+    fn output(&self) -> Result<String> {
+        let outlet = Route::parse(&self.outlet_route)
+            .and_then(|r| route_to_multiaddr(&r))
+            .map(|ma| ma.to_string())
+            .unwrap_or_else(|| self.outlet_route.to_string());
+
+        let output = format!(
+            r#"
+UDP Inlet {}
+    UDP Address: {}
+    Outlet Address: {}
+            "#,
+            self.alias
+                .to_string()
+                .color(OckamColor::PrimaryResource.color()),
+            self.bind_addr
+                .to_string()
+                .color(OckamColor::PrimaryResource.color()),
+            outlet.color(OckamColor::PrimaryResource.color())
+        );
+
+        Ok(output)
+    }
+
+    fn list_output(&self) -> Result<String> {
+        let output = format!(
+            r#"UDP Inlet {}
+From {} to {}"#,
+            self.alias
+                .to_string()
+                .color(OckamColor::PrimaryResource.color()),
+            self.bind_addr
+                .to_string()
+                .color(OckamColor::PrimaryResource.color()),
+            self.outlet_route
+                .to_string()
+                .color(OckamColor::PrimaryResource.color()),
+        );
+
+        Ok(output)
+    }
+}
+
+impl Output for UdpOutletStatus {
+    fn output(&self) -> Result<String> {
+        let output = format!(
+            r#"
+UDP Outlet {}:
+    UDP Address:    {}
+    Worker Address: {}
+"#,
+            self.alias,
+            self.socket_addr,
+            self.worker_address()?
+        );
+
+        Ok(output)
+    }
+
+    fn list_output(&self) -> Result<String> {
+        let output = format!(
+            r#"UDP Outlet {}
+From {} to {}"#,
+            self.alias
+                .to_string()
+                .color(OckamColor::PrimaryResource.color()),
+            self.worker_address()?
+                .to_string()
+                .color(OckamColor::PrimaryResource.color()),
+            self.socket_addr
+                .to_string()
+                .color(OckamColor::PrimaryResource.color()),
+        );
+
+        Ok(output)
+    }
+}
+
 impl Output for Vec<u8> {
     fn output(&self) -> Result<String> {
         Ok(hex::encode(self))
@@ -311,23 +418,56 @@ From {} to {}"#,
     }
 }
 
-impl Output for NamedVault {
+impl Output for PortalConnectionStatus {
     fn output(&self) -> Result<String> {
         let mut output = String::new();
-        writeln!(output, "Name: {}", self.name())?;
+        writeln!(output, "Connection {}", self.worker_addr)?;
+        writeln!(output, "    Peer Address: {}", self.peer_addr)?;
+        if let Some(identifier) = &self.identifier {
+            writeln!(output, "    Peer Identifier: {}", identifier)?;
+        }
         writeln!(
             output,
-            "Type: {}",
-            match self.is_kms() {
-                true => "AWS KMS",
-                false => "OCKAM",
-            }
+            "    Started At: {}",
+            human_readable_time(TimestampInSeconds(self.started_at))
         )?;
+        writeln!(output, "    Bytes Sent: {}", self.bytes_sent)?;
+        writeln!(output, "    Bytes Received: {}", self.bytes_received)?;
+        Ok(output)
+    }
+
+    fn list_output(&self) -> Result<String> {
+        let identifier = self
+            .identifier
+            .as_ref()
+            .map(|i| i.to_string())
+            .unwrap_or_else(|| "unknown identity".to_string());
+        let output = format!(
+            r#"Connection from {} ({})
+Started at {}, {} bytes sent, {} bytes received"#,
+            self.peer_addr
+                .to_string()
+                .color(OckamColor::PrimaryResource.color()),
+            identifier.color(OckamColor::PrimaryResource.color()),
+            human_readable_time(TimestampInSeconds(self.started_at)),
+            self.bytes_sent,
+            self.bytes_received,
+        );
+
+        Ok(output)
+    }
+}
+
+impl Output for NamedVault {
+    fn output(&self) -> Result<String> {
+        let mut output = String::new();
+        writeln!(output, "Name: {}", self.name())?;
+        writeln!(output, "Type: {}", self.vault_type())?;
         Ok(output)
     }
 }
 
-fn human_readable_time(time: TimestampInSeconds) -> String {
+pub(crate) fn human_readable_time(time: TimestampInSeconds) -> String {
     use time::format_description::well_known::iso8601::*;
     use time::Error::Format;
     use time::OffsetDateTime;
@@ -1,4 +1,5 @@
 use clap::Args;
+use colorful::Colorful;
 
 use ockam::Context;
 use ockam_abac::{Action, Expr, Policy, Resource};
@@ -6,10 +7,11 @@ use ockam_api::nodes::BackgroundNodeClient;
 use ockam_core::api::Request;
 
 use crate::node::util::initialize_default_node;
-use crate::policy::policy_path;
+use crate::policy::{policy_expression_parser, policy_path, POLICY_DISPLAY_WIDTH};
 use crate::util::node_rpc;
-use crate::CommandGlobalOpts;
+use crate::{fmt_log, fmt_ok, CommandGlobalOpts};
 
+/// Create or replace the policy of a resource for a given action
 #[derive(Clone, Debug, Args)]
 pub struct CreateCommand {
     #[arg(long, display_order = 900, id = "NODE_NAME")]
@@ -21,7 +23,7 @@ pub struct CreateCommand {
     #[arg(short, long, default_value = "handle_message")]
     action: Action,
 
-    #[arg(short, long)]
+    #[arg(short, long, value_parser = policy_expression_parser)]
     expression: Expr,
 }
 
@@ -42,8 +44,27 @@ async fn run_impl(
 ) -> miette::Result<()> {
     initialize_default_node(ctx, &opts).await?;
     let node = BackgroundNodeClient::create(ctx, &opts.state, &cmd.at).await?;
-    let bdy = Policy::new(cmd.expression);
-    let req = Request::post(policy_path(&cmd.resource, &cmd.action)).body(bdy);
+    opts.terminal.write_line(&fmt_log!(
+        "Setting the policy of resource {} for action {} to:\n{}",
+        cmd.resource,
+        cmd.action,
+        cmd.expression.pretty(POLICY_DISPLAY_WIDTH)
+    ))?;
+    let policy_path = policy_path(&cmd.resource, &cmd.action);
+    let bdy = Policy::new(cmd.expression.clone());
+    let req = Request::post(&policy_path).body(bdy);
     node.tell(ctx, req).await?;
+
+    opts.terminal
+        .stdout()
+        .plain(fmt_ok!("Policy with path '{}' has been set", &policy_path))
+        .machine(&policy_path)
+        .json(serde_json::json!({
+            "resource": &cmd.resource.to_string(),
+            "action": &cmd.action.to_string(),
+            "expression": &cmd.expression.to_string(),
+            "at": &node.node_name()}
+        ))
+        .write_line()?;
     Ok(())
 }
@@ -10,6 +10,7 @@ use crate::policy::policy_path;
 use crate::util::node_rpc;
 use crate::{fmt_ok, CommandGlobalOpts};
 
+/// Delete the policy of a resource for a given action
 #[derive(Clone, Debug, Args)]
 pub struct DeleteCommand {
     #[arg(long, display_order = 900, id = "NODE_NAME")]
@@ -0,0 +1,73 @@
+use clap::Args;
+use colorful::Colorful;
+use miette::miette;
+
+use ockam::identity::utils::now;
+use ockam_abac::expr::{int, str};
+use ockam_abac::{eval, Env, Expr, CURRENT_TIME};
+
+use crate::policy::{policy_expression_parser, POLICY_DISPLAY_WIDTH};
+use crate::util::local_cmd;
+use crate::{fmt_log, fmt_ok, CommandGlobalOpts};
+
+/// Evaluate a policy expression against some attributes, without sending it to a node
+#[derive(Clone, Debug, Args)]
+pub struct EvaluateCommand {
+    #[arg(short, long, value_parser = policy_expression_parser)]
+    expression: Expr,
+
+    /// Attribute used to evaluate the expression, in the NAME=VALUE format.
+    /// Attribute values are strings, as the attributes of an authenticated identity.
+    /// For example: --attribute subject.component=web --attribute target.host=10.0.0.1
+    #[arg(long = "attribute", value_name = "NAME=VALUE", value_parser = parse_attribute)]
+    attributes: Vec<(String, String)>,
+
+    /// Time of the evaluation, in seconds since the unix epoch. Defaults to the current time
+    #[arg(long, value_name = "SECONDS")]
+    time: Option<i64>,
+}
+
+impl EvaluateCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        local_cmd(run_impl(opts, self));
+    }
+}
+
+fn run_impl(opts: CommandGlobalOpts, cmd: EvaluateCommand) -> miette::Result<()> {
+    let mut env = Env::new();
+    for (name, value) in &cmd.attributes {
+        env.put(name.as_str(), str(value.as_str()));
+    }
+    if let Some(time) = cmd.time {
+        env.put(CURRENT_TIME, int(time));
+    } else if !env.contains(CURRENT_TIME) {
+        let time = now().map_err(|e| miette!("{e}"))?;
+        env.put(CURRENT_TIME, int(time.0 as i64));
+    }
+
+    opts.terminal.write_line(&fmt_log!(
+        "Evaluating the policy expression:\n{}",
+        cmd.expression.pretty(POLICY_DISPLAY_WIDTH)
+    ))?;
+    let result = eval(&cmd.expression, &env)
+        .map_err(|e| miette!("The policy expression could not be evaluated: {e}"))?;
+
+    opts.terminal
+        .stdout()
+        .plain(fmt_ok!("The policy expression evaluates to {result}"))
+        .machine(result.to_string())
+        .json(serde_json::json!({
+            "expression": cmd.expression.to_string(),
+            "result": result.to_string(),
+            "authorized": result.is_true(),
+        }))
+        .write_line()?;
+    Ok(())
+}
+
+fn parse_attribute(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((name, value)) if !name.is_empty() => Ok((name.to_string(), value.to_string())),
+        _ => Err(format!("invalid attribute '{s}', expected NAME=VALUE")),
+    }
+}
@@ -0,0 +1,108 @@
+use std::fmt::Write;
+
+use clap::Args;
+use colorful::Colorful;
+
+use ockam::Context;
+use ockam_abac::{Action, PolicyVersion, Resource};
+use ockam_api::nodes::models::policy::PolicyVersionList;
+use ockam_api::nodes::BackgroundNodeClient;
+use ockam_core::api::Request;
+
+use crate::output::{human_readable_time, Output};
+use crate::policy::{policy_path, POLICY_DISPLAY_WIDTH};
+use crate::terminal::OckamColor;
+use crate::util::node_rpc;
+use crate::{CommandGlobalOpts, Result};
+
+/// Show the history of the changes of the policy of a resource for a given action
+#[derive(Clone, Debug, Args)]
+pub struct HistoryCommand {
+    #[arg(long, display_order = 900, id = "NODE_NAME")]
+    at: Option<String>,
+
+    #[arg(short, long)]
+    resource: Resource,
+
+    #[arg(short, long, default_value = "handle_message")]
+    action: Action,
+}
+
+impl HistoryCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        node_rpc(rpc, (options, self));
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, HistoryCommand)) -> miette::Result<()> {
+    run_impl(&ctx, opts, cmd).await
+}
+
+async fn run_impl(
+    ctx: &Context,
+    opts: CommandGlobalOpts,
+    cmd: HistoryCommand,
+) -> miette::Result<()> {
+    let node = BackgroundNodeClient::create(ctx, &opts.state, &cmd.at).await?;
+    let req = Request::get(format!(
+        "{}/versions",
+        policy_path(&cmd.resource, &cmd.action)
+    ));
+    let history: PolicyVersionList = node.ask(ctx, req).await?;
+
+    let list = opts.terminal.build_list(
+        history.versions(),
+        &format!(
+            "History of the policy of {} for {} on Node {}",
+            cmd.resource,
+            cmd.action,
+            node.node_name()
+        ),
+        &format!(
+            "No history for the policy of {} for {} on Node {}",
+            cmd.resource,
+            cmd.action,
+            node.node_name()
+        ),
+    )?;
+    opts.terminal.stdout().plain(list).write_line()?;
+    Ok(())
+}
+
+impl Output for PolicyVersion {
+    fn output(&self) -> Result<String> {
+        let mut output = String::new();
+        writeln!(
+            output,
+            "Version: {}",
+            self.version()
+                .to_string()
+                .color(OckamColor::PrimaryResource.color())
+        )?;
+        writeln!(
+            output,
+            "Changed at: {}",
+            human_readable_time(self.changed_at())
+        )?;
+        if let Some(changed_by) = self.changed_by() {
+            writeln!(
+                output,
+                "Changed by: {}",
+                changed_by
+                    .to_string()
+                    .color(OckamColor::PrimaryResource.color())
+            )?;
+        }
+        match self.expression() {
+            Some(expression) => write!(
+                output,
+                "Expression: {}",
+                expression
+                    .pretty(POLICY_DISPLAY_WIDTH)
+                    .color(OckamColor::PrimaryResource.color())
+            )?,
+            None => write!(output, "Deleted")?,
+        }
+        Ok(output)
+    }
+}
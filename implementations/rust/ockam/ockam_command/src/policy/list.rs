@@ -12,10 +12,12 @@ use ockam_api::nodes::BackgroundNodeClient;
 use ockam_core::api::Request;
 
 use crate::output::Output;
+use crate::policy::POLICY_DISPLAY_WIDTH;
 use crate::terminal::OckamColor;
 use crate::util::node_rpc;
 use crate::{CommandGlobalOpts, Result};
 
+/// List the policies of a resource
 #[derive(Clone, Debug, Args)]
 pub struct ListCommand {
     #[arg(long, display_order = 900, id = "NODE_NAME")]
@@ -86,7 +88,7 @@ impl Output for Expression {
             output,
             "Expression: {}",
             self.expr()
-                .to_string()
+                .pretty(POLICY_DISPLAY_WIDTH)
                 .color(OckamColor::PrimaryResource.color())
         )?;
         Ok(output)
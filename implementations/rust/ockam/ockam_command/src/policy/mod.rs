@@ -2,20 +2,26 @@ use clap::{Args, Subcommand};
 
 use ockam::Context;
 use ockam_abac::expr::{eq, ident, str};
-use ockam_abac::{Action, Policy, Resource};
+use ockam_abac::{validate, Action, Expr, Policy, Resource};
 use ockam_api::nodes::models::policy::PolicyList;
 use ockam_api::nodes::BackgroundNodeClient;
 use ockam_core::api::Request;
 
 use crate::policy::create::CreateCommand;
 use crate::policy::delete::DeleteCommand;
+use crate::policy::evaluate::EvaluateCommand;
+use crate::policy::history::HistoryCommand;
 use crate::policy::list::ListCommand;
+use crate::policy::rollback::RollbackCommand;
 use crate::policy::show::ShowCommand;
 use crate::{CommandGlobalOpts, Result};
 
 mod create;
 mod delete;
+mod evaluate;
+mod history;
 mod list;
+mod rollback;
 mod show;
 
 #[derive(Clone, Debug, Args)]
@@ -26,11 +32,14 @@ pub struct PolicyCommand {
 
 #[derive(Clone, Debug, Subcommand)]
 pub enum PolicySubcommand {
-    #[command(display_order = 900)]
+    #[command(display_order = 900, visible_alias = "set")]
     Create(CreateCommand),
     Show(ShowCommand),
     Delete(DeleteCommand),
     List(ListCommand),
+    History(HistoryCommand),
+    Rollback(RollbackCommand),
+    Evaluate(EvaluateCommand),
 }
 
 impl PolicyCommand {
@@ -40,6 +49,9 @@ impl PolicyCommand {
             PolicySubcommand::Show(c) => c.run(opts),
             PolicySubcommand::Delete(c) => c.run(opts),
             PolicySubcommand::List(c) => c.run(opts),
+            PolicySubcommand::History(c) => c.run(opts),
+            PolicySubcommand::Rollback(c) => c.run(opts),
+            PolicySubcommand::Evaluate(c) => c.run(opts),
         }
     }
 }
@@ -48,6 +60,17 @@ pub(crate) fn policy_path(r: &Resource, a: &Action) -> String {
     format!("/policy/{r}/{a}")
 }
 
+/// Maximum width used to display a policy expression on a single line
+pub(crate) const POLICY_DISPLAY_WIDTH: usize = 80;
+
+/// Parse a policy expression and check that it can be evaluated, so that invalid
+/// expressions are rejected before being sent to a node
+pub(crate) fn policy_expression_parser(input: &str) -> std::result::Result<Expr, String> {
+    let expr = Expr::try_from(input).map_err(|e| format!("invalid policy expression: {e}"))?;
+    validate(&expr).map_err(|e| format!("invalid policy expression: {e}"))?;
+    Ok(expr)
+}
+
 pub(crate) async fn has_policy(
     node_name: &str,
     ctx: &Context,
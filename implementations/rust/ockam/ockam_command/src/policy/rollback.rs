@@ -0,0 +1,87 @@
+use clap::Args;
+use colorful::Colorful;
+
+use ockam::Context;
+use ockam_abac::{Action, PolicyVersion, Resource};
+use ockam_api::nodes::models::policy::RollbackPolicy;
+use ockam_api::nodes::BackgroundNodeClient;
+use ockam_core::api::Request;
+
+use crate::policy::{policy_path, POLICY_DISPLAY_WIDTH};
+use crate::util::node_rpc;
+use crate::{fmt_log, fmt_ok, CommandGlobalOpts};
+
+/// Restore a previous version of the policy of a resource for a given action.
+/// The versions of a policy are listed with `ockam policy history`
+#[derive(Clone, Debug, Args)]
+pub struct RollbackCommand {
+    #[arg(long, display_order = 900, id = "NODE_NAME")]
+    at: Option<String>,
+
+    #[arg(short, long)]
+    resource: Resource,
+
+    #[arg(short, long, default_value = "handle_message")]
+    action: Action,
+
+    /// Version of the policy to restore
+    #[arg(long)]
+    version: u64,
+}
+
+impl RollbackCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        node_rpc(rpc, (options, self));
+    }
+}
+
+async fn rpc(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, RollbackCommand),
+) -> miette::Result<()> {
+    run_impl(&ctx, opts, cmd).await
+}
+
+async fn run_impl(
+    ctx: &Context,
+    opts: CommandGlobalOpts,
+    cmd: RollbackCommand,
+) -> miette::Result<()> {
+    let node = BackgroundNodeClient::create(ctx, &opts.state, &cmd.at).await?;
+    let policy_path = policy_path(&cmd.resource, &cmd.action);
+    let req =
+        Request::post(format!("{policy_path}/rollback")).body(RollbackPolicy::new(cmd.version));
+    let version: PolicyVersion = node.ask(ctx, req).await?;
+
+    match version.expression() {
+        Some(expression) => opts.terminal.write_line(&fmt_log!(
+            "The policy of resource {} for action {} is now:\n{}",
+            cmd.resource,
+            cmd.action,
+            expression.pretty(POLICY_DISPLAY_WIDTH)
+        ))?,
+        None => opts.terminal.write_line(&fmt_log!(
+            "The policy of resource {} for action {} is now deleted",
+            cmd.resource,
+            cmd.action
+        ))?,
+    };
+    opts.terminal
+        .stdout()
+        .plain(fmt_ok!(
+            "Policy with path '{}' has been rolled back to version {}, as version {}",
+            policy_path,
+            cmd.version,
+            version.version()
+        ))
+        .machine(version.version().to_string())
+        .json(serde_json::json!({
+            "resource": cmd.resource.to_string(),
+            "action": cmd.action.to_string(),
+            "restored_version": cmd.version,
+            "version": version.version(),
+            "at": node.node_name(),
+        }))
+        .write_line()?;
+    Ok(())
+}
@@ -5,14 +5,15 @@ use ockam_abac::{Action, Policy, Resource};
 use ockam_api::nodes::BackgroundNodeClient;
 use ockam_core::api::Request;
 
-use crate::policy::policy_path;
+use crate::policy::{policy_path, POLICY_DISPLAY_WIDTH};
 use crate::util::node_rpc;
 use crate::CommandGlobalOpts;
 
+/// Show the policy of a resource for a given action
 #[derive(Clone, Debug, Args)]
 pub struct ShowCommand {
     #[arg(long, display_order = 900, id = "NODE_NAME")]
-    at: String,
+    at: Option<String>,
 
     #[arg(short, long)]
     resource: Resource,
@@ -32,9 +33,19 @@ async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, ShowCommand)) -> mie
 }
 
 async fn run_impl(ctx: &Context, opts: CommandGlobalOpts, cmd: ShowCommand) -> miette::Result<()> {
-    let node = BackgroundNodeClient::create_to_node(ctx, &opts.state, &cmd.at).await?;
+    let node = BackgroundNodeClient::create(ctx, &opts.state, &cmd.at).await?;
     let req = Request::get(policy_path(&cmd.resource, &cmd.action));
     let policy: Policy = node.ask(ctx, req).await?;
-    println!("{}", policy.expression());
+    opts.terminal
+        .stdout()
+        .plain(policy.expression().pretty(POLICY_DISPLAY_WIDTH))
+        .machine(policy.expression().to_string())
+        .json(serde_json::json!({
+            "resource": &cmd.resource.to_string(),
+            "action": &cmd.action.to_string(),
+            "expression": &policy.expression().to_string(),
+            "at": &node.node_name()}
+        ))
+        .write_line()?;
     Ok(())
 }
@@ -0,0 +1,101 @@
+use clap::Args;
+use miette::Context as _;
+use miette::IntoDiagnostic;
+
+use ockam::Context;
+use ockam_api::cloud::AuthorityNodeClient;
+use ockam_api::nodes::InMemoryNode;
+
+use crate::output::CredentialAndPurposeKeyDisplay;
+use crate::util::api::{CloudOpts, TrustContextOpts};
+use crate::util::node_rpc;
+use crate::{docs, CommandGlobalOpts};
+
+const LONG_ABOUT: &str = include_str!("./static/auth/long_about.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/auth/after_long_help.txt");
+
+/// Authenticate an enrolled identity with a project and return its credential
+#[derive(Clone, Debug, Args)]
+#[command(
+long_about = docs::about(LONG_ABOUT),
+after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct AuthCommand {
+    #[command(flatten)]
+    pub cloud_opts: CloudOpts,
+
+    #[command(flatten)]
+    pub trust_opts: TrustContextOpts,
+
+    /// Request a new credential from the project authority, even if a valid one is cached
+    #[arg(long, alias = "refresh-credential")]
+    pub force: bool,
+}
+
+impl AuthCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(run_impl, (opts, self));
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, AuthCommand),
+) -> miette::Result<()> {
+    let identity = opts
+        .state
+        .get_named_identity_or_default(&cmd.cloud_opts.identity)
+        .await?;
+    let project = opts
+        .state
+        .get_project_by_name_or_default(&cmd.trust_opts.project_name)
+        .await
+        .context("A default project or project parameter is required.")?;
+    let authority_identifier = project.authority_identifier().await.into_diagnostic()?;
+
+    // Only contact the authority if there is no valid cached credential
+    let cached_credential = if cmd.force {
+        None
+    } else {
+        opts.state
+            .get_cached_credential(&authority_identifier, &identity.identifier())
+            .await?
+    };
+
+    let credential = match cached_credential {
+        Some(credential) => credential,
+        None => {
+            let trust_context = opts.state.get_trust_context(&project.name).await.ok();
+            let node = InMemoryNode::start_with_trust_context(
+                &ctx,
+                &opts.state,
+                Some(project.name.clone()),
+                trust_context,
+            )
+            .await?;
+            let authority_node: AuthorityNodeClient = node
+                .create_authority_client(
+                    &authority_identifier,
+                    &project.authority_access_route().into_diagnostic()?,
+                    Some(identity.name()),
+                )
+                .await?;
+            let credential = authority_node.issue_credential(&ctx).await?;
+            opts.state
+                .cache_credential(
+                    &authority_identifier,
+                    &identity.identifier(),
+                    credential.clone(),
+                )
+                .await?;
+            credential
+        }
+    };
+
+    opts.terminal
+        .clone()
+        .stdout()
+        .plain(CredentialAndPurposeKeyDisplay(credential))
+        .write_line()?;
+    Ok(())
+}
@@ -11,14 +11,16 @@ use ockam_api::cloud::AuthorityNodeClient;
 use ockam_api::enroll::enrollment::Enrollment;
 use ockam_api::enroll::oidc_service::OidcService;
 use ockam_api::enroll::okta_oidc_provider::OktaOidcProvider;
+use ockam_api::enroll::saml_service::{SamlConfig, SamlService};
 use ockam_api::nodes::InMemoryNode;
 use ockam_api::NamedTrustContext;
+use url::Url;
 
 use crate::enroll::OidcServiceExt;
 use crate::output::CredentialAndPurposeKeyDisplay;
 use crate::util::api::{CloudOpts, TrustContextOpts};
 use crate::util::node_rpc;
-use crate::{docs, CommandGlobalOpts, Result};
+use crate::{docs, fmt_log, CommandGlobalOpts, Result};
 
 const LONG_ABOUT: &str = include_str!("./static/enroll/long_about.txt");
 const AFTER_LONG_HELP: &str = include_str!("./static/enroll/after_long_help.txt");
@@ -33,6 +35,27 @@ pub struct EnrollCommand {
     #[arg(long = "okta", group = "authentication_method")]
     pub okta: bool,
 
+    /// Authenticate with a SAML 2.0 Identity Provider, using the SAML addon of the project authority
+    #[arg(
+        long = "saml",
+        group = "authentication_method",
+        requires = "saml_sso_url"
+    )]
+    pub saml: bool,
+
+    /// URL of the single sign-on endpoint of the SAML Identity Provider
+    #[arg(long, value_name = "URL", requires = "saml")]
+    pub saml_sso_url: Option<Url>,
+
+    /// Entity id registered for Ockam in the SAML Identity Provider
+    #[arg(
+        long,
+        value_name = "ENTITY_ID",
+        default_value = "ockam",
+        requires = "saml"
+    )]
+    pub saml_entity_id: String,
+
     #[arg(group = "authentication_method", value_name = "ENROLLMENT TICKET PATH | ENROLLMENT TICKET", value_parser = parse_enroll_ticket)]
     pub enroll_ticket: Option<EnrollmentTicket>,
 
@@ -81,6 +104,7 @@ async fn run_impl(
         .await?;
     let project = parse_project(&opts, &cmd).await?;
     let trust_context = parse_trust_context(&opts, &cmd, &project).await?;
+    let authority_identifier = project.authority_identifier().await.into_diagnostic()?;
 
     // Create secure channel to the project's authority node
     let node = InMemoryNode::start_with_trust_context(
@@ -92,7 +116,7 @@ async fn run_impl(
     .await?;
     let authority_node: AuthorityNodeClient = node
         .create_authority_client(
-            &project.authority_identifier().await.into_diagnostic()?,
+            &authority_identifier,
             &project.authority_access_route().into_diagnostic()?,
             Some(identity.name()),
         )
@@ -113,11 +137,38 @@ async fn run_impl(
         let auth0 = OidcService::new(Arc::new(OktaOidcProvider::new(okta_config)));
         let token = auth0.get_token_interactively(&opts).await?;
         authority_node.enroll_with_oidc_token(&ctx, token).await?;
+    } else if cmd.saml {
+        if opts.global_args.non_interactive {
+            return Err(miette!(
+                "Enrolling with SAML requires an interaction with a browser, which is not possible in non-interactive mode"
+            ))?;
+        }
+        let sso_url = cmd
+            .saml_sso_url
+            .clone()
+            .ok_or(miette!("The SAML single sign-on URL must be provided"))?;
+        let saml = SamlService::new(SamlConfig::new(sso_url, cmd.saml_entity_id.clone()));
+        opts.terminal.write_line(&fmt_log!(
+            "Please authenticate with your SAML Identity Provider in the browser window"
+        ))?;
+        let assertion = saml.get_assertion().await.into_diagnostic()?;
+        authority_node
+            .enroll_with_saml_assertion(&ctx, assertion)
+            .await?;
     };
 
     // Issue credential
     let credential = authority_node.issue_credential(&ctx).await?;
 
+    // Cache the credential so that `ockam project auth` can reuse it until it expires
+    opts.state
+        .cache_credential(
+            &authority_identifier,
+            &identity.identifier(),
+            credential.clone(),
+        )
+        .await?;
+
     opts.terminal
         .clone()
         .stdout()
@@ -138,8 +189,8 @@ async fn parse_project(opts: &CommandGlobalOpts, cmd: &EnrollCommand) -> Result<
         opts.state.store_project(project.clone()).await?;
         project
     } else {
-        // OKTA AUTHENTICATION FLOW | PREVIOUSLY ENROLLED FLOW
-        // currently okta auth does not use an enrollment token
+        // OKTA/SAML AUTHENTICATION FLOW | PREVIOUSLY ENROLLED FLOW
+        // currently okta and saml auth do not use an enrollment token
         // however, it could be worked to use one in the future
         //
         // REQUIRES Project passed or default project
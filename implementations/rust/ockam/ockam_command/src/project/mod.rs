@@ -2,6 +2,7 @@ use crate::docs;
 use clap::{Args, Subcommand};
 
 pub use addon::AddonCommand;
+pub use auth::AuthCommand;
 pub use create::CreateCommand;
 pub use delete::DeleteCommand;
 pub use enroll::EnrollCommand;
@@ -16,6 +17,7 @@ pub use crate::credential::get::GetCommand;
 use crate::CommandGlobalOpts;
 
 mod addon;
+mod auth;
 mod create;
 mod delete;
 pub(crate) mod enroll;
@@ -53,6 +55,7 @@ pub enum ProjectSubcommand {
     Ticket(TicketCommand),
     Addon(AddonCommand),
     Enroll(Box<EnrollCommand>),
+    Auth(AuthCommand),
 }
 
 impl ProjectCommand {
@@ -68,6 +71,7 @@ impl ProjectCommand {
             ProjectSubcommand::Information(c) => c.run(options),
             ProjectSubcommand::Addon(c) => c.run(options),
             ProjectSubcommand::Enroll(c) => c.run(options),
+            ProjectSubcommand::Auth(c) => c.run(options),
         }
     }
 }
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 use std::time::Duration;
 
-use clap::Args;
+use clap::{Args, ValueEnum};
 use miette::{miette, IntoDiagnostic};
 
 use ockam::identity::Identifier;
@@ -16,6 +16,7 @@ use ockam_multiaddr::{proto, MultiAddr, Protocol};
 use crate::util::api::{CloudOpts, TrustContextOpts};
 use crate::util::duration::duration_parser;
 use crate::util::node_rpc;
+use crate::util::parsers::{env_var_name_parser, k8s_resource_name_parser};
 use crate::{docs, CommandGlobalOpts, Result};
 
 const LONG_ABOUT: &str = include_str!("./static/ticket/long_about.txt");
@@ -58,6 +59,65 @@ pub struct TicketCommand {
     /// The name of the relay that the identity using the ticket will be allowed to create
     #[arg(long = "relay", value_name = "RELAY_NAME")]
     allowed_relay_name: Option<String>,
+
+    /// Format used to display the ticket, for example to add it to a deployment configuration
+    #[arg(
+        long = "format",
+        value_name = "FORMAT",
+        value_enum,
+        default_value_t = TicketFormat::Hex,
+        conflicts_with = "member"
+    )]
+    format: TicketFormat,
+
+    /// Name of the environment variable holding the ticket, for the `dotenv`, `k8s-secret` and `docker` formats
+    #[arg(
+        long = "env-var",
+        value_name = "NAME",
+        default_value = "OCKAM_ENROLLMENT_TICKET",
+        value_parser = env_var_name_parser
+    )]
+    env_var: String,
+
+    /// Name of the Kubernetes Secret, for the `k8s-secret` format
+    #[arg(
+        long = "secret-name",
+        value_name = "NAME",
+        default_value = "ockam-enrollment-ticket",
+        value_parser = k8s_resource_name_parser
+    )]
+    secret_name: String,
+}
+
+/// Output format of an enrollment ticket
+#[derive(Clone, Copy, Debug, ValueEnum, PartialEq, Eq)]
+pub enum TicketFormat {
+    /// The hex-encoded ticket
+    Hex,
+    /// A `NAME=ticket` line which can be appended to a `.env` file
+    Dotenv,
+    /// A Kubernetes Secret manifest containing the ticket
+    K8sSecret,
+    /// A `--env NAME=ticket` argument for `docker run`
+    Docker,
+}
+
+impl TicketFormat {
+    /// Render a hex-encoded ticket in this format
+    pub fn render(&self, ticket: &str, env_var: &str, secret_name: &str) -> String {
+        match self {
+            TicketFormat::Hex => ticket.to_string(),
+            TicketFormat::Dotenv => format!("{env_var}={ticket}"),
+            TicketFormat::K8sSecret => format!(
+                "apiVersion: v1\n\
+                 kind: Secret\n\
+                 metadata:\n  name: {secret_name}\n\
+                 type: Opaque\n\
+                 stringData:\n  {env_var}: \"{ticket}\""
+            ),
+            TicketFormat::Docker => format!("--env {env_var}={ticket}"),
+        }
+    }
 }
 
 impl TicketCommand {
@@ -158,7 +218,10 @@ async fn run_impl(
         opts.terminal
             .clone()
             .stdout()
-            .machine(ticket_serialized)
+            .machine(
+                cmd.format
+                    .render(&ticket_serialized, &cmd.env_var, &cmd.secret_name),
+            )
             .write_line()?;
     }
 
@@ -192,3 +255,32 @@ async fn get_project(cli_state: &CliState, input: &MultiAddr) -> Result<Option<P
         Ok(None)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_ticket() {
+        let env_var = "OCKAM_ENROLLMENT_TICKET";
+        assert_eq!(TicketFormat::Hex.render("abcd", env_var, "secret"), "abcd");
+        assert_eq!(
+            TicketFormat::Dotenv.render("abcd", env_var, "secret"),
+            "OCKAM_ENROLLMENT_TICKET=abcd"
+        );
+        assert_eq!(
+            TicketFormat::Docker.render("abcd", env_var, "secret"),
+            "--env OCKAM_ENROLLMENT_TICKET=abcd"
+        );
+        assert_eq!(
+            TicketFormat::K8sSecret.render("abcd", env_var, "secret"),
+            r#"apiVersion: v1
+kind: Secret
+metadata:
+  name: secret
+type: Opaque
+stringData:
+  OCKAM_ENROLLMENT_TICKET: "abcd""#
+        );
+    }
+}
@@ -7,6 +7,7 @@ use miette::Context as _;
 use miette::{miette, IntoDiagnostic};
 use ockam::Context;
 pub use parser::ConfigRunner;
+pub(crate) use parser::{binary_path, InletConfig, OutletConfig, RelayConfig};
 use std::path::PathBuf;
 
 /// Create nodes given a declarative configuration file
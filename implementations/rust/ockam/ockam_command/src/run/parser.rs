@@ -129,7 +129,7 @@ impl ConfigRunner {
             }
 
             // Wait for CTRL+C or any other exit condition (like receiving a signal)
-            shutdown::wait(opts.terminal, true, true, tx, &mut rx).await?;
+            shutdown::wait(opts.terminal, true, true, tx, &mut rx, None).await?;
 
             // Send a SIGTERM to all nodes if they are still running
             for node_name in &spawned_nodes {
@@ -376,7 +376,7 @@ static BINARY_PATH: Lazy<String> = Lazy::new(|| {
         .expect("Failed to get the binary path")
 });
 
-fn binary_path() -> &'static str {
+pub(crate) fn binary_path() -> &'static str {
     &BINARY_PATH
 }
 
@@ -9,13 +9,17 @@ use tokio::sync::mpsc::{Receiver, Sender};
 use tracing::info;
 
 /// Waits for CTRL+C, EOF or a signal to exit, can provide extra shutdown events by
-/// sending a message through the channel
+/// sending a message through the channel.
+///
+/// When a `reload` channel is given, a SIGHUP signal doesn't stop the process:
+/// a message is sent through that channel instead
 pub async fn wait(
     terminal: Terminal<TerminalStream<Term>>,
     exit_on_eof: bool,
     quiet: bool,
     tx: Sender<()>,
     rx: &mut Receiver<()>,
+    reload: Option<Sender<()>>,
 ) -> miette::Result<bool> {
     // Register a handler for SIGINT, SIGTERM, SIGHUP
     {
@@ -38,6 +42,10 @@ pub async fn wait(
         .expect("Error setting Ctrl+C handler");
     }
 
+    if let Some(reload) = reload {
+        wait_for_reload(terminal.clone(), quiet, reload)?;
+    }
+
     if exit_on_eof {
         // Spawn a thread to monitor STDIN for EOF
         {
@@ -62,3 +70,44 @@ pub async fn wait(
     // Shutdown on SIGINT, SIGTERM, SIGHUP or EOF
     Ok(rx.recv().await.is_some())
 }
+
+/// Send a message through the `reload` channel every time a SIGHUP signal is received
+#[cfg(unix)]
+fn wait_for_reload(
+    terminal: Terminal<TerminalStream<Term>>,
+    quiet: bool,
+    reload: Sender<()>,
+) -> miette::Result<()> {
+    use miette::IntoDiagnostic;
+    use nix::sys::signal::{signal, SigHandler, Signal};
+    use tokio::signal::unix::{signal as unix_signal, SignalKind};
+
+    // The Ctrl+C handler also handles SIGHUP, so it is reset before registering the reload handler.
+    // This is safe since the default handler doesn't run any code in the process
+    unsafe { signal(Signal::SIGHUP, SigHandler::SigDfl) }.into_diagnostic()?;
+    let mut hangup = unix_signal(SignalKind::hangup()).into_diagnostic()?;
+    tokio::spawn(async move {
+        while hangup.recv().await.is_some() {
+            info!("SIGHUP signal received");
+            if !quiet {
+                let _ = terminal.write_line(
+                    format!("{} SIGHUP signal received, reloading", "!".light_yellow()).as_str(),
+                );
+            }
+            if reload.send(()).await.is_err() {
+                break;
+            }
+        }
+    });
+    Ok(())
+}
+
+/// SIGHUP signals are not available on this platform
+#[cfg(not(unix))]
+fn wait_for_reload(
+    _terminal: Terminal<TerminalStream<Term>>,
+    _quiet: bool,
+    _reload: Sender<()>,
+) -> miette::Result<()> {
+    Ok(())
+}
@@ -53,6 +53,22 @@ async fn run_impl(
         transport_status.processor_address
     );
     println!("  Flow Control Id: {}", transport_status.flow_control_id);
+    if let Some(metrics) = transport_status.metrics {
+        println!("  Metrics:");
+        println!("    Bytes sent: {}", metrics.bytes_sent);
+        println!("    Bytes received: {}", metrics.bytes_received);
+        println!("    Messages sent: {}", metrics.messages_sent);
+        println!("    Messages received: {}", metrics.messages_received);
+        println!("    Send failures: {}", metrics.send_failures);
+        println!("    Receive failures: {}", metrics.receive_failures);
+        println!(
+            "    Failed connect attempts: {}",
+            metrics.failed_connect_attempts
+        );
+        if let Some(latency) = metrics.connect_latency_ms {
+            println!("    Connect latency: {latency}ms");
+        }
+    }
 
     Ok(())
 }
@@ -26,6 +26,10 @@ pub struct ShowCommand {
     #[arg(display_order = 900, required = true, id = "ALIAS", value_parser = alias_parser)]
     alias: String,
 
+    /// Show the TCP connections currently going through the inlet
+    #[arg(long)]
+    connections: bool,
+
     /// Node on which the inlet was started
     #[command(flatten)]
     node_opts: NodeOpts,
@@ -53,14 +57,22 @@ pub async fn run_impl(
         alias,
         bind_addr,
         outlet_route,
+        connections,
         ..
     } = inlet_status;
-    let plain = formatdoc! {r#"
+    let mut plain = formatdoc! {r#"
         Inlet:
           Alias: {alias}
           TCP Address: {bind_addr}
           To Outlet Address: {outlet_route}
     "#};
+    if cmd.connections {
+        plain.push_str(&opts.terminal.build_list(
+            &connections.unwrap_or_default(),
+            &format!("Connections to TCP Inlet {alias}"),
+            &format!("No connections to TCP Inlet {alias}"),
+        )?);
+    }
     let machine = bind_addr;
     opts.terminal
         .stdout()
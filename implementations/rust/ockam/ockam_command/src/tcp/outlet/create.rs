@@ -1,4 +1,4 @@
-use std::net::SocketAddr;
+use std::net::IpAddr;
 
 use clap::Args;
 use colorful::Colorful;
@@ -18,7 +18,7 @@ use crate::policy::{add_default_project_policy, has_policy};
 use crate::tcp::util::alias_parser;
 use crate::terminal::OckamColor;
 use crate::util::node_rpc;
-use crate::util::parsers::socket_addr_parser;
+use crate::util::parsers::{host_port_parser, socket_addr_parser};
 use crate::{display_parse_logs, fmt_log};
 use crate::{docs, fmt_ok, CommandGlobalOpts};
 
@@ -37,12 +37,28 @@ pub struct CreateCommand {
     from: String,
 
     /// TCP address to send raw tcp traffic.
-    #[arg(long, display_order = 902, id = "SOCKET_ADDRESS", value_parser = socket_addr_parser)]
-    to: SocketAddr,
+    #[arg(long, display_order = 902, id = "SOCKET_ADDRESS", value_parser = host_port_parser)]
+    to: String,
 
     /// Assign a name to this outlet.
     #[arg(long, display_order = 900, id = "ALIAS", value_parser = alias_parser)]
     alias: Option<String>,
+
+    /// Inject the identifier of the peer in the HTTP requests sent to the target,
+    /// with the `x-ockam-identifier` header.
+    #[arg(long, display_order = 903)]
+    http_headers: bool,
+
+    /// Name of a credential attribute of the peer to inject in the HTTP requests,
+    /// with the `x-ockam-attribute-<name>` header. Implies --http-headers.
+    #[arg(long = "http-header-attribute", display_order = 904, id = "ATTRIBUTE")]
+    http_header_attributes: Vec<String>,
+
+    /// Only connect to the target if it matches one of these entries: a host name,
+    /// a host name wildcard like `*.internal`, an IP address or a CIDR range like `10.0.0.0/8`.
+    /// The target is resolved again and checked for each new connection.
+    #[arg(long = "allow", display_order = 905, id = "ALLOWED_TARGET")]
+    allowlist: Vec<String>,
 }
 
 impl CreateCommand {
@@ -68,6 +84,7 @@ pub async fn run_impl(
     ))?;
     display_parse_logs(&opts);
 
+    let to = socket_addr_parser(&cmd.to)?;
     let node_name = opts.state.get_node_or_default(&cmd.at).await?.name();
     let project = opts.state.get_node_project(&node_name).await.ok();
     let resource = Resource::new("tcp-outlet");
@@ -80,7 +97,29 @@ pub async fn run_impl(
     let is_finished: Mutex<bool> = Mutex::new(false);
 
     let send_req = async {
-        let payload = CreateOutlet::new(cmd.to, cmd.from.clone().into(), cmd.alias, true);
+        let payload = CreateOutlet::new(to, cmd.from.clone().into(), cmd.alias, true);
+        let payload = if cmd.http_headers || !cmd.http_header_attributes.is_empty() {
+            payload.with_http_headers(cmd.http_header_attributes)
+        } else {
+            payload
+        };
+        let payload = if cmd.allowlist.is_empty() {
+            payload
+        } else {
+            // Send the host name of the target, so that the node checks the
+            // addresses it resolves to for each connection
+            let host = cmd
+                .to
+                .rsplit_once(':')
+                .map(|(host, _)| host.trim_start_matches('[').trim_end_matches(']'))
+                .unwrap_or(&cmd.to);
+            let payload = payload.with_allowlist(cmd.allowlist);
+            if host.parse::<IpAddr>().is_err() {
+                payload.with_hostname(host.to_string())
+            } else {
+                payload
+            }
+        };
         let res = send_request(&ctx, &opts, payload, node_name.clone()).await;
         *is_finished.lock().await = true;
         res
@@ -51,7 +51,7 @@ impl From<&GlobalArgs> for Terminal<TerminalStream<Term>> {
         Terminal::new(
             global_args.quiet,
             global_args.no_color,
-            global_args.no_input,
+            global_args.no_input(),
             global_args.output_format.clone(),
         )
     }
@@ -249,6 +249,21 @@ impl<W: TerminalWriter> Terminal<W> {
         ))
     }
 
+    /// Prompt the user for a secret value, like a passphrase, without echoing it.
+    /// If a confirmation message is given, the user has to enter the value twice
+    pub fn password(&self, msg: impl AsRef<str>, confirmation: Option<&str>) -> Result<String> {
+        if !self.can_ask_for_user_input() {
+            return Err(miette!(
+                "A passphrase can only be entered in an interactive terminal"
+            ))?;
+        }
+        let mut prompt = dialoguer::Password::new().with_prompt(msg.as_ref());
+        if let Some(confirmation) = confirmation {
+            prompt = prompt.with_confirmation(confirmation, "The values don't match");
+        }
+        Ok(prompt.interact()?)
+    }
+
     pub fn confirmed_with_flag_or_prompt(
         &self,
         flag: bool,
@@ -266,7 +281,14 @@ impl<W: TerminalWriter> Terminal<W> {
         }
     }
 
-    pub fn confirm_interactively(&self, header: String) -> bool {
+    /// Ask the user to answer YES or NO to a question.
+    /// Return an error if the user can't be asked for any input (e.g. not a TTY, `--no-input` flag, etc.).
+    pub fn confirm_interactively(&self, header: String) -> Result<bool> {
+        if !self.can_ask_for_user_input() {
+            return Err(miette!(
+                "Cannot ask for a confirmation in non-interactive mode: {header}"
+            ))?;
+        }
         let user_input = select_from_list(
             header,
             ["YES", "NO"].iter().map(|it| it.to_string()).collect(),
@@ -276,17 +298,25 @@ impl<W: TerminalWriter> Terminal<W> {
             StyleSheet::default(),
         );
 
-        match &user_input {
+        Ok(match &user_input {
             Some(it) => it.contains(&"YES".to_string()),
             None => false,
-        }
+        })
     }
 
-    /// Returns the selected items by the user, or an empty `Vec` if the user did not select any item
-    /// or if the user is not able to select an item (e.g. not a TTY, `--no-input` flag, etc.).
-    pub fn select_multiple(&self, header: String, items: Vec<String>) -> Vec<String> {
+    /// Returns the selected items by the user, or an empty `Vec` if the user did not select any item.
+    /// Return an error if the user is not able to select an item (e.g. not a TTY, `--no-input` flag, etc.),
+    /// mentioning the argument which can be used to pass the items instead.
+    pub fn select_multiple(
+        &self,
+        header: String,
+        items: Vec<String>,
+        argument: &str,
+    ) -> Result<Vec<String>> {
         if !self.can_ask_for_user_input() {
-            return Vec::new();
+            return Err(miette!(
+                "Cannot select items in non-interactive mode, please pass the {argument} argument instead"
+            ))?;
         }
 
         let user_selected_list = select_from_list(
@@ -298,10 +328,7 @@ impl<W: TerminalWriter> Terminal<W> {
             StyleSheet::default(),
         );
 
-        match user_selected_list {
-            Some(it) => it,
-            None => Vec::new(),
-        }
+        Ok(user_selected_list.unwrap_or_default())
     }
 
     pub fn can_ask_for_user_input(&self) -> bool {
@@ -500,6 +527,21 @@ impl<W: TerminalWriter> Terminal<W> {
         Some(pb)
     }
 
+    /// Return a progress bar for `len` steps, if the terminal can display it
+    pub fn progress_bar(&self, len: u64) -> Option<ProgressBar> {
+        if self.quiet || !self.stderr.is_tty() {
+            return None;
+        }
+        let pb = ProgressBar::new(len);
+        pb.set_draw_target(ProgressDrawTarget::stderr());
+        pb.set_style(
+            ProgressStyle::with_template("     [{bar:30.yellow}] {pos}/{len} {msg}")
+                .expect("Failed to set progress bar template")
+                .progress_chars("=> "),
+        );
+        Some(pb)
+    }
+
     pub async fn progress_output(
         &self,
         output_messages: &Vec<String>,
@@ -556,7 +598,7 @@ pub enum PluralTerm {
 }
 
 impl PluralTerm {
-    fn singular(&self) -> &'static str {
+    pub(crate) fn singular(&self) -> &'static str {
         match self {
             PluralTerm::Vault => "vault",
             PluralTerm::Identity => "identity",
@@ -569,7 +611,7 @@ impl PluralTerm {
         }
     }
 
-    fn plural(&self) -> &'static str {
+    pub(crate) fn plural(&self) -> &'static str {
         match self {
             PluralTerm::Vault => "vaults",
             PluralTerm::Identity => "identities",
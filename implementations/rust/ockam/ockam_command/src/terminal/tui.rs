@@ -58,7 +58,8 @@ pub trait ShowCommandTui {
                         Self::ITEM_NAME.plural()
                     ),
                     items_names,
-                );
+                    &format!("{} name", Self::ITEM_NAME.singular()),
+                )?;
                 match selected_item_names.len() {
                     0 => {
                         terminal
@@ -177,7 +178,8 @@ pub trait DeleteCommandTui {
                         Self::ITEM_NAME.plural()
                     ),
                     items_names,
-                );
+                    &format!("{} name", Self::ITEM_NAME.singular()),
+                )?;
                 match selected_item_names.len() {
                     0 => {
                         terminal
@@ -0,0 +1,95 @@
+use std::net::SocketAddr;
+use std::str::FromStr;
+
+use clap::Args;
+use colorful::Colorful;
+use miette::IntoDiagnostic;
+
+use ockam::Context;
+use ockam_api::address::extract_address_value;
+use ockam_api::nodes::models::udp_portal::{CreateUdpInlet, UdpInletStatus};
+use ockam_api::nodes::BackgroundNodeClient;
+use ockam_core::api::Request;
+use ockam_multiaddr::MultiAddr;
+
+use crate::node::util::initialize_default_node;
+use crate::tcp::util::alias_parser;
+use crate::terminal::OckamColor;
+use crate::util::parsers::socket_addr_parser;
+use crate::util::{node_rpc, process_nodes_multiaddr};
+use crate::{fmt_log, fmt_ok, CommandGlobalOpts, Error};
+
+/// Create a UDP Inlet
+#[derive(Clone, Debug, Args)]
+pub struct CreateCommand {
+    /// Node on which to start the udp inlet.
+    #[arg(long, display_order = 900, id = "NODE_NAME", value_parser = extract_address_value)]
+    at: Option<String>,
+
+    /// Address on which to receive datagrams.
+    #[arg(long, display_order = 900, id = "SOCKET_ADDRESS", value_parser = socket_addr_parser)]
+    from: SocketAddr,
+
+    /// Route to a udp outlet, for example `/node/n1/service/udp_outlet`
+    #[arg(long, display_order = 900, id = "ROUTE")]
+    to: String,
+
+    /// Assign a name to this inlet.
+    #[arg(long, display_order = 900, id = "ALIAS", value_parser = alias_parser)]
+    alias: Option<String>,
+}
+
+impl CreateCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(run_impl, (opts, self));
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, CreateCommand),
+) -> miette::Result<()> {
+    initialize_default_node(&ctx, &opts).await?;
+    opts.terminal.write_line(&fmt_log!(
+        "Creating UDP Inlet at {}...\n",
+        cmd.from
+            .to_string()
+            .color(OckamColor::PrimaryResource.color())
+    ))?;
+
+    let to = MultiAddr::from_str(&cmd.to)
+        .into_diagnostic()
+        .map_err(|e| Error::arg_validation("to", &cmd.to, Some(&e.to_string())))?;
+    let to = process_nodes_multiaddr(&to, &opts.state).await?;
+
+    let node = BackgroundNodeClient::create(&ctx, &opts.state, &cmd.at).await?;
+    let req = Request::post("/node/udp/inlet").body(CreateUdpInlet::new(
+        cmd.from.to_string(),
+        to.clone(),
+        cmd.alias,
+    ));
+    let inlet_status: UdpInletStatus = node.ask(&ctx, req).await?;
+
+    opts.terminal
+        .stdout()
+        .plain(fmt_ok!(
+            "UDP Inlet {} on node {} is now sending the datagrams received at {} to {}",
+            inlet_status
+                .alias
+                .to_string()
+                .color(OckamColor::PrimaryResource.color()),
+            node.node_name()
+                .to_string()
+                .color(OckamColor::PrimaryResource.color()),
+            inlet_status
+                .bind_addr
+                .to_string()
+                .color(OckamColor::PrimaryResource.color()),
+            to.to_string().color(OckamColor::PrimaryResource.color()),
+        ))
+        .machine(inlet_status.bind_addr.to_string())
+        .json(serde_json::to_string_pretty(&inlet_status).into_diagnostic()?)
+        .write_line()?;
+
+    Ok(())
+}
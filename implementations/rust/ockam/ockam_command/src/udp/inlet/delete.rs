@@ -0,0 +1,55 @@
+use clap::Args;
+use colorful::Colorful;
+
+use ockam::Context;
+use ockam_api::nodes::models::udp_portal::UdpInletStatus;
+use ockam_api::nodes::BackgroundNodeClient;
+use ockam_core::api::Request;
+
+use crate::node::NodeOpts;
+use crate::tcp::util::alias_parser;
+use crate::util::node_rpc;
+use crate::{color, fmt_ok, CommandGlobalOpts, OckamColor};
+
+/// Delete a UDP Inlet
+#[derive(Clone, Debug, Args)]
+pub struct DeleteCommand {
+    /// Delete the inlet with this alias
+    #[arg(display_order = 900, id = "ALIAS", value_parser = alias_parser)]
+    alias: String,
+
+    /// Node on which to stop the udp inlet. If none are provided, the default node will be used
+    #[command(flatten)]
+    node_opts: NodeOpts,
+}
+
+impl DeleteCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(run_impl, (opts, self))
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, DeleteCommand),
+) -> miette::Result<()> {
+    let node = BackgroundNodeClient::create(&ctx, &opts.state, &cmd.node_opts.at_node).await?;
+    let _: UdpInletStatus = node
+        .ask(
+            &ctx,
+            Request::delete(format!("/node/udp/inlet/{}", cmd.alias)),
+        )
+        .await?;
+
+    opts.terminal
+        .stdout()
+        .plain(fmt_ok!(
+            "UDP Inlet with alias {} on Node {} has been deleted",
+            color!(cmd.alias, OckamColor::PrimaryResource),
+            color!(node.node_name(), OckamColor::PrimaryResource)
+        ))
+        .machine(&cmd.alias)
+        .json(serde_json::json!({ "alias": cmd.alias, "node": node.node_name() }))
+        .write_line()?;
+    Ok(())
+}
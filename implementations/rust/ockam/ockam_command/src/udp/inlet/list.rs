@@ -0,0 +1,44 @@
+use clap::Args;
+
+use ockam_api::nodes::models::udp_portal::UdpInletList;
+use ockam_api::nodes::BackgroundNodeClient;
+use ockam_core::api::Request;
+use ockam_node::Context;
+
+use crate::node::NodeOpts;
+use crate::util::node_rpc;
+use crate::CommandGlobalOpts;
+
+/// List UDP Inlets on the default node
+#[derive(Clone, Debug, Args)]
+pub struct ListCommand {
+    #[command(flatten)]
+    node_opts: NodeOpts,
+}
+
+impl ListCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(run_impl, (opts, self))
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, ListCommand),
+) -> miette::Result<()> {
+    let node = BackgroundNodeClient::create(&ctx, &opts.state, &cmd.node_opts.at_node).await?;
+    let inlets: UdpInletList = node.ask(&ctx, Request::get("/node/udp/inlet")).await?;
+
+    let list = opts.terminal.build_list(
+        &inlets.list,
+        &format!("UDP Inlets on Node {}", node.node_name()),
+        &format!("No UDP Inlets found on node {}.", node.node_name()),
+    )?;
+    opts.terminal
+        .stdout()
+        .plain(list)
+        .json(serde_json::json!(inlets.list))
+        .write_line()?;
+
+    Ok(())
+}
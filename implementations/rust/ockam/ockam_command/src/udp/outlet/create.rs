@@ -0,0 +1,96 @@
+use clap::Args;
+use colorful::Colorful;
+use miette::IntoDiagnostic;
+
+use ockam::Context;
+use ockam_abac::Resource;
+use ockam_api::address::extract_address_value;
+use ockam_api::nodes::models::udp_portal::{CreateUdpOutlet, UdpOutletStatus};
+use ockam_api::nodes::BackgroundNodeClient;
+use ockam_core::api::Request;
+
+use crate::node::util::initialize_default_node;
+use crate::policy::{add_default_project_policy, has_policy};
+use crate::tcp::util::alias_parser;
+use crate::terminal::OckamColor;
+use crate::util::node_rpc;
+use crate::util::parsers::socket_addr_parser;
+use crate::{fmt_log, fmt_ok, CommandGlobalOpts};
+
+/// Create a UDP Outlet
+#[derive(Clone, Debug, Args)]
+pub struct CreateCommand {
+    /// Node on which to start the udp outlet.
+    #[arg(long, display_order = 900, id = "NODE_NAME", value_parser = extract_address_value)]
+    at: Option<String>,
+
+    /// Address of the udp outlet.
+    #[arg(long, display_order = 901, id = "OUTLET_ADDRESS", default_value_t = default_from_addr(), value_parser = extract_address_value)]
+    from: String,
+
+    /// UDP address to send the datagrams to.
+    #[arg(long, display_order = 902, id = "SOCKET_ADDRESS")]
+    to: String,
+
+    /// Assign a name to this outlet.
+    #[arg(long, display_order = 900, id = "ALIAS", value_parser = alias_parser)]
+    alias: Option<String>,
+}
+
+impl CreateCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(run_impl, (opts, self))
+    }
+}
+
+fn default_from_addr() -> String {
+    "/service/udp_outlet".to_string()
+}
+
+async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, CreateCommand),
+) -> miette::Result<()> {
+    initialize_default_node(&ctx, &opts).await?;
+    opts.terminal.write_line(&fmt_log!(
+        "Creating UDP Outlet to {}...\n",
+        &cmd.to
+            .to_string()
+            .color(OckamColor::PrimaryResource.color())
+    ))?;
+
+    let to = socket_addr_parser(&cmd.to)?;
+    let node_name = opts.state.get_node_or_default(&cmd.at).await?.name();
+    let project = opts.state.get_node_project(&node_name).await.ok();
+    let resource = Resource::new("udp-outlet");
+    if let Some(p) = project {
+        if !has_policy(&node_name, &ctx, &opts, &resource).await? {
+            add_default_project_policy(&node_name, &ctx, &opts, p.id, &resource).await?;
+        }
+    }
+
+    let node = BackgroundNodeClient::create_to_node(&ctx, &opts.state, &node_name).await?;
+    let req = Request::post("/node/udp/outlet").body(CreateUdpOutlet::new(
+        to,
+        cmd.from.clone().into(),
+        cmd.alias,
+    ));
+    let outlet_status: UdpOutletStatus = node.ask(&ctx, req).await?;
+    let machine = outlet_status.worker_address().into_diagnostic()?;
+
+    opts.terminal
+        .stdout()
+        .plain(fmt_ok!(
+            "Created a new UDP Outlet on node {} from address {} to {}",
+            node_name
+                .to_string()
+                .color(OckamColor::PrimaryResource.color()),
+            cmd.from.color(OckamColor::PrimaryResource.color()),
+            to.to_string().color(OckamColor::PrimaryResource.color())
+        ))
+        .machine(machine)
+        .json(serde_json::to_string_pretty(&outlet_status).into_diagnostic()?)
+        .write_line()?;
+
+    Ok(())
+}
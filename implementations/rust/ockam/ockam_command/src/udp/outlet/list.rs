@@ -0,0 +1,44 @@
+use clap::Args;
+
+use ockam_api::nodes::models::udp_portal::UdpOutletList;
+use ockam_api::nodes::BackgroundNodeClient;
+use ockam_core::api::Request;
+use ockam_node::Context;
+
+use crate::node::NodeOpts;
+use crate::util::node_rpc;
+use crate::CommandGlobalOpts;
+
+/// List UDP Outlets on the default node
+#[derive(Clone, Debug, Args)]
+pub struct ListCommand {
+    #[command(flatten)]
+    node_opts: NodeOpts,
+}
+
+impl ListCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(run_impl, (opts, self))
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, ListCommand),
+) -> miette::Result<()> {
+    let node = BackgroundNodeClient::create(&ctx, &opts.state, &cmd.node_opts.at_node).await?;
+    let outlets: UdpOutletList = node.ask(&ctx, Request::get("/node/udp/outlet")).await?;
+
+    let list = opts.terminal.build_list(
+        &outlets.list,
+        &format!("UDP Outlets on Node {}", node.node_name()),
+        &format!("No UDP Outlets found on node {}.", node.node_name()),
+    )?;
+    opts.terminal
+        .stdout()
+        .plain(list)
+        .json(serde_json::json!(outlets.list))
+        .write_line()?;
+
+    Ok(())
+}
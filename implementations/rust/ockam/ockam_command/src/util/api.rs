@@ -1,4 +1,6 @@
 //! API shim to make it nicer to interact with the ockam messaging API
+use std::time::Duration;
+
 use clap::Args;
 use miette::miette;
 // TODO: maybe we can remove this cross-dependency inside the CLI?
@@ -38,7 +40,8 @@ pub(crate) fn list_tcp_listeners() -> Request<()> {
 pub(crate) fn create_tcp_connection(
     cmd: &crate::tcp::connection::CreateCommand,
 ) -> Request<models::transport::CreateTcpConnection> {
-    let payload = models::transport::CreateTcpConnection::new(cmd.address.clone());
+    let payload = models::transport::CreateTcpConnection::new(cmd.address.clone())
+        .with_proxy(cmd.proxy.clone());
 
     Request::post("/node/tcp/connection").body(payload)
 }
@@ -68,6 +71,11 @@ pub(crate) fn list_workers() -> Request<()> {
     Request::get("/node/workers")
 }
 
+/// Construct a request builder to show a worker on the given node
+pub(crate) fn show_worker(address: &str) -> Request<()> {
+    Request::get(format!("/node/workers/{address}"))
+}
+
 pub(crate) fn delete_secure_channel(
     addr: &Address,
 ) -> Request<models::secure_channel::DeleteSecureChannelRequest> {
@@ -152,11 +160,26 @@ pub(crate) fn start_authenticator_service(
     Request::post(node_service(DefaultAddress::DIRECT_AUTHENTICATOR)).body(payload)
 }
 
-pub(crate) fn add_consumer(id: FlowControlId, address: MultiAddr) -> Request<AddConsumer> {
-    let payload = AddConsumer::new(id, address);
+pub(crate) fn add_consumer(
+    id: FlowControlId,
+    address: MultiAddr,
+    max_messages: Option<u64>,
+    duration: Option<Duration>,
+) -> Request<AddConsumer> {
+    let payload = AddConsumer::new(id, address)
+        .with_max_messages(max_messages)
+        .with_duration(duration);
     Request::post("/node/flow_controls/add_consumer").body(payload)
 }
 
+pub(crate) fn list_flow_controls() -> Request<()> {
+    Request::get("/node/flow_controls")
+}
+
+pub(crate) fn get_address_flow_controls(address: &str) -> Request<()> {
+    Request::get(format!("/node/flow_controls/{address}"))
+}
+
 pub(crate) fn start_okta_service(
     cfg: &OktaIdentityProviderConfig,
 ) -> Request<StartOktaIdentityProviderRequest> {
@@ -5,11 +5,12 @@ use std::{
 
 use miette::Context as _;
 use miette::{miette, IntoDiagnostic};
-use tracing::error;
+use tracing::{error, Instrument};
 
 use ockam::{Address, Context, NodeBuilder};
 use ockam_api::cli_state::CliState;
 use ockam_api::config::lookup::{InternetAddress, LookupMeta};
+use ockam_api::logs::Logging;
 use ockam_core::DenyAll;
 use ockam_multiaddr::proto::{DnsAddr, Ip4, Ip6, Project, Space, Tcp};
 use ockam_multiaddr::{proto::Node, MultiAddr, Protocol};
@@ -20,6 +21,7 @@ use crate::Result;
 pub mod api;
 pub mod duration;
 pub mod exitcode;
+pub mod parallel;
 pub mod parsers;
 
 /// A simple wrapper for shutting down the local embedded node (for
@@ -53,6 +55,7 @@ where
             if let Err(e) = res {
                 error!(%e, "Failed to run command");
                 eprintln!("{:?}", e);
+                Logging::shutdown();
                 std::process::exit(exitcode::SOFTWARE);
             }
             Ok(())
@@ -73,25 +76,28 @@ where
     T: Send + 'static,
 {
     let (ctx, mut executor) = NodeBuilder::new().no_logging().build();
-    let res = executor.execute(async move {
-        let child_ctx = ctx
-            .new_detached(
-                Address::random_tagged("Detached.embedded_node"),
-                DenyAll,
-                DenyAll,
-            )
-            .await
-            .expect("Embedded node child ctx can't be created");
-        let r = f(child_ctx, a).await;
-        stop_node(ctx).await;
-        r.map_err(|e| {
-            ockam_core::Error::new(
-                ockam_core::errcode::Origin::Executor,
-                ockam_core::errcode::Kind::Unknown,
-                e,
-            )
-        })
-    });
+    let res = executor.execute(
+        async move {
+            let child_ctx = ctx
+                .new_detached(
+                    Address::random_tagged("Detached.embedded_node"),
+                    DenyAll,
+                    DenyAll,
+                )
+                .await
+                .expect("Embedded node child ctx can't be created");
+            let r = f(child_ctx, a).await;
+            stop_node(ctx).await;
+            r.map_err(|e| {
+                ockam_core::Error::new(
+                    ockam_core::errcode::Origin::Executor,
+                    ockam_core::errcode::Kind::Unknown,
+                    e,
+                )
+            })
+        }
+        .instrument(tracing::Span::current()),
+    );
     let res = res.map_err(|e| miette::miette!(e));
     res?.into_diagnostic()
 }
@@ -104,30 +110,33 @@ where
     T: Send + 'static,
 {
     let (ctx, mut executor) = NodeBuilder::new().no_logging().build();
-    let res = executor.execute(async move {
-        let child_ctx = ctx
-            .new_detached(
-                Address::random_tagged("Detached.embedded_node.not_stopped"),
-                DenyAll,
-                DenyAll,
-            )
-            .await
-            .expect("Embedded node child ctx can't be created");
-        let result = f(child_ctx, a).await;
-        let result = if result.is_err() {
-            ctx.stop().await?;
-            result
-        } else {
-            result
-        };
-        result.map_err(|e| {
-            ockam_core::Error::new(
-                ockam_core::errcode::Origin::Executor,
-                ockam_core::errcode::Kind::Unknown,
-                e,
-            )
-        })
-    });
+    let res = executor.execute(
+        async move {
+            let child_ctx = ctx
+                .new_detached(
+                    Address::random_tagged("Detached.embedded_node.not_stopped"),
+                    DenyAll,
+                    DenyAll,
+                )
+                .await
+                .expect("Embedded node child ctx can't be created");
+            let result = f(child_ctx, a).await;
+            let result = if result.is_err() {
+                ctx.stop().await?;
+                result
+            } else {
+                result
+            };
+            result.map_err(|e| {
+                ockam_core::Error::new(
+                    ockam_core::errcode::Origin::Executor,
+                    ockam_core::errcode::Kind::Unknown,
+                    e,
+                )
+            })
+        }
+        .instrument(tracing::Span::current()),
+    );
 
     let res = res.map_err(|e| miette::miette!(e));
     res?.into_diagnostic()
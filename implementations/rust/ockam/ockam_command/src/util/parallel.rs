@@ -0,0 +1,178 @@
+use std::future::Future;
+use std::sync::Arc;
+
+use colorful::Colorful;
+use console::Term;
+use miette::miette;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::terminal::PluralTerm;
+use crate::{color, fmt_log, fmt_ok, fmt_warn, OckamColor, Terminal, TerminalStream};
+
+/// Maximum number of operations executed at the same time
+pub const DEFAULT_CONCURRENCY: usize = 8;
+
+/// Result of an operation executed on several items concurrently
+#[derive(Debug, Default)]
+pub struct ParallelResults {
+    /// Items for which the operation succeeded
+    pub succeeded: Vec<String>,
+    /// Items for which the operation failed, with the corresponding error message
+    pub failed: Vec<(String, String)>,
+}
+
+impl ParallelResults {
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// Return an error if the operation failed for at least one item,
+    /// so that the command exits with a non-zero status
+    pub fn into_result(self, item: PluralTerm, past_action: &str) -> miette::Result<()> {
+        if self.is_success() {
+            return Ok(());
+        }
+        let names = self
+            .failed
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        Err(miette!(
+            "{} {} could not be {past_action}: {names}",
+            self.failed.len(),
+            if self.failed.len() == 1 {
+                item.singular()
+            } else {
+                item.plural()
+            }
+        ))
+    }
+
+    /// Return one line per item, followed by a summary line
+    pub fn summary(&self, item: PluralTerm, past_action: &str) -> Vec<String> {
+        let mut lines = vec![];
+        for name in &self.succeeded {
+            lines.push(fmt_ok!(
+                "{} {}",
+                item.singular(),
+                color!(name, OckamColor::PrimaryResource)
+            ));
+        }
+        for (name, error) in &self.failed {
+            lines.push(fmt_warn!(
+                "{} {}: {error}",
+                item.singular(),
+                color!(name, OckamColor::PrimaryResource)
+            ));
+        }
+        lines.push(fmt_log!(
+            "{} {} {past_action}, {} failed",
+            self.succeeded.len(),
+            if self.succeeded.len() == 1 {
+                item.singular()
+            } else {
+                item.plural()
+            },
+            self.failed.len()
+        ));
+        lines
+    }
+}
+
+/// Execute an operation on several items, with at most `concurrency` operations running at the
+/// same time. A progress bar is displayed while the operations are running.
+pub async fn run_concurrently<F, Fut>(
+    terminal: &Terminal<TerminalStream<Term>>,
+    items: Vec<String>,
+    concurrency: usize,
+    f: F,
+) -> ParallelResults
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = miette::Result<()>> + Send + 'static,
+{
+    let progress_bar = terminal.progress_bar(items.len() as u64);
+    let semaphore = Arc::new(Semaphore::new(concurrency.max(1)));
+    let mut tasks = JoinSet::new();
+    for (index, item) in items.iter().enumerate() {
+        let semaphore = semaphore.clone();
+        let operation = f(item.clone());
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            (index, operation.await)
+        });
+    }
+
+    let mut outcomes: Vec<Option<Result<(), String>>> = items.iter().map(|_| None).collect();
+    while let Some(joined) = tasks.join_next().await {
+        if let Ok((index, outcome)) = joined {
+            if let Some(pb) = progress_bar.as_ref() {
+                pb.set_message(items[index].clone());
+                pb.inc(1);
+            }
+            outcomes[index] = Some(outcome.map_err(|e| e.to_string()));
+        }
+    }
+    if let Some(pb) = progress_bar {
+        pb.finish_and_clear();
+    }
+
+    let mut results = ParallelResults::default();
+    for (item, outcome) in items.into_iter().zip(outcomes) {
+        match outcome {
+            Some(Ok(())) => results.succeeded.push(item),
+            Some(Err(e)) => results.failed.push((item, e)),
+            None => results
+                .failed
+                .push((item, "the operation was interrupted".to_string())),
+        }
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_run_concurrently_keeps_the_items_order() {
+        let terminal = Terminal::quiet();
+        let items = (0..20).map(|i| format!("n{i}")).collect::<Vec<_>>();
+        let results = run_concurrently(&terminal, items, 4, |item| async move {
+            let index: u64 = item[1..].parse().unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(20 - index)).await;
+            if index % 5 == 0 {
+                Err(miette!("failed"))
+            } else {
+                Ok(())
+            }
+        })
+        .await;
+
+        assert!(!results.is_success());
+        assert_eq!(results.succeeded.len(), 16);
+        assert_eq!(results.succeeded[0], "n1");
+        assert_eq!(
+            results
+                .failed
+                .iter()
+                .map(|(n, _)| n.as_str())
+                .collect::<Vec<_>>(),
+            vec!["n0", "n5", "n10", "n15"]
+        );
+        assert_eq!(results.summary(PluralTerm::Node, "stopped").len(), 21);
+
+        let error = results
+            .into_result(PluralTerm::Node, "stopped")
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "4 nodes could not be stopped: n0, n5, n10, n15"
+        );
+        assert!(ParallelResults::default()
+            .into_result(PluralTerm::Node, "stopped")
+            .is_ok());
+    }
+}
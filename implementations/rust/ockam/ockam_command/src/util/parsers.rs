@@ -26,6 +26,18 @@ pub(crate) fn socket_addr_parser(input: &str) -> Result<SocketAddr> {
         .map_err(|e| miette!("cannot parse the address {address} as a socket address: {e}"))?)
 }
 
+/// Helper function for parsing a `host:port` target from user input, keeping its host name
+/// so that it can be resolved again later. As with [`socket_addr_parser`], it is possible
+/// to just input a `port`
+pub(crate) fn host_port_parser(input: &str) -> Result<String> {
+    let address = match input.split(':').count() {
+        1 => format!("127.0.0.1:{input}"),
+        _ => input.to_string(),
+    };
+    socket_addr_parser(&address)?;
+    Ok(address)
+}
+
 /// Helper fn for parsing an identifier from user input by using
 /// [`ockam_identity::Identifier::from_str()`]
 pub(crate) fn identity_identifier_parser(input: &str) -> Result<Identifier> {
@@ -49,6 +61,44 @@ pub(crate) fn validate_project_name(s: &str) -> Result<String> {
     }
 }
 
+/// Helper fn for parsing the name of an environment variable: letters, digits and '_',
+/// not starting with a digit
+pub(crate) fn env_var_name_parser(input: &str) -> Result<String> {
+    let mut chars = input.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if valid {
+        Ok(input.to_string())
+    } else {
+        Err(miette!(
+            "Invalid environment variable name: {input}. It can only contain letters, digits \
+            and '_', and can't start with a digit"
+        ))?
+    }
+}
+
+/// Helper fn for parsing the name of a Kubernetes resource, which must be a DNS-1123 subdomain:
+/// at most 253 lowercase alphanumeric characters, '-' or '.', where each '.'-separated part
+/// starts and ends with an alphanumeric character
+pub(crate) fn k8s_resource_name_parser(input: &str) -> Result<String> {
+    let is_valid_label = |label: &str| {
+        let is_alphanumeric = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit();
+        label.starts_with(is_alphanumeric)
+            && label.ends_with(is_alphanumeric)
+            && label.chars().all(|c| is_alphanumeric(c) || c == '-')
+    };
+    if input.len() <= 253 && input.split('.').all(is_valid_label) {
+        Ok(input.to_string())
+    } else {
+        Err(miette!(
+            "Invalid Kubernetes resource name: {input}. It can contain at most 253 lowercase \
+            alphanumeric characters, '-' or '.', and must start and end with an alphanumeric character"
+        ))?
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::net::Ipv6Addr;
@@ -102,6 +152,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_host_port() {
+        assert_eq!(host_port_parser("9000").unwrap(), "127.0.0.1:9000");
+        assert_eq!(
+            host_port_parser("localhost:9999").unwrap(),
+            "localhost:9999"
+        );
+        assert!(host_port_parser("invalid").is_err());
+    }
+
+    #[test]
+    fn test_env_var_name() {
+        assert!(env_var_name_parser("OCKAM_ENROLLMENT_TICKET").is_ok());
+        assert!(env_var_name_parser("_ticket2").is_ok());
+        assert!(env_var_name_parser("").is_err());
+        assert!(env_var_name_parser("2TICKET").is_err());
+        assert!(env_var_name_parser("TICKET; rm -rf /").is_err());
+        assert!(env_var_name_parser("MY-TICKET").is_err());
+    }
+
+    #[test]
+    fn test_k8s_resource_name() {
+        assert!(k8s_resource_name_parser("ockam-enrollment-ticket").is_ok());
+        assert!(k8s_resource_name_parser("ticket.ockam.io").is_ok());
+        assert!(k8s_resource_name_parser("").is_err());
+        assert!(k8s_resource_name_parser("Ticket").is_err());
+        assert!(k8s_resource_name_parser("-ticket").is_err());
+        assert!(k8s_resource_name_parser("ticket.").is_err());
+        assert!(k8s_resource_name_parser("ticket..ockam").is_err());
+        assert!(k8s_resource_name_parser("ticket\n  namespace: other").is_err());
+        assert!(k8s_resource_name_parser(&"a".repeat(254)).is_err());
+    }
+
     #[test]
     fn test_invalid_inputs() {
         // Test case 3: Any other format will throw an error
@@ -0,0 +1,62 @@
+use clap::Args;
+use colorful::Colorful;
+
+use ockam::Context;
+
+use crate::util::node_rpc;
+use crate::{color, docs, fmt_ok, CommandGlobalOpts, OckamColor};
+
+const LONG_ABOUT: &str = include_str!("./static/change_passphrase/long_about.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/change_passphrase/after_long_help.txt");
+
+/// Change the passphrase of an encrypted vault
+#[derive(Clone, Debug, Args)]
+#[command(
+long_about = docs::about(LONG_ABOUT),
+after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct ChangePassphraseCommand {
+    /// Name of the vault
+    name: Option<String>,
+}
+
+impl ChangePassphraseCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(rpc, (opts, self));
+    }
+}
+
+async fn rpc(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, ChangePassphraseCommand),
+) -> miette::Result<()> {
+    run_impl(&ctx, opts, cmd).await
+}
+
+async fn run_impl(
+    _ctx: &Context,
+    opts: CommandGlobalOpts,
+    cmd: ChangePassphraseCommand,
+) -> miette::Result<()> {
+    let vault = opts.state.get_named_vault_or_default(&cmd.name).await?;
+    let old_passphrase = opts
+        .terminal
+        .password("Enter the current passphrase of the vault", None)?;
+    let new_passphrase = opts
+        .terminal
+        .password("Enter a new passphrase", Some("Confirm the new passphrase"))?;
+    opts.state
+        .change_named_vault_passphrase(&vault.name(), &old_passphrase, &new_passphrase)
+        .await?;
+
+    opts.terminal
+        .stdout()
+        .plain(fmt_ok!(
+            "The passphrase of the vault {} has been changed",
+            color!(vault.name(), OckamColor::PrimaryResource)
+        ))
+        .machine(vault.name())
+        .json(serde_json::json!({ "name": vault.name() }))
+        .write_line()?;
+    Ok(())
+}
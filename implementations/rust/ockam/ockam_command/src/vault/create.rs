@@ -23,8 +23,24 @@ pub struct CreateCommand {
     #[arg(long)]
     path: Option<PathBuf>,
 
-    #[arg(long, default_value = "false")]
+    #[arg(long, default_value = "false", conflicts_with = "gcp_kms_key_ring")]
     aws_kms: bool,
+
+    /// Store the identity keys in a Google Cloud KMS key ring:
+    /// projects/{project}/locations/{location}/keyRings/{key_ring}
+    #[arg(long, value_name = "KEY_RING")]
+    gcp_kms_key_ring: Option<String>,
+
+    /// Store the identity keys in the secure hardware of this machine:
+    /// the Secure Enclave on macOS or the TPM on Linux and Windows.
+    /// The keys are stored in the vault if there is no such hardware
+    #[arg(long, default_value = "false", conflicts_with_all = ["aws_kms", "gcp_kms_key_ring"])]
+    platform: bool,
+
+    /// Also store the identity keys in the vault when the secure hardware of this machine
+    /// is present but can't be used, for example when OCKAM_TPM_PIN is not set
+    #[arg(long, default_value = "false", requires = "platform")]
+    platform_fallback: bool,
 }
 
 impl CreateCommand {
@@ -49,6 +65,14 @@ async fn run_impl(
     }
     let vault = if cmd.aws_kms {
         opts.state.create_kms_vault(&cmd.name, &cmd.path).await?
+    } else if let Some(key_ring) = &cmd.gcp_kms_key_ring {
+        opts.state
+            .create_gcp_kms_vault(&cmd.name, &cmd.path, key_ring)
+            .await?
+    } else if cmd.platform {
+        opts.state
+            .create_platform_vault(&cmd.name, &cmd.path, cmd.platform_fallback)
+            .await?
     } else {
         opts.state.create_named_vault(&cmd.name, &cmd.path).await?
     };
@@ -0,0 +1,67 @@
+use clap::Args;
+use colorful::Colorful;
+
+use ockam::Context;
+
+use crate::util::node_rpc;
+use crate::{color, docs, fmt_ok, CommandGlobalOpts, OckamColor};
+
+const LONG_ABOUT: &str = include_str!("./static/encrypt/long_about.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/encrypt/after_long_help.txt");
+
+/// Encrypt the secrets of a vault with a passphrase
+#[derive(Clone, Debug, Args)]
+#[command(
+long_about = docs::about(LONG_ABOUT),
+after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct EncryptCommand {
+    /// Name of the vault
+    name: Option<String>,
+
+    /// Use a random passphrase stored in the OS keychain instead of asking for a passphrase
+    #[arg(long)]
+    keychain: bool,
+}
+
+impl EncryptCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(rpc, (opts, self));
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, EncryptCommand)) -> miette::Result<()> {
+    run_impl(&ctx, opts, cmd).await
+}
+
+async fn run_impl(
+    _ctx: &Context,
+    opts: CommandGlobalOpts,
+    cmd: EncryptCommand,
+) -> miette::Result<()> {
+    let vault = opts.state.get_named_vault_or_default(&cmd.name).await?;
+    if cmd.keychain {
+        opts.state
+            .encrypt_named_vault_with_keychain(&vault.name())
+            .await?;
+    } else {
+        let passphrase = opts.terminal.password(
+            "Enter a passphrase for the vault",
+            Some("Confirm the passphrase"),
+        )?;
+        opts.state
+            .encrypt_named_vault(&vault.name(), &passphrase)
+            .await?;
+    }
+
+    opts.terminal
+        .stdout()
+        .plain(fmt_ok!(
+            "Vault {} has been encrypted",
+            color!(vault.name(), OckamColor::PrimaryResource)
+        ))
+        .machine(vault.name())
+        .json(serde_json::json!({ "name": vault.name() }))
+        .write_line()?;
+    Ok(())
+}
@@ -1,12 +1,16 @@
+mod change_passphrase;
 mod create;
 mod delete;
+mod encrypt;
 mod list;
 mod move_vault;
 mod show;
 mod util;
 
+use crate::vault::change_passphrase::ChangePassphraseCommand;
 use crate::vault::create::CreateCommand;
 use crate::vault::delete::DeleteCommand;
+use crate::vault::encrypt::EncryptCommand;
 use crate::vault::list::ListCommand;
 use crate::vault::move_vault::MoveCommand;
 use crate::vault::show::ShowCommand;
@@ -35,6 +39,8 @@ pub enum VaultSubcommand {
     Show(ShowCommand),
     Delete(DeleteCommand),
     List(ListCommand),
+    Encrypt(EncryptCommand),
+    ChangePassphrase(ChangePassphraseCommand),
 }
 
 impl VaultCommand {
@@ -45,6 +51,8 @@ impl VaultCommand {
             VaultSubcommand::Show(cmd) => cmd.run(opts),
             VaultSubcommand::List(cmd) => cmd.run(opts),
             VaultSubcommand::Delete(cmd) => cmd.run(opts),
+            VaultSubcommand::Encrypt(cmd) => cmd.run(opts),
+            VaultSubcommand::ChangePassphrase(cmd) => cmd.run(opts),
         }
     }
 }
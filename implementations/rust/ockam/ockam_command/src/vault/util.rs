@@ -37,12 +37,11 @@ impl Output for VaultOutput {
                 .name()
                 .to_string()
                 .color(OckamColor::PrimaryResource.color()),
-            vault_type = match self.vault.is_kms() {
-                true => "AWS KMS",
-                false => "OCKAM",
-            }
-            .to_string()
-            .color(OckamColor::PrimaryResource.color()),
+            vault_type = self
+                .vault
+                .vault_type()
+                .to_string()
+                .color(OckamColor::PrimaryResource.color()),
             vault_path = self
                 .vault
                 .path_as_string()
@@ -60,12 +59,11 @@ impl Output for VaultOutput {
                 .name()
                 .to_string()
                 .color(OckamColor::PrimaryResource.color()),
-            vault_type = match self.vault.is_kms() {
-                true => "AWS KMS",
-                false => "OCKAM",
-            }
-            .to_string()
-            .color(OckamColor::PrimaryResource.color()),
+            vault_type = self
+                .vault
+                .vault_type()
+                .to_string()
+                .color(OckamColor::PrimaryResource.color()),
             vault_path = self
                 .vault
                 .path_as_string()
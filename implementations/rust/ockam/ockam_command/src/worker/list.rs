@@ -72,11 +72,23 @@ async fn run_impl(
 
 impl Output for WorkerStatus {
     fn output(&self) -> crate::Result<String> {
-        Ok(format!(
+        let mut output = format!(
             "Worker {}",
             self.addr
                 .to_string()
                 .color(OckamColor::PrimaryResource.color())
-        ))
+        );
+        if let Some(worker_type) = &self.worker_type {
+            output.push_str(&format!(" ({worker_type})"));
+        }
+        if let Some(messages_count) = self.messages_count {
+            output.push_str(&format!(
+                ", {} messages routed",
+                messages_count
+                    .to_string()
+                    .color(OckamColor::PrimaryResource.color())
+            ));
+        }
+        Ok(output)
     }
 }
@@ -2,8 +2,10 @@ use crate::{docs, CommandGlobalOpts};
 use clap::{Args, Subcommand};
 
 use list::ListCommand;
+use show::ShowCommand;
 
 mod list;
+mod show;
 
 const LONG_ABOUT: &str = include_str!("./static/long_about.txt");
 
@@ -23,12 +25,15 @@ pub struct WorkerCommand {
 pub enum WorkerSubcommand {
     #[command(display_order = 800)]
     List(ListCommand),
+    #[command(display_order = 800)]
+    Show(ShowCommand),
 }
 
 impl WorkerCommand {
     pub fn run(self, options: CommandGlobalOpts) {
         match self.subcommand {
             WorkerSubcommand::List(c) => c.run(options),
+            WorkerSubcommand::Show(c) => c.run(options),
         }
     }
 }
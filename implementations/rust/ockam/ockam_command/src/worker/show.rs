@@ -0,0 +1,70 @@
+use clap::Args;
+use indoc::formatdoc;
+
+use ockam::Context;
+use ockam_api::address::extract_address_value;
+use ockam_api::nodes::models::workers::WorkerStatus;
+use ockam_api::nodes::BackgroundNodeClient;
+
+use crate::util::{api, node_rpc};
+use crate::{docs, fmt_ok, CommandGlobalOpts};
+
+const PREVIEW_TAG: &str = include_str!("../static/preview_tag.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/show/after_long_help.txt");
+
+/// Show the details of a worker on a node
+#[derive(Clone, Debug, Args)]
+#[command(
+before_help = docs::before_help(PREVIEW_TAG),
+after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct ShowCommand {
+    /// Address of the worker
+    #[arg(display_order = 900, required = true, value_parser = extract_address_value)]
+    address: String,
+
+    /// Node at which to lookup the worker
+    #[arg(value_name = "NODE_NAME", long, display_order = 800, value_parser = extract_address_value)]
+    at: Option<String>,
+}
+
+impl ShowCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(run_impl, (opts, self))
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, ShowCommand),
+) -> miette::Result<()> {
+    let node = BackgroundNodeClient::create(&ctx, &opts.state, &cmd.at).await?;
+    let worker: WorkerStatus = node.ask(&ctx, api::show_worker(&cmd.address)).await?;
+
+    let worker_type = worker
+        .worker_type
+        .map(|t| t.to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    let messages_count = worker
+        .messages_count
+        .map(|c| c.to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    let mailbox_size = worker
+        .mailbox_size
+        .map(|c| c.to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    let plain = formatdoc! {r#"
+        Worker:
+          Address: {address}
+          Type: {worker_type}
+          Messages routed: {messages_count}
+          Messages waiting in mailbox: {mailbox_size}
+    "#, address = worker.addr};
+
+    opts.terminal
+        .stdout()
+        .plain(fmt_ok!("{}", plain))
+        .machine(worker.addr)
+        .write_line()?;
+    Ok(())
+}
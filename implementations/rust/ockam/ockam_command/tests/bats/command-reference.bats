@@ -44,8 +44,12 @@ teardown() {
   n1="$(random_str)"
   run_success "$OCKAM" node create "$n1"
   run_success "$OCKAM" worker list --at "$n1"
+  assert_output --partial "uppercase (service)"
   run_success "$OCKAM" message send hello --to "/node/$n1/service/uppercase"
   assert_output "HELLO"
+
+  run_success "$OCKAM" worker show uppercase --at "$n1"
+  assert_output --partial "Type: service"
 }
 
 @test "projects - list" {
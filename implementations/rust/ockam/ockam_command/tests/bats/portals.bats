@@ -228,3 +228,29 @@ teardown() {
   run_success "$OCKAM" tcp-outlet create --at /node/blue --to 127.0.0.1:5000
   run_success curl --head --retry-connrefused --retry 2 --max-time 10 "127.0.0.1:$port"
 }
+
+@test "portals - udp inlet and outlet CRUD" {
+  outlet_port="$(random_port)"
+  inlet_port="$(random_port)"
+
+  run_success "$OCKAM" node create n1
+  run_success "$OCKAM" node create n2
+
+  run_success $OCKAM udp-outlet create --at /node/n1 --to "127.0.0.1:$outlet_port" --alias "test-udp-outlet"
+  assert_output --partial "/service/udp_outlet"
+
+  run_success $OCKAM udp-inlet create --at /node/n2 --from "127.0.0.1:$inlet_port" --to /node/n1/service/udp_outlet --alias "test-udp-inlet"
+
+  run_success $OCKAM udp-outlet list --at /node/n1 --output json
+  assert_output --partial "test-udp-outlet"
+  run_success $OCKAM udp-inlet list --at /node/n2 --output json
+  assert_output --partial "test-udp-inlet"
+
+  run_success $OCKAM udp-inlet delete "test-udp-inlet" --at /node/n2
+  run_success $OCKAM udp-inlet list --at /node/n2 --output json
+  refute_output --partial "test-udp-inlet"
+
+  run_success $OCKAM udp-outlet delete "test-udp-outlet" --at /node/n1
+  run_success $OCKAM udp-outlet list --at /node/n1 --output json
+  refute_output --partial "test-udp-outlet"
+}
@@ -34,6 +34,8 @@ pub struct RequestHeader {
     #[n(3)] method: Option<Method>,
     /// Indicator if a request body is expected after this header.
     #[n(4)] has_body: bool,
+    /// The W3C trace context of the span which sent the request, if any.
+    #[n(5)] tracing_context: Option<String>,
 }
 
 impl RequestHeader {
@@ -43,6 +45,7 @@ impl RequestHeader {
             method: Some(method),
             path: path.into(),
             has_body,
+            tracing_context: None,
         }
     }
 }
@@ -278,6 +281,10 @@ impl RequestHeader {
     pub fn has_body(&self) -> bool {
         self.has_body
     }
+
+    pub fn tracing_context(&self) -> Option<&str> {
+        self.tracing_context.as_deref()
+    }
 }
 
 impl ResponseHeader {
@@ -462,6 +469,11 @@ impl<T> Request<T> {
         self
     }
 
+    pub fn tracing_context<S: Into<String>>(mut self, tracing_context: S) -> Self {
+        self.header.tracing_context = Some(tracing_context.into());
+        self
+    }
+
     pub fn header(&self) -> &RequestHeader {
         &self.header
     }
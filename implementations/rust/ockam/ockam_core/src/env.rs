@@ -118,6 +118,13 @@ impl FromString for u64 {
     }
 }
 
+impl FromString for usize {
+    fn from_string(s: &str) -> Result<Self> {
+        s.parse::<usize>()
+            .map_err(|_| error("usize parsing error".to_string()))
+    }
+}
+
 #[cfg(feature = "std")]
 impl FromString for PathBuf {
     fn from_string(s: &str) -> Result<Self> {
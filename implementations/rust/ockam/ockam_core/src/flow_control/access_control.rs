@@ -40,9 +40,8 @@ impl FlowControlOutgoingAccessControl {
 
 impl FlowControlOutgoingAccessControl {
     fn is_consumer(&self, next: &Address, flow_control_id: &FlowControlId) -> bool {
-        let consumers_info = self.flow_controls.get_consumers_info(flow_control_id);
-
-        consumers_info.contains(next)
+        self.flow_controls
+            .accept_message_for_consumer(next, flow_control_id)
     }
 }
 
@@ -0,0 +1,57 @@
+#[cfg(feature = "std")]
+use crate::compat::time::{Duration, Instant};
+
+/// Limits of a temporary Consumer. The Consumer is removed once it received
+/// the maximum number of messages, or once its registration expired, so that
+/// temporary grants (e.g. a one-shot reply path) don't stay open forever
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ConsumerLimits {
+    remaining_messages: Option<u64>,
+    #[cfg(feature = "std")]
+    expires_at: Option<Instant>,
+}
+
+impl ConsumerLimits {
+    /// Allow at most `max_messages` messages to reach the Consumer
+    pub fn with_max_messages(mut self, max_messages: u64) -> Self {
+        self.remaining_messages = Some(max_messages);
+        self
+    }
+
+    /// Allow messages to reach the Consumer during the given [`Duration`]
+    #[cfg(feature = "std")]
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.expires_at = Some(Instant::now() + duration);
+        self
+    }
+
+    /// Number of messages which can still reach the Consumer, if limited
+    pub fn remaining_messages(&self) -> Option<u64> {
+        self.remaining_messages
+    }
+
+    /// Time at which the Consumer registration expires, if limited
+    #[cfg(feature = "std")]
+    pub fn expires_at(&self) -> Option<Instant> {
+        self.expires_at
+    }
+
+    /// Return true if no more messages can reach the Consumer
+    pub fn is_exhausted(&self) -> bool {
+        if self.remaining_messages == Some(0) {
+            return true;
+        }
+        #[cfg(feature = "std")]
+        if let Some(expires_at) = self.expires_at {
+            return Instant::now() >= expires_at;
+        }
+        false
+    }
+
+    /// Count one message sent to the Consumer
+    pub(super) fn count_message(&mut self) {
+        if let Some(remaining) = self.remaining_messages.as_mut() {
+            *remaining = remaining.saturating_sub(1);
+        }
+    }
+}
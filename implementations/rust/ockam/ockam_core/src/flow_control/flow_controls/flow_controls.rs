@@ -1,6 +1,6 @@
 use crate::compat::collections::BTreeMap;
 use crate::compat::sync::{Arc, RwLock};
-use crate::flow_control::{ConsumersInfo, FlowControlId, ProducerInfo};
+use crate::flow_control::{ConsumerLimits, ConsumersInfo, FlowControlId, ProducerInfo};
 use crate::Address;
 
 /// Storage for all Flow Control-related data
@@ -8,6 +8,8 @@ use crate::Address;
 pub struct FlowControls {
     // All known consumers
     pub(super) consumers: Arc<RwLock<BTreeMap<FlowControlId, ConsumersInfo>>>,
+    // Limits of the temporary consumers
+    pub(super) consumers_limits: Arc<RwLock<BTreeMap<(FlowControlId, Address), ConsumerLimits>>>,
     // All known producers
     pub(super) producers: Arc<RwLock<BTreeMap<Address, ProducerInfo>>>,
     // Allows to find producer by having its additional Address,
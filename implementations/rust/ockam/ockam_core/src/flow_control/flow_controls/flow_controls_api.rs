@@ -1,6 +1,8 @@
 use crate::compat::rand::random;
 use crate::compat::vec::Vec;
-use crate::flow_control::{ConsumersInfo, FlowControlId, FlowControls, ProducerInfo};
+use crate::flow_control::{
+    ConsumerLimits, ConsumersInfo, FlowControlId, FlowControls, ProducerInfo,
+};
 use crate::Address;
 
 impl FlowControls {
@@ -9,6 +11,7 @@ impl FlowControls {
     pub fn new() -> Self {
         Self {
             consumers: Default::default(),
+            consumers_limits: Default::default(),
             producers: Default::default(),
             producers_additional_addresses: Default::default(),
             spawners: Default::default(),
@@ -33,7 +36,95 @@ impl FlowControls {
 
         let flow_control_consumers = consumers.get_mut(flow_control_id).unwrap();
 
-        flow_control_consumers.0.insert(address);
+        flow_control_consumers.0.insert(address.clone());
+        drop(consumers);
+
+        // A permanent Consumer replaces a temporary one
+        self.consumers_limits
+            .write()
+            .unwrap()
+            .remove(&(flow_control_id.clone(), address));
+    }
+
+    /// Mark that given [`Address`] is a temporary Consumer for a Producer with the given
+    /// [`FlowControlId`]. The Consumer is removed once one of the [`ConsumerLimits`] is reached
+    pub fn add_consumer_with_limits(
+        &self,
+        address: impl Into<Address>,
+        flow_control_id: &FlowControlId,
+        limits: ConsumerLimits,
+    ) {
+        let address = address.into();
+        self.add_consumer(address.clone(), flow_control_id);
+        debug!("Limit Consumer {address} of Producer {flow_control_id} to {limits:?}");
+        self.consumers_limits
+            .write()
+            .unwrap()
+            .insert((flow_control_id.clone(), address), limits);
+    }
+
+    /// Remove given [`Address`] from the Consumers for the given [`FlowControlId`]
+    pub fn remove_consumer(&self, address: &Address, flow_control_id: &FlowControlId) {
+        debug!("Remove Consumer {address} of Producer {flow_control_id}");
+        let mut consumers = self.consumers.write().unwrap();
+        if let Some(info) = consumers.get_mut(flow_control_id) {
+            info.0.remove(address);
+            if info.0.is_empty() {
+                consumers.remove(flow_control_id);
+            }
+        }
+        drop(consumers);
+
+        self.consumers_limits
+            .write()
+            .unwrap()
+            .remove(&(flow_control_id.clone(), address.clone()));
+    }
+
+    /// Get the [`ConsumerLimits`] of a temporary Consumer
+    pub fn get_consumer_limits(
+        &self,
+        address: &Address,
+        flow_control_id: &FlowControlId,
+    ) -> Option<ConsumerLimits> {
+        self.consumers_limits
+            .read()
+            .unwrap()
+            .get(&(flow_control_id.clone(), address.clone()))
+            .cloned()
+    }
+
+    /// Check if a message can be sent to the given [`Address`] as a Consumer for the given
+    /// [`FlowControlId`]. For a temporary Consumer, the message is counted and the Consumer is
+    /// removed when its [`ConsumerLimits`] are reached
+    pub fn accept_message_for_consumer(
+        &self,
+        address: &Address,
+        flow_control_id: &FlowControlId,
+    ) -> bool {
+        if !self.get_consumers_info(flow_control_id).contains(address) {
+            return false;
+        }
+
+        let key = (flow_control_id.clone(), address.clone());
+        let mut consumers_limits = self.consumers_limits.write().unwrap();
+        let limits = match consumers_limits.get_mut(&key) {
+            None => return true, // Permanent Consumer
+            Some(limits) => limits,
+        };
+
+        let accepted = !limits.is_exhausted();
+        if accepted {
+            limits.count_message();
+        }
+        let exhausted = limits.is_exhausted();
+        drop(consumers_limits);
+
+        if exhausted {
+            debug!("Consumer {address} of Producer {flow_control_id} reached its limits");
+            self.remove_consumer(address, flow_control_id);
+        }
+        accepted
     }
 
     /// Mark that given [`Address`] is a Producer for to the given [`FlowControlId`]
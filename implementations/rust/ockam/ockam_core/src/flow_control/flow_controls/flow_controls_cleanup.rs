@@ -37,10 +37,7 @@ impl FlowControls {
 
         // Spawners don't exist, Producers don't exist as well, which means storing Consumers
         // for that FlowControlId doesn't make sense anymore
-        self.consumers
-            .write()
-            .unwrap()
-            .remove(&spawner_flow_control_id);
+        self.remove_consumers(&spawner_flow_control_id);
     }
 
     fn cleanup_producers_spawner(&self, flow_control_id: &FlowControlId) {
@@ -74,7 +71,7 @@ impl FlowControls {
         }
 
         // We can clean Consumers for that FlowControlId
-        self.consumers.write().unwrap().remove(flow_control_id);
+        self.remove_consumers(flow_control_id);
     }
 
     fn cleanup_producer(&self, address: &Address) {
@@ -108,7 +105,7 @@ impl FlowControls {
         }
 
         // We can clean Consumers for that FlowControlId
-        self.consumers.write().unwrap().remove(&flow_control_id);
+        self.remove_consumers(&flow_control_id);
     }
 
     fn cleanup_consumer(&self, address: &Address) {
@@ -131,6 +128,20 @@ impl FlowControls {
 
         // Remove empty Maps
         consumers.retain(|_, info| !info.0.is_empty());
+        drop(consumers);
+
+        self.consumers_limits
+            .write()
+            .unwrap()
+            .retain(|(_flow_control_id, consumer), _| consumer != address);
+    }
+
+    fn remove_consumers(&self, flow_control_id: &FlowControlId) {
+        self.consumers.write().unwrap().remove(flow_control_id);
+        self.consumers_limits
+            .write()
+            .unwrap()
+            .retain(|(id, _consumer), _| id != flow_control_id);
     }
 
     /// Clean everything that is possible after [`Address`] no longer exists
@@ -0,0 +1,149 @@
+use crate::compat::collections::BTreeMap;
+use crate::compat::vec::Vec;
+use crate::flow_control::{FlowControlId, FlowControls, ProducerInfo};
+use crate::Address;
+
+/// Spawners, Producers and Consumers of a Flow Control
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FlowControlInfo {
+    flow_control_id: FlowControlId,
+    spawners: Vec<Address>,
+    producers: Vec<Address>,
+    consumers: Vec<Address>,
+}
+
+impl FlowControlInfo {
+    fn new(flow_control_id: FlowControlId) -> Self {
+        Self {
+            flow_control_id,
+            spawners: Vec::new(),
+            producers: Vec::new(),
+            consumers: Vec::new(),
+        }
+    }
+
+    /// [`FlowControlId`]
+    pub fn flow_control_id(&self) -> &FlowControlId {
+        &self.flow_control_id
+    }
+
+    /// Spawners with this [`FlowControlId`]
+    pub fn spawners(&self) -> &[Address] {
+        &self.spawners
+    }
+
+    /// Producers with this [`FlowControlId`]
+    pub fn producers(&self) -> &[Address] {
+        &self.producers
+    }
+
+    /// Consumers allowed to receive messages from the Producers with this [`FlowControlId`],
+    /// or from the Producers spawned by the Spawners with this [`FlowControlId`]
+    pub fn consumers(&self) -> &[Address] {
+        &self.consumers
+    }
+}
+
+/// Flow Controls in which an [`Address`] takes part
+#[derive(Clone, Debug)]
+pub struct AddressFlowControlInfo {
+    address: Address,
+    consumer_of: Vec<FlowControlId>,
+    producer: Option<ProducerInfo>,
+    producer_address: Option<Address>,
+    spawner_of: Option<FlowControlId>,
+}
+
+impl AddressFlowControlInfo {
+    /// [`Address`]
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// [`FlowControlId`]s for which the [`Address`] is a Consumer
+    pub fn consumer_of(&self) -> &[FlowControlId] {
+        &self.consumer_of
+    }
+
+    /// [`ProducerInfo`] of the Producer with this [`Address`], or of the Producer for which
+    /// this [`Address`] is an additional [`Address`]
+    pub fn producer(&self) -> &Option<ProducerInfo> {
+        &self.producer
+    }
+
+    /// [`Address`] of the Producer, when the [`Address`] is an additional [`Address`] of that Producer
+    /// (e.g. Encryptor address for its Decryptor, or TCP Sender for its TCP Receiver)
+    pub fn producer_address(&self) -> &Option<Address> {
+        &self.producer_address
+    }
+
+    /// [`FlowControlId`] for which the [`Address`] is a Spawner
+    pub fn spawner_of(&self) -> &Option<FlowControlId> {
+        &self.spawner_of
+    }
+
+    /// Return true if the [`Address`] doesn't take part in any Flow Control
+    pub fn is_empty(&self) -> bool {
+        self.consumer_of.is_empty() && self.producer.is_none() && self.spawner_of.is_none()
+    }
+}
+
+impl FlowControls {
+    /// Get the Spawners, Producers and Consumers of all the known Flow Controls,
+    /// sorted by [`FlowControlId`]
+    pub fn get_flow_controls_info(&self) -> Vec<FlowControlInfo> {
+        fn info<'a>(
+            infos: &'a mut BTreeMap<FlowControlId, FlowControlInfo>,
+            id: &FlowControlId,
+        ) -> &'a mut FlowControlInfo {
+            infos
+                .entry(id.clone())
+                .or_insert_with(|| FlowControlInfo::new(id.clone()))
+        }
+
+        let mut infos = BTreeMap::new();
+        for (address, id) in self.spawners.read().unwrap().iter() {
+            info(&mut infos, id).spawners.push(address.clone());
+        }
+        for (address, producer) in self.producers.read().unwrap().iter() {
+            info(&mut infos, producer.flow_control_id())
+                .producers
+                .push(address.clone());
+        }
+        for (id, consumers) in self.consumers.read().unwrap().iter() {
+            info(&mut infos, id)
+                .consumers
+                .extend(consumers.0.iter().cloned());
+        }
+
+        infos.into_values().collect()
+    }
+
+    /// Get the Flow Controls in which the given [`Address`] takes part
+    pub fn get_address_info(&self, address: &Address) -> AddressFlowControlInfo {
+        let consumer_of = self
+            .consumers
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(_, consumers)| consumers.contains(address))
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        let producer_address = self
+            .producers_additional_addresses
+            .read()
+            .unwrap()
+            .get(address)
+            .filter(|producer_address| *producer_address != address)
+            .cloned();
+
+        AddressFlowControlInfo {
+            address: address.clone(),
+            consumer_of,
+            producer: self.find_flow_control_with_producer_address(address),
+            producer_address,
+            spawner_of: self.get_flow_control_with_spawner(address),
+        }
+    }
+}
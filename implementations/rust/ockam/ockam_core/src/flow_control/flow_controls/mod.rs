@@ -1,16 +1,20 @@
+mod consumer_limits;
 mod consumers_info;
 #[allow(clippy::module_inception)]
 mod flow_controls;
 mod flow_controls_api;
 mod flow_controls_cleanup;
 mod flow_controls_debug;
+mod flow_controls_info;
 mod producer_info;
 
+pub use consumer_limits::*;
 pub use consumers_info::*;
 pub use flow_controls::*;
 pub use flow_controls_api::*;
 pub use flow_controls_cleanup::*;
 pub use flow_controls_debug::*;
+pub use flow_controls_info::*;
 pub use producer_info::*;
 
 #[cfg(test)]
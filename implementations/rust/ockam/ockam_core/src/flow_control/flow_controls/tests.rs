@@ -1,5 +1,6 @@
-use crate::flow_control::FlowControls;
+use crate::flow_control::{ConsumerLimits, FlowControls};
 use crate::Address;
+use core::time::Duration;
 use rand::distributions::Distribution;
 use rand::distributions::Uniform;
 use rand::prelude::{IteratorRandom, SliceRandom, ThreadRng};
@@ -195,3 +196,130 @@ fn test_cleanup() {
         .is_empty());
     assert!(flow_controls.spawners.read().unwrap().is_empty());
 }
+
+#[test]
+fn test_flow_controls_info() {
+    let flow_controls = FlowControls::new();
+    let spawner_id = FlowControls::generate_flow_control_id();
+    let producer_id = FlowControls::generate_flow_control_id();
+
+    let listener = Address::random_local();
+    let receiver = Address::random_local();
+    let sender = Address::random_local();
+    let consumer = Address::random_local();
+
+    flow_controls.add_spawner(listener.clone(), &spawner_id);
+    flow_controls.add_producer(
+        receiver.clone(),
+        &producer_id,
+        Some(&spawner_id),
+        vec![sender.clone()],
+    );
+    flow_controls.add_consumer(consumer.clone(), &spawner_id);
+    flow_controls.add_consumer(consumer.clone(), &producer_id);
+
+    let infos = flow_controls.get_flow_controls_info();
+    assert_eq!(infos.len(), 2);
+    let spawner_info = infos
+        .iter()
+        .find(|i| i.flow_control_id() == &spawner_id)
+        .unwrap();
+    assert_eq!(spawner_info.spawners(), [listener.clone()]);
+    assert!(spawner_info.producers().is_empty());
+    assert_eq!(spawner_info.consumers(), [consumer.clone()]);
+    let producer_info = infos
+        .iter()
+        .find(|i| i.flow_control_id() == &producer_id)
+        .unwrap();
+    assert_eq!(producer_info.producers(), [receiver.clone()]);
+    assert_eq!(producer_info.consumers(), [consumer.clone()]);
+
+    let sender_info = flow_controls.get_address_info(&sender);
+    assert_eq!(sender_info.producer_address(), &Some(receiver.clone()));
+    assert_eq!(
+        sender_info.producer().as_ref().map(|p| p.flow_control_id()),
+        Some(&producer_id)
+    );
+    assert_eq!(
+        flow_controls
+            .get_address_info(&consumer)
+            .consumer_of()
+            .len(),
+        2
+    );
+    assert_eq!(
+        flow_controls.get_address_info(&listener).spawner_of(),
+        &Some(spawner_id)
+    );
+    assert!(flow_controls
+        .get_address_info(&Address::random_local())
+        .is_empty());
+}
+
+#[test]
+fn test_consumer_with_max_messages() {
+    let flow_controls = FlowControls::new();
+    let flow_control_id = FlowControls::generate_flow_control_id();
+    let consumer = Address::random_local();
+
+    flow_controls.add_consumer_with_limits(
+        consumer.clone(),
+        &flow_control_id,
+        ConsumerLimits::default().with_max_messages(2),
+    );
+
+    assert!(flow_controls.accept_message_for_consumer(&consumer, &flow_control_id));
+    assert_eq!(
+        flow_controls
+            .get_consumer_limits(&consumer, &flow_control_id)
+            .and_then(|l| l.remaining_messages()),
+        Some(1)
+    );
+    assert!(flow_controls.accept_message_for_consumer(&consumer, &flow_control_id));
+    assert!(!flow_controls.accept_message_for_consumer(&consumer, &flow_control_id));
+
+    // The consumer registration is removed once the limit is reached
+    assert!(!flow_controls
+        .get_consumers_info(&flow_control_id)
+        .contains(&consumer));
+    assert!(flow_controls
+        .get_consumer_limits(&consumer, &flow_control_id)
+        .is_none());
+}
+
+#[test]
+fn test_consumer_with_duration() {
+    let flow_controls = FlowControls::new();
+    let flow_control_id = FlowControls::generate_flow_control_id();
+    let consumer = Address::random_local();
+
+    flow_controls.add_consumer_with_limits(
+        consumer.clone(),
+        &flow_control_id,
+        ConsumerLimits::default().with_duration(Duration::from_millis(50)),
+    );
+    assert!(flow_controls.accept_message_for_consumer(&consumer, &flow_control_id));
+
+    std::thread::sleep(Duration::from_millis(100));
+    assert!(!flow_controls.accept_message_for_consumer(&consumer, &flow_control_id));
+    assert!(!flow_controls
+        .get_consumers_info(&flow_control_id)
+        .contains(&consumer));
+}
+
+#[test]
+fn test_permanent_consumer_replaces_temporary_consumer() {
+    let flow_controls = FlowControls::new();
+    let flow_control_id = FlowControls::generate_flow_control_id();
+    let consumer = Address::random_local();
+
+    flow_controls.add_consumer_with_limits(
+        consumer.clone(),
+        &flow_control_id,
+        ConsumerLimits::default().with_max_messages(1),
+    );
+    flow_controls.add_consumer(consumer.clone(), &flow_control_id);
+
+    assert!(flow_controls.accept_message_for_consumer(&consumer, &flow_control_id));
+    assert!(flow_controls.accept_message_for_consumer(&consumer, &flow_control_id));
+}
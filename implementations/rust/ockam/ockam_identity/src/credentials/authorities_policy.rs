@@ -0,0 +1,141 @@
+use ockam_core::compat::vec::Vec;
+use ockam_core::Result;
+use tracing::warn;
+
+use crate::models::Identifier;
+use crate::IdentityError;
+
+/// Policy determining which authorities must have issued the credentials of a subject.
+///
+/// By default a credential issued by any of the trusted authorities is sufficient. Higher-assurance
+/// deployments can require credentials from a quorum of authorities, for example 2 out of 3, and/or
+/// from some specific authorities.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuthoritiesPolicy {
+    authorities: Vec<Identifier>,
+    threshold: usize,
+    required: Vec<Identifier>,
+}
+
+impl AuthoritiesPolicy {
+    /// A credential issued by any of the authorities is sufficient
+    pub fn any_of(authorities: Vec<Identifier>) -> Self {
+        Self::quorum(authorities, 1)
+    }
+
+    /// Credentials issued by all the authorities are required
+    pub fn all_of(authorities: Vec<Identifier>) -> Self {
+        let threshold = authorities.len();
+        Self::quorum(authorities, threshold)
+    }
+
+    /// Credentials issued by at least `threshold` distinct authorities are required
+    pub fn quorum(authorities: Vec<Identifier>, threshold: usize) -> Self {
+        Self {
+            authorities,
+            threshold,
+            required: Vec::new(),
+        }
+    }
+
+    /// Require a credential issued by a specific authority, in addition to the quorum.
+    /// The authority is trusted even if it was not part of the initial list of authorities
+    pub fn with_required_authority(mut self, authority: Identifier) -> Self {
+        if !self.authorities.contains(&authority) {
+            self.authorities.push(authority.clone());
+        }
+        if !self.required.contains(&authority) {
+            self.required.push(authority);
+        }
+        self
+    }
+
+    /// Return all the trusted authorities
+    pub fn authorities(&self) -> &[Identifier] {
+        &self.authorities
+    }
+
+    /// Return the minimum number of distinct authorities
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Return the authorities which must have issued a credential
+    pub fn required_authorities(&self) -> &[Identifier] {
+        &self.required
+    }
+
+    /// Check that the issuers of the verified credentials of a subject satisfy this policy
+    pub fn check(&self, issuers: &[Identifier]) -> Result<()> {
+        let mut trusted_issuers: Vec<&Identifier> = issuers
+            .iter()
+            .filter(|i| self.authorities.contains(i))
+            .collect();
+        trusted_issuers.sort();
+        trusted_issuers.dedup();
+
+        if trusted_issuers.len() < self.threshold {
+            warn!(
+                "credentials were issued by {} trusted authorities, {} are required",
+                trusted_issuers.len(),
+                self.threshold
+            );
+            return Err(IdentityError::AuthoritiesPolicyNotSatisfied)?;
+        }
+
+        if let Some(missing) = self.required.iter().find(|r| !issuers.contains(r)) {
+            warn!("a credential issued by {missing} is required");
+            return Err(IdentityError::AuthoritiesPolicyNotSatisfied)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<Vec<Identifier>> for AuthoritiesPolicy {
+    fn from(authorities: Vec<Identifier>) -> Self {
+        Self::any_of(authorities)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::str::FromStr;
+
+    #[test]
+    fn test_check_policy() -> Result<()> {
+        let authority1 = Identifier::from_str("I124ed0b2e5a2be82e267ead6b3279f683616b66d")?;
+        let authority2 = Identifier::from_str("I224ed0b2e5a2be82e267ead6b3279f683616b66d")?;
+        let authority3 = Identifier::from_str("I324ed0b2e5a2be82e267ead6b3279f683616b66d")?;
+        let other = Identifier::from_str("I424ed0b2e5a2be82e267ead6b3279f683616b66d")?;
+        let authorities = vec![authority1.clone(), authority2.clone(), authority3.clone()];
+
+        let policy = AuthoritiesPolicy::any_of(authorities.clone());
+        assert!(policy.check(&[authority2.clone()]).is_ok());
+        assert!(policy.check(&[other.clone()]).is_err());
+
+        // 2 distinct trusted authorities are required
+        let policy = AuthoritiesPolicy::quorum(authorities.clone(), 2);
+        assert!(policy.check(&[authority1.clone()]).is_err());
+        assert!(policy
+            .check(&[authority1.clone(), authority1.clone()])
+            .is_err());
+        assert!(policy.check(&[authority1.clone(), other.clone()]).is_err());
+        assert!(policy
+            .check(&[authority1.clone(), authority3.clone()])
+            .is_ok());
+
+        // a specific authority is required
+        let policy = policy.with_required_authority(authority2.clone());
+        assert!(policy
+            .check(&[authority1.clone(), authority3.clone()])
+            .is_err());
+        assert!(policy
+            .check(&[authority1.clone(), authority2.clone()])
+            .is_ok());
+
+        let policy = AuthoritiesPolicy::all_of(authorities);
+        assert!(policy.check(&[authority1, authority2.clone()]).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,240 @@
+use core::time::Duration;
+use minicbor::bytes::ByteVec;
+
+use ockam_core::compat::collections::{BTreeMap, BTreeSet};
+use ockam_core::compat::string::String;
+use ockam_core::compat::sync::RwLock;
+use ockam_core::compat::vec::Vec;
+use ockam_core::{Error, Result};
+
+use crate::models::{
+    AttributeValue, Attributes, Identifier, DELEGATED_ATTRIBUTES_KEY, DELEGATION_SCHEMA_IDENTIFIER,
+};
+use crate::IdentityError;
+
+/// Constraints enforced by an issuer on the credentials it issues
+///
+/// A credential can't be issued if:
+///
+///  - its time to live is longer than the maximum time to live
+///  - one of its attributes is not in the list of allowed attributes, when there is one
+///  - one of its attributes is in the list of denied attributes
+///  - one of the attributes required for the class of the subject is missing.
+///    The class of a subject is the value of its class attribute
+///
+/// The allowed and denied attributes also apply to the attributes delegated with a
+/// delegation credential.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CredentialIssuancePolicy {
+    max_ttl: Option<Duration>,
+    allowed_attributes: Option<BTreeSet<Vec<u8>>>,
+    denied_attributes: BTreeSet<Vec<u8>>,
+    class_attribute: Option<Vec<u8>>,
+    required_attributes: BTreeMap<Vec<u8>, Vec<Vec<u8>>>,
+}
+
+impl CredentialIssuancePolicy {
+    /// Create a policy without any constraint
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum time to live of the issued credentials
+    pub fn with_max_ttl(mut self, max_ttl: Duration) -> Self {
+        self.max_ttl = Some(max_ttl);
+        self
+    }
+
+    /// Allow an attribute. As soon as one attribute is allowed, all the other attributes are denied
+    pub fn with_allowed_attribute(mut self, key: impl Into<Vec<u8>>) -> Self {
+        self.allowed_attributes
+            .get_or_insert_with(BTreeSet::new)
+            .insert(key.into());
+        self
+    }
+
+    /// Deny an attribute
+    pub fn with_denied_attribute(mut self, key: impl Into<Vec<u8>>) -> Self {
+        self.denied_attributes.insert(key.into());
+        self
+    }
+
+    /// Set the attribute whose value is the class of a subject
+    pub fn with_class_attribute(mut self, key: impl Into<Vec<u8>>) -> Self {
+        self.class_attribute = Some(key.into());
+        self
+    }
+
+    /// Require some attributes for the subjects of a given class
+    pub fn with_required_attributes(
+        mut self,
+        class: impl Into<Vec<u8>>,
+        keys: Vec<Vec<u8>>,
+    ) -> Self {
+        self.required_attributes
+            .entry(class.into())
+            .or_default()
+            .extend(keys);
+        self
+    }
+
+    /// Check that a credential with the given attributes and time to live can be issued
+    pub fn check(&self, attributes: &Attributes, ttl: Duration) -> Result<()> {
+        if let Some(max_ttl) = self.max_ttl {
+            if ttl > max_ttl {
+                return Err(Self::denied(format!(
+                    "the time to live {}s is longer than the maximum time to live {}s",
+                    ttl.as_secs(),
+                    max_ttl.as_secs()
+                )));
+            }
+        }
+
+        // for a delegation, check the delegated attributes instead
+        let keys: Vec<Vec<u8>> = if attributes.schema == DELEGATION_SCHEMA_IDENTIFIER {
+            match attributes.get_attribute(DELEGATED_ATTRIBUTES_KEY.as_bytes()) {
+                Some(AttributeValue::StringList(keys)) => {
+                    keys.into_iter().map(String::into_bytes).collect()
+                }
+                _ => Vec::new(),
+            }
+        } else {
+            attributes.map.keys().map(|key| key.to_vec()).collect()
+        };
+
+        for key in keys.iter() {
+            let not_allowed = self
+                .allowed_attributes
+                .as_ref()
+                .map(|allowed| !allowed.contains(key))
+                .unwrap_or(false);
+            if not_allowed || self.denied_attributes.contains(key) {
+                return Err(Self::denied(format!(
+                    "the attribute {} can't be issued",
+                    String::from_utf8_lossy(key)
+                )));
+            }
+        }
+
+        if attributes.schema == DELEGATION_SCHEMA_IDENTIFIER {
+            return Ok(());
+        }
+
+        let class = match self
+            .class_attribute
+            .as_ref()
+            .and_then(|key| attributes.map.get(&ByteVec::from(key.clone())))
+        {
+            Some(class) => class.to_vec(),
+            None => return Ok(()),
+        };
+        if let Some(required) = self.required_attributes.get(&class) {
+            if let Some(missing) = required
+                .iter()
+                .find(|key| !attributes.map.contains_key(&ByteVec::from((*key).clone())))
+            {
+                return Err(Self::denied(format!(
+                    "the attribute {} is required for the subject class {}",
+                    String::from_utf8_lossy(missing),
+                    String::from_utf8_lossy(&class)
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn denied(message: String) -> Error {
+        Error::from(IdentityError::CredentialIssuanceDenied).context("message", message)
+    }
+}
+
+/// Registry of the [`CredentialIssuancePolicy`] of each issuer.
+/// The credentials of an issuer without a policy are issued without constraints.
+#[derive(Default)]
+pub struct CredentialIssuancePolicies {
+    policies: RwLock<BTreeMap<Identifier, CredentialIssuancePolicy>>,
+}
+
+impl CredentialIssuancePolicies {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the policy of an issuer, replacing its previous policy (if any)
+    pub fn set_policy(&self, issuer: &Identifier, policy: CredentialIssuancePolicy) {
+        self.policies
+            .write()
+            .unwrap()
+            .insert(issuer.clone(), policy);
+    }
+
+    /// Remove the policy of an issuer
+    pub fn remove_policy(&self, issuer: &Identifier) {
+        self.policies.write().unwrap().remove(issuer);
+    }
+
+    /// Return the policy of an issuer
+    pub fn get_policy(&self, issuer: &Identifier) -> Option<CredentialIssuancePolicy> {
+        self.policies.read().unwrap().get(issuer).cloned()
+    }
+
+    /// Check that `issuer` can issue a credential with the given attributes and time to live
+    pub fn check(&self, issuer: &Identifier, attributes: &Attributes, ttl: Duration) -> Result<()> {
+        match self.policies.read().unwrap().get(issuer) {
+            Some(policy) => policy.check(attributes, ttl),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CredentialSchemaIdentifier;
+    use crate::utils::AttributesBuilder;
+
+    #[test]
+    fn test_issuance_policy() {
+        let policy = CredentialIssuancePolicy::new()
+            .with_max_ttl(Duration::from_secs(3600))
+            .with_denied_attribute("admin")
+            .with_class_attribute("role")
+            .with_required_attributes("member", vec![b"project".to_vec()]);
+        let builder = || AttributesBuilder::with_schema(CredentialSchemaIdentifier(1));
+        let ttl = Duration::from_secs(60);
+
+        // the time to live is limited
+        let attributes = builder().with_attribute("name", "client").build();
+        assert!(policy.check(&attributes, ttl).is_ok());
+        assert!(policy
+            .check(&attributes, Duration::from_secs(7200))
+            .is_err());
+
+        // denied attributes can't be issued
+        let attributes = builder().with_attribute("admin", "true").build();
+        assert!(policy.check(&attributes, ttl).is_err());
+
+        // a member must have a project
+        let attributes = builder().with_attribute("role", "member").build();
+        assert!(policy.check(&attributes, ttl).is_err());
+        let attributes = builder()
+            .with_attribute("role", "member")
+            .with_attribute("project", "p1")
+            .build();
+        assert!(policy.check(&attributes, ttl).is_ok());
+        let attributes = builder().with_attribute("role", "guest").build();
+        assert!(policy.check(&attributes, ttl).is_ok());
+
+        // only allowed attributes can be issued or delegated
+        let policy = CredentialIssuancePolicy::new().with_allowed_attribute("name");
+        let attributes = builder().with_attribute("name", "client").build();
+        assert!(policy.check(&attributes, ttl).is_ok());
+        let attributes = builder().with_attribute("other", "value").build();
+        assert!(policy.check(&attributes, ttl).is_err());
+        let delegation = AttributesBuilder::with_schema(DELEGATION_SCHEMA_IDENTIFIER)
+            .with_typed_attribute(DELEGATED_ATTRIBUTES_KEY, vec![String::from("other")])
+            .build();
+        assert!(policy.check(&delegation, ttl).is_err());
+    }
+}
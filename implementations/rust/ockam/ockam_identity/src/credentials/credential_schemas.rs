@@ -0,0 +1,337 @@
+use core::str::FromStr;
+
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::string::{String, ToString};
+use ockam_core::compat::sync::RwLock;
+use ockam_core::compat::vec::Vec;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Error, Result};
+
+use crate::models::{AttributeValue, Attributes, CredentialSchemaIdentifier};
+
+/// Type of the value of an attribute
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttributeType {
+    /// A UTF-8 string
+    String,
+    /// A signed integer
+    Int,
+    /// A boolean
+    Bool,
+    /// Raw bytes
+    Bytes,
+    /// A list of UTF-8 strings
+    StringList,
+}
+
+impl AttributeType {
+    /// Return the value with this type, if it has this type.
+    /// Untyped values, set as strings, are parsed to the expected type
+    fn coerce(&self, value: AttributeValue) -> Option<AttributeValue> {
+        match (self, value) {
+            (AttributeType::String, v @ AttributeValue::String(_)) => Some(v),
+            (AttributeType::Int, v @ AttributeValue::Int(_)) => Some(v),
+            (AttributeType::Int, AttributeValue::String(s)) => {
+                i64::from_str(&s).ok().map(AttributeValue::Int)
+            }
+            (AttributeType::Bool, v @ AttributeValue::Bool(_)) => Some(v),
+            (AttributeType::Bool, AttributeValue::String(s)) => {
+                bool::from_str(&s).ok().map(AttributeValue::Bool)
+            }
+            (AttributeType::Bytes, v @ AttributeValue::Bytes(_)) => Some(v),
+            (AttributeType::Bytes, AttributeValue::String(s)) => {
+                Some(AttributeValue::Bytes(s.into_bytes().into()))
+            }
+            (AttributeType::StringList, v @ AttributeValue::StringList(_)) => Some(v),
+            (AttributeType::StringList, AttributeValue::String(s)) => Some(
+                AttributeValue::StringList(s.split(',').map(|s| s.to_string()).collect()),
+            ),
+            _ => None,
+        }
+    }
+}
+
+/// Constraint on the value of an attribute
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AttributeConstraint {
+    /// The value must be one of the listed values.
+    /// For a list of strings, each item must be one of the listed strings
+    OneOf(Vec<AttributeValue>),
+    /// An integer value must be in the inclusive range
+    Range {
+        /// Minimum value
+        min: i64,
+        /// Maximum value
+        max: i64,
+    },
+    /// A string, bytes or a list of strings can't be longer than this length
+    MaxLength(usize),
+}
+
+impl AttributeConstraint {
+    /// Return true if the typed value satisfies the constraint
+    fn check(&self, value: &AttributeValue) -> bool {
+        match (self, value) {
+            (AttributeConstraint::OneOf(values), AttributeValue::StringList(items)) => items
+                .iter()
+                .all(|item| values.iter().any(|v| v.as_str() == Some(item.as_str()))),
+            (AttributeConstraint::OneOf(values), value) => values.contains(value),
+            (AttributeConstraint::Range { min, max }, AttributeValue::Int(i)) => {
+                min <= i && i <= max
+            }
+            (AttributeConstraint::MaxLength(max), AttributeValue::String(s)) => s.len() <= *max,
+            (AttributeConstraint::MaxLength(max), AttributeValue::Bytes(b)) => b.len() <= *max,
+            (AttributeConstraint::MaxLength(max), AttributeValue::StringList(l)) => l.len() <= *max,
+            _ => false,
+        }
+    }
+}
+
+/// Definition of an attribute in a [`CredentialSchema`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AttributeDefinition {
+    /// Name of the attribute
+    pub name: Vec<u8>,
+    /// Type of the attribute value
+    pub value_type: AttributeType,
+    /// True if the attribute must be present
+    pub required: bool,
+    /// Constraints on the attribute value
+    pub constraints: Vec<AttributeConstraint>,
+}
+
+/// Definition of the attributes expected in the credentials having a given
+/// [`CredentialSchemaIdentifier`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CredentialSchema {
+    id: CredentialSchemaIdentifier,
+    attributes: Vec<AttributeDefinition>,
+    allow_additional_attributes: bool,
+}
+
+impl CredentialSchema {
+    /// Create a schema without any attribute
+    pub fn new(id: CredentialSchemaIdentifier) -> Self {
+        Self {
+            id,
+            attributes: Vec::new(),
+            allow_additional_attributes: false,
+        }
+    }
+
+    /// Identifier of the schema
+    pub fn id(&self) -> &CredentialSchemaIdentifier {
+        &self.id
+    }
+
+    /// Attributes defined by the schema
+    pub fn attributes(&self) -> &[AttributeDefinition] {
+        &self.attributes
+    }
+
+    /// Add an attribute which must be present
+    pub fn with_required_attribute(
+        self,
+        name: impl Into<Vec<u8>>,
+        value_type: AttributeType,
+    ) -> Self {
+        self.with_attribute(name, value_type, true)
+    }
+
+    /// Add an attribute which can be absent
+    pub fn with_optional_attribute(
+        self,
+        name: impl Into<Vec<u8>>,
+        value_type: AttributeType,
+    ) -> Self {
+        self.with_attribute(name, value_type, false)
+    }
+
+    /// Add a constraint on the value of an attribute which was previously added
+    pub fn with_constraint(
+        mut self,
+        name: impl Into<Vec<u8>>,
+        constraint: AttributeConstraint,
+    ) -> Self {
+        let name = name.into();
+        if let Some(definition) = self.attributes.iter_mut().find(|a| a.name == name) {
+            definition.constraints.push(constraint);
+        }
+        self
+    }
+
+    /// Accept attributes which are not defined by the schema
+    pub fn with_additional_attributes(mut self) -> Self {
+        self.allow_additional_attributes = true;
+        self
+    }
+
+    fn with_attribute(
+        mut self,
+        name: impl Into<Vec<u8>>,
+        value_type: AttributeType,
+        required: bool,
+    ) -> Self {
+        self.attributes.push(AttributeDefinition {
+            name: name.into(),
+            value_type,
+            required,
+            constraints: Vec::new(),
+        });
+        self
+    }
+
+    /// Check that the attributes of a credential are valid for this schema.
+    ///
+    /// For a credential supporting selective disclosure, only the disclosed attributes are
+    /// checked: required attributes may be missing
+    pub fn validate(&self, attributes: &Attributes) -> Result<()> {
+        if attributes.schema != self.id {
+            return Err(Self::error(format!(
+                "expected the schema {}, got {}",
+                self.id.0, attributes.schema.0
+            )));
+        }
+
+        for definition in self.attributes.iter() {
+            let name = String::from_utf8_lossy(&definition.name);
+            let value = match attributes.get_attribute(&definition.name) {
+                Some(value) => value,
+                None if definition.required && attributes.commitments.is_none() => {
+                    return Err(Self::error(format!("the attribute {name} is missing")));
+                }
+                None => continue,
+            };
+            let value = definition.value_type.coerce(value).ok_or_else(|| {
+                Self::error(format!(
+                    "the attribute {name} must have the type {:?}",
+                    definition.value_type
+                ))
+            })?;
+            if let Some(constraint) = definition.constraints.iter().find(|c| !c.check(&value)) {
+                return Err(Self::error(format!(
+                    "the attribute {name} doesn't satisfy the constraint {constraint:?}"
+                )));
+            }
+        }
+
+        if !self.allow_additional_attributes {
+            if let Some(key) = attributes
+                .map
+                .keys()
+                .find(|key| !self.attributes.iter().any(|a| a.name == key.as_slice()))
+            {
+                return Err(Self::error(format!(
+                    "the attribute {} is not defined by the schema {}",
+                    String::from_utf8_lossy(key),
+                    self.id.0
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn error(message: String) -> Error {
+        Error::new(Origin::Identity, Kind::Invalid, message)
+    }
+}
+
+/// Registry of the [`CredentialSchema`]s used to validate the attributes of the issued and verified
+/// credentials. Credentials with a schema which is not registered are not validated.
+#[derive(Default)]
+pub struct CredentialSchemas {
+    schemas: RwLock<BTreeMap<u64, CredentialSchema>>,
+}
+
+impl CredentialSchemas {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a schema, replacing the schema with the same identifier (if any)
+    pub fn register(&self, schema: CredentialSchema) {
+        self.schemas.write().unwrap().insert(schema.id.0, schema);
+    }
+
+    /// Unregister a schema
+    pub fn unregister(&self, id: &CredentialSchemaIdentifier) {
+        self.schemas.write().unwrap().remove(&id.0);
+    }
+
+    /// Return a registered schema
+    pub fn get(&self, id: &CredentialSchemaIdentifier) -> Option<CredentialSchema> {
+        self.schemas.read().unwrap().get(&id.0).cloned()
+    }
+
+    /// Validate attributes against their registered schema, if there is one
+    pub fn validate(&self, attributes: &Attributes) -> Result<()> {
+        match self.schemas.read().unwrap().get(&attributes.schema.0) {
+            Some(schema) => schema.validate(attributes),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::AttributesBuilder;
+
+    #[test]
+    fn test_validate_attributes() {
+        let schema = CredentialSchema::new(CredentialSchemaIdentifier(1))
+            .with_required_attribute("name", AttributeType::String)
+            .with_required_attribute("age", AttributeType::Int)
+            .with_constraint("age", AttributeConstraint::Range { min: 0, max: 150 })
+            .with_optional_attribute("roles", AttributeType::StringList)
+            .with_constraint(
+                "roles",
+                AttributeConstraint::OneOf(vec!["reader".into(), "writer".into()]),
+            );
+        let builder = || {
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(1))
+                .with_attribute("name", "client")
+        };
+
+        // valid attributes, typed or not
+        let attributes = builder().with_typed_attribute("age", 42i64).build();
+        assert!(schema.validate(&attributes).is_ok());
+        let attributes = builder()
+            .with_attribute("age", "42")
+            .with_attribute("roles", "reader,writer")
+            .build();
+        assert!(schema.validate(&attributes).is_ok());
+
+        // missing attribute
+        assert!(schema.validate(&builder().build()).is_err());
+        // wrong type
+        let attributes = builder().with_typed_attribute("age", true).build();
+        assert!(schema.validate(&attributes).is_err());
+        // unsatisfied constraints
+        let attributes = builder().with_typed_attribute("age", 200i64).build();
+        assert!(schema.validate(&attributes).is_err());
+        let attributes = builder()
+            .with_typed_attribute("age", 42i64)
+            .with_attribute("roles", "admin")
+            .build();
+        assert!(schema.validate(&attributes).is_err());
+        // additional attribute
+        let attributes = builder()
+            .with_typed_attribute("age", 42i64)
+            .with_attribute("other", "value")
+            .build();
+        assert!(schema.validate(&attributes).is_err());
+        assert!(schema
+            .clone()
+            .with_additional_attributes()
+            .validate(&attributes)
+            .is_ok());
+
+        // only credentials with a registered schema are validated
+        let schemas = CredentialSchemas::new();
+        assert!(schemas.validate(&builder().build()).is_ok());
+        schemas.register(schema);
+        assert!(schemas.validate(&builder().build()).is_err());
+    }
+}
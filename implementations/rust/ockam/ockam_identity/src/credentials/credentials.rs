@@ -1,10 +1,15 @@
+use ockam_core::compat::collections::BTreeMap;
 use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
 use ockam_vault::{VaultForSigning, VaultForVerifyingSignatures};
 
-use crate::models::{CredentialData, PurposeKeyAttestationData};
+use crate::models::{AttributeValue, CredentialData, PurposeKeyAttestationData};
+use crate::utils::add_seconds;
+use crate::TimestampInSeconds;
 use crate::{
-    CredentialsCreation, CredentialsVerification, IdentitiesCreation, IdentityAttributesRepository,
-    PurposeKeys,
+    CredentialIssuancePolicies, CredentialSchemas, CredentialsAuditSink, CredentialsCreation,
+    CredentialsVerification, IdentitiesCreation, IdentityAttributesRepository, PurposeKeys,
+    Revocations, RevocationsRepository, VerificationBundles,
 };
 
 /// Structure with both [`CredentialData`] and [`PurposeKeyAttestationData`] that we get
@@ -17,6 +22,53 @@ pub struct CredentialAndPurposeKeyData {
     pub purpose_key_data: PurposeKeyAttestationData,
 }
 
+impl CredentialAndPurposeKeyData {
+    /// Return the typed value of a subject attribute
+    pub fn get_attribute(&self, key: &[u8]) -> Option<AttributeValue> {
+        self.credential_data.subject_attributes.get_attribute(key)
+    }
+
+    /// Return the typed values of all the subject attributes
+    pub fn get_attributes(&self) -> BTreeMap<Vec<u8>, AttributeValue> {
+        self.credential_data.subject_attributes.get_attributes()
+    }
+
+    /// Return the expiration time of a subject attribute: either the expiration time of the
+    /// credential or an earlier time if the attribute has its own time to live
+    pub fn get_attribute_expires_at(&self, key: &[u8]) -> TimestampInSeconds {
+        let expires_at = self.credential_data.expires_at;
+        self.credential_data
+            .subject_attributes
+            .get_ttl(key)
+            .map(|ttl| add_seconds(&self.credential_data.created_at, ttl))
+            .filter(|attribute_expires_at| *attribute_expires_at < expires_at)
+            .unwrap_or(expires_at)
+    }
+
+    /// Return the expiration times of the subject attributes which expire before the credential
+    pub fn get_attributes_expires_at(&self) -> BTreeMap<Vec<u8>, TimestampInSeconds> {
+        self.credential_data
+            .subject_attributes
+            .ttls
+            .iter()
+            .flatten()
+            .map(|(key, _)| (key.to_vec(), self.get_attribute_expires_at(key)))
+            .filter(|(_, expires_at)| *expires_at < self.credential_data.expires_at)
+            .collect()
+    }
+
+    /// Return the typed values of the subject attributes which are still valid at a given time
+    pub fn get_valid_attributes_at(
+        &self,
+        now: TimestampInSeconds,
+    ) -> BTreeMap<Vec<u8>, AttributeValue> {
+        self.get_attributes()
+            .into_iter()
+            .filter(|(key, _)| now < self.get_attribute_expires_at(key))
+            .collect()
+    }
+}
+
 /// Service for managing [`Credential`]s
 pub struct Credentials {
     credential_vault: Arc<dyn VaultForSigning>,
@@ -24,6 +76,10 @@ pub struct Credentials {
     purpose_keys: Arc<PurposeKeys>,
     identities_creation: Arc<IdentitiesCreation>,
     identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
+    revocations_repository: Option<Arc<dyn RevocationsRepository>>,
+    credential_schemas: Arc<CredentialSchemas>,
+    issuance_policies: Arc<CredentialIssuancePolicies>,
+    audit_sink: Option<Arc<dyn CredentialsAuditSink>>,
 }
 
 impl Credentials {
@@ -34,6 +90,10 @@ impl Credentials {
         purpose_keys: Arc<PurposeKeys>,
         identities_creation: Arc<IdentitiesCreation>,
         identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
+        revocations_repository: Option<Arc<dyn RevocationsRepository>>,
+        credential_schemas: Arc<CredentialSchemas>,
+        issuance_policies: Arc<CredentialIssuancePolicies>,
+        audit_sink: Option<Arc<dyn CredentialsAuditSink>>,
     ) -> Self {
         Self {
             credential_vault,
@@ -41,6 +101,10 @@ impl Credentials {
             purpose_keys,
             identities_creation,
             identity_attributes_repository,
+            revocations_repository,
+            credential_schemas,
+            issuance_policies,
+            audit_sink,
         }
     }
 
@@ -56,6 +120,9 @@ impl Credentials {
             self.credential_vault.clone(),
             self.verifying_vault.clone(),
             self.identities_creation.clone(),
+            self.credential_schemas.clone(),
+            self.issuance_policies.clone(),
+            self.audit_sink.clone(),
         ))
     }
 
@@ -65,8 +132,48 @@ impl Credentials {
             self.purpose_keys.purpose_keys_verification(),
             self.verifying_vault.clone(),
             self.identity_attributes_repository.clone(),
+            self.revocations_repository.clone(),
+            self.credential_schemas.clone(),
+            self.audit_sink.clone(),
         ))
     }
+
+    /// Return the registry of [`CredentialSchema`](crate::CredentialSchema)s used to validate
+    /// the attributes of issued and verified credentials
+    pub fn credential_schemas(&self) -> Arc<CredentialSchemas> {
+        self.credential_schemas.clone()
+    }
+
+    /// Return the registry of [`CredentialIssuancePolicy`](crate::CredentialIssuancePolicy)s
+    /// enforced when credentials are issued
+    pub fn issuance_policies(&self) -> Arc<CredentialIssuancePolicies> {
+        self.issuance_policies.clone()
+    }
+
+    /// Return [`VerificationBundles`], to export the data needed to verify credentials offline,
+    /// or to load it
+    pub fn verification_bundles(&self) -> Arc<VerificationBundles> {
+        Arc::new(VerificationBundles::new(
+            self.identities_creation.clone(),
+            self.purpose_keys.clone(),
+            self.credential_vault.clone(),
+            self.verifying_vault.clone(),
+            self.revocations(),
+        ))
+    }
+
+    /// Return [`Revocations`] if a repository is configured to store them.
+    /// In that case credentials are checked for revocation when they are verified
+    pub fn revocations(&self) -> Option<Arc<Revocations>> {
+        self.revocations_repository.as_ref().map(|repository| {
+            Arc::new(Revocations::new(
+                repository.clone(),
+                self.purpose_keys.clone(),
+                self.credential_vault.clone(),
+                self.verifying_vault.clone(),
+            ))
+        })
+    }
 }
 
 #[cfg(test)]
@@ -79,8 +186,13 @@ mod tests {
     use ockam_core::Result;
 
     use crate::identities::identities;
-    use crate::models::CredentialSchemaIdentifier;
-    use crate::Attributes;
+    use crate::models::{AttributeValue, CredentialSchemaIdentifier};
+    use crate::utils::add_seconds;
+    use crate::utils::AttributesBuilder;
+    use crate::{
+        AttributeType, Attributes, AuthoritiesPolicy, CredentialIssuancePolicy, CredentialSchema,
+        IdentityError,
+    };
 
     #[tokio::test]
     async fn test_issue_credential() -> Result<()> {
@@ -96,6 +208,9 @@ mod tests {
         let subject_attributes = Attributes {
             schema: CredentialSchemaIdentifier(1),
             map,
+            typed_map: None,
+            ttls: None,
+            commitments: None,
         };
 
         let credential = credentials
@@ -117,4 +232,296 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_typed_attributes() -> Result<()> {
+        let identities = identities().await?;
+        let creation = identities.identities_creation();
+
+        let issuer = creation.create_identity().await?;
+        let subject = creation.create_identity().await?;
+        let credentials = identities.credentials();
+
+        let subject_attributes = AttributesBuilder::with_schema(CredentialSchemaIdentifier(1))
+            .with_attribute("name", "client")
+            .with_typed_attribute("age", 42i64)
+            .with_typed_attribute("admin", true)
+            .with_typed_attribute("roles", vec!["reader".to_string(), "writer".to_string()])
+            .build();
+
+        let credential = credentials
+            .credentials_creation()
+            .issue_credential(
+                &issuer,
+                &subject,
+                subject_attributes,
+                Duration::from_secs(60),
+            )
+            .await?;
+
+        let data = credentials
+            .credentials_verification()
+            .verify_credential(Some(&subject), &[issuer.clone()], &credential)
+            .await?;
+
+        assert_eq!(
+            data.get_attribute(b"name"),
+            Some(AttributeValue::String("client".to_string()))
+        );
+        assert_eq!(data.get_attribute(b"age"), Some(AttributeValue::Int(42)));
+        assert_eq!(
+            data.get_attribute(b"admin"),
+            Some(AttributeValue::Bool(true))
+        );
+        assert_eq!(
+            data.get_attribute(b"roles")
+                .as_ref()
+                .and_then(|v| v.as_string_list()),
+            Some(["reader".to_string(), "writer".to_string()].as_slice())
+        );
+        assert_eq!(data.get_attribute(b"unknown"), None);
+        assert_eq!(data.get_attributes().len(), 4);
+
+        // typed values are also available as bytes for the verifiers which don't know their types
+        let map = &data.credential_data.subject_attributes.map;
+        assert_eq!(
+            map.get(&ByteVec::from(b"age".to_vec())).map(|v| v.to_vec()),
+            Some(b"42".to_vec())
+        );
+        assert_eq!(
+            map.get(&ByteVec::from(b"roles".to_vec()))
+                .map(|v| v.to_vec()),
+            Some(b"reader,writer".to_vec())
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_credential_schema_validation() -> Result<()> {
+        let identities = identities().await?;
+        let creation = identities.identities_creation();
+
+        let issuer = creation.create_identity().await?;
+        let subject = creation.create_identity().await?;
+        let credentials = identities.credentials();
+        let schema_id = CredentialSchemaIdentifier(1);
+
+        let credential = credentials
+            .credentials_creation()
+            .issue_credential(
+                &issuer,
+                &subject,
+                AttributesBuilder::with_schema(schema_id.clone())
+                    .with_attribute("name", "client")
+                    .build(),
+                Duration::from_secs(60),
+            )
+            .await?;
+
+        credentials.credential_schemas().register(
+            CredentialSchema::new(schema_id.clone())
+                .with_required_attribute("name", AttributeType::String)
+                .with_required_attribute("age", AttributeType::Int),
+        );
+
+        // A credential missing a required attribute can't be issued
+        let result = credentials
+            .credentials_creation()
+            .issue_credential(
+                &issuer,
+                &subject,
+                AttributesBuilder::with_schema(schema_id.clone())
+                    .with_attribute("name", "client")
+                    .build(),
+                Duration::from_secs(60),
+            )
+            .await;
+        assert!(result.is_err());
+
+        // nor verified
+        let result = credentials
+            .credentials_verification()
+            .verify_credential(Some(&subject), &[issuer.clone()], &credential)
+            .await;
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_issuance_policy() -> Result<()> {
+        let identities = identities().await?;
+        let creation = identities.identities_creation();
+
+        let issuer = creation.create_identity().await?;
+        let other_issuer = creation.create_identity().await?;
+        let subject = creation.create_identity().await?;
+        let credentials = identities.credentials();
+        credentials.issuance_policies().set_policy(
+            &issuer,
+            CredentialIssuancePolicy::new().with_max_ttl(Duration::from_secs(3600)),
+        );
+        let attributes = AttributesBuilder::with_schema(CredentialSchemaIdentifier(1))
+            .with_attribute("name", "client")
+            .build();
+
+        // A long-lived credential is denied by the policy of the issuer
+        let err = credentials
+            .credentials_creation()
+            .issue_credential(
+                &issuer,
+                &subject,
+                attributes.clone(),
+                Duration::from_secs(7200),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            IdentityError::from_error(&err),
+            Some(IdentityError::CredentialIssuanceDenied)
+        ));
+
+        // but a short-lived one can be issued
+        credentials
+            .credentials_creation()
+            .issue_credential(
+                &issuer,
+                &subject,
+                attributes.clone(),
+                Duration::from_secs(60),
+            )
+            .await?;
+
+        // Other issuers are not constrained
+        credentials
+            .credentials_creation()
+            .issue_credential(
+                &other_issuer,
+                &subject,
+                attributes,
+                Duration::from_secs(7200),
+            )
+            .await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_verify_credentials_with_quorum() -> Result<()> {
+        let identities = identities().await?;
+        let creation = identities.identities_creation();
+
+        let authority1 = creation.create_identity().await?;
+        let authority2 = creation.create_identity().await?;
+        let authority3 = creation.create_identity().await?;
+        let subject = creation.create_identity().await?;
+        let credentials = identities.credentials();
+
+        let mut presented = vec![];
+        for authority in [&authority1, &authority2] {
+            presented.push(
+                credentials
+                    .credentials_creation()
+                    .issue_credential(
+                        authority,
+                        &subject,
+                        AttributesBuilder::with_schema(CredentialSchemaIdentifier(1))
+                            .with_attribute("name", "client")
+                            .build(),
+                        Duration::from_secs(60),
+                    )
+                    .await?,
+            );
+        }
+
+        let verification = credentials.credentials_verification();
+        let authorities = vec![authority1.clone(), authority2.clone(), authority3.clone()];
+
+        // 2 of 3 authorities issued a credential
+        let policy = AuthoritiesPolicy::quorum(authorities.clone(), 2);
+        let verified = verification
+            .verify_credentials_with_policy(&subject, &policy, &presented)
+            .await?;
+        assert_eq!(verified.len(), 2);
+
+        // but not 3 of 3
+        let policy = AuthoritiesPolicy::all_of(authorities.clone());
+        assert!(verification
+            .verify_credentials_with_policy(&subject, &policy, &presented)
+            .await
+            .is_err());
+
+        // and the third authority didn't endorse the subject
+        let policy = AuthoritiesPolicy::any_of(authorities).with_required_authority(authority3);
+        assert!(verification
+            .verify_credentials_with_policy(&subject, &policy, &presented)
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_attribute_ttl() -> Result<()> {
+        let identities = identities().await?;
+        let creation = identities.identities_creation();
+
+        let issuer = creation.create_identity().await?;
+        let subject = creation.create_identity().await?;
+        let credentials = identities.credentials();
+
+        let credential = credentials
+            .credentials_creation()
+            .issue_credential(
+                &issuer,
+                &subject,
+                AttributesBuilder::with_schema(CredentialSchemaIdentifier(1))
+                    .with_attribute("name", "client")
+                    .with_typed_attribute("on-call", true)
+                    .with_attribute_ttl("on-call", Duration::from_secs(10))
+                    .build(),
+                Duration::from_secs(3600),
+            )
+            .await?;
+
+        let data = credentials
+            .credentials_verification()
+            .verify_credential(Some(&subject), &[issuer.clone()], &credential)
+            .await?;
+        let created_at = data.credential_data.created_at;
+        assert_eq!(
+            data.get_attribute_expires_at(b"on-call"),
+            add_seconds(&created_at, 10)
+        );
+        assert_eq!(
+            data.get_attribute_expires_at(b"name"),
+            data.credential_data.expires_at
+        );
+        assert_eq!(data.get_valid_attributes_at(created_at).len(), 2);
+        let later = add_seconds(&created_at, 20);
+        assert_eq!(
+            data.get_valid_attributes_at(later)
+                .keys()
+                .collect::<Vec<_>>(),
+            vec![&b"name".to_vec()]
+        );
+
+        // the expiration time of the attribute is stored with the subject attributes
+        credentials
+            .credentials_verification()
+            .receive_presented_credential(&subject, &[issuer], &credential)
+            .await?;
+        let entry = identities
+            .identity_attributes_repository()
+            .get_attributes(&subject)
+            .await?
+            .unwrap();
+        assert_eq!(
+            entry.attr_expires(b"on-call"),
+            Some(add_seconds(&created_at, 10))
+        );
+        assert_eq!(entry.valid_attrs_at(later).len(), 1);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,144 @@
+use core::fmt;
+use core::fmt::Formatter;
+use tracing::{info, warn};
+
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::string::{String, ToString};
+use ockam_core::compat::sync::Arc;
+use ockam_core::{async_trait, Result};
+use ockam_vault::VaultForVerifyingSignatures;
+
+use crate::models::{Attributes, CredentialSchemaIdentifier, Identifier};
+use crate::TimestampInSeconds;
+
+/// Operation performed with the [`Credentials`](crate::Credentials) API
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CredentialOperation {
+    /// A credential was issued
+    Issuance,
+    /// A credential was verified
+    Verification,
+}
+
+/// Outcome of a [`CredentialOperation`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CredentialAuditOutcome {
+    /// The operation succeeded
+    Success,
+    /// The operation failed, with the reason of the failure
+    Failure(String),
+}
+
+/// Audit record describing who got what credential, and when
+///
+/// For a failed verification, the issuer, schema and attributes hash are only set
+/// if they could be read from the credential, and they might not be authentic.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CredentialAuditRecord {
+    /// Issuance or verification
+    pub operation: CredentialOperation,
+    /// Issuer of the credential
+    pub issuer: Option<Identifier>,
+    /// Subject of the credential
+    pub subject: Option<Identifier>,
+    /// Schema of the credential attributes
+    pub schema: Option<CredentialSchemaIdentifier>,
+    /// SHA-256 hash of the CBOR encoding of the credential attributes
+    pub attributes_hash: Option<[u8; 32]>,
+    /// Success or failure of the operation
+    pub outcome: CredentialAuditOutcome,
+    /// Time of the operation
+    pub timestamp: TimestampInSeconds,
+}
+
+impl fmt::Display for CredentialAuditRecord {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let or_unknown = |i: &Option<Identifier>| {
+            i.as_ref()
+                .map(|i| i.to_string())
+                .unwrap_or_else(|| "unknown".into())
+        };
+        write!(
+            f,
+            "operation={:?} issuer={} subject={} schema={} attributes_hash={} outcome={:?} timestamp={}",
+            self.operation,
+            or_unknown(&self.issuer),
+            or_unknown(&self.subject),
+            self.schema
+                .as_ref()
+                .map(|s| s.0.to_string())
+                .unwrap_or_else(|| "unknown".into()),
+            self.attributes_hash
+                .map(hex::encode)
+                .unwrap_or_else(|| "unknown".into()),
+            self.outcome,
+            self.timestamp.0
+        )
+    }
+}
+
+/// Destination of the [`CredentialAuditRecord`]s emitted by the [`Credentials`](crate::Credentials) API,
+/// for example a file, a database or a log collector
+#[async_trait]
+pub trait CredentialsAuditSink: Send + Sync + 'static {
+    /// Store an audit record
+    async fn record(&self, record: CredentialAuditRecord) -> Result<()>;
+}
+
+/// Audit sink logging the records with `tracing`, with the `ockam::audit::credentials` target.
+/// The fields of the record are also added as fields of the event
+pub struct TracingCredentialsAuditSink;
+
+#[async_trait]
+impl CredentialsAuditSink for TracingCredentialsAuditSink {
+    async fn record(&self, record: CredentialAuditRecord) -> Result<()> {
+        let event = match (&record.operation, &record.outcome) {
+            (CredentialOperation::Issuance, CredentialAuditOutcome::Success) => "credential_issued",
+            (CredentialOperation::Issuance, CredentialAuditOutcome::Failure(_)) => {
+                "credential_issuance_failed"
+            }
+            (CredentialOperation::Verification, CredentialAuditOutcome::Success) => {
+                "credential_verified"
+            }
+            (CredentialOperation::Verification, CredentialAuditOutcome::Failure(_)) => {
+                "credential_rejected"
+            }
+        };
+        let reason = match &record.outcome {
+            CredentialAuditOutcome::Success => None,
+            CredentialAuditOutcome::Failure(reason) => Some(reason.as_str()),
+        };
+        info!(
+            target: "ockam::audit::credentials",
+            event,
+            issuer = record.issuer.as_ref().map(|i| i.to_string()),
+            subject = record.subject.as_ref().map(|i| i.to_string()),
+            schema = record.schema.as_ref().map(|s| s.0),
+            reason,
+            "{record}"
+        );
+        Ok(())
+    }
+}
+
+/// Hash the attributes of a credential for an audit record
+pub(crate) async fn hash_attributes(
+    verifying_vault: &Arc<dyn VaultForVerifyingSignatures>,
+    attributes: &Attributes,
+) -> Option<[u8; 32]> {
+    let encoded = minicbor::to_vec(attributes).ok()?;
+    verifying_vault.sha256(&encoded).await.ok().map(|h| h.0)
+}
+
+/// Send a record to the audit sink, if there is one.
+/// A failure to store the record is logged but doesn't fail the audited operation
+pub(crate) async fn audit(
+    audit_sink: &Option<Arc<dyn CredentialsAuditSink>>,
+    record: CredentialAuditRecord,
+) {
+    if let Some(audit_sink) = audit_sink {
+        if let Err(err) = audit_sink.record(record).await {
+            warn!("Cannot store a credential audit record: {err}");
+        }
+    }
+}
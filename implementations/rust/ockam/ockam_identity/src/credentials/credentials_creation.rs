@@ -1,12 +1,22 @@
 use core::time::Duration;
 
+use ockam_core::compat::rand::{thread_rng, RngCore};
+use ockam_core::compat::string::{String, ToString};
 use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
 use ockam_core::Result;
 use ockam_vault::{VaultForSigning, VaultForVerifyingSignatures};
 
-use crate::models::{Attributes, Credential, CredentialAndPurposeKey, CredentialData, Identifier};
-use crate::utils::{add_seconds, now};
-use crate::{IdentitiesCreation, PurposeKeyCreation};
+use crate::credentials::credentials_audit::{audit, hash_attributes};
+use crate::models::{
+    AttributeDisclosure, Attributes, Credential, CredentialAndPurposeKey, CredentialData,
+    DisclosableCredential, Identifier, DELEGATED_ATTRIBUTES_KEY, DELEGATION_SCHEMA_IDENTIFIER,
+};
+use crate::utils::{add_seconds, now, AttributesBuilder};
+use crate::{
+    CredentialAuditOutcome, CredentialAuditRecord, CredentialIssuancePolicies, CredentialOperation,
+    CredentialSchemas, CredentialsAuditSink, IdentitiesCreation, PurposeKeyCreation,
+};
 
 /// Service for managing [`Credential`]s
 pub struct CredentialsCreation {
@@ -14,6 +24,9 @@ pub struct CredentialsCreation {
     credential_vault: Arc<dyn VaultForSigning>,
     verifying_vault: Arc<dyn VaultForVerifyingSignatures>,
     identities_creation: Arc<IdentitiesCreation>,
+    credential_schemas: Arc<CredentialSchemas>,
+    issuance_policies: Arc<CredentialIssuancePolicies>,
+    audit_sink: Option<Arc<dyn CredentialsAuditSink>>,
 }
 
 impl CredentialsCreation {
@@ -23,18 +36,27 @@ impl CredentialsCreation {
         credential_vault: Arc<dyn VaultForSigning>,
         verifying_vault: Arc<dyn VaultForVerifyingSignatures>,
         identities_creation: Arc<IdentitiesCreation>,
+        credential_schemas: Arc<CredentialSchemas>,
+        issuance_policies: Arc<CredentialIssuancePolicies>,
+        audit_sink: Option<Arc<dyn CredentialsAuditSink>>,
     ) -> Self {
         Self {
             purpose_keys_creation,
             verifying_vault,
             credential_vault,
             identities_creation,
+            credential_schemas,
+            issuance_policies,
+            audit_sink,
         }
     }
 }
 
 impl CredentialsCreation {
     /// Issue a [`Credential`]
+    ///
+    /// The credential is not issued if its attributes or its time to live don't satisfy the
+    /// [`CredentialIssuancePolicy`](crate::CredentialIssuancePolicy) of the issuer
     pub async fn issue_credential(
         &self,
         issuer: &Identifier,
@@ -42,6 +64,119 @@ impl CredentialsCreation {
         subject_attributes: Attributes,
         ttl: Duration,
     ) -> Result<CredentialAndPurposeKey> {
+        let result = self
+            .create_credential(
+                issuer,
+                subject,
+                &subject_attributes,
+                subject_attributes.clone(),
+                ttl,
+            )
+            .await;
+        self.audit_issuance(issuer, subject, &subject_attributes, &result)
+            .await?;
+        result
+    }
+
+    /// Issue a [`Credential`] supporting selective disclosure.
+    ///
+    /// The credential only contains a salted digest of each attribute. The returned
+    /// [`DisclosableCredential`] contains the disclosures of all the attributes, which are
+    /// sent to the subject only. The subject can then reveal a subset of the attributes to a
+    /// verifier with a [`CredentialPresentation`](crate::models::CredentialPresentation).
+    ///
+    /// The keys of the attributes having their own time to live are not hidden.
+    pub async fn issue_disclosable_credential(
+        &self,
+        issuer: &Identifier,
+        subject: &Identifier,
+        subject_attributes: Attributes,
+        ttl: Duration,
+    ) -> Result<DisclosableCredential> {
+        let mut disclosures = Vec::new();
+        let mut commitments = Vec::new();
+        for (key, value) in subject_attributes.map.iter() {
+            let mut salt = [0u8; 16];
+            thread_rng().fill_bytes(&mut salt);
+            let disclosure = AttributeDisclosure {
+                salt: salt.to_vec().into(),
+                key: key.clone(),
+                value: value.clone(),
+                typed_value: subject_attributes
+                    .typed_map
+                    .as_ref()
+                    .and_then(|m| m.get(key).cloned()),
+            };
+            commitments.push(disclosure.digest(&*self.verifying_vault).await?.into());
+            disclosures.push(disclosure);
+        }
+        // the digests are sorted so that their order doesn't reveal the attribute keys
+        commitments.sort();
+
+        let committed_attributes = Attributes {
+            schema: subject_attributes.schema.clone(),
+            map: Default::default(),
+            typed_map: None,
+            ttls: subject_attributes.ttls.clone(),
+            commitments: Some(commitments),
+        };
+        let result = self
+            .create_credential(
+                issuer,
+                subject,
+                &subject_attributes,
+                committed_attributes,
+                ttl,
+            )
+            .await;
+        self.audit_issuance(issuer, subject, &subject_attributes, &result)
+            .await?;
+        Ok(DisclosableCredential {
+            credential: result?,
+            disclosures,
+        })
+    }
+
+    /// Send an audit record for the issuance of a credential
+    async fn audit_issuance<T>(
+        &self,
+        issuer: &Identifier,
+        subject: &Identifier,
+        subject_attributes: &Attributes,
+        result: &Result<T>,
+    ) -> Result<()> {
+        if self.audit_sink.is_none() {
+            return Ok(());
+        }
+        let record = CredentialAuditRecord {
+            operation: CredentialOperation::Issuance,
+            issuer: Some(issuer.clone()),
+            subject: Some(subject.clone()),
+            schema: Some(subject_attributes.schema.clone()),
+            attributes_hash: hash_attributes(&self.verifying_vault, subject_attributes).await,
+            outcome: match result {
+                Ok(_) => CredentialAuditOutcome::Success,
+                Err(err) => CredentialAuditOutcome::Failure(err.to_string()),
+            },
+            timestamp: now()?,
+        };
+        audit(&self.audit_sink, record).await;
+        Ok(())
+    }
+
+    /// Check the attributes of a credential and sign the credential with the `signed_attributes`
+    async fn create_credential(
+        &self,
+        issuer: &Identifier,
+        subject: &Identifier,
+        subject_attributes: &Attributes,
+        signed_attributes: Attributes,
+        ttl: Duration,
+    ) -> Result<CredentialAndPurposeKey> {
+        self.issuance_policies
+            .check(issuer, subject_attributes, ttl)?;
+        self.credential_schemas.validate(subject_attributes)?;
+
         // TODO: Allow manual PurposeKey management
         let issuer_purpose_key = self
             .purpose_keys_creation
@@ -56,7 +191,7 @@ impl CredentialsCreation {
         let credential_data = CredentialData {
             subject: Some(subject.clone()),
             subject_latest_change_hash: Some(subject_identity.latest_change_hash()?.clone()),
-            subject_attributes,
+            subject_attributes: signed_attributes,
             created_at,
             expires_at,
         };
@@ -85,4 +220,24 @@ impl CredentialsCreation {
 
         Ok(res)
     }
+
+    /// Issue a delegation [`Credential`] authorizing `sub_issuer` to issue credentials
+    /// attesting the `delegated_attributes` only.
+    ///
+    /// The credentials issued by the sub-issuer must be presented with their delegation chain as a
+    /// [`DelegatedCredential`](crate::models::DelegatedCredential). The sub-issuer can itself delegate a subset of
+    /// the delegated attributes to another sub-issuer
+    pub async fn issue_delegation(
+        &self,
+        issuer: &Identifier,
+        sub_issuer: &Identifier,
+        delegated_attributes: Vec<String>,
+        ttl: Duration,
+    ) -> Result<CredentialAndPurposeKey> {
+        let attributes = AttributesBuilder::with_schema(DELEGATION_SCHEMA_IDENTIFIER)
+            .with_typed_attribute(DELEGATED_ATTRIBUTES_KEY, delegated_attributes)
+            .build();
+        self.issue_credential(issuer, sub_issuer, attributes, ttl)
+            .await
+    }
 }
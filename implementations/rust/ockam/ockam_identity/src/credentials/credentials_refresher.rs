@@ -0,0 +1,167 @@
+use core::cmp::max;
+use tracing::{debug, info, warn};
+
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::sync::Arc;
+use ockam_core::compat::time::Duration;
+use ockam_core::compat::vec::Vec;
+use ockam_core::{async_trait, Address, AllowAll, Any, Result, Routed, Worker};
+use ockam_node::{Context, DelayedEvent, WorkerBuilder};
+
+use crate::models::CredentialAndPurposeKey;
+use crate::secure_channel::EncryptorInternalMessage;
+use crate::utils::now;
+use crate::{
+    CredentialsRetriever, Identifier, SecureChannels, DEFAULT_MIN_REFRESH_CREDENTIAL_INTERVAL,
+    DEFAULT_REFRESH_CREDENTIAL_TIME_GAP,
+};
+
+/// Worker retrieving a new credential for an identity ahead of the expiration of the current one,
+/// and presenting it on all the secure channels already established by that identity.
+///
+/// This keeps long-lived secure channels authorized, even when they were created with a
+/// credential which can't be refreshed by the channel itself.
+pub struct CredentialsRefresher {
+    secure_channels: Arc<SecureChannels>,
+    identifier: Identifier,
+    retriever: Arc<dyn CredentialsRetriever>,
+    refresh_credential_time_gap: Duration,
+    min_credential_refresh_interval: Duration,
+    refresh_event: Option<DelayedEvent<()>>,
+}
+
+impl CredentialsRefresher {
+    /// Create a refresher for the credentials of `identifier`
+    pub fn new(
+        secure_channels: Arc<SecureChannels>,
+        identifier: Identifier,
+        retriever: Arc<dyn CredentialsRetriever>,
+    ) -> Self {
+        Self {
+            secure_channels,
+            identifier,
+            retriever,
+            refresh_credential_time_gap: DEFAULT_REFRESH_CREDENTIAL_TIME_GAP,
+            min_credential_refresh_interval: DEFAULT_MIN_REFRESH_CREDENTIAL_INTERVAL,
+            refresh_event: None,
+        }
+    }
+
+    /// Set the time interval before the credential expiration when a new credential is retrieved
+    pub fn with_refresh_credential_time_gap(
+        mut self,
+        refresh_credential_time_gap: Duration,
+    ) -> Self {
+        self.refresh_credential_time_gap = refresh_credential_time_gap;
+        self
+    }
+
+    /// Set the minimal interval between two retrievals, used when a retrieval fails
+    pub fn with_min_credential_refresh_interval(
+        mut self,
+        min_credential_refresh_interval: Duration,
+    ) -> Self {
+        self.min_credential_refresh_interval = min_credential_refresh_interval;
+        self
+    }
+
+    /// Start the refresher at the given address. A credential is retrieved immediately,
+    /// then every time the current credential is about to expire
+    pub async fn start(self, ctx: &Context, address: impl Into<Address>) -> Result<()> {
+        WorkerBuilder::new(self)
+            .with_address(address)
+            .with_incoming_access_control(AllowAll)
+            .with_outgoing_access_control(AllowAll)
+            .start(ctx)
+            .await
+    }
+
+    /// Retrieve a new credential, present it on the secure channels and schedule the next refresh
+    async fn refresh(&mut self, ctx: &Context) -> Result<()> {
+        let delay = match self.retriever.retrieve(ctx, &self.identifier).await {
+            Ok(credential) => {
+                self.present_credential(ctx, &credential).await?;
+                let expires_at = credential.get_credential_data()?.expires_at;
+                let refresh_at =
+                    expires_at.saturating_sub(self.refresh_credential_time_gap.as_secs());
+                let delay = Duration::from_secs(refresh_at.saturating_sub(*now()?));
+                // Avoid retrieving credentials in a loop if they are very short-lived
+                max(self.min_credential_refresh_interval, delay)
+            }
+            Err(err) => {
+                warn!(
+                    "Credentials refresh failed for {} and is rescheduled in {} seconds: {err}",
+                    self.identifier,
+                    self.min_credential_refresh_interval.as_secs()
+                );
+                self.min_credential_refresh_interval
+            }
+        };
+
+        debug!(
+            "Scheduling credentials refresh for {} in {} seconds",
+            self.identifier,
+            delay.as_secs()
+        );
+        let mut refresh_event = DelayedEvent::create(ctx, ctx.address(), ()).await?;
+        refresh_event.schedule(delay).await?;
+        self.refresh_event = Some(refresh_event);
+        Ok(())
+    }
+
+    /// Send the credential to the encryptor of each secure channel established by our identity
+    async fn present_credential(
+        &self,
+        ctx: &Context,
+        credential: &CredentialAndPurposeKey,
+    ) -> Result<()> {
+        let encoded = credential.encode_as_cbor_bytes()?;
+        let channels: Vec<_> = self
+            .secure_channels
+            .secure_channel_registry()
+            .get_channel_list()
+            .into_iter()
+            .filter(|c| c.my_id() == &self.identifier)
+            .collect();
+        for channel in channels.iter() {
+            if let Some(address) = channel.encryptor_internal_address() {
+                if let Err(err) = ctx
+                    .send(
+                        address.clone(),
+                        EncryptorInternalMessage::PresentCredential(encoded.clone()),
+                    )
+                    .await
+                {
+                    warn!(
+                        "Cannot present a new credential on the secure channel {}: {err}",
+                        channel.encryptor_messaging_address()
+                    );
+                }
+            }
+        }
+        info!(
+            "Presented a new credential for {} on {} secure channel(s)",
+            self.identifier,
+            channels.len()
+        );
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Worker for CredentialsRefresher {
+    type Message = Any;
+    type Context = Context;
+
+    async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        self.refresh(ctx).await
+    }
+
+    async fn handle_message(
+        &mut self,
+        ctx: &mut Self::Context,
+        _msg: Routed<Self::Message>,
+    ) -> Result<()> {
+        self.refresh(ctx).await
+    }
+}
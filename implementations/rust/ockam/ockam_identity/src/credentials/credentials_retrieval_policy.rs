@@ -0,0 +1,130 @@
+use ockam_core::compat::time::Duration;
+use ockam_node::DEFAULT_TIMEOUT;
+
+/// Strategy used to compute the delay between two attempts to retrieve a credential or to send
+/// a request with a [`SecureClient`](crate::SecureClient)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BackoffStrategy {
+    /// Always wait for the same delay
+    Fixed(Duration),
+    /// Multiply the delay by `multiplier` after each failed attempt, up to `max_delay`
+    Exponential {
+        /// Delay before the first retry
+        initial_delay: Duration,
+        /// Maximum delay between two attempts
+        max_delay: Duration,
+        /// Factor applied to the delay after each failed attempt
+        multiplier: u32,
+    },
+}
+
+impl BackoffStrategy {
+    /// Delay to wait before the retry number `retry` (starting at 1)
+    pub fn delay(&self, retry: u32) -> Duration {
+        match self {
+            BackoffStrategy::Fixed(delay) => *delay,
+            BackoffStrategy::Exponential {
+                initial_delay,
+                max_delay,
+                multiplier,
+            } => {
+                let mut delay = *initial_delay;
+                for _ in 1..retry {
+                    delay = delay.saturating_mul((*multiplier).max(1));
+                    if delay >= *max_delay {
+                        return *max_delay;
+                    }
+                }
+                delay.min(*max_delay)
+            }
+        }
+    }
+}
+
+/// Retry and timeout settings used by a [`RemoteCredentialsRetriever`](crate::RemoteCredentialsRetriever)
+///
+/// Only the attempts failing because the issuer can't be reached are retried. A request
+/// denied by the issuer fails immediately.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CredentialsRetrievalPolicy {
+    max_retries: u32,
+    backoff: BackoffStrategy,
+    attempt_timeout: Duration,
+}
+
+impl Default for CredentialsRetrievalPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            backoff: BackoffStrategy::Exponential {
+                initial_delay: Duration::from_secs(1),
+                max_delay: Duration::from_secs(30),
+                multiplier: 2,
+            },
+            attempt_timeout: DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+impl CredentialsRetrievalPolicy {
+    /// Default policy: a single attempt, with the default timeout
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of retries after the first failed attempt
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Strategy used to compute the delay between two attempts
+    pub fn with_backoff(mut self, backoff: BackoffStrategy) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Maximum duration of each attempt, including the creation of the secure channel
+    pub fn with_attempt_timeout(mut self, attempt_timeout: Duration) -> Self {
+        self.attempt_timeout = attempt_timeout;
+        self
+    }
+
+    /// Number of retries after the first failed attempt
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    /// Strategy used to compute the delay between two attempts
+    pub fn backoff(&self) -> &BackoffStrategy {
+        &self.backoff
+    }
+
+    /// Maximum duration of each attempt
+    pub fn attempt_timeout(&self) -> Duration {
+        self.attempt_timeout
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_delays() {
+        let fixed = BackoffStrategy::Fixed(Duration::from_millis(500));
+        assert_eq!(fixed.delay(1), Duration::from_millis(500));
+        assert_eq!(fixed.delay(10), Duration::from_millis(500));
+
+        let exponential = BackoffStrategy::Exponential {
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(1),
+            multiplier: 2,
+        };
+        assert_eq!(exponential.delay(1), Duration::from_millis(100));
+        assert_eq!(exponential.delay(2), Duration::from_millis(200));
+        assert_eq!(exponential.delay(4), Duration::from_millis(800));
+        assert_eq!(exponential.delay(5), Duration::from_secs(1));
+        assert_eq!(exponential.delay(100), Duration::from_secs(1));
+    }
+}
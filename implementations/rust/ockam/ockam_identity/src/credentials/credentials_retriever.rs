@@ -1,17 +1,26 @@
-use ockam_core::api::Request;
+use core::time::Duration;
+use ockam_core::api::{Reply, Request, Response};
 use serde::{Deserialize, Serialize};
-use tracing::debug;
 use tracing::trace;
+use tracing::{debug, warn};
 
 use ockam_core::compat::boxed::Box;
 
-use ockam_core::compat::sync::Arc;
-use ockam_core::{async_trait, Address, Result, Route};
-use ockam_node::{Context, DEFAULT_TIMEOUT};
+use ockam_core::compat::sync::{Arc, Mutex};
+use ockam_core::compat::vec::Vec;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{async_trait, route, Address, DenyAll, Error, Result, Route};
+use ockam_node::api::Client;
+use ockam_node::compat::timeout;
+use ockam_node::Context;
 use ockam_transport_core::Transport;
 
 use crate::models::CredentialAndPurposeKey;
-use crate::{Identifier, SecureChannels, SecureClient};
+use crate::utils::{add_seconds, now};
+use crate::{
+    CachedCredentialsRepository, CredentialsRetrievalPolicy, Identifier, IdentityError,
+    SecureChannel, SecureChannels, SecureClient, TimestampInSeconds,
+};
 
 /// Trait for retrieving a credential for a given identity
 #[async_trait]
@@ -50,11 +59,188 @@ impl CredentialsRetriever for CredentialsMemoryRetriever {
     }
 }
 
+/// Default time before the expiration of a cached credential when a new credential is retrieved
+pub const DEFAULT_CREDENTIAL_REFRESH_MARGIN: Duration = Duration::from_secs(5 * 60);
+
+/// Credentials retriever caching the credentials returned by another retriever.
+///
+/// The credentials are stored, per subject, in a [`CachedCredentialsRepository`] so that they
+/// can be reused until they expire, even after a restart. A new credential is retrieved when
+/// the cached one is about to expire. If that retrieval fails, the cached credential is used
+/// for as long as it is valid.
+pub struct CachingCredentialsRetriever {
+    retriever: Arc<dyn CredentialsRetriever>,
+    issuer: Identifier,
+    repository: Arc<dyn CachedCredentialsRepository>,
+    refresh_margin: Duration,
+}
+
+impl CachingCredentialsRetriever {
+    /// Create a new caching retriever for the credentials issued by `issuer`
+    pub fn new(
+        retriever: Arc<dyn CredentialsRetriever>,
+        issuer: Identifier,
+        repository: Arc<dyn CachedCredentialsRepository>,
+    ) -> Self {
+        Self {
+            retriever,
+            issuer,
+            repository,
+            refresh_margin: DEFAULT_CREDENTIAL_REFRESH_MARGIN,
+        }
+    }
+
+    /// Set the time before the expiration of a cached credential when a new credential is retrieved
+    pub fn with_refresh_margin(mut self, refresh_margin: Duration) -> Self {
+        self.refresh_margin = refresh_margin;
+        self
+    }
+}
+
+#[async_trait]
+impl CredentialsRetriever for CachingCredentialsRetriever {
+    async fn retrieve(
+        &self,
+        ctx: &Context,
+        for_identity: &Identifier,
+    ) -> Result<CredentialAndPurposeKey> {
+        let now = now()?;
+        let cached = self
+            .repository
+            .get_credential(&self.issuer, for_identity)
+            .await?;
+
+        if let Some((credential, expires_at)) = &cached {
+            if add_seconds(&now, self.refresh_margin.as_secs()) < *expires_at {
+                trace!("Using the cached credential of {for_identity}");
+                return Ok(credential.clone());
+            }
+        }
+
+        match self.retriever.retrieve(ctx, for_identity).await {
+            Ok(credential) => {
+                let expires_at = credential.get_credential_data()?.expires_at;
+                self.repository
+                    .put_credential(&self.issuer, for_identity, expires_at, &credential)
+                    .await?;
+                Ok(credential)
+            }
+            Err(err) => match cached {
+                Some((credential, expires_at)) if now < expires_at => {
+                    warn!("Cannot refresh the credential of {for_identity}, using the cached one: {err}");
+                    Ok(credential)
+                }
+                _ => Err(err),
+            },
+        }
+    }
+}
+
+/// Credentials retriever trying an ordered list of retrievers, for example from memory,
+/// then from a file, then from a remote authority, and returning the first valid credential.
+///
+/// A retriever is skipped when it fails, when it returns an expired credential or when it
+/// doesn't answer before its timeout, if it has one. This allows a node to operate offline
+/// with cached credentials and to only contact the authority when it is necessary.
+#[derive(Default)]
+pub struct ChainedCredentialsRetriever {
+    retrievers: Vec<(Arc<dyn CredentialsRetriever>, Option<Duration>)>,
+}
+
+impl ChainedCredentialsRetriever {
+    /// Create an empty chain of retrievers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a retriever at the end of the chain
+    pub fn with_retriever(mut self, retriever: Arc<dyn CredentialsRetriever>) -> Self {
+        self.retrievers.push((retriever, None));
+        self
+    }
+
+    /// Add a retriever at the end of the chain. The next retriever is used if this one
+    /// doesn't return a credential before the timeout
+    pub fn with_retriever_and_timeout(
+        mut self,
+        retriever: Arc<dyn CredentialsRetriever>,
+        timeout: Duration,
+    ) -> Self {
+        self.retrievers.push((retriever, Some(timeout)));
+        self
+    }
+}
+
+#[async_trait]
+impl CredentialsRetriever for ChainedCredentialsRetriever {
+    async fn retrieve(
+        &self,
+        ctx: &Context,
+        for_identity: &Identifier,
+    ) -> Result<CredentialAndPurposeKey> {
+        let mut last_error = None;
+        for (index, (retriever, retriever_timeout)) in self.retrievers.iter().enumerate() {
+            let result = match retriever_timeout {
+                Some(t) => timeout(*t, retriever.retrieve(ctx, for_identity))
+                    .await
+                    .map_err(|e| Error::new(Origin::Api, Kind::Timeout, e.to_string()))
+                    .and_then(|r| r),
+                None => retriever.retrieve(ctx, for_identity).await,
+            };
+
+            let err = match result {
+                Ok(credential) => match (credential.get_credential_data(), now()) {
+                    (Ok(data), Ok(now)) if data.expires_at <= now => {
+                        Error::new(Origin::Api, Kind::Invalid, "the credential is expired")
+                    }
+                    _ => {
+                        trace!("Retrieved a credential for {for_identity} with retriever {index}");
+                        return Ok(credential);
+                    }
+                },
+                Err(err) => err,
+            };
+            debug!("Cannot retrieve a credential for {for_identity} with retriever {index}: {err}");
+            last_error = Some(err);
+        }
+
+        match last_error {
+            Some(err) => Err(err),
+            None => Err(IdentityError::NoCredentialsRetriever)?,
+        }
+    }
+}
+
+/// Default time during which an idle connection to a remote credentials issuer is kept open
+pub const DEFAULT_REMOTE_RETRIEVER_IDLE_KEEPALIVE: Duration = Duration::from_secs(5 * 60);
+
 /// Credentials retriever for credentials located on a different node
+///
+/// The transport connection and the secure channel to the issuer are kept open after a
+/// retrieval and reused by the next one. They are closed once they have been idle for longer
+/// than the idle keepalive, when a request fails, or with [`RemoteCredentialsRetriever::disconnect`].
+///
+/// The retrieval is retried according to a [`CredentialsRetrievalPolicy`]. When it fails, the
+/// returned error is caused by [`IdentityError::CredentialIssuerUnreachable`] if the issuer
+/// could not be reached, or by [`IdentityError::CredentialIssuanceDenied`] if the issuer
+/// refused to issue a credential.
 pub struct RemoteCredentialsRetriever {
     transport: Arc<dyn Transport>,
     secure_channels: Arc<SecureChannels>,
     issuer: RemoteCredentialsRetrieverInfo,
+    idle_keepalive: Duration,
+    policy: CredentialsRetrievalPolicy,
+    connection: Arc<Mutex<Option<RemoteConnection>>>,
+}
+
+/// Connection to the issuer node, kept open between two retrievals
+struct RemoteConnection {
+    for_identity: Identifier,
+    secure_channel: SecureChannel,
+    transport_address: Option<Address>,
+    last_used_at: TimestampInSeconds,
+    /// Number of times the connection was released, used to detect a reuse while it is idle
+    releases: u64,
 }
 
 impl RemoteCredentialsRetriever {
@@ -68,18 +254,48 @@ impl RemoteCredentialsRetriever {
             transport,
             secure_channels,
             issuer,
+            idle_keepalive: DEFAULT_REMOTE_RETRIEVER_IDLE_KEEPALIVE,
+            policy: CredentialsRetrievalPolicy::default(),
+            connection: Arc::new(Mutex::new(None)),
         }
     }
-}
 
-#[async_trait]
-impl CredentialsRetriever for RemoteCredentialsRetriever {
-    async fn retrieve(
-        &self,
-        ctx: &Context,
-        for_identity: &Identifier,
-    ) -> Result<CredentialAndPurposeKey> {
-        debug!("Getting credential from: {}", &self.issuer.route);
+    /// Set the time during which an idle connection to the issuer is reused.
+    /// A zero duration creates a new connection for each retrieval
+    pub fn with_idle_keepalive(mut self, idle_keepalive: Duration) -> Self {
+        self.idle_keepalive = idle_keepalive;
+        self
+    }
+
+    /// Set the retry and timeout policy used to retrieve credentials
+    pub fn with_retrieval_policy(mut self, policy: CredentialsRetrievalPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Close the connection to the issuer, if it is open
+    pub async fn disconnect(&self, ctx: &Context) -> Result<()> {
+        let connection = self.connection.lock().unwrap().take();
+        if let Some(connection) = connection {
+            self.close(ctx, connection).await;
+        }
+        Ok(())
+    }
+
+    /// Return the current connection if it can still be used, or create a new one
+    async fn connect(&self, ctx: &Context, for_identity: &Identifier) -> Result<RemoteConnection> {
+        let now = now()?;
+        let connection = self.connection.lock().unwrap().take();
+        if let Some(connection) = connection {
+            if &connection.for_identity == for_identity
+                && now < add_seconds(&connection.last_used_at, self.idle_keepalive.as_secs())
+            {
+                trace!("Reusing the connection to: {}", &self.issuer.route);
+                return Ok(connection);
+            }
+            self.close(ctx, connection).await;
+        }
+
         let transport_type = self.transport.transport_type();
         let (resolved_route, transport_address) = Context::resolve_transport_route_static(
             self.issuer.route.clone(),
@@ -97,28 +313,170 @@ impl CredentialsRetriever for RemoteCredentialsRetriever {
             resolved_route,
             &self.issuer.identifier,
             for_identity,
-            DEFAULT_TIMEOUT,
+            self.policy.attempt_timeout(),
         );
 
-        let credential_result = client
-            .ask(ctx, "credential_issuer", Request::post("/"))
-            .await?
-            .success();
+        match client.create_secure_channel(ctx).await {
+            Ok(secure_channel) => Ok(RemoteConnection {
+                for_identity: for_identity.clone(),
+                secure_channel,
+                transport_address,
+                last_used_at: now,
+                releases: 0,
+            }),
+            Err(err) => {
+                if let Some(transport_address) = transport_address {
+                    let _ = self.transport.disconnect(transport_address).await;
+                }
+                Err(err)
+            }
+        }
+    }
+
+    /// Keep the connection for the next retrieval and close it if it is still idle after the
+    /// idle keepalive. If another connection was stored in the meantime, it is closed
+    async fn release(&self, ctx: &Context, mut connection: RemoteConnection) -> Result<()> {
+        if self.idle_keepalive.is_zero() {
+            self.close(ctx, connection).await;
+            return Ok(());
+        }
+        connection.last_used_at = now()?;
+        connection.releases += 1;
+        let encryptor_address = connection.secure_channel.encryptor_address().clone();
+        let releases = connection.releases;
 
-        if let Some(transport_address) = transport_address {
-            let _ = self.transport.disconnect(transport_address).await;
+        let previous = self.connection.lock().unwrap().replace(connection);
+        if let Some(previous) = previous {
+            self.close(ctx, previous).await;
         }
 
-        match credential_result {
-            Ok(credential) => {
+        let timer_ctx = ctx
+            .new_detached(
+                Address::random_tagged("RemoteCredentialsRetriever.idle_timer"),
+                DenyAll,
+                DenyAll,
+            )
+            .await?;
+        let stored = self.connection.clone();
+        let secure_channels = self.secure_channels.clone();
+        let transport = self.transport.clone();
+        let idle_keepalive = self.idle_keepalive;
+        ockam_node::spawn(async move {
+            timer_ctx.sleep(idle_keepalive).await;
+            let idle = {
+                let mut stored = stored.lock().unwrap();
+                match stored.as_ref() {
+                    Some(c)
+                        if c.releases == releases
+                            && c.secure_channel.encryptor_address() == &encryptor_address =>
+                    {
+                        stored.take()
+                    }
+                    _ => None,
+                }
+            };
+            if let Some(connection) = idle {
+                trace!("Closing the idle connection to the credential issuer");
+                Self::close_connection(&timer_ctx, &secure_channels, &transport, connection).await;
+            }
+        });
+        Ok(())
+    }
+
+    /// Send a credential request to the issuer and return the undecoded response.
+    /// The connection is only kept if the issuer could be reached
+    async fn request_credential(
+        &self,
+        ctx: &Context,
+        for_identity: &Identifier,
+    ) -> Result<Vec<u8>> {
+        let connection = self.connect(ctx, for_identity).await?;
+        let client = Client::new(
+            &route![connection.secure_channel.clone(), "credential_issuer"],
+            Some(self.policy.attempt_timeout()),
+        );
+        match client.request(ctx, Request::post("/")).await {
+            Ok(bytes) => {
+                self.release(ctx, connection).await?;
+                Ok(bytes)
+            }
+            Err(err) => {
+                self.close(ctx, connection).await;
+                Err(err)
+            }
+        }
+    }
+
+    /// Stop the secure channel and disconnect the transport connection
+    async fn close(&self, ctx: &Context, connection: RemoteConnection) {
+        trace!("Closing the connection to: {}", &self.issuer.route);
+        Self::close_connection(ctx, &self.secure_channels, &self.transport, connection).await
+    }
+
+    async fn close_connection(
+        ctx: &Context,
+        secure_channels: &SecureChannels,
+        transport: &Arc<dyn Transport>,
+        connection: RemoteConnection,
+    ) {
+        let _ = secure_channels
+            .stop_secure_channel(ctx, connection.secure_channel.encryptor_address())
+            .await;
+        if let Some(transport_address) = connection.transport_address {
+            let _ = transport.disconnect(transport_address).await;
+        }
+    }
+}
+
+#[async_trait]
+impl CredentialsRetriever for RemoteCredentialsRetriever {
+    async fn retrieve(
+        &self,
+        ctx: &Context,
+        for_identity: &Identifier,
+    ) -> Result<CredentialAndPurposeKey> {
+        debug!("Getting credential from: {}", &self.issuer.route);
+        let mut retries = 0;
+        let bytes = loop {
+            match self.request_credential(ctx, for_identity).await {
+                Ok(bytes) => break bytes,
+                Err(err) if retries < self.policy.max_retries() => {
+                    retries += 1;
+                    let delay = self.policy.backoff().delay(retries);
+                    warn!(
+                        "Cannot reach the credential issuer at: {}, retrying in {}ms: {err}",
+                        &self.issuer.route,
+                        delay.as_millis()
+                    );
+                    ctx.sleep(delay).await;
+                }
+                Err(err) => {
+                    debug!(
+                        "Getting credential from: {} failed with err: {}",
+                        &self.issuer.route, err
+                    );
+                    return Err(Error::from(IdentityError::CredentialIssuerUnreachable)
+                        .context("cause", err));
+                }
+            }
+        };
+
+        match Response::parse_response_reply::<CredentialAndPurposeKey>(bytes.as_slice())? {
+            Reply::Successful(credential) => {
                 debug!("Getting credential from: {} succeeded", &self.issuer.route);
                 Ok(credential)
             }
-            Err(err) => {
+            Reply::Failed(err, status) => {
+                let message = err.message().unwrap_or("no message defined for this error");
                 debug!(
-                    "Getting credential from: {} failed with err: {}",
-                    &self.issuer.route, err
+                    "Getting credential from: {} was denied: {}",
+                    &self.issuer.route, message
                 );
+                let mut err = Error::from(IdentityError::CredentialIssuanceDenied)
+                    .context("message", message);
+                if let Some(status) = status {
+                    err = err.context("status", status);
+                }
                 Err(err)
             }
         }
@@ -1,19 +1,24 @@
 use tracing::{debug, warn};
 
 use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::string::{String, ToString};
 use ockam_core::compat::sync::Arc;
 use ockam_core::compat::vec::Vec;
 use ockam_core::Result;
 use ockam_vault::VaultForVerifyingSignatures;
 
+use crate::credentials::credentials_audit::{audit, hash_attributes};
 use crate::identities::AttributesEntry;
 use crate::models::{
-    CredentialAndPurposeKey, CredentialData, Identifier, PurposePublicKey, VersionedData,
+    AttributeValue, CredentialAndPurposeKey, CredentialData, CredentialPresentation,
+    DelegatedCredential, Identifier, PurposePublicKey, VersionedData, DELEGATED_ATTRIBUTES_KEY,
+    DELEGATION_SCHEMA_IDENTIFIER, MAX_DELEGATION_CHAIN_LENGTH,
 };
 use crate::utils::now;
 use crate::{
-    CredentialAndPurposeKeyData, IdentityAttributesRepository, IdentityError,
-    PurposeKeyVerification, TimestampInSeconds,
+    AuthoritiesPolicy, CredentialAndPurposeKeyData, CredentialAuditOutcome, CredentialAuditRecord,
+    CredentialOperation, CredentialSchemas, CredentialsAuditSink, IdentityAttributesRepository,
+    IdentityError, PurposeKeyVerification, RevocationsRepository, TimestampInSeconds,
 };
 
 /// We allow Credentials to be created in the future related to this machine's time due to
@@ -25,6 +30,9 @@ pub struct CredentialsVerification {
     purpose_keys_verification: Arc<PurposeKeyVerification>,
     verifying_vault: Arc<dyn VaultForVerifyingSignatures>,
     identities_attributes_repository: Arc<dyn IdentityAttributesRepository>,
+    revocations_repository: Option<Arc<dyn RevocationsRepository>>,
+    credential_schemas: Arc<CredentialSchemas>,
+    audit_sink: Option<Arc<dyn CredentialsAuditSink>>,
 }
 
 impl CredentialsVerification {
@@ -33,11 +41,17 @@ impl CredentialsVerification {
         purpose_keys_verification: Arc<PurposeKeyVerification>,
         verifying_vault: Arc<dyn VaultForVerifyingSignatures>,
         identities_attributes_repository: Arc<dyn IdentityAttributesRepository>,
+        revocations_repository: Option<Arc<dyn RevocationsRepository>>,
+        credential_schemas: Arc<CredentialSchemas>,
+        audit_sink: Option<Arc<dyn CredentialsAuditSink>>,
     ) -> Self {
         Self {
             purpose_keys_verification,
             verifying_vault,
             identities_attributes_repository,
+            revocations_repository,
+            credential_schemas,
+            audit_sink,
         }
     }
 }
@@ -50,6 +64,64 @@ impl CredentialsVerification {
         expected_subject: Option<&Identifier>,
         authorities: &[Identifier],
         credential_and_purpose_key: &CredentialAndPurposeKey,
+    ) -> Result<CredentialAndPurposeKeyData> {
+        let result = self
+            .check_credential(expected_subject, authorities, credential_and_purpose_key)
+            .await;
+        if self.audit_sink.is_some() {
+            self.audit_verification(expected_subject, credential_and_purpose_key, &result)
+                .await?;
+        }
+        result
+    }
+
+    /// Send an audit record for the verification of a credential
+    async fn audit_verification(
+        &self,
+        expected_subject: Option<&Identifier>,
+        credential_and_purpose_key: &CredentialAndPurposeKey,
+        result: &Result<CredentialAndPurposeKeyData>,
+    ) -> Result<()> {
+        let (issuer, credential_data, outcome) = match result {
+            Ok(data) => (
+                Some(data.purpose_key_data.subject.clone()),
+                Some(data.credential_data.clone()),
+                CredentialAuditOutcome::Success,
+            ),
+            // the credential data can't be trusted but is recorded to help with investigations
+            Err(err) => (
+                None,
+                credential_and_purpose_key.get_credential_data().ok(),
+                CredentialAuditOutcome::Failure(err.to_string()),
+            ),
+        };
+        let attributes_hash = match &credential_data {
+            Some(data) => hash_attributes(&self.verifying_vault, &data.subject_attributes).await,
+            None => None,
+        };
+        let record = CredentialAuditRecord {
+            operation: CredentialOperation::Verification,
+            issuer,
+            subject: credential_data
+                .as_ref()
+                .and_then(|data| data.subject.clone())
+                .or_else(|| expected_subject.cloned()),
+            schema: credential_data
+                .as_ref()
+                .map(|data| data.subject_attributes.schema.clone()),
+            attributes_hash,
+            outcome,
+            timestamp: now()?,
+        };
+        audit(&self.audit_sink, record).await;
+        Ok(())
+    }
+
+    async fn check_credential(
+        &self,
+        expected_subject: Option<&Identifier>,
+        authorities: &[Identifier],
+        credential_and_purpose_key: &CredentialAndPurposeKey,
     ) -> Result<CredentialAndPurposeKeyData> {
         debug!("verify purpose key attestation");
         let purpose_key_data = self
@@ -149,6 +221,21 @@ impl CredentialsVerification {
             return Err(IdentityError::CredentialVerificationFailed)?;
         }
 
+        if let (Some(revocations_repository), Some(subject)) =
+            (&self.revocations_repository, &credential_data.subject)
+        {
+            debug!("verify revocation");
+            if let Some(revocation) = revocations_repository
+                .get_revocation(&purpose_key_data.subject, subject)
+                .await?
+            {
+                if credential_data.created_at <= revocation.revoked_at {
+                    // Credential was revoked by its issuer
+                    return Err(IdentityError::CredentialRevoked)?;
+                }
+            }
+        }
+
         if let Some(_subject_latest_change_hash) = &credential_data.subject_latest_change_hash {
             // TODO: Check how that aligns with the ChangeHistory of the subject that we have in the storage
             //     For example, if we just established a secure channel with that subject,
@@ -163,8 +250,15 @@ impl CredentialsVerification {
             //     In such cases some limited tolerance may be introduced.
         }
 
+        debug!("verify attributes");
+        if !credential_data.subject_attributes.has_consistent_typed_values() {
+            warn!("the typed attributes of a credential don't match its untyped attributes");
+            return Err(IdentityError::CredentialVerificationFailed)?;
+        }
+        self.credential_schemas
+            .validate(&credential_data.subject_attributes)?;
+
         // FIXME: Verify if given authority is allowed to issue credentials with given Schema <-- Should be handled somewhere in the TrustContext
-        // FIXME: Verify if Schema aligns with Attributes <-- Should be handled somewhere in the TrustContext
 
         Ok(CredentialAndPurposeKeyData {
             credential_data,
@@ -172,6 +266,159 @@ impl CredentialsVerification {
         })
     }
 
+    /// Verify a [`CredentialPresentation`]: verify its credential, then check that each
+    /// disclosure was committed in the credential by its issuer.
+    ///
+    /// The returned data only contains the disclosed attributes
+    pub async fn verify_presentation(
+        &self,
+        expected_subject: Option<&Identifier>,
+        authorities: &[Identifier],
+        presentation: &CredentialPresentation,
+    ) -> Result<CredentialAndPurposeKeyData> {
+        let mut data = self
+            .verify_credential(expected_subject, authorities, &presentation.credential)
+            .await?;
+
+        let attributes = &mut data.credential_data.subject_attributes;
+        let commitments = match attributes.commitments.as_ref() {
+            Some(commitments) => commitments.clone(),
+            None => {
+                warn!("the presented credential doesn't support selective disclosure");
+                return Err(IdentityError::CredentialVerificationFailed)?;
+            }
+        };
+
+        debug!("verify disclosures");
+        for disclosure in presentation.disclosures.iter() {
+            let digest = disclosure.digest(&*self.verifying_vault).await?;
+            if !commitments
+                .iter()
+                .any(|c| c.as_slice() == digest.as_slice())
+                || attributes.map.contains_key(&disclosure.key)
+            {
+                // The attribute was not attested by the issuer, or is disclosed twice
+                return Err(IdentityError::CredentialVerificationFailed)?;
+            }
+            attributes
+                .map
+                .insert(disclosure.key.clone(), disclosure.value.clone());
+            if let Some(typed_value) = &disclosure.typed_value {
+                if typed_value.to_bytes().as_slice() != disclosure.value.as_slice() {
+                    // The typed value must be the same as the disclosed value
+                    return Err(IdentityError::CredentialVerificationFailed)?;
+                }
+                attributes
+                    .typed_map
+                    .get_or_insert_with(BTreeMap::new)
+                    .insert(disclosure.key.clone(), typed_value.clone());
+            }
+        }
+
+        debug!("verify disclosed attributes");
+        self.credential_schemas.validate(attributes)?;
+
+        Ok(data)
+    }
+
+    /// Verify a [`DelegatedCredential`]: walk its delegation chain from a root authority to the
+    /// issuer of the credential, checking that each sub-issuer only delegates or attests
+    /// attributes which were delegated to it
+    pub async fn verify_delegated_credential(
+        &self,
+        expected_subject: Option<&Identifier>,
+        authorities: &[Identifier],
+        delegated_credential: &DelegatedCredential,
+    ) -> Result<CredentialAndPurposeKeyData> {
+        if delegated_credential.delegation_chain.len() > MAX_DELEGATION_CHAIN_LENGTH {
+            warn!(
+                "the delegation chain is too long: {}",
+                delegated_credential.delegation_chain.len()
+            );
+            return Err(IdentityError::InvalidDelegation)?;
+        }
+
+        let mut issuers = authorities.to_vec();
+        let mut delegated_attributes: Option<Vec<String>> = None;
+        for delegation in delegated_credential.delegation_chain.iter() {
+            debug!("verify delegation issued by one of {:?}", issuers);
+            let data = self.verify_credential(None, &issuers, delegation).await?;
+            let attributes = &data.credential_data.subject_attributes;
+            if attributes.schema != DELEGATION_SCHEMA_IDENTIFIER {
+                return Err(IdentityError::InvalidDelegation)?;
+            }
+            let attributes = match attributes.get_attribute(DELEGATED_ATTRIBUTES_KEY.as_bytes()) {
+                Some(AttributeValue::StringList(attributes)) => attributes,
+                _ => return Err(IdentityError::InvalidDelegation)?,
+            };
+            if let Some(delegated_attributes) = &delegated_attributes {
+                if let Some(attribute) = attributes
+                    .iter()
+                    .find(|a| !delegated_attributes.contains(a))
+                {
+                    warn!("the attribute {attribute} can't be delegated by {issuers:?}");
+                    return Err(IdentityError::InvalidDelegation)?;
+                }
+            }
+            delegated_attributes = Some(attributes);
+            issuers = data.credential_data.subject.into_iter().collect();
+        }
+
+        debug!("verify credential issued by one of {:?}", issuers);
+        let data = self
+            .verify_credential(expected_subject, &issuers, &delegated_credential.credential)
+            .await?;
+        if let Some(delegated_attributes) = &delegated_attributes {
+            let attributes = &data.credential_data.subject_attributes;
+            if attributes.commitments.is_some() {
+                // The keys of the committed attributes can't be checked against the delegation
+                warn!("a delegated credential can't support selective disclosure");
+                return Err(IdentityError::InvalidDelegation)?;
+            }
+            let keys = attributes
+                .map
+                .keys()
+                .chain(attributes.typed_map.iter().flat_map(|m| m.keys()))
+                .chain(attributes.ttls.iter().flat_map(|m| m.keys()));
+            for key in keys {
+                let key = String::from_utf8_lossy(key);
+                if !delegated_attributes.iter().any(|a| a.as_str() == key) {
+                    warn!("the attribute {key} was not delegated to {issuers:?}");
+                    return Err(IdentityError::InvalidDelegation)?;
+                }
+            }
+        }
+        Ok(data)
+    }
+
+    /// Verify the [`Credential`]s of a subject and check that their issuers satisfy an
+    /// [`AuthoritiesPolicy`], for example a quorum of authorities.
+    /// Credentials which can't be verified are ignored, only the verified ones are returned
+    pub async fn verify_credentials_with_policy(
+        &self,
+        subject: &Identifier,
+        policy: &AuthoritiesPolicy,
+        credentials: &[CredentialAndPurposeKey],
+    ) -> Result<Vec<CredentialAndPurposeKeyData>> {
+        let mut verified = Vec::new();
+        for credential in credentials {
+            match self
+                .verify_credential(Some(subject), policy.authorities(), credential)
+                .await
+            {
+                Ok(data) => verified.push(data),
+                Err(err) => debug!("ignoring a credential of {subject}: {err}"),
+            }
+        }
+
+        let issuers: Vec<Identifier> = verified
+            .iter()
+            .map(|d| d.purpose_key_data.subject.clone())
+            .collect();
+        policy.check(&issuers)?;
+        Ok(verified)
+    }
+
     /// Receive someone's [`Credential`]: verify and put attributes from it to the storage
     pub async fn receive_presented_credential(
         &self,
@@ -187,6 +434,7 @@ impl CredentialsVerification {
             )
             .await?;
 
+        let attrs_expires = credential_data.get_attributes_expires_at();
         let map = credential_data.credential_data.subject_attributes.map;
         let map: BTreeMap<_, _> = map
             .into_iter()
@@ -201,7 +449,8 @@ impl CredentialsVerification {
                     now()?,
                     Some(credential_data.credential_data.expires_at),
                     Some(credential_data.purpose_key_data.subject),
-                ),
+                )
+                .with_attrs_expires(attrs_expires),
             )
             .await?;
 
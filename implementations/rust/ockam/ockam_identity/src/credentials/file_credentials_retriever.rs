@@ -0,0 +1,132 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tracing::{debug, warn};
+
+use ockam_core::compat::boxed::Box;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{async_trait, Error, Result};
+use ockam_node::Context;
+
+use crate::models::CredentialAndPurposeKey;
+use crate::utils::now;
+use crate::{CredentialsRetriever, Identifier};
+
+/// Credentials retriever reading a credential from a file.
+///
+/// The file can either contain the CBOR encoding of the credential or its hex encoding, as
+/// exported by the `ockam credential` commands. The file is read again every time it is modified,
+/// so that the credential can be rotated by an external process, for example a Kubernetes
+/// secret rotation or a cron job, without restarting the node.
+pub struct FileCredentialsRetriever {
+    path: PathBuf,
+    loaded: Mutex<Option<LoadedCredential>>,
+}
+
+/// Credential read from the file, with the modification time of the file when it was read
+struct LoadedCredential {
+    modified: Option<SystemTime>,
+    credential: CredentialAndPurposeKey,
+}
+
+impl FileCredentialsRetriever {
+    /// Create a new retriever for the credential stored at `path`
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            loaded: Mutex::new(None),
+        }
+    }
+
+    /// Return the path of the credential file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Return the credential, reading the file again if it changed since the last retrieval
+    fn load(&self) -> Result<CredentialAndPurposeKey> {
+        let modified = fs::metadata(&self.path)
+            .map_err(|e| Self::error(&self.path, e))?
+            .modified()
+            .ok();
+
+        let mut loaded = self.loaded.lock().unwrap();
+        if let Some(loaded) = loaded.as_ref() {
+            if modified.is_some() && loaded.modified == modified {
+                return Ok(loaded.credential.clone());
+            }
+        }
+
+        debug!("Reading the credential file {}", self.path.display());
+        let bytes = fs::read(&self.path).map_err(|e| Self::error(&self.path, e))?;
+        let credential = Self::decode(&bytes)?;
+        *loaded = Some(LoadedCredential {
+            modified,
+            credential: credential.clone(),
+        });
+        Ok(credential)
+    }
+
+    /// Decode a credential from its CBOR encoding or from its hex encoding
+    fn decode(bytes: &[u8]) -> Result<CredentialAndPurposeKey> {
+        match CredentialAndPurposeKey::decode_from_cbor_bytes(bytes) {
+            Ok(credential) => Ok(credential),
+            Err(_) => {
+                let as_hex = core::str::from_utf8(bytes)
+                    .map_err(|e| Error::new(Origin::Identity, Kind::Serialization, e))?;
+                CredentialAndPurposeKey::decode_from_string(as_hex.trim())
+            }
+        }
+    }
+
+    fn error(path: &Path, e: std::io::Error) -> Error {
+        Error::new(
+            Origin::Identity,
+            Kind::Io,
+            format!("cannot read the credential file {}: {e}", path.display()),
+        )
+    }
+}
+
+#[async_trait]
+impl CredentialsRetriever for FileCredentialsRetriever {
+    async fn retrieve(
+        &self,
+        _ctx: &Context,
+        for_identity: &Identifier,
+    ) -> Result<CredentialAndPurposeKey> {
+        let credential = match self.load() {
+            Ok(credential) => credential,
+            Err(err) => {
+                // The file might be temporarily missing while it is being rotated
+                let previous = self
+                    .loaded
+                    .lock()
+                    .unwrap()
+                    .as_ref()
+                    .map(|l| l.credential.clone());
+                match previous {
+                    Some(previous) if previous.get_credential_data()?.expires_at > now()? => {
+                        warn!("Using the previously loaded credential: {err}");
+                        previous
+                    }
+                    _ => return Err(err),
+                }
+            }
+        };
+
+        let subject = credential.get_credential_data()?.subject;
+        if subject.as_ref() != Some(for_identity) {
+            return Err(Error::new(
+                Origin::Identity,
+                Kind::Invalid,
+                format!(
+                    "the credential in {} was not issued for {for_identity}",
+                    self.path.display()
+                ),
+            ));
+        }
+        Ok(credential)
+    }
+}
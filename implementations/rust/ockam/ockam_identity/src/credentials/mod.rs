@@ -1,21 +1,43 @@
+mod authorities_policy;
 mod authority_service;
+mod credential_issuance_policies;
+mod credential_schemas;
 #[allow(clippy::module_inception)]
 mod credentials;
+mod credentials_audit;
 mod credentials_creation;
 mod credentials_issuer;
+mod credentials_refresher;
+mod credentials_retrieval_policy;
 mod credentials_retriever;
 mod credentials_server;
 mod credentials_server_worker;
 mod credentials_verification;
+#[cfg(feature = "std")]
+mod file_credentials_retriever;
 mod one_time_code;
+mod revocations;
+mod storage;
 mod trust_context;
+mod verification_bundles;
 
+pub use authorities_policy::*;
 pub use authority_service::*;
+pub use credential_issuance_policies::*;
+pub use credential_schemas::*;
 pub use credentials::*;
+pub use credentials_audit::*;
 pub use credentials_creation::*;
 pub use credentials_issuer::*;
+pub use credentials_refresher::*;
+pub use credentials_retrieval_policy::*;
 pub use credentials_retriever::*;
 pub use credentials_server::*;
 pub use credentials_verification::*;
+#[cfg(feature = "std")]
+pub use file_credentials_retriever::*;
 pub use one_time_code::*;
+pub use revocations::*;
+pub use storage::*;
 pub use trust_context::*;
+pub use verification_bundles::*;
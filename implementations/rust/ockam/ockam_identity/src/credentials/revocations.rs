@@ -0,0 +1,304 @@
+use core::time::Duration;
+use minicbor::Decoder;
+use tracing::{debug, error, info, warn};
+
+use ockam_core::api::{Request, RequestHeader, Response};
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::string::ToString;
+use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
+use ockam_core::{async_trait, Address, AllowAll, Result, Routed, Worker};
+use ockam_node::{Context, WorkerBuilder};
+use ockam_vault::{VaultForSigning, VaultForVerifyingSignatures};
+
+use crate::models::{
+    Identifier, PurposePublicKey, Revocation, RevocationList, RevocationListAndPurposeKey,
+    RevocationListData,
+};
+use crate::utils::{add_seconds, now};
+use crate::{IdentityError, PurposeKeys, RevocationsRepository, SecureClient, TimestampInSeconds};
+
+/// Service for managing the revocation of [`Credential`](crate::models::Credential)s
+///
+/// An issuer revokes all the credentials it issued to a subject until now. The revocations of an
+/// issuer can be published as a signed [`RevocationList`], which is then imported by the nodes
+/// verifying the credentials of that issuer.
+pub struct Revocations {
+    repository: Arc<dyn RevocationsRepository>,
+    purpose_keys: Arc<PurposeKeys>,
+    credential_vault: Arc<dyn VaultForSigning>,
+    verifying_vault: Arc<dyn VaultForVerifyingSignatures>,
+}
+
+impl Revocations {
+    ///Constructor
+    pub fn new(
+        repository: Arc<dyn RevocationsRepository>,
+        purpose_keys: Arc<PurposeKeys>,
+        credential_vault: Arc<dyn VaultForSigning>,
+        verifying_vault: Arc<dyn VaultForVerifyingSignatures>,
+    ) -> Self {
+        Self {
+            repository,
+            purpose_keys,
+            credential_vault,
+            verifying_vault,
+        }
+    }
+
+    /// Revoke all the credentials issued by `issuer` to `subject` until now
+    pub async fn revoke(&self, issuer: &Identifier, subject: &Identifier) -> Result<()> {
+        info!("revoking the credentials issued by {issuer} to {subject}");
+        let revocation = Revocation {
+            subject: subject.clone(),
+            revoked_at: now()?,
+        };
+        self.repository.put_revocation(issuer, &revocation).await
+    }
+
+    /// Cancel the revocation of the credentials issued by `issuer` to `subject`
+    pub async fn cancel_revocation(&self, issuer: &Identifier, subject: &Identifier) -> Result<()> {
+        self.repository.delete_revocation(issuer, subject).await
+    }
+
+    /// Return true if a credential created at `created_at` by `issuer` for `subject` is revoked
+    pub async fn is_revoked(
+        &self,
+        issuer: &Identifier,
+        subject: &Identifier,
+        created_at: TimestampInSeconds,
+    ) -> Result<bool> {
+        Ok(self
+            .repository
+            .get_revocation(issuer, subject)
+            .await?
+            .map(|r| created_at <= r.revoked_at)
+            .unwrap_or(false))
+    }
+
+    /// Create a [`RevocationList`] containing all the revocations published by `issuer`,
+    /// signed with its credentials purpose key
+    pub async fn create_revocation_list(
+        &self,
+        issuer: &Identifier,
+        ttl: Duration,
+    ) -> Result<RevocationListAndPurposeKey> {
+        let issuer_purpose_key = self
+            .purpose_keys
+            .purpose_keys_creation()
+            .get_or_create_credential_purpose_key(issuer)
+            .await?;
+
+        let created_at = now()?;
+        let expires_at = add_seconds(&created_at, ttl.as_secs());
+        let data = RevocationListData {
+            revocations: self.repository.get_revocations(issuer).await?,
+            created_at,
+            expires_at,
+        };
+        let data = minicbor::to_vec(data)?;
+
+        let versioned_data = RevocationList::create_versioned_data(data);
+        let versioned_data = minicbor::to_vec(&versioned_data)?;
+
+        let versioned_data_hash = self.verifying_vault.sha256(&versioned_data).await?;
+
+        let signature = self
+            .credential_vault
+            .sign(issuer_purpose_key.key(), &versioned_data_hash.0)
+            .await?;
+
+        Ok(RevocationListAndPurposeKey {
+            revocation_list: RevocationList {
+                data: versioned_data,
+                signature: signature.into(),
+            },
+            purpose_key_attestation: issuer_purpose_key.attestation().clone(),
+        })
+    }
+
+    /// Verify a [`RevocationList`] and return its issuer and its data
+    pub async fn verify_revocation_list(
+        &self,
+        authorities: &[Identifier],
+        revocation_list_and_purpose_key: &RevocationListAndPurposeKey,
+    ) -> Result<(Identifier, RevocationListData)> {
+        let purpose_key_data = self
+            .purpose_keys
+            .purpose_keys_verification()
+            .verify_purpose_key_attestation(
+                None,
+                &revocation_list_and_purpose_key.purpose_key_attestation,
+            )
+            .await?;
+
+        if !authorities.contains(&purpose_key_data.subject) {
+            warn!(
+                "unknown authority on a revocation list: {}. Accepted authorities: {:?}",
+                purpose_key_data.subject, authorities
+            );
+            return Err(IdentityError::UnknownAuthority)?;
+        }
+
+        let public_key = match purpose_key_data.public_key {
+            PurposePublicKey::SecureChannelStatic(_) => {
+                return Err(IdentityError::InvalidKeyType)?;
+            }
+            PurposePublicKey::CredentialSigning(public_key) => public_key.into(),
+        };
+
+        let revocation_list = &revocation_list_and_purpose_key.revocation_list;
+        let versioned_data_hash = self.verifying_vault.sha256(&revocation_list.data).await?;
+        if !self
+            .verifying_vault
+            .verify_signature(
+                &public_key,
+                &versioned_data_hash.0,
+                &revocation_list.signature.clone().into(),
+            )
+            .await?
+        {
+            return Err(IdentityError::RevocationListVerificationFailed)?;
+        }
+
+        let data = revocation_list.get_revocation_list_data()?;
+        if data.expires_at < now()? {
+            // Revocation list expired, a more recent one must be fetched
+            return Err(IdentityError::RevocationListVerificationFailed)?;
+        }
+
+        Ok((purpose_key_data.subject, data))
+    }
+
+    /// Verify a [`RevocationList`] and replace the stored revocations of its issuer with the
+    /// revocations of the list, so that cancelled revocations are removed.
+    /// A list created before the last list imported from the same issuer is rejected
+    pub async fn import_revocation_list(
+        &self,
+        authorities: &[Identifier],
+        revocation_list_and_purpose_key: &RevocationListAndPurposeKey,
+    ) -> Result<()> {
+        let (issuer, data) = self
+            .verify_revocation_list(authorities, revocation_list_and_purpose_key)
+            .await?;
+
+        // An older list could overwrite the more recent revocations of a subject
+        if let Some(last_created_at) = self
+            .repository
+            .get_revocation_list_created_at(&issuer)
+            .await?
+        {
+            if data.created_at < last_created_at {
+                warn!(
+                    "the revocation list published by {issuer} at {} is older than the last imported one, published at {}",
+                    data.created_at.0, last_created_at.0
+                );
+                return Err(IdentityError::RevocationListOutdated)?;
+            }
+        }
+
+        debug!(
+            "importing {} revocation(s) published by {issuer}",
+            data.revocations.len()
+        );
+        self.repository
+            .replace_revocations(&issuer, &data.revocations, data.created_at)
+            .await
+    }
+
+    /// Fetch the [`RevocationList`] served by an authority and import it
+    pub async fn fetch_revocation_list(
+        &self,
+        ctx: &Context,
+        client: &SecureClient,
+        authorities: &[Identifier],
+    ) -> Result<()> {
+        let revocation_list: RevocationListAndPurposeKey = client
+            .ask(ctx, "revocation_list", Request::get("/"))
+            .await?
+            .success()?;
+        self.import_revocation_list(authorities, &revocation_list)
+            .await
+    }
+
+    /// Start a worker serving the signed [`RevocationList`] of `issuer`.
+    /// A new list, valid for `ttl`, is created for each request
+    pub async fn start_revocation_list_service(
+        self: Arc<Self>,
+        ctx: &Context,
+        address: impl Into<Address>,
+        issuer: &Identifier,
+        ttl: Duration,
+    ) -> Result<()> {
+        let worker = RevocationListWorker {
+            revocations: self,
+            issuer: issuer.clone(),
+            ttl,
+        };
+        WorkerBuilder::new(worker)
+            .with_address(address)
+            .with_incoming_access_control(AllowAll)
+            .with_outgoing_access_control(AllowAll)
+            .start(ctx)
+            .await
+    }
+}
+
+/// Worker serving the [`RevocationList`] of an issuer
+struct RevocationListWorker {
+    revocations: Arc<Revocations>,
+    issuer: Identifier,
+    ttl: Duration,
+}
+
+impl RevocationListWorker {
+    async fn handle_request(&self, req: &RequestHeader) -> Result<Vec<u8>> {
+        use ockam_core::api::Method::*;
+        let r = match (req.method(), req.path_segments::<2>().as_slice()) {
+            (Some(Get), [] | [""]) => {
+                let revocation_list = self
+                    .revocations
+                    .create_revocation_list(&self.issuer, self.ttl)
+                    .await?;
+                Response::ok()
+                    .with_headers(req)
+                    .body(revocation_list)
+                    .to_vec()?
+            }
+            _ => {
+                Response::bad_request(req, &format!("Invalid endpoint: {}", req.path())).to_vec()?
+            }
+        };
+        Ok(r)
+    }
+}
+
+#[async_trait]
+impl Worker for RevocationListWorker {
+    type Message = Vec<u8>;
+    type Context = Context;
+
+    async fn handle_message(
+        &mut self,
+        ctx: &mut Self::Context,
+        msg: Routed<Self::Message>,
+    ) -> Result<()> {
+        let mut dec = Decoder::new(msg.as_body());
+        let req: RequestHeader = match dec.decode() {
+            Ok(r) => r,
+            Err(e) => {
+                error!("failed to decode request: {:?}", e);
+                return Ok(());
+            }
+        };
+
+        let r = match self.handle_request(&req).await {
+            Ok(r) => r,
+            Err(err) => {
+                error!(?err, "Failed to create a revocation list");
+                Response::internal_error(&req, &err.to_string()).to_vec()?
+            }
+        };
+        ctx.send(msg.return_route(), r).await
+    }
+}
@@ -0,0 +1,31 @@
+use ockam_core::async_trait;
+use ockam_core::compat::boxed::Box;
+use ockam_core::Result;
+
+use crate::models::{CredentialAndPurposeKey, Identifier};
+use crate::TimestampInSeconds;
+
+/// This repository stores the credentials retrieved by a
+/// [`CachingCredentialsRetriever`](crate::CachingCredentialsRetriever),
+/// so that they can be reused until they expire, even after a restart
+#[async_trait]
+pub trait CachedCredentialsRepository: Send + Sync + 'static {
+    /// Store the credential issued by `issuer` for `subject`, overwriting the existing one (if any)
+    async fn put_credential(
+        &self,
+        issuer: &Identifier,
+        subject: &Identifier,
+        expires_at: TimestampInSeconds,
+        credential: &CredentialAndPurposeKey,
+    ) -> Result<()>;
+
+    /// Retrieve the credential issued by `issuer` for `subject`, with its expiration time
+    async fn get_credential(
+        &self,
+        issuer: &Identifier,
+        subject: &Identifier,
+    ) -> Result<Option<(CredentialAndPurposeKey, TimestampInSeconds)>>;
+
+    /// Delete the credential issued by `issuer` for `subject`
+    async fn delete_credential(&self, issuer: &Identifier, subject: &Identifier) -> Result<()>;
+}
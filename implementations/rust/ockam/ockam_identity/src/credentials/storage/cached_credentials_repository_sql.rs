@@ -0,0 +1,92 @@
+use sqlx::*;
+use tracing::debug;
+
+use ockam_core::async_trait;
+use ockam_core::compat::vec::Vec;
+use ockam_core::Result;
+use ockam_node::database::{FromSqlxError, SqlxDatabase, ToSqlxType, ToVoid};
+
+use crate::models::{CredentialAndPurposeKey, Identifier};
+use crate::CachedCredentialsRepository;
+use crate::TimestampInSeconds;
+
+/// Implementation of the `CachedCredentialsRepository` trait based on an underlying database
+/// using sqlx as its API, and Sqlite as its driver
+#[derive(Clone)]
+pub struct CachedCredentialsSqlxDatabase {
+    database: SqlxDatabase,
+}
+
+impl CachedCredentialsSqlxDatabase {
+    /// Create a new database for cached credentials
+    pub fn new(database: SqlxDatabase) -> Self {
+        debug!("create a repository for cached credentials");
+        Self { database }
+    }
+
+    /// Create a new in-memory database for cached credentials
+    pub async fn create() -> Result<Self> {
+        Ok(Self::new(
+            SqlxDatabase::in_memory("cached credentials").await?,
+        ))
+    }
+}
+
+#[async_trait]
+impl CachedCredentialsRepository for CachedCredentialsSqlxDatabase {
+    async fn put_credential(
+        &self,
+        issuer: &Identifier,
+        subject: &Identifier,
+        expires_at: TimestampInSeconds,
+        credential: &CredentialAndPurposeKey,
+    ) -> Result<()> {
+        let query = query("INSERT OR REPLACE INTO cached_credential VALUES (?, ?, ?, ?)")
+            .bind(issuer.to_sql())
+            .bind(subject.to_sql())
+            .bind(credential.encode_as_cbor_bytes()?.to_sql())
+            .bind(expires_at.to_sql());
+        query.execute(&*self.database.pool).await.void()
+    }
+
+    async fn get_credential(
+        &self,
+        issuer: &Identifier,
+        subject: &Identifier,
+    ) -> Result<Option<(CredentialAndPurposeKey, TimestampInSeconds)>> {
+        let query = query_as(
+            "SELECT credential, expires_at FROM cached_credential WHERE issuer=$1 AND subject=$2",
+        )
+        .bind(issuer.to_sql())
+        .bind(subject.to_sql());
+        let row: Option<CachedCredentialRow> = query
+            .fetch_optional(&*self.database.pool)
+            .await
+            .into_core()?;
+        row.map(|r| r.credential()).transpose()
+    }
+
+    async fn delete_credential(&self, issuer: &Identifier, subject: &Identifier) -> Result<()> {
+        let query = query("DELETE FROM cached_credential WHERE issuer = ? AND subject = ?")
+            .bind(issuer.to_sql())
+            .bind(subject.to_sql());
+        query.execute(&*self.database.pool).await.void()
+    }
+}
+
+// Database serialization / deserialization
+
+#[derive(FromRow)]
+struct CachedCredentialRow {
+    credential: Vec<u8>,
+    expires_at: i64,
+}
+
+impl CachedCredentialRow {
+    fn credential(&self) -> Result<(CredentialAndPurposeKey, TimestampInSeconds)> {
+        Ok((
+            CredentialAndPurposeKey::decode_from_cbor_bytes(&self.credential)?,
+            TimestampInSeconds(self.expires_at as u64),
+        ))
+    }
+}
@@ -0,0 +1,14 @@
+pub use cached_credentials_repository::*;
+#[cfg(feature = "storage")]
+pub use cached_credentials_repository_sql::*;
+pub use revocations_repository::*;
+#[cfg(feature = "storage")]
+pub use revocations_repository_sql::*;
+
+mod cached_credentials_repository;
+mod revocations_repository;
+
+#[cfg(feature = "storage")]
+mod cached_credentials_repository_sql;
+#[cfg(feature = "storage")]
+mod revocations_repository_sql;
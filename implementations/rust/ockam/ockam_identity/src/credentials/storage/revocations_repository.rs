@@ -0,0 +1,57 @@
+use ockam_core::async_trait;
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::vec::Vec;
+use ockam_core::Result;
+
+use crate::models::{Identifier, Revocation};
+use crate::TimestampInSeconds;
+
+/// This repository stores the revocations published by credential issuers:
+///
+///  - an issuer revokes all the credentials it issued to a subject before a given time
+///  - the revocations are either published locally by the issuer or imported from its revocation list
+///  - the creation time of the last imported revocation list of each issuer is kept, so that an
+///    older list can't be imported again
+///  - importing a revocation list replaces all the revocations of its issuer, so that the
+///    revocations cancelled by the issuer are removed
+///
+#[async_trait]
+pub trait RevocationsRepository: Send + Sync + 'static {
+    /// Store a revocation published by `issuer`, overwriting the previous revocation of the same subject (if any)
+    async fn put_revocation(&self, issuer: &Identifier, revocation: &Revocation) -> Result<()>;
+
+    /// Return the revocation published by `issuer` for `subject`
+    async fn get_revocation(
+        &self,
+        issuer: &Identifier,
+        subject: &Identifier,
+    ) -> Result<Option<Revocation>>;
+
+    /// Return all the revocations published by `issuer`
+    async fn get_revocations(&self, issuer: &Identifier) -> Result<Vec<Revocation>>;
+
+    /// Delete the revocation published by `issuer` for `subject`
+    async fn delete_revocation(&self, issuer: &Identifier, subject: &Identifier) -> Result<()>;
+
+    /// Store the creation time of the last revocation list imported from `issuer`
+    async fn put_revocation_list_created_at(
+        &self,
+        issuer: &Identifier,
+        created_at: TimestampInSeconds,
+    ) -> Result<()>;
+
+    /// Return the creation time of the last revocation list imported from `issuer`
+    async fn get_revocation_list_created_at(
+        &self,
+        issuer: &Identifier,
+    ) -> Result<Option<TimestampInSeconds>>;
+
+    /// Replace all the revocations published by `issuer` with the revocations of an imported
+    /// revocation list and store the creation time of that list, atomically
+    async fn replace_revocations(
+        &self,
+        issuer: &Identifier,
+        revocations: &[Revocation],
+        created_at: TimestampInSeconds,
+    ) -> Result<()>;
+}
@@ -0,0 +1,219 @@
+use core::str::FromStr;
+
+use sqlx::*;
+use tracing::debug;
+
+use ockam_core::async_trait;
+use ockam_core::compat::string::String;
+use ockam_core::compat::vec::Vec;
+use ockam_core::Result;
+use ockam_node::database::{FromSqlxError, SqlxDatabase, ToSqlxType, ToVoid};
+
+use crate::models::{Identifier, Revocation};
+use crate::{RevocationsRepository, TimestampInSeconds};
+
+/// Implementation of the `RevocationsRepository` trait based on an underlying database
+/// using sqlx as its API, and Sqlite as its driver
+#[derive(Clone)]
+pub struct RevocationsSqlxDatabase {
+    database: SqlxDatabase,
+}
+
+impl RevocationsSqlxDatabase {
+    /// Create a new database for revocations
+    pub fn new(database: SqlxDatabase) -> Self {
+        debug!("create a repository for revocations");
+        Self { database }
+    }
+
+    /// Create a new in-memory database for revocations
+    pub async fn create() -> Result<Self> {
+        Ok(Self::new(SqlxDatabase::in_memory("revocations").await?))
+    }
+}
+
+#[async_trait]
+impl RevocationsRepository for RevocationsSqlxDatabase {
+    async fn put_revocation(&self, issuer: &Identifier, revocation: &Revocation) -> Result<()> {
+        let query = query("INSERT OR REPLACE INTO revocation VALUES (?, ?, ?)")
+            .bind(issuer.to_sql())
+            .bind(revocation.subject.to_sql())
+            .bind(revocation.revoked_at.to_sql());
+        query.execute(&*self.database.pool).await.void()
+    }
+
+    async fn get_revocation(
+        &self,
+        issuer: &Identifier,
+        subject: &Identifier,
+    ) -> Result<Option<Revocation>> {
+        let query =
+            query_as("SELECT subject, revoked_at FROM revocation WHERE issuer=$1 AND subject=$2")
+                .bind(issuer.to_sql())
+                .bind(subject.to_sql());
+        let row: Option<RevocationRow> = query
+            .fetch_optional(&*self.database.pool)
+            .await
+            .into_core()?;
+        row.map(|r| r.revocation()).transpose()
+    }
+
+    async fn get_revocations(&self, issuer: &Identifier) -> Result<Vec<Revocation>> {
+        let query =
+            query_as("SELECT subject, revoked_at FROM revocation WHERE issuer=$1 ORDER BY subject")
+                .bind(issuer.to_sql());
+        let rows: Vec<RevocationRow> = query.fetch_all(&*self.database.pool).await.into_core()?;
+        rows.iter().map(|r| r.revocation()).collect()
+    }
+
+    async fn delete_revocation(&self, issuer: &Identifier, subject: &Identifier) -> Result<()> {
+        let query = query("DELETE FROM revocation WHERE issuer = ? AND subject = ?")
+            .bind(issuer.to_sql())
+            .bind(subject.to_sql());
+        query.execute(&*self.database.pool).await.void()
+    }
+
+    async fn put_revocation_list_created_at(
+        &self,
+        issuer: &Identifier,
+        created_at: TimestampInSeconds,
+    ) -> Result<()> {
+        let query = query("INSERT OR REPLACE INTO revocation_list VALUES (?, ?)")
+            .bind(issuer.to_sql())
+            .bind(created_at.to_sql());
+        query.execute(&*self.database.pool).await.void()
+    }
+
+    async fn get_revocation_list_created_at(
+        &self,
+        issuer: &Identifier,
+    ) -> Result<Option<TimestampInSeconds>> {
+        let query = query_as("SELECT created_at FROM revocation_list WHERE issuer=$1")
+            .bind(issuer.to_sql());
+        let row: Option<RevocationListRow> = query
+            .fetch_optional(&*self.database.pool)
+            .await
+            .into_core()?;
+        Ok(row.map(|r| TimestampInSeconds(r.created_at as u64)))
+    }
+
+    async fn replace_revocations(
+        &self,
+        issuer: &Identifier,
+        revocations: &[Revocation],
+        created_at: TimestampInSeconds,
+    ) -> Result<()> {
+        let mut transaction = self.database.begin().await.into_core()?;
+        query("DELETE FROM revocation WHERE issuer = ?")
+            .bind(issuer.to_sql())
+            .execute(&mut *transaction)
+            .await
+            .void()?;
+        for revocation in revocations {
+            query("INSERT OR REPLACE INTO revocation VALUES (?, ?, ?)")
+                .bind(issuer.to_sql())
+                .bind(revocation.subject.to_sql())
+                .bind(revocation.revoked_at.to_sql())
+                .execute(&mut *transaction)
+                .await
+                .void()?;
+        }
+        query("INSERT OR REPLACE INTO revocation_list VALUES (?, ?)")
+            .bind(issuer.to_sql())
+            .bind(created_at.to_sql())
+            .execute(&mut *transaction)
+            .await
+            .void()?;
+        transaction.commit().await.void()
+    }
+}
+
+// Database serialization / deserialization
+
+#[derive(FromRow)]
+struct RevocationRow {
+    subject: String,
+    revoked_at: i64,
+}
+
+impl RevocationRow {
+    fn revocation(&self) -> Result<Revocation> {
+        Ok(Revocation {
+            subject: Identifier::from_str(&self.subject)?,
+            revoked_at: TimestampInSeconds(self.revoked_at as u64),
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct RevocationListRow {
+    created_at: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identities;
+    use ockam_core::compat::sync::Arc;
+
+    #[tokio::test]
+    async fn test_revocations_repository() -> Result<()> {
+        let repository: Arc<dyn RevocationsRepository> =
+            Arc::new(RevocationsSqlxDatabase::create().await?);
+        let identities = identities().await?;
+        let issuer = identities.identities_creation().create_identity().await?;
+        let subject1 = identities.identities_creation().create_identity().await?;
+        let subject2 = identities.identities_creation().create_identity().await?;
+
+        let revocation1 = Revocation {
+            subject: subject1.clone(),
+            revoked_at: TimestampInSeconds(10),
+        };
+        let revocation2 = Revocation {
+            subject: subject2.clone(),
+            revoked_at: TimestampInSeconds(20),
+        };
+        repository.put_revocation(&issuer, &revocation1).await?;
+        repository.put_revocation(&issuer, &revocation2).await?;
+
+        let result = repository.get_revocation(&issuer, &subject1).await?;
+        assert_eq!(result, Some(revocation1.clone()));
+        let result = repository.get_revocation(&subject1, &subject2).await?;
+        assert_eq!(result, None);
+
+        let mut result = repository.get_revocations(&issuer).await?;
+        result.sort_by_key(|r| r.revoked_at);
+        assert_eq!(result, vec![revocation1, revocation2.clone()]);
+
+        // a revocation can be deleted
+        repository.delete_revocation(&issuer, &subject1).await?;
+        let result = repository.get_revocations(&issuer).await?;
+        assert_eq!(result, vec![revocation2]);
+
+        // the creation time of the last imported revocation list is kept per issuer
+        let result = repository.get_revocation_list_created_at(&issuer).await?;
+        assert_eq!(result, None);
+        repository
+            .put_revocation_list_created_at(&issuer, TimestampInSeconds(30))
+            .await?;
+        repository
+            .put_revocation_list_created_at(&issuer, TimestampInSeconds(40))
+            .await?;
+        let result = repository.get_revocation_list_created_at(&issuer).await?;
+        assert_eq!(result, Some(TimestampInSeconds(40)));
+
+        // the revocations of an issuer can be replaced by the ones of an imported list
+        let revocation3 = Revocation {
+            subject: subject1.clone(),
+            revoked_at: TimestampInSeconds(50),
+        };
+        repository
+            .replace_revocations(&issuer, &[revocation3.clone()], TimestampInSeconds(60))
+            .await?;
+        let result = repository.get_revocations(&issuer).await?;
+        assert_eq!(result, vec![revocation3]);
+        let result = repository.get_revocation_list_created_at(&issuer).await?;
+        assert_eq!(result, Some(TimestampInSeconds(60)));
+        Ok(())
+    }
+}
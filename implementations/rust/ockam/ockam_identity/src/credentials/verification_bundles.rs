@@ -0,0 +1,178 @@
+use core::time::Duration;
+use tracing::{debug, warn};
+
+use ockam_core::compat::sync::Arc;
+use ockam_core::Result;
+use ockam_vault::{VaultForSigning, VaultForVerifyingSignatures, VerifyingPublicKey};
+
+use crate::models::{
+    Identifier, PurposeKeyAttestation, PurposePublicKey, VerificationBundle, VerificationBundleData,
+};
+use crate::utils::{add_seconds, now};
+use crate::{IdentitiesCreation, IdentityError, PurposeKeys, Revocations};
+
+/// Service exporting and loading [`VerificationBundle`]s
+///
+/// An Authority exports a signed bundle containing its identity, its credentials purpose key
+/// and optionally its revocations. The bundle can then be carried to a node which can never
+/// reach the Authority, for example an air-gapped node, where it is loaded so that the
+/// credentials issued by that Authority can be verified.
+pub struct VerificationBundles {
+    identities_creation: Arc<IdentitiesCreation>,
+    purpose_keys: Arc<PurposeKeys>,
+    credential_vault: Arc<dyn VaultForSigning>,
+    verifying_vault: Arc<dyn VaultForVerifyingSignatures>,
+    revocations: Option<Arc<Revocations>>,
+}
+
+impl VerificationBundles {
+    ///Constructor
+    pub fn new(
+        identities_creation: Arc<IdentitiesCreation>,
+        purpose_keys: Arc<PurposeKeys>,
+        credential_vault: Arc<dyn VaultForSigning>,
+        verifying_vault: Arc<dyn VaultForVerifyingSignatures>,
+        revocations: Option<Arc<Revocations>>,
+    ) -> Self {
+        Self {
+            identities_creation,
+            purpose_keys,
+            credential_vault,
+            verifying_vault,
+            revocations,
+        }
+    }
+
+    /// Export a [`VerificationBundle`] for `authority`, valid for `ttl`.
+    /// The revocations of the authority are included if `with_revocations` is true
+    pub async fn export_verification_bundle(
+        &self,
+        authority: &Identifier,
+        ttl: Duration,
+        with_revocations: bool,
+    ) -> Result<VerificationBundle> {
+        let purpose_key = self
+            .purpose_keys
+            .purpose_keys_creation()
+            .get_or_create_credential_purpose_key(authority)
+            .await?;
+
+        let revocation_list = match (&self.revocations, with_revocations) {
+            (Some(revocations), true) => {
+                Some(revocations.create_revocation_list(authority, ttl).await?)
+            }
+            _ => None,
+        };
+
+        let created_at = now()?;
+        let expires_at = add_seconds(&created_at, ttl.as_secs());
+        let data = VerificationBundleData {
+            authority: self
+                .identities_creation
+                .get_change_history(authority)
+                .await?,
+            purpose_key_attestations: vec![purpose_key.attestation().clone()],
+            revocation_list,
+            created_at,
+            expires_at,
+        };
+        let data = minicbor::to_vec(data)?;
+
+        let versioned_data = VerificationBundle::create_versioned_data(data);
+        let versioned_data = minicbor::to_vec(&versioned_data)?;
+
+        let versioned_data_hash = self.verifying_vault.sha256(&versioned_data).await?;
+
+        let signature = self
+            .credential_vault
+            .sign(purpose_key.key(), &versioned_data_hash.0)
+            .await?;
+
+        Ok(VerificationBundle {
+            data: versioned_data,
+            signature: signature.into(),
+            purpose_key_attestation: purpose_key.attestation().clone(),
+        })
+    }
+
+    /// Verify a [`VerificationBundle`] exported by `authority` and load it:
+    ///
+    ///  - the identity of the authority is stored, so that its purpose keys can be verified
+    ///  - the revocations of the authority are imported, if any
+    ///
+    /// The `authority` identifier must be known in advance, since it is the trust anchor
+    /// of the bundle
+    pub async fn load_verification_bundle(
+        &self,
+        authority: &Identifier,
+        verification_bundle: &VerificationBundle,
+    ) -> Result<VerificationBundleData> {
+        // The data is not trusted yet, but the change history is self-signed and must
+        // correspond to the expected authority
+        let data = verification_bundle.get_verification_bundle_data()?;
+        self.identities_creation
+            .import_from_change_history(Some(authority), data.authority.clone())
+            .await?;
+
+        debug!("verify the verification bundle signature");
+        let public_key = self
+            .verify_purpose_key(authority, &verification_bundle.purpose_key_attestation)
+            .await?;
+        let versioned_data_hash = self
+            .verifying_vault
+            .sha256(&verification_bundle.data)
+            .await?;
+        if !self
+            .verifying_vault
+            .verify_signature(
+                &public_key,
+                &versioned_data_hash.0,
+                &verification_bundle.signature.clone().into(),
+            )
+            .await?
+        {
+            return Err(IdentityError::VerificationBundleVerificationFailed)?;
+        }
+
+        if data.expires_at < now()? {
+            // Verification bundle expired, a more recent one must be exported
+            return Err(IdentityError::VerificationBundleVerificationFailed)?;
+        }
+
+        for attestation in data.purpose_key_attestations.iter() {
+            self.verify_purpose_key(authority, attestation).await?;
+        }
+
+        if let Some(revocation_list) = &data.revocation_list {
+            match &self.revocations {
+                Some(revocations) => {
+                    revocations
+                        .import_revocation_list(&[authority.clone()], revocation_list)
+                        .await?
+                }
+                None => warn!(
+                    "the revocations of {authority} can't be imported: revocations are disabled"
+                ),
+            }
+        }
+
+        Ok(data)
+    }
+
+    /// Verify that a purpose key attestation is a credentials purpose key of the authority
+    async fn verify_purpose_key(
+        &self,
+        authority: &Identifier,
+        attestation: &PurposeKeyAttestation,
+    ) -> Result<VerifyingPublicKey> {
+        let purpose_key_data = self
+            .purpose_keys
+            .purpose_keys_verification()
+            .verify_purpose_key_attestation(Some(authority), attestation)
+            .await?;
+        match purpose_key_data.public_key {
+            PurposePublicKey::SecureChannelStatic(_) => Err(IdentityError::InvalidKeyType)?,
+            PurposePublicKey::CredentialSigning(public_key) => Ok(public_key.into()),
+        }
+    }
+}
@@ -64,10 +64,47 @@ pub enum IdentityError {
     InvalidHex,
     /// Secret Key doesn't correspond to the Identity
     WrongSecretKey,
+    /// The Credential was revoked by its issuer
+    CredentialRevoked,
+    /// Unknown version of the RevocationList
+    UnknownRevocationListVersion,
+    /// Invalid data_type value for RevocationList
+    InvalidRevocationListDataType,
+    /// RevocationList Verification Failed
+    RevocationListVerificationFailed,
+    /// The RevocationList was created before the last RevocationList imported from its issuer
+    RevocationListOutdated,
+    /// The issuers of the credentials don't satisfy the authorities policy
+    AuthoritiesPolicyNotSatisfied,
+    /// The delegation chain of a credential is invalid
+    InvalidDelegation,
+    /// The credential issuer could not be reached
+    CredentialIssuerUnreachable,
+    /// The credential issuer refused to issue a credential
+    CredentialIssuanceDenied,
+    /// Unknown version of the VerificationBundle
+    UnknownVerificationBundleVersion,
+    /// Invalid data_type value for VerificationBundle
+    InvalidVerificationBundleDataType,
+    /// VerificationBundle Verification Failed
+    VerificationBundleVerificationFailed,
+    /// Too many requests to the node failed, the next requests fail until the circuit breaker resets
+    CircuitBreakerOpen,
+    /// The maximum number of secure channels of the node is reached
+    MaxSecureChannelsReached,
 }
 
 impl ockam_core::compat::error::Error for IdentityError {}
 
+impl IdentityError {
+    /// Return the [`IdentityError`] which caused an [`Error`], if there is one
+    #[cfg(feature = "std")]
+    pub fn from_error(err: &Error) -> Option<&IdentityError> {
+        use std::error::Error as _;
+        err.source()?.downcast_ref::<IdentityError>()
+    }
+}
+
 impl core::fmt::Display for IdentityError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         core::fmt::Debug::fmt(self, f)
@@ -77,8 +114,15 @@ impl core::fmt::Display for IdentityError {
 impl From<IdentityError> for Error {
     #[track_caller]
     fn from(err: IdentityError) -> Self {
-        let kind = Kind::Unknown; // FIXME: fill these in with more
-                                  // meaningful error kinds
+        let kind = match err {
+            IdentityError::CredentialIssuerUnreachable => Kind::Io,
+            IdentityError::CredentialIssuanceDenied => Kind::Invalid,
+            IdentityError::RevocationListOutdated => Kind::Invalid,
+            IdentityError::CircuitBreakerOpen => Kind::Io,
+            IdentityError::MaxSecureChannelsReached => Kind::ResourceExhausted,
+            _ => Kind::Unknown, // FIXME: fill these in with more
+                                // meaningful error kinds
+        };
         Error::new(Origin::Identity, kind, err)
     }
 }
@@ -1,6 +1,7 @@
 use ockam_core::compat::sync::Arc;
 use ockam_core::compat::vec::Vec;
-use ockam_core::Result;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Error, Result};
 #[cfg(feature = "storage")]
 use ockam_node::database::SqlxDatabase;
 
@@ -13,12 +14,14 @@ use crate::models::ChangeHistory;
 use crate::purpose_keys::storage::PurposeKeysRepository;
 #[cfg(feature = "storage")]
 use crate::purpose_keys::storage::PurposeKeysSqlxDatabase;
-#[cfg(feature = "storage")]
-use crate::IdentitiesBuilder;
 use crate::{
-    Credentials, CredentialsServer, CredentialsServerModule, Identifier, IdentitiesCreation,
-    Identity, IdentityAttributesRepository, PurposeKeys, Vault,
+    ChangeHistoryDiff, ChangeHistoryReport, CredentialIssuancePolicies, CredentialSchemas,
+    Credentials, CredentialsAuditSink, CredentialsServer, CredentialsServerModule, Identifier,
+    IdentitiesCreation, Identity, IdentityAttributesRepository, PurposeKeys, RevocationsRepository,
+    Vault,
 };
+#[cfg(feature = "storage")]
+use crate::{IdentitiesBuilder, RevocationsSqlxDatabase};
 
 /// This struct supports all the services related to identities
 #[derive(Clone)]
@@ -27,6 +30,10 @@ pub struct Identities {
     change_history_repository: Arc<dyn ChangeHistoryRepository>,
     identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
     purpose_keys_repository: Arc<dyn PurposeKeysRepository>,
+    revocations_repository: Option<Arc<dyn RevocationsRepository>>,
+    credential_schemas: Arc<CredentialSchemas>,
+    issuance_policies: Arc<CredentialIssuancePolicies>,
+    credentials_audit_sink: Option<Arc<dyn CredentialsAuditSink>>,
 }
 
 impl Identities {
@@ -50,6 +57,26 @@ impl Identities {
         self.purpose_keys_repository.clone()
     }
 
+    /// Return the revocations repository, if credentials are checked for revocation
+    pub fn revocations_repository(&self) -> Option<Arc<dyn RevocationsRepository>> {
+        self.revocations_repository.clone()
+    }
+
+    /// Return the registry of schemas used to validate the attributes of credentials
+    pub fn credential_schemas(&self) -> Arc<CredentialSchemas> {
+        self.credential_schemas.clone()
+    }
+
+    /// Return the registry of policies enforced when credentials are issued
+    pub fn issuance_policies(&self) -> Arc<CredentialIssuancePolicies> {
+        self.issuance_policies.clone()
+    }
+
+    /// Return the sink receiving the audit records of credentials issuance and verification
+    pub fn credentials_audit_sink(&self) -> Option<Arc<dyn CredentialsAuditSink>> {
+        self.credentials_audit_sink.clone()
+    }
+
     /// Get an [`Identity`] from the repository
     pub async fn get_identity(&self, identifier: &Identifier) -> Result<Identity> {
         self.identities_creation().get_identity(identifier).await
@@ -62,6 +89,41 @@ impl Identities {
             .await
     }
 
+    /// Return a verified view of the change history of a persisted identity,
+    /// listing each key rotation and the validity of its signatures
+    pub async fn inspect_change_history(
+        &self,
+        identifier: &Identifier,
+    ) -> Result<ChangeHistoryReport> {
+        let change_history = self.get_change_history(identifier).await?;
+        Identity::inspect_change_history(&change_history, self.vault.verifying_vault.clone()).await
+    }
+
+    /// Return a verified view of the change history of an exported identity
+    pub async fn inspect_exported_identity(&self, data: &[u8]) -> Result<ChangeHistoryReport> {
+        let change_history = ChangeHistory::import(data)?;
+        Identity::inspect_change_history(&change_history, self.vault.verifying_vault.clone()).await
+    }
+
+    /// Compare two exported versions of the same identity: `known` is the version
+    /// which was known earlier and `current` the version to audit
+    pub async fn diff_exported_identities(
+        &self,
+        known: &[u8],
+        current: &[u8],
+    ) -> Result<ChangeHistoryDiff> {
+        let known = self.inspect_exported_identity(known).await?;
+        let current = self.inspect_exported_identity(current).await?;
+        if known.identifier() != current.identifier() {
+            return Err(Error::new(
+                Origin::Identity,
+                Kind::Invalid,
+                "the exported identities don't have the same identifier",
+            ));
+        }
+        Ok(current.diff(&known))
+    }
+
     /// Export an [`Identity`] from the repository
     pub async fn export_identity(&self, identifier: &Identifier) -> Result<Vec<u8>> {
         self.get_identity(identifier).await?.export()
@@ -102,6 +164,10 @@ impl Identities {
             self.purpose_keys(),
             self.identities_creation().clone(),
             self.identity_attributes_repository.clone(),
+            self.revocations_repository.clone(),
+            self.credential_schemas.clone(),
+            self.issuance_policies.clone(),
+            self.credentials_audit_sink.clone(),
         ))
     }
 
@@ -118,12 +184,20 @@ impl Identities {
         change_history_repository: Arc<dyn ChangeHistoryRepository>,
         identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
         purpose_keys_repository: Arc<dyn PurposeKeysRepository>,
+        revocations_repository: Option<Arc<dyn RevocationsRepository>>,
+        credential_schemas: Arc<CredentialSchemas>,
+        issuance_policies: Arc<CredentialIssuancePolicies>,
+        credentials_audit_sink: Option<Arc<dyn CredentialsAuditSink>>,
     ) -> Identities {
         Identities {
             vault,
             change_history_repository,
             identity_attributes_repository,
             purpose_keys_repository,
+            revocations_repository,
+            credential_schemas,
+            issuance_policies,
+            credentials_audit_sink,
         }
     }
 
@@ -145,6 +219,10 @@ impl Identities {
                 database.clone(),
             )),
             purpose_keys_repository: Arc::new(PurposeKeysSqlxDatabase::new(database.clone())),
+            revocations_repository: Some(Arc::new(RevocationsSqlxDatabase::new(database.clone()))),
+            credential_schemas: Arc::new(CredentialSchemas::new()),
+            issuance_policies: Arc::new(CredentialIssuancePolicies::new()),
+            credentials_audit_sink: None,
         }
     }
 }
@@ -7,7 +7,10 @@ use ockam_vault::storage::SecretsRepository;
 
 use crate::identities::{ChangeHistoryRepository, Identities};
 use crate::purpose_keys::storage::PurposeKeysRepository;
-use crate::{IdentityAttributesRepository, Vault};
+use crate::{
+    CredentialIssuancePolicies, CredentialSchemas, CredentialsAuditSink,
+    IdentityAttributesRepository, RevocationsRepository, Vault,
+};
 
 /// Builder for Identities services
 #[derive(Clone)]
@@ -16,6 +19,10 @@ pub struct IdentitiesBuilder {
     pub(crate) change_history_repository: Arc<dyn ChangeHistoryRepository>,
     pub(crate) identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
     pub(crate) purpose_keys_repository: Arc<dyn PurposeKeysRepository>,
+    pub(crate) revocations_repository: Option<Arc<dyn RevocationsRepository>>,
+    pub(crate) credential_schemas: Arc<CredentialSchemas>,
+    pub(crate) issuance_policies: Arc<CredentialIssuancePolicies>,
+    pub(crate) credentials_audit_sink: Option<Arc<dyn CredentialsAuditSink>>,
 }
 
 /// Return a default identities
@@ -70,6 +77,45 @@ impl IdentitiesBuilder {
         self
     }
 
+    /// Set a specific repository for revocations, used to check if credentials are revoked
+    pub fn with_revocations_repository(
+        mut self,
+        repository: Arc<dyn RevocationsRepository>,
+    ) -> Self {
+        self.revocations_repository = Some(repository);
+        self
+    }
+
+    /// Don't check if credentials are revoked when they are verified
+    pub fn without_revocations(mut self) -> Self {
+        self.revocations_repository = None;
+        self
+    }
+
+    /// Set the registry of schemas used to validate the attributes of credentials
+    pub fn with_credential_schemas(mut self, credential_schemas: Arc<CredentialSchemas>) -> Self {
+        self.credential_schemas = credential_schemas;
+        self
+    }
+
+    /// Set the registry of policies enforced when credentials are issued
+    pub fn with_issuance_policies(
+        mut self,
+        issuance_policies: Arc<CredentialIssuancePolicies>,
+    ) -> Self {
+        self.issuance_policies = issuance_policies;
+        self
+    }
+
+    /// Set a sink receiving an audit record for each credential issuance and verification
+    pub fn with_credentials_audit_sink(
+        mut self,
+        credentials_audit_sink: Arc<dyn CredentialsAuditSink>,
+    ) -> Self {
+        self.credentials_audit_sink = Some(credentials_audit_sink);
+        self
+    }
+
     /// Build identities
     pub fn build(self) -> Arc<Identities> {
         Arc::new(Identities::new(
@@ -77,6 +123,10 @@ impl IdentitiesBuilder {
             self.change_history_repository,
             self.identity_attributes_repository,
             self.purpose_keys_repository,
+            self.revocations_repository,
+            self.credential_schemas,
+            self.issuance_policies,
+            self.credentials_audit_sink,
         ))
     }
 }
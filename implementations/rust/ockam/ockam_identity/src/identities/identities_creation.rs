@@ -1,7 +1,9 @@
 use ockam_core::compat::sync::Arc;
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{Error, Result};
-use ockam_vault::{SigningSecretKeyHandle, VaultForSigning, VaultForVerifyingSignatures};
+use ockam_vault::{
+    SigningKeyType, SigningSecretKeyHandle, VaultForSigning, VaultForVerifyingSignatures,
+};
 
 use crate::identities::identity_builder::IdentityBuilder;
 use crate::models::{ChangeHistory, Identifier};
@@ -92,9 +94,24 @@ impl IdentitiesCreation {
         Ok(identity.identifier().clone())
     }
 
+    /// Create an `Identity` with a fresh key of the given type and store it
+    pub async fn create_identity_with_key_type(
+        &self,
+        key_type: SigningKeyType,
+    ) -> Result<Identifier> {
+        let builder = self.identity_builder().with_random_key(key_type);
+        builder.build().await
+    }
+
     /// Rotate an existing `Identity` and update the stored version
+    /// The new key has the same type as the current key of the `Identity`
     pub async fn rotate_identity(&self, identifier: &Identifier) -> Result<()> {
-        let builder = self.identity_builder();
+        let key_type = self
+            .get_identity(identifier)
+            .await?
+            .get_latest_public_key()?
+            .key_type();
+        let builder = self.identity_builder().with_random_key(key_type);
         let options = builder.build_options().await?;
 
         self.rotate_identity_with_options(identifier, options).await
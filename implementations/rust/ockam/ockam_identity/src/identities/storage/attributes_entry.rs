@@ -16,6 +16,7 @@ pub struct AttributesEntry {
     #[n(2)] added: TimestampInSeconds,
     #[n(3)] expires: Option<TimestampInSeconds>,
     #[n(4)] attested_by: Option<Identifier>,
+    #[n(5)] attrs_expires: Option<BTreeMap<Vec<u8>, TimestampInSeconds>>,
 }
 
 impl AttributesEntry {
@@ -35,9 +36,23 @@ impl AttributesEntry {
             added,
             expires,
             attested_by,
+            attrs_expires: None,
         }
     }
 
+    /// Set expiration times for the attributes which expire before the entry
+    pub fn with_attrs_expires(
+        mut self,
+        attrs_expires: BTreeMap<Vec<u8>, TimestampInSeconds>,
+    ) -> Self {
+        self.attrs_expires = if attrs_expires.is_empty() {
+            None
+        } else {
+            Some(attrs_expires)
+        };
+        self
+    }
+
     /// The entry attributes
     pub fn attrs(&self) -> &BTreeMap<Vec<u8>, Vec<u8>> {
         &self.attrs
@@ -57,6 +72,36 @@ impl AttributesEntry {
     pub fn attested_by(&self) -> Option<Identifier> {
         self.attested_by.to_owned()
     }
+
+    /// Expiration times of the attributes which expire before the entry
+    pub fn attrs_expires(&self) -> Option<&BTreeMap<Vec<u8>, TimestampInSeconds>> {
+        self.attrs_expires.as_ref()
+    }
+
+    /// Expiration time of an attribute, if it expires before the entry
+    pub fn attr_expires(&self, key: &[u8]) -> Option<TimestampInSeconds> {
+        self.attrs_expires
+            .as_ref()
+            .and_then(|e| e.get(key))
+            .copied()
+    }
+
+    /// The attributes which are still valid at a given time
+    pub fn valid_attrs_at(&self, now: TimestampInSeconds) -> BTreeMap<Vec<u8>, Vec<u8>> {
+        self.attrs
+            .iter()
+            .filter(|(key, _)| self.attr_expires(key).map(|e| now < e).unwrap_or(true))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+
+    /// The attributes which are still valid now
+    pub fn valid_attrs(&self) -> Result<BTreeMap<Vec<u8>, Vec<u8>>> {
+        if self.attrs_expires.is_none() {
+            return Ok(self.attrs.clone());
+        }
+        Ok(self.valid_attrs_at(now()?))
+    }
 }
 
 impl AttributesEntry {
@@ -73,6 +118,7 @@ impl AttributesEntry {
             added: now()?,
             expires,
             attested_by,
+            attrs_expires: None,
         })
     }
 }
@@ -43,7 +43,7 @@ impl IdentityAttributesSqlxDatabase {
 impl IdentityAttributesRepository for IdentityAttributesSqlxDatabase {
     async fn get_attributes(&self, identity: &Identifier) -> Result<Option<AttributesEntry>> {
         let query = query_as(
-            "SELECT identifier, attributes, added, expires, attested_by FROM identity_attributes WHERE identifier=$1 AND node_name=$2"
+            "SELECT identifier, attributes, added, expires, attested_by, attributes_expires FROM identity_attributes WHERE identifier=$1 AND node_name=$2"
             )
             .bind(identity.to_sql())
             .bind(self.database.node_name()?.to_sql());
@@ -56,7 +56,7 @@ impl IdentityAttributesRepository for IdentityAttributesSqlxDatabase {
 
     async fn list_attributes_by_identifier(&self) -> Result<Vec<(Identifier, AttributesEntry)>> {
         let query = query_as(
-            "SELECT identifier, attributes, added, expires, attested_by FROM identity_attributes WHERE node_name=$1",
+            "SELECT identifier, attributes, added, expires, attested_by, attributes_expires FROM identity_attributes WHERE node_name=$1",
             )
             .bind(self.database.node_name()?.to_sql());
         let result: Vec<IdentityAttributesRow> =
@@ -69,14 +69,15 @@ impl IdentityAttributesRepository for IdentityAttributesSqlxDatabase {
 
     async fn put_attributes(&self, subject: &Identifier, entry: AttributesEntry) -> Result<()> {
         let query = query(
-            "INSERT OR REPLACE INTO identity_attributes (identifier, attributes, added, expires, attested_by, node_name) VALUES (?, ?, ?, ?, ?, ?)"
+            "INSERT OR REPLACE INTO identity_attributes (identifier, attributes, added, expires, attested_by, node_name, attributes_expires) VALUES (?, ?, ?, ?, ?, ?, ?)"
             )
             .bind(subject.to_sql())
             .bind(minicbor::to_vec(entry.attrs())?.to_sql())
             .bind(entry.added().to_sql())
             .bind(entry.expires().map(|e| e.to_sql()))
             .bind(entry.attested_by().map(|e| e.to_sql()))
-            .bind(self.database.node_name()?.to_sql());
+            .bind(self.database.node_name()?.to_sql())
+            .bind(entry.attrs_expires().map(minicbor::to_vec).transpose()?.map(|e| e.to_sql()));
         query.execute(&*self.database.pool).await.void()
     }
 
@@ -104,6 +105,7 @@ struct IdentityAttributesRow {
     added: i64,
     expires: Option<i64>,
     attested_by: Option<String>,
+    attributes_expires: Option<Vec<u8>>,
 }
 
 impl IdentityAttributesRow {
@@ -122,12 +124,17 @@ impl IdentityAttributesRow {
             .map(|v| Identifier::from_str(&v))
             .transpose()?;
 
-        Ok(AttributesEntry::new(
-            attributes,
-            added,
-            expires,
-            attested_by,
-        ))
+        let attributes_expires = self
+            .attributes_expires
+            .as_ref()
+            .map(|e| minicbor::decode(e.as_slice()).map_err(SqlxDatabase::map_decode_err))
+            .transpose()?
+            .unwrap_or_default();
+
+        Ok(
+            AttributesEntry::new(attributes, added, expires, attested_by)
+                .with_attrs_expires(attributes_expires),
+        )
     }
 }
 
@@ -187,7 +194,11 @@ mod tests {
             TimestampInSeconds(1000),
             Some(TimestampInSeconds(2000)),
             Some(identifier.clone()),
-        ))
+        )
+        .with_attrs_expires(BTreeMap::from([(
+            "age".as_bytes().to_vec(),
+            TimestampInSeconds(1500),
+        )])))
     }
 
     async fn create_identity() -> Result<Identifier> {
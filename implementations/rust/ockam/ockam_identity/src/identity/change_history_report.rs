@@ -0,0 +1,356 @@
+use core::fmt;
+use core::fmt::{Display, Formatter};
+
+use ockam_core::compat::string::String;
+use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
+use ockam_core::Result;
+use ockam_vault::{VaultForVerifyingSignatures, VerifyingPublicKey};
+
+use crate::models::{ChangeHash, ChangeHistory, Identifier, PrimaryPublicKey, TimestampInSeconds};
+use crate::{Identity, IdentityHistoryComparison};
+
+/// Type of the primary key of a [`crate::models::Change`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PrimaryKeyType {
+    /// EdDSA Ed25519 key
+    EdDSACurve25519,
+    /// ECDSA P256 key
+    ECDSASHA256CurveP256,
+}
+
+impl Display for PrimaryKeyType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PrimaryKeyType::EdDSACurve25519 => f.write_str("EdDSACurve25519"),
+            PrimaryKeyType::ECDSASHA256CurveP256 => f.write_str("ECDSASHA256CurveP256"),
+        }
+    }
+}
+
+/// Verified view of one change (key rotation) of an identity change history
+#[derive(Clone, Debug)]
+pub struct ChangeReport {
+    change_hash: ChangeHash,
+    previous_change: Option<ChangeHash>,
+    version: u8,
+    key_type: PrimaryKeyType,
+    fingerprint: String,
+    created_at: TimestampInSeconds,
+    expires_at: TimestampInSeconds,
+    revoke_all_purpose_keys: bool,
+    is_consistent: bool,
+    is_signature_valid: bool,
+    is_previous_signature_valid: Option<bool>,
+}
+
+impl ChangeReport {
+    /// Hash of the change
+    pub fn change_hash(&self) -> &ChangeHash {
+        &self.change_hash
+    }
+
+    /// Hash of the previous change, absent for the first change
+    pub fn previous_change(&self) -> Option<&ChangeHash> {
+        self.previous_change.as_ref()
+    }
+
+    /// Version of the change data format
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// Type of the primary key introduced by this change
+    pub fn key_type(&self) -> PrimaryKeyType {
+        self.key_type
+    }
+
+    /// Hex-encoded SHA256 of the primary public key introduced by this change
+    pub fn fingerprint(&self) -> &str {
+        &self.fingerprint
+    }
+
+    /// Creation time of the change
+    pub fn created_at(&self) -> TimestampInSeconds {
+        self.created_at
+    }
+
+    /// Expiration time of the primary key introduced by this change
+    pub fn expires_at(&self) -> TimestampInSeconds {
+        self.expires_at
+    }
+
+    /// True if the purpose keys attested by the previous primary key are revoked
+    pub fn revoke_all_purpose_keys(&self) -> bool {
+        self.revoke_all_purpose_keys
+    }
+
+    /// True if the change is correctly linked to the previous change: it refers to its hash,
+    /// is not created before it and doesn't use an older data format
+    pub fn is_consistent(&self) -> bool {
+        self.is_consistent
+    }
+
+    /// True if the change is correctly signed by its own primary key
+    pub fn is_signature_valid(&self) -> bool {
+        self.is_signature_valid
+    }
+
+    /// For every change but the first one, true if the change is correctly signed
+    /// by the primary key of the previous change
+    pub fn is_previous_signature_valid(&self) -> Option<bool> {
+        self.is_previous_signature_valid
+    }
+
+    /// True if the change is consistent and all its signatures are valid
+    pub fn is_valid(&self) -> bool {
+        self.is_consistent
+            && self.is_signature_valid
+            && self.is_previous_signature_valid.unwrap_or(true)
+    }
+}
+
+/// Verified view of an identity change history, listing each key rotation.
+/// Contrary to an [`Identity`] import, an invalid change doesn't produce an error,
+/// it is reported as invalid, so that the history can be audited.
+#[derive(Clone, Debug)]
+pub struct ChangeHistoryReport {
+    identifier: Option<Identifier>,
+    changes: Vec<ChangeReport>,
+}
+
+impl ChangeHistoryReport {
+    /// Identifier of the identity, computed from its first change, if any
+    pub fn identifier(&self) -> Option<&Identifier> {
+        self.identifier.as_ref()
+    }
+
+    /// Changes of the identity, from the oldest to the most recent
+    pub fn changes(&self) -> &[ChangeReport] {
+        self.changes.as_slice()
+    }
+
+    /// True if the history is not empty and all its changes are valid
+    pub fn is_valid(&self) -> bool {
+        !self.changes.is_empty() && self.changes.iter().all(|change| change.is_valid())
+    }
+
+    /// Compare this change history to a previously known change history of the same identity
+    pub fn diff(&self, known: &ChangeHistoryReport) -> ChangeHistoryDiff {
+        let common_changes = self
+            .changes
+            .iter()
+            .zip(known.changes.iter())
+            .take_while(|(current, known)| current.change_hash == known.change_hash)
+            .count();
+
+        let comparison = if common_changes < self.changes.len().min(known.changes.len()) {
+            IdentityHistoryComparison::Conflict
+        } else if self.changes.len() > known.changes.len() {
+            IdentityHistoryComparison::Newer
+        } else if self.changes.len() < known.changes.len() {
+            IdentityHistoryComparison::Older
+        } else {
+            IdentityHistoryComparison::Equal
+        };
+
+        ChangeHistoryDiff {
+            comparison,
+            common_changes,
+            known_changes: known.changes[common_changes..].to_vec(),
+            current_changes: self.changes[common_changes..].to_vec(),
+        }
+    }
+}
+
+/// Differences between two versions of the change history of the same identity
+#[derive(Clone, Debug)]
+pub struct ChangeHistoryDiff {
+    comparison: IdentityHistoryComparison,
+    common_changes: usize,
+    known_changes: Vec<ChangeReport>,
+    current_changes: Vec<ChangeReport>,
+}
+
+impl ChangeHistoryDiff {
+    /// Comparison of the current version to the known version
+    pub fn comparison(&self) -> &IdentityHistoryComparison {
+        &self.comparison
+    }
+
+    /// Number of changes shared by both versions
+    pub fn common_changes(&self) -> usize {
+        self.common_changes
+    }
+
+    /// Changes which are only present in the known version
+    pub fn known_changes(&self) -> &[ChangeReport] {
+        self.known_changes.as_slice()
+    }
+
+    /// Changes which are only present in the current version
+    pub fn current_changes(&self) -> &[ChangeReport] {
+        self.current_changes.as_slice()
+    }
+}
+
+impl Identity {
+    /// Verify each change of a change history and return a report describing them.
+    /// An error is only returned if a change can't be decoded
+    pub async fn inspect_change_history(
+        change_history: &ChangeHistory,
+        vault: Arc<dyn VaultForVerifyingSignatures>,
+    ) -> Result<ChangeHistoryReport> {
+        let mut changes: Vec<ChangeReport> = Vec::with_capacity(change_history.0.len());
+        let mut previous: Option<(u8, TimestampInSeconds, VerifyingPublicKey)> = None;
+
+        for change in change_history.0.iter() {
+            let details = Self::get_change_details(change, vault.clone()).await?;
+            let data = &details.change_data;
+            let public_key: VerifyingPublicKey = data.primary_public_key.clone().into();
+
+            let is_consistent = match (&previous, changes.last()) {
+                (Some((version, created_at, _)), Some(previous_change)) => {
+                    *version <= details.version
+                        && *created_at <= data.created_at
+                        && Some(&previous_change.change_hash) == data.previous_change.as_ref()
+                }
+                _ => data.previous_change.is_none(),
+            };
+
+            let is_signature_valid = Self::verify_change_signature(
+                &public_key,
+                details.change_full_hash,
+                &change.signature,
+                vault.clone(),
+            )
+            .await
+            .unwrap_or(false);
+
+            let is_previous_signature_valid = match (&previous, &change.previous_signature) {
+                (Some((_, _, previous_key)), Some(previous_signature)) => Some(
+                    Self::verify_change_signature(
+                        previous_key,
+                        details.change_full_hash,
+                        previous_signature,
+                        vault.clone(),
+                    )
+                    .await
+                    .unwrap_or(false),
+                ),
+                (Some(_), None) => Some(false),
+                (None, _) => None,
+            };
+
+            let (key_type, key_bytes) = match &data.primary_public_key {
+                PrimaryPublicKey::EdDSACurve25519(key) => {
+                    (PrimaryKeyType::EdDSACurve25519, key.0.to_vec())
+                }
+                PrimaryPublicKey::ECDSASHA256CurveP256(key) => {
+                    (PrimaryKeyType::ECDSASHA256CurveP256, key.0.to_vec())
+                }
+            };
+            let fingerprint = hex::encode(vault.sha256(&key_bytes).await?.0);
+
+            changes.push(ChangeReport {
+                change_hash: details.change_hash.clone(),
+                previous_change: data.previous_change.clone(),
+                version: details.version,
+                key_type,
+                fingerprint,
+                created_at: data.created_at,
+                expires_at: data.expires_at,
+                revoke_all_purpose_keys: data.revoke_all_purpose_keys,
+                is_consistent,
+                is_signature_valid,
+                is_previous_signature_valid,
+            });
+            previous = Some((details.version, data.created_at, public_key));
+        }
+
+        let identifier = changes
+            .first()
+            .map(|change| change.change_hash.clone().into());
+
+        Ok(ChangeHistoryReport {
+            identifier,
+            changes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ChangeSignature;
+    use crate::{identities, Vault};
+
+    #[tokio::test]
+    async fn test_inspect_and_diff_change_history() -> Result<()> {
+        let identities = identities().await?;
+        let identifier = identities.identities_creation().create_identity().await?;
+        let known = identities.get_change_history(&identifier).await?;
+
+        identities
+            .identities_creation()
+            .rotate_identity(&identifier)
+            .await?;
+        let current = identities.get_change_history(&identifier).await?;
+
+        let vault = Vault::create_verifying_vault();
+        let known = Identity::inspect_change_history(&known, vault.clone()).await?;
+        let current = Identity::inspect_change_history(&current, vault).await?;
+
+        assert!(current.is_valid());
+        assert_eq!(current.identifier(), Some(&identifier));
+        assert_eq!(current.changes().len(), 2);
+        let first = &current.changes()[0];
+        let second = &current.changes()[1];
+        assert_eq!(first.key_type(), PrimaryKeyType::EdDSACurve25519);
+        assert_eq!(first.is_previous_signature_valid(), None);
+        assert_eq!(second.is_previous_signature_valid(), Some(true));
+        assert_eq!(second.previous_change(), Some(first.change_hash()));
+        assert_ne!(first.fingerprint(), second.fingerprint());
+
+        let diff = current.diff(&known);
+        assert_eq!(diff.comparison(), &IdentityHistoryComparison::Newer);
+        assert_eq!(diff.common_changes(), 1);
+        assert!(diff.known_changes().is_empty());
+        assert_eq!(diff.current_changes().len(), 1);
+        assert_eq!(
+            diff.current_changes()[0].change_hash(),
+            second.change_hash()
+        );
+
+        let diff = known.diff(&current);
+        assert_eq!(diff.comparison(), &IdentityHistoryComparison::Older);
+        assert_eq!(diff.known_changes().len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_inspect_invalid_signature() -> Result<()> {
+        let identities = identities().await?;
+        let identifier = identities.identities_creation().create_identity().await?;
+        identities
+            .identities_creation()
+            .rotate_identity(&identifier)
+            .await?;
+        let mut change_history = identities.get_change_history(&identifier).await?;
+
+        // corrupt the signature of the rotation
+        match &mut change_history.0[1].signature {
+            ChangeSignature::EdDSACurve25519(signature) => signature.0[0] ^= 0xff,
+            ChangeSignature::ECDSASHA256CurveP256(signature) => signature.0[0] ^= 0xff,
+        }
+
+        let report =
+            Identity::inspect_change_history(&change_history, Vault::create_verifying_vault())
+                .await?;
+        assert!(!report.is_valid());
+        assert!(report.changes()[0].is_valid());
+        assert!(!report.changes()[1].is_signature_valid());
+        assert!(report.changes()[1].is_consistent());
+        Ok(())
+    }
+}
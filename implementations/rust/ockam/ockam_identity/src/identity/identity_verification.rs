@@ -7,11 +7,11 @@ use ockam_core::compat::vec::Vec;
 use ockam_core::Result;
 use ockam_vault::{VaultForVerifyingSignatures, VerifyingPublicKey, SHA256_LENGTH};
 
-struct ChangeDetails {
-    version: u8,
-    change_hash: ChangeHash,
-    change_full_hash: [u8; SHA256_LENGTH],
-    change_data: ChangeData,
+pub(super) struct ChangeDetails {
+    pub(super) version: u8,
+    pub(super) change_hash: ChangeHash,
+    pub(super) change_full_hash: [u8; SHA256_LENGTH],
+    pub(super) change_data: ChangeData,
 }
 
 impl Identity {
@@ -30,7 +30,7 @@ impl Identity {
         Ok(to_be_verified_changes)
     }
 
-    async fn get_change_details(
+    pub(super) async fn get_change_details(
         change: &Change,
         vault: Arc<dyn VaultForVerifyingSignatures>,
     ) -> Result<ChangeDetails> {
@@ -128,7 +128,7 @@ impl Identity {
         Ok(())
     }
 
-    async fn verify_change_signature(
+    pub(super) async fn verify_change_signature(
         public_key: &VerifyingPublicKey,
         hash: [u8; 32],
         signature: &ChangeSignature,
@@ -1,7 +1,7 @@
 use crate::models::{ChangeHash, Identifier, TimestampInSeconds};
 use minicbor::bytes::ByteVec;
 use minicbor::{Decode, Encode};
-use ockam_core::compat::{collections::BTreeMap, vec::Vec};
+use ockam_core::compat::{collections::BTreeMap, string::String, vec::Vec};
 use ockam_vault::{ECDSASHA256CurveP256Signature, EdDSACurve25519Signature};
 
 /// `data_type` value in [`VersionedData`] struct when used with [`Credential`]
@@ -61,4 +61,29 @@ pub struct Attributes {
     #[n(0)] pub schema: CredentialSchemaIdentifier,
     /// Set of keys&values
     #[n(1)] pub map: BTreeMap<ByteVec, ByteVec>,
+    /// Types of the values, for the keys which were set with an [`AttributeValue`].
+    /// The values are also present in the `map` field, encoded as bytes, for backward compatibility
+    #[n(2)] pub typed_map: Option<BTreeMap<ByteVec, AttributeValue>>,
+    /// Time to live in seconds, counted from the creation of the [`Credential`], for the keys
+    /// which expire before the [`Credential`] itself
+    #[n(3)] pub ttls: Option<BTreeMap<ByteVec, u64>>,
+    /// Sorted SHA-256 digests of the [`AttributeDisclosure`](super::AttributeDisclosure)s of the
+    /// attributes which are not in the `map` field, for a credential supporting selective disclosure
+    #[n(4)] pub commitments: Option<Vec<ByteVec>>,
+}
+
+/// Typed value of an attribute
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[rustfmt::skip]
+pub enum AttributeValue {
+    /// A UTF-8 string
+    #[n(0)] String(#[n(0)] String),
+    /// A signed integer
+    #[n(1)] Int(#[n(0)] i64),
+    /// A boolean
+    #[n(2)] Bool(#[n(0)] bool),
+    /// Raw bytes
+    #[n(3)] Bytes(#[n(0)] ByteVec),
+    /// A list of UTF-8 strings
+    #[n(4)] StringList(#[n(0)] Vec<String>),
 }
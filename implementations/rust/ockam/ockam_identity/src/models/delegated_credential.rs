@@ -0,0 +1,26 @@
+use crate::models::{CredentialAndPurposeKey, CredentialSchemaIdentifier};
+use minicbor::{Decode, Encode};
+use ockam_core::compat::vec::Vec;
+
+/// Attribute of a delegation [`super::Credential`]: the list of the attribute names that the
+/// subject of the delegation is allowed to attest about other identities
+pub const DELEGATED_ATTRIBUTES_KEY: &str = "ockam-delegated-attributes";
+
+/// [`CredentialSchemaIdentifier`] of the delegation credentials
+pub const DELEGATION_SCHEMA_IDENTIFIER: CredentialSchemaIdentifier =
+    CredentialSchemaIdentifier(u64::MAX);
+
+/// Maximum number of delegations between a root authority and a credential issuer
+pub const MAX_DELEGATION_CHAIN_LENGTH: usize = 4;
+
+/// [`CredentialAndPurposeKey`] issued by a sub-issuer, with the chain of delegation
+/// credentials authorizing that sub-issuer
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[rustfmt::skip]
+pub struct DelegatedCredential {
+    /// Credential issued by the last sub-issuer of the chain
+    #[n(0)] pub credential: CredentialAndPurposeKey,
+    /// Delegation credentials, starting with the delegation issued by the root authority.
+    /// Each delegation is issued by the subject of the previous one
+    #[n(1)] pub delegation_chain: Vec<CredentialAndPurposeKey>,
+}
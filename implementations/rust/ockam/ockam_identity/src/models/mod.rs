@@ -1,16 +1,24 @@
 mod change_history;
 mod credential;
 mod credential_and_purpose_key;
+mod delegated_credential;
 mod identifiers;
 mod purpose_key_attestation;
+mod revocation_list;
+mod selective_disclosure;
 mod timestamp;
 mod utils;
+mod verification_bundle;
 mod versioned_data;
 
 pub use change_history::*;
 pub use credential::*;
 pub use credential_and_purpose_key::*;
+pub use delegated_credential::*;
 pub use identifiers::*;
 pub use purpose_key_attestation::*;
+pub use revocation_list::*;
+pub use selective_disclosure::*;
 pub use timestamp::*;
+pub use verification_bundle::*;
 pub use versioned_data::*;
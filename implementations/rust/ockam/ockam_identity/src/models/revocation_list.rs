@@ -0,0 +1,53 @@
+use crate::models::{CredentialSignature, Identifier, PurposeKeyAttestation, TimestampInSeconds};
+use minicbor::{Decode, Encode};
+use ockam_core::compat::vec::Vec;
+
+/// `data_type` value in [`VersionedData`] struct when used with [`RevocationList`]
+pub const REVOCATION_LIST_DATA_TYPE: u8 = 4;
+
+/// List of the revocations published by an Authority
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[rustfmt::skip]
+pub struct RevocationList {
+    /// CBOR serialized [`super::VersionedData`]
+    /// where VersionedData::data is CBOR serialized [`RevocationListData`]
+    /// and VersionedData::data_type is [`REVOCATION_LIST_DATA_TYPE`]
+    #[cbor(with = "minicbor::bytes")]
+    #[n(0)] pub data: Vec<u8>,
+    /// Signature over data field using the Authority Credentials [`super::PurposeKeyAttestation`]
+    #[n(1)] pub signature: CredentialSignature,
+}
+
+/// [`RevocationList`] and the corresponding [`PurposeKeyAttestation`] that was used to sign that
+/// [`RevocationList`] and will be used to verify it
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[rustfmt::skip]
+pub struct RevocationListAndPurposeKey {
+    /// [`RevocationList`]
+    #[n(0)] pub revocation_list: RevocationList,
+    /// Corresponding [`PurposeKeyAttestation`] that was used to sign that
+    /// [`RevocationList`] and will be used to verify it
+    #[n(1)] pub purpose_key_attestation: PurposeKeyAttestation,
+}
+
+/// Data inside a [`RevocationList`]
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[rustfmt::skip]
+pub struct RevocationListData {
+    /// Revoked subjects
+    #[n(0)] pub revocations: Vec<Revocation>,
+    /// Creation [`TimestampInSeconds`] (UTC)
+    #[n(1)] pub created_at: TimestampInSeconds,
+    /// Expiration [`TimestampInSeconds`] (UTC)
+    #[n(2)] pub expires_at: TimestampInSeconds,
+}
+
+/// Revocation of all the Credentials issued to a subject before a given time
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[rustfmt::skip]
+pub struct Revocation {
+    /// Subject of the revoked Credentials
+    #[n(0)] pub subject: Identifier,
+    /// Credentials created at or before that [`TimestampInSeconds`] (UTC) are revoked
+    #[n(1)] pub revoked_at: TimestampInSeconds,
+}
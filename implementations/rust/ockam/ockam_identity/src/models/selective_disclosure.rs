@@ -0,0 +1,43 @@
+use crate::models::{AttributeValue, CredentialAndPurposeKey};
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+use ockam_core::compat::vec::Vec;
+
+/// Value of an attribute which is not signed as such in a [`super::Credential`].
+/// Instead, the credential contains the SHA-256 digest of the CBOR encoding of the disclosure,
+/// so that the holder of the credential can reveal the attribute, or not.
+/// The random salt prevents a verifier from guessing the undisclosed values
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[rustfmt::skip]
+pub struct AttributeDisclosure {
+    /// Random salt
+    #[n(0)] pub salt: ByteVec,
+    /// Key of the attribute
+    #[n(1)] pub key: ByteVec,
+    /// Value of the attribute
+    #[n(2)] pub value: ByteVec,
+    /// Typed value of the attribute, if it was set with an [`AttributeValue`]
+    #[n(3)] pub typed_value: Option<AttributeValue>,
+}
+
+/// [`CredentialAndPurposeKey`] with committed attributes, and the disclosures of all its
+/// attributes. It is kept by the subject of the credential to create [`CredentialPresentation`]s
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[rustfmt::skip]
+pub struct DisclosableCredential {
+    /// Credential containing the digests of the disclosures
+    #[n(0)] pub credential: CredentialAndPurposeKey,
+    /// Disclosures of all the attributes of the credential
+    #[n(1)] pub disclosures: Vec<AttributeDisclosure>,
+}
+
+/// [`CredentialAndPurposeKey`] with committed attributes, and the disclosures of the attributes
+/// revealed to a verifier
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[rustfmt::skip]
+pub struct CredentialPresentation {
+    /// Credential containing the digests of the disclosures
+    #[n(0)] pub credential: CredentialAndPurposeKey,
+    /// Disclosures of the revealed attributes
+    #[n(1)] pub disclosures: Vec<AttributeDisclosure>,
+}
@@ -1,6 +1,12 @@
-use crate::models::{CredentialData, CredentialSignature, VersionedData, CREDENTIAL_DATA_TYPE};
+use crate::models::{
+    AttributeValue, Attributes, CredentialData, CredentialSignature, VersionedData,
+    CREDENTIAL_DATA_TYPE,
+};
 use crate::{Credential, IdentityError};
 
+use minicbor::bytes::ByteVec;
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::string::{String, ToString};
 use ockam_core::compat::vec::Vec;
 use ockam_core::Result;
 use ockam_vault::Signature;
@@ -53,3 +59,144 @@ impl From<Signature> for CredentialSignature {
         }
     }
 }
+
+impl Attributes {
+    /// Return the typed value of an attribute.
+    /// Values which were set without a type are returned as strings when they are valid UTF-8
+    pub fn get_attribute(&self, key: &[u8]) -> Option<AttributeValue> {
+        let key = ByteVec::from(key.to_vec());
+        if let Some(value) = self.typed_map.as_ref().and_then(|m| m.get(&key)) {
+            return Some(value.clone());
+        }
+        self.map
+            .get(&key)
+            .map(|value| AttributeValue::from_bytes(value.to_vec()))
+    }
+
+    /// Return true if each typed value is also present in the untyped map, with the same encoding.
+    /// Schemas are validated against the typed values while access controls read the untyped
+    /// map, so both must hold the same value
+    pub fn has_consistent_typed_values(&self) -> bool {
+        self.typed_map.iter().flatten().all(|(key, typed_value)| {
+            self.map
+                .get(key)
+                .is_some_and(|value| value.as_slice() == typed_value.to_bytes().as_slice())
+        })
+    }
+
+    /// Return the time to live of an attribute, in seconds after the creation of the
+    /// credential, if the attribute has its own time to live
+    pub fn get_ttl(&self, key: &[u8]) -> Option<u64> {
+        self.ttls
+            .as_ref()
+            .and_then(|ttls| ttls.get(&ByteVec::from(key.to_vec())))
+            .copied()
+    }
+
+    /// Return the typed values of all the attributes
+    pub fn get_attributes(&self) -> BTreeMap<Vec<u8>, AttributeValue> {
+        self.map
+            .keys()
+            .filter_map(|key| self.get_attribute(key).map(|value| (key.to_vec(), value)))
+            .collect()
+    }
+}
+
+impl AttributeValue {
+    /// Create a value from untyped bytes: a string if the bytes are valid UTF-8, raw bytes otherwise
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        match String::from_utf8(bytes) {
+            Ok(s) => AttributeValue::String(s),
+            Err(e) => AttributeValue::Bytes(e.into_bytes().into()),
+        }
+    }
+
+    /// Encode the value as bytes, as stored in the untyped map of [`Attributes`].
+    /// Integers and booleans are encoded as strings and list items are separated by commas
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            AttributeValue::String(s) => s.as_bytes().to_vec(),
+            AttributeValue::Int(i) => i.to_string().into_bytes(),
+            AttributeValue::Bool(b) => b.to_string().into_bytes(),
+            AttributeValue::Bytes(b) => b.to_vec(),
+            AttributeValue::StringList(l) => l.join(",").into_bytes(),
+        }
+    }
+
+    /// Return the value if it is a string
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            AttributeValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Return the value if it is an integer
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            AttributeValue::Int(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    /// Return the value if it is a boolean
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            AttributeValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// Return the value if it is a list of strings
+    pub fn as_string_list(&self) -> Option<&[String]> {
+        match self {
+            AttributeValue::StringList(l) => Some(l),
+            _ => None,
+        }
+    }
+
+    /// Return the value as bytes if it is raw bytes or a string
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            AttributeValue::Bytes(b) => Some(b),
+            AttributeValue::String(s) => Some(s.as_bytes()),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for AttributeValue {
+    fn from(value: &str) -> Self {
+        AttributeValue::String(value.to_string())
+    }
+}
+
+impl From<String> for AttributeValue {
+    fn from(value: String) -> Self {
+        AttributeValue::String(value)
+    }
+}
+
+impl From<i64> for AttributeValue {
+    fn from(value: i64) -> Self {
+        AttributeValue::Int(value)
+    }
+}
+
+impl From<bool> for AttributeValue {
+    fn from(value: bool) -> Self {
+        AttributeValue::Bool(value)
+    }
+}
+
+impl From<Vec<u8>> for AttributeValue {
+    fn from(value: Vec<u8>) -> Self {
+        AttributeValue::Bytes(value.into())
+    }
+}
+
+impl From<Vec<String>> for AttributeValue {
+    fn from(value: Vec<String>) -> Self {
+        AttributeValue::StringList(value)
+    }
+}
@@ -2,4 +2,7 @@ mod change_history;
 mod credentials;
 mod identifiers;
 mod purpose_key_attestation;
+mod revocation_list;
+mod selective_disclosure;
 mod timestamp;
+mod verification_bundle;
@@ -0,0 +1,31 @@
+use crate::models::{AttributeDisclosure, CredentialPresentation, DisclosableCredential};
+use ockam_core::compat::vec::Vec;
+use ockam_core::Result;
+use ockam_vault::VaultForVerifyingSignatures;
+
+impl AttributeDisclosure {
+    /// SHA-256 digest of the disclosure, as committed in a [`crate::models::Credential`]
+    pub async fn digest(
+        &self,
+        verifying_vault: &dyn VaultForVerifyingSignatures,
+    ) -> Result<Vec<u8>> {
+        let encoded = minicbor::to_vec(self)?;
+        Ok(verifying_vault.sha256(&encoded).await?.0.to_vec())
+    }
+}
+
+impl DisclosableCredential {
+    /// Create a presentation revealing only the attributes with the given keys
+    pub fn present<K: AsRef<[u8]>>(&self, keys: &[K]) -> CredentialPresentation {
+        let disclosures = self
+            .disclosures
+            .iter()
+            .filter(|d| keys.iter().any(|k| k.as_ref() == d.key.as_slice()))
+            .cloned()
+            .collect();
+        CredentialPresentation {
+            credential: self.credential.clone(),
+            disclosures,
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use crate::models::{
+    VerificationBundle, VerificationBundleData, VersionedData, VERIFICATION_BUNDLE_DATA_TYPE,
+};
+use crate::IdentityError;
+
+use ockam_core::compat::vec::Vec;
+use ockam_core::Result;
+
+impl VerificationBundle {
+    /// Create [`VersionedData`] with corresponding version and data_type
+    pub fn create_versioned_data(data: Vec<u8>) -> VersionedData {
+        VersionedData {
+            version: 1,
+            data_type: VERIFICATION_BUNDLE_DATA_TYPE,
+            data,
+        }
+    }
+
+    /// Extract [`VerificationBundleData`]
+    pub fn get_verification_bundle_data(&self) -> Result<VerificationBundleData> {
+        VerificationBundleData::get_data(&minicbor::decode(&self.data)?)
+    }
+}
+
+impl VerificationBundleData {
+    /// Extract [`VerificationBundleData`] from [`VersionedData`]
+    pub fn get_data(versioned_data: &VersionedData) -> Result<Self> {
+        if versioned_data.version != 1 {
+            return Err(IdentityError::UnknownVerificationBundleVersion)?;
+        }
+
+        if versioned_data.data_type != VERIFICATION_BUNDLE_DATA_TYPE {
+            return Err(IdentityError::InvalidVerificationBundleDataType)?;
+        }
+
+        Ok(minicbor::decode(&versioned_data.data)?)
+    }
+}
@@ -0,0 +1,41 @@
+use crate::models::{
+    ChangeHistory, CredentialSignature, PurposeKeyAttestation, RevocationListAndPurposeKey,
+    TimestampInSeconds,
+};
+use minicbor::{Decode, Encode};
+use ockam_core::compat::vec::Vec;
+
+/// `data_type` value in [`VersionedData`] struct when used with [`VerificationBundle`]
+pub const VERIFICATION_BUNDLE_DATA_TYPE: u8 = 5;
+
+/// Everything needed to verify the credentials of an Authority on a node which can't reach
+/// that Authority: its identity, its credentials purpose keys and its revocations
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[rustfmt::skip]
+pub struct VerificationBundle {
+    /// CBOR serialized [`super::VersionedData`]
+    /// where VersionedData::data is CBOR serialized [`VerificationBundleData`]
+    /// and VersionedData::data_type is [`VERIFICATION_BUNDLE_DATA_TYPE`]
+    #[cbor(with = "minicbor::bytes")]
+    #[n(0)] pub data: Vec<u8>,
+    /// Signature over data field using the Authority Credentials [`PurposeKeyAttestation`]
+    #[n(1)] pub signature: CredentialSignature,
+    /// [`PurposeKeyAttestation`] used to sign the bundle
+    #[n(2)] pub purpose_key_attestation: PurposeKeyAttestation,
+}
+
+/// Data inside a [`VerificationBundle`]
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[rustfmt::skip]
+pub struct VerificationBundleData {
+    /// [`ChangeHistory`] of the Authority
+    #[n(0)] pub authority: ChangeHistory,
+    /// Current credentials [`PurposeKeyAttestation`]s of the Authority
+    #[n(1)] pub purpose_key_attestations: Vec<PurposeKeyAttestation>,
+    /// Revocations published by the Authority, if any
+    #[n(2)] pub revocation_list: Option<RevocationListAndPurposeKey>,
+    /// Creation [`TimestampInSeconds`] (UTC)
+    #[n(3)] pub created_at: TimestampInSeconds,
+    /// Expiration [`TimestampInSeconds`] (UTC)
+    #[n(4)] pub expires_at: TimestampInSeconds,
+}
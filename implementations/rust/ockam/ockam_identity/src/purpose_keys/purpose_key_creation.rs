@@ -99,11 +99,21 @@ impl PurposeKeyCreation {
     }
 
     /// Create a [`PurposeKey`]
+    /// The new key has the same type as the current key of the `Identity`, since some vaults
+    /// only support one type of key
     pub async fn create_credential_purpose_key(
         &self,
         identifier: &Identifier,
     ) -> Result<CredentialPurposeKey> {
-        let builder = self.credential_purpose_key_builder(identifier);
+        let key_type = self
+            .identities_creation
+            .get_identity(identifier)
+            .await?
+            .get_latest_public_key()?
+            .key_type();
+        let builder = self
+            .credential_purpose_key_builder(identifier)
+            .with_random_key(key_type);
         builder.build().await
     }
 
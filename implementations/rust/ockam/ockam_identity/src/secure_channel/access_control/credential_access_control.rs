@@ -1,11 +1,12 @@
 use core::fmt::{Debug, Formatter};
-use ockam_core::access_control::IncomingAccessControl;
+use ockam_core::access_control::{IncomingAccessControl, OutgoingAccessControl};
 use ockam_core::compat::{boxed::Box, sync::Arc, vec::Vec};
 use ockam_core::Result;
 use ockam_core::{async_trait, RelayMessage};
 
+use crate::models::Identifier;
 use crate::secure_channel::local_info::IdentitySecureChannelLocalInfo;
-use crate::IdentityAttributesRepository;
+use crate::{IdentityAttributesRepository, SecureChannelRegistry};
 
 /// Access control checking that message senders have a specific set of attributes
 #[derive(Clone)]
@@ -43,29 +44,99 @@ impl IncomingAccessControl for CredentialAccessControl {
         if let Ok(msg_identity_id) =
             IdentitySecureChannelLocalInfo::find_info(relay_message.local_message())
         {
-            let attributes = match self
-                .identity_attributes_repository
-                .get_attributes(&msg_identity_id.their_identity_id())
-                .await?
-            {
-                Some(a) => a,
-                None => return Ok(false), // No attributes for that Identity
-            };
+            has_required_attributes(
+                &self.required_attributes,
+                &self.identity_attributes_repository,
+                &msg_identity_id.their_identity_id(),
+            )
+            .await
+        } else {
+            Ok(false)
+        }
+    }
+}
+
+/// Access control checking that the identity on the other side of the secure channel
+/// a message is sent through has a specific set of attributes.
+///
+/// Messages which are not sent through a secure channel are denied.
+#[derive(Clone)]
+pub struct CredentialOutgoingAccessControl {
+    required_attributes: Vec<(Vec<u8>, Vec<u8>)>,
+    identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
+    secure_channel_registry: SecureChannelRegistry,
+}
 
-            for required_attribute in self.required_attributes.iter() {
-                let attr_val = match attributes.attrs().get(&required_attribute.0) {
-                    Some(v) => v,
-                    None => return Ok(false), // No required key
-                };
+impl CredentialOutgoingAccessControl {
+    /// Create a new credential outgoing access control
+    pub fn new(
+        required_attributes: &[(Vec<u8>, Vec<u8>)],
+        identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
+        secure_channel_registry: SecureChannelRegistry,
+    ) -> Self {
+        Self {
+            required_attributes: required_attributes.to_vec(),
+            identity_attributes_repository,
+            secure_channel_registry,
+        }
+    }
+}
 
-                if &required_attribute.1 != attr_val {
-                    return Ok(false); // Value doesn't match
-                }
+impl Debug for CredentialOutgoingAccessControl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let attributes = format!("{:?}", self.required_attributes.iter().map(|x| &x.0));
+
+        f.debug_struct("Credential Outgoing Access Control")
+            .field("Required attributes", &attributes)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl OutgoingAccessControl for CredentialOutgoingAccessControl {
+    async fn is_authorized(&self, relay_message: &RelayMessage) -> Result<bool> {
+        match self
+            .secure_channel_registry
+            .get_peer_identifier(relay_message)
+        {
+            Some(identifier) => {
+                has_required_attributes(
+                    &self.required_attributes,
+                    &self.identity_attributes_repository,
+                    &identifier,
+                )
+                .await
             }
+            None => Ok(false),
+        }
+    }
+}
 
-            Ok(true)
-        } else {
-            Ok(false)
+/// Return true if the identity has all the required attributes, with the expected values
+async fn has_required_attributes(
+    required_attributes: &[(Vec<u8>, Vec<u8>)],
+    identity_attributes_repository: &Arc<dyn IdentityAttributesRepository>,
+    identifier: &Identifier,
+) -> Result<bool> {
+    let attributes = match identity_attributes_repository
+        .get_attributes(identifier)
+        .await?
+    {
+        Some(a) => a,
+        None => return Ok(false), // No attributes for that Identity
+    };
+
+    let attributes = attributes.valid_attrs()?;
+    for required_attribute in required_attributes.iter() {
+        let attr_val = match attributes.get(&required_attribute.0) {
+            Some(v) => v,
+            None => return Ok(false), // No required key
+        };
+
+        if &required_attribute.1 != attr_val {
+            return Ok(false); // Value doesn't match
         }
     }
+
+    Ok(true)
 }
@@ -28,3 +28,26 @@ pub enum DecryptionResponse {
     /// Error
     Err(Error),
 }
+
+/// Message type for the `EncryptorWorker` internal address
+#[derive(Serialize, Deserialize, Message, Clone)]
+pub(crate) enum EncryptorInternalMessage {
+    /// Get a new credential from the trust context and present it to the other side
+    RefreshCredentials,
+    /// Present the given credential, encoded as CBOR, to the other side
+    PresentCredential(Vec<u8>),
+    /// Start a renegotiation of the channel keys
+    Rekey,
+    /// Send our response to a renegotiation of the channel keys started by the other side
+    RespondToRekeying,
+    /// Tell the other side if the credentials it presented were accepted
+    RespondToCredentialsRefresh(bool),
+    /// The other side rejected the credentials we presented
+    CredentialsRejected,
+    /// Close the channel if no message was exchanged during the idle timeout
+    CheckIdleTimeout,
+    /// Close the channel since it reached its maximum lifetime
+    MaxLifetimeReached,
+    /// Send a resumption ticket, encoded as CBOR, to the other side
+    SendResumptionTicket(Vec<u8>),
+}
@@ -0,0 +1,70 @@
+use core::fmt;
+use core::fmt::{Display, Formatter};
+use minicbor::{Decode, Encode};
+use ockam_core::compat::collections::BTreeSet;
+
+/// Optional feature of a secure channel, negotiated during the handshake.
+///
+/// The initiator offers the capabilities it supports in the first handshake message and the
+/// responder answers with the capabilities supported by both sides. A capability which is
+/// unknown to one of the sides is simply not negotiated, so that new features can be added
+/// without breaking the compatibility with older peers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Encode, Decode)]
+#[cbor(transparent)]
+pub struct Capability(#[n(0)] pub u16);
+
+impl Capability {
+    /// The responder can send resumption tickets to the initiator
+    pub const RESUMPTION: Capability = Capability(1);
+    /// The channel keys can be renegotiated, see [`RekeyingPolicy`](crate::RekeyingPolicy)
+    pub const REKEYING: Capability = Capability(2);
+    /// The receiver of refreshed credentials tells the sender if they were accepted
+    pub const CREDENTIALS_REFRESH_RESPONSE: Capability = Capability(3);
+}
+
+impl Display for Capability {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            Capability::RESUMPTION => f.write_str("resumption"),
+            Capability::REKEYING => f.write_str("rekeying"),
+            Capability::CREDENTIALS_REFRESH_RESPONSE => f.write_str("credentials-refresh-response"),
+            Capability(other) => write!(f, "capability-{other}"),
+        }
+    }
+}
+
+/// Return the capabilities offered by the other side which are also supported locally.
+/// Nothing is negotiated if the other side doesn't support the negotiation
+pub(crate) fn negotiate_capabilities(
+    offered: Option<&BTreeSet<Capability>>,
+    supported: &BTreeSet<Capability>,
+) -> BTreeSet<Capability> {
+    match offered {
+        Some(offered) => offered.intersection(supported).copied().collect(),
+        None => BTreeSet::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate_capabilities() {
+        let supported: BTreeSet<Capability> = [Capability::RESUMPTION, Capability(43)]
+            .into_iter()
+            .collect();
+        let offered: BTreeSet<Capability> = [Capability::RESUMPTION, Capability(42)]
+            .into_iter()
+            .collect();
+
+        let negotiated = negotiate_capabilities(Some(&offered), &supported);
+        assert_eq!(
+            negotiated.into_iter().collect::<Vec<_>>(),
+            vec![Capability::RESUMPTION]
+        );
+
+        assert!(negotiate_capabilities(None, &supported).is_empty());
+        assert_eq!(Capability(42).to_string(), "capability-42");
+    }
+}
@@ -1,4 +1,5 @@
 use core::sync::atomic::{AtomicBool, Ordering};
+use ockam_core::compat::collections::BTreeSet;
 use ockam_core::compat::sync::Arc;
 use ockam_core::compat::vec::Vec;
 use ockam_core::{Any, Result, Routed, TransportMessage};
@@ -6,15 +7,19 @@ use ockam_core::{Decodable, LocalMessage};
 use ockam_node::Context;
 
 use crate::models::Identifier;
+use crate::secure_channel::api::EncryptorInternalMessage;
 use crate::secure_channel::encryptor::{Encryptor, KEY_RENEWAL_INTERVAL};
 use crate::secure_channel::handshake::handshake_state_machine::CommonStateMachine;
 use crate::secure_channel::key_tracker::KeyTracker;
 use crate::secure_channel::nonce_tracker::NonceTracker;
-use crate::secure_channel::Addresses;
+use crate::secure_channel::{
+    Addresses, ChannelPersistence, Rekeying, Resumption, SecureChannelStats,
+};
 use crate::{
-    DecryptionRequest, DecryptionResponse, Identities, IdentityError,
+    Capability, DecryptionRequest, DecryptionResponse, Identities, IdentityError,
     IdentitySecureChannelLocalInfo, PlaintextPayloadMessage, RefreshCredentialsMessage,
-    SecureChannelMessage, TrustContext,
+    RefreshCredentialsResponseMessage, RekeyMessage, ResumptionTicketMessage, SecureChannelMessage,
+    TrustContext,
 };
 
 use ockam_vault::{AeadSecretKeyHandle, VaultForSecureChannels};
@@ -30,6 +35,11 @@ pub(crate) struct DecryptorHandler {
     identities: Arc<Identities>,
     trust_context: Option<TrustContext>,
     should_send_close: Arc<AtomicBool>,
+    rekeying: Rekeying,
+    persistence: Option<ChannelPersistence>,
+    stats: Arc<SecureChannelStats>,
+    resumption: Option<Resumption>,
+    capabilities: BTreeSet<Capability>,
 }
 
 impl DecryptorHandler {
@@ -39,19 +49,28 @@ impl DecryptorHandler {
         trust_context: Option<TrustContext>,
         role: &'static str,
         addresses: Addresses,
-        key: AeadSecretKeyHandle,
-        vault: Arc<dyn VaultForSecureChannels>,
+        decryptor: Decryptor,
         their_identity_id: Identifier,
         should_send_close: Arc<AtomicBool>,
+        rekeying: Rekeying,
+        persistence: Option<ChannelPersistence>,
+        stats: Arc<SecureChannelStats>,
+        resumption: Option<Resumption>,
+        capabilities: BTreeSet<Capability>,
     ) -> Self {
         Self {
             role,
             addresses,
             their_identity_id,
-            decryptor: Decryptor::new(key, vault),
+            decryptor,
             identities,
             trust_context,
             should_send_close,
+            rekeying,
+            persistence,
+            stats,
+            resumption,
+            capabilities,
         }
     }
 
@@ -74,7 +93,10 @@ impl DecryptorHandler {
         let decrypted_payload = self.decryptor.decrypt(&request.0).await;
 
         let response = match decrypted_payload {
-            Ok(payload) => DecryptionResponse::Ok(payload),
+            Ok(payload) => {
+                self.stats.record_received(payload.len());
+                DecryptionResponse::Ok(payload)
+            }
             Err(err) => DecryptionResponse::Err(err),
         };
 
@@ -90,6 +112,8 @@ impl DecryptorHandler {
         ctx: &mut Context,
         mut msg: PlaintextPayloadMessage,
     ) -> Result<()> {
+        self.stats.record_received(msg.payload.len());
+
         // Add encryptor hop in the return_route (instead of our address)
         msg.return_route
             .modify()
@@ -123,13 +147,21 @@ impl DecryptorHandler {
     async fn handle_close(&mut self, ctx: &mut Context) -> Result<()> {
         // Prevent sending another Close message
         self.should_send_close.store(false, Ordering::Relaxed);
+        // The channel is closed by the other side, it can't be resumed anymore
+        if let Some(persistence) = &self.persistence {
+            persistence.delete().await?;
+        }
         // Should be enough to stop the encryptor, since it will stop the decryptor
         ctx.stop_worker(self.addresses.encryptor.clone()).await
     }
 
+    /// Verify and store the credentials presented by the other side, then tell it if they were
+    /// accepted. The attributes of the other side are updated in place, so that attribute-based
+    /// access controls use them for the next messages, without having to create a new channel.
+    /// If the credentials are rejected, the channel is kept with the previous attributes
     async fn handle_refresh_credentials(
         &mut self,
-        _ctx: &mut Context,
+        ctx: &mut Context,
         msg: RefreshCredentialsMessage,
     ) -> Result<()> {
         debug!(
@@ -137,7 +169,7 @@ impl DecryptorHandler {
             self.addresses.decryptor_remote
         );
 
-        CommonStateMachine::process_identity_payload_static(
+        let result = CommonStateMachine::process_identity_payload_static(
             self.identities.clone(),
             None,
             self.trust_context.clone(),
@@ -146,16 +178,116 @@ impl DecryptorHandler {
             msg.credentials,
             None,
         )
-        .await?;
+        .await;
 
-        info!(
-            "Successfully handled credentials refresh request for {}",
+        let accepted = match result {
+            Ok(_) => {
+                info!(
+                    "Successfully handled credentials refresh request for {}",
+                    self.addresses.decryptor_remote
+                );
+                true
+            }
+            Err(err) => {
+                warn!(
+                    "Rejected the credentials refreshed by {} on {}: {err}",
+                    self.their_identity_id, self.addresses.decryptor_remote
+                );
+                false
+            }
+        };
+
+        // older peers can't decode the response
+        if !self
+            .capabilities
+            .contains(&Capability::CREDENTIALS_REFRESH_RESPONSE)
+        {
+            return Ok(());
+        }
+        ctx.send_from_address(
+            self.addresses.encryptor_internal.clone(),
+            EncryptorInternalMessage::RespondToCredentialsRefresh(accepted),
+            self.addresses.decryptor_remote.clone(),
+        )
+        .await
+    }
+
+    /// Handle the verification result of the credentials we presented
+    async fn handle_refresh_credentials_response(
+        &mut self,
+        ctx: &mut Context,
+        msg: RefreshCredentialsResponseMessage,
+    ) -> Result<()> {
+        if msg.accepted {
+            debug!(
+                "The credentials presented on {} were accepted",
+                self.addresses.encryptor
+            );
+            return Ok(());
+        }
+
+        warn!(
+            "The credentials presented on {} were rejected by {}",
+            self.addresses.encryptor, self.their_identity_id
+        );
+        ctx.send_from_address(
+            self.addresses.encryptor_internal.clone(),
+            EncryptorInternalMessage::CredentialsRejected,
+            self.addresses.decryptor_remote.clone(),
+        )
+        .await
+    }
+
+    /// Derive new keys for a rekeying started by the other side
+    /// and ask the encryptor to send our response
+    async fn handle_rekey_request(&mut self, ctx: &mut Context, msg: RekeyMessage) -> Result<()> {
+        debug!(
+            "Handling rekeying request for {}",
             self.addresses.decryptor_remote
         );
 
+        if let Some(key) = self.rekeying.handle_request(&msg.public_key).await? {
+            self.decryptor.set_next_key(key).await?;
+            ctx.send_from_address(
+                self.addresses.encryptor_internal.clone(),
+                EncryptorInternalMessage::RespondToRekeying,
+                self.addresses.decryptor_remote.clone(),
+            )
+            .await?;
+        }
+
         Ok(())
     }
 
+    /// Derive new keys for a rekeying that we started
+    async fn handle_rekey_response(&mut self, msg: RekeyMessage) -> Result<()> {
+        debug!(
+            "Handling rekeying response for {}",
+            self.addresses.decryptor_remote
+        );
+
+        if let Some(key) = self.rekeying.handle_response(&msg.public_key).await? {
+            self.decryptor.set_next_key(key).await?;
+            info!(
+                "Successfully renegotiated the keys for {}",
+                self.addresses.decryptor_remote
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Store a resumption ticket issued by the other side, if resumption is used
+    fn handle_resumption_ticket(&mut self, msg: ResumptionTicketMessage) {
+        debug!(
+            "Received a resumption ticket for {}",
+            self.addresses.decryptor_remote
+        );
+        if let Some(resumption) = &self.resumption {
+            resumption.save_ticket(&self.their_identity_id, msg);
+        }
+    }
+
     pub(crate) async fn handle_decrypt(
         &mut self,
         ctx: &mut Context,
@@ -166,13 +298,31 @@ impl DecryptorHandler {
             self.role, &self.addresses.decryptor_remote
         );
 
+        let return_route = msg.return_route();
+
         // Decode raw payload binary
         let payload = msg.into_transport_message().payload;
         let payload = Vec::<u8>::decode(&payload)?;
 
         // Decrypt the binary
+        let number_of_rekeys = self.decryptor.number_of_rekeys();
         let decrypted_payload = self.decryptor.decrypt(&payload).await?;
 
+        if let Some(persistence) = &self.persistence {
+            // The other side might have been restarted and reached us via a different route
+            persistence.update_remote_route(return_route);
+            if self.decryptor.number_of_rekeys() != number_of_rekeys {
+                let key_tracker = &self.decryptor.key_tracker;
+                persistence
+                    .update_decryption_keys(
+                        &key_tracker.current_key,
+                        key_tracker.previous_key.as_ref(),
+                        key_tracker.number_of_rekeys(),
+                    )
+                    .await?;
+            }
+        }
+
         let msg: SecureChannelMessage = minicbor::decode(&decrypted_payload)?;
 
         match msg {
@@ -181,13 +331,26 @@ impl DecryptorHandler {
                 self.handle_refresh_credentials(ctx, msg).await?
             }
             SecureChannelMessage::Close => self.handle_close(ctx).await?,
+            SecureChannelMessage::RekeyRequest(msg) => self.handle_rekey_request(ctx, msg).await?,
+            SecureChannelMessage::RekeyResponse(msg) => self.handle_rekey_response(msg).await?,
+            SecureChannelMessage::RefreshCredentialsResponse(msg) => {
+                self.handle_refresh_credentials_response(ctx, msg).await?
+            }
+            SecureChannelMessage::ResumptionTicket(msg) => self.handle_resumption_ticket(msg),
         };
 
         Ok(())
     }
 
-    /// Remove the channel keys on shutdown
+    /// Remove the channel keys on shutdown, unless the channel is persisted
+    /// in order to be resumed later
     pub(crate) async fn shutdown(&self) -> Result<()> {
+        self.rekeying.shutdown().await?;
+        if let Some(persistence) = &self.persistence {
+            if persistence.is_persisted().await? {
+                return Ok(());
+            }
+        }
         self.decryptor.shutdown().await
     }
 }
@@ -196,6 +359,9 @@ pub(crate) struct Decryptor {
     vault: Arc<dyn VaultForSecureChannels>,
     key_tracker: KeyTracker,
     nonce_tracker: NonceTracker,
+    /// Renegotiated key which the other side uses instead of the derived key
+    /// once it reaches its next key renewal
+    next_key: Option<AeadSecretKeyHandle>,
 }
 
 impl Decryptor {
@@ -204,9 +370,44 @@ impl Decryptor {
             vault,
             key_tracker: KeyTracker::new(key, KEY_RENEWAL_INTERVAL),
             nonce_tracker: NonceTracker::new(),
+            next_key: None,
+        }
+    }
+
+    /// Restore a decryptor with the keys of a persisted secure channel.
+    /// The messages of the current key renewal interval are not accepted anymore
+    pub(crate) fn restore(
+        key: AeadSecretKeyHandle,
+        previous_key: Option<AeadSecretKeyHandle>,
+        number_of_rekeys: u64,
+        vault: Arc<dyn VaultForSecureChannels>,
+    ) -> Self {
+        Self {
+            vault,
+            key_tracker: KeyTracker::restore(
+                key,
+                previous_key,
+                number_of_rekeys,
+                KEY_RENEWAL_INTERVAL,
+            ),
+            nonce_tracker: NonceTracker::starting_at(number_of_rekeys * KEY_RENEWAL_INTERVAL),
+            next_key: None,
         }
     }
 
+    /// Return the number of key renewals
+    pub(crate) fn number_of_rekeys(&self) -> u64 {
+        self.key_tracker.number_of_rekeys()
+    }
+
+    /// Set a renegotiated key, expected to be used by the other side at its next key renewal
+    pub(crate) async fn set_next_key(&mut self, next_key: AeadSecretKeyHandle) -> Result<()> {
+        if let Some(previous) = self.next_key.replace(next_key) {
+            self.vault.delete_aead_secret_key(previous).await?;
+        }
+        Ok(())
+    }
+
     /// Restore 12-byte nonce needed for AES GCM from 8 byte that we use for noise
     fn convert_nonce_from_small(b: &[u8]) -> Result<(u64, [u8; 12])> {
         let bytes: [u8; 8] = b.try_into().map_err(|_| IdentityError::InvalidNonce)?;
@@ -229,6 +430,22 @@ impl Decryptor {
         let key = if let Some(key) = self.key_tracker.get_key(nonce)? {
             key
         } else {
+            // if the keys have been renegotiated, the other side might have started using the
+            // new key, otherwise it derived a new key from the current one
+            if let Some(next_key) = self.next_key.clone() {
+                if let Ok(result) = self
+                    .vault
+                    .aead_decrypt(&next_key, &payload[8..], &nonce_buffer, &[])
+                    .await
+                {
+                    self.next_key = None;
+                    self.nonce_tracker = nonce_tracker;
+                    if let Some(key_to_delete) = self.key_tracker.update_key(next_key)? {
+                        self.vault.delete_aead_secret_key(key_to_delete).await?;
+                    }
+                    return Ok(result);
+                }
+            }
             Encryptor::rekey(&self.vault, &self.key_tracker.current_key).await?
         };
 
@@ -256,6 +473,9 @@ impl Decryptor {
         if let Some(previous_key) = self.key_tracker.previous_key.clone() {
             self.vault.delete_aead_secret_key(previous_key).await?;
         };
+        if let Some(next_key) = self.next_key.clone() {
+            self.vault.delete_aead_secret_key(next_key).await?;
+        };
         Ok(())
     }
 }
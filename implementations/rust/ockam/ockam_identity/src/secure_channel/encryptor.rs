@@ -8,6 +8,8 @@ use crate::IdentityError;
 
 pub(crate) struct Encryptor {
     key: AeadSecretKeyHandle,
+    /// Renegotiated key replacing the derived key at the next key renewal
+    next_key: Option<AeadSecretKeyHandle>,
     nonce: u64,
     vault: Arc<dyn VaultForSecureChannels>,
 }
@@ -55,7 +57,10 @@ impl Encryptor {
         self.nonce += 1;
 
         if current_nonce > 0 && current_nonce % KEY_RENEWAL_INTERVAL == 0 {
-            let new_key = Self::rekey(&self.vault, &self.key).await?;
+            let new_key = match self.next_key.take() {
+                Some(next_key) => next_key,
+                None => Self::rekey(&self.vault, &self.key).await?,
+            };
             let old_key = core::mem::replace(&mut self.key, new_key);
             self.vault.delete_aead_secret_key(old_key).await?;
         }
@@ -79,10 +84,37 @@ impl Encryptor {
         nonce: u64,
         vault: Arc<dyn VaultForSecureChannels>,
     ) -> Self {
-        Self { key, nonce, vault }
+        Self {
+            key,
+            next_key: None,
+            nonce,
+            vault,
+        }
+    }
+
+    /// Return the current key
+    pub(crate) fn key(&self) -> &AeadSecretKeyHandle {
+        &self.key
+    }
+
+    /// Return the next nonce
+    pub(crate) fn nonce(&self) -> u64 {
+        self.nonce
+    }
+
+    /// Set a renegotiated key which will be used instead of the derived key
+    /// at the next key renewal
+    pub(crate) async fn set_next_key(&mut self, next_key: AeadSecretKeyHandle) -> Result<()> {
+        if let Some(previous) = self.next_key.replace(next_key) {
+            self.vault.delete_aead_secret_key(previous).await?;
+        }
+        Ok(())
     }
 
     pub(crate) async fn shutdown(&self) -> Result<()> {
+        if let Some(next_key) = self.next_key.clone() {
+            self.vault.delete_aead_secret_key(next_key).await?;
+        }
         if !self.vault.delete_aead_secret_key(self.key.clone()).await? {
             Err(Error::new(
                 Origin::Ockam,
@@ -12,14 +12,16 @@ use ockam_core::{async_trait, Decodable, Error, Route};
 use ockam_core::{Any, Result, Routed, Worker};
 use ockam_node::{Context, DelayedEvent};
 
-use crate::models::{CredentialData, VersionedData};
+use crate::models::{ChangeHistory, CredentialAndPurposeKey, CredentialData, VersionedData};
 use crate::secure_channel::addresses::Addresses;
-use crate::secure_channel::api::{EncryptionRequest, EncryptionResponse};
-use crate::secure_channel::encryptor::Encryptor;
+use crate::secure_channel::api::{EncryptionRequest, EncryptionResponse, EncryptorInternalMessage};
+use crate::secure_channel::encryptor::{Encryptor, KEY_RENEWAL_INTERVAL};
+use crate::secure_channel::{ChannelPersistence, Rekeying, SecureChannelStats};
 use crate::utils::now;
 use crate::{
     ChangeHistoryRepository, Identifier, IdentityError, PlaintextPayloadMessage,
-    RefreshCredentialsMessage, SecureChannelMessage, TimestampInSeconds, TrustContext,
+    RefreshCredentialsMessage, RefreshCredentialsResponseMessage, RekeyMessage,
+    ResumptionTicketMessage, SecureChannelMessage, TimestampInSeconds, TrustContext,
 };
 
 pub(crate) struct EncryptorWorker {
@@ -38,11 +40,24 @@ pub(crate) struct EncryptorWorker {
     /// The time interval before the credential expiration when we'll ask the credential retriever
     /// for a new one
     refresh_credential_time_gap: Duration,
-    credential_refresh_event: Option<DelayedEvent<()>>,
+    credential_refresh_event: Option<DelayedEvent<EncryptorInternalMessage>>,
     // TODO: Should be CredentialsRetriever
     trust_context: Option<TrustContext>,
 
     should_send_close: Arc<AtomicBool>,
+
+    rekeying: Rekeying,
+    /// Number of messages encrypted since the last rekeying
+    encrypted_messages: u64,
+    /// Number of bytes encrypted since the last rekeying
+    encrypted_bytes: u64,
+    rekeying_event: Option<DelayedEvent<EncryptorInternalMessage>>,
+    persistence: Option<ChannelPersistence>,
+    idle_timeout: Option<Duration>,
+    max_lifetime: Option<Duration>,
+    stats: Arc<SecureChannelStats>,
+    idle_timeout_event: Option<DelayedEvent<EncryptorInternalMessage>>,
+    max_lifetime_event: Option<DelayedEvent<EncryptorInternalMessage>>,
 }
 
 impl EncryptorWorker {
@@ -59,6 +74,11 @@ impl EncryptorWorker {
         refresh_credential_time_gap: Duration,
         trust_context: Option<TrustContext>,
         should_send_close: Arc<AtomicBool>,
+        rekeying: Rekeying,
+        persistence: Option<ChannelPersistence>,
+        idle_timeout: Option<Duration>,
+        max_lifetime: Option<Duration>,
+        stats: Arc<SecureChannelStats>,
     ) -> Self {
         Self {
             role,
@@ -73,12 +93,52 @@ impl EncryptorWorker {
             credential_refresh_event: None,
             trust_context,
             should_send_close,
+            rekeying,
+            encrypted_messages: 0,
+            encrypted_bytes: 0,
+            rekeying_event: None,
+            persistence,
+            idle_timeout,
+            max_lifetime,
+            stats,
+            idle_timeout_event: None,
+            max_lifetime_event: None,
+        }
+    }
+
+    /// Return the route to the decryptor on the other side
+    fn remote_route(&self) -> Route {
+        match &self.persistence {
+            Some(persistence) => persistence.remote_route(),
+            None => self.remote_route.clone(),
         }
     }
 
+    /// Encrypt a payload, with renegotiated keys if they are available
+    async fn encrypt_payload(&mut self, payload: &[u8]) -> Result<Vec<u8>> {
+        if let Some(next_key) = self.rekeying.take_next_encryption_key() {
+            self.encryptor.set_next_key(next_key).await?;
+        }
+        let encrypted_payload = self.encryptor.encrypt(payload).await?;
+        self.encrypted_messages += 1;
+        self.encrypted_bytes += payload.len() as u64;
+
+        // Persist the new key once it has been renewed
+        if let Some(persistence) = &self.persistence {
+            let nonce = self.encryptor.nonce() - 1;
+            if nonce > 0 && nonce % KEY_RENEWAL_INTERVAL == 0 {
+                persistence
+                    .update_encryption_key(self.encryptor.key(), nonce)
+                    .await?;
+            }
+        }
+
+        Ok(encrypted_payload)
+    }
+
     /// Encrypt the message
     async fn encrypt(&mut self, ctx: &Context, msg: SecureChannelMessage) -> Result<Vec<u8>> {
-        match self.encryptor.encrypt(&minicbor::to_vec(&msg)?).await {
+        match self.encrypt_payload(&minicbor::to_vec(&msg)?).await {
             Ok(encrypted_payload) => Ok(encrypted_payload),
             // If encryption failed, that means we have some internal error,
             // and we may be in an invalid state, it's better to stop the Worker
@@ -105,11 +165,12 @@ impl EncryptorWorker {
 
         // Decode raw payload binary
         let request = EncryptionRequest::decode(&msg.into_transport_message().payload)?;
+        self.stats.record_sent(request.0.len());
 
         let mut should_stop = false;
 
         // Encrypt the message
-        let response = match self.encryptor.encrypt(&request.0).await {
+        let response = match self.encrypt_payload(&request.0).await {
             Ok(encrypted_payload) => EncryptionResponse::Ok(encrypted_payload),
             // If encryption failed, that means we have some internal error,
             // and we may be in an invalid state, it's better to stop the Worker
@@ -129,9 +190,10 @@ impl EncryptorWorker {
 
         if should_stop {
             ctx.stop_worker(self.addresses.encryptor.clone()).await?;
+            return Ok(());
         }
 
-        Ok(())
+        self.rekey_if_needed(ctx).await
     }
 
     async fn handle_encrypt(
@@ -155,21 +217,155 @@ impl EncryptorWorker {
             return_route,
             payload: msg.into_transport_message().payload,
         };
+        self.stats.record_sent(msg.payload.len());
         let msg = SecureChannelMessage::Payload(msg);
 
         let msg = self.encrypt(ctx, msg).await?;
 
         // Send the message to the decryptor on the other side
-        ctx.send_from_address(
-            self.remote_route.clone(),
-            msg,
-            self.addresses.encryptor.clone(),
+        ctx.send_from_address(self.remote_route(), msg, self.addresses.encryptor.clone())
+            .await?;
+
+        self.rekey_if_needed(ctx).await
+    }
+
+    /// Start a rekeying if the rekeying policy limits are reached
+    async fn rekey_if_needed(&mut self, ctx: &Context) -> Result<()> {
+        let is_exceeded = self
+            .rekeying
+            .policy()
+            .map(|policy| policy.is_exceeded(self.encrypted_messages, self.encrypted_bytes))
+            .unwrap_or(false);
+
+        if is_exceeded {
+            self.start_rekeying(ctx).await
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Send a new ephemeral public key to the other side in order to renegotiate the channel keys
+    async fn start_rekeying(&mut self, ctx: &Context) -> Result<()> {
+        debug!("Starting rekeying for {}", self.addresses.encryptor);
+
+        let public_key = self.rekeying.start().await?;
+        let msg = SecureChannelMessage::RekeyRequest(RekeyMessage { public_key });
+        let msg = self.encrypt(ctx, msg).await?;
+
+        ctx.send_from_address(self.remote_route(), msg, self.addresses.encryptor.clone())
+            .await?;
+
+        self.reset_rekeying(ctx).await
+    }
+
+    /// Send our ephemeral public key to the other side when it started a rekeying.
+    /// The response is encrypted with the current keys, then the new encryption key is used
+    /// from the next key renewal on
+    async fn respond_to_rekeying(&mut self, ctx: &Context) -> Result<()> {
+        let (public_key, next_key) = match self.rekeying.take_response() {
+            Some(response) => response,
+            None => return Ok(()),
+        };
+
+        let msg = SecureChannelMessage::RekeyResponse(RekeyMessage { public_key });
+        let msg = self.encrypt(ctx, msg).await?;
+
+        ctx.send_from_address(self.remote_route(), msg, self.addresses.encryptor.clone())
+            .await?;
+
+        self.encryptor.set_next_key(next_key).await?;
+        self.reset_rekeying(ctx).await
+    }
+
+    /// Reset the rekeying counters and reschedule the periodic rekeying
+    async fn reset_rekeying(&mut self, ctx: &Context) -> Result<()> {
+        self.encrypted_messages = 0;
+        self.encrypted_bytes = 0;
+        self.schedule_rekeying(ctx).await
+    }
+
+    /// Schedule a DelayedEvent that will put a message into EncryptorWorker's own internal
+    /// mailbox when the keys must be renegotiated, if the rekeying policy has a maximum duration
+    async fn schedule_rekeying(&mut self, ctx: &Context) -> Result<()> {
+        let max_duration = match self.rekeying.policy().and_then(|p| p.max_duration) {
+            Some(max_duration) => max_duration,
+            None => return Ok(()),
+        };
+
+        // Cancel the old event
+        self.rekeying_event = None;
+
+        debug!(
+            "Scheduling rekeying for {} in {} seconds",
+            self.addresses.encryptor,
+            max_duration.as_secs()
+        );
+        let mut rekeying_event = DelayedEvent::create(
+            ctx,
+            self.addresses.encryptor_internal.clone(),
+            EncryptorInternalMessage::Rekey,
         )
         .await?;
+        rekeying_event.schedule(max_duration).await?;
+
+        self.rekeying_event = Some(rekeying_event);
+
+        Ok(())
+    }
 
+    /// Schedule a DelayedEvent that will put a message into EncryptorWorker's own internal
+    /// mailbox when the channel might have been idle for too long
+    async fn schedule_idle_timeout(&mut self, ctx: &Context, delay: Duration) -> Result<()> {
+        let mut idle_timeout_event = DelayedEvent::create(
+            ctx,
+            self.addresses.encryptor_internal.clone(),
+            EncryptorInternalMessage::CheckIdleTimeout,
+        )
+        .await?;
+        idle_timeout_event.schedule(delay).await?;
+        self.idle_timeout_event = Some(idle_timeout_event);
         Ok(())
     }
 
+    /// Close the channel if no message was exchanged during the idle timeout,
+    /// otherwise check again when the idle timeout could be reached
+    async fn handle_check_idle_timeout(&mut self, ctx: &Context) -> Result<()> {
+        let idle_timeout = match self.idle_timeout {
+            Some(idle_timeout) => idle_timeout,
+            None => return Ok(()),
+        };
+
+        let idle_duration = self.stats.idle_duration();
+        if idle_duration >= idle_timeout {
+            info!(
+                "Closing the secure channel {} after {} seconds without any message",
+                self.addresses.encryptor,
+                idle_duration.as_secs()
+            );
+            self.close(ctx).await
+        } else {
+            self.schedule_idle_timeout(ctx, idle_timeout - idle_duration)
+                .await
+        }
+    }
+
+    /// Close the channel once it reached its maximum lifetime
+    async fn handle_max_lifetime_reached(&mut self, ctx: &Context) -> Result<()> {
+        info!(
+            "Closing the secure channel {} since it reached its maximum lifetime",
+            self.addresses.encryptor
+        );
+        self.close(ctx).await
+    }
+
+    /// Close the channel for good. The other side is notified when the encryptor is stopped
+    async fn close(&mut self, ctx: &Context) -> Result<()> {
+        if let Some(persistence) = &self.persistence {
+            persistence.delete().await?;
+        }
+        ctx.stop_worker(self.addresses.encryptor.clone()).await
+    }
+
     /// Asks credential retriever for a new credential and presents it to the other side, including
     /// the latest change_history
     async fn handle_refresh_credentials(&mut self, ctx: &<Self as Worker>::Context) -> Result<()> {
@@ -178,20 +374,7 @@ impl EncryptorWorker {
             self.addresses.encryptor
         );
 
-        let change_history = self
-            .change_history_repository
-            .get_change_history(&self.my_identifier)
-            .await?
-            .ok_or_else(|| {
-                Error::new(
-                    Origin::Api,
-                    Kind::NotFound,
-                    format!(
-                        "no change history found for identifier {}",
-                        self.my_identifier
-                    ),
-                )
-            })?;
+        let change_history = self.get_change_history().await?;
 
         let credential = if let Some(trust_context) = &self.trust_context {
             match trust_context.get_credential(ctx, &self.my_identifier).await {
@@ -222,6 +405,60 @@ impl EncryptorWorker {
             return Err(IdentityError::NoCredentialsRetriever)?;
         };
 
+        self.present_credential(ctx, change_history, credential)
+            .await?;
+
+        self.schedule_credentials_refresh(ctx, false).await?;
+
+        Ok(())
+    }
+
+    /// Present a credential which was retrieved by a
+    /// [`CredentialsRefresher`](crate::CredentialsRefresher) to the other side
+    async fn handle_present_credential(
+        &mut self,
+        ctx: &<Self as Worker>::Context,
+        credential: &[u8],
+    ) -> Result<()> {
+        let credential = CredentialAndPurposeKey::decode_from_cbor_bytes(credential)?;
+        let change_history = self.get_change_history().await?;
+
+        self.present_credential(ctx, change_history, credential)
+            .await?;
+
+        // Without a trust context, the credential can only be refreshed by the refresher
+        if self.trust_context.is_some() {
+            self.schedule_credentials_refresh(ctx, false).await
+        } else {
+            self.credential_refresh_event = None;
+            Ok(())
+        }
+    }
+
+    /// Return the latest change history of our identity
+    async fn get_change_history(&self) -> Result<ChangeHistory> {
+        self.change_history_repository
+            .get_change_history(&self.my_identifier)
+            .await?
+            .ok_or_else(|| {
+                Error::new(
+                    Origin::Api,
+                    Kind::NotFound,
+                    format!(
+                        "no change history found for identifier {}",
+                        self.my_identifier
+                    ),
+                )
+            })
+    }
+
+    /// Send a credential to the other side, including the latest change_history
+    async fn present_credential(
+        &mut self,
+        ctx: &<Self as Worker>::Context,
+        change_history: ChangeHistory,
+        credential: CredentialAndPurposeKey,
+    ) -> Result<()> {
         let versioned_data: VersionedData = minicbor::decode(&credential.credential.data)?;
         let data = CredentialData::get_data(&versioned_data)?;
         self.min_credential_expiration = Some(data.expires_at);
@@ -240,16 +477,56 @@ impl EncryptorWorker {
         );
 
         // Send the message to the decryptor on the other side
-        ctx.send_from_address(
-            self.remote_route.clone(),
-            msg,
-            self.addresses.encryptor.clone(),
-        )
-        .await?;
+        ctx.send_from_address(self.remote_route(), msg, self.addresses.encryptor.clone())
+            .await
+    }
 
-        self.schedule_credentials_refresh(ctx, false).await?;
+    /// Tell the other side if the credentials it presented were accepted
+    async fn respond_to_credentials_refresh(
+        &mut self,
+        ctx: &Context,
+        accepted: bool,
+    ) -> Result<()> {
+        let msg =
+            SecureChannelMessage::RefreshCredentialsResponse(RefreshCredentialsResponseMessage {
+                accepted,
+            });
+        let msg = self.encrypt(ctx, msg).await?;
 
-        Ok(())
+        ctx.send_from_address(self.remote_route(), msg, self.addresses.encryptor.clone())
+            .await
+    }
+
+    /// Send a resumption ticket issued by this side to the other side
+    async fn send_resumption_ticket(&mut self, ctx: &Context, ticket: &[u8]) -> Result<()> {
+        let ticket: ResumptionTicketMessage = minicbor::decode(ticket)?;
+        let msg = SecureChannelMessage::ResumptionTicket(ticket);
+        let msg = self.encrypt(ctx, msg).await?;
+
+        debug!(
+            "Sending a resumption ticket for {}",
+            self.addresses.encryptor
+        );
+
+        ctx.send_from_address(self.remote_route(), msg, self.addresses.encryptor.clone())
+            .await
+    }
+
+    /// When the other side rejected our credentials, retrieve new ones sooner than planned,
+    /// if the credentials are refreshed with the trust context.
+    /// Otherwise the [`CredentialsRefresher`](crate::CredentialsRefresher) presenting the
+    /// credentials keeps its own schedule
+    async fn handle_credentials_rejected(&mut self, ctx: &Context) -> Result<()> {
+        if self.trust_context.is_none() {
+            return Ok(());
+        }
+        info!(
+            "Credentials refresh was rejected for {} and is rescheduled in {} seconds",
+            self.addresses.encryptor,
+            self.min_credential_refresh_interval.as_secs()
+        );
+        self.schedule_credentials_refresh_in(ctx, self.min_credential_refresh_interval)
+            .await
     }
 
     async fn send_close_channel(&mut self, ctx: &Context) -> Result<()> {
@@ -259,12 +536,8 @@ impl EncryptorWorker {
         let msg = self.encrypt(ctx, msg).await?;
 
         // Send the message to the decryptor on the other side
-        ctx.send_from_address(
-            self.remote_route.clone(),
-            msg,
-            self.addresses.encryptor.clone(),
-        )
-        .await?;
+        ctx.send_from_address(self.remote_route(), msg, self.addresses.encryptor.clone())
+            .await?;
 
         Ok(())
     }
@@ -281,9 +554,6 @@ impl EncryptorWorker {
                 return Ok(());
             };
 
-        // Cancel the old event
-        self.credential_refresh_event = None;
-
         let now = now()?;
 
         let duration = if min_credential_expiration < now + self.refresh_credential_time_gap {
@@ -305,13 +575,29 @@ impl EncryptorWorker {
             duration
         };
 
+        self.schedule_credentials_refresh_in(ctx, duration).await
+    }
+
+    /// Schedule a credentials refresh after the given duration
+    async fn schedule_credentials_refresh_in(
+        &mut self,
+        ctx: &Context,
+        duration: Duration,
+    ) -> Result<()> {
+        // Cancel the old event
+        self.credential_refresh_event = None;
+
         debug!(
             "Scheduling credentials refresh for {} in {} seconds",
             self.addresses.encryptor,
             duration.as_secs()
         );
-        let mut credential_refresh_event =
-            DelayedEvent::create(ctx, self.addresses.encryptor_internal.clone(), ()).await?;
+        let mut credential_refresh_event = DelayedEvent::create(
+            ctx,
+            self.addresses.encryptor_internal.clone(),
+            EncryptorInternalMessage::RefreshCredentials,
+        )
+        .await?;
         credential_refresh_event.schedule(duration).await?;
 
         self.credential_refresh_event = Some(credential_refresh_event);
@@ -326,7 +612,23 @@ impl Worker for EncryptorWorker {
     type Context = Context;
 
     async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
-        self.schedule_credentials_refresh(ctx, false).await
+        self.schedule_credentials_refresh(ctx, false).await?;
+        self.schedule_rekeying(ctx).await?;
+
+        if let Some(idle_timeout) = self.idle_timeout {
+            self.schedule_idle_timeout(ctx, idle_timeout).await?;
+        }
+        if let Some(max_lifetime) = self.max_lifetime {
+            let mut max_lifetime_event = DelayedEvent::create(
+                ctx,
+                self.addresses.encryptor_internal.clone(),
+                EncryptorInternalMessage::MaxLifetimeReached,
+            )
+            .await?;
+            max_lifetime_event.schedule(max_lifetime).await?;
+            self.max_lifetime_event = Some(max_lifetime_event);
+        }
+        Ok(())
     }
 
     async fn handle_message(
@@ -341,7 +643,33 @@ impl Worker for EncryptorWorker {
         } else if msg_addr == self.addresses.encryptor_api {
             self.handle_encrypt_api(ctx, msg).await?;
         } else if msg_addr == self.addresses.encryptor_internal {
-            self.handle_refresh_credentials(ctx).await?;
+            match EncryptorInternalMessage::decode(&msg.into_transport_message().payload)? {
+                EncryptorInternalMessage::RefreshCredentials => {
+                    self.handle_refresh_credentials(ctx).await?
+                }
+                EncryptorInternalMessage::PresentCredential(credential) => {
+                    self.handle_present_credential(ctx, &credential).await?
+                }
+                EncryptorInternalMessage::Rekey => self.start_rekeying(ctx).await?,
+                EncryptorInternalMessage::RespondToRekeying => {
+                    self.respond_to_rekeying(ctx).await?
+                }
+                EncryptorInternalMessage::RespondToCredentialsRefresh(accepted) => {
+                    self.respond_to_credentials_refresh(ctx, accepted).await?
+                }
+                EncryptorInternalMessage::CredentialsRejected => {
+                    self.handle_credentials_rejected(ctx).await?
+                }
+                EncryptorInternalMessage::CheckIdleTimeout => {
+                    self.handle_check_idle_timeout(ctx).await?
+                }
+                EncryptorInternalMessage::MaxLifetimeReached => {
+                    self.handle_max_lifetime_reached(ctx).await?
+                }
+                EncryptorInternalMessage::SendResumptionTicket(ticket) => {
+                    self.send_resumption_ticket(ctx, &ticket).await?
+                }
+            }
         } else {
             return Err(IdentityError::UnknownChannelMsgDestination)?;
         }
@@ -353,6 +681,13 @@ impl Worker for EncryptorWorker {
         let _ = context
             .stop_worker(self.addresses.decryptor_internal.clone())
             .await;
+        // A persisted channel is kept open on the other side and its keys are kept,
+        // so that it can be resumed after a restart
+        if let Some(persistence) = &self.persistence {
+            if persistence.is_persisted().await? {
+                return Ok(());
+            }
+        }
         if self.should_send_close.load(Ordering::Relaxed) {
             let _ = self.send_close_channel(context).await;
         }
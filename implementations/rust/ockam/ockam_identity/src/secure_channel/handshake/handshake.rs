@@ -20,6 +20,8 @@ pub const SHA256_SIZE: usize = 32;
 pub const AES_GCM_TAGSIZE: usize = 16;
 /// Maximum allowed noise message size
 pub const NOISE_MAX_MESSAGE_SIZE: usize = 65535;
+/// The number of bytes in the response to a resumption request
+const RESUMPTION_RESPONSE_SIZE: usize = X25519_PUBLIC_KEY_LENGTH + AES_GCM_TAGSIZE;
 
 /// Implementation of a Handshake for the noise protocol
 /// The first members are used in the implementation of some of the protocol steps, for example to
@@ -215,6 +217,53 @@ impl Handshake {
         Ok(payload)
     }
 
+    /// Encode the response to a resumption request, sent by the responder instead of message 2
+    /// That message contains: the responder ephemeral public key + an empty encrypted payload
+    ///   proving that the responder knows the secret of the resumption ticket
+    pub(super) async fn encode_resumption_response(&mut self, secret: &[u8]) -> Result<Vec<u8>> {
+        let mut state = self.state.clone();
+        // output e.pubKey
+        let e_pub_key = self.get_public_key(state.e()?).await?;
+        state.mix_hash(&e_pub_key.0);
+        let mut message = e_pub_key.0.to_vec();
+
+        self.mix_resumption_secret(&mut state, secret).await?;
+
+        // encrypt and output an empty payload
+        let c = self.encrypt_and_hash(&mut state, &[]).await?;
+        message.extend(c);
+
+        self.state = state;
+        Ok(message)
+    }
+
+    /// Decode the response to a resumption request sent by the responder
+    /// Return false if the responder rejected the resumption ticket and sent a message 2 instead
+    pub(super) async fn decode_resumption_response(
+        &mut self,
+        message: &[u8],
+        secret: &[u8],
+    ) -> Result<bool> {
+        if message.len() != RESUMPTION_RESPONSE_SIZE {
+            return Ok(false);
+        }
+
+        let mut state = self.state.clone();
+        // decode re.pubKey
+        let re_pub_key = Self::read_key(message)?;
+        state.re = Some(X25519PublicKey(*re_pub_key));
+        state.mix_hash(re_pub_key);
+
+        self.mix_resumption_secret(&mut state, secret).await?;
+
+        // decrypt the empty payload
+        let c = Self::read_end::<X25519_PUBLIC_KEY_LENGTH>(message)?;
+        self.hash_and_decrypt(&mut state, c).await?;
+
+        self.state = state;
+        Ok(true)
+    }
+
     /// Set the final state of the state machine by creating the encryption / decryption keys
     /// and return the other party identity
     pub(super) async fn set_final_state(&mut self, role: Role) -> Result<()> {
@@ -229,6 +278,7 @@ impl Handshake {
         state.status = Ready(HandshakeKeys {
             encryption_key,
             decryption_key,
+            chaining_key: state.take_ck()?,
         });
         // now remove the ephemeral keys which are not useful anymore
         self.state = state;
@@ -313,7 +363,8 @@ impl Handshake {
         Ok(())
     }
 
-    /// Compute the final encryption and decryption keys
+    /// Compute the final encryption and decryption keys.
+    /// The chaining key is kept in the state since it is used to renegotiate the channel keys
     async fn compute_final_keys(
         &self,
         state: &mut HandshakeState,
@@ -332,7 +383,6 @@ impl Handshake {
         let k1 = self.vault.convert_secret_buffer_to_aead_key(k1).await?;
         let k2 = self.vault.convert_secret_buffer_to_aead_key(k2).await?;
 
-        self.vault.delete_secret_buffer(state.take_ck()?).await?;
         self.vault.delete_aead_secret_key(state.take_k()?).await?;
 
         Ok((k1, k2))
@@ -368,6 +418,17 @@ impl Handshake {
         Ok(result)
     }
 
+    /// Derive new ck, and k keys from the secret of a resumption ticket + a Diffie-Hellman key
+    async fn mix_resumption_secret(&self, state: &mut HandshakeState, secret: &[u8]) -> Result<()> {
+        // ck, k = HKDF(ck, secret, 2)
+        let secret = self.vault.import_secret_buffer(secret.to_vec()).await?;
+        self.hkdf(state, secret).await?;
+
+        // ck, k = HKDF(ck, DH(e, re), 2)
+        let dh = self.dh(state.e()?, state.re()?).await?;
+        self.hkdf(state, dh).await
+    }
+
     async fn delete_ephemeral_keys(&mut self) -> Result<()> {
         _ = self
             .vault
@@ -670,6 +731,67 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_resumption() -> Result<()> {
+        let vault = SoftwareVaultForSecureChannels::create().await?;
+        let secret = [7u8; 32];
+
+        let initiator_static_key = vault.generate_static_x25519_secret_key().await?;
+        let mut initiator = Handshake::new(vault.clone(), initiator_static_key).await?;
+        let responder_static_key = vault.generate_static_x25519_secret_key().await?;
+        let mut responder = Handshake::new(vault.clone(), responder_static_key).await?;
+        initiator.initialize().await?;
+        responder.initialize().await?;
+
+        let message1 = initiator.encode_message1(b"ticket").await?;
+        responder.decode_message1(&message1).await?;
+
+        let response = responder.encode_resumption_response(&secret).await?;
+        assert_eq!(response.len(), RESUMPTION_RESPONSE_SIZE);
+        assert!(
+            initiator
+                .decode_resumption_response(&response, &secret)
+                .await?
+        );
+
+        initiator.set_final_state(Role::Initiator).await?;
+        responder.set_final_state(Role::Responder).await?;
+
+        let initiator_keys = initiator.get_handshake_keys().unwrap();
+        let responder_keys = responder.get_handshake_keys().unwrap();
+        let nonce = [0u8; 12];
+        let ciphertext = vault
+            .aead_encrypt(&initiator_keys.encryption_key, b"hello", &nonce, &[])
+            .await?;
+        let plaintext = vault
+            .aead_decrypt(&responder_keys.decryption_key, &ciphertext, &nonce, &[])
+            .await?;
+        assert_eq!(plaintext, b"hello");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_resumption_with_a_wrong_secret() -> Result<()> {
+        let vault = SoftwareVaultForSecureChannels::create().await?;
+
+        let initiator_static_key = vault.generate_static_x25519_secret_key().await?;
+        let mut initiator = Handshake::new(vault.clone(), initiator_static_key).await?;
+        let responder_static_key = vault.generate_static_x25519_secret_key().await?;
+        let mut responder = Handshake::new(vault.clone(), responder_static_key).await?;
+        initiator.initialize().await?;
+        responder.initialize().await?;
+
+        let message1 = initiator.encode_message1(b"ticket").await?;
+        responder.decode_message1(&message1).await?;
+
+        let response = responder.encode_resumption_response(&[1u8; 32]).await?;
+        assert!(initiator
+            .decode_resumption_response(&response, &[2u8; 32])
+            .await
+            .is_err());
+        Ok(())
+    }
+
     // --------------------
     // TESTS IMPLEMENTATION
     // --------------------
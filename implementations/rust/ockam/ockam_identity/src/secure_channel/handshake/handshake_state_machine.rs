@@ -2,17 +2,19 @@ use minicbor::{Decode, Encode};
 use tracing::{debug, warn};
 
 use ockam_core::compat::boxed::Box;
+use ockam_core::compat::collections::BTreeSet;
 use ockam_core::compat::string::ToString;
 use ockam_core::compat::sync::Arc;
 use ockam_core::compat::vec::Vec;
 use ockam_core::{async_trait, Result};
-use ockam_vault::{AeadSecretKeyHandle, X25519PublicKey};
+use ockam_vault::{AeadSecretKeyHandle, SecretBufferHandle, X25519PublicKey};
 
 use crate::models::{
     ChangeHistory, CredentialAndPurposeKey, PurposeKeyAttestation, PurposePublicKey,
 };
 use crate::{
-    Identifier, Identities, IdentityError, SecureChannelTrustInfo, TrustContext, TrustPolicy,
+    Capability, Identifier, Identities, IdentityError, SecureChannelTrustInfo, TrustContext,
+    TrustPolicy,
 };
 
 /// Interface for a state machine in a key exchange protocol
@@ -52,6 +54,8 @@ pub(super) enum Status {
 pub(super) struct HandshakeKeys {
     pub(super) encryption_key: AeadSecretKeyHandle,
     pub(super) decryption_key: AeadSecretKeyHandle,
+    /// Final chaining key of the handshake, used to renegotiate the channel keys
+    pub(super) chaining_key: SecretBufferHandle,
 }
 
 /// The end result of a handshake with identity/credentials exchange is
@@ -60,6 +64,7 @@ pub(super) struct HandshakeKeys {
 pub(super) struct HandshakeResults {
     pub(super) handshake_keys: HandshakeKeys,
     pub(super) their_identifier: Identifier,
+    pub(super) capabilities: BTreeSet<Capability>,
 }
 
 /// This struct implements functions common to both initiator and the responder state machines
@@ -70,7 +75,10 @@ pub(crate) struct CommonStateMachine {
     pub(super) credentials: Vec<CredentialAndPurposeKey>,
     pub(super) trust_policy: Arc<dyn TrustPolicy>,
     pub(super) trust_context: Option<TrustContext>,
+    /// capabilities supported locally
+    pub(super) capabilities: BTreeSet<Capability>,
     their_identifier: Option<Identifier>,
+    negotiated_capabilities: BTreeSet<Capability>,
 }
 
 impl CommonStateMachine {
@@ -81,6 +89,7 @@ impl CommonStateMachine {
         credentials: Vec<CredentialAndPurposeKey>,
         trust_policy: Arc<dyn TrustPolicy>,
         trust_context: Option<TrustContext>,
+        capabilities: BTreeSet<Capability>,
     ) -> Self {
         Self {
             identities,
@@ -89,7 +98,9 @@ impl CommonStateMachine {
             credentials,
             trust_policy,
             trust_context,
+            capabilities,
             their_identifier: None,
+            negotiated_capabilities: BTreeSet::new(),
         }
     }
 
@@ -99,14 +110,19 @@ impl CommonStateMachine {
     ///  - the current Identity Change History
     ///  - the current Secure Channel Purpose Key Attestation
     ///  - the Identity Credentials and corresponding Credentials Purpose Key Attestations
+    ///  - the negotiated capabilities, when sent by the responder
     ///
-    pub(super) async fn make_identity_payload(&self) -> Result<Vec<u8>> {
+    pub(super) async fn make_identity_payload(
+        &self,
+        capabilities: Option<BTreeSet<Capability>>,
+    ) -> Result<Vec<u8>> {
         // prepare the payload that will be sent either in message 2 or message 3
         let change_history = self.identities.get_change_history(&self.identifier).await?;
         let payload = IdentityAndCredentials {
             change_history,
             purpose_key_attestation: self.purpose_key_attestation.clone(),
             credentials: self.credentials.clone(),
+            capabilities,
         };
         Ok(minicbor::to_vec(payload)?)
     }
@@ -135,6 +151,31 @@ impl CommonStateMachine {
         Ok(())
     }
 
+    /// Check that the identity which was authenticated when a resumption ticket was issued
+    /// is still trusted, and store its identifier
+    pub(super) async fn process_resumed_identity(
+        &mut self,
+        their_identifier: Identifier,
+    ) -> Result<()> {
+        Self::verify_credentials(
+            self.identities.clone(),
+            Some(self.trust_policy.clone()),
+            self.trust_context.clone(),
+            &their_identifier,
+            vec![],
+        )
+        .await?;
+
+        self.their_identifier = Some(their_identifier);
+
+        Ok(())
+    }
+
+    /// Set the capabilities negotiated with the other party
+    pub(super) fn set_negotiated_capabilities(&mut self, capabilities: BTreeSet<Capability>) {
+        self.negotiated_capabilities = capabilities;
+    }
+
     /// Return the results of the full handshake
     ///  - the other party identity
     ///  - the encryption and decryption keys to use on the next messages to exchange
@@ -146,6 +187,7 @@ impl CommonStateMachine {
             (Some(their_identifier), Some(handshake_keys)) => Some(HandshakeResults {
                 their_identifier,
                 handshake_keys,
+                capabilities: self.negotiated_capabilities.clone(),
             }),
             _ => None,
         }
@@ -271,4 +313,38 @@ pub(super) struct IdentityAndCredentials {
     /// Credentials associated to the identity along with corresponding Credentials Purpose Keys
     /// to verify those Credentials
     #[n(2)] pub(super) credentials: Vec<CredentialAndPurposeKey>,
+    /// Capabilities negotiated by the responder, absent if the responder doesn't support
+    /// the negotiation
+    #[n(3)] pub(super) capabilities: Option<BTreeSet<Capability>>,
+}
+
+/// This internal structure is used as the payload of message 1 to extend the handshake.
+/// A responder which doesn't know one of the fields ignores it
+#[derive(Debug, Clone, Default, Encode, Decode)]
+#[rustfmt::skip]
+pub(super) struct HandshakeExtensions {
+    /// Identifier of a resumption ticket issued by the responder
+    #[cbor(with = "minicbor::bytes")]
+    #[n(0)] pub(super) resumption_ticket_id: Option<Vec<u8>>,
+    /// Capabilities offered by the initiator
+    #[n(1)] pub(super) capabilities: Option<BTreeSet<Capability>>,
+}
+
+impl HandshakeExtensions {
+    /// Encode the extensions, an empty payload is sent when there are no extensions
+    pub(super) fn encode(&self) -> Result<Vec<u8>> {
+        if self.resumption_ticket_id.is_none() && self.capabilities.is_none() {
+            Ok(vec![])
+        } else {
+            Ok(minicbor::to_vec(self)?)
+        }
+    }
+
+    /// Decode the extensions sent by an initiator, which might not support any extension
+    pub(super) fn decode(payload: &[u8]) -> Self {
+        if payload.is_empty() {
+            return Self::default();
+        }
+        minicbor::decode(payload).unwrap_or_default()
+    }
 }
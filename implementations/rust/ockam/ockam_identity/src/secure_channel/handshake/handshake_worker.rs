@@ -1,20 +1,23 @@
 use alloc::sync::Arc;
 use core::sync::atomic::AtomicBool;
 use core::time::Duration;
+use ockam_core::compat::collections::BTreeSet;
 use ockam_core::compat::{boxed::Box, vec::Vec};
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{
-    AllowAll, Any, Decodable, DenyAll, Error, Mailbox, Mailboxes, OutgoingAccessControl, Route,
-    Routed,
+    Address, AllowAll, Any, Decodable, DenyAll, Error, Mailbox, Mailboxes, OutgoingAccessControl,
+    Route, Routed,
 };
 use ockam_core::{AllowOnwardAddress, Result, Worker};
 use ockam_node::callback::CallbackSender;
 use ockam_node::{Context, WorkerBuilder};
-use tracing::{debug, info};
+use ockam_vault::SecretBufferHandle;
+use tracing::{debug, info, info_span, Instrument, Span};
 
 use crate::models::{CredentialAndPurposeKey, CredentialData, Identifier, VersionedData};
-use crate::secure_channel::decryptor::DecryptorHandler;
-use crate::secure_channel::encryptor::Encryptor;
+use crate::secure_channel::api::EncryptorInternalMessage;
+use crate::secure_channel::decryptor::{Decryptor, DecryptorHandler};
+use crate::secure_channel::encryptor::{Encryptor, KEY_RENEWAL_INTERVAL};
 use crate::secure_channel::encryptor_worker::EncryptorWorker;
 use crate::secure_channel::handshake::handshake_state_machine::Action::SendMessage;
 use crate::secure_channel::handshake::handshake_state_machine::Event::{
@@ -25,10 +28,13 @@ use crate::secure_channel::handshake::handshake_state_machine::{
 };
 use crate::secure_channel::handshake::initiator_state_machine::InitiatorStateMachine;
 use crate::secure_channel::handshake::responder_state_machine::ResponderStateMachine;
-use crate::secure_channel::{Addresses, Role};
+use crate::secure_channel::{
+    Addresses, ChannelPersistence, Rekeying, RekeyingPolicy, Resumption, Role, SecureChannelStats,
+};
 use crate::{
-    ChangeHistoryRepository, IdentityError, SecureChannelPurposeKey, SecureChannelRegistryEntry,
-    SecureChannels, TimestampInSeconds, TrustContext, TrustPolicy,
+    Capability, ChangeHistoryRepository, IdentityError, PersistedSecureChannel,
+    SecureChannelPurposeKey, SecureChannelRegistryEntry, SecureChannelRepository, SecureChannels,
+    TimestampInSeconds, TrustContext, TrustPolicy,
 };
 
 /// This struct implements a Worker receiving and sending messages
@@ -36,7 +42,8 @@ use crate::{
 pub(crate) struct HandshakeWorker {
     secure_channels: Arc<SecureChannels>,
     callback_sender: Option<CallbackSender<()>>,
-    state_machine: Box<dyn StateMachine>,
+    /// The state machine is not set when a persisted channel is resumed
+    state_machine: Option<Box<dyn StateMachine>>,
     identifier: Identifier,
     addresses: Addresses,
     role: Role,
@@ -46,8 +53,15 @@ pub(crate) struct HandshakeWorker {
     min_credential_expiration: Option<TimestampInSeconds>,
     refresh_credential_time_gap: Duration,
     trust_context: Option<TrustContext>,
+    rekeying: Option<RekeyingPolicy>,
+    persistence: Option<Arc<dyn SecureChannelRepository>>,
+    idle_timeout: Option<Duration>,
+    max_lifetime: Option<Duration>,
+    resumption: Option<Resumption>,
     change_history_repository: Arc<dyn ChangeHistoryRepository>,
     should_send_close: Arc<AtomicBool>,
+    /// Span covering the handshake, a child of the span which created the channel
+    handshake_span: Span,
 }
 
 #[ockam_core::worker]
@@ -58,23 +72,8 @@ impl Worker for HandshakeWorker {
     /// Initialize the state machine with an `Initialize` event
     /// Depending on the state machine role there might be a message to send to the other party
     async fn initialize(&mut self, context: &mut Self::Context) -> Result<()> {
-        match self.state_machine.on_event(Initialize).await? {
-            SendMessage(message) => {
-                debug!(
-                    "remote route {:?}, decryptor remote {:?}",
-                    self.remote_route.clone(),
-                    self.addresses.decryptor_remote.clone()
-                );
-                context
-                    .send_from_address(
-                        self.remote_route()?,
-                        message,
-                        self.addresses.decryptor_remote.clone(),
-                    )
-                    .await
-            }
-            Action::NoAction => Ok(()),
-        }
+        let span = self.handshake_span.clone();
+        self.start_handshake(context).instrument(span).await
     }
 
     /// Handle a message coming from the other party
@@ -93,48 +92,50 @@ impl Worker for HandshakeWorker {
         // used to support the decryption of Kafka messages for example
         if let Some(decryptor_handler) = self.decryptor_handler.as_mut() {
             let msg_addr = message.msg_addr();
+            // add the identifier of the other side to the log events
+            let span = info_span!(
+                target: ockam_node::LOG_CONTEXT_TARGET,
+                "secure_channel",
+                peer = %decryptor_handler.their_identity_id
+            );
 
             let result = if msg_addr == self.addresses.decryptor_remote {
-                decryptor_handler.handle_decrypt(context, message).await
+                decryptor_handler
+                    .handle_decrypt(context, message)
+                    .instrument(span)
+                    .await
             } else if msg_addr == self.addresses.decryptor_api {
-                decryptor_handler.handle_decrypt_api(context, message).await
+                decryptor_handler
+                    .handle_decrypt_api(context, message)
+                    .instrument(span)
+                    .await
             } else {
                 Err(IdentityError::UnknownChannelMsgDestination)?
             };
             return result;
         };
 
-        let transport_message = message.into_transport_message();
-        if let SendMessage(message) = self
-            .state_machine
-            .on_event(ReceivedMessage(Vec::<u8>::decode(
-                &transport_message.payload,
-            )?))
-            .await?
+        let span = self.handshake_span.clone();
+        if let Err(e) = self
+            .handle_handshake_message(context, message)
+            .instrument(span)
+            .await
         {
-            // set the remote route by taking the most up to date message return route
-            // In the case of the initiator the first return route mentions the secure channel listener
-            // address so we need to wait for the return route corresponding to the remote handshake worker
-            // when it has been spawned
-            self.remote_route = Some(transport_message.return_route);
-
-            context
-                .send_from_address(
-                    self.remote_route()?,
-                    message,
-                    self.addresses.decryptor_remote.clone(),
-                )
-                .await?
-        };
+            info!(
+                target: "ockam::audit::secure_channels",
+                event = "secure_channel_rejected",
+                role = self.role.str(),
+                address = %self.addresses.decryptor_remote,
+                reason = %e,
+                "the secure channel handshake failed"
+            );
+            return Err(e);
+        }
 
-        // if we reached the final state we can make a pair of encryptor/decryptor
-        if let Some(final_state) = self.state_machine.get_handshake_results() {
-            // start the encryptor worker and return the decryptor
-            self.decryptor_handler = Some(self.finalize(context, final_state).await?);
-            if let Some(callback_sender) = self.callback_sender.take() {
-                callback_sender.send(())?;
-            }
-        };
+        // close the span once the handshake is completed
+        if self.decryptor_handler.is_some() {
+            self.handshake_span = Span::none();
+        }
 
         Ok(())
     }
@@ -168,6 +169,12 @@ impl HandshakeWorker {
         min_credential_refresh_interval: Duration,
         refresh_credential_time_gap: Duration,
         trust_context: Option<TrustContext>,
+        rekeying: Option<RekeyingPolicy>,
+        persistence: Option<Arc<dyn SecureChannelRepository>>,
+        idle_timeout: Option<Duration>,
+        max_lifetime: Option<Duration>,
+        mut capabilities: BTreeSet<Capability>,
+        resumption: Option<Resumption>,
         remote_route: Option<Route>,
         timeout: Option<Duration>,
         role: Role,
@@ -184,6 +191,15 @@ impl HandshakeWorker {
             .map(|data| data.expires_at)
             .min();
 
+        // resumption tickets can be exchanged if both sides use resumption
+        if resumption.is_some() {
+            capabilities.insert(Capability::RESUMPTION);
+        }
+        // the keys can always be renegotiated when the other side asks for it
+        // and the verification of refreshed credentials can always be acknowledged
+        capabilities.insert(Capability::REKEYING);
+        capabilities.insert(Capability::CREDENTIALS_REFRESH_RESPONSE);
+
         let state_machine: Box<dyn StateMachine> = if role.is_initiator() {
             Box::new(
                 InitiatorStateMachine::new(
@@ -194,6 +210,11 @@ impl HandshakeWorker {
                     credentials,
                     trust_policy,
                     trust_context.clone(),
+                    capabilities,
+                    match &resumption {
+                        Some(resumption) => resumption.take_ticket()?,
+                        None => None,
+                    },
                 )
                 .await?,
             )
@@ -207,6 +228,8 @@ impl HandshakeWorker {
                     credentials,
                     trust_policy,
                     trust_context.clone(),
+                    capabilities,
+                    resumption.as_ref().and_then(|r| r.issued_tickets()),
                 )
                 .await?,
             )
@@ -222,7 +245,7 @@ impl HandshakeWorker {
         let worker = Self {
             secure_channels,
             callback_sender,
-            state_machine,
+            state_machine: Some(state_machine),
             identifier,
             role,
             remote_route: remote_route.clone(),
@@ -232,8 +255,14 @@ impl HandshakeWorker {
             min_credential_expiration,
             refresh_credential_time_gap,
             trust_context,
+            rekeying,
+            persistence,
+            idle_timeout,
+            max_lifetime,
+            resumption,
             change_history_repository: identities.change_history_repository(),
             should_send_close: Arc::new(AtomicBool::new(true)),
+            handshake_span: info_span!("secure_channel_handshake", role = %role),
         };
 
         WorkerBuilder::new(worker)
@@ -266,6 +295,107 @@ impl HandshakeWorker {
         Ok(())
     }
 
+    /// Resume a persisted secure channel without running a new handshake.
+    /// The channel workers are started with the persisted addresses and keys
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn resume(
+        context: &Context,
+        secure_channels: Arc<SecureChannels>,
+        addresses: Addresses,
+        persisted: PersistedSecureChannel,
+        repository: Arc<dyn SecureChannelRepository>,
+        decryptor_outgoing_access_control: Arc<dyn OutgoingAccessControl>,
+        min_credential_refresh_interval: Duration,
+        refresh_credential_time_gap: Duration,
+        trust_context: Option<TrustContext>,
+        rekeying: Option<RekeyingPolicy>,
+        idle_timeout: Option<Duration>,
+        max_lifetime: Option<Duration>,
+        remote_route: Route,
+    ) -> Result<()> {
+        let vault = secure_channels.identities.vault().secure_channel_vault;
+        let change_history_repository = secure_channels.identities.change_history_repository();
+        let role = if persisted.is_initiator {
+            Role::Initiator
+        } else {
+            Role::Responder
+        };
+
+        let mut worker = Self {
+            secure_channels,
+            callback_sender: None,
+            state_machine: None,
+            identifier: persisted.my_identifier.clone(),
+            role,
+            remote_route: Some(remote_route.clone()),
+            addresses: addresses.clone(),
+            decryptor_handler: None,
+            min_credential_refresh_interval,
+            min_credential_expiration: None,
+            refresh_credential_time_gap,
+            trust_context,
+            rekeying,
+            persistence: Some(repository.clone()),
+            idle_timeout,
+            max_lifetime,
+            resumption: None,
+            change_history_repository,
+            should_send_close: Arc::new(AtomicBool::new(true)),
+            handshake_span: Span::none(),
+        };
+
+        // Skip the rest of the current key renewal interval, since some of its nonces
+        // might have been used before the restart
+        let encryptor = Encryptor::new(
+            persisted.encryption_key.clone(),
+            persisted.encryption_nonce + KEY_RENEWAL_INTERVAL,
+            vault.clone(),
+        );
+        let decryptor = Decryptor::restore(
+            persisted.decryption_key.clone(),
+            persisted.previous_decryption_key.clone(),
+            persisted.decryption_rekeys,
+            vault.clone(),
+        );
+        let persistence = ChannelPersistence::new(
+            repository,
+            vault,
+            addresses.encryptor.clone(),
+            persisted.their_decryptor.clone(),
+            remote_route,
+        );
+
+        let decryptor_handler = worker
+            .start_channel(
+                context,
+                persisted.their_identifier.clone(),
+                BTreeSet::new(),
+                None,
+                encryptor,
+                decryptor,
+                Some(persistence),
+            )
+            .await?;
+        worker.decryptor_handler = Some(decryptor_handler);
+
+        WorkerBuilder::new(worker)
+            .with_mailboxes(Self::create_mailboxes(
+                &addresses,
+                decryptor_outgoing_access_control,
+            ))
+            .start(context)
+            .await?;
+
+        info!(
+            "Resumed SecureChannel {} at local: {}, remote: {}",
+            role.str(),
+            &addresses.encryptor,
+            &addresses.decryptor_remote
+        );
+
+        Ok(())
+    }
+
     /// Return the route for the other party's handshake worker
     fn remote_route(&self) -> Result<Route> {
         self.remote_route.clone().ok_or_else(|| {
@@ -305,6 +435,97 @@ impl HandshakeWorker {
         Mailboxes::new(remote_mailbox, vec![internal_mailbox, api_mailbox])
     }
 
+    /// Initialize the state machine with an `Initialize` event
+    async fn start_handshake(&mut self, context: &mut Context) -> Result<()> {
+        let state_machine = match self.state_machine.as_mut() {
+            Some(state_machine) => state_machine,
+            // a resumed channel doesn't need a handshake
+            None => return Ok(()),
+        };
+        match state_machine.on_event(Initialize).await? {
+            SendMessage(message) => {
+                debug!(
+                    "remote route {:?}, decryptor remote {:?}",
+                    self.remote_route.clone(),
+                    self.addresses.decryptor_remote.clone()
+                );
+                context
+                    .send_from_address(
+                        self.remote_route()?,
+                        message,
+                        self.addresses.decryptor_remote.clone(),
+                    )
+                    .await
+            }
+            Action::NoAction => Ok(()),
+        }
+    }
+
+    /// Send a message coming from the other party to the state machine, and finalize
+    /// the channel once the handshake is completed
+    async fn handle_handshake_message(
+        &mut self,
+        context: &mut Context,
+        message: Routed<Any>,
+    ) -> Result<()> {
+        let state_machine = self.state_machine.as_mut().ok_or_else(|| {
+            Error::new(
+                Origin::KeyExchange,
+                Kind::Invalid,
+                "a handshake state machine should have been set",
+            )
+        })?;
+
+        let transport_message = message.into_transport_message();
+        // set the remote route by taking the most up to date message return route
+        // In the case of the initiator the first return route mentions the secure channel listener
+        // address so we need to wait for the return route corresponding to the remote handshake worker
+        // when it has been spawned
+        self.remote_route = Some(transport_message.return_route);
+
+        if let SendMessage(message) = state_machine
+            .on_event(ReceivedMessage(Vec::<u8>::decode(
+                &transport_message.payload,
+            )?))
+            .await?
+        {
+            context
+                .send_from_address(
+                    self.remote_route()?,
+                    message,
+                    self.addresses.decryptor_remote.clone(),
+                )
+                .await?
+        };
+
+        // if we reached the final state we can make a pair of encryptor/decryptor
+        if let Some(final_state) = self
+            .state_machine
+            .as_ref()
+            .and_then(|state_machine| state_machine.get_handshake_results())
+        {
+            // start the encryptor worker and return the decryptor
+            let their_identifier = final_state.their_identifier.clone();
+            let capabilities = final_state.capabilities.clone();
+            self.decryptor_handler = Some(self.finalize(context, final_state).await?);
+            info!(
+                target: "ockam::audit::secure_channels",
+                event = "secure_channel_established",
+                role = self.role.str(),
+                address = %self.addresses.encryptor,
+                peer = %their_identifier,
+                "secure channel established"
+            );
+            self.send_resumption_ticket(context, &their_identifier, &capabilities)
+                .await?;
+            if let Some(callback_sender) = self.callback_sender.take() {
+                callback_sender.send(())?;
+            }
+        };
+
+        Ok(())
+    }
+
     /// Finalize the handshake by creating a `Decryptor` and an `EncryptorWorker`
     /// Note that `EncryptorWorker` is actually started as an independent worker while
     /// the `Decryptor` is directly used by this worker to delegate the decryption of messages
@@ -313,29 +534,122 @@ impl HandshakeWorker {
         context: &Context,
         handshake_results: HandshakeResults,
     ) -> Result<DecryptorHandler> {
+        let vault = self.secure_channels.identities.vault().secure_channel_vault;
+
+        let persistence = match &self.persistence {
+            Some(repository) => {
+                let persistence = ChannelPersistence::new(
+                    repository.clone(),
+                    vault.clone(),
+                    self.addresses.encryptor.clone(),
+                    self.their_decryptor_address()?,
+                    self.remote_route()?,
+                );
+                persistence
+                    .persist(&PersistedSecureChannel {
+                        is_initiator: self.role.is_initiator(),
+                        my_identifier: self.identifier.clone(),
+                        their_identifier: handshake_results.their_identifier.clone(),
+                        encryptor: self.addresses.encryptor.clone(),
+                        encryptor_api: self.addresses.encryptor_api.clone(),
+                        encryptor_internal: self.addresses.encryptor_internal.clone(),
+                        decryptor_remote: self.addresses.decryptor_remote.clone(),
+                        decryptor_api: self.addresses.decryptor_api.clone(),
+                        decryptor_internal: self.addresses.decryptor_internal.clone(),
+                        their_decryptor: self.their_decryptor_address()?,
+                        encryption_key: handshake_results.handshake_keys.encryption_key.clone(),
+                        encryption_nonce: 0,
+                        decryption_key: handshake_results.handshake_keys.decryption_key.clone(),
+                        previous_decryption_key: None,
+                        decryption_rekeys: 0,
+                    })
+                    .await?;
+                Some(persistence)
+            }
+            None => None,
+        };
+
+        self.start_channel(
+            context,
+            handshake_results.their_identifier,
+            handshake_results.capabilities,
+            Some(handshake_results.handshake_keys.chaining_key),
+            Encryptor::new(
+                handshake_results.handshake_keys.encryption_key,
+                0,
+                vault.clone(),
+            ),
+            Decryptor::new(handshake_results.handshake_keys.decryption_key, vault),
+            persistence,
+        )
+        .await
+    }
+
+    /// Start the `EncryptorWorker`, register the channel and
+    /// return the handler used to decrypt messages.
+    ///
+    /// A resumed persisted channel doesn't have a chaining key and can't renegotiate its keys
+    async fn start_channel(
+        &self,
+        context: &Context,
+        their_identifier: Identifier,
+        capabilities: BTreeSet<Capability>,
+        chaining_key: Option<SecretBufferHandle>,
+        encryptor: Encryptor,
+        decryptor: Decryptor,
+        persistence: Option<ChannelPersistence>,
+    ) -> Result<DecryptorHandler> {
+        // the statistics are shared by the encryptor and the decryptor
+        let stats = Arc::new(SecureChannelStats::new());
+
+        // the rekeying state is shared by the encryptor and the decryptor
+        let vault = self.secure_channels.identities.vault().secure_channel_vault;
+        let chaining_key = match chaining_key {
+            Some(chaining_key) if capabilities.contains(&Capability::REKEYING) => {
+                Some(chaining_key)
+            }
+            Some(chaining_key) => {
+                vault.delete_secret_buffer(chaining_key).await?;
+                None
+            }
+            None => None,
+        };
+        let rekeying = Rekeying::new(
+            self.rekeying.clone(),
+            self.role.is_initiator(),
+            chaining_key,
+            vault,
+        );
+
         // create a decryptor to delegate the processing of all messages after the handshake
         let decryptor = DecryptorHandler::new(
             self.secure_channels.identities.clone(),
             self.trust_context.clone(),
             self.role.str(),
             self.addresses.clone(),
-            handshake_results.handshake_keys.decryption_key,
-            self.secure_channels.identities.vault().secure_channel_vault,
-            handshake_results.their_identifier.clone(),
+            decryptor,
+            their_identifier.clone(),
             self.should_send_close.clone(),
+            rekeying.clone(),
+            persistence.clone(),
+            stats.clone(),
+            self.resumption.clone(),
+            capabilities.clone(),
         );
 
         // create a separate encryptor worker which will be started independently
         {
+            // the route to a persistent channel can change when the other side is restarted
+            let outgoing_access_control: Arc<dyn OutgoingAccessControl> = match &persistence {
+                Some(persistence) => persistence.outgoing_access_control(),
+                None => Arc::new(AllowOnwardAddress(self.remote_route()?.next()?.clone())),
+            };
+
             let encryptor = EncryptorWorker::new(
                 self.role.str(),
                 self.addresses.clone(),
                 self.remote_route()?,
-                Encryptor::new(
-                    handshake_results.handshake_keys.encryption_key,
-                    0,
-                    self.secure_channels.identities.vault().secure_channel_vault,
-                ),
+                encryptor,
                 self.identifier.clone(),
                 self.change_history_repository.clone(),
                 self.min_credential_expiration,
@@ -343,13 +657,17 @@ impl HandshakeWorker {
                 self.refresh_credential_time_gap,
                 self.trust_context.clone(),
                 self.should_send_close.clone(),
+                rekeying,
+                persistence,
+                self.idle_timeout,
+                self.max_lifetime,
+                stats.clone(),
             );
 
-            let next_hop = self.remote_route()?.next()?.clone();
             let main_mailbox = Mailbox::new(
                 self.addresses.encryptor.clone(),
                 Arc::new(AllowAll),
-                Arc::new(AllowOnwardAddress(next_hop)),
+                outgoing_access_control,
             );
             let api_mailbox = Mailbox::new(
                 self.addresses.encryptor_api.clone(),
@@ -378,13 +696,6 @@ impl HandshakeWorker {
             &self.addresses.decryptor_remote
         );
 
-        let their_decryptor_address = self
-            .remote_route()?
-            .iter()
-            .last()
-            .expect("the remote route should not be empty")
-            .clone();
-
         let info = SecureChannelRegistryEntry::new(
             self.addresses.encryptor.clone(),
             self.addresses.encryptor_api.clone(),
@@ -392,9 +703,12 @@ impl HandshakeWorker {
             self.addresses.decryptor_api.clone(),
             self.role.is_initiator(),
             self.identifier.clone(),
-            handshake_results.their_identifier,
-            their_decryptor_address,
-        );
+            their_identifier,
+            self.their_decryptor_address()?,
+        )
+        .with_encryptor_internal_address(self.addresses.encryptor_internal.clone())
+        .with_stats(stats)
+        .with_capabilities(capabilities);
 
         self.secure_channels
             .secure_channel_registry()
@@ -402,4 +716,39 @@ impl HandshakeWorker {
 
         Ok(decryptor)
     }
+
+    /// Issue a resumption ticket and ask the encryptor to send it to the initiator,
+    /// when resumption is used by the responder and supported by the initiator
+    async fn send_resumption_ticket(
+        &self,
+        context: &Context,
+        their_identifier: &Identifier,
+        capabilities: &BTreeSet<Capability>,
+    ) -> Result<()> {
+        if !capabilities.contains(&Capability::RESUMPTION) {
+            return Ok(());
+        }
+        let tickets = match self.resumption.as_ref().and_then(|r| r.issued_tickets()) {
+            Some(tickets) => tickets,
+            None => return Ok(()),
+        };
+        let ticket = tickets.issue(&self.identifier, their_identifier, capabilities)?;
+        context
+            .send_from_address(
+                self.addresses.encryptor_internal.clone(),
+                EncryptorInternalMessage::SendResumptionTicket(minicbor::to_vec(ticket)?),
+                self.addresses.decryptor_remote.clone(),
+            )
+            .await
+    }
+
+    /// Return the address of the decryptor on the other side
+    fn their_decryptor_address(&self) -> Result<Address> {
+        Ok(self
+            .remote_route()?
+            .iter()
+            .last()
+            .expect("the remote route should not be empty")
+            .clone())
+    }
 }
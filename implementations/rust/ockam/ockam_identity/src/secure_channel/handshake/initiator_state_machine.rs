@@ -1,5 +1,6 @@
 use delegate::delegate;
 use ockam_core::async_trait;
+use ockam_core::compat::collections::BTreeSet;
 use ockam_core::compat::sync::Arc;
 use ockam_core::compat::{boxed::Box, vec::Vec};
 use ockam_core::errcode::{Kind, Origin};
@@ -14,10 +15,11 @@ use crate::models::{CredentialAndPurposeKey, Identifier};
 use crate::secure_channel::handshake::error::XXError;
 use crate::secure_channel::handshake::handshake::Handshake;
 use crate::secure_channel::handshake::handshake_state_machine::{
-    Action, CommonStateMachine, Event, HandshakeKeys, HandshakeResults, IdentityAndCredentials,
-    StateMachine, Status,
+    Action, CommonStateMachine, Event, HandshakeExtensions, HandshakeKeys, HandshakeResults,
+    IdentityAndCredentials, StateMachine, Status,
 };
-use crate::{Identities, Role, SecureChannelPurposeKey, TrustContext, TrustPolicy};
+use crate::secure_channel::{negotiate_capabilities, ReceivedTicket};
+use crate::{Capability, Identities, Role, SecureChannelPurposeKey, TrustContext, TrustPolicy};
 
 /// Implementation of a state machine for the key exchange on the initiator side
 #[async_trait]
@@ -28,7 +30,19 @@ impl StateMachine for InitiatorStateMachine {
             // Initialize the handshake and send message 1
             (Initial, Initialize) => {
                 self.initialize_handshake().await?;
-                let message1 = self.encode_message1(&[]).await?;
+                // offer our capabilities and present a resumption ticket if there is one
+                let extensions = HandshakeExtensions {
+                    resumption_ticket_id: self
+                        .resumption_ticket
+                        .as_ref()
+                        .map(|ticket| ticket.ticket_id.clone()),
+                    capabilities: if self.common.capabilities.is_empty() {
+                        None
+                    } else {
+                        Some(self.common.capabilities.clone())
+                    },
+                };
+                let message1 = self.encode_message1(&extensions.encode()?).await?;
 
                 // Send message 1 and wait for message 2
                 self.handshake.state.status = WaitingForMessage2;
@@ -36,9 +50,28 @@ impl StateMachine for InitiatorStateMachine {
             }
             // Process message 2 and send message 3
             (WaitingForMessage2, ReceivedMessage(message)) => {
+                // if the resumption ticket was accepted, the handshake is finished
+                if let Some(ticket) = self.resumption_ticket.take() {
+                    if self
+                        .decode_resumption_response(&message, &ticket.secret)
+                        .await?
+                    {
+                        self.process_resumed_identity(ticket.their_identifier)
+                            .await?;
+                        self.common.set_negotiated_capabilities(ticket.capabilities);
+                        self.set_final_state(Initiator).await?;
+                        return Ok(NoAction);
+                    }
+                }
+
                 let message2_payload = self.decode_message2(&message).await?;
                 let their_identity_payload: IdentityAndCredentials =
                     minicbor::decode(&message2_payload)?;
+                let capabilities = negotiate_capabilities(
+                    their_identity_payload.capabilities.as_ref(),
+                    &self.common.capabilities,
+                );
+                self.common.set_negotiated_capabilities(capabilities);
                 self.process_identity_payload(
                     their_identity_payload,
                     self.handshake.state.rs()?.clone(),
@@ -75,12 +108,15 @@ pub(super) struct InitiatorStateMachine {
     pub(super) handshake: Handshake,
     /// this serialized payload contains an identity, its credentials and a signature of its static key
     pub(super) identity_payload: Option<Vec<u8>>,
+    /// ticket presented to the responder to resume a previous channel
+    pub(super) resumption_ticket: Option<ReceivedTicket>,
 }
 
 impl InitiatorStateMachine {
     delegate! {
         to self.common {
             async fn process_identity_payload(&mut self, peer: IdentityAndCredentials, peer_public_key: X25519PublicKey) -> Result<()>;
+            async fn process_resumed_identity(&mut self, their_identifier: Identifier) -> Result<()>;
             fn make_handshake_results(&self, handshake_keys: Option<HandshakeKeys>) -> Option<HandshakeResults>;
         }
     }
@@ -90,6 +126,7 @@ impl InitiatorStateMachine {
             async fn initialize_handshake(&mut self) -> Result<()>;
             async fn encode_message1(&mut self, payload: &[u8]) -> Result<Vec<u8>>;
             async fn decode_message2(&mut self, message: &[u8]) -> Result<Vec<u8>>;
+            async fn decode_resumption_response(&mut self, message: &[u8], secret: &[u8]) -> Result<bool>;
             async fn encode_message3(&mut self, payload: &[u8]) -> Result<Vec<u8>>;
             async fn set_final_state(&mut self, role: Role) -> Result<()>;
             fn get_handshake_keys(&self) -> Option<HandshakeKeys>;
@@ -106,6 +143,8 @@ impl InitiatorStateMachine {
         credentials: Vec<CredentialAndPurposeKey>,
         trust_policy: Arc<dyn TrustPolicy>,
         trust_context: Option<TrustContext>,
+        capabilities: BTreeSet<Capability>,
+        resumption_ticket: Option<ReceivedTicket>,
     ) -> Result<InitiatorStateMachine> {
         let common = CommonStateMachine::new(
             identities,
@@ -114,13 +153,15 @@ impl InitiatorStateMachine {
             credentials,
             trust_policy,
             trust_context,
+            capabilities,
         );
-        let identity_payload = common.make_identity_payload().await?;
+        let identity_payload = common.make_identity_payload(None).await?;
 
         Ok(InitiatorStateMachine {
             common,
             handshake: Handshake::new(vault, purpose_key.key().clone()).await?,
             identity_payload: Some(identity_payload),
+            resumption_ticket,
         })
     }
 }
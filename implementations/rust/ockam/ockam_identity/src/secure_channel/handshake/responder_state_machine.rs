@@ -1,5 +1,6 @@
 use async_trait::async_trait;
 use delegate::delegate;
+use ockam_core::compat::collections::BTreeSet;
 use ockam_core::compat::sync::Arc;
 use ockam_core::compat::{boxed::Box, vec::Vec};
 use ockam_core::errcode::{Kind, Origin};
@@ -11,13 +12,16 @@ use Role::*;
 use Status::*;
 
 use crate::models::{CredentialAndPurposeKey, Identifier};
-use crate::secure_channel::handshake::error::XXError;
 use crate::secure_channel::handshake::handshake::Handshake;
 use crate::secure_channel::handshake::handshake_state_machine::{
-    Action, CommonStateMachine, Event, HandshakeKeys, HandshakeResults, IdentityAndCredentials,
-    StateMachine, Status,
+    Action, CommonStateMachine, Event, HandshakeExtensions, HandshakeKeys, HandshakeResults,
+    IdentityAndCredentials, StateMachine, Status,
+};
+use crate::secure_channel::{negotiate_capabilities, RedeemedTicket};
+use crate::{
+    Capability, Identities, ResumptionTickets, Role, SecureChannelPurposeKey, TrustContext,
+    TrustPolicy,
 };
-use crate::{Identities, Role, SecureChannelPurposeKey, TrustContext, TrustPolicy};
 
 /// Implementation of a state machine for the key exchange on the responder side
 #[async_trait]
@@ -33,11 +37,31 @@ impl StateMachine for ResponderStateMachine {
             }
             // Process message 1 and send message 2
             (WaitingForMessage1, ReceivedMessage(message)) => {
-                self.decode_message1(&message).await?;
+                let message1_payload = self.decode_message1(&message).await?;
+                let extensions = HandshakeExtensions::decode(&message1_payload);
+
+                // if a valid resumption ticket is presented, the handshake is finished
+                if let Some(ticket) = self.redeem_resumption_ticket(&extensions)? {
+                    self.process_resumed_identity(ticket.their_identifier)
+                        .await?;
+                    self.common.set_negotiated_capabilities(ticket.capabilities);
+                    let response = self.encode_resumption_response(&ticket.secret).await?;
+                    self.set_final_state(Responder).await?;
+                    return Ok(SendMessage(response));
+                }
+
+                // answer with the capabilities supported by both sides,
+                // if the initiator supports the negotiation
+                let capabilities = negotiate_capabilities(
+                    extensions.capabilities.as_ref(),
+                    &self.common.capabilities,
+                );
+                self.common
+                    .set_negotiated_capabilities(capabilities.clone());
                 let identity_payload = self
-                    .identity_payload
-                    .take()
-                    .ok_or(XXError::InvalidInternalState)?;
+                    .common
+                    .make_identity_payload(extensions.capabilities.map(|_| capabilities))
+                    .await?;
                 let message2 = self.encode_message2(&identity_payload).await?;
 
                 self.handshake.state.status = WaitingForMessage3;
@@ -76,14 +100,15 @@ impl StateMachine for ResponderStateMachine {
 pub struct ResponderStateMachine {
     common: CommonStateMachine,
     handshake: Handshake,
-    /// this serialized payload contains an identity, its credentials and a signature of its static key
-    identity_payload: Option<Vec<u8>>,
+    /// tickets issued to resume previous channels
+    resumption_tickets: Option<Arc<ResumptionTickets>>,
 }
 
 impl ResponderStateMachine {
     delegate! {
         to self.common {
             async fn process_identity_payload(&mut self, peer: IdentityAndCredentials, peer_public_key: X25519PublicKey) -> Result<()>;
+            async fn process_resumed_identity(&mut self, their_identifier: Identifier) -> Result<()>;
             fn make_handshake_results(&self, handshake_keys: Option<HandshakeKeys>) -> Option<HandshakeResults>;
         }
     }
@@ -93,6 +118,7 @@ impl ResponderStateMachine {
             async fn initialize_handshake(&mut self) -> Result<()>;
             async fn decode_message1(&mut self, message: &[u8]) -> Result<Vec<u8>>;
             async fn encode_message2(&mut self, payload: &[u8]) -> Result<Vec<u8>>;
+            async fn encode_resumption_response(&mut self, secret: &[u8]) -> Result<Vec<u8>>;
             async fn decode_message3(&mut self, message: &[u8]) -> Result<Vec<u8>>;
             async fn set_final_state(&mut self, role: Role) -> Result<()>;
             fn get_handshake_keys(&self) -> Option<HandshakeKeys>;
@@ -109,6 +135,8 @@ impl ResponderStateMachine {
         credentials: Vec<CredentialAndPurposeKey>,
         trust_policy: Arc<dyn TrustPolicy>,
         trust_context: Option<TrustContext>,
+        capabilities: BTreeSet<Capability>,
+        resumption_tickets: Option<Arc<ResumptionTickets>>,
     ) -> Result<ResponderStateMachine> {
         let common = CommonStateMachine::new(
             identities,
@@ -117,13 +145,25 @@ impl ResponderStateMachine {
             credentials,
             trust_policy,
             trust_context,
+            capabilities,
         );
-        let identity_payload = common.make_identity_payload().await?;
 
         Ok(ResponderStateMachine {
             common,
             handshake: Handshake::new(vault, purpose_key.key().clone()).await?,
-            identity_payload: Some(identity_payload),
+            resumption_tickets,
         })
     }
+
+    /// Return the resumption ticket presented in message 1 if it is valid.
+    /// The ticket is removed so that it can't be presented again
+    fn redeem_resumption_ticket(
+        &self,
+        extensions: &HandshakeExtensions,
+    ) -> Result<Option<RedeemedTicket>> {
+        match (&self.resumption_tickets, &extensions.resumption_ticket_id) {
+            (Some(tickets), Some(ticket_id)) => tickets.redeem(ticket_id, &self.common.identifier),
+            _ => Ok(None),
+        }
+    }
 }
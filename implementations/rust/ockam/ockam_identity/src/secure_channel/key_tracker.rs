@@ -23,6 +23,28 @@ impl KeyTracker {
             renewal_interval,
         }
     }
+
+    /// Restore a key tracker with the keys and the number of key renewals
+    /// of a persisted secure channel
+    pub(crate) fn restore(
+        current_key: AeadSecretKeyHandle,
+        previous_key: Option<AeadSecretKeyHandle>,
+        number_of_rekeys: u64,
+        renewal_interval: u64,
+    ) -> Self {
+        KeyTracker {
+            current_key,
+            number_of_rekeys,
+            max_rekeys_reached: false,
+            previous_key,
+            renewal_interval,
+        }
+    }
+
+    /// Return the number of key renewals
+    pub(crate) fn number_of_rekeys(&self) -> u64 {
+        self.number_of_rekeys
+    }
 }
 
 impl KeyTracker {
@@ -2,11 +2,13 @@ use ockam_core::compat::boxed::Box;
 use ockam_core::compat::sync::Arc;
 use ockam_core::{Address, Any, Result, Routed, Worker};
 use ockam_node::Context;
+use tracing::info;
 
 use crate::models::Identifier;
 use crate::secure_channel::addresses::Addresses;
 use crate::secure_channel::handshake_worker::HandshakeWorker;
 use crate::secure_channel::options::SecureChannelListenerOptions;
+use crate::secure_channel::resumption::Resumption;
 use crate::secure_channel::role::Role;
 use crate::secure_channels::secure_channels::SecureChannels;
 
@@ -56,6 +58,24 @@ impl Worker for SecureChannelListenerWorker {
         ctx: &mut Self::Context,
         message: Routed<Self::Message>,
     ) -> Result<()> {
+        // the handshake is dropped if the node can't accept more secure channels,
+        // the listener keeps running so that channels can be created again later
+        if let Err(e) = self
+            .secure_channels
+            .secure_channel_registry
+            .check_channels_limit()
+        {
+            info!(
+                target: "ockam::audit::secure_channels",
+                event = "secure_channel_rejected",
+                role = Role::Responder.str(),
+                address = %ctx.address(),
+                reason = %e,
+                "the secure channel handshake was rejected"
+            );
+            return Ok(());
+        }
+
         let addresses = Addresses::generate(Role::Responder);
         let flow_control_id = self.options.setup_flow_control_for_channel(
             ctx.flow_controls(),
@@ -91,12 +111,21 @@ impl Worker for SecureChannelListenerWorker {
             addresses.clone(),
             self.identifier.clone(),
             purpose_key,
-            self.options.trust_policy.clone(),
+            self.options.trust_policy(),
             access_control.decryptor_outgoing_access_control,
             credentials,
             self.options.min_credential_refresh_interval,
             self.options.refresh_credential_time_gap,
             self.options.trust_context.clone(),
+            self.options.rekeying.clone(),
+            self.options.persistence.clone(),
+            self.options.idle_timeout,
+            self.options.max_lifetime,
+            self.options.capabilities.clone(),
+            self.options
+                .resumption_tickets
+                .clone()
+                .map(|tickets| Resumption::Responder { tickets }),
             None,
             None,
             Role::Responder,
@@ -1,7 +1,10 @@
-use crate::models::{ChangeHistory, CredentialAndPurposeKey};
+use crate::models::{ChangeHistory, CredentialAndPurposeKey, TimestampInSeconds};
+use crate::Capability;
 use minicbor::{Decode, Encode};
+use ockam_core::compat::collections::BTreeSet;
 use ockam_core::compat::vec::Vec;
 use ockam_core::Route;
+use ockam_vault::X25519PublicKey;
 
 /// Secure Channel Message format.
 #[derive(Debug, Encode, Decode, Clone)]
@@ -13,6 +16,14 @@ pub enum SecureChannelMessage {
     #[n(1)] RefreshCredentials(#[n(0)] RefreshCredentialsMessage),
     /// Close the channel.
     #[n(2)] Close,
+    /// Start a renegotiation of the channel keys.
+    #[n(3)] RekeyRequest(#[n(0)] RekeyMessage),
+    /// Complete a renegotiation of the channel keys.
+    #[n(4)] RekeyResponse(#[n(0)] RekeyMessage),
+    /// Tell the other side if the credentials it presented were accepted.
+    #[n(5)] RefreshCredentialsResponse(#[n(0)] RefreshCredentialsResponseMessage),
+    /// Ticket which can be used to resume the channel without a full handshake.
+    #[n(6)] ResumptionTicket(#[n(0)] ResumptionTicketMessage),
 }
 
 /// Secure Channel Message format.
@@ -38,3 +49,35 @@ pub struct RefreshCredentialsMessage {
     /// to verify those Credentials
     #[n(1)] pub credentials: Vec<CredentialAndPurposeKey>,
 }
+
+/// Secure Channel Message format.
+#[derive(Debug, Encode, Decode, Clone)]
+#[rustfmt::skip]
+pub struct RefreshCredentialsResponseMessage {
+    /// True if the presented credentials were verified and stored
+    #[n(0)] pub accepted: bool,
+}
+
+/// Secure Channel Message format.
+#[derive(Debug, Encode, Decode, Clone)]
+#[rustfmt::skip]
+pub struct RekeyMessage {
+    /// Ephemeral public key used to derive the new channel keys
+    #[n(0)] pub public_key: X25519PublicKey,
+}
+
+/// Secure Channel Message format.
+#[derive(Debug, Encode, Decode, Clone)]
+#[rustfmt::skip]
+pub struct ResumptionTicketMessage {
+    /// Identifier of the ticket, presented by the initiator to resume a channel
+    #[cbor(with = "minicbor::bytes")]
+    #[n(0)] pub ticket_id: Vec<u8>,
+    /// Secret used to derive the keys of the resumed channel
+    #[cbor(with = "minicbor::bytes")]
+    #[n(1)] pub secret: Vec<u8>,
+    /// Time after which the ticket can't be used anymore
+    #[n(2)] pub expires_at: TimestampInSeconds,
+    /// Capabilities of the resumed channels
+    #[n(3)] pub capabilities: Option<BTreeSet<Capability>>,
+}
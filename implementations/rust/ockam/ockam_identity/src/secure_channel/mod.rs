@@ -2,6 +2,7 @@
 pub mod access_control;
 mod addresses;
 mod api;
+mod capabilities;
 mod decryptor;
 mod encryptor;
 mod encryptor_worker;
@@ -12,8 +13,12 @@ mod local_info;
 mod message;
 mod nonce_tracker;
 mod options;
+mod persistence;
 mod registry;
+mod rekeying;
+mod resumption;
 mod role;
+mod stats;
 
 /// List of trust policies to setup ABAC controls
 pub mod trust_policy;
@@ -21,21 +26,32 @@ pub mod trust_policy;
 pub use access_control::*;
 pub(crate) use addresses::*;
 pub use api::*;
+pub(crate) use capabilities::negotiate_capabilities;
+pub use capabilities::Capability;
 pub(crate) use handshake::*;
 pub(crate) use listener::*;
 pub use local_info::*;
 pub use message::*;
 pub use options::*;
+pub(crate) use persistence::*;
 pub use registry::*;
+pub(crate) use rekeying::Rekeying;
+pub use rekeying::RekeyingPolicy;
+pub(crate) use resumption::{ReceivedTicket, RedeemedTicket, Resumption};
+pub use resumption::{ResumptionTickets, DEFAULT_RESUMPTION_TICKET_LIFETIME};
 pub(crate) use role::*;
+pub use stats::*;
 pub use trust_policy::*;
 
 #[cfg(test)]
 mod tests {
-    use crate::secure_channel::{decryptor::Decryptor, encryptor::Encryptor};
+    use crate::secure_channel::{
+        decryptor::Decryptor, encryptor::Encryptor, Rekeying, RekeyingPolicy,
+    };
     use ockam_core::compat::rand::RngCore;
+    use ockam_core::compat::sync::Arc;
     use ockam_core::Result;
-    use ockam_vault::{SoftwareVaultForSecureChannels, VaultForSecureChannels};
+    use ockam_vault::{SecretBufferHandle, SoftwareVaultForSecureChannels, VaultForSecureChannels};
     use rand::seq::SliceRandom;
     use rand::thread_rng;
 
@@ -134,10 +150,142 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_encrypt_decrypt_with_renegotiated_keys() -> Result<()> {
+        let vault1: Arc<dyn VaultForSecureChannels> =
+            SoftwareVaultForSecureChannels::create().await?;
+        let vault2: Arc<dyn VaultForSecureChannels> =
+            SoftwareVaultForSecureChannels::create().await?;
+        let (mut encryptor, mut decryptor) =
+            create_encryptor_decryptor_with_vaults(vault1.clone(), vault2.clone()).await?;
+
+        let (chaining_key1, chaining_key2) =
+            create_chaining_keys(vault1.clone(), vault2.clone()).await?;
+        let requester = Rekeying::new(
+            Some(RekeyingPolicy::new()),
+            true,
+            Some(chaining_key1),
+            vault1,
+        );
+        let responder = Rekeying::new(
+            Some(RekeyingPolicy::new()),
+            false,
+            Some(chaining_key2),
+            vault2,
+        );
+
+        // Messages encrypted before the end of the rekeying are still in flight
+        let mut in_flight: Vec<(Vec<u8>, Vec<u8>)> = Vec::new();
+        for n in 0..40 {
+            let msg = vec![n];
+            in_flight.push((msg.clone(), encryptor.encrypt(&msg).await?));
+        }
+
+        let public_key = requester.start().await?;
+        let decryption_key = responder.handle_request(&public_key).await?.unwrap();
+        decryptor.set_next_key(decryption_key).await?;
+
+        let (public_key, _) = responder.take_response().unwrap();
+        assert!(requester.handle_response(&public_key).await?.is_some());
+        encryptor
+            .set_next_key(requester.take_next_encryption_key().unwrap())
+            .await?;
+
+        // The renegotiated key is used from the next key renewal on
+        for n in 40..100 {
+            let msg = vec![n];
+            in_flight.push((msg.clone(), encryptor.encrypt(&msg).await?));
+        }
+
+        for (plaintext, ciphertext) in in_flight.iter() {
+            assert_eq!(plaintext, &decryptor.decrypt(ciphertext).await?);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_rekeying() -> Result<()> {
+        let vault1: Arc<dyn VaultForSecureChannels> =
+            SoftwareVaultForSecureChannels::create().await?;
+        let vault2: Arc<dyn VaultForSecureChannels> =
+            SoftwareVaultForSecureChannels::create().await?;
+
+        let (chaining_key1, chaining_key2) =
+            create_chaining_keys(vault1.clone(), vault2.clone()).await?;
+        let initiator = Rekeying::new(
+            Some(RekeyingPolicy::new()),
+            true,
+            Some(chaining_key1),
+            vault1,
+        );
+        let responder = Rekeying::new(
+            Some(RekeyingPolicy::new()),
+            false,
+            Some(chaining_key2),
+            vault2,
+        );
+
+        let initiator_public_key = initiator.start().await?;
+        let responder_public_key = responder.start().await?;
+
+        // The rekeying started by the initiator wins
+        assert!(initiator
+            .handle_request(&responder_public_key)
+            .await?
+            .is_none());
+        assert!(responder
+            .handle_request(&initiator_public_key)
+            .await?
+            .is_some());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rekeying_not_negotiated() -> Result<()> {
+        let vault1: Arc<dyn VaultForSecureChannels> =
+            SoftwareVaultForSecureChannels::create().await?;
+        let vault2: Arc<dyn VaultForSecureChannels> =
+            SoftwareVaultForSecureChannels::create().await?;
+        let (chaining_key1, _) = create_chaining_keys(vault1.clone(), vault2.clone()).await?;
+
+        let requester = Rekeying::new(
+            Some(RekeyingPolicy::new()),
+            true,
+            Some(chaining_key1),
+            vault1,
+        );
+        let responder = Rekeying::new(Some(RekeyingPolicy::new()), false, None, vault2);
+
+        // Without a chaining key the rekeying policy is not used and requests are ignored
+        assert!(responder.policy().is_none());
+        let public_key = requester.start().await?;
+        assert!(responder.handle_request(&public_key).await?.is_none());
+        assert!(responder.take_response().is_none());
+        Ok(())
+    }
+
+    async fn create_chaining_keys(
+        vault1: Arc<dyn VaultForSecureChannels>,
+        vault2: Arc<dyn VaultForSecureChannels>,
+    ) -> Result<(SecretBufferHandle, SecretBufferHandle)> {
+        let mut key = [0u8; 32];
+        thread_rng().fill_bytes(&mut key);
+        Ok((
+            vault1.import_secret_buffer(key.to_vec()).await?,
+            vault2.import_secret_buffer(key.to_vec()).await?,
+        ))
+    }
+
     async fn create_encryptor_decryptor() -> Result<(Encryptor, Decryptor)> {
         let vault1 = SoftwareVaultForSecureChannels::create().await?;
         let vault2 = SoftwareVaultForSecureChannels::create().await?;
+        create_encryptor_decryptor_with_vaults(vault1, vault2).await
+    }
 
+    async fn create_encryptor_decryptor_with_vaults(
+        vault1: Arc<dyn VaultForSecureChannels>,
+        vault2: Arc<dyn VaultForSecureChannels>,
+    ) -> Result<(Encryptor, Decryptor)> {
         let mut rng = thread_rng();
         let mut key = [0u8; 32];
         rng.fill_bytes(&mut key);
@@ -22,6 +22,15 @@ impl NonceTracker {
         }
     }
 
+    /// Create a tracker accepting nonces starting from the given one.
+    /// All the nonces before it are considered as already received
+    pub(crate) fn starting_at(nonce: u64) -> Self {
+        Self {
+            nonce_bitmap: !1,
+            current_nonce: nonce,
+        }
+    }
+
     /// Mark a nonce as received, reject all invalid nonce values
     pub(crate) fn mark(&self, nonce: u64) -> ockam_core::Result<NonceTracker> {
         let new_tracker = if nonce > self.current_nonce {
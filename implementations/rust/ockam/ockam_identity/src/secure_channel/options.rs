@@ -1,11 +1,15 @@
+use ockam_core::compat::collections::BTreeSet;
 use ockam_core::compat::sync::Arc;
 use ockam_core::compat::vec::Vec;
 use ockam_core::flow_control::{FlowControlId, FlowControlOutgoingAccessControl, FlowControls};
-use ockam_core::{Address, OutgoingAccessControl, Result};
+use ockam_core::{Address, AllOutgoingAccessControl, OutgoingAccessControl, Result};
 
 use crate::models::CredentialAndPurposeKey;
-use crate::secure_channel::Addresses;
-use crate::{TrustContext, TrustEveryonePolicy, TrustPolicy};
+use crate::secure_channel::{Addresses, RekeyingPolicy};
+use crate::{
+    AllTrustPolicy, Capability, PinnedIdentities, ResumptionTickets, SecureChannelRepository,
+    TrustContext, TrustEveryonePolicy, TrustPolicy,
+};
 
 use core::fmt;
 use core::fmt::Formatter;
@@ -30,6 +34,14 @@ pub struct SecureChannelOptions {
     pub(crate) timeout: Duration,
     pub(crate) min_credential_refresh_interval: Duration,
     pub(crate) credential_refresh_time_gap: Duration,
+    pub(crate) rekeying: Option<RekeyingPolicy>,
+    pub(crate) persistence: Option<Arc<dyn SecureChannelRepository>>,
+    pub(crate) idle_timeout: Option<Duration>,
+    pub(crate) max_lifetime: Option<Duration>,
+    pub(crate) pinned_identities: Option<PinnedIdentities>,
+    pub(crate) resumption_tickets: Option<Arc<ResumptionTickets>>,
+    pub(crate) capabilities: BTreeSet<Capability>,
+    pub(crate) decryptor_outgoing_access_control: Option<Arc<dyn OutgoingAccessControl>>,
 }
 
 impl fmt::Debug for SecureChannelOptions {
@@ -54,6 +66,14 @@ impl SecureChannelOptions {
             timeout: DEFAULT_TIMEOUT,
             min_credential_refresh_interval: DEFAULT_MIN_REFRESH_CREDENTIAL_INTERVAL,
             credential_refresh_time_gap: DEFAULT_REFRESH_CREDENTIAL_TIME_GAP,
+            rekeying: None,
+            persistence: None,
+            idle_timeout: None,
+            max_lifetime: None,
+            pinned_identities: None,
+            resumption_tickets: None,
+            capabilities: BTreeSet::new(),
+            decryptor_outgoing_access_control: None,
         }
     }
 
@@ -87,6 +107,12 @@ impl SecureChannelOptions {
         self
     }
 
+    /// Only accept a responder whose identifier is pinned, in addition to the trust policy
+    pub fn with_pinned_identities(mut self, pinned_identities: PinnedIdentities) -> Self {
+        self.pinned_identities = Some(pinned_identities);
+        self
+    }
+
     /// Freshly generated [`FlowControlId`]
     pub fn producer_flow_control_id(&self) -> FlowControlId {
         self.flow_control_id.clone()
@@ -109,9 +135,71 @@ impl SecureChannelOptions {
         self.min_credential_refresh_interval = min_credential_refresh_interval;
         self
     }
+
+    /// Renegotiate the channel keys according to the given [`RekeyingPolicy`]
+    pub fn with_rekeying(mut self, rekeying: RekeyingPolicy) -> Self {
+        self.rekeying = Some(rekeying);
+        self
+    }
+
+    /// Persist the state of the channel, so that it can be resumed after a restart with
+    /// [`SecureChannels::resume_secure_channel`](crate::SecureChannels::resume_secure_channel)
+    /// instead of running a new handshake.
+    ///
+    /// The channel keys are persisted by the vault. Messages sent by the other side while this
+    /// node is down are lost, and the channel can't be resumed if the other side sent more than
+    /// 32 messages in the meantime.
+    pub fn with_persistence(mut self, repository: Arc<dyn SecureChannelRepository>) -> Self {
+        self.persistence = Some(repository);
+        self
+    }
+
+    /// Close the channel when no message was exchanged during the given duration.
+    /// The other side is notified that the channel is closed
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = Some(idle_timeout);
+        self
+    }
+
+    /// Restrict the messages forwarded by the decryptor of the channel, in addition to the
+    /// flow control of the channel. For example, to only forward the messages of an identity
+    /// having some attributes
+    pub fn with_decryptor_outgoing_access_control(
+        mut self,
+        access_control: Arc<dyn OutgoingAccessControl>,
+    ) -> Self {
+        self.decryptor_outgoing_access_control = Some(access_control);
+        self
+    }
+
+    /// Close the channel once it has been open for the given duration, even if it is in use.
+    /// The other side is notified that the channel is closed
+    pub fn with_max_lifetime(mut self, max_lifetime: Duration) -> Self {
+        self.max_lifetime = Some(max_lifetime);
+        self
+    }
+
+    /// Present a resumption ticket received on a previous channel to the same route, if any,
+    /// to skip the exchange of identities, and store the tickets sent by the responder
+    pub fn with_resumption(mut self, resumption_tickets: Arc<ResumptionTickets>) -> Self {
+        self.resumption_tickets = Some(resumption_tickets);
+        self
+    }
+
+    /// Offer a capability to the responder. The capability is only used on the channel
+    /// if the responder supports it as well
+    pub fn with_capability(mut self, capability: Capability) -> Self {
+        self.capabilities.insert(capability);
+        self
+    }
 }
 
 impl SecureChannelOptions {
+    /// Trust policy checked during the handshake, restricted to the pinned identities if any
+    pub(crate) fn trust_policy(&self) -> Arc<dyn TrustPolicy> {
+        trust_policy(&self.trust_policy, &self.pinned_identities)
+    }
+
     pub(crate) fn setup_flow_control(
         &self,
         flow_controls: &FlowControls,
@@ -147,7 +235,10 @@ impl SecureChannelOptions {
         );
 
         SecureChannelAccessControl {
-            decryptor_outgoing_access_control: Arc::new(ac),
+            decryptor_outgoing_access_control: decryptor_outgoing_access_control(
+                ac,
+                &self.decryptor_outgoing_access_control,
+            ),
         }
     }
 }
@@ -161,6 +252,14 @@ pub struct SecureChannelListenerOptions {
     pub(crate) credentials: Vec<CredentialAndPurposeKey>,
     pub(crate) min_credential_refresh_interval: Duration,
     pub(crate) refresh_credential_time_gap: Duration,
+    pub(crate) rekeying: Option<RekeyingPolicy>,
+    pub(crate) persistence: Option<Arc<dyn SecureChannelRepository>>,
+    pub(crate) idle_timeout: Option<Duration>,
+    pub(crate) max_lifetime: Option<Duration>,
+    pub(crate) pinned_identities: Option<PinnedIdentities>,
+    pub(crate) resumption_tickets: Option<Arc<ResumptionTickets>>,
+    pub(crate) capabilities: BTreeSet<Capability>,
+    pub(crate) decryptor_outgoing_access_control: Option<Arc<dyn OutgoingAccessControl>>,
 }
 
 impl fmt::Debug for SecureChannelListenerOptions {
@@ -183,6 +282,14 @@ impl SecureChannelListenerOptions {
             credentials: vec![],
             min_credential_refresh_interval: DEFAULT_MIN_REFRESH_CREDENTIAL_INTERVAL,
             refresh_credential_time_gap: DEFAULT_REFRESH_CREDENTIAL_TIME_GAP,
+            rekeying: None,
+            persistence: None,
+            idle_timeout: None,
+            max_lifetime: None,
+            pinned_identities: None,
+            resumption_tickets: None,
+            capabilities: BTreeSet::new(),
+            decryptor_outgoing_access_control: None,
         }
     }
 
@@ -219,6 +326,12 @@ impl SecureChannelListenerOptions {
         self
     }
 
+    /// Only accept initiators whose identifier is pinned, in addition to the trust policy
+    pub fn with_pinned_identities(mut self, pinned_identities: PinnedIdentities) -> Self {
+        self.pinned_identities = Some(pinned_identities);
+        self
+    }
+
     /// Freshly generated [`FlowControlId`]
     pub fn spawner_flow_control_id(&self) -> FlowControlId {
         self.flow_control_id.clone()
@@ -241,9 +354,70 @@ impl SecureChannelListenerOptions {
         self.min_credential_refresh_interval = min_credential_refresh_interval;
         self
     }
+
+    /// Renegotiate the keys of spawned channels according to the given [`RekeyingPolicy`]
+    pub fn with_rekeying(mut self, rekeying: RekeyingPolicy) -> Self {
+        self.rekeying = Some(rekeying);
+        self
+    }
+
+    /// Persist the state of spawned channels, so that they can be resumed after a restart with
+    /// [`SecureChannels::resume_secure_channel`](crate::SecureChannels::resume_secure_channel)
+    /// instead of running a new handshake.
+    ///
+    /// The channel keys are persisted by the vault. Messages sent by the other side while this
+    /// node is down are lost, and the channel can't be resumed if the other side sent more than
+    /// 32 messages in the meantime.
+    pub fn with_persistence(mut self, repository: Arc<dyn SecureChannelRepository>) -> Self {
+        self.persistence = Some(repository);
+        self
+    }
+
+    /// Close spawned channels when no message was exchanged during the given duration.
+    /// The other side is notified that the channel is closed
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = Some(idle_timeout);
+        self
+    }
+
+    /// Restrict the messages forwarded by the decryptors of the spawned channels, in addition to the
+    /// flow control of the channels. For example, to only forward the messages of an identity
+    /// having some attributes
+    pub fn with_decryptor_outgoing_access_control(
+        mut self,
+        access_control: Arc<dyn OutgoingAccessControl>,
+    ) -> Self {
+        self.decryptor_outgoing_access_control = Some(access_control);
+        self
+    }
+
+    /// Close spawned channels once they have been open for the given duration, even if they are in use.
+    /// The other side is notified that the channel is closed
+    pub fn with_max_lifetime(mut self, max_lifetime: Duration) -> Self {
+        self.max_lifetime = Some(max_lifetime);
+        self
+    }
+
+    /// Send a resumption ticket to initiators once a channel is established,
+    /// and accept the tickets presented by initiators to re-establish a channel
+    pub fn with_resumption(mut self, resumption_tickets: Arc<ResumptionTickets>) -> Self {
+        self.resumption_tickets = Some(resumption_tickets);
+        self
+    }
+
+    /// Accept a capability when it is offered by an initiator
+    pub fn with_capability(mut self, capability: Capability) -> Self {
+        self.capabilities.insert(capability);
+        self
+    }
 }
 
 impl SecureChannelListenerOptions {
+    /// Trust policy checked during the handshake, restricted to the pinned identities if any
+    pub(crate) fn trust_policy(&self) -> Arc<dyn TrustPolicy> {
+        trust_policy(&self.trust_policy, &self.pinned_identities)
+    }
+
     pub(crate) fn setup_flow_control_for_listener(
         &self,
         flow_controls: &FlowControls,
@@ -276,6 +450,14 @@ impl SecureChannelListenerOptions {
             );
         }
 
+        // A persistent channel can be resumed by the other side via a new connection,
+        // so its decryptor also accepts messages from the listener producers
+        if self.persistence.is_some() {
+            for id in &self.consumer {
+                flow_controls.add_consumer(addresses.decryptor_remote.clone(), id);
+            }
+        }
+
         let flow_control_id = FlowControls::generate_flow_control_id();
         flow_controls.add_producer(
             addresses.decryptor_internal.clone(),
@@ -299,7 +481,38 @@ impl SecureChannelListenerOptions {
         );
 
         SecureChannelAccessControl {
-            decryptor_outgoing_access_control: Arc::new(ac),
+            decryptor_outgoing_access_control: decryptor_outgoing_access_control(
+                ac,
+                &self.decryptor_outgoing_access_control,
+            ),
         }
     }
 }
+
+/// Combine the flow control of a decryptor with an additional outgoing access control
+fn decryptor_outgoing_access_control(
+    flow_control_access_control: FlowControlOutgoingAccessControl,
+    access_control: &Option<Arc<dyn OutgoingAccessControl>>,
+) -> Arc<dyn OutgoingAccessControl> {
+    match access_control {
+        Some(access_control) => Arc::new(AllOutgoingAccessControl::new(vec![
+            Arc::new(flow_control_access_control),
+            access_control.clone(),
+        ])),
+        None => Arc::new(flow_control_access_control),
+    }
+}
+
+/// Combine a trust policy with a set of pinned identities
+fn trust_policy(
+    trust_policy: &Arc<dyn TrustPolicy>,
+    pinned_identities: &Option<PinnedIdentities>,
+) -> Arc<dyn TrustPolicy> {
+    match pinned_identities {
+        Some(pinned_identities) => Arc::new(AllTrustPolicy::new(
+            trust_policy.clone(),
+            pinned_identities.clone(),
+        )),
+        None => trust_policy.clone(),
+    }
+}
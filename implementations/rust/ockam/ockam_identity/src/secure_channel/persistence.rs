@@ -0,0 +1,142 @@
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::sync::{Arc, RwLock};
+use ockam_core::{async_trait, Address, OutgoingAccessControl, RelayMessage, Result, Route};
+use ockam_vault::{AeadSecretKeyHandle, VaultForSecureChannels};
+use tracing::debug;
+
+use crate::{PersistedSecureChannel, SecureChannelRepository};
+
+/// Persistence of the state of a secure channel, shared by its encryptor and its decryptor,
+/// so that the channel can be resumed after a restart.
+///
+/// The other side of the channel might reconnect via a different route after a restart,
+/// so the route to the other side is updated with the return route of the messages that
+/// we successfully decrypt.
+#[derive(Clone)]
+pub(crate) struct ChannelPersistence {
+    repository: Arc<dyn SecureChannelRepository>,
+    vault: Arc<dyn VaultForSecureChannels>,
+    encryptor: Address,
+    their_decryptor: Address,
+    remote_route: Arc<RwLock<Route>>,
+}
+
+impl ChannelPersistence {
+    pub(crate) fn new(
+        repository: Arc<dyn SecureChannelRepository>,
+        vault: Arc<dyn VaultForSecureChannels>,
+        encryptor: Address,
+        their_decryptor: Address,
+        remote_route: Route,
+    ) -> Self {
+        Self {
+            repository,
+            vault,
+            encryptor,
+            their_decryptor,
+            remote_route: Arc::new(RwLock::new(remote_route)),
+        }
+    }
+
+    /// Store a new secure channel and persist its keys in the vault
+    pub(crate) async fn persist(&self, secure_channel: &PersistedSecureChannel) -> Result<()> {
+        self.vault
+            .persist_aead_secret_key(&secure_channel.encryption_key)
+            .await?;
+        self.vault
+            .persist_aead_secret_key(&secure_channel.decryption_key)
+            .await?;
+        self.repository.put_secure_channel(secure_channel).await
+    }
+
+    /// Persist a new encryption key after a key renewal
+    pub(crate) async fn update_encryption_key(
+        &self,
+        key: &AeadSecretKeyHandle,
+        nonce: u64,
+    ) -> Result<()> {
+        self.vault.persist_aead_secret_key(key).await?;
+        self.repository
+            .update_encryption_key(&self.encryptor, key, nonce)
+            .await
+    }
+
+    /// Persist a new decryption key after a key renewal
+    pub(crate) async fn update_decryption_keys(
+        &self,
+        key: &AeadSecretKeyHandle,
+        previous_key: Option<&AeadSecretKeyHandle>,
+        number_of_rekeys: u64,
+    ) -> Result<()> {
+        self.vault.persist_aead_secret_key(key).await?;
+        self.repository
+            .update_decryption_keys(&self.encryptor, key, previous_key, number_of_rekeys)
+            .await
+    }
+
+    /// Return true if the channel is still persisted.
+    /// Once a channel has been closed, its state is deleted
+    pub(crate) async fn is_persisted(&self) -> Result<bool> {
+        Ok(self
+            .repository
+            .get_secure_channel(&self.encryptor)
+            .await?
+            .is_some())
+    }
+
+    /// Delete the state of a closed channel
+    pub(crate) async fn delete(&self) -> Result<()> {
+        self.repository.delete_secure_channel(&self.encryptor).await
+    }
+
+    /// Return the current route to the decryptor on the other side
+    pub(crate) fn remote_route(&self) -> Route {
+        self.remote_route.read().unwrap().clone()
+    }
+
+    /// Update the route to the decryptor on the other side, given the return route of a message
+    /// sent by its encryptor
+    pub(crate) fn update_remote_route(&self, mut return_route: Route) {
+        let route: Route = return_route
+            .modify()
+            .pop_back()
+            .append(self.their_decryptor.clone())
+            .into();
+
+        let mut remote_route = self.remote_route.write().unwrap();
+        if *remote_route != route {
+            debug!(
+                "the route to the other side of the secure channel {} is now {}",
+                self.encryptor, route
+            );
+            *remote_route = route;
+        }
+    }
+
+    /// Return an access control allowing the encryptor to send messages to the next hop
+    /// of the current route to the other side
+    pub(crate) fn outgoing_access_control(&self) -> Arc<dyn OutgoingAccessControl> {
+        Arc::new(AllowRemoteRouteNextHop {
+            remote_route: self.remote_route.clone(),
+        })
+    }
+}
+
+/// Outgoing access control allowing messages to the next hop of a route which can be updated
+struct AllowRemoteRouteNextHop {
+    remote_route: Arc<RwLock<Route>>,
+}
+
+#[async_trait]
+impl OutgoingAccessControl for AllowRemoteRouteNextHop {
+    async fn is_authorized(&self, relay_msg: &RelayMessage) -> Result<bool> {
+        let next_hop = self.remote_route.read().unwrap().next()?.clone();
+
+        // Check if next hop is equal to expected value. Further hops are not checked
+        if &next_hop != relay_msg.onward_route().next()? {
+            return ockam_core::deny();
+        }
+
+        ockam_core::allow()
+    }
+}
@@ -1,10 +1,13 @@
-use ockam_core::compat::collections::BTreeMap;
+use core::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use ockam_core::compat::collections::{BTreeMap, BTreeSet};
 use ockam_core::compat::sync::{Arc, RwLock};
 use ockam_core::compat::vec::Vec;
-use ockam_core::{Address, Result};
+#[cfg(feature = "std")]
+use ockam_core::env::get_env;
+use ockam_core::{Address, RelayMessage, Result};
 
 use crate::models::Identifier;
-use crate::IdentityError;
+use crate::{Capability, IdentityError, IdentitySecureChannelLocalInfo, SecureChannelStats};
 
 /// Known information about particular SecureChannel
 #[derive(Clone, Debug)]
@@ -17,6 +20,9 @@ pub struct SecureChannelRegistryEntry {
     my_id: Identifier,
     their_id: Identifier,
     their_decryptor_address: Address,
+    encryptor_internal_address: Option<Address>,
+    stats: Option<Arc<SecureChannelStats>>,
+    capabilities: BTreeSet<Capability>,
 }
 
 impl SecureChannelRegistryEntry {
@@ -41,9 +47,30 @@ impl SecureChannelRegistryEntry {
             my_id,
             their_id,
             their_decryptor_address,
+            encryptor_internal_address: None,
+            stats: None,
+            capabilities: BTreeSet::new(),
         }
     }
 
+    /// Set the address used to present new credentials on this channel
+    pub(crate) fn with_encryptor_internal_address(mut self, address: Address) -> Self {
+        self.encryptor_internal_address = Some(address);
+        self
+    }
+
+    /// Set the statistics of this channel
+    pub(crate) fn with_stats(mut self, stats: Arc<SecureChannelStats>) -> Self {
+        self.stats = Some(stats);
+        self
+    }
+
+    /// Set the capabilities negotiated for this channel
+    pub(crate) fn with_capabilities(mut self, capabilities: BTreeSet<Capability>) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
     /// Encryptor messaging address
     pub fn encryptor_messaging_address(&self) -> &Address {
         &self.encryptor_messaging_address
@@ -83,22 +110,84 @@ impl SecureChannelRegistryEntry {
     pub fn their_decryptor_address(&self) -> Address {
         self.their_decryptor_address.clone()
     }
+
+    /// Statistics about the messages exchanged on this channel
+    pub fn stats(&self) -> Option<&SecureChannelStats> {
+        self.stats.as_deref()
+    }
+
+    /// Capabilities negotiated with the other side during the handshake
+    pub fn capabilities(&self) -> &BTreeSet<Capability> {
+        &self.capabilities
+    }
+
+    /// Address used to present new credentials on this channel
+    pub(crate) fn encryptor_internal_address(&self) -> Option<&Address> {
+        self.encryptor_internal_address.as_ref()
+    }
 }
 
+/// Name of the environment variable setting the maximum number of secure channels of a node
+pub const OCKAM_MAX_SECURE_CHANNELS: &str = "OCKAM_MAX_SECURE_CHANNELS";
+
 /// Registry of all known Secure Channels
 #[derive(Clone, Default)]
 pub struct SecureChannelRegistry {
     // Encryptor address is used as a key
     registry: Arc<RwLock<BTreeMap<Address, SecureChannelRegistryEntry>>>,
+    /// Maximum number of secure channels, 0 if there is no limit
+    max_channels: Arc<AtomicUsize>,
+    /// Number of secure channels rejected because the maximum was reached
+    rejected_channels: Arc<AtomicU64>,
 }
 
 impl SecureChannelRegistry {
-    /// Create an empty registry
+    /// Create an empty registry.
+    /// The maximum number of secure channels is read from the `OCKAM_MAX_SECURE_CHANNELS`
+    /// environment variable
     pub fn new() -> Self {
-        Self {
-            registry: Default::default(),
+        let registry = Self::default();
+        #[cfg(feature = "std")]
+        if let Ok(Some(max_channels)) = get_env::<usize>(OCKAM_MAX_SECURE_CHANNELS) {
+            registry.set_max_channels(Some(max_channels));
+        }
+        registry
+    }
+}
+
+impl SecureChannelRegistry {
+    /// Set the maximum number of secure channels.
+    /// The handshakes which are in progress are not counted, so the number of channels can
+    /// slightly exceed the maximum when many channels are created at the same time
+    pub fn set_max_channels(&self, max_channels: Option<usize>) {
+        self.max_channels
+            .store(max_channels.unwrap_or(0), Ordering::Relaxed);
+    }
+
+    /// Maximum number of secure channels, if there is one
+    pub fn max_channels(&self) -> Option<usize> {
+        match self.max_channels.load(Ordering::Relaxed) {
+            0 => None,
+            max_channels => Some(max_channels),
         }
     }
+
+    /// Number of secure channels rejected because the maximum number of channels was reached
+    pub fn rejected_channels(&self) -> u64 {
+        self.rejected_channels.load(Ordering::Relaxed)
+    }
+
+    /// Return an error, and count the rejection, if a new secure channel can't be created
+    /// because the maximum number of secure channels is reached
+    pub fn check_channels_limit(&self) -> Result<()> {
+        if let Some(max_channels) = self.max_channels() {
+            if self.registry.read().unwrap().len() >= max_channels {
+                self.rejected_channels.fetch_add(1, Ordering::Relaxed);
+                return Err(IdentityError::MaxSecureChannelsReached)?;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl SecureChannelRegistry {
@@ -142,6 +231,20 @@ impl SecureChannelRegistry {
             .cloned()
     }
 
+    /// Get the [`Identifier`] of the identity on the other side of the SecureChannel used by a
+    /// message: the SecureChannel encrypting the message when it is sent to an encryptor, or
+    /// the SecureChannel which decrypted the message otherwise
+    pub fn get_peer_identifier(&self, relay_msg: &RelayMessage) -> Option<Identifier> {
+        if let Ok(next) = relay_msg.onward_route().next() {
+            if let Some(entry) = self.get_channel_by_encryptor_address(next) {
+                return Some(entry.their_id().clone());
+            }
+        }
+        IdentitySecureChannelLocalInfo::find_info(relay_msg.local_message())
+            .ok()
+            .map(|info| info.their_identity_id())
+    }
+
     /// Get SecureChannel with given decryptor messaging address
     pub fn get_channel_by_decryptor_address(
         &self,
@@ -0,0 +1,291 @@
+use core::time::Duration;
+use ockam_core::compat::sync::{Arc, Mutex};
+use ockam_core::Result;
+use ockam_vault::{
+    AeadSecretKeyHandle, HKDFNumberOfOutputs, SecretBufferHandle, VaultForSecureChannels,
+    X25519PublicKey, X25519SecretKeyHandle,
+};
+use tracing::{debug, warn};
+
+use crate::IdentityError;
+
+/// Policy specifying when the keys of an established secure channel must be renegotiated.
+///
+/// A new pair of ephemeral keys is exchanged with the other side of the channel as soon as one of
+/// the limits is reached. The new encryption keys are used once both sides have
+/// derived them, and messages encrypted with the previous keys can still be decrypted, so that
+/// in-flight messages are not lost.
+///
+/// The keys are only renegotiated if both sides of the channel negotiated the
+/// [`Capability::REKEYING`](crate::Capability::REKEYING) capability during the handshake.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RekeyingPolicy {
+    pub(crate) max_messages: Option<u64>,
+    pub(crate) max_bytes: Option<u64>,
+    pub(crate) max_duration: Option<Duration>,
+}
+
+impl RekeyingPolicy {
+    /// Create a policy without any limit
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Renegotiate the keys after a number of encrypted messages
+    pub fn with_max_messages(mut self, max_messages: u64) -> Self {
+        self.max_messages = Some(max_messages);
+        self
+    }
+
+    /// Renegotiate the keys after a number of encrypted bytes
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Renegotiate the keys periodically
+    pub fn with_max_duration(mut self, max_duration: Duration) -> Self {
+        self.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Return true if the number of messages or bytes encrypted with the current keys
+    /// requires new keys
+    pub(crate) fn is_exceeded(&self, messages: u64, bytes: u64) -> bool {
+        self.max_messages
+            .map(|max| messages >= max)
+            .unwrap_or(false)
+            || self.max_bytes.map(|max| bytes >= max).unwrap_or(false)
+    }
+}
+
+#[derive(Default)]
+struct RekeyingState {
+    /// Chaining key mixed with each new Diffie-Hellman secret, so that the new keys also depend
+    /// on all the previous ones
+    chaining_key: Option<SecretBufferHandle>,
+    /// Our ephemeral secret key for a rekeying that we started
+    ephemeral_secret: Option<X25519SecretKeyHandle>,
+    /// New encryption key, used by the encryptor at its next key renewal
+    next_encryption_key: Option<AeadSecretKeyHandle>,
+    /// Response to a rekeying started by the other side and the encryption key
+    /// to use once that response has been sent
+    response: Option<(X25519PublicKey, AeadSecretKeyHandle)>,
+}
+
+/// State of the rekeying, shared by the encryptor and the decryptor of a secure channel.
+///
+/// The rekeying is disabled when there is no chaining key, i.e. when the
+/// rekeying capability was not negotiated with the other side
+#[derive(Clone)]
+pub(crate) struct Rekeying {
+    policy: Option<RekeyingPolicy>,
+    is_initiator: bool,
+    is_enabled: bool,
+    vault: Arc<dyn VaultForSecureChannels>,
+    state: Arc<Mutex<RekeyingState>>,
+}
+
+impl Rekeying {
+    pub(crate) fn new(
+        policy: Option<RekeyingPolicy>,
+        is_initiator: bool,
+        chaining_key: Option<SecretBufferHandle>,
+        vault: Arc<dyn VaultForSecureChannels>,
+    ) -> Self {
+        Self {
+            policy,
+            is_initiator,
+            is_enabled: chaining_key.is_some(),
+            vault,
+            state: Arc::new(Mutex::new(RekeyingState {
+                chaining_key,
+                ..Default::default()
+            })),
+        }
+    }
+
+    /// Return the rekeying policy of this side of the channel, if there is one
+    /// and if the rekeying is supported by both sides
+    pub(crate) fn policy(&self) -> Option<&RekeyingPolicy> {
+        if self.is_enabled {
+            self.policy.as_ref()
+        } else {
+            None
+        }
+    }
+
+    /// Start a rekeying and return the ephemeral public key to send to the other side.
+    /// If a previous rekeying was not answered, it is abandoned
+    pub(crate) async fn start(&self) -> Result<X25519PublicKey> {
+        let secret = self.vault.generate_ephemeral_x25519_secret_key().await?;
+        let public_key = self.vault.get_x25519_public_key(&secret).await?;
+
+        let previous = self.state.lock().unwrap().ephemeral_secret.replace(secret);
+        if let Some(previous) = previous {
+            self.vault
+                .delete_ephemeral_x25519_secret_key(previous)
+                .await?;
+        }
+        Ok(public_key)
+    }
+
+    /// Handle a rekeying started by the other side.
+    /// Return the new decryption key, or None if the request is ignored because
+    /// both sides started a rekeying at the same time. In that case the rekeying started by
+    /// the initiator of the channel wins.
+    pub(crate) async fn handle_request(
+        &self,
+        peer_public_key: &X25519PublicKey,
+    ) -> Result<Option<AeadSecretKeyHandle>> {
+        if !self.is_enabled {
+            warn!("ignoring a rekeying request since rekeying was not negotiated");
+            return Ok(None);
+        }
+        let abandoned = {
+            let mut state = self.state.lock().unwrap();
+            if state.ephemeral_secret.is_some() && self.is_initiator {
+                debug!("ignoring a concurrent rekeying started by the responder");
+                return Ok(None);
+            }
+            state.ephemeral_secret.take()
+        };
+        if let Some(abandoned) = abandoned {
+            self.vault
+                .delete_ephemeral_x25519_secret_key(abandoned)
+                .await?;
+        }
+
+        let secret = self.vault.generate_ephemeral_x25519_secret_key().await?;
+        let public_key = self.vault.get_x25519_public_key(&secret).await?;
+        let (requester_key, responder_key) = self.derive_keys(&secret, peer_public_key).await?;
+        self.vault
+            .delete_ephemeral_x25519_secret_key(secret)
+            .await?;
+
+        let previous = self
+            .state
+            .lock()
+            .unwrap()
+            .response
+            .replace((public_key, responder_key));
+        if let Some((_, previous_key)) = previous {
+            self.vault.delete_aead_secret_key(previous_key).await?;
+        }
+        Ok(Some(requester_key))
+    }
+
+    /// Handle the response to a rekeying that we started.
+    /// Return the new decryption key, or None if there was no rekeying in progress
+    pub(crate) async fn handle_response(
+        &self,
+        peer_public_key: &X25519PublicKey,
+    ) -> Result<Option<AeadSecretKeyHandle>> {
+        let secret = self.state.lock().unwrap().ephemeral_secret.take();
+        let secret = match secret {
+            Some(secret) => secret,
+            None => {
+                debug!("ignoring a rekeying response since no rekeying is in progress");
+                return Ok(None);
+            }
+        };
+
+        let (requester_key, responder_key) = self.derive_keys(&secret, peer_public_key).await?;
+        self.vault
+            .delete_ephemeral_x25519_secret_key(secret)
+            .await?;
+
+        let previous = self
+            .state
+            .lock()
+            .unwrap()
+            .next_encryption_key
+            .replace(requester_key);
+        if let Some(previous) = previous {
+            self.vault.delete_aead_secret_key(previous).await?;
+        }
+        Ok(Some(responder_key))
+    }
+
+    /// Take the response to send to the other side, along with the encryption key
+    /// to use after the response has been sent
+    pub(crate) fn take_response(&self) -> Option<(X25519PublicKey, AeadSecretKeyHandle)> {
+        self.state.lock().unwrap().response.take()
+    }
+
+    /// Take the new encryption key, if a rekeying we started has been completed
+    pub(crate) fn take_next_encryption_key(&self) -> Option<AeadSecretKeyHandle> {
+        self.state.lock().unwrap().next_encryption_key.take()
+    }
+
+    /// Remove the pending keys
+    pub(crate) async fn shutdown(&self) -> Result<()> {
+        let state = core::mem::take(&mut *self.state.lock().unwrap());
+        if let Some(chaining_key) = state.chaining_key {
+            self.vault.delete_secret_buffer(chaining_key).await?;
+        }
+        if let Some(secret) = state.ephemeral_secret {
+            self.vault
+                .delete_ephemeral_x25519_secret_key(secret)
+                .await?;
+        }
+        if let Some(key) = state.next_encryption_key {
+            self.vault.delete_aead_secret_key(key).await?;
+        }
+        if let Some((_, key)) = state.response {
+            self.vault.delete_aead_secret_key(key).await?;
+        }
+        Ok(())
+    }
+
+    /// Derive the new keys from a Diffie-Hellman exchange:
+    /// ck, k1, k2 = HKDF(ck, DH(e, re), 3).
+    /// The first key encrypts the messages sent by the side which started the rekeying,
+    /// the second one encrypts the messages sent by the other side
+    async fn derive_keys(
+        &self,
+        secret: &X25519SecretKeyHandle,
+        peer_public_key: &X25519PublicKey,
+    ) -> Result<(AeadSecretKeyHandle, AeadSecretKeyHandle)> {
+        // The chaining key is only replaced once the new keys are derived, so that a failed
+        // rekeying can be attempted again
+        let chaining_key = self
+            .state
+            .lock()
+            .unwrap()
+            .chaining_key
+            .clone()
+            .ok_or(IdentityError::InvalidKeyData)?;
+        let dh = self.vault.x25519_ecdh(secret, peer_public_key).await?;
+        let hkdf_output = self
+            .vault
+            .hkdf(&chaining_key, Some(&dh), HKDFNumberOfOutputs::Three)
+            .await;
+        self.vault.delete_secret_buffer(dh).await?;
+
+        let [new_chaining_key, requester_key, responder_key]: [SecretBufferHandle; 3] =
+            hkdf_output?
+                .0
+                 .0
+                .try_into()
+                .map_err(|_| IdentityError::InvalidKeyData)?;
+        let previous = self
+            .state
+            .lock()
+            .unwrap()
+            .chaining_key
+            .replace(new_chaining_key);
+        if let Some(previous) = previous {
+            self.vault.delete_secret_buffer(previous).await?;
+        }
+        let requester_key = self
+            .vault
+            .convert_secret_buffer_to_aead_key(requester_key)
+            .await?;
+        let responder_key = self
+            .vault
+            .convert_secret_buffer_to_aead_key(responder_key)
+            .await?;
+        Ok((requester_key, responder_key))
+    }
+}
@@ -0,0 +1,293 @@
+use core::time::Duration;
+use ockam_core::compat::collections::{BTreeMap, BTreeSet};
+use ockam_core::compat::rand::{thread_rng, RngCore};
+use ockam_core::compat::string::{String, ToString};
+use ockam_core::compat::sync::{Arc, Mutex};
+use ockam_core::compat::vec::Vec;
+use ockam_core::{Result, Route};
+use tracing::debug;
+
+use crate::models::Identifier;
+use crate::utils::{add_seconds, now};
+use crate::{Capability, ResumptionTicketMessage, TimestampInSeconds};
+
+/// Default time during which a resumption ticket can be used
+pub const DEFAULT_RESUMPTION_TICKET_LIFETIME: Duration = Duration::from_secs(10 * 60);
+
+/// Length of the secret shared with a resumption ticket
+const RESUMPTION_SECRET_LENGTH: usize = 32;
+
+/// Length of the identifier of a resumption ticket
+const RESUMPTION_TICKET_ID_LENGTH: usize = 16;
+
+/// Resumption tickets allowing an initiator to re-establish a secure channel with a responder
+/// without a full handshake.
+///
+/// Once a channel is established, a responder using resumption sends a ticket to the initiator.
+/// The ticket contains a fresh secret and can be presented, once, before it expires, to create a
+/// new channel to the same responder. The keys of that channel are derived from the ticket secret
+/// and from new ephemeral keys, and the channel is established as soon as the responder answered
+/// the first handshake message, without exchanging and verifying identities again.
+///
+/// Tickets are single-use, so that a replayed handshake message is rejected. If a ticket is
+/// rejected, because it expired or the responder was restarted, a full handshake is performed.
+///
+/// The tickets and their secrets are only kept in memory.
+pub struct ResumptionTickets {
+    lifetime: Duration,
+    issued: Mutex<BTreeMap<Vec<u8>, IssuedTicket>>,
+    received: Mutex<BTreeMap<String, ReceivedTicket>>,
+}
+
+/// Ticket issued by a responder
+struct IssuedTicket {
+    issuer: Identifier,
+    their_identifier: Identifier,
+    secret: Vec<u8>,
+    capabilities: BTreeSet<Capability>,
+    expires_at: TimestampInSeconds,
+}
+
+/// Ticket received by an initiator
+#[derive(Clone)]
+pub(crate) struct ReceivedTicket {
+    pub(crate) ticket_id: Vec<u8>,
+    pub(crate) secret: Vec<u8>,
+    pub(crate) their_identifier: Identifier,
+    pub(crate) capabilities: BTreeSet<Capability>,
+    expires_at: TimestampInSeconds,
+}
+
+/// Ticket redeemed by a responder
+pub(crate) struct RedeemedTicket {
+    pub(crate) their_identifier: Identifier,
+    pub(crate) secret: Vec<u8>,
+    pub(crate) capabilities: BTreeSet<Capability>,
+}
+
+impl Default for ResumptionTickets {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResumptionTickets {
+    /// Create an empty set of tickets with the default lifetime
+    pub fn new() -> Self {
+        Self {
+            lifetime: DEFAULT_RESUMPTION_TICKET_LIFETIME,
+            issued: Default::default(),
+            received: Default::default(),
+        }
+    }
+
+    /// Set the lifetime of the tickets issued by a responder
+    pub fn with_lifetime(mut self, lifetime: Duration) -> Self {
+        self.lifetime = lifetime;
+        self
+    }
+
+    /// Return true if there is a valid ticket to resume a channel to the given route
+    pub fn has_ticket(&self, route: &Route) -> bool {
+        let now = match now() {
+            Ok(now) => now,
+            Err(_) => return false,
+        };
+        self.received
+            .lock()
+            .unwrap()
+            .get(&route.to_string())
+            .map(|ticket| now < ticket.expires_at)
+            .unwrap_or(false)
+    }
+
+    /// Return the number of tickets issued by a responder which have not been redeemed yet
+    pub fn issued_tickets_count(&self) -> usize {
+        self.issued.lock().unwrap().len()
+    }
+
+    /// Issue a new ticket for a channel between the issuer and the initiator `their_identifier`.
+    /// The capabilities negotiated for that channel are used by the resumed channels
+    pub(crate) fn issue(
+        &self,
+        issuer: &Identifier,
+        their_identifier: &Identifier,
+        capabilities: &BTreeSet<Capability>,
+    ) -> Result<ResumptionTicketMessage> {
+        let now = now()?;
+        let expires_at = add_seconds(&now, self.lifetime.as_secs());
+
+        let mut ticket_id = vec![0u8; RESUMPTION_TICKET_ID_LENGTH];
+        thread_rng().fill_bytes(&mut ticket_id);
+        let mut secret = vec![0u8; RESUMPTION_SECRET_LENGTH];
+        thread_rng().fill_bytes(&mut secret);
+
+        let mut issued = self.issued.lock().unwrap();
+        issued.retain(|_, ticket| now < ticket.expires_at);
+        issued.insert(
+            ticket_id.clone(),
+            IssuedTicket {
+                issuer: issuer.clone(),
+                their_identifier: their_identifier.clone(),
+                secret: secret.clone(),
+                capabilities: capabilities.clone(),
+                expires_at,
+            },
+        );
+
+        Ok(ResumptionTicketMessage {
+            ticket_id,
+            secret,
+            expires_at,
+            capabilities: Some(capabilities.clone()),
+        })
+    }
+
+    /// Remove a ticket issued by `issuer` and return it if it is still valid
+    pub(crate) fn redeem(
+        &self,
+        ticket_id: &[u8],
+        issuer: &Identifier,
+    ) -> Result<Option<RedeemedTicket>> {
+        let now = now()?;
+        let ticket = self.issued.lock().unwrap().remove(ticket_id);
+        match ticket {
+            Some(ticket) if &ticket.issuer == issuer && now < ticket.expires_at => {
+                Ok(Some(RedeemedTicket {
+                    their_identifier: ticket.their_identifier,
+                    secret: ticket.secret,
+                    capabilities: ticket.capabilities,
+                }))
+            }
+            _ => {
+                debug!(
+                    "the resumption ticket {} is not valid",
+                    hex::encode(ticket_id)
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    /// Store a ticket received from the responder at the end of `route`
+    pub(crate) fn save(
+        &self,
+        route: &str,
+        their_identifier: &Identifier,
+        ticket: ResumptionTicketMessage,
+    ) {
+        self.received.lock().unwrap().insert(
+            route.to_string(),
+            ReceivedTicket {
+                ticket_id: ticket.ticket_id,
+                secret: ticket.secret,
+                their_identifier: their_identifier.clone(),
+                capabilities: ticket.capabilities.unwrap_or_default(),
+                expires_at: ticket.expires_at,
+            },
+        );
+    }
+
+    /// Remove the ticket received for `route` and return it if it is still valid
+    pub(crate) fn take(&self, route: &str) -> Result<Option<ReceivedTicket>> {
+        let now = now()?;
+        let ticket = self.received.lock().unwrap().remove(route);
+        Ok(ticket.filter(|ticket| now < ticket.expires_at))
+    }
+}
+
+/// Resumption settings for one side of a secure channel
+#[derive(Clone)]
+pub(crate) enum Resumption {
+    /// The initiator presents and stores the tickets issued for the route to the responder
+    Initiator {
+        tickets: Arc<ResumptionTickets>,
+        route: String,
+    },
+    /// The responder issues and redeems tickets
+    Responder { tickets: Arc<ResumptionTickets> },
+}
+
+impl Resumption {
+    /// Return the ticket to present when initiating a channel, if there is one
+    pub(crate) fn take_ticket(&self) -> Result<Option<ReceivedTicket>> {
+        match self {
+            Resumption::Initiator { tickets, route } => tickets.take(route),
+            Resumption::Responder { .. } => Ok(None),
+        }
+    }
+
+    /// Store a ticket sent by the responder
+    pub(crate) fn save_ticket(
+        &self,
+        their_identifier: &Identifier,
+        ticket: ResumptionTicketMessage,
+    ) {
+        match self {
+            Resumption::Initiator { tickets, route } => {
+                tickets.save(route, their_identifier, ticket)
+            }
+            Resumption::Responder { .. } => {
+                debug!("ignoring a resumption ticket received by a responder")
+            }
+        }
+    }
+
+    /// Return the tickets issued by a responder
+    pub(crate) fn issued_tickets(&self) -> Option<Arc<ResumptionTickets>> {
+        match self {
+            Resumption::Initiator { .. } => None,
+            Resumption::Responder { tickets } => Some(tickets.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_core::route;
+
+    #[test]
+    fn test_tickets_are_single_use() -> Result<()> {
+        let responder = Identifier::try_from(
+            "Iabababababababababababababababababababababababababababababababab",
+        )?;
+        let initiator = Identifier::try_from(
+            "Icdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcd",
+        )?;
+
+        let tickets = ResumptionTickets::new();
+        let ticket = tickets.issue(&responder, &initiator, &BTreeSet::new())?;
+        let ticket_id = ticket.ticket_id.clone();
+        let secret = ticket.secret.clone();
+
+        // a ticket can only be redeemed by its issuer
+        assert!(tickets.redeem(&ticket_id, &initiator)?.is_none());
+
+        let ticket = tickets.issue(&responder, &initiator, &BTreeSet::new())?;
+        let redeemed = tickets.redeem(&ticket.ticket_id, &responder)?.unwrap();
+        assert_eq!(redeemed.their_identifier, initiator);
+        assert_ne!(redeemed.secret, secret);
+        assert!(tickets.redeem(&ticket.ticket_id, &responder)?.is_none());
+
+        tickets.save("route", &responder, ticket);
+        assert!(tickets.take("route")?.is_some());
+        assert!(tickets.take("route")?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_expired_tickets_are_rejected() -> Result<()> {
+        let responder = Identifier::try_from(
+            "Iabababababababababababababababababababababababababababababababab",
+        )?;
+        let tickets = ResumptionTickets::new().with_lifetime(Duration::from_secs(0));
+        let ticket = tickets.issue(&responder, &responder, &BTreeSet::new())?;
+        assert!(tickets.redeem(&ticket.ticket_id, &responder)?.is_none());
+
+        tickets.save("route", &responder, ticket);
+        assert!(!tickets.has_ticket(&route!["route"]));
+        assert!(tickets.take("route")?.is_none());
+        Ok(())
+    }
+}
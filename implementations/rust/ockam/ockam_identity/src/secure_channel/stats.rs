@@ -0,0 +1,90 @@
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
+
+use crate::utils::now;
+use crate::TimestampInSeconds;
+
+/// Statistics about the messages exchanged on a secure channel.
+///
+/// They are shared by the encryptor and the decryptor of the channel and only count the payload
+/// messages, not the messages used to maintain the channel (credentials refresh, rekeying, etc...)
+#[derive(Debug)]
+pub struct SecureChannelStats {
+    created_at: TimestampInSeconds,
+    last_activity: AtomicU64,
+    messages_sent: AtomicU64,
+    bytes_sent: AtomicU64,
+    messages_received: AtomicU64,
+    bytes_received: AtomicU64,
+}
+
+impl SecureChannelStats {
+    pub(crate) fn new() -> Self {
+        let now = current_time();
+        Self {
+            created_at: TimestampInSeconds(now),
+            last_activity: AtomicU64::new(now),
+            messages_sent: AtomicU64::new(0),
+            bytes_sent: AtomicU64::new(0),
+            messages_received: AtomicU64::new(0),
+            bytes_received: AtomicU64::new(0),
+        }
+    }
+
+    /// Record a payload sent to the other side
+    pub(crate) fn record_sent(&self, bytes: usize) {
+        self.last_activity.store(current_time(), Ordering::Relaxed);
+        self.messages_sent.fetch_add(1, Ordering::Relaxed);
+        self.bytes_sent.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Record a payload received from the other side
+    pub(crate) fn record_received(&self, bytes: usize) {
+        self.last_activity.store(current_time(), Ordering::Relaxed);
+        self.messages_received.fetch_add(1, Ordering::Relaxed);
+        self.bytes_received
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Return the time elapsed since the last message
+    pub(crate) fn idle_duration(&self) -> Duration {
+        Duration::from_secs(
+            current_time().saturating_sub(self.last_activity.load(Ordering::Relaxed)),
+        )
+    }
+
+    /// Creation time of the channel
+    pub fn created_at(&self) -> TimestampInSeconds {
+        self.created_at
+    }
+
+    /// Time of the last message sent or received on the channel
+    pub fn last_activity(&self) -> TimestampInSeconds {
+        TimestampInSeconds(self.last_activity.load(Ordering::Relaxed))
+    }
+
+    /// Number of messages sent to the other side
+    pub fn messages_sent(&self) -> u64 {
+        self.messages_sent.load(Ordering::Relaxed)
+    }
+
+    /// Number of payload bytes sent to the other side
+    pub fn bytes_sent(&self) -> u64 {
+        self.bytes_sent.load(Ordering::Relaxed)
+    }
+
+    /// Number of messages received from the other side
+    pub fn messages_received(&self) -> u64 {
+        self.messages_received.load(Ordering::Relaxed)
+    }
+
+    /// Number of payload bytes received from the other side
+    pub fn bytes_received(&self) -> u64 {
+        self.bytes_received.load(Ordering::Relaxed)
+    }
+}
+
+/// The current time, or 0 if it is not available on this platform
+fn current_time() -> u64 {
+    now().map(|now| *now).unwrap_or(0)
+}
@@ -1,5 +1,6 @@
 mod all_trust_policy;
 mod any_trust_policy;
+mod pinned_identities_policy;
 mod trust_everyone_policy;
 mod trust_identifier_policy;
 mod trust_multi_identifier_policy;
@@ -7,6 +8,7 @@ mod trust_policy_type;
 
 pub use all_trust_policy::*;
 pub use any_trust_policy::*;
+pub use pinned_identities_policy::*;
 pub use trust_everyone_policy::*;
 pub use trust_identifier_policy::*;
 pub use trust_multi_identifier_policy::*;
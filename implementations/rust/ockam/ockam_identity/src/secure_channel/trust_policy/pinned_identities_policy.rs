@@ -0,0 +1,149 @@
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::collections::BTreeSet;
+use ockam_core::compat::sync::Arc;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{async_trait, Error, Result};
+use tracing::info;
+
+use crate::models::Identifier;
+use crate::trust_policy::{SecureChannelTrustInfo, TrustPolicy};
+
+/// Set of pinned identifiers: the only authorities or peers which are trusted by a node.
+///
+/// When used as a [`TrustPolicy`], or set with `with_pinned_identities` on the secure channel
+/// options, a secure channel can only be established with a peer whose identifier is pinned.
+///
+/// The identifiers can be loaded from a "known identities" file containing one identifier per
+/// line. Empty lines and the text following a `#` are ignored, so that each identifier can be
+/// annotated:
+///
+/// ```text
+/// # production authority
+/// I0f5e3a0c2a1bbe3cd8a26a1b8d6a1a5e2e3e6b7d0c8f9a2b3c4d5e6f7a8b9c0d
+/// I1b2c3d4e5f60718293a4b5c6d7e8f9011223344556677889900aabbccddeeff0 # edge node
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PinnedIdentities {
+    identifiers: Arc<BTreeSet<Identifier>>,
+}
+
+impl PinnedIdentities {
+    /// Create a set of pinned identifiers
+    pub fn new(identifiers: impl IntoIterator<Item = Identifier>) -> Self {
+        Self {
+            identifiers: Arc::new(identifiers.into_iter().collect()),
+        }
+    }
+
+    /// Parse the content of a known identities file
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut identifiers = BTreeSet::new();
+        for (index, line) in content.lines().enumerate() {
+            let line = match line.split_once('#') {
+                Some((identifier, _comment)) => identifier,
+                None => line,
+            }
+            .trim();
+            if line.is_empty() {
+                continue;
+            }
+            let identifier = Identifier::try_from(line).map_err(|e| {
+                Error::new(
+                    Origin::Identity,
+                    Kind::Invalid,
+                    format!("invalid identifier on line {}: {e}", index + 1),
+                )
+            })?;
+            identifiers.insert(identifier);
+        }
+        Ok(Self {
+            identifiers: Arc::new(identifiers),
+        })
+    }
+
+    /// Load a known identities file
+    #[cfg(feature = "std")]
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            Error::new(
+                Origin::Identity,
+                Kind::Io,
+                format!("cannot read the file {}: {e}", path.display()),
+            )
+        })?;
+        Self::parse(&content)
+    }
+
+    /// Return true if the identifier is pinned
+    pub fn contains(&self, identifier: &Identifier) -> bool {
+        self.identifiers.contains(identifier)
+    }
+
+    /// Pinned identifiers
+    pub fn identifiers(&self) -> impl Iterator<Item = &Identifier> {
+        self.identifiers.iter()
+    }
+
+    /// Return true if no identifier is pinned
+    pub fn is_empty(&self) -> bool {
+        self.identifiers.is_empty()
+    }
+}
+
+#[async_trait]
+impl TrustPolicy for PinnedIdentities {
+    async fn check(&self, trust_info: &SecureChannelTrustInfo) -> Result<bool> {
+        if self.contains(trust_info.their_identity_id()) {
+            Ok(true)
+        } else {
+            info!(
+                "{} is not one of the pinned identifiers",
+                trust_info.their_identity_id()
+            );
+            Ok(false)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_parse_known_identities() -> Result<()> {
+        let pinned = PinnedIdentities::parse(
+            r#"
+# authority
+Iabababababababababababababababababababababababababababababababab
+
+Icdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcd   # peer
+"#,
+        )?;
+        let authority = Identifier::try_from(
+            "Iabababababababababababababababababababababababababababababababab",
+        )?;
+        let peer = Identifier::try_from(
+            "Icdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcd",
+        )?;
+        let other = Identifier::try_from(
+            "Iefefefefefefefefefefefefefefefefefefefefefefefefefefefefefefefef",
+        )?;
+
+        assert_eq!(pinned.identifiers().count(), 2);
+        assert!(
+            pinned
+                .check(&SecureChannelTrustInfo::new(authority))
+                .await?
+        );
+        assert!(pinned.check(&SecureChannelTrustInfo::new(peer)).await?);
+        assert!(!pinned.check(&SecureChannelTrustInfo::new(other)).await?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_invalid_identifier() {
+        let result = PinnedIdentities::parse("# authority\nnot an identifier\n");
+        assert!(result.unwrap_err().to_string().contains("line 2"));
+    }
+}
@@ -3,6 +3,8 @@ use core::fmt::Formatter;
 use ockam_core::flow_control::FlowControlId;
 use ockam_core::Address;
 
+use crate::{AttributesEntry, SecureChannelRegistryEntry};
+
 /// Result of [`super::SecureChannels::create_secure_channel()`] call.
 #[derive(Debug, Clone)]
 pub struct SecureChannel {
@@ -92,3 +94,34 @@ impl SecureChannelListener {
         &self.flow_control_id
     }
 }
+
+/// Details about an established secure channel, returned by
+/// [`super::SecureChannels::get_secure_channel_details()`]
+#[derive(Debug, Clone)]
+pub struct SecureChannelDetails {
+    entry: SecureChannelRegistryEntry,
+    their_attributes: Option<AttributesEntry>,
+}
+
+impl SecureChannelDetails {
+    /// Constructor.
+    pub fn new(
+        entry: SecureChannelRegistryEntry,
+        their_attributes: Option<AttributesEntry>,
+    ) -> Self {
+        Self {
+            entry,
+            their_attributes,
+        }
+    }
+
+    /// Identifiers, addresses and statistics of the channel
+    pub fn entry(&self) -> &SecureChannelRegistryEntry {
+        &self.entry
+    }
+
+    /// Attributes of the other side, as presented in its credentials
+    pub fn their_attributes(&self) -> Option<&AttributesEntry> {
+        self.their_attributes.as_ref()
+    }
+}
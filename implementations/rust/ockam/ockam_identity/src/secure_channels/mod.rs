@@ -4,8 +4,12 @@ mod common;
 pub mod secure_channels;
 mod secure_channels_builder;
 mod secure_client;
+mod secure_client_policy;
+mod storage;
 
 pub use common::*;
 pub use secure_channels::*;
 pub use secure_channels_builder::*;
 pub use secure_client::*;
+pub use secure_client_policy::*;
+pub use storage::*;
@@ -1,19 +1,24 @@
+use ockam_core::compat::string::ToString;
 use ockam_core::compat::sync::Arc;
 use ockam_core::compat::vec::Vec;
-use ockam_core::Result;
+use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{Address, Route};
+use ockam_core::{Error, Result};
 use ockam_node::Context;
 
 use crate::identities::Identities;
 use crate::models::{CredentialAndPurposeKey, Identifier};
 use crate::secure_channel::handshake_worker::HandshakeWorker;
 use crate::secure_channel::{
-    Addresses, Role, SecureChannelListenerOptions, SecureChannelListenerWorker,
+    Addresses, Resumption, Role, SecureChannelListenerOptions, SecureChannelListenerWorker,
     SecureChannelOptions, SecureChannelRegistry,
 };
 #[cfg(feature = "storage")]
 use crate::SecureChannelsBuilder;
-use crate::{SecureChannel, SecureChannelListener, TrustContext, Vault};
+use crate::{
+    SecureChannel, SecureChannelDetails, SecureChannelListener, SecureChannelRegistryEntry,
+    SecureChannelRepository, TrustContext, Vault,
+};
 
 /// Identity implementation
 #[derive(Clone)]
@@ -116,6 +121,7 @@ impl SecureChannels {
         route: impl Into<Route>,
         options: impl Into<SecureChannelOptions>,
     ) -> Result<SecureChannel> {
+        self.secure_channel_registry.check_channels_limit()?;
         let addresses = Addresses::generate(Role::Initiator);
         let options = options.into();
         let flow_control_id = options.flow_control_id.clone();
@@ -147,12 +153,23 @@ impl SecureChannels {
             addresses.clone(),
             identifier.clone(),
             purpose_key,
-            options.trust_policy,
+            options.trust_policy(),
             access_control.decryptor_outgoing_access_control,
             credentials,
             options.min_credential_refresh_interval,
             options.credential_refresh_time_gap,
             options.trust_context,
+            options.rekeying,
+            options.persistence,
+            options.idle_timeout,
+            options.max_lifetime,
+            options.capabilities,
+            options
+                .resumption_tickets
+                .map(|tickets| Resumption::Initiator {
+                    tickets,
+                    route: route.to_string(),
+                }),
             Some(route),
             Some(options.timeout),
             Role::Initiator,
@@ -166,8 +183,133 @@ impl SecureChannels {
         ))
     }
 
+    /// Resume a persisted SecureChannel, given the address of its encryptor and a `Route` to
+    /// the node on the other side of the channel (which is empty if that node is the local node).
+    ///
+    /// The [`SecureChannelOptions`] must contain the repository where the channel was persisted.
+    /// The channel keeps its addresses and its keys, so the other side can keep using it
+    /// without running a new handshake.
+    pub async fn resume_secure_channel(
+        &self,
+        ctx: &Context,
+        encryptor: &Address,
+        route: impl Into<Route>,
+        options: impl Into<SecureChannelOptions>,
+    ) -> Result<SecureChannel> {
+        let options = options.into();
+        let flow_control_id = options.flow_control_id.clone();
+
+        let repository = options.persistence.clone().ok_or_else(|| {
+            Error::new(
+                Origin::Api,
+                Kind::Invalid,
+                "the secure channel options must specify a repository to resume a channel",
+            )
+        })?;
+        let persisted = repository
+            .get_secure_channel(encryptor)
+            .await?
+            .ok_or_else(|| {
+                Error::new(
+                    Origin::Api,
+                    Kind::NotFound,
+                    format!("no persisted secure channel found for {encryptor}"),
+                )
+            })?;
+
+        let addresses = Addresses {
+            decryptor_internal: persisted.decryptor_internal.clone(),
+            decryptor_remote: persisted.decryptor_remote.clone(),
+            decryptor_api: persisted.decryptor_api.clone(),
+            encryptor: persisted.encryptor.clone(),
+            encryptor_api: persisted.encryptor_api.clone(),
+            encryptor_internal: persisted.encryptor_internal.clone(),
+        };
+
+        let mut route: Route = route.into();
+        let remote_route: Route = route
+            .modify()
+            .append(persisted.their_decryptor.clone())
+            .into();
+
+        let next = remote_route.next()?;
+        options.setup_flow_control(ctx.flow_controls(), &addresses, next)?;
+        let access_control = options.create_access_control(ctx.flow_controls());
+
+        HandshakeWorker::resume(
+            ctx,
+            Arc::new(self.clone()),
+            addresses.clone(),
+            persisted,
+            repository,
+            access_control.decryptor_outgoing_access_control,
+            options.min_credential_refresh_interval,
+            options.credential_refresh_time_gap,
+            options.trust_context,
+            options.rekeying,
+            options.idle_timeout,
+            options.max_lifetime,
+            remote_route,
+        )
+        .await?;
+
+        Ok(SecureChannel::new(
+            addresses.encryptor,
+            addresses.encryptor_api,
+            flow_control_id,
+        ))
+    }
+
+    /// Return the details of a SecureChannel given its encryptor address:
+    /// identifiers, addresses, statistics and attributes of the other side
+    pub async fn get_secure_channel_details(
+        &self,
+        encryptor: &Address,
+    ) -> Result<Option<SecureChannelDetails>> {
+        match self
+            .secure_channel_registry
+            .get_channel_by_encryptor_address(encryptor)
+        {
+            Some(entry) => Ok(Some(self.make_secure_channel_details(entry).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Return the details of all the established SecureChannels
+    pub async fn list_secure_channel_details(&self) -> Result<Vec<SecureChannelDetails>> {
+        let mut details = vec![];
+        for entry in self.secure_channel_registry.get_channel_list() {
+            details.push(self.make_secure_channel_details(entry).await?);
+        }
+        Ok(details)
+    }
+
+    async fn make_secure_channel_details(
+        &self,
+        entry: SecureChannelRegistryEntry,
+    ) -> Result<SecureChannelDetails> {
+        let their_attributes = self
+            .identities
+            .identity_attributes_repository()
+            .get_attributes(entry.their_id())
+            .await?;
+        Ok(SecureChannelDetails::new(entry, their_attributes))
+    }
+
     /// Stop a SecureChannel given an encryptor address
     pub async fn stop_secure_channel(&self, ctx: &Context, channel: &Address) -> Result<()> {
         ctx.stop_worker(channel.clone()).await
     }
+
+    /// Stop a persisted SecureChannel given an encryptor address.
+    /// Its persisted state is deleted so that the channel can't be resumed anymore
+    pub async fn stop_persisted_secure_channel(
+        &self,
+        ctx: &Context,
+        channel: &Address,
+        repository: Arc<dyn SecureChannelRepository>,
+    ) -> Result<()> {
+        repository.delete_secure_channel(channel).await?;
+        ctx.stop_worker(channel.clone()).await
+    }
 }
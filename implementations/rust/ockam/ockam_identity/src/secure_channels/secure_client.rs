@@ -1,5 +1,9 @@
-use crate::{Identifier, SecureChannelOptions, TrustIdentifierPolicy};
+use crate::{
+    CircuitBreaker, Identifier, SecureChannelOptions, SecureClientRetryPolicy,
+    TrustIdentifierPolicy,
+};
 use minicbor::{Decode, Encode};
+use tracing::warn;
 
 use crate::{SecureChannel, SecureChannels};
 use ockam_core::api::Reply::Successful;
@@ -8,8 +12,7 @@ use ockam_core::compat::sync::Arc;
 use ockam_core::compat::time::Duration;
 use ockam_core::compat::vec::Vec;
 use ockam_core::{self, route, Result, Route};
-use ockam_node::api::Client;
-use ockam_node::Context;
+use ockam_node::{Context, MessageSendReceiveOptions};
 
 /// This client creates a secure channel to a node
 /// and can then send a typed request to that node (and receive a typed response)
@@ -24,6 +27,10 @@ use ockam_node::Context;
 ///  - the `secure_route` must start with the transport address of a worker connected to the requested node transport listener
 ///  - the requested node must have started the services named `api_service` in the `ask/tell` methods
 ///
+/// By default a request is attempted only once. Failed requests can be retried with a
+/// [`SecureClientRetryPolicy`] and a [`CircuitBreaker`] can be used to fail fast when the node
+/// can't be reached.
+///
 #[derive(Clone)]
 pub struct SecureClient {
     // secure_channels is used to create a secure channel before sending a request
@@ -36,6 +43,10 @@ pub struct SecureClient {
     client_identifier: Identifier,
     // default timeout to use for receiving a reply
     timeout: Duration,
+    // retries of the requests which could not reach the node
+    retry_policy: SecureClientRetryPolicy,
+    // optional circuit breaker, possibly shared with other clients
+    circuit_breaker: Option<Arc<CircuitBreaker>>,
 }
 
 impl SecureClient {
@@ -55,8 +66,22 @@ impl SecureClient {
             server_identifier: server_identifier.clone(),
             client_identifier: client_identifier.clone(),
             timeout,
+            retry_policy: SecureClientRetryPolicy::default(),
+            circuit_breaker: None,
         }
     }
+
+    /// Set the policy used to retry the requests which could not reach the node
+    pub fn with_retry_policy(mut self, retry_policy: SecureClientRetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Use a circuit breaker to fail fast after repeated failures to reach the node
+    pub fn with_circuit_breaker(mut self, circuit_breaker: Arc<CircuitBreaker>) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
 }
 
 impl SecureClient {
@@ -83,12 +108,29 @@ impl SecureClient {
         api_service: &str,
         req: Request<T>,
     ) -> Result<Reply<R>>
+    where
+        T: Encode<()>,
+        R: for<'a> Decode<'a, ()>,
+    {
+        self.ask_with_timeout(ctx, api_service, req, self.timeout)
+            .await
+    }
+
+    /// Send a request of type T and receive a reply of type R within a specific timeout
+    /// See `ask` for more information
+    pub async fn ask_with_timeout<T, R>(
+        &self,
+        ctx: &Context,
+        api_service: &str,
+        req: Request<T>,
+        timeout: Duration,
+    ) -> Result<Reply<R>>
     where
         T: Encode<()>,
         R: for<'a> Decode<'a, ()>,
     {
         let bytes: Vec<u8> = self
-            .request_with_timeout(ctx, api_service, req, self.timeout)
+            .request_with_timeout(ctx, api_service, req, timeout)
             .await?;
         Response::parse_response_reply::<R>(bytes.as_slice())
     }
@@ -101,12 +143,28 @@ impl SecureClient {
         api_service: &str,
         req: Request<T>,
     ) -> Result<Reply<()>>
+    where
+        T: Encode<()>,
+    {
+        self.tell_with_timeout(ctx, api_service, req, self.timeout)
+            .await
+    }
+
+    /// Send a request of type T and don't expect a reply, within a specific timeout
+    /// See `ask` for more information
+    pub async fn tell_with_timeout<T>(
+        &self,
+        ctx: &Context,
+        api_service: &str,
+        req: Request<T>,
+        timeout: Duration,
+    ) -> Result<Reply<()>>
     where
         T: Encode<()>,
     {
         let request_header = req.header().clone();
         let bytes = self
-            .request_with_timeout(ctx, api_service, req, self.timeout)
+            .request_with_timeout(ctx, api_service, req, timeout)
             .await?;
         let (response, decoder) = Response::parse_response_header(bytes.as_slice())?;
         if !response.is_ok() {
@@ -136,6 +194,9 @@ impl SecureClient {
 
     /// Send a request of type T and expect an untyped reply within a specific timeout
     /// See `ask` for more information
+    ///
+    /// The request is retried according to the retry policy if the node can't be reached
+    /// or doesn't answer within the timeout.
     pub async fn request_with_timeout<T>(
         &self,
         ctx: &Context,
@@ -146,10 +207,69 @@ impl SecureClient {
     where
         T: Encode<()>,
     {
-        let sc = self.create_secure_channel(ctx).await?;
+        // the request is encoded once so that it can be sent again for each attempt
+        let mut buf = Vec::new();
+        req.encode(&mut buf)?;
+
+        let mut retries = 0;
+        loop {
+            if let Some(circuit_breaker) = &self.circuit_breaker {
+                circuit_breaker.check()?;
+            }
+            let err = match self
+                .send_request(ctx, api_service, buf.clone(), timeout)
+                .await
+            {
+                Ok(response) => {
+                    if let Some(circuit_breaker) = &self.circuit_breaker {
+                        circuit_breaker.record_success();
+                    }
+                    return Ok(response);
+                }
+                Err(err) => err,
+            };
+
+            let mut circuit_open = false;
+            if let Some(circuit_breaker) = &self.circuit_breaker {
+                circuit_breaker.record_failure()?;
+                circuit_open = circuit_breaker.is_open();
+            }
+            if circuit_open || retries >= self.retry_policy.max_retries() {
+                return Err(err);
+            }
+
+            retries += 1;
+            let delay = self.retry_policy.backoff().delay(retries);
+            warn!(
+                "Cannot send a request to {} at {}, retrying in {}ms: {err}",
+                api_service,
+                &self.secure_route,
+                delay.as_millis()
+            );
+            ctx.sleep(delay).await;
+        }
+    }
+
+    /// Send an encoded request over a new secure channel and return the undecoded response
+    async fn send_request(
+        &self,
+        ctx: &Context,
+        api_service: &str,
+        request: Vec<u8>,
+        timeout: Duration,
+    ) -> Result<Vec<u8>> {
+        let sc = self
+            .create_secure_channel_with_timeout(ctx, timeout)
+            .await?;
         let route = route![sc.clone(), api_service];
-        let client = Client::new(&route, Some(timeout));
-        let response = client.request(ctx, req).await;
+        let response = ctx
+            .send_and_receive_extended::<Vec<u8>>(
+                route,
+                request,
+                MessageSendReceiveOptions::new().with_timeout(timeout),
+            )
+            .await
+            .map(|response| response.body());
         self.secure_channels
             .stop_secure_channel(ctx, sc.encryptor_address())
             .await?;
@@ -160,9 +280,19 @@ impl SecureClient {
 
     /// Create a secure channel to the node
     pub async fn create_secure_channel(&self, ctx: &Context) -> Result<SecureChannel> {
+        self.create_secure_channel_with_timeout(ctx, self.timeout)
+            .await
+    }
+
+    /// Create a secure channel to the node within a specific timeout
+    async fn create_secure_channel_with_timeout(
+        &self,
+        ctx: &Context,
+        timeout: Duration,
+    ) -> Result<SecureChannel> {
         let options = SecureChannelOptions::new()
             .with_trust_policy(TrustIdentifierPolicy::new(self.server_identifier.clone()))
-            .with_timeout(self.timeout);
+            .with_timeout(timeout);
         self.secure_channels
             .create_secure_channel(
                 ctx,
@@ -0,0 +1,178 @@
+use ockam_core::compat::sync::Mutex;
+use ockam_core::compat::time::Duration;
+use ockam_core::Result;
+
+use crate::utils::{add_seconds, now};
+use crate::{BackoffStrategy, IdentityError, TimestampInSeconds};
+
+/// Retry settings used by a [`SecureClient`](crate::SecureClient)
+///
+/// Only the requests failing because the node can't be reached, or doesn't answer in time,
+/// are retried. A request which receives an error response is not retried.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SecureClientRetryPolicy {
+    max_retries: u32,
+    backoff: BackoffStrategy,
+}
+
+impl Default for SecureClientRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            backoff: BackoffStrategy::Exponential {
+                initial_delay: Duration::from_millis(500),
+                max_delay: Duration::from_secs(10),
+                multiplier: 2,
+            },
+        }
+    }
+}
+
+impl SecureClientRetryPolicy {
+    /// Default policy: a single attempt
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of retries after the first failed attempt
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Strategy used to compute the delay between two attempts
+    pub fn with_backoff(mut self, backoff: BackoffStrategy) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Number of retries after the first failed attempt
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    /// Strategy used to compute the delay between two attempts
+    pub fn backoff(&self) -> &BackoffStrategy {
+        &self.backoff
+    }
+}
+
+/// Circuit breaker used by a [`SecureClient`](crate::SecureClient) to fail fast when a node
+/// can't be reached.
+///
+/// The circuit opens after `failure_threshold` consecutive failed requests. While it is open,
+/// requests fail immediately with [`IdentityError::CircuitBreakerOpen`]. Once `reset_timeout`
+/// has elapsed a single request is let through: the circuit closes again if it succeeds and
+/// stays open for another `reset_timeout` if it fails.
+///
+/// A circuit breaker can be shared by several clients sending requests to the same node.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    reset_timeout: Duration,
+    state: Mutex<CircuitBreakerState>,
+}
+
+#[derive(Debug, Default)]
+struct CircuitBreakerState {
+    consecutive_failures: u32,
+    opened_at: Option<TimestampInSeconds>,
+    trial_in_progress: bool,
+}
+
+impl CircuitBreaker {
+    /// Create a new, closed, circuit breaker
+    pub fn new(failure_threshold: u32, reset_timeout: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            reset_timeout,
+            state: Mutex::new(CircuitBreakerState::default()),
+        }
+    }
+
+    /// Return true if requests are currently rejected
+    pub fn is_open(&self) -> bool {
+        self.state.lock().unwrap().opened_at.is_some()
+    }
+
+    /// Number of consecutive failed requests
+    pub fn consecutive_failures(&self) -> u32 {
+        self.state.lock().unwrap().consecutive_failures
+    }
+
+    /// Return an error if a request must not be sent
+    pub(crate) fn check(&self) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let opened_at = match state.opened_at {
+            Some(opened_at) => opened_at,
+            None => return Ok(()),
+        };
+        if !state.trial_in_progress
+            && add_seconds(&opened_at, self.reset_timeout.as_secs()) <= now()?
+        {
+            state.trial_in_progress = true;
+            return Ok(());
+        }
+        Err(IdentityError::CircuitBreakerOpen)?
+    }
+
+    /// Close the circuit after a successful request
+    pub(crate) fn record_success(&self) {
+        *self.state.lock().unwrap() = CircuitBreakerState::default();
+    }
+
+    /// Count a failed request and open the circuit if there were too many of them
+    pub(crate) fn record_failure(&self) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+        state.trial_in_progress = false;
+        if state.opened_at.is_some() || state.consecutive_failures >= self.failure_threshold {
+            state.opened_at = Some(now()?);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_circuit_breaker() -> Result<()> {
+        let circuit_breaker = CircuitBreaker::new(2, Duration::from_secs(3600));
+        circuit_breaker.check()?;
+        circuit_breaker.record_failure()?;
+        circuit_breaker.check()?;
+        assert!(!circuit_breaker.is_open());
+
+        circuit_breaker.record_failure()?;
+        assert!(circuit_breaker.is_open());
+        assert!(circuit_breaker.check().is_err());
+
+        circuit_breaker.record_success();
+        assert!(!circuit_breaker.is_open());
+        assert_eq!(circuit_breaker.consecutive_failures(), 0);
+        circuit_breaker.check()
+    }
+
+    #[test]
+    fn test_circuit_breaker_lets_one_request_through_after_the_reset_timeout() -> Result<()> {
+        let circuit_breaker = CircuitBreaker::new(1, Duration::from_secs(0));
+        circuit_breaker.record_failure()?;
+        assert!(circuit_breaker.is_open());
+
+        // a single trial request is allowed
+        circuit_breaker.check()?;
+        assert!(circuit_breaker.check().is_err());
+
+        // the circuit is opened again if it fails
+        circuit_breaker.record_failure()?;
+        assert!(circuit_breaker.is_open());
+        circuit_breaker.check()?;
+
+        // and closed if it succeeds
+        circuit_breaker.record_success();
+        assert!(!circuit_breaker.is_open());
+        Ok(())
+    }
+}
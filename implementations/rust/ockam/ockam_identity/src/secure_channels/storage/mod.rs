@@ -0,0 +1,8 @@
+pub use secure_channel_repository::*;
+#[cfg(feature = "storage")]
+pub use secure_channel_repository_sql::*;
+
+mod secure_channel_repository;
+
+#[cfg(feature = "storage")]
+mod secure_channel_repository_sql;
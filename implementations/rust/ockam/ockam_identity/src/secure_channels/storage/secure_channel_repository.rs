@@ -0,0 +1,92 @@
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::vec::Vec;
+use ockam_core::{async_trait, Address, Result};
+use ockam_vault::AeadSecretKeyHandle;
+
+use crate::models::Identifier;
+
+/// This repository stores the state of persistent secure channels, so that they can be resumed
+/// after a restart without running a new handshake.
+///
+/// The channel keys themselves are kept in the vault, only their handles are stored here.
+#[async_trait]
+pub trait SecureChannelRepository: Send + Sync + 'static {
+    /// Store a secure channel, overwriting the existing one (if any)
+    async fn put_secure_channel(&self, secure_channel: &PersistedSecureChannel) -> Result<()>;
+
+    /// Retrieve a secure channel given the address of its encryptor
+    async fn get_secure_channel(
+        &self,
+        encryptor: &Address,
+    ) -> Result<Option<PersistedSecureChannel>>;
+
+    /// Retrieve all the persisted secure channels
+    async fn get_secure_channels(&self) -> Result<Vec<PersistedSecureChannel>>;
+
+    /// Update the encryption key of a secure channel after a key renewal
+    async fn update_encryption_key(
+        &self,
+        encryptor: &Address,
+        key: &AeadSecretKeyHandle,
+        nonce: u64,
+    ) -> Result<()>;
+
+    /// Update the decryption keys of a secure channel after a key renewal
+    async fn update_decryption_keys(
+        &self,
+        encryptor: &Address,
+        key: &AeadSecretKeyHandle,
+        previous_key: Option<&AeadSecretKeyHandle>,
+        number_of_rekeys: u64,
+    ) -> Result<()>;
+
+    /// Delete a secure channel
+    async fn delete_secure_channel(&self, encryptor: &Address) -> Result<()>;
+}
+
+/// State of a persistent secure channel
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PersistedSecureChannel {
+    pub(crate) is_initiator: bool,
+    pub(crate) my_identifier: Identifier,
+    pub(crate) their_identifier: Identifier,
+    pub(crate) encryptor: Address,
+    pub(crate) encryptor_api: Address,
+    pub(crate) encryptor_internal: Address,
+    pub(crate) decryptor_remote: Address,
+    pub(crate) decryptor_api: Address,
+    pub(crate) decryptor_internal: Address,
+    pub(crate) their_decryptor: Address,
+    pub(crate) encryption_key: AeadSecretKeyHandle,
+    pub(crate) encryption_nonce: u64,
+    pub(crate) decryption_key: AeadSecretKeyHandle,
+    pub(crate) previous_decryption_key: Option<AeadSecretKeyHandle>,
+    pub(crate) decryption_rekeys: u64,
+}
+
+impl PersistedSecureChannel {
+    /// Return true if this side of the channel initiated it
+    pub fn is_initiator(&self) -> bool {
+        self.is_initiator
+    }
+
+    /// Identifier of the identity on this side of the channel
+    pub fn my_identifier(&self) -> Identifier {
+        self.my_identifier.clone()
+    }
+
+    /// Identifier of the identity on the other side of the channel
+    pub fn their_identifier(&self) -> Identifier {
+        self.their_identifier.clone()
+    }
+
+    /// Address of the channel encryptor
+    pub fn encryptor(&self) -> Address {
+        self.encryptor.clone()
+    }
+
+    /// Address of the channel decryptor on the other side
+    pub fn their_decryptor(&self) -> Address {
+        self.their_decryptor.clone()
+    }
+}
@@ -0,0 +1,249 @@
+use core::str::FromStr;
+
+use sqlx::*;
+use tracing::debug;
+
+use ockam_core::async_trait;
+use ockam_core::compat::vec::Vec;
+use ockam_core::{Address, Result};
+use ockam_node::database::{FromSqlxError, SqlxDatabase, ToSqlxType, ToVoid};
+use ockam_vault::{AeadSecretKeyHandle, HandleToSecret};
+
+use crate::models::Identifier;
+use crate::{PersistedSecureChannel, SecureChannelRepository};
+
+/// Implementation of the `SecureChannelRepository` trait based on an underlying database
+/// using sqlx as its API, and Sqlite as its driver
+#[derive(Clone)]
+pub struct SecureChannelSqlxDatabase {
+    database: SqlxDatabase,
+}
+
+impl SecureChannelSqlxDatabase {
+    /// Create a new database for secure channels
+    pub fn new(database: SqlxDatabase) -> Self {
+        debug!("create a repository for secure channels");
+        Self { database }
+    }
+
+    /// Create a new in-memory database for secure channels
+    pub async fn create() -> Result<Self> {
+        Ok(Self::new(SqlxDatabase::in_memory("secure channels").await?))
+    }
+}
+
+#[async_trait]
+impl SecureChannelRepository for SecureChannelSqlxDatabase {
+    async fn put_secure_channel(&self, secure_channel: &PersistedSecureChannel) -> Result<()> {
+        let query = query(
+            "INSERT OR REPLACE INTO secure_channel VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(secure_channel.encryptor.to_sql())
+        .bind(secure_channel.encryptor_api.to_sql())
+        .bind(secure_channel.encryptor_internal.to_sql())
+        .bind(secure_channel.decryptor_remote.to_sql())
+        .bind(secure_channel.decryptor_api.to_sql())
+        .bind(secure_channel.decryptor_internal.to_sql())
+        .bind(role(secure_channel.is_initiator).to_sql())
+        .bind(secure_channel.my_identifier.to_sql())
+        .bind(secure_channel.their_identifier.to_sql())
+        .bind(secure_channel.their_decryptor.to_sql())
+        .bind(secure_channel.encryption_key.handle().to_sql())
+        .bind(secure_channel.encryption_nonce.to_sql())
+        .bind(secure_channel.decryption_key.handle().to_sql())
+        .bind(
+            secure_channel
+                .previous_decryption_key
+                .as_ref()
+                .map(|k| k.handle().to_sql()),
+        )
+        .bind(secure_channel.decryption_rekeys.to_sql());
+        query.execute(&*self.database.pool).await.void()
+    }
+
+    async fn get_secure_channel(
+        &self,
+        encryptor: &Address,
+    ) -> Result<Option<PersistedSecureChannel>> {
+        let query = query_as("SELECT * FROM secure_channel WHERE encryptor_address = ?")
+            .bind(encryptor.to_sql());
+        let row: Option<SecureChannelRow> = query
+            .fetch_optional(&*self.database.pool)
+            .await
+            .into_core()?;
+        row.map(|r| r.secure_channel()).transpose()
+    }
+
+    async fn get_secure_channels(&self) -> Result<Vec<PersistedSecureChannel>> {
+        let query = query_as("SELECT * FROM secure_channel");
+        let rows: Vec<SecureChannelRow> =
+            query.fetch_all(&*self.database.pool).await.into_core()?;
+        rows.iter().map(|r| r.secure_channel()).collect()
+    }
+
+    async fn update_encryption_key(
+        &self,
+        encryptor: &Address,
+        key: &AeadSecretKeyHandle,
+        nonce: u64,
+    ) -> Result<()> {
+        let query = query(
+            "UPDATE secure_channel SET encryption_key = ?, encryption_nonce = ? WHERE encryptor_address = ?",
+        )
+        .bind(key.handle().to_sql())
+        .bind(nonce.to_sql())
+        .bind(encryptor.to_sql());
+        query.execute(&*self.database.pool).await.void()
+    }
+
+    async fn update_decryption_keys(
+        &self,
+        encryptor: &Address,
+        key: &AeadSecretKeyHandle,
+        previous_key: Option<&AeadSecretKeyHandle>,
+        number_of_rekeys: u64,
+    ) -> Result<()> {
+        let query = query(
+            "UPDATE secure_channel SET decryption_key = ?, previous_decryption_key = ?, decryption_rekeys = ? WHERE encryptor_address = ?",
+        )
+        .bind(key.handle().to_sql())
+        .bind(previous_key.map(|k| k.handle().to_sql()))
+        .bind(number_of_rekeys.to_sql())
+        .bind(encryptor.to_sql());
+        query.execute(&*self.database.pool).await.void()
+    }
+
+    async fn delete_secure_channel(&self, encryptor: &Address) -> Result<()> {
+        let query = query("DELETE FROM secure_channel WHERE encryptor_address = ?")
+            .bind(encryptor.to_sql());
+        query.execute(&*self.database.pool).await.void()
+    }
+}
+
+const INITIATOR: &str = "initiator";
+const RESPONDER: &str = "responder";
+
+fn role(is_initiator: bool) -> &'static str {
+    if is_initiator {
+        INITIATOR
+    } else {
+        RESPONDER
+    }
+}
+
+// Database serialization / deserialization
+
+#[derive(FromRow)]
+struct SecureChannelRow {
+    encryptor_address: String,
+    encryptor_api_address: String,
+    encryptor_internal_address: String,
+    decryptor_remote_address: String,
+    decryptor_api_address: String,
+    decryptor_internal_address: String,
+    role: String,
+    my_identifier: String,
+    their_identifier: String,
+    their_decryptor_address: String,
+    encryption_key: Vec<u8>,
+    encryption_nonce: i64,
+    decryption_key: Vec<u8>,
+    previous_decryption_key: Option<Vec<u8>>,
+    decryption_rekeys: i64,
+}
+
+impl SecureChannelRow {
+    fn secure_channel(&self) -> Result<PersistedSecureChannel> {
+        Ok(PersistedSecureChannel {
+            is_initiator: self.role == INITIATOR,
+            my_identifier: Identifier::from_str(&self.my_identifier)?,
+            their_identifier: Identifier::from_str(&self.their_identifier)?,
+            encryptor: Address::from_string(&self.encryptor_address),
+            encryptor_api: Address::from_string(&self.encryptor_api_address),
+            encryptor_internal: Address::from_string(&self.encryptor_internal_address),
+            decryptor_remote: Address::from_string(&self.decryptor_remote_address),
+            decryptor_api: Address::from_string(&self.decryptor_api_address),
+            decryptor_internal: Address::from_string(&self.decryptor_internal_address),
+            their_decryptor: Address::from_string(&self.their_decryptor_address),
+            encryption_key: key_handle(&self.encryption_key),
+            encryption_nonce: self.encryption_nonce as u64,
+            decryption_key: key_handle(&self.decryption_key),
+            previous_decryption_key: self.previous_decryption_key.as_deref().map(key_handle),
+            decryption_rekeys: self.decryption_rekeys as u64,
+        })
+    }
+}
+
+fn key_handle(handle: &[u8]) -> AeadSecretKeyHandle {
+    AeadSecretKeyHandle::new(HandleToSecret::new(handle.to_vec()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identities;
+
+    #[tokio::test]
+    async fn test_secure_channel_repository() -> Result<()> {
+        let repository = SecureChannelSqlxDatabase::create().await?;
+        let identities = identities().await?;
+        let alice = identities.identities_creation().create_identity().await?;
+        let bob = identities.identities_creation().create_identity().await?;
+
+        let secure_channel = PersistedSecureChannel {
+            is_initiator: true,
+            my_identifier: alice,
+            their_identifier: bob,
+            encryptor: Address::random_local(),
+            encryptor_api: Address::random_local(),
+            encryptor_internal: Address::random_local(),
+            decryptor_remote: Address::random_local(),
+            decryptor_api: Address::random_local(),
+            decryptor_internal: Address::random_local(),
+            their_decryptor: Address::random_local(),
+            encryption_key: key_handle(&[1, 2, 3]),
+            encryption_nonce: 0,
+            decryption_key: key_handle(&[4, 5, 6]),
+            previous_decryption_key: None,
+            decryption_rekeys: 0,
+        };
+        repository.put_secure_channel(&secure_channel).await?;
+
+        let result = repository
+            .get_secure_channel(&secure_channel.encryptor)
+            .await?;
+        assert_eq!(result, Some(secure_channel.clone()));
+
+        // the keys are updated after each key renewal
+        repository
+            .update_encryption_key(&secure_channel.encryptor, &key_handle(&[7]), 32)
+            .await?;
+        repository
+            .update_decryption_keys(
+                &secure_channel.encryptor,
+                &key_handle(&[8]),
+                Some(&key_handle(&[4, 5, 6])),
+                1,
+            )
+            .await?;
+        let expected = PersistedSecureChannel {
+            encryption_key: key_handle(&[7]),
+            encryption_nonce: 32,
+            decryption_key: key_handle(&[8]),
+            previous_decryption_key: Some(key_handle(&[4, 5, 6])),
+            decryption_rekeys: 1,
+            ..secure_channel.clone()
+        };
+        let result = repository.get_secure_channels().await?;
+        assert_eq!(result, vec![expected]);
+
+        repository
+            .delete_secure_channel(&secure_channel.encryptor)
+            .await?;
+        let result = repository
+            .get_secure_channel(&secure_channel.encryptor)
+            .await?;
+        assert_eq!(result, None);
+        Ok(())
+    }
+}
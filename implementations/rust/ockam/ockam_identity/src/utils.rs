@@ -1,9 +1,10 @@
+use core::time::Duration;
 use minicbor::bytes::ByteVec;
 use ockam_core::compat::collections::BTreeMap;
 use ockam_core::compat::vec::Vec;
 use ockam_core::Result;
 
-use crate::models::{Attributes, CredentialSchemaIdentifier, TimestampInSeconds};
+use crate::models::{AttributeValue, Attributes, CredentialSchemaIdentifier, TimestampInSeconds};
 use crate::IdentityError;
 
 /// Create a new timestamp using the system time
@@ -31,6 +32,8 @@ pub fn add_seconds(timestamp: &TimestampInSeconds, seconds: u64) -> TimestampInS
 pub struct AttributesBuilder {
     schema_id: CredentialSchemaIdentifier,
     map: BTreeMap<ByteVec, ByteVec>,
+    typed_map: BTreeMap<ByteVec, AttributeValue>,
+    ttls: BTreeMap<ByteVec, u64>,
 }
 
 impl AttributesBuilder {
@@ -39,6 +42,8 @@ impl AttributesBuilder {
         Self {
             schema_id,
             map: Default::default(),
+            typed_map: Default::default(),
+            ttls: Default::default(),
         }
     }
 
@@ -49,11 +54,47 @@ impl AttributesBuilder {
         self
     }
 
+    /// Add a typed attribute to the [`Attributes`]
+    pub fn with_typed_attribute(
+        mut self,
+        key: impl Into<Vec<u8>>,
+        value: impl Into<AttributeValue>,
+    ) -> Self {
+        let key: ByteVec = key.into().into();
+        let value = value.into();
+        self.map.insert(key.clone(), value.to_bytes().into());
+        self.typed_map.insert(key, value);
+
+        self
+    }
+
+    /// Set a time to live for an attribute, shorter than the credential time to live.
+    /// The attribute is not valid anymore after that time, even if the credential still is
+    pub fn with_attribute_ttl(mut self, key: impl Into<Vec<u8>>, ttl: Duration) -> Self {
+        self.ttls.insert(key.into().into(), ttl.as_secs());
+
+        self
+    }
+
     /// Build the corresponding [`Attributes`]
     pub fn build(self) -> Attributes {
+        // Credentials without typed attributes keep the same encoding as before
+        let typed_map = if self.typed_map.is_empty() {
+            None
+        } else {
+            Some(self.typed_map)
+        };
+        let ttls = if self.ttls.is_empty() {
+            None
+        } else {
+            Some(self.ttls)
+        };
         Attributes {
             schema: self.schema_id,
             map: self.map,
+            typed_map,
+            ttls,
+            commitments: None,
         }
     }
 }
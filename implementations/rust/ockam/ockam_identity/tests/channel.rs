@@ -2,15 +2,19 @@ use core::time::Duration;
 use std::sync::atomic::{AtomicU8, Ordering};
 
 use ockam_core::compat::sync::Arc;
-use ockam_core::{route, Address, AllowAll, Any, DenyAll, Mailboxes, Result, Routed, Worker};
+use ockam_core::errcode::Kind;
+use ockam_core::{
+    route, Address, AllowAll, Any, DenyAll, Mailboxes, Result, Route, Routed, Worker,
+};
 use ockam_identity::models::{CredentialSchemaIdentifier, Identifier};
 use ockam_identity::secure_channels::secure_channels;
 use ockam_identity::utils::AttributesBuilder;
 use ockam_identity::{
-    AuthorityService, DecryptionResponse, EncryptionRequest, EncryptionResponse,
-    IdentityAccessControlBuilder, IdentitySecureChannelLocalInfo, SecureChannelListenerOptions,
-    SecureChannelOptions, SecureChannels, TrustContext, TrustEveryonePolicy, TrustIdentifierPolicy,
-    Vault,
+    AuthorityService, Capability, DecryptionResponse, EncryptionRequest, EncryptionResponse,
+    IdentityAccessControlBuilder, IdentitySecureChannelLocalInfo, PinnedIdentities, RekeyingPolicy,
+    ResumptionTickets, SecureChannelListenerOptions, SecureChannelOptions, SecureChannelRepository,
+    SecureChannelSqlxDatabase, SecureChannels, TrustContext, TrustEveryonePolicy,
+    TrustIdentifierPolicy, Vault,
 };
 use ockam_node::{Context, MessageReceiveOptions, WorkerBuilder};
 use ockam_vault::{
@@ -279,6 +283,151 @@ async fn test_channel_rejected_trust_policy(ctx: &mut Context) -> Result<()> {
     ctx.stop().await
 }
 
+#[ockam_macros::test]
+async fn test_channel_pinned_identities(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+    let identities_creation = secure_channels.identities().identities_creation();
+
+    let alice = identities_creation.create_identity().await?;
+    let bob = identities_creation.create_identity().await?;
+
+    let known_identities = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(
+        known_identities.path(),
+        format!("# alice\n{alice}\n# bob\n{bob}\n"),
+    )
+    .unwrap();
+    let pinned = PinnedIdentities::from_file(known_identities.path())?;
+
+    secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            &bob,
+            "bob_listener",
+            SecureChannelListenerOptions::new().with_pinned_identities(pinned.clone()),
+        )
+        .await?;
+
+    // both identities are pinned
+    secure_channels
+        .create_secure_channel(
+            ctx,
+            &alice,
+            route!["bob_listener"],
+            SecureChannelOptions::new().with_pinned_identities(pinned),
+        )
+        .await?;
+
+    // bob is not pinned
+    let only_alice = PinnedIdentities::new(vec![alice.clone()]);
+    let result = secure_channels
+        .create_secure_channel(
+            ctx,
+            &alice,
+            route!["bob_listener"],
+            SecureChannelOptions::new()
+                .with_trust_policy(TrustEveryonePolicy)
+                .with_pinned_identities(only_alice)
+                .with_timeout(Duration::from_millis(500)),
+        )
+        .await;
+    assert!(result.is_err());
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn test_channel_resumption(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+    let identities_creation = secure_channels.identities().identities_creation();
+
+    let alice = identities_creation.create_identity().await?;
+    let bob = identities_creation.create_identity().await?;
+
+    let alice_tickets = Arc::new(ResumptionTickets::new());
+    let bob_tickets = Arc::new(ResumptionTickets::new());
+
+    let bob_listener = secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            &bob,
+            "bob_listener",
+            SecureChannelListenerOptions::new()
+                .with_trust_policy(TrustIdentifierPolicy::new(alice.clone()))
+                .with_resumption(bob_tickets.clone()),
+        )
+        .await?;
+    ctx.flow_controls()
+        .add_consumer(ctx.address(), bob_listener.flow_control_id());
+
+    let alice_options = || {
+        SecureChannelOptions::new()
+            .with_trust_policy(TrustIdentifierPolicy::new(bob.clone()))
+            .with_resumption(alice_tickets.clone())
+    };
+
+    // the first channel is established with a full handshake and bob sends a ticket
+    secure_channels
+        .create_secure_channel(ctx, &alice, route!["bob_listener"], alice_options())
+        .await?;
+    wait_for_ticket(ctx, &alice_tickets, route!["bob_listener"]).await;
+    assert_eq!(bob_tickets.issued_tickets_count(), 1);
+
+    // the second channel is established by presenting the ticket, which is redeemed,
+    // and bob sends a new ticket
+    let alice_channel = secure_channels
+        .create_secure_channel(ctx, &alice, route!["bob_listener"], alice_options())
+        .await?;
+    wait_for_ticket(ctx, &alice_tickets, route!["bob_listener"]).await;
+    assert_eq!(bob_tickets.issued_tickets_count(), 1);
+
+    let entry = secure_channels
+        .secure_channel_registry()
+        .get_channel_by_encryptor_address(alice_channel.encryptor_address())
+        .unwrap();
+    assert_eq!(entry.their_id(), &bob);
+
+    ctx.send(
+        route![alice_channel, ctx.address()],
+        "Hello, Bob!".to_string(),
+    )
+    .await?;
+    let msg = ctx.receive::<String>().await?;
+    let local_info = IdentitySecureChannelLocalInfo::find_info(msg.local_message())?;
+    assert_eq!(local_info.their_identity_id(), alice);
+    assert_eq!("Hello, Bob!", msg.body());
+
+    // a ticket is rejected by a listener which didn't issue it
+    // and a full handshake is performed instead
+    let other_tickets = Arc::new(ResumptionTickets::new());
+    secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            &bob,
+            "other_listener",
+            SecureChannelListenerOptions::new().with_resumption(other_tickets.clone()),
+        )
+        .await?;
+    secure_channels
+        .create_secure_channel(ctx, &alice, route!["other_listener"], alice_options())
+        .await?;
+    wait_for_ticket(ctx, &alice_tickets, route!["other_listener"]).await;
+    assert_eq!(other_tickets.issued_tickets_count(), 1);
+
+    ctx.stop().await
+}
+
+/// Wait until a resumption ticket has been received for the given route
+async fn wait_for_ticket(ctx: &Context, tickets: &ResumptionTickets, route: Route) {
+    for _ in 0..50 {
+        if tickets.has_ticket(&route) {
+            return;
+        }
+        ctx.sleep(Duration::from_millis(20)).await;
+    }
+    panic!("no resumption ticket received for {route}");
+}
+
 #[ockam_macros::test]
 async fn test_channel_send_multiple_messages_both_directions(ctx: &mut Context) -> Result<()> {
     let secure_channels = secure_channels().await?;
@@ -340,6 +489,73 @@ async fn test_channel_send_multiple_messages_both_directions(ctx: &mut Context)
     ctx.stop().await
 }
 
+#[ockam_macros::test]
+async fn test_channel_with_rekeying(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+    let identities_creation = secure_channels.identities().identities_creation();
+
+    let alice = identities_creation.create_identity().await?;
+    let bob = identities_creation.create_identity().await?;
+
+    let bob_options = SecureChannelListenerOptions::new()
+        .with_trust_policy(TrustIdentifierPolicy::new(alice.clone()))
+        .with_rekeying(RekeyingPolicy::new().with_max_messages(10));
+    let sc_listener_flow_control_id = bob_options.spawner_flow_control_id();
+    secure_channels
+        .create_secure_channel_listener(ctx, &bob, "bob_listener", bob_options)
+        .await?;
+
+    let alice_options = SecureChannelOptions::new()
+        .with_trust_policy(TrustIdentifierPolicy::new(bob.clone()))
+        .with_rekeying(
+            RekeyingPolicy::new()
+                .with_max_messages(7)
+                .with_max_bytes(500),
+        );
+    let sc_flow_control_id = alice_options.producer_flow_control_id();
+    let alice_channel = secure_channels
+        .create_secure_channel(ctx, &alice, route!["bob_listener"], alice_options)
+        .await?;
+
+    let mut child_ctx = ctx
+        .new_detached_with_mailboxes(Mailboxes::main(
+            "child",
+            Arc::new(AllowAll),
+            Arc::new(AllowAll),
+        ))
+        .await?;
+    child_ctx
+        .flow_controls()
+        .add_consumer(child_ctx.address(), &sc_listener_flow_control_id);
+    child_ctx
+        .flow_controls()
+        .add_consumer(child_ctx.address(), &sc_flow_control_id);
+
+    // The keys are renegotiated several times while messages are exchanged
+    for n in 0..200 {
+        let payload = format!("Hello, Bob! {}", n);
+        child_ctx
+            .send(
+                route![alice_channel.clone(), child_ctx.address()],
+                payload.clone(),
+            )
+            .await?;
+
+        let message = child_ctx.receive::<String>().await?;
+        assert_eq!(&payload, message.as_body());
+
+        let payload = format!("Hello, Alice! {}", n);
+        child_ctx
+            .send(message.return_route(), payload.clone())
+            .await?;
+
+        let message = child_ctx.receive::<String>().await?;
+        assert_eq!(&payload, message.as_body());
+    }
+
+    ctx.stop().await
+}
+
 #[ockam_macros::test]
 async fn test_channel_registry(ctx: &mut Context) -> Result<()> {
     let secure_channels = secure_channels().await?;
@@ -411,6 +627,75 @@ async fn test_channel_registry(ctx: &mut Context) -> Result<()> {
     ctx.stop().await
 }
 
+#[ockam_macros::test]
+async fn test_channel_capabilities(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+    let identities_creation = secure_channels.identities().identities_creation();
+
+    let alice = identities_creation.create_identity().await?;
+    let bob = identities_creation.create_identity().await?;
+
+    secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            &bob,
+            "bob_listener",
+            SecureChannelListenerOptions::new()
+                .with_capability(Capability(42))
+                .with_capability(Capability(43)),
+        )
+        .await?;
+    secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            &bob,
+            "bob_default_listener",
+            SecureChannelListenerOptions::new(),
+        )
+        .await?;
+
+    let alice_options = || {
+        SecureChannelOptions::new()
+            .with_capability(Capability(42))
+            .with_capability(Capability(44))
+    };
+
+    // only the capabilities supported by both sides are negotiated
+    let alice_channel = secure_channels
+        .create_secure_channel(ctx, &alice, route!["bob_listener"], alice_options())
+        .await?;
+    let entry = secure_channels
+        .secure_channel_registry()
+        .get_channel_by_encryptor_address(alice_channel.encryptor_address())
+        .unwrap();
+    assert_eq!(
+        entry.capabilities().iter().copied().collect::<Vec<_>>(),
+        vec![
+            Capability::REKEYING,
+            Capability::CREDENTIALS_REFRESH_RESPONSE,
+            Capability(42)
+        ]
+    );
+
+    // only the built-in capabilities are negotiated with a side which doesn't offer other ones
+    let alice_channel = secure_channels
+        .create_secure_channel(ctx, &alice, route!["bob_default_listener"], alice_options())
+        .await?;
+    let entry = secure_channels
+        .secure_channel_registry()
+        .get_channel_by_encryptor_address(alice_channel.encryptor_address())
+        .unwrap();
+    assert_eq!(
+        entry.capabilities().iter().copied().collect::<Vec<_>>(),
+        vec![
+            Capability::REKEYING,
+            Capability::CREDENTIALS_REFRESH_RESPONSE
+        ]
+    );
+
+    ctx.stop().await
+}
+
 #[ockam_macros::test]
 async fn test_channel_api(ctx: &mut Context) -> Result<()> {
     let secure_channels = secure_channels().await?;
@@ -1093,3 +1378,330 @@ async fn should_stop_encryptor__and__decryptor__in__secure_channel(
 
     ctx.stop().await
 }
+
+#[ockam_macros::test]
+async fn test_persistent_channel_is_resumed(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+    let identities_creation = secure_channels.identities().identities_creation();
+
+    let alice = identities_creation.create_identity().await?;
+    let bob = identities_creation.create_identity().await?;
+
+    let bob_options = SecureChannelListenerOptions::new()
+        .with_trust_policy(TrustIdentifierPolicy::new(alice.clone()));
+    let sc_listener_flow_control_id = bob_options.spawner_flow_control_id();
+    secure_channels
+        .create_secure_channel_listener(ctx, &bob, "bob_listener", bob_options)
+        .await?;
+
+    let repository: Arc<dyn SecureChannelRepository> =
+        Arc::new(SecureChannelSqlxDatabase::create().await?);
+    let alice_options = SecureChannelOptions::new()
+        .with_trust_policy(TrustIdentifierPolicy::new(bob.clone()))
+        .with_persistence(repository.clone());
+    let sc_flow_control_id = alice_options.producer_flow_control_id();
+    let alice_channel = secure_channels
+        .create_secure_channel(ctx, &alice, route!["bob_listener"], alice_options)
+        .await?;
+
+    let mut child_ctx = ctx
+        .new_detached_with_mailboxes(Mailboxes::main(
+            "child",
+            Arc::new(AllowAll),
+            Arc::new(AllowAll),
+        ))
+        .await?;
+    child_ctx
+        .flow_controls()
+        .add_consumer(child_ctx.address(), &sc_listener_flow_control_id);
+    child_ctx
+        .flow_controls()
+        .add_consumer(child_ctx.address(), &sc_flow_control_id);
+
+    exchange_messages(&mut child_ctx, alice_channel.encryptor_address(), 0..50).await?;
+
+    // Stopping the workers keeps the persisted channel
+    ctx.stop_worker(alice_channel.encryptor_address().clone())
+        .await?;
+    ctx.sleep(Duration::from_millis(100)).await;
+    assert!(repository
+        .get_secure_channel(alice_channel.encryptor_address())
+        .await?
+        .is_some());
+
+    let alice_options = SecureChannelOptions::new().with_persistence(repository.clone());
+    child_ctx.flow_controls().add_consumer(
+        child_ctx.address(),
+        &alice_options.producer_flow_control_id(),
+    );
+    let resumed_channel = secure_channels
+        .resume_secure_channel(
+            ctx,
+            alice_channel.encryptor_address(),
+            route![],
+            alice_options,
+        )
+        .await?;
+    assert_eq!(
+        resumed_channel.encryptor_address(),
+        alice_channel.encryptor_address()
+    );
+
+    exchange_messages(&mut child_ctx, resumed_channel.encryptor_address(), 50..100).await?;
+
+    // Once stopped explicitly, the channel can't be resumed anymore
+    secure_channels
+        .stop_persisted_secure_channel(ctx, resumed_channel.encryptor_address(), repository.clone())
+        .await?;
+    assert!(repository
+        .get_secure_channel(resumed_channel.encryptor_address())
+        .await?
+        .is_none());
+
+    Ok(())
+}
+
+async fn exchange_messages(
+    child_ctx: &mut Context,
+    channel: &Address,
+    range: core::ops::Range<u32>,
+) -> Result<()> {
+    for n in range {
+        let payload = format!("Hello, Bob! {}", n);
+        child_ctx
+            .send(
+                route![channel.clone(), child_ctx.address()],
+                payload.clone(),
+            )
+            .await?;
+
+        let message = child_ctx.receive::<String>().await?;
+        assert_eq!(&payload, message.as_body());
+
+        let payload = format!("Hello, Alice! {}", n);
+        child_ctx
+            .send(message.return_route(), payload.clone())
+            .await?;
+
+        let message = child_ctx.receive::<String>().await?;
+        assert_eq!(&payload, message.as_body());
+    }
+    Ok(())
+}
+
+#[ockam_macros::test]
+async fn test_channel_closed_after_idle_timeout(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+    let identities_creation = secure_channels.identities().identities_creation();
+
+    let alice = identities_creation.create_identity().await?;
+    let bob = identities_creation.create_identity().await?;
+
+    secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            &bob,
+            "bob_listener",
+            SecureChannelListenerOptions::new(),
+        )
+        .await?;
+
+    let alice_channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            &alice,
+            route!["bob_listener"],
+            SecureChannelOptions::new().with_idle_timeout(Duration::from_secs(1)),
+        )
+        .await?;
+
+    ctx.sleep(Duration::from_millis(100)).await;
+    assert!(secure_channels
+        .secure_channel_registry()
+        .get_channel_by_encryptor_address(alice_channel.encryptor_address())
+        .is_some());
+    assert_eq!(
+        secure_channels
+            .secure_channel_registry()
+            .get_channel_list()
+            .len(),
+        2
+    );
+
+    // Both sides of the channel are closed
+    ctx.sleep(Duration::from_secs(3)).await;
+    assert!(secure_channels
+        .secure_channel_registry()
+        .get_channel_list()
+        .is_empty());
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn test_channel_closed_after_max_lifetime(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+    let identities_creation = secure_channels.identities().identities_creation();
+
+    let alice = identities_creation.create_identity().await?;
+    let bob = identities_creation.create_identity().await?;
+
+    let bob_options =
+        SecureChannelListenerOptions::new().with_max_lifetime(Duration::from_millis(1_500));
+    let sc_listener_flow_control_id = bob_options.spawner_flow_control_id();
+    secure_channels
+        .create_secure_channel_listener(ctx, &bob, "bob_listener", bob_options)
+        .await?;
+
+    let alice_options = SecureChannelOptions::new();
+    let sc_flow_control_id = alice_options.producer_flow_control_id();
+    let alice_channel = secure_channels
+        .create_secure_channel(ctx, &alice, route!["bob_listener"], alice_options)
+        .await?;
+
+    let mut child_ctx = ctx
+        .new_detached_with_mailboxes(Mailboxes::main(
+            "child",
+            Arc::new(AllowAll),
+            Arc::new(AllowAll),
+        ))
+        .await?;
+    child_ctx
+        .flow_controls()
+        .add_consumer(child_ctx.address(), &sc_listener_flow_control_id);
+    child_ctx
+        .flow_controls()
+        .add_consumer(child_ctx.address(), &sc_flow_control_id);
+
+    // The channel is closed even if it is used
+    exchange_messages(&mut child_ctx, alice_channel.encryptor_address(), 0..5).await?;
+    ctx.sleep(Duration::from_secs(1)).await;
+    exchange_messages(&mut child_ctx, alice_channel.encryptor_address(), 5..10).await?;
+    ctx.sleep(Duration::from_secs(1)).await;
+
+    assert!(secure_channels
+        .secure_channel_registry()
+        .get_channel_list()
+        .is_empty());
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn test_channel_details(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+    let identities_creation = secure_channels.identities().identities_creation();
+
+    let alice = identities_creation.create_identity().await?;
+    let bob = identities_creation.create_identity().await?;
+
+    let bob_options = SecureChannelListenerOptions::new();
+    let sc_listener_flow_control_id = bob_options.spawner_flow_control_id();
+    secure_channels
+        .create_secure_channel_listener(ctx, &bob, "bob_listener", bob_options)
+        .await?;
+
+    let alice_options = SecureChannelOptions::new();
+    let sc_flow_control_id = alice_options.producer_flow_control_id();
+    let alice_channel = secure_channels
+        .create_secure_channel(ctx, &alice, route!["bob_listener"], alice_options)
+        .await?;
+
+    let mut child_ctx = ctx
+        .new_detached_with_mailboxes(Mailboxes::main(
+            "child",
+            Arc::new(AllowAll),
+            Arc::new(AllowAll),
+        ))
+        .await?;
+    child_ctx
+        .flow_controls()
+        .add_consumer(child_ctx.address(), &sc_listener_flow_control_id);
+    child_ctx
+        .flow_controls()
+        .add_consumer(child_ctx.address(), &sc_flow_control_id);
+
+    exchange_messages(&mut child_ctx, alice_channel.encryptor_address(), 0..3).await?;
+
+    let details = secure_channels
+        .get_secure_channel_details(alice_channel.encryptor_address())
+        .await?
+        .unwrap();
+    let entry = details.entry();
+    assert!(entry.is_initiator());
+    assert_eq!(entry.my_id(), &alice);
+    assert_eq!(entry.their_id(), &bob);
+    assert!(details.their_attributes().is_none());
+
+    let stats = entry.stats().unwrap();
+    assert_eq!(stats.messages_sent(), 3);
+    assert_eq!(stats.messages_received(), 3);
+    assert!(stats.bytes_sent() > 0);
+    assert!(stats.bytes_received() > 0);
+    assert!(stats.last_activity() >= stats.created_at());
+
+    let all_details = secure_channels.list_secure_channel_details().await?;
+    assert_eq!(all_details.len(), 2);
+    let bob_details = all_details
+        .iter()
+        .find(|d| !d.entry().is_initiator())
+        .unwrap();
+    assert_eq!(bob_details.entry().their_id(), &alice);
+    assert_eq!(bob_details.entry().stats().unwrap().messages_received(), 3);
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn test_channel_max_secure_channels(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+    let identities_creation = secure_channels.identities().identities_creation();
+
+    let alice = identities_creation.create_identity().await?;
+    let bob = identities_creation.create_identity().await?;
+
+    secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            &bob,
+            "bob_listener",
+            SecureChannelListenerOptions::new(),
+        )
+        .await?;
+
+    secure_channels
+        .create_secure_channel(
+            ctx,
+            &alice,
+            route!["bob_listener"],
+            SecureChannelOptions::new(),
+        )
+        .await?;
+
+    // the channel created above is registered on both sides
+    let registry = secure_channels.secure_channel_registry();
+    registry.set_max_channels(Some(1));
+
+    let result = secure_channels
+        .create_secure_channel(
+            ctx,
+            &alice,
+            route!["bob_listener"],
+            SecureChannelOptions::new(),
+        )
+        .await;
+    assert_eq!(result.unwrap_err().code().kind, Kind::ResourceExhausted);
+    assert_eq!(registry.rejected_channels(), 1);
+
+    registry.set_max_channels(None);
+    secure_channels
+        .create_secure_channel(
+            ctx,
+            &alice,
+            route!["bob_listener"],
+            SecureChannelOptions::new(),
+        )
+        .await?;
+
+    ctx.stop().await
+}
@@ -329,3 +329,30 @@ impl Worker for CountingWorker {
         Ok(())
     }
 }
+
+#[tokio::test]
+async fn typed_attributes_must_match_untyped_attributes() -> Result<()> {
+    let identities = ockam_identity::identities().await?;
+    let credentials = identities.credentials();
+    let authority = identities.identities_creation().create_identity().await?;
+    let subject = identities.identities_creation().create_identity().await?;
+
+    // the typed value says "admin" while the untyped value, used for access control, says "user"
+    let mut attributes = AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+        .with_typed_attribute("role", "admin")
+        .build();
+    attributes
+        .map
+        .insert(b"role".to_vec().into(), b"user".to_vec().into());
+
+    let credential = credentials
+        .credentials_creation()
+        .issue_credential(&authority, &subject, attributes, Duration::from_secs(60))
+        .await?;
+    assert!(credentials
+        .credentials_verification()
+        .verify_credential(Some(&subject), &[authority], &credential)
+        .await
+        .is_err());
+    Ok(())
+}
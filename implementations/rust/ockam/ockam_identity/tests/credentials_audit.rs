@@ -0,0 +1,83 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use ockam_core::compat::sync::Arc;
+use ockam_core::{async_trait, Result};
+use ockam_identity::models::CredentialSchemaIdentifier;
+use ockam_identity::utils::AttributesBuilder;
+use ockam_identity::{
+    CredentialAuditOutcome, CredentialAuditRecord, CredentialOperation, CredentialsAuditSink,
+    Identities,
+};
+
+#[derive(Default)]
+struct MemoryAuditSink {
+    records: Mutex<Vec<CredentialAuditRecord>>,
+}
+
+#[async_trait]
+impl CredentialsAuditSink for MemoryAuditSink {
+    async fn record(&self, record: CredentialAuditRecord) -> Result<()> {
+        self.records.lock().unwrap().push(record);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn credentials_are_audited() -> Result<()> {
+    let sink = Arc::new(MemoryAuditSink::default());
+    let identities = Identities::builder()
+        .await?
+        .with_credentials_audit_sink(sink.clone())
+        .build();
+    let identities_creation = identities.identities_creation();
+    let credentials = identities.credentials();
+
+    let authority = identities_creation.create_identity().await?;
+    let client = identities_creation.create_identity().await?;
+    let other = identities_creation.create_identity().await?;
+
+    let credential = credentials
+        .credentials_creation()
+        .issue_credential(
+            &authority,
+            &client,
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(1))
+                .with_attribute("name", "client")
+                .build(),
+            Duration::from_secs(60),
+        )
+        .await?;
+    let verification = credentials.credentials_verification();
+    verification
+        .verify_credential(Some(&client), &[authority.clone()], &credential)
+        .await?;
+    assert!(verification
+        .verify_credential(Some(&client), &[other.clone()], &credential)
+        .await
+        .is_err());
+
+    let records = sink.records.lock().unwrap().clone();
+    assert_eq!(records.len(), 3);
+
+    let issuance = &records[0];
+    assert_eq!(issuance.operation, CredentialOperation::Issuance);
+    assert_eq!(issuance.issuer, Some(authority.clone()));
+    assert_eq!(issuance.subject, Some(client.clone()));
+    assert_eq!(issuance.schema, Some(CredentialSchemaIdentifier(1)));
+    assert_eq!(issuance.outcome, CredentialAuditOutcome::Success);
+
+    let verification = &records[1];
+    assert_eq!(verification.operation, CredentialOperation::Verification);
+    assert_eq!(verification.issuer, Some(authority));
+    assert_eq!(verification.attributes_hash, issuance.attributes_hash);
+    assert_eq!(verification.outcome, CredentialAuditOutcome::Success);
+
+    let failed_verification = &records[2];
+    assert_eq!(failed_verification.subject, Some(client));
+    assert!(matches!(
+        failed_verification.outcome,
+        CredentialAuditOutcome::Failure(_)
+    ));
+    Ok(())
+}
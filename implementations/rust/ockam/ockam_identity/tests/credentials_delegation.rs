@@ -0,0 +1,149 @@
+use std::time::Duration;
+
+use ockam_core::Result;
+use ockam_identity::models::{AttributeValue, CredentialSchemaIdentifier, DelegatedCredential};
+use ockam_identity::utils::AttributesBuilder;
+use ockam_identity::{identities, Identifier, Identities};
+
+#[tokio::test]
+async fn delegated_credential() -> Result<()> {
+    let identities = identities().await?;
+    let identities_creation = identities.identities_creation();
+    let credentials = identities.credentials();
+    let creation = credentials.credentials_creation();
+    let verification = credentials.credentials_verification();
+
+    let root = identities_creation.create_identity().await?;
+    let region = identities_creation.create_identity().await?;
+    let team = identities_creation.create_identity().await?;
+    let client = identities_creation.create_identity().await?;
+
+    // The root authority delegates the "region" and "team" attributes to a regional issuer,
+    // which delegates the "team" attribute to a team issuer
+    let region_delegation = creation
+        .issue_delegation(
+            &root,
+            &region,
+            vec!["region".into(), "team".into()],
+            Duration::from_secs(60),
+        )
+        .await?;
+    let team_delegation = creation
+        .issue_delegation(&region, &team, vec!["team".into()], Duration::from_secs(60))
+        .await?;
+
+    let credential = DelegatedCredential {
+        credential: issue(&identities, &team, &client, "team").await?,
+        delegation_chain: vec![region_delegation.clone(), team_delegation.clone()],
+    };
+    let data = verification
+        .verify_delegated_credential(Some(&client), &[root.clone()], &credential)
+        .await?;
+    assert_eq!(
+        data.get_attribute(b"team")
+            .and_then(|v| v.as_str().map(|s| s.to_string())),
+        Some("value".to_string())
+    );
+
+    // The chain must start with a root authority
+    assert!(verification
+        .verify_delegated_credential(Some(&client), &[region.clone()], &credential)
+        .await
+        .is_err());
+
+    // The team issuer can't attest an attribute which was not delegated to it
+    let credential = DelegatedCredential {
+        credential: issue(&identities, &team, &client, "region").await?,
+        delegation_chain: vec![region_delegation.clone(), team_delegation.clone()],
+    };
+    assert!(verification
+        .verify_delegated_credential(Some(&client), &[root.clone()], &credential)
+        .await
+        .is_err());
+
+    // The team issuer can't attest an attribute which was not delegated to it as a typed
+    // attribute or with its own time to live
+    let attributes = AttributesBuilder::with_schema(CredentialSchemaIdentifier(1))
+        .with_attribute("team", "value")
+        .with_attribute_ttl("region", Duration::from_secs(10))
+        .build();
+    let credential = DelegatedCredential {
+        credential: creation
+            .issue_credential(&team, &client, attributes, Duration::from_secs(60))
+            .await?,
+        delegation_chain: vec![region_delegation.clone(), team_delegation.clone()],
+    };
+    assert!(verification
+        .verify_delegated_credential(Some(&client), &[root.clone()], &credential)
+        .await
+        .is_err());
+
+    let mut attributes = AttributesBuilder::with_schema(CredentialSchemaIdentifier(1))
+        .with_attribute("team", "value")
+        .build();
+    attributes.typed_map = Some(
+        [(b"region".to_vec().into(), AttributeValue::from("value"))]
+            .into_iter()
+            .collect(),
+    );
+    let credential = DelegatedCredential {
+        credential: creation
+            .issue_credential(&team, &client, attributes, Duration::from_secs(60))
+            .await?,
+        delegation_chain: vec![region_delegation.clone(), team_delegation.clone()],
+    };
+    assert!(verification
+        .verify_delegated_credential(Some(&client), &[root.clone()], &credential)
+        .await
+        .is_err());
+
+    // The regional issuer can't delegate an attribute which was not delegated to it
+    let team_delegation = creation
+        .issue_delegation(
+            &region,
+            &team,
+            vec!["admin".into()],
+            Duration::from_secs(60),
+        )
+        .await?;
+    let credential = DelegatedCredential {
+        credential: issue(&identities, &team, &client, "admin").await?,
+        delegation_chain: vec![region_delegation, team_delegation],
+    };
+    assert!(verification
+        .verify_delegated_credential(Some(&client), &[root.clone()], &credential)
+        .await
+        .is_err());
+
+    // A credential which is not a delegation can't be used in the chain
+    let credential = DelegatedCredential {
+        credential: issue(&identities, &team, &client, "team").await?,
+        delegation_chain: vec![issue(&identities, &root, &team, "team").await?],
+    };
+    assert!(verification
+        .verify_delegated_credential(Some(&client), &[root], &credential)
+        .await
+        .is_err());
+    Ok(())
+}
+
+/// HELPERS
+async fn issue(
+    identities: &Identities,
+    issuer: &Identifier,
+    subject: &Identifier,
+    attribute: &str,
+) -> Result<ockam_identity::models::CredentialAndPurposeKey> {
+    identities
+        .credentials()
+        .credentials_creation()
+        .issue_credential(
+            issuer,
+            subject,
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(1))
+                .with_attribute(attribute, "value")
+                .build(),
+            Duration::from_secs(60),
+        )
+        .await
+}
@@ -8,8 +8,10 @@ use ockam_identity::models::{CredentialAndPurposeKey, CredentialSchemaIdentifier
 use ockam_identity::secure_channels::secure_channels;
 use ockam_identity::utils::AttributesBuilder;
 use ockam_identity::{
-    AuthorityService, Credentials, CredentialsRetriever, Identifier, IdentityError,
-    SecureChannelListenerOptions, SecureChannelOptions, TrustContext,
+    AuthorityService, CachedCredentialsSqlxDatabase, CachingCredentialsRetriever,
+    ChainedCredentialsRetriever, Credentials, CredentialsRefresher, CredentialsRetriever,
+    FileCredentialsRetriever, Identifier, IdentityError, SecureChannelListenerOptions,
+    SecureChannelOptions, TrustContext,
 };
 use ockam_node::Context;
 
@@ -257,6 +259,364 @@ async fn autorefresh_retry(ctx: &mut Context) -> Result<()> {
 
     ctx.stop().await
 }
+
+#[ockam_macros::test]
+async fn autorefresh_rejected(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+    let identities = secure_channels.identities();
+    let identities_creation = identities.identities_creation();
+    let credentials = identities.credentials();
+
+    let authority = identities_creation.create_identity().await?;
+    let other_authority = identities_creation.create_identity().await?;
+    let client1 = identities_creation.create_identity().await?;
+    let client2 = identities_creation.create_identity().await?;
+
+    let authority_service2 = AuthorityService::new(credentials.clone(), authority.clone(), None);
+    let trust_context2 = TrustContext::new(
+        "test_trust_context_id".to_string(),
+        Some(authority_service2),
+    );
+    let _listener = secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            &client2,
+            "listener",
+            SecureChannelListenerOptions::new().with_trust_context(trust_context2),
+        )
+        .await?;
+
+    // The refreshed credentials are issued by an authority unknown to the listener
+    let call_counter = Arc::new(AtomicU8::new(0));
+    let retriever = LocalCredentialsRetriever::new(
+        credentials.clone(),
+        other_authority.clone(),
+        client1.clone(),
+        None,
+        Duration::from_secs(10),
+        Some(call_counter.clone()),
+        None,
+    );
+    let authority_service1 = AuthorityService::new(
+        credentials.clone(),
+        other_authority.clone(),
+        Some(Arc::new(retriever)),
+    );
+    let trust_context1 = TrustContext::new(
+        "test_trust_context_id".to_string(),
+        Some(authority_service1),
+    );
+    let credential = credentials
+        .credentials_creation()
+        .issue_credential(
+            &authority,
+            &client1,
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(1))
+                .with_attribute(b"name".to_vec(), b"client1".to_vec())
+                .build(),
+            Duration::from_secs(3),
+        )
+        .await?;
+    let _channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            &client1,
+            route!["listener"],
+            SecureChannelOptions::new()
+                .with_credential(credential)
+                .with_trust_context(trust_context1)
+                .with_credential_refresh_time_gap(Duration::from_secs(2))
+                .with_min_credential_refresh_interval(Duration::from_secs(2)),
+        )
+        .await?;
+
+    ctx.sleep(Duration::from_millis(1_500)).await;
+    assert_eq!(call_counter.load(Ordering::Relaxed), 1);
+
+    // The channel is kept with the attributes of the first credential
+    let attributes = identities
+        .identity_attributes_repository()
+        .get_attributes(&client1)
+        .await?
+        .unwrap();
+    assert_eq!(attributes.attested_by(), Some(authority));
+
+    // The rejected credential is refreshed again after the minimal refresh interval,
+    // instead of waiting for its expiration
+    ctx.sleep(Duration::from_millis(2_000)).await;
+    assert_eq!(call_counter.load(Ordering::Relaxed), 2);
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn refresher(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+    let identities = secure_channels.identities();
+    let identities_creation = identities.identities_creation();
+    let credentials = identities.credentials();
+
+    let authority = identities_creation.create_identity().await?;
+
+    let server = identities_creation.create_identity().await?;
+    let authority_service_server =
+        AuthorityService::new(credentials.clone(), authority.clone(), None);
+    let trust_context_server = TrustContext::new(
+        "test_trust_context_id".to_string(),
+        Some(authority_service_server),
+    );
+    let _listener = secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            &server,
+            "listener",
+            SecureChannelListenerOptions::new().with_trust_context(trust_context_server),
+        )
+        .await?;
+
+    // The client channel is created with a credential which it can't refresh by itself
+    let client = identities_creation.create_identity().await?;
+    let credential = credentials
+        .credentials_creation()
+        .issue_credential(
+            &authority,
+            &client,
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(1))
+                .with_attribute(b"name".to_vec(), b"client1".to_vec())
+                .build(),
+            Duration::from_secs(10),
+        )
+        .await?;
+    let _channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            &client,
+            route!["listener"],
+            SecureChannelOptions::new()
+                .with_credential(credential)
+                .with_credential_refresh_time_gap(Duration::from_secs(1)),
+        )
+        .await?;
+
+    ctx.sleep(Duration::from_millis(100)).await;
+    let attributes_reader = identities.identity_attributes_repository();
+    let added1 = attributes_reader
+        .get_attributes(&client)
+        .await?
+        .unwrap()
+        .added();
+
+    ctx.sleep(Duration::from_millis(1_100)).await;
+    let call_counter = Arc::new(AtomicU8::new(0));
+    let retriever = LocalCredentialsRetriever::new(
+        credentials.clone(),
+        authority.clone(),
+        client.clone(),
+        None,
+        Duration::from_secs(4),
+        Some(call_counter.clone()),
+        None,
+    );
+    CredentialsRefresher::new(secure_channels.clone(), client.clone(), Arc::new(retriever))
+        .with_refresh_credential_time_gap(Duration::from_secs(1))
+        .with_min_credential_refresh_interval(Duration::from_secs(1))
+        .start(ctx, "credentials_refresher")
+        .await?;
+
+    // The new credential is presented on the established channel
+    ctx.sleep(Duration::from_millis(100)).await;
+    assert_eq!(call_counter.load(Ordering::Relaxed), 1);
+    let added2 = attributes_reader
+        .get_attributes(&client)
+        .await?
+        .unwrap()
+        .added();
+    assert!(added1 < added2);
+
+    // And refreshed 1 second before its expiration
+    ctx.sleep(Duration::from_millis(3_500)).await;
+    assert_eq!(call_counter.load(Ordering::Relaxed), 2);
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn caching_retriever(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+    let identities = secure_channels.identities();
+    let identities_creation = identities.identities_creation();
+    let credentials = identities.credentials();
+
+    let authority = identities_creation.create_identity().await?;
+    let client = identities_creation.create_identity().await?;
+
+    let call_counter = Arc::new(AtomicU8::new(0));
+    let retriever: Arc<dyn CredentialsRetriever> = Arc::new(LocalCredentialsRetriever::new(
+        credentials.clone(),
+        authority.clone(),
+        client.clone(),
+        None,
+        Duration::from_secs(600),
+        Some(call_counter.clone()),
+        None,
+    ));
+    let repository = Arc::new(CachedCredentialsSqlxDatabase::create().await?);
+
+    // The credential is only retrieved once
+    let caching_retriever =
+        CachingCredentialsRetriever::new(retriever.clone(), authority.clone(), repository.clone());
+    let credential1 = caching_retriever.retrieve(ctx, &client).await?;
+    let credential2 = caching_retriever.retrieve(ctx, &client).await?;
+    assert_eq!(credential1, credential2);
+    assert_eq!(call_counter.load(Ordering::Relaxed), 1);
+
+    // The cached credential is found again by another retriever using the same repository
+    let caching_retriever =
+        CachingCredentialsRetriever::new(retriever.clone(), authority.clone(), repository.clone());
+    assert_eq!(caching_retriever.retrieve(ctx, &client).await?, credential1);
+    assert_eq!(call_counter.load(Ordering::Relaxed), 1);
+
+    // A credential which is about to expire is retrieved again
+    let caching_retriever =
+        CachingCredentialsRetriever::new(retriever, authority.clone(), repository.clone())
+            .with_refresh_margin(Duration::from_secs(900));
+    caching_retriever.retrieve(ctx, &client).await?;
+    assert_eq!(call_counter.load(Ordering::Relaxed), 2);
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn chained_retriever(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+    let identities = secure_channels.identities();
+    let identities_creation = identities.identities_creation();
+    let credentials = identities.credentials();
+
+    let authority = identities_creation.create_identity().await?;
+    let client = identities_creation.create_identity().await?;
+
+    // The first retriever fails on its first call, the second one always succeeds
+    let call_counter1 = Arc::new(AtomicU8::new(0));
+    let call_counter2 = Arc::new(AtomicU8::new(0));
+    let chained_retriever = ChainedCredentialsRetriever::new()
+        .with_retriever(Arc::new(LocalCredentialsRetriever::new(
+            credentials.clone(),
+            authority.clone(),
+            client.clone(),
+            Some(1),
+            Duration::from_secs(600),
+            Some(call_counter1.clone()),
+            None,
+        )))
+        .with_retriever(Arc::new(LocalCredentialsRetriever::new(
+            credentials.clone(),
+            authority.clone(),
+            client.clone(),
+            None,
+            Duration::from_secs(600),
+            Some(call_counter2.clone()),
+            None,
+        )));
+
+    // The second retriever is used when the first one fails
+    chained_retriever.retrieve(ctx, &client).await?;
+    assert_eq!(call_counter1.load(Ordering::Relaxed), 1);
+    assert_eq!(call_counter2.load(Ordering::Relaxed), 1);
+
+    // The second retriever is not used when the first one succeeds
+    chained_retriever.retrieve(ctx, &client).await?;
+    assert_eq!(call_counter1.load(Ordering::Relaxed), 2);
+    assert_eq!(call_counter2.load(Ordering::Relaxed), 1);
+
+    // A retriever which is too slow is skipped
+    let call_counter3 = Arc::new(AtomicU8::new(0));
+    let chained_retriever = ChainedCredentialsRetriever::new()
+        .with_retriever_and_timeout(
+            Arc::new(SlowCredentialsRetriever),
+            Duration::from_millis(100),
+        )
+        .with_retriever(Arc::new(LocalCredentialsRetriever::new(
+            credentials.clone(),
+            authority.clone(),
+            client.clone(),
+            None,
+            Duration::from_secs(600),
+            Some(call_counter3.clone()),
+            None,
+        )));
+    chained_retriever.retrieve(ctx, &client).await?;
+    assert_eq!(call_counter3.load(Ordering::Relaxed), 1);
+
+    // An empty chain can't retrieve any credential
+    assert!(ChainedCredentialsRetriever::new()
+        .retrieve(ctx, &client)
+        .await
+        .is_err());
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn file_retriever(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+    let identities = secure_channels.identities();
+    let identities_creation = identities.identities_creation();
+    let credentials = identities.credentials();
+
+    let authority = identities_creation.create_identity().await?;
+    let client = identities_creation.create_identity().await?;
+    let retriever = LocalCredentialsRetriever::new(
+        credentials.clone(),
+        authority.clone(),
+        client.clone(),
+        None,
+        Duration::from_secs(600),
+        None,
+        None,
+    );
+
+    // The credential can be stored as an hex string
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let credential1 = retriever.retrieve(ctx, &client).await?;
+    std::fs::write(file.path(), credential1.encode_as_string()?).unwrap();
+    let file_retriever = FileCredentialsRetriever::new(file.path());
+    assert_eq!(file_retriever.retrieve(ctx, &client).await?, credential1);
+
+    // The credential is reloaded when the file is rotated
+    let credential2 = credentials
+        .credentials_creation()
+        .issue_credential(
+            &authority,
+            &client,
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(1)).build(),
+            Duration::from_secs(1200),
+        )
+        .await?;
+    assert_ne!(credential1, credential2);
+    std::fs::write(file.path(), credential2.encode_as_cbor_bytes()?).unwrap();
+    assert_eq!(file_retriever.retrieve(ctx, &client).await?, credential2);
+
+    // The credential is only returned for its subject
+    assert!(file_retriever.retrieve(ctx, &authority).await.is_err());
+
+    ctx.stop().await
+}
+
+struct SlowCredentialsRetriever;
+
+#[async_trait]
+impl CredentialsRetriever for SlowCredentialsRetriever {
+    async fn retrieve(
+        &self,
+        ctx: &Context,
+        _for_identity: &Identifier,
+    ) -> Result<CredentialAndPurposeKey> {
+        ctx.sleep(Duration::from_secs(10)).await;
+        Err(IdentityError::InvalidKeyData)?
+    }
+}
+
 struct LocalCredentialsRetriever {
     credentials: Arc<Credentials>,
     authority: Identifier,
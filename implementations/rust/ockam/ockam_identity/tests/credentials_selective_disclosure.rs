@@ -0,0 +1,75 @@
+use std::time::Duration;
+
+use ockam_core::Result;
+use ockam_identity::identities;
+use ockam_identity::models::{AttributeValue, CredentialSchemaIdentifier};
+use ockam_identity::utils::AttributesBuilder;
+
+#[tokio::test]
+async fn only_disclosed_attributes_are_revealed() -> Result<()> {
+    let identities = identities().await?;
+    let identities_creation = identities.identities_creation();
+    let credentials = identities.credentials();
+
+    let authority = identities_creation.create_identity().await?;
+    let client = identities_creation.create_identity().await?;
+
+    let disclosable = credentials
+        .credentials_creation()
+        .issue_disclosable_credential(
+            &authority,
+            &client,
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(1))
+                .with_attribute("name", "client")
+                .with_typed_attribute("age", 42i64)
+                .with_attribute("email", "client@example.com")
+                .build(),
+            Duration::from_secs(60),
+        )
+        .await?;
+    assert_eq!(disclosable.disclosures.len(), 3);
+
+    // The credential itself doesn't reveal any attribute
+    let data = disclosable.credential.get_credential_data()?;
+    assert!(data.subject_attributes.map.is_empty());
+
+    // A presentation only reveals the chosen attributes
+    let verification = credentials.credentials_verification();
+    let presentation = disclosable.present(&["name", "age"]);
+    let data = verification
+        .verify_presentation(Some(&client), &[authority.clone()], &presentation)
+        .await?;
+    assert_eq!(
+        data.get_attribute(b"name"),
+        Some(AttributeValue::String("client".into()))
+    );
+    assert_eq!(data.get_attribute(b"age"), Some(AttributeValue::Int(42)));
+    assert_eq!(data.get_attribute(b"email"), None);
+
+    // A disclosure which was not committed by the issuer is rejected
+    let mut forged = disclosable.present(&["name"]);
+    forged.disclosures[0].value = b"admin".to_vec().into();
+    assert!(verification
+        .verify_presentation(Some(&client), &[authority.clone()], &forged)
+        .await
+        .is_err());
+
+    // A regular credential can't be used as a presentation
+    let mut not_disclosable = disclosable.present(&["name"]);
+    not_disclosable.credential = credentials
+        .credentials_creation()
+        .issue_credential(
+            &authority,
+            &client,
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(1))
+                .with_attribute("name", "client")
+                .build(),
+            Duration::from_secs(60),
+        )
+        .await?;
+    assert!(verification
+        .verify_presentation(Some(&client), &[authority], &not_disclosable)
+        .await
+        .is_err());
+    Ok(())
+}
@@ -4,6 +4,7 @@ use ockam_core::compat::sync::Arc;
 use ockam_core::Result;
 use ockam_identity::models::ChangeHistory;
 use ockam_identity::{Identifier, Identities, Identity, Vault};
+use ockam_vault::SigningKeyType;
 use rand::{thread_rng, Rng};
 
 mod common;
@@ -23,6 +24,31 @@ async fn test_valid_identity() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_valid_p256_identity() -> Result<()> {
+    let identities = Identities::builder().await?.build();
+    let identities_creation = identities.identities_creation();
+    let identifier = identities_creation
+        .create_identity_with_key_type(SigningKeyType::ECDSASHA256CurveP256)
+        .await?;
+
+    let j: i32 = thread_rng().gen_range(1..10);
+    for _ in 0..j {
+        identities_creation.rotate_identity(&identifier).await?;
+    }
+
+    let identity = identities.get_identity(&identifier).await?;
+    check_identity(&identity).await?;
+    for change in identity.changes() {
+        assert_eq!(
+            change.primary_public_key().key_type(),
+            SigningKeyType::ECDSASHA256CurveP256
+        );
+    }
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_invalid_signature() -> Result<()> {
     let mut vault = Vault::create().await?;
@@ -110,10 +110,15 @@ async fn create_with_p256_identity() -> Result<()> {
         .await;
     assert!(res.is_err());
 
-    let _purpose_key = purpose_keys
+    let purpose_key = purpose_keys
         .purpose_keys_creation()
         .create_credential_purpose_key(&identifier)
         .await?;
+    // The credential purpose key has the same type as the identity key by default
+    assert_eq!(
+        purpose_key.public_key().key_type(),
+        SigningKeyType::ECDSASHA256CurveP256
+    );
 
     let res = purpose_keys
         .purpose_keys_creation()
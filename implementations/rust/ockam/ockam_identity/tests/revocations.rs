@@ -0,0 +1,194 @@
+use std::time::Duration;
+
+use ockam_core::compat::sync::Arc;
+use ockam_core::Result;
+use ockam_identity::models::CredentialSchemaIdentifier;
+use ockam_identity::utils::AttributesBuilder;
+use ockam_identity::{identities, Revocations, RevocationsSqlxDatabase};
+
+#[tokio::test]
+async fn revoked_credential_is_rejected() -> Result<()> {
+    let identities = identities().await?;
+    let identities_creation = identities.identities_creation();
+    let credentials = identities.credentials();
+
+    let authority = identities_creation.create_identity().await?;
+    let client = identities_creation.create_identity().await?;
+
+    let credential = credentials
+        .credentials_creation()
+        .issue_credential(
+            &authority,
+            &client,
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(1))
+                .with_attribute("name", "client")
+                .build(),
+            Duration::from_secs(60),
+        )
+        .await?;
+    let verification = credentials.credentials_verification();
+    verification
+        .verify_credential(Some(&client), &[authority.clone()], &credential)
+        .await?;
+
+    // A revoked credential can't be verified anymore
+    let revocations = credentials.revocations().unwrap();
+    revocations.revoke(&authority, &client).await?;
+    assert!(verification
+        .verify_credential(Some(&client), &[authority.clone()], &credential)
+        .await
+        .is_err());
+
+    // Unless the revocation is cancelled
+    revocations.cancel_revocation(&authority, &client).await?;
+    verification
+        .verify_credential(Some(&client), &[authority.clone()], &credential)
+        .await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn revocation_list_is_imported() -> Result<()> {
+    let identities = identities().await?;
+    let identities_creation = identities.identities_creation();
+    let credentials = identities.credentials();
+
+    let authority = identities_creation.create_identity().await?;
+    let client = identities_creation.create_identity().await?;
+    let other = identities_creation.create_identity().await?;
+
+    // The authority publishes a signed list of its revocations
+    let authority_revocations = credentials.revocations().unwrap();
+    authority_revocations.revoke(&authority, &client).await?;
+    let revocation_list = authority_revocations
+        .create_revocation_list(&authority, Duration::from_secs(60))
+        .await?;
+
+    // Another node imports the list from a trusted authority
+    let revocations = Revocations::new(
+        Arc::new(RevocationsSqlxDatabase::create().await?),
+        identities.purpose_keys(),
+        identities.vault().credential_vault,
+        identities.vault().verifying_vault,
+    );
+    assert!(revocations
+        .import_revocation_list(&[other.clone()], &revocation_list)
+        .await
+        .is_err());
+    revocations
+        .import_revocation_list(&[authority.clone()], &revocation_list)
+        .await?;
+
+    let revoked_at = revocation_list
+        .revocation_list
+        .get_revocation_list_data()?
+        .revocations[0]
+        .revoked_at;
+    assert!(
+        revocations
+            .is_revoked(&authority, &client, revoked_at)
+            .await?
+    );
+    assert!(
+        !revocations
+            .is_revoked(&authority, &other, revoked_at)
+            .await?
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn older_revocation_list_is_rejected() -> Result<()> {
+    let identities = identities().await?;
+    let identities_creation = identities.identities_creation();
+    let credentials = identities.credentials();
+
+    let authority = identities_creation.create_identity().await?;
+    let client = identities_creation.create_identity().await?;
+
+    let authority_revocations = credentials.revocations().unwrap();
+    authority_revocations.revoke(&authority, &client).await?;
+    let older_revocation_list = authority_revocations
+        .create_revocation_list(&authority, Duration::from_secs(60))
+        .await?;
+    // Revocation lists are timestamped in seconds
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    let revocation_list = authority_revocations
+        .create_revocation_list(&authority, Duration::from_secs(60))
+        .await?;
+
+    let revocations = Revocations::new(
+        Arc::new(RevocationsSqlxDatabase::create().await?),
+        identities.purpose_keys(),
+        identities.vault().credential_vault,
+        identities.vault().verifying_vault,
+    );
+    revocations
+        .import_revocation_list(&[authority.clone()], &revocation_list)
+        .await?;
+
+    // The older list can't be replayed, the same list can be imported again
+    assert!(revocations
+        .import_revocation_list(&[authority.clone()], &older_revocation_list)
+        .await
+        .is_err());
+    revocations
+        .import_revocation_list(&[authority.clone()], &revocation_list)
+        .await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn cancelled_revocation_is_removed_on_import() -> Result<()> {
+    let identities = identities().await?;
+    let identities_creation = identities.identities_creation();
+    let credentials = identities.credentials();
+
+    let authority = identities_creation.create_identity().await?;
+    let client = identities_creation.create_identity().await?;
+
+    let authority_revocations = credentials.revocations().unwrap();
+    authority_revocations.revoke(&authority, &client).await?;
+    let revocation_list = authority_revocations
+        .create_revocation_list(&authority, Duration::from_secs(60))
+        .await?;
+    let revoked_at = revocation_list
+        .revocation_list
+        .get_revocation_list_data()?
+        .revocations[0]
+        .revoked_at;
+
+    let revocations = Revocations::new(
+        Arc::new(RevocationsSqlxDatabase::create().await?),
+        identities.purpose_keys(),
+        identities.vault().credential_vault,
+        identities.vault().verifying_vault,
+    );
+    revocations
+        .import_revocation_list(&[authority.clone()], &revocation_list)
+        .await?;
+    assert!(
+        revocations
+            .is_revoked(&authority, &client, revoked_at)
+            .await?
+    );
+
+    // The authority cancels the revocation and publishes a new list
+    authority_revocations
+        .cancel_revocation(&authority, &client)
+        .await?;
+    // Revocation lists are timestamped in seconds
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    let revocation_list = authority_revocations
+        .create_revocation_list(&authority, Duration::from_secs(60))
+        .await?;
+    revocations
+        .import_revocation_list(&[authority.clone()], &revocation_list)
+        .await?;
+    assert!(
+        !revocations
+            .is_revoked(&authority, &client, revoked_at)
+            .await?
+    );
+    Ok(())
+}
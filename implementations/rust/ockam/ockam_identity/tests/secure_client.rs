@@ -0,0 +1,147 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::Duration;
+
+use minicbor::Decoder;
+use ockam_core::api::{Request, RequestHeader, Response};
+use ockam_core::compat::sync::Arc;
+use ockam_core::{async_trait, route, Result, Routed, Worker};
+use ockam_identity::secure_channels::secure_channels;
+use ockam_identity::{
+    BackoffStrategy, CircuitBreaker, IdentityError, SecureChannelListenerOptions, SecureClient,
+    SecureClientRetryPolicy,
+};
+use ockam_node::Context;
+
+#[ockam_macros::test]
+async fn test_secure_client_retries(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+    let identities_creation = secure_channels.identities().identities_creation();
+    let server = identities_creation.create_identity().await?;
+    let client = identities_creation.create_identity().await?;
+
+    let listener = secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            &server,
+            "listener",
+            SecureChannelListenerOptions::new(),
+        )
+        .await?;
+
+    // the service only answers the third request
+    let calls = Arc::new(AtomicU8::new(0));
+    ctx.flow_controls()
+        .add_consumer("flaky", listener.flow_control_id());
+    ctx.start_worker(
+        "flaky",
+        FlakyService {
+            calls: calls.clone(),
+            failures: 2,
+        },
+    )
+    .await?;
+
+    let secure_client = SecureClient::new(
+        secure_channels.clone(),
+        route!["listener"],
+        &server,
+        &client,
+        Duration::from_millis(500),
+    );
+
+    // without retries the request fails
+    let result: Result<Vec<u8>> = secure_client.request(ctx, "flaky", Request::get("/")).await;
+    assert!(result.is_err());
+    assert_eq!(calls.load(Ordering::Relaxed), 1);
+
+    // with retries the request eventually succeeds
+    let secure_client = secure_client.with_retry_policy(
+        SecureClientRetryPolicy::new()
+            .with_max_retries(2)
+            .with_backoff(BackoffStrategy::Fixed(Duration::from_millis(10))),
+    );
+    let reply: String = secure_client
+        .ask(ctx, "flaky", Request::get("/"))
+        .await?
+        .success()?;
+    assert_eq!(reply, "pong");
+    assert_eq!(calls.load(Ordering::Relaxed), 3);
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn test_secure_client_circuit_breaker(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+    let identities_creation = secure_channels.identities().identities_creation();
+    let server = identities_creation.create_identity().await?;
+    let client = identities_creation.create_identity().await?;
+
+    // there is no listener at this address
+    let circuit_breaker = Arc::new(CircuitBreaker::new(2, Duration::from_secs(3600)));
+    let secure_client = SecureClient::new(
+        secure_channels.clone(),
+        route!["unknown_listener"],
+        &server,
+        &client,
+        Duration::from_millis(200),
+    )
+    .with_retry_policy(
+        SecureClientRetryPolicy::new()
+            .with_max_retries(5)
+            .with_backoff(BackoffStrategy::Fixed(Duration::from_millis(10))),
+    )
+    .with_circuit_breaker(circuit_breaker.clone());
+
+    // the retries stop as soon as the circuit opens
+    let result: Result<Vec<u8>> = secure_client
+        .request(ctx, "service", Request::get("/"))
+        .await;
+    assert!(result.is_err());
+    assert!(circuit_breaker.is_open());
+    assert_eq!(circuit_breaker.consecutive_failures(), 2);
+
+    // the next requests fail immediately
+    let err = secure_client
+        .request(ctx, "service", Request::get("/"))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        IdentityError::from_error(&err),
+        Some(IdentityError::CircuitBreakerOpen)
+    ));
+    assert_eq!(circuit_breaker.consecutive_failures(), 2);
+
+    // a request with a longer timeout fails fast as well
+    let result = secure_client
+        .request_with_timeout(ctx, "service", Request::get("/"), Duration::from_secs(10))
+        .await;
+    assert!(result.is_err());
+
+    ctx.stop().await
+}
+
+/// Service which doesn't answer to the first `failures` requests
+struct FlakyService {
+    calls: Arc<AtomicU8>,
+    failures: u8,
+}
+
+#[async_trait]
+impl Worker for FlakyService {
+    type Message = Vec<u8>;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Vec<u8>>) -> Result<()> {
+        if self.calls.fetch_add(1, Ordering::Relaxed) < self.failures {
+            return Ok(());
+        }
+        let mut decoder = Decoder::new(msg.as_body());
+        let request: RequestHeader = decoder.decode()?;
+        let response = Response::ok()
+            .with_headers(&request)
+            .body("pong")
+            .to_vec()?;
+        ctx.send(msg.return_route(), response).await
+    }
+}
@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use ockam_core::Result;
+use ockam_identity::identities;
+use ockam_identity::models::CredentialSchemaIdentifier;
+use ockam_identity::utils::AttributesBuilder;
+
+#[tokio::test]
+async fn credentials_are_verified_offline_with_a_verification_bundle() -> Result<()> {
+    // Authority node
+    let authority_identities = identities().await?;
+    let authority_credentials = authority_identities.credentials();
+    let authority = authority_identities
+        .identities_creation()
+        .create_identity()
+        .await?;
+    let client = authority_identities
+        .identities_creation()
+        .create_identity()
+        .await?;
+    let revoked_client = authority_identities
+        .identities_creation()
+        .create_identity()
+        .await?;
+
+    let issue = |subject| {
+        let credentials_creation = authority_credentials.credentials_creation();
+        let authority = authority.clone();
+        async move {
+            credentials_creation
+                .issue_credential(
+                    &authority,
+                    &subject,
+                    AttributesBuilder::with_schema(CredentialSchemaIdentifier(1))
+                        .with_attribute("name", "client")
+                        .build(),
+                    Duration::from_secs(60),
+                )
+                .await
+        }
+    };
+    let credential = issue(client.clone()).await?;
+    let revoked_credential = issue(revoked_client.clone()).await?;
+    authority_credentials
+        .revocations()
+        .unwrap()
+        .revoke(&authority, &revoked_client)
+        .await?;
+
+    let bundle = authority_credentials
+        .verification_bundles()
+        .export_verification_bundle(&authority, Duration::from_secs(3600), true)
+        .await?;
+
+    // Air-gapped node, which doesn't know the authority yet
+    let offline_identities = identities().await?;
+    let offline_credentials = offline_identities.credentials();
+    let verification = offline_credentials.credentials_verification();
+    assert!(verification
+        .verify_credential(Some(&client), &[authority.clone()], &credential)
+        .await
+        .is_err());
+
+    // A bundle is only loaded for the expected authority
+    assert!(offline_credentials
+        .verification_bundles()
+        .load_verification_bundle(&client, &bundle)
+        .await
+        .is_err());
+    let data = offline_credentials
+        .verification_bundles()
+        .load_verification_bundle(&authority, &bundle)
+        .await?;
+    assert_eq!(data.purpose_key_attestations.len(), 1);
+
+    verification
+        .verify_credential(Some(&client), &[authority.clone()], &credential)
+        .await?;
+    assert!(verification
+        .verify_credential(Some(&revoked_client), &[authority], &revoked_credential)
+        .await
+        .is_err());
+    Ok(())
+}
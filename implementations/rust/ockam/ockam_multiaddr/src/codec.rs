@@ -1,5 +1,5 @@
 use super::{Buffer, Checked, Code, Codec, Protocol};
-use crate::proto::{DnsAddr, Node, Project, Secure, Service, Space, Tcp, Worker};
+use crate::proto::{DnsAddr, Node, Project, Secure, Service, Space, Tcp, Unix, Worker};
 use crate::{Error, ProtoValue};
 use core::fmt;
 use unsigned_varint::decode;
@@ -55,7 +55,8 @@ impl Codec for StdCodec {
             | c @ Node::CODE
             | c @ Project::CODE
             | c @ Space::CODE
-            | c @ Secure::CODE => {
+            | c @ Secure::CODE
+            | c @ Unix::CODE => {
                 let (len, input) = decode::usize(input)?;
                 if input.len() < len {
                     return Err(Error::required_bytes(c, len));
@@ -81,6 +82,7 @@ impl Codec for StdCodec {
             Project::CODE => Project::read_bytes(input).is_ok(),
             Space::CODE => Space::read_bytes(input).is_ok(),
             Secure::CODE => Secure::read_bytes(input).is_ok(),
+            Unix::CODE => Unix::read_bytes(input).is_ok(),
             _ => false,
         }
     }
@@ -99,6 +101,7 @@ impl Codec for StdCodec {
             Project::CODE => Project::read_bytes(val.data())?.write_bytes(buf),
             Space::CODE => Space::read_bytes(val.data())?.write_bytes(buf),
             Secure::CODE => Secure::read_bytes(val.data())?.write_bytes(buf),
+            Unix::CODE => Unix::read_bytes(val.data())?.write_bytes(buf),
             code => return Err(Error::unregistered(code)),
         }
         Ok(())
@@ -153,6 +156,10 @@ impl Codec for StdCodec {
                 Secure::read_str(value)?.write_bytes(buf);
                 Ok(())
             }
+            Unix::PREFIX => {
+                Unix::read_str(value)?.write_bytes(buf);
+                Ok(())
+            }
             _ => Err(Error::unregistered_prefix(prefix)),
         }
     }
@@ -206,6 +213,10 @@ impl Codec for StdCodec {
                 Secure::read_bytes(value)?.write_str(f)?;
                 Ok(())
             }
+            Unix::CODE => {
+                Unix::read_bytes(value)?.write_str(f)?;
+                Ok(())
+            }
             _ => Err(Error::unregistered(code)),
         }
     }
@@ -1,6 +1,8 @@
 use super::{Buffer, Checked, Code, Protocol};
 use crate::Error;
 use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
 use core::fmt;
 use core::ops::Deref;
 use core::str::{self, FromStr};
@@ -153,6 +155,86 @@ impl Protocol<'_> for Tcp {
     }
 }
 
+/// A Unix domain socket path.
+///
+/// In the textual representation the `/` and `%` characters of the path are
+/// percent-encoded, e.g. `/unix/%2Ftmp%2Fnode.sock`, so that other protocols
+/// can follow the path in the same multi-address.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Unix<'a>(Cow<'a, str>);
+
+impl<'a> Unix<'a> {
+    pub fn new<S: Into<Cow<'a, str>>>(s: S) -> Self {
+        Self(s.into())
+    }
+}
+
+impl Deref for Unix<'_> {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'a> Protocol<'a> for Unix<'a> {
+    const CODE: Code = Code::new(400);
+    const PREFIX: &'static str = "unix";
+
+    fn read_str(input: Checked<&'a str>) -> Result<Self, Error> {
+        if !input.contains('%') {
+            return Ok(Self(Cow::Borrowed(input.0)));
+        }
+        let mut path = Vec::with_capacity(input.len());
+        let mut bytes = input.bytes();
+        while let Some(b) = bytes.next() {
+            if b == b'%' {
+                let hex = [
+                    bytes
+                        .next()
+                        .ok_or_else(|| Error::message("invalid percent-encoding"))?,
+                    bytes
+                        .next()
+                        .ok_or_else(|| Error::message("invalid percent-encoding"))?,
+                ];
+                let hex = str::from_utf8(&hex).map_err(Error::message)?;
+                path.push(u8::from_str_radix(hex, 16).map_err(Error::message)?)
+            } else {
+                path.push(b)
+            }
+        }
+        let path = String::from_utf8(path).map_err(Error::message)?;
+        Ok(Self(Cow::Owned(path)))
+    }
+
+    fn read_bytes(input: Checked<&'a [u8]>) -> Result<Self, Error> {
+        let s = str::from_utf8(&input).map_err(Error::message)?;
+        Ok(Self(Cow::Borrowed(s)))
+    }
+
+    fn write_str(&self, f: &mut fmt::Formatter) -> Result<(), Error> {
+        write!(f, "/{}/", Self::PREFIX)?;
+        for c in self.0.chars() {
+            match c {
+                '/' => f.write_str("%2F")?,
+                '%' => f.write_str("%25")?,
+                c => write!(f, "{c}")?,
+            }
+        }
+        Ok(())
+    }
+
+    fn write_bytes(&self, buf: &mut dyn Buffer) {
+        let mut b = encode::u32_buffer();
+        let uvi = encode::u32(Self::CODE.into(), &mut b);
+        buf.extend_with(uvi);
+        let mut b = encode::usize_buffer();
+        let uvi = encode::usize(self.0.len(), &mut b);
+        buf.extend_with(uvi);
+        buf.extend_with(self.0.as_bytes())
+    }
+}
+
 macro_rules! gen_str_proto {
     ($t:ident, $c:literal, $p:literal) => {
         #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -1,5 +1,5 @@
 use core::fmt;
-use ockam_multiaddr::proto::{DnsAddr, Ip4, Ip6, Node, Project, Secure, Service, Space, Tcp};
+use ockam_multiaddr::proto::{DnsAddr, Ip4, Ip6, Node, Project, Secure, Service, Space, Tcp, Unix};
 use ockam_multiaddr::{Code, Match, MultiAddr, Protocol};
 use quickcheck::{quickcheck, Arbitrary, Gen};
 use rand::distributions::{Alphanumeric, DistString};
@@ -132,6 +132,10 @@ quickcheck! {
                         addr.push_back(Space::new("space")).unwrap();
                         prot.push_back(Space::CODE);
                     }
+                    Unix::CODE => {
+                        addr.push_back(Unix::new("/tmp/node.sock")).unwrap();
+                        prot.push_back(Unix::CODE);
+                    }
                     _ => unreachable!()
                 }
             }
@@ -153,6 +157,7 @@ const PROTOS: &[Code] = &[
     Node::CODE,
     Project::CODE,
     Space::CODE,
+    Unix::CODE,
 ];
 
 impl Arbitrary for Addr {
@@ -169,6 +174,7 @@ impl Arbitrary for Addr {
                 Project::CODE => a.push_back(Project::new(gen_string())).unwrap(),
                 Space::CODE => a.push_back(Space::new(gen_string())).unwrap(),
                 Node::CODE => a.push_back(Node::new(gen_string())).unwrap(),
+                Unix::CODE => a.push_back(Unix::new(gen_path())).unwrap(),
                 _ => unreachable!(),
             }
         }
@@ -220,3 +226,16 @@ fn gen_string() -> String {
     s.retain(|c| c != '/');
     s
 }
+
+fn gen_path() -> String {
+    format!("/tmp/{}/%{}.sock", gen_string(), gen_string())
+}
+
+#[test]
+fn unix_path() {
+    let a = MultiAddr::from_str("/unix/%2Ftmp%2Fnode%25.sock/service/api").unwrap();
+    let path = a.first().unwrap();
+    assert_eq!(&*path.cast::<Unix>().unwrap(), "/tmp/node%.sock");
+    assert_eq!(a.to_string(), "/unix/%2Ftmp%2Fnode%25.sock/service/api");
+    assert!(MultiAddr::from_str("/unix/%2").is_err());
+}
@@ -124,6 +124,15 @@ impl Client {
     where
         T: Encode<()>,
     {
+        // Let the other node continue the current trace
+        #[cfg(feature = "opentelemetry")]
+        let req = match crate::TracingContext::current() {
+            Some(tracing_context) if req.header().tracing_context().is_none() => {
+                req.tracing_context(tracing_context.to_string())
+            }
+            _ => req,
+        };
+
         let mut buf = Vec::new();
         req.encode(&mut buf)?;
         trace! {
@@ -5,7 +5,13 @@ pub type MessageReceiver<T> = crate::tokio::sync::mpsc::Receiver<T>;
 
 /// Create message channel
 pub fn message_channel<T>() -> (MessageSender<T>, MessageReceiver<T>) {
-    crate::tokio::sync::mpsc::channel(16)
+    message_channel_with_capacity(crate::DEFAULT_MAILBOX_CAPACITY)
+}
+
+/// Create message channel holding at most `capacity` messages.
+/// Sending a message on a full channel waits until a message is received
+pub fn message_channel_with_capacity<T>(capacity: usize) -> (MessageSender<T>, MessageReceiver<T>) {
+    crate::tokio::sync::mpsc::channel(capacity)
 }
 
 /// Router sender
@@ -1,6 +1,6 @@
 use crate::channel_types::{SmallReceiver, SmallSender};
 use crate::tokio::runtime::Handle;
-use crate::{error::*, AsyncDropSender, NodeMessage};
+use crate::{error::*, AsyncDropSender, NodeMessage, ResourceUsage, WorkerInfo};
 use core::sync::atomic::AtomicUsize;
 use ockam_core::compat::collections::HashMap;
 use ockam_core::compat::sync::{Arc, RwLock};
@@ -24,6 +24,8 @@ pub struct Context {
     pub(super) receiver: SmallReceiver<RelayMessage>,
     pub(super) async_drop_sender: Option<AsyncDropSender>,
     pub(super) mailbox_count: Arc<AtomicUsize>,
+    /// Maximum number of messages queued in the mailbox of this context and its children
+    pub(super) mailbox_capacity: usize,
     /// List of transports used to resolve external addresses to local workers in routes
     pub(super) transports: Arc<RwLock<HashMap<TransportType, Arc<dyn Transport>>>>,
     pub(super) flow_controls: FlowControls,
@@ -128,6 +130,39 @@ impl Context {
             .take_workers()
     }
 
+    /// Return the list of all workers and processors on a node, with
+    /// the number of messages routed to each of them
+    pub async fn list_workers_info(&self) -> Result<Vec<WorkerInfo>> {
+        let (msg, mut reply_rx) = NodeMessage::list_workers_info();
+
+        self.sender
+            .send(msg)
+            .await
+            .map_err(NodeError::from_send_err)?;
+
+        reply_rx
+            .recv()
+            .await
+            .ok_or_else(|| NodeError::NodeState(NodeReason::Unknown).internal())??
+            .take_workers_info()
+    }
+
+    /// Return the limits on the resources used by the node, and their current usage
+    pub async fn resource_usage(&self) -> Result<ResourceUsage> {
+        let (msg, mut reply_rx) = NodeMessage::resource_usage();
+
+        self.sender
+            .send(msg)
+            .await
+            .map_err(NodeError::from_send_err)?;
+
+        reply_rx
+            .recv()
+            .await
+            .ok_or_else(|| NodeError::NodeState(NodeReason::Unknown).internal())??
+            .take_resource_usage()
+    }
+
     /// Send a shutdown acknowledgement to the router
     pub(crate) async fn send_stop_ack(&self) -> Result<()> {
         self.sender
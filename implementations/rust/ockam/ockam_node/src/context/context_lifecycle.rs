@@ -11,7 +11,9 @@ use ockam_core::{
 use ockam_transport_core::Transport;
 
 use crate::async_drop::AsyncDrop;
-use crate::channel_types::{message_channel, small_channel, SmallReceiver, SmallSender};
+use crate::channel_types::{
+    message_channel_with_capacity, small_channel, SmallReceiver, SmallSender,
+};
 use crate::tokio::{self, runtime::Handle};
 use crate::{debugger, Context};
 use crate::{error::*, relay::CtrlSignal, router::SenderPair, NodeMessage};
@@ -62,8 +64,9 @@ impl Context {
         async_drop_sender: Option<AsyncDropSender>,
         transports: Arc<RwLock<HashMap<TransportType, Arc<dyn Transport>>>>,
         flow_controls: &FlowControls,
+        mailbox_capacity: usize,
     ) -> (Self, SenderPair, SmallReceiver<CtrlSignal>) {
-        let (mailbox_tx, receiver) = message_channel();
+        let (mailbox_tx, receiver) = message_channel_with_capacity(mailbox_capacity);
         let (ctrl_tx, ctrl_rx) = small_channel();
         (
             Self {
@@ -73,6 +76,7 @@ impl Context {
                 receiver,
                 async_drop_sender,
                 mailbox_count: Arc::new(0.into()),
+                mailbox_capacity,
                 transports,
                 flow_controls: flow_controls.clone(),
             },
@@ -95,6 +99,7 @@ impl Context {
             None,
             self.transports.clone(),
             &self.flow_controls,
+            self.mailbox_capacity,
         )
     }
 
@@ -110,6 +115,7 @@ impl Context {
             Some(drop_sender),
             self.transports.clone(),
             &self.flow_controls,
+            self.mailbox_capacity,
         )
     }
 
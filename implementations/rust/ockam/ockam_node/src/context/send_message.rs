@@ -228,6 +228,14 @@ impl Context {
             return Err(Error::new_without_cause(Origin::Node, Kind::Invalid));
         }
 
+        // Propagate the current trace to the worker receiving the message
+        #[cfg(feature = "opentelemetry")]
+        let local_info = if crate::TracingContext::is_enabled() {
+            crate::TracingContext::mark(local_info)
+        } else {
+            local_info
+        };
+
         // First resolve the next hop in the route
         let (reply_tx, mut reply_rx) = small_channel();
         let next = match route.next() {
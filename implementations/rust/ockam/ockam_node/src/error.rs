@@ -40,6 +40,11 @@ impl NodeError {
     pub fn conflict(self) -> Error {
         Error::new(Origin::Node, Kind::Conflict, self)
     }
+    /// Turn a NodeError into a Kind::ResourceExhausted ockam_core::Error
+    #[track_caller]
+    pub fn resource_exhausted(self) -> Error {
+        Error::new(Origin::Node, Kind::ResourceExhausted, self)
+    }
     /// Turn a NodeError into a Kind::Internal ockam_core::Error
     #[track_caller]
     pub fn internal(self) -> Error {
@@ -122,6 +127,8 @@ pub enum NodeReason {
     Shutdown,
     /// The node has been corrupted
     Corrupt,
+    /// The maximum number of workers of the node is reached
+    MaxWorkers,
 }
 
 impl fmt::Display for NodeReason {
@@ -133,6 +140,7 @@ impl fmt::Display for NodeReason {
                 Self::Unknown => "unknown node state",
                 Self::Shutdown => "ockam node is shutting down",
                 Self::Corrupt => "ockam node is corrupt and can not be recovered",
+                Self::MaxWorkers => "the maximum number of workers of the node is reached",
             }
         )
     }
@@ -4,7 +4,7 @@ use crate::channel_types::SmallSender;
 use crate::{
     router::{Router, SenderPair},
     tokio::runtime::{Handle, Runtime},
-    NodeMessage,
+    NodeLimits, NodeMessage,
 };
 use core::future::Future;
 use ockam_core::{Address, Result};
@@ -41,9 +41,9 @@ pub struct Executor {
 
 impl Executor {
     /// Create a new Ockam node [`Executor`] instance
-    pub fn new(flow_controls: &FlowControls) -> Self {
+    pub fn new(flow_controls: &FlowControls, limits: NodeLimits) -> Self {
         let rt = Runtime::new().unwrap();
-        let router = Router::new(flow_controls);
+        let router = Router::new(flow_controls, limits);
         #[cfg(feature = "metrics")]
         let metrics = Metrics::new(&rt, router.get_metrics_readout());
         Self {
@@ -54,12 +54,15 @@ mod context;
 mod delayed;
 mod error;
 mod executor;
+mod limits;
 mod messages;
 mod node;
 mod parser;
 mod processor_builder;
 mod relay;
 mod router;
+#[cfg(feature = "opentelemetry")]
+mod tracing_context;
 
 /// Support for storing persistent values
 pub mod storage;
@@ -69,13 +72,20 @@ pub use context::*;
 pub use delayed::*;
 pub use error::*;
 pub use executor::*;
+pub use limits::*;
 pub use messages::*;
 pub use processor_builder::ProcessorBuilder;
 pub use storage::*;
+#[cfg(feature = "opentelemetry")]
+pub use tracing_context::*;
 pub use worker_builder::WorkerBuilder;
 
 pub use node::{NodeBuilder, NullWorker};
 
+/// Target of the spans which only add some context to the log events, like the address
+/// of the worker handling a message. They are not exported as traces.
+pub const LOG_CONTEXT_TARGET: &str = "ockam_log_context";
+
 #[cfg(feature = "std")]
 use core::future::Future;
 #[cfg(feature = "std")]
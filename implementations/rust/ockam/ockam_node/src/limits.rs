@@ -0,0 +1,88 @@
+//! Limits on the resources used by a node.
+//!
+//! A node running as a relay or a portal for many clients can run out of memory if it accepts
+//! any number of workers, or if workers queue any number of messages. These limits are set with a
+//! [`NodeLimits`] on the [`NodeBuilder`](crate::NodeBuilder) or with environment variables:
+//!
+//!  - `OCKAM_MAX_WORKERS`: maximum number of workers and processors. When the limit is reached,
+//!    starting a new worker or processor fails with a `ResourceExhausted` error
+//!  - `OCKAM_MAILBOX_CAPACITY`: maximum number of messages queued in the mailbox of a worker.
+//!    When a mailbox is full, the senders of a message wait until the worker processes one of
+//!    its messages, which slows down the producers instead of buffering their messages
+#[cfg(feature = "std")]
+use ockam_core::env::get_env;
+
+/// Name of the environment variable setting the maximum number of workers and processors
+pub const OCKAM_MAX_WORKERS: &str = "OCKAM_MAX_WORKERS";
+
+/// Name of the environment variable setting the maximum number of messages in a mailbox
+pub const OCKAM_MAILBOX_CAPACITY: &str = "OCKAM_MAILBOX_CAPACITY";
+
+/// Default maximum number of messages queued in the mailbox of a worker
+pub const DEFAULT_MAILBOX_CAPACITY: usize = 16;
+
+/// Limits on the resources used by a node
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NodeLimits {
+    /// Maximum number of workers and processors running on the node.
+    /// Detached contexts are not counted
+    pub max_workers: Option<usize>,
+    /// Maximum number of messages queued in the mailbox of a worker
+    pub mailbox_capacity: usize,
+}
+
+impl Default for NodeLimits {
+    fn default() -> Self {
+        Self {
+            max_workers: None,
+            mailbox_capacity: DEFAULT_MAILBOX_CAPACITY,
+        }
+    }
+}
+
+impl NodeLimits {
+    /// Set the maximum number of workers and processors
+    pub fn with_max_workers(mut self, max_workers: usize) -> Self {
+        self.max_workers = Some(max_workers);
+        self
+    }
+
+    /// Set the maximum number of messages queued in the mailbox of a worker
+    pub fn with_mailbox_capacity(mut self, mailbox_capacity: usize) -> Self {
+        // a channel with a capacity of 0 can't be created
+        self.mailbox_capacity = mailbox_capacity.max(1);
+        self
+    }
+
+    /// Read the limits from the `OCKAM_MAX_WORKERS` and `OCKAM_MAILBOX_CAPACITY`
+    /// environment variables. Invalid values are ignored
+    #[cfg(feature = "std")]
+    pub fn from_env() -> Self {
+        let mut limits = Self::default();
+        if let Ok(Some(max_workers)) = get_env::<usize>(OCKAM_MAX_WORKERS) {
+            limits = limits.with_max_workers(max_workers);
+        }
+        if let Ok(Some(mailbox_capacity)) = get_env::<usize>(OCKAM_MAILBOX_CAPACITY) {
+            limits = limits.with_mailbox_capacity(mailbox_capacity);
+        }
+        limits
+    }
+
+    /// Without environment variables, the default limits are used
+    #[cfg(not(feature = "std"))]
+    pub fn from_env() -> Self {
+        Self::default()
+    }
+}
+
+/// Usage of the resources limited by the [`NodeLimits`] of a node
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ResourceUsage {
+    /// Limits of the node
+    pub limits: NodeLimits,
+    /// Number of workers and processors running on the node, detached contexts excluded
+    pub workers: usize,
+    /// Number of workers and processors which could not be started because
+    /// the maximum number of workers was reached
+    pub rejected_workers: u64,
+}
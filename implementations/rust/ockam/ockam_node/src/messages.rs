@@ -2,6 +2,7 @@ use crate::channel_types::{small_channel, MessageSender, SmallReceiver, SmallSen
 use crate::{
     error::{NodeError, NodeReason, RouterReason, WorkerReason},
     router::SenderPair,
+    ResourceUsage,
 };
 use core::{fmt, sync::atomic::AtomicUsize};
 use ockam_core::compat::{string::String, sync::Arc, vec::Vec};
@@ -25,6 +26,8 @@ pub enum NodeMessage {
     },
     /// Return a list of all worker addresses
     ListWorkers(SmallSender<NodeReplyResult>),
+    /// Return a list of all workers with their routing statistics
+    ListWorkersInfo(SmallSender<NodeReplyResult>),
     /// Add an existing address to a cluster
     SetCluster(Address, String, SmallSender<NodeReplyResult>),
     /// Stop an existing worker
@@ -47,6 +50,8 @@ pub enum NodeMessage {
     SetReady(Address),
     /// Check whether an address has been marked as "ready"
     CheckReady(Address, SmallSender<NodeReplyResult>),
+    /// Return the usage of the resources limited on the node
+    ResourceUsage(SmallSender<NodeReplyResult>),
 }
 
 impl fmt::Display for NodeMessage {
@@ -54,6 +59,7 @@ impl fmt::Display for NodeMessage {
         match self {
             NodeMessage::StartWorker { .. } => write!(f, "StartWorker"),
             NodeMessage::ListWorkers(_) => write!(f, "ListWorkers"),
+            NodeMessage::ListWorkersInfo(_) => write!(f, "ListWorkersInfo"),
             NodeMessage::SetCluster(_, _, _) => write!(f, "SetCluster"),
             NodeMessage::StopWorker(_, _, _) => write!(f, "StopWorker"),
             NodeMessage::StartProcessor(_, _, _) => write!(f, "StartProcessor"),
@@ -65,6 +71,7 @@ impl fmt::Display for NodeMessage {
             NodeMessage::Router(_, _, _) => write!(f, "Router"),
             NodeMessage::SetReady(_) => write!(f, "SetReady"),
             NodeMessage::CheckReady(_, _) => write!(f, "CheckReady"),
+            NodeMessage::ResourceUsage(_) => write!(f, "ResourceUsage"),
         }
     }
 }
@@ -118,6 +125,18 @@ impl NodeMessage {
         (Self::ListWorkers(tx), rx)
     }
 
+    /// Create a list workers info message and reply receiver
+    pub fn list_workers_info() -> (Self, SmallReceiver<NodeReplyResult>) {
+        let (tx, rx) = small_channel();
+        (Self::ListWorkersInfo(tx), rx)
+    }
+
+    /// Create a resource usage message and reply receiver
+    pub fn resource_usage() -> (Self, SmallReceiver<NodeReplyResult>) {
+        let (tx, rx) = small_channel();
+        (Self::ResourceUsage(tx), rx)
+    }
+
     /// Create a set cluster message and reply receiver
     pub fn set_cluster(addr: Address, label: String) -> (Self, SmallReceiver<NodeReplyResult>) {
         let (tx, rx) = small_channel();
@@ -164,6 +183,8 @@ pub enum RouterReply {
     Ok,
     /// A list of worker addresses
     Workers(Vec<Address>),
+    /// A list of workers with their routing statistics
+    WorkersInfo(Vec<WorkerInfo>),
     /// Message sender to a specific worker
     Sender {
         /// The address a message is being sent to
@@ -173,6 +194,23 @@ pub enum RouterReply {
     },
     /// Indicate the 'ready' state of an address
     State(bool),
+    /// Usage of the resources limited on the node
+    ResourceUsage(ResourceUsage),
+}
+
+/// Routing information about a worker or processor registered on a node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkerInfo {
+    /// Primary address of the worker
+    pub address: Address,
+    /// All the addresses of the worker, including the primary one
+    pub addresses: Vec<Address>,
+    /// True if this entry is a processor rather than a worker
+    pub processor: bool,
+    /// Number of messages currently waiting in the worker mailbox
+    pub mailbox_size: usize,
+    /// Number of messages routed to the worker since it was started
+    pub messages_count: u64,
 }
 
 /// Specify the type of node shutdown
@@ -253,6 +291,12 @@ impl RouterReply {
         Err(NodeError::NodeState(reason).conflict())
     }
 
+    /// Return [NodeError::NodeState] resource exhausted
+    #[track_caller]
+    pub fn limit_reached(reason: NodeReason) -> NodeReplyResult {
+        Err(NodeError::NodeState(reason).resource_exhausted())
+    }
+
     /// Return [NodeError::WorkerState] conflict
     #[track_caller]
     pub fn worker_rejected(reason: WorkerReason) -> NodeReplyResult {
@@ -264,6 +308,16 @@ impl RouterReply {
         Ok(Self::Workers(v))
     }
 
+    /// Return [RouterReply::WorkersInfo] for the given workers
+    pub fn workers_info(v: Vec<WorkerInfo>) -> NodeReplyResult {
+        Ok(Self::WorkersInfo(v))
+    }
+
+    /// Return [RouterReply::ResourceUsage] for the given usage
+    pub fn resource_usage(usage: ResourceUsage) -> NodeReplyResult {
+        Ok(Self::ResourceUsage(usage))
+    }
+
     /// Return [RouterReply::Sender] for the given information
     pub fn sender(addr: Address, sender: MessageSender<RelayMessage>) -> NodeReplyResult {
         Ok(RouterReply::Sender { addr, sender })
@@ -285,6 +339,22 @@ impl RouterReply {
         }
     }
 
+    /// Consume the wrapper and return [RouterReply::WorkersInfo]
+    pub fn take_workers_info(self) -> Result<Vec<WorkerInfo>> {
+        match self {
+            Self::WorkersInfo(w) => Ok(w),
+            _ => Err(NodeError::NodeState(NodeReason::Unknown).internal()),
+        }
+    }
+
+    /// Consume the wrapper and return [RouterReply::ResourceUsage]
+    pub fn take_resource_usage(self) -> Result<ResourceUsage> {
+        match self {
+            Self::ResourceUsage(usage) => Ok(usage),
+            _ => Err(NodeError::NodeState(NodeReason::Unknown).internal()),
+        }
+    }
+
     /// Consume the wrapper and return [RouterReply::State]
     pub fn take_state(self) -> Result<bool> {
         match self {
@@ -2,7 +2,7 @@ use ockam_core::compat::sync::Arc;
 use ockam_core::flow_control::FlowControls;
 use ockam_core::{Address, AllowAll, Mailbox, Mailboxes};
 
-use crate::{debugger, Context, Executor};
+use crate::{debugger, Context, Executor, NodeLimits};
 
 /// A minimal worker implementation that does nothing
 pub struct NullWorker;
@@ -20,6 +20,7 @@ impl ockam_core::Worker for NullWorker {
 pub struct NodeBuilder {
     logging: bool,
     exit_on_panic: bool,
+    limits: Option<NodeLimits>,
 }
 
 impl Default for NodeBuilder {
@@ -34,6 +35,7 @@ impl NodeBuilder {
         Self {
             logging: true,
             exit_on_panic: true,
+            limits: None,
         }
     }
 
@@ -41,15 +43,24 @@ impl NodeBuilder {
     pub fn no_logging(self) -> Self {
         Self {
             logging: false,
-            exit_on_panic: self.exit_on_panic,
+            ..self
         }
     }
 
     /// Disable exit on panic on this node
     pub fn no_exit_on_panic(self) -> Self {
         Self {
-            logging: self.logging,
             exit_on_panic: false,
+            ..self
+        }
+    }
+
+    /// Set the limits on the resources used by this node.
+    /// The limits are read from the environment by default, see [`NodeLimits::from_env`]
+    pub fn with_limits(self, limits: NodeLimits) -> Self {
+        Self {
+            limits: Some(limits),
+            ..self
         }
     }
 
@@ -83,7 +94,8 @@ impl NodeBuilder {
         // Shared instance of FlowControls
         let flow_controls = FlowControls::new();
 
-        let mut exe = Executor::new(&flow_controls);
+        let limits = self.limits.unwrap_or_else(NodeLimits::from_env);
+        let mut exe = Executor::new(&flow_controls, limits);
         let addr: Address = "app".into();
 
         // The root application worker needs a mailbox and relay to accept
@@ -98,6 +110,7 @@ impl NodeBuilder {
             None,
             Default::default(),
             &flow_controls,
+            limits.mailbox_capacity,
         );
 
         debugger::log_inherit_context("NODE", &ctx, &ctx);
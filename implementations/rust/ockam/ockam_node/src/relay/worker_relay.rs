@@ -71,12 +71,45 @@ where
 
         // Call the worker handle function - pass errors up
         let routed = Self::wrap_direct_message(relay_msg)?;
+
+        #[cfg(feature = "std")]
+        {
+            use tracing::Instrument;
+            let span = self.handle_message_span(&routed);
+            self.worker
+                .handle_message(&mut self.ctx, routed)
+                .instrument(span)
+                .await?;
+        }
+        #[cfg(not(feature = "std"))]
         self.worker.handle_message(&mut self.ctx, routed).await?;
 
         // Signal to the outer loop that we would like to run again
         Ok(true)
     }
 
+    /// Return the span in which a message is handled
+    #[cfg(feature = "std")]
+    #[cfg_attr(not(feature = "opentelemetry"), allow(unused_variables))]
+    fn handle_message_span(&self, routed: &Routed<M>) -> tracing::Span {
+        // Continue the trace of the sender, if there is one
+        #[cfg(feature = "opentelemetry")]
+        if crate::TracingContext::is_enabled() {
+            if let Some(tracing_context) = crate::TracingContext::find_info(routed.local_message())
+            {
+                let span = info_span!("handle_message", address = %self.ctx.address());
+                tracing_context.set_as_parent_of(&span);
+                return span;
+            }
+        }
+        // Otherwise only add the address of the worker to its log events
+        info_span!(
+            target: crate::LOG_CONTEXT_TARGET,
+            "worker",
+            address = %self.ctx.address()
+        )
+    }
+
     #[cfg_attr(not(feature = "std"), allow(unused_mut))]
     #[cfg_attr(not(feature = "std"), allow(unused_variables))]
     async fn run(mut self, mut ctrl_rx: SmallReceiver<CtrlSignal>) {
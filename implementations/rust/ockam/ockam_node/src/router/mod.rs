@@ -17,7 +17,7 @@ use crate::channel_types::{router_channel, MessageSender, RouterReceiver, SmallS
 use crate::{
     error::{NodeError, NodeReason},
     relay::CtrlSignal,
-    NodeMessage, NodeReplyResult, RouterReply, ShutdownType,
+    NodeLimits, NodeMessage, NodeReplyResult, ResourceUsage, RouterReply, ShutdownType,
 };
 use ockam_core::compat::{collections::BTreeMap, sync::Arc};
 use ockam_core::flow_control::FlowControls;
@@ -48,6 +48,10 @@ pub struct Router {
     external: BTreeMap<TransportType, Address>,
     /// Receiver for messages from node
     receiver: Option<RouterReceiver<NodeMessage>>,
+    /// Limits on the resources used by the node
+    limits: NodeLimits,
+    /// Number of workers and processors rejected because of the limits
+    rejected_workers: u64,
 }
 
 enum RouteType {
@@ -64,13 +68,15 @@ fn determine_type(next: &Address) -> RouteType {
 }
 
 impl Router {
-    pub fn new(flow_controls: &FlowControls) -> Self {
+    pub fn new(flow_controls: &FlowControls, limits: NodeLimits) -> Self {
         let (sender, receiver) = router_channel();
         Self {
             state: RouterState::new(sender),
             map: InternalMap::new(flow_controls),
             external: BTreeMap::new(),
             receiver: Some(receiver),
+            limits,
+            rejected_workers: 0,
         }
     }
 
@@ -145,6 +151,31 @@ impl Router {
         }
     }
 
+    /// Reject the start of a worker or processor if the maximum number of workers is reached
+    async fn check_workers_limit(&mut self, reply: &SmallSender<NodeReplyResult>) -> Result<()> {
+        match self.limits.max_workers {
+            Some(max_workers) if self.map.workers_count() >= max_workers => {
+                self.rejected_workers += 1;
+                warn!("Cannot start a new worker: the maximum of {max_workers} workers is reached");
+                reply
+                    .send(RouterReply::limit_reached(NodeReason::MaxWorkers))
+                    .await
+                    .map_err(|_| NodeError::NodeState(NodeReason::Unknown).internal())?;
+
+                Err(NodeError::NodeState(NodeReason::MaxWorkers).resource_exhausted())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn resource_usage(&self) -> ResourceUsage {
+        ResourceUsage {
+            limits: self.limits,
+            workers: self.map.workers_count(),
+            rejected_workers: self.rejected_workers,
+        }
+    }
+
     async fn handle_msg(&mut self, msg: NodeMessage) -> Result<bool> {
         #[cfg(feature = "metrics")]
         self.map.update_metrics(); // Possibly remove this from the hot path?
@@ -249,6 +280,16 @@ impl Router {
                 .await
                 .map_err(|_| NodeError::NodeState(NodeReason::Unknown).internal())?,
 
+            ListWorkersInfo(sender) => sender
+                .send(RouterReply::workers_info(self.map.workers_info()))
+                .await
+                .map_err(|_| NodeError::NodeState(NodeReason::Unknown).internal())?,
+
+            NodeMessage::ResourceUsage(sender) => sender
+                .send(RouterReply::resource_usage(self.resource_usage()))
+                .await
+                .map_err(|_| NodeError::NodeState(NodeReason::Unknown).internal())?,
+
             SetCluster(addr, label, reply) => {
                 debug!("Setting cluster on address {}", addr);
                 let msg = self.map.set_cluster(label, addr);
@@ -2,7 +2,7 @@ use crate::channel_types::{MessageSender, SmallSender};
 use crate::relay::CtrlSignal;
 use crate::{
     error::{NodeError, NodeReason},
-    NodeReplyResult, RouterReply,
+    NodeReplyResult, RouterReply, WorkerInfo,
 };
 use core::sync::atomic::{AtomicUsize, Ordering};
 use ockam_core::{
@@ -55,10 +55,6 @@ impl InternalMap {
         self.address_records_map.clear()
     }
 
-    pub(super) fn get_address_record(&self, primary_address: &Address) -> Option<&AddressRecord> {
-        self.address_records_map.get(primary_address)
-    }
-
     pub(super) fn get_address_record_mut(
         &mut self,
         primary_address: &Address,
@@ -120,6 +116,22 @@ impl InternalMap {
         self.metrics.0.load(Ordering::Acquire)
     }
 
+    /// Return the number of workers and processors, detached contexts excluded
+    pub(super) fn workers_count(&self) -> usize {
+        self.address_records_map
+            .values()
+            .filter(|record| !record.meta.detached)
+            .count()
+    }
+
+    /// Return routing information for all the registered workers and processors
+    pub(super) fn workers_info(&self) -> Vec<WorkerInfo> {
+        self.address_records_map
+            .iter()
+            .map(|(primary, record)| record.info(primary))
+            .collect()
+    }
+
     /// Add an address to a particular cluster
     pub(super) fn set_cluster(&mut self, label: String, primary: Address) -> NodeReplyResult {
         let rec = self
@@ -250,6 +262,7 @@ pub struct AddressRecord {
     ready: ReadyState,
     meta: AddressMeta,
     msg_count: Arc<AtomicUsize>,
+    routed_count: u64,
 }
 
 impl AddressRecord {
@@ -279,12 +292,25 @@ impl AddressRecord {
             state: AddressState::Running,
             ready: ReadyState::Initialising(vec![]),
             msg_count,
+            routed_count: 0,
             meta,
         }
     }
 
-    pub fn increment_msg_count(&self) {
+    pub fn increment_msg_count(&mut self) {
         self.msg_count.fetch_add(1, Ordering::Acquire);
+        self.routed_count += 1;
+    }
+
+    /// Return a summary of this record, identified by its primary address
+    pub fn info(&self, primary_address: &Address) -> WorkerInfo {
+        WorkerInfo {
+            address: primary_address.clone(),
+            addresses: self.address_set.clone(),
+            processor: self.meta.processor,
+            mailbox_size: self.msg_count.load(Ordering::Acquire),
+            messages_count: self.routed_count,
+        }
     }
 
     /// Signal this worker to stop -- it will no longer be able to receive messages
@@ -384,6 +410,28 @@ mod test {
         assert_eq!(map.next_cluster(), None);
     }
 
+    #[test]
+    fn test_workers_info() {
+        let mut map = InternalMap::new(&FlowControls::new());
+        map.address_records_map
+            .insert("address1".into(), create_address_record("address1"));
+        map.address_records_map
+            .insert("address2".into(), create_address_record("address2"));
+
+        let record = map.get_address_record_mut(&"address1".into()).unwrap();
+        record.increment_msg_count();
+        record.increment_msg_count();
+
+        let info = map.workers_info();
+        assert_eq!(info.len(), 2);
+        assert_eq!(info[0].address, "address1".into());
+        assert_eq!(info[0].messages_count, 2);
+        assert_eq!(info[0].mailbox_size, 3);
+        assert!(!info[0].processor);
+        assert_eq!(info[1].address, "address2".into());
+        assert_eq!(info[1].messages_count, 0);
+    }
+
     /// HELPERS
     fn create_address_record(primary: &str) -> AddressRecord {
         let (tx1, _) = small_channel();
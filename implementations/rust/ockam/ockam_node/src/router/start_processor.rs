@@ -30,6 +30,7 @@ async fn start(
     reply: &SmallSender<NodeReplyResult>,
 ) -> Result<()> {
     router.check_addr_not_exist(&addr, reply).await?;
+    router.check_workers_limit(reply).await?;
 
     debug!("Starting new processor '{}'", &addr);
 
@@ -42,6 +42,9 @@ async fn start(
         .ok_or_else(|| NodeError::RouterState(RouterReason::EmptyAddressSet).internal())?;
 
     router.check_addr_not_exist(primary_addr, reply).await?;
+    if !detached {
+        router.check_workers_limit(reply).await?;
+    }
 
     debug!("Starting new worker '{}'", primary_addr);
 
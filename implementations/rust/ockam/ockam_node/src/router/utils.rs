@@ -30,7 +30,7 @@ pub(super) async fn resolve(
         return Ok(());
     };
 
-    match router.map.get_address_record(&primary_address) {
+    match router.map.get_address_record_mut(&primary_address) {
         Some(record) if record.check() => {
             trace!("{} OK", base);
             record.increment_msg_count();
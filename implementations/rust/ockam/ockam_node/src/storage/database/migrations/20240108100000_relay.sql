@@ -0,0 +1,13 @@
+-- This table stores the definition of the relays created by a node
+-- so that they can be recreated when the node is restarted
+CREATE TABLE relay
+(
+    node_name      TEXT    NOT NULL, -- Name of the node which created the relay
+    alias          TEXT    NOT NULL, -- Relay alias
+    remote_address TEXT    NOT NULL, -- Address of the relay on the remote node
+    address        TEXT    NOT NULL, -- Route to the node where the relay is created
+    at_rust_node   INTEGER NOT NULL, -- boolean indicating if the relay service is on a Rust node (0 means true)
+    authorized     TEXT              -- optional identifier of the identity authorized to create a secure channel for the relay
+);
+
+CREATE UNIQUE INDEX relay_index ON relay (node_name, alias);
@@ -0,0 +1,12 @@
+-- This table stores the registrations received by the relay service of a node
+-- so that the relays can be recreated when the node is restarted
+CREATE TABLE relay_registration
+(
+    node_name  TEXT NOT NULL, -- Name of the node hosting the relay service
+    alias      TEXT NOT NULL, -- Relay alias
+    route      BLOB NOT NULL, -- Serialized return route of the registration message
+    identifier TEXT,          -- optional identifier of the registrant, when it registered through a secure channel
+    policy     TEXT NOT NULL  -- Alias conflict policy applied to the registration
+);
+
+CREATE INDEX relay_registration_index ON relay_registration (node_name, alias);
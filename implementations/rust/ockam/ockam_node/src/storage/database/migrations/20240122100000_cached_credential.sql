@@ -0,0 +1,11 @@
+-- This table stores the credentials retrieved from an issuer by a caching credentials retriever
+-- so that they can be reused until they expire
+CREATE TABLE cached_credential
+(
+    issuer     TEXT    NOT NULL, -- Identifier of the credential issuer
+    subject    TEXT    NOT NULL, -- Identifier of the identity the credential was issued for
+    credential BLOB    NOT NULL, -- Serialized credential and purpose key attestation
+    expires_at INTEGER NOT NULL  -- UNIX timestamp in seconds: when the credential expires
+);
+
+CREATE UNIQUE INDEX cached_credential_index ON cached_credential (issuer, subject);
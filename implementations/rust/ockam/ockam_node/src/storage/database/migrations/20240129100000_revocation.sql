@@ -0,0 +1,10 @@
+-- This table stores the revocations published by credential issuers
+-- All the credentials issued to a subject before the revocation time are revoked
+CREATE TABLE revocation
+(
+    issuer     TEXT    NOT NULL, -- Identifier of the credential issuer
+    subject    TEXT    NOT NULL, -- Identifier of the subject of the revoked credentials
+    revoked_at INTEGER NOT NULL  -- UNIX timestamp in seconds: credentials created until then are revoked
+);
+
+CREATE UNIQUE INDEX revocation_index ON revocation (issuer, subject);
@@ -0,0 +1,3 @@
+-- Attributes which expire before the rest of the attributes of an identity, for example
+-- short-lived attributes in a longer-lived credential
+ALTER TABLE identity_attributes ADD COLUMN attributes_expires BLOB; -- optional serialized map of attribute names and UNIX timestamps in seconds: when those attributes expire
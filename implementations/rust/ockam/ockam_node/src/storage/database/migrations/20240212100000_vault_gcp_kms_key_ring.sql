@@ -0,0 +1,2 @@
+-- Vaults can store their signing keys in a Google Cloud KMS key ring instead of AWS KMS
+ALTER TABLE vault ADD COLUMN gcp_kms_key_ring TEXT; -- optional key ring used by a KMS vault: projects/{project}/locations/{location}/keyRings/{key_ring}
@@ -0,0 +1,6 @@
+-- The secrets of a vault can be encrypted with a passphrase
+CREATE TABLE secrets_encryption_key
+(
+    salt          BLOB NOT NULL, -- salt used to derive a key from the passphrase
+    encrypted_key BLOB NOT NULL  -- key used to encrypt the secrets, encrypted with the key derived from the passphrase
+);
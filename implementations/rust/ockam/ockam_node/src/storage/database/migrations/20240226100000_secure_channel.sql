@@ -0,0 +1,26 @@
+-- This table stores the keys of persistent secure channels
+CREATE TABLE aead_secret
+(
+    handle BLOB PRIMARY KEY, -- Secret handle
+    secret BLOB NOT NULL     -- Secret binary
+);
+
+-- This table stores the state of the secure channels which can be resumed after a restart
+CREATE TABLE secure_channel
+(
+    encryptor_address          TEXT PRIMARY KEY, -- Local address of the channel encryptor
+    encryptor_api_address      TEXT    NOT NULL, -- Local address used to encrypt messages via the API
+    encryptor_internal_address TEXT    NOT NULL, -- Local address used for internal messages to the encryptor
+    decryptor_remote_address   TEXT    NOT NULL, -- Address receiving the messages of the other side
+    decryptor_api_address      TEXT    NOT NULL, -- Local address used to decrypt messages via the API
+    decryptor_internal_address TEXT    NOT NULL, -- Local address used to forward decrypted messages
+    role                       TEXT    NOT NULL, -- initiator or responder
+    my_identifier              TEXT    NOT NULL, -- Identifier of the local identity
+    their_identifier           TEXT    NOT NULL, -- Identifier of the identity on the other side
+    their_decryptor_address    TEXT    NOT NULL, -- Address of the decryptor on the other side
+    encryption_key             BLOB    NOT NULL, -- Handle to the current encryption key in the vault
+    encryption_nonce           INTEGER NOT NULL, -- First nonce used with the current encryption key
+    decryption_key             BLOB    NOT NULL, -- Handle to the current decryption key in the vault
+    previous_decryption_key    BLOB,             -- Handle to the previous decryption key in the vault
+    decryption_rekeys          INTEGER NOT NULL  -- Number of key renewals for the decryption key
+);
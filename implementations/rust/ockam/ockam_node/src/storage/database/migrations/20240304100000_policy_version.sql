@@ -0,0 +1,13 @@
+-- This table stores the history of the changes made to the policies.
+-- Each time a policy is set or deleted a new version is added for its resource and action
+CREATE TABLE policy_version
+(
+    resource   TEXT    NOT NULL, -- resource name
+    action     TEXT    NOT NULL, -- action name
+    version    INTEGER NOT NULL, -- version number, starting at 1 for each resource and action
+    expression BLOB,             -- encoded expression, NULL when the policy was deleted
+    changed_by TEXT,             -- optional identifier of the identity which made the change
+    changed_at INTEGER NOT NULL  -- UNIX timestamp in seconds of the change
+);
+
+CREATE UNIQUE INDEX policy_version_index ON policy_version (resource, action, version);
@@ -0,0 +1,9 @@
+-- This table stores the creation time of the last revocation list imported from each issuer
+-- so that an older revocation list can't be imported again
+CREATE TABLE revocation_list
+(
+    issuer     TEXT    NOT NULL, -- Identifier of the credential issuer
+    created_at INTEGER NOT NULL  -- UNIX timestamp in seconds: creation time of the revocation list
+);
+
+CREATE UNIQUE INDEX revocation_list_index ON revocation_list (issuer);
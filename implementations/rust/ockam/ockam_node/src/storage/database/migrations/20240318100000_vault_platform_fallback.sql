@@ -0,0 +1,2 @@
+-- Vaults can store their signing keys in the secure hardware of the platform (Secure Enclave or TPM)
+ALTER TABLE vault ADD COLUMN platform_fallback INTEGER; -- set for a platform vault only: 1 if keys can be stored in the vault database when the secure hardware can't be used, 0 otherwise
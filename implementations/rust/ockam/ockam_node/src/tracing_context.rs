@@ -0,0 +1,156 @@
+use core::fmt::{Display, Formatter};
+use core::sync::atomic::{AtomicBool, Ordering};
+use ockam_core::compat::string::{String, ToString};
+use ockam_core::compat::vec::Vec;
+use ockam_core::{LocalInfo, LocalMessage};
+use opentelemetry::trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState};
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// TracingContext LocalInfo unique Identifier
+pub const TRACING_CONTEXT_IDENTIFIER: &str = "TRACING_CONTEXT_IDENTIFIER";
+
+/// True when the spans are exported, so that the trace context is worth propagating
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Trace context of a span, written as a W3C `traceparent`: `00-<trace id>-<span id>-<flags>`.
+///
+/// It is attached to the local info of the messages sent by a worker, and to the headers of the
+/// API requests sent to other nodes, so that the spans created when handling a message are
+/// children of the span which sent it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TracingContext(String);
+
+impl TracingContext {
+    /// Propagate the trace context with the messages. This is called once an
+    /// OpenTelemetry layer is installed, until then there is no trace to propagate.
+    pub fn enable() {
+        ENABLED.store(true, Ordering::Relaxed);
+    }
+
+    /// Return true if the trace context is propagated with the messages
+    pub fn is_enabled() -> bool {
+        ENABLED.load(Ordering::Relaxed)
+    }
+
+    /// Return the trace context of the current span, if it is recorded by an OpenTelemetry layer
+    pub fn current() -> Option<Self> {
+        if !Self::is_enabled() {
+            return None;
+        }
+        Self::of(&Span::current())
+    }
+
+    /// Return the trace context of a span, if it is recorded by an OpenTelemetry layer
+    pub fn of(span: &Span) -> Option<Self> {
+        let context = span.context();
+        let span_context = context.span().span_context().clone();
+        if !span_context.is_valid() {
+            return None;
+        }
+        Some(Self(format!(
+            "00-{}-{}-{:02x}",
+            span_context.trace_id(),
+            span_context.span_id(),
+            span_context.trace_flags().to_u8()
+        )))
+    }
+
+    /// Parse a `traceparent` value
+    pub fn parse(traceparent: &str) -> Option<Self> {
+        Self::span_context(traceparent).map(|_| Self(traceparent.to_string()))
+    }
+
+    /// Make this context the parent of a span
+    pub fn set_as_parent_of(&self, span: &Span) {
+        if let Some(span_context) = Self::span_context(&self.0) {
+            span.set_parent(opentelemetry::Context::new().with_remote_span_context(span_context));
+        }
+    }
+
+    /// Encode the context as a `LocalInfo`
+    pub fn to_local_info(&self) -> LocalInfo {
+        LocalInfo::new(
+            TRACING_CONTEXT_IDENTIFIER.into(),
+            self.0.as_bytes().to_vec(),
+        )
+    }
+
+    /// Find a `TracingContext` in the local info of a message
+    pub fn find_info(local_msg: &LocalMessage) -> Option<Self> {
+        Self::find_info_from_list(local_msg.local_info())
+    }
+
+    /// Find a `TracingContext` in a list of `LocalInfo`
+    pub fn find_info_from_list(local_info: &[LocalInfo]) -> Option<Self> {
+        local_info
+            .iter()
+            .find(|x| x.type_identifier() == TRACING_CONTEXT_IDENTIFIER)
+            .and_then(|x| core::str::from_utf8(x.data()).ok())
+            .and_then(Self::parse)
+    }
+
+    /// Mark a `LocalInfo` vector with the trace context of the current span, unless
+    /// it already contains a trace context
+    pub fn mark(mut local_info: Vec<LocalInfo>) -> Vec<LocalInfo> {
+        if Self::find_info_from_list(&local_info).is_none() {
+            if let Some(context) = Self::current() {
+                local_info.push(context.to_local_info());
+            }
+        }
+        local_info
+    }
+
+    fn span_context(traceparent: &str) -> Option<SpanContext> {
+        let mut parts = traceparent.split('-');
+        let (Some("00"), Some(trace_id), Some(span_id), Some(flags), None) = (
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+        ) else {
+            return None;
+        };
+        let span_context = SpanContext::new(
+            TraceId::from_hex(trace_id).ok()?,
+            SpanId::from_hex(span_id).ok()?,
+            TraceFlags::new(u8::from_str_radix(flags, 16).ok()?),
+            true,
+            TraceState::default(),
+        );
+        span_context.is_valid().then_some(span_context)
+    }
+}
+
+impl Display for TracingContext {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_traceparent() {
+        let traceparent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let context = TracingContext::parse(traceparent).unwrap();
+        assert_eq!(context.to_string(), traceparent);
+        assert_eq!(
+            TracingContext::find_info_from_list(&[context.to_local_info()]),
+            Some(context)
+        );
+
+        for invalid in [
+            "",
+            "01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-00",
+        ] {
+            assert_eq!(TracingContext::parse(invalid), None, "{invalid}");
+        }
+    }
+}
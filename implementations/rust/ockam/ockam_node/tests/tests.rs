@@ -9,7 +9,7 @@ use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{async_trait, Address, AllowAll, Any, Decodable, DenyAll, Message, LOCAL};
 use ockam_core::{route, Processor, Result, Routed, Worker};
 use ockam_node::compat::futures::FutureExt;
-use ockam_node::{Context, MessageReceiveOptions, NodeBuilder};
+use ockam_node::{Context, MessageReceiveOptions, NodeBuilder, NodeLimits};
 use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicI8, AtomicU32};
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -610,3 +610,35 @@ async fn message_handle__error_during_handling__keep_worker_running(
 
     ctx.stop().await
 }
+
+#[allow(non_snake_case)]
+#[test]
+fn start_worker__max_workers_reached__should_fail() {
+    let (ctx, mut executor) = NodeBuilder::new()
+        .with_limits(NodeLimits::default().with_max_workers(1))
+        .build();
+    executor
+        .execute(async move {
+            let res = std::panic::AssertUnwindSafe(async {
+                ctx.start_worker("worker1", DummyWorker).await?;
+                let err = ctx.start_worker("worker2", DummyWorker).await.unwrap_err();
+                assert_eq!(err.code().kind, Kind::ResourceExhausted);
+
+                // detached contexts are not limited
+                let _child_ctx = ctx.new_detached("child", AllowAll, AllowAll).await?;
+
+                let usage = ctx.resource_usage().await?;
+                assert_eq!(usage.workers, 1);
+                assert_eq!(usage.rejected_workers, 1);
+                Result::<()>::Ok(())
+            })
+            .catch_unwind()
+            .await;
+
+            ctx.stop().await?;
+
+            res.unwrap()
+        })
+        .unwrap()
+        .unwrap()
+}
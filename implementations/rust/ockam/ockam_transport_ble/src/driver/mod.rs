@@ -13,6 +13,9 @@ pub mod bluetooth_hci;
 ))]
 pub mod btleplug;
 
+/// support for serial (UART) links
+pub mod serial;
+
 #[cfg(not(feature = "std"))]
 mod mutex;
 mod packet;
@@ -0,0 +1,345 @@
+//! Driver for serial (UART) links
+//!
+//! Frames every buffer written by the transport with SLIP (RFC 1055)
+//! so that the same fragmented packets sent over BLE characteristics
+//! can be sent over a raw byte stream. A serial link is point to
+//! point, so there is nothing to scan for or advertise.
+//!
+//! Implement [`SerialPort`] on top of the UART peripheral of your
+//! HAL, or of a serial port device of your OS, and use the
+//! [`SerialAdapter`] with a [`BleClient`](crate::BleClient) or a
+//! [`BleServer`](crate::BleServer).
+
+use ockam_core::compat::boxed::Box;
+use ockam_core::{async_trait, Result};
+
+use crate::driver::{BleClientDriver, BleEvent, BleServerDriver, BleStreamDriver};
+use crate::error::BleError;
+use crate::BleAddr;
+
+/// Marks the end of a frame
+const END: u8 = 0xC0;
+/// Escapes END and ESC bytes in the frame contents
+const ESC: u8 = 0xDB;
+/// Escaped END byte
+const ESC_END: u8 = 0xDC;
+/// Escaped ESC byte
+const ESC_ESC: u8 = 0xDD;
+
+/// Size of the buffer holding the bytes read from the serial port
+const READ_BUFFER_LENGTH: usize = 64;
+
+/// Implement the SerialPort trait to transmit and receive bytes over
+/// your UART hardware
+#[async_trait]
+pub trait SerialPort {
+    /// Read at least one byte into the buffer and return the number
+    /// of bytes read, or 0 if the link is closed
+    async fn read(&mut self, buffer: &mut [u8]) -> Result<usize>;
+
+    /// Write all the bytes of the buffer
+    async fn write_all(&mut self, buffer: &[u8]) -> Result<()>;
+}
+
+/// SerialAdapter frames the packets of the transport with SLIP over
+/// a [`SerialPort`]
+pub struct SerialAdapter<P> {
+    port: P,
+    connected: bool,
+    read_buffer: [u8; READ_BUFFER_LENGTH],
+    read_start: usize,
+    read_end: usize,
+}
+
+impl<P> SerialAdapter<P>
+where
+    P: SerialPort + Send,
+{
+    /// Create an adapter sending and receiving frames over the given
+    /// serial port, which must already be opened and configured
+    /// (baud rate, parity, ...) the same way on both ends of the link
+    pub fn new(port: P) -> Self {
+        Self {
+            port,
+            connected: false,
+            read_buffer: [0_u8; READ_BUFFER_LENGTH],
+            read_start: 0,
+            read_end: 0,
+        }
+    }
+
+    /// Return the next byte received on the serial port, or None if
+    /// the link is closed
+    async fn next_byte(&mut self) -> Result<Option<u8>> {
+        if self.read_start == self.read_end {
+            let len = self.port.read(&mut self.read_buffer).await?;
+            if len == 0 {
+                return Ok(None);
+            }
+            self.read_start = 0;
+            self.read_end = len;
+        }
+
+        let byte = self.read_buffer[self.read_start];
+        self.read_start += 1;
+        Ok(Some(byte))
+    }
+}
+
+#[async_trait]
+impl<P> BleClientDriver for SerialAdapter<P>
+where
+    P: SerialPort + Send,
+{
+    async fn scan(&mut self, ble_addr: &BleAddr) -> Result<()> {
+        debug!("SerialAdapter::scan nothing to scan for: {}", ble_addr);
+        Ok(())
+    }
+
+    async fn connect(&mut self) -> Result<()> {
+        self.connected = true;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<P> BleServerDriver for SerialAdapter<P>
+where
+    P: SerialPort + Send,
+{
+    async fn bind(&mut self, ble_addr: &BleAddr) -> Result<()> {
+        debug!("SerialAdapter::bind serial link bound to: {}", ble_addr);
+        Ok(())
+    }
+
+    async fn start_advertising(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<P> BleStreamDriver for SerialAdapter<P>
+where
+    P: SerialPort + Send,
+{
+    async fn poll<'b>(&mut self, buffer: &'b mut [u8]) -> Result<BleEvent<'b>> {
+        // the link is up as soon as the listener starts polling it
+        if !self.connected {
+            self.connected = true;
+            return Ok(BleEvent::ConnectionComplete);
+        }
+
+        let mut len = 0;
+        let mut escaped = false;
+        let mut overflow = false;
+        loop {
+            let byte = match self.next_byte().await? {
+                Some(byte) => byte,
+                None => {
+                    self.connected = false;
+                    return Ok(BleEvent::DisconnectionComplete);
+                }
+            };
+
+            let byte = match (escaped, byte) {
+                (false, END) if len == 0 && !overflow => continue, // skip empty frames
+                (false, END) if overflow => {
+                    error!("SerialAdapter::poll dropped a frame larger than the buffer");
+                    return Ok(BleEvent::Unknown);
+                }
+                (false, END) => return Ok(BleEvent::Received(&buffer[..len])),
+                (false, ESC) => {
+                    escaped = true;
+                    continue;
+                }
+                (true, ESC_END) => END,
+                (true, ESC_ESC) => ESC,
+                (true, byte) => {
+                    warn!("SerialAdapter::poll invalid escape sequence: {:#x}", byte);
+                    byte
+                }
+                (false, byte) => byte,
+            };
+            escaped = false;
+
+            if len < buffer.len() {
+                buffer[len] = byte;
+                len += 1;
+            } else {
+                overflow = true;
+            }
+        }
+    }
+
+    async fn write(&mut self, buffer: &[u8]) -> Result<()> {
+        // a frame has at most twice as many bytes as the buffer when every byte is escaped
+        let mut frame = [0_u8; 2 * crate::driver::CHARACTERISTIC_VALUE_LENGTH + 2];
+        if buffer.len() > crate::driver::CHARACTERISTIC_VALUE_LENGTH {
+            error!(
+                "SerialAdapter::write buffer too long: {} > {}",
+                buffer.len(),
+                crate::driver::CHARACTERISTIC_VALUE_LENGTH
+            );
+            return Err(BleError::WriteError)?;
+        }
+
+        // a leading END flushes any line noise received by the peer
+        let mut len = 0;
+        frame[len] = END;
+        len += 1;
+        for byte in buffer {
+            match *byte {
+                END => {
+                    frame[len..len + 2].copy_from_slice(&[ESC, ESC_END]);
+                    len += 2;
+                }
+                ESC => {
+                    frame[len..len + 2].copy_from_slice(&[ESC, ESC_ESC]);
+                    len += 2;
+                }
+                byte => {
+                    frame[len] = byte;
+                    len += 1;
+                }
+            }
+        }
+        frame[len] = END;
+        len += 1;
+
+        self.port.write_all(&frame[..len]).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_core::compat::collections::VecDeque;
+    use ockam_core::compat::vec::Vec;
+
+    /// Serial port reading the bytes of a buffer, at most `chunk` bytes
+    /// at a time, and recording the bytes written
+    struct InMemoryPort {
+        input: VecDeque<u8>,
+        output: Vec<u8>,
+        chunk: usize,
+    }
+
+    impl InMemoryPort {
+        fn new(input: &[u8]) -> Self {
+            Self {
+                input: input.iter().copied().collect(),
+                output: Vec::new(),
+                chunk: 3,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl SerialPort for InMemoryPort {
+        async fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+            let len = buffer.len().min(self.chunk).min(self.input.len());
+            for byte in buffer.iter_mut().take(len) {
+                *byte = self.input.pop_front().unwrap();
+            }
+            Ok(len)
+        }
+
+        async fn write_all(&mut self, buffer: &[u8]) -> Result<()> {
+            self.output.extend_from_slice(buffer);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        run(async {
+            let packet = [1, END, 2, ESC, 3];
+            let mut writer = SerialAdapter::new(InMemoryPort::new(&[]));
+            writer.write(&packet).await.unwrap();
+            // both special bytes are escaped
+            assert_eq!(
+                writer.port.output,
+                [END, 1, ESC, ESC_END, 2, ESC, ESC_ESC, 3, END]
+            );
+
+            let mut reader = SerialAdapter::new(InMemoryPort::new(&writer.port.output));
+            assert_eq!(poll(&mut reader, 16).await, Polled::Connected);
+            assert_eq!(
+                poll(&mut reader, 16).await,
+                Polled::Received(packet.to_vec())
+            );
+            assert_eq!(poll(&mut reader, 16).await, Polled::Disconnected);
+        })
+    }
+
+    #[test]
+    fn test_invalid_escape() {
+        run(async {
+            // the escaped byte is kept as it is
+            let mut reader = connected(&[ESC, 0x01, 0x02, END]).await;
+            assert_eq!(poll(&mut reader, 16).await, Polled::Received(vec![1, 2]));
+        })
+    }
+
+    #[test]
+    fn test_oversized_frame_dropped() {
+        run(async {
+            let mut reader = connected(&[1, 2, 3, 4, 5, END, 6, END]).await;
+            assert_eq!(poll(&mut reader, 4).await, Polled::Unknown);
+            // the next frame is received
+            assert_eq!(poll(&mut reader, 4).await, Polled::Received(vec![6]));
+        })
+    }
+
+    #[test]
+    fn test_empty_frames_skipped() {
+        run(async {
+            let mut reader = connected(&[END, END, 7, END, END]).await;
+            assert_eq!(poll(&mut reader, 16).await, Polled::Received(vec![7]));
+            assert_eq!(poll(&mut reader, 16).await, Polled::Disconnected);
+        })
+    }
+
+    #[test]
+    fn test_write_too_long() {
+        run(async {
+            let mut writer = SerialAdapter::new(InMemoryPort::new(&[]));
+            let packet = [0_u8; crate::driver::CHARACTERISTIC_VALUE_LENGTH + 1];
+            assert!(writer.write(&packet).await.is_err());
+            assert!(writer.port.output.is_empty());
+        })
+    }
+
+    /// Owned version of a [`BleEvent`]
+    #[derive(Debug, PartialEq, Eq)]
+    enum Polled {
+        Connected,
+        Received(Vec<u8>),
+        Disconnected,
+        Unknown,
+    }
+
+    async fn poll(adapter: &mut SerialAdapter<InMemoryPort>, buffer_length: usize) -> Polled {
+        let mut buffer = vec![0_u8; buffer_length];
+        match adapter.poll(&mut buffer).await.unwrap() {
+            BleEvent::ConnectionComplete => Polled::Connected,
+            BleEvent::Received(bytes) => Polled::Received(bytes.to_vec()),
+            BleEvent::DisconnectionComplete => Polled::Disconnected,
+            BleEvent::Unknown | BleEvent::None => Polled::Unknown,
+        }
+    }
+
+    /// Return an adapter reading the given bytes, after the connection event
+    async fn connected(input: &[u8]) -> SerialAdapter<InMemoryPort> {
+        let mut adapter = SerialAdapter::new(InMemoryPort::new(input));
+        assert_eq!(poll(&mut adapter, 16).await, Polled::Connected);
+        adapter
+    }
+
+    fn run(test: impl core::future::Future<Output = ()>) {
+        ockam_node::tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(test)
+    }
+}
@@ -1,4 +1,5 @@
 //! This crate provides a BLE Transport for Ockam's Routing Protocol.
+//! The same transport can also run over serial (UART) links, see [`driver::serial`].
 //! Please read the support [documentation](./documentation.md) for more information.
 #![deny(
     //missing_docs,
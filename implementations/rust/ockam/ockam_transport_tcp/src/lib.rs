@@ -24,8 +24,15 @@ mod registry;
 mod transport;
 
 use ockam_core::TransportType;
-pub use options::{TcpConnectionOptions, TcpListenerOptions};
-pub use portal::{PortalInternalMessage, PortalMessage, MAX_PAYLOAD_SIZE};
+pub use options::{
+    TcpConnectionOptions, TcpKeepaliveOptions, TcpListenerOptions, TcpRateLimit, TcpRetryPolicy,
+};
+pub use portal::{
+    PortalInterceptor, PortalInterceptorFactory, PortalInternalMessage, PortalMessage,
+    TcpHealthCheckOptions, TcpLoadBalancedOutlet, TcpLoadBalancer, TcpLoadBalancingOptions,
+    TcpLoadBalancingStrategy, TcpOutletAllowlist, TcpOutletConnectionAccessControl,
+    TcpOutletConnectionInitializer, MAX_PAYLOAD_SIZE,
+};
 pub use registry::*;
 pub use transport::common::*;
 pub use transport::*;
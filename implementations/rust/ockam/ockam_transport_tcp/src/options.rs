@@ -1,4 +1,6 @@
 use crate::workers::Addresses;
+use crate::{TcpProxy, TcpTlsClientOptions, TcpTlsServerOptions};
+use core::time::Duration;
 use ockam_core::compat::sync::Arc;
 use ockam_core::flow_control::{FlowControlId, FlowControlOutgoingAccessControl, FlowControls};
 use ockam_core::{Address, AllowAll, IncomingAccessControl, OutgoingAccessControl};
@@ -8,11 +10,159 @@ pub(crate) struct TcpConnectionAccessControl {
     pub receiver_outgoing_access_control: Arc<dyn OutgoingAccessControl>,
 }
 
+/// TCP keepalive settings of a connection, used to detect dead peers
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TcpKeepaliveOptions {
+    time: Duration,
+    interval: Duration,
+    retries: u32,
+}
+
+impl Default for TcpKeepaliveOptions {
+    fn default() -> Self {
+        Self {
+            time: Duration::from_secs(300),
+            interval: Duration::from_secs(75),
+            retries: 2,
+        }
+    }
+}
+
+impl TcpKeepaliveOptions {
+    /// Idle time before the first keepalive probe is sent
+    pub fn with_time(mut self, time: Duration) -> Self {
+        self.time = time;
+        self
+    }
+
+    /// Time between two keepalive probes
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Number of unanswered probes before the connection is considered dead.
+    /// Only supported on unix platforms
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Idle time before the first keepalive probe is sent
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+
+    /// Time between two keepalive probes
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Number of unanswered probes before the connection is considered dead
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+}
+
+/// Policy used to retry a failed attempt to establish a TCP connection.
+/// The delay between two attempts doubles after each attempt, up to a maximum delay
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TcpRetryPolicy {
+    retries: u32,
+    initial_delay: Duration,
+    max_delay: Duration,
+}
+
+impl Default for TcpRetryPolicy {
+    /// Don't retry
+    fn default() -> Self {
+        Self::new(0, Duration::from_secs(1))
+    }
+}
+
+impl TcpRetryPolicy {
+    /// Retry at most `retries` times, waiting `initial_delay` before the first retry
+    pub fn new(retries: u32, initial_delay: Duration) -> Self {
+        Self {
+            retries,
+            initial_delay,
+            max_delay: Duration::from_secs(30),
+        }
+    }
+
+    /// Maximum delay between two attempts
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Maximum number of retries
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+
+    /// Delay to wait before the given retry, starting at 0,
+    /// or None if no more retries are allowed
+    pub fn delay(&self, retry: u32) -> Option<Duration> {
+        if retry >= self.retries {
+            return None;
+        }
+        let delay = self.initial_delay.saturating_mul(1 << retry.min(16));
+        Some(delay.min(self.max_delay))
+    }
+}
+
+/// Limit of the bandwidth used in one direction of a TCP stream
+///
+/// Bytes can be transferred in bursts of up to `burst` bytes, and are then throttled
+/// to `bytes_per_second`. By default, the burst is one second worth of bytes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TcpRateLimit {
+    bytes_per_second: u64,
+    burst: u64,
+}
+
+impl TcpRateLimit {
+    /// Limit the bandwidth to the given number of bytes per second (at least 1)
+    pub fn new(bytes_per_second: u64) -> Self {
+        let bytes_per_second = bytes_per_second.max(1);
+        Self {
+            bytes_per_second,
+            burst: bytes_per_second,
+        }
+    }
+
+    /// Maximum number of bytes transferred at once before throttling kicks in (at least 1)
+    pub fn with_burst(mut self, burst: u64) -> Self {
+        self.burst = burst.max(1);
+        self
+    }
+
+    /// Number of bytes per second
+    pub fn bytes_per_second(&self) -> u64 {
+        self.bytes_per_second
+    }
+
+    /// Maximum number of bytes transferred at once
+    pub fn burst(&self) -> u64 {
+        self.burst
+    }
+}
+
 /// Trust Options for a TCP connection
 #[derive(Debug)]
 pub struct TcpConnectionOptions {
     pub(super) consumer: Vec<FlowControlId>,
     pub(crate) flow_control_id: FlowControlId,
+    pub(crate) proxy: Option<TcpProxy>,
+    pub(crate) connect_timeout: Option<Duration>,
+    pub(crate) keepalive: Option<TcpKeepaliveOptions>,
+    pub(crate) nodelay: bool,
+    pub(crate) retry_policy: TcpRetryPolicy,
+    pub(crate) shared: bool,
+    pub(crate) upload_rate_limit: Option<TcpRateLimit>,
+    pub(crate) download_rate_limit: Option<TcpRateLimit>,
+    pub(crate) tls: Option<TcpTlsClientOptions>,
 }
 
 impl TcpConnectionOptions {
@@ -22,6 +172,15 @@ impl TcpConnectionOptions {
         Self {
             consumer: vec![],
             flow_control_id: FlowControls::generate_flow_control_id(),
+            proxy: None,
+            connect_timeout: None,
+            keepalive: Some(TcpKeepaliveOptions::default()),
+            nodelay: false,
+            retry_policy: TcpRetryPolicy::default(),
+            shared: false,
+            upload_rate_limit: None,
+            download_rate_limit: None,
+            tls: None,
         }
     }
 
@@ -32,13 +191,118 @@ impl TcpConnectionOptions {
         self
     }
 
+    /// Dial the connection through the given proxy.
+    /// When no proxy is set, the proxy configured with the
+    /// [`OCKAM_PROXY`](crate::OCKAM_PROXY) environment variable is used for non-local peers
+    pub fn with_proxy(mut self, proxy: TcpProxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Fail an attempt to establish the connection if it takes longer than the given duration.
+    /// By default, the operating system timeout is used
+    pub fn with_connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    /// Set the TCP keepalive settings of the connection
+    pub fn with_keepalive(mut self, keepalive: TcpKeepaliveOptions) -> Self {
+        self.keepalive = Some(keepalive);
+        self
+    }
+
+    /// Don't send TCP keepalive probes on the connection
+    pub fn without_keepalive(mut self) -> Self {
+        self.keepalive = None;
+        self
+    }
+
+    /// Set the `TCP_NODELAY` option of the connection to disable the Nagle algorithm,
+    /// which reduces the latency of small messages. Not set by default
+    pub fn with_nodelay(mut self, nodelay: bool) -> Self {
+        self.nodelay = nodelay;
+        self
+    }
+
+    /// Retry failed attempts to establish the connection. By default, there are no retries
+    pub fn with_retry_policy(mut self, retry_policy: TcpRetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Share the connection with the other shared connections to the same peer: if one
+    /// is already open, it is returned instead of opening a new socket. Connecting with
+    /// different options (proxy, timeouts, keepalive, retries, rate limits or TLS) to a peer
+    /// which already has a shared connection fails.
+    ///
+    /// Each user sends messages through its own stream, whose address is the
+    /// [`TcpConnection::sender_address`](crate::TcpConnection::sender_address) returned to that
+    /// user. A stream can only have a limited number of messages waiting to be written to the
+    /// socket, so that a busy user doesn't starve the other users.
+    ///
+    /// All the users of a shared connection get the same [`FlowControlId`], which must be taken
+    /// from the returned connection, so the users must trust each other. The consumers added
+    /// by a user are removed when that user stops the connection, and the connection is closed
+    /// when it has been stopped by all its users.
+    /// [`TcpTransport::disconnect`](crate::TcpTransport::disconnect) closes it for all its users
+    /// when given the [`TcpConnection::connection_sender_address`](crate::TcpConnection::connection_sender_address).
+    pub fn shared(mut self) -> Self {
+        self.shared = true;
+        self
+    }
+
+    /// Limit the bandwidth used to send messages over the connection
+    pub fn with_upload_rate_limit(mut self, rate_limit: TcpRateLimit) -> Self {
+        self.upload_rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Limit the bandwidth used to receive messages from the connection
+    pub fn with_download_rate_limit(mut self, rate_limit: TcpRateLimit) -> Self {
+        self.download_rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Wrap the connection in a TLS session, beneath the Ockam protocol
+    pub fn with_tls(mut self, tls: TcpTlsClientOptions) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
     /// Getter for freshly generated [`FlowControlId`]
     pub fn flow_control_id(&self) -> FlowControlId {
         self.flow_control_id.clone()
     }
 }
 
+/// Options which must be the same for all the users of a shared connection
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct SharedTcpConnectionSettings {
+    proxy: Option<TcpProxy>,
+    connect_timeout: Option<Duration>,
+    keepalive: Option<TcpKeepaliveOptions>,
+    nodelay: bool,
+    retry_policy: TcpRetryPolicy,
+    upload_rate_limit: Option<TcpRateLimit>,
+    download_rate_limit: Option<TcpRateLimit>,
+    tls: Option<TcpTlsClientOptions>,
+}
+
 impl TcpConnectionOptions {
+    pub(crate) fn shared_settings(&self) -> SharedTcpConnectionSettings {
+        SharedTcpConnectionSettings {
+            proxy: self.proxy.clone(),
+            connect_timeout: self.connect_timeout,
+            keepalive: self.keepalive.clone(),
+            nodelay: self.nodelay,
+            retry_policy: self.retry_policy.clone(),
+            upload_rate_limit: self.upload_rate_limit.clone(),
+            download_rate_limit: self.download_rate_limit.clone(),
+            tls: self.tls.clone(),
+        }
+    }
+
     pub(crate) fn setup_flow_control(&self, flow_controls: &FlowControls, addresses: &Addresses) {
         flow_controls.add_producer(
             addresses.receiver_address().clone(),
@@ -47,8 +311,11 @@ impl TcpConnectionOptions {
             vec![addresses.sender_address().clone()],
         );
 
-        for id in &self.consumer {
-            flow_controls.add_consumer(addresses.sender_address().clone(), id);
+        // the consumers of a shared connection are added to the stream of each user
+        if !self.shared {
+            for id in &self.consumer {
+                flow_controls.add_consumer(addresses.sender_address().clone(), id);
+            }
         }
     }
 
@@ -71,6 +338,9 @@ impl TcpConnectionOptions {
 #[derive(Debug)]
 pub struct TcpListenerOptions {
     pub(crate) flow_control_id: FlowControlId,
+    pub(crate) upload_rate_limit: Option<TcpRateLimit>,
+    pub(crate) download_rate_limit: Option<TcpRateLimit>,
+    pub(crate) tls: Option<TcpTlsServerOptions>,
 }
 
 impl TcpListenerOptions {
@@ -81,9 +351,30 @@ impl TcpListenerOptions {
     pub fn new() -> Self {
         Self {
             flow_control_id: FlowControls::generate_flow_control_id(),
+            upload_rate_limit: None,
+            download_rate_limit: None,
+            tls: None,
         }
     }
 
+    /// Limit the bandwidth used to send messages over each accepted connection
+    pub fn with_upload_rate_limit(mut self, rate_limit: TcpRateLimit) -> Self {
+        self.upload_rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Limit the bandwidth used to receive messages from each accepted connection
+    pub fn with_download_rate_limit(mut self, rate_limit: TcpRateLimit) -> Self {
+        self.download_rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Only accept connections wrapped in a TLS session, beneath the Ockam protocol
+    pub fn with_tls(mut self, tls: TcpTlsServerOptions) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
     /// Getter for freshly generated [`FlowControlId`]
     pub fn spawner_flow_control_id(&self) -> FlowControlId {
         self.flow_control_id.clone()
@@ -131,3 +422,22 @@ impl TcpListenerOptions {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_policy_delays() {
+        let policy = TcpRetryPolicy::new(4, Duration::from_millis(100))
+            .with_max_delay(Duration::from_millis(300));
+
+        assert_eq!(policy.delay(0), Some(Duration::from_millis(100)));
+        assert_eq!(policy.delay(1), Some(Duration::from_millis(200)));
+        assert_eq!(policy.delay(2), Some(Duration::from_millis(300)));
+        assert_eq!(policy.delay(3), Some(Duration::from_millis(300)));
+        assert_eq!(policy.delay(4), None);
+
+        assert_eq!(TcpRetryPolicy::default().delay(0), None);
+    }
+}
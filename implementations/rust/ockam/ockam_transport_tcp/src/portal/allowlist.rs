@@ -0,0 +1,259 @@
+use crate::transport::common::peer_host;
+use ockam_core::compat::net::{IpAddr, SocketAddr};
+use ockam_core::compat::string::String;
+use ockam_core::compat::vec::Vec;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Error, Result};
+use ockam_transport_core::TransportError;
+
+/// Targets which an Outlet is allowed to connect to.
+///
+/// Each entry is either:
+///  - a host name, for example `db.internal`,
+///  - a host name wildcard matching all the sub-domains of a domain, for example `*.internal`,
+///  - an IP address, for example `10.0.0.12`,
+///  - a CIDR range, for example `10.0.0.0/24` or `fd00::/8`.
+///
+/// A target is allowed if its host name matches a host name entry, or if the address
+/// it resolves to is in one of the IP ranges. The host name of the target is resolved
+/// again for each new connection, so that the check is always done on the address
+/// the Outlet actually connects to, even if the DNS records changed.
+///
+/// An empty allowlist doesn't allow any target.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TcpOutletAllowlist {
+    hosts: Vec<HostPattern>,
+    networks: Vec<IpNetwork>,
+}
+
+impl TcpOutletAllowlist {
+    /// Create an empty allowlist
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an entry: a host name, a host name wildcard, an IP address or a CIDR range
+    pub fn with_entry(mut self, entry: &str) -> Result<Self> {
+        let entry = entry.trim();
+        if let Some(network) = IpNetwork::parse(entry)? {
+            self.networks.push(network);
+        } else {
+            self.hosts.push(HostPattern::parse(entry)?);
+        }
+        Ok(self)
+    }
+
+    /// Create an allowlist from a list of entries
+    pub fn from_entries<S: AsRef<str>>(entries: &[S]) -> Result<Self> {
+        entries.iter().try_fold(Self::new(), |allowlist, entry| {
+            allowlist.with_entry(entry.as_ref())
+        })
+    }
+
+    /// Return true if a target with the given host name, resolved to the given address,
+    /// can be reached
+    pub fn is_allowed(&self, host: &str, address: IpAddr) -> bool {
+        self.hosts.iter().any(|pattern| pattern.matches(host))
+            || self
+                .networks
+                .iter()
+                .any(|network| network.contains(address))
+    }
+
+    /// Resolve the `host:port` peer and return the first of its addresses which is allowed
+    pub(crate) async fn resolve(&self, peer: &str) -> Result<SocketAddr> {
+        let host = peer_host(peer);
+        let addresses = tokio::net::lookup_host(peer)
+            .await
+            .map_err(|_| TransportError::InvalidAddress)?;
+
+        let mut addresses = addresses.peekable();
+        if addresses.peek().is_none() {
+            return Err(TransportError::InvalidAddress)?;
+        }
+
+        addresses
+            .find(|address| self.is_allowed(host, address.ip()))
+            .ok_or_else(|| {
+                Error::new(
+                    Origin::Transport,
+                    Kind::Invalid,
+                    format!("the target {peer} is not in the allowlist of the Outlet"),
+                )
+            })
+    }
+}
+
+/// A host name, possibly starting with a `*.` wildcard
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum HostPattern {
+    Exact(String),
+    SubDomains(String),
+}
+
+impl HostPattern {
+    fn parse(entry: &str) -> Result<Self> {
+        let (pattern, host) = match entry.strip_prefix("*.") {
+            Some(domain) => (HostPattern::SubDomains(domain.to_lowercase()), domain),
+            None => (HostPattern::Exact(entry.to_lowercase()), entry),
+        };
+
+        let is_valid = !host.is_empty()
+            && host.split('.').all(|label| {
+                !label.is_empty()
+                    && label
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            });
+        if !is_valid {
+            return Err(invalid_entry(entry));
+        }
+
+        Ok(pattern)
+    }
+
+    fn matches(&self, host: &str) -> bool {
+        let host = host.trim_end_matches('.').to_lowercase();
+        match self {
+            HostPattern::Exact(name) => &host == name,
+            HostPattern::SubDomains(domain) => host
+                .strip_suffix(domain.as_str())
+                .map(|prefix| prefix.len() > 1 && prefix.ends_with('.'))
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// An IP address range, in the CIDR notation
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct IpNetwork {
+    address: IpAddr,
+    prefix_length: u8,
+}
+
+impl IpNetwork {
+    /// Parse an IP address or a CIDR range. Return None if the entry is not an IP address
+    fn parse(entry: &str) -> Result<Option<Self>> {
+        let (address, prefix_length) = match entry.split_once('/') {
+            Some((address, prefix_length)) => (address, Some(prefix_length)),
+            None => (entry, None),
+        };
+        let address: IpAddr = match address.trim_matches(|c| c == '[' || c == ']').parse() {
+            Ok(address) => address,
+            Err(_) if prefix_length.is_none() => return Ok(None),
+            Err(_) => return Err(invalid_entry(entry)),
+        };
+
+        let max_prefix_length = if address.is_ipv4() { 32 } else { 128 };
+        let prefix_length = match prefix_length {
+            Some(prefix_length) => prefix_length
+                .parse::<u8>()
+                .ok()
+                .filter(|l| *l <= max_prefix_length)
+                .ok_or_else(|| invalid_entry(entry))?,
+            None => max_prefix_length,
+        };
+
+        Ok(Some(Self {
+            address,
+            prefix_length,
+        }))
+    }
+
+    fn contains(&self, address: IpAddr) -> bool {
+        // compare IPv4 addresses mapped to IPv6 as IPv4 addresses
+        let address = match address {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(address),
+            _ => address,
+        };
+        match (self.address, address) {
+            (IpAddr::V4(network), IpAddr::V4(address)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - self.prefix_length as u32)
+                    .unwrap_or(0);
+                u32::from(network) & mask == u32::from(address) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(address)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - self.prefix_length as u32)
+                    .unwrap_or(0);
+                u128::from(network) & mask == u128::from(address) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+fn invalid_entry(entry: &str) -> Error {
+    Error::new(
+        Origin::Transport,
+        Kind::Invalid,
+        format!("invalid Outlet allowlist entry: {entry}"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_host_names() -> Result<()> {
+        let allowlist = TcpOutletAllowlist::new()
+            .with_entry("db.internal")?
+            .with_entry("*.svc.cluster.local")?;
+
+        let address = ip("192.168.1.1");
+        assert!(allowlist.is_allowed("db.internal", address));
+        assert!(allowlist.is_allowed("DB.Internal.", address));
+        assert!(allowlist.is_allowed("api.ns.svc.cluster.local", address));
+        assert!(!allowlist.is_allowed("svc.cluster.local", address));
+        assert!(!allowlist.is_allowed("evilsvc.cluster.local", address));
+        assert!(!allowlist.is_allowed("other.internal", address));
+        Ok(())
+    }
+
+    #[test]
+    fn test_ip_ranges() -> Result<()> {
+        let allowlist = TcpOutletAllowlist::new()
+            .with_entry("10.0.0.0/8")?
+            .with_entry("192.168.1.12")?
+            .with_entry("fd00::/8")?;
+
+        assert!(allowlist.is_allowed("host", ip("10.1.2.3")));
+        assert!(allowlist.is_allowed("host", ip("::ffff:10.1.2.3")));
+        assert!(allowlist.is_allowed("host", ip("192.168.1.12")));
+        assert!(!allowlist.is_allowed("host", ip("192.168.1.13")));
+        assert!(allowlist.is_allowed("host", ip("fd12::1")));
+        assert!(!allowlist.is_allowed("host", ip("fe80::1")));
+        assert!(!allowlist.is_allowed("host", ip("11.0.0.1")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_allowlist_denies_everything() {
+        assert!(!TcpOutletAllowlist::new().is_allowed("localhost", ip("127.0.0.1")));
+    }
+
+    #[test]
+    fn test_invalid_entries() {
+        for entry in [
+            "",
+            "10.0.0.0/33",
+            "10.0.0.0/x",
+            "*.",
+            "a..b",
+            "host:80",
+            "*",
+        ] {
+            assert!(
+                TcpOutletAllowlist::new().with_entry(entry).is_err(),
+                "{entry} should be invalid"
+            );
+        }
+        assert!(TcpOutletAllowlist::new().with_entry("0.0.0.0/0").is_ok());
+    }
+}
@@ -0,0 +1,17 @@
+use core::fmt::Debug;
+use ockam_core::compat::{boxed::Box, net::SocketAddr};
+use ockam_core::{async_trait, LocalInfo, Result};
+
+/// Authorizes every new connection of an Outlet to its target
+///
+/// This is checked in addition to the incoming access control of the Outlet,
+/// once per connection, with the target of the connection. A connection which is
+/// not authorized is closed on both sides of the portal.
+#[async_trait]
+pub trait TcpOutletConnectionAccessControl: Debug + Send + Sync + 'static {
+    /// Return true if the connection to the target can be opened.
+    ///
+    /// The [`LocalInfo`] of the message which requested the connection is provided,
+    /// for example to find the identifier of the secure channel it was received from.
+    async fn is_authorized(&self, local_info: &[LocalInfo], target: SocketAddr) -> Result<bool>;
+}
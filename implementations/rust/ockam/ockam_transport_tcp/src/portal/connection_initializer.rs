@@ -0,0 +1,20 @@
+use core::fmt::Debug;
+use ockam_core::compat::boxed::Box;
+use ockam_core::{async_trait, Result};
+use tokio::io::{AsyncRead, AsyncWrite};
+
+/// Runs an exchange with the target on every new connection of an Outlet
+///
+/// The exchange takes place once the connection is established, after the TLS handshake
+/// if TLS is used, and before any data received through the portal is written to the target.
+/// It can be used to authenticate the connection with credentials only known by the Outlet.
+/// If it fails the connection is closed on both sides of the portal.
+#[async_trait]
+pub trait TcpOutletConnectionInitializer: Debug + Send + Sync + 'static {
+    /// Run the exchange with the target, reading and writing the connection
+    async fn initialize(
+        &self,
+        reader: &mut (dyn AsyncRead + Send + Unpin),
+        writer: &mut (dyn AsyncWrite + Send + Unpin),
+    ) -> Result<()>;
+}
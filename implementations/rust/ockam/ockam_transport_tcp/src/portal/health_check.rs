@@ -0,0 +1,104 @@
+use crate::{PortalMessage, TcpLoadBalancer};
+use ockam_core::compat::{boxed::Box, sync::Arc};
+use ockam_core::{
+    async_trait, Address, AllowAll, IncomingAccessControl, Mailboxes, Processor, Result, Route,
+};
+use ockam_node::{Context, MessageReceiveOptions};
+use tracing::debug;
+
+/// A processor checking the health of the Outlets of a load balanced Inlet
+///
+/// It is started with the Inlet listen processor created by
+/// [`TcpTransport::create_load_balanced_inlet`](crate::TcpTransport::create_load_balanced_inlet)
+/// and stops after the Inlet is stopped.
+pub(crate) struct TcpInletHealthCheckProcessor {
+    load_balancer: TcpLoadBalancer,
+    incoming_access_control: Arc<dyn IncomingAccessControl>,
+}
+
+impl TcpInletHealthCheckProcessor {
+    pub(crate) async fn start(
+        ctx: &Context,
+        load_balancer: TcpLoadBalancer,
+        incoming_access_control: Arc<dyn IncomingAccessControl>,
+    ) -> Result<()> {
+        let processor = Self {
+            load_balancer,
+            incoming_access_control,
+        };
+
+        ctx.start_processor(Address::random_tagged("TcpInletHealthCheck"), processor)
+            .await
+    }
+
+    /// Open a portal connection to the Outlet and close it as soon as the Outlet answers
+    async fn check(&self, ctx: &Context, route: Route) -> Result<bool> {
+        let timeout = match self.load_balancer.health_check_options() {
+            Some(options) => options.timeout,
+            None => return Ok(true),
+        };
+
+        let address = Address::random_tagged("TcpInletHealthCheck.remote");
+        if let Some(flow_control_id) = ctx
+            .flow_controls()
+            .find_flow_control_with_producer_address(route.next()?)
+            .map(|x| x.flow_control_id().clone())
+        {
+            ctx.flow_controls()
+                .add_consumer(address.clone(), &flow_control_id);
+        }
+
+        let mut child_ctx = ctx
+            .new_detached_with_mailboxes(Mailboxes::main(
+                address,
+                self.incoming_access_control.clone(),
+                Arc::new(AllowAll),
+            ))
+            .await?;
+
+        child_ctx.send(route, PortalMessage::Ping).await?;
+        let pong = child_ctx
+            .receive_extended::<PortalMessage>(MessageReceiveOptions::new().with_timeout(timeout))
+            .await;
+
+        match pong {
+            Ok(pong) if matches!(pong.as_body(), PortalMessage::Pong) => {
+                child_ctx
+                    .send(pong.return_route(), PortalMessage::Disconnect)
+                    .await?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+}
+
+#[async_trait]
+impl Processor for TcpInletHealthCheckProcessor {
+    type Context = Context;
+
+    async fn process(&mut self, ctx: &mut Self::Context) -> Result<bool> {
+        let interval = match self.load_balancer.health_check_options() {
+            Some(options) => options.interval,
+            None => return Ok(false),
+        };
+        ctx.sleep(interval).await;
+
+        for (index, route) in self.load_balancer.routes().into_iter().enumerate() {
+            if self.load_balancer.is_closed() {
+                return Ok(false);
+            }
+
+            let healthy = match self.check(ctx, route.clone()).await {
+                Ok(healthy) => healthy,
+                Err(err) => {
+                    debug!("Health check of the Outlet at {} failed: {}", route, err);
+                    false
+                }
+            };
+            self.load_balancer.record_health_check(index, healthy);
+        }
+
+        Ok(!self.load_balancer.is_closed())
+    }
+}
@@ -1,37 +1,45 @@
 use crate::portal::addresses::{Addresses, PortalType};
-use crate::{portal::TcpPortalWorker, TcpInletOptions, TcpRegistry};
+use crate::portal::TcpInletHealthCheckProcessor;
+use crate::split_stream;
+use crate::transport::tls::accept_tls;
+use crate::{portal::TcpPortalWorker, TcpInletOptions, TcpLoadBalancer, TcpRegistry};
 use ockam_core::compat::net::SocketAddr;
 use ockam_core::{async_trait, compat::boxed::Box};
-use ockam_core::{Address, Processor, Result, Route};
+use ockam_core::{Address, Processor, Result};
 use ockam_node::Context;
 use ockam_transport_core::TransportError;
 use tokio::net::TcpListener;
-use tracing::{debug, error};
+use tokio_rustls::TlsAcceptor;
+use tracing::{debug, error, warn};
 
 /// A TCP Portal Inlet listen processor
 ///
 /// TCP Portal Inlet listen processors are created by `TcpTransport`
 /// after a call is made to
-/// [`TcpTransport::create_inlet`](crate::TcpTransport::create_inlet).
+/// [`TcpTransport::create_inlet`](crate::TcpTransport::create_inlet)
+/// or [`TcpTransport::create_load_balanced_inlet`](crate::TcpTransport::create_load_balanced_inlet).
 pub(crate) struct TcpInletListenProcessor {
     registry: TcpRegistry,
     inner: TcpListener,
-    outlet_listener_route: Route,
+    load_balancer: TcpLoadBalancer,
     options: TcpInletOptions,
+    tls_acceptor: Option<TlsAcceptor>,
 }
 
 impl TcpInletListenProcessor {
     pub fn new(
         registry: TcpRegistry,
         inner: TcpListener,
-        outlet_listener_route: Route,
+        load_balancer: TcpLoadBalancer,
         options: TcpInletOptions,
+        tls_acceptor: Option<TlsAcceptor>,
     ) -> Self {
         Self {
             registry,
             inner,
-            outlet_listener_route,
+            load_balancer,
             options,
+            tls_acceptor,
         }
     }
 
@@ -39,11 +47,12 @@ impl TcpInletListenProcessor {
     pub(crate) async fn start(
         ctx: &Context,
         registry: TcpRegistry,
-        outlet_listener_route: Route,
+        load_balancer: TcpLoadBalancer,
         addr: SocketAddr,
         options: TcpInletOptions,
     ) -> Result<(SocketAddr, Address)> {
         let processor_address = Address::random_tagged("TcpInletListenProcessor");
+        let tls_acceptor = options.tls.as_ref().map(|tls| tls.acceptor()).transpose()?;
 
         debug!("Binding TcpPortalListenerWorker to {}", addr);
         let inner = match TcpListener::bind(addr).await {
@@ -54,11 +63,23 @@ impl TcpInletListenProcessor {
             }
         };
         let socket_addr = inner.local_addr().map_err(TransportError::from)?;
-        let processor = Self::new(registry, inner, outlet_listener_route, options);
+        let incoming_access_control = options.incoming_access_control.clone();
+        let processor = Self::new(
+            registry,
+            inner,
+            load_balancer.clone(),
+            options,
+            tls_acceptor,
+        );
 
         ctx.start_processor(processor_address.clone(), processor)
             .await?;
 
+        if load_balancer.health_check_options().is_some() {
+            TcpInletHealthCheckProcessor::start(ctx, load_balancer, incoming_access_control)
+                .await?;
+        }
+
         Ok((socket_addr, processor_address))
     }
 }
@@ -76,21 +97,41 @@ impl Processor for TcpInletListenProcessor {
     async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
         self.registry
             .remove_inlet_listener_processor(&ctx.address());
+        self.load_balancer.close();
 
         Ok(())
     }
 
     async fn process(&mut self, ctx: &mut Self::Context) -> Result<bool> {
-        let addresses = Addresses::generate(PortalType::Inlet);
-        let outlet_listener_route = self.outlet_listener_route.clone();
+        let (stream, peer) = self.inner.accept().await.map_err(TransportError::from)?;
+
+        let stream = match &self.tls_acceptor {
+            Some(acceptor) => match accept_tls(acceptor, &peer.to_string(), stream).await {
+                Ok(stream) => stream,
+                Err(err) => {
+                    // a failed handshake only drops this connection, not the inlet
+                    warn!(%peer, %err, "Rejected TCP connection");
+                    return Ok(true);
+                }
+            },
+            None => split_stream(stream),
+        };
+
+        let (outlet_listener_route, connection) = match self.load_balancer.pick() {
+            Some(picked) => picked,
+            None => {
+                warn!(%peer, "no healthy outlet, the connection is dropped");
+                return Ok(true);
+            }
+        };
 
+        let addresses = Addresses::generate(PortalType::Inlet);
         self.options.setup_flow_control(
             ctx.flow_controls(),
             &addresses,
             outlet_listener_route.next()?,
         );
 
-        let (stream, peer) = self.inner.accept().await.map_err(TransportError::from)?;
         TcpPortalWorker::start_new_inlet(
             ctx,
             self.registry.clone(),
@@ -99,6 +140,10 @@ impl Processor for TcpInletListenProcessor {
             outlet_listener_route,
             addresses,
             self.options.incoming_access_control.clone(),
+            self.options.outgoing_access_control.clone(),
+            self.options.upload_rate_limit.clone(),
+            self.options.download_rate_limit.clone(),
+            connection,
         )
         .await?;
 
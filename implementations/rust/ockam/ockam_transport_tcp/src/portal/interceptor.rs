@@ -0,0 +1,25 @@
+use core::fmt::Debug;
+use ockam_core::compat::{boxed::Box, vec::Vec};
+use ockam_core::{async_trait, LocalInfo, Result};
+
+/// Creates a [`PortalInterceptor`] for every connection of an Outlet
+pub trait PortalInterceptorFactory: Debug + Send + Sync + 'static {
+    /// Create the interceptor for a new connection
+    fn create(&self) -> Box<dyn PortalInterceptor>;
+}
+
+/// Transforms the data which an Outlet writes to a TCP connection
+///
+/// Since TCP is a stream, the data sent by the Inlet can be split in any number
+/// of payloads, so an interceptor must keep the state of the protocol it parses
+/// from one payload to the next.
+#[async_trait]
+pub trait PortalInterceptor: Send + 'static {
+    /// Transform a payload received from the other side of the portal before
+    /// it is written to the TCP connection.
+    ///
+    /// The [`LocalInfo`] of the message carrying the payload is provided, for example
+    /// to find the identifier of the secure channel it was received from.
+    /// Returning an error closes the connection.
+    async fn intercept(&mut self, local_info: &[LocalInfo], payload: Vec<u8>) -> Result<Vec<u8>>;
+}
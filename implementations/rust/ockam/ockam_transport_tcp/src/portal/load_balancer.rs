@@ -0,0 +1,387 @@
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use core::time::Duration;
+use ockam_core::compat::sync::{Arc, Mutex};
+use ockam_core::compat::vec::Vec;
+use ockam_core::Route;
+use tracing::{info, warn};
+
+/// Strategy used by a load balanced Inlet to pick the Outlet of each new connection
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TcpLoadBalancingStrategy {
+    /// Use the healthy Outlets in turn
+    #[default]
+    RoundRobin,
+    /// Use the healthy Outlet with the fewest open connections
+    LeastConnections,
+}
+
+/// Health checks of the Outlets of a load balanced Inlet
+///
+/// A health check opens a portal connection to the Outlet, which succeeds if the Outlet
+/// can connect to its target, and closes it right away.
+#[derive(Clone, Debug)]
+pub struct TcpHealthCheckOptions {
+    pub(crate) interval: Duration,
+    pub(crate) timeout: Duration,
+    pub(crate) unhealthy_threshold: u32,
+    pub(crate) healthy_threshold: u32,
+}
+
+impl TcpHealthCheckOptions {
+    /// Check every 10 seconds, eject an Outlet after 3 failed checks,
+    /// and restore it after a successful one
+    pub fn new() -> Self {
+        Self {
+            interval: Duration::from_secs(10),
+            timeout: Duration::from_secs(5),
+            unhealthy_threshold: 3,
+            healthy_threshold: 1,
+        }
+    }
+
+    /// Set the time between two checks of the Outlets
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Set the time after which a check without answer fails
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the number of consecutive failed checks after which an Outlet is ejected
+    pub fn with_unhealthy_threshold(mut self, threshold: u32) -> Self {
+        self.unhealthy_threshold = threshold.max(1);
+        self
+    }
+
+    /// Set the number of consecutive successful checks after which an ejected Outlet is restored
+    pub fn with_healthy_threshold(mut self, threshold: u32) -> Self {
+        self.healthy_threshold = threshold.max(1);
+        self
+    }
+}
+
+impl Default for TcpHealthCheckOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Load balancing options of an Inlet
+#[derive(Clone, Debug)]
+pub struct TcpLoadBalancingOptions {
+    pub(crate) strategy: TcpLoadBalancingStrategy,
+    pub(crate) health_check: Option<TcpHealthCheckOptions>,
+}
+
+impl TcpLoadBalancingOptions {
+    /// Round-robin with the default health checks
+    pub fn new() -> Self {
+        Self {
+            strategy: TcpLoadBalancingStrategy::default(),
+            health_check: Some(TcpHealthCheckOptions::default()),
+        }
+    }
+
+    /// Set the strategy used to pick the Outlet of a new connection
+    pub fn with_strategy(mut self, strategy: TcpLoadBalancingStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Set the health checks of the Outlets
+    pub fn with_health_check(mut self, health_check: TcpHealthCheckOptions) -> Self {
+        self.health_check = Some(health_check);
+        self
+    }
+
+    /// Never check the health of the Outlets, they are then always used
+    pub fn without_health_check(mut self) -> Self {
+        self.health_check = None;
+        self
+    }
+}
+
+impl Default for TcpLoadBalancingOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Status of an Outlet of a load balanced Inlet
+#[derive(Clone, Debug)]
+pub struct TcpLoadBalancedOutlet {
+    route: Route,
+    healthy: bool,
+    connections: usize,
+}
+
+impl TcpLoadBalancedOutlet {
+    /// Route to the Outlet
+    pub fn route(&self) -> &Route {
+        &self.route
+    }
+
+    /// False if the Outlet was ejected after failing its health checks
+    pub fn is_healthy(&self) -> bool {
+        self.healthy
+    }
+
+    /// Number of open connections to the Outlet
+    pub fn connections(&self) -> usize {
+        self.connections
+    }
+}
+
+#[derive(Debug)]
+struct OutletState {
+    route: Route,
+    healthy: bool,
+    consecutive_failures: u32,
+    consecutive_successes: u32,
+    connections: usize,
+}
+
+/// Outlets of a load balanced Inlet.
+///
+/// The state of the Outlets is shared by all the clones of this value, so it can
+/// be read while the Inlet is running.
+#[derive(Clone, Debug)]
+pub struct TcpLoadBalancer {
+    inner: Arc<TcpLoadBalancerInner>,
+}
+
+#[derive(Debug)]
+struct TcpLoadBalancerInner {
+    options: TcpLoadBalancingOptions,
+    outlets: Mutex<Vec<OutletState>>,
+    next: AtomicUsize,
+    closed: AtomicBool,
+}
+
+impl TcpLoadBalancer {
+    /// Create a load balancer for the Outlets at the given routes
+    pub fn new(outlet_routes: Vec<Route>, options: TcpLoadBalancingOptions) -> Self {
+        let outlets = outlet_routes
+            .into_iter()
+            .map(|route| OutletState {
+                route,
+                healthy: true,
+                consecutive_failures: 0,
+                consecutive_successes: 0,
+                connections: 0,
+            })
+            .collect();
+
+        Self {
+            inner: Arc::new(TcpLoadBalancerInner {
+                options,
+                outlets: Mutex::new(outlets),
+                next: AtomicUsize::new(0),
+                closed: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// Load balancer for an Inlet with a single Outlet
+    pub(crate) fn single(outlet_route: Route) -> Self {
+        Self::new(
+            vec![outlet_route],
+            TcpLoadBalancingOptions::new().without_health_check(),
+        )
+    }
+
+    /// Status of the Outlets
+    pub fn outlets(&self) -> Vec<TcpLoadBalancedOutlet> {
+        self.inner
+            .outlets
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|outlet| TcpLoadBalancedOutlet {
+                route: outlet.route.clone(),
+                healthy: outlet.healthy,
+                connections: outlet.connections,
+            })
+            .collect()
+    }
+
+    pub(crate) fn health_check_options(&self) -> Option<&TcpHealthCheckOptions> {
+        self.inner.options.health_check.as_ref()
+    }
+
+    pub(crate) fn routes(&self) -> Vec<Route> {
+        self.inner
+            .outlets
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|outlet| outlet.route.clone())
+            .collect()
+    }
+
+    /// Pick the Outlet of a new connection, or return None if all the Outlets are unhealthy.
+    /// The connection is counted until the returned value is dropped.
+    pub(crate) fn pick(&self) -> Option<(Route, TcpLoadBalancedConnection)> {
+        let mut outlets = self.inner.outlets.lock().unwrap();
+        let healthy: Vec<usize> = (0..outlets.len()).filter(|i| outlets[*i].healthy).collect();
+        if healthy.is_empty() {
+            return None;
+        }
+
+        let index = match self.inner.options.strategy {
+            TcpLoadBalancingStrategy::RoundRobin => {
+                healthy[self.inner.next.fetch_add(1, Ordering::Relaxed) % healthy.len()]
+            }
+            TcpLoadBalancingStrategy::LeastConnections => *healthy
+                .iter()
+                .min_by_key(|i| outlets[**i].connections)
+                .unwrap_or(&healthy[0]),
+        };
+
+        let outlet = &mut outlets[index];
+        outlet.connections += 1;
+        Some((
+            outlet.route.clone(),
+            TcpLoadBalancedConnection {
+                load_balancer: self.inner.clone(),
+                index,
+            },
+        ))
+    }
+
+    /// Record the result of a health check, and eject or restore the Outlet accordingly
+    pub(crate) fn record_health_check(&self, index: usize, success: bool) {
+        let options = match self.health_check_options() {
+            Some(options) => options,
+            None => return,
+        };
+
+        let mut outlets = self.inner.outlets.lock().unwrap();
+        let outlet = match outlets.get_mut(index) {
+            Some(outlet) => outlet,
+            None => return,
+        };
+
+        if success {
+            outlet.consecutive_failures = 0;
+            outlet.consecutive_successes += 1;
+            if !outlet.healthy && outlet.consecutive_successes >= options.healthy_threshold {
+                outlet.healthy = true;
+                info!("Outlet at {} is healthy again", outlet.route);
+            }
+        } else {
+            outlet.consecutive_successes = 0;
+            outlet.consecutive_failures += 1;
+            if outlet.healthy && outlet.consecutive_failures >= options.unhealthy_threshold {
+                outlet.healthy = false;
+                warn!(
+                    "Outlet at {} failed {} health checks and was ejected",
+                    outlet.route, outlet.consecutive_failures
+                );
+            }
+        }
+    }
+
+    /// Stop the health checks, once the Inlet is stopped
+    pub(crate) fn close(&self) {
+        self.inner.closed.store(true, Ordering::Relaxed);
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.inner.closed.load(Ordering::Relaxed)
+    }
+}
+
+/// A connection to an Outlet picked by a [`TcpLoadBalancer`]
+#[derive(Debug)]
+pub(crate) struct TcpLoadBalancedConnection {
+    load_balancer: Arc<TcpLoadBalancerInner>,
+    index: usize,
+}
+
+impl Drop for TcpLoadBalancedConnection {
+    fn drop(&mut self) {
+        if let Some(outlet) = self
+            .load_balancer
+            .outlets
+            .lock()
+            .unwrap()
+            .get_mut(self.index)
+        {
+            outlet.connections = outlet.connections.saturating_sub(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_core::route;
+
+    fn load_balancer(strategy: TcpLoadBalancingStrategy) -> TcpLoadBalancer {
+        TcpLoadBalancer::new(
+            vec![route!["outlet1"], route!["outlet2"], route!["outlet3"]],
+            TcpLoadBalancingOptions::new()
+                .with_strategy(strategy)
+                .with_health_check(TcpHealthCheckOptions::new().with_unhealthy_threshold(2)),
+        )
+    }
+
+    #[test]
+    fn test_round_robin() {
+        let load_balancer = load_balancer(TcpLoadBalancingStrategy::RoundRobin);
+        let picked: Vec<Route> = (0..4).map(|_| load_balancer.pick().unwrap().0).collect();
+        assert_eq!(
+            picked,
+            vec![
+                route!["outlet1"],
+                route!["outlet2"],
+                route!["outlet3"],
+                route!["outlet1"]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_least_connections() {
+        let load_balancer = load_balancer(TcpLoadBalancingStrategy::LeastConnections);
+        let (route1, _connection1) = load_balancer.pick().unwrap();
+        let (route2, connection2) = load_balancer.pick().unwrap();
+        assert_eq!(route1, route!["outlet1"]);
+        assert_eq!(route2, route!["outlet2"]);
+
+        // the connection to the second outlet is closed
+        drop(connection2);
+        assert_eq!(load_balancer.pick().unwrap().0, route!["outlet2"]);
+        assert_eq!(load_balancer.outlets()[0].connections(), 1);
+    }
+
+    #[test]
+    fn test_ejection() {
+        let load_balancer = load_balancer(TcpLoadBalancingStrategy::RoundRobin);
+
+        // the outlet is ejected after 2 failed checks
+        load_balancer.record_health_check(1, false);
+        assert!(load_balancer.outlets()[1].is_healthy());
+        load_balancer.record_health_check(1, false);
+        assert!(!load_balancer.outlets()[1].is_healthy());
+
+        let picked: Vec<Route> = (0..3).map(|_| load_balancer.pick().unwrap().0).collect();
+        assert!(!picked.contains(&route!["outlet2"]));
+
+        // and restored after a successful one
+        load_balancer.record_health_check(1, true);
+        assert!(load_balancer.outlets()[1].is_healthy());
+
+        // no connection is accepted when all the outlets are unhealthy
+        for index in 0..3 {
+            load_balancer.record_health_check(index, false);
+            load_balancer.record_health_check(index, false);
+        }
+        assert!(load_balancer.pick().is_none());
+    }
+}
@@ -1,12 +1,24 @@
 mod addresses;
+mod allowlist;
+mod connection_access_control;
+mod connection_initializer;
+mod health_check;
 mod inlet_listener;
+mod interceptor;
+mod load_balancer;
 pub mod options;
 mod outlet_listener;
 mod portal_message;
 mod portal_receiver;
 mod portal_worker;
 
+pub use allowlist::*;
+pub use connection_access_control::*;
+pub use connection_initializer::*;
+pub(crate) use health_check::*;
 pub(crate) use inlet_listener::*;
+pub use interceptor::*;
+pub use load_balancer::*;
 pub(crate) use outlet_listener::*;
 pub use portal_message::*;
 pub(crate) use portal_receiver::*;
@@ -1,12 +1,20 @@
 use crate::portal::addresses::Addresses;
+use crate::{
+    PortalInterceptorFactory, TcpOutletAllowlist, TcpOutletConnectionAccessControl,
+    TcpOutletConnectionInitializer, TcpRateLimit, TcpTlsClientOptions, TcpTlsServerOptions,
+};
 use ockam_core::compat::sync::Arc;
 use ockam_core::flow_control::{FlowControlId, FlowControls};
-use ockam_core::{Address, AllowAll, IncomingAccessControl};
+use ockam_core::{Address, AllowAll, IncomingAccessControl, OutgoingAccessControl};
 
 /// Trust Options for an Inlet
 #[derive(Debug)]
 pub struct TcpInletOptions {
     pub(super) incoming_access_control: Arc<dyn IncomingAccessControl>,
+    pub(super) outgoing_access_control: Arc<dyn OutgoingAccessControl>,
+    pub(super) upload_rate_limit: Option<TcpRateLimit>,
+    pub(super) download_rate_limit: Option<TcpRateLimit>,
+    pub(super) tls: Option<TcpTlsServerOptions>,
 }
 
 impl TcpInletOptions {
@@ -14,6 +22,10 @@ impl TcpInletOptions {
     pub fn new() -> Self {
         Self {
             incoming_access_control: Arc::new(AllowAll),
+            outgoing_access_control: Arc::new(AllowAll),
+            upload_rate_limit: None,
+            download_rate_limit: None,
+            tls: None,
         }
     }
 
@@ -35,6 +47,45 @@ impl TcpInletOptions {
         self
     }
 
+    /// Set Outgoing Access Control, authorizing the messages sent to the other side of the portal
+    pub fn with_outgoing_access_control_impl(
+        mut self,
+        access_control: impl OutgoingAccessControl,
+    ) -> Self {
+        self.outgoing_access_control = Arc::new(access_control);
+        self
+    }
+
+    /// Set Outgoing Access Control, authorizing the messages sent to the other side of the portal
+    pub fn with_outgoing_access_control(
+        mut self,
+        access_control: Arc<dyn OutgoingAccessControl>,
+    ) -> Self {
+        self.outgoing_access_control = access_control;
+        self
+    }
+
+    /// Limit the bandwidth used to write the data received from the other side of the portal
+    /// to each TCP connection
+    pub fn with_upload_rate_limit(mut self, rate_limit: TcpRateLimit) -> Self {
+        self.upload_rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Limit the bandwidth used to read the data sent to the other side of the portal
+    /// from each TCP connection
+    pub fn with_download_rate_limit(mut self, rate_limit: TcpRateLimit) -> Self {
+        self.download_rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Terminate TLS for the local clients: the connections are decrypted by the Inlet,
+    /// and the plaintext goes through the portal
+    pub fn with_tls(mut self, tls: TcpTlsServerOptions) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
     pub(super) fn setup_flow_control(
         &self,
         flow_controls: &FlowControls,
@@ -62,6 +113,14 @@ impl Default for TcpInletOptions {
 pub struct TcpOutletOptions {
     pub(super) consumer: Vec<FlowControlId>,
     pub(super) incoming_access_control: Arc<dyn IncomingAccessControl>,
+    pub(super) outgoing_access_control: Arc<dyn OutgoingAccessControl>,
+    pub(super) upload_rate_limit: Option<TcpRateLimit>,
+    pub(super) download_rate_limit: Option<TcpRateLimit>,
+    pub(super) interceptor_factory: Option<Arc<dyn PortalInterceptorFactory>>,
+    pub(super) tls: Option<TcpTlsClientOptions>,
+    pub(super) connection_access_control: Option<Arc<dyn TcpOutletConnectionAccessControl>>,
+    pub(super) allowlist: Option<TcpOutletAllowlist>,
+    pub(super) connection_initializer: Option<Arc<dyn TcpOutletConnectionInitializer>>,
 }
 
 impl TcpOutletOptions {
@@ -70,6 +129,14 @@ impl TcpOutletOptions {
         Self {
             consumer: vec![],
             incoming_access_control: Arc::new(AllowAll),
+            outgoing_access_control: Arc::new(AllowAll),
+            upload_rate_limit: None,
+            download_rate_limit: None,
+            interceptor_factory: None,
+            tls: None,
+            connection_access_control: None,
+            allowlist: None,
+            connection_initializer: None,
         }
     }
 
@@ -91,6 +158,86 @@ impl TcpOutletOptions {
         self
     }
 
+    /// Set Outgoing Access Control, authorizing the messages sent to the other side of the portal
+    pub fn with_outgoing_access_control_impl(
+        mut self,
+        access_control: impl OutgoingAccessControl,
+    ) -> Self {
+        self.outgoing_access_control = Arc::new(access_control);
+        self
+    }
+
+    /// Set Outgoing Access Control, authorizing the messages sent to the other side of the portal
+    pub fn with_outgoing_access_control(
+        mut self,
+        access_control: Arc<dyn OutgoingAccessControl>,
+    ) -> Self {
+        self.outgoing_access_control = access_control;
+        self
+    }
+
+    /// Limit the bandwidth used to write the data received from the other side of the portal
+    /// to each TCP connection
+    pub fn with_upload_rate_limit(mut self, rate_limit: TcpRateLimit) -> Self {
+        self.upload_rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Limit the bandwidth used to read the data sent to the other side of the portal
+    /// from each TCP connection
+    pub fn with_download_rate_limit(mut self, rate_limit: TcpRateLimit) -> Self {
+        self.download_rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Transform the data written to each TCP connection with an interceptor
+    /// created by the given factory
+    pub fn with_interceptor(mut self, factory: Arc<dyn PortalInterceptorFactory>) -> Self {
+        self.interceptor_factory = Some(factory);
+        self
+    }
+
+    /// Authorize every new connection to the target, in addition to the
+    /// Incoming Access Control
+    pub fn with_connection_access_control(
+        mut self,
+        access_control: Arc<dyn TcpOutletConnectionAccessControl>,
+    ) -> Self {
+        self.connection_access_control = Some(access_control);
+        self
+    }
+
+    /// Run an exchange with the target on every new connection, before forwarding
+    /// the data received through the portal, for example to authenticate the connection
+    pub fn with_connection_initializer(
+        mut self,
+        initializer: Arc<dyn TcpOutletConnectionInitializer>,
+    ) -> Self {
+        self.connection_initializer = Some(initializer);
+        self
+    }
+
+    /// Only connect to the target if it is in the allowlist. The target is resolved again
+    /// and checked for each new connection
+    pub fn with_allowlist(mut self, allowlist: TcpOutletAllowlist) -> Self {
+        self.allowlist = Some(allowlist);
+        self
+    }
+
+    /// Originate TLS to the target: the plaintext received through the portal is encrypted
+    /// by the Outlet. The target host is used as the server name unless another one is set
+    pub fn with_tls(mut self, tls: TcpTlsClientOptions) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
+    /// Keep the host name of the target to verify its TLS certificate, since the
+    /// target is only known by its socket address afterwards
+    pub(crate) fn with_target(mut self, peer: &str) -> Self {
+        self.tls = self.tls.map(|tls| tls.with_default_server_name(peer));
+        self
+    }
+
     /// Mark that this Outlet listener is a Consumer for to the given [`FlowControlId`]
     /// Also, in this case spawned Outlets will be marked as Consumers with [`FlowControlId`]
     /// of the message that was used to create the Outlet
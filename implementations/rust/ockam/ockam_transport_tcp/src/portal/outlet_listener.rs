@@ -1,6 +1,8 @@
 use crate::portal::addresses::{Addresses, PortalType};
+use crate::transport::common::peer_host;
 use crate::{portal::TcpPortalWorker, PortalMessage, TcpOutletOptions, TcpRegistry};
-use ockam_core::{async_trait, Address, DenyAll, Result, Routed, Worker};
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{async_trait, Address, DenyAll, Error, Result, Routed, Worker};
 use ockam_node::{Context, WorkerBuilder};
 use ockam_transport_core::TransportError;
 use std::net::SocketAddr;
@@ -13,15 +15,23 @@ use tracing::debug;
 /// [`TcpTransport::create_outlet`](crate::TcpTransport::create_outlet).
 pub(crate) struct TcpOutletListenWorker {
     registry: TcpRegistry,
+    /// `host:port` of the target
+    target: String,
     peer: SocketAddr,
     options: TcpOutletOptions,
 }
 
 impl TcpOutletListenWorker {
     /// Create a new `TcpOutletListenWorker`
-    fn new(registry: TcpRegistry, peer: SocketAddr, options: TcpOutletOptions) -> Self {
+    fn new(
+        registry: TcpRegistry,
+        target: String,
+        peer: SocketAddr,
+        options: TcpOutletOptions,
+    ) -> Self {
         Self {
             registry,
+            target,
             peer,
             options,
         }
@@ -31,14 +41,25 @@ impl TcpOutletListenWorker {
         ctx: &Context,
         registry: TcpRegistry,
         address: Address,
+        target: String,
         peer: SocketAddr,
         options: TcpOutletOptions,
     ) -> Result<()> {
+        if let Some(allowlist) = &options.allowlist {
+            if !allowlist.is_allowed(peer_host(&target), peer.ip()) {
+                return Err(Error::new(
+                    Origin::Transport,
+                    Kind::Invalid,
+                    format!("the target {target} is not in the allowlist of the Outlet"),
+                ));
+            }
+        }
+
         let access_control = options.incoming_access_control.clone();
 
         options.setup_flow_control_for_outlet_listener(ctx.flow_controls(), &address);
 
-        let worker = Self::new(registry, peer, options);
+        let worker = Self::new(registry, target, peer, options);
         WorkerBuilder::new(worker)
             .with_address(address)
             .with_incoming_access_control_arc(access_control)
@@ -74,6 +95,7 @@ impl Worker for TcpOutletListenWorker {
     ) -> Result<()> {
         let return_route = msg.return_route();
         let src_addr = msg.src_addr();
+        let local_info = msg.local_message().local_info().to_vec();
 
         if let PortalMessage::Ping = msg.body() {
         } else {
@@ -92,6 +114,21 @@ impl Worker for TcpOutletListenWorker {
             return_route.clone(),
             addresses.clone(),
             self.options.incoming_access_control.clone(),
+            self.options.outgoing_access_control.clone(),
+            self.options.upload_rate_limit.clone(),
+            self.options.download_rate_limit.clone(),
+            self.options
+                .interceptor_factory
+                .as_ref()
+                .map(|factory| factory.create()),
+            self.options.tls.clone(),
+            self.options.connection_access_control.clone(),
+            self.options
+                .allowlist
+                .clone()
+                .map(|allowlist| (allowlist, self.target.clone())),
+            self.options.connection_initializer.clone(),
+            local_info,
         )
         .await?;
 
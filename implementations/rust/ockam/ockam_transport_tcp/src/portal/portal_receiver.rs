@@ -1,10 +1,13 @@
 use crate::portal::portal_message::MAX_PAYLOAD_SIZE;
-use crate::{PortalInternalMessage, PortalMessage, TcpRegistry};
+use crate::{
+    PortalInternalMessage, PortalMessage, RateLimiter, TcpConnectionMetrics, TcpRateLimit,
+    TcpReadHalf, TcpRegistry,
+};
 use ockam_core::compat::vec::Vec;
 use ockam_core::{async_trait, Encodable, LocalMessage, Route, TransportMessage};
 use ockam_core::{route, Address, Processor, Result};
 use ockam_node::Context;
-use tokio::{io::AsyncReadExt, net::tcp::OwnedReadHalf};
+use tokio::io::AsyncReadExt;
 use tracing::{error, warn};
 
 /// A TCP Portal receiving message processor
@@ -15,18 +18,22 @@ use tracing::{error, warn};
 pub(crate) struct TcpPortalRecvProcessor {
     registry: TcpRegistry,
     buf: Vec<u8>,
-    read_half: OwnedReadHalf,
+    read_half: TcpReadHalf,
     sender_address: Address,
     onward_route: Route,
+    rate_limiter: Option<RateLimiter>,
+    metrics: TcpConnectionMetrics,
 }
 
 impl TcpPortalRecvProcessor {
     /// Create a new `TcpPortalRecvProcessor`
     pub fn new(
         registry: TcpRegistry,
-        read_half: OwnedReadHalf,
+        read_half: TcpReadHalf,
         sender_address: Address,
         onward_route: Route,
+        rate_limit: Option<&TcpRateLimit>,
+        metrics: TcpConnectionMetrics,
     ) -> Self {
         Self {
             registry,
@@ -34,6 +41,8 @@ impl TcpPortalRecvProcessor {
             read_half,
             sender_address,
             onward_route,
+            rate_limiter: RateLimiter::from_options(rate_limit),
+            metrics,
         }
     }
 }
@@ -58,9 +67,10 @@ impl Processor for TcpPortalRecvProcessor {
     async fn process(&mut self, ctx: &mut Context) -> Result<bool> {
         self.buf.clear();
 
-        let _len = match self.read_half.read_buf(&mut self.buf).await {
+        let len = match self.read_half.read_buf(&mut self.buf).await {
             Ok(len) => len,
             Err(err) => {
+                self.metrics.record_receive_failure();
                 error!("Tcp Portal connection read failed with error: {}", err);
                 return Ok(false);
             }
@@ -91,6 +101,13 @@ impl Processor for TcpPortalRecvProcessor {
             return Ok(false);
         }
 
+        self.metrics.record_received(len);
+
+        // Throttle the connection by delaying the next read
+        if let Some(rate_limiter) = &mut self.rate_limiter {
+            rate_limiter.acquire(len).await;
+        }
+
         // Loop just in case buf was extended (should not happen though)
         for chunk in self.buf.chunks(MAX_PAYLOAD_SIZE) {
             let msg = TransportMessage::v1(
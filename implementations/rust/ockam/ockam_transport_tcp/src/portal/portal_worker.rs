@@ -1,18 +1,26 @@
 use crate::portal::addresses::{Addresses, PortalType};
-use crate::{portal::TcpPortalRecvProcessor, PortalInternalMessage, PortalMessage, TcpRegistry};
+use crate::portal::TcpLoadBalancedConnection;
+use crate::{
+    portal::TcpPortalRecvProcessor, PortalInterceptor, PortalInternalMessage, PortalMessage,
+    RateLimiter, TcpConnectionMetrics, TcpOutletAllowlist, TcpOutletConnectionAccessControl,
+    TcpOutletConnectionInitializer, TcpPortalConnectionInfo, TcpRateLimit, TcpRegistry,
+    TcpTlsClientOptions,
+};
+use crate::{split_stream, TcpReadHalf, TcpWriteHalf};
 use core::time::Duration;
 use ockam_core::compat::{boxed::Box, net::SocketAddr, sync::Arc};
+use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{
-    async_trait, AllowAll, AllowOnwardAddresses, AllowSourceAddress, Decodable, DenyAll,
-    IncomingAccessControl, Mailbox, Mailboxes,
+    async_trait, AllOutgoingAccessControl, AllowOnwardAddress, AllowSourceAddress,
+    AnyOutgoingAccessControl, Decodable, DenyAll, IncomingAccessControl, LocalInfo, Mailbox,
+    Mailboxes, OutgoingAccessControl,
 };
-use ockam_core::{Any, Result, Route, Routed, Worker};
+use ockam_core::{Address, Any, Error, Result, Route, Routed, Worker};
 use ockam_node::{Context, ProcessorBuilder, WorkerBuilder};
 use ockam_transport_core::TransportError;
 use tokio::io::AsyncWriteExt;
-use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
 use tokio::net::TcpStream;
-use tracing::{debug, info, trace, warn};
+use tracing::{debug, debug_span, info, info_span, trace, warn, Instrument};
 
 /// Enumerate all `TcpPortalWorker` states
 ///
@@ -36,26 +44,51 @@ enum State {
 /// after a new connection has been accepted.
 pub(crate) struct TcpPortalWorker {
     registry: TcpRegistry,
+    /// Address of the Inlet listen processor or of the Outlet listen worker
+    listener_address: Address,
+    /// [`LocalInfo`] of the message which started an Outlet
+    local_info: Vec<LocalInfo>,
+    metrics: TcpConnectionMetrics,
     state: State,
-    write_half: Option<OwnedWriteHalf>,
-    read_half: Option<OwnedReadHalf>,
+    write_half: Option<TcpWriteHalf>,
+    read_half: Option<TcpReadHalf>,
     peer: SocketAddr,
     addresses: Addresses,
     remote_route: Option<Route>,
     is_disconnecting: bool,
     portal_type: PortalType,
+    /// Authorizes the messages sent to the other side of the portal
+    outgoing_access_control: Arc<dyn OutgoingAccessControl>,
+    upload_rate_limiter: Option<RateLimiter>,
+    download_rate_limit: Option<TcpRateLimit>,
+    interceptor: Option<Box<dyn PortalInterceptor>>,
+    /// TLS settings used by an Outlet to connect to its target
+    tls: Option<TcpTlsClientOptions>,
+    /// Authorizes the connection of an Outlet to its target
+    connection_access_control: Option<Arc<dyn TcpOutletConnectionAccessControl>>,
+    /// Targets allowed for an Outlet, with the `host:port` of its target, resolved for each connection
+    allowlist: Option<(TcpOutletAllowlist, String)>,
+    /// Exchange run by an Outlet on every connection to its target
+    connection_initializer: Option<Arc<dyn TcpOutletConnectionInitializer>>,
+    /// Counts the connection to the Outlet picked by the Inlet until the worker stops
+    _connection: Option<TcpLoadBalancedConnection>,
 }
 
 impl TcpPortalWorker {
     /// Start a new `TcpPortalWorker` of type [`TypeName::Inlet`]
+    #[allow(clippy::too_many_arguments)]
     pub(super) async fn start_new_inlet(
         ctx: &Context,
         registry: TcpRegistry,
-        stream: TcpStream,
+        stream: (TcpReadHalf, TcpWriteHalf),
         peer: SocketAddr,
         ping_route: Route,
         addresses: Addresses,
         access_control: Arc<dyn IncomingAccessControl>,
+        outgoing_access_control: Arc<dyn OutgoingAccessControl>,
+        upload_rate_limit: Option<TcpRateLimit>,
+        download_rate_limit: Option<TcpRateLimit>,
+        connection: TcpLoadBalancedConnection,
     ) -> Result<()> {
         Self::start(
             ctx,
@@ -66,11 +99,22 @@ impl TcpPortalWorker {
             addresses,
             PortalType::Inlet,
             access_control,
+            outgoing_access_control,
+            upload_rate_limit,
+            download_rate_limit,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(connection),
+            vec![],
         )
         .await
     }
 
     /// Start a new `TcpPortalWorker` of type [`TypeName::Outlet`]
+    #[allow(clippy::too_many_arguments)]
     pub(super) async fn start_new_outlet(
         ctx: &Context,
         registry: TcpRegistry,
@@ -78,6 +122,15 @@ impl TcpPortalWorker {
         pong_route: Route,
         addresses: Addresses,
         access_control: Arc<dyn IncomingAccessControl>,
+        outgoing_access_control: Arc<dyn OutgoingAccessControl>,
+        upload_rate_limit: Option<TcpRateLimit>,
+        download_rate_limit: Option<TcpRateLimit>,
+        interceptor: Option<Box<dyn PortalInterceptor>>,
+        tls: Option<TcpTlsClientOptions>,
+        connection_access_control: Option<Arc<dyn TcpOutletConnectionAccessControl>>,
+        allowlist: Option<(TcpOutletAllowlist, String)>,
+        connection_initializer: Option<Arc<dyn TcpOutletConnectionInitializer>>,
+        local_info: Vec<LocalInfo>,
     ) -> Result<()> {
         Self::start(
             ctx,
@@ -88,6 +141,16 @@ impl TcpPortalWorker {
             addresses,
             PortalType::Outlet,
             access_control,
+            outgoing_access_control,
+            upload_rate_limit,
+            download_rate_limit,
+            interceptor,
+            tls,
+            connection_access_control,
+            allowlist,
+            connection_initializer,
+            None,
+            local_info,
         )
         .await
     }
@@ -99,10 +162,20 @@ impl TcpPortalWorker {
         registry: TcpRegistry,
         peer: SocketAddr,
         state: State,
-        stream: Option<TcpStream>,
+        stream: Option<(TcpReadHalf, TcpWriteHalf)>,
         addresses: Addresses,
         portal_type: PortalType,
         access_control: Arc<dyn IncomingAccessControl>,
+        outgoing_access_control: Arc<dyn OutgoingAccessControl>,
+        upload_rate_limit: Option<TcpRateLimit>,
+        download_rate_limit: Option<TcpRateLimit>,
+        interceptor: Option<Box<dyn PortalInterceptor>>,
+        tls: Option<TcpTlsClientOptions>,
+        connection_access_control: Option<Arc<dyn TcpOutletConnectionAccessControl>>,
+        allowlist: Option<(TcpOutletAllowlist, String)>,
+        connection_initializer: Option<Arc<dyn TcpOutletConnectionInitializer>>,
+        connection: Option<TcpLoadBalancedConnection>,
+        local_info: Vec<LocalInfo>,
     ) -> Result<()> {
         info!(
             "Creating new {:?} at internal: {}, remote: {}",
@@ -112,15 +185,15 @@ impl TcpPortalWorker {
         );
 
         let (rx, tx) = match stream {
-            Some(s) => {
-                let (rx, tx) = s.into_split();
-                (Some(rx), Some(tx))
-            }
+            Some((rx, tx)) => (Some(rx), Some(tx)),
             None => (None, None),
         };
 
         let worker = Self {
             registry,
+            listener_address: ctx.address(),
+            local_info,
+            metrics: TcpConnectionMetrics::default(),
             state,
             write_half: tx,
             read_half: rx,
@@ -129,6 +202,15 @@ impl TcpPortalWorker {
             remote_route: None,
             is_disconnecting: false,
             portal_type,
+            outgoing_access_control: outgoing_access_control.clone(),
+            upload_rate_limiter: RateLimiter::from_options(upload_rate_limit.as_ref()),
+            download_rate_limit,
+            interceptor,
+            tls,
+            connection_access_control,
+            allowlist,
+            connection_initializer,
+            _connection: connection,
         };
 
         let internal_mailbox = Mailbox::new(
@@ -140,7 +222,8 @@ impl TcpPortalWorker {
         let remote_mailbox = Mailbox::new(
             addresses.remote,
             access_control,
-            Arc::new(AllowAll), // FIXME: @ac Allow to respond anywhere using return_route
+            // FIXME: @ac Allow to respond anywhere using return_route, unless restricted
+            outgoing_access_control,
         );
 
         // start worker
@@ -173,14 +256,20 @@ impl TcpPortalWorker {
                 rx,
                 self.addresses.internal.clone(),
                 onward_route,
+                self.download_rate_limit.as_ref(),
+                self.metrics.clone(),
             );
 
             ProcessorBuilder::new(receiver)
                 .with_address(self.addresses.receiver.clone())
-                .with_outgoing_access_control(AllowOnwardAddresses(vec![
-                    next_hop,
-                    self.addresses.internal.clone(),
-                ])) // Only sends messages to `onward_route` and Sender
+                // Only sends messages to `onward_route`, when authorized, and Sender
+                .with_outgoing_access_control(AnyOutgoingAccessControl::new(vec![
+                    Arc::new(AllOutgoingAccessControl::new(vec![
+                        Arc::new(AllowOnwardAddress(next_hop)),
+                        self.outgoing_access_control.clone(),
+                    ])),
+                    Arc::new(AllowOnwardAddress(self.addresses.internal.clone())),
+                ]))
                 .start(ctx)
                 .await?;
 
@@ -286,31 +375,79 @@ impl TcpPortalWorker {
         Ok(State::ReceivePong)
     }
 
-    async fn handle_send_pong(&mut self, ctx: &Context, pong_route: Route) -> Result<State> {
-        // Respond to Inlet
-        ctx.send_from_address(
-            pong_route.clone(),
-            PortalMessage::Pong,
-            self.addresses.remote.clone(),
-        )
-        .await?;
+    /// Connect an Outlet to its target, in a TLS session if configured
+    async fn connect_target(&mut self) -> Result<(TcpReadHalf, TcpWriteHalf)> {
+        if let Some((allowlist, target)) = &self.allowlist {
+            // The DNS records of the target may have changed since the Outlet was created
+            self.peer = allowlist.resolve(target).await?;
+        }
+
+        if let Some(access_control) = &self.connection_access_control {
+            if !access_control
+                .is_authorized(&self.local_info, self.peer)
+                .await?
+            {
+                return Err(Error::new(
+                    Origin::Transport,
+                    Kind::Invalid,
+                    format!(
+                        "Outlet at: {} is not authorized to connect to {}",
+                        self.addresses.internal, self.peer
+                    ),
+                ));
+            }
+        }
+
+        let stream = TcpStream::connect(self.peer)
+            .await
+            .map_err(TransportError::from)?;
+        let (mut rx, mut tx) = match &self.tls {
+            Some(tls) => tls.connect(&self.peer.to_string(), stream).await?,
+            None => split_stream(stream),
+        };
+
+        if let Some(initializer) = &self.connection_initializer {
+            initializer.initialize(&mut rx, &mut tx).await?;
+        }
+
+        Ok((rx, tx))
+    }
 
+    async fn handle_send_pong(&mut self, ctx: &Context, pong_route: Route) -> Result<State> {
+        // Connect before answering, so that the Inlet only gets a pong
+        // when the target can be reached
         if self.write_half.is_none() {
-            let stream = TcpStream::connect(self.peer)
-                .await
-                .map_err(TransportError::from)?;
-            let (rx, tx) = stream.into_split();
+            let (rx, tx) = match self.connect_target().await {
+                Ok(stream) => stream,
+                Err(err) => {
+                    ctx.send_from_address(
+                        pong_route,
+                        PortalMessage::Disconnect,
+                        self.addresses.remote.clone(),
+                    )
+                    .await?;
+                    return Err(err);
+                }
+            };
             self.write_half = Some(tx);
             self.read_half = Some(rx);
 
-            self.start_receiver(ctx, pong_route.clone()).await?;
-
             debug!(
                 "Outlet at: {} successfully connected",
                 self.addresses.internal
             );
         }
 
+        // Respond to Inlet
+        ctx.send_from_address(
+            pong_route.clone(),
+            PortalMessage::Pong,
+            self.addresses.remote.clone(),
+        )
+        .await?;
+
+        self.start_receiver(ctx, pong_route.clone()).await?;
+
         debug!("Outlet at: {} sent pong", self.addresses.internal);
 
         self.remote_route = Some(pong_route);
@@ -325,20 +462,38 @@ impl Worker for TcpPortalWorker {
 
     async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
         let state = self.clone_state();
+        let span = info_span!(
+            "portal_connection",
+            portal = self.portal_type.str(),
+            address = %self.addresses.internal
+        );
 
         match state {
             State::SendPing { ping_route } => {
-                self.state = self.handle_send_ping(ctx, ping_route.clone()).await?;
+                self.state = self
+                    .handle_send_ping(ctx, ping_route.clone())
+                    .instrument(span)
+                    .await?;
             }
             State::SendPong { pong_route } => {
-                self.state = self.handle_send_pong(ctx, pong_route.clone()).await?;
+                self.state = self
+                    .handle_send_pong(ctx, pong_route.clone())
+                    .instrument(span)
+                    .await?;
             }
             State::ReceivePong | State::Initialized { .. } => {
                 return Err(TransportError::PortalInvalidState)?
             }
         }
 
-        self.registry.add_portal_worker(&self.addresses.remote);
+        let mut info = TcpPortalConnectionInfo::new(
+            self.addresses.remote.clone(),
+            self.listener_address.clone(),
+            self.peer,
+            self.metrics.clone(),
+        );
+        info.set_local_info(core::mem::take(&mut self.local_info));
+        self.registry.add_portal_worker(info);
 
         Ok(())
     }
@@ -375,11 +530,21 @@ impl Worker for TcpPortalWorker {
                     return Err(TransportError::PortalInvalidState)?;
                 }
 
+                let local_info = msg.local_message().local_info().to_vec();
                 let msg = PortalMessage::decode(msg.payload())?;
 
-                if let PortalMessage::Pong = msg {
-                } else {
-                    return Err(TransportError::Protocol)?;
+                match msg {
+                    PortalMessage::Pong => {
+                        self.registry
+                            .set_portal_worker_local_info(&self.addresses.remote, local_info);
+                    }
+                    PortalMessage::Disconnect => {
+                        // The Outlet could not connect to its target
+                        return self
+                            .start_disconnection(ctx, DisconnectionReason::Remote)
+                            .await;
+                    }
+                    _ => return Err(TransportError::Protocol)?,
                 }
 
                 self.start_receiver(ctx, return_route.clone()).await?;
@@ -418,14 +583,49 @@ impl Worker for TcpPortalWorker {
                     );
 
                     // Send to Tcp stream
+                    let local_info = msg.local_message().local_info().to_vec();
                     let msg = PortalMessage::decode(msg.payload())?;
 
                     match msg {
                         PortalMessage::Payload(payload) => {
+                            let span = debug_span!(
+                                "portal_payload",
+                                portal = self.portal_type.str(),
+                                size = payload.len()
+                            );
+                            let payload = match &mut self.interceptor {
+                                Some(interceptor) => {
+                                    match interceptor
+                                        .intercept(&local_info, payload)
+                                        .instrument(span.clone())
+                                        .await
+                                    {
+                                        Ok(payload) => payload,
+                                        Err(err) => {
+                                            warn!(
+                                                "Failed to intercept a payload for peer {} with error: {}",
+                                                self.peer, err
+                                            );
+                                            return self
+                                                .start_disconnection(
+                                                    ctx,
+                                                    DisconnectionReason::FailedTx,
+                                                )
+                                                .await;
+                                        }
+                                    }
+                                }
+                                None => payload,
+                            };
+
                             if let Some(tx) = &mut self.write_half {
-                                match tx.write_all(&payload).await {
-                                    Ok(()) => {}
+                                if let Some(rate_limiter) = &mut self.upload_rate_limiter {
+                                    rate_limiter.acquire(payload.len()).await;
+                                }
+                                match tx.write_all(&payload).instrument(span).await {
+                                    Ok(()) => self.metrics.record_sent(payload.len()),
                                     Err(err) => {
+                                        self.metrics.record_send_failure();
                                         warn!(
                                             "Failed to send message to peer {} with error: {}",
                                             self.peer, err
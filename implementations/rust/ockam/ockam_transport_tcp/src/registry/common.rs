@@ -1,8 +1,10 @@
+use crate::TcpConnectionMetrics;
 use core::fmt;
 use core::fmt::Formatter;
 use ockam_core::flow_control::FlowControlId;
-use ockam_core::Address;
+use ockam_core::{Address, LocalInfo};
 use std::net::SocketAddr;
+use std::time::SystemTime;
 
 /// Tcp connection mode
 #[derive(Copy, Debug, Clone)]
@@ -30,6 +32,7 @@ pub struct TcpSenderInfo {
     socket_address: SocketAddr,
     mode: TcpConnectionMode,
     flow_control_id: FlowControlId,
+    metrics: TcpConnectionMetrics,
 }
 
 impl TcpSenderInfo {
@@ -47,9 +50,16 @@ impl TcpSenderInfo {
             socket_address,
             mode,
             flow_control_id,
+            metrics: TcpConnectionMetrics::default(),
         }
     }
 
+    /// Set the metrics of the connection
+    pub(crate) fn with_metrics(mut self, metrics: TcpConnectionMetrics) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
     /// Address of the Sender worker
     pub fn address(&self) -> &Address {
         &self.address
@@ -70,6 +80,10 @@ impl TcpSenderInfo {
     pub fn mode(&self) -> &TcpConnectionMode {
         &self.mode
     }
+    /// Traffic counters of the connection
+    pub fn metrics(&self) -> &TcpConnectionMetrics {
+        &self.metrics
+    }
 }
 
 /// Information about specific Tcp sender (corresponds to one specific Tcp connection)
@@ -157,3 +171,64 @@ impl TcpListenerInfo {
         &self.flow_control_id
     }
 }
+
+/// Information about a connection going through a TCP Portal
+#[derive(Debug, Clone)]
+pub struct TcpPortalConnectionInfo {
+    address: Address,
+    listener_address: Address,
+    peer: SocketAddr,
+    local_info: Vec<LocalInfo>,
+    started_at: SystemTime,
+    metrics: TcpConnectionMetrics,
+}
+
+impl TcpPortalConnectionInfo {
+    /// Constructor
+    pub fn new(
+        address: Address,
+        listener_address: Address,
+        peer: SocketAddr,
+        metrics: TcpConnectionMetrics,
+    ) -> Self {
+        Self {
+            address,
+            listener_address,
+            peer,
+            local_info: vec![],
+            started_at: SystemTime::now(),
+            metrics,
+        }
+    }
+
+    /// Remote Address of the Portal worker handling the connection
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+    /// Address of the Inlet listen processor which accepted the connection,
+    /// or of the Outlet listen worker which created it
+    pub fn listener_address(&self) -> &Address {
+        &self.listener_address
+    }
+    /// Socket address of the TCP client for an Inlet, or of the target for an Outlet
+    pub fn peer(&self) -> SocketAddr {
+        self.peer
+    }
+    /// [`LocalInfo`] of the first message received from the other side of the Portal,
+    /// which identifies it when the Portal goes through a secure channel
+    pub fn local_info(&self) -> &[LocalInfo] {
+        &self.local_info
+    }
+    /// Time at which the connection was started
+    pub fn started_at(&self) -> SystemTime {
+        self.started_at
+    }
+    /// Traffic counters of the connection, bytes are sent to and received from the peer
+    pub fn metrics(&self) -> &TcpConnectionMetrics {
+        &self.metrics
+    }
+
+    pub(crate) fn set_local_info(&mut self, local_info: Vec<LocalInfo>) {
+        self.local_info = local_info;
+    }
+}
@@ -1,10 +1,24 @@
-use crate::{TcpListenerInfo, TcpReceiverInfo, TcpRegistry, TcpSenderInfo};
-use ockam_core::Address;
+use crate::options::SharedTcpConnectionSettings;
+use crate::{
+    TcpConnection, TcpListenerInfo, TcpPortalConnectionInfo, TcpReceiverInfo, TcpRegistry,
+    TcpSenderInfo,
+};
+use ockam_core::compat::string::String;
+use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Address, Error, LocalInfo, Result};
+use tokio::sync::Semaphore;
 
 impl TcpRegistry {
-    pub(crate) fn add_portal_worker(&self, addr: &Address) {
+    pub(crate) fn add_portal_worker(&self, info: TcpPortalConnectionInfo) {
         if let Ok(mut lock) = self.registry.write() {
-            lock.add_portal_worker(addr);
+            lock.add_portal_worker(info);
+        }
+    }
+    pub(crate) fn set_portal_worker_local_info(&self, addr: &Address, local_info: Vec<LocalInfo>) {
+        if let Ok(mut lock) = self.registry.write() {
+            lock.set_portal_worker_local_info(addr, local_info);
         }
     }
     pub(crate) fn remove_portal_worker(&self, addr: &Address) {
@@ -72,4 +86,54 @@ impl TcpRegistry {
             lock.remove_receiver_processor(addr);
         }
     }
+    /// Add a stream to the shared connection to the given peer, if any, and return that
+    /// connection with the window of the new stream.
+    /// Return an error if the shared connection was opened with different settings
+    pub(crate) fn open_shared_stream(
+        &self,
+        peer: &str,
+        settings: &SharedTcpConnectionSettings,
+        stream_address: &Address,
+        window: usize,
+    ) -> Result<Option<(TcpConnection, Arc<Semaphore>)>> {
+        match self.registry.write() {
+            Ok(mut lock) => lock.open_shared_stream(peer, settings, stream_address, window),
+            Err(_) => Ok(None),
+        }
+    }
+    /// Share a new connection to the given peer and add a stream to it.
+    /// If another connection to that peer was shared in the meantime, the stream is added
+    /// to that connection instead, which is returned
+    pub(crate) fn share_connection(
+        &self,
+        peer: String,
+        connection: TcpConnection,
+        settings: SharedTcpConnectionSettings,
+        stream_address: &Address,
+        window: usize,
+    ) -> Result<(TcpConnection, Arc<Semaphore>)> {
+        let mut lock = self.registry.write().map_err(|_| {
+            Error::new(
+                Origin::Transport,
+                Kind::Internal,
+                "the TCP registry is poisoned",
+            )
+        })?;
+        lock.share_connection(peer, connection, settings, stream_address, window)
+    }
+    /// Remove a stream from its shared connection.
+    /// Return true if that was the last stream and the connection can be stopped,
+    /// or None if the stream is unknown
+    pub(crate) fn close_shared_stream(&self, stream_address: &Address) -> Option<bool> {
+        self.registry
+            .write()
+            .ok()
+            .and_then(|mut lock| lock.close_shared_stream(stream_address))
+    }
+    /// Let a stream send one more message, after one of its messages was written
+    pub(crate) fn release_stream_window(&self, stream_address: &Address) {
+        if let Ok(lock) = self.registry.read() {
+            lock.release_stream_window(stream_address);
+        }
+    }
 }
@@ -1,23 +1,50 @@
-use crate::{TcpListenerInfo, TcpReceiverInfo, TcpSenderInfo};
-use ockam_core::Address;
+use crate::options::SharedTcpConnectionSettings;
+use crate::{
+    TcpConnection, TcpListenerInfo, TcpPortalConnectionInfo, TcpReceiverInfo, TcpSenderInfo,
+};
+use ockam_core::compat::string::String;
+use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Address, Error, LocalInfo, Result};
+use tokio::sync::Semaphore;
+
+/// Outgoing connection shared by several users, see [`crate::TcpConnectionOptions::shared`]
+pub(super) struct SharedTcpConnection {
+    pub(super) peer: String,
+    pub(super) connection: TcpConnection,
+    pub(super) settings: SharedTcpConnectionSettings,
+    /// Stream of each user, with the window of messages it can still send to the connection
+    pub(super) streams: Vec<(Address, Arc<Semaphore>)>,
+}
 
 #[derive(Default)]
 pub(super) struct InternalRegistry {
-    pub(super) portal_workers: Vec<Address>,
+    pub(super) portal_workers: Vec<TcpPortalConnectionInfo>,
     pub(super) portal_receiver_processors: Vec<Address>,
     pub(super) inlet_listener_processors: Vec<Address>,
     pub(super) outlet_listener_workers: Vec<Address>,
     pub(super) listener_processors: Vec<TcpListenerInfo>,
     pub(super) sender_workers: Vec<TcpSenderInfo>,
     pub(super) receiver_processors: Vec<TcpReceiverInfo>,
+    pub(super) shared_connections: Vec<SharedTcpConnection>,
 }
 
 impl InternalRegistry {
-    pub(super) fn add_portal_worker(&mut self, addr: &Address) {
-        self.portal_workers.push(addr.clone())
+    pub(super) fn add_portal_worker(&mut self, info: TcpPortalConnectionInfo) {
+        self.portal_workers.push(info)
     }
     pub(super) fn remove_portal_worker(&mut self, addr: &Address) {
-        self.portal_workers.retain(|x| x != addr);
+        self.portal_workers.retain(|x| x.address() != addr);
+    }
+    pub(super) fn set_portal_worker_local_info(
+        &mut self,
+        addr: &Address,
+        local_info: Vec<LocalInfo>,
+    ) {
+        if let Some(info) = self.portal_workers.iter_mut().find(|x| x.address() == addr) {
+            info.set_local_info(local_info);
+        }
     }
     pub(super) fn add_portal_receiver_processor(&mut self, addr: &Address) {
         self.portal_receiver_processors.push(addr.clone())
@@ -48,6 +75,16 @@ impl InternalRegistry {
     }
     pub(super) fn remove_sender_worker(&mut self, addr: &Address) {
         self.sender_workers.retain(|x| x.address() != addr);
+        // the streams of a closed connection are stopped when their window is closed
+        self.shared_connections.retain(|x| {
+            if x.connection.sender_address() != addr {
+                return true;
+            }
+            for (_, window) in &x.streams {
+                window.close();
+            }
+            false
+        });
     }
     pub(super) fn add_receiver_processor(&mut self, info: TcpReceiverInfo) {
         self.receiver_processors.push(info)
@@ -55,4 +92,76 @@ impl InternalRegistry {
     pub(super) fn remove_receiver_processor(&mut self, addr: &Address) {
         self.receiver_processors.retain(|x| x.address() != addr);
     }
+    pub(super) fn open_shared_stream(
+        &mut self,
+        peer: &str,
+        settings: &SharedTcpConnectionSettings,
+        stream_address: &Address,
+        window: usize,
+    ) -> Result<Option<(TcpConnection, Arc<Semaphore>)>> {
+        let shared = match self.shared_connections.iter_mut().find(|x| x.peer == peer) {
+            Some(shared) => shared,
+            None => return Ok(None),
+        };
+        if &shared.settings != settings {
+            return Err(Error::new(
+                Origin::Transport,
+                Kind::Conflict,
+                format!("the connection shared with {peer} was opened with different options"),
+            ));
+        }
+        let window = Arc::new(Semaphore::new(window));
+        shared
+            .streams
+            .push((stream_address.clone(), window.clone()));
+        Ok(Some((shared.connection.clone(), window)))
+    }
+    pub(super) fn share_connection(
+        &mut self,
+        peer: String,
+        connection: TcpConnection,
+        settings: SharedTcpConnectionSettings,
+        stream_address: &Address,
+        window: usize,
+    ) -> Result<(TcpConnection, Arc<Semaphore>)> {
+        if let Some(shared) = self.open_shared_stream(&peer, &settings, stream_address, window)? {
+            return Ok(shared);
+        }
+        let window = Arc::new(Semaphore::new(window));
+        self.shared_connections.push(SharedTcpConnection {
+            peer,
+            connection: connection.clone(),
+            settings,
+            streams: vec![(stream_address.clone(), window.clone())],
+        });
+        Ok((connection, window))
+    }
+    pub(super) fn close_shared_stream(&mut self, stream_address: &Address) -> Option<bool> {
+        let position = self
+            .shared_connections
+            .iter()
+            .position(|x| x.streams.iter().any(|(a, _)| a == stream_address))?;
+        let shared = &mut self.shared_connections[position];
+        shared.streams.retain(|(address, window)| {
+            if address == stream_address {
+                window.close();
+            }
+            address != stream_address
+        });
+        if shared.streams.is_empty() {
+            self.shared_connections.remove(position);
+            return Some(true);
+        }
+        Some(false)
+    }
+    pub(super) fn release_stream_window(&self, stream_address: &Address) {
+        let window = self
+            .shared_connections
+            .iter()
+            .flat_map(|x| x.streams.iter())
+            .find(|(address, _)| address == stream_address);
+        if let Some((_, window)) = window {
+            window.add_permits(1);
+        }
+    }
 }
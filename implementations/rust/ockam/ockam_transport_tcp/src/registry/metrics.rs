@@ -0,0 +1,105 @@
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
+use ockam_core::compat::sync::Arc;
+
+/// Counters of the traffic on a TCP connection.
+///
+/// The counters are shared by the sender and the receiver of the connection, and by all the
+/// clones of this value, so they can be read while the connection is running
+#[derive(Clone, Debug, Default)]
+pub struct TcpConnectionMetrics {
+    inner: Arc<TcpConnectionMetricsInner>,
+}
+
+#[derive(Debug, Default)]
+struct TcpConnectionMetricsInner {
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    messages_sent: AtomicU64,
+    messages_received: AtomicU64,
+    send_failures: AtomicU64,
+    receive_failures: AtomicU64,
+    failed_connect_attempts: AtomicU64,
+    /// Time taken to establish an outgoing connection in microseconds, 0 if unknown
+    connect_latency_micros: AtomicU64,
+}
+
+impl TcpConnectionMetrics {
+    /// Number of bytes written to the connection
+    pub fn bytes_sent(&self) -> u64 {
+        self.inner.bytes_sent.load(Ordering::Relaxed)
+    }
+
+    /// Number of bytes read from the connection
+    pub fn bytes_received(&self) -> u64 {
+        self.inner.bytes_received.load(Ordering::Relaxed)
+    }
+
+    /// Number of messages written to the connection
+    pub fn messages_sent(&self) -> u64 {
+        self.inner.messages_sent.load(Ordering::Relaxed)
+    }
+
+    /// Number of messages read from the connection, excluding heartbeats
+    pub fn messages_received(&self) -> u64 {
+        self.inner.messages_received.load(Ordering::Relaxed)
+    }
+
+    /// Number of messages which could not be written to the connection
+    pub fn send_failures(&self) -> u64 {
+        self.inner.send_failures.load(Ordering::Relaxed)
+    }
+
+    /// Number of messages which could not be read from the connection, or decoded
+    pub fn receive_failures(&self) -> u64 {
+        self.inner.receive_failures.load(Ordering::Relaxed)
+    }
+
+    /// Number of failed attempts before the connection was established
+    pub fn failed_connect_attempts(&self) -> u64 {
+        self.inner.failed_connect_attempts.load(Ordering::Relaxed)
+    }
+
+    /// Time taken to establish an outgoing connection, including the failed attempts.
+    /// None for incoming connections
+    pub fn connect_latency(&self) -> Option<Duration> {
+        match self.inner.connect_latency_micros.load(Ordering::Relaxed) {
+            0 => None,
+            micros => Some(Duration::from_micros(micros)),
+        }
+    }
+
+    pub(crate) fn record_sent(&self, bytes: usize) {
+        self.inner
+            .bytes_sent
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+        self.inner.messages_sent.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_received(&self, bytes: usize) {
+        self.inner
+            .bytes_received
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+        self.inner.messages_received.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_send_failure(&self) {
+        self.inner.send_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_receive_failure(&self) {
+        self.inner.receive_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_connect(&self, latency: Duration, failed_attempts: u32) {
+        let micros = u64::try_from(latency.as_micros())
+            .unwrap_or(u64::MAX)
+            .max(1);
+        self.inner
+            .connect_latency_micros
+            .store(micros, Ordering::Relaxed);
+        self.inner
+            .failed_connect_attempts
+            .store(failed_attempts as u64, Ordering::Relaxed);
+    }
+}
@@ -1,8 +1,10 @@
 mod common;
 mod crate_api;
 mod internal;
+mod metrics;
 #[allow(clippy::module_inception)]
 mod registry;
 
 pub use common::*;
+pub use metrics::*;
 pub use registry::*;
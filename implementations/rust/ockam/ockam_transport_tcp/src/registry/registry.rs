@@ -1,6 +1,9 @@
 use crate::registry::internal::InternalRegistry;
-use crate::{TcpListenerInfo, TcpReceiverInfo, TcpSenderInfo};
+use crate::{TcpListenerInfo, TcpPortalConnectionInfo, TcpReceiverInfo, TcpSenderInfo};
+use core::fmt;
+use core::fmt::Formatter;
 use ockam_core::compat::sync::{Arc, RwLock};
+use ockam_core::Address;
 
 /// Registry of all active workers and processors in TCP Transport to ease their lifecycle management
 #[derive(Default, Clone)]
@@ -23,4 +26,42 @@ impl TcpRegistry {
     pub fn get_all_listeners(&self) -> Vec<TcpListenerInfo> {
         self.registry.read().unwrap().listener_processors.clone()
     }
+
+    /// Return the connections going through the Inlets and Outlets
+    pub fn get_all_portal_connections(&self) -> Vec<TcpPortalConnectionInfo> {
+        self.registry.read().unwrap().portal_workers.clone()
+    }
+
+    /// Return the connections going through the Inlet or Outlet with the given listener [`Address`]
+    pub fn get_portal_connections(
+        &self,
+        listener_address: &Address,
+    ) -> Vec<TcpPortalConnectionInfo> {
+        self.registry
+            .read()
+            .unwrap()
+            .portal_workers
+            .iter()
+            .filter(|x| x.listener_address() == listener_address)
+            .cloned()
+            .collect()
+    }
+
+    /// Return the number of users of a shared connection, given its sender [`Address`],
+    /// or None if the connection is not shared. Each user has its own stream
+    pub fn get_shared_connection_users(&self, sender_address: &Address) -> Option<usize> {
+        self.registry
+            .read()
+            .unwrap()
+            .shared_connections
+            .iter()
+            .find(|x| x.connection.sender_address() == sender_address)
+            .map(|x| x.streams.len())
+    }
+}
+
+impl fmt::Debug for TcpRegistry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TcpRegistry").finish_non_exhaustive()
+    }
 }
@@ -1,4 +1,4 @@
-use crate::TcpConnectionMode;
+use crate::{TcpConnectionMetrics, TcpConnectionMode, TcpRegistry};
 use core::fmt;
 use core::fmt::Formatter;
 use ockam_core::compat::net::{SocketAddr, ToSocketAddrs};
@@ -15,6 +15,12 @@ pub struct TcpConnection {
     socket_address: SocketAddr,
     mode: TcpConnectionMode,
     flow_control_id: FlowControlId,
+    /// Sender of the underlying connection, which differs from the sender address when
+    /// the connection is shared and the sender address is the stream of this user
+    connection_sender_address: Address,
+    /// Registry tracking the streams of the connection when it is shared
+    shared_registry: Option<TcpRegistry>,
+    metrics: TcpConnectionMetrics,
 }
 
 impl fmt::Display for TcpConnection {
@@ -43,24 +49,60 @@ impl TcpConnection {
         flow_control_id: FlowControlId,
     ) -> Self {
         Self {
+            connection_sender_address: sender_address.clone(),
             sender_address,
             receiver_address,
             socket_address,
             mode,
             flow_control_id,
+            shared_registry: None,
+            metrics: TcpConnectionMetrics::default(),
         }
     }
+    /// Set the metrics of the connection
+    pub(crate) fn with_metrics(mut self, metrics: TcpConnectionMetrics) -> Self {
+        self.metrics = metrics;
+        self
+    }
+    /// Mark this connection as shared, messages being sent through the given stream of
+    /// this user, which is tracked in the registry with the streams of the other users
+    pub(crate) fn shared(mut self, registry: TcpRegistry, stream_address: Address) -> Self {
+        self.shared_registry = Some(registry);
+        self.sender_address = stream_address;
+        self
+    }
     /// Stops the [`TcpConnection`], this method must be called to avoid
     /// leakage of the connection.
-    /// Simply dropping this object won't close the connection
+    /// Simply dropping this object won't close the connection.
+    /// A shared connection is only closed when it is stopped by its last user,
+    /// the other users only stop their stream, which removes their consumers
     pub async fn stop(&self, context: &Context) -> Result<()> {
-        context.stop_worker(self.sender_address.clone()).await
+        if let Some(registry) = &self.shared_registry {
+            let last_stream = registry.close_shared_stream(&self.sender_address);
+            let _ = context.stop_worker(self.sender_address.clone()).await;
+            if last_stream == Some(false) {
+                return Ok(());
+            }
+        }
+        context
+            .stop_worker(self.connection_sender_address.clone())
+            .await
+    }
+    /// True if the connection is shared with other users
+    pub fn is_shared(&self) -> bool {
+        self.shared_registry.is_some()
     }
     /// Corresponding [`TcpSendWorker`](super::workers::TcpSendWorker) [`Address`] that can be used
-    /// in a route to send messages to the other side of the TCP connection
+    /// in a route to send messages to the other side of the TCP connection.
+    /// For a shared connection, this is the address of the stream of this user
     pub fn sender_address(&self) -> &Address {
         &self.sender_address
     }
+    /// [`TcpSendWorker`](super::workers::TcpSendWorker) [`Address`] of the underlying connection,
+    /// which is the same for all the users of a shared connection
+    pub fn connection_sender_address(&self) -> &Address {
+        &self.connection_sender_address
+    }
     /// Corresponding [`TcpReceiveProcessor`](super::workers::TcpRecvProcessor) [`Address`]
     pub fn receiver_address(&self) -> &Address {
         &self.receiver_address
@@ -77,6 +119,10 @@ impl TcpConnection {
     pub fn mode(&self) -> TcpConnectionMode {
         self.mode
     }
+    /// Traffic counters of the connection
+    pub fn metrics(&self) -> &TcpConnectionMetrics {
+        &self.metrics
+    }
 }
 
 /// Result of [`TcpTransport::listen`] call.
@@ -150,13 +196,62 @@ pub fn resolve_peer(peer: String) -> Result<SocketAddr> {
     Err(TransportError::InvalidAddress)?
 }
 
+/// Resolve the given peer to all its [`SocketAddr`](std::net::SocketAddr), sorted in the order
+/// in which connections should be attempted as described in RFC 8305: the address families
+/// alternate, starting with the family of the first address returned by the resolver
+pub(crate) fn resolve_peer_addresses(peer: &str) -> Result<Vec<SocketAddr>> {
+    // Try to parse as SocketAddr
+    if let Ok(p) = parse_socket_addr(peer) {
+        return Ok(vec![p]);
+    }
+
+    // Try to resolve hostname
+    let addresses: Vec<SocketAddr> = peer
+        .to_socket_addrs()
+        .map_err(|_| TransportError::InvalidAddress)?
+        .collect();
+    if addresses.is_empty() {
+        return Err(TransportError::InvalidAddress)?;
+    }
+
+    Ok(interleave_address_families(addresses))
+}
+
+/// Alternate the IPv6 and IPv4 addresses, starting with the family of the first address
+fn interleave_address_families(addresses: Vec<SocketAddr>) -> Vec<SocketAddr> {
+    let prefer_ipv6 = addresses.first().map(|a| a.is_ipv6()).unwrap_or(true);
+    let (preferred, other): (Vec<_>, Vec<_>) = addresses
+        .into_iter()
+        .partition(|a| a.is_ipv6() == prefer_ipv6);
+    let mut preferred = preferred.into_iter();
+    let mut other = other.into_iter();
+
+    let mut result = vec![];
+    loop {
+        match (preferred.next(), other.next()) {
+            (None, None) => break,
+            (p, o) => result.extend(p.into_iter().chain(o)),
+        }
+    }
+    result
+}
+
+/// Return the host of a `host:port` peer
+pub(crate) fn peer_host(peer: &str) -> &str {
+    peer.rsplit_once(':')
+        .map(|(host, _)| host)
+        .unwrap_or(peer)
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+}
+
 pub(super) fn parse_socket_addr(s: &str) -> Result<SocketAddr> {
     Ok(s.parse().map_err(|_| TransportError::InvalidAddress)?)
 }
 
 #[cfg(test)]
 mod test {
-    use crate::transport::common::parse_socket_addr;
+    use crate::transport::common::{interleave_address_families, parse_socket_addr};
     use core::fmt::Debug;
     use ockam_core::{Error, Result};
     use ockam_transport_core::TransportError;
@@ -208,4 +303,35 @@ mod test {
         let result = parse_socket_addr("127.0.0.1:8080");
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_interleave_address_families() {
+        let addresses: Vec<_> = [
+            "[::1]:80",
+            "[::2]:80",
+            "[::3]:80",
+            "10.0.0.1:80",
+            "10.0.0.2:80",
+        ]
+        .iter()
+        .map(|a| parse_socket_addr(a).unwrap())
+        .collect();
+        let expected: Vec<_> = [
+            "[::1]:80",
+            "10.0.0.1:80",
+            "[::2]:80",
+            "10.0.0.2:80",
+            "[::3]:80",
+        ]
+        .iter()
+        .map(|a| parse_socket_addr(a).unwrap())
+        .collect();
+        assert_eq!(interleave_address_families(addresses), expected);
+
+        let addresses: Vec<_> = ["10.0.0.1:80", "[::1]:80"]
+            .iter()
+            .map(|a| parse_socket_addr(a).unwrap())
+            .collect();
+        assert_eq!(interleave_address_families(addresses.clone()), addresses);
+    }
 }
@@ -1,7 +1,21 @@
-use crate::transport::common::{resolve_peer, TcpConnection};
-use crate::workers::{Addresses, TcpRecvProcessor, TcpSendWorker};
-use crate::{TcpConnectionMode, TcpConnectionOptions, TcpTransport};
-use ockam_core::{Address, Result};
+use crate::transport::common::{resolve_peer_addresses, TcpConnection};
+use crate::workers::{
+    split_stream, Addresses, TcpRecvProcessor, TcpSendWorker, TcpSharedStreamWorker,
+    SHARED_STREAM_WINDOW,
+};
+use crate::{
+    TcpConnectionMetrics, TcpConnectionMode, TcpConnectionOptions, TcpProxy, TcpTransport,
+};
+use core::time::Duration;
+use ockam_core::compat::sync::Arc;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::flow_control::FlowControlId;
+use ockam_core::{Address, Error, Result};
+use std::net::{IpAddr, SocketAddr};
+use tokio::net::TcpStream;
+use tokio::sync::Semaphore;
+use tokio::time::Instant;
+use tracing::debug;
 
 impl TcpTransport {
     /// Establish an outgoing TCP connection.
@@ -16,19 +30,83 @@ impl TcpTransport {
     /// let connection = tcp.connect("127.0.0.1:5000", TcpConnectionOptions::new()).await?; // and connect to port 5000
     /// # Ok(()) }
     /// ```
+    ///
+    /// The connection can be dialed through a SOCKS5 or HTTP CONNECT proxy with
+    /// [`TcpConnectionOptions::with_proxy`], or with the [`OCKAM_PROXY`](crate::OCKAM_PROXY)
+    /// environment variable.
+    ///
+    /// When the peer hostname resolves to several IPv6 and IPv4 addresses, connections to these
+    /// addresses are raced as described in RFC 8305 ("Happy Eyeballs") and the first established
+    /// connection is kept.
+    ///
+    /// The connection can be wrapped in a standard TLS session beneath the Ockam protocol with
+    /// [`TcpConnectionOptions::with_tls`], when the network only lets TLS traffic through.
+    ///
+    /// The connect timeout, the retries of failed attempts, TCP keepalive, `TCP_NODELAY` and
+    /// the bandwidth limits can also be configured with the [`TcpConnectionOptions`].
     pub async fn connect(
         &self,
         peer: impl Into<String>,
         options: TcpConnectionOptions,
     ) -> Result<TcpConnection> {
-        // Resolve peer address
-        let socket = resolve_peer(peer.into())?;
+        let peer = peer.into();
+
+        // reuse a shared connection to the same peer
+        let shared_settings = options.shared_settings();
+        let stream_address = Address::random_tagged("TcpSharedStream");
+        if options.shared {
+            if let Some((connection, window)) = self.registry.open_shared_stream(
+                &peer,
+                &shared_settings,
+                &stream_address,
+                SHARED_STREAM_WINDOW,
+            )? {
+                debug!(%peer, "Reusing shared connection {}", connection.sender_address());
+                return self
+                    .start_shared_stream(connection, stream_address, window, &options.consumer)
+                    .await;
+            }
+        }
+
+        let proxy = match &options.proxy {
+            Some(proxy) => Some(proxy.clone()),
+            None if !is_local_peer(&peer) => TcpProxy::from_env()?,
+            None => None,
+        };
+
+        let start = Instant::now();
+        let mut retry = 0;
+        let (stream, socket) = loop {
+            match Self::dial(&peer, proxy.as_ref(), options.connect_timeout).await {
+                Ok(result) => break result,
+                Err(err) => match options.retry_policy.delay(retry) {
+                    Some(delay) => {
+                        debug!(%peer, %err, "Failed to connect, retrying in {delay:?}");
+                        tokio::time::sleep(delay).await;
+                        retry += 1;
+                    }
+                    None => return Err(err),
+                },
+            }
+        };
 
-        let (read_half, write_half) = TcpSendWorker::connect(socket).await?;
+        let metrics = TcpConnectionMetrics::default();
+        metrics.record_connect(start.elapsed(), retry);
+
+        let stream =
+            TcpSendWorker::setup_connection(stream, options.keepalive.as_ref(), options.nodelay)?;
+        let (read_half, write_half) = match &options.tls {
+            Some(tls) => tls.connect(&peer, stream).await?,
+            None => split_stream(stream),
+        };
 
         let mode = TcpConnectionMode::Outgoing;
         let addresses = Addresses::generate(mode);
 
+        let shared = options.shared;
+        let consumers = options.consumer.clone();
+        let upload_rate_limit = options.upload_rate_limit.clone();
+        let download_rate_limit = options.download_rate_limit.clone();
         options.setup_flow_control(self.ctx.flow_controls(), &addresses);
         let flow_control_id = options.flow_control_id.clone();
         let access_control = options.create_access_control(self.ctx.flow_controls());
@@ -42,6 +120,8 @@ impl TcpTransport {
             mode,
             access_control.sender_incoming_access_control,
             &flow_control_id,
+            upload_rate_limit.as_ref(),
+            metrics.clone(),
         )
         .await?;
 
@@ -54,20 +134,142 @@ impl TcpTransport {
             mode,
             &flow_control_id,
             access_control.receiver_outgoing_access_control,
+            download_rate_limit.as_ref(),
+            metrics.clone(),
         )
         .await?;
 
-        Ok(TcpConnection::new(
+        let connection = TcpConnection::new(
             addresses.sender_address().clone(),
             addresses.receiver_address().clone(),
             socket,
             mode,
             flow_control_id,
-        ))
+        )
+        .with_metrics(metrics);
+
+        if !shared {
+            return Ok(connection);
+        }
+
+        // another connection to the same peer may have been shared while this one was opened,
+        // in which case this one is closed and the other one is used
+        let sender_address = connection.sender_address().clone();
+        let (shared_connection, window) = match self.registry.share_connection(
+            peer.clone(),
+            connection,
+            shared_settings,
+            &stream_address,
+            SHARED_STREAM_WINDOW,
+        ) {
+            Ok(shared) => shared,
+            Err(err) => {
+                let _ = self.ctx.stop_worker(sender_address).await;
+                return Err(err);
+            }
+        };
+        if shared_connection.sender_address() != &sender_address {
+            debug!(%peer, "Closing {sender_address}, connection {} was shared first", shared_connection.sender_address());
+            self.ctx.stop_worker(sender_address).await?;
+        }
+        self.start_shared_stream(shared_connection, stream_address, window, &consumers)
+            .await
     }
 
-    /// Interrupt an active TCP connection given its Sender `Address`
+    /// Start the stream of a new user of a shared connection
+    async fn start_shared_stream(
+        &self,
+        connection: TcpConnection,
+        stream_address: Address,
+        window: Arc<Semaphore>,
+        consumers: &[FlowControlId],
+    ) -> Result<TcpConnection> {
+        if let Err(err) = TcpSharedStreamWorker::start(
+            &self.ctx,
+            stream_address.clone(),
+            connection.sender_address().clone(),
+            window,
+        )
+        .await
+        {
+            if self.registry.close_shared_stream(&stream_address) == Some(true) {
+                let _ = self
+                    .ctx
+                    .stop_worker(connection.sender_address().clone())
+                    .await;
+            }
+            return Err(err);
+        }
+
+        // the stream is an address of the connection, like its sender, for the flow control
+        // of the receiver, and the consumers of this user are only reachable through it
+        let flow_controls = self.ctx.flow_controls();
+        flow_controls.add_producer(
+            connection.receiver_address().clone(),
+            connection.flow_control_id(),
+            None,
+            vec![connection.sender_address().clone(), stream_address.clone()],
+        );
+        for id in consumers {
+            flow_controls.add_consumer(stream_address.clone(), id);
+        }
+
+        Ok(connection.shared(self.registry.clone(), stream_address))
+    }
+
+    /// Make one attempt to open a TCP stream to the peer, directly or through a proxy
+    async fn dial(
+        peer: &str,
+        proxy: Option<&TcpProxy>,
+        connect_timeout: Option<Duration>,
+    ) -> Result<(TcpStream, SocketAddr)> {
+        let dial = async {
+            match proxy {
+                Some(proxy) => proxy.connect(peer).await,
+                None => {
+                    // Resolve all the peer addresses and race connections to them
+                    let sockets = resolve_peer_addresses(peer)?;
+                    TcpSendWorker::connect_any(sockets).await
+                }
+            }
+        };
+
+        match connect_timeout {
+            Some(connect_timeout) => {
+                tokio::time::timeout(connect_timeout, dial)
+                    .await
+                    .map_err(|_| {
+                        Error::new(
+                            Origin::Transport,
+                            Kind::Timeout,
+                            format!("timed out after {connect_timeout:?} connecting to {peer}"),
+                        )
+                    })?
+            }
+            None => dial.await,
+        }
+    }
+
+    /// Interrupt an active TCP connection given its Sender `Address`.
+    /// A shared connection is interrupted for all its users when given its
+    /// [`TcpConnection::connection_sender_address`], use [`TcpConnection::stop`]
+    /// to only release it for one user
     pub async fn disconnect(&self, address: impl Into<Address>) -> Result<()> {
         self.ctx.stop_worker(address.into()).await
     }
 }
+
+/// Return true if the peer is on the local host, in which case the proxy configured
+/// with an environment variable is not used
+fn is_local_peer(peer: &str) -> bool {
+    let host = peer
+        .rsplit_once(':')
+        .map(|(host, _)| host)
+        .unwrap_or(peer)
+        .trim_start_matches('[')
+        .trim_end_matches(']');
+    match host.parse::<IpAddr>() {
+        Ok(ip) => ip.is_loopback() || ip.is_unspecified(),
+        Err(_) => host.eq_ignore_ascii_case("localhost"),
+    }
+}
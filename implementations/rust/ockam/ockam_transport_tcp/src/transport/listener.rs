@@ -11,6 +11,9 @@ impl TcpTransport {
     /// This can be useful, for example, when binding to port 0 to figure out
     /// which port was actually bound.
     ///
+    /// With [`TcpListenerOptions::with_tls`], the accepted connections must be wrapped in a
+    /// standard TLS session beneath the Ockam protocol.
+    ///
     /// ```rust
     /// use ockam_transport_tcp::{TcpListenerOptions, TcpTransport};
     /// # use ockam_node::Context;
@@ -3,8 +3,12 @@ mod connection;
 mod lifecycle;
 mod listener;
 mod portals;
+mod proxy;
+pub(crate) mod tls;
 
 pub use common::*;
+pub use proxy::*;
+pub use tls::{TcpTlsClientOptions, TcpTlsServerOptions};
 
 pub use crate::portal::options::*;
 
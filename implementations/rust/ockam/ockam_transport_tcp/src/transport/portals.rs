@@ -1,8 +1,11 @@
 use crate::portal::TcpInletListenProcessor;
 use crate::transport::common::{parse_socket_addr, resolve_peer};
-use crate::{portal::TcpOutletListenWorker, TcpInletOptions, TcpOutletOptions, TcpTransport};
+use crate::{
+    portal::TcpOutletListenWorker, TcpInletOptions, TcpLoadBalancer, TcpOutletOptions, TcpTransport,
+};
 use ockam_core::compat::net::SocketAddr;
 use ockam_core::{Address, Result, Route};
+use ockam_transport_core::TransportError;
 
 impl TcpTransport {
     /// Create Tcp Inlet that listens on bind_addr, transforms Tcp stream into Ockam Routable
@@ -32,7 +35,48 @@ impl TcpTransport {
         TcpInletListenProcessor::start(
             &self.ctx,
             self.registry.clone(),
-            outlet_route.into(),
+            TcpLoadBalancer::single(outlet_route.into()),
+            socket_addr,
+            options,
+        )
+        .await
+    }
+
+    /// Create Tcp Inlet that distributes its connections across several Outlets.
+    /// Each new connection is sent to one of the healthy Outlets of the load balancer,
+    /// and the Outlets failing their health checks are ejected until they recover.
+    ///
+    /// ```rust
+    /// use ockam_transport_tcp::{TcpInletOptions, TcpLoadBalancer, TcpLoadBalancingOptions, TcpTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::{AllowAll, Result, route};
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let load_balancer = TcpLoadBalancer::new(
+    ///     vec![route!["outlet1"], route!["outlet2"]],
+    ///     TcpLoadBalancingOptions::new(),
+    /// );
+    ///
+    /// let tcp = TcpTransport::create(&ctx).await?;
+    /// tcp.create_load_balanced_inlet("inlet", load_balancer.clone(), TcpInletOptions::new()).await?;
+    /// let outlets = load_balancer.outlets();
+    /// # tcp.stop_inlet("inlet").await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn create_load_balanced_inlet(
+        &self,
+        bind_addr: impl Into<String>,
+        load_balancer: TcpLoadBalancer,
+        options: TcpInletOptions,
+    ) -> Result<(SocketAddr, Address)> {
+        if load_balancer.routes().is_empty() {
+            return Err(TransportError::InvalidAddress)?;
+        }
+
+        let socket_addr = parse_socket_addr(&bind_addr.into())?;
+        TcpInletListenProcessor::start(
+            &self.ctx,
+            self.registry.clone(),
+            load_balancer,
             socket_addr,
             options,
         )
@@ -83,13 +127,15 @@ impl TcpTransport {
         options: TcpOutletOptions,
     ) -> Result<()> {
         // Resolve peer address
-        let peer_addr = resolve_peer(peer.into())?;
+        let peer = peer.into();
+        let peer_addr = resolve_peer(peer.clone())?;
         TcpOutletListenWorker::start(
             &self.ctx,
             self.registry.clone(),
             address.into(),
+            peer.clone(),
             peer_addr,
-            options,
+            options.with_target(&peer),
         )
         .await?;
 
@@ -103,8 +149,15 @@ impl TcpTransport {
         peer: SocketAddr,
         options: TcpOutletOptions,
     ) -> Result<()> {
-        TcpOutletListenWorker::start(&self.ctx, self.registry.clone(), address, peer, options)
-            .await?;
+        TcpOutletListenWorker::start(
+            &self.ctx,
+            self.registry.clone(),
+            address,
+            peer.to_string(),
+            peer,
+            options,
+        )
+        .await?;
 
         Ok(())
     }
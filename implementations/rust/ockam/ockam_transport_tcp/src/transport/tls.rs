@@ -0,0 +1,311 @@
+use crate::transport::common::peer_host;
+use crate::workers::{split_tls_stream, TcpReadHalf, TcpWriteHalf};
+use core::time::Duration;
+use ockam_core::compat::sync::Arc;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Error, Result};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::server::AllowAnyAuthenticatedClient;
+use tokio_rustls::rustls::{
+    Certificate, ClientConfig, PrivateKey, RootCertStore, ServerConfig, ServerName,
+};
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+use tracing::debug;
+
+/// Maximum duration of a TLS handshake, so that a peer can't hold a listener indefinitely
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// TLS settings used to wrap an outgoing TCP connection.
+///
+/// The TLS session is established beneath the Ockam protocol, which stays end-to-end encrypted
+/// with its own secure channels. It is only useful when the network requires all the traffic
+/// to be standard TLS, for example to be inspected by a middlebox.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TcpTlsClientOptions {
+    server_name: Option<String>,
+    ca_certificates: Option<PathBuf>,
+    client_certificate: Option<(PathBuf, PathBuf)>,
+}
+
+impl TcpTlsClientOptions {
+    /// Verify the server with the platform trusted certificates, and send the host of the peer
+    /// as the server name (SNI)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Send this server name (SNI) and verify the server certificate against it,
+    /// instead of the host of the peer
+    pub fn with_server_name(mut self, server_name: impl Into<String>) -> Self {
+        self.server_name = Some(server_name.into());
+        self
+    }
+
+    /// Verify the server with the certificates of the given PEM file,
+    /// instead of the platform trusted certificates
+    pub fn with_ca_certificates(mut self, path: impl Into<PathBuf>) -> Self {
+        self.ca_certificates = Some(path.into());
+        self
+    }
+
+    /// Authenticate to the server with the certificate chain and the private key
+    /// of the given PEM files
+    pub fn with_client_certificate(
+        mut self,
+        certificate_chain: impl Into<PathBuf>,
+        private_key: impl Into<PathBuf>,
+    ) -> Self {
+        self.client_certificate = Some((certificate_chain.into(), private_key.into()));
+        self
+    }
+
+    /// Server name sent to the server, if it differs from the host of the peer
+    pub fn server_name(&self) -> Option<&str> {
+        self.server_name.as_deref()
+    }
+
+    /// PEM file of the certificates used to verify the server
+    pub fn ca_certificates(&self) -> Option<&Path> {
+        self.ca_certificates.as_deref()
+    }
+
+    /// Use the host of the `host:port` peer as the server name, unless one was set
+    pub(crate) fn with_default_server_name(mut self, peer: &str) -> Self {
+        if self.server_name.is_none() {
+            self.server_name = Some(peer_host(peer).to_string());
+        }
+        self
+    }
+
+    /// Perform a TLS handshake over an established stream to the `host:port` peer
+    pub(crate) async fn connect(
+        &self,
+        peer: &str,
+        stream: TcpStream,
+    ) -> Result<(TcpReadHalf, TcpWriteHalf)> {
+        let server_name = self
+            .server_name
+            .as_deref()
+            .unwrap_or_else(|| peer_host(peer));
+        let server_name = ServerName::try_from(server_name).map_err(|_| {
+            Error::new(
+                Origin::Transport,
+                Kind::Invalid,
+                format!("invalid TLS server name {server_name}"),
+            )
+        })?;
+
+        let connector = TlsConnector::from(Arc::new(self.client_config()?));
+        debug!(%peer, "Starting TLS handshake");
+        let stream = tokio::time::timeout(
+            TLS_HANDSHAKE_TIMEOUT,
+            connector.connect(server_name, stream),
+        )
+        .await
+        .map_err(|_| handshake_timeout(peer))?
+        .map_err(|e| handshake_error(peer, e))?;
+
+        Ok(split_tls_stream(stream.into()))
+    }
+
+    fn client_config(&self) -> Result<ClientConfig> {
+        let roots = match &self.ca_certificates {
+            Some(path) => root_store(&read_certificates(path)?)?,
+            None => native_root_store()?,
+        };
+
+        let builder = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots);
+        match &self.client_certificate {
+            Some((certificate_chain, private_key)) => builder
+                .with_client_auth_cert(
+                    read_certificates(certificate_chain)?,
+                    read_private_key(private_key)?,
+                )
+                .map_err(config_error),
+            None => Ok(builder.with_no_client_auth()),
+        }
+    }
+}
+
+/// TLS settings used to wrap the TCP connections accepted by a listener.
+///
+/// Clients which don't start a TLS session are rejected. See [`TcpTlsClientOptions`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TcpTlsServerOptions {
+    certificate_chain: PathBuf,
+    private_key: PathBuf,
+    client_ca_certificates: Option<PathBuf>,
+}
+
+impl TcpTlsServerOptions {
+    /// Present the certificate chain and use the private key of the given PEM files
+    pub fn new(certificate_chain: impl Into<PathBuf>, private_key: impl Into<PathBuf>) -> Self {
+        Self {
+            certificate_chain: certificate_chain.into(),
+            private_key: private_key.into(),
+            client_ca_certificates: None,
+        }
+    }
+
+    /// Require the clients to authenticate with a certificate issued by one of the
+    /// certificates of the given PEM file
+    pub fn with_client_ca_certificates(mut self, path: impl Into<PathBuf>) -> Self {
+        self.client_ca_certificates = Some(path.into());
+        self
+    }
+
+    /// PEM file of the certificate chain presented to the clients
+    pub fn certificate_chain(&self) -> &Path {
+        &self.certificate_chain
+    }
+
+    /// PEM file of the private key
+    pub fn private_key(&self) -> &Path {
+        &self.private_key
+    }
+
+    /// Load the certificates and the key. This is done once when the listener starts,
+    /// so that an invalid configuration is reported right away
+    pub(crate) fn acceptor(&self) -> Result<TlsAcceptor> {
+        let builder = ServerConfig::builder().with_safe_defaults();
+        let builder = match &self.client_ca_certificates {
+            Some(path) => {
+                let roots = root_store(&read_certificates(path)?)?;
+                builder.with_client_cert_verifier(AllowAnyAuthenticatedClient::new(roots).boxed())
+            }
+            None => builder.with_no_client_auth(),
+        };
+        let config = builder
+            .with_single_cert(
+                read_certificates(&self.certificate_chain)?,
+                read_private_key(&self.private_key)?,
+            )
+            .map_err(config_error)?;
+
+        Ok(TlsAcceptor::from(Arc::new(config)))
+    }
+}
+
+/// Perform a TLS handshake over a stream accepted by a listener
+pub(crate) async fn accept_tls(
+    acceptor: &TlsAcceptor,
+    peer: &str,
+    stream: TcpStream,
+) -> Result<(TcpReadHalf, TcpWriteHalf)> {
+    let stream = tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream))
+        .await
+        .map_err(|_| handshake_timeout(peer))?
+        .map_err(|e| handshake_error(peer, e))?;
+
+    Ok(split_tls_stream(stream.into()))
+}
+
+/// Return the host of a `host:port` peer, without the brackets of an IPv6 address
+fn read_certificates(path: &Path) -> Result<Vec<Certificate>> {
+    let mut reader = BufReader::new(File::open(path).map_err(|e| read_error(path, e))?);
+    let certificates = rustls_pemfile::certs(&mut reader).map_err(|e| read_error(path, e))?;
+    if certificates.is_empty() {
+        return Err(Error::new(
+            Origin::Transport,
+            Kind::Invalid,
+            format!("no certificate found in {}", path.display()),
+        ));
+    }
+    Ok(certificates.into_iter().map(Certificate).collect())
+}
+
+fn read_private_key(path: &Path) -> Result<PrivateKey> {
+    let mut reader = BufReader::new(File::open(path).map_err(|e| read_error(path, e))?);
+    for item in rustls_pemfile::read_all(&mut reader).map_err(|e| read_error(path, e))? {
+        match item {
+            rustls_pemfile::Item::PKCS8Key(key)
+            | rustls_pemfile::Item::RSAKey(key)
+            | rustls_pemfile::Item::ECKey(key) => return Ok(PrivateKey(key)),
+            _ => {}
+        }
+    }
+    Err(Error::new(
+        Origin::Transport,
+        Kind::Invalid,
+        format!("no private key found in {}", path.display()),
+    ))
+}
+
+fn root_store(certificates: &[Certificate]) -> Result<RootCertStore> {
+    let mut roots = RootCertStore::empty();
+    for certificate in certificates {
+        roots.add(certificate).map_err(config_error)?;
+    }
+    Ok(roots)
+}
+
+fn native_root_store() -> Result<RootCertStore> {
+    let certificates = rustls_native_certs::load_native_certs().map_err(|e| {
+        Error::new(
+            Origin::Transport,
+            Kind::Io,
+            format!("failed to load the platform certificates: {e}"),
+        )
+    })?;
+    let mut roots = RootCertStore::empty();
+    let (_, ignored) = roots.add_parsable_certificates(
+        &certificates
+            .into_iter()
+            .map(|certificate| certificate.0)
+            .collect::<Vec<_>>(),
+    );
+    if ignored > 0 {
+        debug!("Ignored {ignored} invalid platform certificates");
+    }
+    Ok(roots)
+}
+
+fn read_error(path: &Path, err: std::io::Error) -> Error {
+    Error::new(
+        Origin::Transport,
+        Kind::Io,
+        format!("failed to read {}: {err}", path.display()),
+    )
+}
+
+fn config_error(err: impl core::fmt::Display) -> Error {
+    Error::new(
+        Origin::Transport,
+        Kind::Invalid,
+        format!("invalid TLS configuration: {err}"),
+    )
+}
+
+fn handshake_timeout(peer: &str) -> Error {
+    Error::new(
+        Origin::Transport,
+        Kind::Timeout,
+        format!("timed out after {TLS_HANDSHAKE_TIMEOUT:?} during the TLS handshake with {peer}"),
+    )
+}
+
+fn handshake_error(peer: &str, err: std::io::Error) -> Error {
+    Error::new(
+        Origin::Transport,
+        Kind::Io,
+        format!("TLS handshake with {peer} failed: {err}"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peer_host() {
+        assert_eq!(peer_host("example.com:443"), "example.com");
+        assert_eq!(peer_host("127.0.0.1:443"), "127.0.0.1");
+        assert_eq!(peer_host("[::1]:443"), "::1");
+        assert_eq!(peer_host("example.com"), "example.com");
+    }
+}
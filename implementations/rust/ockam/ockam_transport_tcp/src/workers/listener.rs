@@ -1,11 +1,16 @@
-use crate::workers::{Addresses, TcpRecvProcessor};
-use crate::{TcpConnectionMode, TcpListenerInfo, TcpListenerOptions, TcpRegistry, TcpSendWorker};
+use crate::transport::tls::accept_tls;
+use crate::workers::{split_stream, Addresses, TcpRecvProcessor};
+use crate::{
+    TcpConnectionMetrics, TcpConnectionMode, TcpListenerInfo, TcpListenerOptions, TcpRegistry,
+    TcpSendWorker,
+};
 use ockam_core::{async_trait, compat::net::SocketAddr};
 use ockam_core::{Address, Processor, Result};
 use ockam_node::Context;
 use ockam_transport_core::TransportError;
 use tokio::net::TcpListener;
-use tracing::debug;
+use tokio_rustls::TlsAcceptor;
+use tracing::{debug, warn};
 
 /// A TCP Listen processor
 ///
@@ -17,6 +22,7 @@ pub(crate) struct TcpListenProcessor {
     inner: TcpListener,
     socket_address: SocketAddr,
     options: TcpListenerOptions,
+    tls_acceptor: Option<TlsAcceptor>,
 }
 
 impl TcpListenProcessor {
@@ -26,6 +32,8 @@ impl TcpListenProcessor {
         addr: SocketAddr,
         options: TcpListenerOptions,
     ) -> Result<(SocketAddr, Address)> {
+        let tls_acceptor = options.tls.as_ref().map(|tls| tls.acceptor()).transpose()?;
+
         debug!("Binding TcpListener to {}", addr);
         let inner = TcpListener::bind(addr)
             .await
@@ -40,6 +48,7 @@ impl TcpListenProcessor {
             inner,
             socket_address: saddr,
             options,
+            tls_acceptor,
         };
 
         ctx.start_processor(address.clone(), processor).await?;
@@ -77,6 +86,18 @@ impl Processor for TcpListenProcessor {
         let (stream, peer) = self.inner.accept().await.map_err(TransportError::from)?;
         debug!("TCP connection accepted");
 
+        let (read_half, write_half) = match &self.tls_acceptor {
+            Some(acceptor) => match accept_tls(acceptor, &peer.to_string(), stream).await {
+                Ok(halves) => halves,
+                Err(err) => {
+                    // a failed handshake only drops this connection, not the listener
+                    warn!(%peer, %err, "Rejected TCP connection");
+                    return Ok(true);
+                }
+            },
+            None => split_stream(stream),
+        };
+
         let mode = TcpConnectionMode::Incoming;
         let addresses = Addresses::generate(mode);
 
@@ -87,7 +108,7 @@ impl Processor for TcpListenProcessor {
             .options
             .create_access_control(ctx.flow_controls(), receiver_flow_control_id.clone());
 
-        let (read_half, write_half) = stream.into_split();
+        let metrics = TcpConnectionMetrics::default();
 
         // Worker to receive messages from the Node and send them over the wire
         TcpSendWorker::start(
@@ -99,6 +120,8 @@ impl Processor for TcpListenProcessor {
             mode,
             access_control.sender_incoming_access_control,
             &receiver_flow_control_id,
+            self.options.upload_rate_limit.as_ref(),
+            metrics.clone(),
         )
         .await?;
 
@@ -112,6 +135,8 @@ impl Processor for TcpListenProcessor {
             mode,
             &receiver_flow_control_id,
             access_control.receiver_outgoing_access_control,
+            self.options.download_rate_limit.as_ref(),
+            metrics,
         )
         .await?;
 
@@ -1,9 +1,15 @@
 mod addresses;
 mod listener;
+mod rate_limiter;
 mod receiver;
 mod sender;
+mod shared_stream;
+mod stream;
 
 pub(crate) use addresses::*;
 pub(crate) use listener::*;
+pub(crate) use rate_limiter::*;
 pub(crate) use receiver::*;
 pub(crate) use sender::*;
+pub(crate) use shared_stream::*;
+pub(crate) use stream::*;
@@ -0,0 +1,78 @@
+use crate::TcpRateLimit;
+use core::time::Duration;
+use tokio::time::Instant;
+
+/// Token bucket throttling the bytes sent or received on a TCP stream
+///
+/// The bucket holds at most `burst` bytes and is refilled at `bytes_per_second`.
+/// Consuming more bytes than available puts the bucket in debt, and the caller
+/// waits until the debt is paid back.
+pub(crate) struct RateLimiter {
+    bytes_per_second: f64,
+    burst: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(rate_limit: &TcpRateLimit) -> Self {
+        let burst = rate_limit.burst() as f64;
+        Self {
+            bytes_per_second: rate_limit.bytes_per_second() as f64,
+            burst,
+            tokens: burst,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Create a limiter if a rate limit is configured
+    pub(crate) fn from_options(rate_limit: Option<&TcpRateLimit>) -> Option<Self> {
+        rate_limit.map(Self::new)
+    }
+
+    /// Consume `bytes` from the bucket and return how long the caller must wait
+    /// before transferring them
+    fn consume(&mut self, bytes: usize, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.last_refill = now;
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * self.bytes_per_second).min(self.burst);
+        self.tokens -= bytes as f64;
+
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.bytes_per_second)
+        }
+    }
+
+    /// Wait until `bytes` can be transferred without exceeding the rate limit
+    pub(crate) async fn acquire(&mut self, bytes: usize) {
+        let delay = self.consume(bytes, Instant::now());
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter_delays() {
+        let mut limiter = RateLimiter::new(&TcpRateLimit::new(1000).with_burst(500));
+        let start = Instant::now();
+
+        // the burst is available right away
+        assert_eq!(limiter.consume(500, start), Duration::ZERO);
+        // then bytes are throttled at the configured rate
+        assert_eq!(limiter.consume(250, start), Duration::from_millis(250));
+        // the debt is paid back over time
+        let later = start + Duration::from_millis(250);
+        assert_eq!(limiter.consume(0, later), Duration::ZERO);
+        // the bucket never holds more than the burst
+        let much_later = later + Duration::from_secs(10);
+        assert_eq!(limiter.consume(500, much_later), Duration::ZERO);
+        assert_eq!(limiter.consume(100, much_later), Duration::from_millis(100));
+    }
+}
@@ -1,5 +1,8 @@
-use crate::workers::Addresses;
-use crate::{TcpConnectionMode, TcpReceiverInfo, TcpRegistry, TcpSendWorkerMsg};
+use crate::workers::{Addresses, RateLimiter, TcpReadHalf};
+use crate::{
+    TcpConnectionMetrics, TcpConnectionMode, TcpRateLimit, TcpReceiverInfo, TcpRegistry,
+    TcpSendWorkerMsg,
+};
 use ockam_core::compat::net::SocketAddr;
 use ockam_core::compat::sync::Arc;
 use ockam_core::flow_control::FlowControlId;
@@ -9,7 +12,7 @@ use ockam_core::{
 use ockam_core::{Decodable, LocalMessage, Processor, Result, TransportMessage};
 use ockam_node::{Context, ProcessorBuilder};
 use ockam_transport_core::TransportError;
-use tokio::{io::AsyncReadExt, net::tcp::OwnedReadHalf};
+use tokio::io::AsyncReadExt;
 use tracing::{error, info, trace};
 
 /// A TCP receiving message processor
@@ -22,22 +25,27 @@ use tracing::{error, info, trace};
 /// the node message system.
 pub(crate) struct TcpRecvProcessor {
     registry: TcpRegistry,
-    read_half: OwnedReadHalf,
+    read_half: TcpReadHalf,
     socket_address: SocketAddr,
     addresses: Addresses,
     mode: TcpConnectionMode,
     flow_control_id: FlowControlId,
+    rate_limiter: Option<RateLimiter>,
+    metrics: TcpConnectionMetrics,
 }
 
 impl TcpRecvProcessor {
     /// Create a new `TcpRecvProcessor`
+    #[allow(clippy::too_many_arguments)]
     fn new(
         registry: TcpRegistry,
-        read_half: OwnedReadHalf,
+        read_half: TcpReadHalf,
         socket_address: SocketAddr,
         addresses: Addresses,
         mode: TcpConnectionMode,
         flow_control_id: FlowControlId,
+        rate_limit: Option<&TcpRateLimit>,
+        metrics: TcpConnectionMetrics,
     ) -> Self {
         Self {
             registry,
@@ -46,6 +54,8 @@ impl TcpRecvProcessor {
             addresses,
             mode,
             flow_control_id,
+            rate_limiter: RateLimiter::from_options(rate_limit),
+            metrics,
         }
     }
 
@@ -53,12 +63,14 @@ impl TcpRecvProcessor {
     pub async fn start(
         ctx: &Context,
         registry: TcpRegistry,
-        read_half: OwnedReadHalf,
+        read_half: TcpReadHalf,
         addresses: &Addresses,
         socket_address: SocketAddr,
         mode: TcpConnectionMode,
         flow_control_id: &FlowControlId,
         receiver_outgoing_access_control: Arc<dyn OutgoingAccessControl>,
+        rate_limit: Option<&TcpRateLimit>,
+        metrics: TcpConnectionMetrics,
     ) -> Result<()> {
         let receiver = TcpRecvProcessor::new(
             registry,
@@ -67,6 +79,8 @@ impl TcpRecvProcessor {
             addresses.clone(),
             mode,
             flow_control_id.clone(),
+            rate_limit,
+            metrics,
         );
 
         let mailbox = Mailbox::new(
@@ -158,18 +172,31 @@ impl Processor for TcpRecvProcessor {
             Ok(_) => {}
             _ => {
                 error!("Failed to receive message of length: {}", len);
+                self.metrics.record_receive_failure();
                 return Ok(true);
             }
         }
 
+        // Throttle the connection by delaying the next read
+        if let Some(rate_limiter) = &mut self.rate_limiter {
+            rate_limiter.acquire(len as usize + 2).await;
+        }
+
         // Deserialize the message now
-        let mut msg = TransportMessage::decode(&buf).map_err(|_| TransportError::RecvBadMessage)?;
+        let mut msg = match TransportMessage::decode(&buf) {
+            Ok(msg) => msg,
+            Err(_) => {
+                self.metrics.record_receive_failure();
+                return Err(TransportError::RecvBadMessage)?;
+            }
+        };
 
         // Heartbeat message
         if msg.onward_route.next().is_err() {
             trace!("Got heartbeat message from: {}", self.socket_address);
             return Ok(true);
         }
+        self.metrics.record_received(len as usize + 2);
 
         // Insert the peer address into the return route so that
         // reply routing can be properly resolved
@@ -1,12 +1,16 @@
-use crate::workers::Addresses;
-use crate::{TcpConnectionMode, TcpRegistry, TcpSenderInfo};
+use crate::workers::{Addresses, RateLimiter, TcpSharedStreamWorker, TcpWriteHalf};
+use crate::{
+    TcpConnectionMetrics, TcpConnectionMode, TcpKeepaliveOptions, TcpRateLimit, TcpRegistry,
+    TcpSenderInfo,
+};
 use cfg_if::cfg_if;
 use core::time::Duration;
+use ockam_core::errcode::{Kind, Origin};
 use ockam_core::flow_control::FlowControlId;
 use ockam_core::{
     async_trait,
     compat::{net::SocketAddr, sync::Arc},
-    AllowSourceAddress, DenyAll, IncomingAccessControl,
+    AllowSourceAddress, DenyAll, Error, IncomingAccessControl,
 };
 use ockam_core::{Any, Decodable, Mailbox, Mailboxes, Message, Result, Routed, Worker};
 use ockam_node::{Context, WorkerBuilder};
@@ -15,10 +19,14 @@ use ockam_transport_core::{prepare_message, TransportError};
 use serde::{Deserialize, Serialize};
 use socket2::{SockRef, TcpKeepalive};
 use tokio::io::AsyncWriteExt;
-use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
 use tokio::net::TcpStream;
+use tokio::task::JoinSet;
 use tracing::{debug, info, trace, warn};
 
+/// Delay before starting the next connection attempt while the previous ones are still
+/// in progress, as recommended by RFC 8305
+const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
 #[derive(Serialize, Deserialize, Message, Clone)]
 pub(crate) enum TcpSendWorkerMsg {
     ConnectionClosed,
@@ -34,23 +42,28 @@ pub(crate) enum TcpSendWorkerMsg {
 /// to dispatch to a remote peer.
 pub(crate) struct TcpSendWorker {
     registry: TcpRegistry,
-    write_half: OwnedWriteHalf,
+    write_half: TcpWriteHalf,
     socket_address: SocketAddr,
     addresses: Addresses,
     mode: TcpConnectionMode,
     receiver_flow_control_id: FlowControlId,
+    rate_limiter: Option<RateLimiter>,
+    metrics: TcpConnectionMetrics,
     rx_should_be_stopped: bool,
 }
 
 impl TcpSendWorker {
     /// Create a new `TcpSendWorker`
+    #[allow(clippy::too_many_arguments)]
     fn new(
         registry: TcpRegistry,
-        write_half: OwnedWriteHalf,
+        write_half: TcpWriteHalf,
         socket_address: SocketAddr,
         addresses: Addresses,
         mode: TcpConnectionMode,
         receiver_flow_control_id: FlowControlId,
+        rate_limit: Option<&TcpRateLimit>,
+        metrics: TcpConnectionMetrics,
     ) -> Self {
         Self {
             registry,
@@ -59,6 +72,8 @@ impl TcpSendWorker {
             addresses,
             receiver_flow_control_id,
             mode,
+            rate_limiter: RateLimiter::from_options(rate_limit),
+            metrics,
             rx_should_be_stopped: true,
         }
     }
@@ -71,12 +86,14 @@ impl TcpSendWorker {
     pub(crate) async fn start(
         ctx: &Context,
         registry: TcpRegistry,
-        write_half: OwnedWriteHalf,
+        write_half: TcpWriteHalf,
         addresses: &Addresses,
         socket_address: SocketAddr,
         mode: TcpConnectionMode,
         sender_incoming_access_control: Arc<dyn IncomingAccessControl>,
         receiver_flow_control_id: &FlowControlId,
+        rate_limit: Option<&TcpRateLimit>,
+        metrics: TcpConnectionMetrics,
     ) -> Result<()> {
         trace!("Creating new TCP worker pair");
         let sender_worker = Self::new(
@@ -86,6 +103,8 @@ impl TcpSendWorker {
             addresses.clone(),
             mode,
             receiver_flow_control_id.clone(),
+            rate_limit,
+            metrics,
         );
 
         let main_mailbox = Mailbox::new(
@@ -117,35 +136,84 @@ impl TcpSendWorker {
         Ok(())
     }
 
-    pub(crate) async fn connect(
-        socket_address: SocketAddr,
-    ) -> Result<(OwnedReadHalf, OwnedWriteHalf)> {
+    pub(crate) async fn connect(socket_address: SocketAddr) -> Result<TcpStream> {
         debug!(addr = %socket_address, "Connecting");
-        let connection = match TcpStream::connect(socket_address).await {
+        match TcpStream::connect(socket_address).await {
             Ok(c) => {
                 debug!(addr = %socket_address, "Connected");
-                c
+                Ok(c)
             }
             Err(e) => {
                 debug!(addr = %socket_address, err = %e, "Failed to connect");
-                return Err(TransportError::from(e))?;
+                Err(TransportError::from(e))?
             }
-        };
+        }
+    }
 
-        let mut keepalive = TcpKeepalive::new()
-            .with_time(Duration::from_secs(300))
-            .with_interval(Duration::from_secs(75));
+    /// Race connection attempts to the given addresses ("Happy Eyeballs", RFC 8305).
+    ///
+    /// A new attempt is started when the previous one fails, or after
+    /// [`CONNECTION_ATTEMPT_DELAY`] if it is still in progress. The first established
+    /// connection is returned and the other attempts are cancelled
+    pub(crate) async fn connect_any(
+        socket_addresses: Vec<SocketAddr>,
+    ) -> Result<(TcpStream, SocketAddr)> {
+        let mut socket_addresses = socket_addresses.into_iter();
+        let mut attempts = JoinSet::new();
+        let mut last_error = None;
 
-        cfg_if! {
-            if #[cfg(unix)] {
-               keepalive = keepalive.with_retries(2);
+        loop {
+            if let Some(socket_address) = socket_addresses.next() {
+                attempts
+                    .spawn(async move { (socket_address, Self::connect(socket_address).await) });
+            }
+            if attempts.is_empty() {
+                return Err(last_error.unwrap_or_else(|| TransportError::InvalidAddress.into()));
+            }
+
+            let has_next_address = socket_addresses.len() > 0;
+            tokio::select! {
+                Some(result) = attempts.join_next() => match result {
+                    // the remaining attempts are aborted when the JoinSet is dropped
+                    Ok((socket_address, Ok(stream))) => return Ok((stream, socket_address)),
+                    Ok((_, Err(err))) => last_error = Some(err),
+                    Err(err) => {
+                        last_error = Some(Error::new(Origin::Transport, Kind::Internal, err))
+                    }
+                },
+                _ = tokio::time::sleep(CONNECTION_ATTEMPT_DELAY), if has_next_address => {}
             }
         }
+    }
 
-        let socket = SockRef::from(&connection);
-        socket.set_tcp_keepalive(&keepalive).unwrap();
+    /// Configure the socket options of an established connection
+    pub(crate) fn setup_connection(
+        connection: TcpStream,
+        keepalive: Option<&TcpKeepaliveOptions>,
+        nodelay: bool,
+    ) -> Result<TcpStream> {
+        if let Some(keepalive) = keepalive {
+            let mut tcp_keepalive = TcpKeepalive::new()
+                .with_time(keepalive.time())
+                .with_interval(keepalive.interval());
 
-        Ok(connection.into_split())
+            cfg_if! {
+                if #[cfg(unix)] {
+                   tcp_keepalive = tcp_keepalive.with_retries(keepalive.retries());
+                }
+            }
+
+            let socket = SockRef::from(&connection);
+            socket
+                .set_tcp_keepalive(&tcp_keepalive)
+                .map_err(TransportError::from)?;
+        }
+
+        connection
+            .set_nodelay(nodelay)
+            .map_err(TransportError::from)?;
+
+        Ok(connection)
     }
 }
 
@@ -157,13 +225,16 @@ impl Worker for TcpSendWorker {
     async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
         ctx.set_cluster(crate::CLUSTER_NAME).await?;
 
-        self.registry.add_sender_worker(TcpSenderInfo::new(
-            self.addresses.sender_address().clone(),
-            self.addresses.receiver_address().clone(),
-            self.socket_address,
-            self.mode,
-            self.receiver_flow_control_id.clone(),
-        ));
+        self.registry.add_sender_worker(
+            TcpSenderInfo::new(
+                self.addresses.sender_address().clone(),
+                self.addresses.receiver_address().clone(),
+                self.socket_address,
+                self.mode,
+                self.receiver_flow_control_id.clone(),
+            )
+            .with_metrics(self.metrics.clone()),
+        );
 
         Ok(())
     }
@@ -207,19 +278,33 @@ impl Worker for TcpSendWorker {
                 }
             }
         } else {
-            let mut msg = msg.into_transport_message();
+            let local_message = msg.into_local_message();
+            let stream_address = TcpSharedStreamWorker::find_stream_address(&local_message);
+            let mut msg = local_message.into_transport_message();
             // Remove our own address from the route so the other end
             // knows what to do with the incoming message
             msg.onward_route.step()?;
             // Create a message buffer with prepended length
             let msg = prepare_message(msg)?;
 
-            if self.write_half.write_all(msg.as_slice()).await.is_err() {
+            if let Some(rate_limiter) = &mut self.rate_limiter {
+                rate_limiter.acquire(msg.len()).await;
+            }
+
+            let written = self.write_half.write_all(msg.as_slice()).await;
+            // let the stream of a shared connection send one more message
+            if let Some(stream_address) = &stream_address {
+                self.registry.release_stream_window(stream_address);
+            }
+
+            if written.is_err() {
                 warn!("Failed to send message to peer {}", self.socket_address);
+                self.metrics.record_send_failure();
                 self.stop(ctx).await?;
 
                 return Ok(());
             }
+            self.metrics.record_sent(msg.len());
         }
 
         Ok(())
@@ -0,0 +1,102 @@
+use ockam_core::compat::sync::Arc;
+use ockam_core::{
+    async_trait, Address, AllowAll, AllowOnwardAddress, Any, LocalInfo, LocalMessage, Mailboxes,
+    Result, Routed, Worker,
+};
+use ockam_node::{Context, WorkerBuilder};
+use tokio::sync::Semaphore;
+use tracing::debug;
+
+/// Number of messages a stream of a shared connection can have waiting to be written
+/// before it is paused, so that a busy user doesn't starve the other users
+pub(crate) const SHARED_STREAM_WINDOW: usize = 64;
+
+const TCP_SHARED_STREAM_IDENTIFIER: &str = "TCP_SHARED_STREAM";
+
+/// Stream of one user of a shared connection, see [`crate::TcpConnectionOptions::shared`]
+///
+/// Messages sent to the stream are tagged with the stream address and forwarded to the
+/// [`TcpSendWorker`](super::TcpSendWorker) of the connection, which gives back one message
+/// of the window of the stream once the message is written. The route of a message is
+/// what tells the other side which user it is for.
+pub(crate) struct TcpSharedStreamWorker {
+    sender_address: Address,
+    window: Arc<Semaphore>,
+}
+
+impl TcpSharedStreamWorker {
+    /// Start a stream forwarding messages to the given sender
+    pub(crate) async fn start(
+        ctx: &Context,
+        stream_address: Address,
+        sender_address: Address,
+        window: Arc<Semaphore>,
+    ) -> Result<()> {
+        let mailboxes = Mailboxes::main(
+            stream_address,
+            Arc::new(AllowAll),
+            Arc::new(AllowOnwardAddress(sender_address.clone())),
+        );
+        WorkerBuilder::new(Self {
+            sender_address,
+            window,
+        })
+        .with_mailboxes(mailboxes)
+        .start(ctx)
+        .await
+    }
+
+    /// Tag a message with the address of the stream it was sent to
+    pub(crate) fn to_local_info(stream_address: &Address) -> LocalInfo {
+        LocalInfo::new(
+            TCP_SHARED_STREAM_IDENTIFIER.into(),
+            stream_address.to_string().into_bytes(),
+        )
+    }
+
+    /// Return the address of the stream a message was sent to, if any
+    pub(crate) fn find_stream_address(local_message: &LocalMessage) -> Option<Address> {
+        local_message
+            .local_info()
+            .iter()
+            .find(|x| x.type_identifier() == TCP_SHARED_STREAM_IDENTIFIER)
+            .and_then(|x| core::str::from_utf8(x.data()).ok())
+            .and_then(|x| x.parse().ok())
+    }
+}
+
+#[async_trait]
+impl Worker for TcpSharedStreamWorker {
+    type Context = Context;
+    type Message = Any;
+
+    async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        ctx.set_cluster(crate::CLUSTER_NAME).await
+    }
+
+    async fn handle_message(
+        &mut self,
+        ctx: &mut Context,
+        msg: Routed<Self::Message>,
+    ) -> Result<()> {
+        // wait until the connection has written enough of the messages of this stream,
+        // the window is closed when the stream or the connection is stopped
+        match self.window.acquire().await {
+            Ok(permit) => permit.forget(),
+            Err(_) => {
+                debug!("Stopping closed TCP stream {}", ctx.address());
+                return ctx.stop_worker(ctx.address()).await;
+            }
+        }
+
+        let stream_address = ctx.address();
+        let mut local_message = msg.into_local_message();
+        local_message
+            .transport_mut()
+            .onward_route
+            .modify()
+            .replace(self.sender_address.clone());
+        local_message.append_local_info(Self::to_local_info(&stream_address));
+        ctx.forward(local_message).await
+    }
+}
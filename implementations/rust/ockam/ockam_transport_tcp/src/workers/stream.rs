@@ -0,0 +1,74 @@
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::io;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf, ReadHalf, WriteHalf};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsStream;
+
+/// Read half of a TCP stream, which is optionally wrapped in TLS
+pub(crate) enum TcpReadHalf {
+    Plain(OwnedReadHalf),
+    Tls(ReadHalf<TlsStream<TcpStream>>),
+}
+
+/// Write half of a TCP stream, which is optionally wrapped in TLS
+pub(crate) enum TcpWriteHalf {
+    Plain(OwnedWriteHalf),
+    Tls(WriteHalf<TlsStream<TcpStream>>),
+}
+
+/// Split a plain TCP stream
+pub(crate) fn split_stream(stream: TcpStream) -> (TcpReadHalf, TcpWriteHalf) {
+    let (read_half, write_half) = stream.into_split();
+    (
+        TcpReadHalf::Plain(read_half),
+        TcpWriteHalf::Plain(write_half),
+    )
+}
+
+/// Split a TCP stream wrapped in TLS
+pub(crate) fn split_tls_stream(stream: TlsStream<TcpStream>) -> (TcpReadHalf, TcpWriteHalf) {
+    let (read_half, write_half) = tokio::io::split(stream);
+    (TcpReadHalf::Tls(read_half), TcpWriteHalf::Tls(write_half))
+}
+
+impl AsyncRead for TcpReadHalf {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            TcpReadHalf::Plain(read_half) => Pin::new(read_half).poll_read(cx, buf),
+            TcpReadHalf::Tls(read_half) => Pin::new(read_half).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for TcpWriteHalf {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            TcpWriteHalf::Plain(write_half) => Pin::new(write_half).poll_write(cx, buf),
+            TcpWriteHalf::Tls(write_half) => Pin::new(write_half).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            TcpWriteHalf::Plain(write_half) => Pin::new(write_half).poll_flush(cx),
+            TcpWriteHalf::Tls(write_half) => Pin::new(write_half).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            TcpWriteHalf::Plain(write_half) => Pin::new(write_half).poll_shutdown(cx),
+            TcpWriteHalf::Tls(write_half) => Pin::new(write_half).poll_shutdown(cx),
+        }
+    }
+}
@@ -1,11 +1,28 @@
 use core::time::Duration;
 use ockam_core::compat::rand::{self, Rng};
+use ockam_core::flow_control::FlowControls;
 use ockam_core::{route, Result, Routed, Worker};
-use ockam_node::Context;
-use ockam_transport_tcp::{TcpConnectionOptions, TcpListenerOptions, TcpTransport};
+use ockam_node::{Context, MessageSendReceiveOptions};
+use ockam_transport_tcp::{
+    TcpConnectionMode, TcpConnectionOptions, TcpKeepaliveOptions, TcpListenerOptions, TcpRateLimit,
+    TcpRetryPolicy, TcpTransport,
+};
+use std::time::Instant;
 
 pub struct Echoer;
 
+pub struct Sink;
+
+#[ockam_core::worker]
+impl Worker for Sink {
+    type Message = String;
+    type Context = Context;
+
+    async fn handle_message(&mut self, _ctx: &mut Context, _msg: Routed<String>) -> Result<()> {
+        Ok(())
+    }
+}
+
 #[ockam_core::worker]
 impl Worker for Echoer {
     type Message = String;
@@ -187,3 +204,402 @@ async fn tcp_lifecycle__stop_listener__should_stop_accepting_connections(
 
     Ok(())
 }
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tcp_lifecycle__connection_options__should_be_applied(ctx: &mut Context) -> Result<()> {
+    let options = TcpListenerOptions::new();
+    ctx.flow_controls()
+        .add_consumer("echoer", &options.spawner_flow_control_id());
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let listener = transport.listen("127.0.0.1:0", options).await?;
+
+    let connection = transport
+        .connect(
+            listener.socket_string(),
+            TcpConnectionOptions::new()
+                .with_connect_timeout(Duration::from_secs(5))
+                .with_keepalive(
+                    TcpKeepaliveOptions::default()
+                        .with_time(Duration::from_secs(10))
+                        .with_interval(Duration::from_secs(5)),
+                )
+                .with_nodelay(true),
+        )
+        .await?;
+
+    let msg = "Hello".to_string();
+    let reply: String = ctx
+        .send_and_receive(route![connection.clone(), "echoer"], msg.clone())
+        .await?;
+    assert_eq!(reply, msg, "Should receive the same message");
+
+    // a connection to a stopped listener fails after all the retries
+    transport
+        .stop_listener(listener.processor_address())
+        .await?;
+    ctx.sleep(Duration::from_millis(10)).await;
+
+    let start = Instant::now();
+    let res = transport
+        .connect(
+            listener.socket_string(),
+            TcpConnectionOptions::new()
+                .with_retry_policy(TcpRetryPolicy::new(2, Duration::from_millis(50))),
+        )
+        .await;
+    assert!(res.is_err(), "Should not connect to a stopped listener");
+    assert!(
+        start.elapsed() >= Duration::from_millis(150),
+        "Should wait between retries"
+    );
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tcp_lifecycle__shared_connection__should_be_reused(ctx: &mut Context) -> Result<()> {
+    let options = TcpListenerOptions::new();
+    ctx.flow_controls()
+        .add_consumer("echoer", &options.spawner_flow_control_id());
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let listener = transport.listen("127.0.0.1:0", options).await?;
+
+    let consumer_id = FlowControls::generate_flow_control_id();
+    let connection1 = transport
+        .connect(
+            listener.socket_string(),
+            TcpConnectionOptions::new().shared(),
+        )
+        .await?;
+    let connection2 = transport
+        .connect(
+            listener.socket_string(),
+            TcpConnectionOptions::new()
+                .shared()
+                .as_consumer(&consumer_id),
+        )
+        .await?;
+    let connection3 = transport
+        .connect(listener.socket_string(), TcpConnectionOptions::new())
+        .await?;
+
+    // the shared connection is reused, with one stream per user,
+    // but not by connections which are not shared
+    assert!(connection1.is_shared());
+    assert_ne!(connection1.sender_address(), connection2.sender_address());
+    assert_eq!(
+        connection1.connection_sender_address(),
+        connection2.connection_sender_address()
+    );
+    assert_eq!(connection1.flow_control_id(), connection2.flow_control_id());
+    assert_ne!(
+        connection1.connection_sender_address(),
+        connection3.connection_sender_address()
+    );
+    assert_eq!(
+        transport
+            .registry()
+            .get_shared_connection_users(connection1.connection_sender_address()),
+        Some(2)
+    );
+
+    // the shared connection can't be reused with different options
+    assert!(transport
+        .connect(
+            listener.socket_string(),
+            TcpConnectionOptions::new().shared().with_nodelay(true),
+        )
+        .await
+        .is_err());
+
+    // the consumers of a user are removed when that user stops the connection
+    assert!(ctx
+        .flow_controls()
+        .get_consumers_info(&consumer_id)
+        .contains(connection2.sender_address()));
+    connection2.stop(ctx).await?;
+    assert!(!ctx
+        .flow_controls()
+        .get_consumers_info(&consumer_id)
+        .contains(connection2.sender_address()));
+
+    // the connection is still usable after being stopped by one of its users
+    let msg = "Hello".to_string();
+    let reply: String = ctx
+        .send_and_receive(route![connection1.clone(), "echoer"], msg.clone())
+        .await?;
+    assert_eq!(reply, msg, "Should receive the same message");
+
+    // the connection is closed when its last user stops it
+    connection1.stop(ctx).await?;
+    ctx.sleep(Duration::from_millis(100)).await;
+    assert!(transport
+        .registry()
+        .get_all_sender_workers()
+        .iter()
+        .all(|sender| sender.address() != connection1.connection_sender_address()));
+    assert_eq!(
+        transport
+            .registry()
+            .get_shared_connection_users(connection1.connection_sender_address()),
+        None
+    );
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tcp_lifecycle__concurrent_shared_connections__should_keep_one_socket(
+    ctx: &mut Context,
+) -> Result<()> {
+    let options = TcpListenerOptions::new();
+    ctx.flow_controls()
+        .add_consumer("echoer", &options.spawner_flow_control_id());
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let listener = transport.listen("127.0.0.1:0", options).await?;
+
+    // both connections are opened before either of them is shared
+    let (connection1, connection2) = tokio::join!(
+        transport.connect(
+            listener.socket_string(),
+            TcpConnectionOptions::new().shared()
+        ),
+        transport.connect(
+            listener.socket_string(),
+            TcpConnectionOptions::new().shared()
+        ),
+    );
+    let (connection1, connection2) = (connection1?, connection2?);
+    assert_eq!(
+        connection1.connection_sender_address(),
+        connection2.connection_sender_address()
+    );
+
+    // the socket opened by the second connection is closed
+    ctx.sleep(Duration::from_millis(100)).await;
+    let outgoing_senders = transport
+        .registry()
+        .get_all_sender_workers()
+        .into_iter()
+        .filter(|sender| sender.mode() == &TcpConnectionMode::Outgoing)
+        .count();
+    assert_eq!(outgoing_senders, 1);
+
+    for connection in [&connection1, &connection2] {
+        let msg = "Hello".to_string();
+        let reply: String = ctx
+            .send_and_receive(route![connection.clone(), "echoer"], msg.clone())
+            .await?;
+        assert_eq!(reply, msg, "Should receive the same message");
+    }
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tcp_lifecycle__busy_shared_connection_user__should_not_starve_other_users(
+    ctx: &mut Context,
+) -> Result<()> {
+    let options = TcpListenerOptions::new();
+    ctx.flow_controls()
+        .add_consumer("echoer", &options.spawner_flow_control_id());
+    ctx.flow_controls()
+        .add_consumer("sink", &options.spawner_flow_control_id());
+    ctx.start_worker("echoer", Echoer).await?;
+    ctx.start_worker("sink", Sink).await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let listener = transport.listen("127.0.0.1:0", options).await?;
+
+    let options = || {
+        TcpConnectionOptions::new()
+            .shared()
+            .with_upload_rate_limit(TcpRateLimit::new(10_000).with_burst(1000))
+    };
+    let busy = transport
+        .connect(listener.socket_string(), options())
+        .await?;
+    let other = transport
+        .connect(listener.socket_string(), options())
+        .await?;
+
+    // it takes several seconds to write all the messages of the busy user
+    for _ in 0..600 {
+        ctx.send(route![busy.clone(), "sink"], "a".repeat(100))
+            .await?;
+    }
+
+    // but only a limited number of them are written before the message of the other user
+    let start = Instant::now();
+    let msg = "Hello".to_string();
+    let reply: String = ctx
+        .send_and_receive_extended(
+            route![other.clone(), "echoer"],
+            msg.clone(),
+            MessageSendReceiveOptions::new().with_timeout(Duration::from_secs(3)),
+        )
+        .await?
+        .body();
+    assert_eq!(reply, msg, "Should receive the same message");
+    assert!(
+        start.elapsed() < Duration::from_secs(3),
+        "Should not wait for all the messages of the other user"
+    );
+
+    busy.stop(ctx).await?;
+    other.stop(ctx).await?;
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tcp_lifecycle__rate_limited_connection__should_be_throttled(
+    ctx: &mut Context,
+) -> Result<()> {
+    let options = TcpListenerOptions::new();
+    ctx.flow_controls()
+        .add_consumer("echoer", &options.spawner_flow_control_id());
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let listener = transport.listen("127.0.0.1:0", options).await?;
+
+    let connection = transport
+        .connect(
+            listener.socket_string(),
+            TcpConnectionOptions::new()
+                .with_upload_rate_limit(TcpRateLimit::new(1000).with_burst(1000)),
+        )
+        .await?;
+
+    // the first message fits in the burst, the next ones are sent at 1000 bytes/sec
+    let start = Instant::now();
+    for _ in 0..3 {
+        let msg = "a".repeat(1000);
+        let reply: String = ctx
+            .send_and_receive(route![connection.clone(), "echoer"], msg.clone())
+            .await?;
+        assert_eq!(reply, msg, "Should receive the same message");
+    }
+    assert!(
+        start.elapsed() >= Duration::from_millis(1500),
+        "Should throttle the messages"
+    );
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tcp_lifecycle__dual_stack_hostname__should_connect_to_reachable_address(
+    ctx: &mut Context,
+) -> Result<()> {
+    let options = TcpListenerOptions::new();
+    ctx.flow_controls()
+        .add_consumer("echoer", &options.spawner_flow_control_id());
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    // only listen on IPv4, while localhost may also resolve to ::1
+    let listener = transport.listen("127.0.0.1:0", options).await?;
+
+    let connection = transport
+        .connect(
+            format!("localhost:{}", listener.socket_address().port()),
+            TcpConnectionOptions::new().with_connect_timeout(Duration::from_secs(5)),
+        )
+        .await?;
+    assert!(connection.socket_address().is_ipv4());
+
+    let msg = "Hello".to_string();
+    let reply: String = ctx
+        .send_and_receive(route![connection.clone(), "echoer"], msg.clone())
+        .await?;
+    assert_eq!(reply, msg, "Should receive the same message");
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tcp_lifecycle__connection_metrics__should_count_traffic(ctx: &mut Context) -> Result<()> {
+    let options = TcpListenerOptions::new();
+    ctx.flow_controls()
+        .add_consumer("echoer", &options.spawner_flow_control_id());
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let listener = transport.listen("127.0.0.1:0", options).await?;
+
+    let connection = transport
+        .connect(listener.socket_string(), TcpConnectionOptions::new())
+        .await?;
+
+    let msg = "Hello".to_string();
+    let reply: String = ctx
+        .send_and_receive(route![connection.clone(), "echoer"], msg.clone())
+        .await?;
+    assert_eq!(reply, msg, "Should receive the same message");
+
+    let metrics = connection.metrics();
+    assert!(metrics.connect_latency().is_some());
+    assert_eq!(metrics.failed_connect_attempts(), 0);
+    assert_eq!(metrics.messages_sent(), 1);
+    assert_eq!(metrics.messages_received(), 1);
+    assert!(metrics.bytes_sent() > msg.len() as u64);
+    assert!(metrics.bytes_received() > msg.len() as u64);
+    assert_eq!(metrics.send_failures(), 0);
+
+    // the incoming side of the connection is instrumented too
+    ctx.sleep(Duration::from_millis(100)).await;
+    let incoming = transport
+        .registry()
+        .get_all_sender_workers()
+        .into_iter()
+        .find(|sender| sender.address() != connection.sender_address())
+        .unwrap();
+    assert_eq!(incoming.metrics().messages_received(), 1);
+    assert_eq!(incoming.metrics().messages_sent(), 1);
+    assert!(incoming.metrics().connect_latency().is_none());
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
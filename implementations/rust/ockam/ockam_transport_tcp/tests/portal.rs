@@ -1,13 +1,18 @@
+use std::net::SocketAddr;
 use std::time::Duration;
 
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
 
 use ockam_core::compat::rand::random;
-use ockam_core::{route, Result};
+use ockam_core::compat::sync::Arc;
+use ockam_core::{async_trait, route, LocalInfo, Result};
 use ockam_node::Context;
 use ockam_transport_tcp::{
-    TcpConnectionOptions, TcpInletOptions, TcpListenerOptions, TcpOutletOptions, TcpTransport,
+    PortalInterceptor, PortalInterceptorFactory, TcpConnectionOptions, TcpHealthCheckOptions,
+    TcpInletOptions, TcpListenerOptions, TcpLoadBalancer, TcpLoadBalancingOptions,
+    TcpOutletAllowlist, TcpOutletConnectionAccessControl, TcpOutletConnectionInitializer,
+    TcpOutletOptions, TcpTransport,
 };
 
 const LENGTH: usize = 32;
@@ -238,3 +243,377 @@ async fn portal__tcp_connection_with_invalid_message_flow__should_not_succeed(
 
     Ok(())
 }
+
+/// Inverts every bit written to the outlet target
+#[derive(Debug)]
+struct Inverter;
+
+impl PortalInterceptorFactory for Inverter {
+    fn create(&self) -> Box<dyn PortalInterceptor> {
+        Box::new(Inverter)
+    }
+}
+
+#[async_trait]
+impl PortalInterceptor for Inverter {
+    async fn intercept(&mut self, _local_info: &[LocalInfo], payload: Vec<u8>) -> Result<Vec<u8>> {
+        Ok(payload.into_iter().map(|b| !b).collect())
+    }
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn portal__interceptor__should_transform_payloads(ctx: &mut Context) -> Result<()> {
+    let payload1 = generate_binary();
+    let payload2 = generate_binary();
+
+    let tcp = TcpTransport::create(ctx).await?;
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let bind_address = listener.local_addr().unwrap().to_string();
+    tcp.create_outlet(
+        "outlet",
+        bind_address,
+        TcpOutletOptions::new().with_interceptor(Arc::new(Inverter)),
+    )
+    .await?;
+    let (inlet_addr, _) = tcp
+        .create_inlet("127.0.0.1:0", route!["outlet"], TcpInletOptions::new())
+        .await?;
+
+    let handle = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+
+        read_assert_binary(&mut stream, payload1.map(|b| !b)).await;
+        // the data sent back to the inlet is not intercepted
+        write_binary(&mut stream, payload2).await;
+    });
+
+    let mut stream = TcpStream::connect(inlet_addr).await.unwrap();
+    write_binary(&mut stream, payload1).await;
+    read_assert_binary(&mut stream, payload2).await;
+
+    let res = handle.await;
+    assert!(res.is_ok());
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 10000)]
+async fn portal__load_balanced_inlet__should_eject_unhealthy_outlets(
+    ctx: &mut Context,
+) -> Result<()> {
+    let payload1 = generate_binary();
+    let payload2 = generate_binary();
+
+    let tcp = TcpTransport::create(ctx).await?;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let bind_address = listener.local_addr().unwrap().to_string();
+    tcp.create_outlet("outlet1", bind_address, TcpOutletOptions::new())
+        .await?;
+
+    // the target of the second outlet is not listening
+    let closed_address = {
+        let closed = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        closed.local_addr().unwrap().to_string()
+    };
+    tcp.create_outlet("outlet2", closed_address, TcpOutletOptions::new())
+        .await?;
+
+    let load_balancer = TcpLoadBalancer::new(
+        vec![route!["outlet1"], route!["outlet2"]],
+        TcpLoadBalancingOptions::new().with_health_check(
+            TcpHealthCheckOptions::new()
+                .with_interval(Duration::from_millis(200))
+                .with_timeout(Duration::from_secs(1))
+                .with_unhealthy_threshold(1),
+        ),
+    );
+    let (inlet_addr, _) = tcp
+        .create_load_balanced_inlet("127.0.0.1:0", load_balancer.clone(), TcpInletOptions::new())
+        .await?;
+
+    // Wait till the second outlet is ejected
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    let outlets = load_balancer.outlets();
+    assert!(outlets[0].is_healthy());
+    assert!(!outlets[1].is_healthy());
+
+    let handle = tokio::spawn(async move {
+        for _ in 0..2 {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            read_assert_binary(&mut stream, payload1).await;
+            write_binary(&mut stream, payload2).await;
+        }
+    });
+
+    // all the connections are sent to the healthy outlet
+    for _ in 0..2 {
+        let mut stream = TcpStream::connect(inlet_addr).await.unwrap();
+        write_binary(&mut stream, payload1).await;
+        read_assert_binary(&mut stream, payload2).await;
+    }
+
+    let res = handle.await;
+    assert!(res.is_ok());
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 10000)]
+async fn portal__connections__should_be_tracked(ctx: &mut Context) -> Result<()> {
+    let payload1 = generate_binary();
+    let payload2 = generate_binary();
+
+    let tcp = TcpTransport::create(ctx).await?;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let target_addr = listener.local_addr().unwrap();
+    tcp.create_outlet("outlet", target_addr.to_string(), TcpOutletOptions::new())
+        .await?;
+    let (inlet_addr, inlet_address) = tcp
+        .create_inlet("127.0.0.1:0", route!["outlet"], TcpInletOptions::new())
+        .await?;
+
+    let handle = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        read_assert_binary(&mut stream, payload1).await;
+        write_binary(&mut stream, payload2).await;
+        stream
+    });
+
+    let mut stream = TcpStream::connect(inlet_addr).await.unwrap();
+    write_binary(&mut stream, payload1).await;
+    read_assert_binary(&mut stream, payload2).await;
+    let _target_stream = handle.await.unwrap();
+
+    let inlet_connections = tcp.registry().get_portal_connections(&inlet_address);
+    assert_eq!(inlet_connections.len(), 1);
+    let inlet_connection = &inlet_connections[0];
+    assert_eq!(inlet_connection.peer(), stream.local_addr().unwrap());
+    assert_eq!(inlet_connection.metrics().bytes_received(), LENGTH as u64);
+    assert_eq!(inlet_connection.metrics().bytes_sent(), LENGTH as u64);
+
+    let outlet_connections = tcp.registry().get_portal_connections(&"outlet".into());
+    assert_eq!(outlet_connections.len(), 1);
+    let outlet_connection = &outlet_connections[0];
+    assert_eq!(outlet_connection.peer(), target_addr);
+    assert_eq!(outlet_connection.metrics().bytes_sent(), LENGTH as u64);
+    assert_eq!(outlet_connection.metrics().bytes_received(), LENGTH as u64);
+
+    // the connections are removed once closed
+    drop(stream);
+    tokio::time::sleep(Duration::from_secs(3)).await;
+    assert!(tcp.registry().get_all_portal_connections().is_empty());
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+/// Only authorize the connections to a given target
+#[derive(Debug)]
+struct AllowTarget(SocketAddr);
+
+#[async_trait]
+impl TcpOutletConnectionAccessControl for AllowTarget {
+    async fn is_authorized(&self, _local_info: &[LocalInfo], target: SocketAddr) -> Result<bool> {
+        Ok(target == self.0)
+    }
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 10000)]
+async fn portal__connection_access_control__should_authorize_each_connection(
+    ctx: &mut Context,
+) -> Result<()> {
+    let payload1 = generate_binary();
+    let payload2 = generate_binary();
+
+    let tcp = TcpTransport::create(ctx).await?;
+
+    let allowed_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let allowed_addr = allowed_listener.local_addr().unwrap();
+    let denied_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let denied_addr = denied_listener.local_addr().unwrap();
+
+    let access_control = Arc::new(AllowTarget(allowed_addr));
+    tcp.create_outlet(
+        "allowed_outlet",
+        allowed_addr.to_string(),
+        TcpOutletOptions::new().with_connection_access_control(access_control.clone()),
+    )
+    .await?;
+    tcp.create_outlet(
+        "denied_outlet",
+        denied_addr.to_string(),
+        TcpOutletOptions::new().with_connection_access_control(access_control),
+    )
+    .await?;
+
+    let (allowed_inlet_addr, _) = tcp
+        .create_inlet(
+            "127.0.0.1:0",
+            route!["allowed_outlet"],
+            TcpInletOptions::new(),
+        )
+        .await?;
+    let (denied_inlet_addr, _) = tcp
+        .create_inlet(
+            "127.0.0.1:0",
+            route!["denied_outlet"],
+            TcpInletOptions::new(),
+        )
+        .await?;
+
+    // the connection to the allowed target goes through
+    let handle = tokio::spawn(async move {
+        let (mut stream, _) = allowed_listener.accept().await.unwrap();
+        read_assert_binary(&mut stream, payload1).await;
+        write_binary(&mut stream, payload2).await;
+    });
+
+    let mut stream = TcpStream::connect(allowed_inlet_addr).await.unwrap();
+    write_binary(&mut stream, payload1).await;
+    read_assert_binary(&mut stream, payload2).await;
+    assert!(handle.await.is_ok());
+
+    // the connection to the denied target is closed without reaching the target
+    let mut stream = TcpStream::connect(denied_inlet_addr).await.unwrap();
+    let mut payload = [0u8; LENGTH];
+    let length = stream.read(&mut payload).await.unwrap();
+    assert_eq!(length, 0);
+
+    let accepted = tokio::time::timeout(Duration::from_millis(500), denied_listener.accept()).await;
+    assert!(accepted.is_err(), "The denied target should not be reached");
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn portal__outlet_allowlist__should_restrict_targets(ctx: &mut Context) -> Result<()> {
+    let payload1 = generate_binary();
+    let payload2 = generate_binary();
+
+    let tcp = TcpTransport::create(ctx).await?;
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let target = format!("localhost:{}", listener.local_addr().unwrap().port());
+
+    // the target is checked when the outlet is created
+    let res = tcp
+        .create_outlet(
+            "denied_outlet",
+            target.clone(),
+            TcpOutletOptions::new()
+                .with_allowlist(TcpOutletAllowlist::new().with_entry("10.0.0.0/8")?),
+        )
+        .await;
+    assert!(res.is_err());
+
+    // and resolved again for each connection, only using the allowed addresses
+    tcp.create_outlet(
+        "outlet",
+        target,
+        TcpOutletOptions::new()
+            .with_allowlist(TcpOutletAllowlist::new().with_entry("127.0.0.0/8")?),
+    )
+    .await?;
+    let (inlet_addr, _) = tcp
+        .create_inlet("127.0.0.1:0", route!["outlet"], TcpInletOptions::new())
+        .await?;
+
+    let handle = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        read_assert_binary(&mut stream, payload1).await;
+        write_binary(&mut stream, payload2).await;
+    });
+
+    let mut stream = TcpStream::connect(inlet_addr).await.unwrap();
+    write_binary(&mut stream, payload1).await;
+    read_assert_binary(&mut stream, payload2).await;
+    assert!(handle.await.is_ok());
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+/// Send a fixed greeting to the target and wait for it to be echoed
+#[derive(Debug)]
+struct Greeter([u8; LENGTH]);
+
+#[async_trait]
+impl TcpOutletConnectionInitializer for Greeter {
+    async fn initialize(
+        &self,
+        reader: &mut (dyn AsyncRead + Send + Unpin),
+        writer: &mut (dyn AsyncWrite + Send + Unpin),
+    ) -> Result<()> {
+        writer.write_all(&self.0).await.unwrap();
+        let mut answer = [0u8; LENGTH];
+        reader.read_exact(&mut answer).await.unwrap();
+        assert_eq!(answer, self.0);
+        Ok(())
+    }
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn portal__connection_initializer__should_run_before_forwarding(
+    ctx: &mut Context,
+) -> Result<()> {
+    let greeting = generate_binary();
+    let payload1 = generate_binary();
+    let payload2 = generate_binary();
+
+    let tcp = TcpTransport::create(ctx).await?;
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    tcp.create_outlet(
+        "outlet",
+        listener.local_addr().unwrap().to_string(),
+        TcpOutletOptions::new().with_connection_initializer(Arc::new(Greeter(greeting))),
+    )
+    .await?;
+    let (inlet_addr, _) = tcp
+        .create_inlet("127.0.0.1:0", route!["outlet"], TcpInletOptions::new())
+        .await?;
+
+    let handle = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        read_assert_binary(&mut stream, greeting).await;
+        write_binary(&mut stream, greeting).await;
+
+        read_assert_binary(&mut stream, payload1).await;
+        write_binary(&mut stream, payload2).await;
+    });
+
+    let mut stream = TcpStream::connect(inlet_addr).await.unwrap();
+    write_binary(&mut stream, payload1).await;
+    read_assert_binary(&mut stream, payload2).await;
+    assert!(handle.await.is_ok());
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use ockam_core::{route, Result, Routed, Worker};
+use ockam_node::Context;
+use ockam_transport_tcp::{TcpConnectionOptions, TcpListenerOptions, TcpProxy, TcpTransport};
+use std::net::{Ipv4Addr, SocketAddr};
+use tokio::io::{copy_bidirectional, AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+pub struct Echoer;
+
+#[ockam_core::worker]
+impl Worker for Echoer {
+    type Message = String;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<String>) -> Result<()> {
+        ctx.send(msg.return_route(), msg.body()).await
+    }
+}
+
+/// Start a minimal SOCKS5 proxy, accepting a single connection to an IPv4 address
+/// with the `alice:secret` credentials
+async fn start_socks5_proxy() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (mut client, _) = listener.accept().await.unwrap();
+
+        let mut greeting = [0u8; 2];
+        client.read_exact(&mut greeting).await.unwrap();
+        let mut methods = vec![0u8; greeting[1] as usize];
+        client.read_exact(&mut methods).await.unwrap();
+        assert!(methods.contains(&0x02));
+        client.write_all(&[0x05, 0x02]).await.unwrap();
+
+        let mut credentials = vec![0u8; 2 + "alice".len() + 1 + "secret".len()];
+        client.read_exact(&mut credentials).await.unwrap();
+        assert_eq!(&credentials[2..7], b"alice");
+        assert_eq!(&credentials[8..], b"secret");
+        client.write_all(&[0x01, 0x00]).await.unwrap();
+
+        let mut request = [0u8; 10];
+        client.read_exact(&mut request).await.unwrap();
+        assert_eq!(request[3], 0x01);
+        let ip = Ipv4Addr::new(request[4], request[5], request[6], request[7]);
+        let port = u16::from_be_bytes([request[8], request[9]]);
+        let mut target = TcpStream::connect((ip, port)).await.unwrap();
+        client
+            .write_all(&[0x05, 0x00, 0x00, 0x01, 0, 0, 0, 0, 0, 0])
+            .await
+            .unwrap();
+
+        let _ = copy_bidirectional(&mut client, &mut target).await;
+    });
+    address
+}
+
+/// Start a minimal HTTP CONNECT proxy, accepting a single connection
+async fn start_http_proxy() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (mut client, _) = listener.accept().await.unwrap();
+
+        let mut request = Vec::new();
+        while !request.ends_with(b"\r\n\r\n") {
+            let mut byte = [0u8; 1];
+            client.read_exact(&mut byte).await.unwrap();
+            request.push(byte[0]);
+        }
+        let request = String::from_utf8(request).unwrap();
+        let target = request
+            .strip_prefix("CONNECT ")
+            .and_then(|r| r.split_whitespace().next())
+            .unwrap()
+            .to_string();
+
+        let mut target = TcpStream::connect(target).await.unwrap();
+        client
+            .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+            .await
+            .unwrap();
+
+        let _ = copy_bidirectional(&mut client, &mut target).await;
+    });
+    address
+}
+
+#[ockam_macros::test]
+async fn tcp_connection_through_proxies(ctx: &mut Context) -> Result<()> {
+    let options = TcpListenerOptions::new();
+    ctx.flow_controls()
+        .add_consumer("echoer", &options.spawner_flow_control_id());
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let listener = transport.listen("127.0.0.1:0", options).await?;
+
+    let proxies = [
+        TcpProxy::socks5(start_socks5_proxy().await.to_string())
+            .with_credentials("alice", "secret"),
+        TcpProxy::http_connect(start_http_proxy().await.to_string()),
+    ];
+
+    for proxy in proxies {
+        let connection = transport
+            .connect(
+                listener.socket_string(),
+                TcpConnectionOptions::new().with_proxy(proxy),
+            )
+            .await?;
+
+        let msg = "Hello through a proxy".to_string();
+        let reply: String = ctx
+            .send_and_receive(route![connection.clone(), "echoer"], msg.clone())
+            .await?;
+        assert_eq!(reply, msg);
+    }
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn tcp_connection_through_unreachable_proxy(ctx: &mut Context) -> Result<()> {
+    let transport = TcpTransport::create(ctx).await?;
+
+    // bind and drop a listener to find a port where nothing is listening
+    let address = TcpListener::bind("127.0.0.1:0")
+        .await
+        .unwrap()
+        .local_addr()
+        .unwrap();
+
+    let result = transport
+        .connect(
+            "127.0.0.1:4000",
+            TcpConnectionOptions::new().with_proxy(TcpProxy::socks5(address.to_string())),
+        )
+        .await;
+    assert!(result.is_err());
+
+    ctx.stop().await
+}
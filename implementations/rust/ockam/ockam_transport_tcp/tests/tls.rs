@@ -0,0 +1,174 @@
+use core::time::Duration;
+use ockam_core::{route, Result, Routed, Worker};
+use ockam_node::{Context, MessageSendReceiveOptions};
+use ockam_transport_tcp::{
+    TcpConnectionOptions, TcpInletOptions, TcpListenerOptions, TcpOutletOptions,
+    TcpTlsClientOptions, TcpTlsServerOptions, TcpTransport,
+};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+pub struct Echoer;
+
+#[ockam_core::worker]
+impl Worker for Echoer {
+    type Message = String;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<String>) -> Result<()> {
+        ctx.send(msg.return_route(), msg.body()).await
+    }
+}
+
+fn certificate() -> String {
+    format!("{}/tests/tls/cert.pem", env!("CARGO_MANIFEST_DIR"))
+}
+
+fn private_key() -> String {
+    format!("{}/tests/tls/key.pem", env!("CARGO_MANIFEST_DIR"))
+}
+
+async fn start_tls_listener(ctx: &Context, transport: &TcpTransport) -> Result<String> {
+    let options =
+        TcpListenerOptions::new().with_tls(TcpTlsServerOptions::new(certificate(), private_key()));
+    ctx.flow_controls()
+        .add_consumer("echoer", &options.spawner_flow_control_id());
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let listener = transport.listen("127.0.0.1:0", options).await?;
+    Ok(format!("localhost:{}", listener.socket_address().port()))
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tcp_tls__tls_connection__should_exchange_messages(ctx: &mut Context) -> Result<()> {
+    let transport = TcpTransport::create(ctx).await?;
+    let peer = start_tls_listener(ctx, &transport).await?;
+
+    let options = TcpConnectionOptions::new()
+        .with_tls(TcpTlsClientOptions::new().with_ca_certificates(certificate()));
+    let connection = transport.connect(peer, options).await?;
+
+    let reply: String = ctx
+        .send_and_receive(
+            route![connection.sender_address().clone(), "echoer"],
+            "Hello".to_string(),
+        )
+        .await?;
+    assert_eq!(reply, "Hello");
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tcp_tls__untrusted_server__should_fail_to_connect(ctx: &mut Context) -> Result<()> {
+    let transport = TcpTransport::create(ctx).await?;
+    let peer = start_tls_listener(ctx, &transport).await?;
+
+    // the self-signed certificate is not trusted by the platform
+    let options = TcpConnectionOptions::new().with_tls(TcpTlsClientOptions::new());
+    assert!(transport.connect(peer, options).await.is_err());
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tcp_tls__plain_client__should_be_rejected(ctx: &mut Context) -> Result<()> {
+    let transport = TcpTransport::create(ctx).await?;
+    let peer = start_tls_listener(ctx, &transport).await?;
+
+    let plain_connection = transport
+        .connect(peer.clone(), TcpConnectionOptions::new())
+        .await?;
+    let reply = ctx
+        .send_and_receive_extended::<String>(
+            route![plain_connection.sender_address().clone(), "echoer"],
+            "Hello".to_string(),
+            MessageSendReceiveOptions::new().with_timeout(Duration::from_secs(1)),
+        )
+        .await;
+    assert!(reply.is_err());
+
+    // the listener keeps accepting TLS connections
+    let options = TcpConnectionOptions::new()
+        .with_tls(TcpTlsClientOptions::new().with_ca_certificates(certificate()));
+    let connection = transport.connect(peer, options).await?;
+    let reply: String = ctx
+        .send_and_receive(
+            route![connection.sender_address().clone(), "echoer"],
+            "Hello".to_string(),
+        )
+        .await?;
+    assert_eq!(reply, "Hello");
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tcp_tls__portal__should_terminate_and_originate_tls(ctx: &mut Context) -> Result<()> {
+    let transport = TcpTransport::create(ctx).await?;
+
+    // plaintext client -> inlet1 -> outlet1 =(TLS)=> inlet2 -> outlet2 -> plaintext target
+    let target = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    transport
+        .create_outlet(
+            "outlet2",
+            target.local_addr().unwrap().to_string(),
+            TcpOutletOptions::new(),
+        )
+        .await?;
+    let (inlet2_addr, _) = transport
+        .create_inlet(
+            "127.0.0.1:0",
+            route!["outlet2"],
+            TcpInletOptions::new().with_tls(TcpTlsServerOptions::new(certificate(), private_key())),
+        )
+        .await?;
+
+    transport
+        .create_outlet(
+            "outlet1",
+            format!("localhost:{}", inlet2_addr.port()),
+            TcpOutletOptions::new()
+                .with_tls(TcpTlsClientOptions::new().with_ca_certificates(certificate())),
+        )
+        .await?;
+    let (inlet1_addr, _) = transport
+        .create_inlet("127.0.0.1:0", route!["outlet1"], TcpInletOptions::new())
+        .await?;
+
+    let handle = tokio::spawn(async move {
+        let (mut stream, _) = target.accept().await.unwrap();
+        let mut buffer = [0u8; 5];
+        stream.read_exact(&mut buffer).await.unwrap();
+        stream.write_all(&buffer).await.unwrap();
+    });
+
+    let mut stream = TcpStream::connect(inlet1_addr).await.unwrap();
+    stream.write_all(b"Hello").await.unwrap();
+    let mut buffer = [0u8; 5];
+    stream.read_exact(&mut buffer).await.unwrap();
+    assert_eq!(&buffer, b"Hello");
+    assert!(handle.await.is_ok());
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
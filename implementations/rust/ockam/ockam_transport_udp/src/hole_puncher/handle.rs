@@ -1,5 +1,7 @@
 use super::message::PunchMessage;
-use crate::{hole_puncher::worker::UdpHolePunchWorker, PunchError};
+use super::responder::UdpHolePunchResponder;
+use crate::hole_puncher::worker::{PeerDiscovery, UdpHolePunchWorker};
+use crate::PunchError;
 use ockam_core::{Address, AllowOnwardAddress, AllowSourceAddress, Result, Route};
 use ockam_node::Context;
 
@@ -23,6 +25,39 @@ use ockam_node::Context;
 /// UDP and NAT Hole Punching are unreliable protocols. Expect send and receive
 /// failures.
 ///
+/// # Coordination through a relay
+///
+/// Instead of registering names with the Rendezvous service, the punchers can
+/// exchange their public UDP routes through any route between the two nodes,
+/// usually a route going through a relay. Both nodes still need to reach the
+/// Rendezvous service over UDP, to learn their public UDP address.
+///
+/// The remote node starts a responder with [`UdpHolePuncher::start_responder`],
+/// which creates a puncher for each node offering its route. The local node
+/// creates its puncher with [`UdpHolePuncher::create_via_relay`]. Both punchers
+/// then ping each other at the same time, so that the NATs on both sides let
+/// the datagrams of the other side through, and keep pinging to hold the NAT
+/// mappings open.
+///
+/// ```rust
+/// # use {ockam_node::Context, ockam_core::{Result, route}};
+/// # async fn test(ctx: &mut Context) -> Result<()> {
+/// use ockam_transport_udp::{UdpHolePuncher, UdpTransport, UDP};
+///
+/// UdpTransport::create(ctx).await?;
+/// let rendezvous_route = route![(UDP, "192.168.1.10:4000"), "zurg"];
+///
+/// // On 'bob', start a responder at the address 'puncher', reachable through a relay
+/// UdpHolePuncher::start_responder(ctx, "puncher", rendezvous_route.clone()).await?;
+///
+/// // On 'alice', open a hole to 'bob' using the relay 'forward_to_bob'
+/// let relay_route = route!["tcp_connection_to_relay", "forward_to_bob", "puncher"];
+/// let mut puncher = UdpHolePuncher::create_via_relay(ctx, rendezvous_route, relay_route).await?;
+/// puncher.wait_for_hole_open().await?;
+/// # Ok(())
+/// # }
+/// ```
+///
 /// # Example
 ///
 /// ```rust
@@ -68,9 +103,52 @@ impl UdpHolePuncher {
         peer_puncher_name: S,
         rendezvous_route: R,
     ) -> Result<UdpHolePuncher> {
-        // Check if we can reach the rendezvous service
+        let discovery = PeerDiscovery::Named {
+            this_puncher_name: puncher_name.as_ref().to_string(),
+            peer_puncher_name: peer_puncher_name.as_ref().to_string(),
+        };
+        Self::create_with_discovery(ctx, rendezvous_route.into(), discovery).await
+    }
+
+    /// Create a new UDP NAT Hole Puncher exchanging public routes with a
+    /// responder started by the peer node with [`UdpHolePuncher::start_responder`].
+    ///
+    /// `peer_route` is a route to the responder, for example through a relay.
+    pub async fn create_via_relay<R: Into<Route>, P: Into<Route>>(
+        ctx: &mut Context,
+        rendezvous_route: R,
+        peer_route: P,
+    ) -> Result<UdpHolePuncher> {
+        let discovery = PeerDiscovery::Coordinated {
+            coordination_route: peer_route.into(),
+        };
+        Self::create_with_discovery(ctx, rendezvous_route.into(), discovery).await
+    }
+
+    /// Start a responder at the given address. For each puncher created with
+    /// [`UdpHolePuncher::create_via_relay`] which sends its public route to this
+    /// address, the responder creates a local puncher opening a hole to it.
+    ///
+    /// Messages received through these holes are forwarded to local entities,
+    /// with a return route going back through the hole.
+    pub async fn start_responder<R: Into<Route>>(
+        ctx: &mut Context,
+        address: impl Into<Address>,
+        rendezvous_route: R,
+    ) -> Result<()> {
         let rendezvous_route = rendezvous_route.into();
+        if !UdpHolePunchWorker::rendezvous_reachable(ctx, &rendezvous_route).await {
+            return Err(PunchError::RendezvousServiceNotFound)?;
+        }
+        UdpHolePunchResponder::start(ctx, address.into(), rendezvous_route).await
+    }
 
+    async fn create_with_discovery(
+        ctx: &mut Context,
+        rendezvous_route: Route,
+        discovery: PeerDiscovery,
+    ) -> Result<UdpHolePuncher> {
+        // Check if we can reach the rendezvous service
         if !UdpHolePunchWorker::rendezvous_reachable(ctx, &rendezvous_route).await {
             return Err(PunchError::RendezvousServiceNotFound)?;
         }
@@ -79,10 +157,10 @@ impl UdpHolePuncher {
         let handle_addr = Address::random_tagged("UdpHolePuncher.detached");
         let (worker_main_addr, worker_local_addr) = UdpHolePunchWorker::create(
             ctx,
-            &handle_addr,
+            Some(handle_addr.clone()),
             rendezvous_route,
-            puncher_name.as_ref(),
-            peer_puncher_name.as_ref(),
+            discovery,
+            None,
         )
         .await?;
 
@@ -1,4 +1,4 @@
-use ockam_core::Message;
+use ockam_core::{Message, Route};
 use serde::{Deserialize, Serialize};
 
 // TODO: Use CBOR encoding for messages
@@ -11,4 +11,6 @@ pub(crate) enum PunchMessage {
     Heartbeat,
     WaitForHoleOpen,
     Payload(Vec<u8>),
+    /// Public route to the sending puncher, exchanged through a coordination route
+    Offer(Route),
 }
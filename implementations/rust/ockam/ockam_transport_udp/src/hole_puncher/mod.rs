@@ -4,4 +4,5 @@ pub use handle::UdpHolePuncher;
 mod error;
 mod handle;
 mod message;
+mod responder;
 mod worker;
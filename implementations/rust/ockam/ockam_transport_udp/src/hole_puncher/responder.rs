@@ -0,0 +1,73 @@
+use crate::hole_puncher::message::PunchMessage;
+use crate::hole_puncher::worker::{PeerDiscovery, UdpHolePunchWorker};
+use ockam_core::{async_trait, Address, Any, Decodable, Result, Route, Routed, Worker};
+use ockam_node::Context;
+use std::collections::BTreeMap;
+use tracing::{debug, trace};
+
+/// [`Worker`] creating a [`UdpHolePunchWorker`] for each remote puncher
+/// offering its public route through a coordination route.
+///
+/// See [`UdpHolePuncher::start_responder`](crate::hole_puncher::UdpHolePuncher::start_responder).
+pub(crate) struct UdpHolePunchResponder {
+    /// Route to Rendezvous service
+    rendezvous_route: Route,
+    /// Main address of the puncher created for each remote puncher route
+    punchers: BTreeMap<String, Address>,
+}
+
+impl UdpHolePunchResponder {
+    pub(crate) async fn start(
+        ctx: &Context,
+        address: Address,
+        rendezvous_route: Route,
+    ) -> Result<()> {
+        let responder = Self {
+            rendezvous_route,
+            punchers: BTreeMap::new(),
+        };
+        ctx.start_worker(address, responder).await
+    }
+}
+
+#[async_trait]
+impl Worker for UdpHolePunchResponder {
+    type Message = Any;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        let peer_route = match PunchMessage::decode(msg.payload())? {
+            PunchMessage::Offer(peer_route) => peer_route,
+            other => {
+                trace!("Responder ignoring {:?}", other);
+                return Ok(());
+            }
+        };
+
+        // The remote puncher offers its route until the hole is open,
+        // these offers are passed on to the puncher created for it
+        if let Some(main_addr) = self.punchers.get(&peer_route.to_string()) {
+            return ctx
+                .send(main_addr.clone(), PunchMessage::Offer(peer_route))
+                .await;
+        }
+
+        debug!(
+            "Creating a puncher for the remote puncher at {}",
+            peer_route
+        );
+        let discovery = PeerDiscovery::Coordinated {
+            coordination_route: msg.return_route(),
+        };
+        let (main_addr, _) = UdpHolePunchWorker::create(
+            ctx,
+            None,
+            self.rendezvous_route.clone(),
+            discovery,
+            Some(peer_route.clone()),
+        )
+        .await?;
+        self.punchers.insert(peer_route.to_string(), main_addr);
+        Ok(())
+    }
+}
@@ -2,8 +2,8 @@ use crate::hole_puncher::message::PunchMessage;
 use crate::rendezvous_service::{RendezvousRequest, RendezvousResponse};
 use crate::PunchError;
 use ockam_core::{
-    Address, AllowAll, Any, Decodable, Encodable, LocalMessage, Mailbox, Mailboxes, Result, Route,
-    Routed, Worker,
+    route, Address, AllowAll, Any, Decodable, Encodable, LocalMessage, Mailbox, Mailboxes, Result,
+    Route, Routed, Worker,
 };
 use ockam_node::{Context, DelayedEvent, MessageSendReceiveOptions, WorkerBuilder};
 use std::sync::Arc;
@@ -27,6 +27,26 @@ const QUICK_TIMEOUT: Duration = Duration::from_secs(3);
 // TODO: Should `hole_puncher` and `rendezvous_service` files be moved to their
 // own crate outside  `ockam_transport_udp`?
 
+/// How a puncher finds the public route to its peer puncher
+#[derive(Clone, Debug)]
+pub(crate) enum PeerDiscovery {
+    /// Both punchers register their name with the Rendezvous service and
+    /// query the route registered by the other one
+    Named {
+        /// Name of this puncher
+        this_puncher_name: String,
+        /// Name of peer node's puncher
+        peer_puncher_name: String,
+    },
+    /// Both punchers get their own public address from the Rendezvous service
+    /// and offer it to the other one through a coordination route, for example
+    /// a route going through a relay
+    Coordinated {
+        /// Route to the peer puncher, or to the responder creating it
+        coordination_route: Route,
+    },
+}
+
 /// [`Worker`] for UDP NAT Hole Puncher
 ///
 /// Using a remote Rendezvous service [`UdpRendezvousService`](crate::rendezvous_service::UdpRendezvousService`) tries to create
@@ -44,6 +64,8 @@ const QUICK_TIMEOUT: Duration = Duration::from_secs(3);
 /// Receives from...
 /// - the remote peer's puncher [`UdpHolePunchWorker`]
 /// - this puncher's local handle [`UdpHolePuncher`](crate::hole_puncher::UdpHolePuncher)
+/// - the coordination route, when the punchers exchange their public routes
+///   through it
 ///
 /// # 'Local' Mailbox
 ///
@@ -60,16 +82,14 @@ pub(crate) struct UdpHolePunchWorker {
     main_addr: Address,
     /// Address of local mailbox
     local_addr: Address,
-    /// Address of our handle's mailbox
-    handle_addr: Address,
+    /// Address of our handle's mailbox, if the puncher has a handle
+    handle_addr: Option<Address>,
     /// For generating internal heartbeat messages
     heartbeat: DelayedEvent<PunchMessage>,
     /// Route to Rendezvous service
     rendezvous_route: Route,
-    /// Name of this puncher
-    this_puncher_name: String,
-    /// Name of peer node's puncher
-    peer_puncher_name: String,
+    /// How the route to the peer puncher is found
+    discovery: PeerDiscovery,
     /// Is hole open to peer?
     hole_open: bool,
     /// Route to peer node's puncher
@@ -83,17 +103,17 @@ pub(crate) struct UdpHolePunchWorker {
 
 impl UdpHolePunchWorker {
     /// Update the Rendezvous service
-    async fn rendezvous_update(&self, ctx: &mut Context) -> Result<()> {
+    async fn rendezvous_update(&self, ctx: &mut Context, this_puncher_name: &str) -> Result<()> {
         let msg = RendezvousRequest::Update {
-            puncher_name: self.this_puncher_name.clone(),
+            puncher_name: this_puncher_name.to_string(),
         };
         ctx.send(self.rendezvous_route.clone(), msg).await
     }
 
     /// Query the Rendezvous service
-    async fn rendezvous_query(&self, ctx: &mut Context) -> Result<Route> {
+    async fn rendezvous_query(&self, ctx: &mut Context, peer_puncher_name: &str) -> Result<Route> {
         let msg = RendezvousRequest::Query {
-            puncher_name: self.peer_puncher_name.clone(),
+            puncher_name: peer_puncher_name.to_string(),
         };
 
         // Send from a temporary context/address, so we can process the reply here
@@ -112,6 +132,26 @@ impl UdpHolePunchWorker {
         }
     }
 
+    /// Ask the Rendezvous service for our public UDP address.
+    ///
+    /// The request is sent from the 'client' UDP socket, like the messages
+    /// sent to the peer, so the address is the one the peer must use to reach us
+    async fn rendezvous_my_address(&self, ctx: &mut Context) -> Result<Address> {
+        let res = ctx
+            .send_and_receive_extended::<RendezvousResponse>(
+                self.rendezvous_route.clone(),
+                RendezvousRequest::GetMyAddress,
+                MessageSendReceiveOptions::new().with_timeout(QUICK_TIMEOUT),
+            )
+            .await?
+            .body();
+
+        match res {
+            RendezvousResponse::GetMyAddress(r) => r,
+            _ => Err(PunchError::Internal)?,
+        }
+    }
+
     /// Test to see if we can reach the Rendezvous service
     pub(crate) async fn rendezvous_reachable(ctx: &mut Context, rendezvous_route: &Route) -> bool {
         for _ in 0..PING_TRIES {
@@ -138,16 +178,20 @@ impl UdpHolePunchWorker {
 
     pub(crate) async fn create(
         ctx: &Context,
-        handle_addr: &Address,
+        handle_addr: Option<Address>,
         rendezvous_route: Route,
-        this_puncher_name: &str,
-        peer_puncher_name: &str,
+        discovery: PeerDiscovery,
+        peer_route: Option<Route>,
     ) -> Result<(Address, Address)> {
         // Create worker' addresses, heartbeat & mailboxes
-        let main_addr =
-            Address::random_tagged(format!("UdpHolePuncher.main.{}", this_puncher_name).as_str());
-        let local_addr =
-            Address::random_tagged(format!("UdpHolePuncher.local.{}", this_puncher_name).as_str());
+        let tag = match &discovery {
+            PeerDiscovery::Named {
+                this_puncher_name, ..
+            } => this_puncher_name.as_str(),
+            PeerDiscovery::Coordinated { .. } => "coordinated",
+        };
+        let main_addr = Address::random_tagged(format!("UdpHolePuncher.main.{}", tag).as_str());
+        let local_addr = Address::random_tagged(format!("UdpHolePuncher.local.{}", tag).as_str());
 
         let heartbeat =
             DelayedEvent::create(ctx, main_addr.clone(), PunchMessage::Heartbeat).await?;
@@ -174,13 +218,12 @@ impl UdpHolePunchWorker {
         let worker = Self {
             main_addr: main_addr.clone(),
             local_addr: local_addr.clone(),
-            handle_addr: handle_addr.clone(),
+            handle_addr,
             heartbeat,
             rendezvous_route,
-            this_puncher_name: String::from(this_puncher_name),
-            peer_puncher_name: String::from(peer_puncher_name),
+            discovery,
             hole_open: false,
-            peer_route: None,
+            peer_route,
             peer_received_at: Instant::now(),
             wait_for_hole_open_addr: None,
         };
@@ -205,6 +248,22 @@ impl UdpHolePunchWorker {
         Ok(())
     }
 
+    /// Handle the public route offered by the peer through the coordination route.
+    ///
+    /// The peer starts pinging us when it sends its offer, so we ping it
+    /// right away: the datagrams sent by both sides open the holes in both NATs
+    async fn handle_offer(&mut self, ctx: &mut Context, peer_route: Route) -> Result<()> {
+        if self.peer_route.as_ref() != Some(&peer_route) {
+            trace!("Received a new route from peer: {}", peer_route);
+            self.hole_open = false;
+            self.peer_route = Some(peer_route.clone());
+        }
+        if !self.hole_open {
+            ctx.send(peer_route, PunchMessage::Ping).await?;
+        }
+        Ok(())
+    }
+
     /// Handle messages from peer
     async fn handle_peer(
         &mut self,
@@ -270,14 +329,31 @@ impl UdpHolePunchWorker {
             // Attempt hole open if it is closed
             trace!("Hole closed. Will attempt to open hole to peer");
 
-            // Update Rendezvous service
-            self.rendezvous_update(ctx).await?;
-
-            // Query Rendezvous service
-            if let Ok(peer_route) = self.rendezvous_query(ctx).await {
-                self.peer_route = Some(peer_route.clone());
+            match self.discovery.clone() {
+                PeerDiscovery::Named {
+                    this_puncher_name,
+                    peer_puncher_name,
+                } => {
+                    // Update Rendezvous service
+                    self.rendezvous_update(ctx, &this_puncher_name).await?;
+
+                    // Query Rendezvous service
+                    if let Ok(peer_route) = self.rendezvous_query(ctx, &peer_puncher_name).await {
+                        self.peer_route = Some(peer_route);
+                    }
+                }
+                PeerDiscovery::Coordinated { coordination_route } => {
+                    // Offer our public route to the peer, which pings us as soon as it gets it
+                    if let Ok(public_addr) = self.rendezvous_my_address(ctx).await {
+                        let our_route = route![public_addr, self.main_addr.clone()];
+                        ctx.send(coordination_route, PunchMessage::Offer(our_route))
+                            .await?;
+                    }
+                }
+            }
 
-                // Ping peer
+            // Ping peer
+            if let Some(peer_route) = self.peer_route.as_ref() {
                 ctx.send(peer_route.clone(), PunchMessage::Ping).await?;
             }
         } else {
@@ -353,17 +429,25 @@ impl Worker for UdpHolePunchWorker {
                     self.handle_peer(ctx, msg, &return_route).await?;
                 } else if sender_addr == self.heartbeat.address() {
                     self.handle_heartbeat(ctx).await?;
-                } else if sender_addr == self.handle_addr {
+                } else if Some(&sender_addr) == self.handle_addr.as_ref() {
                     let inner_msg = PunchMessage::decode(msg.payload())?;
                     match inner_msg {
                         PunchMessage::WaitForHoleOpen => {
-                            self.wait_for_hole_open_addr = Some(sender_addr)
-                            // TODO: If the hole is already open, the handle
-                            // will not be informed until it's closed and
-                            // opened again. Is this what we want?
+                            self.wait_for_hole_open_addr = Some(sender_addr);
+                            if self.hole_open {
+                                self.set_hole_open(ctx).await?;
+                            }
                         }
                         _ => return Err(PunchError::Internal)?,
                     }
+                } else if let PeerDiscovery::Coordinated { .. } = self.discovery {
+                    match PunchMessage::decode(msg.payload())? {
+                        PunchMessage::Offer(peer_route) => {
+                            self.handle_offer(ctx, peer_route).await?;
+                        }
+                        // The peer may ping us before its offer reaches us
+                        other => trace!("Ignoring {:?} from an unknown sender", other),
+                    }
                 }
             }
 
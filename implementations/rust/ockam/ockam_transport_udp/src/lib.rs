@@ -10,11 +10,15 @@
 use ockam_core::TransportType;
 
 pub use hole_puncher::{PunchError, UdpHolePuncher};
+pub use portal::{
+    UdpInletOptions, UdpOutletOptions, UdpPortalMessage, DEFAULT_IDLE_TIMEOUT, MAX_DATAGRAM_SIZE,
+};
 pub use rendezvous_service::UdpRendezvousService;
 pub use transport::UdpTransport;
 pub use transport::UdpTransportExtension;
 
 mod hole_puncher;
+mod portal;
 mod rendezvous_service;
 mod router;
 mod transport;
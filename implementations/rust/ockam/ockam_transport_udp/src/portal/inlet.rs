@@ -0,0 +1,145 @@
+use crate::portal::addresses::{Addresses, PortalType};
+use crate::portal::{
+    UdpInletOptions, UdpInletSessions, UdpPortalInternalMessage, UdpPortalWorker,
+    MAX_DATAGRAM_SIZE, RECEIVE_BUFFER_SIZE,
+};
+use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
+use ockam_core::{async_trait, Address, Processor, Result, Route};
+use ockam_node::Context;
+use ockam_transport_core::TransportError;
+use std::net::SocketAddr;
+use tokio::net::UdpSocket;
+use tracing::{debug, error, warn};
+
+/// A UDP Portal Inlet processor
+///
+/// UDP Portal Inlet processors are created by `UdpTransport`
+/// after a call is made to
+/// [`UdpTransport::create_inlet`](crate::UdpTransport::create_inlet).
+///
+/// The datagrams received from each UDP client are relayed by a dedicated
+/// `UdpPortalWorker`, which is created for the first datagram of that client.
+pub(crate) struct UdpInletProcessor {
+    socket: Arc<UdpSocket>,
+    outlet_listener_route: Route,
+    options: UdpInletOptions,
+    sessions: UdpInletSessions,
+    buf: Vec<u8>,
+}
+
+impl UdpInletProcessor {
+    /// Start a new `UdpInletProcessor`
+    pub(crate) async fn start(
+        ctx: &Context,
+        outlet_listener_route: Route,
+        addr: SocketAddr,
+        options: UdpInletOptions,
+    ) -> Result<(SocketAddr, Address)> {
+        let processor_address = Address::random_tagged("UdpInletProcessor");
+
+        debug!("Binding UdpInletProcessor to {}", addr);
+        let socket = match UdpSocket::bind(addr).await {
+            Ok(socket) => socket,
+            Err(err) => {
+                error!(%addr, %err, "could not bind to address");
+                return Err(TransportError::from(err))?;
+            }
+        };
+        let socket_addr = socket.local_addr().map_err(TransportError::from)?;
+
+        let processor = Self {
+            socket: Arc::new(socket),
+            outlet_listener_route,
+            options,
+            sessions: Default::default(),
+            buf: vec![0; RECEIVE_BUFFER_SIZE],
+        };
+
+        ctx.start_processor(processor_address.clone(), processor)
+            .await?;
+
+        Ok((socket_addr, processor_address))
+    }
+
+    /// Return the address of the worker relaying the datagrams of the client,
+    /// creating it if needed
+    async fn session(&self, ctx: &Context, peer: SocketAddr) -> Result<Address> {
+        if let Some(address) = self.sessions.lock().unwrap().get(&peer) {
+            return Ok(address.clone());
+        }
+
+        let addresses = Addresses::generate(PortalType::Inlet);
+        self.options.setup_flow_control(
+            ctx.flow_controls(),
+            &addresses,
+            self.outlet_listener_route.next()?,
+        );
+
+        self.sessions
+            .lock()
+            .unwrap()
+            .insert(peer, addresses.internal.clone());
+
+        UdpPortalWorker::start_new_inlet(
+            ctx,
+            self.socket.clone(),
+            peer,
+            self.outlet_listener_route.clone(),
+            addresses.clone(),
+            self.sessions.clone(),
+            self.options.incoming_access_control.clone(),
+            self.options.idle_timeout,
+        )
+        .await?;
+
+        Ok(addresses.internal)
+    }
+}
+
+#[async_trait]
+impl Processor for UdpInletProcessor {
+    type Context = Context;
+
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        let sessions: Vec<Address> = self.sessions.lock().unwrap().values().cloned().collect();
+        for address in sessions {
+            let _ = ctx.stop_worker(address).await;
+        }
+
+        Ok(())
+    }
+
+    async fn process(&mut self, ctx: &mut Self::Context) -> Result<bool> {
+        let (len, peer) = match self.socket.recv_from(&mut self.buf).await {
+            Ok(result) => result,
+            Err(err) => {
+                warn!("UDP Inlet failed to receive a datagram: {}", err);
+                return Ok(true);
+            }
+        };
+
+        if len > MAX_DATAGRAM_SIZE {
+            warn!(
+                "UDP Inlet dropped a datagram of {} bytes from {}, larger than {} bytes",
+                len, peer, MAX_DATAGRAM_SIZE
+            );
+            return Ok(true);
+        }
+
+        let address = self.session(ctx, peer).await?;
+        if let Err(err) = ctx
+            .send(
+                address,
+                UdpPortalInternalMessage::Datagram(self.buf[..len].to_vec()),
+            )
+            .await
+        {
+            // the session may have just been closed
+            warn!("UDP Inlet dropped a datagram from {}: {}", peer, err);
+            self.sessions.lock().unwrap().remove(&peer);
+        }
+
+        Ok(true)
+    }
+}
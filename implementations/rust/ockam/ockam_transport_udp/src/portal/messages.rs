@@ -0,0 +1,38 @@
+use ockam_core::compat::vec::Vec;
+use ockam_core::Message;
+use serde::{Deserialize, Serialize};
+
+/// A command message type for a UDP Portal
+#[derive(Serialize, Deserialize, Message, Debug)]
+pub enum UdpPortalMessage {
+    /// First message that Inlet sends to the Outlet
+    Ping,
+    /// First message that Outlet sends to the Inlet
+    Pong,
+    /// Message to indicate that the session was closed on the other side
+    Disconnect,
+    /// One datagram, which is written to the UDP socket as a single datagram
+    Payload(Vec<u8>),
+}
+
+/// An internal message type for a UDP Portal
+#[derive(Serialize, Deserialize, Message, Clone)]
+pub(crate) enum UdpPortalInternalMessage {
+    /// Datagram received from the local UDP socket
+    Datagram(Vec<u8>),
+    /// Check if the session has been idle for too long
+    CheckIdle,
+}
+
+/// Maximum size of a datagram relayed by a UDP portal.
+///
+/// Each datagram is sent over the Ockam route as a single message, which must still fit
+/// in a transport message (at most `u16::MAX` bytes) once encrypted by a secure channel.
+/// This is the same limit as the size of the chunks sent by a TCP portal.
+/// Larger datagrams are dropped.
+pub const MAX_DATAGRAM_SIZE: usize = 48 * 1024;
+
+/// Size of the buffers used to receive datagrams from a UDP socket: 65,535 bytes minus
+/// the IPv4 and UDP headers, so that datagrams larger than [`MAX_DATAGRAM_SIZE`] are
+/// received entirely and can be dropped
+pub(crate) const RECEIVE_BUFFER_SIZE: usize = 65_507;
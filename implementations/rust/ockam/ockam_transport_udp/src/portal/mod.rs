@@ -0,0 +1,14 @@
+mod addresses;
+mod inlet;
+mod messages;
+mod options;
+mod outlet_listener;
+mod portal_receiver;
+mod portal_worker;
+
+pub(crate) use inlet::*;
+pub use messages::*;
+pub use options::*;
+pub(crate) use outlet_listener::*;
+pub(crate) use portal_receiver::*;
+pub(crate) use portal_worker::*;
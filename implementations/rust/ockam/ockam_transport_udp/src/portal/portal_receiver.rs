@@ -0,0 +1,60 @@
+use crate::portal::{UdpPortalInternalMessage, MAX_DATAGRAM_SIZE, RECEIVE_BUFFER_SIZE};
+use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
+use ockam_core::{async_trait, Address, Processor, Result};
+use ockam_node::Context;
+use tokio::net::UdpSocket;
+use tracing::warn;
+
+/// A UDP Portal receiving processor
+///
+/// UDP Portal receiving processors are created by an Outlet `UdpPortalWorker`
+/// to read the datagrams sent back by the target, on a socket connected to it
+pub(crate) struct UdpPortalRecvProcessor {
+    socket: Arc<UdpSocket>,
+    worker_address: Address,
+    buf: Vec<u8>,
+}
+
+impl UdpPortalRecvProcessor {
+    /// Create a new `UdpPortalRecvProcessor`
+    pub fn new(socket: Arc<UdpSocket>, worker_address: Address) -> Self {
+        Self {
+            socket,
+            worker_address,
+            buf: vec![0; RECEIVE_BUFFER_SIZE],
+        }
+    }
+}
+
+#[async_trait]
+impl Processor for UdpPortalRecvProcessor {
+    type Context = Context;
+
+    async fn process(&mut self, ctx: &mut Context) -> Result<bool> {
+        let len = match self.socket.recv(&mut self.buf).await {
+            Ok(len) => len,
+            Err(err) => {
+                // e.g. the target port is closed, the session is kept until it is idle
+                warn!("UDP Portal failed to receive a datagram: {}", err);
+                return Ok(true);
+            }
+        };
+
+        if len > MAX_DATAGRAM_SIZE {
+            warn!(
+                "UDP Portal dropped a datagram of {} bytes, larger than {} bytes",
+                len, MAX_DATAGRAM_SIZE
+            );
+            return Ok(true);
+        }
+
+        ctx.send(
+            self.worker_address.clone(),
+            UdpPortalInternalMessage::Datagram(self.buf[..len].to_vec()),
+        )
+        .await?;
+
+        Ok(true)
+    }
+}
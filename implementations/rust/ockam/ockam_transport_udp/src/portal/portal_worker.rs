@@ -0,0 +1,435 @@
+use crate::portal::addresses::{Addresses, PortalType};
+use crate::portal::{UdpPortalInternalMessage, UdpPortalMessage, UdpPortalRecvProcessor};
+use core::time::Duration;
+use ockam_core::compat::collections::VecDeque;
+use ockam_core::compat::sync::{Arc, Mutex};
+use ockam_core::{
+    async_trait, Address, AllowAll, AllowOnwardAddress, AllowSourceAddresses, Any, Decodable,
+    DenyAll, IncomingAccessControl, Mailbox, Mailboxes, Result, Route, Routed, Worker,
+};
+use ockam_node::{Context, DelayedEvent, ProcessorBuilder, WorkerBuilder};
+use ockam_transport_core::TransportError;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use tokio::net::UdpSocket;
+use tokio::time::Instant;
+use tracing::{debug, info, trace, warn};
+
+/// Sessions of an Inlet, indexed by the socket address of their UDP client
+pub(crate) type UdpInletSessions = Arc<Mutex<HashMap<SocketAddr, Address>>>;
+
+/// Maximum number of datagrams kept while waiting for the Outlet to respond
+const MAX_PENDING_DATAGRAMS: usize = 64;
+
+/// Enumerate all `UdpPortalWorker` states
+///
+/// Possible state transitions are:
+///
+/// `Outlet`: `SendPong` -> `Initialized`
+/// `Inlet`: `SendPing` -> `ReceivePong` -> `Initialized`
+#[derive(Clone)]
+enum State {
+    SendPing { ping_route: Route },
+    SendPong { pong_route: Route },
+    ReceivePong,
+    Initialized,
+}
+
+/// A UDP Portal worker
+///
+/// A UDP Portal worker relays the datagrams of one UDP session: the datagrams
+/// exchanged with one client for an Inlet, and with the target for an Outlet.
+/// Each datagram is sent over the Ockam route as a single
+/// [`UdpPortalMessage::Payload`], so that datagram boundaries are preserved.
+///
+/// Since UDP has no notion of connection, the session is closed when no datagram
+/// was exchanged during the idle timeout.
+pub(crate) struct UdpPortalWorker {
+    state: State,
+    socket: Option<Arc<UdpSocket>>,
+    peer: SocketAddr,
+    addresses: Addresses,
+    remote_route: Option<Route>,
+    portal_type: PortalType,
+    pending_datagrams: VecDeque<Vec<u8>>,
+    idle_timeout: Duration,
+    idle_timer: DelayedEvent<UdpPortalInternalMessage>,
+    last_activity: Instant,
+    inlet_sessions: Option<UdpInletSessions>,
+    is_disconnecting: bool,
+}
+
+impl UdpPortalWorker {
+    /// Start a new `UdpPortalWorker` of type [`PortalType::Inlet`] for the datagrams
+    /// received from `peer` by the Inlet processor
+    #[allow(clippy::too_many_arguments)]
+    pub(super) async fn start_new_inlet(
+        ctx: &Context,
+        socket: Arc<UdpSocket>,
+        peer: SocketAddr,
+        ping_route: Route,
+        addresses: Addresses,
+        inlet_sessions: UdpInletSessions,
+        access_control: Arc<dyn IncomingAccessControl>,
+        idle_timeout: Duration,
+    ) -> Result<()> {
+        Self::start(
+            ctx,
+            State::SendPing { ping_route },
+            Some(socket),
+            peer,
+            addresses,
+            PortalType::Inlet,
+            ctx.address(),
+            Some(inlet_sessions),
+            access_control,
+            idle_timeout,
+        )
+        .await
+    }
+
+    /// Start a new `UdpPortalWorker` of type [`PortalType::Outlet`] sending datagrams to `peer`
+    pub(super) async fn start_new_outlet(
+        ctx: &Context,
+        peer: SocketAddr,
+        pong_route: Route,
+        addresses: Addresses,
+        access_control: Arc<dyn IncomingAccessControl>,
+        idle_timeout: Duration,
+    ) -> Result<()> {
+        let receiver = addresses.receiver.clone();
+        Self::start(
+            ctx,
+            State::SendPong { pong_route },
+            None,
+            peer,
+            addresses,
+            PortalType::Outlet,
+            receiver,
+            None,
+            access_control,
+            idle_timeout,
+        )
+        .await
+    }
+
+    /// Start a new `UdpPortalWorker`. Datagrams received from the local UDP socket
+    /// are only accepted from `datagram_source`
+    #[allow(clippy::too_many_arguments)]
+    async fn start(
+        ctx: &Context,
+        state: State,
+        socket: Option<Arc<UdpSocket>>,
+        peer: SocketAddr,
+        addresses: Addresses,
+        portal_type: PortalType,
+        datagram_source: Address,
+        inlet_sessions: Option<UdpInletSessions>,
+        access_control: Arc<dyn IncomingAccessControl>,
+        idle_timeout: Duration,
+    ) -> Result<()> {
+        info!(
+            "Creating new UDP {:?} at internal: {}, remote: {}",
+            portal_type.str(),
+            addresses.internal,
+            addresses.remote
+        );
+
+        let idle_timer = DelayedEvent::create(
+            ctx,
+            addresses.internal.clone(),
+            UdpPortalInternalMessage::CheckIdle,
+        )
+        .await?;
+
+        let internal_mailbox = Mailbox::new(
+            addresses.internal.clone(),
+            Arc::new(AllowSourceAddresses(vec![
+                datagram_source,
+                idle_timer.address(),
+            ])),
+            Arc::new(DenyAll),
+        );
+
+        let remote_mailbox = Mailbox::new(
+            addresses.remote.clone(),
+            access_control,
+            Arc::new(AllowAll), // FIXME: @ac Allow to respond anywhere using return_route
+        );
+
+        let worker = Self {
+            state,
+            socket,
+            peer,
+            addresses,
+            remote_route: None,
+            portal_type,
+            pending_datagrams: VecDeque::new(),
+            idle_timeout,
+            idle_timer,
+            last_activity: Instant::now(),
+            inlet_sessions,
+            is_disconnecting: false,
+        };
+
+        WorkerBuilder::new(worker)
+            .with_mailboxes(Mailboxes::new(internal_mailbox, vec![remote_mailbox]))
+            .start(ctx)
+            .await?;
+
+        Ok(())
+    }
+}
+
+impl UdpPortalWorker {
+    fn clone_state(&self) -> State {
+        self.state.clone()
+    }
+
+    async fn handle_send_ping(&self, ctx: &Context, ping_route: Route) -> Result<State> {
+        // Force creation of Outlet on the other side
+        ctx.send_from_address(
+            ping_route,
+            UdpPortalMessage::Ping,
+            self.addresses.remote.clone(),
+        )
+        .await?;
+
+        debug!("UDP Inlet at: {} sent ping", self.addresses.internal);
+
+        Ok(State::ReceivePong)
+    }
+
+    async fn handle_send_pong(&mut self, ctx: &Context, pong_route: Route) -> Result<State> {
+        // Use a socket connected to the target, so that only its datagrams are received
+        let bind_addr = if self.peer.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        let socket = UdpSocket::bind(bind_addr)
+            .await
+            .map_err(TransportError::from)?;
+        socket
+            .connect(self.peer)
+            .await
+            .map_err(TransportError::from)?;
+        let socket = Arc::new(socket);
+        self.socket = Some(socket.clone());
+
+        let receiver = UdpPortalRecvProcessor::new(socket, self.addresses.internal.clone());
+        ProcessorBuilder::new(receiver)
+            .with_address(self.addresses.receiver.clone())
+            .with_outgoing_access_control(AllowOnwardAddress(self.addresses.internal.clone()))
+            .start(ctx)
+            .await?;
+
+        // Respond to Inlet
+        ctx.send_from_address(
+            pong_route.clone(),
+            UdpPortalMessage::Pong,
+            self.addresses.remote.clone(),
+        )
+        .await?;
+
+        debug!("UDP Outlet at: {} sent pong", self.addresses.internal);
+
+        self.remote_route = Some(pong_route);
+        Ok(State::Initialized)
+    }
+
+    /// Send a datagram received from the local UDP socket to the other side of the portal
+    async fn send_to_remote(&self, ctx: &Context, datagram: Vec<u8>) -> Result<()> {
+        let remote_route = match &self.remote_route {
+            Some(remote_route) => remote_route.clone(),
+            None => return Err(TransportError::PortalInvalidState)?,
+        };
+
+        ctx.send_from_address(
+            remote_route,
+            UdpPortalMessage::Payload(datagram),
+            self.addresses.remote.clone(),
+        )
+        .await
+    }
+
+    /// Write a datagram received from the other side of the portal to the local UDP socket
+    async fn send_to_peer(&self, datagram: &[u8]) -> Result<()> {
+        let socket = match &self.socket {
+            Some(socket) => socket,
+            None => return Err(TransportError::PortalInvalidState)?,
+        };
+
+        let result = match self.portal_type {
+            // the Inlet socket is shared by all the clients
+            PortalType::Inlet => socket.send_to(datagram, self.peer).await,
+            PortalType::Outlet => socket.send(datagram).await,
+        };
+        if let Err(err) = result {
+            // datagrams can be lost, the session is kept until it is idle
+            warn!(
+                "Failed to send a datagram to peer {} with error: {}",
+                self.peer, err
+            );
+        }
+
+        Ok(())
+    }
+
+    async fn handle_check_idle(&mut self, ctx: &Context) -> Result<()> {
+        let idle = self.last_activity.elapsed();
+        if idle >= self.idle_timeout {
+            info!(
+                "UDP {:?} at: {} is idle, closing the session",
+                self.portal_type.str(),
+                self.addresses.internal
+            );
+            self.start_disconnection(ctx, true).await
+        } else {
+            self.idle_timer.schedule(self.idle_timeout - idle).await
+        }
+    }
+
+    /// Stop the session, and notify the other side if it didn't initiate the disconnection
+    async fn start_disconnection(&mut self, ctx: &Context, notify_remote: bool) -> Result<()> {
+        self.is_disconnecting = true;
+        self.idle_timer.cancel();
+
+        if notify_remote {
+            if let Some(remote_route) = self.remote_route.take() {
+                if let Err(err) = ctx
+                    .send_from_address(
+                        remote_route,
+                        UdpPortalMessage::Disconnect,
+                        self.addresses.remote.clone(),
+                    )
+                    .await
+                {
+                    warn!("Error notifying the other side of the UDP portal: {}", err);
+                }
+            }
+        }
+
+        ctx.stop_worker(self.addresses.internal.clone()).await
+    }
+}
+
+#[async_trait]
+impl Worker for UdpPortalWorker {
+    type Context = Context;
+    type Message = Any;
+
+    async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        let state = self.clone_state();
+
+        match state {
+            State::SendPing { ping_route } => {
+                self.state = self.handle_send_ping(ctx, ping_route).await?;
+            }
+            State::SendPong { pong_route } => {
+                self.state = self.handle_send_pong(ctx, pong_route).await?;
+            }
+            State::ReceivePong | State::Initialized => {
+                return Err(TransportError::PortalInvalidState)?
+            }
+        }
+
+        self.idle_timer.schedule(self.idle_timeout).await?;
+
+        Ok(())
+    }
+
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        self.idle_timer.cancel();
+
+        if let Some(inlet_sessions) = &self.inlet_sessions {
+            inlet_sessions.lock().unwrap().remove(&self.peer);
+        }
+
+        if let PortalType::Outlet = self.portal_type {
+            let _ = ctx.stop_processor(self.addresses.receiver.clone()).await;
+        }
+
+        Ok(())
+    }
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        if self.is_disconnecting {
+            return Ok(());
+        }
+
+        // Remove our own address from the route so the other end
+        // knows what to do with the incoming message
+        let mut onward_route = msg.onward_route();
+        let recipient = onward_route.step()?;
+
+        let return_route = msg.return_route();
+
+        if onward_route.next().is_ok() {
+            return Err(TransportError::UnknownRoute)?;
+        }
+
+        if recipient == self.addresses.internal {
+            match UdpPortalInternalMessage::decode(msg.payload())? {
+                UdpPortalInternalMessage::Datagram(datagram) => {
+                    trace!(
+                        "UDP {:?} at: {} received a local datagram",
+                        self.portal_type.str(),
+                        self.addresses.internal
+                    );
+                    self.last_activity = Instant::now();
+
+                    match self.state {
+                        State::Initialized => self.send_to_remote(ctx, datagram).await?,
+                        _ if self.pending_datagrams.len() < MAX_PENDING_DATAGRAMS => {
+                            self.pending_datagrams.push_back(datagram)
+                        }
+                        _ => warn!(
+                            "UDP {:?} at: {} dropped a datagram while waiting for the outlet",
+                            self.portal_type.str(),
+                            self.addresses.internal
+                        ),
+                    }
+                }
+                UdpPortalInternalMessage::CheckIdle => self.handle_check_idle(ctx).await?,
+            }
+
+            return Ok(());
+        }
+
+        let state = self.clone_state();
+
+        match (state, UdpPortalMessage::decode(msg.payload())?) {
+            (State::ReceivePong, UdpPortalMessage::Pong) => {
+                debug!("UDP Inlet at: {} received pong", self.addresses.internal);
+
+                self.remote_route = Some(return_route);
+                self.state = State::Initialized;
+
+                while let Some(datagram) = self.pending_datagrams.pop_front() {
+                    self.send_to_remote(ctx, datagram).await?;
+                }
+            }
+            (State::Initialized, UdpPortalMessage::Payload(datagram)) => {
+                trace!(
+                    "UDP {:?} at: {} received a remote datagram",
+                    self.portal_type.str(),
+                    self.addresses.internal
+                );
+                self.last_activity = Instant::now();
+                self.send_to_peer(&datagram).await?;
+            }
+            (State::Initialized, UdpPortalMessage::Disconnect) => {
+                self.start_disconnection(ctx, false).await?;
+            }
+            (State::ReceivePong | State::Initialized, _) => {
+                return Err(TransportError::Protocol)?;
+            }
+            (State::SendPing { .. } | State::SendPong { .. }, _) => {
+                return Err(TransportError::PortalInvalidState)?;
+            }
+        }
+
+        Ok(())
+    }
+}
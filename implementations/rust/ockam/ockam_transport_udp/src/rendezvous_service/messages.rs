@@ -1,4 +1,4 @@
-use ockam_core::{Message, Result, Route};
+use ockam_core::{Address, Message, Result, Route};
 use serde::{Deserialize, Serialize};
 
 // TODO: Change this Request/Response protocol to use CBOR encoding for messages.
@@ -20,6 +20,9 @@ pub enum RendezvousRequest {
     },
     /// Ping service to see if it is reachable and working.
     Ping,
+    /// Query the public UDP address from which the service
+    /// receives the datagrams of the sending node.
+    GetMyAddress,
 }
 
 /// Response type for UDP Hole Punching Rendezvous service
@@ -27,4 +30,5 @@ pub enum RendezvousRequest {
 pub enum RendezvousResponse {
     Query(Result<Route>),
     Pong,
+    GetMyAddress(Result<Address>),
 }
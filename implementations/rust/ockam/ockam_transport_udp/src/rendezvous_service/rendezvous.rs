@@ -96,6 +96,18 @@ impl RendezvousWorker {
         }
     }
 
+    // Handle GetMyAddress request
+    fn handle_get_my_address(return_route: &Route) -> Result<Address> {
+        match Self::parse_route(return_route).next() {
+            Ok(address) => Ok(address.clone()),
+            Err(_) => Err(Error::new(
+                Origin::Other,
+                Kind::NotFound,
+                "the request was not received over UDP",
+            )),
+        }
+    }
+
     // Handle Query request
     fn handle_query(&self, puncher_name: &String) -> Result<Route> {
         match self.map.get(puncher_name) {
@@ -133,6 +145,11 @@ impl Worker for RendezvousWorker {
             RendezvousRequest::Ping => {
                 ctx.send(return_route, RendezvousResponse::Pong).await?;
             }
+            RendezvousRequest::GetMyAddress => {
+                let res = Self::handle_get_my_address(&return_route);
+                ctx.send(return_route, RendezvousResponse::GetMyAddress(res))
+                    .await?;
+            }
         }
         trace!("Map: {:?}", self.map);
         Ok(())
@@ -145,7 +162,7 @@ mod tests {
     use crate::rendezvous_service::{RendezvousRequest, RendezvousResponse};
     use crate::{UdpRendezvousService, UdpTransport, UDP};
     use ockam_core::errcode::Origin;
-    use ockam_core::{route, Error, Result, Route, Routed, TransportType, Worker};
+    use ockam_core::{route, Address, Error, Result, Route, Routed, TransportType, Worker};
     use ockam_node::Context;
     use std::net::SocketAddr;
     use tokio::net::UdpSocket;
@@ -214,6 +231,25 @@ mod tests {
         Ok(())
     }
 
+    #[ockam_macros::test]
+    async fn get_my_address(ctx: &mut Context) -> Result<()> {
+        let (rendezvous_route, send_addr) = test_setup(ctx).await?;
+
+        let res: RendezvousResponse = ctx
+            .send_and_receive(rendezvous_route, RendezvousRequest::GetMyAddress)
+            .await?;
+        match res {
+            RendezvousResponse::GetMyAddress(r) => {
+                assert_eq!(r?, Address::new(UDP, send_addr.to_string()))
+            }
+            r => panic!("Unexpected response: {:?}", r),
+        }
+
+        // Shutdown
+        ctx.stop().await?;
+        Ok(())
+    }
+
     #[ockam_macros::test]
     async fn ping(ctx: &mut Context) -> Result<()> {
         let (rendezvous_route, _) = test_setup(ctx).await?;
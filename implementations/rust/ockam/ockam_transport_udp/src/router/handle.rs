@@ -1,6 +1,7 @@
 use crate::router::messages::{UdpRouterRequest, UdpRouterResponse};
 use ockam_core::{Address, AllowAll, Result};
 use ockam_node::Context;
+use ockam_transport_core::TransportError;
 use std::net::SocketAddr;
 
 /// A handle to connect to a UdpRouter
@@ -33,10 +34,32 @@ impl UdpRouterHandle {
     /// so the local node can act as a server to other nodes
     pub async fn listen(&self, local_addr: SocketAddr) -> Result<()> {
         let msg = UdpRouterRequest::Listen { local_addr };
-        let UdpRouterResponse::Listen(res) = self
+        match self
             .ctx
             .send_and_receive(self.api_addr.clone(), msg)
-            .await?;
-        res
+            .await?
+        {
+            UdpRouterResponse::Listen(res) => res,
+            _ => Err(TransportError::Protocol)?,
+        }
+    }
+
+    /// Request router to create a local worker for a remote UDP peer
+    /// and return its address
+    pub async fn connect(&self, peer_addr: String) -> Result<Address> {
+        let msg = UdpRouterRequest::Connect { peer_addr };
+        match self
+            .ctx
+            .send_and_receive(self.api_addr.clone(), msg)
+            .await?
+        {
+            UdpRouterResponse::Connect(res) => res,
+            _ => Err(TransportError::Protocol)?,
+        }
+    }
+
+    /// Stop a worker created for a remote UDP peer
+    pub async fn disconnect(&self, address: Address) -> Result<()> {
+        self.ctx.stop_worker(address).await
     }
 }
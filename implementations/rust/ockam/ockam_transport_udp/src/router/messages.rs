@@ -1,4 +1,4 @@
-use ockam_core::{Message, Result};
+use ockam_core::{Address, Message, Result};
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 
@@ -7,9 +7,13 @@ pub enum UdpRouterRequest {
     /// Listen on a local UDP port so the local node can
     /// act as a server to other nodes
     Listen { local_addr: SocketAddr },
+    /// Create a local worker sending messages to a remote
+    /// UDP peer from the 'client' socket
+    Connect { peer_addr: String },
 }
 
 #[derive(Serialize, Deserialize, Debug, Message)]
 pub enum UdpRouterResponse {
     Listen(Result<()>),
+    Connect(Result<Address>),
 }
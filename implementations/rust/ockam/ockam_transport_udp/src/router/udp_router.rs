@@ -1,14 +1,14 @@
 use crate::router::messages::{UdpRouterRequest, UdpRouterResponse};
 use crate::router::UdpRouterHandle;
-use crate::workers::{TransportMessageCodec, UdpListenProcessor, UdpSendWorker};
+use crate::workers::{TransportMessageCodec, UdpListenProcessor, UdpPeerWorker, UdpSendWorker};
 use futures_util::StreamExt;
 use ockam_core::{
-    async_trait, Address, AllowAll, Any, Decodable, DenyAll, LocalMessage, Mailbox, Mailboxes,
-    Result, Routed, Worker,
+    async_trait, Address, AllowAll, AllowOnwardAddress, Any, Decodable, DenyAll, LocalMessage,
+    Mailbox, Mailboxes, Result, Routed, Worker,
 };
 use ockam_node::{Context, WorkerBuilder};
 use ockam_transport_core::TransportError;
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, ToSocketAddrs};
 use std::sync::Arc;
 use tokio::net::UdpSocket;
 use tokio_util::udp::UdpFramed;
@@ -132,6 +132,37 @@ impl UdpRouter {
 
         Ok(sender_addr)
     }
+
+    /// Create a worker sending messages to the given peer from the 'client' socket.
+    ///
+    /// Returns the address of the created worker.
+    async fn create_peer_worker(&self, peer_addr: String) -> Result<Address> {
+        // This transport only supports IPv4
+        let is_ipv4 = peer_addr
+            .to_socket_addrs()
+            .map_err(|_| TransportError::InvalidAddress)?
+            .any(|addr| addr.is_ipv4() && addr.port() != 0);
+        if !is_ipv4 {
+            error!(peer_addr = %peer_addr, "No IPv4 address resolved for peer");
+            return Err(TransportError::InvalidAddress)?;
+        }
+
+        let addr = Address::random_tagged("UdpPeerWorker");
+        let worker = UdpPeerWorker::new(
+            self.client_sender.clone(),
+            Address::new(crate::UDP, peer_addr),
+        );
+        // Any local entity can send messages to the peer, like with the router,
+        // but the worker can only pass them on to the 'client' sender
+        WorkerBuilder::new(worker)
+            .with_address(addr.clone())
+            .with_incoming_access_control(AllowAll)
+            .with_outgoing_access_control(AllowOnwardAddress(self.client_sender.clone()))
+            .start(&self.ctx)
+            .await?;
+
+        Ok(addr)
+    }
 }
 
 #[async_trait]
@@ -168,6 +199,11 @@ impl Worker for UdpRouter {
                     ctx.send_from_address(return_route, UdpRouterResponse::Listen(res), msg_addr)
                         .await?;
                 }
+                UdpRouterRequest::Connect { peer_addr } => {
+                    let res = self.create_peer_worker(peer_addr).await;
+                    ctx.send_from_address(return_route, UdpRouterResponse::Connect(res), msg_addr)
+                        .await?;
+                }
             };
         } else {
             return Err(TransportError::Protocol)?;
@@ -1,22 +1,37 @@
+use crate::portal::{UdpInletProcessor, UdpOutletListenWorker};
 use crate::router::{UdpRouter, UdpRouterHandle};
-use ockam_core::{async_trait, Result};
+use crate::{UdpInletOptions, UdpOutletOptions, UDP};
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{async_trait, Address, AsyncTryClone, Error, Result, Route, TransportType};
 use ockam_node::{Context, HasContext};
-use ockam_transport_core::TransportError;
+use ockam_transport_core::{Transport, TransportError};
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::sync::Arc;
 
 /// High level management interface for UDP transport
 ///
 /// A node will have, at most, one UDP transport running.
 ///
 /// This transport only supports IPv4.
+#[derive(Clone)]
 pub struct UdpTransport {
-    router_handle: UdpRouterHandle,
+    ctx: Arc<Context>,
+    router_handle: Arc<UdpRouterHandle>,
 }
 
 impl UdpTransport {
     /// Create a new UDP transport for the current node
     pub async fn create(ctx: &Context) -> Result<UdpTransport> {
         let router_handle = UdpRouter::register(ctx).await?;
-        Ok(Self { router_handle })
+        let udp = Self {
+            ctx: Arc::new(ctx.async_try_clone().await?),
+            router_handle: Arc::new(router_handle),
+        };
+        // make the UDP transport available in the list of supported transports for
+        // later address resolution when socket addresses will need to be instantiated as UDP
+        // worker addresses
+        ctx.register_transport(Arc::new(udp.clone()));
+        Ok(udp)
     }
 
     /// Start listening to incoming datagrams on a specified local address
@@ -27,6 +42,114 @@ impl UdpTransport {
             .map_err(|_| TransportError::InvalidAddress)?;
         self.router_handle.listen(bind_addr).await
     }
+
+    /// Create a local worker sending messages to a remote UDP peer
+    /// and return its address
+    pub async fn connect<S: AsRef<str>>(&self, peer: S) -> Result<Address> {
+        self.router_handle.connect(peer.as_ref().to_string()).await
+    }
+
+    /// Stop a worker created with [`UdpTransport::connect`]
+    pub async fn disconnect(&self, address: impl Into<Address>) -> Result<()> {
+        self.router_handle.disconnect(address.into()).await
+    }
+
+    /// Create a UDP Inlet that receives datagrams on bind_addr and forwards them to the
+    /// Outlet using outlet_route. Each UDP client gets its own session with the Outlet,
+    /// and the datagrams sent back by the Outlet are sent to that client.
+    /// Each datagram is sent over the route as a single message, so that datagram
+    /// boundaries are preserved.
+    ///
+    /// ```rust
+    /// use ockam_transport_udp::{UdpInletOptions, UdpTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::{Result, route};
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let udp = UdpTransport::create(&ctx).await?;
+    /// let (_, inlet) = udp
+    ///     .create_inlet("127.0.0.1:5353", route!["outlet"], UdpInletOptions::new())
+    ///     .await?;
+    /// # udp.stop_inlet(inlet).await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn create_inlet(
+        &self,
+        bind_addr: impl AsRef<str>,
+        outlet_route: impl Into<Route>,
+        options: UdpInletOptions,
+    ) -> Result<(SocketAddr, Address)> {
+        let bind_addr = bind_addr
+            .as_ref()
+            .parse()
+            .map_err(|_| TransportError::InvalidAddress)?;
+        UdpInletProcessor::start(&self.ctx, outlet_route.into(), bind_addr, options).await
+    }
+
+    /// Stop the inlet at addr, and all its sessions
+    pub async fn stop_inlet(&self, addr: impl Into<Address>) -> Result<()> {
+        self.ctx.stop_processor(addr).await
+    }
+
+    /// Create a UDP Outlet Listener at address. For each session started by an Inlet,
+    /// datagrams are sent to peer from a dedicated local port, and the datagrams sent back
+    /// by peer are forwarded to that Inlet.
+    ///
+    /// ```rust
+    /// use ockam_transport_udp::{UdpOutletOptions, UdpTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let udp = UdpTransport::create(&ctx).await?;
+    /// udp.create_outlet("outlet", "127.0.0.1:53", UdpOutletOptions::new())
+    ///     .await?;
+    /// # udp.stop_outlet("outlet").await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn create_outlet(
+        &self,
+        address: impl Into<Address>,
+        peer: impl AsRef<str>,
+        options: UdpOutletOptions,
+    ) -> Result<()> {
+        let peer = peer
+            .as_ref()
+            .to_socket_addrs()
+            .ok()
+            .and_then(|mut addrs| addrs.next())
+            .ok_or(TransportError::InvalidAddress)?;
+        UdpOutletListenWorker::start(&self.ctx, address.into(), peer, options).await
+    }
+
+    /// Stop the outlet listener at addr. The existing sessions are closed when they are idle
+    pub async fn stop_outlet(&self, addr: impl Into<Address>) -> Result<()> {
+        self.ctx.stop_worker(addr).await
+    }
+}
+
+#[async_trait]
+impl Transport for UdpTransport {
+    fn transport_type(&self) -> TransportType {
+        UDP
+    }
+
+    async fn resolve_address(&self, address: Address) -> Result<Address> {
+        if address.transport_type() == UDP {
+            self.connect(address.address()).await
+        } else {
+            Err(Error::new(
+                Origin::Transport,
+                Kind::NotFound,
+                format!(
+                    "this address can not be resolved by a UDP transport {}",
+                    address
+                ),
+            ))
+        }
+    }
+
+    async fn disconnect(&self, address: Address) -> Result<()> {
+        self.disconnect(address).await
+    }
 }
 
 /// This trait adds a `create_udp_transport` method to any struct returning a Context.
@@ -0,0 +1,50 @@
+use ockam_core::{async_trait, Address, Any, Result, Routed, Worker};
+use ockam_node::Context;
+use tracing::trace;
+
+/// A local address for a remote UDP peer
+///
+/// This worker is created when a UDP address is resolved by the
+/// [`UdpTransport`](crate::UdpTransport). Messages sent to this worker are sent
+/// to the peer with the 'client' sender ([`UdpSendWorker`](crate::workers::UdpSendWorker)),
+/// like messages routed to the UDP address by the [`UdpRouter`](crate::router::UdpRouter).
+pub(crate) struct UdpPeerWorker {
+    /// Address of the 'client' sender
+    client_sender: Address,
+    /// UDP address of the peer
+    peer_addr: Address,
+}
+
+impl UdpPeerWorker {
+    /// Create a new `UdpPeerWorker`
+    pub(crate) fn new(client_sender: Address, peer_addr: Address) -> Self {
+        Self {
+            client_sender,
+            peer_addr,
+        }
+    }
+}
+
+#[async_trait]
+impl Worker for UdpPeerWorker {
+    type Message = Any;
+    type Context = Context;
+
+    async fn initialize(&mut self, ctx: &mut Context) -> Result<()> {
+        ctx.set_cluster(crate::CLUSTER_NAME).await
+    }
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        // Replace our address with the client sender and the peer UDP address
+        let mut msg = msg.into_local_message();
+        let onward_route = &mut msg.transport_mut().onward_route;
+        onward_route.step()?;
+        onward_route
+            .modify()
+            .prepend(self.peer_addr.clone())
+            .prepend(self.client_sender.clone());
+
+        trace!("Sending message to UDP peer {}", self.peer_addr);
+        ctx.forward(msg).await
+    }
+}
@@ -0,0 +1,190 @@
+use ockam::identity::{secure_channels, SecureChannelListenerOptions, SecureChannelOptions};
+use ockam::{TcpConnectionOptions, TcpListenerOptions, TcpTransport};
+use ockam_core::{route, Result};
+use ockam_node::Context;
+use ockam_transport_udp::{UdpInletOptions, UdpOutletOptions, UdpTransport, MAX_DATAGRAM_SIZE};
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::time::timeout;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Start a UDP server which echoes the datagrams it receives
+async fn start_echo_server() -> Result<String> {
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let addr = socket.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        let mut buf = vec![0; 65_535];
+        while let Ok((len, peer)) = socket.recv_from(&mut buf).await {
+            let _ = socket.send_to(&buf[..len], peer).await;
+        }
+    });
+
+    Ok(addr.to_string())
+}
+
+async fn receive(socket: &UdpSocket) -> Vec<u8> {
+    let mut buf = vec![0; 65_535];
+    let len = timeout(TIMEOUT, socket.recv(&mut buf))
+        .await
+        .expect("timed out waiting for a datagram")
+        .unwrap();
+    buf.truncate(len);
+    buf
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn udp_portal__datagrams__should_preserve_boundaries(ctx: &mut Context) -> Result<()> {
+    let echo_server = start_echo_server().await?;
+
+    let udp = UdpTransport::create(ctx).await?;
+    udp.create_outlet("outlet", echo_server, UdpOutletOptions::new())
+        .await?;
+    let (inlet_addr, _) = udp
+        .create_inlet("127.0.0.1:0", route!["outlet"], UdpInletOptions::new())
+        .await?;
+
+    let client = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    client.connect(inlet_addr).await.unwrap();
+
+    // datagrams are neither merged nor split by the portal
+    let datagrams = [vec![1; 10], vec![2; 1500], vec![3; 20_000]];
+    for datagram in &datagrams {
+        client.send(datagram).await.unwrap();
+        assert_eq!(&receive(&client).await, datagram);
+    }
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn udp_portal__several_clients__should_get_their_own_replies(
+    ctx: &mut Context,
+) -> Result<()> {
+    let echo_server = start_echo_server().await?;
+
+    let udp = UdpTransport::create(ctx).await?;
+    udp.create_outlet("outlet", echo_server, UdpOutletOptions::new())
+        .await?;
+    let (inlet_addr, _) = udp
+        .create_inlet("127.0.0.1:0", route!["outlet"], UdpInletOptions::new())
+        .await?;
+
+    let client1 = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    client1.connect(inlet_addr).await.unwrap();
+    let client2 = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    client2.connect(inlet_addr).await.unwrap();
+
+    client1.send(b"client 1").await.unwrap();
+    client2.send(b"client 2").await.unwrap();
+
+    assert_eq!(receive(&client1).await, b"client 1");
+    assert_eq!(receive(&client2).await, b"client 2");
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn udp_portal__max_size_datagram__should_go_through_a_secure_channel(
+    ctx: &mut Context,
+) -> Result<()> {
+    let echo_server = start_echo_server().await?;
+
+    // The outlet is reached through a secure channel over TCP
+    let tcp_listener_options = TcpListenerOptions::new();
+    let secure_channel_listener_options = SecureChannelListenerOptions::new()
+        .as_consumer(&tcp_listener_options.spawner_flow_control_id());
+    let outlet_options = UdpOutletOptions::new()
+        .as_consumer(&secure_channel_listener_options.spawner_flow_control_id());
+
+    let secure_channels = secure_channels().await?;
+    let identities_creation = secure_channels.identities().identities_creation();
+    let outlet_identity = identities_creation.create_identity().await?;
+    let inlet_identity = identities_creation.create_identity().await?;
+    secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            &outlet_identity,
+            "secure_channel_listener",
+            secure_channel_listener_options,
+        )
+        .await?;
+
+    let tcp = TcpTransport::create(ctx).await?;
+    let listener = tcp.listen("127.0.0.1:0", tcp_listener_options).await?;
+
+    let udp = UdpTransport::create(ctx).await?;
+    udp.create_outlet("outlet", echo_server, outlet_options)
+        .await?;
+
+    let connection = tcp
+        .connect(listener.socket_string(), TcpConnectionOptions::new())
+        .await?;
+    let channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            &inlet_identity,
+            route![connection, "secure_channel_listener"],
+            SecureChannelOptions::new(),
+        )
+        .await?;
+    let (inlet_addr, _) = udp
+        .create_inlet(
+            "127.0.0.1:0",
+            route![channel, "outlet"],
+            UdpInletOptions::new(),
+        )
+        .await?;
+
+    let client = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    client.connect(inlet_addr).await.unwrap();
+
+    let datagram = vec![1; MAX_DATAGRAM_SIZE];
+    client.send(&datagram).await.unwrap();
+    assert_eq!(receive(&client).await, datagram);
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn udp_portal__oversized_datagram__should_be_dropped(ctx: &mut Context) -> Result<()> {
+    let echo_server = start_echo_server().await?;
+
+    let udp = UdpTransport::create(ctx).await?;
+    udp.create_outlet("outlet", echo_server, UdpOutletOptions::new())
+        .await?;
+    let (inlet_addr, _) = udp
+        .create_inlet("127.0.0.1:0", route!["outlet"], UdpInletOptions::new())
+        .await?;
+
+    let client = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    client.connect(inlet_addr).await.unwrap();
+
+    // the oversized datagram is dropped, and the following datagrams are still relayed
+    client.send(&vec![1; MAX_DATAGRAM_SIZE + 1]).await.unwrap();
+    client.send(b"after").await.unwrap();
+    assert_eq!(receive(&client).await, b"after");
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
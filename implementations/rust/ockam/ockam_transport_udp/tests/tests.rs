@@ -1,7 +1,7 @@
 use ockam_core::compat::rand::{self, Rng};
 use ockam_core::{route, Address, AllowAll, Result, Routed, Worker};
 use ockam_node::{Context, MessageReceiveOptions, MessageSendReceiveOptions};
-use ockam_transport_udp::{UdpTransport, UDP};
+use ockam_transport_udp::{UdpHolePuncher, UdpRendezvousService, UdpTransport, UDP};
 use std::net::SocketAddr;
 use std::time::Duration;
 use tracing::{debug, error, trace};
@@ -192,6 +192,88 @@ async fn send_receive(ctx: &mut Context) -> Result<()> {
     Ok(())
 }
 
+/// UDP addresses in a route can be resolved to local workers sending
+/// messages to the peer from the 'client' UDP port.
+#[ockam_macros::test]
+async fn resolve_udp_address(ctx: &mut Context) -> Result<()> {
+    // Find an available port
+    let bind_addr = *utils::available_local_ports(1).await?.first().unwrap();
+
+    // Transport
+    let transport = UdpTransport::create(ctx).await?;
+    assert!(ctx.is_transport_registered(UDP));
+
+    // Listener
+    ctx.start_worker("echoer", Echoer::new()).await?;
+    transport.listen(bind_addr.to_string()).await?;
+
+    // The UDP address is replaced with a local address
+    let r = ctx
+        .resolve_transport_route(route![(UDP, bind_addr.to_string()), "echoer"])
+        .await?;
+    let peer = r.next()?.clone();
+    assert!(peer.is_local());
+
+    let msg = String::from("Hola");
+    let reply = ctx
+        .send_and_receive_extended::<String>(
+            r,
+            msg.clone(),
+            MessageSendReceiveOptions::new().with_timeout(TIMEOUT),
+        )
+        .await?
+        .body();
+    assert_eq!(reply, msg);
+
+    // Invalid addresses are not resolved
+    let res = ctx
+        .resolve_transport_route(route![(UDP, "192.168.1.10:0"), "echoer"])
+        .await;
+    assert!(res.is_err());
+
+    transport.disconnect(peer).await?;
+
+    ctx.stop().await?;
+    Ok(())
+}
+
+/// Two punchers exchange their public routes through a coordination route,
+/// which would usually go through a relay, and open a hole to each other.
+#[ockam_macros::test]
+async fn hole_punching_via_coordination_route(ctx: &mut Context) -> Result<()> {
+    // Find an available port
+    let bind_addr = *utils::available_local_ports(1).await?.first().unwrap();
+
+    // Transport and Rendezvous service
+    let transport = UdpTransport::create(ctx).await?;
+    UdpRendezvousService::start(ctx, "rendezvous").await?;
+    transport.listen(bind_addr.to_string()).await?;
+    let rendezvous_route = route![(UDP, bind_addr.to_string()), "rendezvous"];
+
+    // The responder creates a puncher for each offer it receives
+    ctx.start_worker("echoer", Echoer::new()).await?;
+    UdpHolePuncher::start_responder(ctx, "responder", rendezvous_route.clone()).await?;
+
+    let mut puncher =
+        UdpHolePuncher::create_via_relay(ctx, rendezvous_route, route!["responder"]).await?;
+    puncher.wait_for_hole_open().await?;
+
+    // Messages go through the hole
+    let msg = String::from("Hola");
+    let reply = ctx
+        .send_and_receive_extended::<String>(
+            route![puncher.address(), "echoer"],
+            msg.clone(),
+            MessageSendReceiveOptions::new().with_timeout(TIMEOUT),
+        )
+        .await?
+        .body();
+    assert_eq!(reply, msg);
+
+    ctx.stop().await?;
+    Ok(())
+}
+
 pub struct Echoer {
     prev_src_addr: Option<String>,
 }
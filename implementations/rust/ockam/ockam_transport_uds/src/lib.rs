@@ -20,7 +20,7 @@ use std::os::unix::net::SocketAddr;
 use ockam_core::{Address, Result, TransportType};
 use ockam_transport_core::TransportError;
 
-// TODO: Should have documentation or enum for this
+/// Transport type for UDS addresses
 pub const UDS: TransportType = TransportType::new(5);
 
 pub const CLUSTER_NAME: &str = "_internals.transport.uds";
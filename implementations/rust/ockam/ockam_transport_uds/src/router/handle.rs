@@ -67,9 +67,19 @@ impl UdsRouterHandle {
 
 impl UdsRouterHandle {
     /// Bind an incoming connection listener for this router
-    pub async fn bind(&self, addr: impl Into<SocketAddr>) -> Result<SocketAddr> {
+    pub async fn bind(
+        &self,
+        addr: impl Into<SocketAddr>,
+        permissions: Option<u32>,
+    ) -> Result<SocketAddr> {
         let socket_addr = addr.into();
-        UdsListenProcessor::start(&self.ctx, self.async_try_clone().await?, socket_addr).await
+        UdsListenProcessor::start(
+            &self.ctx,
+            self.async_try_clone().await?,
+            socket_addr,
+            permissions,
+        )
+        .await
     }
 
     /// Establish an outgoing UDS connection on an existing transport
@@ -88,6 +88,11 @@ impl UdsRouter {
     async fn handle_connect(&mut self, peer: String) -> Result<Address> {
         let (peer_addr, pathnames) = UdsRouterHandle::resolve_peer(peer)?;
 
+        // Reuse the existing connection to this peer
+        if let Some(self_addr) = self.map.get(&address_from_socket_addr(&peer_addr)?) {
+            return Ok(self_addr.clone());
+        }
+
         let router_handle = self.create_self_handle().await?;
         let pair =
             UdsSendWorker::start_pair(&self.ctx, router_handle, None, peer_addr, pathnames.clone())
@@ -140,16 +145,10 @@ impl UdsRouter {
             error!("UDS registration request failed due to an invalid address list. Please provide at least one valid Address.");
         }
 
-        let duplicate_addrs: Vec<String> = self
-            .map
+        let duplicate_addrs: Vec<String> = accepts
             .iter()
-            .filter_map(|(addr, _)| {
-                if self.map.contains_key(addr) {
-                    Some(addr.to_string())
-                } else {
-                    None
-                }
-            })
+            .filter(|addr| self.map.contains_key(addr))
+            .map(|addr| addr.to_string())
             .collect();
 
         if !duplicate_addrs.is_empty() {
@@ -1,11 +1,15 @@
 use std::os::unix::net::SocketAddr;
 
-use ockam_core::{async_trait, Address, AsyncTryClone, Result};
+use ockam_core::compat::sync::Arc;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{async_trait, Address, Error, Result, TransportType};
 use ockam_node::{Context, HasContext};
+use ockam_transport_core::Transport;
 
 use crate::{
     parse_socket_addr,
     router::{UdsRouter, UdsRouterHandle},
+    UDS,
 };
 
 /// High level management interface for UDS transports
@@ -46,20 +50,23 @@ use crate::{
 /// uds.listen("/tmp/socket-two").await?; // Listen on `/tmp/socket-two`
 /// # Ok(()) }
 /// ```
-#[derive(AsyncTryClone)]
-#[async_try_clone(crate = "ockam_core")]
+#[derive(Clone)]
 pub struct UdsTransport {
-    router_handle: UdsRouterHandle,
+    router_handle: Arc<UdsRouterHandle>,
 }
 
 impl UdsTransport {
     /// Creates a a UDS Router and registers it with the given node [`Context`]
     pub async fn create(ctx: &Context) -> Result<Self> {
         let router = UdsRouter::register(ctx).await?;
-
-        Ok(Self {
-            router_handle: router,
-        })
+        let uds = Self {
+            router_handle: Arc::new(router),
+        };
+        // make the UDS transport available in the list of supported transports for
+        // later address resolution when socket paths will need to be instantiated as UDS
+        // worker addresses
+        ctx.register_transport(Arc::new(uds.clone()));
+        Ok(uds)
     }
 
     /// Connects the [`UdsTransport`] to the given socket peer.
@@ -107,7 +114,55 @@ impl UdsTransport {
     /// ```
     pub async fn listen<S: AsRef<str>>(&self, bind_addr: S) -> Result<SocketAddr> {
         let sock_addr = parse_socket_addr(bind_addr.as_ref())?;
-        self.router_handle.bind(sock_addr).await
+        self.router_handle.bind(sock_addr, None).await
+    }
+
+    /// Binds the [`UdsTransport`] to the given socket, and sets the unix permissions of
+    /// the socket file, so that only the users and groups allowed to write to it can connect.
+    ///
+    /// ```rust
+    /// use ockam_transport_uds::UdsTransport;
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let uds = UdsTransport::create(&ctx).await?;
+    /// uds.listen_with_permissions("/tmp/socket-name", 0o600).await?; // Only the owner can connect
+    /// # Ok(()) }
+    /// ```
+    pub async fn listen_with_permissions<S: AsRef<str>>(
+        &self,
+        bind_addr: S,
+        mode: u32,
+    ) -> Result<SocketAddr> {
+        let sock_addr = parse_socket_addr(bind_addr.as_ref())?;
+        self.router_handle.bind(sock_addr, Some(mode)).await
+    }
+}
+
+#[async_trait]
+impl Transport for UdsTransport {
+    fn transport_type(&self) -> TransportType {
+        UDS
+    }
+
+    async fn resolve_address(&self, address: Address) -> Result<Address> {
+        if address.transport_type() == UDS {
+            self.connect(address.address()).await
+        } else {
+            Err(Error::new(
+                Origin::Transport,
+                Kind::NotFound,
+                format!(
+                    "this address can not be resolved by a UDS transport {}",
+                    address
+                ),
+            ))
+        }
+    }
+
+    async fn disconnect(&self, address: Address) -> Result<()> {
+        self.router_handle.unregister(address.clone()).await?;
+        self.router_handle.ctx().stop_worker(address).await
     }
 }
 
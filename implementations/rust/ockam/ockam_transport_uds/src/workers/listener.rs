@@ -1,3 +1,5 @@
+use std::fs::{self, Permissions};
+use std::os::unix::fs::PermissionsExt;
 use std::os::unix::net::SocketAddr;
 
 use ockam_core::{
@@ -22,13 +24,15 @@ pub(crate) struct UdsListenProcessor {
 }
 
 impl UdsListenProcessor {
-    /// Binds a UDS socket at the given [`SocketAddr`]
+    /// Binds a UDS socket at the given [`SocketAddr`], and sets the permissions
+    /// of the socket file if they are provided
     ///
     /// Starts a [`Processor`] which listens for incoming connections to accept.
     pub(crate) async fn start(
         ctx: &Context,
         router_handle: UdsRouterHandle,
         addr: SocketAddr,
+        permissions: Option<u32>,
     ) -> Result<SocketAddr> {
         let path = match addr.as_pathname() {
             Some(p) => p,
@@ -40,6 +44,12 @@ impl UdsListenProcessor {
         debug!("Binding UnixListener to {}", path.display());
         let inner = UnixListener::bind(path).map_err(TransportError::from)?;
 
+        // Only the users allowed to write to the socket file can connect to it
+        if let Some(mode) = permissions {
+            fs::set_permissions(path, Permissions::from_mode(mode))
+                .map_err(TransportError::from)?;
+        }
+
         let tokio_sock_addr = inner.local_addr().map_err(TransportError::from)?;
 
         let std_sock_addr = std_socket_addr_from_tokio(&tokio_sock_addr)?;
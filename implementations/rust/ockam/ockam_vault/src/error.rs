@@ -33,6 +33,10 @@ pub enum VaultError {
     InvalidSha256Len,
     /// Invalid Signature Size
     InvalidSignatureSize,
+    /// The passphrase used to unlock a vault is incorrect
+    InvalidPassphrase,
+    /// The vault is encrypted and must be unlocked with a passphrase
+    VaultLocked,
 }
 
 impl ockam_core::compat::error::Error for VaultError {}
@@ -54,6 +58,8 @@ impl core::fmt::Display for VaultError {
             Self::KeyNotFound => write!(f, "key not found"),
             Self::InvalidSha256Len => write!(f, "invalid sha256 len"),
             Self::InvalidSignatureSize => write!(f, "invalid signature len"),
+            Self::InvalidPassphrase => write!(f, "invalid vault passphrase"),
+            Self::VaultLocked => write!(f, "the vault is encrypted and must be unlocked"),
         }
     }
 }
@@ -63,7 +69,11 @@ impl From<VaultError> for Error {
     fn from(err: VaultError) -> Self {
         use VaultError::*;
         let kind = match err {
-            InvalidPublicKey | InvalidKeyType | InvalidHkdfOutputType => Kind::Misuse,
+            InvalidPublicKey
+            | InvalidKeyType
+            | InvalidHkdfOutputType
+            | InvalidPassphrase
+            | VaultLocked => Kind::Misuse,
             UnknownEcdhKeyType => Kind::NotFound,
             _ => Kind::Invalid,
         };
@@ -1,7 +1,7 @@
 use sha2::{Digest, Sha256};
 
 use ockam_core::compat::boxed::Box;
-use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::collections::{BTreeMap, BTreeSet};
 use ockam_core::compat::rand::{thread_rng, RngCore};
 use ockam_core::compat::sync::{Arc, RwLock};
 use ockam_core::compat::vec::{vec, Vec};
@@ -26,6 +26,8 @@ pub struct SoftwareVaultForSecureChannels {
     ephemeral_buffer_secrets: Arc<RwLock<BTreeMap<SecretBufferHandle, BufferSecret>>>,
     ephemeral_aead_secrets: Arc<RwLock<BTreeMap<AeadSecretKeyHandle, AeadSecret>>>,
     ephemeral_x25519_secrets: Arc<RwLock<BTreeMap<X25519SecretKeyHandle, X25519SecretKey>>>,
+    /// Handles of the AEAD secrets which are also stored in the repository
+    persistent_aead_secrets: Arc<RwLock<BTreeSet<AeadSecretKeyHandle>>>,
     static_x25519_secrets: Arc<dyn SecretsRepository>,
 }
 
@@ -36,6 +38,7 @@ impl SoftwareVaultForSecureChannels {
             ephemeral_buffer_secrets: Default::default(),
             ephemeral_aead_secrets: Default::default(),
             ephemeral_x25519_secrets: Default::default(),
+            persistent_aead_secrets: Default::default(),
             static_x25519_secrets: repository,
         }
     }
@@ -201,10 +204,25 @@ impl SoftwareVaultForSecureChannels {
     }
 
     async fn get_aead_secret(&self, handle: &AeadSecretKeyHandle) -> Result<AeadSecret> {
-        match self.ephemeral_aead_secrets.read().unwrap().get(handle) {
-            Some(secret) => Ok(secret.clone()),
-            None => Err(VaultError::KeyNotFound)?,
+        if let Some(secret) = self.ephemeral_aead_secrets.read().unwrap().get(handle) {
+            return Ok(secret.clone());
         }
+
+        // the secret might have been persisted before a restart
+        let secret = self
+            .static_x25519_secrets
+            .get_aead_secret(handle)
+            .await?
+            .ok_or(VaultError::KeyNotFound)?;
+        self.ephemeral_aead_secrets
+            .write()
+            .unwrap()
+            .insert(handle.clone(), secret.clone());
+        self.persistent_aead_secrets
+            .write()
+            .unwrap()
+            .insert(handle.clone());
+        Ok(secret)
     }
 }
 
@@ -398,11 +416,36 @@ impl VaultForSecureChannels for SoftwareVaultForSecureChannels {
     }
 
     async fn delete_aead_secret_key(&self, secret_key_handle: AeadSecretKeyHandle) -> Result<bool> {
-        Ok(self
+        let deleted = self
             .ephemeral_aead_secrets
             .write()
             .unwrap()
             .remove(&secret_key_handle)
-            .is_some())
+            .is_some();
+
+        let is_persistent = self
+            .persistent_aead_secrets
+            .write()
+            .unwrap()
+            .remove(&secret_key_handle);
+        if is_persistent {
+            self.static_x25519_secrets
+                .delete_aead_secret(&secret_key_handle)
+                .await?;
+        }
+
+        Ok(deleted)
+    }
+
+    async fn persist_aead_secret_key(&self, secret_key_handle: &AeadSecretKeyHandle) -> Result<()> {
+        let secret = self.get_aead_secret(secret_key_handle).await?;
+        self.static_x25519_secrets
+            .store_aead_secret(secret_key_handle, secret)
+            .await?;
+        self.persistent_aead_secrets
+            .write()
+            .unwrap()
+            .insert(secret_key_handle.clone());
+        Ok(())
     }
 }
@@ -1,3 +1,5 @@
+#[cfg(feature = "storage")]
+mod secrets_encryption;
 mod secrets_repository;
 #[cfg(feature = "storage")]
 mod secrets_repository_sql;
@@ -0,0 +1,153 @@
+use aes_gcm::aead::{Aead, NewAead, Payload};
+use aes_gcm::Aes256Gcm;
+use argon2::Argon2;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use ockam_core::compat::rand::{thread_rng, RngCore};
+use ockam_core::compat::vec::Vec;
+use ockam_core::Result;
+
+use crate::VaultError;
+
+/// Length of the salt used to derive a key from a passphrase
+const SALT_LENGTH: usize = 16;
+
+/// Length of the keys used to encrypt secrets
+const KEY_LENGTH: usize = 32;
+
+/// Length of an AES-GCM nonce
+const NONCE_LENGTH: usize = 12;
+
+/// Additional data used when encrypting the data encryption key
+const WRAPPED_KEY_AAD: &[u8] = b"ockam_vault_secrets_encryption_key";
+
+/// Key used to encrypt the secrets stored in a vault (data encryption key).
+///
+/// The key is randomly generated when the encryption of a vault is enabled and is stored
+/// in the vault, encrypted with a key derived from a passphrase.
+/// Changing the passphrase then only requires to encrypt this key again.
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
+pub(crate) struct SecretsEncryptionKey([u8; KEY_LENGTH]);
+
+impl SecretsEncryptionKey {
+    /// Generate a new random key
+    pub(crate) fn generate() -> Self {
+        let mut key = [0u8; KEY_LENGTH];
+        thread_rng().fill_bytes(&mut key);
+        Self(key)
+    }
+
+    /// Derive a key from a passphrase with Argon2id
+    fn from_passphrase(passphrase: &str, salt: &[u8]) -> Result<Self> {
+        let mut key = [0u8; KEY_LENGTH];
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|_| VaultError::InvalidPassphrase)?;
+        Ok(Self(key))
+    }
+
+    /// Encrypt some data with AES-256-GCM and return `nonce || ciphertext`.
+    /// The additional data is used to bind the ciphertext to its context (a secret handle for example)
+    pub(crate) fn encrypt(&self, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+        let mut nonce = [0u8; NONCE_LENGTH];
+        thread_rng().fill_bytes(&mut nonce);
+
+        let ciphertext = Aes256Gcm::new((&self.0).into())
+            .encrypt(
+                nonce.as_slice().into(),
+                Payload {
+                    msg: plaintext,
+                    aad,
+                },
+            )
+            .map_err(|_| VaultError::AeadAesGcmEncrypt)?;
+
+        let mut result = nonce.to_vec();
+        result.extend(ciphertext);
+        Ok(result)
+    }
+
+    /// Decrypt some data encrypted with [`SecretsEncryptionKey::encrypt`]
+    pub(crate) fn decrypt(&self, data: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+        if data.len() < NONCE_LENGTH {
+            return Err(VaultError::AeadAesGcmDecrypt)?;
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_LENGTH);
+
+        Ok(Aes256Gcm::new((&self.0).into())
+            .decrypt(
+                nonce.into(),
+                Payload {
+                    msg: ciphertext,
+                    aad,
+                },
+            )
+            .map_err(|_| VaultError::AeadAesGcmDecrypt)?)
+    }
+
+    /// Encrypt this key with a key derived from a passphrase
+    pub(crate) fn wrap(&self, passphrase: &str) -> Result<WrappedSecretsEncryptionKey> {
+        let mut salt = [0u8; SALT_LENGTH];
+        thread_rng().fill_bytes(&mut salt);
+
+        let key_encryption_key = Self::from_passphrase(passphrase, &salt)?;
+        let encrypted_key = key_encryption_key.encrypt(&self.0, WRAPPED_KEY_AAD)?;
+        Ok(WrappedSecretsEncryptionKey {
+            salt: salt.to_vec(),
+            encrypted_key,
+        })
+    }
+}
+
+/// Data encryption key, encrypted with a key derived from a passphrase
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct WrappedSecretsEncryptionKey {
+    pub(crate) salt: Vec<u8>,
+    pub(crate) encrypted_key: Vec<u8>,
+}
+
+impl WrappedSecretsEncryptionKey {
+    /// Decrypt the data encryption key with a passphrase.
+    /// An error is returned if the passphrase is incorrect
+    pub(crate) fn unwrap(&self, passphrase: &str) -> Result<SecretsEncryptionKey> {
+        let key_encryption_key = SecretsEncryptionKey::from_passphrase(passphrase, &self.salt)?;
+        let mut key = key_encryption_key
+            .decrypt(&self.encrypted_key, WRAPPED_KEY_AAD)
+            .map_err(|_| VaultError::InvalidPassphrase)?;
+        let result = key
+            .as_slice()
+            .try_into()
+            .map(SecretsEncryptionKey)
+            .map_err(|_| VaultError::InvalidPassphrase.into());
+        key.zeroize();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_decrypt() -> Result<()> {
+        let key = SecretsEncryptionKey::generate();
+        let encrypted = key.encrypt(b"secret", b"handle")?;
+        assert_eq!(key.decrypt(&encrypted, b"handle")?, b"secret".to_vec());
+
+        // the ciphertext is bound to its additional data
+        assert!(key.decrypt(&encrypted, b"other handle").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_wrap_unwrap() -> Result<()> {
+        let key = SecretsEncryptionKey::generate();
+        let wrapped = key.wrap("passphrase")?;
+
+        let unwrapped = wrapped.unwrap("passphrase")?;
+        assert_eq!(unwrapped.0, key.0);
+
+        assert!(wrapped.unwrap("wrong passphrase").is_err());
+        Ok(())
+    }
+}
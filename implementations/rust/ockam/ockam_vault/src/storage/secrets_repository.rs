@@ -1,10 +1,13 @@
-use crate::{SigningSecret, SigningSecretKeyHandle, X25519SecretKey, X25519SecretKeyHandle};
+use crate::{
+    AeadSecret, AeadSecretKeyHandle, SigningSecret, SigningSecretKeyHandle, X25519SecretKey,
+    X25519SecretKeyHandle,
+};
 use ockam_core::async_trait;
 use ockam_core::compat::boxed::Box;
 use ockam_core::compat::vec::Vec;
 use ockam_core::Result;
 
-/// A secrets repository supports the persistence of signing and X25519 secrets
+/// A secrets repository supports the persistence of signing, X25519 and AEAD secrets
 #[async_trait]
 pub trait SecretsRepository: Send + Sync + 'static {
     /// Store a signing secret
@@ -51,6 +54,19 @@ pub trait SecretsRepository: Send + Sync + 'static {
     /// Get the list of all X25519 secret handles
     async fn get_x25519_secret_handles(&self) -> Result<Vec<X25519SecretKeyHandle>>;
 
+    /// Store an AEAD secret, for example the key of a persistent secure channel
+    async fn store_aead_secret(
+        &self,
+        handle: &AeadSecretKeyHandle,
+        secret: AeadSecret,
+    ) -> Result<()>;
+
+    /// Delete an AEAD secret
+    async fn delete_aead_secret(&self, handle: &AeadSecretKeyHandle) -> Result<bool>;
+
+    /// Get an AEAD secret
+    async fn get_aead_secret(&self, handle: &AeadSecretKeyHandle) -> Result<Option<AeadSecret>>;
+
     /// Delete all secrets
     async fn delete_all(&self) -> Result<()>;
 }
@@ -2,35 +2,234 @@ use sqlx::*;
 use tracing::debug;
 
 use ockam_core::async_trait;
+use ockam_core::compat::sync::{Arc, RwLock};
 use ockam_core::compat::vec::Vec;
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::Result;
 use ockam_node::database::{FromSqlxError, SqlxDatabase, SqlxType, ToSqlxType, ToVoid};
 
+use crate::storage::secrets_encryption::{SecretsEncryptionKey, WrappedSecretsEncryptionKey};
 use crate::storage::secrets_repository::SecretsRepository;
 
 use crate::{
-    ECDSASHA256CurveP256SecretKey, EdDSACurve25519SecretKey, HandleToSecret, SigningSecret,
-    SigningSecretKeyHandle, X25519SecretKey, X25519SecretKeyHandle,
+    AeadSecret, AeadSecretKeyHandle, ECDSASHA256CurveP256SecretKey, EdDSACurve25519SecretKey,
+    HandleToSecret, SigningSecret, SigningSecretKeyHandle, VaultError, X25519SecretKey,
+    X25519SecretKeyHandle, AEAD_SECRET_LENGTH,
 };
 
 /// Implementation of a secrets repository using a SQL database
+///
+/// The secrets can be encrypted with a passphrase. In that case, each secret is encrypted
+/// with a random data encryption key, which is itself stored encrypted with a key derived from
+/// the passphrase. An encrypted database must be unlocked with [`SecretsSqlxDatabase::unlock`]
+/// before its secrets can be used.
 #[derive(Clone)]
 pub struct SecretsSqlxDatabase {
     database: SqlxDatabase,
+    encryption_key: Option<SecretsEncryptionKey>,
+    /// Cached result of [`SecretsSqlxDatabase::is_encrypted`], shared by the clones
+    /// of this repository, so that storing a secret doesn't need an additional query
+    encrypted: Arc<RwLock<Option<bool>>>,
 }
 
 impl SecretsSqlxDatabase {
     /// Create a new database for policies keys
     pub fn new(database: SqlxDatabase) -> Self {
         debug!("create a repository for secrets");
-        Self { database }
+        Self {
+            database,
+            encryption_key: None,
+            encrypted: Arc::new(RwLock::new(None)),
+        }
     }
 
     /// Create a new in-memory database for policies
     pub async fn create() -> Result<Self> {
         Ok(Self::new(SqlxDatabase::in_memory("secrets").await?))
     }
+
+    /// Return true if the secrets are encrypted with a passphrase
+    pub async fn is_encrypted(&self) -> Result<bool> {
+        if let Some(encrypted) = *self.encrypted.read().unwrap() {
+            return Ok(encrypted);
+        }
+        let encrypted = self.get_wrapped_encryption_key().await?.is_some();
+        *self.encrypted.write().unwrap() = Some(encrypted);
+        Ok(encrypted)
+    }
+
+    /// Return a repository which can access the secrets of an encrypted database.
+    /// An error is returned if the passphrase is incorrect
+    pub async fn unlock(self, passphrase: &str) -> Result<Self> {
+        let wrapped_key = self
+            .get_wrapped_encryption_key()
+            .await?
+            .ok_or_else(Self::not_encrypted)?;
+        Ok(Self {
+            encryption_key: Some(wrapped_key.unwrap(passphrase)?),
+            ..self
+        })
+    }
+
+    /// Encrypt all the existing secrets with a passphrase and return a repository
+    /// which encrypts new secrets before storing them
+    pub async fn encrypt(self, passphrase: &str) -> Result<Self> {
+        let encryption_key = SecretsEncryptionKey::generate();
+        let wrapped_key = encryption_key.wrap(passphrase)?;
+
+        let mut transaction = self.database.begin().await.into_core()?;
+        let row: Option<SecretsEncryptionKeyRow> =
+            query_as("SELECT salt, encrypted_key FROM secrets_encryption_key")
+                .fetch_optional(&mut *transaction)
+                .await
+                .into_core()?;
+        if row.is_some() {
+            return Err(ockam_core::Error::new(
+                Origin::Vault,
+                Kind::AlreadyExists,
+                "the vault secrets are already encrypted",
+            ));
+        }
+
+        let signing_secrets: Vec<SigningSecretRow> =
+            query_as("SELECT handle, secret_type, secret FROM signing_secret")
+                .fetch_all(&mut *transaction)
+                .await
+                .into_core()?;
+        for row in signing_secrets {
+            let secret = encryption_key.encrypt(&row.secret, &row.handle)?;
+            query("UPDATE signing_secret SET secret = ? WHERE handle = ?")
+                .bind(secret.to_sql())
+                .bind(row.handle.to_sql())
+                .execute(&mut *transaction)
+                .await
+                .void()?;
+        }
+
+        let x25519_secrets: Vec<X25519SecretRow> =
+            query_as("SELECT handle, secret FROM x25519_secret")
+                .fetch_all(&mut *transaction)
+                .await
+                .into_core()?;
+        for row in x25519_secrets {
+            let secret = encryption_key.encrypt(&row.secret, &row.handle)?;
+            query("UPDATE x25519_secret SET secret = ? WHERE handle = ?")
+                .bind(secret.to_sql())
+                .bind(row.handle.to_sql())
+                .execute(&mut *transaction)
+                .await
+                .void()?;
+        }
+
+        let aead_secrets: Vec<AeadSecretRow> = query_as("SELECT handle, secret FROM aead_secret")
+            .fetch_all(&mut *transaction)
+            .await
+            .into_core()?;
+        for row in aead_secrets {
+            let secret = encryption_key.encrypt(&row.secret, &row.handle)?;
+            query("UPDATE aead_secret SET secret = ? WHERE handle = ?")
+                .bind(secret.to_sql())
+                .bind(row.handle.to_sql())
+                .execute(&mut *transaction)
+                .await
+                .void()?;
+        }
+
+        query("INSERT INTO secrets_encryption_key VALUES (?, ?)")
+            .bind(wrapped_key.salt.to_sql())
+            .bind(wrapped_key.encrypted_key.to_sql())
+            .execute(&mut *transaction)
+            .await
+            .void()?;
+        transaction.commit().await.void()?;
+        *self.encrypted.write().unwrap() = Some(true);
+
+        Ok(Self {
+            encryption_key: Some(encryption_key),
+            ..self
+        })
+    }
+
+    /// Change the passphrase used to encrypt the secrets.
+    /// The secrets themselves don't need to be encrypted again
+    pub async fn change_passphrase(
+        &self,
+        old_passphrase: &str,
+        new_passphrase: &str,
+    ) -> Result<()> {
+        let wrapped_key = self
+            .get_wrapped_encryption_key()
+            .await?
+            .ok_or_else(Self::not_encrypted)?;
+        let new_wrapped_key = wrapped_key.unwrap(old_passphrase)?.wrap(new_passphrase)?;
+
+        query("UPDATE secrets_encryption_key SET salt = ?, encrypted_key = ?")
+            .bind(new_wrapped_key.salt.to_sql())
+            .bind(new_wrapped_key.encrypted_key.to_sql())
+            .execute(&*self.database.pool)
+            .await
+            .void()
+    }
+
+    async fn get_wrapped_encryption_key(&self) -> Result<Option<WrappedSecretsEncryptionKey>> {
+        let row: Option<SecretsEncryptionKeyRow> =
+            query_as("SELECT salt, encrypted_key FROM secrets_encryption_key")
+                .fetch_optional(&*self.database.pool)
+                .await
+                .into_core()?;
+        Ok(row.map(|r| WrappedSecretsEncryptionKey {
+            salt: r.salt,
+            encrypted_key: r.encrypted_key,
+        }))
+    }
+
+    fn not_encrypted() -> ockam_core::Error {
+        ockam_core::Error::new(
+            Origin::Vault,
+            Kind::Misuse,
+            "the vault secrets are not encrypted",
+        )
+    }
+
+    /// Encrypt a secret before storing it, if the database is encrypted.
+    /// The secret is bound to its handle so that encrypted secrets can't be swapped
+    async fn seal(&self, handle: &HandleToSecret, secret: &[u8]) -> Result<Vec<u8>> {
+        match &self.encryption_key {
+            Some(key) => key.encrypt(secret, handle.value()),
+            None if self.is_encrypted().await? => Err(VaultError::VaultLocked)?,
+            None => Ok(secret.to_vec()),
+        }
+    }
+
+    /// Decrypt a stored secret if the database is encrypted
+    fn open(&self, handle: &[u8], secret: Vec<u8>) -> Result<Vec<u8>> {
+        self.open_with_length(handle, secret, 32)
+    }
+
+    /// Decrypt a stored secret if the database is encrypted, given the length of
+    /// the unencrypted secret
+    fn open_with_length(&self, handle: &[u8], secret: Vec<u8>, length: usize) -> Result<Vec<u8>> {
+        match &self.encryption_key {
+            Some(key) => key.decrypt(&secret, handle),
+            // Encrypted secrets also contain a nonce and an authentication tag
+            None if secret.len() != length => Err(VaultError::VaultLocked)?,
+            None => Ok(secret),
+        }
+    }
+
+    fn open_signing_secret_row(&self, row: SigningSecretRow) -> Result<SigningSecretRow> {
+        Ok(SigningSecretRow {
+            secret: self.open(&row.handle, row.secret)?,
+            ..row
+        })
+    }
+
+    fn open_x25519_secret_row(&self, row: X25519SecretRow) -> Result<X25519SecretRow> {
+        Ok(X25519SecretRow {
+            secret: self.open(&row.handle, row.secret)?,
+            ..row
+        })
+    }
 }
 
 const ED_DSA_CURVE_25519: &str = "EdDSACurve25519";
@@ -48,6 +247,11 @@ impl SecretsRepository for SecretsSqlxDatabase {
             SigningSecretKeyHandle::ECDSASHA256CurveP256(_) => EC_DSA_SHA256_CURVE_P256.into(),
         };
 
+        let secret = match &secret {
+            SigningSecret::EdDSACurve25519(k) => self.seal(handle.handle(), k.key()).await?,
+            SigningSecret::ECDSASHA256CurveP256(k) => self.seal(handle.handle(), k.key()).await?,
+        };
+
         let query = query("INSERT OR REPLACE INTO signing_secret VALUES (?, ?, ?)")
             .bind(handle.to_sql())
             .bind(secret_type.to_sql())
@@ -65,7 +269,9 @@ impl SecretsRepository for SecretsSqlxDatabase {
                 .bind(handle.to_sql());
         let row: Option<SigningSecretRow> =
             query1.fetch_optional(&mut *transaction).await.into_core()?;
-        let secret = row.map(|r| r.signing_secret()).transpose()?;
+        let secret = row
+            .map(|r| self.open_signing_secret_row(r)?.signing_secret())
+            .transpose()?;
 
         let result = if let Some(secret) = secret {
             let query = query("DELETE FROM signing_secret WHERE handle = ?").bind(handle.to_sql());
@@ -89,7 +295,9 @@ impl SecretsRepository for SecretsSqlxDatabase {
             .fetch_optional(&*self.database.pool)
             .await
             .into_core()?;
-        Ok(row.map(|r| r.signing_secret()).transpose()?)
+        Ok(row
+            .map(|r| self.open_signing_secret_row(r)?.signing_secret())
+            .transpose()?)
     }
 
     async fn get_signing_secret_handles(&self) -> Result<Vec<SigningSecretKeyHandle>> {
@@ -107,6 +315,8 @@ impl SecretsRepository for SecretsSqlxDatabase {
         handle: &X25519SecretKeyHandle,
         secret: X25519SecretKey,
     ) -> Result<()> {
+        let secret = self.seal(&handle.0, secret.key()).await?;
+
         let query = query("INSERT OR REPLACE INTO x25519_secret VALUES (?, ?)")
             .bind(handle.to_sql())
             .bind(secret.to_sql());
@@ -122,7 +332,9 @@ impl SecretsRepository for SecretsSqlxDatabase {
             .bind(handle.to_sql());
         let row: Option<X25519SecretRow> =
             query1.fetch_optional(&mut *transaction).await.into_core()?;
-        let secret = row.map(|r| r.x25519_secret()).transpose()?;
+        let secret = row
+            .map(|r| self.open_x25519_secret_row(r)?.x25519_secret())
+            .transpose()?;
 
         let result = if let Some(secret) = secret {
             let query = query("DELETE FROM x25519_secret WHERE handle = ?").bind(handle.to_sql());
@@ -145,7 +357,9 @@ impl SecretsRepository for SecretsSqlxDatabase {
             .fetch_optional(&*self.database.pool)
             .await
             .into_core()?;
-        Ok(row.map(|r| r.x25519_secret()).transpose()?)
+        Ok(row
+            .map(|r| self.open_x25519_secret_row(r)?.x25519_secret())
+            .transpose()?)
     }
 
     async fn get_x25519_secret_handles(&self) -> Result<Vec<X25519SecretKeyHandle>> {
@@ -157,6 +371,40 @@ impl SecretsRepository for SecretsSqlxDatabase {
             .collect::<Result<Vec<_>>>()?)
     }
 
+    async fn store_aead_secret(
+        &self,
+        handle: &AeadSecretKeyHandle,
+        secret: AeadSecret,
+    ) -> Result<()> {
+        let secret = self.seal(&handle.0 .0, &secret.0).await?;
+
+        let query = query("INSERT OR REPLACE INTO aead_secret VALUES (?, ?)")
+            .bind(handle.0 .0.to_sql())
+            .bind(secret.to_sql());
+        query.execute(&*self.database.pool).await.void()
+    }
+
+    async fn delete_aead_secret(&self, handle: &AeadSecretKeyHandle) -> Result<bool> {
+        let query = query("DELETE FROM aead_secret WHERE handle = ?").bind(handle.0 .0.to_sql());
+        let result = query.execute(&*self.database.pool).await.into_core()?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn get_aead_secret(&self, handle: &AeadSecretKeyHandle) -> Result<Option<AeadSecret>> {
+        let query = query_as("SELECT handle, secret FROM aead_secret WHERE handle=?")
+            .bind(handle.0 .0.to_sql());
+        let row: Option<AeadSecretRow> = query
+            .fetch_optional(&*self.database.pool)
+            .await
+            .into_core()?;
+        Ok(row
+            .map(|r| {
+                let secret = self.open_with_length(&r.handle, r.secret, AEAD_SECRET_LENGTH)?;
+                AeadSecretRow { secret, ..r }.aead_secret()
+            })
+            .transpose()?)
+    }
+
     async fn delete_all(&self) -> Result<()> {
         let mut transaction = self.database.begin().await.into_core()?;
         let query1 = query("DELETE FROM signing_secret");
@@ -164,6 +412,12 @@ impl SecretsRepository for SecretsSqlxDatabase {
 
         let query2 = query("DELETE FROM x25519_secret");
         query2.execute(&mut *transaction).await.void()?;
+
+        let query3 = query("DELETE FROM aead_secret");
+        query3.execute(&mut *transaction).await.void()?;
+
+        let query4 = query("DELETE FROM secrets_encryption_key");
+        query4.execute(&mut *transaction).await.void()?;
         transaction.commit().await.void()
     }
 }
@@ -249,6 +503,12 @@ impl SigningSecretRow {
     }
 }
 
+#[derive(FromRow)]
+struct SecretsEncryptionKeyRow {
+    salt: Vec<u8>,
+    encrypted_key: Vec<u8>,
+}
+
 #[derive(FromRow)]
 struct X25519SecretRow {
     handle: Vec<u8>,
@@ -274,9 +534,29 @@ impl X25519SecretRow {
     }
 }
 
+#[derive(FromRow)]
+struct AeadSecretRow {
+    handle: Vec<u8>,
+    secret: Vec<u8>,
+}
+
+impl AeadSecretRow {
+    fn aead_secret(&self) -> Result<AeadSecret> {
+        let secret: [u8; AEAD_SECRET_LENGTH] = self.secret.clone().try_into().map_err(|_| {
+            ockam_core::Error::new(
+                Origin::Api,
+                Kind::Serialization,
+                "cannot convert an AEAD secret to a fixed size array",
+            )
+        })?;
+        Ok(AeadSecret(secret))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::Aes256GcmSecretKeyHandle;
 
     use ockam_core::compat::sync::Arc;
 
@@ -344,6 +624,85 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_aead_secrets_repository() -> Result<()> {
+        let repository = create_repository().await?;
+
+        let handle =
+            AeadSecretKeyHandle(Aes256GcmSecretKeyHandle(HandleToSecret::new(vec![1, 2, 3])));
+        let secret = AeadSecret([1; AEAD_SECRET_LENGTH]);
+
+        repository
+            .store_aead_secret(&handle, secret.clone())
+            .await?;
+
+        let result = repository.get_aead_secret(&handle).await?;
+        assert!(result == Some(secret));
+
+        assert!(repository.delete_aead_secret(&handle).await?);
+        assert!(!repository.delete_aead_secret(&handle).await?);
+
+        let result = repository.get_aead_secret(&handle).await?;
+        assert!(result.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_secrets_repository() -> Result<()> {
+        let repository = SecretsSqlxDatabase::create().await?;
+
+        let handle1 = SigningSecretKeyHandle::EdDSACurve25519(HandleToSecret::new(vec![1, 2, 3]));
+        let secret1 = SigningSecret::EdDSACurve25519(EdDSACurve25519SecretKey::new([1; 32]));
+        repository
+            .store_signing_secret(&handle1, secret1.clone())
+            .await?;
+
+        // existing secrets are encrypted and can still be read with an unlocked repository
+        let unencrypted = repository.clone();
+        assert!(!unencrypted.is_encrypted().await?);
+        let repository = repository.encrypt("passphrase").await?;
+        assert!(repository.is_encrypted().await?);
+
+        // the clones of the repository know that new secrets must be encrypted
+        assert!(unencrypted.is_encrypted().await?);
+        assert!(unencrypted
+            .store_signing_secret(&handle1, secret1.clone())
+            .await
+            .is_err());
+        let result = repository.get_signing_secret(&handle1).await?;
+        assert!(result == Some(secret1.clone()));
+
+        let handle2 = X25519SecretKeyHandle(HandleToSecret::new(vec![4, 5, 6]));
+        let secret2 = X25519SecretKey::new([2; 32]);
+        repository
+            .store_x25519_secret(&handle2, secret2.clone())
+            .await?;
+
+        // a locked repository can neither read nor write secrets
+        let locked = SecretsSqlxDatabase::new(repository.database.clone());
+        assert!(locked.get_signing_secret(&handle1).await.is_err());
+        assert!(locked.get_x25519_secret(&handle2).await.is_err());
+        assert!(locked
+            .store_x25519_secret(&handle2, secret2.clone())
+            .await
+            .is_err());
+        assert!(locked.clone().unlock("wrong passphrase").await.is_err());
+
+        // the passphrase can be changed
+        repository
+            .change_passphrase("passphrase", "new passphrase")
+            .await?;
+        assert!(locked.clone().unlock("passphrase").await.is_err());
+        let unlocked = locked.unlock("new passphrase").await?;
+        let result = unlocked.get_signing_secret(&handle1).await?;
+        assert!(result == Some(secret1));
+        let result = unlocked.get_x25519_secret(&handle2).await?;
+        assert!(result == Some(secret2));
+
+        Ok(())
+    }
+
     /// HELPERS
     async fn create_repository() -> Result<Arc<dyn SecretsRepository>> {
         Ok(Arc::new(SecretsSqlxDatabase::create().await?))
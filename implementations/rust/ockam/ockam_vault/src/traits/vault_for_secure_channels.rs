@@ -102,4 +102,8 @@ pub trait VaultForSecureChannels: Send + Sync + 'static {
 
     /// Delete AEAD Key.
     async fn delete_aead_secret_key(&self, secret_key_handle: AeadSecretKeyHandle) -> Result<bool>;
+
+    /// Persist an AEAD Key, so that it can still be used after a restart,
+    /// until it is deleted.
+    async fn persist_aead_secret_key(&self, secret_key_handle: &AeadSecretKeyHandle) -> Result<()>;
 }
@@ -27,6 +27,18 @@ cfg_if! {
         /// Handle to a AEAD Secret Key.
         #[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq)]
         pub struct AeadSecretKeyHandle(pub Aes256GcmSecretKeyHandle);
+
+        impl AeadSecretKeyHandle {
+            /// Create a handle to an AEAD Secret Key.
+            pub fn new(handle: HandleToSecret) -> Self {
+                Self(Aes256GcmSecretKeyHandle(handle))
+            }
+
+            /// Return the underlying handle.
+            pub fn handle(&self) -> &HandleToSecret {
+                &self.0 .0
+            }
+        }
     } else if #[cfg(feature = "OCKAM_XX_25519_AES128_GCM_SHA256")] {
         /// Hash used for Noise handshake.
         pub struct HashOutput(pub Sha256Output);
@@ -44,6 +56,18 @@ cfg_if! {
         /// Handle to a AEAD Secret Key.
         #[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq)]
         pub struct AeadSecretKeyHandle(pub Aes128GcmSecretKeyHandle);
+
+        impl AeadSecretKeyHandle {
+            /// Create a handle to an AEAD Secret Key.
+            pub fn new(handle: HandleToSecret) -> Self {
+                Self(Aes128GcmSecretKeyHandle(handle))
+            }
+
+            /// Return the underlying handle.
+            pub fn handle(&self) -> &HandleToSecret {
+                &self.0 .0
+            }
+        }
     } else if #[cfg(feature = "OCKAM_XX_25519_ChaChaPolyBLAKE2s")] {
         /// Blake2s digest length
         pub const BLAKE2S_LENGTH: usize = 32;
@@ -67,5 +91,17 @@ cfg_if! {
         /// Handle to a AEAD Secret Key.
         #[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq)]
         pub struct AeadSecretKeyHandle(pub Chacha20Poly1305SecretKeyHandle);
+
+        impl AeadSecretKeyHandle {
+            /// Create a handle to an AEAD Secret Key.
+            pub fn new(handle: HandleToSecret) -> Self {
+                Self(Chacha20Poly1305SecretKeyHandle(handle))
+            }
+
+            /// Return the underlying handle.
+            pub fn handle(&self) -> &HandleToSecret {
+                &self.0 .0
+            }
+        }
     }
 }
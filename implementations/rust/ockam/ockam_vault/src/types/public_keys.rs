@@ -1,3 +1,4 @@
+use crate::SigningKeyType;
 use minicbor::{Decode, Encode};
 
 /// X25519 public key length.
@@ -19,6 +20,16 @@ pub enum VerifyingPublicKey {
     #[n(1)] ECDSASHA256CurveP256(#[n(0)] ECDSASHA256CurveP256PublicKey),
 }
 
+impl VerifyingPublicKey {
+    /// [`SigningKeyType`] of the corresponding secret key
+    pub fn key_type(&self) -> SigningKeyType {
+        match self {
+            VerifyingPublicKey::EdDSACurve25519(_) => SigningKeyType::EdDSACurve25519,
+            VerifyingPublicKey::ECDSASHA256CurveP256(_) => SigningKeyType::ECDSASHA256CurveP256,
+        }
+    }
+}
+
 /// A Curve25519 Public Key that is only used for EdDSA signatures.
 ///
 /// - EdDSA Signature as defined [here][1] and [here][2].
@@ -39,10 +39,18 @@ impl SigningSecretKeyHandle {
             SigningSecretKeyHandle::ECDSASHA256CurveP256(handle) => handle,
         }
     }
+
+    /// [`SigningKeyType`] of this key
+    pub fn key_type(&self) -> SigningKeyType {
+        match self {
+            SigningSecretKeyHandle::EdDSACurve25519(_) => SigningKeyType::EdDSACurve25519,
+            SigningSecretKeyHandle::ECDSASHA256CurveP256(_) => SigningKeyType::ECDSASHA256CurveP256,
+        }
+    }
 }
 
 /// Key type for Signing. See [`super::signatures::Signature`].
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum SigningKeyType {
     /// See [`super::signatures::EdDSACurve25519Signature`]
     EdDSACurve25519,
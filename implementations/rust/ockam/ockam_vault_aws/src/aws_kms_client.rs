@@ -55,8 +55,9 @@ impl AwsKmsConfig {
     }
 
     /// Create multi-region keys.
-    pub fn multi_region(mut self, val: bool) {
+    pub fn multi_region(mut self, val: bool) -> Self {
         self.multi_region = val;
+        self
     }
 
     /// Configure initial key discovery
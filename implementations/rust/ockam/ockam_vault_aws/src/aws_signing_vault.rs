@@ -18,7 +18,8 @@ pub struct AwsSigningVault {
     client: Arc<dyn KmsClient + Send + Sync>,
     // Store mapping from PublicKey to KeyId in memory
     // This is fetched at the Vault initialization
-    // and is updated locally during add/delete operations
+    // and is updated locally during add/delete operations,
+    // or when the public key of an unknown key is requested
     // WARNING: The assumption is that there is no concurrent access to the same keys from
     // different places.
     keys: Arc<RwLock<Vec<AwsKeyPair>>>,
@@ -103,18 +104,33 @@ impl VaultForSigning for AwsSigningVault {
         &self,
         signing_secret_key_handle: &SigningSecretKeyHandle,
     ) -> Result<VerifyingPublicKey> {
-        self.keys
-            .read()
-            .unwrap()
-            .iter()
-            .find_map(|x| {
-                if &x.key == signing_secret_key_handle {
-                    Some(x.public_key.clone())
-                } else {
-                    None
-                }
-            })
-            .ok_or(Error::KeyNotFound.into())
+        let cached = self.keys.read().unwrap().iter().find_map(|x| {
+            if &x.key == signing_secret_key_handle {
+                Some(x.public_key.clone())
+            } else {
+                None
+            }
+        });
+        if let Some(public_key) = cached {
+            return Ok(public_key);
+        }
+
+        // The key might have been created after the vault initialization, for example by
+        // another node sharing the same KMS. Fetch its public key once and cache it
+        let public_key = self
+            .client
+            .public_key(signing_secret_key_handle)
+            .await
+            .map_err(|err| {
+                error!("Error exporting public key: {err}");
+                Error::KeyNotFound
+            })?;
+        self.keys.write().unwrap().push(AwsKeyPair {
+            key: signing_secret_key_handle.clone(),
+            public_key: public_key.clone(),
+        });
+
+        Ok(public_key)
     }
 
     async fn get_secret_key_handle(
@@ -0,0 +1,37 @@
+use ockam_core::errcode::{Kind, Origin};
+use thiserror::Error;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("gcp kms error creating new key")]
+    Create(String),
+    #[error("gcp kms error signing message with key {keyid}")]
+    Sign { keyid: String, error: String },
+    #[error("gcp kms error exporting public key {keyid}")]
+    Export { keyid: String, error: String },
+    #[error("gcp kms error destroying key {keyid}")]
+    Delete { keyid: String, error: String },
+    #[error("gcp kms did not return the list of existing keys")]
+    MissingKeys,
+    #[error("gcp kms request failed")]
+    Request(String),
+    #[error("no access token could be obtained for gcp kms")]
+    MissingAccessToken(String),
+    #[error("key type is not supported")]
+    UnsupportedKeyType,
+    #[error("public key pem is incorrect")]
+    InvalidPublicKeyPem,
+    #[error("signature der is incorrect")]
+    InvalidSignatureDer,
+    #[error("key was not found")]
+    KeyNotFound,
+    #[error("invalid handle")]
+    InvalidHandle,
+}
+
+impl From<Error> for ockam_core::Error {
+    fn from(e: Error) -> Self {
+        ockam_core::Error::new(Origin::Other, Kind::Io, e)
+    }
+}
@@ -0,0 +1,544 @@
+use crate::error::Error;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ockam_core::compat::rand::{thread_rng, RngCore};
+use ockam_core::compat::sync::{Arc, Mutex};
+use ockam_core::{async_trait, Result};
+use ockam_vault::{
+    ECDSASHA256CurveP256PublicKey, ECDSASHA256CurveP256Signature, HandleToSecret, Signature,
+    SigningSecretKeyHandle, VerifyingPublicKey,
+};
+use reqwest::{RequestBuilder, StatusCode};
+use serde::Deserialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::time::{Duration, Instant};
+use tracing as log;
+
+/// Google Cloud KMS REST endpoint
+const GCP_KMS_ENDPOINT: &str = "https://cloudkms.googleapis.com/v1";
+
+/// Endpoint of the metadata server returning an access token for the default service account
+/// of a GCE / GKE / Cloud Run instance
+const GCP_METADATA_TOKEN_ENDPOINT: &str =
+    "http://metadata.google.internal/computeMetadata/v1/instance/service-accounts/default/token";
+
+/// Environment variable which can be used to provide an access token,
+/// for example the output of `gcloud auth print-access-token`
+pub const GCP_ACCESS_TOKEN_ENV: &str = "GOOGLE_OAUTH_ACCESS_TOKEN";
+
+/// Algorithm of the keys created and used by the vault
+const EC_SIGN_P256_SHA256: &str = "EC_SIGN_P256_SHA256";
+
+/// The key material of a new key may take some time to be generated, especially in an HSM
+const PUBLIC_KEY_RETRIES: u32 = 10;
+const PUBLIC_KEY_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Google Cloud KMS client.
+#[derive(Clone)]
+pub struct GcpKmsClient {
+    client: reqwest::Client,
+    config: GcpKmsConfig,
+    access_token: Arc<Mutex<Option<AccessToken>>>,
+}
+
+#[derive(Clone)]
+struct AccessToken {
+    value: String,
+    expires_at: Instant,
+}
+
+/// Key versions known by a [`GcpSigningVault`](crate::GcpSigningVault) when it is created
+#[derive(Debug, Clone)]
+pub enum InitialKeysDiscovery {
+    /// List the enabled P-256 signing keys of the key ring
+    ListFromGcpKms,
+
+    /// Use a specific set of key versions
+    Keys(Vec<SigningSecretKeyHandle>),
+}
+
+/// Protection level of the keys created in Google Cloud KMS
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtectionLevel {
+    /// Keys are stored and used in software
+    Software,
+    /// Keys are stored and used in a Cloud HSM
+    Hsm,
+}
+
+impl ProtectionLevel {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ProtectionLevel::Software => "SOFTWARE",
+            ProtectionLevel::Hsm => "HSM",
+        }
+    }
+}
+
+/// Google Cloud KMS configuration.
+#[derive(Debug, Clone)]
+pub struct GcpKmsConfig {
+    key_ring: String,
+    protection_level: ProtectionLevel,
+    access_token: Option<String>,
+    initial_keys_discovery: InitialKeysDiscovery,
+}
+
+impl GcpKmsConfig {
+    /// Create a new configuration for the Google Cloud KMS.
+    /// The key ring has the form `projects/{project}/locations/{location}/keyRings/{key_ring}`
+    pub fn new(key_ring: impl Into<String>) -> GcpKmsConfig {
+        GcpKmsConfig {
+            key_ring: key_ring.into(),
+            protection_level: ProtectionLevel::Hsm,
+            access_token: None,
+            initial_keys_discovery: InitialKeysDiscovery::ListFromGcpKms,
+        }
+    }
+
+    /// Set the protection level of the created keys. The default is [`ProtectionLevel::Hsm`]
+    pub fn with_protection_level(mut self, protection_level: ProtectionLevel) -> Self {
+        self.protection_level = protection_level;
+        self
+    }
+
+    /// Use a fixed access token instead of getting it from the
+    /// `GOOGLE_OAUTH_ACCESS_TOKEN` environment variable or from the metadata server
+    pub fn with_access_token(mut self, access_token: impl Into<String>) -> Self {
+        self.access_token = Some(access_token.into());
+        self
+    }
+
+    /// Configure initial key discovery
+    pub fn with_initial_keys_discovery(self, initial_keys_discovery: InitialKeysDiscovery) -> Self {
+        Self {
+            initial_keys_discovery,
+            ..self
+        }
+    }
+
+    /// Return the key ring
+    pub fn key_ring(&self) -> &str {
+        &self.key_ring
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: u64,
+}
+
+#[derive(Deserialize)]
+struct CryptoKey {
+    name: String,
+    #[serde(rename = "versionTemplate")]
+    version_template: Option<VersionTemplate>,
+}
+
+#[derive(Deserialize)]
+struct VersionTemplate {
+    algorithm: String,
+}
+
+#[derive(Deserialize)]
+struct CryptoKeyVersion {
+    name: String,
+}
+
+#[derive(Deserialize, Default)]
+struct ListCryptoKeysResponse {
+    #[serde(rename = "cryptoKeys", default)]
+    crypto_keys: Vec<CryptoKey>,
+    #[serde(rename = "nextPageToken")]
+    next_page_token: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+struct ListCryptoKeyVersionsResponse {
+    #[serde(rename = "cryptoKeyVersions", default)]
+    crypto_key_versions: Vec<CryptoKeyVersion>,
+    #[serde(rename = "nextPageToken")]
+    next_page_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PublicKeyResponse {
+    pem: String,
+    algorithm: String,
+}
+
+#[derive(Deserialize)]
+struct AsymmetricSignResponse {
+    signature: String,
+}
+
+impl GcpKmsClient {
+    /// Create a new Google Cloud KMS client.
+    pub async fn new(config: GcpKmsConfig) -> Result<GcpKmsClient> {
+        Ok(Self {
+            client: reqwest::Client::new(),
+            config,
+            access_token: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    /// A handle is the full resource name of a key version:
+    /// `{key_ring}/cryptoKeys/{key}/cryptoKeyVersions/{version}`
+    fn cast_handle_to_kid(handle: &SigningSecretKeyHandle) -> Result<String> {
+        let handle = match handle {
+            SigningSecretKeyHandle::EdDSACurve25519(_) => return Err(Error::InvalidHandle)?,
+            SigningSecretKeyHandle::ECDSASHA256CurveP256(handle) => handle.value().clone(),
+        };
+
+        let kid = String::from_utf8(handle).map_err(|_| Error::InvalidHandle)?;
+
+        Ok(kid)
+    }
+
+    fn kid_to_handle(kid: &str) -> SigningSecretKeyHandle {
+        SigningSecretKeyHandle::ECDSASHA256CurveP256(HandleToSecret::new(kid.as_bytes().to_vec()))
+    }
+
+    /// Return an access token, refreshing it from the metadata server if it expired
+    async fn access_token(&self) -> Result<String> {
+        if let Some(access_token) = &self.config.access_token {
+            return Ok(access_token.clone());
+        }
+        if let Ok(access_token) = std::env::var(GCP_ACCESS_TOKEN_ENV) {
+            return Ok(access_token);
+        }
+        if let Some(token) = self.access_token.lock().unwrap().as_ref() {
+            if token.expires_at > Instant::now() {
+                return Ok(token.value.clone());
+            }
+        }
+
+        log::trace!("get an access token from the metadata server");
+        let response = self
+            .client
+            .get(GCP_METADATA_TOKEN_ENDPOINT)
+            .header("Metadata-Flavor", "Google")
+            .send()
+            .await
+            .map_err(|err| Error::MissingAccessToken(err.to_string()))?;
+        let token: TokenResponse = response
+            .error_for_status()
+            .map_err(|err| Error::MissingAccessToken(err.to_string()))?
+            .json()
+            .await
+            .map_err(|err| Error::MissingAccessToken(err.to_string()))?;
+
+        // refresh the token a bit before its actual expiration
+        let expires_in = Duration::from_secs(token.expires_in.saturating_sub(60));
+        *self.access_token.lock().unwrap() = Some(AccessToken {
+            value: token.access_token.clone(),
+            expires_at: Instant::now() + expires_in,
+        });
+        Ok(token.access_token)
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<reqwest::Response> {
+        let access_token = self.access_token().await?;
+        request
+            .bearer_auth(access_token)
+            .send()
+            .await
+            .map_err(|err| Error::Request(err.to_string()).into())
+    }
+
+    /// Create a new NIST P-256 key-pair in Google Cloud KMS and return the name of its first version.
+    pub async fn create_key(&self) -> Result<SigningSecretKeyHandle> {
+        log::trace!("create new key");
+        let mut random = [0u8; 8];
+        thread_rng().fill_bytes(&mut random);
+        let key_id = format!("ockam-{}", hex::encode(random));
+
+        let request = self
+            .client
+            .post(format!(
+                "{GCP_KMS_ENDPOINT}/{}/cryptoKeys",
+                self.config.key_ring
+            ))
+            .query(&[("cryptoKeyId", key_id.as_str())])
+            .json(&json!({
+                "purpose": "ASYMMETRIC_SIGN",
+                "versionTemplate": {
+                    "algorithm": EC_SIGN_P256_SHA256,
+                    "protectionLevel": self.config.protection_level.as_str(),
+                }
+            }));
+        let response = self.send(request).await?;
+        if !response.status().is_success() {
+            let err = response.text().await.unwrap_or_default();
+            log::error!(%err, "failed to create new key");
+            return Err(Error::Create(err))?;
+        }
+        let key: CryptoKey = response
+            .json()
+            .await
+            .map_err(|err| Error::Create(err.to_string()))?;
+
+        // the first version of an asymmetric key is created together with the key
+        let kid = format!("{}/cryptoKeyVersions/1", key.name);
+        log::debug!(%kid, "created new key");
+        Ok(Self::kid_to_handle(&kid))
+    }
+
+    /// Have Google Cloud KMS destroy a key version.
+    /// The key material is destroyed after the scheduled destroy duration of the key (30 days by default)
+    pub async fn delete_key(&self, key: &SigningSecretKeyHandle) -> Result<bool> {
+        let key = Self::cast_handle_to_kid(key)?;
+        log::trace!(%key, "schedule key for destruction");
+        let request = self
+            .client
+            .post(format!("{GCP_KMS_ENDPOINT}/{key}:destroy"))
+            .json(&json!({}));
+        let response = self.send(request).await?;
+        match response.status() {
+            StatusCode::NOT_FOUND => {
+                log::debug!(%key, "key does not exist");
+                Ok(false)
+            }
+            status if status.is_success() => {
+                log::debug!(%key, "key is scheduled for destruction");
+                Ok(true)
+            }
+            _ => {
+                let err = response.text().await.unwrap_or_default();
+                log::error!(%key, %err, "failed to schedule key for destruction");
+                Err(Error::Delete {
+                    keyid: key.to_string(),
+                    error: err,
+                })?
+            }
+        }
+    }
+
+    /// Get the public key part of a Google Cloud KMS key version.
+    pub async fn public_key(&self, key: &SigningSecretKeyHandle) -> Result<VerifyingPublicKey> {
+        let kid = Self::cast_handle_to_kid(key)?;
+        log::trace!(%kid, "get public key");
+
+        let mut retry = 0;
+        let response = loop {
+            let request = self
+                .client
+                .get(format!("{GCP_KMS_ENDPOINT}/{kid}/publicKey"));
+            let response = self.send(request).await?;
+            // a new key version is PENDING_GENERATION until its key material is available
+            if response.status() == StatusCode::FAILED_PRECONDITION && retry < PUBLIC_KEY_RETRIES {
+                retry += 1;
+                tokio::time::sleep(PUBLIC_KEY_RETRY_DELAY).await;
+                continue;
+            }
+            break response;
+        };
+
+        if !response.status().is_success() {
+            let err = response.text().await.unwrap_or_default();
+            log::error!(%kid, %err, "failed to get public key");
+            return Err(Error::Export {
+                keyid: kid.to_string(),
+                error: err,
+            })?;
+        }
+        let output: PublicKeyResponse = response.json().await.map_err(|err| Error::Export {
+            keyid: kid.to_string(),
+            error: err.to_string(),
+        })?;
+        if output.algorithm != EC_SIGN_P256_SHA256 {
+            log::error!(%kid, "key algorithm not supported to get a public key");
+            return Err(Error::UnsupportedKeyType)?;
+        }
+
+        log::debug!(%kid, "received public key");
+        let der = pem_to_der(&output.pem)?;
+        use p256::pkcs8::DecodePublicKey;
+        let k = p256::ecdsa::VerifyingKey::from_public_key_der(&der)
+            .map_err(|_| Error::InvalidPublicKeyPem)?;
+        let public_key = k.to_sec1_bytes().to_vec();
+        let public_key = ECDSASHA256CurveP256PublicKey(
+            public_key
+                .try_into()
+                .map_err(|_| Error::InvalidPublicKeyPem)?,
+        );
+        Ok(VerifyingPublicKey::ECDSASHA256CurveP256(public_key))
+    }
+
+    /// Have Google Cloud KMS sign a message.
+    pub async fn sign(&self, key: &SigningSecretKeyHandle, message: &[u8]) -> Result<Signature> {
+        let kid = Self::cast_handle_to_kid(key)?;
+        log::trace!(%kid, "sign message");
+        let request = self
+            .client
+            .post(format!("{GCP_KMS_ENDPOINT}/{kid}:asymmetricSign"))
+            .json(&json!({
+                "digest": { "sha256": BASE64.encode(Sha256::digest(message)) }
+            }));
+        let response = self.send(request).await?;
+        if !response.status().is_success() {
+            let err = response.text().await.unwrap_or_default();
+            log::error!(%kid, %err, "failed to sign message");
+            return Err(Error::Sign {
+                keyid: kid.to_string(),
+                error: err,
+            })?;
+        }
+        let output: AsymmetricSignResponse = response.json().await.map_err(|err| Error::Sign {
+            keyid: kid.to_string(),
+            error: err.to_string(),
+        })?;
+
+        log::debug!(%kid, "signed message");
+        let sig = BASE64
+            .decode(output.signature)
+            .map_err(|_| Error::InvalidSignatureDer)?;
+        let sig = p256::ecdsa::Signature::from_der(&sig).map_err(|_| Error::InvalidSignatureDer)?;
+        let sig = ECDSASHA256CurveP256Signature(
+            sig.to_vec()
+                .try_into()
+                .map_err(|_| Error::InvalidSignatureDer)?,
+        );
+        Ok(Signature::ECDSASHA256CurveP256(sig))
+    }
+
+    /// List the enabled versions of the P-256 signing keys of the key ring
+    async fn list_key_versions(&self) -> Result<Vec<SigningSecretKeyHandle>> {
+        let mut keys = vec![];
+        let mut page_token: Option<String> = None;
+        loop {
+            let mut request = self
+                .client
+                .get(format!(
+                    "{GCP_KMS_ENDPOINT}/{}/cryptoKeys",
+                    self.config.key_ring
+                ))
+                .query(&[("filter", "purpose=ASYMMETRIC_SIGN")]);
+            if let Some(page_token) = &page_token {
+                request = request.query(&[("pageToken", page_token)]);
+            }
+            let output: ListCryptoKeysResponse = self.list(request).await?;
+            keys.extend(output.crypto_keys.into_iter().filter(|k| {
+                k.version_template
+                    .as_ref()
+                    .map(|t| t.algorithm == EC_SIGN_P256_SHA256)
+                    .unwrap_or(false)
+            }));
+            page_token = output.next_page_token.filter(|t| !t.is_empty());
+            if page_token.is_none() {
+                break;
+            }
+        }
+
+        let mut result = vec![];
+        for key in keys {
+            let mut page_token: Option<String> = None;
+            loop {
+                let mut request = self
+                    .client
+                    .get(format!("{GCP_KMS_ENDPOINT}/{}/cryptoKeyVersions", key.name))
+                    .query(&[("filter", "state=ENABLED")]);
+                if let Some(page_token) = &page_token {
+                    request = request.query(&[("pageToken", page_token)]);
+                }
+                let output: ListCryptoKeyVersionsResponse = self.list(request).await?;
+                result.extend(
+                    output
+                        .crypto_key_versions
+                        .iter()
+                        .map(|v| Self::kid_to_handle(&v.name)),
+                );
+                page_token = output.next_page_token.filter(|t| !t.is_empty());
+                if page_token.is_none() {
+                    break;
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    async fn list<T: for<'de> Deserialize<'de> + Default>(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<T> {
+        let response = self.send(request).await?;
+        if !response.status().is_success() {
+            let err = response.text().await.unwrap_or_default();
+            log::error!(%err, "failed to list keys");
+            return Err(Error::MissingKeys)?;
+        }
+        // an empty list is returned as an empty JSON object
+        Ok(response.json().await.map_err(|_| Error::MissingKeys)?)
+    }
+}
+
+/// This trait is introduced to help with the testing of the GcpSigningVault
+#[async_trait]
+pub trait KmsClient {
+    /// Create a key
+    async fn create_key(&self) -> Result<SigningSecretKeyHandle>;
+
+    /// Delete a key
+    async fn delete_key(&self, key: &SigningSecretKeyHandle) -> Result<bool>;
+
+    /// Return the public key of a key
+    async fn public_key(&self, key: &SigningSecretKeyHandle) -> Result<VerifyingPublicKey>;
+
+    /// Return the list of keys available at startup
+    async fn list_keys(&self) -> Result<Vec<SigningSecretKeyHandle>>;
+
+    /// Sign a message
+    async fn sign(&self, key: &SigningSecretKeyHandle, message: &[u8]) -> Result<Signature>;
+}
+
+#[async_trait]
+impl KmsClient for GcpKmsClient {
+    async fn create_key(&self) -> Result<SigningSecretKeyHandle> {
+        self.create_key().await
+    }
+
+    async fn delete_key(&self, key: &SigningSecretKeyHandle) -> Result<bool> {
+        self.delete_key(key).await
+    }
+
+    async fn public_key(&self, key: &SigningSecretKeyHandle) -> Result<VerifyingPublicKey> {
+        self.public_key(key).await
+    }
+
+    async fn list_keys(&self) -> Result<Vec<SigningSecretKeyHandle>> {
+        match &self.config.initial_keys_discovery {
+            InitialKeysDiscovery::ListFromGcpKms => self.list_key_versions().await,
+            InitialKeysDiscovery::Keys(keys) => Ok(keys.clone()),
+        }
+    }
+
+    async fn sign(&self, key: &SigningSecretKeyHandle, message: &[u8]) -> Result<Signature> {
+        self.sign(key, message).await
+    }
+}
+
+/// Extract the DER bytes of a PEM-encoded public key
+fn pem_to_der(pem: &str) -> Result<Vec<u8>> {
+    let body: String = pem
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .map(str::trim)
+        .collect();
+    Ok(BASE64
+        .decode(body)
+        .map_err(|_| Error::InvalidPublicKeyPem)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pem_to_der() {
+        let pem = "-----BEGIN PUBLIC KEY-----\nAQID\nBAU=\n-----END PUBLIC KEY-----\n";
+        assert_eq!(pem_to_der(pem).unwrap(), vec![1, 2, 3, 4, 5]);
+        assert!(pem_to_der("-----BEGIN PUBLIC KEY-----\n!!\n").is_err());
+    }
+}
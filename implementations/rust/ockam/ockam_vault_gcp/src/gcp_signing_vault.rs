@@ -0,0 +1,169 @@
+use crate::error::Error;
+use crate::gcp_kms_client::{GcpKmsClient, GcpKmsConfig, KmsClient};
+use ockam_core::compat::sync::{Arc, RwLock};
+use ockam_core::{async_trait, Result};
+use ockam_vault::{
+    Signature, SigningKeyType, SigningSecretKeyHandle, VaultError, VaultForSigning,
+    VerifyingPublicKey,
+};
+use tracing::error;
+
+struct GcpKeyPair {
+    key: SigningSecretKeyHandle,
+    public_key: VerifyingPublicKey,
+}
+
+/// Security module implementation using a Google Cloud KMS key ring
+pub struct GcpSigningVault {
+    client: Arc<dyn KmsClient + Send + Sync>,
+    // Key versions of the key ring with their public keys. Google Cloud KMS never returns
+    // the private key material, so the public key of each key version is downloaded once,
+    // when the vault is created or when a key version is created, and then cached here.
+    // A key version created in the key ring by another node is added the first time its
+    // public key is requested, and one destroyed by another node stays in the cache:
+    // signing with it fails on the Google Cloud KMS side.
+    keys: Arc<RwLock<Vec<GcpKeyPair>>>,
+}
+
+impl GcpSigningVault {
+    /// Create a Google Cloud KMS security module using the keys of a given key ring
+    pub async fn create(key_ring: &str) -> Result<Self> {
+        Self::create_with_config(GcpKmsConfig::new(key_ring)).await
+    }
+
+    /// Create a new Google Cloud KMS security module
+    pub async fn create_with_config(config: GcpKmsConfig) -> Result<Self> {
+        let client = GcpKmsClient::new(config).await?;
+
+        let mut key_pairs: Vec<GcpKeyPair> = vec![];
+        // List the key versions of the key ring, then download the public key of each one
+        let keys = client.list_keys().await?;
+
+        for key in keys {
+            match client.public_key(&key).await {
+                Ok(public_key) => key_pairs.push(GcpKeyPair { key, public_key }),
+                // The public key may not be available yet if the key version was just
+                // created in a Cloud HSM, or the service account may not be allowed to read
+                // it. The key version can't be used by this vault, skip it
+                Err(err) => error!("Error exporting public key: {err}"),
+            }
+        }
+
+        Ok(Self {
+            client: Arc::new(client),
+            keys: Arc::new(RwLock::new(key_pairs)),
+        })
+    }
+
+    /// Return list of all keys
+    pub fn keys(&self) -> Vec<SigningSecretKeyHandle> {
+        self.keys
+            .read()
+            .unwrap()
+            .iter()
+            .map(|x| x.key.clone())
+            .collect()
+    }
+
+    /// Return number of keys
+    pub async fn number_of_keys(&self) -> Result<usize> {
+        Ok(self.keys.read().unwrap().len())
+    }
+}
+
+#[async_trait]
+impl VaultForSigning for GcpSigningVault {
+    async fn sign(
+        &self,
+        signing_secret_key_handle: &SigningSecretKeyHandle,
+        data: &[u8],
+    ) -> Result<Signature> {
+        self.client.sign(signing_secret_key_handle, data).await
+    }
+
+    async fn generate_signing_secret_key(
+        &self,
+        signing_key_type: SigningKeyType,
+    ) -> Result<SigningSecretKeyHandle> {
+        if signing_key_type != SigningKeyType::ECDSASHA256CurveP256 {
+            return Err(VaultError::InvalidKeyType)?;
+        }
+
+        let key = self.client.create_key().await?;
+        let public_key = self.client.public_key(&key).await?;
+
+        self.keys.write().unwrap().push(GcpKeyPair {
+            key: key.clone(),
+            public_key,
+        });
+
+        Ok(key)
+    }
+
+    async fn get_verifying_public_key(
+        &self,
+        signing_secret_key_handle: &SigningSecretKeyHandle,
+    ) -> Result<VerifyingPublicKey> {
+        let cached = self.keys.read().unwrap().iter().find_map(|x| {
+            if &x.key == signing_secret_key_handle {
+                Some(x.public_key.clone())
+            } else {
+                None
+            }
+        });
+        if let Some(public_key) = cached {
+            return Ok(public_key);
+        }
+
+        // The key might have been created after the vault initialization, for example by
+        // another node sharing the same KMS. Fetch its public key once and cache it
+        let public_key = self
+            .client
+            .public_key(signing_secret_key_handle)
+            .await
+            .map_err(|err| {
+                error!("Error exporting public key: {err}");
+                Error::KeyNotFound
+            })?;
+        self.keys.write().unwrap().push(GcpKeyPair {
+            key: signing_secret_key_handle.clone(),
+            public_key: public_key.clone(),
+        });
+
+        Ok(public_key)
+    }
+
+    async fn get_secret_key_handle(
+        &self,
+        verifying_public_key: &VerifyingPublicKey,
+    ) -> Result<SigningSecretKeyHandle> {
+        self.keys
+            .read()
+            .unwrap()
+            .iter()
+            .find_map(|x| {
+                if &x.public_key == verifying_public_key {
+                    Some(x.key.clone())
+                } else {
+                    None
+                }
+            })
+            .ok_or(Error::KeyNotFound.into())
+    }
+
+    async fn delete_signing_secret_key(
+        &self,
+        signing_secret_key_handle: SigningSecretKeyHandle,
+    ) -> Result<bool> {
+        if self.client.delete_key(&signing_secret_key_handle).await? {
+            self.keys
+                .write()
+                .unwrap()
+                .retain(|x| x.key != signing_secret_key_handle);
+
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+}
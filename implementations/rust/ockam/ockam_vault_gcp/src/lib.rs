@@ -0,0 +1,18 @@
+//! Google Cloud KMS implementation of the ockam_vault signing traits
+//!
+#![deny(unsafe_code)]
+#![warn(
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_import_braces,
+    unused_qualifications
+)]
+
+mod error;
+mod gcp_kms_client;
+mod gcp_signing_vault;
+
+pub use error::*;
+pub use gcp_kms_client::*;
+pub use gcp_signing_vault::*;
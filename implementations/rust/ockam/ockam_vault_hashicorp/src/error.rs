@@ -0,0 +1,39 @@
+use ockam_core::errcode::{Kind, Origin};
+use thiserror::Error;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("hashicorp vault error creating new key")]
+    Create(String),
+    #[error("hashicorp vault error signing message with key {keyid}")]
+    Sign { keyid: String, error: String },
+    #[error("hashicorp vault error exporting public key {keyid}")]
+    Export { keyid: String, error: String },
+    #[error("hashicorp vault error deleting key {keyid}")]
+    Delete { keyid: String, error: String },
+    #[error("hashicorp vault did not return the list of existing keys")]
+    MissingKeys,
+    #[error("hashicorp vault request failed")]
+    Request(String),
+    #[error("hashicorp vault authentication failed")]
+    Authentication(String),
+    #[error("missing hashicorp vault configuration {0}")]
+    MissingConfiguration(String),
+    #[error("key type is not supported")]
+    UnsupportedKeyType,
+    #[error("public key is incorrect")]
+    InvalidPublicKey,
+    #[error("signature is incorrect")]
+    InvalidSignature,
+    #[error("key was not found")]
+    KeyNotFound,
+    #[error("invalid handle")]
+    InvalidHandle,
+}
+
+impl From<Error> for ockam_core::Error {
+    fn from(e: Error) -> Self {
+        ockam_core::Error::new(Origin::Other, Kind::Io, e)
+    }
+}
@@ -0,0 +1,168 @@
+use crate::error::Error;
+use crate::hashicorp_vault_client::{HashicorpVaultClient, HashicorpVaultConfig, KmsClient};
+use ockam_core::compat::sync::{Arc, RwLock};
+use ockam_core::{async_trait, Result};
+use ockam_vault::{
+    Signature, SigningKeyType, SigningSecretKeyHandle, VaultError, VaultForSigning,
+    VerifyingPublicKey,
+};
+use tracing::error;
+
+struct HashicorpKeyPair {
+    key: SigningSecretKeyHandle,
+    public_key: VerifyingPublicKey,
+}
+
+/// Security module implementation using the Transit secrets engine of HashiCorp Vault
+pub struct HashicorpSigningVault {
+    client: Arc<dyn KmsClient + Send + Sync>,
+    // Names of the Transit keys with the public key of their latest version. The public
+    // keys are read once, when the vault is created or when a key is created, and then
+    // cached here. Transit signs with the latest version of a key, so the cached public
+    // key becomes outdated if the key is rotated outside of this vault: the key must then
+    // be used with a new vault instance.
+    keys: Arc<RwLock<Vec<HashicorpKeyPair>>>,
+}
+
+impl HashicorpSigningVault {
+    /// Create a HashiCorp Vault security module configured with the standard
+    /// `VAULT_*` environment variables
+    pub async fn create() -> Result<Self> {
+        Self::create_with_config(HashicorpVaultConfig::from_env()?).await
+    }
+
+    /// Create a new HashiCorp Vault security module
+    pub async fn create_with_config(config: HashicorpVaultConfig) -> Result<Self> {
+        let client = HashicorpVaultClient::new(config).await?;
+
+        let mut key_pairs: Vec<HashicorpKeyPair> = vec![];
+        // List the names of the Transit keys, then read the public key of each one
+        let keys = client.list_keys().await?;
+
+        for key in keys {
+            match client.public_key(&key).await {
+                Ok(public_key) => key_pairs.push(HashicorpKeyPair { key, public_key }),
+                // The Transit engine may contain keys of other types, used by other
+                // applications, or keys which the token is not allowed to read. Skip them
+                Err(err) => error!("Error exporting public key: {err}"),
+            }
+        }
+
+        Ok(Self {
+            client: Arc::new(client),
+            keys: Arc::new(RwLock::new(key_pairs)),
+        })
+    }
+
+    /// Return list of all keys
+    pub fn keys(&self) -> Vec<SigningSecretKeyHandle> {
+        self.keys
+            .read()
+            .unwrap()
+            .iter()
+            .map(|x| x.key.clone())
+            .collect()
+    }
+
+    /// Return number of keys
+    pub async fn number_of_keys(&self) -> Result<usize> {
+        Ok(self.keys.read().unwrap().len())
+    }
+}
+
+#[async_trait]
+impl VaultForSigning for HashicorpSigningVault {
+    async fn sign(
+        &self,
+        signing_secret_key_handle: &SigningSecretKeyHandle,
+        data: &[u8],
+    ) -> Result<Signature> {
+        self.client.sign(signing_secret_key_handle, data).await
+    }
+
+    async fn generate_signing_secret_key(
+        &self,
+        signing_key_type: SigningKeyType,
+    ) -> Result<SigningSecretKeyHandle> {
+        if signing_key_type != SigningKeyType::EdDSACurve25519 {
+            return Err(VaultError::InvalidKeyType)?;
+        }
+
+        let key = self.client.create_key().await?;
+        let public_key = self.client.public_key(&key).await?;
+
+        self.keys.write().unwrap().push(HashicorpKeyPair {
+            key: key.clone(),
+            public_key,
+        });
+
+        Ok(key)
+    }
+
+    async fn get_verifying_public_key(
+        &self,
+        signing_secret_key_handle: &SigningSecretKeyHandle,
+    ) -> Result<VerifyingPublicKey> {
+        let cached = self.keys.read().unwrap().iter().find_map(|x| {
+            if &x.key == signing_secret_key_handle {
+                Some(x.public_key.clone())
+            } else {
+                None
+            }
+        });
+        if let Some(public_key) = cached {
+            return Ok(public_key);
+        }
+
+        // The key might have been created after the vault initialization, for example by
+        // another node using the same Transit engine. Fetch its public key once and cache it
+        let public_key = self
+            .client
+            .public_key(signing_secret_key_handle)
+            .await
+            .map_err(|err| {
+                error!("Error exporting public key: {err}");
+                Error::KeyNotFound
+            })?;
+        self.keys.write().unwrap().push(HashicorpKeyPair {
+            key: signing_secret_key_handle.clone(),
+            public_key: public_key.clone(),
+        });
+
+        Ok(public_key)
+    }
+
+    async fn get_secret_key_handle(
+        &self,
+        verifying_public_key: &VerifyingPublicKey,
+    ) -> Result<SigningSecretKeyHandle> {
+        self.keys
+            .read()
+            .unwrap()
+            .iter()
+            .find_map(|x| {
+                if &x.public_key == verifying_public_key {
+                    Some(x.key.clone())
+                } else {
+                    None
+                }
+            })
+            .ok_or(Error::KeyNotFound.into())
+    }
+
+    async fn delete_signing_secret_key(
+        &self,
+        signing_secret_key_handle: SigningSecretKeyHandle,
+    ) -> Result<bool> {
+        if self.client.delete_key(&signing_secret_key_handle).await? {
+            self.keys
+                .write()
+                .unwrap()
+                .retain(|x| x.key != signing_secret_key_handle);
+
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+}
@@ -0,0 +1,595 @@
+use crate::error::Error;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::rand::{thread_rng, RngCore};
+use ockam_core::compat::sync::{Arc, Mutex};
+use ockam_core::{async_trait, Result};
+use ockam_vault::{
+    EdDSACurve25519PublicKey, EdDSACurve25519Signature, HandleToSecret, Signature,
+    SigningSecretKeyHandle, VerifyingPublicKey,
+};
+use reqwest::{RequestBuilder, StatusCode};
+use serde::Deserialize;
+use serde_json::json;
+use std::time::{Duration, Instant};
+use tracing as log;
+
+/// Environment variable containing the address of the HashiCorp Vault server
+pub const VAULT_ADDR_ENV: &str = "VAULT_ADDR";
+/// Environment variable containing a HashiCorp Vault token
+pub const VAULT_TOKEN_ENV: &str = "VAULT_TOKEN";
+/// Environment variable containing the HashiCorp Vault namespace (Vault Enterprise)
+pub const VAULT_NAMESPACE_ENV: &str = "VAULT_NAMESPACE";
+/// Environment variable containing the role id used to log in with AppRole
+pub const VAULT_ROLE_ID_ENV: &str = "VAULT_ROLE_ID";
+/// Environment variable containing the secret id used to log in with AppRole
+pub const VAULT_SECRET_ID_ENV: &str = "VAULT_SECRET_ID";
+
+/// Default mount path of the Transit secrets engine
+pub const DEFAULT_TRANSIT_MOUNT: &str = "transit";
+/// Default mount path of the AppRole auth method
+pub const DEFAULT_APPROLE_MOUNT: &str = "approle";
+
+/// A token is renewed when it expires in less than this duration
+const TOKEN_RENEWAL_THRESHOLD: Duration = Duration::from_secs(60);
+
+/// HashiCorp Vault Transit client.
+#[derive(Clone)]
+pub struct HashicorpVaultClient {
+    client: reqwest::Client,
+    config: HashicorpVaultConfig,
+    token: Arc<Mutex<Option<VaultToken>>>,
+}
+
+#[derive(Clone)]
+struct VaultToken {
+    value: String,
+    // None if the token never expires
+    expires_at: Option<Instant>,
+    renewable: bool,
+}
+
+impl VaultToken {
+    fn new(value: String, ttl: u64, renewable: bool) -> Self {
+        let expires_at = if ttl == 0 {
+            None
+        } else {
+            Some(Instant::now() + Duration::from_secs(ttl))
+        };
+        Self {
+            value,
+            expires_at,
+            renewable,
+        }
+    }
+
+    fn needs_renewal(&self) -> bool {
+        self.expires_at
+            .map(|expires_at| expires_at <= Instant::now() + TOKEN_RENEWAL_THRESHOLD)
+            .unwrap_or(false)
+    }
+}
+
+/// Authentication method used to obtain a HashiCorp Vault token
+#[derive(Debug, Clone)]
+pub enum HashicorpVaultAuth {
+    /// Use a token directly. The token is renewed before it expires if it is renewable
+    Token(String),
+
+    /// Log in with the AppRole auth method.
+    /// The token is renewed before it expires, or a new login is made if it can't be renewed
+    AppRole {
+        /// Mount path of the AppRole auth method
+        mount: String,
+        /// Role id
+        role_id: String,
+        /// Secret id
+        secret_id: String,
+    },
+}
+
+/// Transit keys known by a [`HashicorpSigningVault`](crate::HashicorpSigningVault) when it is created
+#[derive(Debug, Clone)]
+pub enum InitialKeysDiscovery {
+    /// List the keys of the Transit secrets engine
+    ListFromHashicorpVault,
+
+    /// Use a specific set of key names
+    Keys(Vec<SigningSecretKeyHandle>),
+}
+
+/// HashiCorp Vault configuration.
+#[derive(Debug, Clone)]
+pub struct HashicorpVaultConfig {
+    address: String,
+    namespace: Option<String>,
+    transit_mount: String,
+    auth: HashicorpVaultAuth,
+    initial_keys_discovery: InitialKeysDiscovery,
+}
+
+impl HashicorpVaultConfig {
+    /// Create a new configuration for a HashiCorp Vault server, for example `https://vault:8200`
+    pub fn new(address: impl Into<String>, auth: HashicorpVaultAuth) -> HashicorpVaultConfig {
+        HashicorpVaultConfig {
+            address: address.into().trim_end_matches('/').to_string(),
+            namespace: None,
+            transit_mount: DEFAULT_TRANSIT_MOUNT.to_string(),
+            auth,
+            initial_keys_discovery: InitialKeysDiscovery::ListFromHashicorpVault,
+        }
+    }
+
+    /// Create a configuration from the standard environment variables:
+    ///
+    ///  - `VAULT_ADDR`: address of the server
+    ///  - `VAULT_NAMESPACE`: optional namespace
+    ///  - `VAULT_ROLE_ID` and `VAULT_SECRET_ID` to log in with AppRole, otherwise `VAULT_TOKEN`
+    pub fn from_env() -> Result<HashicorpVaultConfig> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let address =
+            var(VAULT_ADDR_ENV).ok_or(Error::MissingConfiguration(VAULT_ADDR_ENV.into()))?;
+        let auth = match (var(VAULT_ROLE_ID_ENV), var(VAULT_SECRET_ID_ENV)) {
+            (Some(role_id), Some(secret_id)) => HashicorpVaultAuth::AppRole {
+                mount: DEFAULT_APPROLE_MOUNT.to_string(),
+                role_id,
+                secret_id,
+            },
+            _ => HashicorpVaultAuth::Token(
+                var(VAULT_TOKEN_ENV).ok_or(Error::MissingConfiguration(VAULT_TOKEN_ENV.into()))?,
+            ),
+        };
+        let config = Self::new(address, auth);
+        Ok(match var(VAULT_NAMESPACE_ENV) {
+            Some(namespace) => config.with_namespace(namespace),
+            None => config,
+        })
+    }
+
+    /// Set the namespace (Vault Enterprise)
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    /// Set the mount path of the Transit secrets engine. The default is `transit`
+    pub fn with_transit_mount(mut self, transit_mount: impl Into<String>) -> Self {
+        self.transit_mount = transit_mount.into();
+        self
+    }
+
+    /// Configure initial key discovery
+    pub fn with_initial_keys_discovery(self, initial_keys_discovery: InitialKeysDiscovery) -> Self {
+        Self {
+            initial_keys_discovery,
+            ..self
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct AuthResponse {
+    auth: AuthData,
+}
+
+#[derive(Deserialize)]
+struct AuthData {
+    client_token: String,
+    lease_duration: u64,
+    renewable: bool,
+}
+
+#[derive(Deserialize)]
+struct DataResponse<T> {
+    data: T,
+}
+
+#[derive(Deserialize)]
+struct TokenLookup {
+    ttl: u64,
+    renewable: Option<bool>,
+}
+
+#[derive(Deserialize)]
+struct KeyData {
+    #[serde(rename = "type")]
+    key_type: String,
+    latest_version: u64,
+    keys: BTreeMap<String, KeyVersion>,
+}
+
+#[derive(Deserialize)]
+struct KeyVersion {
+    public_key: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SignData {
+    signature: String,
+}
+
+#[derive(Deserialize)]
+struct ListData {
+    keys: Vec<String>,
+}
+
+impl HashicorpVaultClient {
+    /// Create a new HashiCorp Vault client.
+    pub async fn new(config: HashicorpVaultConfig) -> Result<HashicorpVaultClient> {
+        Ok(Self {
+            client: reqwest::Client::new(),
+            config,
+            token: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    /// A handle is the name of a Transit key
+    fn cast_handle_to_kid(handle: &SigningSecretKeyHandle) -> Result<String> {
+        let handle = match handle {
+            SigningSecretKeyHandle::EdDSACurve25519(handle) => handle.value().clone(),
+            SigningSecretKeyHandle::ECDSASHA256CurveP256(_) => return Err(Error::InvalidHandle)?,
+        };
+
+        let kid = String::from_utf8(handle).map_err(|_| Error::InvalidHandle)?;
+
+        Ok(kid)
+    }
+
+    fn kid_to_handle(kid: &str) -> SigningSecretKeyHandle {
+        SigningSecretKeyHandle::EdDSACurve25519(HandleToSecret::new(kid.as_bytes().to_vec()))
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/v1/{path}", self.config.address)
+    }
+
+    fn with_namespace(&self, request: RequestBuilder) -> RequestBuilder {
+        match &self.config.namespace {
+            Some(namespace) => request.header("X-Vault-Namespace", namespace),
+            None => request,
+        }
+    }
+
+    /// Return a valid token: the current token is renewed if it is about to expire,
+    /// otherwise a new token is obtained with the configured authentication method
+    async fn token(&self) -> Result<String> {
+        let current = self.token.lock().unwrap().clone();
+        let token = match current {
+            Some(token) if !token.needs_renewal() => return Ok(token.value),
+            Some(token) if token.renewable => match self.renew(&token.value).await {
+                Ok(token) => token,
+                Err(err) => {
+                    log::warn!(%err, "failed to renew the hashicorp vault token");
+                    self.login().await?
+                }
+            },
+            _ => self.login().await?,
+        };
+        let value = token.value.clone();
+        *self.token.lock().unwrap() = Some(token);
+        Ok(value)
+    }
+
+    async fn login(&self) -> Result<VaultToken> {
+        match &self.config.auth {
+            HashicorpVaultAuth::Token(token) => {
+                log::trace!("look up the hashicorp vault token");
+                let request = self
+                    .with_namespace(self.client.get(self.url("auth/token/lookup-self")))
+                    .header("X-Vault-Token", token);
+                let response = Self::send(request).await?;
+                if !response.status().is_success() {
+                    let err = response.text().await.unwrap_or_default();
+                    return Err(Error::Authentication(err))?;
+                }
+                let lookup: DataResponse<TokenLookup> = response
+                    .json()
+                    .await
+                    .map_err(|err| Error::Authentication(err.to_string()))?;
+                Ok(VaultToken::new(
+                    token.clone(),
+                    lookup.data.ttl,
+                    lookup.data.renewable.unwrap_or(false),
+                ))
+            }
+            HashicorpVaultAuth::AppRole {
+                mount,
+                role_id,
+                secret_id,
+            } => {
+                log::trace!("log in to hashicorp vault with approle");
+                let request = self
+                    .with_namespace(self.client.post(self.url(&format!("auth/{mount}/login"))))
+                    .json(&json!({ "role_id": role_id, "secret_id": secret_id }));
+                Self::auth_response(Self::send(request).await?).await
+            }
+        }
+    }
+
+    async fn renew(&self, token: &str) -> Result<VaultToken> {
+        log::trace!("renew the hashicorp vault token");
+        let request = self
+            .with_namespace(self.client.post(self.url("auth/token/renew-self")))
+            .header("X-Vault-Token", token)
+            .json(&json!({}));
+        Self::auth_response(Self::send(request).await?).await
+    }
+
+    async fn auth_response(response: reqwest::Response) -> Result<VaultToken> {
+        if !response.status().is_success() {
+            let err = response.text().await.unwrap_or_default();
+            return Err(Error::Authentication(err))?;
+        }
+        let auth: AuthResponse = response
+            .json()
+            .await
+            .map_err(|err| Error::Authentication(err.to_string()))?;
+        Ok(VaultToken::new(
+            auth.auth.client_token,
+            auth.auth.lease_duration,
+            auth.auth.renewable,
+        ))
+    }
+
+    async fn send(request: RequestBuilder) -> Result<reqwest::Response> {
+        request
+            .send()
+            .await
+            .map_err(|err| Error::Request(err.to_string()).into())
+    }
+
+    /// Send an authenticated request to the Transit secrets engine
+    async fn send_transit(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<reqwest::Response> {
+        let token = self.token().await?;
+        let url = self.url(&format!("{}/{path}", self.config.transit_mount));
+        let mut request = self
+            .with_namespace(self.client.request(method, url))
+            .header("X-Vault-Token", token);
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+        Self::send(request).await
+    }
+
+    /// Create a new Ed25519 key in the Transit secrets engine and return its name.
+    pub async fn create_key(&self) -> Result<SigningSecretKeyHandle> {
+        log::trace!("create new key");
+        let kid = format!("ockam-{:016x}", thread_rng().next_u64());
+        let response = self
+            .send_transit(
+                reqwest::Method::POST,
+                &format!("keys/{kid}"),
+                Some(json!({ "type": "ed25519" })),
+            )
+            .await?;
+        if !response.status().is_success() {
+            let err = response.text().await.unwrap_or_default();
+            log::error!(%err, "failed to create new key");
+            return Err(Error::Create(err))?;
+        }
+        log::debug!(%kid, "created new key");
+        Ok(Self::kid_to_handle(&kid))
+    }
+
+    /// Delete a Transit key. The deletion of the key is allowed first since
+    /// Transit keys can't be deleted by default
+    pub async fn delete_key(&self, key: &SigningSecretKeyHandle) -> Result<bool> {
+        let kid = Self::cast_handle_to_kid(key)?;
+        log::trace!(%kid, "delete key");
+        let response = self
+            .send_transit(
+                reqwest::Method::POST,
+                &format!("keys/{kid}/config"),
+                Some(json!({ "deletion_allowed": true })),
+            )
+            .await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            log::debug!(%kid, "key does not exist");
+            return Ok(false);
+        }
+        if !response.status().is_success() {
+            let err = response.text().await.unwrap_or_default();
+            log::error!(%kid, %err, "failed to allow the key deletion");
+            return Err(Error::Delete {
+                keyid: kid,
+                error: err,
+            })?;
+        }
+
+        let response = self
+            .send_transit(reqwest::Method::DELETE, &format!("keys/{kid}"), None)
+            .await?;
+        if !response.status().is_success() {
+            let err = response.text().await.unwrap_or_default();
+            log::error!(%kid, %err, "failed to delete key");
+            return Err(Error::Delete {
+                keyid: kid,
+                error: err,
+            })?;
+        }
+        log::debug!(%kid, "key deleted");
+        Ok(true)
+    }
+
+    /// Get the public key of the latest version of a Transit key.
+    /// Since Transit signs with the latest version of a key, the keys used by Ockam
+    /// must not be rotated
+    pub async fn public_key(&self, key: &SigningSecretKeyHandle) -> Result<VerifyingPublicKey> {
+        let kid = Self::cast_handle_to_kid(key)?;
+        log::trace!(%kid, "get public key");
+        let response = self
+            .send_transit(reqwest::Method::GET, &format!("keys/{kid}"), None)
+            .await?;
+        if !response.status().is_success() {
+            let err = response.text().await.unwrap_or_default();
+            log::error!(%kid, %err, "failed to get public key");
+            return Err(Error::Export {
+                keyid: kid,
+                error: err,
+            })?;
+        }
+        let output: DataResponse<KeyData> = response.json().await.map_err(|err| Error::Export {
+            keyid: kid.clone(),
+            error: err.to_string(),
+        })?;
+        if output.data.key_type != "ed25519" {
+            log::error!(%kid, "key type not supported to get a public key");
+            return Err(Error::UnsupportedKeyType)?;
+        }
+
+        let public_key = output
+            .data
+            .keys
+            .get(&output.data.latest_version.to_string())
+            .and_then(|v| v.public_key.as_ref())
+            .ok_or(Error::InvalidPublicKey)?;
+        log::debug!(%kid, "received public key");
+        let public_key = BASE64
+            .decode(public_key)
+            .map_err(|_| Error::InvalidPublicKey)?;
+        let public_key =
+            EdDSACurve25519PublicKey(public_key.try_into().map_err(|_| Error::InvalidPublicKey)?);
+        Ok(VerifyingPublicKey::EdDSACurve25519(public_key))
+    }
+
+    /// Have the Transit secrets engine sign a message.
+    pub async fn sign(&self, key: &SigningSecretKeyHandle, message: &[u8]) -> Result<Signature> {
+        let kid = Self::cast_handle_to_kid(key)?;
+        log::trace!(%kid, "sign message");
+        let response = self
+            .send_transit(
+                reqwest::Method::POST,
+                &format!("sign/{kid}"),
+                Some(json!({ "input": BASE64.encode(message) })),
+            )
+            .await?;
+        if !response.status().is_success() {
+            let err = response.text().await.unwrap_or_default();
+            log::error!(%kid, %err, "failed to sign message");
+            return Err(Error::Sign {
+                keyid: kid,
+                error: err,
+            })?;
+        }
+        let output: DataResponse<SignData> = response.json().await.map_err(|err| Error::Sign {
+            keyid: kid.clone(),
+            error: err.to_string(),
+        })?;
+
+        log::debug!(%kid, "signed message");
+        let signature = parse_signature(&output.data.signature)?;
+        Ok(Signature::EdDSACurve25519(signature))
+    }
+
+    /// List the names of the Transit keys
+    async fn list_key_names(&self) -> Result<Vec<SigningSecretKeyHandle>> {
+        let response = self
+            .send_transit(reqwest::Method::GET, "keys?list=true", None)
+            .await?;
+        // there are no keys yet
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(vec![]);
+        }
+        if !response.status().is_success() {
+            let err = response.text().await.unwrap_or_default();
+            log::error!(%err, "failed to list keys");
+            return Err(Error::MissingKeys)?;
+        }
+        let output: DataResponse<ListData> =
+            response.json().await.map_err(|_| Error::MissingKeys)?;
+        Ok(output
+            .data
+            .keys
+            .iter()
+            .map(|kid| Self::kid_to_handle(kid))
+            .collect())
+    }
+}
+
+/// This trait is introduced to help with the testing of the HashicorpSigningVault
+#[async_trait]
+pub trait KmsClient {
+    /// Create a key
+    async fn create_key(&self) -> Result<SigningSecretKeyHandle>;
+
+    /// Delete a key
+    async fn delete_key(&self, key: &SigningSecretKeyHandle) -> Result<bool>;
+
+    /// Return the public key of a key
+    async fn public_key(&self, key: &SigningSecretKeyHandle) -> Result<VerifyingPublicKey>;
+
+    /// Return the list of keys available at startup
+    async fn list_keys(&self) -> Result<Vec<SigningSecretKeyHandle>>;
+
+    /// Sign a message
+    async fn sign(&self, key: &SigningSecretKeyHandle, message: &[u8]) -> Result<Signature>;
+}
+
+#[async_trait]
+impl KmsClient for HashicorpVaultClient {
+    async fn create_key(&self) -> Result<SigningSecretKeyHandle> {
+        self.create_key().await
+    }
+
+    async fn delete_key(&self, key: &SigningSecretKeyHandle) -> Result<bool> {
+        self.delete_key(key).await
+    }
+
+    async fn public_key(&self, key: &SigningSecretKeyHandle) -> Result<VerifyingPublicKey> {
+        self.public_key(key).await
+    }
+
+    async fn list_keys(&self) -> Result<Vec<SigningSecretKeyHandle>> {
+        match &self.config.initial_keys_discovery {
+            InitialKeysDiscovery::ListFromHashicorpVault => self.list_key_names().await,
+            InitialKeysDiscovery::Keys(keys) => Ok(keys.clone()),
+        }
+    }
+
+    async fn sign(&self, key: &SigningSecretKeyHandle, message: &[u8]) -> Result<Signature> {
+        self.sign(key, message).await
+    }
+}
+
+/// Parse a Transit signature: `vault:v{version}:{base64 signature}`
+fn parse_signature(signature: &str) -> Result<EdDSACurve25519Signature> {
+    let encoded = signature
+        .strip_prefix("vault:")
+        .and_then(|s| s.split_once(':'))
+        .map(|(_version, encoded)| encoded)
+        .ok_or(Error::InvalidSignature)?;
+    let signature = BASE64
+        .decode(encoded)
+        .map_err(|_| Error::InvalidSignature)?;
+    Ok(EdDSACurve25519Signature(
+        signature.try_into().map_err(|_| Error::InvalidSignature)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_signature() {
+        let signature = BASE64.encode([7u8; 64]);
+        let parsed = parse_signature(&format!("vault:v1:{signature}")).unwrap();
+        assert_eq!(parsed.0, [7u8; 64]);
+
+        assert!(parse_signature(&signature).is_err());
+        assert!(parse_signature("vault:v1:AQID").is_err());
+    }
+
+    #[test]
+    fn test_token_renewal() {
+        assert!(!VaultToken::new("t".into(), 0, false).needs_renewal());
+        assert!(!VaultToken::new("t".into(), 3600, true).needs_renewal());
+        assert!(VaultToken::new("t".into(), 30, true).needs_renewal());
+    }
+}
@@ -0,0 +1,18 @@
+//! HashiCorp Vault Transit implementation of the ockam_vault signing traits
+//!
+#![deny(unsafe_code)]
+#![warn(
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_import_braces,
+    unused_qualifications
+)]
+
+mod error;
+mod hashicorp_signing_vault;
+mod hashicorp_vault_client;
+
+pub use error::*;
+pub use hashicorp_signing_vault::*;
+pub use hashicorp_vault_client::*;
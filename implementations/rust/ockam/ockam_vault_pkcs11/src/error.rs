@@ -0,0 +1,39 @@
+use ockam_core::errcode::{Kind, Origin};
+use thiserror::Error;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("pkcs11 error loading the library {0}")]
+    Library(String),
+    #[error("pkcs11 error opening a session")]
+    Session(String),
+    #[error("pkcs11 token not found in slot {0}")]
+    SlotNotFound(u64),
+    #[error("pkcs11 error creating new key")]
+    Create(String),
+    #[error("pkcs11 error signing message with key {keyid}")]
+    Sign { keyid: String, error: String },
+    #[error("pkcs11 error exporting public key {keyid}")]
+    Export { keyid: String, error: String },
+    #[error("pkcs11 error destroying key {keyid}")]
+    Delete { keyid: String, error: String },
+    #[error("pkcs11 error listing the existing keys")]
+    MissingKeys(String),
+    #[error("missing pkcs11 configuration {0}")]
+    MissingConfiguration(String),
+    #[error("public key is incorrect")]
+    InvalidPublicKey,
+    #[error("signature is incorrect")]
+    InvalidSignature,
+    #[error("key was not found")]
+    KeyNotFound,
+    #[error("invalid handle")]
+    InvalidHandle,
+}
+
+impl From<Error> for ockam_core::Error {
+    fn from(e: Error) -> Self {
+        ockam_core::Error::new(Origin::Other, Kind::Io, e)
+    }
+}
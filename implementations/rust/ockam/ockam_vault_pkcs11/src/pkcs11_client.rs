@@ -0,0 +1,420 @@
+use crate::error::Error;
+use cryptoki::context::{CInitializeArgs, Pkcs11};
+use cryptoki::mechanism::Mechanism;
+use cryptoki::object::{Attribute, AttributeType, KeyType, ObjectClass, ObjectHandle};
+use cryptoki::session::{Session, UserType};
+use cryptoki::types::AuthPin;
+use ockam_core::compat::rand::{thread_rng, RngCore};
+use ockam_core::compat::sync::{Arc, Mutex};
+use ockam_core::{async_trait, Result};
+use ockam_vault::{
+    ECDSASHA256CurveP256PublicKey, ECDSASHA256CurveP256Signature, HandleToSecret, Signature,
+    SigningSecretKeyHandle, VerifyingPublicKey,
+};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use tracing as log;
+
+/// Environment variable containing the path of the PKCS#11 library of the token
+pub const PKCS11_LIBRARY_ENV: &str = "PKCS11_LIBRARY";
+/// Environment variable containing the slot of the token
+pub const PKCS11_SLOT_ENV: &str = "PKCS11_SLOT";
+/// Environment variable containing the user PIN of the token
+pub const PKCS11_PIN_ENV: &str = "PKCS11_PIN";
+
+/// Default prefix of the labels of the keys created by the vault
+pub const DEFAULT_KEY_LABEL_PREFIX: &str = "ockam-";
+
+/// DER encoding of the OID of the NIST P-256 curve
+const P256_EC_PARAMS: [u8; 10] = [0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+
+/// PKCS#11 client, using a logged in session on a token
+#[derive(Clone)]
+pub struct Pkcs11Client {
+    // PKCS#11 sessions can't be used concurrently
+    session: Arc<Mutex<Session>>,
+    config: Pkcs11Config,
+}
+
+/// Key pairs of the token known by a [`Pkcs11SigningVault`](crate::Pkcs11SigningVault) when it is created
+#[derive(Debug, Clone)]
+pub enum InitialKeysDiscovery {
+    /// Use all the P-256 private keys of the token whose label starts with the key label prefix
+    ListFromToken,
+
+    /// Use a specific set of key labels
+    Keys(Vec<SigningSecretKeyHandle>),
+}
+
+/// PKCS#11 configuration.
+#[derive(Clone)]
+pub struct Pkcs11Config {
+    library_path: PathBuf,
+    slot: Option<u64>,
+    pin: String,
+    key_label_prefix: String,
+    initial_keys_discovery: InitialKeysDiscovery,
+}
+
+impl core::fmt::Debug for Pkcs11Config {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Pkcs11Config")
+            .field("library_path", &self.library_path)
+            .field("slot", &self.slot)
+            .field("pin", &"<redacted>")
+            .field("key_label_prefix", &self.key_label_prefix)
+            .field("initial_keys_discovery", &self.initial_keys_discovery)
+            .finish()
+    }
+}
+
+impl Pkcs11Config {
+    /// Create a new configuration for the token accessed with a given PKCS#11 library,
+    /// for example `/usr/lib/softhsm/libsofthsm2.so`.
+    /// The first slot containing a token is used, unless a slot is specified
+    pub fn new(library_path: impl Into<PathBuf>, pin: impl Into<String>) -> Pkcs11Config {
+        Pkcs11Config {
+            library_path: library_path.into(),
+            slot: None,
+            pin: pin.into(),
+            key_label_prefix: DEFAULT_KEY_LABEL_PREFIX.to_string(),
+            initial_keys_discovery: InitialKeysDiscovery::ListFromToken,
+        }
+    }
+
+    /// Create a configuration from the `PKCS11_LIBRARY`, `PKCS11_PIN`
+    /// and optional `PKCS11_SLOT` environment variables
+    pub fn from_env() -> Result<Pkcs11Config> {
+        let var = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .filter(|v| !v.is_empty())
+                .ok_or(Error::MissingConfiguration(name.to_string()))
+        };
+        let config = Self::new(var(PKCS11_LIBRARY_ENV)?, var(PKCS11_PIN_ENV)?);
+        Ok(match var(PKCS11_SLOT_ENV) {
+            Ok(slot) => config.with_slot(
+                slot.parse()
+                    .map_err(|_| Error::MissingConfiguration(PKCS11_SLOT_ENV.to_string()))?,
+            ),
+            Err(_) => config,
+        })
+    }
+
+    /// Use the token of a specific slot
+    pub fn with_slot(mut self, slot: u64) -> Self {
+        self.slot = Some(slot);
+        self
+    }
+
+    /// Set the prefix of the labels of the created keys
+    pub fn with_key_label_prefix(mut self, key_label_prefix: impl Into<String>) -> Self {
+        self.key_label_prefix = key_label_prefix.into();
+        self
+    }
+
+    /// Configure initial key discovery
+    pub fn with_initial_keys_discovery(self, initial_keys_discovery: InitialKeysDiscovery) -> Self {
+        Self {
+            initial_keys_discovery,
+            ..self
+        }
+    }
+}
+
+impl Pkcs11Client {
+    /// Load the PKCS#11 library and open a logged in session on the configured token
+    pub async fn new(config: Pkcs11Config) -> Result<Pkcs11Client> {
+        let pkcs11 = Pkcs11::new(&config.library_path)
+            .map_err(|err| Error::Library(format!("{:?}: {err}", config.library_path)))?;
+        pkcs11
+            .initialize(CInitializeArgs::OsThreads)
+            .map_err(|err| Error::Library(err.to_string()))?;
+
+        let slots = pkcs11
+            .get_slots_with_token()
+            .map_err(|err| Error::Session(err.to_string()))?;
+        let slot = match config.slot {
+            Some(id) => slots
+                .into_iter()
+                .find(|slot| slot.id() == id)
+                .ok_or(Error::SlotNotFound(id))?,
+            None => slots.into_iter().next().ok_or(Error::SlotNotFound(0))?,
+        };
+        log::debug!(slot = slot.id(), "open a pkcs11 session");
+
+        let session = pkcs11
+            .open_rw_session(slot)
+            .map_err(|err| Error::Session(err.to_string()))?;
+        session
+            .login(UserType::User, Some(&AuthPin::new(config.pin.clone())))
+            .map_err(|err| Error::Session(err.to_string()))?;
+
+        Ok(Self {
+            session: Arc::new(Mutex::new(session)),
+            config,
+        })
+    }
+
+    /// A handle is the label of a key pair
+    fn cast_handle_to_kid(handle: &SigningSecretKeyHandle) -> Result<String> {
+        let handle = match handle {
+            SigningSecretKeyHandle::EdDSACurve25519(_) => return Err(Error::InvalidHandle)?,
+            SigningSecretKeyHandle::ECDSASHA256CurveP256(handle) => handle.value().clone(),
+        };
+
+        let kid = String::from_utf8(handle).map_err(|_| Error::InvalidHandle)?;
+
+        Ok(kid)
+    }
+
+    fn kid_to_handle(kid: &str) -> SigningSecretKeyHandle {
+        SigningSecretKeyHandle::ECDSASHA256CurveP256(HandleToSecret::new(kid.as_bytes().to_vec()))
+    }
+
+    /// Find the object of a given class with a given label
+    fn find_object(session: &Session, class: ObjectClass, kid: &str) -> Result<ObjectHandle> {
+        let objects = session
+            .find_objects(&[
+                Attribute::Class(class),
+                Attribute::Label(kid.as_bytes().to_vec()),
+            ])
+            .map_err(|err| Error::Export {
+                keyid: kid.to_string(),
+                error: err.to_string(),
+            })?;
+        Ok(objects.into_iter().next().ok_or(Error::KeyNotFound)?)
+    }
+
+    /// Generate a new non-extractable NIST P-256 key pair on the token and return its label.
+    pub async fn create_key(&self) -> Result<SigningSecretKeyHandle> {
+        log::trace!("create new key");
+        let kid = format!(
+            "{}{:016x}",
+            self.config.key_label_prefix,
+            thread_rng().next_u64()
+        );
+        let label = kid.as_bytes().to_vec();
+
+        let public_key_template = vec![
+            Attribute::Token(true),
+            Attribute::Verify(true),
+            Attribute::EcParams(P256_EC_PARAMS.to_vec()),
+            Attribute::Label(label.clone()),
+            Attribute::Id(label.clone()),
+        ];
+        let private_key_template = vec![
+            Attribute::Token(true),
+            Attribute::Private(true),
+            Attribute::Sensitive(true),
+            Attribute::Extractable(false),
+            Attribute::Sign(true),
+            Attribute::Label(label.clone()),
+            Attribute::Id(label),
+        ];
+
+        let session = self.session.lock().unwrap();
+        session
+            .generate_key_pair(
+                &Mechanism::EccKeyPairGen,
+                &public_key_template,
+                &private_key_template,
+            )
+            .map_err(|err| {
+                log::error!(%err, "failed to create new key");
+                Error::Create(err.to_string())
+            })?;
+        log::debug!(%kid, "created new key");
+        Ok(Self::kid_to_handle(&kid))
+    }
+
+    /// Destroy a key pair.
+    pub async fn delete_key(&self, key: &SigningSecretKeyHandle) -> Result<bool> {
+        let kid = Self::cast_handle_to_kid(key)?;
+        log::trace!(%kid, "destroy key");
+        let session = self.session.lock().unwrap();
+        let mut found = false;
+        for class in [ObjectClass::PRIVATE_KEY, ObjectClass::PUBLIC_KEY] {
+            let object = match Self::find_object(&session, class, &kid) {
+                Ok(object) => object,
+                Err(_) => continue,
+            };
+            session.destroy_object(object).map_err(|err| {
+                log::error!(%kid, %err, "failed to destroy key");
+                Error::Delete {
+                    keyid: kid.clone(),
+                    error: err.to_string(),
+                }
+            })?;
+            found = true;
+        }
+        if found {
+            log::debug!(%kid, "key destroyed");
+        } else {
+            log::debug!(%kid, "key does not exist");
+        }
+        Ok(found)
+    }
+
+    /// Get the public key of a key pair.
+    pub async fn public_key(&self, key: &SigningSecretKeyHandle) -> Result<VerifyingPublicKey> {
+        let kid = Self::cast_handle_to_kid(key)?;
+        log::trace!(%kid, "get public key");
+        let session = self.session.lock().unwrap();
+        let object = Self::find_object(&session, ObjectClass::PUBLIC_KEY, &kid)?;
+        let attributes = session
+            .get_attributes(object, &[AttributeType::EcParams, AttributeType::EcPoint])
+            .map_err(|err| {
+                log::error!(%kid, %err, "failed to get public key");
+                Error::Export {
+                    keyid: kid.clone(),
+                    error: err.to_string(),
+                }
+            })?;
+
+        let mut ec_point = None;
+        for attribute in attributes {
+            match attribute {
+                Attribute::EcParams(params) if params != P256_EC_PARAMS => {
+                    log::error!(%kid, "curve not supported to get a public key");
+                    return Err(Error::InvalidPublicKey)?;
+                }
+                Attribute::EcPoint(point) => ec_point = Some(point),
+                _ => (),
+            }
+        }
+        let public_key = parse_ec_point(&ec_point.ok_or(Error::InvalidPublicKey)?)?;
+        log::debug!(%kid, "received public key");
+        Ok(VerifyingPublicKey::ECDSASHA256CurveP256(public_key))
+    }
+
+    /// Sign the SHA-256 digest of a message with the private key of a key pair.
+    pub async fn sign(&self, key: &SigningSecretKeyHandle, message: &[u8]) -> Result<Signature> {
+        let kid = Self::cast_handle_to_kid(key)?;
+        log::trace!(%kid, "sign message");
+        let session = self.session.lock().unwrap();
+        let object = Self::find_object(&session, ObjectClass::PRIVATE_KEY, &kid)?;
+        // CKM_ECDSA signs a digest and returns the raw r || s signature
+        let signature = session
+            .sign(&Mechanism::Ecdsa, object, &Sha256::digest(message))
+            .map_err(|err| {
+                log::error!(%kid, %err, "failed to sign message");
+                Error::Sign {
+                    keyid: kid.clone(),
+                    error: err.to_string(),
+                }
+            })?;
+        log::debug!(%kid, "signed message");
+        let signature = ECDSASHA256CurveP256Signature(
+            signature.try_into().map_err(|_| Error::InvalidSignature)?,
+        );
+        Ok(Signature::ECDSASHA256CurveP256(signature))
+    }
+
+    /// List the labels of the P-256 private keys having the configured label prefix
+    fn list_key_labels(&self) -> Result<Vec<SigningSecretKeyHandle>> {
+        let session = self.session.lock().unwrap();
+        let objects = session
+            .find_objects(&[
+                Attribute::Class(ObjectClass::PRIVATE_KEY),
+                Attribute::KeyType(KeyType::EC),
+            ])
+            .map_err(|err| Error::MissingKeys(err.to_string()))?;
+
+        let mut result = vec![];
+        for object in objects {
+            let attributes = session
+                .get_attributes(object, &[AttributeType::Label])
+                .map_err(|err| Error::MissingKeys(err.to_string()))?;
+            for attribute in attributes {
+                if let Attribute::Label(label) = attribute {
+                    match String::from_utf8(label) {
+                        Ok(kid) if kid.starts_with(&self.config.key_label_prefix) => {
+                            result.push(Self::kid_to_handle(&kid))
+                        }
+                        _ => (),
+                    }
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// This trait is introduced to help with the testing of the Pkcs11SigningVault
+#[async_trait]
+pub trait KmsClient {
+    /// Create a key
+    async fn create_key(&self) -> Result<SigningSecretKeyHandle>;
+
+    /// Delete a key
+    async fn delete_key(&self, key: &SigningSecretKeyHandle) -> Result<bool>;
+
+    /// Return the public key of a key
+    async fn public_key(&self, key: &SigningSecretKeyHandle) -> Result<VerifyingPublicKey>;
+
+    /// Return the list of keys available at startup
+    async fn list_keys(&self) -> Result<Vec<SigningSecretKeyHandle>>;
+
+    /// Sign a message
+    async fn sign(&self, key: &SigningSecretKeyHandle, message: &[u8]) -> Result<Signature>;
+}
+
+#[async_trait]
+impl KmsClient for Pkcs11Client {
+    async fn create_key(&self) -> Result<SigningSecretKeyHandle> {
+        self.create_key().await
+    }
+
+    async fn delete_key(&self, key: &SigningSecretKeyHandle) -> Result<bool> {
+        self.delete_key(key).await
+    }
+
+    async fn public_key(&self, key: &SigningSecretKeyHandle) -> Result<VerifyingPublicKey> {
+        self.public_key(key).await
+    }
+
+    async fn list_keys(&self) -> Result<Vec<SigningSecretKeyHandle>> {
+        match &self.config.initial_keys_discovery {
+            InitialKeysDiscovery::ListFromToken => self.list_key_labels(),
+            InitialKeysDiscovery::Keys(keys) => Ok(keys.clone()),
+        }
+    }
+
+    async fn sign(&self, key: &SigningSecretKeyHandle, message: &[u8]) -> Result<Signature> {
+        self.sign(key, message).await
+    }
+}
+
+/// Parse a CKA_EC_POINT value: an uncompressed point, usually wrapped in a DER octet string
+fn parse_ec_point(ec_point: &[u8]) -> Result<ECDSASHA256CurveP256PublicKey> {
+    let point = match ec_point {
+        [0x04, 0x41, point @ ..] if point.len() == 65 => point,
+        point if point.len() == 65 => point,
+        _ => return Err(Error::InvalidPublicKey)?,
+    };
+    Ok(ECDSASHA256CurveP256PublicKey(
+        point.try_into().map_err(|_| Error::InvalidPublicKey)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ec_point() {
+        let mut point = vec![0x04];
+        point.extend([1u8; 64]);
+
+        let raw = parse_ec_point(&point).unwrap();
+        assert_eq!(raw.0.to_vec(), point);
+
+        let mut wrapped = vec![0x04, 0x41];
+        wrapped.extend(point.clone());
+        let parsed = parse_ec_point(&wrapped).unwrap();
+        assert_eq!(parsed, raw);
+
+        assert!(parse_ec_point(&point[1..]).is_err());
+    }
+}
@@ -0,0 +1,167 @@
+use crate::error::Error;
+use crate::pkcs11_client::{KmsClient, Pkcs11Client, Pkcs11Config};
+use ockam_core::compat::sync::{Arc, RwLock};
+use ockam_core::{async_trait, Result};
+use ockam_vault::{
+    Signature, SigningKeyType, SigningSecretKeyHandle, VaultError, VaultForSigning,
+    VerifyingPublicKey,
+};
+use tracing::error;
+
+struct Pkcs11KeyPair {
+    key: SigningSecretKeyHandle,
+    public_key: VerifyingPublicKey,
+}
+
+/// Security module implementation using a PKCS#11 token, for example an HSM or a smartcard
+pub struct Pkcs11SigningVault {
+    client: Arc<dyn KmsClient + Send + Sync>,
+    // Labels of the key pairs of the token with their public keys. The public key object
+    // of each key pair is read once, when the vault is created or when a key pair is
+    // generated, and then cached here to avoid taking the session lock for each lookup.
+    // The private keys never leave the token.
+    keys: Arc<RwLock<Vec<Pkcs11KeyPair>>>,
+}
+
+impl Pkcs11SigningVault {
+    /// Create a PKCS#11 security module configured with the
+    /// `PKCS11_LIBRARY`, `PKCS11_PIN` and `PKCS11_SLOT` environment variables
+    pub async fn create() -> Result<Self> {
+        Self::create_with_config(Pkcs11Config::from_env()?).await
+    }
+
+    /// Create a new PKCS#11 security module
+    pub async fn create_with_config(config: Pkcs11Config) -> Result<Self> {
+        let client = Pkcs11Client::new(config).await?;
+
+        let mut key_pairs: Vec<Pkcs11KeyPair> = vec![];
+        // List the labels of the private keys of the token, then read the public key of each one
+        let keys = client.list_keys().await?;
+
+        for key in keys {
+            match client.public_key(&key).await {
+                Ok(public_key) => key_pairs.push(Pkcs11KeyPair { key, public_key }),
+                // The private key may have no public key object with the same label on
+                // the token, or its public key may not be on the P-256 curve. Skip it
+                Err(err) => error!("Error exporting public key: {err}"),
+            }
+        }
+
+        Ok(Self {
+            client: Arc::new(client),
+            keys: Arc::new(RwLock::new(key_pairs)),
+        })
+    }
+
+    /// Return list of all keys
+    pub fn keys(&self) -> Vec<SigningSecretKeyHandle> {
+        self.keys
+            .read()
+            .unwrap()
+            .iter()
+            .map(|x| x.key.clone())
+            .collect()
+    }
+
+    /// Return number of keys
+    pub async fn number_of_keys(&self) -> Result<usize> {
+        Ok(self.keys.read().unwrap().len())
+    }
+}
+
+#[async_trait]
+impl VaultForSigning for Pkcs11SigningVault {
+    async fn sign(
+        &self,
+        signing_secret_key_handle: &SigningSecretKeyHandle,
+        data: &[u8],
+    ) -> Result<Signature> {
+        self.client.sign(signing_secret_key_handle, data).await
+    }
+
+    async fn generate_signing_secret_key(
+        &self,
+        signing_key_type: SigningKeyType,
+    ) -> Result<SigningSecretKeyHandle> {
+        if signing_key_type != SigningKeyType::ECDSASHA256CurveP256 {
+            return Err(VaultError::InvalidKeyType)?;
+        }
+
+        let key = self.client.create_key().await?;
+        let public_key = self.client.public_key(&key).await?;
+
+        self.keys.write().unwrap().push(Pkcs11KeyPair {
+            key: key.clone(),
+            public_key,
+        });
+
+        Ok(key)
+    }
+
+    async fn get_verifying_public_key(
+        &self,
+        signing_secret_key_handle: &SigningSecretKeyHandle,
+    ) -> Result<VerifyingPublicKey> {
+        let cached = self.keys.read().unwrap().iter().find_map(|x| {
+            if &x.key == signing_secret_key_handle {
+                Some(x.public_key.clone())
+            } else {
+                None
+            }
+        });
+        if let Some(public_key) = cached {
+            return Ok(public_key);
+        }
+
+        // The key might have been created after the vault initialization, for example by
+        // another node sharing the same token. Fetch its public key once and cache it
+        let public_key = self
+            .client
+            .public_key(signing_secret_key_handle)
+            .await
+            .map_err(|err| {
+                error!("Error exporting public key: {err}");
+                Error::KeyNotFound
+            })?;
+        self.keys.write().unwrap().push(Pkcs11KeyPair {
+            key: signing_secret_key_handle.clone(),
+            public_key: public_key.clone(),
+        });
+
+        Ok(public_key)
+    }
+
+    async fn get_secret_key_handle(
+        &self,
+        verifying_public_key: &VerifyingPublicKey,
+    ) -> Result<SigningSecretKeyHandle> {
+        self.keys
+            .read()
+            .unwrap()
+            .iter()
+            .find_map(|x| {
+                if &x.public_key == verifying_public_key {
+                    Some(x.key.clone())
+                } else {
+                    None
+                }
+            })
+            .ok_or(Error::KeyNotFound.into())
+    }
+
+    async fn delete_signing_secret_key(
+        &self,
+        signing_secret_key_handle: SigningSecretKeyHandle,
+    ) -> Result<bool> {
+        if self.client.delete_key(&signing_secret_key_handle).await? {
+            self.keys
+                .write()
+                .unwrap()
+                .retain(|x| x.key != signing_secret_key_handle);
+
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use ockam_core::errcode::{Kind, Origin};
+use thiserror::Error;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("no secure key storage is available on this platform: {0}")]
+    Unavailable(String),
+    #[error("the secure key storage of this platform can't be used: {0}")]
+    Misconfigured(String),
+    #[error("error creating new key in the secure key storage")]
+    Create(String),
+    #[error("error signing message with key {keyid}")]
+    Sign { keyid: String, error: String },
+    #[error("error deleting key {keyid}")]
+    Delete { keyid: String, error: String },
+    #[error("public key is incorrect")]
+    InvalidPublicKey,
+    #[error("signature is incorrect")]
+    InvalidSignature,
+    #[error("key was not found")]
+    KeyNotFound,
+    #[error("invalid handle")]
+    InvalidHandle,
+}
+
+impl From<Error> for ockam_core::Error {
+    fn from(e: Error) -> Self {
+        ockam_core::Error::new(Origin::Other, Kind::Io, e)
+    }
+}
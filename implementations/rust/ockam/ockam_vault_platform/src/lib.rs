@@ -0,0 +1,38 @@
+//! Signing vault storing non-exportable keys in the secure hardware of the platform:
+//!
+//!  - the Apple Secure Enclave on macOS
+//!  - a TPM 2.0 on Linux, accessed with the tpm2-pkcs11 library
+//!  - a TPM 2.0 on Windows, accessed with the Microsoft Platform Crypto Provider
+//!
+//! When no secure hardware is available, a fallback vault can be used instead.
+//! Secure hardware which is present but can't be used is an error, unless the fallback
+//! is explicitly requested.
+//!
+#![deny(unsafe_code)]
+#![warn(
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_import_braces,
+    unused_qualifications
+)]
+
+mod error;
+mod platform_signing_vault;
+#[cfg(target_os = "macos")]
+mod secure_enclave_signing_vault;
+#[cfg(target_os = "linux")]
+mod tpm_signing_vault;
+// NCrypt is a C API
+#[cfg(windows)]
+#[allow(unsafe_code)]
+mod windows_tpm_signing_vault;
+
+pub use error::*;
+pub use platform_signing_vault::*;
+#[cfg(target_os = "macos")]
+pub use secure_enclave_signing_vault::*;
+#[cfg(target_os = "linux")]
+pub use tpm_signing_vault::*;
+#[cfg(windows)]
+pub use windows_tpm_signing_vault::*;
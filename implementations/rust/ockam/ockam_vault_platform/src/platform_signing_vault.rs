@@ -0,0 +1,185 @@
+use ockam_core::compat::sync::Arc;
+use ockam_core::{async_trait, Result};
+use ockam_vault::{
+    Signature, SigningKeyType, SigningSecretKeyHandle, VaultForSigning, VerifyingPublicKey,
+};
+use tracing::{debug, info, warn};
+
+use crate::Error;
+
+/// Environment variable containing the PIN protecting the keys stored in a TPM
+pub const TPM_PIN_ENV: &str = "OCKAM_TPM_PIN";
+
+/// Return the PIN protecting the keys stored in a TPM
+pub(crate) fn tpm_pin() -> core::result::Result<String, Error> {
+    std::env::var(TPM_PIN_ENV)
+        .ok()
+        .filter(|pin| !pin.is_empty())
+        .ok_or_else(|| Error::Misconfigured(format!("{TPM_PIN_ENV} is not set")))
+}
+
+/// Storage used for the keys of a [`PlatformSigningVault`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlatformKeyStorage {
+    /// Keys are stored in the Apple Secure Enclave
+    SecureEnclave,
+    /// Keys are stored in a TPM 2.0
+    Tpm,
+    /// Keys are stored in a TPM 2.0 through the Windows Platform Crypto Provider
+    WindowsTpm,
+    /// No secure hardware is available, keys are stored by a fallback vault
+    Fallback,
+}
+
+impl PlatformKeyStorage {
+    /// Return true if the keys are bound to the secure hardware of the platform
+    pub fn is_hardware_backed(&self) -> bool {
+        !matches!(self, PlatformKeyStorage::Fallback)
+    }
+}
+
+/// When a [`PlatformSigningVault`] can store its keys in a fallback vault
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlatformFallback {
+    /// Only when the platform has no secure hardware. Secure hardware which is present but
+    /// can't be used, for example because its PIN is not set, is an error
+    NoHardware,
+    /// Whenever the keys can't be stored in the secure hardware
+    Always,
+}
+
+/// Signing vault using the secure hardware of the platform, when there is one.
+///
+/// The keys created in the secure hardware are NIST P-256 keys which can't be exported.
+pub struct PlatformSigningVault {
+    vault: Arc<dyn VaultForSigning>,
+    storage: PlatformKeyStorage,
+}
+
+impl PlatformSigningVault {
+    /// Create a vault using the secure hardware of the platform,
+    /// or return an error if there is none
+    pub async fn create() -> Result<Self> {
+        Ok(Self::try_create().await?)
+    }
+
+    /// Create a vault using the secure hardware of the platform, returning
+    /// [`Error::Unavailable`] if there is none, and [`Error::Misconfigured`] if it can't be used
+    #[allow(clippy::needless_return)]
+    async fn try_create() -> core::result::Result<Self, Error> {
+        #[cfg(target_os = "macos")]
+        {
+            let vault = crate::SecureEnclaveSigningVault::create()
+                .await
+                .map_err(|err| Error::Unavailable(err.to_string()))?;
+            return Ok(Self {
+                vault: Arc::new(vault),
+                storage: PlatformKeyStorage::SecureEnclave,
+            });
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            let vault = crate::try_create_tpm_signing_vault().await?;
+            return Ok(Self {
+                vault: Arc::new(vault),
+                storage: PlatformKeyStorage::Tpm,
+            });
+        }
+
+        #[cfg(windows)]
+        {
+            let vault = crate::WindowsTpmSigningVault::try_create()?;
+            return Ok(Self {
+                vault: Arc::new(vault),
+                storage: PlatformKeyStorage::WindowsTpm,
+            });
+        }
+
+        #[cfg(not(any(target_os = "macos", target_os = "linux", windows)))]
+        {
+            return Err(Error::Unavailable(
+                "this platform is not supported".to_string(),
+            ));
+        }
+    }
+
+    /// Create a vault using the secure hardware of the platform, or use the `fallback` vault
+    /// when the secure hardware can't be used and the `when` policy allows it.
+    /// Otherwise an error is returned
+    pub async fn create_or_fallback(
+        fallback: Arc<dyn VaultForSigning>,
+        when: PlatformFallback,
+    ) -> Result<Self> {
+        match Self::try_create().await {
+            Ok(vault) => {
+                debug!("keys are stored with {:?}", vault.storage);
+                Ok(vault)
+            }
+            Err(err @ Error::Misconfigured(_)) if when != PlatformFallback::Always => {
+                Err(err.into())
+            }
+            Err(err) => {
+                if matches!(err, Error::Misconfigured(_)) {
+                    warn!("keys can't be stored in secure hardware, using a fallback vault: {err}");
+                } else {
+                    info!("keys can't be stored in secure hardware, using a fallback vault: {err}");
+                }
+                Ok(Self {
+                    vault: fallback,
+                    storage: PlatformKeyStorage::Fallback,
+                })
+            }
+        }
+    }
+
+    /// Return the storage used for the keys
+    pub fn storage(&self) -> PlatformKeyStorage {
+        self.storage
+    }
+}
+
+#[async_trait]
+impl VaultForSigning for PlatformSigningVault {
+    async fn sign(
+        &self,
+        signing_secret_key_handle: &SigningSecretKeyHandle,
+        data: &[u8],
+    ) -> Result<Signature> {
+        self.vault.sign(signing_secret_key_handle, data).await
+    }
+
+    async fn generate_signing_secret_key(
+        &self,
+        signing_key_type: SigningKeyType,
+    ) -> Result<SigningSecretKeyHandle> {
+        self.vault
+            .generate_signing_secret_key(signing_key_type)
+            .await
+    }
+
+    async fn get_verifying_public_key(
+        &self,
+        signing_secret_key_handle: &SigningSecretKeyHandle,
+    ) -> Result<VerifyingPublicKey> {
+        self.vault
+            .get_verifying_public_key(signing_secret_key_handle)
+            .await
+    }
+
+    async fn get_secret_key_handle(
+        &self,
+        verifying_public_key: &VerifyingPublicKey,
+    ) -> Result<SigningSecretKeyHandle> {
+        self.vault.get_secret_key_handle(verifying_public_key).await
+    }
+
+    async fn delete_signing_secret_key(
+        &self,
+        signing_secret_key_handle: SigningSecretKeyHandle,
+    ) -> Result<bool> {
+        self.vault
+            .delete_signing_secret_key(signing_secret_key_handle)
+            .await
+    }
+}
@@ -0,0 +1,257 @@
+use crate::Error;
+use ockam_core::compat::sync::{Arc, RwLock};
+use ockam_core::{async_trait, Result};
+use ockam_vault::{
+    ECDSASHA256CurveP256PublicKey, ECDSASHA256CurveP256Signature, HandleToSecret, Signature,
+    SigningKeyType, SigningSecretKeyHandle, VaultError, VaultForSigning, VerifyingPublicKey,
+};
+use security_framework::item::{
+    ItemClass, ItemSearchOptions, Limit, Location, Reference, SearchResult,
+};
+use security_framework::key::{Algorithm, GenerateKeyOptions, KeyType, SecKey, Token};
+use tracing::{debug, error};
+
+/// Label prefix of the keys created in the Secure Enclave
+const SECURE_ENCLAVE_KEY_LABEL_PREFIX: &str = "ockam-se-";
+
+struct SecureEnclaveKeyPair {
+    key: SigningSecretKeyHandle,
+    public_key: VerifyingPublicKey,
+}
+
+/// Security module implementation using the Apple Secure Enclave
+///
+/// The private keys are generated inside the Secure Enclave and never leave it.
+/// A key handle is the label of the key in the data protection keychain.
+pub struct SecureEnclaveSigningVault {
+    // Store mapping from PublicKey to key label in memory
+    // This is fetched at the Vault initialization
+    // and is updated locally during add/delete operations
+    keys: Arc<RwLock<Vec<SecureEnclaveKeyPair>>>,
+}
+
+impl SecureEnclaveSigningVault {
+    /// Create a Secure Enclave security module.
+    /// An error is returned if the Secure Enclave can't be used, for example if there is
+    /// no Secure Enclave or if the application is not entitled to use the keychain
+    pub async fn create() -> Result<Self> {
+        let vault = Self {
+            keys: Arc::new(RwLock::new(vec![])),
+        };
+
+        // Check that keys can be created and deleted
+        let probe = vault.create_key(&format!("{SECURE_ENCLAVE_KEY_LABEL_PREFIX}probe"))?;
+        probe
+            .delete()
+            .map_err(|err| Error::Unavailable(format!("{err:?}")))?;
+
+        for label in Self::list_labels() {
+            match Self::find_key(&label).and_then(|key| Self::public_key(&key)) {
+                Ok(public_key) => vault.keys.write().unwrap().push(SecureEnclaveKeyPair {
+                    key: Self::label_to_handle(&label),
+                    public_key,
+                }),
+                Err(err) => error!("Error exporting public key: {err}"),
+            }
+        }
+        Ok(vault)
+    }
+
+    /// Return list of all keys
+    pub fn keys(&self) -> Vec<SigningSecretKeyHandle> {
+        self.keys
+            .read()
+            .unwrap()
+            .iter()
+            .map(|x| x.key.clone())
+            .collect()
+    }
+
+    fn label_to_handle(label: &str) -> SigningSecretKeyHandle {
+        SigningSecretKeyHandle::ECDSASHA256CurveP256(HandleToSecret::new(label.as_bytes().to_vec()))
+    }
+
+    fn handle_to_label(handle: &SigningSecretKeyHandle) -> Result<String> {
+        let handle = match handle {
+            SigningSecretKeyHandle::EdDSACurve25519(_) => return Err(Error::InvalidHandle)?,
+            SigningSecretKeyHandle::ECDSASHA256CurveP256(handle) => handle.value().clone(),
+        };
+        Ok(String::from_utf8(handle).map_err(|_| Error::InvalidHandle)?)
+    }
+
+    fn create_key(&self, label: &str) -> Result<SecKey> {
+        let mut options = GenerateKeyOptions::default();
+        options
+            .set_key_type(KeyType::ec())
+            .set_size_in_bits(256)
+            .set_label(label)
+            .set_token(Token::SecureEnclave)
+            .set_location(Location::DataProtectionKeychain);
+        Ok(SecKey::new(&options).map_err(|err| Error::Unavailable(format!("{err:?}")))?)
+    }
+
+    /// Return the labels of the keys created by this vault
+    fn list_labels() -> Vec<String> {
+        let results = ItemSearchOptions::new()
+            .class(ItemClass::key())
+            .ignore_legacy_keychains()
+            .load_attributes(true)
+            .limit(Limit::All)
+            .search()
+            .unwrap_or_default();
+        results
+            .iter()
+            .filter_map(|result| result.simplify_dict())
+            .filter_map(|attributes| attributes.get("labl").cloned())
+            .filter(|label| label.starts_with(SECURE_ENCLAVE_KEY_LABEL_PREFIX))
+            .collect()
+    }
+
+    fn find_key(label: &str) -> Result<SecKey> {
+        let results = ItemSearchOptions::new()
+            .class(ItemClass::key())
+            .ignore_legacy_keychains()
+            .label(label)
+            .load_refs(true)
+            .search()
+            .map_err(|_| Error::KeyNotFound)?;
+        Ok(results
+            .into_iter()
+            .find_map(|result| match result {
+                SearchResult::Ref(Reference::Key(key)) => Some(key),
+                _ => None,
+            })
+            .ok_or(Error::KeyNotFound)?)
+    }
+
+    fn public_key(key: &SecKey) -> Result<VerifyingPublicKey> {
+        // The external representation of a P-256 public key is its uncompressed X9.63 encoding
+        let public_key = key
+            .public_key()
+            .and_then(|public_key| public_key.external_representation())
+            .ok_or(Error::InvalidPublicKey)?
+            .to_vec();
+        Ok(VerifyingPublicKey::ECDSASHA256CurveP256(
+            ECDSASHA256CurveP256PublicKey(
+                public_key.try_into().map_err(|_| Error::InvalidPublicKey)?,
+            ),
+        ))
+    }
+}
+
+#[async_trait]
+impl VaultForSigning for SecureEnclaveSigningVault {
+    async fn sign(
+        &self,
+        signing_secret_key_handle: &SigningSecretKeyHandle,
+        data: &[u8],
+    ) -> Result<Signature> {
+        let label = Self::handle_to_label(signing_secret_key_handle)?;
+        let key = Self::find_key(&label)?;
+        let signature = key
+            .create_signature(Algorithm::ECDSASignatureMessageX962SHA256, data)
+            .map_err(|err| Error::Sign {
+                keyid: label.clone(),
+                error: format!("{err:?}"),
+            })?;
+        let signature =
+            p256::ecdsa::Signature::from_der(&signature).map_err(|_| Error::InvalidSignature)?;
+        Ok(Signature::ECDSASHA256CurveP256(
+            ECDSASHA256CurveP256Signature(
+                signature
+                    .to_vec()
+                    .try_into()
+                    .map_err(|_| Error::InvalidSignature)?,
+            ),
+        ))
+    }
+
+    async fn generate_signing_secret_key(
+        &self,
+        signing_key_type: SigningKeyType,
+    ) -> Result<SigningSecretKeyHandle> {
+        if signing_key_type != SigningKeyType::ECDSASHA256CurveP256 {
+            return Err(VaultError::InvalidKeyType)?;
+        }
+
+        let label = format!(
+            "{SECURE_ENCLAVE_KEY_LABEL_PREFIX}{:016x}",
+            ockam_core::compat::rand::random::<u64>()
+        );
+        let key = self
+            .create_key(&label)
+            .map_err(|err| Error::Create(err.to_string()))?;
+        let public_key = Self::public_key(&key)?;
+        debug!(%label, "created new key in the secure enclave");
+
+        let handle = Self::label_to_handle(&label);
+        self.keys.write().unwrap().push(SecureEnclaveKeyPair {
+            key: handle.clone(),
+            public_key,
+        });
+
+        Ok(handle)
+    }
+
+    async fn get_verifying_public_key(
+        &self,
+        signing_secret_key_handle: &SigningSecretKeyHandle,
+    ) -> Result<VerifyingPublicKey> {
+        let cached = self.keys.read().unwrap().iter().find_map(|x| {
+            if &x.key == signing_secret_key_handle {
+                Some(x.public_key.clone())
+            } else {
+                None
+            }
+        });
+        if let Some(public_key) = cached {
+            return Ok(public_key);
+        }
+
+        let label = Self::handle_to_label(signing_secret_key_handle)?;
+        let public_key = Self::public_key(&Self::find_key(&label)?)?;
+        self.keys.write().unwrap().push(SecureEnclaveKeyPair {
+            key: signing_secret_key_handle.clone(),
+            public_key: public_key.clone(),
+        });
+        Ok(public_key)
+    }
+
+    async fn get_secret_key_handle(
+        &self,
+        verifying_public_key: &VerifyingPublicKey,
+    ) -> Result<SigningSecretKeyHandle> {
+        self.keys
+            .read()
+            .unwrap()
+            .iter()
+            .find_map(|x| {
+                if &x.public_key == verifying_public_key {
+                    Some(x.key.clone())
+                } else {
+                    None
+                }
+            })
+            .ok_or(Error::KeyNotFound.into())
+    }
+
+    async fn delete_signing_secret_key(
+        &self,
+        signing_secret_key_handle: SigningSecretKeyHandle,
+    ) -> Result<bool> {
+        let label = Self::handle_to_label(&signing_secret_key_handle)?;
+        let key = match Self::find_key(&label) {
+            Ok(key) => key,
+            Err(_) => return Ok(false),
+        };
+        key.delete().map_err(|err| Error::Delete {
+            keyid: label,
+            error: format!("{err:?}"),
+        })?;
+        self.keys
+            .write()
+            .unwrap()
+            .retain(|x| x.key != signing_secret_key_handle);
+        Ok(true)
+    }
+}
@@ -0,0 +1,61 @@
+use crate::{tpm_pin, Error};
+use ockam_core::Result;
+use ockam_vault_pkcs11::{Pkcs11Config, Pkcs11SigningVault};
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// Environment variable containing the path of the tpm2-pkcs11 library,
+/// when it is not installed at a standard location
+pub const TPM2_PKCS11_LIBRARY_ENV: &str = "OCKAM_TPM2_PKCS11_LIBRARY";
+
+/// Label prefix of the keys created in the TPM
+const TPM_KEY_LABEL_PREFIX: &str = "ockam-tpm-";
+
+/// Devices exposing a TPM 2.0
+const TPM_DEVICES: [&str; 2] = ["/dev/tpmrm0", "/dev/tpm0"];
+
+/// Standard locations of the tpm2-pkcs11 library
+const TPM2_PKCS11_LIBRARIES: [&str; 5] = [
+    "/usr/lib/x86_64-linux-gnu/pkcs11/libtpm2_pkcs11.so",
+    "/usr/lib/aarch64-linux-gnu/pkcs11/libtpm2_pkcs11.so",
+    "/usr/lib64/pkcs11/libtpm2_pkcs11.so",
+    "/usr/lib/pkcs11/libtpm2_pkcs11.so",
+    "/usr/local/lib/libtpm2_pkcs11.so",
+];
+
+/// Create a signing vault storing its keys in the TPM 2.0 of the machine.
+///
+/// The TPM is accessed with the tpm2-pkcs11 library, using a token initialized with
+/// `tpm2_ptool` and whose user PIN is given by the `OCKAM_TPM_PIN` environment variable.
+/// An error is returned if there is no TPM, no library or no PIN.
+pub async fn create_tpm_signing_vault() -> Result<Pkcs11SigningVault> {
+    Ok(try_create_tpm_signing_vault().await?)
+}
+
+/// Create a TPM signing vault, returning [`Error::Unavailable`] if there is no TPM
+/// and [`Error::Misconfigured`] if the TPM is present but can't be used
+pub(crate) async fn try_create_tpm_signing_vault() -> core::result::Result<Pkcs11SigningVault, Error>
+{
+    if !TPM_DEVICES.iter().any(|device| Path::new(device).exists()) {
+        return Err(Error::Unavailable("no TPM device was found".to_string()));
+    }
+
+    let library = std::env::var(TPM2_PKCS11_LIBRARY_ENV)
+        .ok()
+        .map(PathBuf::from)
+        .or_else(|| {
+            TPM2_PKCS11_LIBRARIES
+                .iter()
+                .map(PathBuf::from)
+                .find(|path| path.exists())
+        })
+        .ok_or_else(|| Error::Misconfigured("the tpm2-pkcs11 library was not found".to_string()))?;
+
+    let pin = tpm_pin()?;
+
+    debug!("use the TPM with the library {library:?}");
+    let config = Pkcs11Config::new(library, pin).with_key_label_prefix(TPM_KEY_LABEL_PREFIX);
+    Pkcs11SigningVault::create_with_config(config)
+        .await
+        .map_err(|err| Error::Misconfigured(err.to_string()))
+}
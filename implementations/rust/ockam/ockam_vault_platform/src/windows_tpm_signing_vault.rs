@@ -0,0 +1,389 @@
+use crate::{tpm_pin, Error};
+use core::ffi::c_void;
+use ockam_core::compat::sync::{Arc, RwLock};
+use ockam_core::{async_trait, Result};
+use ockam_vault::{
+    ECDSASHA256CurveP256PublicKey, ECDSASHA256CurveP256Signature, HandleToSecret, Signature,
+    SigningKeyType, SigningSecretKeyHandle, VaultError, VaultForSigning, VerifyingPublicKey,
+};
+use sha2::{Digest, Sha256};
+use tracing::{debug, error};
+use windows::core::{HSTRING, PCWSTR};
+use windows::Win32::Foundation::NTE_NO_MORE_ITEMS;
+use windows::Win32::Security::Cryptography::{
+    NCryptCreatePersistedKey, NCryptDeleteKey, NCryptEnumKeys, NCryptExportKey, NCryptFinalizeKey,
+    NCryptFreeBuffer, NCryptFreeObject, NCryptKeyName, NCryptOpenKey, NCryptOpenStorageProvider,
+    NCryptSetProperty, NCryptSignHash, BCRYPT_ECCKEY_BLOB, BCRYPT_ECCPUBLIC_BLOB,
+    BCRYPT_ECDSA_P256_ALGORITHM, BCRYPT_ECDSA_PUBLIC_P256_MAGIC, CERT_KEY_SPEC,
+    MS_PLATFORM_CRYPTO_PROVIDER, NCRYPT_FLAGS, NCRYPT_HANDLE, NCRYPT_KEY_HANDLE,
+    NCRYPT_PIN_PROPERTY, NCRYPT_PROV_HANDLE, NCRYPT_SILENT_FLAG,
+};
+
+/// Label prefix of the keys created in the TPM
+const WINDOWS_TPM_KEY_LABEL_PREFIX: &str = "ockam-tpm-";
+
+/// Size of the X and Y coordinates of a P-256 public key
+const P256_COORDINATE_SIZE: usize = 32;
+
+struct WindowsTpmKeyPair {
+    key: SigningSecretKeyHandle,
+    public_key: VerifyingPublicKey,
+}
+
+/// Provider handle of the Microsoft Platform Crypto Provider
+struct Provider(NCRYPT_PROV_HANDLE);
+
+// The provider handle can be used from any thread
+unsafe impl Send for Provider {}
+unsafe impl Sync for Provider {}
+
+impl Drop for Provider {
+    fn drop(&mut self) {
+        let _ = unsafe { NCryptFreeObject(NCRYPT_HANDLE(self.0 .0)) };
+    }
+}
+
+/// Handle of a key opened in the provider, freed when dropped
+struct Key(NCRYPT_KEY_HANDLE);
+
+impl Drop for Key {
+    fn drop(&mut self) {
+        if self.0 .0 != 0 {
+            let _ = unsafe { NCryptFreeObject(NCRYPT_HANDLE(self.0 .0)) };
+        }
+    }
+}
+
+/// Security module implementation using a TPM 2.0 through the Microsoft Platform Crypto
+/// Provider of the Windows CNG key storage (NCrypt)
+///
+/// The private keys are generated inside the TPM and never leave it. They are protected
+/// by the PIN given by the `OCKAM_TPM_PIN` environment variable.
+/// A key handle is the name of the key in the provider.
+pub struct WindowsTpmSigningVault {
+    provider: Provider,
+    pin: Vec<u8>,
+    // Store mapping from PublicKey to key name in memory
+    // This is fetched at the Vault initialization
+    // and is updated locally during add/delete operations
+    keys: Arc<RwLock<Vec<WindowsTpmKeyPair>>>,
+}
+
+impl WindowsTpmSigningVault {
+    /// Create a TPM security module.
+    /// An error is returned if there is no TPM or if `OCKAM_TPM_PIN` is not set
+    pub fn create() -> Result<Self> {
+        Ok(Self::try_create()?)
+    }
+
+    /// Create a TPM security module, returning [`Error::Unavailable`] if there is no TPM
+    /// and [`Error::Misconfigured`] if the TPM is present but can't be used
+    pub(crate) fn try_create() -> core::result::Result<Self, Error> {
+        let mut provider = NCRYPT_PROV_HANDLE::default();
+        unsafe { NCryptOpenStorageProvider(&mut provider, MS_PLATFORM_CRYPTO_PROVIDER, 0) }
+            .map_err(|err| Error::Unavailable(format!("no TPM was found: {err}")))?;
+        let provider = Provider(provider);
+
+        // The PIN is encoded as a null-terminated UTF-16 string
+        let pin = tpm_pin()?
+            .encode_utf16()
+            .chain(Some(0))
+            .flat_map(u16::to_le_bytes)
+            .collect();
+
+        let vault = Self {
+            provider,
+            pin,
+            keys: Arc::new(RwLock::new(vec![])),
+        };
+        for name in vault.list_names()? {
+            match vault.open_key(&name).and_then(|key| Self::public_key(&key)) {
+                Ok(public_key) => vault.keys.write().unwrap().push(WindowsTpmKeyPair {
+                    key: Self::name_to_handle(&name),
+                    public_key,
+                }),
+                Err(err) => error!("Error exporting public key: {err}"),
+            }
+        }
+        Ok(vault)
+    }
+
+    /// Return list of all keys
+    pub fn keys(&self) -> Vec<SigningSecretKeyHandle> {
+        self.keys
+            .read()
+            .unwrap()
+            .iter()
+            .map(|x| x.key.clone())
+            .collect()
+    }
+
+    fn name_to_handle(name: &str) -> SigningSecretKeyHandle {
+        SigningSecretKeyHandle::ECDSASHA256CurveP256(HandleToSecret::new(name.as_bytes().to_vec()))
+    }
+
+    fn handle_to_name(handle: &SigningSecretKeyHandle) -> core::result::Result<String, Error> {
+        let handle = match handle {
+            SigningSecretKeyHandle::EdDSACurve25519(_) => return Err(Error::InvalidHandle),
+            SigningSecretKeyHandle::ECDSASHA256CurveP256(handle) => handle.value().clone(),
+        };
+        String::from_utf8(handle).map_err(|_| Error::InvalidHandle)
+    }
+
+    /// Return the names of the keys created by this vault
+    fn list_names(&self) -> core::result::Result<Vec<String>, Error> {
+        let mut names = vec![];
+        let mut state: *mut c_void = core::ptr::null_mut();
+        loop {
+            let mut key_name: *mut NCryptKeyName = core::ptr::null_mut();
+            let result = unsafe {
+                NCryptEnumKeys(
+                    self.provider.0,
+                    PCWSTR::null(),
+                    &mut key_name,
+                    &mut state,
+                    NCRYPT_SILENT_FLAG,
+                )
+            };
+            match result {
+                Ok(()) => {
+                    let name = unsafe { (*key_name).pszName.to_string() };
+                    let _ = unsafe { NCryptFreeBuffer(key_name as *mut c_void) };
+                    if let Ok(name) = name {
+                        if name.starts_with(WINDOWS_TPM_KEY_LABEL_PREFIX) {
+                            names.push(name);
+                        }
+                    }
+                }
+                Err(err) if err.code() == NTE_NO_MORE_ITEMS => break,
+                Err(err) => {
+                    if !state.is_null() {
+                        let _ = unsafe { NCryptFreeBuffer(state) };
+                    }
+                    return Err(Error::Misconfigured(format!(
+                        "the TPM keys can't be listed: {err}"
+                    )));
+                }
+            }
+        }
+        if !state.is_null() {
+            let _ = unsafe { NCryptFreeBuffer(state) };
+        }
+        Ok(names)
+    }
+
+    /// Set the PIN protecting a key, before it is finalized or used
+    fn set_pin(&self, key: &Key) -> windows::core::Result<()> {
+        unsafe {
+            NCryptSetProperty(
+                NCRYPT_HANDLE(key.0 .0),
+                NCRYPT_PIN_PROPERTY,
+                &self.pin,
+                NCRYPT_SILENT_FLAG,
+            )
+        }
+    }
+
+    fn create_key(&self, name: &str) -> core::result::Result<Key, Error> {
+        let mut handle = NCRYPT_KEY_HANDLE::default();
+        unsafe {
+            NCryptCreatePersistedKey(
+                self.provider.0,
+                &mut handle,
+                BCRYPT_ECDSA_P256_ALGORITHM,
+                &HSTRING::from(name),
+                CERT_KEY_SPEC(0),
+                NCRYPT_FLAGS(0),
+            )
+        }
+        .map_err(|err| Error::Create(err.to_string()))?;
+        let key = Key(handle);
+        self.set_pin(&key)
+            .and_then(|_| unsafe { NCryptFinalizeKey(key.0, NCRYPT_SILENT_FLAG) })
+            .map_err(|err| Error::Create(err.to_string()))?;
+        Ok(key)
+    }
+
+    fn open_key(&self, name: &str) -> core::result::Result<Key, Error> {
+        let mut handle = NCRYPT_KEY_HANDLE::default();
+        unsafe {
+            NCryptOpenKey(
+                self.provider.0,
+                &mut handle,
+                &HSTRING::from(name),
+                CERT_KEY_SPEC(0),
+                NCRYPT_SILENT_FLAG,
+            )
+        }
+        .map_err(|_| Error::KeyNotFound)?;
+        let key = Key(handle);
+        self.set_pin(&key).map_err(|_| Error::KeyNotFound)?;
+        Ok(key)
+    }
+
+    fn public_key(key: &Key) -> core::result::Result<VerifyingPublicKey, Error> {
+        // The public key blob is a BCRYPT_ECCKEY_BLOB header followed by the X and Y coordinates
+        let mut blob = [0u8; core::mem::size_of::<BCRYPT_ECCKEY_BLOB>() + 2 * P256_COORDINATE_SIZE];
+        let mut size = 0u32;
+        unsafe {
+            NCryptExportKey(
+                key.0,
+                NCRYPT_KEY_HANDLE::default(),
+                BCRYPT_ECCPUBLIC_BLOB,
+                None,
+                Some(&mut blob),
+                &mut size,
+                NCRYPT_SILENT_FLAG,
+            )
+        }
+        .map_err(|_| Error::InvalidPublicKey)?;
+
+        let header_size = core::mem::size_of::<BCRYPT_ECCKEY_BLOB>();
+        if (size as usize) < header_size {
+            return Err(Error::InvalidPublicKey);
+        }
+        let (header, coordinates) = blob[..size as usize].split_at(header_size);
+        let magic = u32::from_le_bytes(header[0..4].try_into().unwrap());
+        let key_size = u32::from_le_bytes(header[4..8].try_into().unwrap());
+        if magic != BCRYPT_ECDSA_PUBLIC_P256_MAGIC
+            || key_size as usize != P256_COORDINATE_SIZE
+            || coordinates.len() != 2 * P256_COORDINATE_SIZE
+        {
+            return Err(Error::InvalidPublicKey);
+        }
+
+        // Uncompressed X9.63 encoding of the public key
+        let public_key: Vec<u8> = [0x04].iter().chain(coordinates).copied().collect();
+        Ok(VerifyingPublicKey::ECDSASHA256CurveP256(
+            ECDSASHA256CurveP256PublicKey(
+                public_key.try_into().map_err(|_| Error::InvalidPublicKey)?,
+            ),
+        ))
+    }
+}
+
+#[async_trait]
+impl VaultForSigning for WindowsTpmSigningVault {
+    async fn sign(
+        &self,
+        signing_secret_key_handle: &SigningSecretKeyHandle,
+        data: &[u8],
+    ) -> Result<Signature> {
+        let name = Self::handle_to_name(signing_secret_key_handle)?;
+        let key = self.open_key(&name)?;
+        let digest = Sha256::digest(data);
+
+        // An ECDSA signature is the concatenation of r and s
+        let mut signature = [0u8; 2 * P256_COORDINATE_SIZE];
+        let mut size = 0u32;
+        unsafe {
+            NCryptSignHash(
+                key.0,
+                None,
+                digest.as_slice(),
+                Some(&mut signature),
+                &mut size,
+                NCRYPT_SILENT_FLAG,
+            )
+        }
+        .map_err(|err| Error::Sign {
+            keyid: name.clone(),
+            error: err.to_string(),
+        })?;
+        if size as usize != signature.len() {
+            return Err(Error::InvalidSignature)?;
+        }
+        Ok(Signature::ECDSASHA256CurveP256(
+            ECDSASHA256CurveP256Signature(signature),
+        ))
+    }
+
+    async fn generate_signing_secret_key(
+        &self,
+        signing_key_type: SigningKeyType,
+    ) -> Result<SigningSecretKeyHandle> {
+        if signing_key_type != SigningKeyType::ECDSASHA256CurveP256 {
+            return Err(VaultError::InvalidKeyType)?;
+        }
+
+        let name = format!(
+            "{WINDOWS_TPM_KEY_LABEL_PREFIX}{:016x}",
+            ockam_core::compat::rand::random::<u64>()
+        );
+        let key = self.create_key(&name)?;
+        let public_key = Self::public_key(&key)?;
+        debug!(%name, "created new key in the TPM");
+
+        let handle = Self::name_to_handle(&name);
+        self.keys.write().unwrap().push(WindowsTpmKeyPair {
+            key: handle.clone(),
+            public_key,
+        });
+
+        Ok(handle)
+    }
+
+    async fn get_verifying_public_key(
+        &self,
+        signing_secret_key_handle: &SigningSecretKeyHandle,
+    ) -> Result<VerifyingPublicKey> {
+        let cached = self.keys.read().unwrap().iter().find_map(|x| {
+            if &x.key == signing_secret_key_handle {
+                Some(x.public_key.clone())
+            } else {
+                None
+            }
+        });
+        if let Some(public_key) = cached {
+            return Ok(public_key);
+        }
+
+        let name = Self::handle_to_name(signing_secret_key_handle)?;
+        let public_key = Self::public_key(&self.open_key(&name)?)?;
+        self.keys.write().unwrap().push(WindowsTpmKeyPair {
+            key: signing_secret_key_handle.clone(),
+            public_key: public_key.clone(),
+        });
+        Ok(public_key)
+    }
+
+    async fn get_secret_key_handle(
+        &self,
+        verifying_public_key: &VerifyingPublicKey,
+    ) -> Result<SigningSecretKeyHandle> {
+        self.keys
+            .read()
+            .unwrap()
+            .iter()
+            .find_map(|x| {
+                if &x.public_key == verifying_public_key {
+                    Some(x.key.clone())
+                } else {
+                    None
+                }
+            })
+            .ok_or(Error::KeyNotFound.into())
+    }
+
+    async fn delete_signing_secret_key(
+        &self,
+        signing_secret_key_handle: SigningSecretKeyHandle,
+    ) -> Result<bool> {
+        let name = Self::handle_to_name(&signing_secret_key_handle)?;
+        let mut key = match self.open_key(&name) {
+            Ok(key) => key,
+            Err(_) => return Ok(false),
+        };
+        // The key handle is freed when the key is deleted
+        let handle = core::mem::take(&mut key.0);
+        unsafe { NCryptDeleteKey(handle, 0) }.map_err(|err| Error::Delete {
+            keyid: name,
+            error: err.to_string(),
+        })?;
+        self.keys
+            .write()
+            .unwrap()
+            .retain(|x| x.key != signing_secret_key_handle);
+        Ok(true)
+    }
+}
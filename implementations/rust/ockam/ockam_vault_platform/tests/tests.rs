@@ -0,0 +1,40 @@
+use ockam_core::Result;
+use ockam_vault::{
+    SigningKeyType, SoftwareVaultForSigning, SoftwareVaultForVerifyingSignatures, VaultForSigning,
+    VaultForVerifyingSignatures,
+};
+use ockam_vault_platform::{PlatformFallback, PlatformKeyStorage, PlatformSigningVault};
+
+/// Keys are stored in the secure hardware if there is one (for example a TPM with
+/// OCKAM_TPM_PIN set), otherwise in the fallback vault
+
+#[tokio::test]
+async fn test_sign_verify_with_fallback() -> Result<()> {
+    let signing_vault = PlatformSigningVault::create_or_fallback(
+        SoftwareVaultForSigning::create().await?,
+        PlatformFallback::Always,
+    )
+    .await?;
+    if PlatformSigningVault::create().await.is_err() {
+        assert_eq!(signing_vault.storage(), PlatformKeyStorage::Fallback);
+        assert!(!signing_vault.storage().is_hardware_backed());
+    }
+
+    let handle = signing_vault
+        .generate_signing_secret_key(SigningKeyType::ECDSASHA256CurveP256)
+        .await?;
+    let message = b"hello world";
+    let signature = signing_vault.sign(&handle, message.as_slice()).await?;
+    let public_key = signing_vault.get_verifying_public_key(&handle).await?;
+
+    let verifier = SoftwareVaultForVerifyingSignatures::new();
+    assert!(
+        verifier
+            .verify_signature(&public_key, message, &signature)
+            .await?
+    );
+
+    signing_vault.delete_signing_secret_key(handle).await?;
+
+    Ok(())
+}